@@ -0,0 +1,1816 @@
+use grammers_client::grammers_tl_types as tl;
+use grammers_client::types::{Chat, Message};
+use grammers_client::{Client, Config, InitParams, InvocationError};
+use grammers_session::Session;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::backend_config::{BackendConfig, BackendPolicy, BackendRateLimiter, BackendType};
+use crate::blocklist::{BlockTarget, ChannelBlocklist};
+use crate::cache::{
+    merge_fetched_messages, AnalysisResult, CacheManager, CachedCorpus, CorpusFreshness,
+};
+use crate::circuit_breaker::telegram_breaker;
+use crate::error::AppError;
+use crate::llm::{ModelTier, MAX_RETRIES};
+use crate::mock;
+use crate::prompts::analysis::OutputLanguage;
+use crate::rate_limiters::telegram::TelegramRateLimiter;
+use crate::retry_budget::RetryBudget;
+use crate::session_manager::{SessionManager, TelegramCredentials};
+use crate::session_pool::SessionPool;
+use crate::web_scraper::{TelegramWebScraper, WebScrapingError};
+use deadpool_postgres::Pool;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageDict {
+    // telegram message id; absent in corpora cached before ids were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<String>>,
+    // forum topic of a group message; absent in general topics, channels and corpora
+    // cached before topics were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<i32>,
+}
+
+// ids don't change what the llm reads, so they stay out of llm cache keys; neither do
+// thread ids, a topic analysis only reads the messages of its topic anyway
+impl Hash for MessageDict {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.date.hash(state);
+        self.message.hash(state);
+        self.images.hash(state);
+    }
+}
+
+/// a topic of a forum group, analyzed on its own
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ForumTopic {
+    // id of the message that created the topic
+    pub thread_id: i32,
+    pub name: String,
+}
+
+/// the messages posted in a forum topic
+pub fn topic_messages(messages: Vec<MessageDict>, thread_id: i32) -> Vec<MessageDict> {
+    messages
+        .into_iter()
+        .filter(|msg| msg.thread_id == Some(thread_id))
+        .collect()
+}
+
+/// forum topic of a fetched message; replies outside forums carry no topic
+fn message_thread_id(message: &Message) -> Option<i32> {
+    match message.reply_header()? {
+        tl::enums::MessageReplyHeader::Header(header) if header.forum_topic => {
+            // replies within a topic point at its root with reply_to_top_id, posts
+            // directly in the topic point at the root with reply_to_msg_id
+            header.reply_to_top_id.or(header.reply_to_msg_id)
+        }
+        _ => None,
+    }
+}
+
+/// counts cached messages the author has deleted since: ids within the span of a fresh
+/// fetch that it no longer contains. older cached messages only fell out of the fetch
+/// window and don't count, nor do messages cached without an id
+pub fn count_deleted_messages(cached: &[MessageDict], fresh: &[MessageDict]) -> usize {
+    let visible = fresh
+        .iter()
+        .filter_map(|msg| msg.id)
+        .collect::<HashSet<_>>();
+    let Some(oldest_visible) = visible.iter().min().copied() else {
+        return 0;
+    };
+    cached
+        .iter()
+        .filter_map(|msg| msg.id)
+        .filter(|id| *id >= oldest_visible && !visible.contains(id))
+        .count()
+}
+
+// messages a user forwarded for a self-analysis are stored like a channel corpus, under
+// a name no channel username can take
+const SELF_CORPUS_PREFIX: &str = "self:";
+
+/// corpus name of the messages the user forwarded to be analyzed
+pub fn self_corpus_name(telegram_user_id: i64) -> String {
+    format!("{}{}", SELF_CORPUS_PREFIX, telegram_user_id)
+}
+
+/// whether the analyzed "channel" is a user's forwarded messages, which can't be fetched
+pub fn is_self_corpus(channel_username: &str) -> bool {
+    channel_username.starts_with(SELF_CORPUS_PREFIX)
+}
+
+// a user's messages from the groups they share with the bot can't be fetched either, so
+// they are a self corpus of their own
+const CROSS_GROUP_CORPUS_PREFIX: &str = "self:groups:";
+
+/// corpus name of the user's messages gathered from the groups they share with the bot
+pub fn cross_group_corpus_name(telegram_user_id: i64) -> String {
+    format!("{}{}", CROSS_GROUP_CORPUS_PREFIX, telegram_user_id)
+}
+
+/// whether the analyzed "channel" is a user's messages from their groups
+pub fn is_cross_group_corpus(channel_username: &str) -> bool {
+    channel_username.starts_with(CROSS_GROUP_CORPUS_PREFIX)
+}
+
+// two members' messages in one group for a roast battle; the usernames are part of the name
+// so results can say who fought whom
+const ROAST_BATTLE_CORPUS_PREFIX: &str = "self:battle:";
+
+/// corpus name of a roast battle between two members of a group, by their usernames
+/// without the @
+pub fn roast_battle_corpus_name(chat_id: i64, first: &str, second: &str) -> String {
+    format!("{}{}:{}:{}", ROAST_BATTLE_CORPUS_PREFIX, chat_id, first, second)
+}
+
+/// the usernames of the two members a roast battle corpus is about
+pub fn roast_battle_members(channel_username: &str) -> Option<(&str, &str)> {
+    let rest = channel_username.strip_prefix(ROAST_BATTLE_CORPUS_PREFIX)?;
+    let mut parts = rest.splitn(3, ':');
+    let (_chat_id, first, second) = (parts.next()?, parts.next()?, parts.next()?);
+    (!first.is_empty() && !second.is_empty()).then_some((first, second))
+}
+
+// private channels are analyzed through an invite link and stored under its hash; no
+// username starts with '+'
+const INVITE_PREFIX: &str = "+";
+
+/// name a private channel reached through the invite link with `hash` is analyzed under
+pub fn invite_channel_name(hash: &str) -> String {
+    format!("{}{}", INVITE_PREFIX, hash)
+}
+
+/// the invite hash of a private channel's name, None for public channels and groups
+pub fn invite_hash(channel_username: &str) -> Option<&str> {
+    channel_username
+        .strip_prefix(INVITE_PREFIX)
+        .filter(|hash| !hash.is_empty())
+}
+
+/// the chat behind an invite link and whether the account joined it just now, so only
+/// chats it wasn't in before are left afterwards
+async fn join_invite(client: &Client, hash: &str) -> Result<(Chat, bool), AppError> {
+    let invite = client
+        .invoke(&tl::functions::messages::CheckChatInvite {
+            hash: hash.to_string(),
+        })
+        .await?;
+    if let tl::enums::ChatInvite::Already(already) = invite {
+        return Ok((Chat::from_raw(already.chat), false));
+    }
+
+    // invites that need an admin's approval fail with INVITE_REQUEST_SENT here
+    let updates = client
+        .invoke(&tl::functions::messages::ImportChatInvite {
+            hash: hash.to_string(),
+        })
+        .await?;
+    let chats = match updates {
+        tl::enums::Updates::Updates(updates) => updates.chats,
+        tl::enums::Updates::Combined(updates) => updates.chats,
+        _ => Vec::new(),
+    };
+    let chat = chats
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::telegram("Joined chat is missing from the updates"))?;
+    Ok((Chat::from_raw(chat), true))
+}
+
+// default ceiling on message text sent to the llm for a small analysis, in characters
+const DEFAULT_MAX_CORPUS_CHARS: usize = 400_000;
+
+/// total characters of message text, i.e. what ends up in the llm prompt
+pub fn corpus_chars(messages: &[MessageDict]) -> usize {
+    messages
+        .iter()
+        .filter_map(|msg| msg.message.as_deref())
+        .map(|text| text.chars().count())
+        .sum()
+}
+
+/// posts with less text than this, in bytes, carry too little to analyze
+pub const MIN_TEXT_LENGTH: usize = 32;
+
+/// text coverage in percent below which an analysis needs the user's confirmation
+pub const MIN_TEXT_COVERAGE: u32 = 50;
+
+/// share of a channel's posts with enough text to analyze, in percent: the corpus
+/// messages with at least MIN_TEXT_LENGTH of text out of them and the `skipped` posts the
+/// fetch left out; None without any posts
+pub fn text_coverage(messages: &[MessageDict], skipped: usize) -> Option<u32> {
+    let total = messages.len() + skipped;
+    if total == 0 {
+        return None;
+    }
+    let text_posts = messages
+        .iter()
+        .filter(|msg| {
+            msg.message
+                .as_deref()
+                .is_some_and(|text| text.trim().len() >= MIN_TEXT_LENGTH)
+        })
+        .count();
+    Some((text_posts * 100 / total) as u32)
+}
+
+/// what a name resolved to: a channel or group, or a user whose public profile is analyzed
+/// in the reduced profile mode; a user's messages from several groups and two members'
+/// messages from one group for a roast battle get prompts of their own too
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CorpusKind {
+    #[default]
+    Channel,
+    Profile,
+    CrossGroup,
+    RoastBattle,
+}
+
+impl CorpusKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorpusKind::Channel => "channel",
+            CorpusKind::Profile => "profile",
+            CorpusKind::CrossGroup => "cross_group",
+            CorpusKind::RoastBattle => "roast_battle",
+        }
+    }
+
+    /// parses a stored kind, corpora cached before profiles were recognized are channels
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "profile" => CorpusKind::Profile,
+            "cross_group" => CorpusKind::CrossGroup,
+            "roast_battle" => CorpusKind::RoastBattle,
+            _ => CorpusKind::Channel,
+        }
+    }
+}
+
+/// the corpus of a user's profile: a card with their name and bio, followed by the posts
+/// of the channel they show on it; empty when there's neither a bio nor posts
+pub fn profile_messages(
+    name: &str,
+    bio: Option<&str>,
+    posts: Vec<MessageDict>,
+) -> Vec<MessageDict> {
+    let bio = bio.map(str::trim).filter(|bio| !bio.is_empty());
+    if bio.is_none() && posts.is_empty() {
+        return Vec::new();
+    }
+
+    let card = match bio {
+        Some(bio) => format!("Name: {}\nBio: {}", name, bio),
+        None => format!("Name: {}", name),
+    };
+    let mut messages = vec![MessageDict {
+        id: None,
+        date: None,
+        message: Some(card),
+        images: None,
+        thread_id: None,
+    }];
+    messages.extend(posts);
+    messages
+}
+
+#[derive(Debug)]
+pub struct AnalysisData {
+    pub messages: Vec<MessageDict>,
+    pub cache_key: String,
+    // time spent fetching from telegram, None when the messages came from the cache
+    pub fetch_duration: Option<Duration>,
+    // cached posts dropped because the author deleted them, found by a refresh
+    pub removed_messages: usize,
+    // backend that fetched the messages, unknown for corpora cached before it was recorded
+    pub backend: Option<BackendType>,
+    pub kind: CorpusKind,
+    // the messages came from a corpus past its freshness, the channel is due to be fetched
+    // again in the background
+    pub revalidate: bool,
+}
+
+/// how far back into a channel's history an analysis reads
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalysisDepth {
+    #[default]
+    Small,
+    Medium,
+    Deep,
+}
+
+impl AnalysisDepth {
+    pub const ALL: [AnalysisDepth; 3] = [
+        AnalysisDepth::Small,
+        AnalysisDepth::Medium,
+        AnalysisDepth::Deep,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalysisDepth::Small => "small",
+            AnalysisDepth::Medium => "medium",
+            AnalysisDepth::Deep => "deep",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "small" => Some(AnalysisDepth::Small),
+            "medium" => Some(AnalysisDepth::Medium),
+            "deep" => Some(AnalysisDepth::Deep),
+            _ => None,
+        }
+    }
+
+    /// max number of messages collected through the telegram api
+    pub fn api_message_limit(&self) -> usize {
+        match self {
+            AnalysisDepth::Small => 100,
+            AnalysisDepth::Medium => 300,
+            AnalysisDepth::Deep => 600,
+        }
+    }
+
+    /// max number of t.me/s pages fetched by the web scraper
+    pub fn web_pages(&self) -> usize {
+        match self {
+            AnalysisDepth::Small => 10,
+            AnalysisDepth::Medium => 30,
+            AnalysisDepth::Deep => 60,
+        }
+    }
+
+    /// max characters of message text an analysis may send to the llm;
+    /// deeper tiers cost more credits and get a proportionally larger budget
+    pub fn corpus_char_limit(&self, base_limit: usize) -> usize {
+        match self {
+            AnalysisDepth::Small => base_limit,
+            AnalysisDepth::Medium => base_limit * 2,
+            AnalysisDepth::Deep => base_limit * 4,
+        }
+    }
+
+    /// channel message cache entry name; small keeps the plain channel name
+    pub fn cache_name(&self, channel_username: &str) -> String {
+        match self {
+            AnalysisDepth::Small => channel_username.to_string(),
+            _ => format!("{}#{}", channel_username, self.as_str()),
+        }
+    }
+}
+
+/// failure classes of an analysis run, each mapped to its own user-facing explanation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    ChannelPrivate,
+    ChannelNotFound,
+    FloodWait(u32), // seconds until telegram accepts requests again
+    CorpusTooLarge {
+        depth: AnalysisDepth,
+        chars: usize,
+        limit: usize,
+    },
+    AiRefusal,
+    AiUnavailable,
+    // the analysis used up its retry budget before any stage succeeded
+    BudgetExhausted,
+    // the dated posts fit in one time window, so a trends analysis has nothing to compare
+    ShortHistory,
+    // too few posts have text, the user has to confirm the analysis; coverage in percent
+    LowTextCoverage(u32),
+    // the invite link of a private channel expired or never existed
+    InviteInvalid,
+    // the private channel approves new members, a join request is waiting for its admins
+    JoinRequestSent,
+    // the name belongs to a user with neither a bio nor a channel on their profile
+    EmptyProfile,
+    // the llm or telegram kept failing and its circuit breaker is open
+    ServiceDegraded,
+    // the channel is on the blocklist, e.g. its owner asked to be excluded
+    ChannelBlocked,
+    Internal(String),
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::ChannelPrivate => write!(f, "Channel is private or restricted"),
+            AnalysisError::ChannelNotFound => write!(f, "Channel not found or deleted"),
+            AnalysisError::FloodWait(seconds) => {
+                write!(f, "Telegram flood wait for {} seconds", seconds)
+            }
+            AnalysisError::CorpusTooLarge {
+                depth,
+                chars,
+                limit,
+            } => write!(
+                f,
+                "Channel corpus of {} characters exceeds the {} limit of {} depth",
+                chars,
+                limit,
+                depth.as_str()
+            ),
+            AnalysisError::AiRefusal => write!(f, "LLM refused or failed to produce the analysis"),
+            AnalysisError::AiUnavailable => write!(f, "LLM service is unavailable"),
+            AnalysisError::BudgetExhausted => write!(f, "Analysis ran out of its retry budget"),
+            AnalysisError::ShortHistory => {
+                write!(f, "Channel posts cover too short a period to show trends")
+            }
+            AnalysisError::InviteInvalid => write!(f, "Invite link is invalid or expired"),
+            AnalysisError::JoinRequestSent => {
+                write!(f, "Private channel requires approval to join")
+            }
+            AnalysisError::EmptyProfile => {
+                write!(f, "User profile has no bio or public posts to analyze")
+            }
+            AnalysisError::ServiceDegraded => {
+                write!(f, "Service is degraded after repeated backend failures")
+            }
+            AnalysisError::ChannelBlocked => write!(f, "Channel is excluded from analysis"),
+            AnalysisError::LowTextCoverage(coverage) => write!(
+                f,
+                "Only {}% of the channel posts have enough text to analyze",
+                coverage
+            ),
+            AnalysisError::Internal(e) => write!(f, "Internal error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+impl AnalysisError {
+    /// maps an arbitrary error from the analysis pipeline to its failure class
+    pub fn classify(err: &(dyn std::error::Error + 'static)) -> Self {
+        if let Some(analysis_error) = err.downcast_ref::<AnalysisError>() {
+            return analysis_error.clone();
+        }
+
+        if let Some(app_error) = err.downcast_ref::<AppError>() {
+            return app_error.failure();
+        }
+
+        if let Some(invocation_error) = err.downcast_ref::<InvocationError>() {
+            if let Some(analysis_error) = Self::from_invocation_error(invocation_error) {
+                return analysis_error;
+            }
+        }
+
+        if let Some(WebScrapingError::StatusCodeError(404)) = err.downcast_ref::<WebScrapingError>()
+        {
+            return AnalysisError::ChannelNotFound;
+        }
+
+        AnalysisError::Internal(err.to_string())
+    }
+
+    /// recognizes telegram rpc errors that retrying will not fix
+    pub fn from_invocation_error(err: &InvocationError) -> Option<Self> {
+        match err {
+            InvocationError::Rpc(rpc) if rpc.is("FLOOD_WAIT") => {
+                Some(AnalysisError::FloodWait(rpc.value.unwrap_or(0)))
+            }
+            InvocationError::Rpc(rpc)
+                if rpc.is("CHANNEL_PRIVATE") || rpc.is("CHANNEL_PUBLIC_GROUP_NA") =>
+            {
+                Some(AnalysisError::ChannelPrivate)
+            }
+            InvocationError::Rpc(rpc)
+                if rpc.is("USERNAME_NOT_OCCUPIED")
+                    || rpc.is("USERNAME_INVALID")
+                    || rpc.is("CHANNEL_INVALID") =>
+            {
+                Some(AnalysisError::ChannelNotFound)
+            }
+            InvocationError::Rpc(rpc)
+                if rpc.is("INVITE_HASH_EXPIRED")
+                    || rpc.is("INVITE_HASH_INVALID")
+                    || rpc.is("INVITE_HASH_EMPTY") =>
+            {
+                Some(AnalysisError::InviteInvalid)
+            }
+            InvocationError::Rpc(rpc) if rpc.is("INVITE_REQUEST_SENT") => {
+                Some(AnalysisError::JoinRequestSent)
+            }
+            _ => None,
+        }
+    }
+}
+
+// tells the telegram circuit breaker how a fetch went: a channel that is private or gone
+// still means telegram answered, a flood wait only means it wants us to slow down
+fn record_fetch_outcome(fetched: &Result<FetchedMessages, AppError>) {
+    let Err(e) = fetched else {
+        telegram_breaker().record_success();
+        return;
+    };
+    match e.failure() {
+        AnalysisError::Internal(_) | AnalysisError::BudgetExhausted => {
+            telegram_breaker().record_failure(Instant::now())
+        }
+        AnalysisError::FloodWait(_) => {}
+        _ => telegram_breaker().record_success(),
+    }
+}
+
+/// hands short flood waits to the rate limiter so the next retry waits them out precisely;
+/// returns false for other failures and for waits too long to sit through or to fit the budget
+async fn absorb_flood_wait(
+    rate_limiter: &TelegramRateLimiter,
+    failure: &AnalysisError,
+    budget: &RetryBudget,
+) -> bool {
+    match failure {
+        AnalysisError::FloodWait(seconds) => {
+            rate_limiter.record_flood_wait(*seconds).await
+                && budget.take_retry(Duration::from_secs(u64::from(*seconds)))
+        }
+        _ => false,
+    }
+}
+
+/// a stage that gave up because the analysis ran out of time reports BudgetExhausted,
+/// unless the channel itself is the problem
+fn budget_failure(err: AppError, budget: &RetryBudget) -> AppError {
+    if budget.is_exhausted()
+        && !matches!(
+            err.failure(),
+            AnalysisError::ChannelNotFound | AnalysisError::ChannelPrivate
+        )
+    {
+        AnalysisError::BudgetExhausted.into()
+    } else {
+        err
+    }
+}
+
+/// messages of a fresh fetch, the backend that fetched them and the number of posts it
+/// left out as forwarded, too short or media only
+struct FetchedMessages {
+    messages: Vec<MessageDict>,
+    backend: BackendType,
+    skipped: usize,
+    kind: CorpusKind,
+}
+
+pub struct AnalysisEngine {
+    client: Option<Client>,
+    api_id: i32,
+    api_hash: String,
+    pub cache: CacheManager,
+    blocklist: ChannelBlocklist,
+    resolved_channels: HashMap<String, Arc<Chat>>,
+    rate_limiter: TelegramRateLimiter,
+    session_pool: SessionPool,
+    current_session: Option<String>,
+    web_scraper: TelegramWebScraper,
+    backend_config: BackendConfig,
+    backend_rate_limiter: BackendRateLimiter,
+    max_corpus_chars: usize,
+}
+
+impl AnalysisEngine {
+    /// an engine rotating through every session in sessions/
+    pub fn new(pool: Arc<Pool>) -> Result<Self, AppError> {
+        let session_files = SessionManager::discover_sessions().map_err(AppError::telegram)?;
+        Self::with_sessions(pool, session_files)
+    }
+
+    /// an engine that only connects with the given session files, so engines running
+    /// side by side don't share a telegram session
+    pub fn with_sessions(pool: Arc<Pool>, session_files: Vec<String>) -> Result<Self, AppError> {
+        // mock mode reads fixtures and never connects a client
+        let (api_id, api_hash) = if mock::enabled() {
+            (0, String::new())
+        } else {
+            let credentials = TelegramCredentials::from_env().map_err(AppError::Validation)?;
+            (credentials.api_id, credentials.api_hash)
+        };
+
+        let max_corpus_chars = match env::var("MAX_CORPUS_CHARS") {
+            Ok(value) => value.parse::<usize>().map_err(|_| {
+                AppError::Validation("MAX_CORPUS_CHARS must be a valid integer".into())
+            })?,
+            Err(_) => DEFAULT_MAX_CORPUS_CHARS,
+        };
+
+        let blocklist = ChannelBlocklist::new(pool.clone());
+        let cache = CacheManager::new(pool);
+
+        let session_pool = SessionPool::new(session_files);
+        if session_pool.is_empty() && !mock::enabled() {
+            return Err(AppError::telegram(
+                "No session files found in sessions/ directory",
+            ));
+        }
+        info!("Found {} session files", session_pool.len());
+
+        let web_scraper = TelegramWebScraper::new()
+            .map_err(|e| AppError::telegram(format!("Failed to initialize web scraper: {}", e)))?;
+
+        Ok(Self {
+            client: None,
+            api_id,
+            api_hash,
+            cache,
+            blocklist,
+            resolved_channels: HashMap::new(),
+            rate_limiter: TelegramRateLimiter::new(),
+            session_pool,
+            current_session: None,
+            web_scraper,
+            backend_config: BackendConfig::new(BackendPolicy::from_env()),
+            backend_rate_limiter: BackendRateLimiter::new(),
+            max_corpus_chars,
+        })
+    }
+
+    /// refuses a channel on the blocklist, by its name and by its id once it is known;
+    /// forwarded messages and other corpora that aren't a telegram chat are never blocked
+    async fn enforce_blocklist(
+        &self,
+        channel_username: &str,
+        channel_id: Option<i64>,
+    ) -> Result<(), AppError> {
+        let targets = BlockTarget::of_channel(channel_username)
+            .into_iter()
+            .chain(channel_id.map(BlockTarget::ChannelId))
+            .collect::<Vec<_>>();
+        if self.blocklist.is_blocked(&targets).await? {
+            warn!("Refusing to analyze blocked channel {}", channel_username);
+            return Err(AnalysisError::ChannelBlocked.into());
+        }
+        Ok(())
+    }
+
+    /// records a session-level failure and drops the client so the next call rotates sessions
+    fn penalize_current_session(&mut self, failure: &AnalysisError) {
+        if let AnalysisError::FloodWait(seconds) = failure {
+            if let Some(session_file) = self.current_session.take() {
+                self.session_pool.record_flood_wait(&session_file, *seconds);
+            }
+            self.client = None;
+            self.resolved_channels.clear();
+        }
+    }
+
+    async fn ensure_client(&mut self, budget: &RetryBudget) -> Result<&Client, AppError> {
+        if self.client.is_none() {
+            self.session_pool.revalidate_if_due().await;
+            info!(
+                "Initializing Telegram client ({}/{} sessions healthy)...",
+                self.session_pool.healthy_count(),
+                self.session_pool.len()
+            );
+
+            for attempt in 0..=MAX_RETRIES {
+                let session_file = self.session_pool.select().ok_or_else(|| {
+                    AppError::telegram("No usable sessions left in the session pool")
+                })?;
+                let session = match Session::load_file(&session_file) {
+                    Ok(session) => {
+                        info!("Loaded existing session: {}", session_file);
+                        session
+                    }
+                    Err(_) => {
+                        info!("Failed to load session {}, creating new one", session_file);
+                        Session::new()
+                    }
+                };
+
+                let config = Config {
+                    session,
+                    api_id: self.api_id,
+                    api_hash: self.api_hash.clone(),
+                    params: InitParams {
+                        ..Default::default()
+                    },
+                };
+
+                let client = match Client::connect(config).await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        let Some(delay) = budget.retry_delay(attempt) else {
+                            error!(
+                                "Failed to connect Telegram client after {} attempts: {}",
+                                attempt + 1,
+                                e
+                            );
+                            return Err(AppError::telegram(e));
+                        };
+                        warn!(
+                            "Failed to connect Telegram client (attempt {}/{}): {}. Retrying in {}ms",
+                            attempt + 1,
+                            MAX_RETRIES + 1,
+                            e,
+                            delay.as_millis()
+                        );
+                        sleep(delay).await;
+                        continue;
+                    }
+                };
+
+                match client.is_authorized().await {
+                    Ok(true) => {
+                        info!(
+                            "Client connected and authorized successfully with {} (attempt {})",
+                            session_file,
+                            attempt + 1
+                        );
+                        self.session_pool.record_success(&session_file);
+                        self.current_session = Some(session_file);
+                        self.client = Some(client);
+                        break;
+                    }
+                    Ok(false) => {
+                        self.session_pool.record_auth_failure(&session_file);
+                        if attempt == MAX_RETRIES || !budget.take_retry(Duration::ZERO) {
+                            return Err(AppError::telegram("No authorized session available. Please run `cargo run --bin authorize` to create new sessions."));
+                        }
+                        warn!(
+                            "Session {} is not authorized (attempt {}/{}), rotating to another session",
+                            session_file,
+                            attempt + 1,
+                            MAX_RETRIES + 1
+                        );
+                    }
+                    Err(e) => {
+                        let Some(delay) = budget.retry_delay(attempt) else {
+                            error!(
+                                "Failed to check client authorization after {} attempts: {}",
+                                attempt + 1,
+                                e
+                            );
+                            return Err(e.into());
+                        };
+                        warn!(
+                            "Failed to check client authorization (attempt {}/{}): {}. Retrying in {}ms",
+                            attempt + 1,
+                            MAX_RETRIES + 1,
+                            e,
+                            delay.as_millis()
+                        );
+                        sleep(delay).await;
+                    }
+                }
+            }
+        }
+
+        Ok(self.client.as_ref().unwrap())
+    }
+
+    /// sessions of this engine that are neither flood waited nor logged out
+    pub fn healthy_sessions(&self) -> usize {
+        self.session_pool.healthy_count()
+    }
+
+    pub fn backend_policy(&self) -> BackendPolicy {
+        self.backend_config.policy
+    }
+
+    /// switches the backends used by the following fetches
+    pub fn set_backend_policy(&mut self, policy: BackendPolicy) {
+        info!("Backend policy set to {}", policy.as_str());
+        self.backend_config = BackendConfig::new(policy);
+    }
+
+    fn api_enabled(&self) -> bool {
+        !mock::enabled()
+            && self
+                .backend_config
+                .enabled_backends
+                .contains(&BackendType::Api)
+    }
+
+    /// connects a telegram client ahead of time so a following analysis can fetch right away;
+    /// skipped when the channel's cached messages will be served without a refresh
+    pub async fn prewarm(&mut self, channel_username: &str, depth: AnalysisDepth) {
+        if self.client.is_some() || !self.api_enabled() {
+            return;
+        }
+        if let Some(corpus) = self
+            .cache
+            .load_channel_messages_with_age(&depth.cache_name(channel_username))
+            .await
+        {
+            if corpus.freshness() != CorpusFreshness::Outdated {
+                return;
+            }
+        }
+
+        info!(
+            "Pre-warming Telegram client for channel {}",
+            channel_username
+        );
+        if let Err(e) = self.ensure_client(&RetryBudget::unbounded()).await {
+            warn!(
+                "Failed to pre-warm Telegram client for {}: {}",
+                channel_username, e
+            );
+        }
+    }
+
+    pub async fn validate_channel(&mut self, channel_username: &str) -> Result<bool, AppError> {
+        self.validate_channel_within(channel_username, &RetryBudget::unbounded())
+            .await
+    }
+
+    /// like validate_channel, drawing retries from an analysis' budget
+    async fn validate_channel_within(
+        &mut self,
+        channel_username: &str,
+        budget: &RetryBudget,
+    ) -> Result<bool, AppError> {
+        let clean_username = channel_username
+            .strip_prefix('@')
+            .unwrap_or(channel_username);
+
+        info!("Validating channel: {}", clean_username);
+        if mock::enabled() {
+            return Ok(mock::fixture_exists(clean_username));
+        }
+
+        for attempt in 0..=MAX_RETRIES {
+            // rate limit username resolution on every attempt
+            self.rate_limiter.wait_for_username_resolution().await;
+
+            let client = match self.ensure_client(budget).await {
+                Ok(client) => client,
+                Err(e) => {
+                    let Some(delay) = budget.retry_delay(attempt) else {
+                        error!(
+                            "Failed to get client for channel validation after {} attempts: {}",
+                            attempt + 1,
+                            e
+                        );
+                        return Err(e);
+                    };
+                    warn!(
+                        "Failed to get client for channel validation (attempt {}/{}): {}. Retrying in {}ms",
+                        attempt + 1,
+                        MAX_RETRIES + 1,
+                        e,
+                        delay.as_millis()
+                    );
+                    sleep(delay).await;
+                    continue;
+                }
+            };
+
+            match client.resolve_username(clean_username).await {
+                Ok(Some(chat)) => {
+                    info!(
+                        "Channel {} is valid and accessible (attempt {})",
+                        clean_username,
+                        attempt + 1
+                    );
+                    // cache the resolved channel
+                    self.resolved_channels
+                        .insert(clean_username.to_string(), Arc::new(chat));
+                    return Ok(true);
+                }
+                Ok(None) => {
+                    info!("Channel {} not found", clean_username);
+                    return Ok(false);
+                }
+                Err(e) => {
+                    if let Some(analysis_error) = AnalysisError::from_invocation_error(&e) {
+                        if attempt < MAX_RETRIES
+                            && absorb_flood_wait(&self.rate_limiter, &analysis_error, budget).await
+                        {
+                            continue;
+                        }
+                        warn!(
+                            "Channel validation for {} failed permanently: {}",
+                            clean_username, e
+                        );
+                        self.penalize_current_session(&analysis_error);
+                        return Err(analysis_error.into());
+                    }
+
+                    let Some(delay) = budget.retry_delay(attempt) else {
+                        error!(
+                            "Error validating channel {} after {} attempts: {}",
+                            clean_username,
+                            attempt + 1,
+                            e
+                        );
+                        return Err(e.into());
+                    };
+                    warn!(
+                        "Channel validation failed for {} (attempt {}/{}): {}. Retrying in {}ms",
+                        clean_username,
+                        attempt + 1,
+                        MAX_RETRIES + 1,
+                        e,
+                        delay.as_millis()
+                    );
+                    sleep(delay).await;
+                    // reset client and clear channel cache on connection errors
+                    self.client = None;
+                    self.resolved_channels.remove(clean_username);
+                }
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// loads or fetches the messages to analyze; a channel whose posts are mostly without
+    /// text fails with LowTextCoverage unless `allow_low_text` is set
+    #[allow(clippy::too_many_arguments)]
+    pub async fn prepare_analysis_data(
+        &mut self,
+        channel_username: &str,
+        focus: Option<&str>,
+        topic: Option<&ForumTopic>,
+        tier: ModelTier,
+        language: OutputLanguage,
+        depth: AnalysisDepth,
+        allow_low_text: bool,
+        budget: &RetryBudget,
+    ) -> Result<AnalysisData, AppError> {
+        info!(
+            "Starting {} analysis for channel: {}",
+            depth.as_str(),
+            channel_username
+        );
+        // before anything is fetched or charged
+        let resolved_id = self
+            .resolved_channels
+            .get(channel_username.trim_start_matches('@'))
+            .map(|chat| chat.id());
+        self.enforce_blocklist(channel_username, resolved_id).await?;
+
+        // forwarded messages are stored once, whatever the depth
+        let self_corpus = is_self_corpus(channel_username);
+        let cache_name = if self_corpus {
+            channel_username.to_string()
+        } else {
+            depth.cache_name(channel_username)
+        };
+        let mut fetch_duration = None;
+        let mut removed_messages = 0;
+        let mut revalidate = false;
+        let cached = self.cache.load_channel_messages_with_age(&cache_name).await;
+        let (messages, backend, skipped, kind) = match cached {
+            Some(corpus)
+                if self_corpus
+                    || corpus.freshness() != CorpusFreshness::Outdated
+                    || !self.any_backend_available() =>
+            {
+                // a stale corpus, or an outdated one while every backend is busy, is served
+                // as it is and fetched again once a worker is free
+                revalidate = !self_corpus && corpus.freshness() != CorpusFreshness::Fresh;
+                info!(
+                    "Using cached messages for channel: {} ({} messages)",
+                    channel_username,
+                    corpus.messages.len()
+                );
+                (corpus.messages, corpus.backend, corpus.skipped, corpus.kind)
+            }
+            Some(corpus) => {
+                info!(
+                    "Refreshing {}h old cached messages for channel: {}",
+                    corpus.age.as_secs() / 3600,
+                    channel_username
+                );
+                let fetch_started = Instant::now();
+                match self
+                    .fetch_and_cache_messages(
+                        channel_username,
+                        &cache_name,
+                        depth,
+                        budget,
+                        Some(&corpus),
+                    )
+                    .await
+                {
+                    Ok(fetched) => {
+                        fetch_duration = Some(fetch_started.elapsed());
+                        removed_messages =
+                            count_deleted_messages(&corpus.messages, &fetched.messages);
+                        if removed_messages > 0 {
+                            info!(
+                                "Dropped {} deleted posts of channel {} from its corpus",
+                                removed_messages, channel_username
+                            );
+                        }
+                        (
+                            fetched.messages,
+                            Some(fetched.backend),
+                            fetched.skipped,
+                            fetched.kind,
+                        )
+                    }
+                    // the cached corpus is still valid, a failed refresh shouldn't fail the analysis
+                    Err(e) => {
+                        warn!(
+                            "Failed to refresh messages of channel {}, using the cached ones: {}",
+                            channel_username, e
+                        );
+                        (corpus.messages, corpus.backend, corpus.skipped, corpus.kind)
+                    }
+                }
+            }
+            None if self_corpus => {
+                return Err(AnalysisError::Internal(format!(
+                    "forwarded messages of {} are no longer stored",
+                    channel_username
+                ))
+                .into());
+            }
+            None => {
+                info!("Fetching fresh messages from channel: {}", channel_username);
+                let fetch_started = Instant::now();
+                let fetched = self
+                    .fetch_and_cache_messages(channel_username, &cache_name, depth, budget, None)
+                    .await?;
+                fetch_duration = Some(fetch_started.elapsed());
+                (
+                    fetched.messages,
+                    Some(fetched.backend),
+                    fetched.skipped,
+                    fetched.kind,
+                )
+            }
+        };
+
+        // a topic analysis reads the topic's messages out of the group's corpus; the
+        // skipped posts can't be told apart by topic, so they only count for the group
+        let (messages, skipped) = match topic {
+            Some(topic) => {
+                let messages = topic_messages(messages, topic.thread_id);
+                info!(
+                    "Analyzing {} messages of topic {} in {}",
+                    messages.len(),
+                    topic.thread_id,
+                    channel_username
+                );
+                (messages, 0)
+            }
+            None => (messages, skipped),
+        };
+
+        // refuse oversized channels before any llm call, so no credit is spent on a run
+        // that would exhaust its retries anyway
+        let chars = corpus_chars(&messages);
+        let limit = depth.corpus_char_limit(self.max_corpus_chars);
+        if chars > limit {
+            warn!(
+                "Channel {} is too large for {} analysis: {} characters, limit {}",
+                channel_username,
+                depth.as_str(),
+                chars,
+                limit
+            );
+            return Err(AnalysisError::CorpusTooLarge {
+                depth,
+                chars,
+                limit,
+            }
+            .into());
+        }
+
+        // mostly image, video or forwarded posts make for a poor analysis, so the user
+        // confirms it before a credit is spent on it; a channel with nothing to analyze at
+        // all fails with no messages instead. a profile is short by nature, its card and
+        // the few posts it has are all there is, and group messages are stored as text
+        let coverage = match kind {
+            CorpusKind::Channel => text_coverage(&messages, skipped),
+            CorpusKind::Profile | CorpusKind::CrossGroup | CorpusKind::RoastBattle => None,
+        };
+        if let Some(coverage) = coverage {
+            if coverage < MIN_TEXT_COVERAGE && !allow_low_text && !messages.is_empty() {
+                warn!(
+                    "Channel {} has low text coverage: {}% of {} posts",
+                    channel_username,
+                    coverage,
+                    messages.len() + skipped
+                );
+                return Err(AnalysisError::LowTextCoverage(coverage).into());
+            }
+        }
+
+        // different focus instructions, topics, model tiers and output languages produce
+        // different results, so they must not share a cache entry; the defaults keep the
+        // original key. profiles and cross-group messages are analyzed with their own prompts
+        let base_prompt_type = match kind {
+            CorpusKind::Channel => "analysis",
+            CorpusKind::Profile => "profile",
+            CorpusKind::CrossGroup => "cross_group",
+            CorpusKind::RoastBattle => "roast_battle",
+        };
+        let mut prompt_type = match focus {
+            Some(focus) => format!("{}:{}", base_prompt_type, focus),
+            None => base_prompt_type.to_string(),
+        };
+        if let Some(topic) = topic {
+            prompt_type = format!("{}/{}:{}", prompt_type, topic.thread_id, topic.name);
+        }
+        if tier != ModelTier::Auto {
+            prompt_type = format!("{}@{}", prompt_type, tier.as_str());
+        }
+        if language != OutputLanguage::Channel {
+            prompt_type = format!("{}#{}", prompt_type, language.as_str());
+        }
+        let cache_key = self.cache.get_llm_cache_key(&messages, &prompt_type);
+        Ok(AnalysisData {
+            messages,
+            cache_key,
+            fetch_duration,
+            removed_messages,
+            backend,
+            kind,
+            revalidate,
+        })
+    }
+
+    /// fetches the channel again and replaces the corpus an analysis was served stale,
+    /// unless another refresh got to it first
+    pub async fn refresh_messages(
+        &mut self,
+        channel_username: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+    ) -> Result<(), AppError> {
+        let cache_name = depth.cache_name(channel_username);
+        let cached = self.cache.load_channel_messages_with_age(&cache_name).await;
+        if cached
+            .as_ref()
+            .is_some_and(|corpus| corpus.freshness() == CorpusFreshness::Fresh)
+        {
+            return Ok(());
+        }
+        info!(
+            "Refreshing stale messages of channel {} in the background",
+            channel_username
+        );
+        self.fetch_and_cache_messages(
+            channel_username,
+            &cache_name,
+            depth,
+            budget,
+            cached.as_ref(),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// whether a fetch could start right away, without waiting out a backend rate limit
+    fn any_backend_available(&self) -> bool {
+        self.backend_config
+            .enabled_backends
+            .iter()
+            .any(|backend| self.backend_rate_limiter.is_available(*backend))
+    }
+
+    /// fetches the channel's current messages and replaces its cached corpus with them;
+    /// with a `cached` corpus fetched in full recently, only the posts newer than it are
+    /// fetched and merged into it
+    async fn fetch_and_cache_messages(
+        &mut self,
+        channel_username: &str,
+        cache_name: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+        cached: Option<&CachedCorpus>,
+    ) -> Result<FetchedMessages, AppError> {
+        // while telegram keeps failing, fetches fail fast instead of retrying
+        telegram_breaker().try_acquire(Instant::now())?;
+        let since = cached.and_then(CachedCorpus::fetch_since);
+        let fetched = self
+            .fetch_messages(channel_username, depth, budget, since)
+            .await;
+        record_fetch_outcome(&fetched);
+        let mut fetched = fetched?;
+        info!(
+            "Fetched {} messages from channel {} with the {} backend, skipped {}",
+            fetched.messages.len(),
+            channel_username,
+            fetched.backend.name(),
+            fetched.skipped
+        );
+        // a channel that turned out to be a profile is taken as fetched, in full
+        let merge = match (since, cached) {
+            (Some(since), Some(corpus)) if fetched.kind == CorpusKind::Channel => {
+                Some((since, corpus))
+            }
+            _ => None,
+        };
+        let saved = match merge {
+            Some((since, corpus)) => {
+                // the corpus keeps the size of a full fetch, or grows to the depth's limit
+                let limit = corpus.messages.len().max(depth.api_message_limit());
+                let (messages, skipped) = merge_fetched_messages(
+                    fetched.messages,
+                    fetched.skipped,
+                    &corpus.messages,
+                    corpus.skipped,
+                    since,
+                    limit,
+                );
+                fetched.messages = messages;
+                fetched.skipped = skipped;
+                self.cache
+                    .save_merged_channel_messages(
+                        cache_name,
+                        &fetched.messages,
+                        Some(fetched.backend),
+                        fetched.skipped,
+                        fetched.kind,
+                    )
+                    .await
+            }
+            None => {
+                self.cache
+                    .save_channel_messages(
+                        cache_name,
+                        &fetched.messages,
+                        Some(fetched.backend),
+                        fetched.skipped,
+                        fetched.kind,
+                    )
+                    .await
+            }
+        };
+        if let Err(e) = saved {
+            error!(
+                "Failed to cache messages for channel {}: {}",
+                channel_username, e
+            );
+            // Continue execution - caching failure shouldn't stop the analysis
+        }
+        Ok(fetched)
+    }
+
+    async fn fetch_messages(
+        &mut self,
+        channel_username: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+        since: Option<i64>,
+    ) -> Result<FetchedMessages, AppError> {
+        // web scraping needs no telegram session
+        if self.api_enabled() {
+            self.ensure_client(budget).await.map_err(|e| {
+                let e = budget_failure(e, budget);
+                error!(
+                    "Failed to ensure client for channel {}: {}",
+                    channel_username, e
+                );
+                e
+            })?;
+        }
+        self.get_all_messages(channel_username, depth, budget, since)
+            .await
+            .map_err(|e| {
+                let e = budget_failure(e, budget);
+                error!(
+                    "Failed to fetch messages from channel {}: {}",
+                    channel_username, e
+                );
+                e
+            })
+    }
+
+    pub async fn finish_analysis(
+        &mut self,
+        cache_key: &str,
+        result: AnalysisResult,
+    ) -> Result<(), AppError> {
+        // cache the full analysis result
+        if let Err(e) = self.cache.save_llm_result(cache_key, &result).await {
+            info!("Failed to cache LLM result: {}", e);
+        }
+        Ok(())
+    }
+
+    /// fetches with the first available backend of the policy, or waits for the one
+    /// available soonest; with `since`, only the messages newer than that id
+    async fn get_all_messages(
+        &mut self,
+        channel_username: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+        since: Option<i64>,
+    ) -> Result<FetchedMessages, AppError> {
+        info!("Getting messages from {}", channel_username);
+
+        if mock::enabled() {
+            info!("Reading channel {} from its fixture", channel_username);
+            let messages = mock::load_fixture(channel_username)?
+                .into_iter()
+                .filter(|msg| since.is_none() || msg.id > since)
+                .collect();
+            return Ok(FetchedMessages {
+                messages,
+                // fixtures stand in for the web preview
+                backend: BackendType::WebScraping,
+                skipped: 0,
+                kind: CorpusKind::Channel,
+            });
+        }
+
+        // only a telegram account can join a private channel
+        if let Some(hash) = invite_hash(channel_username) {
+            if !self.api_enabled() {
+                return Err(AnalysisError::ChannelPrivate.into());
+            }
+            self.backend_rate_limiter
+                .wait_for_backend(BackendType::Api)
+                .await;
+            return self
+                .fetch_with_invite(channel_username, hash, depth, budget, since)
+                .await;
+        }
+
+        let enabled_backends = self.backend_config.enabled_backends.clone();
+        let backend = match enabled_backends
+            .iter()
+            .find(|backend| self.backend_rate_limiter.is_available(**backend))
+        {
+            Some(backend) => *backend,
+            None => {
+                // every enabled backend is rate limited, wait for the closest one
+                let closest_backend = enabled_backends
+                    .iter()
+                    .copied()
+                    .min_by_key(|backend| {
+                        self.backend_rate_limiter
+                            .time_until_available(*backend)
+                            .unwrap_or_default()
+                    })
+                    .unwrap_or(BackendType::WebScraping);
+                if let Some(wait_time) = self
+                    .backend_rate_limiter
+                    .time_until_available(closest_backend)
+                {
+                    info!(
+                        "Waiting {}s for {} backend",
+                        wait_time.as_secs(),
+                        closest_backend.name()
+                    );
+                    self.backend_rate_limiter
+                        .wait_for_backend(closest_backend)
+                        .await;
+                }
+                closest_backend
+            }
+        };
+
+        match backend {
+            BackendType::WebScraping => {
+                let fetched = self
+                    .fetch_with_web_scraping(channel_username, depth, since)
+                    .await?;
+                // users have no web preview, only the api can tell a profile from an
+                // empty channel; a refresh finding no new posts is neither
+                if fetched.messages.is_empty()
+                    && fetched.skipped == 0
+                    && since.is_none()
+                    && self.api_enabled()
+                {
+                    info!(
+                        "Web preview of {} has no posts, checking it with the API backend",
+                        channel_username
+                    );
+                    self.backend_rate_limiter
+                        .wait_for_backend(BackendType::Api)
+                        .await;
+                    self.ensure_client(budget).await?;
+                    return self
+                        .fetch_with_api(channel_username, depth, budget, since)
+                        .await;
+                }
+                Ok(fetched)
+            }
+            BackendType::Api => match self
+                .fetch_with_api(channel_username, depth, budget, since)
+                .await
+            {
+                Ok(fetched) => Ok(fetched),
+                Err(e) => {
+                    let AnalysisError::FloodWait(seconds) = e.failure() else {
+                        return Err(e);
+                    };
+                    // keep the api out of rotation until telegram lets us back in
+                    self.backend_rate_limiter.record_flood_wait(seconds);
+                    if !self
+                        .backend_config
+                        .enabled_backends
+                        .contains(&BackendType::WebScraping)
+                    {
+                        return Err(e);
+                    }
+
+                    warn!(
+                        "API backend flood-waited for {}s, falling back to web scraping for {}",
+                        seconds, channel_username
+                    );
+                    self.backend_rate_limiter
+                        .wait_for_backend(BackendType::WebScraping)
+                        .await;
+                    self.fetch_with_web_scraping(channel_username, depth, since)
+                        .await
+                }
+            },
+        }
+    }
+
+    async fn fetch_with_web_scraping(
+        &mut self,
+        channel_username: &str,
+        depth: AnalysisDepth,
+        since: Option<i64>,
+    ) -> Result<FetchedMessages, AppError> {
+        info!("Using web scraping backend for {}", channel_username);
+        let channel_url = format!("https://t.me/{}", channel_username.trim_start_matches('@'));
+        let (messages, skipped) = self
+            .web_scraper
+            .scrape_channel_messages(&channel_url, depth.web_pages(), since)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Web scraping failed for channel {}: {}",
+                    channel_username, e
+                );
+                AppError::from(e)
+            })?;
+        self.backend_rate_limiter
+            .record_backend_call(BackendType::WebScraping);
+        Ok(FetchedMessages {
+            messages,
+            backend: BackendType::WebScraping,
+            skipped,
+            kind: CorpusKind::Channel,
+        })
+    }
+
+    /// fetches a private channel through its invite link: joins it unless the account is
+    /// already a member, reads the messages and leaves again
+    async fn fetch_with_invite(
+        &mut self,
+        channel_username: &str,
+        hash: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+        since: Option<i64>,
+    ) -> Result<FetchedMessages, AppError> {
+        info!("Joining private channel {} by invite", channel_username);
+        self.rate_limiter.wait_for_username_resolution().await;
+        // the account that joined is the one to leave, even if the session rotates meanwhile
+        let client = self.ensure_client(budget).await?.clone();
+        let (chat, joined) = join_invite(&client, hash).await.map_err(|e| {
+            error!("Failed to join private channel {}: {}", channel_username, e);
+            e
+        })?;
+        self.resolved_channels
+            .insert(channel_username.to_string(), Arc::new(chat.clone()));
+
+        let fetched = self
+            .get_all_messages_api(channel_username, depth, budget, since)
+            .await;
+        self.resolved_channels.remove(channel_username);
+        if joined {
+            match client.delete_dialog(&chat).await {
+                Ok(()) => info!("Left private channel {}", channel_username),
+                Err(e) => warn!(
+                    "Failed to leave private channel {}: {}",
+                    channel_username, e
+                ),
+            }
+        }
+        self.backend_rate_limiter
+            .record_backend_call(BackendType::Api);
+        fetched
+    }
+
+    async fn fetch_with_api(
+        &mut self,
+        channel_username: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+        since: Option<i64>,
+    ) -> Result<FetchedMessages, AppError> {
+        info!("Using API backend for {}", channel_username);
+
+        // validate channel when using API backend
+        match self.validate_channel_within(channel_username, budget).await {
+            Ok(true) => {}
+            Ok(false) => {
+                error!(
+                    "Channel validation failed for {}: channel not found or not accessible",
+                    channel_username
+                );
+                return Err(AnalysisError::ChannelNotFound.into());
+            }
+            Err(e) => {
+                error!("Channel validation error for {}: {}", channel_username, e);
+                return Err(e);
+            }
+        }
+
+        self.ensure_client(budget).await.map_err(|e| {
+            error!("Failed to ensure client for API backend: {}", e);
+            e
+        })?;
+        let fetched = self
+            .get_all_messages_api(channel_username, depth, budget, since)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to get messages via API for channel {}: {}",
+                    channel_username, e
+                );
+                e
+            })?;
+        self.backend_rate_limiter
+            .record_backend_call(BackendType::Api);
+        Ok(fetched)
+    }
+
+    /// the channel's messages and the number of forwarded or too short ones left out
+    async fn get_all_messages_api(
+        &mut self,
+        channel_username: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+        since: Option<i64>,
+    ) -> Result<FetchedMessages, AppError> {
+        let clean_username = channel_username
+            .strip_prefix('@')
+            .unwrap_or(channel_username);
+
+        // check for cached channel first, fallback to resolution if needed
+        let channel = if let Some(cached_channel) = self.resolved_channels.get(clean_username) {
+            info!("Using cached channel for {}", clean_username);
+            Some(cached_channel.clone())
+        } else {
+            info!("No cached channel found, resolving {}", clean_username);
+            // get client reference
+            let client = self
+                .client
+                .as_ref()
+                .ok_or_else(|| AppError::telegram("Client not initialized"))?;
+            // retry channel resolution
+            let mut attempt = 0;
+            loop {
+                self.rate_limiter.wait_for_username_resolution().await;
+                match client.resolve_username(clean_username).await {
+                    Ok(channel) => {
+                        if let Some(ref ch) = channel {
+                            // cache the newly resolved channel
+                            self.resolved_channels
+                                .insert(clean_username.to_string(), Arc::new(ch.clone()));
+                        }
+                        break channel.map(Arc::new);
+                    }
+                    Err(e) => {
+                        if let Some(analysis_error) = AnalysisError::from_invocation_error(&e) {
+                            if attempt < MAX_RETRIES
+                                && absorb_flood_wait(&self.rate_limiter, &analysis_error, budget)
+                                    .await
+                            {
+                                attempt += 1;
+                                continue;
+                            }
+                            warn!(
+                                "Channel resolution for {} failed permanently: {}",
+                                clean_username, e
+                            );
+                            self.penalize_current_session(&analysis_error);
+                            return Err(analysis_error.into());
+                        }
+
+                        let Some(delay) = budget.retry_delay(attempt) else {
+                            error!(
+                                "Failed to resolve channel {} after {} attempts: {}",
+                                clean_username,
+                                attempt + 1,
+                                e
+                            );
+                            return Err(e.into());
+                        };
+                        warn!(
+                            "Failed to resolve channel {} for message fetching (attempt {}/{}): {}. Retrying in {}ms",
+                            clean_username,
+                            attempt + 1,
+                            MAX_RETRIES + 1,
+                            e,
+                            delay.as_millis()
+                        );
+                        sleep(delay).await;
+                        attempt += 1;
+                    }
+                }
+            }
+        };
+
+        let Some(chat) = channel else {
+            return Ok(FetchedMessages {
+                messages: Vec::new(),
+                backend: BackendType::Api,
+                skipped: 0,
+                kind: CorpusKind::Channel,
+            });
+        };
+        // a channel blocked by id stays blocked after a rename, and so does a private one
+        self.enforce_blocklist(channel_username, Some(chat.id())).await?;
+
+        // a person has no posts of their own, their public profile is analyzed instead
+        if let Chat::User(user) = chat.as_ref() {
+            info!("{} is a user, analyzing their profile", clean_username);
+            return self.fetch_profile(user, depth, budget).await;
+        }
+
+        let (messages, skipped) = self
+            .fetch_chat_messages(&chat, clean_username, depth, budget, since)
+            .await?;
+        Ok(FetchedMessages {
+            messages,
+            backend: BackendType::Api,
+            skipped,
+            kind: CorpusKind::Channel,
+        })
+    }
+
+    /// a user's name, bio and the posts of the channel shown on their profile
+    async fn fetch_profile(
+        &mut self,
+        user: &grammers_client::types::User,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+    ) -> Result<FetchedMessages, AppError> {
+        let client = self
+            .client
+            .clone()
+            .ok_or_else(|| AppError::telegram("Client not initialized"))?;
+        self.rate_limiter.wait_for_username_resolution().await;
+        let tl::enums::users::UserFull::Full(full) = client
+            .invoke(&tl::functions::users::GetFullUser {
+                id: user.pack().to_input_user_lossy(),
+            })
+            .await
+            .map_err(|e| match AnalysisError::from_invocation_error(&e) {
+                Some(analysis_error) => AppError::from(analysis_error),
+                None => AppError::from(e),
+            })?;
+        let tl::enums::UserFull::Full(profile) = full.full_user;
+
+        let personal_channel = profile.personal_channel_id.and_then(|channel_id| {
+            full.chats
+                .into_iter()
+                .map(Chat::from_raw)
+                .find(|chat| chat.id() == channel_id)
+        });
+        let (posts, skipped) = match personal_channel {
+            Some(channel) => {
+                info!(
+                    "Fetching posts of the personal channel of user {}",
+                    user.id()
+                );
+                let label = channel.id().to_string();
+                self.fetch_chat_messages(&channel, &label, depth, budget, None)
+                    .await?
+            }
+            None => (Vec::new(), 0),
+        };
+
+        let messages = profile_messages(&user.full_name(), profile.about.as_deref(), posts);
+        if messages.is_empty() {
+            return Err(AnalysisError::EmptyProfile.into());
+        }
+        Ok(FetchedMessages {
+            messages,
+            backend: BackendType::Api,
+            skipped,
+            kind: CorpusKind::Profile,
+        })
+    }
+
+    /// up to the depth's limit of a chat's messages and the number of forwarded or too
+    /// short ones left out, stopping at the message `since` when given; `label` names the
+    /// chat in logs and resolution cache
+    async fn fetch_chat_messages(
+        &mut self,
+        chat: &Chat,
+        label: &str,
+        depth: AnalysisDepth,
+        budget: &RetryBudget,
+        since: Option<i64>,
+    ) -> Result<(Vec<MessageDict>, usize), AppError> {
+        let mut messages = Vec::new();
+        let mut skipped = 0;
+
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| AppError::telegram("Client not initialized"))?;
+        for attempt in 0..=MAX_RETRIES {
+            self.rate_limiter.wait_for_message_iteration().await;
+            let mut message_iter = client.iter_messages(chat);
+            let mut current_messages = Vec::new();
+            let mut current_skipped = 0;
+
+            match async {
+                while let Some(message) = message_iter.next().await? {
+                    // messages come newest first, the rest are cached already
+                    if since.is_some_and(|since| i64::from(message.id()) <= since) {
+                        break;
+                    }
+                    if message.forward_header().is_some() {
+                        current_skipped += 1;
+                        continue;
+                    }
+                    if message.text().len() < MIN_TEXT_LENGTH {
+                        current_skipped += 1;
+                        continue;
+                    }
+
+                    current_messages.push(MessageDict {
+                        id: Some(i64::from(message.id())),
+                        date: Some(message.date().format("%Y-%m-%d").to_string()),
+                        message: Some(message.text().to_string()),
+                        images: None, // Telegram API messages don't include images in this context
+                        thread_id: message_thread_id(&message),
+                    });
+
+                    if current_messages.len() >= depth.api_message_limit() {
+                        break;
+                    }
+                }
+                Ok::<(), InvocationError>(())
+            }
+            .await
+            {
+                Ok(_) => {
+                    messages = current_messages;
+                    skipped = current_skipped;
+                    info!(
+                        "Retrieved {} messages, skipped {} (attempt {})",
+                        messages.len(),
+                        skipped,
+                        attempt + 1
+                    );
+                    break;
+                }
+                Err(e) => {
+                    if let Some(analysis_error) = AnalysisError::from_invocation_error(&e) {
+                        if attempt < MAX_RETRIES
+                            && absorb_flood_wait(&self.rate_limiter, &analysis_error, budget).await
+                        {
+                            continue;
+                        }
+                        warn!("Fetching messages from {} failed permanently: {}", label, e);
+                        self.penalize_current_session(&analysis_error);
+                        return Err(analysis_error.into());
+                    }
+
+                    let Some(delay) = budget.retry_delay(attempt) else {
+                        error!(
+                            "Failed to fetch messages from {} after {} attempts: {}",
+                            label,
+                            attempt + 1,
+                            e
+                        );
+                        return Err(e.into());
+                    };
+                    warn!(
+                        "Failed to fetch messages from {} (attempt {}/{}): {}. Retrying in {}ms",
+                        label,
+                        attempt + 1,
+                        MAX_RETRIES + 1,
+                        e,
+                        delay.as_millis()
+                    );
+                    sleep(delay).await;
+                    // clear channel cache on message fetching errors
+                    self.resolved_channels.remove(label);
+                }
+            }
+        }
+
+        info!("Retrieved {} messages, skipped {}", messages.len(), skipped);
+        Ok((messages, skipped))
+    }
+}
@@ -0,0 +1,209 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackendType {
+    Api,
+    WebScraping,
+}
+
+impl BackendType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendType::Api => "API",
+            BackendType::WebScraping => "WebScraping",
+        }
+    }
+
+    /// code stored with cached corpora and analyses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackendType::Api => "api",
+            BackendType::WebScraping => "web",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "api" => Some(BackendType::Api),
+            "web" => Some(BackendType::WebScraping),
+            _ => None,
+        }
+    }
+}
+
+/// which backends may fetch messages, and which one is tried first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BackendPolicy {
+    WebOnly,
+    ApiOnly,
+    #[default]
+    PreferWeb,
+    PreferApi,
+}
+
+impl BackendPolicy {
+    pub const ALL: [BackendPolicy; 4] = [
+        BackendPolicy::WebOnly,
+        BackendPolicy::ApiOnly,
+        BackendPolicy::PreferWeb,
+        BackendPolicy::PreferApi,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackendPolicy::WebOnly => "web-only",
+            BackendPolicy::ApiOnly => "api-only",
+            BackendPolicy::PreferWeb => "prefer-web",
+            BackendPolicy::PreferApi => "prefer-api",
+        }
+    }
+
+    /// accepts underscores too, as in "web_only"
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_lowercase().replace('_', "-");
+        Self::ALL.into_iter().find(|policy| policy.as_str() == code)
+    }
+
+    /// reads BACKEND_POLICY, falling back to the default for missing or unknown values
+    pub fn from_env() -> Self {
+        match env::var("BACKEND_POLICY") {
+            Ok(code) => Self::from_code(&code).unwrap_or_else(|| {
+                warn!("Ignoring unknown BACKEND_POLICY {}", code);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// enabled backends in order of preference
+    pub fn backends(&self) -> Vec<BackendType> {
+        match self {
+            BackendPolicy::WebOnly => vec![BackendType::WebScraping],
+            BackendPolicy::ApiOnly => vec![BackendType::Api],
+            BackendPolicy::PreferWeb => vec![BackendType::WebScraping, BackendType::Api],
+            BackendPolicy::PreferApi => vec![BackendType::Api, BackendType::WebScraping],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendConfig {
+    pub policy: BackendPolicy,
+    pub enabled_backends: Vec<BackendType>,
+}
+
+impl BackendConfig {
+    pub fn new(policy: BackendPolicy) -> Self {
+        Self {
+            policy,
+            enabled_backends: policy.backends(),
+        }
+    }
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self::new(BackendPolicy::default())
+    }
+}
+
+#[derive(Debug)]
+pub struct BackendRateLimiter {
+    api_last_call: Option<Instant>,
+    web_scraping_last_call: Option<Instant>,
+    api_rate_limit: Duration,
+    web_scraping_rate_limit: Duration,
+    // set when telegram asked the api backend to back off for longer than we are willing to wait
+    api_flood_wait_until: Option<Instant>,
+}
+
+impl Default for BackendRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackendRateLimiter {
+    pub fn new() -> Self {
+        Self {
+            api_last_call: None,
+            web_scraping_last_call: None,
+            api_rate_limit: Duration::from_secs(600), // 10 minutes for API operations
+            web_scraping_rate_limit: Duration::from_secs(20), // 20 sec for web scraping
+            api_flood_wait_until: None,
+        }
+    }
+
+    pub fn time_until_available(&self, backend: BackendType) -> Option<Duration> {
+        let (last_call, rate_limit) = match backend {
+            BackendType::Api => (self.api_last_call, self.api_rate_limit),
+            BackendType::WebScraping => (self.web_scraping_last_call, self.web_scraping_rate_limit),
+        };
+
+        let rate_limit_wait = last_call
+            .map(|last_time| last_time.elapsed())
+            .filter(|elapsed| *elapsed < rate_limit)
+            .map(|elapsed| rate_limit - elapsed);
+        let flood_wait = match backend {
+            BackendType::Api => self
+                .api_flood_wait_until
+                .and_then(|until| until.checked_duration_since(Instant::now())),
+            BackendType::WebScraping => None,
+        };
+
+        rate_limit_wait
+            .max(flood_wait)
+            .filter(|wait| !wait.is_zero())
+    }
+
+    /// keeps the api backend out of rotation until telegram's FLOOD_WAIT expires
+    pub fn record_flood_wait(&mut self, seconds: u32) {
+        info!(
+            "API backend flood-waited for {}s, preferring other backends until then",
+            seconds
+        );
+        self.api_flood_wait_until = Some(Instant::now() + Duration::from_secs(seconds as u64));
+    }
+
+    pub fn is_available(&self, backend: BackendType) -> bool {
+        self.time_until_available(backend).is_none()
+    }
+
+    pub fn select_available_backend(&self, backends: &[BackendType]) -> Option<BackendType> {
+        for &backend in backends {
+            if self.is_available(backend) {
+                return Some(backend);
+            }
+        }
+        // if none available, return first backend (caller will handle rate limiting)
+        backends.first().copied()
+    }
+
+    pub async fn wait_for_backend(&mut self, backend: BackendType) {
+        if let Some(wait_time) = self.time_until_available(backend) {
+            // add jitter to avoid thundering herd
+            let jitter =
+                Duration::from_millis(fastrand::u64(0..=wait_time.as_millis() as u64 / 10));
+            let total_wait = wait_time + jitter;
+
+            info!(
+                "Rate limiting {}: waiting {}ms (with {}ms jitter)",
+                backend.name(),
+                total_wait.as_millis(),
+                jitter.as_millis()
+            );
+            tokio::time::sleep(total_wait).await;
+        }
+    }
+
+    pub fn record_backend_call(&mut self, backend: BackendType) {
+        // record the call time after the actual request
+        match backend {
+            BackendType::Api => self.api_last_call = Some(Instant::now()),
+            BackendType::WebScraping => self.web_scraping_last_call = Some(Instant::now()),
+        }
+    }
+}
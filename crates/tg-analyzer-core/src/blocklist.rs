@@ -0,0 +1,158 @@
+//! channels that must not be analyzed, e.g. ones whose owners asked to be excluded;
+//! managed by admins with /blocklist and enforced by AnalysisEngine::prepare_analysis_data
+
+use deadpool_postgres::Pool;
+use log::info;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::AppError;
+
+// the bot api writes channel ids with this prefix, mtproto without it
+const BOT_API_CHANNEL_PREFIX: &str = "-100";
+
+/// a blocked channel, by username or by the telegram id that survives renames
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockTarget {
+    // lowercase, without the @
+    Username(String),
+    // bare channel id, as mtproto reports it
+    ChannelId(i64),
+}
+
+impl BlockTarget {
+    /// reads `@name`, `name`, a t.me link, or a channel id with or without the -100 prefix
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Ok(id) = text.parse::<i64>() {
+            let bare = text
+                .strip_prefix(BOT_API_CHANNEL_PREFIX)
+                .and_then(|rest| rest.parse::<i64>().ok())
+                .unwrap_or(id.abs());
+            return (bare > 0).then_some(BlockTarget::ChannelId(bare));
+        }
+        let name = text
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_start_matches("t.me/")
+            .trim_start_matches('@')
+            .trim_end_matches('/');
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        valid.then(|| BlockTarget::Username(name.to_lowercase()))
+    }
+
+    /// the analyzed name of a channel as a target; None for forwarded messages and
+    /// other corpora that aren't a telegram chat, and for private invite links
+    pub fn of_channel(channel_username: &str) -> Option<Self> {
+        if !channel_username.starts_with('@') {
+            return None;
+        }
+        Self::parse(channel_username)
+    }
+
+    /// the form stored in blocked_channels: `@name` or the bare id
+    pub fn key(&self) -> String {
+        match self {
+            BlockTarget::Username(name) => format!("@{}", name),
+            BlockTarget::ChannelId(id) => id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for BlockTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockedChannel {
+    pub target: String,
+    pub reason: Option<String>,
+    pub blocked_by: i64,
+    pub created_at: String, // formatted by postgres as YYYY-MM-DD HH24:MI (UTC)
+}
+
+pub struct ChannelBlocklist {
+    pool: Arc<Pool>,
+}
+
+impl ChannelBlocklist {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// adds a channel, or updates the reason of one already blocked
+    pub async fn block(
+        &self,
+        target: &BlockTarget,
+        reason: Option<&str>,
+        blocked_by: i64,
+    ) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO blocked_channels (target, reason, blocked_by)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (target) DO UPDATE SET reason = $2, blocked_by = $3",
+                &[&target.key(), &reason, &blocked_by],
+            )
+            .await?;
+        info!("Channel {} blocked by {}", target, blocked_by);
+        Ok(())
+    }
+
+    /// false if the channel wasn't blocked
+    pub async fn unblock(&self, target: &BlockTarget) -> Result<bool, AppError> {
+        let client = self.pool.get().await?;
+        let removed = client
+            .execute(
+                "DELETE FROM blocked_channels WHERE target = $1",
+                &[&target.key()],
+            )
+            .await?;
+        if removed > 0 {
+            info!("Channel {} unblocked", target);
+        }
+        Ok(removed > 0)
+    }
+
+    /// every blocked channel, most recently blocked first
+    pub async fn list(&self) -> Result<Vec<BlockedChannel>, AppError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT target, reason, blocked_by,
+                        TO_CHAR(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM blocked_channels
+                 ORDER BY created_at DESC, target",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| BlockedChannel {
+                target: row.get(0),
+                reason: row.get(1),
+                blocked_by: row.get(2),
+                created_at: row.get(3),
+            })
+            .collect())
+    }
+
+    /// whether any of the targets is blocked
+    pub async fn is_blocked(&self, targets: &[BlockTarget]) -> Result<bool, AppError> {
+        if targets.is_empty() {
+            return Ok(false);
+        }
+        let keys = targets.iter().map(BlockTarget::key).collect::<Vec<_>>();
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "SELECT EXISTS (SELECT 1 FROM blocked_channels WHERE target = ANY($1))",
+                &[&keys],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+}
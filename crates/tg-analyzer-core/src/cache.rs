@@ -0,0 +1,527 @@
+use deadpool_postgres::{Config, Pool, Runtime};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::env;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use crate::analysis::{is_self_corpus, CorpusKind, MessageDict};
+use crate::backend_config::BackendType;
+use crate::error::AppError;
+use crate::facts::QuickFacts;
+use crate::report::AnalysisReport;
+
+/// cached corpora younger than this are served as they are
+pub const CORPUS_FRESH_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// cached corpora younger than this but past CORPUS_FRESH_AGE are served right away while
+/// the channel is fetched again in the background; older ones are fetched again before the
+/// analysis, so posts the author deleted drop out well before the cache expires
+pub const CORPUS_REVALIDATE_AGE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// a refresh fetches only the posts newer than the cached ones while the corpus had a full
+/// fetch this recently; older ones are fetched in full again, the only way to see posts the
+/// author deleted
+pub const CORPUS_FULL_FETCH_AGE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// how an analysis treats a cached corpus of a given age
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpusFreshness {
+    Fresh,
+    // served, with a refresh in the background
+    Stale,
+    // refreshed before it's served, if a backend is free
+    Outdated,
+}
+
+impl CorpusFreshness {
+    pub fn of(age: Duration) -> Self {
+        if age < CORPUS_FRESH_AGE {
+            CorpusFreshness::Fresh
+        } else if age < CORPUS_REVALIDATE_AGE {
+            CorpusFreshness::Stale
+        } else {
+            CorpusFreshness::Outdated
+        }
+    }
+}
+
+/// a channel's cached messages and what is known about their fetch
+#[derive(Debug, Clone)]
+pub struct CachedCorpus {
+    pub messages: Vec<MessageDict>,
+    // time since the messages were fetched
+    pub age: Duration,
+    // unknown for corpora cached before it was recorded
+    pub backend: Option<BackendType>,
+    // posts the fetch left out, zero for corpora cached before they were counted
+    pub skipped: usize,
+    pub kind: CorpusKind,
+    // newest message id in the corpus, None when no message has an id
+    pub last_message_id: Option<i64>,
+    // time since the last full fetch, None for corpora never fetched in full since it was
+    // recorded
+    pub full_fetch_age: Option<Duration>,
+}
+
+impl CachedCorpus {
+    pub fn freshness(&self) -> CorpusFreshness {
+        CorpusFreshness::of(self.age)
+    }
+
+    /// the message id a refresh may fetch from instead of fetching the channel in full;
+    /// profiles are always fetched in full, their name and bio have no ids
+    pub fn fetch_since(&self) -> Option<i64> {
+        let recent_full_fetch = self
+            .full_fetch_age
+            .is_some_and(|age| age < CORPUS_FULL_FETCH_AGE);
+        if self.kind == CorpusKind::Channel && recent_full_fetch {
+            self.last_message_id
+        } else {
+            None
+        }
+    }
+}
+
+/// a corpus refreshed from `since`: the posts fetched after it, then the cached ones, down
+/// to `limit` messages by dropping the oldest; the skipped count shrinks with the messages
+/// dropped, so the text coverage keeps its proportion
+pub fn merge_fetched_messages(
+    fetched: Vec<MessageDict>,
+    fetched_skipped: usize,
+    cached: &[MessageDict],
+    cached_skipped: usize,
+    since: i64,
+    limit: usize,
+) -> (Vec<MessageDict>, usize) {
+    let mut messages = fetched
+        .into_iter()
+        .filter(|msg| msg.id.is_some_and(|id| id > since))
+        .collect::<Vec<_>>();
+    let new_ids = messages
+        .iter()
+        .filter_map(|msg| msg.id)
+        .collect::<HashSet<_>>();
+    messages.extend(
+        cached
+            .iter()
+            .filter(|msg| msg.id.is_none_or(|id| !new_ids.contains(&id)))
+            .cloned(),
+    );
+    let skipped = fetched_skipped + cached_skipped;
+    if messages.len() <= limit {
+        return (messages, skipped);
+    }
+
+    // the corpora of both backends aren't strictly ordered by date, so the oldest are
+    // told by their ids; messages without one go first
+    let mut ids = messages.iter().map(|msg| msg.id).collect::<Vec<_>>();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    let oldest_kept = ids[limit - 1];
+    let total = messages.len();
+    let mut kept = 0;
+    messages.retain(|msg| {
+        let keep = kept < limit && msg.id >= oldest_kept;
+        kept += usize::from(keep);
+        keep
+    });
+    let skipped = skipped * messages.len() / total;
+    (messages, skipped)
+}
+
+// set once at startup when DATABASE_READ_URL is configured
+static READ_POOL: OnceLock<Arc<Pool>> = OnceLock::new();
+
+/// sends the read-heavy queries that tolerate replica lag to `pool` from now on
+pub fn enable_read_replica(pool: Arc<Pool>) {
+    if READ_POOL.set(pool).is_err() {
+        warn!("The read replica was already enabled");
+    }
+}
+
+/// the pool for read-heavy queries: the replica if one is enabled, `primary` otherwise
+pub fn read_pool(primary: &Arc<Pool>) -> &Arc<Pool> {
+    READ_POOL.get().unwrap_or(primary)
+}
+
+pub struct CacheManager {
+    pool: Arc<Pool>,
+}
+
+impl CacheManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_pool() -> Result<Pool, AppError> {
+        let database_url = env::var("DATABASE_URL").map_err(|_| {
+            AppError::Validation("DATABASE_URL environment variable not set".into())
+        })?;
+        Self::pool_for(database_url)
+    }
+
+    /// a pool for the read-only replica at DATABASE_READ_URL, None when it isn't set
+    pub async fn create_read_pool() -> Result<Option<Pool>, AppError> {
+        match env::var("DATABASE_READ_URL") {
+            Ok(database_url) if !database_url.trim().is_empty() => {
+                Self::pool_for(database_url).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn pool_for(database_url: String) -> Result<Pool, AppError> {
+        let mut config = Config::new();
+        config.url = Some(database_url);
+        let mut root_store = rustls::RootCertStore::empty();
+        root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let tls = MakeRustlsConnect::new(
+            rustls::ClientConfig::builder()
+                .with_root_certificates(root_store)
+                .with_no_client_auth(),
+        );
+        config
+            .create_pool(Some(Runtime::Tokio1), tls)
+            .map_err(AppError::db)
+    }
+
+    // channel message cache (7-day TTL)
+    const CHANNEL_CACHE_TTL_DAYS: f64 = 7.0;
+
+    pub async fn load_channel_messages(&self, channel_name: &str) -> Option<Vec<MessageDict>> {
+        self.load_channel_messages_with_age(channel_name)
+            .await
+            .map(|corpus| corpus.messages)
+    }
+
+    /// cached messages with how long ago they were fetched, by which backend and how
+    /// many posts the fetch skipped
+    pub async fn load_channel_messages_with_age(&self, channel_name: &str) -> Option<CachedCorpus> {
+        // a lagging replica at worst costs a fetch, but forwarded messages were just
+        // stored and can't be fetched again
+        let pool = if is_self_corpus(channel_name) {
+            &self.pool
+        } else {
+            read_pool(&self.pool)
+        };
+        let client = match pool.get().await {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to get database connection: {}", e);
+                return None;
+            }
+        };
+
+        match client
+            .query_opt(
+                "SELECT messages_data, EXTRACT(EPOCH FROM NOW() - updated_at)::float8, backend,
+                        skipped_messages, peer_kind, last_message_id,
+                        EXTRACT(EPOCH FROM NOW() - full_fetch_at)::float8
+                 FROM channel_messages
+                 WHERE channel_name = $1
+                 AND updated_at > NOW() - INTERVAL '1 day' * $2",
+                &[&channel_name, &Self::CHANNEL_CACHE_TTL_DAYS],
+            )
+            .await
+        {
+            Ok(Some(row)) => {
+                let messages_json: serde_json::Value = row.get(0);
+                let age = Duration::from_secs_f64(row.get::<_, f64>(1).max(0.0));
+                let backend = row
+                    .get::<_, Option<&str>>(2)
+                    .and_then(BackendType::from_code);
+                let skipped = row.get::<_, i32>(3).max(0) as usize;
+                let kind = CorpusKind::from_code(row.get(4));
+                let last_message_id = row.get::<_, Option<i64>>(5);
+                let full_fetch_age = row
+                    .get::<_, Option<f64>>(6)
+                    .map(|secs| Duration::from_secs_f64(secs.max(0.0)));
+                match serde_json::from_value::<Vec<MessageDict>>(messages_json) {
+                    Ok(msg_vec) => {
+                        info!(
+                            "Loaded {} messages from cache for channel {}",
+                            msg_vec.len(),
+                            channel_name
+                        );
+                        Some(CachedCorpus {
+                            messages: msg_vec,
+                            age,
+                            backend,
+                            skipped,
+                            kind,
+                            last_message_id,
+                            full_fetch_age,
+                        })
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to parse cached messages for {}: {}",
+                            channel_name, e
+                        );
+                        None
+                    }
+                }
+            }
+            Ok(None) => {
+                info!(
+                    "No cache found for channel {} (or cache expired)",
+                    channel_name
+                );
+                None
+            }
+            Err(e) => {
+                error!("Database query failed for channel {}: {}", channel_name, e);
+                None
+            }
+        }
+    }
+
+    pub async fn save_channel_messages(
+        &self,
+        channel_name: &str,
+        messages: &[MessageDict],
+        // None for messages that weren't fetched, like the ones forwarded for a self-analysis
+        backend: Option<BackendType>,
+        // posts the fetch left out as forwarded, too short or media only
+        skipped: usize,
+        kind: CorpusKind,
+    ) -> Result<(), AppError> {
+        self.save_corpus(channel_name, messages, backend, skipped, kind, true)
+            .await
+    }
+
+    /// like save_channel_messages, for a corpus merged from a refresh that fetched only the
+    /// newest posts; keeps the time of its last full fetch
+    pub async fn save_merged_channel_messages(
+        &self,
+        channel_name: &str,
+        messages: &[MessageDict],
+        backend: Option<BackendType>,
+        skipped: usize,
+        kind: CorpusKind,
+    ) -> Result<(), AppError> {
+        self.save_corpus(channel_name, messages, backend, skipped, kind, false)
+            .await
+    }
+
+    async fn save_corpus(
+        &self,
+        channel_name: &str,
+        messages: &[MessageDict],
+        backend: Option<BackendType>,
+        skipped: usize,
+        kind: CorpusKind,
+        full_fetch: bool,
+    ) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        let messages_json = serde_json::to_value(messages).map_err(AppError::db)?;
+        let backend = backend.map(|backend| backend.as_str());
+        let skipped = i32::try_from(skipped).unwrap_or(i32::MAX);
+        let last_message_id = messages.iter().filter_map(|msg| msg.id).max();
+
+        // upsert: insert or update if channel already exists
+        client
+            .execute(
+                "INSERT INTO channel_messages (channel_name, messages_data, backend, skipped_messages, peer_kind, last_message_id, full_fetch_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, CASE WHEN $7 THEN NOW() END, NOW())
+             ON CONFLICT (channel_name)
+             DO UPDATE SET messages_data = $2, backend = $3, skipped_messages = $4, peer_kind = $5, last_message_id = $6,
+                 full_fetch_at = CASE WHEN $7 THEN NOW() ELSE channel_messages.full_fetch_at END, updated_at = NOW()",
+                &[
+                    &channel_name,
+                    &messages_json,
+                    &backend,
+                    &skipped,
+                    &kind.as_str(),
+                    &last_message_id,
+                    &full_fetch,
+                ],
+            )
+            .await?;
+
+        info!(
+            "Cached {} messages for channel {}",
+            messages.len(),
+            channel_name
+        );
+        Ok(())
+    }
+
+    /// drops a channel's cached messages at every depth, so its next analysis fetches
+    /// them again; returns how many corpora went
+    pub async fn purge_channel_messages(&self, channel_name: &str) -> Result<u64, AppError> {
+        let client = self.pool.get().await?;
+        let purged = client
+            .execute(
+                "DELETE FROM channel_messages
+                 WHERE channel_name = $1 OR starts_with(channel_name, $1 || '#')",
+                &[&channel_name],
+            )
+            .await?;
+        Ok(purged)
+    }
+
+    /// drops cached messages past the ttl, which analyses no longer read; returns how many
+    /// corpora went
+    pub async fn purge_stale_channel_messages(&self) -> Result<u64, AppError> {
+        let client = self.pool.get().await?;
+        let purged = client
+            .execute(
+                "DELETE FROM channel_messages WHERE updated_at <= NOW() - INTERVAL '1 day' * $1",
+                &[&Self::CHANNEL_CACHE_TTL_DAYS],
+            )
+            .await?;
+        Ok(purged)
+    }
+
+    /// drops every cached corpus; returns how many went
+    pub async fn purge_all_channel_messages(&self) -> Result<u64, AppError> {
+        let client = self.pool.get().await?;
+        let purged = client.execute("DELETE FROM channel_messages", &[]).await?;
+        Ok(purged)
+    }
+
+    // llm result cache
+    fn hash_content<T: Hash>(content: &T) -> String {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        format!("{:x}", hasher.finish())
+    }
+
+    pub fn get_llm_cache_key(&self, messages: &[MessageDict], prompt_type: &str) -> String {
+        let cache_input = (messages, prompt_type);
+        Self::hash_content(&cache_input)
+    }
+
+    pub async fn load_llm_result(&self, cache_key: &str) -> Option<AnalysisResult> {
+        let client = match self.pool.get().await {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to get database connection: {}", e);
+                return None;
+            }
+        };
+
+        match client
+            .query_opt(
+                "SELECT analysis_result FROM llm_results WHERE cache_key = $1",
+                &[&cache_key],
+            )
+            .await
+        {
+            Ok(Some(row)) => {
+                let result_json: serde_json::Value = row.get(0);
+                match serde_json::from_value::<AnalysisResult>(result_json) {
+                    Ok(result) => {
+                        info!("Loaded LLM result from cache (key: {})", cache_key);
+                        Some(result)
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to parse cached LLM result for key {}: {}",
+                            cache_key, e
+                        );
+                        None
+                    }
+                }
+            }
+            Ok(None) => {
+                info!("No LLM cache found for key {}", cache_key);
+                None
+            }
+            Err(e) => {
+                error!(
+                    "Database query failed for LLM cache key {}: {}",
+                    cache_key, e
+                );
+                None
+            }
+        }
+    }
+
+    pub async fn save_llm_result(
+        &self,
+        cache_key: &str,
+        result: &AnalysisResult,
+    ) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        let result_json = serde_json::to_value(result).map_err(AppError::db)?;
+
+        // a partial result is only kept until a regeneration produces a better one
+        client
+            .execute(
+                "INSERT INTO llm_results (cache_key, analysis_result) VALUES ($1, $2)
+                 ON CONFLICT (cache_key) DO UPDATE SET analysis_result = EXCLUDED.analysis_result
+                 WHERE (llm_results.analysis_result->>'partial')::boolean IS TRUE",
+                &[&cache_key, &result_json],
+            )
+            .await?;
+
+        info!("Cached LLM result (key: {})", cache_key);
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnalysisResult {
+    pub professional: Option<String>,
+    pub personal: Option<String>,
+    pub roast: Option<String>,
+    // timeline of a trends analysis, which has none of the other sections
+    #[serde(default)]
+    pub trends: Option<String>,
+    pub messages_count: usize,
+    // model that produced the result; absent for results cached before it was recorded
+    #[serde(default)]
+    pub model: Option<String>,
+    // structured fields when the model answered in json mode
+    #[serde(default)]
+    pub report: Option<AnalysisReport>,
+    // posts the author deleted that a corpus refresh left out of this analysis
+    #[serde(default)]
+    pub removed_messages: usize,
+    // the answer was cut off and couldn't be stitched back together
+    #[serde(default)]
+    pub partial: bool,
+    // prompt version of an analysis; absent for trends and for results cached before
+    // versions were recorded, which all used the base prompt
+    #[serde(default)]
+    pub prompt_version: Option<i32>,
+    // counted from the messages of a channel or group analysis; absent for other corpora
+    // and for results cached before facts were counted
+    #[serde(default)]
+    pub facts: Option<QuickFacts>,
+}
+
+impl AnalysisResult {
+    /// result of a json mode answer; the text sections are kept in their usual fields
+    pub fn from_report(report: AnalysisReport, model: &str) -> Self {
+        Self {
+            professional: Some(report.professional.clone()),
+            personal: Some(report.personal.clone()),
+            roast: Some(report.roast.clone()),
+            trends: None,
+            messages_count: 0,
+            model: Some(model.to_string()),
+            report: Some(report),
+            removed_messages: 0,
+            partial: false,
+            prompt_version: None,
+            facts: None,
+        }
+    }
+
+    /// text sections by tag name, in prompt order
+    pub fn sections(&self) -> [(&'static str, &Option<String>); 4] {
+        [
+            ("professional", &self.professional),
+            ("personal", &self.personal),
+            ("roast", &self.roast),
+            ("trends", &self.trends),
+        ]
+    }
+}
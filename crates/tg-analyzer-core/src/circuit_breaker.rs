@@ -0,0 +1,186 @@
+//! circuit breakers for the llm and telegram backends: after repeated outages new analyses
+//! fail fast with AnalysisError::ServiceDegraded instead of sitting through every retry,
+//! and a background probe closes the breaker once the backend answers again
+
+use log::{info, warn};
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::analysis::AnalysisError;
+use crate::llm::{query_llm_with_schema, ModelTier};
+use crate::retry_budget::RetryBudget;
+use crate::web_scraper::TelegramWebScraper;
+
+/// consecutive failed calls that open a breaker
+pub const FAILURE_THRESHOLD: u32 = 5;
+
+/// how long an open breaker refuses requests before one is let through to probe
+pub const OPEN_COOLDOWN: Duration = Duration::from_secs(60);
+
+// how often the background task looks for breakers to probe
+const PROBE_INTERVAL: Duration = Duration::from_secs(15);
+
+// a probe waits this long for its backend
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// a public channel whose web preview is always there
+const PROBE_CHANNEL: &str = "@telegram";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    // requests fail fast
+    Open,
+    // one request or probe is finding out whether the backend is back
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { since: Instant },
+}
+
+/// counts consecutive failures of one backend and refuses requests while it's down
+pub struct CircuitBreaker {
+    name: &'static str,
+    threshold: u32,
+    cooldown: Duration,
+    circuit: Mutex<Circuit>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name,
+            threshold: threshold.max(1),
+            cooldown,
+            circuit: Mutex::new(Circuit::Closed { failures: 0 }),
+        }
+    }
+
+    fn circuit(&self) -> std::sync::MutexGuard<'_, Circuit> {
+        self.circuit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn state(&self) -> BreakerState {
+        match *self.circuit() {
+            Circuit::Closed { .. } => BreakerState::Closed,
+            Circuit::Open { .. } => BreakerState::Open,
+            Circuit::HalfOpen { .. } => BreakerState::HalfOpen,
+        }
+    }
+
+    /// whether a request may go to the backend at `now`. once the cooldown is over the
+    /// first caller gets through as the probe and the breaker stays half open until its
+    /// outcome is recorded; a probe that never reports is replaced after another cooldown
+    pub fn try_acquire(&self, now: Instant) -> Result<(), AnalysisError> {
+        let mut circuit = self.circuit();
+        match *circuit {
+            Circuit::Closed { .. } => Ok(()),
+            Circuit::Open { until } if now >= until => {
+                info!("{} circuit breaker is half open, probing", self.name);
+                *circuit = Circuit::HalfOpen { since: now };
+                Ok(())
+            }
+            Circuit::HalfOpen { since } if now >= since + self.cooldown => {
+                *circuit = Circuit::HalfOpen { since: now };
+                Ok(())
+            }
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => Err(AnalysisError::ServiceDegraded),
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut circuit = self.circuit();
+        if !matches!(*circuit, Circuit::Closed { .. }) {
+            info!(
+                "{} circuit breaker closed, the backend recovered",
+                self.name
+            );
+        }
+        *circuit = Circuit::Closed { failures: 0 };
+    }
+
+    /// counts a failed call at `now`; a failed probe opens the breaker for another cooldown
+    pub fn record_failure(&self, now: Instant) {
+        let mut circuit = self.circuit();
+        match *circuit {
+            Circuit::Closed { failures } if failures + 1 < self.threshold => {
+                *circuit = Circuit::Closed {
+                    failures: failures + 1,
+                };
+            }
+            Circuit::Closed { .. } | Circuit::HalfOpen { .. } => {
+                warn!(
+                    "{} circuit breaker opened, failing requests fast for {}s",
+                    self.name,
+                    self.cooldown.as_secs()
+                );
+                *circuit = Circuit::Open {
+                    until: now + self.cooldown,
+                };
+            }
+            // calls started before the breaker opened keep failing, that's no news
+            Circuit::Open { .. } => {}
+        }
+    }
+}
+
+/// the breaker of gemini calls
+pub fn llm_breaker() -> &'static CircuitBreaker {
+    static BREAKER: OnceLock<CircuitBreaker> = OnceLock::new();
+    BREAKER.get_or_init(|| CircuitBreaker::new("LLM", FAILURE_THRESHOLD, OPEN_COOLDOWN))
+}
+
+/// the breaker of channel fetches, over both the web and the api backend
+pub fn telegram_breaker() -> &'static CircuitBreaker {
+    static BREAKER: OnceLock<CircuitBreaker> = OnceLock::new();
+    BREAKER.get_or_init(|| CircuitBreaker::new("Telegram", FAILURE_THRESHOLD, OPEN_COOLDOWN))
+}
+
+// a one word answer from the fast tier's first model
+async fn probe_llm() -> bool {
+    let model = ModelTier::Fast.models()[0];
+    let budget = RetryBudget::new(PROBE_TIMEOUT, 0);
+    query_llm_with_schema("Reply with OK.", model, None, PROBE_TIMEOUT, &budget)
+        .await
+        .is_ok()
+}
+
+// the web preview of a channel that's always there
+async fn probe_telegram() -> bool {
+    match TelegramWebScraper::new() {
+        Ok(scraper) => matches!(
+            scraper.fetch_channel_preview(PROBE_CHANNEL).await,
+            Ok(Some(_))
+        ),
+        Err(_) => false,
+    }
+}
+
+// probes the backend of an open breaker and records the outcome
+async fn probe<F: Future<Output = bool>>(breaker: &CircuitBreaker, check: impl FnOnce() -> F) {
+    if breaker.state() != BreakerState::Open || breaker.try_acquire(Instant::now()).is_err() {
+        return;
+    }
+    if check().await {
+        breaker.record_success();
+    } else {
+        info!("{} is still down, the breaker stays open", breaker.name);
+        breaker.record_failure(Instant::now());
+    }
+}
+
+/// probes open breakers in the background, so they close once their backend is back even
+/// when no request comes along to probe it
+pub async fn run_recovery_probes() {
+    loop {
+        sleep(PROBE_INTERVAL).await;
+        probe(llm_breaker(), probe_llm).await;
+        probe(telegram_breaker(), probe_telegram).await;
+    }
+}
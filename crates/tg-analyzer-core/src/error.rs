@@ -0,0 +1,106 @@
+use grammers_client::InvocationError;
+use std::error::Error;
+use std::fmt;
+
+use crate::analysis::AnalysisError;
+use crate::web_scraper::WebScrapingError;
+
+type Source = Box<dyn Error + Send + Sync>;
+
+/// crate-wide error; the variant tells callers what failed, so flood waits, private
+/// channels or missing credits can be handled without downcasting
+#[derive(Debug)]
+pub enum AppError {
+    // telegram sessions, the web preview or the bot api
+    Telegram(Source),
+    Llm(Source),
+    Db(Source),
+    // input or configuration the operation can't work with
+    Validation(String),
+    // invoices, refunds and other star payment failures
+    Payment(String),
+    InsufficientCredits(i32), // user_id
+    // a classified analysis failure, each with its own user-facing explanation
+    Analysis(AnalysisError),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Telegram(e) => write!(f, "Telegram error: {}", e),
+            AppError::Llm(e) => write!(f, "LLM error: {}", e),
+            AppError::Db(e) => write!(f, "Database error: {}", e),
+            AppError::Validation(e) => write!(f, "Invalid input: {}", e),
+            AppError::Payment(e) => write!(f, "Payment error: {}", e),
+            AppError::InsufficientCredits(user_id) => {
+                write!(f, "User with id {} has insufficient credits", user_id)
+            }
+            AppError::Analysis(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Telegram(e) | AppError::Llm(e) | AppError::Db(e) => Some(e.as_ref()),
+            AppError::Analysis(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl AppError {
+    pub fn telegram(err: impl Into<Source>) -> Self {
+        AppError::Telegram(err.into())
+    }
+
+    pub fn llm(err: impl Into<Source>) -> Self {
+        AppError::Llm(err.into())
+    }
+
+    pub fn db(err: impl Into<Source>) -> Self {
+        AppError::Db(err.into())
+    }
+
+    /// the analysis failure class of this error, Internal for the unrecognized ones
+    pub fn failure(&self) -> AnalysisError {
+        match self {
+            AppError::Analysis(e) => e.clone(),
+            AppError::Telegram(e) | AppError::Llm(e) | AppError::Db(e) => {
+                AnalysisError::classify(e.as_ref())
+            }
+            _ => AnalysisError::Internal(self.to_string()),
+        }
+    }
+}
+
+impl From<AnalysisError> for AppError {
+    fn from(err: AnalysisError) -> Self {
+        AppError::Analysis(err)
+    }
+}
+
+impl From<InvocationError> for AppError {
+    fn from(err: InvocationError) -> Self {
+        AppError::Telegram(Box::new(err))
+    }
+}
+
+impl From<WebScrapingError> for AppError {
+    fn from(err: WebScrapingError) -> Self {
+        AppError::Telegram(Box::new(err))
+    }
+}
+
+impl From<tokio_postgres::Error> for AppError {
+    fn from(err: tokio_postgres::Error) -> Self {
+        AppError::Db(Box::new(err))
+    }
+}
+
+impl From<deadpool_postgres::PoolError> for AppError {
+    fn from(err: deadpool_postgres::PoolError) -> Self {
+        AppError::Db(Box::new(err))
+    }
+}
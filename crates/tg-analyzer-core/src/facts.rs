@@ -0,0 +1,168 @@
+//! quick facts about a corpus counted locally before the llm call: its most used words and
+//! hashtags and the days of the week its messages were posted on
+
+use chrono::{Datelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::analysis::MessageDict;
+use crate::prompts::trends::message_day;
+
+pub const TOP_KEYWORDS: usize = 10;
+pub const TOP_HASHTAGS: usize = 5;
+
+// shorter words are mostly grammar, in every language the bot sees
+const MIN_KEYWORD_CHARS: usize = 4;
+
+// a word used once says little even in a small channel
+const MIN_KEYWORD_COUNT: usize = 2;
+
+// frequent words of at least MIN_KEYWORD_CHARS that say nothing about a channel
+#[rustfmt::skip]
+const STOP_WORDS: &[&str] = &[
+    // english
+    "about", "after", "also", "been", "before", "being", "could", "does", "each", "even",
+    "from", "have", "here", "into", "just", "know", "like", "make", "more", "most", "much",
+    "only", "other", "over", "really", "should", "some", "such", "than", "that", "their",
+    "them", "then", "there", "these", "they", "think", "this", "those", "very", "were", "what",
+    "when", "where", "which", "while", "will", "with", "would", "your",
+    // russian
+    "более", "будет", "была", "были", "было", "быть", "всего", "всех", "даже", "если", "есть",
+    "затем", "здесь", "какие", "какой", "когда", "которая", "которые", "который", "меня",
+    "можно", "надо", "нужно", "очень", "почему", "потом", "потому", "после", "просто", "свой",
+    "себя", "сейчас", "также", "теперь", "тогда", "тоже", "только", "чтобы", "этих", "этого",
+    "этой", "этом", "этот",
+    // ukrainian
+    "більше", "буде", "була", "були", "було", "дуже", "його", "коли", "можна", "навіть",
+    "після", "також", "тільки", "тому", "треба", "цього", "через", "щоби", "якщо", "який",
+];
+
+/// counted from the messages without the llm, so they ground the analysis and can be shown
+/// as they are
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct QuickFacts {
+    // most used words with their counts, most used first
+    pub keywords: Vec<(String, usize)>,
+    // hashtags as written, lowercased, with their counts
+    pub hashtags: Vec<(String, usize)>,
+    // messages posted on each day of the week, monday first
+    pub weekdays: [usize; 7],
+}
+
+impl QuickFacts {
+    pub fn from_messages(messages: &[MessageDict]) -> Self {
+        let mut keywords: HashMap<String, usize> = HashMap::new();
+        let mut hashtags: HashMap<String, usize> = HashMap::new();
+        let mut weekdays = [0; 7];
+
+        for message in messages {
+            if let Some(day) = message_day(message) {
+                weekdays[day.weekday().num_days_from_monday() as usize] += 1;
+            }
+            let Some(text) = message.message.as_deref() else {
+                continue;
+            };
+            for word in text.split_whitespace() {
+                // links and mentions are names, not topics
+                if word.contains("://") || word.starts_with("www.") || word.starts_with('@') {
+                    continue;
+                }
+                if let Some(tag) = word.strip_prefix('#') {
+                    let tag = tag
+                        .trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_')
+                        .to_lowercase();
+                    if !tag.is_empty() {
+                        *hashtags.entry(format!("#{}", tag)).or_default() += 1;
+                    }
+                    continue;
+                }
+                for token in word.split(|c: char| !c.is_alphanumeric()) {
+                    if let Some(keyword) = keyword(token) {
+                        *keywords.entry(keyword).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        Self {
+            keywords: top(
+                keywords
+                    .into_iter()
+                    .filter(|(_, count)| *count >= MIN_KEYWORD_COUNT)
+                    .collect(),
+                TOP_KEYWORDS,
+            ),
+            hashtags: top(hashtags, TOP_HASHTAGS),
+            weekdays,
+        }
+    }
+
+    /// the day of the week with the most messages; None without dated messages
+    pub fn busiest_weekday(&self) -> Option<Weekday> {
+        let (index, count) = self
+            .weekdays
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, count)| (**count, std::cmp::Reverse(*index)))?;
+        (*count > 0).then(|| Weekday::try_from(index as u8).expect("seven weekdays"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty() && self.hashtags.is_empty() && self.busiest_weekday().is_none()
+    }
+
+    /// the facts as a prompt section; empty when nothing was counted
+    pub fn prompt_section(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let counts = |items: &[(String, usize)]| {
+            items
+                .iter()
+                .map(|(item, count)| format!("{} ({})", item, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut section = String::from(
+            "\nQUICK FACTS (counted from the messages; use them to ground the analysis, don't list them back):\n",
+        );
+        if !self.keywords.is_empty() {
+            section.push_str(&format!("- Most used words: {}\n", counts(&self.keywords)));
+        }
+        if !self.hashtags.is_empty() {
+            section.push_str(&format!("- Hashtags: {}\n", counts(&self.hashtags)));
+        }
+        if self.busiest_weekday().is_some() {
+            let days = self
+                .weekdays
+                .iter()
+                .enumerate()
+                .map(|(index, count)| {
+                    let day = Weekday::try_from(index as u8).expect("seven weekdays");
+                    format!("{} {}", day, count)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            section.push_str(&format!("- Messages by day of the week: {}\n", days));
+        }
+        section
+    }
+}
+
+// the lowercased token when it's worth counting
+fn keyword(token: &str) -> Option<String> {
+    if token.chars().count() < MIN_KEYWORD_CHARS || token.chars().all(|c| c.is_numeric()) {
+        return None;
+    }
+    let keyword = token.to_lowercase();
+    (!STOP_WORDS.contains(&keyword.as_str())).then_some(keyword)
+}
+
+// the most frequent entries, ties in alphabetical order so the prompt stays stable
+fn top(counts: HashMap<String, usize>, limit: usize) -> Vec<(String, usize)> {
+    let mut entries: Vec<_> = counts.into_iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(limit);
+    entries
+}
@@ -0,0 +1,101 @@
+//! registry of analyses in progress: a request for something already being worked on waits
+//! for that result instead of doing the same fetch and llm call a second time
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// in-flight work by key, the first request for a key leads and later ones subscribe to
+/// its result until it completes
+pub struct InFlight<T> {
+    pending: Mutex<HashMap<String, watch::Receiver<Option<T>>>>,
+}
+
+/// what a request for a key does
+pub enum Flight<'a, T> {
+    // nobody is working on the key, this request does and completes it
+    Lead(FlightLead<'a, T>),
+    // another request is, wait for its result
+    Follow(FlightFollower<T>),
+}
+
+/// held by the request doing the work; dropping it without completing lets the subscribers
+/// know there won't be a result
+pub struct FlightLead<'a, T> {
+    registry: &'a InFlight<T>,
+    key: String,
+    result: watch::Sender<Option<T>>,
+}
+
+/// a subscription to the result of a leading request
+pub struct FlightFollower<T> {
+    result: watch::Receiver<Option<T>>,
+}
+
+impl<T: Clone> Default for InFlight<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> InFlight<T> {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// leads the work for `key`, or follows the request already leading it
+    pub fn join(&self, key: &str) -> Flight<'_, T> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(result) = pending.get(key) {
+            return Flight::Follow(FlightFollower {
+                result: result.clone(),
+            });
+        }
+        let (result, subscription) = watch::channel(None);
+        pending.insert(key.to_string(), subscription);
+        Flight::Lead(FlightLead {
+            registry: self,
+            key: key.to_string(),
+            result,
+        })
+    }
+
+    /// requests being led right now
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> FlightLead<'_, T> {
+    /// hands `value` to every subscriber; the next request for the key leads again
+    pub fn complete(self, value: T) {
+        self.result.send_replace(Some(value));
+    }
+}
+
+impl<T> Drop for FlightLead<'_, T> {
+    fn drop(&mut self) {
+        self.registry
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.key);
+    }
+}
+
+impl<T: Clone> FlightFollower<T> {
+    /// the leader's result, None when it failed or was cancelled
+    pub async fn wait(mut self) -> Option<T> {
+        self.result
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|result| result.clone())
+    }
+}
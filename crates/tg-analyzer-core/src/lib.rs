@@ -0,0 +1,34 @@
+//! channel analysis pipeline without any bot dependencies: fetches channel messages
+//! (web scraping or telegram api sessions), caches them in postgres and runs the llm analysis
+
+pub mod analysis;
+pub mod backend_config;
+pub mod blocklist;
+pub mod cache;
+pub mod circuit_breaker;
+pub mod error;
+pub mod facts;
+pub mod in_flight;
+pub mod llm;
+pub mod mock;
+pub mod prompts;
+pub mod rate_limiters;
+pub mod report;
+pub mod retry_budget;
+pub mod session_manager;
+pub mod session_pool;
+pub mod stats;
+pub mod tokens;
+pub mod web_scraper;
+pub mod workers;
+
+pub use analysis::{
+    AnalysisData, AnalysisDepth, AnalysisEngine, AnalysisError, ForumTopic, MessageDict,
+};
+pub use cache::{AnalysisResult, CacheManager};
+pub use error::AppError;
+pub use llm::ModelTier;
+pub use prompts::analysis::OutputLanguage;
+pub use report::AnalysisReport;
+pub use retry_budget::RetryBudget;
+pub use workers::AnalysisWorkers;
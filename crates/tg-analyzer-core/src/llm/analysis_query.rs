@@ -0,0 +1,602 @@
+use crate::analysis::{corpus_chars, AnalysisError, MessageDict};
+use crate::cache::AnalysisResult;
+use crate::facts::QuickFacts;
+use crate::llm::routing::ModelRoute;
+use crate::llm::{continue_llm_response, extract_tag, query_llm_with_schema};
+use crate::prompts::analysis::{
+    generate_batch_summary_prompt, generate_summaries_analysis_prompt, AnalysisPrompt,
+    BatchSummary, OutputLanguage,
+};
+use crate::prompts::trends::message_day;
+use crate::prompts::versions::PromptVersion;
+use crate::report::AnalysisReport;
+use crate::retry_budget::RetryBudget;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+
+const SECTIONS: [&str; 3] = ["professional", "personal", "roast"];
+
+// continuation requests per answer before giving up on stitching it together
+const MAX_CONTINUATIONS: u32 = 2;
+
+/// corpora with more message text than this, in characters, are summarized in batches and
+/// analyzed over the summaries, so they don't fill the model's context
+pub const MAP_REDUCE_CHARS: usize = 300_000;
+
+/// message text of one summarized batch, in characters
+pub const BATCH_CHARS: usize = 60_000;
+
+// batch summaries requested at once; the gemini quotas still hold them back
+const PARALLEL_SUMMARIES: usize = 4;
+
+/// batches of a large corpus summarized so far and their total, for progress messages
+pub type SummaryProgress = watch::Sender<(usize, usize)>;
+
+/// whether the messages take the map-reduce path
+pub fn needs_map_reduce(messages: &[MessageDict]) -> bool {
+    corpus_chars(messages) > MAP_REDUCE_CHARS
+}
+
+/// consecutive batches of at most `max_chars` of message text; a message longer than that
+/// makes a batch of its own rather than being cut
+pub fn batch_messages(messages: &[MessageDict], max_chars: usize) -> Vec<&[MessageDict]> {
+    let mut batches = Vec::new();
+    let (mut start, mut chars) = (0, 0);
+    for (i, message) in messages.iter().enumerate() {
+        let length = message
+            .message
+            .as_deref()
+            .map_or(0, |text| text.chars().count());
+        if i > start && chars + length > max_chars {
+            batches.push(&messages[start..i]);
+            (start, chars) = (i, 0);
+        }
+        chars += length;
+    }
+    if start < messages.len() {
+        batches.push(&messages[start..]);
+    }
+    batches
+}
+
+// the days the batch spans, like "2024-01-05 – 2024-02-11"
+fn batch_period(messages: &[MessageDict]) -> Option<String> {
+    let first = messages.iter().filter_map(message_day).min()?;
+    let last = messages.iter().filter_map(message_day).max()?;
+    Some(if first == last {
+        first.to_string()
+    } else {
+        format!("{} – {}", first, last)
+    })
+}
+
+// the summary of one batch from the first of the route's models that gives one
+async fn summarize_batch(
+    messages: &[MessageDict],
+    batch: usize,
+    total: usize,
+    topic: Option<&str>,
+    route: &ModelRoute,
+    budget: &RetryBudget,
+) -> Result<BatchSummary, Box<dyn std::error::Error + Send + Sync>> {
+    let prompt = generate_batch_summary_prompt(messages, batch, total, topic)?;
+    let mut last_error = None;
+    for model in &route.models {
+        if budget.is_exhausted() {
+            return Err(AnalysisError::BudgetExhausted.into());
+        }
+        match query_llm_with_schema(&prompt, model, None, route.timeout, budget).await {
+            // a summary cut short still carries most of the batch
+            Ok(response) if !response.content.trim().is_empty() => {
+                return Ok(BatchSummary {
+                    batch,
+                    period: batch_period(messages),
+                    summary: response.content.trim().to_string(),
+                });
+            }
+            Ok(_) => warn!("{} returned an empty summary of batch {}", model, batch),
+            Err(e) => {
+                warn!("{} failed to summarize batch {}: {}", model, batch, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| AnalysisError::AiRefusal.into()))
+}
+
+/// the map step: summaries of consecutive batches of the messages, oldest batch first.
+/// batches are summarized a few at a time and the first failure ends the run
+pub async fn summarize_batches(
+    messages: &[MessageDict],
+    topic: Option<&str>,
+    route: &ModelRoute,
+    budget: &RetryBudget,
+    progress: Option<&SummaryProgress>,
+) -> Result<Vec<BatchSummary>, Box<dyn std::error::Error + Send + Sync>> {
+    let batches = batch_messages(messages, BATCH_CHARS);
+    let total = batches.len();
+    info!(
+        "Summarizing {} messages in {} batches before the analysis",
+        messages.len(),
+        total
+    );
+    let done = AtomicUsize::new(0);
+    if let Some(progress) = progress {
+        progress.send_replace((0, total));
+    }
+
+    // collected first: a stream mapping through a closure that borrows trips up the Send
+    // check of the spawned analysis
+    let summaries: Vec<_> = batches
+        .into_iter()
+        .enumerate()
+        .map(|(i, batch)| {
+            let done = &done;
+            async move {
+                let summary = summarize_batch(batch, i + 1, total, topic, route, budget).await?;
+                let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                info!("Summarized batch {} ({}/{} done)", i + 1, done, total);
+                if let Some(progress) = progress {
+                    progress.send_replace((done, total));
+                }
+                Ok(summary)
+            }
+        })
+        .collect();
+    stream::iter(summaries)
+        .buffered(PARALLEL_SUMMARIES)
+        .try_collect()
+        .await
+}
+
+/// the analysis of a corpus too large for one prompt: its batches are summarized, then
+/// the usual analysis runs over the summaries and the facts of the whole corpus
+#[allow(clippy::too_many_arguments)]
+pub async fn query_and_parse_large_analysis(
+    messages: &[MessageDict],
+    focus: Option<&str>,
+    topic: Option<&str>,
+    language: OutputLanguage,
+    version: &PromptVersion,
+    route: &ModelRoute,
+    budget: &RetryBudget,
+    progress: Option<&SummaryProgress>,
+) -> Result<AnalysisResult, Box<dyn std::error::Error + Send + Sync>> {
+    let summaries = summarize_batches(messages, topic, route, budget, progress).await?;
+    let prompt = generate_summaries_analysis_prompt(
+        &summaries,
+        &QuickFacts::from_messages(messages),
+        focus,
+        topic,
+        language,
+        version,
+    )?;
+    query_and_parse_analysis(&prompt, route, budget).await
+}
+
+/// the section a cut off answer stopped in: opened but never closed
+pub fn unclosed_section(text: &str) -> Option<&'static str> {
+    SECTIONS.into_iter().find(|section| {
+        text.rfind(&format!("<{}>", section))
+            .is_some_and(|open| !text[open..].contains(&format!("</{}>", section)))
+    })
+}
+
+/// appends a continuation to the answer it continues; models sometimes restart
+/// the cut off section instead, then the restarted section replaces the cut off one
+pub fn stitch_continuation(partial: &str, continuation: &str) -> String {
+    if let Some(section) = unclosed_section(partial) {
+        let open = format!("<{}>", section);
+        let restarted = continuation.trim_start();
+        if restarted.starts_with(&open) {
+            if let Some(cut) = partial.rfind(&open) {
+                return format!("{}{}", &partial[..cut], restarted);
+            }
+        }
+    }
+    format!("{}{}", partial, continuation)
+}
+
+/// what could be salvaged from an incomplete answer: the closed sections and the text
+/// of the cut off one, marked partial; None when no section has any text
+pub fn parse_partial_analysis(text: &str, model: &str) -> Option<AnalysisResult> {
+    let cut_off = unclosed_section(text);
+    let section = |name: &str| {
+        extract_tag(text, name)
+            .or_else(|| {
+                (cut_off == Some(name))
+                    .then(|| {
+                        let open = format!("<{}>", name);
+                        text.rfind(&open)
+                            .map(|start| text[start + open.len()..].trim().to_string())
+                    })
+                    .flatten()
+            })
+            .filter(|content| !content.is_empty())
+    };
+    let result = AnalysisResult {
+        professional: section("professional"),
+        personal: section("personal"),
+        roast: section("roast"),
+        trends: None,
+        messages_count: 0,
+        model: Some(model.to_string()),
+        report: None,
+        removed_messages: 0,
+        partial: true,
+        prompt_version: None,
+        facts: None,
+    };
+    (result.professional.is_some() || result.personal.is_some() || result.roast.is_some())
+        .then_some(result)
+}
+
+// continues a cut off answer until it is whole or the continuations run out
+async fn complete_truncated(
+    prompt: &str,
+    model: &str,
+    mut content: String,
+    mut truncated: bool,
+    call_timeout: Duration,
+    budget: &RetryBudget,
+) -> String {
+    for continuation in 0..MAX_CONTINUATIONS {
+        if !truncated && unclosed_section(&content).is_none() {
+            break;
+        }
+        info!(
+            "Answer from {} was cut off, requesting continuation {}/{}",
+            model,
+            continuation + 1,
+            MAX_CONTINUATIONS
+        );
+        match continue_llm_response(prompt, &content, model, call_timeout, budget).await {
+            Ok(next) => {
+                content = stitch_continuation(&content, &next.content);
+                truncated = next.truncated;
+            }
+            Err(e) => {
+                warn!("Failed to continue answer from {}: {}", model, e);
+                break;
+            }
+        }
+    }
+    content
+}
+
+// what a run through the route's models ends with when none answered: a labeled partial
+// beats no answer at all, otherwise the failure says whether the budget, a refusal or the
+// service itself is to blame
+fn route_outcome(
+    route: &ModelRoute,
+    best_partial: Option<AnalysisResult>,
+    last_error: Option<Box<dyn std::error::Error + Send + Sync>>,
+    budget: &RetryBudget,
+) -> Result<AnalysisResult, Box<dyn std::error::Error + Send + Sync>> {
+    error!(
+        "All models of the route failed: {}",
+        route.models.join(", ")
+    );
+    if let Some(partial) = best_partial {
+        warn!(
+            "Delivering a partial analysis from {}",
+            partial.model.as_deref().unwrap_or("unknown model")
+        );
+        return Ok(partial);
+    }
+    if budget.is_exhausted() {
+        return Err(AnalysisError::BudgetExhausted.into());
+    }
+    // anything other than a refusal means the service itself is failing
+    match last_error.map(|e| AnalysisError::classify(e.as_ref())) {
+        Some(AnalysisError::AiRefusal) => Err(AnalysisError::AiRefusal.into()),
+        Some(AnalysisError::BudgetExhausted) => Err(AnalysisError::BudgetExhausted.into()),
+        _ => Err(AnalysisError::AiUnavailable.into()),
+    }
+}
+
+fn section_count(result: &AnalysisResult) -> usize {
+    [&result.professional, &result.personal, &result.roast]
+        .into_iter()
+        .filter(|section| section.is_some())
+        .count()
+}
+
+/// queries the route's models until one returns a complete analysis; every model and
+/// retry draws from the analysis' `budget`. truncated answers are continued and
+/// stitched; when no model gets a complete one, the most complete partial is returned
+pub async fn query_and_parse_analysis(
+    prompt: &AnalysisPrompt,
+    route: &ModelRoute,
+    budget: &RetryBudget,
+) -> Result<AnalysisResult, Box<dyn std::error::Error + Send + Sync>> {
+    // helper function to check if analysis result is complete
+    fn is_analysis_complete(
+        professional: &Option<String>,
+        personal: &Option<String>,
+        roast: &Option<String>,
+    ) -> bool {
+        professional.is_some() && personal.is_some() && roast.is_some()
+    }
+
+    // helper function to try a model with content retries; incomplete answers
+    // are kept in `best_partial` when they salvage more sections than it has
+    async fn try_model_with_content_retries(
+        prompt: &str,
+        model: &str,
+        api_retries: u32,
+        content_retries: u32,
+        call_timeout: Duration,
+        budget: &RetryBudget,
+        best_partial: &mut Option<AnalysisResult>,
+    ) -> Result<AnalysisResult, Box<dyn std::error::Error + Send + Sync>> {
+        // retry API calls
+        for api_attempt in 0..api_retries {
+            match query_llm_with_schema(prompt, model, None, call_timeout, budget).await {
+                Ok(response) => {
+                    let content = complete_truncated(
+                        prompt,
+                        model,
+                        response.content,
+                        response.truncated,
+                        call_timeout,
+                        budget,
+                    )
+                    .await;
+                    // retry content parsing
+                    for content_attempt in 0..content_retries {
+                        let professional = extract_tag(&content, "professional");
+                        let personal = extract_tag(&content, "personal");
+                        let roast = extract_tag(&content, "roast");
+
+                        // log missing sections
+                        let mut missing_sections = Vec::new();
+                        if professional.is_none() {
+                            missing_sections.push("professional");
+                        }
+                        if personal.is_none() {
+                            missing_sections.push("personal");
+                        }
+                        if roast.is_none() {
+                            missing_sections.push("roast");
+                        }
+
+                        if !missing_sections.is_empty() {
+                            warn!(
+                                "Missing analysis sections [{}] from {} (api_attempt: {}, content_attempt: {})",
+                                missing_sections.join(", "),
+                                model,
+                                api_attempt + 1,
+                                content_attempt + 1
+                            );
+                        }
+
+                        // if all sections are present, return immediately
+                        if is_analysis_complete(&professional, &personal, &roast) {
+                            info!("Complete analysis received from {} (api_attempt: {}, content_attempt: {})",
+                                  model, api_attempt + 1, content_attempt + 1);
+                            return Ok(AnalysisResult {
+                                professional,
+                                personal,
+                                roast,
+                                trends: None,
+                                messages_count: 0,
+                                model: Some(model.to_string()),
+                                report: None,
+                                removed_messages: 0,
+                                partial: false,
+                                prompt_version: None,
+                                facts: None,
+                            });
+                        }
+
+                        // if incomplete and not the last content attempt, retry with same response
+                        if content_attempt < content_retries - 1 {
+                            warn!(
+                                "Retrying content parsing for {} (content_attempt: {})",
+                                model,
+                                content_attempt + 1
+                            );
+                            // in this case, we're re-parsing the same response, so we just continue the loop
+                            // but in practice, extract_tag is deterministic, so this won't help
+                            // this structure is here for future improvements like fuzzy parsing
+                        } else {
+                            // last content attempt failed, need new API call if available
+                            warn!("Content parsing failed for {} after {} attempts, need new API call",
+                                  model, content_retries);
+                            if let Some(partial) = parse_partial_analysis(&content, model) {
+                                if best_partial.as_ref().is_none_or(|best| {
+                                    section_count(&partial) > section_count(best)
+                                }) {
+                                    *best_partial = Some(partial);
+                                }
+                            }
+                            // if this was the last api attempt, we failed completely for this model
+                            if api_attempt == api_retries - 1 {
+                                error!(
+                                    "Failed to get complete analysis from {} after {} API attempts and {} content attempts per API call",
+                                    model, api_retries, content_retries
+                                );
+                                return Err(AnalysisError::AiRefusal.into());
+                            }
+                            break; // break content loop to try new API call
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("{} API attempt {} failed: {}", model, api_attempt + 1, e);
+                    if api_attempt == api_retries - 1 {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        // if we get here, all API attempts failed but didn't return Err - this shouldn't happen
+        Err(format!(
+            "Unexpected failure in {} after {} API attempts",
+            model, api_retries
+        )
+        .into())
+    }
+
+    // one json mode attempt, None when the model fails or ignores the schema
+    async fn try_model_json(
+        prompt: &str,
+        model: &str,
+        call_timeout: Duration,
+        budget: &RetryBudget,
+    ) -> Option<AnalysisResult> {
+        let schema = AnalysisReport::schema();
+        match query_llm_with_schema(prompt, model, Some(&schema), call_timeout, budget).await {
+            Ok(response) => match AnalysisReport::parse(&response.content) {
+                Some(report) => {
+                    info!("Structured analysis received from {}", model);
+                    Some(AnalysisResult::from_report(report, model))
+                }
+                None => {
+                    warn!(
+                        "{} returned a response not matching the report schema",
+                        model
+                    );
+                    None
+                }
+            },
+            Err(e) => {
+                warn!("{} JSON mode attempt failed: {}", model, e);
+                None
+            }
+        }
+    }
+
+    // try each model of the route in order, falling back on failure;
+    // each model gets a json mode attempt first and the tagged format as fallback
+    let mut last_error = None;
+    let mut best_partial = None;
+    for (i, model) in route.models.iter().enumerate() {
+        if budget.is_exhausted() {
+            warn!("Retry budget exhausted, not trying {}", model);
+            break;
+        }
+        if i > 0 {
+            info!("Falling back to {}", model);
+        }
+        if let Some(result) = try_model_json(&prompt.json, model, route.timeout, budget).await {
+            return Ok(result);
+        }
+        match try_model_with_content_retries(
+            &prompt.tagged,
+            model,
+            route.api_attempts,
+            2,
+            route.timeout,
+            budget,
+            &mut best_partial,
+        )
+        .await
+        {
+            Ok(result) => return Ok(result),
+            Err(e) => {
+                warn!("{} failed with error: {}", model, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    route_outcome(route, best_partial, last_error, budget)
+}
+
+/// a result holding only `section`, the answer to a single-type prompt
+pub fn section_result(section: &str, text: String, model: &str, partial: bool) -> AnalysisResult {
+    let text = Some(text);
+    let (professional, personal, roast) = match section {
+        "professional" => (text, None, None),
+        "personal" => (None, text, None),
+        _ => (None, None, text),
+    };
+    AnalysisResult {
+        professional,
+        personal,
+        roast,
+        trends: None,
+        messages_count: 0,
+        model: Some(model.to_string()),
+        report: None,
+        removed_messages: 0,
+        partial,
+        prompt_version: None,
+        facts: None,
+    }
+}
+
+/// queries the route's models with a single-type prompt until one returns its `section`;
+/// truncated answers are continued like those of a full analysis, and when no model
+/// completes the section the text of a cut off one is returned as partial
+pub async fn query_and_parse_section(
+    prompt: &str,
+    section: &str,
+    route: &ModelRoute,
+    budget: &RetryBudget,
+) -> Result<AnalysisResult, Box<dyn std::error::Error + Send + Sync>> {
+    let mut last_error = None;
+    let mut best_partial = None;
+    'models: for (i, model) in route.models.iter().enumerate() {
+        if i > 0 {
+            info!("Falling back to {}", model);
+        }
+        for attempt in 0..route.api_attempts {
+            if budget.is_exhausted() {
+                warn!("Retry budget exhausted, not trying {}", model);
+                break 'models;
+            }
+            let response =
+                match query_llm_with_schema(prompt, model, None, route.timeout, budget).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        error!("{} API attempt {} failed: {}", model, attempt + 1, e);
+                        last_error = Some(e);
+                        continue;
+                    }
+                };
+            let content = complete_truncated(
+                prompt,
+                model,
+                response.content,
+                response.truncated,
+                route.timeout,
+                budget,
+            )
+            .await;
+            if let Some(text) = extract_tag(&content, section) {
+                info!(
+                    "{} section received from {} (attempt {})",
+                    section,
+                    model,
+                    attempt + 1
+                );
+                return Ok(section_result(section, text, model, false));
+            }
+            warn!(
+                "Missing {} section from {} (attempt {})",
+                section,
+                model,
+                attempt + 1
+            );
+            if best_partial.is_none() {
+                best_partial = parse_partial_analysis(&content, model)
+                    .and_then(|partial| {
+                        let (_, text) = partial
+                            .sections()
+                            .into_iter()
+                            .find(|(name, _)| *name == section)?;
+                        text.clone()
+                    })
+                    .map(|text| section_result(section, text, model, true));
+            }
+            last_error = Some(AnalysisError::AiRefusal.into());
+        }
+    }
+    route_outcome(route, best_partial, last_error, budget)
+}
@@ -0,0 +1,212 @@
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::analysis::MessageDict;
+use crate::cache::AnalysisResult;
+use crate::llm::{extract_tag, query_llm};
+use crate::prompts::analysis::OutputLanguage;
+
+// fewer letters than this and the script of a text isn't trusted
+const MIN_LETTERS: usize = 40;
+// share of letters a script needs to be the dominant one
+const DOMINANT_SHARE: f64 = 0.7;
+// channel posts quoted in the translation prompt when the target is the channel's language
+const SAMPLE_CHARS: usize = 1000;
+
+/// writing system of a text; all supported output languages differ by script,
+/// so comparing scripts is enough to catch a report in the wrong language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Latin,
+    Cyrillic,
+}
+
+/// script most letters of the text are written in, None for short or mixed texts
+pub fn dominant_script(text: &str) -> Option<Script> {
+    let (mut latin, mut cyrillic, mut letters) = (0usize, 0usize, 0usize);
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        if c.is_ascii_alphabetic() {
+            latin += 1;
+        } else if ('\u{0400}'..='\u{04FF}').contains(&c) {
+            cyrillic += 1;
+        }
+    }
+
+    if letters < MIN_LETTERS {
+        return None;
+    }
+    let threshold = (letters as f64 * DOMINANT_SHARE) as usize;
+    if latin >= threshold {
+        Some(Script::Latin)
+    } else if cyrillic >= threshold {
+        Some(Script::Cyrillic)
+    } else {
+        None
+    }
+}
+
+/// language an analysis must be delivered in
+#[derive(Debug, Clone)]
+pub struct LanguageTarget {
+    pub script: Script,
+    // how the translation prompt names the target language
+    description: String,
+}
+
+impl LanguageTarget {
+    /// explicit preferences map to their script; the channel's language is detected
+    /// from its posts, None when the posts have no dominant script
+    pub fn resolve(language: OutputLanguage, messages: &[MessageDict]) -> Option<Self> {
+        match language {
+            OutputLanguage::English => Some(Self {
+                script: Script::Latin,
+                description: "English".to_string(),
+            }),
+            OutputLanguage::Russian => Some(Self {
+                script: Script::Cyrillic,
+                description: "Russian".to_string(),
+            }),
+            OutputLanguage::Channel => {
+                let text = messages
+                    .iter()
+                    .filter_map(|msg| msg.message.as_deref())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let script = dominant_script(&text)?;
+                let sample = text.chars().take(SAMPLE_CHARS).collect::<String>();
+                Some(Self {
+                    script,
+                    description: format!(
+                        "the language of these posts from the analyzed channel:\n\"\"\"\n{}\n\"\"\"",
+                        sample
+                    ),
+                })
+            }
+        }
+    }
+}
+
+/// script of the analysis text, None when there isn't enough of it to tell
+pub fn result_script(result: &AnalysisResult) -> Option<Script> {
+    let text = result
+        .sections()
+        .into_iter()
+        .filter_map(|(_, text)| text.as_deref())
+        .collect::<Vec<_>>()
+        .join("\n");
+    dominant_script(&text)
+}
+
+// checked and mismatched results per model, for the mismatch rate in the logs
+static LANGUAGE_CHECKS: OnceLock<Mutex<HashMap<String, (u64, u64)>>> = OnceLock::new();
+
+fn record_language_check(model: &str, mismatched: bool) {
+    let mut checks = LANGUAGE_CHECKS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (checked, mismatches) = checks.entry(model.to_string()).or_insert((0, 0));
+    *checked += 1;
+    if mismatched {
+        *mismatches += 1;
+    }
+    info!(
+        "Language mismatch rate for {}: {}/{} ({:.1}%)",
+        model,
+        mismatches,
+        checked,
+        *mismatches as f64 * 100.0 / *checked as f64
+    );
+}
+
+fn translation_prompt(result: &AnalysisResult, target: &LanguageTarget) -> String {
+    let sections = result
+        .sections()
+        .into_iter()
+        .filter_map(|(tag, text)| Some(format!("<{tag}>\n{}\n</{tag}>", text.as_deref()?)))
+        .collect::<Vec<_>>();
+    format!(
+        "Translate the following analysis into {}.
+
+REQUIREMENTS:
+1. Keep the meaning, tone and structure of every section
+2. Keep the XML tags exactly as shown, translate only the text inside them
+3. Output nothing but the translated sections
+
+{}",
+        target.description,
+        sections.join("\n\n")
+    )
+}
+
+/// checks that the analysis is written in the target language and translates it with the
+/// same model if not; the original is delivered when the translation fails
+pub async fn enforce_output_language(
+    result: AnalysisResult,
+    target: &LanguageTarget,
+) -> AnalysisResult {
+    let Some(detected) = result_script(&result) else {
+        return result;
+    };
+    let model = result
+        .model
+        .clone()
+        .unwrap_or_else(|| "unknown".to_string());
+    let mismatched = detected != target.script;
+    record_language_check(&model, mismatched);
+    if !mismatched {
+        return result;
+    }
+
+    warn!(
+        "Analysis from {} is in {:?} script, expected {:?}; translating",
+        model, detected, target.script
+    );
+    let response = match query_llm(&translation_prompt(&result, target), &model).await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Failed to translate analysis with {}: {}", model, e);
+            return result;
+        }
+    };
+
+    // only the sections the original has were sent for translation
+    let translate = |tag: &str, text: &Option<String>| {
+        text.as_ref()
+            .and_then(|_| extract_tag(&response.content, tag))
+    };
+    let translated = AnalysisResult {
+        professional: translate("professional", &result.professional),
+        personal: translate("personal", &result.personal),
+        roast: translate("roast", &result.roast),
+        trends: translate("trends", &result.trends),
+        messages_count: result.messages_count,
+        model: result.model.clone(),
+        // structured fields aren't translated, so they're dropped with the original text
+        report: None,
+        removed_messages: result.removed_messages,
+        partial: result.partial,
+        prompt_version: result.prompt_version,
+        facts: result.facts.clone(),
+    };
+    let complete = result
+        .sections()
+        .into_iter()
+        .zip(translated.sections())
+        .all(|((_, original), (_, text))| original.is_none() || text.is_some());
+    if complete && result_script(&translated) == Some(target.script) {
+        info!(
+            "Translated analysis from {} into the target language",
+            model
+        );
+        translated
+    } else {
+        warn!(
+            "Translation from {} is incomplete or still in the wrong language, keeping the original",
+            model
+        );
+        result
+    }
+}
@@ -1,6 +1,12 @@
 pub mod analysis_query;
+pub mod language_check;
+pub mod routing;
+pub mod safety;
+pub mod trends_query;
+pub mod usage;
 
 use base64::{engine::general_purpose, Engine as _};
+use gemini_rs::types::{Content, FinishReason, Part, Role, Schema};
 use image::{GenericImageView, ImageFormat};
 use log::{error, info, warn};
 use regex::Regex;
@@ -12,51 +18,71 @@ use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::time::{sleep, timeout};
 
-use crate::analysis::MessageDict;
+use crate::analysis::{AnalysisError, MessageDict};
+use crate::circuit_breaker::llm_breaker;
+use crate::mock;
+use crate::rate_limiters::gemini::get_gemini_rate_limiter;
+use crate::retry_budget::RetryBudget;
+use crate::tokens::estimate_tokens;
 
-// rate limiter for Gemini API calls
-pub struct GeminiRateLimiter {
-    last_call: Arc<Mutex<Option<Instant>>>,
-    min_interval: Duration,
+// constants for API interaction
+pub const MAX_RETRIES: u32 = 3;
+pub const BASE_DELAY_MS: u64 = 1000;
+pub const GEMINI_TIMEOUT_SECS: u64 = 300;
+
+/// per-user model preference, stored as text in users.model_tier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModelTier {
+    #[default]
+    Auto,
+    Fast,
+    Quality,
 }
 
-impl GeminiRateLimiter {
-    pub fn new(min_interval: Duration) -> Self {
-        Self {
-            last_call: Arc::new(Mutex::new(None)),
-            min_interval,
+impl ModelTier {
+    pub const ALL: [ModelTier; 3] = [ModelTier::Auto, ModelTier::Fast, ModelTier::Quality];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelTier::Auto => "auto",
+            ModelTier::Fast => "fast",
+            ModelTier::Quality => "quality",
         }
     }
 
-    pub async fn wait_for_api_call(&self) {
-        let mut last = self.last_call.lock().await;
-        if let Some(last_instant) = *last {
-            let elapsed = last_instant.elapsed();
-            if elapsed < self.min_interval {
-                let wait_time = self.min_interval - elapsed;
-                info!("Gemini rate limiter: waiting for {:?}", wait_time);
-                sleep(wait_time).await;
-            }
+    /// parses a stored tier, falling back to auto for unknown values
+    pub fn from_code(code: Option<&str>) -> Self {
+        match code {
+            Some("fast") => ModelTier::Fast,
+            Some("quality") => ModelTier::Quality,
+            _ => ModelTier::Auto,
         }
-        *last = Some(Instant::now());
     }
-}
 
-// global rate limiter for Gemini API (1 request per second)
-static GEMINI_RATE_LIMITER: OnceLock<GeminiRateLimiter> = OnceLock::new();
+    /// models to try in order, first one that returns a complete analysis wins
+    pub fn models(&self) -> &'static [&'static str] {
+        match self {
+            ModelTier::Auto => &["gemini-3-flash-preview", "gemini-2.5-flash"],
+            ModelTier::Fast => &["gemini-2.5-flash", "gemini-3-flash-preview"],
+            ModelTier::Quality => &["gemini-2.5-pro", "gemini-3-flash-preview"],
+        }
+    }
 
-pub fn get_gemini_rate_limiter() -> &'static GeminiRateLimiter {
-    GEMINI_RATE_LIMITER.get_or_init(|| GeminiRateLimiter::new(Duration::from_secs(1)))
+    /// tier a concrete model belongs to, used to tell users what actually ran
+    pub fn of_model(model: &str) -> Self {
+        if model.contains("pro") {
+            ModelTier::Quality
+        } else {
+            ModelTier::Fast
+        }
+    }
 }
 
-// constants for API interaction
-pub const MAX_RETRIES: u32 = 3;
-pub const BASE_DELAY_MS: u64 = 1000;
-pub const GEMINI_TIMEOUT_SECS: u64 = 300;
-
 #[derive(Debug)]
 pub struct LLMResponse {
     pub content: String,
+    // the model hit its output token limit and stopped mid-answer
+    pub truncated: bool,
 }
 
 pub fn extract_tag(text: &str, tag: &str) -> Option<String> {
@@ -71,30 +97,140 @@ pub async fn query_llm(
     prompt: &str,
     model: &str,
 ) -> Result<LLMResponse, Box<dyn std::error::Error + Send + Sync>> {
-    info!("Querying LLM with model: {}", model);
+    query_llm_with_schema(
+        prompt,
+        model,
+        None,
+        Duration::from_secs(GEMINI_TIMEOUT_SECS),
+        &RetryBudget::unbounded(),
+    )
+    .await
+}
 
-    // apply rate limiting before each attempt
-    get_gemini_rate_limiter().wait_for_api_call().await;
+/// like query_llm; with a schema the model answers in json mode and the content
+/// is a json document matching it. each call may take up to `call_timeout`, retries
+/// and the total time are drawn from `budget`
+pub async fn query_llm_with_schema(
+    prompt: &str,
+    model: &str,
+    schema: Option<&Schema>,
+    call_timeout: Duration,
+    budget: &RetryBudget,
+) -> Result<LLMResponse, Box<dyn std::error::Error + Send + Sync>> {
+    info!(
+        "Querying LLM with model: {}{}",
+        model,
+        if schema.is_some() { " (json mode)" } else { "" }
+    );
+    send_with_retries(model, schema, &[], prompt, call_timeout, budget).await
+}
+
+// asks the model to pick up a cut off answer where it stopped
+const CONTINUATION_PROMPT: &str = "Your answer was cut off. Continue it exactly where it stopped, \
+without repeating anything already written and keeping the same format and tags.";
+
+/// asks the model for the rest of `partial`, its answer to `prompt` that was cut off;
+/// the returned content only holds the continuation
+pub async fn continue_llm_response(
+    prompt: &str,
+    partial: &str,
+    model: &str,
+    call_timeout: Duration,
+    budget: &RetryBudget,
+) -> Result<LLMResponse, Box<dyn std::error::Error + Send + Sync>> {
+    info!("Asking {} to continue a truncated answer", model);
+    let history = [
+        Content {
+            role: Role::User,
+            parts: vec![Part::text(prompt)],
+        },
+        Content {
+            role: Role::Model,
+            parts: vec![Part::text(partial)],
+        },
+    ];
+    send_with_retries(
+        model,
+        None,
+        &history,
+        CONTINUATION_PROMPT,
+        call_timeout,
+        budget,
+    )
+    .await
+}
+
+// sends the message and tells the llm circuit breaker how it went; a call that never
+// started for lack of budget says nothing about the service
+async fn send_with_retries(
+    model: &str,
+    schema: Option<&Schema>,
+    history: &[Content],
+    message: &str,
+    call_timeout: Duration,
+    budget: &RetryBudget,
+) -> Result<LLMResponse, Box<dyn std::error::Error + Send + Sync>> {
+    if mock::enabled() {
+        return Ok(mock::llm_response(message, schema));
+    }
+    let result = send_attempts(model, schema, history, message, call_timeout, budget).await;
+    match &result {
+        Ok(_) => llm_breaker().record_success(),
+        Err(e) => match AnalysisError::classify(e.as_ref()) {
+            AnalysisError::BudgetExhausted => {}
+            _ => llm_breaker().record_failure(Instant::now()),
+        },
+    }
+    result
+}
+
+async fn send_attempts(
+    model: &str,
+    schema: Option<&Schema>,
+    history: &[Content],
+    message: &str,
+    call_timeout: Duration,
+    budget: &RetryBudget,
+) -> Result<LLMResponse, Box<dyn std::error::Error + Send + Sync>> {
+    // the whole conversation counts against the model's token quota
+    let prompt_tokens = history
+        .iter()
+        .flat_map(|content| &content.parts)
+        .filter_map(|part| part.text.as_deref())
+        .chain([message])
+        .map(estimate_tokens)
+        .sum();
 
     for attempt in 0..=MAX_RETRIES {
-        let response = match timeout(
-            Duration::from_secs(GEMINI_TIMEOUT_SECS),
-            gemini_rs::chat(model).send_message(prompt),
-        )
-        .await
-        {
+        if budget.is_exhausted() {
+            warn!("Retry budget exhausted before querying {}", model);
+            return Err(AnalysisError::BudgetExhausted.into());
+        }
+        let mut chat = gemini_rs::chat(model);
+        chat.history_mut().extend_from_slice(history);
+        if let Some(schema) = schema {
+            let config = chat.config_mut();
+            config.response_mime_type = Some("application/json".to_string());
+            config.response_schema = Some(schema.clone());
+        }
+        // apply rate limiting before each attempt, holding the permit until the answer
+        let sent = {
+            let _permit = get_gemini_rate_limiter()
+                .acquire(model, prompt_tokens)
+                .await;
+            timeout(budget.cap_timeout(call_timeout), chat.send_message(message)).await
+        };
+        let response = match sent {
             Ok(Ok(resp)) => resp,
             Ok(Err(e)) => {
-                if attempt == MAX_RETRIES {
+                let Some(delay) = budget.retry_delay(attempt) else {
                     error!(
                         "Failed to get response from Gemini API after {} attempts: {:?}",
-                        MAX_RETRIES + 1,
+                        attempt + 1,
                         e
                     );
                     return Err(e.into());
-                }
-
-                let delay = calculate_delay(attempt);
+                };
                 warn!(
                     "Gemini API call failed (attempt {}/{}): {:?}. Retrying in {}ms",
                     attempt + 1,
@@ -106,21 +242,23 @@ pub async fn query_llm(
                 continue;
             }
             Err(_timeout) => {
-                if attempt == MAX_RETRIES {
+                if budget.is_exhausted() {
+                    error!("Gemini API call ran out of the retry budget");
+                    return Err(AnalysisError::BudgetExhausted.into());
+                }
+                let Some(delay) = budget.retry_delay(attempt) else {
                     error!(
                         "Gemini API call timed out after {} attempts ({}s timeout)",
-                        MAX_RETRIES + 1,
-                        GEMINI_TIMEOUT_SECS
+                        attempt + 1,
+                        call_timeout.as_secs()
                     );
                     return Err("Gemini API call timed out".into());
-                }
-
-                let delay = calculate_delay(attempt);
+                };
                 warn!(
                     "Gemini API call timed out (attempt {}/{}): {}s timeout. Retrying in {}ms",
                     attempt + 1,
                     MAX_RETRIES + 1,
-                    GEMINI_TIMEOUT_SECS,
+                    call_timeout.as_secs(),
                     delay.as_millis()
                 );
                 sleep(delay).await;
@@ -128,18 +266,28 @@ pub async fn query_llm(
             }
         };
 
+        // every answer is paid for, empty ones included
+        if let Some(tokens) = &response.usage_metadata {
+            usage::record_call(
+                model,
+                tokens.prompt_token_count,
+                tokens.candidates_token_count,
+            );
+        }
+
         let content = response.to_string();
+        let truncated = response.candidates.first().is_some_and(|candidate| {
+            matches!(candidate.finish_reason, Some(FinishReason::MaxTokens))
+        });
 
         if content.is_empty() {
-            if attempt == MAX_RETRIES {
+            let Some(delay) = budget.retry_delay(attempt) else {
                 error!(
                     "Received empty response from Gemini API after {} attempts",
-                    MAX_RETRIES + 1
+                    attempt + 1
                 );
                 return Err("Empty response from Gemini API".into());
-            }
-
-            let delay = calculate_delay(attempt);
+            };
             warn!(
                 "Received empty response from Gemini API (attempt {}/{}). Retrying in {}ms",
                 attempt + 1,
@@ -151,11 +299,12 @@ pub async fn query_llm(
         }
 
         info!(
-            "Received LLM response of length: {} (attempt {})",
+            "Received LLM response of length: {} (attempt {}{})",
             content.len(),
-            attempt + 1
+            attempt + 1,
+            if truncated { ", truncated" } else { "" }
         );
-        return Ok(LLMResponse { content });
+        return Ok(LLMResponse { content, truncated });
     }
 
     unreachable!()
@@ -204,6 +353,20 @@ impl ImageDescriptionRateLimiter {
 
 // global rate limiter for image description API (2 requests per second)
 #[allow(dead_code)]
+static GEMINI_API_KEY: OnceLock<Option<String>> = OnceLock::new();
+
+/// GEMINI_API_KEY, read once, for the gemini calls made without the gemini client (image
+/// descriptions, speech); a new key needs a restart
+pub fn gemini_api_key() -> Option<&'static str> {
+    GEMINI_API_KEY
+        .get_or_init(|| {
+            std::env::var("GEMINI_API_KEY")
+                .ok()
+                .filter(|key| !key.is_empty())
+        })
+        .as_deref()
+}
+
 static IMAGE_RATE_LIMITER: OnceLock<ImageDescriptionRateLimiter> = OnceLock::new();
 
 #[allow(dead_code)]
@@ -341,9 +504,8 @@ async fn describe_single_image(
         }
     });
 
-    // get API key from environment
-    let api_key = std::env::var("GEMINI_API_KEY")
-        .map_err(|_| ImageProcessingError::ApiCall("GEMINI_API_KEY not set".to_string()))?;
+    let api_key = gemini_api_key()
+        .ok_or_else(|| ImageProcessingError::ApiCall("GEMINI_API_KEY not set".to_string()))?;
 
     // make API call to Gemini
     let url = format!(
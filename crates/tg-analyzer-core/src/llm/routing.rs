@@ -0,0 +1,192 @@
+use log::warn;
+use std::env;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::llm::{ModelTier, GEMINI_TIMEOUT_SECS};
+use crate::tokens::prompt_token_limit;
+
+// api attempts per model unless a route says otherwise
+pub const DEFAULT_API_ATTEMPTS: u32 = 2;
+
+/// the models one analysis is queried with and how long and how often each may be tried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelRoute {
+    // tried in order, the first complete answer wins
+    pub models: Vec<String>,
+    // per llm call
+    pub timeout: Duration,
+    pub api_attempts: u32,
+}
+
+impl ModelRoute {
+    /// the tier's models with the default timeout and attempts
+    pub fn for_tier(tier: ModelTier) -> Self {
+        Self {
+            models: tier
+                .models()
+                .iter()
+                .map(|model| model.to_string())
+                .collect(),
+            timeout: Duration::from_secs(GEMINI_TIMEOUT_SECS),
+            api_attempts: DEFAULT_API_ATTEMPTS,
+        }
+    }
+
+    /// the route of a second opinion on an answer from `first_model`: the other tier's
+    /// models, pro for flash answers and flash for pro ones, without the first model
+    pub fn second_opinion(first_model: &str) -> Self {
+        let tier = match ModelTier::of_model(first_model) {
+            ModelTier::Quality => ModelTier::Fast,
+            _ => ModelTier::Quality,
+        };
+        let mut route = Self::for_tier(tier);
+        route.models.retain(|model| model != first_model);
+        route
+    }
+
+    /// estimated tokens a prompt may take to fit every model of the route
+    pub fn prompt_token_limit(&self) -> usize {
+        self.models
+            .iter()
+            .map(|model| prompt_token_limit(model))
+            .min()
+            .unwrap_or_else(|| prompt_token_limit(""))
+    }
+}
+
+// what MODEL_ROUTING changes for one analysis type, unset parts keep the tier's defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct RouteOverride {
+    model: Option<String>,
+    timeout_secs: Option<u64>,
+    api_attempts: Option<u32>,
+}
+
+/// per analysis type overrides of the model, timeout and attempts; every type follows
+/// the user's tier unless MODEL_ROUTING says otherwise
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelRouting {
+    overrides: Vec<(String, RouteOverride)>,
+}
+
+impl ModelRouting {
+    /// parses "<type>=<model>[:<timeout secs>[:<api attempts>]],..." such as
+    /// "roast=gemini-2.5-flash-lite:60:1,professional=gemini-2.5-pro"; an empty model
+    /// keeps the tier's models, and malformed entries are left out
+    pub fn parse(spec: &str) -> Self {
+        let overrides = spec
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .filter_map(|entry| {
+                let parsed = Self::parse_entry(entry);
+                if parsed.is_none() {
+                    warn!("Ignoring malformed MODEL_ROUTING entry {:?}", entry.trim());
+                }
+                parsed
+            })
+            .collect();
+        Self { overrides }
+    }
+
+    fn parse_entry(entry: &str) -> Option<(String, RouteOverride)> {
+        let (analysis_type, settings) = entry.trim().split_once('=')?;
+        let analysis_type = analysis_type.trim();
+        if analysis_type.is_empty() {
+            return None;
+        }
+        let mut parts = settings.split(':').map(str::trim);
+        let model = parts
+            .next()
+            .filter(|model| !model.is_empty())
+            .map(str::to_string);
+        let timeout_secs = positive::<u64>(parts.next())?;
+        let api_attempts = positive::<u32>(parts.next())?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some((
+            analysis_type.to_string(),
+            RouteOverride {
+                model,
+                timeout_secs,
+                api_attempts,
+            },
+        ))
+    }
+
+    pub fn from_env() -> Self {
+        env::var("MODEL_ROUTING")
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    fn override_for(&self, analysis_type: &str) -> Option<&RouteOverride> {
+        self.overrides
+            .iter()
+            .find(|(routed_type, _)| routed_type == analysis_type)
+            .map(|(_, route)| route)
+    }
+
+    /// the model routed for the type, None when it follows the tier
+    pub fn model_for(&self, analysis_type: &str) -> Option<&str> {
+        self.override_for(analysis_type)?.model.as_deref()
+    }
+
+    /// the route of an analysis type for a user of `tier`; a routed model is tried first
+    /// and the tier's models stay as fallbacks
+    pub fn route(&self, analysis_type: &str, tier: ModelTier) -> ModelRoute {
+        let mut route = ModelRoute::for_tier(tier);
+        let Some(routed) = self.override_for(analysis_type) else {
+            return route;
+        };
+        if let Some(model) = &routed.model {
+            route.models.retain(|fallback| fallback != model);
+            route.models.insert(0, model.clone());
+        }
+        if let Some(timeout_secs) = routed.timeout_secs {
+            route.timeout = Duration::from_secs(timeout_secs);
+        }
+        if let Some(api_attempts) = routed.api_attempts {
+            route.api_attempts = api_attempts;
+        }
+        route
+    }
+}
+
+// the routing analyses are queried with, read from MODEL_ROUTING until it is set
+static MODEL_ROUTING: RwLock<Option<Arc<ModelRouting>>> = RwLock::new(None);
+
+/// the model routing in effect
+pub fn model_routing() -> Arc<ModelRouting> {
+    if let Some(routing) = MODEL_ROUTING
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        return routing.clone();
+    }
+    MODEL_ROUTING
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| Arc::new(ModelRouting::from_env()))
+        .clone()
+}
+
+/// replaces the model routing of the analyses started from now on
+pub fn set_model_routing(routing: ModelRouting) {
+    *MODEL_ROUTING.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(routing));
+}
+
+// an unset setting is Some(None); zero would never query anything, so it is malformed
+fn positive<T: FromStr + Default + PartialOrd>(part: Option<&str>) -> Option<Option<T>> {
+    match part.filter(|part| !part.is_empty()) {
+        Some(part) => part
+            .parse::<T>()
+            .ok()
+            .filter(|value| *value > T::default())
+            .map(Some),
+        None => Some(None),
+    }
+}
@@ -0,0 +1,354 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use regex::Regex;
+use std::sync::{Arc, OnceLock};
+
+use crate::cache::AnalysisResult;
+use crate::error::AppError;
+use crate::llm::routing::{model_routing, ModelRoute, ModelRouting};
+use crate::llm::{query_llm_with_schema, ModelTier};
+use crate::retry_budget::RetryBudget;
+
+/// the MODEL_ROUTING entry of the review, e.g. "safety_review=gemini-2.5-flash-lite:30"
+pub const REVIEW_ROUTE: &str = "safety_review";
+// what flagged text is replaced with when it can't be regenerated away
+pub const REDACTED: &str = "[removed]";
+// phone numbers have at least this many digits, fewer are years, counts and prices
+const MIN_PHONE_DIGITS: usize = 10;
+const MAX_PHONE_DIGITS: usize = 15;
+
+/// kind of disallowed content in an analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafetyCategory {
+    // contact details, addresses or other private data about a person
+    Doxxing,
+    Slur,
+    // attacks beyond the roast's teasing: threats, protected traits, calls to harass
+    Harassment,
+}
+
+impl SafetyCategory {
+    pub const ALL: [SafetyCategory; 3] = [
+        SafetyCategory::Doxxing,
+        SafetyCategory::Slur,
+        SafetyCategory::Harassment,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SafetyCategory::Doxxing => "doxxing",
+            SafetyCategory::Slur => "slur",
+            SafetyCategory::Harassment => "harassment",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == code.trim())
+    }
+}
+
+/// a passage of an analysis that must not be delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyFlag {
+    pub section: String,
+    pub category: SafetyCategory,
+    pub excerpt: String,
+}
+
+/// what was done about a flagged answer, as recorded in flagged_outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafetyAction {
+    // the answer was thrown away and asked for again
+    Regenerated,
+    // the flagged passages were cut out of the delivered answer
+    Redacted,
+}
+
+impl SafetyAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SafetyAction::Regenerated => "regenerated",
+            SafetyAction::Redacted => "redacted",
+        }
+    }
+}
+
+/// the texts of an analysis by section, the report's lists and tone as "highlights"
+fn reviewed_texts(result: &AnalysisResult) -> Vec<(&'static str, String)> {
+    let mut texts = result
+        .sections()
+        .into_iter()
+        .filter_map(|(tag, text)| Some((tag, text.clone()?)))
+        .collect::<Vec<_>>();
+    if let Some(report) = &result.report {
+        let highlights = report
+            .strengths
+            .iter()
+            .chain(&report.weaknesses)
+            .chain(&report.topics)
+            .chain([&report.tone])
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        texts.push(("highlights", highlights));
+    }
+    texts
+}
+
+fn email_pattern() -> &'static Regex {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    EMAIL.get_or_init(|| {
+        Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap()
+    })
+}
+
+fn phone_pattern() -> &'static Regex {
+    static PHONE: OnceLock<Regex> = OnceLock::new();
+    PHONE.get_or_init(|| Regex::new(r"\+?\d[\d \t().-]{7,}\d").unwrap())
+}
+
+/// email addresses and phone numbers in a text, which an analysis never needs to repeat
+pub fn personal_data(text: &str) -> Vec<String> {
+    let emails = email_pattern()
+        .find_iter(text)
+        .map(|found| found.as_str().to_string());
+    let phones = phone_pattern()
+        .find_iter(text)
+        .map(|found| found.as_str().trim().to_string())
+        .filter(|phone| {
+            let digits = phone.chars().filter(char::is_ascii_digit).count();
+            (MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits)
+        });
+    emails.chain(phones).collect()
+}
+
+/// the personal data found in the analysis, flagged as doxxing without asking the llm
+pub fn personal_data_flags(result: &AnalysisResult) -> Vec<SafetyFlag> {
+    reviewed_texts(result)
+        .into_iter()
+        .flat_map(|(section, text)| {
+            personal_data(&text)
+                .into_iter()
+                .map(move |excerpt| SafetyFlag {
+                    section: section.to_string(),
+                    category: SafetyCategory::Doxxing,
+                    excerpt,
+                })
+        })
+        .collect()
+}
+
+/// asks the review model to quote every passage that crosses the line; roasting the
+/// author's writing is the point of the roast, so teasing stays allowed
+pub fn review_prompt(result: &AnalysisResult) -> String {
+    let sections = reviewed_texts(result)
+        .into_iter()
+        .map(|(section, text)| format!("<section name=\"{section}\">\n{text}\n</section>"))
+        .collect::<Vec<_>>();
+    format!(
+        "You review an AI-written analysis of a Telegram channel's author before it is sent to a user.
+
+Flag only passages that contain:
+- doxxing: home addresses, phone numbers, emails, names of relatives or other private details about a person
+- slur: slurs or hateful terms about ethnicity, religion, gender, sexuality, disability or other protected traits
+- harassment: threats, wishes of harm, sexual remarks about a person, attacks on protected traits, or calls to harass or find the person
+
+Mockery of the author's writing, opinions, habits and public persona is allowed, even when harsh: the roast section is meant to be a comedic roast.
+
+If nothing crosses the line, answer exactly <verdict>safe</verdict>
+Otherwise answer one line per passage, quoting the passage exactly as written:
+<flag section=\"SECTION NAME\" category=\"doxxing|slur|harassment\">exact passage</flag>
+
+{}",
+        sections.join("\n\n")
+    )
+}
+
+fn flag_pattern() -> &'static Regex {
+    static FLAG: OnceLock<Regex> = OnceLock::new();
+    FLAG.get_or_init(|| {
+        Regex::new(r#"(?s)<flag\s+section="([^"]*)"\s+category="([^"]*)"\s*>(.*?)</flag>"#).unwrap()
+    })
+}
+
+/// the flags of a review answer; None when the answer is neither a verdict nor flags,
+/// so a confused review can't block an analysis
+pub fn parse_review(text: &str) -> Option<Vec<SafetyFlag>> {
+    let flags = flag_pattern()
+        .captures_iter(text)
+        .filter_map(|caps| {
+            let excerpt = caps[3].trim();
+            Some(SafetyFlag {
+                section: caps[1].trim().to_string(),
+                category: SafetyCategory::from_code(&caps[2])?,
+                excerpt: excerpt.to_string(),
+            })
+            .filter(|_| !excerpt.is_empty())
+        })
+        .collect::<Vec<_>>();
+    if !flags.is_empty() {
+        return Some(flags);
+    }
+    text.contains("<verdict>safe</verdict>").then(Vec::new)
+}
+
+/// the route the review is queried with; only its first model is tried, which is the fast
+/// tier's unless routed, cheap and quick as the review only has to read the answer
+pub fn review_route(routing: &ModelRouting) -> ModelRoute {
+    routing.route(REVIEW_ROUTE, ModelTier::Fast)
+}
+
+/// everything in the analysis that must not be delivered: personal data found by pattern
+/// and what the review model flags. a failed review passes, the patterns still apply
+pub async fn review_output(result: &AnalysisResult) -> Vec<SafetyFlag> {
+    let mut flags = personal_data_flags(result);
+    let route = review_route(&model_routing());
+    let model = &route.models[0];
+    let review = query_llm_with_schema(
+        &review_prompt(result),
+        model,
+        None,
+        route.timeout,
+        &RetryBudget::unbounded(),
+    )
+    .await;
+    match review {
+        Ok(response) => match parse_review(&response.content) {
+            Some(reviewed) => {
+                for flag in reviewed {
+                    if !flags.contains(&flag) {
+                        flags.push(flag);
+                    }
+                }
+            }
+            None => warn!("Safety review from {} was unreadable", model),
+        },
+        Err(e) => warn!("Safety review with {} failed: {}", model, e),
+    }
+    if !flags.is_empty() {
+        warn!(
+            "Safety review flagged {} passages of an analysis from {}",
+            flags.len(),
+            result.model.as_deref().unwrap_or("unknown model")
+        );
+    }
+    flags
+}
+
+/// the analysis with every flagged passage replaced by REDACTED, wherever it appears
+pub fn redact(mut result: AnalysisResult, flags: &[SafetyFlag]) -> AnalysisResult {
+    let cut = |text: &mut String| {
+        for flag in flags {
+            if text.contains(&flag.excerpt) {
+                *text = text.replace(&flag.excerpt, REDACTED);
+            }
+        }
+    };
+    for section in [
+        &mut result.professional,
+        &mut result.personal,
+        &mut result.roast,
+        &mut result.trends,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        cut(section);
+    }
+    if let Some(report) = &mut result.report {
+        for text in [
+            &mut report.professional,
+            &mut report.personal,
+            &mut report.roast,
+            &mut report.tone,
+        ] {
+            cut(text);
+        }
+        for item in report
+            .strengths
+            .iter_mut()
+            .chain(&mut report.weaknesses)
+            .chain(&mut report.topics)
+        {
+            cut(item);
+        }
+    }
+    result
+}
+
+/// what a flagged answer was for, stored with its flags for admin review
+#[derive(Debug, Clone)]
+pub struct FlagContext {
+    pub channel_name: String,
+    pub analysis_type: String,
+    pub cache_key: String,
+    pub model: Option<String>,
+}
+
+// set once at startup; without it flags are only logged, e.g. in tools and tests
+static FLAG_POOL: OnceLock<Arc<Pool>> = OnceLock::new();
+
+/// stores every following flagged answer in flagged_outputs
+pub fn enable_flag_recording(pool: Arc<Pool>) {
+    if FLAG_POOL.set(pool).is_err() {
+        warn!("Flagged output recording was already enabled");
+    }
+}
+
+/// records the flags of an answer in the background; a failed insert must never fail
+/// the analysis
+pub fn record_flags(context: &FlagContext, flags: &[SafetyFlag], action: SafetyAction) {
+    for flag in flags {
+        info!(
+            "Flagged {} in the {} section of the {} analysis of {} ({})",
+            flag.category.as_str(),
+            flag.section,
+            context.analysis_type,
+            context.channel_name,
+            action.as_str()
+        );
+    }
+    let Some(pool) = FLAG_POOL.get().cloned() else {
+        return;
+    };
+    let (context, flags) = (context.clone(), flags.to_vec());
+    tokio::spawn(async move {
+        if let Err(e) = insert_flags(&pool, &context, &flags, action).await {
+            warn!(
+                "Failed to record flagged output of {}: {}",
+                context.channel_name, e
+            );
+        }
+    });
+}
+
+/// stores the flags of one answer
+pub async fn insert_flags(
+    pool: &Pool,
+    context: &FlagContext,
+    flags: &[SafetyFlag],
+    action: SafetyAction,
+) -> Result<(), AppError> {
+    let client = pool.get().await?;
+    for flag in flags {
+        client
+            .execute(
+                "INSERT INTO flagged_outputs (channel_name, analysis_type, cache_key, model, section, category, excerpt, action)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &context.channel_name,
+                    &context.analysis_type,
+                    &context.cache_key,
+                    &context.model,
+                    &flag.section,
+                    &flag.category.as_str(),
+                    &flag.excerpt,
+                    &action.as_str(),
+                ],
+            )
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use crate::analysis::AnalysisError;
+use crate::cache::AnalysisResult;
+use crate::llm::routing::ModelRoute;
+use crate::llm::{continue_llm_response, extract_tag, query_llm_with_schema};
+use crate::retry_budget::RetryBudget;
+use log::{error, info, warn};
+
+// continuation requests per answer before giving up on stitching it together
+const MAX_CONTINUATIONS: u32 = 2;
+
+/// the timeline of a trends answer; a cut off one is returned partial with the text it got
+/// to, None when there is no timeline at all
+pub fn parse_trends(text: &str, model: &str) -> Option<AnalysisResult> {
+    let (trends, partial) = match extract_tag(text, "trends") {
+        Some(trends) => (trends, false),
+        None => {
+            let start = text.rfind("<trends>")? + "<trends>".len();
+            (text[start..].trim().to_string(), true)
+        }
+    };
+    (!trends.is_empty()).then(|| AnalysisResult {
+        professional: None,
+        personal: None,
+        roast: None,
+        trends: Some(trends),
+        messages_count: 0,
+        model: Some(model.to_string()),
+        report: None,
+        removed_messages: 0,
+        partial,
+        prompt_version: None,
+        facts: None,
+    })
+}
+
+/// queries the route's models until one returns a complete timeline, continuing cut off
+/// answers like the main analysis; every model and retry draws from the analysis' `budget`
+pub async fn query_trends(
+    prompt: &str,
+    route: &ModelRoute,
+    budget: &RetryBudget,
+) -> Result<AnalysisResult, Box<dyn std::error::Error + Send + Sync>> {
+    let mut last_error = None;
+    let mut best_partial: Option<AnalysisResult> = None;
+    for (i, model) in route.models.iter().enumerate() {
+        if i > 0 {
+            info!("Falling back to {}", model);
+        }
+        for attempt in 0..route.api_attempts {
+            if budget.is_exhausted() {
+                warn!("Retry budget exhausted, not querying {}", model);
+                break;
+            }
+            let response =
+                match query_llm_with_schema(prompt, model, None, route.timeout, budget).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        error!("{} API attempt {} failed: {}", model, attempt + 1, e);
+                        last_error = Some(e);
+                        continue;
+                    }
+                };
+
+            let mut content = response.content;
+            let mut truncated = response.truncated;
+            for continuation in 0..MAX_CONTINUATIONS {
+                if !truncated {
+                    break;
+                }
+                info!(
+                    "Trends answer from {} was cut off, requesting continuation {}/{}",
+                    model,
+                    continuation + 1,
+                    MAX_CONTINUATIONS
+                );
+                match continue_llm_response(prompt, &content, model, route.timeout, budget).await {
+                    Ok(next) => {
+                        content.push_str(&next.content);
+                        truncated = next.truncated;
+                    }
+                    Err(e) => {
+                        warn!("Failed to continue trends answer from {}: {}", model, e);
+                        break;
+                    }
+                }
+            }
+
+            match parse_trends(&content, model) {
+                Some(result) if !result.partial => {
+                    info!("Trends analysis received from {}", model);
+                    return Ok(result);
+                }
+                Some(partial) => {
+                    warn!("Trends answer from {} is incomplete", model);
+                    best_partial.get_or_insert(partial);
+                }
+                None => {
+                    warn!(
+                        "No trends section in the answer from {} (attempt {})",
+                        model,
+                        attempt + 1
+                    );
+                    last_error = Some(AnalysisError::AiRefusal.into());
+                }
+            }
+        }
+    }
+
+    error!(
+        "All models of the route failed the trends analysis: {}",
+        route.models.join(", ")
+    );
+    // a labeled partial timeline beats no timeline at all
+    if let Some(partial) = best_partial {
+        return Ok(partial);
+    }
+    if budget.is_exhausted() {
+        return Err(AnalysisError::BudgetExhausted.into());
+    }
+    match last_error.map(|e| AnalysisError::classify(e.as_ref())) {
+        Some(AnalysisError::AiRefusal) => Err(AnalysisError::AiRefusal.into()),
+        Some(AnalysisError::BudgetExhausted) => Err(AnalysisError::BudgetExhausted.into()),
+        _ => Err(AnalysisError::AiUnavailable.into()),
+    }
+}
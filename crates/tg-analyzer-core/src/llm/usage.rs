@@ -0,0 +1,147 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::sync::{Arc, OnceLock};
+
+use crate::error::AppError;
+
+/// list price of a model in usd per million tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+// unknown models are priced like the most expensive one, so the budget errs on the safe side
+const FALLBACK_PRICE: ModelPrice = ModelPrice {
+    input_per_million: 1.25,
+    output_per_million: 10.0,
+};
+
+/// the price used to estimate what a call to `model` cost
+pub fn model_price(model: &str) -> ModelPrice {
+    if model.starts_with("gemini-2.5-flash-lite") {
+        ModelPrice {
+            input_per_million: 0.10,
+            output_per_million: 0.40,
+        }
+    } else if model.starts_with("gemini-2.5-flash") {
+        ModelPrice {
+            input_per_million: 0.30,
+            output_per_million: 2.50,
+        }
+    } else if model.starts_with("gemini-3-flash") {
+        ModelPrice {
+            input_per_million: 0.50,
+            output_per_million: 3.00,
+        }
+    } else {
+        FALLBACK_PRICE
+    }
+}
+
+/// estimated cost of one call in usd
+pub fn estimate_cost_usd(model: &str, prompt_tokens: u64, output_tokens: u64) -> f64 {
+    let price = model_price(model);
+    (prompt_tokens as f64 * price.input_per_million
+        + output_tokens as f64 * price.output_per_million)
+        / 1_000_000.0
+}
+
+// set once at startup; without it calls are only logged, e.g. in tools and tests
+static USAGE_POOL: OnceLock<Arc<Pool>> = OnceLock::new();
+
+/// stores the token usage of every following llm call in llm_calls
+pub fn enable_usage_recording(pool: Arc<Pool>) {
+    if USAGE_POOL.set(pool).is_err() {
+        warn!("LLM usage recording was already enabled");
+    }
+}
+
+/// records a call in the background; a failed insert must never fail the analysis
+pub(crate) fn record_call(model: &str, prompt_tokens: u64, output_tokens: u64) {
+    let cost_usd = estimate_cost_usd(model, prompt_tokens, output_tokens);
+    info!(
+        "LLM call to {} used {} prompt and {} output tokens (~${:.4})",
+        model, prompt_tokens, output_tokens, cost_usd
+    );
+    let Some(pool) = USAGE_POOL.get().cloned() else {
+        return;
+    };
+    let model = model.to_string();
+    let prompt_tokens = i64::try_from(prompt_tokens).unwrap_or(i64::MAX);
+    let output_tokens = i64::try_from(output_tokens).unwrap_or(i64::MAX);
+    tokio::spawn(async move {
+        if let Err(e) = insert_call(&pool, &model, prompt_tokens, output_tokens, cost_usd).await {
+            warn!("Failed to record LLM call to {}: {}", model, e);
+        }
+    });
+}
+
+async fn insert_call(
+    pool: &Pool,
+    model: &str,
+    prompt_tokens: i64,
+    output_tokens: i64,
+    cost_usd: f64,
+) -> Result<(), AppError> {
+    let client = pool.get().await?;
+    client
+        .execute(
+            "INSERT INTO llm_calls (model, prompt_tokens, output_tokens, cost_usd)
+             VALUES ($1, $2, $3, $4)",
+            &[&model, &prompt_tokens, &output_tokens, &cost_usd],
+        )
+        .await?;
+    Ok(())
+}
+
+/// llm usage of one utc day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailySpend {
+    // YYYY-MM-DD
+    pub day: String,
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+}
+
+/// estimated cost of today's llm calls in usd, days start at midnight utc
+pub async fn today_spend_usd(pool: &Pool) -> Result<f64, AppError> {
+    let client = pool.get().await?;
+    let row = client
+        .query_one(
+            "SELECT COALESCE(SUM(cost_usd), 0)::float8 FROM llm_calls
+             WHERE created_at >= date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'",
+            &[],
+        )
+        .await?;
+    Ok(row.get(0))
+}
+
+/// usage of each of the last `days` days that had any calls, newest first
+pub async fn daily_spend(pool: &Pool, days: i32) -> Result<Vec<DailySpend>, AppError> {
+    let client = pool.get().await?;
+    let rows = client
+        .query(
+            "SELECT to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD') AS day, COUNT(*),
+                    SUM(prompt_tokens)::bigint, SUM(output_tokens)::bigint, SUM(cost_usd)::float8
+             FROM llm_calls
+             WHERE created_at >= date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+                                 - make_interval(days => $1 - 1)
+             GROUP BY day
+             ORDER BY day DESC",
+            &[&days],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| DailySpend {
+            day: row.get(0),
+            calls: row.get(1),
+            prompt_tokens: row.get(2),
+            output_tokens: row.get(3),
+            cost_usd: row.get(4),
+        })
+        .collect())
+}
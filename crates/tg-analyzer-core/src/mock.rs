@@ -0,0 +1,116 @@
+//! development mode enabled by LLM_MOCK=1: llm calls return canned answers and channels
+//! are read from fixture files, so the whole bot flow runs without api keys, telegram
+//! sessions or llm spend
+
+use gemini_rs::types::Schema;
+use log::info;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::analysis::{AnalysisError, MessageDict};
+use crate::llm::LLMResponse;
+use crate::report::{AnalysisReport, ReportScores};
+
+// directory the fixtures are read from unless MOCK_FIXTURES_DIR says otherwise
+const DEFAULT_FIXTURES_DIR: &str = "fixtures";
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// whether LLM_MOCK is set to 1 or true; read once per process
+pub fn enabled() -> bool {
+    *ENABLED.get_or_init(|| {
+        let enabled = env::var("LLM_MOCK")
+            .is_ok_and(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true"));
+        if enabled {
+            info!("LLM_MOCK is set: using canned llm answers and channel fixtures");
+        }
+        enabled
+    })
+}
+
+fn mock_report() -> AnalysisReport {
+    AnalysisReport {
+        professional: "Mock professional analysis: a methodical author who writes about \
+            their field with confidence and backs claims with examples."
+            .to_string(),
+        personal: "Mock personal analysis: curious, patient and fond of long explanations, \
+            with a dry sense of humor showing between the lines."
+            .to_string(),
+        roast: "Mock roast: posts so thorough that readers finish them a week later, \
+            just in time for the next one."
+            .to_string(),
+        strengths: vec!["clear writing".to_string(), "consistency".to_string()],
+        weaknesses: vec!["long posts".to_string()],
+        topics: vec!["software".to_string(), "productivity".to_string()],
+        tone: "friendly".to_string(),
+        scores: ReportScores {
+            expertise: 7,
+            communication: 8,
+            consistency: 6,
+            humor: 5,
+        },
+    }
+}
+
+/// the canned answer to a prompt: a json report in json mode, a timeline for trends
+/// prompts, a clean verdict for safety reviews and the three tagged sections otherwise;
+/// the same prompt always gets the same answer
+pub fn llm_response(prompt: &str, schema: Option<&Schema>) -> LLMResponse {
+    let report = mock_report();
+    let content = if schema.is_some() {
+        serde_json::to_string(&report).unwrap_or_default()
+    } else if prompt.contains("<verdict>safe</verdict>") {
+        "<verdict>safe</verdict>".to_string()
+    } else if prompt.contains("<trends>") && !prompt.contains("<professional>") {
+        "<trends>\n**Earlier period**: Mock trends analysis: the author wrote mostly about \
+         tools and posted every few days.\n\n**Later period**: the posts moved to \
+         productivity and became longer and rarer.\n\n**Overall**: fewer but deeper posts.\n\
+         </trends>"
+            .to_string()
+    } else {
+        format!(
+            "<professional>\n{}\n</professional>\n\n<personal>\n{}\n</personal>\n\n<roast>\n{}\n</roast>",
+            report.professional, report.personal, report.roast
+        )
+    };
+    LLMResponse {
+        content,
+        truncated: false,
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    env::var("MOCK_FIXTURES_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_FIXTURES_DIR))
+}
+
+/// path of a channel's fixture, `<dir>/<name without @, lowercased>.json`
+pub fn fixture_path(dir: &Path, channel_username: &str) -> PathBuf {
+    let name = channel_username.trim_start_matches('@').to_lowercase();
+    dir.join(format!("{}.json", name))
+}
+
+/// the messages of a channel from its fixture in `dir`, a json array of messages;
+/// a missing fixture is a channel that doesn't exist
+pub fn load_fixture_from(
+    dir: &Path,
+    channel_username: &str,
+) -> Result<Vec<MessageDict>, AnalysisError> {
+    let path = fixture_path(dir, channel_username);
+    let content = fs::read_to_string(&path).map_err(|_| AnalysisError::ChannelNotFound)?;
+    serde_json::from_str(&content)
+        .map_err(|e| AnalysisError::Internal(format!("invalid fixture {}: {}", path.display(), e)))
+}
+
+/// like load_fixture_from, reading MOCK_FIXTURES_DIR (fixtures/ by default)
+pub fn load_fixture(channel_username: &str) -> Result<Vec<MessageDict>, AnalysisError> {
+    load_fixture_from(&fixtures_dir(), channel_username)
+}
+
+/// whether a channel has a fixture, the mock counterpart of resolving its username
+pub fn fixture_exists(channel_username: &str) -> bool {
+    fixture_path(&fixtures_dir(), channel_username).is_file()
+}
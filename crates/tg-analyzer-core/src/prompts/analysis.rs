@@ -0,0 +1,719 @@
+use log::warn;
+use serde::Serialize;
+
+use crate::analysis::MessageDict;
+use crate::facts::QuickFacts;
+use crate::prompts::trends::message_day;
+use crate::prompts::versions::PromptVersion;
+use crate::report::{MAX_SCORE, MIN_SCORE};
+use crate::tokens::estimate_tokens;
+
+// maximum length of a user-provided focus instruction (in characters)
+pub const MAX_FOCUS_LENGTH: usize = 200;
+
+/// language the analysis is written in, stored as text in users.output_language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputLanguage {
+    /// the channel's dominant language, detected by the model
+    #[default]
+    Channel,
+    English,
+    Russian,
+}
+
+impl OutputLanguage {
+    pub const ALL: [OutputLanguage; 3] = [
+        OutputLanguage::Channel,
+        OutputLanguage::English,
+        OutputLanguage::Russian,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputLanguage::Channel => "channel",
+            OutputLanguage::English => "en",
+            OutputLanguage::Russian => "ru",
+        }
+    }
+
+    /// parses a stored language, falling back to the channel's language for unknown values
+    pub fn from_code(code: Option<&str>) -> Self {
+        match code {
+            Some("en") => OutputLanguage::English,
+            Some("ru") => OutputLanguage::Russian,
+            _ => OutputLanguage::Channel,
+        }
+    }
+
+    pub(crate) fn prompt_requirement(&self) -> &'static str {
+        match self {
+            OutputLanguage::Channel => {
+                "Write in the same language as the messages (detect automatically)"
+            }
+            OutputLanguage::English => {
+                "Write in English, regardless of the language of the messages"
+            }
+            OutputLanguage::Russian => {
+                "Write in Russian, regardless of the language of the messages"
+            }
+        }
+    }
+}
+
+// what each of the three analysis sections should cover, shared by both output formats
+const PROFESSIONAL_BRIEF: &str =
+    "Write a detailed professional assessment suitable for a hiring manager. Focus on:
+- Technical skills and expertise demonstrated
+- Communication style and professionalism
+- Leadership qualities or lack thereof
+- Work ethic and reliability indicators
+- Potential red flags or concerns for employers
+- Industry knowledge and thought leadership
+- Team collaboration potential
+
+Tone: Formal, objective, balanced - highlight both strengths and weaknesses
+Length: ~2048 characters";
+
+const PERSONAL_BRIEF: &str =
+    "Write a psychological personality analysis for a general audience. Focus on:
+- Core personality traits and characteristics
+- Emotional intelligence and social skills
+- Decision-making patterns and cognitive style
+- Values, beliefs, and motivations
+- Relationship patterns and social behavior
+- Stress responses and coping mechanisms
+- Growth mindset vs fixed mindset indicators
+
+Tone: Insightful, empathetic, professional psychological assessment
+Length: ~2048 characters";
+
+const ROAST_BRIEF: &str = "Write a sharp, witty critique as if from a close friend who knows them well. Focus on:
+- Quirks, habits, and annoying tendencies
+- Contradictions in their behavior or beliefs
+- Pretentious or hypocritical moments
+- Social media behavior and online persona
+- Pet peeves others might have about them
+- Blind spots and areas of self-delusion
+
+Tone: Brutally honest, sharp humor, keeping in mind the cultural context (e.g. Eastern European directness)
+Length: ~2048 characters
+Note: Adjust harshness based on cultural context - Eastern Europeans typically appreciate more direct criticism";
+
+// the brief of one analysis section by its tag name
+fn section_brief(section: &str) -> Option<&'static str> {
+    match section {
+        "professional" => Some(PROFESSIONAL_BRIEF),
+        "personal" => Some(PERSONAL_BRIEF),
+        "roast" => Some(ROAST_BRIEF),
+        _ => None,
+    }
+}
+
+/// the same analysis request in both output formats: json mode is tried first,
+/// the tagged format is the fallback for models or responses that don't comply
+#[derive(Debug, Clone)]
+pub struct AnalysisPrompt {
+    pub json: String,
+    pub tagged: String,
+}
+
+/// limits a group analysis to one of its forum topics, by topic name
+pub(crate) fn topic_section(topic: Option<&str>) -> String {
+    match topic {
+        Some(name) => format!(
+            "\nTOPIC SCOPE:\nAll messages come from the topic \"{}\" of a forum group. Analyze the authors as they show up in this topic and don't draw conclusions about the rest of the group.\n",
+            name
+        ),
+        None => String::new(),
+    }
+}
+
+// length of each section of a full analysis, in characters
+const SECTION_LENGTH: usize = 2048;
+
+// a profile has far less material than a channel, so its sections are shorter
+const PROFILE_SECTION_LENGTH: usize = 1024;
+
+// the section briefs are written for a full analysis, a shorter one only changes the length
+fn sized_brief(brief: &str, section_length: usize) -> String {
+    brief.replace(
+        "~2048 characters",
+        &format!("~{} characters", section_length),
+    )
+}
+
+/// the tagged and the json output format, with sections of `section_length` characters
+fn output_formats(section_length: usize) -> (String, String) {
+    let professional = sized_brief(PROFESSIONAL_BRIEF, section_length);
+    let personal = sized_brief(PERSONAL_BRIEF, section_length);
+    let roast = sized_brief(ROAST_BRIEF, section_length);
+    let tagged_format = format!(
+        "OUTPUT FORMAT (use these exact tags):
+
+<professional>
+{professional}
+</professional>
+
+<personal>
+{personal}
+</personal>
+
+<roast>
+{roast}
+</roast>"
+    );
+    let json_format = format!(
+        "OUTPUT FORMAT (a single JSON object with these fields):
+
+\"professional\": {professional}
+
+\"personal\": {personal}
+
+\"roast\": {roast}
+
+\"strengths\": 3-5 short phrases naming the author's main strengths
+\"weaknesses\": 3-5 short phrases naming the author's main weaknesses
+\"topics\": up to 5 main topics of the channel, a few words each
+\"tone\": one short phrase describing the overall tone of the channel
+\"scores\": integers from {MIN_SCORE} to {MAX_SCORE} rating the author's \"expertise\", \"communication\", \"consistency\" and \"humor\""
+    );
+    (tagged_format, json_format)
+}
+
+// a message as the llm reads it, without id, image urls and topic
+fn llm_message(msg: &MessageDict) -> MessageDict {
+    MessageDict {
+        id: None, // ids would only cost prompt tokens
+        date: msg.date.clone(),
+        message: msg.message.clone(),
+        images: None, // exclude images from LLM analysis
+        thread_id: None,
+    }
+}
+
+/// messages as the llm reads them, without ids, image urls and topics
+fn messages_json(messages: &[MessageDict]) -> Result<String, serde_json::Error> {
+    let messages_for_llm: Vec<MessageDict> = messages.iter().map(llm_message).collect();
+    serde_json::to_string_pretty(&messages_for_llm)
+}
+
+// the comma and indentation a message takes in the json array, in tokens
+const MESSAGE_SEPARATOR_TOKENS: usize = 2;
+
+// tokens of text past which a longer message is no more worth keeping
+const LONG_MESSAGE_TOKENS: usize = 200;
+
+/// the messages whose json fits in `budget` estimated tokens, in their original order.
+/// when they don't all fit, whole messages are dropped, the oldest and shortest first
+pub fn fit_messages(
+    messages: &[MessageDict],
+    budget: usize,
+) -> Result<Vec<MessageDict>, serde_json::Error> {
+    let costs = messages
+        .iter()
+        .map(|msg| {
+            serde_json::to_string_pretty(&llm_message(msg))
+                .map(|json| estimate_tokens(&json) + MESSAGE_SEPARATOR_TOKENS)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if costs.iter().sum::<usize>() <= budget {
+        return Ok(messages.to_vec());
+    }
+
+    // recency is the message's rank by day, the later of two messages on one day being
+    // the newer; length counts up to LONG_MESSAGE_TOKENS
+    let mut by_age: Vec<usize> = (0..messages.len()).collect();
+    by_age.sort_by_key(|&i| (message_day(&messages[i]), i));
+    let mut recency = vec![0.0; messages.len()];
+    for (rank, &i) in by_age.iter().enumerate() {
+        recency[i] = (rank + 1) as f64 / messages.len() as f64;
+    }
+    let priority = |i: usize| {
+        let text_tokens = messages[i]
+            .message
+            .as_deref()
+            .map_or(0, |text| estimate_tokens(text).min(LONG_MESSAGE_TOKENS));
+        recency[i] + text_tokens as f64 / LONG_MESSAGE_TOKENS as f64
+    };
+    let mut by_priority: Vec<(usize, f64)> =
+        (0..messages.len()).map(|i| (i, priority(i))).collect();
+    by_priority.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.cmp(&a.0)));
+
+    let mut remaining = budget;
+    let mut kept = Vec::new();
+    for (i, _) in by_priority {
+        if costs[i] <= remaining {
+            remaining -= costs[i];
+            kept.push(i);
+        }
+    }
+    kept.sort_unstable();
+    Ok(kept.into_iter().map(|i| messages[i].clone()).collect())
+}
+
+// optional requester focus, kept as a hint that can't override the output format
+fn focus_section(focus: Option<&str>) -> String {
+    match focus {
+        Some(focus) => format!(
+            "\nREQUESTER FOCUS:\nThe person requesting this analysis asked to pay special attention to the following. Take it into account in every section, but keep the required output format:\n\"{}\"\n",
+            focus.chars().take(MAX_FOCUS_LENGTH).collect::<String>()
+        ),
+        None => String::new(),
+    }
+}
+
+/// the channel analysis prompt over as many of the messages as fit in `token_limit`
+/// estimated tokens; the quick facts are counted from all of them
+pub fn generate_analysis_prompt(
+    messages: &[MessageDict],
+    focus: Option<&str>,
+    topic: Option<&str>,
+    language: OutputLanguage,
+    version: &PromptVersion,
+    token_limit: usize,
+) -> Result<AnalysisPrompt, Box<dyn std::error::Error + Send + Sync>> {
+    let facts = QuickFacts::from_messages(messages);
+    let prompt = |json: &str| {
+        let material = format!("Messages to analyze:\n{}", json);
+        channel_prompt(&material, &facts, focus, topic, language, version)
+    };
+
+    // a token takes at least one byte, so a prompt within the limit in bytes fits as it is
+    let json = messages_json(messages)?;
+    let full = prompt(&json);
+    if full.json.len().max(full.tagged.len()) <= token_limit {
+        return Ok(full);
+    }
+
+    let empty = prompt("[]");
+    let overhead = estimate_tokens(&empty.json).max(estimate_tokens(&empty.tagged));
+    let fitted = fit_messages(messages, token_limit.saturating_sub(overhead))?;
+    if fitted.len() == messages.len() {
+        return Ok(full);
+    }
+    warn!(
+        "Analysis prompt keeps {} of {} messages to fit {} tokens",
+        fitted.len(),
+        messages.len(),
+        token_limit
+    );
+    Ok(prompt(&messages_json(&fitted)?))
+}
+
+/// one batch of a corpus too large for a single prompt, summarized for the analysis
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub batch: usize,
+    // "2024-01-05 – 2024-02-11", absent when the batch has no dated messages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    pub summary: String,
+}
+
+// length of a batch summary, in characters
+const BATCH_SUMMARY_LENGTH: usize = 3000;
+
+/// asks for a summary of one batch of a corpus too large for a single prompt, keeping
+/// what the analysis needs to judge the author
+pub fn generate_batch_summary_prompt(
+    messages: &[MessageDict],
+    batch: usize,
+    total: usize,
+    topic: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(format!(
+        "You are preparing material for a personality analysis of the author of a Telegram channel. The channel is too large to analyze at once, so it is read in {total} consecutive batches and this is batch {batch}. Summarize the batch so that the final analysis can rely on your summary alone.
+
+COVER:
+- Main topics and how much of the batch each takes
+- Opinions, beliefs and attitudes the author expresses
+- Writing style: tone, humor, formality, vocabulary, typical post length
+- Behavioral patterns: how the author argues, reacts, promotes and engages the audience
+- Three to five short verbatim quotes that are characteristic of the author
+
+REQUIREMENTS:
+- Write in the same language as the messages, as plain text with short bullet lists
+- Keep to approximately {BATCH_SUMMARY_LENGTH} characters
+- Report what the messages show without judging the author yet
+{}
+Messages of batch {batch}:
+{}",
+        topic_section(topic),
+        messages_json(messages)?
+    ))
+}
+
+/// the analysis prompt over batch summaries of a corpus too large for a single prompt; the
+/// facts are counted from the whole corpus
+pub fn generate_summaries_analysis_prompt(
+    summaries: &[BatchSummary],
+    facts: &QuickFacts,
+    focus: Option<&str>,
+    topic: Option<&str>,
+    language: OutputLanguage,
+    version: &PromptVersion,
+) -> Result<AnalysisPrompt, Box<dyn std::error::Error + Send + Sync>> {
+    let material = format!(
+        "The channel is too large to read at once, so its messages were summarized in {} consecutive batches, oldest first. Treat the quotes in the summaries as the author's own words.\n\nSummaries to analyze:\n{}",
+        summaries.len(),
+        serde_json::to_string_pretty(summaries)?
+    );
+    Ok(channel_prompt(
+        &material, facts, focus, topic, language, version,
+    ))
+}
+
+// the channel analysis prompt in both output formats, over the messages or their summaries
+fn channel_prompt(
+    material: &str,
+    facts: &QuickFacts,
+    focus: Option<&str>,
+    topic: Option<&str>,
+    language: OutputLanguage,
+    version: &PromptVersion,
+) -> AnalysisPrompt {
+    let focus_section = focus_section(focus);
+    let facts_section = facts.prompt_section();
+    let (tagged_format, json_format) = output_formats(SECTION_LENGTH);
+
+    let build = |format_requirement: &str, output_format: &str| {
+        format!(
+            "You are an expert analyst tasked with creating a comprehensive personality profile based on Telegram channel messages. Analyze the writing style, topics discussed, opinions expressed, and behavioral patterns to understand the author's character.
+
+CRITICAL REQUIREMENTS:
+1. {}
+2. Each section must be approximately {} characters long
+3. {}
+4. Base analysis solely on the message content provided
+5. Do not make assumptions about gender, age, or location unless clearly evident
+
+{}
+
+ANALYSIS GUIDELINES:
+- Look for patterns across multiple messages, not isolated incidents
+- Consider context and nuance, not just surface-level content
+- Identify both explicit statements and implied attitudes
+- Note communication style: formal vs casual, technical vs accessible
+- Observe emotional regulation and reaction patterns
+- Consider the audience they're writing for and how they adapt their voice
+{}{}{}{}
+{}",
+            language.prompt_requirement(),
+            SECTION_LENGTH,
+            format_requirement,
+            output_format,
+            version.guidelines,
+            topic_section(topic),
+            facts_section,
+            focus_section,
+            material
+        )
+    };
+
+    AnalysisPrompt {
+        json: build(
+            "Respond with JSON only, using exactly the fields described below; all text fields follow requirement 1",
+            &json_format,
+        ),
+        tagged: build(
+            "Use ONLY the provided XML tags exactly as shown",
+            &tagged_format,
+        ),
+    }
+}
+
+/// the channel analysis prompt for `section` alone ("professional", "personal" or "roast"),
+/// in the tagged format only, over as many of the messages as fit in `token_limit`
+/// estimated tokens
+pub fn generate_section_prompt(
+    messages: &[MessageDict],
+    section: &str,
+    focus: Option<&str>,
+    topic: Option<&str>,
+    language: OutputLanguage,
+    version: &PromptVersion,
+    token_limit: usize,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let brief =
+        section_brief(section).ok_or_else(|| format!("unknown analysis section {:?}", section))?;
+    let facts = QuickFacts::from_messages(messages);
+    let prompt = |json: &str| {
+        let material = format!("Messages to analyze:\n{}", json);
+        section_prompt(
+            &material, section, brief, &facts, focus, topic, language, version,
+        )
+    };
+
+    let json = messages_json(messages)?;
+    let full = prompt(&json);
+    if full.len() <= token_limit {
+        return Ok(full);
+    }
+    let overhead = estimate_tokens(&prompt("[]"));
+    let fitted = fit_messages(messages, token_limit.saturating_sub(overhead))?;
+    if fitted.len() == messages.len() {
+        return Ok(full);
+    }
+    warn!(
+        "Section prompt keeps {} of {} messages to fit {} tokens",
+        fitted.len(),
+        messages.len(),
+        token_limit
+    );
+    Ok(prompt(&messages_json(&fitted)?))
+}
+
+// the channel analysis prompt asking for one section, with the guidelines of the full one
+#[allow(clippy::too_many_arguments)]
+fn section_prompt(
+    material: &str,
+    section: &str,
+    brief: &str,
+    facts: &QuickFacts,
+    focus: Option<&str>,
+    topic: Option<&str>,
+    language: OutputLanguage,
+    version: &PromptVersion,
+) -> String {
+    format!(
+        "You are an expert analyst tasked with writing one part of a personality profile based on Telegram channel messages. Analyze the writing style, topics discussed, opinions expressed, and behavioral patterns to understand the author's character.
+
+CRITICAL REQUIREMENTS:
+1. {}
+2. The section must be approximately {} characters long
+3. Use ONLY the provided XML tags exactly as shown
+4. Base analysis solely on the message content provided
+5. Do not make assumptions about gender, age, or location unless clearly evident
+
+OUTPUT FORMAT (use these exact tags):
+
+<{section}>
+{}
+</{section}>
+
+ANALYSIS GUIDELINES:
+- Look for patterns across multiple messages, not isolated incidents
+- Consider context and nuance, not just surface-level content
+- Identify both explicit statements and implied attitudes
+- Note communication style: formal vs casual, technical vs accessible
+- Observe emotional regulation and reaction patterns
+- Consider the audience they're writing for and how they adapt their voice
+{}{}{}{}
+{}",
+        language.prompt_requirement(),
+        SECTION_LENGTH,
+        brief,
+        version.guidelines,
+        topic_section(topic),
+        facts.prompt_section(),
+        focus_section(focus),
+        material
+    )
+}
+
+/// the reduced prompt for a user's public profile: the first message is a card with their
+/// name and bio, any others are posts of the channel shown on their profile
+pub fn generate_profile_prompt(
+    messages: &[MessageDict],
+    focus: Option<&str>,
+    language: OutputLanguage,
+) -> Result<AnalysisPrompt, Box<dyn std::error::Error + Send + Sync>> {
+    let messages_json = messages_json(messages)?;
+    let focus_section = focus_section(focus);
+    let (tagged_format, json_format) = output_formats(PROFILE_SECTION_LENGTH);
+
+    let build = |format_requirement: &str, output_format: &str| {
+        format!(
+            "You are an expert analyst tasked with creating a short personality profile of a Telegram user from what they show publicly. The first message is their profile card with their name and bio; any further messages are posts of the channel they show on their profile. There is far less material than in a channel analysis, so keep every section brief.
+
+CRITICAL REQUIREMENTS:
+1. {}
+2. Each section must be approximately {} characters long
+3. {}
+4. Base analysis solely on the profile content provided
+5. Do not make assumptions about gender, age, or location unless clearly evident
+6. Where the material is too thin to judge something, say so briefly instead of inventing details
+
+{}
+{}
+Profile to analyze:
+{}",
+            language.prompt_requirement(),
+            PROFILE_SECTION_LENGTH,
+            format_requirement,
+            output_format,
+            focus_section,
+            messages_json
+        )
+    };
+
+    Ok(AnalysisPrompt {
+        json: build(
+            "Respond with JSON only, using exactly the fields described below; all text fields follow requirement 1",
+            &json_format,
+        ),
+        tagged: build(
+            "Use ONLY the provided XML tags exactly as shown",
+            &tagged_format,
+        ),
+    })
+}
+
+/// the prompt for one user's messages gathered from all the groups they share with the
+/// bot; every message starts with the name of its group in brackets
+pub fn generate_cross_group_prompt(
+    messages: &[MessageDict],
+    focus: Option<&str>,
+    language: OutputLanguage,
+) -> Result<AnalysisPrompt, Box<dyn std::error::Error + Send + Sync>> {
+    let messages_json = messages_json(messages)?;
+    let focus_section = focus_section(focus);
+    let (tagged_format, json_format) = output_formats(SECTION_LENGTH);
+
+    let build = |format_requirement: &str, output_format: &str| {
+        format!(
+            "You are an expert analyst tasked with creating a comprehensive personality profile of one Telegram user from the messages they wrote in several group chats. Every message starts with the name of its group in square brackets. Analyze the writing style, topics discussed, opinions expressed, and behavioral patterns to understand the author's character.
+
+CRITICAL REQUIREMENTS:
+1. {}
+2. Each section must be approximately {} characters long
+3. {}
+4. Base analysis solely on the message content provided
+5. Do not make assumptions about gender, age, or location unless clearly evident
+6. The messages are replies in conversations you can't see, so don't judge them as standalone posts
+
+{}
+
+ANALYSIS GUIDELINES:
+- Compare how the author behaves in different groups: what stays the same and what changes with the audience
+- Look for patterns across groups, not isolated incidents in one of them
+- Note the role they take in conversations: asking, helping, arguing, joking
+- Observe emotional regulation and reaction patterns
+- Mention groups by name only where it explains a difference in behavior
+{}
+Messages to analyze:
+{}",
+            language.prompt_requirement(),
+            SECTION_LENGTH,
+            format_requirement,
+            output_format,
+            focus_section,
+            messages_json
+        )
+    };
+
+    Ok(AnalysisPrompt {
+        json: build(
+            "Respond with JSON only, using exactly the fields described below; all text fields follow requirement 1",
+            &json_format,
+        ),
+        tagged: build(
+            "Use ONLY the provided XML tags exactly as shown",
+            &tagged_format,
+        ),
+    })
+}
+
+/// the tagged and the json output format of a roast battle: the usual sections, each
+/// comparing the two members, with the roast as the battle itself
+fn battle_output_formats(first: &str, second: &str) -> (String, String) {
+    let professional = format!(
+        "Compare @{first} and @{second} as colleagues: skills, expertise, how they argue and how they'd do on the same team. Say who you'd rather hire and why.
+Length: ~1024 characters"
+    );
+    let personal = format!(
+        "Compare the personalities of @{first} and @{second}: temperament, values, how they treat others and how they react when challenged.
+Length: ~1024 characters"
+    );
+    let roast = format!(
+        "Stage a roast battle between @{first} and @{second}. Alternate rounds, each roasting one of them with their own quirks, contradictions and favorite topics, and quote or paraphrase their messages where it lands. Finish with a verdict naming the winner of the battle and the one line that won it.
+
+Tone: Sharp, playful and equally merciless to both, never cruel about things they can't change
+Length: ~{SECTION_LENGTH} characters"
+    );
+    let tagged_format = format!(
+        "OUTPUT FORMAT (use these exact tags):
+
+<professional>
+{professional}
+</professional>
+
+<personal>
+{personal}
+</personal>
+
+<roast>
+{roast}
+</roast>"
+    );
+    let json_format = format!(
+        "OUTPUT FORMAT (a single JSON object with these fields):
+
+\"professional\": {professional}
+
+\"personal\": {personal}
+
+\"roast\": {roast}
+
+\"strengths\": 3-5 short phrases naming the winner's main strengths
+\"weaknesses\": 3-5 short phrases naming the loser's main weaknesses
+\"topics\": up to 5 topics the two of them write about most, a few words each
+\"tone\": one short phrase describing the tone between them
+\"scores\": integers from {MIN_SCORE} to {MAX_SCORE} rating the winner's \"expertise\", \"communication\", \"consistency\" and \"humor\""
+    );
+    (tagged_format, json_format)
+}
+
+/// the prompt for a roast battle between two members of a group, from the messages they
+/// wrote there; every message starts with its author's username in brackets
+pub fn generate_roast_battle_prompt(
+    messages: &[MessageDict],
+    first: &str,
+    second: &str,
+    focus: Option<&str>,
+    language: OutputLanguage,
+) -> Result<AnalysisPrompt, Box<dyn std::error::Error + Send + Sync>> {
+    let messages_json = messages_json(messages)?;
+    let focus_section = focus_section(focus);
+    let (tagged_format, json_format) = battle_output_formats(first, second);
+
+    let build = |format_requirement: &str, output_format: &str| {
+        format!(
+            "You are the host of a roast battle between two members of a Telegram group, @{first} and @{second}. Below are the messages each of them wrote in the group; every message starts with its author's username in square brackets. Use their writing style, topics, opinions and habits as material.
+
+CRITICAL REQUIREMENTS:
+1. {}
+2. {}
+3. Base everything solely on the message content provided, and never mix up who wrote what
+4. Do not make assumptions about gender, age, or location unless clearly evident
+5. The messages are replies in conversations you can't see, so don't judge them as standalone posts
+
+{}
+
+BATTLE GUIDELINES:
+- Give both members the same amount of attention and the same level of harshness
+- Contrast them: where one is strong the other is usually not, use that
+- Build on patterns that repeat in their messages, not on a single message
+- Keep it about their online personas in this group
+{}
+Messages of both members:
+{}",
+            language.prompt_requirement(),
+            format_requirement,
+            output_format,
+            focus_section,
+            messages_json
+        )
+    };
+
+    Ok(AnalysisPrompt {
+        json: build(
+            "Respond with JSON only, using exactly the fields described below; all text fields follow requirement 1",
+            &json_format,
+        ),
+        tagged: build(
+            "Use ONLY the provided XML tags exactly as shown",
+            &tagged_format,
+        ),
+    })
+}
@@ -0,0 +1,3 @@
+pub mod analysis;
+pub mod trends;
+pub mod versions;
@@ -0,0 +1,117 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::analysis::{AnalysisError, MessageDict};
+use crate::prompts::analysis::{topic_section, OutputLanguage, MAX_FOCUS_LENGTH};
+
+/// the texts of the messages posted within one time window
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimeBucket {
+    // "2024-03" for monthly windows, "2024-W11" for weekly ones
+    pub period: String,
+    pub messages: Vec<String>,
+}
+
+/// day a message was posted; dates are stored as "YYYY-MM-DD", optionally with a time
+pub fn message_day(message: &MessageDict) -> Option<NaiveDate> {
+    let day = message.date.as_deref()?.get(..10)?;
+    NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()
+}
+
+/// groups the text messages into time windows, oldest first: by month when they span
+/// several months, by iso week otherwise. messages without a text or a date are left out
+pub fn bucket_messages(messages: &[MessageDict]) -> Vec<TimeBucket> {
+    let dated = messages
+        .iter()
+        .filter_map(|msg| Some((message_day(msg)?, msg.message.as_deref()?)))
+        .filter(|(_, text)| !text.trim().is_empty())
+        .collect::<Vec<_>>();
+    let months = dated
+        .iter()
+        .map(|(day, _)| (day.year(), day.month()))
+        .collect::<HashSet<_>>();
+    let monthly = months.len() > 1;
+
+    let mut buckets: BTreeMap<(i32, u32), Vec<(NaiveDate, &str)>> = BTreeMap::new();
+    for (day, text) in dated {
+        let key = if monthly {
+            (day.year(), day.month())
+        } else {
+            let week = day.iso_week();
+            (week.year(), week.week())
+        };
+        buckets.entry(key).or_default().push((day, text));
+    }
+
+    buckets
+        .into_iter()
+        .map(|((year, number), mut entries)| {
+            // fetches come newest first, a timeline reads better in posting order
+            entries.sort_by_key(|(day, _)| *day);
+            TimeBucket {
+                period: if monthly {
+                    format!("{}-{:02}", year, number)
+                } else {
+                    format!("{}-W{:02}", year, number)
+                },
+                messages: entries
+                    .into_iter()
+                    .map(|(_, text)| text.to_string())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// asks how the channel's topics and tone changed from one time window to the next;
+/// fails with ShortHistory unless the dated messages fill at least two windows
+pub fn generate_trends_prompt(
+    messages: &[MessageDict],
+    focus: Option<&str>,
+    topic: Option<&str>,
+    language: OutputLanguage,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let buckets = bucket_messages(messages);
+    if buckets.len() < 2 {
+        return Err(AnalysisError::ShortHistory.into());
+    }
+    let buckets_json = serde_json::to_string_pretty(&buckets)?;
+
+    let focus_section = match focus {
+        Some(focus) => format!(
+            "\nREQUESTER FOCUS:\nThe person requesting this analysis asked to pay special attention to the following. Take it into account, but keep the required output format:\n\"{}\"\n",
+            focus.chars().take(MAX_FOCUS_LENGTH).collect::<String>()
+        ),
+        None => String::new(),
+    };
+
+    Ok(format!(
+        "You are an expert analyst tracking how a Telegram channel evolves over time. The channel's messages are grouped into consecutive time periods, oldest first. Describe how the topics, tone and posting habits of the author changed from one period to the next.
+
+CRITICAL REQUIREMENTS:
+1. {}
+2. Use ONLY the provided XML tags exactly as shown
+3. Base the analysis solely on the message content provided
+4. Compare periods with each other instead of summarizing each one in isolation
+
+OUTPUT FORMAT (use these exact tags):
+
+<trends>
+A timeline with one short paragraph per period, each starting with the period label in bold (e.g. **2024-03**), covering:
+- Main topics of the period and which of them are new or gone compared to the previous one
+- Tone and mood of the period
+- How often and how much the author posted
+
+End with an \"Overall\" paragraph naming the most significant shifts across the whole timeline.
+Length: ~3000 characters
+</trends>
+{}{}
+Periods to analyze:
+{}",
+        language.prompt_requirement(),
+        topic_section(topic),
+        focus_section,
+        buckets_json
+    ))
+}
@@ -0,0 +1,104 @@
+use log::warn;
+use std::env;
+
+/// version every analysis used before versions were recorded, and the one trends use
+pub const BASE_PROMPT_VERSION: i32 = 1;
+
+/// a revision of the analysis prompt; results, analyses and feedback record its id, so
+/// variants can be compared by their ratings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptVersion {
+    pub id: i32,
+    pub name: &'static str,
+    // extra lines of the analysis guidelines, empty for the base prompt
+    pub(crate) guidelines: &'static str,
+}
+
+/// every prompt version that can be assigned; ids are never reused once results carry them
+pub const PROMPT_VERSIONS: [PromptVersion; 2] = [
+    PromptVersion {
+        id: BASE_PROMPT_VERSION,
+        name: "base",
+        guidelines: "",
+    },
+    PromptVersion {
+        id: 2,
+        name: "evidence",
+        guidelines: "- Back every conclusion with a short paraphrase of the messages it rests on
+- Prefer fewer well-supported observations over many speculative ones
+",
+    },
+];
+
+impl PromptVersion {
+    pub fn find(id: i32) -> Option<&'static PromptVersion> {
+        PROMPT_VERSIONS.iter().find(|version| version.id == id)
+    }
+
+    pub fn base() -> &'static PromptVersion {
+        &PROMPT_VERSIONS[0]
+    }
+}
+
+/// A/B split of users between prompt versions by weight; everyone gets the base version
+/// unless PROMPT_EXPERIMENT says otherwise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptExperiment {
+    buckets: Vec<(i32, u32)>,
+}
+
+impl Default for PromptExperiment {
+    fn default() -> Self {
+        Self {
+            buckets: vec![(BASE_PROMPT_VERSION, 1)],
+        }
+    }
+}
+
+impl PromptExperiment {
+    /// parses "<version>:<weight>,..." such as "1:80,2:20"; unknown versions and zero
+    /// weights are left out, and nothing left means no experiment
+    pub fn parse(spec: &str) -> Self {
+        let buckets: Vec<(i32, u32)> = spec
+            .split(',')
+            .filter_map(|bucket| {
+                let (id, weight) = bucket.trim().split_once(':')?;
+                let id = id.trim().parse().ok()?;
+                let weight = weight.trim().parse().ok().filter(|weight| *weight > 0)?;
+                if PromptVersion::find(id).is_none() {
+                    warn!(
+                        "Ignoring unknown prompt version {} in PROMPT_EXPERIMENT",
+                        id
+                    );
+                    return None;
+                }
+                Some((id, weight))
+            })
+            .collect();
+        if buckets.is_empty() {
+            return Self::default();
+        }
+        Self { buckets }
+    }
+
+    pub fn from_env() -> Self {
+        env::var("PROMPT_EXPERIMENT")
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    /// the version of the user's bucket; a user stays in one bucket while the split is
+    /// unchanged
+    pub fn version_for(&self, user_id: i32) -> &'static PromptVersion {
+        let total: u32 = self.buckets.iter().map(|(_, weight)| weight).sum();
+        // spreads consecutive user ids over the buckets
+        let mut point = (user_id as u32).wrapping_mul(2_654_435_761) % total;
+        for (id, weight) in &self.buckets {
+            if point < *weight {
+                return PromptVersion::find(*id).unwrap_or(PromptVersion::base());
+            }
+            point -= weight;
+        }
+        PromptVersion::base()
+    }
+}
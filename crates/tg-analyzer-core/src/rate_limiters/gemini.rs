@@ -0,0 +1,253 @@
+use log::{info, warn};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::sleep;
+
+// requests and tokens are counted over the last minute
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// estimated prompt tokens per concurrency slot: a call takes one slot plus one for every
+/// full TOKENS_PER_SLOT of its prompt, so a few large prompts don't run next to many others
+pub const TOKENS_PER_SLOT: usize = 100_000;
+
+/// what one gemini model may be sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeminiQuota {
+    pub requests_per_minute: u32,
+    // estimated prompt tokens
+    pub tokens_per_minute: u64,
+    // concurrency slots, see TOKENS_PER_SLOT
+    pub max_concurrent: u32,
+}
+
+impl GeminiQuota {
+    /// for models without a quota of their own; 60 requests a minute keeps the one call per
+    /// second the limiter used to space calls by
+    pub const DEFAULT: GeminiQuota = GeminiQuota {
+        requests_per_minute: 60,
+        tokens_per_minute: 1_000_000,
+        max_concurrent: 8,
+    };
+
+    /// concurrency slots a call with a prompt of `tokens` takes
+    pub fn slots(&self, tokens: usize) -> u32 {
+        (1 + tokens / TOKENS_PER_SLOT).min(self.max_concurrent as usize) as u32
+    }
+}
+
+/// per model quotas, set with GEMINI_QUOTAS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeminiQuotas {
+    default: GeminiQuota,
+    models: Vec<(String, GeminiQuota)>,
+}
+
+impl Default for GeminiQuotas {
+    fn default() -> Self {
+        Self {
+            default: GeminiQuota::DEFAULT,
+            models: Vec::new(),
+        }
+    }
+}
+
+impl GeminiQuotas {
+    /// parses "<model>=<rpm>:<tpm>[:<concurrent>],..." such as
+    /// "gemini-2.5-pro=150:2000000:4,gemini-2.5-flash=1000:1000000"; a model takes the
+    /// quota of the longest prefix of its name, "*" replaces the default, and malformed
+    /// entries are left out
+    pub fn parse(spec: &str) -> Self {
+        let mut quotas = Self::default();
+        for entry in spec.split(',').filter(|entry| !entry.trim().is_empty()) {
+            match Self::parse_entry(entry) {
+                Some((model, quota)) if model == "*" => quotas.default = quota,
+                Some((model, quota)) => quotas.models.push((model, quota)),
+                None => warn!("Ignoring malformed GEMINI_QUOTAS entry {:?}", entry.trim()),
+            }
+        }
+        quotas
+    }
+
+    fn parse_entry(entry: &str) -> Option<(String, GeminiQuota)> {
+        let (model, settings) = entry.trim().split_once('=')?;
+        let model = model.trim();
+        if model.is_empty() {
+            return None;
+        }
+        let mut parts = settings.split(':').map(str::trim);
+        let requests_per_minute = parts.next()?.parse().ok().filter(|rpm| *rpm > 0)?;
+        let tokens_per_minute = parts.next()?.parse().ok().filter(|tpm| *tpm > 0)?;
+        let max_concurrent = match parts.next() {
+            Some(concurrent) => concurrent.parse().ok().filter(|slots| *slots > 0)?,
+            None => GeminiQuota::DEFAULT.max_concurrent,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some((
+            model.to_string(),
+            GeminiQuota {
+                requests_per_minute,
+                tokens_per_minute,
+                max_concurrent,
+            },
+        ))
+    }
+
+    pub fn from_env() -> Self {
+        env::var("GEMINI_QUOTAS")
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    /// the quota `model` is held to
+    pub fn for_model(&self, model: &str) -> GeminiQuota {
+        self.models
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, quota)| *quota)
+    }
+}
+
+/// the calls of the last minute to one model, against its requests and tokens per minute
+pub struct QuotaWindow {
+    quota: GeminiQuota,
+    // start and estimated prompt tokens of each call, oldest first
+    calls: VecDeque<(Instant, u64)>,
+}
+
+impl QuotaWindow {
+    pub fn new(quota: GeminiQuota) -> Self {
+        Self {
+            quota,
+            calls: VecDeque::new(),
+        }
+    }
+
+    /// records a call of `tokens` starting at `now` if it fits the quota, otherwise returns
+    /// how long until it would. a call larger than the whole token quota goes through once
+    /// the window is empty, so it is never stuck
+    pub fn try_reserve(&mut self, tokens: u64, now: Instant) -> Result<(), Duration> {
+        while self
+            .calls
+            .front()
+            .is_some_and(|(start, _)| now.saturating_duration_since(*start) >= WINDOW)
+        {
+            self.calls.pop_front();
+        }
+
+        let fits = |requests: usize, used: u64| {
+            requests == 0
+                || (requests < self.quota.requests_per_minute as usize
+                    && used + tokens <= self.quota.tokens_per_minute)
+        };
+        let mut used: u64 = self.calls.iter().map(|(_, tokens)| tokens).sum();
+        if fits(self.calls.len(), used) {
+            self.calls.push_back((now, tokens));
+            return Ok(());
+        }
+
+        // the wait until enough of the oldest calls leave the window
+        for (expired, (start, call_tokens)) in self.calls.iter().enumerate() {
+            used -= call_tokens;
+            if fits(self.calls.len() - expired - 1, used) {
+                return Err((*start + WINDOW).saturating_duration_since(now));
+            }
+        }
+        unreachable!("an empty window fits any call")
+    }
+}
+
+// one model's concurrency slots and quota window
+struct ModelLimiter {
+    quota: GeminiQuota,
+    slots: Arc<Semaphore>,
+    window: Mutex<QuotaWindow>,
+}
+
+/// held for the duration of a gemini call, gives its concurrency slots back when dropped
+pub struct GeminiPermit {
+    _slots: OwnedSemaphorePermit,
+}
+
+/// quota-aware limiter for gemini calls, one set of quotas per model; every analysis path
+/// goes through it, so channel and group analyses share the same budgets
+pub struct GeminiRateLimiter {
+    quotas: RwLock<GeminiQuotas>,
+    models: Mutex<HashMap<String, Arc<ModelLimiter>>>,
+}
+
+impl GeminiRateLimiter {
+    pub fn new(quotas: GeminiQuotas) -> Self {
+        Self {
+            quotas: RwLock::new(quotas),
+            models: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// switches to new quotas; a model whose quota changed starts over with fresh slots
+    /// and window, while calls holding the old ones finish under them
+    pub fn set_quotas(&self, quotas: GeminiQuotas) {
+        // held throughout, so no limiter is made with the old quotas meanwhile
+        let mut models = self.models.lock().unwrap_or_else(|e| e.into_inner());
+        models.retain(|model, limiter| limiter.quota == quotas.for_model(model));
+        *self.quotas.write().unwrap_or_else(|e| e.into_inner()) = quotas;
+    }
+
+    fn model(&self, model: &str) -> Arc<ModelLimiter> {
+        let mut models = self.models.lock().unwrap_or_else(|e| e.into_inner());
+        models
+            .entry(model.to_string())
+            .or_insert_with(|| {
+                let quota = self
+                    .quotas
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .for_model(model);
+                Arc::new(ModelLimiter {
+                    quota,
+                    slots: Arc::new(Semaphore::new(quota.max_concurrent as usize)),
+                    window: Mutex::new(QuotaWindow::new(quota)),
+                })
+            })
+            .clone()
+    }
+
+    /// waits for the concurrency slots a prompt of `tokens` takes and for room in the
+    /// model's per minute quotas
+    pub async fn acquire(&self, model: &str, tokens: usize) -> GeminiPermit {
+        let limiter = self.model(model);
+        let slots = limiter
+            .slots
+            .clone()
+            .acquire_many_owned(limiter.quota.slots(tokens))
+            .await
+            .expect("the semaphore is never closed");
+
+        loop {
+            let reserved = limiter
+                .window
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .try_reserve(tokens as u64, Instant::now());
+            match reserved {
+                Ok(()) => return GeminiPermit { _slots: slots },
+                Err(wait) => {
+                    info!("Gemini quota of {}: waiting for {:?}", model, wait);
+                    sleep(wait).await;
+                }
+            }
+        }
+    }
+}
+
+// global limiter for the gemini api, quotas from GEMINI_QUOTAS
+static GEMINI_RATE_LIMITER: OnceLock<GeminiRateLimiter> = OnceLock::new();
+
+pub fn get_gemini_rate_limiter() -> &'static GeminiRateLimiter {
+    GEMINI_RATE_LIMITER.get_or_init(|| GeminiRateLimiter::new(GeminiQuotas::from_env()))
+}
@@ -0,0 +1,2 @@
+pub mod gemini;
+pub mod telegram;
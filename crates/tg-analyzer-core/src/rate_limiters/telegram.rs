@@ -0,0 +1,101 @@
+use log::{info, warn};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+// flood waits up to this long are waited out; longer ones are better avoided by switching backends
+pub const FLOOD_WAIT_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// rate limiter for telegram api operations
+pub struct TelegramRateLimiter {
+    username_resolution_last_call: Arc<Mutex<Option<Instant>>>,
+    message_iteration_last_call: Arc<Mutex<Option<Instant>>>,
+    flood_wait_until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Default for TelegramRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TelegramRateLimiter {
+    pub fn new() -> Self {
+        Self {
+            username_resolution_last_call: Arc::new(Mutex::new(None)),
+            message_iteration_last_call: Arc::new(Mutex::new(None)),
+            flood_wait_until: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// records a FLOOD_WAIT so the next call waits exactly as long as telegram asked;
+    /// returns false when the wait exceeds FLOOD_WAIT_THRESHOLD and should not be waited out
+    pub async fn record_flood_wait(&self, seconds: u32) -> bool {
+        let wait = Duration::from_secs(seconds as u64);
+        if wait > FLOOD_WAIT_THRESHOLD {
+            warn!(
+                "FLOOD_WAIT of {}s exceeds the {}s threshold",
+                seconds,
+                FLOOD_WAIT_THRESHOLD.as_secs()
+            );
+            return false;
+        }
+
+        let mut flood_wait_until = self.flood_wait_until.lock().await;
+        let until = Instant::now() + wait;
+        // never shorten a wait that is already in effect
+        if flood_wait_until.is_none_or(|current| current < until) {
+            *flood_wait_until = Some(until);
+        }
+        true
+    }
+
+    /// remaining time of the last recorded flood wait, if any
+    pub async fn flood_wait_remaining(&self) -> Option<Duration> {
+        self.flood_wait_until
+            .lock()
+            .await
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    async fn wait_for_flood_wait(&self) {
+        if let Some(remaining) = self.flood_wait_remaining().await {
+            info!(
+                "Respecting Telegram FLOOD_WAIT: waiting {}ms",
+                remaining.as_millis()
+            );
+            sleep(remaining).await;
+        }
+    }
+
+    /// wait for username resolution rate limit (1 request per 10 minutes)
+    pub async fn wait_for_username_resolution(&self) {
+        self.wait_for_flood_wait().await;
+        let mut last_call = self.username_resolution_last_call.lock().await;
+
+        if let Some(last_time) = *last_call {
+            let elapsed = last_time.elapsed();
+            let min_interval = Duration::from_secs(600);
+
+            if elapsed < min_interval {
+                let wait_time = min_interval - elapsed;
+                info!(
+                    "Rate limiting username resolution: waiting {}ms",
+                    wait_time.as_millis()
+                );
+                sleep(wait_time).await;
+            }
+        }
+
+        *last_call = Some(Instant::now());
+    }
+
+    /// wait for message iteration rate limit (no artificial limit beyond flood waits, just tracking)
+    pub async fn wait_for_message_iteration(&self) {
+        self.wait_for_flood_wait().await;
+        let mut last_call = self.message_iteration_last_call.lock().await;
+        *last_call = Some(Instant::now());
+    }
+}
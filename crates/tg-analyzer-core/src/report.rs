@@ -0,0 +1,135 @@
+use gemini_rs::types::{Schema, Type};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// bounds of every score in the report
+pub const MIN_SCORE: u8 = 1;
+pub const MAX_SCORE: u8 = 10;
+// list fields are trimmed to this many items
+const MAX_LIST_ITEMS: usize = 5;
+
+/// 1-10 ratings of the author, as judged by the model
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportScores {
+    pub expertise: u8,
+    pub communication: u8,
+    pub consistency: u8,
+    pub humor: u8,
+}
+
+impl ReportScores {
+    /// mean of the four scores, the overall score shown on leaderboards
+    pub fn average(&self) -> f64 {
+        f64::from(
+            u16::from(self.expertise)
+                + u16::from(self.communication)
+                + u16::from(self.consistency)
+                + u16::from(self.humor),
+        ) / 4.0
+    }
+}
+
+/// structured analysis returned by the llm in json mode; the three text sections are
+/// the same ones the tagged format produces
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AnalysisReport {
+    pub professional: String,
+    pub personal: String,
+    pub roast: String,
+    pub strengths: Vec<String>,
+    pub weaknesses: Vec<String>,
+    pub topics: Vec<String>,
+    pub tone: String,
+    pub scores: ReportScores,
+}
+
+impl AnalysisReport {
+    /// parses a json response, None if it doesn't match the schema or a section is empty
+    pub fn parse(json: &str) -> Option<Self> {
+        let mut report: Self = serde_json::from_str(json.trim()).ok()?;
+        if [&report.professional, &report.personal, &report.roast]
+            .iter()
+            .any(|section| section.trim().is_empty())
+        {
+            return None;
+        }
+
+        for list in [
+            &mut report.strengths,
+            &mut report.weaknesses,
+            &mut report.topics,
+        ] {
+            list.retain(|item| !item.trim().is_empty());
+            list.truncate(MAX_LIST_ITEMS);
+        }
+        for score in [
+            &mut report.scores.expertise,
+            &mut report.scores.communication,
+            &mut report.scores.consistency,
+            &mut report.scores.humor,
+        ] {
+            *score = (*score).clamp(MIN_SCORE, MAX_SCORE);
+        }
+        Some(report)
+    }
+
+    /// response schema enforced through gemini's json mode
+    pub fn schema() -> Schema {
+        fn string() -> Schema {
+            Schema {
+                schema_type: Some(Type::String),
+                ..Default::default()
+            }
+        }
+        fn string_list() -> Schema {
+            Schema {
+                schema_type: Some(Type::Array),
+                items: Some(Box::new(string())),
+                max_items: Some(MAX_LIST_ITEMS.to_string()),
+                ..Default::default()
+            }
+        }
+        fn object(fields: Vec<(&str, Schema)>) -> Schema {
+            let names = fields
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>();
+            Schema {
+                schema_type: Some(Type::Object),
+                properties: Some(
+                    fields
+                        .into_iter()
+                        .map(|(name, schema)| (name.to_string(), schema))
+                        .collect::<BTreeMap<_, _>>(),
+                ),
+                required: Some(names.clone()),
+                property_ordering: Some(names),
+                ..Default::default()
+            }
+        }
+        let score = || Schema {
+            schema_type: Some(Type::Integer),
+            description: Some(format!("from {} to {}", MIN_SCORE, MAX_SCORE)),
+            ..Default::default()
+        };
+
+        object(vec![
+            ("professional", string()),
+            ("personal", string()),
+            ("roast", string()),
+            ("strengths", string_list()),
+            ("weaknesses", string_list()),
+            ("topics", string_list()),
+            ("tone", string()),
+            (
+                "scores",
+                object(vec![
+                    ("expertise", score()),
+                    ("communication", score()),
+                    ("consistency", score()),
+                    ("humor", score()),
+                ]),
+            ),
+        ])
+    }
+}
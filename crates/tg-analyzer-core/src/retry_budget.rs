@@ -0,0 +1,111 @@
+use log::warn;
+use std::env;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::llm::{calculate_delay, MAX_RETRIES};
+
+// defaults of one analysis: enough for a slow llm answer plus a few retries
+const DEFAULT_TIME_BUDGET_SECS: u64 = 900;
+const DEFAULT_RETRY_BUDGET: u32 = 12;
+
+static BUDGET_SETTINGS: OnceLock<(Duration, u32)> = OnceLock::new();
+
+/// retries and wall-clock time one analysis may spend across all of its stages
+/// (client connect, channel resolution, message fetch, llm calls); stages draw from
+/// the same budget, so the total duration stays bounded however many stages fail.
+/// early retries have short backoffs and go through while later, longer ones are
+/// refused once they no longer fit before the deadline
+#[derive(Debug)]
+pub struct RetryBudget {
+    deadline: Option<Instant>,
+    retries_left: AtomicU32,
+}
+
+impl RetryBudget {
+    pub fn new(time: Duration, retries: u32) -> Self {
+        Self {
+            deadline: Instant::now().checked_add(time),
+            retries_left: AtomicU32::new(retries),
+        }
+    }
+
+    /// a budget of ANALYSIS_TIME_BUDGET_SECS and ANALYSIS_RETRY_BUDGET, read once by the
+    /// first analysis
+    pub fn from_env() -> Self {
+        let (time, retries) = *BUDGET_SETTINGS.get_or_init(|| {
+            let time = env::var("ANALYSIS_TIME_BUDGET_SECS")
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_TIME_BUDGET_SECS);
+            let retries = env::var("ANALYSIS_RETRY_BUDGET")
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok())
+                .unwrap_or(DEFAULT_RETRY_BUDGET);
+            (Duration::from_secs(time), retries)
+        });
+        Self::new(time, retries)
+    }
+
+    /// no shared limit, every stage only has its own MAX_RETRIES; for calls outside an analysis
+    pub fn unbounded() -> Self {
+        Self {
+            deadline: None,
+            retries_left: AtomicU32::new(u32::MAX),
+        }
+    }
+
+    /// time left until the deadline, Duration::MAX when there is none
+    pub fn remaining(&self) -> Duration {
+        match self.deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => Duration::MAX,
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    pub fn retries_left(&self) -> u32 {
+        self.retries_left.load(Ordering::Relaxed)
+    }
+
+    /// caps a per-call timeout so a single call can't outlive the budget
+    pub fn cap_timeout(&self, timeout: Duration) -> Duration {
+        timeout.min(self.remaining())
+    }
+
+    /// takes one retry if the budget has one left and `wait` ends before the deadline
+    pub fn take_retry(&self, wait: Duration) -> bool {
+        if wait >= self.remaining() {
+            warn!(
+                "Retry budget can't afford a {}ms wait ({}ms left)",
+                wait.as_millis(),
+                self.remaining().as_millis()
+            );
+            return false;
+        }
+        let taken = self
+            .retries_left
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                left.checked_sub(1)
+            })
+            .is_ok();
+        if !taken {
+            warn!("Retry budget has no retries left");
+        }
+        taken
+    }
+
+    /// backoff before retrying a stage after its `attempt`-th failure (zero-based),
+    /// None when the stage used up its MAX_RETRIES or the budget can't afford the retry
+    pub fn retry_delay(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= MAX_RETRIES {
+            return None;
+        }
+        let delay = calculate_delay(attempt);
+        self.take_retry(delay).then_some(delay)
+    }
+}
@@ -5,6 +5,25 @@ use std::env;
 use std::fs;
 use std::path::Path;
 
+/// the telegram app's api credentials, from TG_API_ID and TG_API_HASH
+#[derive(Debug, Clone)]
+pub struct TelegramCredentials {
+    pub api_id: i32,
+    pub api_hash: String,
+}
+
+impl TelegramCredentials {
+    pub fn from_env() -> Result<Self, String> {
+        let api_id = env::var("TG_API_ID")
+            .map_err(|_| "TG_API_ID environment variable is required".to_string())?
+            .parse::<i32>()
+            .map_err(|_| "TG_API_ID must be a valid integer".to_string())?;
+        let api_hash = env::var("TG_API_HASH")
+            .map_err(|_| "TG_API_HASH environment variable is required".to_string())?;
+        Ok(Self { api_id, api_hash })
+    }
+}
+
 pub struct SessionManager;
 
 impl SessionManager {
@@ -77,7 +96,7 @@ impl SessionManager {
     }
 
     /// validates a single session by attempting to connect and check authorization
-    async fn validate_single_session(
+    pub async fn validate_single_session(
         session_file: &str,
     ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         // load session
@@ -89,18 +108,13 @@ impl SessionManager {
             }
         };
 
-        // get API credentials from environment
-        let api_id = env::var("TG_API_ID")
-            .map_err(|_| "TG_API_ID not set in environment")?
-            .parse::<i32>()
-            .map_err(|_| "TG_API_ID must be a valid integer")?;
-        let api_hash = env::var("TG_API_HASH").map_err(|_| "TG_API_HASH not set in environment")?;
+        let credentials = TelegramCredentials::from_env()?;
 
         // attempt to create client and connect
         let client = Client::connect(Config {
             session,
-            api_id,
-            api_hash,
+            api_id: credentials.api_id,
+            api_hash: credentials.api_hash,
             params: Default::default(),
         })
         .await;
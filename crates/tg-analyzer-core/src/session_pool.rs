@@ -0,0 +1,149 @@
+use log::{info, warn};
+use std::time::{Duration, Instant};
+
+use crate::session_manager::SessionManager;
+
+// sessions with this many consecutive auth failures are rotated out until re-validated
+const MAX_AUTH_FAILURES: u32 = 2;
+// how often every session is re-validated via SessionManager
+const REVALIDATION_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone)]
+pub struct SessionHealth {
+    pub session_file: String,
+    pub flood_wait_until: Option<Instant>,
+    pub auth_failures: u32,
+    pub last_used: Option<Instant>,
+    pub last_validated: Instant,
+}
+
+impl SessionHealth {
+    fn new(session_file: String) -> Self {
+        Self {
+            session_file,
+            flood_wait_until: None,
+            auth_failures: 0,
+            last_used: None,
+            // sessions are validated on startup before the pool is built
+            last_validated: Instant::now(),
+        }
+    }
+
+    /// returns true if the session is not flood-waited and has no outstanding auth failures
+    pub fn is_healthy(&self) -> bool {
+        let flood_waited = self
+            .flood_wait_until
+            .is_some_and(|until| until > Instant::now());
+        !flood_waited && self.auth_failures < MAX_AUTH_FAILURES
+    }
+
+    fn needs_revalidation(&self) -> bool {
+        self.last_validated.elapsed() >= REVALIDATION_INTERVAL
+    }
+}
+
+/// pool of telegram user sessions with health tracking and least-recently-used rotation
+pub struct SessionPool {
+    sessions: Vec<SessionHealth>,
+}
+
+impl SessionPool {
+    pub fn new(session_files: Vec<String>) -> Self {
+        Self {
+            sessions: session_files.into_iter().map(SessionHealth::new).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn healthy_count(&self) -> usize {
+        self.sessions.iter().filter(|s| s.is_healthy()).count()
+    }
+
+    /// picks the least recently used healthy session, or the one whose flood wait ends soonest
+    pub fn select(&mut self) -> Option<String> {
+        let index = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_healthy())
+            .min_by_key(|(_, s)| s.last_used)
+            .map(|(i, _)| i)
+            .or_else(|| {
+                warn!("No healthy sessions available, falling back to the least penalized one");
+                self.sessions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| s.auth_failures < MAX_AUTH_FAILURES)
+                    .min_by_key(|(_, s)| s.flood_wait_until)
+                    .map(|(i, _)| i)
+            })?;
+
+        let session = &mut self.sessions[index];
+        session.last_used = Some(Instant::now());
+        Some(session.session_file.clone())
+    }
+
+    fn find_mut(&mut self, session_file: &str) -> Option<&mut SessionHealth> {
+        self.sessions
+            .iter_mut()
+            .find(|s| s.session_file == session_file)
+    }
+
+    pub fn record_success(&mut self, session_file: &str) {
+        if let Some(session) = self.find_mut(session_file) {
+            session.auth_failures = 0;
+        }
+    }
+
+    pub fn record_flood_wait(&mut self, session_file: &str, seconds: u32) {
+        if let Some(session) = self.find_mut(session_file) {
+            warn!(
+                "Session {} hit FLOOD_WAIT of {}s, rotating away from it",
+                session_file, seconds
+            );
+            session.flood_wait_until = Some(Instant::now() + Duration::from_secs(seconds as u64));
+        }
+    }
+
+    pub fn record_auth_failure(&mut self, session_file: &str) {
+        if let Some(session) = self.find_mut(session_file) {
+            session.auth_failures += 1;
+            warn!(
+                "Session {} failed authorization ({} consecutive failures)",
+                session_file, session.auth_failures
+            );
+        }
+    }
+
+    /// re-validates sessions that have not been checked within the revalidation interval
+    pub async fn revalidate_if_due(&mut self) {
+        for session in self.sessions.iter_mut().filter(|s| s.needs_revalidation()) {
+            match SessionManager::validate_single_session(&session.session_file).await {
+                Ok(true) => {
+                    if session.auth_failures > 0 {
+                        info!("Session {} is valid again", session.session_file);
+                    }
+                    session.auth_failures = 0;
+                }
+                Ok(false) => {
+                    warn!("Session {} failed re-validation", session.session_file);
+                    session.auth_failures = session.auth_failures.max(MAX_AUTH_FAILURES);
+                }
+                Err(e) => {
+                    warn!(
+                        "Session {} re-validation error: {}",
+                        session.session_file, e
+                    );
+                }
+            }
+            session.last_validated = Instant::now();
+        }
+    }
+}
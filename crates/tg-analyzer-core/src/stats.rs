@@ -0,0 +1,88 @@
+//! audience and engagement statistics of a channel, computed from the view and reaction
+//! counters of the posts on its web preview
+
+use crate::web_scraper::ChannelPreview;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// the quantitative side of a channel analysis
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelHealth {
+    // posts the statistics were computed from
+    pub posts: usize,
+    // None with fewer than two dated posts
+    pub posts_per_week: Option<f64>,
+    pub days_since_last_post: Option<i64>,
+    // None when the channel hides view counters
+    pub average_views: Option<u64>,
+    pub median_views: Option<u64>,
+    // average views of a post per subscriber, in percent
+    pub reach_percent: Option<f64>,
+    pub reactions_per_thousand_views: Option<f64>,
+}
+
+impl ChannelHealth {
+    /// statistics of the preview's posts as of `now` (unix time); None without posts
+    pub fn from_preview(preview: &ChannelPreview, now: i64) -> Option<Self> {
+        if preview.posts.is_empty() {
+            return None;
+        }
+
+        let dates: Vec<i64> = preview
+            .posts
+            .iter()
+            .filter_map(|post| post.published_at)
+            .collect();
+        let first = dates.iter().min().copied();
+        let last = dates.iter().max().copied();
+        let posts_per_week = match (first, last) {
+            (Some(first), Some(last)) if last > first => {
+                let weeks = (last - first) as f64 / (7 * SECONDS_PER_DAY) as f64;
+                Some((dates.len() - 1) as f64 / weeks)
+            }
+            _ => None,
+        };
+
+        let mut views: Vec<u64> = preview.posts.iter().filter_map(|post| post.views).collect();
+        views.sort_unstable();
+        let total_views: u64 = views.iter().sum();
+        let average_views = (!views.is_empty()).then(|| total_views / views.len() as u64);
+        let median_views = match views.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(views[len / 2]),
+            len => Some((views[len / 2 - 1] + views[len / 2]) / 2),
+        };
+
+        // reactions only count on posts whose views are known
+        let reactions: u64 = preview
+            .posts
+            .iter()
+            .filter(|post| post.views.is_some())
+            .map(|post| post.reactions)
+            .sum();
+
+        Some(Self {
+            posts: preview.posts.len(),
+            posts_per_week,
+            days_since_last_post: last.map(|last| (now - last).max(0) / SECONDS_PER_DAY),
+            average_views,
+            median_views,
+            reach_percent: average_views
+                .zip(preview.subscribers.filter(|subscribers| *subscribers > 0))
+                .map(|(views, subscribers)| views as f64 * 100.0 / subscribers as f64),
+            reactions_per_thousand_views: (total_views > 0)
+                .then(|| reactions as f64 * 1000.0 / total_views as f64),
+        })
+    }
+}
+
+/// a count the way telegram shows it: 950, 12.3K, 1.2M
+pub fn format_count(count: u64) -> String {
+    let (value, suffix) = match count {
+        0..=999 => return count.to_string(),
+        1_000..=999_949 => (count as f64 / 1_000.0, "K"),
+        _ => (count as f64 / 1_000_000.0, "M"),
+    };
+    let rounded = format!("{:.1}", value);
+    format!("{}{}", rounded.trim_end_matches(".0"), suffix)
+}
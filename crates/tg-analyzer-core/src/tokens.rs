@@ -0,0 +1,43 @@
+//! token estimates for fitting prompts into a model's context; gemini's tokenizer isn't
+//! published, so the o200k encoding stands in for it and the limits keep some headroom
+
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+// every gemini model since 1.5 reads about a million tokens
+const GEMINI_CONTEXT_TOKENS: usize = 1_048_576;
+
+// unknown models are assumed to read this much, so their prompts err on the small side
+const FALLBACK_CONTEXT_TOKENS: usize = 128_000;
+
+/// tokens of the context kept free for the answer, thinking included
+pub const OUTPUT_RESERVE_TOKENS: usize = 32_768;
+
+// share of the context a prompt may fill, for the gap between the estimate and gemini's count
+const CONTEXT_HEADROOM_PERCENT: usize = 90;
+
+/// estimated tokens of `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    static ENCODING: OnceLock<CoreBPE> = OnceLock::new();
+    ENCODING
+        .get_or_init(|| tiktoken_rs::o200k_base().expect("the o200k encoding is bundled"))
+        .encode_ordinary(text)
+        .len()
+}
+
+/// tokens `model` reads at once
+pub fn context_tokens(model: &str) -> usize {
+    if ["gemini-1.5", "gemini-2", "gemini-3"]
+        .iter()
+        .any(|family| model.starts_with(family))
+    {
+        GEMINI_CONTEXT_TOKENS
+    } else {
+        FALLBACK_CONTEXT_TOKENS
+    }
+}
+
+/// estimated tokens a prompt for `model` may take, leaving room for the answer
+pub fn prompt_token_limit(model: &str) -> usize {
+    (context_tokens(model) * CONTEXT_HEADROOM_PERCENT / 100).saturating_sub(OUTPUT_RESERVE_TOKENS)
+}
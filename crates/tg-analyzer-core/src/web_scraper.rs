@@ -5,7 +5,8 @@ use serde_json::Value;
 use std::time::Duration;
 use tokio::time::timeout;
 
-use crate::analysis::MessageDict;
+use crate::analysis::{MessageDict, MIN_TEXT_LENGTH};
+use crate::mock;
 
 #[derive(Debug)]
 pub enum WebScrapingError {
@@ -38,6 +39,155 @@ impl From<reqwest::Error> for WebScrapingError {
     }
 }
 
+/// what the web preview shows about the audience of one post
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostMetrics {
+    // unix time the post was published
+    pub published_at: Option<i64>,
+    // hidden on posts of channels that turned view counters off
+    pub views: Option<u64>,
+    // all reactions on the post, whatever the emoji
+    pub reactions: u64,
+}
+
+/// the texts of the posts on a t.me/s/<channel> page, oldest first
+pub fn recent_post_texts(html_content: &str) -> Vec<String> {
+    let document = Html::parse_document(html_content);
+    let Ok(text_selector) = Selector::parse("div.tgme_widget_message_text") else {
+        return Vec::new();
+    };
+    document
+        .select(&text_selector)
+        .map(|text| text.text().collect::<String>().trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+/// what the channel's web preview shows before anything is charged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelPreview {
+    pub title: String,
+    pub subscribers: Option<u64>,
+    // day of the newest post, yyyy-mm-dd
+    pub last_post_date: Option<String>,
+    // own posts on the first page and how many of them have enough text
+    pub posts_sampled: usize,
+    pub text_posts: usize,
+    // every post on the first page, forwarded ones included, oldest first
+    pub posts: Vec<PostMetrics>,
+}
+
+impl ChannelPreview {
+    /// share of sampled posts with enough text, in percent; None without posts
+    pub fn text_coverage(&self) -> Option<u32> {
+        if self.posts_sampled == 0 {
+            return None;
+        }
+        Some((self.text_posts * 100 / self.posts_sampled) as u32)
+    }
+
+    /// parses the first page of t.me/s/<channel>; None when the page isn't a public
+    /// channel, as telegram redirects users, bots, groups and unknown names elsewhere
+    pub fn parse(html_content: &str) -> Option<Self> {
+        let document = Html::parse_document(html_content);
+        let selector = |css: &str| Selector::parse(css).ok();
+
+        let title = document
+            .select(&selector("div.tgme_channel_info_header_title")?)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+
+        // counters are "12.3K subscribers", "2 photos" and so on
+        let value_selector = selector("span.counter_value")?;
+        let type_selector = selector("span.counter_type")?;
+        let subscribers = document
+            .select(&selector("div.tgme_channel_info_counter")?)
+            .find(|counter| {
+                counter
+                    .select(&type_selector)
+                    .next()
+                    .is_some_and(|kind| kind.text().collect::<String>().contains("subscriber"))
+            })
+            .and_then(|counter| counter.select(&value_selector).next())
+            .and_then(|value| parse_counter(&value.text().collect::<String>()));
+
+        let forwarded_selector = selector("div.tgme_widget_message_forwarded_from")?;
+        let text_selector = selector("div.tgme_widget_message_text")?;
+        let date_selector = selector("a.tgme_widget_message_date time")?;
+        let views_selector = selector("span.tgme_widget_message_views")?;
+        let reaction_selector = selector("span.tgme_reaction")?;
+
+        let mut last_post_date = None;
+        let mut posts_sampled = 0;
+        let mut text_posts = 0;
+        let mut posts = Vec::new();
+        for wrap in document.select(&selector("div.tgme_widget_message_wrap")?) {
+            let datetime = wrap
+                .select(&date_selector)
+                .next()
+                .and_then(|time_elem| time_elem.value().attr("datetime"));
+            // the page lists posts oldest first
+            if let Some(date) = datetime.and_then(|datetime| datetime.get(..10)) {
+                last_post_date = Some(date.to_string());
+            }
+
+            // a reaction reads as its emoji followed by the count, like "👍1.2K"
+            let reactions = wrap
+                .select(&reaction_selector)
+                .filter_map(|reaction| {
+                    let text = reaction.text().collect::<String>();
+                    parse_counter(text.trim_start_matches(|c: char| !c.is_ascii_digit()))
+                })
+                .sum();
+            posts.push(PostMetrics {
+                published_at: datetime
+                    .and_then(|datetime| chrono::DateTime::parse_from_rfc3339(datetime).ok())
+                    .map(|datetime| datetime.timestamp()),
+                views: wrap
+                    .select(&views_selector)
+                    .next()
+                    .and_then(|views| parse_counter(&views.text().collect::<String>())),
+                reactions,
+            });
+
+            if wrap.select(&forwarded_selector).next().is_some() {
+                continue;
+            }
+            posts_sampled += 1;
+            let text_length = wrap.select(&text_selector).next().map_or(0, |text| {
+                text.text().collect::<String>().trim().chars().count()
+            });
+            if text_length >= MIN_TEXT_LENGTH {
+                text_posts += 1;
+            }
+        }
+
+        Some(Self {
+            title,
+            subscribers,
+            last_post_date,
+            posts_sampled,
+            text_posts,
+            posts,
+        })
+    }
+}
+
+// "12.3K" is 12300, "1 234" is 1234
+fn parse_counter(value: &str) -> Option<u64> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let (number, multiplier) = match value.chars().last()? {
+        'K' | 'k' => (&value[..value.len() - 1], 1_000.0),
+        'M' | 'm' => (&value[..value.len() - 1], 1_000_000.0),
+        _ => (value.as_str(), 1.0),
+    };
+    let number: f64 = number.replace(',', ".").parse().ok()?;
+    Some((number * multiplier).round() as u64)
+}
+
 pub struct TelegramWebScraper {
     client: Client,
     cookies_initialized: bool,
@@ -112,13 +262,16 @@ impl TelegramWebScraper {
         Err(last_error.unwrap())
     }
 
-    /// Scrape messages from a Telegram channel with 30-second timeout
+    /// Scrape messages from a Telegram channel with 30-second timeout; returns them with
+    /// the number of posts left out as forwarded or without text and images
     pub async fn scrape_channel_messages(
         &mut self,
         channel_url: &str,
         max_pages: usize,
-    ) -> Result<Vec<MessageDict>, WebScrapingError> {
-        let operation = self.scrape_channel_messages_impl(channel_url, max_pages);
+        // only the posts newer than this id, for a refresh of a cached channel
+        since: Option<i64>,
+    ) -> Result<(Vec<MessageDict>, usize), WebScrapingError> {
+        let operation = self.scrape_channel_messages_impl(channel_url, max_pages, since);
 
         match timeout(Duration::from_secs(30), operation).await {
             Ok(result) => result,
@@ -129,11 +282,66 @@ impl TelegramWebScraper {
         }
     }
 
+    /// fetches the channel's preview from a single page, without the cookie handshake
+    /// and retries of a full scrape, so it can run before the user picks an analysis
+    pub async fn fetch_channel_preview(
+        &self,
+        channel_url: &str,
+    ) -> Result<Option<ChannelPreview>, WebScrapingError> {
+        if mock::enabled() {
+            return Ok(None);
+        }
+        let url = self.normalize_channel_url(channel_url)?;
+        let operation = async {
+            let response = self.client.get(&url).send().await?;
+            if !response.status().is_success() {
+                return Err(WebScrapingError::StatusCodeError(
+                    response.status().as_u16(),
+                ));
+            }
+            Ok(ChannelPreview::parse(&response.text().await?))
+        };
+
+        match timeout(Duration::from_secs(10), operation).await {
+            Ok(result) => result,
+            Err(_) => Err(WebScrapingError::TimeoutError),
+        }
+    }
+
+    /// the texts of the posts on the channel's first page, newest last, from a single
+    /// request; enough to find a code the channel's owner just posted
+    pub async fn fetch_recent_posts(
+        &self,
+        channel_url: &str,
+    ) -> Result<Vec<String>, WebScrapingError> {
+        if mock::enabled() {
+            return Ok(mock::load_fixture(channel_url)
+                .map(|messages| messages.into_iter().filter_map(|m| m.message).collect())
+                .unwrap_or_default());
+        }
+        let url = self.normalize_channel_url(channel_url)?;
+        let operation = async {
+            let response = self.client.get(&url).send().await?;
+            if !response.status().is_success() {
+                return Err(WebScrapingError::StatusCodeError(
+                    response.status().as_u16(),
+                ));
+            }
+            Ok(recent_post_texts(&response.text().await?))
+        };
+
+        match timeout(Duration::from_secs(10), operation).await {
+            Ok(result) => result,
+            Err(_) => Err(WebScrapingError::TimeoutError),
+        }
+    }
+
     async fn scrape_channel_messages_impl(
         &mut self,
         channel_url: &str,
         max_pages: usize,
-    ) -> Result<Vec<MessageDict>, WebScrapingError> {
+        since: Option<i64>,
+    ) -> Result<(Vec<MessageDict>, usize), WebScrapingError> {
         info!("Starting web scraping for channel: {}", channel_url);
 
         let normalized_url = self.normalize_channel_url(channel_url)?;
@@ -153,7 +361,8 @@ impl TelegramWebScraper {
         let html_content = response.text().await?;
         debug!("Initial page content length: {}", html_content.len());
 
-        let (mut messages, last_id) = self.extract_messages_from_html(&html_content)?;
+        let (mut messages, last_id, mut skipped) =
+            self.extract_messages_from_html(&html_content, since)?;
         all_messages.append(&mut messages);
         before_id = last_id;
 
@@ -165,7 +374,8 @@ impl TelegramWebScraper {
 
         // fetch additional pages with pagination
         for page in 1..max_pages {
-            if before_id.is_none() {
+            // the older pages hold nothing newer than `since`
+            if before_id.is_none() || before_id.zip(since).is_some_and(|(id, since)| id <= since) {
                 break;
             }
 
@@ -224,7 +434,9 @@ impl TelegramWebScraper {
                 response_text
             };
 
-            let (mut page_messages, last_id) = self.extract_messages_from_html(&html_content)?;
+            let (mut page_messages, last_id, page_skipped) =
+                self.extract_messages_from_html(&html_content, since)?;
+            skipped += page_skipped;
 
             if page_messages.is_empty() {
                 info!("No more messages found at page {}", page);
@@ -242,15 +454,16 @@ impl TelegramWebScraper {
         }
 
         info!(
-            "Total extracted: {} non-forwarded messages",
-            all_messages.len()
+            "Total extracted: {} non-forwarded messages, skipped {}",
+            all_messages.len(),
+            skipped
         );
-        Ok(all_messages)
+        Ok((all_messages, skipped))
     }
 
     fn normalize_channel_url(&self, channel_url: &str) -> Result<String, WebScrapingError> {
-        let clean_url = if channel_url.starts_with('@') {
-            format!("https://t.me/s/{}/", &channel_url[1..])
+        let clean_url = if let Some(channel_name) = channel_url.strip_prefix('@') {
+            format!("https://t.me/s/{}/", channel_name)
         } else if channel_url.starts_with("https://t.me/") && !channel_url.contains("/s/") {
             // convert t.me/channel to t.me/s/channel/
             let channel_name = channel_url
@@ -304,10 +517,14 @@ impl TelegramWebScraper {
         Ok(())
     }
 
+    /// the page's messages, the oldest message id to paginate from and the number of
+    /// posts skipped as forwarded or without text and images; posts at or below `since`
+    /// are left out without counting
     fn extract_messages_from_html(
         &self,
         html_content: &str,
-    ) -> Result<(Vec<MessageDict>, Option<i64>), WebScrapingError> {
+        since: Option<i64>,
+    ) -> Result<(Vec<MessageDict>, Option<i64>, usize), WebScrapingError> {
         let document = Html::parse_document(html_content);
 
         // css selectors equivalent to Python's BeautifulSoup
@@ -326,8 +543,12 @@ impl TelegramWebScraper {
         let image_selector = Selector::parse("a.tgme_widget_message_photo_wrap")
             .map_err(|e| WebScrapingError::ParseError(format!("Invalid selector: {}", e)))?;
 
+        let date_selector = Selector::parse("a.tgme_widget_message_date time")
+            .map_err(|e| WebScrapingError::ParseError(format!("Invalid selector: {}", e)))?;
+
         let mut messages = Vec::new();
         let mut all_message_ids = Vec::new();
+        let mut skipped = 0;
 
         let message_wraps: Vec<_> = document.select(&message_wrap_selector).collect();
         debug!("Found {} message wraps", message_wraps.len());
@@ -339,7 +560,7 @@ impl TelegramWebScraper {
             if let Some(message_elem) = wrap.select(&data_post_selector).next() {
                 if let Some(data_post) = message_elem.value().attr("data-post") {
                     // data-post format is "channel_name/message_id" or "channel_name/message_idg"
-                    if let Some(post_id_str) = data_post.split('/').last() {
+                    if let Some(post_id_str) = data_post.split('/').next_back() {
                         // remove any non-numeric suffixes like 'g'
                         let numeric_part: String =
                             post_id_str.chars().filter(|c| c.is_ascii_digit()).collect();
@@ -353,11 +574,27 @@ impl TelegramWebScraper {
                 }
             }
 
+            if current_message_id
+                .zip(since)
+                .is_some_and(|(id, since)| id <= since)
+            {
+                continue;
+            }
+
             // check if this is a forwarded message
             if wrap.select(&forwarded_selector).next().is_some() {
+                skipped += 1;
                 continue; // skip forwarded messages
             }
 
+            // the datetime attribute is iso 8601, keep the day as the api backend does
+            let date = wrap
+                .select(&date_selector)
+                .next()
+                .and_then(|time_elem| time_elem.value().attr("datetime"))
+                .and_then(|datetime| datetime.get(..10))
+                .map(str::to_string);
+
             // extract images
             let mut image_urls = Vec::new();
             for image_elem in wrap.select(&image_selector) {
@@ -391,22 +628,32 @@ impl TelegramWebScraper {
                     .to_string();
                 if (!text.is_empty() || !image_urls.is_empty()) && current_message_id.is_some() {
                     messages.push(MessageDict {
-                        date: None, // date extraction can be added later if needed
+                        id: current_message_id,
+                        date,
                         message: Some(text),
                         images: if image_urls.is_empty() {
                             None
                         } else {
                             Some(image_urls)
                         },
+                        // the web preview only serves channels, which have no topics
+                        thread_id: None,
                     });
+                } else {
+                    skipped += 1;
                 }
             } else if !image_urls.is_empty() && current_message_id.is_some() {
                 // message with only images, no text
                 messages.push(MessageDict {
-                    date: None,
+                    id: current_message_id,
+                    date,
                     message: None,
                     images: Some(image_urls),
+                    thread_id: None,
                 });
+            } else {
+                // videos, files and polls leave nothing to analyze
+                skipped += 1;
             }
         }
 
@@ -422,6 +669,6 @@ impl TelegramWebScraper {
             None
         };
 
-        Ok((messages, last_message_id))
+        Ok((messages, last_message_id, skipped))
     }
 }
@@ -0,0 +1,241 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::collections::HashSet;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, RwLock};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use crate::analysis::{AnalysisDepth, AnalysisEngine};
+use crate::backend_config::BackendPolicy;
+use crate::cache::CacheManager;
+use crate::error::AppError;
+use crate::mock;
+use crate::retry_budget::RetryBudget;
+use crate::session_manager::SessionManager;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+type Job = Box<dyn for<'a> FnOnce(&'a mut AnalysisEngine) -> BoxFuture<'a, ()> + Send>;
+
+/// splits the session files between `workers` engines round robin, so no two engines
+/// connect with the same session; never more engines than sessions
+pub fn assign_sessions(session_files: Vec<String>, workers: usize) -> Vec<Vec<String>> {
+    let workers = workers.clamp(1, session_files.len().max(1));
+    let mut assigned = vec![Vec::new(); workers];
+    for (i, session_file) in session_files.into_iter().enumerate() {
+        assigned[i % workers].push(session_file);
+    }
+    assigned.retain(|sessions| !sessions.is_empty());
+    assigned
+}
+
+/// engines that fetch side by side, each with its own telegram sessions and client;
+/// jobs wait in one queue and go to whichever worker is free first
+pub struct AnalysisWorkers {
+    jobs: mpsc::UnboundedSender<Job>,
+    // applied by every worker before its next job
+    backend_policy: Arc<RwLock<BackendPolicy>>,
+    idle: Arc<AtomicUsize>,
+    // healthy telegram sessions of each worker, as of its last job
+    healthy_sessions: Arc<Vec<AtomicUsize>>,
+    size: usize,
+    // corpora being fetched again in the background
+    refreshing: Arc<StdMutex<HashSet<String>>>,
+    // cache reads and writes don't need an engine
+    pub cache: CacheManager,
+}
+
+impl AnalysisWorkers {
+    /// starts one worker per session, or ANALYSIS_WORKERS workers sharing the sessions
+    pub fn start(pool: Arc<Pool>) -> Result<Self, AppError> {
+        // mock mode reads fixtures, its workers run without sessions
+        let session_files = if mock::enabled() {
+            Vec::new()
+        } else {
+            SessionManager::discover_sessions().map_err(AppError::telegram)?
+        };
+        let workers = match env::var("ANALYSIS_WORKERS") {
+            Ok(value) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|workers| *workers > 0)
+                .ok_or_else(|| {
+                    AppError::Validation("ANALYSIS_WORKERS must be a positive integer".into())
+                })?,
+            Err(_) => session_files.len(),
+        };
+
+        let backend_policy = Arc::new(RwLock::new(BackendPolicy::from_env()));
+        let idle = Arc::new(AtomicUsize::new(0));
+        let (jobs, receiver) = mpsc::unbounded_channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let assigned = if mock::enabled() {
+            vec![Vec::new(); workers.max(1)]
+        } else {
+            assign_sessions(session_files, workers)
+        };
+        let size = assigned.len();
+        if size == 0 {
+            return Err(AppError::telegram(
+                "No session files found in sessions/ directory",
+            ));
+        }
+        let healthy_sessions = Arc::new(
+            assigned
+                .iter()
+                .map(|sessions| AtomicUsize::new(sessions.len()))
+                .collect::<Vec<_>>(),
+        );
+        for (worker, sessions) in assigned.into_iter().enumerate() {
+            info!(
+                "Starting analysis worker {} with {} sessions",
+                worker,
+                sessions.len()
+            );
+            let engine = AnalysisEngine::with_sessions(pool.clone(), sessions)?;
+            tokio::spawn(Self::work(
+                worker,
+                engine,
+                receiver.clone(),
+                backend_policy.clone(),
+                idle.clone(),
+                healthy_sessions.clone(),
+            ));
+        }
+
+        Ok(Self {
+            jobs,
+            backend_policy,
+            idle,
+            healthy_sessions,
+            size,
+            refreshing: Arc::new(StdMutex::new(HashSet::new())),
+            cache: CacheManager::new(pool),
+        })
+    }
+
+    async fn work(
+        worker: usize,
+        mut engine: AnalysisEngine,
+        receiver: Arc<Mutex<mpsc::UnboundedReceiver<Job>>>,
+        backend_policy: Arc<RwLock<BackendPolicy>>,
+        idle: Arc<AtomicUsize>,
+        healthy_sessions: Arc<Vec<AtomicUsize>>,
+    ) {
+        loop {
+            idle.fetch_add(1, Ordering::Relaxed);
+            // only the worker holding the lock waits for a job, the others wait for it
+            let job = receiver.lock().await.recv().await;
+            idle.fetch_sub(1, Ordering::Relaxed);
+            let Some(job) = job else {
+                info!("Analysis worker {} stopped", worker);
+                return;
+            };
+
+            let policy = *backend_policy.read().unwrap_or_else(|e| e.into_inner());
+            if engine.backend_policy() != policy {
+                engine.set_backend_policy(policy);
+            }
+            job(&mut engine).await;
+            healthy_sessions[worker].store(engine.healthy_sessions(), Ordering::Relaxed);
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// workers waiting for a job
+    pub fn idle(&self) -> usize {
+        self.idle.load(Ordering::Relaxed)
+    }
+
+    /// telegram sessions of all workers that are neither flood waited nor logged out,
+    /// as of each worker's last job
+    pub fn healthy_sessions(&self) -> usize {
+        self.healthy_sessions
+            .iter()
+            .map(|healthy| healthy.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// runs `job` on the first free worker and waits for its result
+    pub async fn run<T, F>(&self, job: F) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: for<'a> FnOnce(&'a mut AnalysisEngine) -> BoxFuture<'a, Result<T, AppError>>
+            + Send
+            + 'static,
+    {
+        let (sender, result) = oneshot::channel();
+        let job: Job = Box::new(move |engine| {
+            Box::pin(async move {
+                // the caller may have given up waiting
+                let _ = sender.send(job(engine).await);
+            })
+        });
+        self.jobs
+            .send(job)
+            .map_err(|_| AppError::telegram("Analysis workers have stopped"))?;
+        result.await.map_err(|_| {
+            warn!("An analysis worker dropped a job without finishing it");
+            AppError::telegram("Analysis worker stopped during the job")
+        })?
+    }
+
+    /// fetches the channel again on the next free worker without waiting for it, once an
+    /// analysis was served its stale cached corpus; one refresh per corpus at a time
+    pub fn refresh_in_background(&self, channel_name: &str, depth: AnalysisDepth) {
+        let cache_name = depth.cache_name(channel_name);
+        let refreshing = self.refreshing.clone();
+        if !refreshing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(cache_name.clone())
+        {
+            return;
+        }
+
+        let channel_name = channel_name.to_string();
+        let job: Job = Box::new(move |engine| {
+            Box::pin(async move {
+                let budget = RetryBudget::from_env();
+                if let Err(e) = engine.refresh_messages(&channel_name, depth, &budget).await {
+                    warn!(
+                        "Background refresh of channel {} failed, its cache stays: {}",
+                        channel_name, e
+                    );
+                }
+                refreshing
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(&cache_name);
+            })
+        });
+        if self.jobs.send(job).is_err() {
+            warn!("Analysis workers have stopped, skipping a background refresh");
+        }
+    }
+
+    pub fn backend_policy(&self) -> BackendPolicy {
+        *self
+            .backend_policy
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// switches the backends of every worker from its next job on
+    pub fn set_backend_policy(&self, policy: BackendPolicy) {
+        info!(
+            "Backend policy of the analysis workers set to {}",
+            policy.as_str()
+        );
+        *self
+            .backend_policy
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+}
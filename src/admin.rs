@@ -0,0 +1,342 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+use crate::error::AppError;
+
+#[derive(Debug)]
+pub enum AdminError {
+    Database(Box<dyn Error + Send + Sync>),
+    // the actor has no admin role at all
+    NotAdmin(i64),
+    Forbidden {
+        role: AdminRole,
+        action: AdminAction,
+    },
+    // owners from ADMIN_USER_IDS can't be changed from the bot
+    BootstrapOwner(i64),
+}
+
+impl fmt::Display for AdminError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdminError::Database(e) => write!(f, "Database error: {}", e),
+            AdminError::NotAdmin(telegram_user_id) => {
+                write!(f, "User {} is not an admin", telegram_user_id)
+            }
+            AdminError::Forbidden { role, action } => write!(
+                f,
+                "Role {} is not allowed to {}",
+                role.as_str(),
+                action.as_str()
+            ),
+            AdminError::BootstrapOwner(telegram_user_id) => write!(
+                f,
+                "User {} is an owner through ADMIN_USER_IDS",
+                telegram_user_id
+            ),
+        }
+    }
+}
+
+impl Error for AdminError {}
+
+impl From<tokio_postgres::Error> for AdminError {
+    fn from(err: tokio_postgres::Error) -> Self {
+        AdminError::Database(Box::new(err))
+    }
+}
+
+impl From<deadpool_postgres::PoolError> for AdminError {
+    fn from(err: deadpool_postgres::PoolError) -> Self {
+        AdminError::Database(Box::new(err))
+    }
+}
+
+impl From<AdminError> for AppError {
+    fn from(err: AdminError) -> Self {
+        match err {
+            AdminError::Database(e) => AppError::Db(e),
+            _ => AppError::Validation(err.to_string()),
+        }
+    }
+}
+
+/// admin roles stored in admin_roles; owners can do everything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminRole {
+    Owner,
+    Support,
+    Marketing,
+}
+
+impl AdminRole {
+    pub const ALL: [AdminRole; 3] = [AdminRole::Owner, AdminRole::Support, AdminRole::Marketing];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminRole::Owner => "owner",
+            AdminRole::Support => "support",
+            AdminRole::Marketing => "marketing",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|role| role.as_str() == code)
+    }
+
+    /// permission table of the admin commands
+    pub fn allows(&self, action: AdminAction) -> bool {
+        match self {
+            AdminRole::Owner => true,
+            AdminRole::Support => matches!(action, AdminAction::Refund),
+            AdminRole::Marketing => matches!(action, AdminAction::Announce),
+        }
+    }
+}
+
+/// privileged actions, each checked against the actor's role and audited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminAction {
+    Refund,
+    ManageRoles,
+    ViewAuditLog,
+    Announce,
+    SetBackend,
+    Requeue,
+    ViewFeedback,
+    ViewLlmCosts,
+    ConfigureReferrals,
+    ReviewReferrals,
+    ManageWarmup,
+    ReviewFlaggedContent,
+    ManageBlocklist,
+    ViewRevenue,
+    ManagePackages,
+    ReviewTrials,
+    ReloadConfig,
+}
+
+impl AdminAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminAction::Refund => "refund",
+            AdminAction::ManageRoles => "manage_roles",
+            AdminAction::ViewAuditLog => "view_audit_log",
+            AdminAction::Announce => "announce",
+            AdminAction::SetBackend => "set_backend",
+            AdminAction::Requeue => "requeue",
+            AdminAction::ViewFeedback => "view_feedback",
+            AdminAction::ViewLlmCosts => "view_llm_costs",
+            AdminAction::ConfigureReferrals => "configure_referrals",
+            AdminAction::ReviewReferrals => "review_referrals",
+            AdminAction::ManageWarmup => "manage_warmup",
+            AdminAction::ReviewFlaggedContent => "review_flagged_content",
+            AdminAction::ManageBlocklist => "manage_blocklist",
+            AdminAction::ViewRevenue => "view_revenue",
+            AdminAction::ManagePackages => "manage_packages",
+            AdminAction::ReviewTrials => "review_trials",
+            AdminAction::ReloadConfig => "reload_config",
+        }
+    }
+}
+
+/// how a privileged action ended, as recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Succeeded,
+    Failed,
+    Denied,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Succeeded => "succeeded",
+            AuditOutcome::Failed => "failed",
+            AuditOutcome::Denied => "denied",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub actor_telegram_user_id: i64,
+    pub action: String,
+    pub arguments: String,
+    pub outcome: String,
+    pub created_at: String, // formatted by postgres as YYYY-MM-DD HH24:MI (UTC)
+}
+
+pub struct AdminManager {
+    pool: Arc<Pool>,
+    // ADMIN_USER_IDS, always owners so a fresh database can't lock everyone out
+    bootstrap_owners: Vec<i64>,
+}
+
+impl AdminManager {
+    pub fn new(pool: Arc<Pool>, bootstrap_owners: Vec<i64>) -> Self {
+        Self {
+            pool,
+            bootstrap_owners,
+        }
+    }
+
+    /// reads the comma-separated ADMIN_USER_IDS owners
+    pub fn from_env(pool: Arc<Pool>) -> Self {
+        let bootstrap_owners = std::env::var("ADMIN_USER_IDS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|id| id.trim().parse::<i64>().ok())
+            .collect::<Vec<_>>();
+        info!(
+            "Loaded {} bootstrap owners from ADMIN_USER_IDS",
+            bootstrap_owners.len()
+        );
+        Self::new(pool, bootstrap_owners)
+    }
+
+    pub async fn role_of(&self, telegram_user_id: i64) -> Result<Option<AdminRole>, AdminError> {
+        if self.bootstrap_owners.contains(&telegram_user_id) {
+            return Ok(Some(AdminRole::Owner));
+        }
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT role FROM admin_roles WHERE telegram_user_id = $1",
+                &[&telegram_user_id],
+            )
+            .await?;
+        Ok(row.and_then(|row| AdminRole::from_code(row.get(0))))
+    }
+
+    /// checks the actor may perform the action; refusals to admins are audited,
+    /// non-admins are rejected without a trace so the commands stay hidden
+    pub async fn authorize(
+        &self,
+        telegram_user_id: i64,
+        action: AdminAction,
+        arguments: &str,
+    ) -> Result<AdminRole, AdminError> {
+        let role = self
+            .role_of(telegram_user_id)
+            .await?
+            .ok_or(AdminError::NotAdmin(telegram_user_id))?;
+        if role.allows(action) {
+            return Ok(role);
+        }
+        warn!(
+            "Admin {} ({}) is not allowed to {}",
+            telegram_user_id,
+            role.as_str(),
+            action.as_str()
+        );
+        self.record(telegram_user_id, action, arguments, AuditOutcome::Denied)
+            .await?;
+        Err(AdminError::Forbidden { role, action })
+    }
+
+    /// appends a privileged action to the audit log
+    pub async fn record(
+        &self,
+        telegram_user_id: i64,
+        action: AdminAction,
+        arguments: &str,
+        outcome: AuditOutcome,
+    ) -> Result<(), AdminError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO admin_audit_log (actor_telegram_user_id, action, arguments, outcome) VALUES ($1, $2, $3, $4)",
+                &[&telegram_user_id, &action.as_str(), &arguments, &outcome.as_str()],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// grants a role, or removes it with None
+    pub async fn set_role(
+        &self,
+        granted_by: i64,
+        telegram_user_id: i64,
+        role: Option<AdminRole>,
+    ) -> Result<(), AdminError> {
+        if self.bootstrap_owners.contains(&telegram_user_id) {
+            return Err(AdminError::BootstrapOwner(telegram_user_id));
+        }
+        let client = self.pool.get().await?;
+        match role {
+            Some(role) => {
+                client
+                    .execute(
+                        "INSERT INTO admin_roles (telegram_user_id, role, granted_by) VALUES ($1, $2, $3)
+                         ON CONFLICT (telegram_user_id) DO UPDATE SET role = $2, granted_by = $3, created_at = NOW()",
+                        &[&telegram_user_id, &role.as_str(), &granted_by],
+                    )
+                    .await?;
+            }
+            None => {
+                client
+                    .execute(
+                        "DELETE FROM admin_roles WHERE telegram_user_id = $1",
+                        &[&telegram_user_id],
+                    )
+                    .await?;
+            }
+        }
+        info!(
+            "Admin {} set role of {} to {}",
+            granted_by,
+            telegram_user_id,
+            role.map(|role| role.as_str()).unwrap_or("none")
+        );
+        Ok(())
+    }
+
+    /// telegram ids of every owner, the ADMIN_USER_IDS ones first
+    pub async fn owner_ids(&self) -> Result<Vec<i64>, AdminError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT telegram_user_id FROM admin_roles WHERE role = $1 ORDER BY telegram_user_id",
+                &[&AdminRole::Owner.as_str()],
+            )
+            .await?;
+        let mut owners = self.bootstrap_owners.clone();
+        for row in rows {
+            let telegram_user_id: i64 = row.get(0);
+            if !owners.contains(&telegram_user_id) {
+                owners.push(telegram_user_id);
+            }
+        }
+        Ok(owners)
+    }
+
+    /// latest audit log entries, newest first
+    pub async fn recent_actions(&self, limit: i64) -> Result<Vec<AuditEntry>, AdminError> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT actor_telegram_user_id, action, arguments, outcome,
+                        TO_CHAR(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM admin_audit_log
+                 ORDER BY id DESC
+                 LIMIT $1",
+                &[&limit],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| AuditEntry {
+                actor_telegram_user_id: row.get(0),
+                action: row.get(1),
+                arguments: row.get(2),
+                outcome: row.get(3),
+                created_at: row.get(4),
+            })
+            .collect())
+    }
+}
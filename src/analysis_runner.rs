@@ -0,0 +1,888 @@
+use log::{error, info, warn};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+use crate::analysis::{
+    invite_hash, is_self_corpus, roast_battle_members, AnalysisData, AnalysisDepth, CorpusKind,
+    ForumTopic,
+};
+use crate::backend_config::BackendType;
+use crate::bot::ChannelLocks;
+use crate::cache::AnalysisResult;
+use crate::channel_stats::ChannelStatsManager;
+use crate::circuit_breaker::llm_breaker;
+use crate::error::AppError;
+use crate::facts::QuickFacts;
+use crate::in_flight::{Flight, InFlight};
+use crate::llm::analysis_query::{
+    needs_map_reduce, query_and_parse_analysis, query_and_parse_large_analysis,
+    query_and_parse_section, SummaryProgress,
+};
+use crate::llm::language_check::{enforce_output_language, LanguageTarget};
+use crate::llm::routing::{model_routing, ModelRoute};
+use crate::llm::safety::{record_flags, redact, review_output, FlagContext, SafetyAction};
+use crate::llm::trends_query::query_trends;
+use crate::llm::ModelTier;
+use crate::llm_budget::LlmBudget;
+use crate::metrics::{metrics, CacheKind};
+use crate::observability;
+use crate::prompts::analysis::{
+    generate_analysis_prompt, generate_cross_group_prompt, generate_profile_prompt,
+    generate_roast_battle_prompt, generate_section_prompt, OutputLanguage,
+};
+use crate::prompts::trends::generate_trends_prompt;
+use crate::prompts::versions::{PromptExperiment, PromptVersion, BASE_PROMPT_VERSION};
+use crate::retry_budget::RetryBudget;
+use crate::user_manager::{UserManager, UserManagerError};
+use crate::workers::AnalysisWorkers;
+
+/// how the llm is asked for the professional, personal and roast analyses of a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnalysisMode {
+    /// one call answers all three types, and its cached answer serves each of them
+    #[default]
+    Combined,
+    /// a call asks only for the requested type, a shorter prompt and answer
+    Single,
+}
+
+impl AnalysisMode {
+    /// ANALYSIS_MODE, combined unless set to "single"
+    pub fn from_env() -> Self {
+        match env::var("ANALYSIS_MODE") {
+            Ok(mode) if mode.trim().eq_ignore_ascii_case("single") => AnalysisMode::Single,
+            _ => AnalysisMode::Combined,
+        }
+    }
+}
+
+/// a pending analysis record to run, independent of the front end that delivers it
+#[derive(Debug, Clone)]
+pub struct AnalysisJob {
+    pub analysis_id: i32,
+    pub user_id: i32,
+    pub channel_name: String,
+    pub analysis_type: String,
+    pub depth: AnalysisDepth,
+    // credits charged on completion, fixed when the analysis was requested
+    pub credits: i32,
+    pub focus: Option<String>,
+    // forum topic a group analysis is limited to
+    pub topic: Option<ForumTopic>,
+    // the user confirmed analyzing a channel with little text
+    pub allow_low_text: bool,
+    // the model whose answer this analysis is a second opinion on
+    pub second_opinion_on: Option<String>,
+}
+
+/// the stage an analysis run stopped at, so each front end can report it its own way
+#[derive(Debug)]
+pub enum AnalysisRunError {
+    Prepare(AppError),
+    NoMessages,
+    Prompt(Box<dyn Error + Send + Sync>),
+    // today's llm spend reached the daily budget and the result isn't cached
+    BudgetExceeded,
+    Llm(AppError),
+    Complete(UserManagerError),
+}
+
+impl fmt::Display for AnalysisRunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisRunError::Prepare(e) => write!(f, "Failed to prepare analysis data: {}", e),
+            AnalysisRunError::NoMessages => write!(f, "No messages found in channel"),
+            AnalysisRunError::Prompt(e) => write!(f, "Failed to generate analysis prompt: {}", e),
+            AnalysisRunError::BudgetExceeded => write!(f, "Daily LLM budget exhausted"),
+            AnalysisRunError::Llm(e) => write!(f, "Failed to query LLM: {}", e),
+            AnalysisRunError::Complete(e) => write!(f, "Failed to complete analysis: {}", e),
+        }
+    }
+}
+
+impl Error for AnalysisRunError {}
+
+impl AnalysisJob {
+    /// what the job reads and asks the llm
+    pub fn target(&self) -> AnalysisTarget {
+        AnalysisTarget {
+            channel_name: self.channel_name.clone(),
+            analysis_type: self.analysis_type.clone(),
+            depth: self.depth,
+            focus: self.focus.clone(),
+            topic: self.topic.clone(),
+            allow_low_text: self.allow_low_text,
+        }
+    }
+}
+
+/// what an analysis reads and asks the llm, apart from the record and the user it's for
+#[derive(Debug, Clone)]
+pub struct AnalysisTarget {
+    pub channel_name: String,
+    pub analysis_type: String,
+    pub depth: AnalysisDepth,
+    pub focus: Option<String>,
+    pub topic: Option<ForumTopic>,
+    pub allow_low_text: bool,
+}
+
+impl AnalysisRunError {
+    /// short label of the failed stage, used in metrics
+    pub fn stage(&self) -> &'static str {
+        match self {
+            AnalysisRunError::Prepare(_) => "prepare",
+            AnalysisRunError::NoMessages => "no_messages",
+            AnalysisRunError::Prompt(_) => "prompt",
+            AnalysisRunError::BudgetExceeded => "llm_budget",
+            AnalysisRunError::Llm(_) => "llm",
+            AnalysisRunError::Complete(_) => "complete",
+        }
+    }
+}
+
+/// a finished analysis and the user's balance after paying for it
+#[derive(Debug, Clone)]
+pub struct AnalysisOutcome {
+    pub result: AnalysisResult,
+    pub remaining_credits: i32,
+    // a user's profile rather than a channel
+    pub kind: CorpusKind,
+}
+
+/// fetches messages, queries the llm (or reuses a cached result) and charges the user;
+/// shared by the bot and the rest api
+pub async fn run_analysis(
+    analysis_workers: &AnalysisWorkers,
+    user_manager: &UserManager,
+    channel_stats: &ChannelStatsManager,
+    channel_locks: &ChannelLocks,
+    llm_budget: &LlmBudget,
+    job: &AnalysisJob,
+    progress: Option<&SummaryProgress>,
+) -> Result<AnalysisOutcome, AnalysisRunError> {
+    metrics().analysis_started();
+    let outcome = run_analysis_stages(
+        analysis_workers,
+        user_manager,
+        channel_stats,
+        channel_locks,
+        llm_budget,
+        job,
+        progress,
+    )
+    .await;
+    match &outcome {
+        Ok(_) => metrics().analysis_completed(),
+        Err(e) => {
+            metrics().analysis_failed(e.stage());
+            observability::report_analysis_failure(job, e);
+        }
+    }
+    outcome
+}
+
+async fn run_analysis_stages(
+    analysis_workers: &AnalysisWorkers,
+    user_manager: &UserManager,
+    channel_stats: &ChannelStatsManager,
+    channel_locks: &ChannelLocks,
+    llm_budget: &LlmBudget,
+    job: &AnalysisJob,
+    progress: Option<&SummaryProgress>,
+) -> Result<AnalysisOutcome, AnalysisRunError> {
+    // a missing preference shouldn't block the analysis, fall back to auto
+    let tier = user_manager
+        .get_model_tier(job.user_id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to load model tier for user {}: {}", job.user_id, e);
+            ModelTier::Auto
+        });
+    let output_language = user_manager
+        .get_output_language(job.user_id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to load output language for user {}: {}",
+                job.user_id, e
+            );
+            OutputLanguage::Channel
+        });
+
+    let target = job.target();
+    // the user's experiment arm is part of the key, so only requests that would send the
+    // same prompt wait for each other
+    let experiment_version = PromptExperiment::from_env().version_for(job.user_id);
+    let flight_key = format!(
+        "{:?}|{:?}|{:?}|{}|{:?}",
+        target, tier, output_language, experiment_version.id, job.second_opinion_on
+    );
+    let shared = match in_flight_analyses().join(&flight_key) {
+        Flight::Lead(lead) => {
+            let shared = Arc::new(
+                produce_analysis(
+                    analysis_workers,
+                    user_manager,
+                    channel_locks,
+                    llm_budget,
+                    job,
+                    &target,
+                    tier,
+                    output_language,
+                    experiment_version,
+                    progress,
+                )
+                .await?,
+            );
+            lead.complete(shared.clone());
+            shared
+        }
+        Flight::Follow(follower) => {
+            info!(
+                "Waiting for the {} analysis of channel {} already in progress",
+                job.analysis_type, job.channel_name
+            );
+            match follower.wait().await {
+                Some(shared) => {
+                    record_analysis_details(
+                        user_manager,
+                        job.analysis_id,
+                        shared.backend,
+                        &shared.cache_key,
+                        shared.prompt_version,
+                    )
+                    .await;
+                    shared
+                }
+                // the leading request failed or was cancelled, this one tries on its own
+                None => Arc::new(
+                    produce_analysis(
+                        analysis_workers,
+                        user_manager,
+                        channel_locks,
+                        llm_budget,
+                        job,
+                        &target,
+                        tier,
+                        output_language,
+                        experiment_version,
+                        progress,
+                    )
+                    .await?,
+                ),
+            }
+        }
+    };
+    let result = shared.result.clone();
+    let profile = shared.kind == CorpusKind::Profile;
+
+    // ATOMIC OPERATION: consume credit + mark completed (protected from shutdown)
+    let remaining_credits = user_manager
+        .atomic_complete_analysis(job.analysis_id, job.user_id, job.credits)
+        .await
+        .map_err(AnalysisRunError::Complete)?;
+
+    // which model answered, so a second opinion can ask another one
+    if let Some(model) = &result.model {
+        if let Err(e) = user_manager
+            .set_analysis_model(job.analysis_id, model)
+            .await
+        {
+            warn!(
+                "Failed to store the model of analysis {}: {}",
+                job.analysis_id, e
+            );
+        }
+    }
+
+    // remembered so the user can claim a free regeneration
+    if result.partial {
+        if let Err(e) = user_manager.mark_analysis_partial(job.analysis_id).await {
+            error!(
+                "Failed to mark analysis {} as partial: {}",
+                job.analysis_id, e
+            );
+        }
+    }
+
+    // the leaderboard is best effort, the user has already paid for the analysis; forwarded
+    // messages are nobody's channel, private channels aren't ours to rank and people
+    // aren't channels. a second opinion is the same analysis again, it isn't counted twice
+    let score = result.report.as_ref().map(|report| report.scores.average());
+    if !is_self_corpus(&job.channel_name)
+        && invite_hash(&job.channel_name).is_none()
+        && !profile
+        && job.second_opinion_on.is_none()
+    {
+        if let Err(e) = channel_stats
+            .record_analysis(&job.channel_name, score)
+            .await
+        {
+            error!(
+                "Failed to record channel stats for analysis {}: {}",
+                job.analysis_id, e
+            );
+        }
+    }
+
+    Ok(AnalysisOutcome {
+        result,
+        remaining_credits,
+        kind: shared.kind,
+    })
+}
+
+/// what one request fetched and got from the llm, handed to the requests that waited for it
+struct SharedAnalysis {
+    backend: Option<BackendType>,
+    kind: CorpusKind,
+    cache_key: String,
+    prompt_version: &'static PromptVersion,
+    result: AnalysisResult,
+}
+
+/// analyses in progress by what they fetch and ask, so the same request from another user
+/// waits for the running one instead of fetching and querying the llm again
+fn in_flight_analyses() -> &'static InFlight<Arc<SharedAnalysis>> {
+    static IN_FLIGHT: OnceLock<InFlight<Arc<SharedAnalysis>>> = OnceLock::new();
+    IN_FLIGHT.get_or_init(InFlight::new)
+}
+
+/// fetches the messages and gets the llm answer for them, recording on the way where the
+/// analysis got its messages and answer from
+#[allow(clippy::too_many_arguments)]
+async fn produce_analysis(
+    analysis_workers: &AnalysisWorkers,
+    user_manager: &UserManager,
+    channel_locks: &ChannelLocks,
+    llm_budget: &LlmBudget,
+    job: &AnalysisJob,
+    target: &AnalysisTarget,
+    tier: ModelTier,
+    output_language: OutputLanguage,
+    experiment_version: &'static PromptVersion,
+    progress: Option<&SummaryProgress>,
+) -> Result<SharedAnalysis, AnalysisRunError> {
+    // fetch and llm stages share one budget, so their retries can't add up unbounded
+    let budget = Arc::new(RetryBudget::from_env());
+
+    let analysis_data =
+        fetch_analysis_data(analysis_workers, target, tier, output_language, &budget).await?;
+
+    if analysis_data.messages.is_empty() {
+        return Err(AnalysisRunError::NoMessages);
+    }
+
+    // trends, user profiles, cross-group messages and roast battles have a single prompt, so
+    // only the other types of channels take part in prompt experiments
+    let own_prompt = analysis_data.kind != CorpusKind::Channel;
+    let prompt_version = if job.analysis_type == "trends" || own_prompt {
+        PromptVersion::base()
+    } else {
+        experiment_version
+    };
+    // a second opinion asks the whole analysis of a model of the other tier and keeps its
+    // answer apart from the first one
+    let (route, mode) = match &job.second_opinion_on {
+        Some(first_model) => (
+            ModelRoute::second_opinion(first_model),
+            AnalysisMode::Combined,
+        ),
+        None => (
+            model_routing().route(&job.analysis_type, tier),
+            AnalysisMode::from_env(),
+        ),
+    };
+    let (mut cache_key, section) = answer_cache_key(
+        analysis_workers,
+        &analysis_data,
+        &job.analysis_type,
+        prompt_version,
+        mode,
+    )
+    .await;
+    if let (Some(_), Some(model)) = (&job.second_opinion_on, route.models.first()) {
+        cache_key = format!("{}:m{}", cache_key, model);
+    }
+
+    record_analysis_details(
+        user_manager,
+        job.analysis_id,
+        analysis_data.backend,
+        &cache_key,
+        prompt_version,
+    )
+    .await;
+
+    let result = query_llm(
+        analysis_workers,
+        channel_locks,
+        llm_budget,
+        target,
+        &analysis_data,
+        &route,
+        output_language,
+        prompt_version,
+        &cache_key,
+        section,
+        &budget,
+        progress,
+    )
+    .await?;
+    Ok(SharedAnalysis {
+        backend: analysis_data.backend,
+        kind: analysis_data.kind,
+        cache_key,
+        prompt_version,
+        result,
+    })
+}
+
+/// stores the backend, cache key and prompt version of an analysis, best effort
+async fn record_analysis_details(
+    user_manager: &UserManager,
+    analysis_id: i32,
+    backend: Option<BackendType>,
+    cache_key: &str,
+    prompt_version: &PromptVersion,
+) {
+    if let Some(backend) = backend {
+        if let Err(e) = user_manager
+            .set_analysis_backend(analysis_id, backend)
+            .await
+        {
+            warn!("Failed to store backend of analysis {}: {}", analysis_id, e);
+        }
+    }
+
+    // remember where the result lives so the analysis can be shared later
+    if let Err(e) = user_manager
+        .set_analysis_cache_key(analysis_id, cache_key)
+        .await
+    {
+        warn!(
+            "Failed to store cache key for analysis {}: {}",
+            analysis_id, e
+        );
+    }
+
+    if let Err(e) = user_manager
+        .set_analysis_prompt_version(analysis_id, prompt_version.id)
+        .await
+    {
+        warn!(
+            "Failed to store prompt version of analysis {}: {}",
+            analysis_id, e
+        );
+    }
+}
+
+/// runs an analysis outside of any account, for operators: nothing is recorded or charged
+/// and the default model tier and output language apply; messages and llm answers share
+/// the bot's caches
+pub async fn run_unrecorded_analysis(
+    analysis_workers: &AnalysisWorkers,
+    channel_locks: &ChannelLocks,
+    llm_budget: &LlmBudget,
+    target: &AnalysisTarget,
+) -> Result<AnalysisResult, AnalysisRunError> {
+    let (tier, output_language) = (ModelTier::Auto, OutputLanguage::Channel);
+    let budget = Arc::new(RetryBudget::from_env());
+    let analysis_data =
+        fetch_analysis_data(analysis_workers, target, tier, output_language, &budget).await?;
+    if analysis_data.messages.is_empty() {
+        return Err(AnalysisRunError::NoMessages);
+    }
+
+    let prompt_version = PromptVersion::base();
+    // always combined, so one answer warms every type whatever ANALYSIS_MODE says
+    let cache_key = result_cache_key(&analysis_data, &target.analysis_type, prompt_version);
+    query_llm(
+        analysis_workers,
+        channel_locks,
+        llm_budget,
+        target,
+        &analysis_data,
+        &model_routing().route(&target.analysis_type, tier),
+        output_language,
+        prompt_version,
+        &cache_key,
+        None,
+        &budget,
+        None,
+    )
+    .await
+}
+
+/// loads or fetches the messages to analyze on the first free worker, other analyses
+/// fetch on the others meanwhile
+async fn fetch_analysis_data(
+    analysis_workers: &AnalysisWorkers,
+    target: &AnalysisTarget,
+    tier: ModelTier,
+    output_language: OutputLanguage,
+    budget: &Arc<RetryBudget>,
+) -> Result<AnalysisData, AnalysisRunError> {
+    let fetch_target = target.clone();
+    let fetch_budget = budget.clone();
+    let analysis_data = analysis_workers
+        .run(move |engine| {
+            Box::pin(async move {
+                engine
+                    .prepare_analysis_data(
+                        &fetch_target.channel_name,
+                        fetch_target.focus.as_deref(),
+                        fetch_target.topic.as_ref(),
+                        tier,
+                        output_language,
+                        fetch_target.depth,
+                        fetch_target.allow_low_text,
+                        &fetch_budget,
+                    )
+                    .await
+            })
+        })
+        .await
+        .map_err(AnalysisRunError::Prepare)?;
+
+    // the analysis goes on with the stale messages, the next one gets the fresh ones
+    if analysis_data.revalidate {
+        analysis_workers.refresh_in_background(&target.channel_name, target.depth);
+    }
+    metrics().cache_lookup(CacheKind::Messages, analysis_data.fetch_duration.is_none());
+    if let Some(fetch_duration) = analysis_data.fetch_duration {
+        metrics().observe_telegram_fetch(fetch_duration);
+    }
+    Ok(analysis_data)
+}
+
+/// where the llm answer for the messages is cached: the three profile types come from one
+/// answer, trends need their own and so do experimental prompts and routed models
+fn result_cache_key(
+    analysis_data: &AnalysisData,
+    analysis_type: &str,
+    prompt_version: &PromptVersion,
+) -> String {
+    let mut cache_key = if analysis_type == "trends" {
+        format!("{}:trends", analysis_data.cache_key)
+    } else if prompt_version.id != BASE_PROMPT_VERSION {
+        // the base version keeps the original key
+        format!("{}:v{}", analysis_data.cache_key, prompt_version.id)
+    } else {
+        analysis_data.cache_key.clone()
+    };
+    // a type routed to its own model must not share answers with the tier's models
+    if let Some(model) = model_routing().model_for(analysis_type) {
+        cache_key = format!("{}:m{}", cache_key, model);
+    }
+    cache_key
+}
+
+/// where the llm answer of an analysis is cached, and the section to ask the llm for alone
+/// if any: in single mode a channel analysis of one of the three profile types gets an
+/// answer of its own, stored under its own key, unless a complete combined answer for the
+/// messages is cached already. corpora too large for one prompt stay combined, their batch
+/// summaries cost the same for any type
+async fn answer_cache_key<'a>(
+    analysis_workers: &AnalysisWorkers,
+    analysis_data: &AnalysisData,
+    analysis_type: &'a str,
+    prompt_version: &PromptVersion,
+    mode: AnalysisMode,
+) -> (String, Option<&'a str>) {
+    let cache_key = result_cache_key(analysis_data, analysis_type, prompt_version);
+    let single = mode == AnalysisMode::Single
+        && analysis_data.kind == CorpusKind::Channel
+        && matches!(analysis_type, "professional" | "personal" | "roast")
+        && !needs_map_reduce(&analysis_data.messages);
+    if !single {
+        return (cache_key, None);
+    }
+    let combined = analysis_workers
+        .cache
+        .load_llm_result(&cache_key)
+        .await
+        .is_some_and(|result| !result.partial);
+    if combined {
+        (cache_key, None)
+    } else {
+        (
+            format!("{}:{}", cache_key, analysis_type),
+            Some(analysis_type),
+        )
+    }
+}
+
+/// the llm answer for the messages, reused from the cache when another analysis already
+/// asked; one call per channel at a time. with a `section`, only that section is asked for.
+/// channels too large for one prompt are summarized in batches first, reporting each
+/// finished batch to `progress`
+#[allow(clippy::too_many_arguments)]
+async fn query_llm(
+    analysis_workers: &AnalysisWorkers,
+    channel_locks: &ChannelLocks,
+    llm_budget: &LlmBudget,
+    target: &AnalysisTarget,
+    analysis_data: &AnalysisData,
+    route: &ModelRoute,
+    output_language: OutputLanguage,
+    prompt_version: &'static PromptVersion,
+    cache_key: &str,
+    section: Option<&str>,
+    budget: &RetryBudget,
+    progress: Option<&SummaryProgress>,
+) -> Result<AnalysisResult, AnalysisRunError> {
+    let trends = target.analysis_type == "trends";
+    // quick facts go with the analyses whose prompt counted them
+    let quick_facts = (!trends && analysis_data.kind == CorpusKind::Channel)
+        .then(|| QuickFacts::from_messages(&analysis_data.messages));
+
+    // get or create per-channel lock to prevent concurrent LLM calls
+    let channel_lock = {
+        let mut locks = channel_locks.lock().await;
+        locks
+            .entry(target.channel_name.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone()
+    };
+
+    // acquire channel lock before checking cache and calling LLM
+    let _channel_guard = channel_lock.lock().await;
+
+    // check for cached result (re-check after acquiring channel lock)
+    // partial results are only cached for reading back, every new analysis retries them
+    let cached_result = analysis_workers
+        .cache
+        .load_llm_result(cache_key)
+        .await
+        .filter(|result| !result.partial);
+    metrics().cache_lookup(CacheKind::Llm, cached_result.is_some());
+
+    let result = if let Some(mut cached_result) = cached_result {
+        info!(
+            "Using cached LLM result for channel {}",
+            target.channel_name
+        );
+        if cached_result.facts.is_none() {
+            cached_result.facts = quick_facts;
+        }
+        cached_result
+    } else {
+        // cached results stay available once the budget is spent, new llm calls don't
+        if !llm_budget.allows_llm_call().await {
+            return Err(AnalysisRunError::BudgetExceeded);
+        }
+        // and while the llm keeps failing, new calls fail fast instead of retrying
+        llm_breaker()
+            .try_acquire(Instant::now())
+            .map_err(|e| AnalysisRunError::Llm(e.into()))?;
+        info!(
+            "Querying LLM for {} analysis of channel {}...",
+            target.analysis_type, target.channel_name
+        );
+        // perform LLM call (protected by channel lock)
+        let mut result = reviewed_answer(
+            target,
+            analysis_data,
+            route,
+            output_language,
+            prompt_version,
+            cache_key,
+            section,
+            budget,
+            progress,
+        )
+        .await?;
+        result.facts = quick_facts;
+
+        // cache the result
+        if let Err(e) = analysis_workers
+            .cache
+            .save_llm_result(cache_key, &result)
+            .await
+        {
+            error!(
+                "Failed to cache analysis result for channel {}: {}",
+                target.channel_name, e
+            );
+            // continue execution - caching failure shouldn't stop the analysis
+        }
+
+        result
+    };
+
+    Ok(result)
+}
+
+/// one answer of the llm for the messages, in the requested language
+#[allow(clippy::too_many_arguments)]
+async fn ask_llm(
+    target: &AnalysisTarget,
+    analysis_data: &AnalysisData,
+    route: &ModelRoute,
+    output_language: OutputLanguage,
+    prompt_version: &'static PromptVersion,
+    section: Option<&str>,
+    budget: &RetryBudget,
+    progress: Option<&SummaryProgress>,
+) -> Result<AnalysisResult, AnalysisRunError> {
+    let trends = target.analysis_type == "trends";
+    let profile = analysis_data.kind == CorpusKind::Profile;
+    let llm_started = Instant::now();
+    let topic_name = target.topic.as_ref().map(|topic| topic.name.as_str());
+    let llm_result = if trends {
+        let prompt = generate_trends_prompt(
+            &analysis_data.messages,
+            target.focus.as_deref(),
+            topic_name,
+            output_language,
+        )
+        .map_err(AnalysisRunError::Prompt)?;
+        query_trends(&prompt, route, budget).await
+    } else if profile {
+        let prompt = generate_profile_prompt(
+            &analysis_data.messages,
+            target.focus.as_deref(),
+            output_language,
+        )
+        .map_err(AnalysisRunError::Prompt)?;
+        query_and_parse_analysis(&prompt, route, budget).await
+    } else if analysis_data.kind == CorpusKind::CrossGroup {
+        let prompt = generate_cross_group_prompt(
+            &analysis_data.messages,
+            target.focus.as_deref(),
+            output_language,
+        )
+        .map_err(AnalysisRunError::Prompt)?;
+        query_and_parse_analysis(&prompt, route, budget).await
+    } else if let Some((first, second)) = (analysis_data.kind == CorpusKind::RoastBattle)
+        .then(|| roast_battle_members(&target.channel_name))
+        .flatten()
+    {
+        let prompt = generate_roast_battle_prompt(
+            &analysis_data.messages,
+            first,
+            second,
+            target.focus.as_deref(),
+            output_language,
+        )
+        .map_err(AnalysisRunError::Prompt)?;
+        query_and_parse_analysis(&prompt, route, budget).await
+    } else if let Some(section) = section {
+        let prompt = generate_section_prompt(
+            &analysis_data.messages,
+            section,
+            target.focus.as_deref(),
+            topic_name,
+            output_language,
+            prompt_version,
+            route.prompt_token_limit(),
+        )
+        .map_err(AnalysisRunError::Prompt)?;
+        query_and_parse_section(&prompt, section, route, budget).await
+    } else if needs_map_reduce(&analysis_data.messages) {
+        query_and_parse_large_analysis(
+            &analysis_data.messages,
+            target.focus.as_deref(),
+            topic_name,
+            output_language,
+            prompt_version,
+            route,
+            budget,
+            progress,
+        )
+        .await
+    } else {
+        let prompt = generate_analysis_prompt(
+            &analysis_data.messages,
+            target.focus.as_deref(),
+            topic_name,
+            output_language,
+            prompt_version,
+            route.prompt_token_limit(),
+        )
+        .map_err(AnalysisRunError::Prompt)?;
+        query_and_parse_analysis(&prompt, route, budget).await
+    };
+    metrics().observe_llm_latency(llm_started.elapsed());
+    let mut result = llm_result.map_err(|e| AnalysisRunError::Llm(AppError::llm(e)))?;
+    result.messages_count = analysis_data.messages.len();
+    result.removed_messages = analysis_data.removed_messages;
+    if !trends {
+        result.prompt_version = Some(prompt_version.id);
+    }
+
+    // the model sometimes ignores the requested language, fix that before caching
+    if let Some(language) = LanguageTarget::resolve(output_language, &analysis_data.messages) {
+        result = enforce_output_language(result, &language).await;
+    }
+    Ok(result)
+}
+
+/// the llm answer after the safety review: a flagged answer is asked for once more, and
+/// what the review still flags then is cut out; the flags are kept for admins either way
+#[allow(clippy::too_many_arguments)]
+async fn reviewed_answer(
+    target: &AnalysisTarget,
+    analysis_data: &AnalysisData,
+    route: &ModelRoute,
+    output_language: OutputLanguage,
+    prompt_version: &'static PromptVersion,
+    cache_key: &str,
+    section: Option<&str>,
+    budget: &RetryBudget,
+    progress: Option<&SummaryProgress>,
+) -> Result<AnalysisResult, AnalysisRunError> {
+    let ask = || {
+        ask_llm(
+            target,
+            analysis_data,
+            route,
+            output_language,
+            prompt_version,
+            section,
+            budget,
+            progress,
+        )
+    };
+    let result = ask().await?;
+    let flags = review_output(&result).await;
+    if flags.is_empty() {
+        return Ok(result);
+    }
+
+    let context = |result: &AnalysisResult| FlagContext {
+        channel_name: target.channel_name.clone(),
+        analysis_type: target.analysis_type.clone(),
+        cache_key: cache_key.to_string(),
+        model: result.model.clone(),
+    };
+    warn!(
+        "Regenerating the {} analysis of channel {} after the safety review",
+        target.analysis_type, target.channel_name
+    );
+    let retry = match ask().await {
+        Ok(retry) => retry,
+        // the first answer is still there to deliver, without what was flagged
+        Err(e) => {
+            warn!(
+                "Failed to regenerate the {} analysis of channel {}: {}",
+                target.analysis_type, target.channel_name, e
+            );
+            record_flags(&context(&result), &flags, SafetyAction::Redacted);
+            return Ok(redact(result, &flags));
+        }
+    };
+    record_flags(&context(&result), &flags, SafetyAction::Regenerated);
+    let retry_flags = review_output(&retry).await;
+    if retry_flags.is_empty() {
+        return Ok(retry);
+    }
+    record_flags(&context(&retry), &retry_flags, SafetyAction::Redacted);
+    Ok(redact(retry, &retry_flags))
+}
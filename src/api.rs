@@ -0,0 +1,402 @@
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use deadpool_postgres::Pool;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::analysis::AnalysisDepth;
+use crate::analysis_runner::{run_analysis, AnalysisJob, AnalysisRunError};
+use crate::bot::{ChannelLocks, TelegramBot};
+use crate::cache::CacheManager;
+use crate::channel_stats::ChannelStatsManager;
+use crate::config::LiveConfig;
+use crate::job_queue::{JobQueue, JobRunner, LeasedJob, JOB_LEASE};
+use crate::llm_budget::LlmBudget;
+use crate::prompts::analysis::MAX_FOCUS_LENGTH;
+use crate::report::{AnalysisReport, ReportScores};
+use crate::user_manager::{AnalysisRecord, AnalysisSource, User, UserManager, UserManagerError};
+use crate::utils::ResultPresenter;
+use crate::workers::AnalysisWorkers;
+
+const ANALYSIS_TYPES: [&str; 4] = ["professional", "personal", "roast", "trends"];
+
+#[derive(Debug)]
+pub enum ApiError {
+    Unauthorized,
+    NotFound,
+    BadRequest(String),
+    InsufficientCredits { required: i32, available: i32 },
+    TooManyRunning,
+    Internal(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Unauthorized => write!(f, "Missing or invalid API key"),
+            ApiError::NotFound => write!(f, "Analysis not found"),
+            ApiError::BadRequest(reason) => write!(f, "{}", reason),
+            ApiError::InsufficientCredits {
+                required,
+                available,
+            } => write!(
+                f,
+                "Analysis costs {} credits, {} available",
+                required, available
+            ),
+            ApiError::TooManyRunning => {
+                write!(f, "Too many analyses running, wait for one to finish")
+            }
+            ApiError::Internal(_) => write!(f, "Internal server error"),
+        }
+    }
+}
+
+impl Error for ApiError {}
+
+impl From<UserManagerError> for ApiError {
+    fn from(err: UserManagerError) -> Self {
+        match err {
+            UserManagerError::TooManyRunning(_) => ApiError::TooManyRunning,
+            _ => ApiError::Internal(Box::new(err)),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::InsufficientCredits { .. } => StatusCode::PAYMENT_REQUIRED,
+            ApiError::TooManyRunning => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Internal(e) => {
+                error!("API request failed: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        let body = serde_json::json!({ "error": self.to_string() });
+        (status, Json(body)).into_response()
+    }
+}
+
+/// rest api settings; the api is disabled unless API_BIND_ADDR is set
+#[derive(Debug, Clone)]
+pub struct ApiConfig {
+    pub bind_addr: String,
+}
+
+impl ApiConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            bind_addr: env::var("API_BIND_ADDR").ok()?,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateAnalysisRequest {
+    pub channel: String,
+    pub analysis_type: String,
+    #[serde(default)]
+    pub depth: Option<String>,
+    #[serde(default)]
+    pub focus: Option<String>,
+}
+
+/// structured fields of a json mode result; the other analysis types' texts are left out
+#[derive(Serialize, Debug)]
+pub struct ReportHighlights<'a> {
+    pub strengths: &'a [String],
+    pub weaknesses: &'a [String],
+    pub topics: &'a [String],
+    pub tone: &'a str,
+    pub scores: ReportScores,
+}
+
+impl<'a> From<&'a AnalysisReport> for ReportHighlights<'a> {
+    fn from(report: &'a AnalysisReport) -> Self {
+        Self {
+            strengths: &report.strengths,
+            weaknesses: &report.weaknesses,
+            topics: &report.topics,
+            tone: &report.tone,
+            scores: report.scores,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnalysisResultBody<'a> {
+    pub content: &'a str,
+    pub model: Option<&'a str>,
+    pub messages_count: usize,
+    // posts left out because the author deleted them
+    pub removed_messages: usize,
+    // the model's answer was cut off, some sections may be missing or incomplete
+    pub partial: bool,
+    pub report: Option<ReportHighlights<'a>>,
+}
+
+/// shared by the bot-free api handlers; the channel locks are separate from the bot's,
+/// the llm cache keeps both from paying for the same result twice
+#[derive(Clone)]
+pub struct ApiState {
+    pub analysis_workers: Arc<AnalysisWorkers>,
+    pub user_manager: Arc<UserManager>,
+    pub channel_stats: Arc<ChannelStatsManager>,
+    pub cache: Arc<CacheManager>,
+    pub channel_locks: ChannelLocks,
+    pub config: Arc<LiveConfig>,
+    pub llm_budget: Arc<LlmBudget>,
+    pub job_queue: Arc<JobQueue>,
+}
+
+impl ApiState {
+    /// shares the bot's analysis workers, config and llm budget, so both front ends fetch
+    /// with the same sessions and charge and spend by the same limits
+    pub fn new(
+        pool: Arc<Pool>,
+        config: Arc<LiveConfig>,
+        analysis_workers: Arc<AnalysisWorkers>,
+        llm_budget: Arc<LlmBudget>,
+    ) -> Self {
+        Self {
+            analysis_workers,
+            user_manager: Arc::new(UserManager::new(pool.clone())),
+            channel_stats: Arc::new(ChannelStatsManager::new(pool.clone())),
+            cache: Arc::new(CacheManager::new(pool.clone())),
+            channel_locks: Arc::new(Mutex::new(HashMap::new())),
+            job_queue: Arc::new(JobQueue::new(pool)),
+            config,
+            llm_budget,
+        }
+    }
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/analyses", post(create_analysis))
+        .route("/analyses/{id}", get(get_analysis))
+        .with_state(state)
+}
+
+/// re-queues api analyses interrupted by a restart and runs the queue in the background,
+/// then serves the api until the process exits
+pub async fn serve(config: ApiConfig, state: ApiState) -> Result<(), Box<dyn Error + Send + Sync>> {
+    for pending in state
+        .user_manager
+        .get_pending_analyses(AnalysisSource::Api)
+        .await?
+    {
+        // one another replica is running is left to it
+        if state.job_queue.resume(pending.id, true).await?.is_some() {
+            info!(
+                "Resumed API analysis {} for user {} (channel: {})",
+                pending.id, pending.user_id, pending.channel_name
+            );
+        }
+    }
+    let runner = JobRunner::new(
+        state.job_queue.clone(),
+        AnalysisSource::Api,
+        state.analysis_workers.size(),
+        JOB_LEASE,
+    );
+    let job_state = state.clone();
+    tokio::spawn(async move {
+        runner
+            .run(move |job| run_queued_analysis(job_state.clone(), job))
+            .await;
+    });
+
+    let listener = tokio::net::TcpListener::bind(&config.bind_addr).await?;
+    info!("REST API listening on {}", config.bind_addr);
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+/// runs an analysis leased from the job queue; failures only surface through its status
+async fn run_queued_analysis(state: ApiState, job: LeasedJob) -> Result<(), AnalysisRunError> {
+    let analysis = job.analysis;
+    let depth = AnalysisDepth::from_code(&analysis.depth).unwrap_or_default();
+    let job = AnalysisJob {
+        analysis_id: analysis.id,
+        user_id: analysis.user_id,
+        channel_name: analysis.channel_name,
+        analysis_type: analysis.analysis_type,
+        depth,
+        credits: state.config.limits().depth_credits(depth),
+        focus: analysis.focus,
+        topic: analysis.topic,
+        allow_low_text: job.allow_low_text,
+        // second opinions are only offered by the bot
+        second_opinion_on: None,
+    };
+    match run_analysis(
+        &state.analysis_workers,
+        &state.user_manager,
+        &state.channel_stats,
+        &state.channel_locks,
+        &state.llm_budget,
+        &job,
+        None,
+    )
+    .await
+    {
+        Ok(outcome) => {
+            info!(
+                "Completed API analysis {} for user {} (remaining credits: {})",
+                job.analysis_id, job.user_id, outcome.remaining_credits
+            );
+            Ok(())
+        }
+        Err(e) => {
+            error!("API analysis {} failed: {}", job.analysis_id, e);
+            Err(e)
+        }
+    }
+}
+
+/// resolves the `Authorization: Bearer <key>` header to the key's owner
+async fn authenticate(state: &ApiState, headers: &HeaderMap) -> Result<User, ApiError> {
+    let key = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .ok_or(ApiError::Unauthorized)?;
+    state
+        .user_manager
+        .get_user_by_api_key(key)
+        .await?
+        .ok_or(ApiError::Unauthorized)
+}
+
+async fn create_analysis(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateAnalysisRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let user = authenticate(&state, &headers).await?;
+
+    let channel_name = TelegramBot::validate_and_normalize_channel(request.channel.trim())
+        .ok_or_else(|| {
+            ApiError::BadRequest("channel must be @username or a t.me link".to_string())
+        })?;
+    if !ANALYSIS_TYPES.contains(&request.analysis_type.as_str()) {
+        return Err(ApiError::BadRequest(format!(
+            "analysis_type must be one of {}",
+            ANALYSIS_TYPES.join(", ")
+        )));
+    }
+    let depth = match request.depth.as_deref() {
+        Some(code) => AnalysisDepth::from_code(code).ok_or_else(|| {
+            ApiError::BadRequest("depth must be one of small, medium, deep".to_string())
+        })?,
+        None => AnalysisDepth::default(),
+    };
+    let focus = request
+        .focus
+        .as_deref()
+        .map(str::trim)
+        .filter(|focus| !focus.is_empty());
+    if focus.is_some_and(|focus| focus.chars().count() > MAX_FOCUS_LENGTH) {
+        return Err(ApiError::BadRequest(format!(
+            "focus must be at most {} characters",
+            MAX_FOCUS_LENGTH
+        )));
+    }
+
+    // credits are charged when the analysis completes, this only rejects hopeless requests
+    let required = state.config.limits().depth_credits(depth);
+    if user.analysis_credits < required {
+        return Err(ApiError::InsufficientCredits {
+            required,
+            available: user.analysis_credits,
+        });
+    }
+
+    let analysis_id = state
+        .user_manager
+        .create_pending_analysis(
+            user.id,
+            &channel_name,
+            &request.analysis_type,
+            depth.as_str(),
+            user.language.as_deref(),
+            focus,
+            AnalysisSource::Api,
+            Some(state.config.limits().max_running_analyses),
+        )
+        .await?;
+    // api clients have no way to confirm, they get what the channel has
+    state
+        .job_queue
+        .enqueue(analysis_id, None, None, true)
+        .await
+        .map_err(|e| ApiError::Internal(Box::new(e)))?;
+
+    let record = state
+        .user_manager
+        .get_analysis(analysis_id, user.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok((StatusCode::ACCEPTED, Json(analysis_json(&record, None))))
+}
+
+async fn get_analysis(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    let record = state
+        .user_manager
+        .get_analysis(id, user.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let result = match (&record.cache_key, record.status.as_str()) {
+        (Some(cache_key), "completed") => state.cache.load_llm_result(cache_key).await,
+        _ => None,
+    };
+    let body = result.as_ref().and_then(|result| {
+        let content = ResultPresenter::content_for(result, record.analysis_type.as_deref()?)?;
+        Some(AnalysisResultBody {
+            content,
+            model: result.model.as_deref(),
+            messages_count: result.messages_count,
+            removed_messages: result.removed_messages,
+            partial: result.partial,
+            report: result.report.as_ref().map(ReportHighlights::from),
+        })
+    });
+    Ok(Json(analysis_json(&record, body)))
+}
+
+/// the analysis as returned by both endpoints; `result` is null until it completes
+fn analysis_json(record: &AnalysisRecord, result: Option<AnalysisResultBody>) -> serde_json::Value {
+    serde_json::json!({
+        "id": record.id,
+        "channel": record.channel_name,
+        "analysis_type": record.analysis_type,
+        "depth": record.depth,
+        "status": record.status,
+        "credits_used": record.credits_used,
+        "model": record.model,
+        "created_at": record.created_at,
+        "result": result,
+    })
+}
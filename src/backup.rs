@@ -0,0 +1,371 @@
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tokio::time::sleep;
+
+// defaults for the scheduled backup task, overridable through the environment
+const DEFAULT_RETENTION_DAYS: u32 = 14;
+const DEFAULT_MAX_AGE_HOURS: f64 = 36.0;
+const BACKUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+// wait before retrying after a failed scheduled backup
+const FAILED_BACKUP_RETRY: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug)]
+pub enum BackupError {
+    Io(std::io::Error),
+    CommandFailed { program: String, stderr: String },
+    Database(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupError::Io(e) => write!(f, "I/O error: {}", e),
+            BackupError::CommandFailed { program, stderr } => {
+                write!(f, "{} failed: {}", program, stderr.trim())
+            }
+            BackupError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl Error for BackupError {}
+
+impl From<std::io::Error> for BackupError {
+    fn from(err: std::io::Error) -> Self {
+        BackupError::Io(err)
+    }
+}
+
+impl From<tokio_postgres::Error> for BackupError {
+    fn from(err: tokio_postgres::Error) -> Self {
+        BackupError::Database(Box::new(err))
+    }
+}
+
+impl From<deadpool_postgres::PoolError> for BackupError {
+    fn from(err: deadpool_postgres::PoolError) -> Self {
+        BackupError::Database(Box::new(err))
+    }
+}
+
+/// where a dump is written to or read from: a local path or an s3:// uri
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupLocation {
+    Local(PathBuf),
+    S3(String),
+}
+
+impl BackupLocation {
+    pub fn parse(location: &str) -> Self {
+        if location.starts_with("s3://") {
+            BackupLocation::S3(location.to_string())
+        } else {
+            BackupLocation::Local(PathBuf::from(location))
+        }
+    }
+
+    /// explicit .dump files are kept; directories and s3 prefixes get a timestamped file inside
+    pub fn resolve_file(&self, timestamp: &str) -> Self {
+        let file_name = format!("tg-analyzer-{}.dump", timestamp);
+        match self {
+            BackupLocation::Local(path) if path.extension().is_some_and(|ext| ext == "dump") => {
+                self.clone()
+            }
+            BackupLocation::Local(path) => BackupLocation::Local(path.join(file_name)),
+            BackupLocation::S3(uri) if uri.ends_with(".dump") => self.clone(),
+            BackupLocation::S3(uri) => {
+                BackupLocation::S3(format!("{}/{}", uri.trim_end_matches('/'), file_name))
+            }
+        }
+    }
+}
+
+impl fmt::Display for BackupLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupLocation::Local(path) => write!(f, "{}", path.display()),
+            BackupLocation::S3(uri) => write!(f, "{}", uri),
+        }
+    }
+}
+
+/// scheduled backup settings; backups are disabled unless BACKUP_DESTINATION is set
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub destination: BackupLocation,
+    pub retention_days: u32,
+    pub max_age_hours: f64,
+}
+
+impl BackupConfig {
+    pub fn from_env() -> Option<Self> {
+        let destination = env::var("BACKUP_DESTINATION").ok()?;
+        let retention_days = env::var("BACKUP_RETENTION_DAYS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+        let max_age_hours = env::var("BACKUP_MAX_AGE_HOURS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_AGE_HOURS);
+
+        Some(Self {
+            destination: BackupLocation::parse(&destination),
+            retention_days,
+            max_age_hours,
+        })
+    }
+}
+
+/// pg_dump/pg_restore wrapper; s3 transfers are streamed through the aws cli
+pub struct BackupManager {
+    pool: Arc<Pool>,
+    database_url: String,
+}
+
+impl BackupManager {
+    pub fn new(pool: Arc<Pool>, database_url: String) -> Self {
+        Self { pool, database_url }
+    }
+
+    /// dumps the database to the destination and records the backup
+    pub async fn backup(
+        &self,
+        destination: &BackupLocation,
+    ) -> Result<BackupLocation, BackupError> {
+        let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let target = destination.resolve_file(&timestamp);
+        info!("Backing up database to {}", target);
+
+        let mut pg_dump = Command::new("pg_dump");
+        pg_dump
+            .arg("--format=custom")
+            .arg("--no-owner")
+            .arg(format!("--dbname={}", self.database_url));
+
+        match &target {
+            BackupLocation::Local(path) => {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                pg_dump.arg(format!("--file={}", path.display()));
+                Self::wait("pg_dump", pg_dump.stderr(Stdio::piped()).spawn()?).await?;
+            }
+            BackupLocation::S3(uri) => {
+                let mut dump = pg_dump
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                let dump_stdout: Stdio = dump
+                    .stdout
+                    .take()
+                    .expect("pg_dump stdout is piped")
+                    .try_into()?;
+                let upload = Command::new("aws")
+                    .args(["s3", "cp", "-", uri])
+                    .stdin(dump_stdout)
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                let (dumped, uploaded) =
+                    tokio::join!(Self::wait("pg_dump", dump), Self::wait("aws s3 cp", upload));
+                if let Err(e) = dumped {
+                    // don't leave a truncated dump behind
+                    let _ = Command::new("aws").args(["s3", "rm", uri]).output().await;
+                    return Err(e);
+                }
+                uploaded?;
+            }
+        }
+
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO database_backups (location) VALUES ($1)",
+                &[&target.to_string()],
+            )
+            .await?;
+
+        info!("Database backup written to {}", target);
+        Ok(target)
+    }
+
+    /// restores the database from a dump created by `backup`, replacing existing objects
+    pub async fn restore(&self, source: &BackupLocation) -> Result<(), BackupError> {
+        info!("Restoring database from {}", source);
+
+        let mut pg_restore = Command::new("pg_restore");
+        pg_restore
+            .arg("--clean")
+            .arg("--if-exists")
+            .arg("--no-owner")
+            .arg(format!("--dbname={}", self.database_url));
+
+        match source {
+            BackupLocation::Local(path) => {
+                pg_restore.arg(path);
+                Self::wait("pg_restore", pg_restore.stderr(Stdio::piped()).spawn()?).await?;
+            }
+            BackupLocation::S3(uri) => {
+                let mut download = Command::new("aws")
+                    .args(["s3", "cp", uri, "-"])
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                let download_stdout: Stdio = download
+                    .stdout
+                    .take()
+                    .expect("aws stdout is piped")
+                    .try_into()?;
+                let restore = pg_restore
+                    .stdin(download_stdout)
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                let (downloaded, restored) = tokio::join!(
+                    Self::wait("aws s3 cp", download),
+                    Self::wait("pg_restore", restore)
+                );
+                downloaded?;
+                restored?;
+            }
+        }
+
+        info!("Database restored from {}", source);
+        Ok(())
+    }
+
+    /// hours since the last successful backup, None if there has never been one
+    pub async fn hours_since_last_backup(&self) -> Result<Option<f64>, BackupError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "SELECT EXTRACT(EPOCH FROM NOW() - MAX(created_at))::FLOAT8 / 3600.0
+                 FROM database_backups",
+                &[],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    /// deletes backups older than the retention period, returns how many were removed
+    pub async fn prune(&self, retention_days: u32) -> Result<usize, BackupError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT id, location FROM database_backups
+                 WHERE deleted_at IS NULL AND created_at < NOW() - INTERVAL '1 day' * $1",
+                &[&(retention_days as f64)],
+            )
+            .await?;
+
+        let mut removed = 0;
+        for row in rows {
+            let id: i32 = row.get(0);
+            let location = BackupLocation::parse(row.get(1));
+            let deleted = match &location {
+                BackupLocation::Local(path) => Self::remove_local(path).await,
+                BackupLocation::S3(uri) => {
+                    let child = Command::new("aws")
+                        .args(["s3", "rm", uri])
+                        .stderr(Stdio::piped())
+                        .spawn()?;
+                    Self::wait("aws s3 rm", child).await
+                }
+            };
+            match deleted {
+                Ok(()) => {
+                    client
+                        .execute(
+                            "UPDATE database_backups SET deleted_at = NOW() WHERE id = $1",
+                            &[&id],
+                        )
+                        .await?;
+                    info!("Removed expired backup {}", location);
+                    removed += 1;
+                }
+                // keep the record so the next run tries again
+                Err(e) => warn!("Failed to remove expired backup {}: {}", location, e),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// warns when the last successful backup is older than the configured threshold
+    pub async fn warn_if_stale(&self, max_age_hours: f64) {
+        match self.hours_since_last_backup().await {
+            Ok(Some(hours)) if hours > max_age_hours => warn!(
+                "Last successful database backup is {:.1} hours old (threshold {} hours)",
+                hours, max_age_hours
+            ),
+            Ok(Some(_)) => {}
+            Ok(None) => warn!("No successful database backup has been recorded yet"),
+            Err(e) => error!("Failed to check last database backup: {}", e),
+        }
+    }
+
+    /// runs a backup every 24 hours (immediately if one is overdue) and prunes expired ones
+    pub async fn run_nightly(self: Arc<Self>, config: BackupConfig) {
+        loop {
+            let wait = match self.hours_since_last_backup().await {
+                Ok(Some(hours)) => {
+                    BACKUP_INTERVAL.saturating_sub(Duration::from_secs_f64(hours.max(0.0) * 3600.0))
+                }
+                Ok(None) => Duration::ZERO,
+                Err(e) => {
+                    error!("Failed to check last database backup: {}", e);
+                    FAILED_BACKUP_RETRY
+                }
+            };
+            sleep(wait).await;
+
+            if let Err(e) = self.backup(&config.destination).await {
+                error!("Scheduled database backup failed: {}", e);
+                sleep(FAILED_BACKUP_RETRY).await;
+                continue;
+            }
+
+            match self.prune(config.retention_days).await {
+                Ok(removed) if removed > 0 => {
+                    info!(
+                        "Pruned {} backups older than {} days",
+                        removed, config.retention_days
+                    )
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to prune old database backups: {}", e),
+            }
+        }
+    }
+
+    async fn remove_local(path: &Path) -> Result<(), BackupError> {
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => Ok(()),
+            // already gone counts as removed
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn wait(program: &str, child: Child) -> Result<(), BackupError> {
+        let output = child.wait_with_output().await?;
+        Self::check_status(program, output.status, &output.stderr)
+    }
+
+    fn check_status(program: &str, status: ExitStatus, stderr: &[u8]) -> Result<(), BackupError> {
+        if status.success() {
+            Ok(())
+        } else {
+            Err(BackupError::CommandFailed {
+                program: program.to_string(),
+                stderr: String::from_utf8_lossy(stderr).into_owned(),
+            })
+        }
+    }
+}
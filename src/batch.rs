@@ -0,0 +1,369 @@
+use log::{error, info, warn};
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, MessageId, ParseMode};
+
+use crate::analysis::AnalysisDepth;
+use crate::bot::{BotContext, TelegramBot};
+use crate::handlers::CallbackHandler;
+use crate::job_queue::JobStatus;
+use crate::localization::Lang;
+use crate::user_manager::{AnalysisSource, User, UserManagerError};
+use crate::user_sessions::UserSession;
+use crate::utils::MessageFormatter;
+
+// how often a running batch checks on its jobs
+const BATCH_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// what a message naming several channels asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchRequest {
+    // normalized and deduplicated, in the order they were sent
+    Channels(Vec<String>),
+    // tokens that are neither @usernames nor t.me links
+    Invalid(Vec<String>),
+    TooMany(usize),
+}
+
+impl BatchRequest {
+    /// splits on whitespace and commas; None unless the text has several tokens and at
+    /// least one of them is a channel, so single channels and chatter keep their handling
+    pub fn parse(text: &str, max_channels: usize) -> Option<Self> {
+        let tokens = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+            .collect::<Vec<_>>();
+        if tokens.len() < 2 {
+            return None;
+        }
+
+        let mut channels: Vec<String> = Vec::new();
+        let mut invalid = Vec::new();
+        for token in tokens {
+            match TelegramBot::validate_and_normalize_channel(token) {
+                // usernames are case-insensitive
+                Some(channel) => {
+                    if !channels.iter().any(|c| c.eq_ignore_ascii_case(&channel)) {
+                        channels.push(channel);
+                    }
+                }
+                None => invalid.push(token.to_string()),
+            }
+        }
+
+        if channels.is_empty() {
+            None
+        } else if !invalid.is_empty() {
+            Some(BatchRequest::Invalid(invalid))
+        } else if channels.len() > max_channels {
+            Some(BatchRequest::TooMany(channels.len()))
+        } else {
+            Some(BatchRequest::Channels(channels))
+        }
+    }
+}
+
+/// answers a message naming several channels; a valid batch is kept in the user's
+/// session until they pick the analysis type
+pub async fn handle_batch_request(
+    ctx: BotContext,
+    msg: &Message,
+    request: BatchRequest,
+    lang: Lang,
+) -> ResponseResult<()> {
+    let mut channels = match request {
+        BatchRequest::Channels(channels) => channels,
+        BatchRequest::Invalid(invalid) => {
+            ctx.bot
+                .send_message(msg.chat.id, lang.batch_invalid_channels(&invalid))
+                .await?;
+            return Ok(());
+        }
+        BatchRequest::TooMany(_) => {
+            ctx.bot
+                .send_message(
+                    msg.chat.id,
+                    lang.batch_too_many(ctx.limits().max_batch_channels),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+    // "@a @A" is one channel after all
+    if channels.len() == 1 {
+        return TelegramBot::offer_channel_analysis(ctx, msg, channels.remove(0), lang).await;
+    }
+
+    let from = msg.from.as_ref();
+    let telegram_user_id = from.map(|user| user.id.0 as i64).unwrap_or(0);
+    let user = match ctx
+        .user_manager
+        .get_or_create_user(
+            telegram_user_id,
+            from.and_then(|user| user.username.as_deref()),
+            from.map(|user| user.first_name.as_str()),
+            from.and_then(|user| user.last_name.as_deref()),
+            None,
+            from.and_then(|user| user.language_code.as_deref()),
+        )
+        .await
+    {
+        Ok((user, _)) => user,
+        Err(e) => {
+            error!("Failed to get/create user: {}", e);
+            ctx.bot
+                .send_message(msg.chat.id, lang.error_processing_request())
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if user.analysis_credits <= 0 {
+        ctx.bot
+            .send_message(msg.chat.id, lang.no_credits_short())
+            .reply_markup(CallbackHandler::create_payment_keyboard(
+                &ctx.packages.active().await,
+                lang,
+            ))
+            .await?;
+        return Ok(());
+    }
+
+    info!(
+        "User {} requested a batch analysis of {} channels: {}",
+        telegram_user_id,
+        channels.len(),
+        channels.join(", ")
+    );
+    let escaped = channels
+        .iter()
+        .map(|channel| MessageFormatter::escape_html(channel))
+        .collect::<Vec<_>>();
+    ctx.user_sessions
+        .insert(
+            telegram_user_id,
+            UserSession {
+                batch: channels,
+                ..Default::default()
+            },
+        )
+        .await;
+
+    ctx.bot
+        .send_message(
+            msg.chat.id,
+            lang.batch_select_type(
+                &escaped,
+                ctx.limits().depth_credits(AnalysisDepth::default()),
+                user.analysis_credits,
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(CallbackHandler::create_batch_keyboard(lang))
+        .await?;
+    Ok(())
+}
+
+/// queues the analyses of a batch and tracks them in one progress message until all
+/// have finished; each analysis is charged on its own completion, so failures cost nothing
+pub async fn run_batch(
+    ctx: BotContext,
+    chat_id: ChatId,
+    user: User,
+    channels: Vec<String>,
+    analysis_type: String,
+    lang: Lang,
+) {
+    let depth = AnalysisDepth::default();
+    let total = channels.len();
+    let mut failed = Vec::new();
+
+    // queue every analysis up front, so a restart resumes the whole batch; the batch
+    // counts as one start against max_running_analyses, so only its first is checked
+    let mut queued = Vec::new();
+    for (i, channel) in channels.into_iter().enumerate() {
+        let max_running = (i == 0).then_some(ctx.limits().max_running_analyses);
+        let analysis_id = match ctx
+            .user_manager
+            .create_pending_analysis(
+                user.id,
+                &channel,
+                &analysis_type,
+                depth.as_str(),
+                Some(lang.code()),
+                None,
+                AnalysisSource::Bot,
+                max_running,
+            )
+            .await
+        {
+            Ok(analysis_id) => analysis_id,
+            Err(UserManagerError::TooManyRunning(_)) => {
+                let _ = ctx
+                    .bot
+                    .send_message(chat_id, lang.analysis_already_running())
+                    .await;
+                return;
+            }
+            Err(e) => {
+                error!("Failed to record batch analysis of {}: {}", channel, e);
+                failed.push(MessageFormatter::escape_html(&channel));
+                continue;
+            }
+        };
+        if let Err(e) = ctx
+            .job_queue
+            .enqueue(analysis_id, Some(chat_id.0), Some(lang.code()), false)
+            .await
+        {
+            error!("Failed to queue batch analysis of {}: {}", channel, e);
+            mark_failed(&ctx, analysis_id).await;
+            failed.push(MessageFormatter::escape_html(&channel));
+            continue;
+        }
+        queued.push((channel, analysis_id));
+    }
+
+    let analysis_ids = queued.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+    let mut progress = None;
+    let mut completed = 0;
+    let mut skipped = Vec::new();
+    let mut shown = None;
+    while !queued.is_empty() {
+        let statuses = match ctx.job_queue.statuses(&analysis_ids).await {
+            Ok(statuses) => statuses,
+            Err(e) => {
+                warn!("Failed to read batch job statuses: {}", e);
+                tokio::time::sleep(BATCH_POLL_INTERVAL).await;
+                continue;
+            }
+        };
+        let status_of = |analysis_id: i32| {
+            statuses
+                .iter()
+                .find(|(id, _)| *id == analysis_id)
+                .map(|(_, status)| *status)
+        };
+
+        let mut newly_failed = false;
+        queued.retain(|(channel, analysis_id)| match status_of(*analysis_id) {
+            Some(JobStatus::Completed) => {
+                completed += 1;
+                false
+            }
+            Some(JobStatus::Cancelled) => {
+                skipped.push(MessageFormatter::escape_html(channel));
+                false
+            }
+            Some(status) if status.is_finished() => {
+                newly_failed = true;
+                failed.push(MessageFormatter::escape_html(channel));
+                false
+            }
+            _ => true,
+        });
+
+        // credits were spent elsewhere meanwhile, the rest of the batch can't be paid for
+        if newly_failed && !queued.is_empty() {
+            skip_unaffordable(&ctx, user.id, &queued, depth).await;
+        }
+
+        // the channel running now, or the next one in line
+        let current = queued
+            .iter()
+            .find(|(_, analysis_id)| status_of(*analysis_id) == Some(JobStatus::Running))
+            .or(queued.first())
+            .map(|(channel, _)| channel.clone());
+        if let Some(channel) = current {
+            let done = completed + failed.len() + skipped.len();
+            if shown.as_ref() != Some(&(channel.clone(), done)) {
+                let text = lang.batch_progress(
+                    &analysis_type,
+                    done,
+                    total,
+                    &MessageFormatter::escape_html(&channel),
+                );
+                progress = update_progress(&ctx, chat_id, progress, text).await;
+                shown = Some((channel, done));
+            }
+            tokio::time::sleep(BATCH_POLL_INTERVAL).await;
+        }
+    }
+
+    info!(
+        "Batch analysis for user {} finished: {} of {} completed",
+        user.id, completed, total
+    );
+    let summary = lang.batch_finished(completed, total, &failed, &skipped);
+    update_progress(&ctx, chat_id, progress, summary).await;
+}
+
+/// cancels the batch's analyses that haven't started if the user can't pay for one anymore
+async fn skip_unaffordable(
+    ctx: &BotContext,
+    user_id: i32,
+    queued: &[(String, i32)],
+    depth: AnalysisDepth,
+) {
+    let credits = match ctx.user_manager.get_credits(user_id).await {
+        Ok(credits) => credits,
+        Err(e) => {
+            warn!("Failed to read credits of user {}: {}", user_id, e);
+            return;
+        }
+    };
+    if credits >= ctx.limits().depth_credits(depth) {
+        return;
+    }
+    let analysis_ids = queued.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+    match ctx.job_queue.cancel(&analysis_ids).await {
+        Ok(cancelled) => info!(
+            "Skipped {} batch analyses of user {} with {} credits left",
+            cancelled.len(),
+            user_id,
+            credits
+        ),
+        Err(e) => error!("Failed to skip batch analyses of user {}: {}", user_id, e),
+    }
+}
+
+/// edits the progress message, or sends it if there is none yet
+async fn update_progress(
+    ctx: &BotContext,
+    chat_id: ChatId,
+    progress: Option<MessageId>,
+    text: String,
+) -> Option<MessageId> {
+    if let Some(message_id) = progress {
+        match ctx
+            .bot
+            .edit_message_text(chat_id, message_id, &text)
+            .parse_mode(ParseMode::Html)
+            .await
+        {
+            Ok(_) => return Some(message_id),
+            Err(e) => warn!("Failed to update batch progress in {}: {}", chat_id, e),
+        }
+    }
+    match ctx
+        .bot
+        .send_message(chat_id, text)
+        .parse_mode(ParseMode::Html)
+        .await
+    {
+        Ok(message) => Some(message.id),
+        Err(e) => {
+            warn!("Failed to send batch progress to {}: {}", chat_id, e);
+            None
+        }
+    }
+}
+
+async fn mark_failed(ctx: &BotContext, analysis_id: i32) {
+    if let Err(e) = ctx.user_manager.mark_analysis_failed(analysis_id).await {
+        error!(
+            "Failed to mark batch analysis {} as failed: {}",
+            analysis_id, e
+        );
+    }
+}
@@ -3,6 +3,7 @@ use grammers_session::Session;
 use std::env;
 use std::fs;
 use std::io::{self, Write};
+use tg_main::session_manager::TelegramCredentials;
 
 /// extracts phone number digits only, removing all formatting
 fn sanitize_phone_number(phone: &str) -> String {
@@ -14,13 +15,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // load .env file if it exists
     let _ = dotenvy::dotenv();
 
-    let api_id = env::var("TG_API_ID")
-        .map_err(|_| "TG_API_ID environment variable is required")?
-        .parse::<i32>()
-        .map_err(|_| "TG_API_ID must be a valid integer")?;
-
-    let api_hash =
-        env::var("TG_API_HASH").map_err(|_| "TG_API_HASH environment variable is required")?;
+    let TelegramCredentials { api_id, api_hash } = TelegramCredentials::from_env()?;
 
     println!("Connecting to Telegram...");
 
@@ -1,9 +1,11 @@
 use clap::Parser;
 use log::{error, info};
 use std::sync::Arc;
-use tg_main::analysis::AnalysisEngine;
+use tg_main::analysis::{AnalysisDepth, AnalysisEngine};
 use tg_main::cache::CacheManager;
-use tg_main::llm::query_llm;
+use tg_main::llm::{query_llm, ModelTier};
+use tg_main::prompts::analysis::OutputLanguage;
+use tg_main::retry_budget::RetryBudget;
 
 #[derive(Parser, Debug)]
 #[command(name = "custom_prompt")]
@@ -66,7 +68,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // get messages (from cache or fresh)
     info!("Preparing analysis data for channel: {}", args.channel);
-    let analysis_data = match engine.prepare_analysis_data(&args.channel).await {
+    let analysis_data = match engine
+        .prepare_analysis_data(
+            &args.channel,
+            None,
+            None,
+            ModelTier::Auto,
+            OutputLanguage::Channel,
+            AnalysisDepth::Small,
+            true,
+            &RetryBudget::unbounded(),
+        )
+        .await
+    {
         Ok(data) => data,
         Err(e) => {
             error!("Failed to prepare analysis data: {}", e);
@@ -2,6 +2,7 @@ use grammers_client::{Client, Config, InitParams};
 use grammers_session::Session;
 use log::info;
 use std::fs;
+use tg_main::session_manager::TelegramCredentials;
 
 const CHANNEL: &str = "partially_unsupervised";
 const LIMIT: usize = 50;
@@ -12,13 +13,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
     dotenvy::dotenv().ok();
 
-    let api_id: i32 = std::env::var("TG_API_ID")?.parse()?;
-    let api_hash = std::env::var("TG_API_HASH")?;
+    let TelegramCredentials { api_id, api_hash } = TelegramCredentials::from_env()?;
 
     // find session file
     let session_file = fs::read_dir("sessions")?
         .filter_map(|e| e.ok())
-        .find(|e| e.path().extension().map_or(false, |ext| ext == "session"))
+        .find(|e| e.path().extension().is_some_and(|ext| ext == "session"))
         .map(|e| e.path())
         .ok_or("No session file found")?;
 
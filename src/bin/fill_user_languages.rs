@@ -60,7 +60,7 @@ async fn infer_language_batch(
         return Ok(Vec::new());
     }
 
-    let mut prompt = format!(
+    let mut prompt = String::from(
         r#"You are a language detection expert. For each user below, analyze their name and username to determine their most likely language.
 
 You must choose ONLY from these 4 options:
@@ -74,7 +74,7 @@ Consider:
 2. Common name patterns (e.g., -ov/-ev endings for Russian, Hispanic surnames for Spanish)
 3. Username conventions
 
-Respond with ONLY a JSON array where each element is {{"user_id": <id>, "language": "<code>"}}.
+Respond with ONLY a JSON array where each element is {"user_id": <id>, "language": "<code>"}.
 
 Users to analyze:
 "#
@@ -206,7 +206,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!(
             "Processing batch {}/{}",
             batch_idx + 1,
-            (users.len() + BATCH_SIZE - 1) / BATCH_SIZE
+            users.len().div_ceil(BATCH_SIZE)
         );
 
         // infer languages
@@ -236,7 +236,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         // small delay to avoid rate limiting
-        if batch_idx + 1 < (users.len() + BATCH_SIZE - 1) / BATCH_SIZE {
+        if batch_idx + 1 < users.len().div_ceil(BATCH_SIZE) {
             tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
         }
     }
@@ -1,21 +1,71 @@
-use log::{error, info};
+use log::{error, info, warn};
 use regex::Regex;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use teloxide::prelude::*;
-use teloxide::types::{CallbackQuery, ChatId, ParseMode, PreCheckoutQuery, SuccessfulPayment};
+use teloxide::types::{
+    BotCommand, BotCommandScope, CallbackQuery, ChatId, InlineKeyboardButton, InlineKeyboardMarkup,
+    InlineQuery, ParseMode, PreCheckoutQuery, SuccessfulPayment, User,
+};
 use teloxide::utils::command::BotCommands;
-use tokio::sync::Mutex;
+use teloxide::{ApiError, RequestError};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinSet;
 
-use crate::analysis::AnalysisEngine;
-use crate::cache::AnalysisResult;
+use crate::admin::AdminManager;
+use crate::analysis::{
+    invite_channel_name, invite_hash, is_self_corpus, AnalysisDepth, AnalysisError, CorpusKind,
+    ForumTopic,
+};
+use crate::analysis_runner::{run_analysis, AnalysisJob, AnalysisOutcome, AnalysisRunError};
+use crate::batch::{self, BatchRequest};
+use crate::blocklist::ChannelBlocklist;
+use crate::cache::{AnalysisResult, CacheManager};
+use crate::changelog::ChangelogManager;
+use crate::channel_claims::{self, ChannelClaimManager};
+use crate::channel_stats::ChannelStatsManager;
+use crate::config::LiveConfig;
+use crate::cross_group::{self, CrossGroupManager};
+use crate::data_export::DataExportManager;
+use crate::error::AppError;
+use crate::feedback::FeedbackManager;
+use crate::flagged_outputs::FlaggedOutputsManager;
+use crate::free_trials::FreeTrialManager;
 use crate::handlers::{
-    payment_handler::{BULK_PACKAGE_AMOUNT, BULK_PACKAGE_PRICE, SINGLE_PACKAGE_PRICE},
-    CallbackHandler, CommandHandler, PaymentHandler,
+    CallbackData, CallbackHandler, CommandHandler, InlineHandler, PaymentHandler,
 };
+use crate::job_queue::{JobQueue, JobRunner, LeasedJob, JOB_LEASE};
+use crate::language_overrides::LanguageOverrides;
+use crate::limits::Limits;
+use crate::llm::ModelTier;
+use crate::llm_budget::LlmBudget;
 use crate::localization::Lang;
-use crate::user_manager::{UserManager, UserManagerError};
-use crate::utils::MessageFormatter;
+use crate::message_queue::{
+    MessageQueue, QueuedMessage, TokenBucket, SEND_BATCH_SIZE, TELEGRAM_MESSAGES_PER_SECOND,
+};
+use crate::packages::PackageManager;
+use crate::privacy::PrivacyManager;
+use crate::prompts::analysis::MAX_FOCUS_LENGTH;
+use crate::rate_limiters::user::{Throttle, UserRateLimiter};
+use crate::receipts::ReceiptsManager;
+use crate::recovery;
+use crate::referral_flags::ReferralFlagManager;
+use crate::referrals::ReferralManager;
+use crate::self_analysis;
+use crate::showcase::{self, ShowcaseConfig, ShowcaseManager};
+use crate::stats::ChannelHealth;
+use crate::subscriptions::{self, SubscriptionManager};
+use crate::telegraph::{TelegraphClient, WEB_PAGE_MIN_PARTS};
+use crate::user_manager::{AnalysisRecord, AnalysisSource, UserManager, UserManagerError};
+use crate::user_sessions::{self, UserSession, UserSessions};
+use crate::utils::{MessageFormatter, ResultPresenter};
+use crate::voice::{VoiceConfig, VoiceSummaries};
+use crate::warmup::{self, WarmupConfig, WarmupManager};
+use crate::watchdog;
+use crate::web_scraper::{ChannelPreview, TelegramWebScraper};
+use crate::workers::AnalysisWorkers;
 use deadpool_postgres::Pool;
 
 // per-channel locks to prevent concurrent LLM calls for the same channel
@@ -26,31 +76,181 @@ pub type ChannelLocks = Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>;
 pub enum Command {
     #[command(description = "start the bot")]
     Start,
-    #[command(description = "buy 1 analysis for 50 stars")]
+    #[command(description = "buy analysis credits")]
+    Buy,
+    #[command(hide)]
     Buy1,
-    #[command(description = "buy 10 analyses for 200 stars")]
+    #[command(hide)]
     Buy10,
+    #[command(description = "subscribe to monthly analysis credits")]
+    Subscribe,
+    #[command(description = "choose preferred model tier")]
+    Settings,
+    #[command(description = "show credit balance and history")]
+    Balance,
+    #[command(description = "choose the language of the bot and of analyses")]
+    Language,
+    #[command(description = "see what's new in the bot")]
+    WhatsNew,
+    #[command(description = "get a key for the REST API")]
+    ApiKey,
+    #[command(description = "most analyzed channels this week")]
+    Top,
+    #[command(description = "your referral stats and this week's top referrers")]
+    Referrals,
+    #[command(description = "send the result of your last analysis again")]
+    Resend,
+    #[command(
+        rename = "analyze_group",
+        description = "analyze this group (group admins only)"
+    )]
+    AnalyzeGroup,
+    #[command(
+        description = "analyze this group with a chosen type, e.g. /analyze roast (group admins only)"
+    )]
+    Analyze(String),
+    #[command(
+        rename = "analyze_me",
+        description = "analyze your own messages forwarded to the bot"
+    )]
+    AnalyzeMe,
+    #[command(
+        rename = "groupprofile",
+        description = "your profile from your messages in groups shared with the bot"
+    )]
+    GroupProfile,
+    #[command(
+        rename = "roastbattle",
+        description = "roast two group members against each other, e.g. /roastbattle @alice @bob"
+    )]
+    RoastBattle(String),
+    #[command(
+        description = "prove you own a channel to opt it out or analyze it for free, e.g. /claim @channel"
+    )]
+    Claim(String),
+    #[command(
+        rename = "export_my_data",
+        description = "get everything the bot stores about you as a file"
+    )]
+    ExportMyData,
+    #[command(
+        rename = "delete_account",
+        description = "delete your account and the data the bot keeps about you"
+    )]
+    DeleteAccount,
+    #[command(description = "list your purchases with their dates and star amounts")]
+    Receipts,
+    #[command(hide)]
+    Refund(String),
+    #[command(hide)]
+    Role(String),
+    #[command(hide)]
+    Audit(String),
+    #[command(hide)]
+    Announce(String),
+    #[command(hide)]
+    Backend(String),
+    #[command(hide)]
+    Reload,
+    #[command(hide)]
+    Requeue(String),
+    #[command(hide)]
+    Feedback(String),
+    #[command(hide)]
+    LlmCosts(String),
+    #[command(hide)]
+    Revenue(String),
+    #[command(hide)]
+    Packages(String),
+    #[command(hide)]
+    ReferralConfig(String),
+    #[command(hide)]
+    ReferralFlags(String),
+    #[command(hide)]
+    TrialClusters(String),
+    #[command(hide)]
+    Warmup(String),
+    #[command(hide)]
+    Flagged(String),
+    #[command(hide)]
+    Blocklist(String),
 }
 
 pub struct TelegramBot {
     bot: Arc<Bot>,
-    analysis_engine: Arc<Mutex<AnalysisEngine>>,
+    analysis_workers: Arc<AnalysisWorkers>,
     user_manager: Arc<UserManager>,
     pool: Arc<Pool>,
     payment_handler: PaymentHandler,
+    admin: Arc<AdminManager>,
+    config: Arc<LiveConfig>,
+    llm_budget: Arc<LlmBudget>,
+    subscriptions: Arc<SubscriptionManager>,
+    web_scraper: Arc<TelegramWebScraper>,
 }
 
 #[derive(Clone)]
 pub struct BotContext {
     pub bot: Arc<Bot>,
-    pub analysis_engine: Arc<Mutex<AnalysisEngine>>,
+    pub analysis_workers: Arc<AnalysisWorkers>,
     pub user_manager: Arc<UserManager>,
     pub payment_handler: PaymentHandler,
+    pub changelog: Arc<ChangelogManager>,
+    pub message_queue: Arc<MessageQueue>,
+    pub channel_stats: Arc<ChannelStatsManager>,
+    pub referrals: Arc<ReferralManager>,
+    pub referral_flags: Arc<ReferralFlagManager>,
+    pub cross_group: Arc<CrossGroupManager>,
     pub channel_locks: ChannelLocks,
+    pub user_sessions: Arc<UserSessions>,
+    pub language_overrides: Arc<LanguageOverrides>,
+    pub admin: Arc<AdminManager>,
+    pub config: Arc<LiveConfig>,
+    pub llm_budget: Arc<LlmBudget>,
+    pub job_queue: Arc<JobQueue>,
+    pub feedback: Arc<FeedbackManager>,
+    pub subscriptions: Arc<SubscriptionManager>,
+    pub user_rate_limiter: Arc<UserRateLimiter>,
+    pub web_scraper: Arc<TelegramWebScraper>,
+    // None unless a showcase channel is configured
+    pub showcase: Option<Arc<ShowcaseManager>>,
+    pub telegraph: Arc<TelegraphClient>,
+    // None unless a TTS model is configured
+    pub voice: Option<Arc<VoiceSummaries>>,
+    pub warmup: Arc<WarmupManager>,
+    pub flagged_outputs: Arc<FlaggedOutputsManager>,
+    pub blocklist: Arc<ChannelBlocklist>,
+    pub channel_claims: Arc<ChannelClaimManager>,
+    pub data_export: Arc<DataExportManager>,
+    pub privacy: Arc<PrivacyManager>,
+    pub receipts: Arc<ReceiptsManager>,
+    pub packages: Arc<PackageManager>,
+    pub free_trials: Arc<FreeTrialManager>,
+}
+
+impl BotContext {
+    /// the limits in effect, as of the last config reload
+    pub fn limits(&self) -> Arc<Limits> {
+        self.config.limits()
+    }
+
+    /// reloads the config and hands the new limits to the parts that keep their own copy;
+    /// returns what changed
+    pub async fn reload_config(
+        &self,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let changes = self.config.reload().await?;
+        let limits = self.limits();
+        self.llm_budget
+            .set_daily_budget_cents(limits.daily_llm_budget_cents);
+        self.user_rate_limiter
+            .set_rates(limits.user_burst_requests, limits.user_requests_per_minute);
+        Ok(changes)
+    }
 }
 
 impl TelegramBot {
-    fn validate_and_normalize_channel(text: &str) -> Option<String> {
+    pub fn validate_and_normalize_channel(text: &str) -> Option<String> {
         // regex for valid telegram channel username (5-32 chars, alphanumeric and underscore)
         let channel_regex = Regex::new(r"^@([a-zA-Z0-9_]{5,32})$").unwrap();
 
@@ -70,79 +270,96 @@ impl TelegramBot {
         None
     }
 
-    async fn run_message_queue_processor(bot: Arc<Bot>, pool: Arc<Pool>) {
+    /// name of the private channel behind a t.me/+hash or t.me/joinchat/hash invite link
+    pub fn parse_invite_link(text: &str) -> Option<String> {
+        let invite_regex =
+            Regex::new(r"^(?:https?://)?t\.me/(?:\+|joinchat/)([a-zA-Z0-9_-]{8,32})/?$").unwrap();
+        invite_regex
+            .captures(text)
+            .map(|captures| invite_channel_name(&captures[1]))
+    }
+
+    /// sends due messages in parallel batches, one message per recipient per batch so each
+    /// chat gets its messages in order; a shared token bucket keeps the bot under telegram's
+    /// global rate limit
+    async fn run_message_queue_processor(bot: Arc<Bot>, queue: Arc<MessageQueue>) {
         info!("Starting message queue processor");
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+        let bucket = Arc::new(Mutex::new(TokenBucket::new(
+            TELEGRAM_MESSAGES_PER_SECOND,
+            TELEGRAM_MESSAGES_PER_SECOND,
+            Instant::now(),
+        )));
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
 
         loop {
             interval.tick().await;
 
-            let client = match pool.get().await {
-                Ok(client) => client,
-                Err(e) => {
-                    error!(
-                        "Failed to get database connection for queue processor: {}",
-                        e
-                    );
-                    continue;
-                }
-            };
-
-            // get next pending message
-            let row = match client
-                .query_opt(
-                    "SELECT id, telegram_user_id, message, parse_mode 
-                 FROM message_queue 
-                 WHERE status = 'pending' 
-                 ORDER BY created_at 
-                 LIMIT 1 
-                 FOR UPDATE SKIP LOCKED",
-                    &[],
-                )
-                .await
-            {
-                Ok(row) => row,
+            let batch = match queue.due_batch(SEND_BATCH_SIZE).await {
+                Ok(batch) => batch,
                 Err(e) => {
                     error!("Failed to query message queue: {}", e);
                     continue;
                 }
             };
 
-            if let Some(row) = row {
-                let id: i32 = row.get(0);
-                let user_id: i64 = row.get(1);
-                let message: String = row.get(2);
-                let parse_mode: String = row.get(3);
+            let mut sends = JoinSet::new();
+            for queued in batch {
+                let bot = bot.clone();
+                let queue = queue.clone();
+                let bucket = bucket.clone();
+                sends.spawn(async move {
+                    let wait = bucket.lock().await.reserve(Instant::now());
+                    tokio::time::sleep(wait).await;
+                    Self::send_queued_message(&bot, &queue, queued).await;
+                });
+            }
+            // the next batch may hold the following message of the same chats
+            sends.join_all().await;
+        }
+    }
 
-                // send message
-                let send_result = if parse_mode.to_uppercase() == "HTML" {
-                    bot.send_message(ChatId(user_id), &message)
-                        .parse_mode(ParseMode::Html)
-                        .await
-                } else {
-                    bot.send_message(ChatId(user_id), &message)
-                        .parse_mode(ParseMode::MarkdownV2)
-                        .await
-                };
+    /// reloads the config on every SIGHUP, e.g. `kill -HUP <pid>` after editing CONFIG_FILE
+    async fn reload_config_on_sighup(ctx: BotContext) {
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                error!("Failed to listen for SIGHUP, use /reload instead: {}", e);
+                return;
+            }
+        };
+        while hangups.recv().await.is_some() {
+            info!("Received SIGHUP, reloading the config");
+            if let Err(e) = ctx.reload_config().await {
+                error!(
+                    "Failed to reload the config, keeping the current one: {}",
+                    e
+                );
+            }
+        }
+    }
 
-                match send_result {
-                    Ok(_) => {
-                        if let Err(e) = client.execute(
-                            "UPDATE message_queue SET status = 'sent', sent_at = NOW() WHERE id = $1",
-                            &[&id],
-                        ).await {
-                            error!("Failed to update message status to sent: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        let error_msg = e.to_string();
-                        if let Err(e) = client.execute(
-                            "UPDATE message_queue SET status = 'failed', error_message = $2 WHERE id = $1",
-                            &[&id, &error_msg],
-                        ).await {
-                            error!("Failed to update message status to failed: {}", e);
-                        }
-                    }
+    async fn send_queued_message(bot: &Bot, queue: &MessageQueue, queued: QueuedMessage) {
+        let parse_mode = if queued.parse_mode.to_uppercase() == "HTML" {
+            ParseMode::Html
+        } else {
+            ParseMode::MarkdownV2
+        };
+        match bot
+            .send_message(ChatId(queued.telegram_user_id), &queued.message)
+            .parse_mode(parse_mode)
+            .await
+        {
+            Ok(_) => {
+                if let Err(e) = queue.mark_sent(queued.id).await {
+                    error!("Failed to update message status to sent: {}", e);
+                }
+            }
+            Err(e) => {
+                if let Err(e) = queue.record_failure(&queued, &e.to_string()).await {
+                    error!(
+                        "Failed to record failed send of message {}: {}",
+                        queued.id, e
+                    );
                 }
             }
         }
@@ -152,17 +369,29 @@ impl TelegramBot {
         bot_token: &str,
         user_manager: Arc<UserManager>,
         pool: Arc<Pool>,
+        config: Arc<LiveConfig>,
+        analysis_workers: Arc<AnalysisWorkers>,
+        llm_budget: Arc<LlmBudget>,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let bot = Arc::new(Bot::new(bot_token));
-        let analysis_engine = Arc::new(Mutex::new(AnalysisEngine::new(pool.clone())?));
-        let payment_handler = PaymentHandler::new(user_manager.clone());
+        let subscriptions = Arc::new(SubscriptionManager::new(pool.clone()));
+        let payment_handler = PaymentHandler::new(user_manager.clone(), subscriptions.clone());
+
+        let admin = Arc::new(AdminManager::from_env(pool.clone()));
+        // previews are fetched apart from the workers, which are busy with whole analyses
+        let web_scraper = Arc::new(TelegramWebScraper::new()?);
 
         Ok(Self {
             bot,
-            analysis_engine,
+            analysis_workers,
             user_manager,
             pool,
             payment_handler,
+            admin,
+            config,
+            llm_budget,
+            subscriptions,
+            web_scraper,
         })
     }
 
@@ -170,21 +399,178 @@ impl TelegramBot {
         info!("Starting Telegram bot...");
 
         // spawn message queue processor
+        let message_queue = Arc::new(MessageQueue::new(self.pool.clone()));
         let bot_clone = self.bot.clone();
-        let pool_clone = self.pool.clone();
+        let queue_clone = message_queue.clone();
         tokio::spawn(async move {
-            Self::run_message_queue_processor(bot_clone, pool_clone).await;
+            Self::run_message_queue_processor(bot_clone, queue_clone).await;
         });
 
+        // top up renewed subscriptions and expire unpaid ones
+        tokio::spawn(subscriptions::run_subscription_scheduler(
+            self.bot.clone(),
+            self.subscriptions.clone(),
+        ));
+
+        // publish the analyses users agreed to show if a showcase channel is configured
+        let showcase = match ShowcaseConfig::from_env() {
+            Some(config) => {
+                let showcase = Arc::new(ShowcaseManager::new(self.pool.clone()));
+                tokio::spawn(showcase::run_showcase_publisher(
+                    self.bot.clone(),
+                    showcase.clone(),
+                    CacheManager::new(self.pool.clone()),
+                    config,
+                ));
+                Some(showcase)
+            }
+            None => {
+                info!("SHOWCASE_CHANNEL_ID is not set, the showcase channel is disabled");
+                None
+            }
+        };
+
+        // read results out as voice messages if a TTS model is configured
+        let voice = match VoiceConfig::from_env() {
+            Some(config) => Some(Arc::new(VoiceSummaries::new(self.pool.clone(), config))),
+            None => {
+                info!("TTS_MODEL is not set, voice summaries are disabled");
+                None
+            }
+        };
+
         // create context for all handlers
+        let limits = self.config.limits();
         let ctx = BotContext {
             bot: self.bot.clone(),
-            analysis_engine: self.analysis_engine.clone(),
+            analysis_workers: self.analysis_workers.clone(),
             user_manager: self.user_manager.clone(),
             payment_handler: self.payment_handler.clone(),
+            changelog: Arc::new(ChangelogManager::new(self.pool.clone())),
+            message_queue,
+            channel_stats: Arc::new(ChannelStatsManager::new(self.pool.clone())),
+            referrals: Arc::new(ReferralManager::new(self.pool.clone())),
+            referral_flags: Arc::new(ReferralFlagManager::new(self.pool.clone())),
+            cross_group: Arc::new(CrossGroupManager::new(self.pool.clone())),
             channel_locks: Arc::new(Mutex::new(HashMap::new())),
+            user_sessions: Arc::new(UserSessions::new(self.pool.clone())),
+            language_overrides: Arc::new(LanguageOverrides::new(self.user_manager.clone())),
+            admin: self.admin.clone(),
+            config: self.config.clone(),
+            llm_budget: self.llm_budget.clone(),
+            job_queue: Arc::new(JobQueue::new(self.pool.clone())),
+            feedback: Arc::new(FeedbackManager::new(self.pool.clone())),
+            subscriptions: self.subscriptions.clone(),
+            user_rate_limiter: Arc::new(UserRateLimiter::new(
+                limits.user_burst_requests,
+                limits.user_requests_per_minute,
+            )),
+            web_scraper: self.web_scraper.clone(),
+            showcase,
+            telegraph: Arc::new(TelegraphClient::from_env()),
+            voice,
+            warmup: Arc::new(WarmupManager::new(self.pool.clone())),
+            flagged_outputs: Arc::new(FlaggedOutputsManager::new(self.pool.clone())),
+            blocklist: Arc::new(ChannelBlocklist::new(self.pool.clone())),
+            channel_claims: Arc::new(ChannelClaimManager::new(self.pool.clone())),
+            data_export: Arc::new(DataExportManager::new(self.pool.clone())),
+            privacy: Arc::new(PrivacyManager::new(self.pool.clone())),
+            receipts: Arc::new(ReceiptsManager::new(self.pool.clone())),
+            packages: Arc::new(PackageManager::new(self.pool.clone())),
+            free_trials: Arc::new(FreeTrialManager::new(self.pool.clone())),
         };
 
+        // precompute the analyses of popular channels at night if an off-peak window is set
+        match WarmupConfig::from_env() {
+            Some(config) => {
+                tokio::spawn(warmup::run_cache_warmup(
+                    self.analysis_workers.clone(),
+                    ctx.channel_stats.clone(),
+                    ctx.warmup.clone(),
+                    ctx.channel_locks.clone(),
+                    ctx.llm_budget.clone(),
+                    config,
+                ));
+            }
+            None => info!("WARMUP_HOURS is not set, the cache warm-up is disabled"),
+        }
+
+        // forget the sessions of users who never came back and group messages past what
+        // a profile reads
+        tokio::spawn(user_sessions::run_session_purger(
+            ctx.user_sessions.clone(),
+            ctx.cross_group.clone(),
+        ));
+
+        // limits, model routing and gemini quotas can change without a restart
+        tokio::spawn(Self::reload_config_on_sighup(ctx.clone()));
+
+        // re-queue analyses interrupted by the previous shutdown, then work through the queue;
+        // the llm part of an analysis doesn't hold a worker, so twice as many run at a time
+        let runner = JobRunner::new(
+            ctx.job_queue.clone(),
+            AnalysisSource::Bot,
+            self.analysis_workers.size() * 2,
+            JOB_LEASE,
+        );
+        let job_ctx = ctx.clone();
+        let watchdog_pool = self.pool.clone();
+        let watchdog_config = self.config.clone();
+        tokio::spawn(async move {
+            recovery::recover_pending_analyses(job_ctx.clone()).await;
+            // started after recovery, which restarts the clock of the analyses it resumes
+            tokio::spawn(watchdog::run_analysis_watchdog(
+                watchdog_pool,
+                watchdog_config,
+            ));
+            runner
+                .run(move |job| Self::run_queued_analysis(job_ctx.clone(), job))
+                .await;
+        });
+
+        // group analysis commands only make sense to group admins, so only show them in their menu
+        let group_commands = Command::bot_commands()
+            .into_iter()
+            .filter(|command| matches!(command.command.as_str(), "/analyze_group" | "/analyze"))
+            .collect::<Vec<_>>();
+        if let Err(e) = self
+            .bot
+            .set_my_commands(group_commands.clone())
+            .scope(BotCommandScope::AllChatAdministrators)
+            .await
+        {
+            warn!("Failed to register group admin commands: {}", e);
+        }
+        // the same menu for admins whose telegram app is in another supported language
+        for lang in Lang::ALL
+            .into_iter()
+            .filter(|lang| *lang != Lang::default())
+        {
+            let commands = group_commands
+                .iter()
+                .map(|command| {
+                    let name = command.command.trim_start_matches('/');
+                    let description = lang
+                        .command_description(name)
+                        .unwrap_or(&command.description);
+                    BotCommand::new(name, description)
+                })
+                .collect::<Vec<_>>();
+            if let Err(e) = self
+                .bot
+                .set_my_commands(commands)
+                .scope(BotCommandScope::AllChatAdministrators)
+                .language_code(lang.code())
+                .await
+            {
+                warn!(
+                    "Failed to register {} group admin commands: {}",
+                    lang.code(),
+                    e
+                );
+            }
+        }
+
         let handler = dptree::entry()
             .branch(Update::filter_pre_checkout_query().endpoint({
                 let ctx = ctx.clone();
@@ -193,6 +579,13 @@ impl TelegramBot {
                     async move { PaymentHandler::handle_pre_checkout_query(ctx.bot, query).await }
                 }
             }))
+            .branch(Update::filter_inline_query().endpoint({
+                let ctx = ctx.clone();
+                move |query: InlineQuery| {
+                    let ctx = ctx.clone();
+                    async move { InlineHandler::handle_inline_query(ctx, query).await }
+                }
+            }))
             .branch(Update::filter_callback_query().endpoint({
                 let ctx = ctx.clone();
                 move |query: CallbackQuery| {
@@ -249,92 +642,123 @@ impl TelegramBot {
             .await;
     }
 
+    /// the user's bot language: their /language choice, else the Telegram app's language
+    pub(crate) async fn user_lang(ctx: &BotContext, user: Option<&User>) -> Lang {
+        let Some(user) = user else {
+            return Lang::default();
+        };
+        let language_override = match ctx.language_overrides.get(user.id.0 as i64).await {
+            Ok(language_override) => language_override,
+            Err(e) => {
+                warn!(
+                    "Failed to get language override for user {}: {}",
+                    user.id, e
+                );
+                None
+            }
+        };
+        Lang::resolve(language_override.as_deref(), user.language_code.as_deref())
+    }
+
+    /// whether a user's message or command may be handled; one over the user's rate limit
+    /// is dropped, and the first one of a flood gets a reply asking to slow down
+    pub(crate) async fn admit_message(
+        ctx: &BotContext,
+        msg: &Message,
+        lang: Lang,
+    ) -> ResponseResult<bool> {
+        let Some(user) = msg.from.as_ref() else {
+            return Ok(true);
+        };
+        match ctx
+            .user_rate_limiter
+            .check(user.id.0 as i64, Instant::now())
+        {
+            Throttle::Allowed => Ok(true),
+            Throttle::Limited {
+                retry_after,
+                notify,
+            } => {
+                info!("Throttled message from user {}", user.id);
+                if notify {
+                    ctx.bot
+                        .send_message(msg.chat.id, lang.slow_down(retry_after.as_secs().max(1)))
+                        .await?;
+                }
+                Ok(false)
+            }
+        }
+    }
+
     async fn handle_message(ctx: BotContext, msg: Message) -> ResponseResult<()> {
-        let lang = Lang::from_code(
-            msg.from
-                .as_ref()
-                .and_then(|user| user.language_code.as_deref()),
-        );
+        // group conversation is only answered while its sender types a focus instruction;
+        // otherwise it's kept for the cross-group profiles of those who agreed to it
+        if msg.chat.is_group() || msg.chat.is_supergroup() {
+            let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+            let awaiting_focus = ctx
+                .user_sessions
+                .get(telegram_user_id)
+                .await
+                .is_some_and(|session| session.awaiting_focus);
+            if !awaiting_focus {
+                cross_group::record_group_message(&ctx, &msg).await;
+                return Ok(());
+            }
+        }
+
+        let lang = Self::user_lang(&ctx, msg.from.as_ref()).await;
+
+        if !Self::admit_message(&ctx, &msg, lang).await? {
+            return Ok(());
+        }
+
+        // forwards belong to a self-analysis while one is being collected
+        if let Some(origin) = msg.forward_origin() {
+            if self_analysis::collect_forward(&ctx, &msg, origin, lang).await? {
+                return Ok(());
+            }
+        }
 
         if let Some(text) = msg.text() {
             let text = text.trim();
+            let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
 
-            // validate and normalize channel input
-            if let Some(channel_name) = Self::validate_and_normalize_channel(text) {
-                info!("Received channel analysis request: {}", channel_name);
-
-                // get user info from telegram message
-                let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
-                let username = msg.from.as_ref().and_then(|user| user.username.as_deref());
-                let first_name = msg.from.as_ref().map(|user| user.first_name.as_str());
-                let last_name = msg.from.as_ref().and_then(|user| user.last_name.as_deref());
-                let language_code = msg
-                    .from
-                    .as_ref()
-                    .and_then(|user| user.language_code.as_deref());
-
-                // get or create user and check credits
-                let user = match ctx
-                    .user_manager
-                    .get_or_create_user(
+            // a pending focus request takes any non-channel text as the focus instruction
+            let awaiting_focus_channel = ctx
+                .user_sessions
+                .get(telegram_user_id)
+                .await
+                .filter(|session| session.awaiting_focus)
+                .and_then(|session| session.channel_name);
+            if let Some(channel_name) = awaiting_focus_channel {
+                if Self::validate_and_normalize_channel(text).is_none()
+                    && Self::parse_invite_link(text).is_none()
+                {
+                    return Self::handle_focus_input(
+                        ctx,
+                        msg.chat.id,
                         telegram_user_id,
-                        username,
-                        first_name,
-                        last_name,
-                        None,
-                        language_code,
+                        &channel_name,
+                        text,
+                        lang,
                     )
-                    .await
-                {
-                    Ok((user, _)) => user,
-                    Err(e) => {
-                        error!("Failed to get/create user: {}", e);
-                        ctx.bot
-                            .send_message(msg.chat.id, lang.error_processing_request())
-                            .await?;
-                        return Ok(());
-                    }
-                };
-
-                // check if user has credits
-                if user.analysis_credits <= 0 {
-                    let bulk_discount =
-                        (SINGLE_PACKAGE_PRICE * BULK_PACKAGE_AMOUNT as u32) - BULK_PACKAGE_PRICE;
-                    let no_credits_msg = lang.no_credits_available(
-                        SINGLE_PACKAGE_PRICE,
-                        BULK_PACKAGE_PRICE,
-                        bulk_discount,
-                        user.analysis_credits,
-                        user.total_analyses_performed,
-                    );
-
-                    ctx.bot
-                        .send_message(msg.chat.id, no_credits_msg)
-                        .parse_mode(ParseMode::Html)
-                        .reply_markup(CallbackHandler::create_payment_keyboard(lang))
-                        .await?;
-                    return Ok(());
+                    .await;
                 }
+            }
 
-                // send immediate response with credit info
-                let credits_msg = lang.analysis_starting(user.analysis_credits - 1);
-                ctx.bot
-                    .send_message(msg.chat.id, credits_msg)
-                    .parse_mode(ParseMode::Html)
-                    .await?;
+            if let Some(channel_name) = Self::parse_invite_link(text) {
+                info!("Received private channel analysis request");
+                return Self::ask_invite_consent(ctx, &msg, channel_name, lang).await;
+            }
 
-                // show analysis type selection directly (validation will happen during analysis)
-                let selection_msg =
-                    lang.analysis_select_type(&MessageFormatter::escape_html(&channel_name));
+            if let Some(request) = BatchRequest::parse(text, ctx.limits().max_batch_channels) {
+                return batch::handle_batch_request(ctx, &msg, request, lang).await;
+            }
 
-                ctx.bot
-                    .send_message(msg.chat.id, selection_msg)
-                    .parse_mode(ParseMode::Html)
-                    .reply_markup(CallbackHandler::create_analysis_selection_keyboard(
-                        &channel_name,
-                        lang,
-                    ))
-                    .await?;
+            // validate and normalize channel input
+            if let Some(channel_name) = Self::validate_and_normalize_channel(text) {
+                info!("Received channel analysis request: {}", channel_name);
+                Self::offer_channel_analysis(ctx, &msg, channel_name, lang).await?;
             } else {
                 // send help message for invalid input
                 ctx.bot
@@ -345,141 +769,530 @@ impl TelegramBot {
         Ok(())
     }
 
-    pub async fn perform_single_analysis(
-        bot: Arc<Bot>,
-        user_chat_id: ChatId,
+    /// registers the requester and offers the analysis type selection for a channel,
+    /// with a preview of the channel when its web page is public
+    pub(crate) async fn offer_channel_analysis(
+        ctx: BotContext,
+        msg: &Message,
         channel_name: String,
-        analysis_type: String,
-        analysis_engine: Arc<Mutex<AnalysisEngine>>,
-        user_manager: Arc<UserManager>,
-        user_id: i32,
-        analysis_id: i32,
-        channel_locks: ChannelLocks,
         lang: Lang,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        info!(
-            "Starting {} analysis for channel: {}",
-            analysis_type, channel_name
-        );
+    ) -> ResponseResult<()> {
+        // the preview is a courtesy, analysis validates the channel again on its own
+        let preview = match ctx.web_scraper.fetch_channel_preview(&channel_name).await {
+            Ok(preview) => preview,
+            Err(e) => {
+                warn!("Failed to fetch preview of {}: {}", channel_name, e);
+                None
+            }
+        };
 
-        // notify user that analysis is starting
-        bot.send_message(user_chat_id, lang.analysis_in_progress(&analysis_type))
+        Self::offer_analysis(
+            ctx,
+            msg.chat.id,
+            msg.from.as_ref(),
+            channel_name,
+            None,
+            preview,
+            lang,
+        )
+        .await
+    }
+
+    /// explains what joining a private channel by its invite link involves; the analysis
+    /// types are only offered once the user agrees
+    async fn ask_invite_consent(
+        ctx: BotContext,
+        msg: &Message,
+        channel_name: String,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+        ctx.user_sessions
+            .insert(
+                telegram_user_id,
+                UserSession {
+                    channel_name: Some(channel_name),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        ctx.bot
+            .send_message(msg.chat.id, lang.invite_consent())
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback(
+                    lang.btn_invite_consent(),
+                    CallbackData::InviteConsent.encode(),
+                ),
+            ]]))
             .await?;
+        Ok(())
+    }
 
-        // prepare analysis data (with lock)
-        let analysis_data = {
-            let mut engine = analysis_engine.lock().await;
-            match engine.prepare_analysis_data(&channel_name).await {
-                Ok(data) => data,
-                Err(e) => {
-                    error!(
-                        "Failed to prepare analysis data for channel {}: {}",
-                        channel_name, e
-                    );
-                    bot.send_message(user_chat_id, lang.error_analysis_prepare(&channel_name))
-                        .parse_mode(ParseMode::Html)
-                        .await?;
-                    return Err(e);
-                }
+    /// offers the analysis types to `from` in `chat_id`; a group analysis can be limited
+    /// to one of its forum topics
+    pub(crate) async fn offer_analysis(
+        ctx: BotContext,
+        chat_id: ChatId,
+        from: Option<&teloxide::types::User>,
+        channel_name: String,
+        topic: Option<ForumTopic>,
+        preview: Option<ChannelPreview>,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let telegram_user_id = from.map(|user| user.id.0 as i64).unwrap_or(0);
+
+        // get user info from telegram message
+        let username = from.and_then(|user| user.username.as_deref());
+        let first_name = from.map(|user| user.first_name.as_str());
+        let last_name = from.and_then(|user| user.last_name.as_deref());
+        let language_code = from.and_then(|user| user.language_code.as_deref());
+
+        // get or create user and check credits
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                telegram_user_id,
+                username,
+                first_name,
+                last_name,
+                None,
+                language_code,
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_processing_request())
+                    .await?;
+                return Ok(());
             }
         };
 
-        // check if we received 0 messages and raise error
-        if analysis_data.messages.is_empty() {
-            bot.send_message(user_chat_id, lang.error_no_messages())
+        // check if user has credits, a new user has to claim the free trial first
+        if user.analysis_credits <= 0 && ctx.free_trials.is_pending(user.id).await {
+            ctx.bot
+                .send_message(chat_id, lang.welcome_verify_human())
                 .parse_mode(ParseMode::Html)
+                .reply_markup(CallbackHandler::create_verify_human_keyboard(lang))
                 .await?;
-            return Err("No messages found in channel".into());
+            return Ok(());
         }
+        if user.analysis_credits <= 0 {
+            let packages = ctx.packages.active().await;
+            let no_credits_msg = lang.no_credits_available(
+                &lang.package_prices(&packages),
+                user.analysis_credits,
+                user.total_analyses_performed,
+            );
 
-        // get or create per-channel lock to prevent concurrent LLM calls
-        let channel_lock = {
-            let mut locks = channel_locks.lock().await;
-            locks
-                .entry(channel_name.clone())
-                .or_insert_with(|| Arc::new(Mutex::new(())))
-                .clone()
-        };
+            ctx.bot
+                .send_message(chat_id, no_credits_msg)
+                .parse_mode(ParseMode::Html)
+                .reply_markup(CallbackHandler::create_payment_keyboard(&packages, lang))
+                .await?;
+            return Ok(());
+        }
 
-        // acquire channel lock before checking cache and calling LLM
-        let _channel_guard = channel_lock.lock().await;
+        // send immediate response with credit info
+        let credits_msg = lang.analysis_starting(user.analysis_credits - 1);
+        ctx.bot
+            .send_message(chat_id, credits_msg)
+            .parse_mode(ParseMode::Html)
+            .await?;
 
-        // check for cached result (re-check after acquiring channel lock)
-        let cached_result = {
-            let engine = analysis_engine.lock().await;
-            engine
-                .cache
-                .load_llm_result(&analysis_data.cache_key)
-                .await
+        // the topic is shown next to the group it belongs to
+        let target = match &topic {
+            Some(topic) => {
+                MessageFormatter::escape_html(&format!("{} › {}", channel_name, topic.name))
+            }
+            None => ResultPresenter::target_label(&channel_name, lang),
         };
 
-        let result = if let Some(cached_result) = cached_result {
-            info!("Using cached LLM result for channel {}", channel_name);
-            cached_result
-        } else {
-            // generate prompt without lock
-            let prompt = match crate::prompts::analysis::generate_analysis_prompt(
-                &analysis_data.messages,
-            ) {
-                Ok(p) => p,
-                Err(e) => {
-                    error!(
-                        "Failed to generate analysis prompt for channel {}: {}",
-                        channel_name, e
-                    );
-                    bot.send_message(user_chat_id, lang.error_prompt_generation())
-                        .parse_mode(ParseMode::Html)
-                        .await?;
-                    return Err(e);
+        // remember the channel so a focus instruction can be attached to it
+        ctx.user_sessions
+            .insert(
+                telegram_user_id,
+                UserSession {
+                    channel_name: Some(channel_name.clone()),
+                    topic,
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        // connect a client while the user picks a type; busy workers have a live
+        // client, so only prewarm when one is waiting for work
+        if ctx.analysis_workers.idle() > 0 {
+            let analysis_workers = ctx.analysis_workers.clone();
+            let prewarm_channel = channel_name.clone();
+            tokio::spawn(async move {
+                let prewarmed = analysis_workers
+                    .run(move |engine| {
+                        Box::pin(async move {
+                            engine
+                                .prewarm(&prewarm_channel, AnalysisDepth::default())
+                                .await;
+                            Ok(())
+                        })
+                    })
+                    .await;
+                if let Err(e) = prewarmed {
+                    warn!("Failed to pre-warm an analysis worker: {}", e);
                 }
-            };
+            });
+        }
 
-            info!(
-                "Querying LLM for {} analysis of channel {}...",
-                analysis_type, channel_name
+        // show analysis type selection directly (validation will happen during analysis)
+        let mut selection_msg = lang.analysis_select_type(&target);
+        if let Some(preview) = &preview {
+            let title = MessageFormatter::escape_html(&preview.title);
+            selection_msg = format!(
+                "{}\n\n{}",
+                lang.channel_preview(&title, preview),
+                selection_msg
             );
-            // perform LLM call (protected by channel lock)
-            let mut result =
-                match crate::llm::analysis_query::query_and_parse_analysis(&prompt).await {
-                    Ok(r) => r,
-                    Err(e) => {
-                        error!(
-                            "Failed to query LLM for {} analysis of channel {}: {}",
-                            analysis_type, channel_name, e
-                        );
-                        bot.send_message(user_chat_id, lang.error_ai_service())
-                            .parse_mode(ParseMode::Html)
-                            .await?;
-                        return Err(e);
-                    }
-                };
-            result.messages_count = analysis_data.messages.len();
+        }
 
-            // cache the result
-            {
-                let mut engine = analysis_engine.lock().await;
-                if let Err(e) = engine
-                    .finish_analysis(&analysis_data.cache_key, result.clone())
-                    .await
-                {
-                    error!(
-                        "Failed to cache analysis result for channel {}: {}",
-                        channel_name, e
-                    );
-                    // continue execution - caching failure shouldn't stop the analysis
+        ctx.bot
+            .send_message(chat_id, selection_msg)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_analysis_selection_keyboard(
+                &channel_name,
+                AnalysisDepth::default(),
+                &ctx.limits(),
+                lang,
+            ))
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_focus_input(
+        ctx: BotContext,
+        chat_id: ChatId,
+        telegram_user_id: i64,
+        channel_name: &str,
+        text: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        if text.chars().count() > MAX_FOCUS_LENGTH {
+            ctx.bot
+                .send_message(chat_id, lang.error_focus_too_long(MAX_FOCUS_LENGTH))
+                .await?;
+            return Ok(());
+        }
+
+        let depth = ctx
+            .user_sessions
+            .update_existing(telegram_user_id, |session| session.set_focus(text))
+            .await
+            .unwrap_or_default();
+
+        info!(
+            "Saved focus for user {} on channel {}: {}",
+            telegram_user_id, channel_name, text
+        );
+
+        ctx.bot
+            .send_message(
+                chat_id,
+                lang.focus_saved(
+                    &ResultPresenter::target_label(channel_name, lang),
+                    &MessageFormatter::escape_html(text),
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_analysis_selection_keyboard(
+                channel_name,
+                depth,
+                &ctx.limits(),
+                lang,
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// runs an analysis leased from the job queue; failures the analysis doesn't explain
+    /// itself get a generic message, the queue marks the analysis failed
+    pub async fn run_queued_analysis(ctx: BotContext, job: LeasedJob) -> Result<(), AppError> {
+        let analysis = job.analysis;
+        let chat_id = ChatId(job.chat_id.unwrap_or(analysis.telegram_user_id));
+        let lang = Lang::from_code(job.language.as_deref().or(analysis.language.as_deref()));
+        let depth = AnalysisDepth::from_code(&analysis.depth).unwrap_or_default();
+
+        // a second opinion needs the analysis it's on, for the model to avoid and compare with
+        let second_opinion = match analysis.second_opinion_of {
+            Some(original_id) => {
+                let original = ctx
+                    .user_manager
+                    .get_analysis(original_id, analysis.user_id)
+                    .await?
+                    .filter(|original| original.model.is_some());
+                if original.is_none() {
+                    let _ = ctx
+                        .bot
+                        .send_message(chat_id, lang.second_opinion_unavailable())
+                        .await;
+                    return Err(AppError::Validation(format!(
+                        "Analysis {} has no model to give a second opinion on",
+                        original_id
+                    )));
                 }
+                original
             }
-
-            result
+            None => None,
+        };
+        // verified owners analyze their own channel for free a few times a month
+        let credits = match &second_opinion {
+            Some(_) => ctx.limits().second_opinion_credits(depth),
+            None if channel_claims::is_free_owner_analysis(
+                &ctx,
+                analysis.telegram_user_id,
+                &analysis.channel_name,
+            )
+            .await =>
+            {
+                0
+            }
+            None => ctx.limits().depth_credits(depth),
         };
 
-        // ATOMIC OPERATION: consume credit + mark completed + send result (protected from shutdown)
-        let remaining_credits = match user_manager
-            .atomic_complete_analysis(analysis_id, user_id)
-            .await
-        {
-            Ok(credits) => credits,
+        let result = Self::perform_single_analysis(
+            ctx.bot.clone(),
+            chat_id,
+            analysis.channel_name.clone(),
+            analysis.analysis_type.clone(),
+            depth,
+            credits,
+            analysis.focus,
+            analysis.topic,
+            job.allow_low_text,
+            ctx.analysis_workers.clone(),
+            ctx.user_manager.clone(),
+            ctx.channel_stats.clone(),
+            ctx.limits(),
+            ctx.llm_budget.clone(),
+            analysis.user_id,
+            analysis.id,
+            second_opinion,
+            ctx.channel_locks.clone(),
+            ctx.showcase.is_some(),
+            ctx.voice.is_some(),
+            ctx.telegraph.clone(),
+            ctx.web_scraper.clone(),
+            lang,
+        )
+        .await;
+
+        match &result {
+            Err(AppError::InsufficientCredits(user_id)) => {
+                info!("Analysis failed: User {} has insufficient credits", user_id);
+                let _ = ctx
+                    .bot
+                    .send_message(chat_id, lang.error_insufficient_credits())
+                    .await;
+            }
+            Err(e @ AppError::Db(_)) => {
+                error!(
+                    "Analysis failed for channel {} (type: {}): {}",
+                    analysis.channel_name, analysis.analysis_type, e
+                );
+                let _ = ctx.bot.send_message(chat_id, lang.error_system()).await;
+            }
             Err(e) => {
+                // already explained to the user by perform_single_analysis
+                error!(
+                    "Analysis failed for channel {} (type: {}): {}",
+                    analysis.channel_name, analysis.analysis_type, e
+                );
+            }
+            Ok(()) => {}
+        }
+        result
+    }
+
+    /// runs an analysis and delivers its result or failure; announcing the start is up to
+    /// the caller, so a batch can track all of its analyses in one progress message
+    #[allow(clippy::too_many_arguments)]
+    pub async fn perform_single_analysis(
+        bot: Arc<Bot>,
+        user_chat_id: ChatId,
+        channel_name: String,
+        analysis_type: String,
+        depth: AnalysisDepth,
+        credits: i32,
+        focus: Option<String>,
+        topic: Option<ForumTopic>,
+        allow_low_text: bool,
+        analysis_workers: Arc<AnalysisWorkers>,
+        user_manager: Arc<UserManager>,
+        channel_stats: Arc<ChannelStatsManager>,
+        limits: Arc<Limits>,
+        llm_budget: Arc<LlmBudget>,
+        user_id: i32,
+        analysis_id: i32,
+        second_opinion: Option<AnalysisRecord>,
+        channel_locks: ChannelLocks,
+        showcase_enabled: bool,
+        voice_enabled: bool,
+        telegraph: Arc<TelegraphClient>,
+        web_scraper: Arc<TelegramWebScraper>,
+        lang: Lang,
+    ) -> Result<(), AppError> {
+        info!(
+            "Starting {} analysis for channel: {}",
+            analysis_type, channel_name
+        );
+
+        let job = AnalysisJob {
+            analysis_id,
+            user_id,
+            channel_name: channel_name.clone(),
+            analysis_type: analysis_type.clone(),
+            depth,
+            credits,
+            focus,
+            topic,
+            allow_low_text,
+            second_opinion_on: second_opinion
+                .as_ref()
+                .and_then(|original| original.model.clone()),
+        };
+        // only channels too large for one prompt report progress, in a message of its own
+        let (progress, progress_updates) = watch::channel((0, 0));
+        let progress_reporter = tokio::spawn(Self::report_summary_progress(
+            bot.clone(),
+            user_chat_id,
+            progress_updates,
+            lang,
+        ));
+        let outcome = run_analysis(
+            &analysis_workers,
+            &user_manager,
+            &channel_stats,
+            &channel_locks,
+            &llm_budget,
+            &job,
+            Some(&progress),
+        )
+        .await;
+        drop(progress);
+        let _ = progress_reporter.await;
+
+        let AnalysisOutcome {
+            result,
+            remaining_credits,
+            kind,
+        } = match outcome {
+            Ok(outcome) => outcome,
+            Err(AnalysisRunError::Prepare(e)) => {
+                error!(
+                    "Failed to prepare analysis data for channel {}: {}",
+                    channel_name, e
+                );
+                let failure = e.failure();
+                let mut request = bot
+                    .send_message(
+                        user_chat_id,
+                        lang.error_analysis_failed(
+                            &failure,
+                            &ResultPresenter::target_label(&channel_name, lang),
+                        ),
+                    )
+                    .parse_mode(ParseMode::Html);
+                // oversized channels can be retried right away at another depth; forwarded
+                // messages are stored once and only come in the quick one. channels with
+                // little text run once the user confirms
+                match failure {
+                    AnalysisError::CorpusTooLarge { depth, .. }
+                        if !is_self_corpus(&channel_name) =>
+                    {
+                        let suggested = match depth {
+                            AnalysisDepth::Deep => AnalysisDepth::Small,
+                            _ => AnalysisDepth::Deep,
+                        };
+                        request = request.reply_markup(
+                            CallbackHandler::create_analysis_selection_keyboard(
+                                &channel_name,
+                                suggested,
+                                &limits,
+                                lang,
+                            ),
+                        );
+                    }
+                    AnalysisError::LowTextCoverage(_) => {
+                        request = request.reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                            InlineKeyboardButton::callback(
+                                lang.btn_analyze_anyway(),
+                                CallbackData::LowTextConfirm(analysis_id).encode(),
+                            ),
+                        ]]));
+                    }
+                    _ => {}
+                }
+                request.await.map_err(AppError::telegram)?;
+                return Err(e);
+            }
+            Err(AnalysisRunError::NoMessages) => {
+                bot.send_message(user_chat_id, lang.error_no_messages())
+                    .parse_mode(ParseMode::Html)
+                    .await
+                    .map_err(AppError::telegram)?;
+                return Err(AppError::Validation(
+                    AnalysisRunError::NoMessages.to_string(),
+                ));
+            }
+            Err(AnalysisRunError::BudgetExceeded) => {
+                bot.send_message(user_chat_id, lang.error_llm_budget_exceeded())
+                    .parse_mode(ParseMode::Html)
+                    .await
+                    .map_err(AppError::telegram)?;
+                return Err(AppError::Validation(
+                    AnalysisRunError::BudgetExceeded.to_string(),
+                ));
+            }
+            Err(AnalysisRunError::Prompt(e)) => {
+                error!(
+                    "Failed to generate analysis prompt for channel {}: {}",
+                    channel_name, e
+                );
+                // a trends analysis of a channel without enough history is the user's to fix
+                let failure = AnalysisError::classify(e.as_ref());
+                let text = match failure {
+                    AnalysisError::ShortHistory => lang.error_analysis_failed(
+                        &failure,
+                        &ResultPresenter::target_label(&channel_name, lang),
+                    ),
+                    _ => lang.error_prompt_generation().to_string(),
+                };
+                bot.send_message(user_chat_id, text)
+                    .parse_mode(ParseMode::Html)
+                    .await
+                    .map_err(AppError::telegram)?;
+                return Err(failure.into());
+            }
+            Err(AnalysisRunError::Llm(e)) => {
+                error!(
+                    "Failed to query LLM for {} analysis of channel {}: {}",
+                    analysis_type, channel_name, e
+                );
+                let failure = e.failure();
+                bot.send_message(
+                    user_chat_id,
+                    lang.error_analysis_failed(
+                        &failure,
+                        &ResultPresenter::target_label(&channel_name, lang),
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .await
+                .map_err(AppError::telegram)?;
+                return Err(e);
+            }
+            Err(AnalysisRunError::Complete(e)) => {
                 match &e {
                     UserManagerError::InsufficientCredits(user_id) => {
                         info!(
@@ -487,6 +1300,12 @@ impl TelegramBot {
                             analysis_id, user_id
                         );
                     }
+                    UserManagerError::NotPending(_) => {
+                        warn!(
+                            "Analysis {} not completed: the watchdog timed it out",
+                            analysis_id
+                        );
+                    }
                     _ => {
                         error!(
                             "Failed to atomically complete analysis {}: {}",
@@ -501,108 +1320,356 @@ impl TelegramBot {
                         analysis_id, mark_err
                     );
                 }
-                return Err(Box::new(e));
+                return Err(e.into());
             }
         };
 
         // notify user that analysis is complete and send results with credit info
-        let completion_msg = lang.analysis_complete(&analysis_type, user_id, remaining_credits);
+        let mut completion_msg = lang.analysis_complete(&analysis_type, user_id, remaining_credits);
+        if let Some(model) = &result.model {
+            completion_msg.push_str(&lang.analysis_model_used(ModelTier::of_model(model)));
+        }
+        if result.removed_messages > 0 {
+            completion_msg.push_str(&lang.analysis_removed_posts(result.removed_messages));
+        }
+        if kind == CorpusKind::Profile {
+            completion_msg.push_str(lang.analysis_profile_note());
+        }
+        if let Some(original) = &second_opinion {
+            let first_score = match &original.cache_key {
+                Some(cache_key) => analysis_workers.cache.load_llm_result(cache_key).await,
+                None => None,
+            }
+            .and_then(|first| first.report)
+            .map(|report| report.scores.average());
+            let second_score = result.report.as_ref().map(|report| report.scores.average());
+            completion_msg.push_str(&lang.second_opinion_comparison(
+                original.model.as_deref().unwrap_or_default(),
+                first_score,
+                result.model.as_deref().unwrap_or_default(),
+                second_score,
+            ));
+        }
+        let mut buttons = vec![vec![InlineKeyboardButton::callback(
+            lang.btn_resend_result(),
+            CallbackData::Resend(analysis_id).encode(),
+        )]];
+        if voice_enabled {
+            buttons.push(vec![InlineKeyboardButton::callback(
+                lang.btn_listen(),
+                CallbackData::Listen(analysis_id).encode(),
+            )]);
+        }
+        // a second opinion doesn't get one of its own
+        if result.model.is_some() && second_opinion.is_none() {
+            buttons.push(vec![InlineKeyboardButton::callback(
+                lang.btn_second_opinion(limits.second_opinion_credits(depth)),
+                CallbackData::SecondOpinion(analysis_id).encode(),
+            )]);
+        }
         bot.send_message(user_chat_id, completion_msg)
             .parse_mode(ParseMode::Html)
-            .await?;
+            .reply_markup(InlineKeyboardMarkup::new(buttons))
+            .await
+            .map_err(AppError::telegram)?;
+
+        let partial = result.partial;
+        let public_channel = kind == CorpusKind::Channel
+            && !is_self_corpus(&channel_name)
+            && invite_hash(&channel_name).is_none();
+        let health = if public_channel {
+            Self::channel_health(&web_scraper, &channel_name, lang).await
+        } else {
+            None
+        };
 
         // send single analysis result to user
-        Self::send_single_analysis_to_user(
-            bot,
+        let delivered = Self::send_single_analysis_to_user(
+            bot.clone(),
+            &user_manager,
+            &telegraph,
             user_chat_id,
             &channel_name,
             &analysis_type,
             result,
+            health,
             user_id,
+            analysis_id,
             lang,
         )
         .await?;
+        // nothing to regenerate or show off, the credits are back already
+        if !delivered {
+            return Ok(());
+        }
+
+        if partial {
+            bot.send_message(user_chat_id, lang.analysis_partial_offer())
+                .reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                    InlineKeyboardButton::callback(
+                        lang.btn_regenerate_free(),
+                        CallbackData::Regenerate(analysis_id).encode(),
+                    ),
+                ]]))
+                .await
+                .map_err(AppError::telegram)?;
+        } else if showcase_enabled && public_channel {
+            // only complete analyses of public channels are worth showing off
+            bot.send_message(user_chat_id, lang.showcase_offer())
+                .reply_markup(CallbackHandler::create_showcase_keyboard(analysis_id, lang))
+                .await
+                .map_err(AppError::telegram)?;
+        }
 
         Ok(())
     }
 
+    /// keeps a message up to date with the batches of a large channel summarized so far and
+    /// deletes it once the analysis is done; returns when the analysis drops its sender
+    async fn report_summary_progress(
+        bot: Arc<Bot>,
+        chat_id: ChatId,
+        mut updates: watch::Receiver<(usize, usize)>,
+        lang: Lang,
+    ) {
+        let mut message_id = None;
+        while updates.changed().await.is_ok() {
+            let (done, total) = *updates.borrow_and_update();
+            let text = lang.analysis_summarizing(done, total);
+            let sent = match message_id {
+                Some(id) => bot
+                    .edit_message_text(chat_id, id, text)
+                    .await
+                    .map(|message| message.id),
+                None => bot
+                    .send_message(chat_id, text)
+                    .await
+                    .map(|message| message.id),
+            };
+            match sent {
+                Ok(id) => message_id = Some(id),
+                Err(e) => warn!("Failed to report summary progress in {}: {}", chat_id, e),
+            }
+        }
+        if let Some(id) = message_id {
+            let _ = bot.delete_message(chat_id, id).await;
+        }
+    }
+
+    /// the channel health section of a public channel's analysis, from the view and reaction
+    /// counters on its web page; None when the page can't be read or shows no posts
+    async fn channel_health(
+        web_scraper: &TelegramWebScraper,
+        channel_name: &str,
+        lang: Lang,
+    ) -> Option<String> {
+        let preview = match web_scraper.fetch_channel_preview(channel_name).await {
+            Ok(preview) => preview?,
+            Err(e) => {
+                warn!(
+                    "Failed to fetch preview of {} for its health: {}",
+                    channel_name, e
+                );
+                return None;
+            }
+        };
+        let health = ChannelHealth::from_preview(&preview, chrono::Utc::now().timestamp())?;
+        Some(lang.channel_health(&health))
+    }
+
+    /// sends the messages of a rendered result, with the rating buttons under the last one
+    pub(crate) async fn send_rendered_result(
+        bot: &Bot,
+        chat_id: ChatId,
+        messages: &[String],
+        analysis_id: i32,
+    ) -> ResponseResult<()> {
+        Self::send_result_messages(bot, chat_id, messages, analysis_id, ParseMode::Html).await
+    }
+
+    async fn send_result_messages(
+        bot: &Bot,
+        chat_id: ChatId,
+        messages: &[String],
+        analysis_id: i32,
+        parse_mode: ParseMode,
+    ) -> ResponseResult<()> {
+        for (i, message) in messages.iter().enumerate() {
+            let mut request = bot.send_message(chat_id, message).parse_mode(parse_mode);
+            if i + 1 == messages.len() {
+                request =
+                    request.reply_markup(CallbackHandler::create_feedback_keyboard(analysis_id));
+            }
+            request.await?;
+        }
+        Ok(())
+    }
+
+    /// false when the result had nothing to deliver, its credits are given back then
+    #[allow(clippy::too_many_arguments)]
     async fn send_single_analysis_to_user(
         bot: Arc<Bot>,
+        user_manager: &UserManager,
+        telegraph: &TelegraphClient,
         user_chat_id: ChatId,
         channel_name: &str,
         analysis_type: &str,
         result: AnalysisResult,
+        health: Option<String>,
         user_id: i32,
+        analysis_id: i32,
         lang: Lang,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let analysis_content = match analysis_type {
-            "professional" => &result.professional,
-            "personal" => &result.personal,
-            "roast" => &result.roast,
-            _ => &None,
-        };
-
-        match analysis_content {
-            Some(content) if !content.is_empty() => {
-                // convert LLM markdown content to HTML first
-                let html_content = MessageFormatter::markdown_to_html_safe(content);
-
-                // prepare header template that will be added to each part
-                let header =
-                    lang.analysis_result_header(&MessageFormatter::escape_html(channel_name), user_id);
-                let analysis_header = lang.analysis_type_header(analysis_type);
-
-                // calculate available space for content after headers (using UTF-16 code units as Telegram does)
-                const MAX_MESSAGE_LENGTH: usize = 3584;
-                let headers_length = MessageFormatter::count_utf16_code_units(&header)
-                    + MessageFormatter::count_utf16_code_units(&analysis_header);
-                let available_content_length =
-                    MAX_MESSAGE_LENGTH.saturating_sub(headers_length + 100); // buffer for part indicators
-
-                // split content if needed
-                let content_chunks = MessageFormatter::split_message_into_chunks(
-                    &html_content,
-                    available_content_length,
-                );
-
-                for (i, chunk) in content_chunks.iter().enumerate() {
-                    let full_message = if content_chunks.len() > 1 {
-                        format!(
-                            "{}{}{}{}",
-                            header,
-                            analysis_header,
-                            chunk,
-                            lang.analysis_part_indicator(i + 1, content_chunks.len())
+    ) -> Result<bool, AppError> {
+        match ResultPresenter::render(&result, analysis_type, channel_name, user_id, lang) {
+            Some(mut messages) => {
+                if messages.len() >= WEB_PAGE_MIN_PARTS {
+                    let summary = Self::publish_web_page(
+                        telegraph,
+                        user_manager,
+                        &result,
+                        channel_name,
+                        analysis_type,
+                        user_id,
+                        analysis_id,
+                        lang,
+                    )
+                    .await;
+                    if let Some(summary) = summary {
+                        messages = vec![summary];
+                    }
+                }
+                // the numbers follow the analysis as a message of their own
+                messages.extend(health.clone());
+                // stored first, so a failed send below can be fixed with /resend
+                if let Err(e) = user_manager
+                    .store_rendered_result(analysis_id, &messages)
+                    .await
+                {
+                    error!(
+                        "Failed to store rendered result of analysis {}: {}",
+                        analysis_id, e
+                    );
+                }
+                match Self::send_rendered_result(&bot, user_chat_id, &messages, analysis_id).await
+                {
+                    // the model's markdown can still make html telegram won't parse, the
+                    // whole result goes out again in MarkdownV2 then
+                    Err(RequestError::Api(ApiError::CantParseEntities(reason))) => {
+                        warn!(
+                            "Telegram couldn't parse the HTML of analysis {} ({}), sending it as MarkdownV2",
+                            analysis_id, reason
+                        );
+                        let fallback = ResultPresenter::render_markdown_v2(
+                            &result,
+                            analysis_type,
+                            channel_name,
+                            user_id,
+                            lang,
                         )
-                    } else {
-                        format!("{}{}{}", header, analysis_header, chunk)
-                    };
-
-                    bot.send_message(user_chat_id, full_message)
-                        .parse_mode(ParseMode::Html)
-                        .await?;
+                        .unwrap_or_default();
+                        Self::send_result_messages(
+                            &bot,
+                            user_chat_id,
+                            &fallback,
+                            analysis_id,
+                            ParseMode::MarkdownV2,
+                        )
+                        .await
+                        .map_err(AppError::telegram)?;
+                        // the health section is built by the bot and always parses
+                        if let Some(health) = health {
+                            bot.send_message(user_chat_id, health)
+                                .parse_mode(ParseMode::Html)
+                                .await
+                                .map_err(AppError::telegram)?;
+                        }
+                    }
+                    sent => sent.map_err(AppError::telegram)?,
                 }
 
                 info!(
                     "Sent {} analysis results to user for channel: {} ({} parts)",
                     analysis_type,
                     channel_name,
-                    content_chunks.len()
+                    messages.len()
                 );
             }
-            _ => {
+            None => {
                 error!(
                     "No {} analysis content available for channel: {} (user: {})",
                     analysis_type, channel_name, user_chat_id
                 );
-                bot.send_message(
-                    user_chat_id,
-                    lang.error_no_analysis_content(analysis_type),
-                )
-                .await?;
+                let mut text = lang.error_no_analysis_content(analysis_type);
+                // the analysis was charged when it completed, a failed send can be fixed
+                // with /resend but an empty result can't
+                match user_manager.refund_analysis_credits(analysis_id).await {
+                    Ok(Some((credits, balance))) => {
+                        text.push_str("\n\n");
+                        text.push_str(&lang.analysis_credits_returned(credits, balance));
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!(
+                        "Failed to give back the credits of analysis {}: {}",
+                        analysis_id, e
+                    ),
+                }
+                bot.send_message(user_chat_id, text)
+                    .await
+                    .map_err(AppError::telegram)?;
+                return Ok(false);
             }
         }
 
-        Ok(())
+        Ok(true)
+    }
+
+    /// publishes a long result as a telegra.ph page and returns the message that links to it;
+    /// None when the user didn't turn pages on or publishing failed, the result then goes out
+    /// as messages
+    #[allow(clippy::too_many_arguments)]
+    async fn publish_web_page(
+        telegraph: &TelegraphClient,
+        user_manager: &UserManager,
+        result: &AnalysisResult,
+        channel_name: &str,
+        analysis_type: &str,
+        user_id: i32,
+        analysis_id: i32,
+        lang: Lang,
+    ) -> Option<String> {
+        match user_manager.get_web_pages_enabled(user_id).await {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => {
+                error!(
+                    "Failed to get web pages setting for user {}: {}",
+                    user_id, e
+                );
+                return None;
+            }
+        }
+
+        let (title, content) =
+            ResultPresenter::render_web_page(result, analysis_type, channel_name, lang)?;
+        match telegraph.publish(&title, &content).await {
+            Ok(url) => {
+                info!("Published analysis {} as {}", analysis_id, url);
+                ResultPresenter::render_web_page_summary(
+                    result,
+                    analysis_type,
+                    channel_name,
+                    user_id,
+                    &url,
+                    lang,
+                )
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to publish analysis {} as a page, sending it as messages: {}",
+                    analysis_id, e
+                );
+                None
+            }
+        }
     }
 }
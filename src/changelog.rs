@@ -0,0 +1,243 @@
+use deadpool_postgres::Pool;
+use log::info;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use tokio_postgres::Row;
+
+use crate::error::AppError;
+use crate::localization::Lang;
+use crate::utils::MessageFormatter;
+
+// users who joined, changed anything or ran an analysis this recently get announcements
+const ACTIVE_USER_DAYS: f64 = 90.0;
+
+// columns read by ChangelogManager::entry_from_row, in order
+const ENTRY_COLUMNS: &str = "id, title_en, title_ru, body_en, body_ru, major, \
+    TO_CHAR(published_at AT TIME ZONE 'UTC', 'YYYY-MM-DD'), announced_at IS NOT NULL";
+
+#[derive(Debug)]
+pub enum ChangelogError {
+    Database(Box<dyn Error + Send + Sync>),
+    NotFound(i32),
+    NotMajor(i32),
+    AlreadyAnnounced(i32),
+}
+
+impl fmt::Display for ChangelogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangelogError::Database(e) => write!(f, "Database error: {}", e),
+            ChangelogError::NotFound(id) => write!(f, "Changelog entry {} not found", id),
+            ChangelogError::NotMajor(id) => {
+                write!(f, "Changelog entry {} is not a major release", id)
+            }
+            ChangelogError::AlreadyAnnounced(id) => {
+                write!(f, "Changelog entry {} has already been announced", id)
+            }
+        }
+    }
+}
+
+impl Error for ChangelogError {}
+
+impl From<tokio_postgres::Error> for ChangelogError {
+    fn from(err: tokio_postgres::Error) -> Self {
+        ChangelogError::Database(Box::new(err))
+    }
+}
+
+impl From<deadpool_postgres::PoolError> for ChangelogError {
+    fn from(err: deadpool_postgres::PoolError) -> Self {
+        ChangelogError::Database(Box::new(err))
+    }
+}
+
+impl From<ChangelogError> for AppError {
+    fn from(err: ChangelogError) -> Self {
+        match err {
+            ChangelogError::Database(e) => AppError::Db(e),
+            _ => AppError::Validation(err.to_string()),
+        }
+    }
+}
+
+/// a shipped change as shown by /whatsnew; russian texts are optional, and the other
+/// languages get the english ones
+#[derive(Debug, Clone)]
+pub struct ChangelogEntry {
+    pub id: i32,
+    pub title_en: String,
+    pub title_ru: Option<String>,
+    pub body_en: String,
+    pub body_ru: Option<String>,
+    pub major: bool,
+    pub published_at: String, // formatted by postgres as YYYY-MM-DD (UTC)
+    pub announced: bool,
+}
+
+impl ChangelogEntry {
+    pub fn title(&self, lang: Lang) -> &str {
+        match lang {
+            Lang::Ru => self.title_ru.as_deref().unwrap_or(&self.title_en),
+            Lang::En | Lang::Uk | Lang::Es | Lang::De => &self.title_en,
+        }
+    }
+
+    pub fn body(&self, lang: Lang) -> &str {
+        match lang {
+            Lang::Ru => self.body_ru.as_deref().unwrap_or(&self.body_en),
+            Lang::En | Lang::Uk | Lang::Es | Lang::De => &self.body_en,
+        }
+    }
+
+    /// entry texts are plain text, escaped here for html messages
+    pub fn render(&self, lang: Lang) -> String {
+        lang.changelog_entry(
+            &self.published_at,
+            &MessageFormatter::escape_html(self.title(lang)),
+            &MessageFormatter::escape_html(self.body(lang)),
+        )
+    }
+}
+
+pub struct ChangelogManager {
+    pool: Arc<Pool>,
+}
+
+impl ChangelogManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    fn entry_from_row(row: &Row) -> ChangelogEntry {
+        ChangelogEntry {
+            id: row.get(0),
+            title_en: row.get(1),
+            title_ru: row.get(2),
+            body_en: row.get(3),
+            body_ru: row.get(4),
+            major: row.get(5),
+            published_at: row.get(6),
+            announced: row.get(7),
+        }
+    }
+
+    pub async fn add_entry(
+        &self,
+        title_en: &str,
+        title_ru: Option<&str>,
+        body_en: &str,
+        body_ru: Option<&str>,
+        major: bool,
+    ) -> Result<i32, ChangelogError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "INSERT INTO changelog_entries (title_en, title_ru, body_en, body_ru, major)
+                 VALUES ($1, $2, $3, $4, $5) RETURNING id",
+                &[&title_en, &title_ru, &body_en, &body_ru, &major],
+            )
+            .await?;
+        let id: i32 = row.get(0);
+        info!("Added changelog entry {}: {}", id, title_en);
+        Ok(id)
+    }
+
+    /// newest entries first
+    pub async fn recent_entries(&self, limit: i64) -> Result<Vec<ChangelogEntry>, ChangelogError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM changelog_entries ORDER BY published_at DESC, id DESC LIMIT $1",
+                    ENTRY_COLUMNS
+                ),
+                &[&limit],
+            )
+            .await?;
+        Ok(rows.iter().map(Self::entry_from_row).collect())
+    }
+
+    pub async fn get_entry(&self, id: i32) -> Result<ChangelogEntry, ChangelogError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM changelog_entries WHERE id = $1",
+                    ENTRY_COLUMNS
+                ),
+                &[&id],
+            )
+            .await?
+            .ok_or(ChangelogError::NotFound(id))?;
+        Ok(Self::entry_from_row(&row))
+    }
+
+    /// (telegram_user_id, language) of active users who haven't turned announcements off
+    pub async fn announcement_recipients(
+        &self,
+    ) -> Result<Vec<(i64, Option<String>)>, ChangelogError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT u.telegram_user_id, COALESCE(u.language_override, u.language)
+                 FROM users u
+                 WHERE u.announcements_enabled
+                   AND (GREATEST(u.created_at, u.updated_at) > NOW() - INTERVAL '1 day' * $1
+                        OR EXISTS (
+                            SELECT 1 FROM user_analyses ua
+                            WHERE ua.user_id = u.id
+                              AND ua.analysis_timestamp > NOW() - INTERVAL '1 day' * $1
+                        ))",
+                &[&ACTIVE_USER_DAYS],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect())
+    }
+
+    /// queues a one-time announcement of a major entry for the message queue processor,
+    /// returns the number of queued messages
+    pub async fn announce(&self, id: i32) -> Result<usize, ChangelogError> {
+        let entry = self.get_entry(id).await?;
+        if !entry.major {
+            return Err(ChangelogError::NotMajor(id));
+        }
+        let recipients = self.announcement_recipients().await?;
+
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        // claiming the entry in the same transaction keeps announcements one-time
+        let claimed = transaction
+            .execute(
+                "UPDATE changelog_entries SET announced_at = NOW() WHERE id = $1 AND announced_at IS NULL",
+                &[&id],
+            )
+            .await?;
+        if claimed == 0 {
+            return Err(ChangelogError::AlreadyAnnounced(id));
+        }
+
+        for (telegram_user_id, language) in &recipients {
+            let lang = Lang::from_code(language.as_deref());
+            let message = lang.changelog_announcement(&entry.render(lang));
+            transaction
+                .execute(
+                    "INSERT INTO message_queue (telegram_user_id, message, parse_mode) VALUES ($1, $2, $3)",
+                    &[telegram_user_id, &message, &"HTML"],
+                )
+                .await?;
+        }
+        transaction.commit().await?;
+
+        info!(
+            "Queued announcement of changelog entry {} for {} users",
+            id,
+            recipients.len()
+        );
+        Ok(recipients.len())
+    }
+}
@@ -0,0 +1,366 @@
+//! channel owners prove they run a channel with /claim, by posting a code in it or by
+//! making the bot one of its admins for a moment; a verified owner can opt the channel out
+//! of analysis and analyzes it for free a few times a month
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use rand::Rng;
+use std::error::Error;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::{ParseMode, Recipient};
+
+use crate::blocklist::BlockTarget;
+use crate::bot::{BotContext, TelegramBot};
+use crate::handlers::CallbackHandler;
+use crate::localization::Lang;
+use crate::utils::MessageFormatter;
+
+// codes are short enough to type into a post and long enough not to be guessed
+const CODE_LENGTH: usize = 8;
+// no 0/O or 1/I, the owner may retype the code instead of pasting it
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_PREFIX: &str = "claim-";
+
+// the blocklist reason of channels their owners opted out
+const OPT_OUT_REASON: &str = "opted out by the owner";
+
+/// how an owner proved they run the channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimMethod {
+    // the claim's code showed up in the channel's latest posts
+    Post,
+    // the bot was made an admin and saw the user among the channel's admins
+    Admin,
+}
+
+impl ClaimMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClaimMethod::Post => "post",
+            ClaimMethod::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChannelClaim {
+    pub id: i32,
+    // lowercase @username
+    pub channel_name: String,
+    pub telegram_user_id: i64,
+    pub code: String,
+    pub verified: bool,
+    pub opted_out: bool,
+}
+
+/// a fresh verification code, e.g. claim-7KQ2MX9P
+pub fn new_claim_code() -> String {
+    let mut rng = rand::thread_rng();
+    let code = (0..CODE_LENGTH)
+        .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect::<String>();
+    format!("{}{}", CODE_PREFIX, code)
+}
+
+/// whether one of the posts carries the code, whatever the case
+pub fn posts_contain_code(posts: &[String], code: &str) -> bool {
+    let code = code.to_lowercase();
+    posts.iter().any(|post| post.to_lowercase().contains(&code))
+}
+
+/// channel names are matched whatever the case they were typed in
+fn claim_key(channel_name: &str) -> String {
+    channel_name.to_lowercase()
+}
+
+pub struct ChannelClaimManager {
+    pool: Arc<Pool>,
+}
+
+impl ChannelClaimManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    fn claim_from_row(row: &tokio_postgres::Row) -> ChannelClaim {
+        ChannelClaim {
+            id: row.get(0),
+            channel_name: row.get(1),
+            telegram_user_id: row.get(2),
+            code: row.get(3),
+            verified: row.get(4),
+            opted_out: row.get(5),
+        }
+    }
+
+    /// the user's claim of the channel, started with a new code unless they already have
+    /// one; None when someone else has verified the channel already
+    pub async fn start_claim(
+        &self,
+        channel_name: &str,
+        telegram_user_id: i64,
+    ) -> Result<Option<ChannelClaim>, Box<dyn Error + Send + Sync>> {
+        let channel_name = claim_key(channel_name);
+        let client = self.pool.get().await?;
+        let owner = client
+            .query_opt(
+                "SELECT telegram_user_id FROM channel_claims
+                 WHERE channel_name = $1 AND verified_at IS NOT NULL",
+                &[&channel_name],
+            )
+            .await?;
+        if owner.is_some_and(|row| row.get::<_, i64>(0) != telegram_user_id) {
+            return Ok(None);
+        }
+        let row = client
+            .query_one(
+                "INSERT INTO channel_claims (channel_name, telegram_user_id, code)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (channel_name, telegram_user_id)
+                 DO UPDATE SET channel_name = EXCLUDED.channel_name
+                 RETURNING id, channel_name, telegram_user_id, code, verified_at IS NOT NULL, opted_out",
+                &[&channel_name, &telegram_user_id, &new_claim_code()],
+            )
+            .await?;
+        Ok(Some(Self::claim_from_row(&row)))
+    }
+
+    /// a claim by id, only for the user who started it
+    pub async fn get_claim(
+        &self,
+        claim_id: i32,
+        telegram_user_id: i64,
+    ) -> Result<Option<ChannelClaim>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT id, channel_name, telegram_user_id, code, verified_at IS NOT NULL, opted_out
+                 FROM channel_claims WHERE id = $1 AND telegram_user_id = $2",
+                &[&claim_id, &telegram_user_id],
+            )
+            .await?;
+        Ok(row.as_ref().map(Self::claim_from_row))
+    }
+
+    /// records the proof; false when someone else verified the channel first
+    pub async fn mark_verified(
+        &self,
+        claim_id: i32,
+        method: ClaimMethod,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE channel_claims SET verified_at = NOW(), method = $2
+                 WHERE id = $1 AND verified_at IS NULL
+                   AND NOT EXISTS (
+                       SELECT 1 FROM channel_claims other
+                       WHERE other.channel_name = channel_claims.channel_name
+                         AND other.verified_at IS NOT NULL
+                   )",
+                &[&claim_id, &method.as_str()],
+            )
+            .await?;
+        if updated > 0 {
+            info!("Channel claim {} verified by {}", claim_id, method.as_str());
+        }
+        Ok(updated > 0)
+    }
+
+    /// remembers the owner's choice; the blocklist entry itself is up to the caller
+    pub async fn set_opted_out(
+        &self,
+        claim_id: i32,
+        opted_out: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE channel_claims SET opted_out = $2
+                 WHERE id = $1 AND verified_at IS NOT NULL",
+                &[&claim_id, &opted_out],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// whether an analysis of the channel by this user is on the house: they are its verified
+    /// owner and got fewer than `free_per_month` free analyses of it this month
+    pub async fn owner_analysis_is_free(
+        &self,
+        telegram_user_id: i64,
+        channel_name: &str,
+        free_per_month: i32,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "SELECT EXISTS (
+                     SELECT 1 FROM channel_claims
+                     WHERE channel_name = $2 AND telegram_user_id = $1 AND verified_at IS NOT NULL
+                 ),
+                 (SELECT COUNT(*) FROM user_analyses ua
+                  JOIN users u ON u.id = ua.user_id
+                  WHERE u.telegram_user_id = $1 AND LOWER(ua.channel_name) = $2
+                    AND ua.status = 'completed' AND ua.credits_used = 0
+                    AND ua.analysis_timestamp >= DATE_TRUNC('month', NOW()))",
+                &[&telegram_user_id, &claim_key(channel_name)],
+            )
+            .await?;
+        let (owner, used): (bool, i64) = (row.get(0), row.get(1));
+        Ok(owner && used < i64::from(free_per_month))
+    }
+}
+
+/// whether the user's analysis of the channel is one of their free owner analyses; a failed
+/// lookup charges as usual
+pub async fn is_free_owner_analysis(
+    ctx: &BotContext,
+    telegram_user_id: i64,
+    channel_name: &str,
+) -> bool {
+    match ctx
+        .channel_claims
+        .owner_analysis_is_free(
+            telegram_user_id,
+            channel_name,
+            ctx.limits().owner_free_analyses,
+        )
+        .await
+    {
+        Ok(free) => free,
+        Err(e) => {
+            warn!(
+                "Failed to check the claims of user {} on {}: {}",
+                telegram_user_id, channel_name, e
+            );
+            false
+        }
+    }
+}
+
+/// /claim @channel: starts the user's claim of a public channel, or shows where it stands
+pub async fn handle_claim_command(
+    ctx: BotContext,
+    msg: &Message,
+    args: &str,
+    lang: Lang,
+) -> ResponseResult<()> {
+    if !msg.chat.is_private() {
+        ctx.bot
+            .send_message(msg.chat.id, lang.claim_private_only())
+            .await?;
+        return Ok(());
+    }
+    let Some(channel_name) = TelegramBot::validate_and_normalize_channel(args.trim()) else {
+        ctx.bot
+            .send_message(msg.chat.id, lang.claim_usage())
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    };
+    let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+    match ctx
+        .channel_claims
+        .start_claim(&channel_name, telegram_user_id)
+        .await
+    {
+        Ok(Some(claim)) => send_claim_status(&ctx, msg.chat.id, &claim, lang).await,
+        Ok(None) => {
+            ctx.bot
+                .send_message(
+                    msg.chat.id,
+                    lang.claim_taken(&MessageFormatter::escape_html(&channel_name)),
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+            Ok(())
+        }
+        Err(e) => {
+            error!(
+                "Failed to start the claim of user {} on {}: {}",
+                telegram_user_id, channel_name, e
+            );
+            ctx.bot
+                .send_message(msg.chat.id, lang.error_processing_request())
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/// the code to prove ownership with, or the owner's choices once the channel is verified
+pub async fn send_claim_status(
+    ctx: &BotContext,
+    chat_id: ChatId,
+    claim: &ChannelClaim,
+    lang: Lang,
+) -> ResponseResult<()> {
+    let channel = MessageFormatter::escape_html(&claim.channel_name);
+    let (text, keyboard) = if claim.verified {
+        (
+            lang.claim_owner_status(&channel, claim.opted_out, ctx.limits().owner_free_analyses),
+            CallbackHandler::create_claim_owner_keyboard(claim.id, !claim.opted_out, lang),
+        )
+    } else {
+        (
+            lang.claim_instructions(&channel, &claim.code),
+            CallbackHandler::create_claim_verify_keyboard(claim.id, lang),
+        )
+    };
+    ctx.bot
+        .send_message(chat_id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    Ok(())
+}
+
+/// how the user proved they run the channel, if they did. the bot only sees a channel's
+/// admins while it is one of them, otherwise the code has to be among the latest posts
+pub async fn find_proof(ctx: &BotContext, claim: &ChannelClaim) -> Option<ClaimMethod> {
+    let channel = Recipient::ChannelUsername(claim.channel_name.clone());
+    let user_id = UserId(claim.telegram_user_id as u64);
+    match ctx.bot.get_chat_member(channel, user_id).await {
+        // only someone who can post could have posted the code as well
+        Ok(member) if member.can_post_messages() => return Some(ClaimMethod::Admin),
+        Ok(_) => {}
+        Err(e) => info!(
+            "Can't see the admins of {}, checking its posts: {}",
+            claim.channel_name, e
+        ),
+    }
+    match ctx
+        .web_scraper
+        .fetch_recent_posts(&claim.channel_name)
+        .await
+    {
+        Ok(posts) => posts_contain_code(&posts, &claim.code).then_some(ClaimMethod::Post),
+        Err(e) => {
+            warn!(
+                "Failed to fetch the latest posts of {}: {}",
+                claim.channel_name, e
+            );
+            None
+        }
+    }
+}
+
+/// excludes the verified owner's channel from analysis through the blocklist, where admins
+/// see it too, or lets it be analyzed again
+pub async fn set_opted_out(
+    ctx: &BotContext,
+    claim: &ChannelClaim,
+    opted_out: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let target = BlockTarget::of_channel(&claim.channel_name)
+        .ok_or_else(|| format!("{} can't be blocked", claim.channel_name))?;
+    if opted_out {
+        ctx.blocklist
+            .block(&target, Some(OPT_OUT_REASON), claim.telegram_user_id)
+            .await?;
+    } else {
+        ctx.blocklist.unblock(&target).await?;
+    }
+    ctx.channel_claims.set_opted_out(claim.id, opted_out).await
+}
@@ -0,0 +1,94 @@
+use deadpool_postgres::Pool;
+use log::info;
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+
+// current week in the database's calendar, weeks start on monday
+const CURRENT_WEEK: &str = "date_trunc('week', NOW())::date";
+
+/// a channel's place on the /top leaderboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelPopularity {
+    pub channel_name: String,
+    // analyses completed this week
+    pub analyses: i32,
+    // analyses completed ever
+    pub total_analyses: i64,
+    // mean overall score of this week's structured reports, None without any
+    pub average_score: Option<f64>,
+}
+
+/// per-channel analysis counts, kept in weekly buckets of the channel_stats table
+pub struct ChannelStatsManager {
+    pool: Arc<Pool>,
+}
+
+impl ChannelStatsManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// counts a completed analysis; `score` is the report's overall score, if it had one
+    pub async fn record_analysis(
+        &self,
+        channel_name: &str,
+        score: Option<f64>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        // usernames are case-insensitive, @RustLang and @rustlang are the same channel
+        let channel_name = channel_name.to_lowercase();
+        let scored = i32::from(score.is_some());
+        let score = score.unwrap_or(0.0);
+        client
+            .execute(
+                &format!(
+                    "INSERT INTO channel_stats (channel_name, week_start, analyses, scored_analyses, score_total)
+                     VALUES ($1, {CURRENT_WEEK}, 1, $2, $3)
+                     ON CONFLICT (channel_name, week_start) DO UPDATE SET
+                         analyses = channel_stats.analyses + 1,
+                         scored_analyses = channel_stats.scored_analyses + $2,
+                         score_total = channel_stats.score_total + $3"
+                ),
+                &[&channel_name, &scored, &score],
+            )
+            .await?;
+        info!("Recorded analysis of {} in channel stats", channel_name);
+        Ok(())
+    }
+
+    /// the most analyzed channels this week, most analyzed first
+    pub async fn top_this_week(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<ChannelPopularity>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT week.channel_name, week.analyses,
+                            (SELECT SUM(total.analyses) FROM channel_stats total
+                             WHERE total.channel_name = week.channel_name)::bigint,
+                            CASE WHEN week.scored_analyses > 0
+                                 THEN week.score_total / week.scored_analyses END
+                     FROM channel_stats week
+                     WHERE week.week_start = {CURRENT_WEEK}
+                     ORDER BY week.analyses DESC, week.channel_name
+                     LIMIT $1"
+                ),
+                &[&limit],
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ChannelPopularity {
+                channel_name: row.get(0),
+                analyses: row.get(1),
+                total_analyses: row.get(2),
+                average_score: row.get(3),
+            })
+            .collect())
+    }
+}
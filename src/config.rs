@@ -0,0 +1,171 @@
+//! the settings operators tune while the bot runs: limits, model routing and gemini
+//! quotas; read at startup and again on SIGHUP or /reload, from CONFIG_FILE, the
+//! environment and the limit_overrides table. they are the only hot-reloadable ones, every
+//! other setting (credentials, database urls, bind addresses, workers, budgets, backend
+//! policy, backups, showcase, warm-up, voice and telegraph) is read from the environment
+//! once as the bot starts, by the `from_env` of what it configures, and can't be set in
+//! CONFIG_FILE
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+
+use crate::limits::{Limits, LIMIT_NAMES};
+use crate::llm::routing::{set_model_routing, ModelRouting};
+use crate::rate_limiters::gemini::{get_gemini_rate_limiter, GeminiQuotas};
+
+/// named settings: those of CONFIG_FILE, a file in the .env format, before the environment's
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    file: HashMap<String, String>,
+}
+
+impl Settings {
+    /// reads CONFIG_FILE if it is set; a file that can't be read fails the load instead of
+    /// quietly going back to the environment's values
+    pub fn read() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let Ok(path) = env::var("CONFIG_FILE") else {
+            return Ok(Self::default());
+        };
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read CONFIG_FILE {}: {}", path, e))?;
+        Self::parse(&contents)
+            .map_err(|e| format!("Failed to parse CONFIG_FILE {}: {}", path, e).into())
+    }
+
+    /// settings in the .env format, e.g. `LIMIT_TOP_CHANNELS=20`; one that isn't reloadable
+    /// is left out with a warning, as the file's value would never be read
+    pub fn parse(contents: &str) -> Result<Self, dotenvy::Error> {
+        let mut file: HashMap<String, String> =
+            dotenvy::from_read_iter(contents.as_bytes()).collect::<Result<_, _>>()?;
+        file.retain(|name, _| {
+            let reloadable = is_reloadable(name);
+            if !reloadable {
+                warn!(
+                    "Ignoring {} in CONFIG_FILE: it is read from the environment at startup only",
+                    name
+                );
+            }
+            reloadable
+        });
+        Ok(Self { file })
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.file.get(name).cloned().or_else(|| env::var(name).ok())
+    }
+}
+
+/// whether a reload picks the setting up: LIMIT_<NAME> of a limit, MODEL_ROUTING or
+/// GEMINI_QUOTAS
+pub fn is_reloadable(name: &str) -> bool {
+    matches!(name, "MODEL_ROUTING" | "GEMINI_QUOTAS")
+        || name
+            .strip_prefix("LIMIT_")
+            .is_some_and(|limit| LIMIT_NAMES.iter().any(|n| n.eq_ignore_ascii_case(limit)))
+}
+
+/// everything a reload can change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub limits: Arc<Limits>,
+    pub model_routing: ModelRouting,
+    pub gemini_quotas: GeminiQuotas,
+}
+
+impl Config {
+    pub async fn load(pool: &Pool) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let settings = Settings::read()?;
+        let limits = Limits::load_with(pool, &settings).await?;
+        Ok(Self::from_settings(&settings, limits))
+    }
+
+    pub fn from_settings(settings: &Settings, limits: Limits) -> Self {
+        Self {
+            limits: Arc::new(limits),
+            model_routing: settings
+                .get("MODEL_ROUTING")
+                .map(|spec| ModelRouting::parse(&spec))
+                .unwrap_or_default(),
+            gemini_quotas: settings
+                .get("GEMINI_QUOTAS")
+                .map(|spec| GeminiQuotas::parse(&spec))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// what `next` changes, e.g. "top_channels: 10 → 20", in LIMIT_NAMES order
+    pub fn changes(&self, next: &Config) -> Vec<String> {
+        let mut changes = self
+            .limits
+            .values()
+            .into_iter()
+            .zip(next.limits.values())
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((name, old), (_, new))| format!("{}: {} → {}", name, old, new))
+            .collect::<Vec<_>>();
+        if self.model_routing != next.model_routing {
+            changes.push("MODEL_ROUTING".to_string());
+        }
+        if self.gemini_quotas != next.gemini_quotas {
+            changes.push("GEMINI_QUOTAS".to_string());
+        }
+        changes
+    }
+
+    // model routing and gemini quotas are process-wide, as the llm calls reading them are
+    fn apply_globals(&self) {
+        set_model_routing(self.model_routing.clone());
+        get_gemini_rate_limiter().set_quotas(self.gemini_quotas.clone());
+    }
+}
+
+/// the config in effect; readers take a snapshot, a reload swaps in a new one as a whole
+pub struct LiveConfig {
+    pool: Arc<Pool>,
+    current: RwLock<Arc<Config>>,
+    // so the changes of a reload are counted from the config it replaces
+    reloading: Mutex<()>,
+}
+
+impl LiveConfig {
+    pub async fn load(pool: Arc<Pool>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let config = Config::load(&pool).await?;
+        config.apply_globals();
+        Ok(Self {
+            pool,
+            current: RwLock::new(Arc::new(config)),
+            reloading: Mutex::new(()),
+        })
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn limits(&self) -> Arc<Limits> {
+        self.current().limits.clone()
+    }
+
+    /// reads the config again and puts it in effect, returning what changed; the current
+    /// one stays if the new one can't be read
+    pub async fn reload(&self) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let _reloading = self.reloading.lock().await;
+        let config = Config::load(&self.pool).await?;
+        let changes = self.current().changes(&config);
+        config.apply_globals();
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
+        if changes.is_empty() {
+            info!("Config reloaded, nothing changed");
+        } else {
+            info!("Config reloaded: {}", changes.join(", "));
+        }
+        Ok(changes)
+    }
+}
@@ -0,0 +1,459 @@
+use deadpool_postgres::Pool;
+use log::{error, info};
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use tokio::sync::Mutex;
+
+use crate::analysis::{cross_group_corpus_name, AnalysisDepth, MessageDict};
+use crate::bot::BotContext;
+use crate::cache::read_pool;
+use crate::handlers::CallbackHandler;
+use crate::localization::Lang;
+use crate::privacy::purge_corpora;
+use crate::utils::MessageFormatter;
+
+// fewer messages say too little about their author
+pub const MIN_CROSS_GROUP_MESSAGES: usize = 30;
+
+// the profile reads the latest messages, at most this many from one group so a busy group
+// doesn't drown out the others; older ones are pruned
+pub const MAX_MESSAGES_PER_GROUP: i64 = 300;
+pub const MAX_CROSS_GROUP_MESSAGES: i64 = 1000;
+
+// how long a consent given or withdrawn on another replica can go unnoticed here; a
+// withdrawn one still keeps nothing, the insert checks it again
+const CONSENTS_REFRESH: Duration = Duration::from_secs(60);
+
+// the profile reads several groups at once, so it's billed like a deep analysis
+pub const CROSS_GROUP_DEPTH: AnalysisDepth = AnalysisDepth::Deep;
+
+/// how many of the user's messages are kept from one group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupActivity {
+    pub chat_title: String,
+    pub messages: i64,
+}
+
+/// a kept group message, as read for the profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredGroupMessage {
+    pub chat_title: String,
+    pub text: String,
+    pub date: String, // formatted by postgres as YYYY-MM-DD (UTC)
+}
+
+/// the corpus of a cross-group profile: every message prefixed with its group in brackets,
+/// in the order they were written
+pub fn corpus_messages(messages: &[StoredGroupMessage]) -> Vec<MessageDict> {
+    messages
+        .iter()
+        .map(|msg| MessageDict {
+            id: None,
+            date: Some(msg.date.clone()),
+            message: Some(format!("[{}] {}", msg.chat_title, msg.text)),
+            images: None,
+            thread_id: None,
+        })
+        .collect()
+}
+
+/// consents in cross_group_consents and the group messages kept for them in group_messages;
+/// withdrawing the consent deletes the messages and the profiles made of them. the consenting
+/// users are also kept in memory, so messages of everyone else cost no database work
+pub struct CrossGroupManager {
+    pool: Arc<Pool>,
+    // with the time they were last read from the database
+    consents: Mutex<Option<(HashSet<i64>, Instant)>>,
+}
+
+impl CrossGroupManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self {
+            pool,
+            consents: Mutex::new(None),
+        }
+    }
+
+    // whether the user agreed, as of the last read of the consents
+    async fn consented(&self, telegram_user_id: i64) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let mut consents = self.consents.lock().await;
+        if let Some((users, read_at)) = consents.as_ref() {
+            if read_at.elapsed() < CONSENTS_REFRESH {
+                return Ok(users.contains(&telegram_user_id));
+            }
+        }
+        let client = read_pool(&self.pool).get().await?;
+        let users = client
+            .query("SELECT telegram_user_id FROM cross_group_consents", &[])
+            .await?
+            .iter()
+            .map(|row| row.get(0))
+            .collect::<HashSet<i64>>();
+        let consented = users.contains(&telegram_user_id);
+        *consents = Some((users, Instant::now()));
+        Ok(consented)
+    }
+
+    pub async fn has_consent(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT 1 FROM cross_group_consents WHERE telegram_user_id = $1",
+                &[&telegram_user_id],
+            )
+            .await?;
+        Ok(row.is_some())
+    }
+
+    /// false if the user had already agreed
+    pub async fn consent(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let inserted = client
+            .execute(
+                "INSERT INTO cross_group_consents (telegram_user_id) VALUES ($1)
+                 ON CONFLICT (telegram_user_id) DO NOTHING",
+                &[&telegram_user_id],
+            )
+            .await?;
+        if let Some((users, _)) = self.consents.lock().await.as_mut() {
+            users.insert(telegram_user_id);
+        }
+        if inserted > 0 {
+            info!("User {} agreed to a cross-group profile", telegram_user_id);
+        }
+        Ok(inserted > 0)
+    }
+
+    /// withdraws the consent and deletes the kept messages, the profile corpus gathered from
+    /// them and its cached answers and rendered results; returns how many messages were
+    /// deleted
+    pub async fn revoke(&self, telegram_user_id: i64) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        let deleted = transaction
+            .execute(
+                "DELETE FROM group_messages WHERE telegram_user_id = $1",
+                &[&telegram_user_id],
+            )
+            .await?;
+        transaction
+            .execute(
+                "DELETE FROM cross_group_consents WHERE telegram_user_id = $1",
+                &[&telegram_user_id],
+            )
+            .await?;
+        purge_corpora(&transaction, &[cross_group_corpus_name(telegram_user_id)]).await?;
+        transaction.commit().await?;
+        if let Some((users, _)) = self.consents.lock().await.as_mut() {
+            users.remove(&telegram_user_id);
+        }
+        info!(
+            "User {} withdrew their cross-group consent, {} messages deleted",
+            telegram_user_id, deleted
+        );
+        Ok(deleted)
+    }
+
+    /// keeps a group message of a user who agreed to it; false for everyone else
+    pub async fn record(
+        &self,
+        telegram_user_id: i64,
+        chat_id: i64,
+        chat_title: &str,
+        message_id: i32,
+        text: &str,
+        sent_at_unix: f64,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        if !self.consented(telegram_user_id).await? {
+            return Ok(false);
+        }
+        let client = self.pool.get().await?;
+        let inserted = client
+            .execute(
+                "INSERT INTO group_messages
+                     (telegram_user_id, chat_id, chat_title, message_id, text, sent_at)
+                 SELECT telegram_user_id, $2, $3, $4, $5, TO_TIMESTAMP($6)
+                 FROM cross_group_consents
+                 WHERE telegram_user_id = $1
+                 ON CONFLICT (chat_id, message_id) DO NOTHING",
+                &[
+                    &telegram_user_id,
+                    &chat_id,
+                    &chat_title,
+                    &message_id,
+                    &text,
+                    &sent_at_unix,
+                ],
+            )
+            .await?;
+        Ok(inserted > 0)
+    }
+
+    /// deletes the messages past the latest MAX_MESSAGES_PER_GROUP of a user in a group,
+    /// which nothing reads anymore; returns how many were deleted
+    pub async fn prune(&self) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let deleted = client
+            .execute(
+                "DELETE FROM group_messages
+                 WHERE id IN (
+                     SELECT id FROM (
+                         SELECT id, ROW_NUMBER() OVER (
+                             PARTITION BY telegram_user_id, chat_id ORDER BY sent_at DESC, id DESC
+                         ) AS recent
+                         FROM group_messages
+                     ) latest
+                     WHERE recent > $1
+                 )",
+                &[&MAX_MESSAGES_PER_GROUP],
+            )
+            .await?;
+        Ok(deleted)
+    }
+
+    /// the groups the user's messages were kept from, busiest first
+    pub async fn groups(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<Vec<GroupActivity>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT (ARRAY_AGG(chat_title ORDER BY sent_at DESC))[1], COUNT(*)
+                 FROM group_messages
+                 WHERE telegram_user_id = $1
+                 GROUP BY chat_id
+                 ORDER BY COUNT(*) DESC, MAX(sent_at) DESC",
+                &[&telegram_user_id],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| GroupActivity {
+                chat_title: row.get(0),
+                messages: row.get(1),
+            })
+            .collect())
+    }
+
+    /// the latest kept messages across all groups, oldest first, at most
+    /// MAX_MESSAGES_PER_GROUP from one group and MAX_CROSS_GROUP_MESSAGES overall
+    pub async fn messages(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<Vec<StoredGroupMessage>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT chat_title, text, TO_CHAR(sent_at AT TIME ZONE 'UTC', 'YYYY-MM-DD')
+                 FROM (
+                     SELECT chat_title, text, sent_at,
+                            ROW_NUMBER() OVER (PARTITION BY chat_id ORDER BY sent_at DESC) AS recent
+                     FROM group_messages
+                     WHERE telegram_user_id = $1
+                 ) latest
+                 WHERE recent <= $2
+                 ORDER BY sent_at DESC
+                 LIMIT $3",
+                &[
+                    &telegram_user_id,
+                    &MAX_MESSAGES_PER_GROUP,
+                    &MAX_CROSS_GROUP_MESSAGES,
+                ],
+            )
+            .await?;
+        let mut messages = rows
+            .iter()
+            .map(|row| StoredGroupMessage {
+                chat_title: row.get(0),
+                text: row.get(1),
+                date: row.get(2),
+            })
+            .collect::<Vec<_>>();
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// the bot user with this username (without the @) who agreed to keep their group
+    /// messages; None for anyone else
+    pub async fn consenting_user(
+        &self,
+        username: &str,
+    ) -> Result<Option<i64>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let row = client
+            .query_opt(
+                "SELECT c.telegram_user_id
+                 FROM cross_group_consents c
+                 JOIN users u ON u.telegram_user_id = c.telegram_user_id
+                 WHERE LOWER(u.username) = LOWER($1)
+                 LIMIT 1",
+                &[&username],
+            )
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    /// the user's latest kept messages in one group, oldest first
+    pub async fn chat_messages(
+        &self,
+        telegram_user_id: i64,
+        chat_id: i64,
+        limit: i64,
+    ) -> Result<Vec<StoredGroupMessage>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT chat_title, text, TO_CHAR(sent_at AT TIME ZONE 'UTC', 'YYYY-MM-DD')
+                 FROM group_messages
+                 WHERE telegram_user_id = $1 AND chat_id = $2
+                 ORDER BY sent_at DESC
+                 LIMIT $3",
+                &[&telegram_user_id, &chat_id, &limit],
+            )
+            .await?;
+        let mut messages = rows
+            .iter()
+            .map(|row| StoredGroupMessage {
+                chat_title: row.get(0),
+                text: row.get(1),
+                date: row.get(2),
+            })
+            .collect::<Vec<_>>();
+        messages.reverse();
+        Ok(messages)
+    }
+}
+
+/// keeps the text of a group message for its sender's cross-group profile, if they agreed
+pub async fn record_group_message(ctx: &BotContext, msg: &Message) {
+    // messages sent on behalf of a chat have no person behind them
+    let Some(user) = msg.from.as_ref().filter(|user| !user.is_bot) else {
+        return;
+    };
+    if msg.sender_chat.is_some() {
+        return;
+    }
+    let Some(text) = msg
+        .text()
+        .or(msg.caption())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+    else {
+        return;
+    };
+    let chat_title = msg.chat.title().unwrap_or_default();
+    if let Err(e) = ctx
+        .cross_group
+        .record(
+            user.id.0 as i64,
+            msg.chat.id.0,
+            chat_title,
+            msg.id.0,
+            text,
+            msg.date.timestamp() as f64,
+        )
+        .await
+    {
+        error!(
+            "Failed to keep group message of user {} in chat {}: {}",
+            user.id, msg.chat.id, e
+        );
+    }
+}
+
+/// /groupprofile: asks for the consent first, then shows what was kept so far and offers
+/// the analysis once there's enough of it
+pub async fn handle_group_profile_command(
+    ctx: BotContext,
+    msg: &Message,
+    lang: Lang,
+) -> ResponseResult<()> {
+    if !msg.chat.is_private() {
+        ctx.bot
+            .send_message(msg.chat.id, lang.cross_group_private_only())
+            .await?;
+        return Ok(());
+    }
+    let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+    match ctx.cross_group.has_consent(telegram_user_id).await {
+        Ok(true) => send_status(&ctx, msg.chat.id, telegram_user_id, lang).await,
+        Ok(false) => {
+            ctx.bot
+                .send_message(msg.chat.id, lang.cross_group_consent())
+                .parse_mode(ParseMode::Html)
+                .reply_markup(CallbackHandler::create_cross_group_consent_keyboard(lang))
+                .await?;
+            Ok(())
+        }
+        Err(e) => {
+            error!(
+                "Failed to check cross-group consent of user {}: {}",
+                telegram_user_id, e
+            );
+            ctx.bot
+                .send_message(msg.chat.id, lang.error_processing_request())
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/// the groups the user's messages were kept from, with the analysis types once there are
+/// enough of them
+pub async fn send_status(
+    ctx: &BotContext,
+    chat_id: ChatId,
+    telegram_user_id: i64,
+    lang: Lang,
+) -> ResponseResult<()> {
+    let groups = match ctx.cross_group.groups(telegram_user_id).await {
+        Ok(groups) => groups,
+        Err(e) => {
+            error!(
+                "Failed to load cross-group messages of user {}: {}",
+                telegram_user_id, e
+            );
+            ctx.bot
+                .send_message(chat_id, lang.error_processing_request())
+                .await?;
+            return Ok(());
+        }
+    };
+    let total = groups.iter().map(|group| group.messages).sum::<i64>() as usize;
+    let ready = total >= MIN_CROSS_GROUP_MESSAGES;
+    let entries = groups
+        .iter()
+        .map(|group| {
+            format!(
+                "• {} — {}",
+                MessageFormatter::escape_html(&group.chat_title),
+                group.messages
+            )
+        })
+        .collect::<Vec<_>>();
+    ctx.bot
+        .send_message(
+            chat_id,
+            lang.cross_group_status(
+                &entries,
+                total,
+                MIN_CROSS_GROUP_MESSAGES,
+                ctx.limits().depth_credits(CROSS_GROUP_DEPTH),
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(CallbackHandler::create_cross_group_keyboard(ready, lang))
+        .await?;
+    Ok(())
+}
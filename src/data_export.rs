@@ -0,0 +1,145 @@
+//! /export_my_data: what the bot stores about a user, sent to them as one json document
+use deadpool_postgres::Pool;
+use log::{error, info};
+use serde_json::Value;
+use std::error::Error;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::InputFile;
+
+use crate::bot::BotContext;
+use crate::cache::read_pool;
+use crate::localization::Lang;
+
+const EXPORT_FILE_NAME: &str = "my_data.json";
+
+pub struct DataExportManager {
+    pool: Arc<Pool>,
+}
+
+impl DataExportManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// the user's row with their analyses, payments and refunds, the referral rewards they
+    /// gave or got, and the group messages kept for /groupprofile, oldest first; None if
+    /// the bot doesn't know the user
+    pub async fn export(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<Option<Value>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let row = client
+            .query_opt(
+                "SELECT jsonb_build_object(
+                     'exported_at', NOW(),
+                     'user', to_jsonb(u),
+                     'analyses', COALESCE((
+                         SELECT jsonb_agg(to_jsonb(a) ORDER BY a.id)
+                         FROM user_analyses a WHERE a.user_id = u.id
+                     ), '[]'::jsonb),
+                     'payments', COALESCE((
+                         SELECT jsonb_agg(to_jsonb(p) ORDER BY p.id)
+                         FROM payments p WHERE p.user_id = u.id
+                     ), '[]'::jsonb),
+                     'refunds', COALESCE((
+                         SELECT jsonb_agg(to_jsonb(r) ORDER BY r.id)
+                         FROM refunds r WHERE r.user_id = u.id
+                     ), '[]'::jsonb),
+                     'referral_rewards', COALESCE((
+                         SELECT jsonb_agg(to_jsonb(rr) ORDER BY rr.id)
+                         FROM referral_rewards rr
+                         WHERE rr.referrer_user_id = u.id OR rr.referee_user_id = u.id
+                     ), '[]'::jsonb),
+                     'group_messages', COALESCE((
+                         SELECT jsonb_agg(to_jsonb(g) ORDER BY g.sent_at, g.id)
+                         FROM group_messages g WHERE g.telegram_user_id = u.telegram_user_id
+                     ), '[]'::jsonb)
+                 )
+                 FROM users u WHERE u.telegram_user_id = $1",
+                &[&telegram_user_id],
+            )
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+}
+
+/// /export_my_data, in private chats only since the file holds the user's analyses and
+/// messages; the export is gathered and sent in the background
+pub async fn handle_export_command(
+    ctx: BotContext,
+    msg: &Message,
+    lang: Lang,
+) -> ResponseResult<()> {
+    if !msg.chat.is_private() {
+        ctx.bot
+            .send_message(msg.chat.id, lang.data_export_private_only())
+            .await?;
+        return Ok(());
+    }
+    let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+    ctx.bot
+        .send_message(msg.chat.id, lang.data_export_started())
+        .await?;
+    tokio::spawn(send_export(ctx, msg.chat.id, telegram_user_id, lang));
+    Ok(())
+}
+
+/// gathers the user's export and sends it as a document, or explains why it can't
+pub async fn send_export(ctx: BotContext, chat_id: ChatId, telegram_user_id: i64, lang: Lang) {
+    let export = match ctx.data_export.export(telegram_user_id).await {
+        Ok(Some(export)) => export,
+        Ok(None) => {
+            let _ = ctx
+                .bot
+                .send_message(chat_id, lang.data_export_empty())
+                .await;
+            return;
+        }
+        Err(e) => {
+            error!(
+                "Failed to export the data of user {}: {}",
+                telegram_user_id, e
+            );
+            let _ = ctx
+                .bot
+                .send_message(chat_id, lang.error_processing_request())
+                .await;
+            return;
+        }
+    };
+    let document = match serde_json::to_vec_pretty(&export) {
+        Ok(document) => document,
+        Err(e) => {
+            error!(
+                "Failed to serialize the data of user {}: {}",
+                telegram_user_id, e
+            );
+            let _ = ctx
+                .bot
+                .send_message(chat_id, lang.error_processing_request())
+                .await;
+            return;
+        }
+    };
+    let size = document.len();
+    match ctx
+        .bot
+        .send_document(
+            chat_id,
+            InputFile::memory(document).file_name(EXPORT_FILE_NAME),
+        )
+        .caption(lang.data_export_caption())
+        .await
+    {
+        Ok(_) => info!(
+            "Sent the data export of user {} ({} bytes)",
+            telegram_user_id, size
+        ),
+        Err(e) => error!(
+            "Failed to send the data export of user {}: {}",
+            telegram_user_id, e
+        ),
+    }
+}
@@ -0,0 +1,215 @@
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::admin::AdminManager;
+use crate::error::AppError;
+use crate::localization::Lang;
+use crate::metrics::metrics;
+use crate::utils::MessageFormatter;
+
+// how often the pool is pinged
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+// a check that waits longer than this for a connection counts as slow
+pub const SLOW_ACQUIRE: Duration = Duration::from_secs(2);
+// a check that can't get and ping a connection in time counts as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+// consecutive slow or failed checks before the owners are alerted
+pub const ALERT_AFTER_CHECKS: u32 = 3;
+// pool stats are logged every this many checks while healthy
+const LOG_STATS_EVERY: u32 = 20;
+
+/// what a single health check saw
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckOutcome {
+    Healthy(Duration),
+    Slow(Duration),
+    Failed(String),
+}
+
+impl CheckOutcome {
+    /// classifies a check that got and pinged a connection in `elapsed`
+    pub fn from_elapsed(elapsed: Duration) -> Self {
+        if elapsed >= SLOW_ACQUIRE {
+            CheckOutcome::Slow(elapsed)
+        } else {
+            CheckOutcome::Healthy(elapsed)
+        }
+    }
+}
+
+/// what the owners should be told after a check
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthAlert {
+    // the last checks failed, with the latest error
+    Unreachable { checks: u32, error: String },
+    // the last checks were slow, the slowest took this long
+    Slow { checks: u32, slowest: Duration },
+    Recovered { checks: u32 },
+}
+
+/// counts consecutive bad checks and decides when to alert, so a blip doesn't page
+/// anyone and an outage is reported once rather than every check
+#[derive(Debug, Default)]
+pub struct HealthTracker {
+    bad_checks: u32,
+    slowest: Duration,
+    // latest error of the current streak, if any check in it failed
+    last_error: Option<String>,
+    alerted: bool,
+}
+
+impl HealthTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// records a check and returns the alert to send, if any; a problem counts as
+    /// reported only once `alert_sent` is called, so a failed delivery is retried
+    pub fn record(&mut self, outcome: &CheckOutcome) -> Option<HealthAlert> {
+        match outcome {
+            CheckOutcome::Healthy(_) => {
+                let checks = self.bad_checks;
+                let alerted = self.alerted;
+                *self = Self::default();
+                return alerted.then_some(HealthAlert::Recovered { checks });
+            }
+            CheckOutcome::Slow(elapsed) => self.slowest = self.slowest.max(*elapsed),
+            CheckOutcome::Failed(error) => self.last_error = Some(error.clone()),
+        }
+        self.bad_checks += 1;
+        if self.alerted || self.bad_checks < ALERT_AFTER_CHECKS {
+            return None;
+        }
+        // one failure in the streak means the database is more than just slow
+        Some(match &self.last_error {
+            Some(error) => HealthAlert::Unreachable {
+                checks: self.bad_checks,
+                error: error.clone(),
+            },
+            None => HealthAlert::Slow {
+                checks: self.bad_checks,
+                slowest: self.slowest,
+            },
+        })
+    }
+
+    pub fn alert_sent(&mut self, alert: &HealthAlert) {
+        self.alerted = !matches!(alert, HealthAlert::Recovered { .. });
+    }
+}
+
+impl HealthAlert {
+    pub fn message(&self, lang: Lang) -> String {
+        match self {
+            HealthAlert::Unreachable { checks, error } => {
+                lang.db_unreachable_alert(*checks, &MessageFormatter::escape_html(error))
+            }
+            HealthAlert::Slow { checks, slowest } => {
+                lang.db_slow_alert(*checks, slowest.as_secs_f64())
+            }
+            HealthAlert::Recovered { checks } => lang.db_recovered_alert(*checks),
+        }
+    }
+}
+
+/// gets a connection and pings it, recording the pool stats and latency in the metrics;
+/// on failure drops the idle connections so the next checkout reconnects
+pub async fn check_pool(pool: &Pool) -> CheckOutcome {
+    let started = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, async {
+        let client = pool.get().await?;
+        client.simple_query("SELECT 1").await?;
+        Ok::<_, AppError>(())
+    })
+    .await;
+    let elapsed = started.elapsed();
+    metrics().set_db_pool_status(&pool.status());
+
+    let error = match result {
+        Ok(Ok(())) => {
+            metrics().observe_db_acquire(elapsed);
+            return CheckOutcome::from_elapsed(elapsed);
+        }
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => format!("no connection within {}s", CHECK_TIMEOUT.as_secs()),
+    };
+    metrics().db_health_check_failed();
+    // connections broken by a restart or failover would otherwise be handed out again
+    pool.retain(|_, _| false);
+    CheckOutcome::Failed(error)
+}
+
+/// queues the alert for every owner in their language
+pub async fn alert_owners(
+    pool: &Pool,
+    admin: &AdminManager,
+    alert: &HealthAlert,
+) -> Result<(), AppError> {
+    let owners = admin.owner_ids().await?;
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let rows = transaction
+        .query(
+            "SELECT owner.id, COALESCE(users.language_override, users.language)
+             FROM unnest($1::bigint[]) AS owner(id)
+             LEFT JOIN users ON users.telegram_user_id = owner.id",
+            &[&owners],
+        )
+        .await?;
+    for row in &rows {
+        let telegram_user_id: i64 = row.get(0);
+        let message = alert.message(Lang::from_code(row.get::<_, Option<&str>>(1)));
+        transaction
+            .execute(
+                "INSERT INTO message_queue (telegram_user_id, message, parse_mode) VALUES ($1, $2, $3)",
+                &[&telegram_user_id, &message, &"HTML"],
+            )
+            .await?;
+    }
+    transaction.commit().await?;
+
+    info!("Queued a database health alert for {} owners", rows.len());
+    Ok(())
+}
+
+/// pings the pool every half a minute and alerts the owners when connections keep
+/// failing or stay slow, until the process exits
+pub async fn run_db_health_checker(pool: Arc<Pool>, admin: Arc<AdminManager>) {
+    let mut tracker = HealthTracker::new();
+    let mut checks: u32 = 0;
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let outcome = check_pool(&pool).await;
+        let status = pool.status();
+        match &outcome {
+            CheckOutcome::Healthy(_) if checks.is_multiple_of(LOG_STATS_EVERY) => info!(
+                "Database pool: {} connections, {} available, {} waiting",
+                status.size, status.available, status.waiting
+            ),
+            CheckOutcome::Healthy(_) => {}
+            CheckOutcome::Slow(elapsed) => warn!(
+                "Database connection took {:.1}s ({} connections, {} available, {} waiting)",
+                elapsed.as_secs_f64(),
+                status.size,
+                status.available,
+                status.waiting
+            ),
+            CheckOutcome::Failed(e) => error!(
+                "Database health check failed: {} ({} waiting for a connection)",
+                e, status.waiting
+            ),
+        }
+        checks = checks.wrapping_add(1);
+
+        let Some(alert) = tracker.record(&outcome) else {
+            continue;
+        };
+        match alert_owners(&pool, &admin, &alert).await {
+            Ok(()) => tracker.alert_sent(&alert),
+            Err(e) => error!("Failed to alert owners about database health: {}", e),
+        }
+    }
+}
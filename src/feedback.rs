@@ -0,0 +1,132 @@
+use deadpool_postgres::Pool;
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+use crate::prompts::versions::{PromptVersion, BASE_PROMPT_VERSION};
+
+// the /feedback report looks this far back unless told otherwise
+pub const DEFAULT_REPORT_DAYS: i32 = 30;
+
+/// a user's rating of one of their analyses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vote {
+    Up,
+    Down,
+}
+
+impl Vote {
+    pub fn value(&self) -> i16 {
+        match self {
+            Vote::Up => 1,
+            Vote::Down => -1,
+        }
+    }
+}
+
+/// votes on the analyses of one type or one model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Satisfaction {
+    pub key: String,
+    pub positive: i64,
+    pub negative: i64,
+}
+
+impl Satisfaction {
+    /// share of positive votes, in percent
+    pub fn rate(&self) -> f64 {
+        let total = self.positive + self.negative;
+        if total == 0 {
+            return 0.0;
+        }
+        self.positive as f64 * 100.0 / total as f64
+    }
+}
+
+/// satisfaction over a window of days, per analysis type, llm model and prompt version
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeedbackReport {
+    pub by_type: Vec<Satisfaction>,
+    pub by_model: Vec<Satisfaction>,
+    // keyed "<id> (<name>)", to compare the variants of a prompt experiment
+    pub by_prompt_version: Vec<Satisfaction>,
+}
+
+/// thumbs up/down votes on delivered analyses, stored in the feedback table
+pub struct FeedbackManager {
+    pool: Arc<Pool>,
+}
+
+impl FeedbackManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// records the vote of the analysis' requester, replacing an earlier one; false unless
+    /// the analysis is theirs and completed. the model and prompt version the analysis ran
+    /// with are kept with the vote so later prompt changes don't blur the numbers
+    pub async fn record(
+        &self,
+        analysis_id: i32,
+        telegram_user_id: i64,
+        vote: Vote,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let recorded = client
+            .execute(
+                "INSERT INTO feedback (analysis_id, user_id, vote, analysis_type, model, prompt_version)
+                 SELECT ua.id, ua.user_id, $3, ua.analysis_type, lr.analysis_result->>'model',
+                        COALESCE(ua.prompt_version, $4)
+                 FROM user_analyses ua
+                 JOIN users u ON u.id = ua.user_id
+                 LEFT JOIN llm_results lr ON lr.cache_key = ua.cache_key
+                 WHERE ua.id = $1 AND u.telegram_user_id = $2 AND ua.status = 'completed'
+                 ON CONFLICT (analysis_id)
+                 DO UPDATE SET vote = EXCLUDED.vote, updated_at = NOW()",
+                &[&analysis_id, &telegram_user_id, &vote.value(), &BASE_PROMPT_VERSION],
+            )
+            .await?;
+        Ok(recorded > 0)
+    }
+
+    /// votes cast in the last `days` days, most rated first; votes on results cached
+    /// before the model was recorded count under "unknown"
+    pub async fn report(&self, days: i32) -> Result<FeedbackReport, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let mut report = FeedbackReport::default();
+        for (column, target) in [
+            ("analysis_type", &mut report.by_type),
+            ("COALESCE(model, 'unknown')", &mut report.by_model),
+            ("prompt_version::TEXT", &mut report.by_prompt_version),
+        ] {
+            let rows = client
+                .query(
+                    &format!(
+                        "SELECT {column},
+                                COUNT(*) FILTER (WHERE vote > 0),
+                                COUNT(*) FILTER (WHERE vote < 0)
+                         FROM feedback
+                         WHERE created_at > NOW() - make_interval(days => $1)
+                         GROUP BY 1
+                         ORDER BY COUNT(*) DESC, 1"
+                    ),
+                    &[&days],
+                )
+                .await?;
+            *target = rows
+                .into_iter()
+                .map(|row| Satisfaction {
+                    key: row.get(0),
+                    positive: row.get(1),
+                    negative: row.get(2),
+                })
+                .collect();
+        }
+        for satisfaction in &mut report.by_prompt_version {
+            if let Some(version) = satisfaction.key.parse().ok().and_then(PromptVersion::find) {
+                satisfaction.key = format!("{} ({})", version.id, version.name);
+            }
+        }
+        Ok(report)
+    }
+}
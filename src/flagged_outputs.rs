@@ -0,0 +1,89 @@
+use deadpool_postgres::Pool;
+use log::info;
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+
+// /flagged lists at most this many unreviewed passages
+pub const FLAGGED_LIST_LIMIT: i64 = 20;
+
+/// a passage of an llm answer the safety review flagged, see llm::safety
+#[derive(Debug, Clone)]
+pub struct FlaggedOutput {
+    pub id: i32,
+    pub channel_name: String,
+    pub analysis_type: String,
+    pub model: Option<String>,
+    pub section: String,
+    pub category: String,
+    pub excerpt: String,
+    // regenerated or redacted
+    pub action: String,
+    pub created_at: String, // formatted by postgres as YYYY-MM-DD HH24:MI (UTC)
+}
+
+/// the admin side of flagged_outputs: the answers are flagged and recorded by the
+/// analysis runner, admins go through them with /flagged
+pub struct FlaggedOutputsManager {
+    pool: Arc<Pool>,
+}
+
+impl FlaggedOutputsManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// the latest passages no admin has reviewed yet, newest first
+    pub async fn unreviewed(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<FlaggedOutput>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT id, channel_name, analysis_type, model, section, category, excerpt, action,
+                        TO_CHAR(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM flagged_outputs
+                 WHERE reviewed_at IS NULL
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT $1",
+                &[&limit],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| FlaggedOutput {
+                id: row.get(0),
+                channel_name: row.get(1),
+                analysis_type: row.get(2),
+                model: row.get(3),
+                section: row.get(4),
+                category: row.get(5),
+                excerpt: row.get(6),
+                action: row.get(7),
+                created_at: row.get(8),
+            })
+            .collect())
+    }
+
+    /// marks a passage as reviewed; false if it doesn't exist or was already reviewed
+    pub async fn mark_reviewed(
+        &self,
+        id: i32,
+        telegram_user_id: i64,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE flagged_outputs SET reviewed_by = $2, reviewed_at = NOW()
+                 WHERE id = $1 AND reviewed_at IS NULL",
+                &[&id, &telegram_user_id],
+            )
+            .await?;
+        if updated > 0 {
+            info!("Flagged output {} reviewed by {}", id, telegram_user_id);
+        }
+        Ok(updated > 0)
+    }
+}
@@ -0,0 +1,314 @@
+//! free trials: a new account's credit waits for the "I'm human" button, and look-alike
+//! signups of one referrer get a limited number of trials an hour; the rest are held in
+//! trial_clusters until an owner reviews them with /trialclusters
+use deadpool_postgres::{GenericClient, Pool};
+use log::{info, warn};
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+use crate::user_manager::{CreditTransactionKind, UserManager};
+
+// credits of a free trial
+pub const FREE_TRIAL_CREDITS: i32 = 1;
+// look-alike signups of one referrer share free_trials_per_hour trials within this many minutes
+pub const TRIAL_WINDOW_MINUTES: i32 = 60;
+// /trialclusters lists at most this many pending clusters
+pub const CLUSTER_LIST_LIMIT: i64 = 20;
+
+/// what look-alike signups of one referrer have in common: the client language and
+/// whether they have a username and a last name. None for signups without a referrer,
+/// which are mostly organic and share too little to tell a farm from a busy hour, so they
+/// aren't throttled
+pub fn signup_fingerprint(
+    language: Option<&str>,
+    has_username: bool,
+    has_last_name: bool,
+    referrer_user_id: Option<i32>,
+) -> Option<String> {
+    let referrer = referrer_user_id?;
+    let language = language
+        .map(|language| language.trim().to_lowercase())
+        .filter(|language| !language.is_empty())
+        .unwrap_or_else(|| "-".to_string());
+    let flag = |present: bool| if present { "yes" } else { "no" };
+    Some(format!(
+        "lang={} username={} last_name={} referrer={}",
+        language,
+        flag(has_username),
+        flag(has_last_name),
+        referrer
+    ))
+}
+
+/// how a pending free trial was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrialOutcome {
+    // the balance after the trial credits
+    Granted { credits: i32 },
+    Withheld,
+}
+
+/// an admin's decision on a pending cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterStatus {
+    // the signups are genuine: their withheld trials are granted and the fingerprint
+    // isn't throttled again
+    Cleared,
+    // the signups are farmed: later ones are withheld without counting
+    Confirmed,
+}
+
+impl ClusterStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClusterStatus::Cleared => "cleared",
+            ClusterStatus::Confirmed => "confirmed",
+        }
+    }
+}
+
+/// a cluster as listed by /trialclusters
+#[derive(Debug, Clone)]
+pub struct TrialCluster {
+    pub id: i32,
+    pub fingerprint: String,
+    pub withheld_count: i32,
+    pub created_at: String, // formatted by postgres as YYYY-MM-DD HH24:MI (UTC)
+    pub last_seen_at: String, // same format
+}
+
+/// whether a signup with this fingerprint gets its trial; holds the fingerprint's lock
+/// until the caller's transaction ends so concurrent signups are counted one by one
+async fn trial_allowed(
+    client: &impl GenericClient,
+    fingerprint: &str,
+    per_hour: i32,
+) -> Result<bool, tokio_postgres::Error> {
+    client
+        .execute(
+            "SELECT pg_advisory_xact_lock(hashtext($1))",
+            &[&fingerprint],
+        )
+        .await?;
+    let status = client
+        .query_opt(
+            "SELECT status FROM trial_clusters WHERE fingerprint = $1",
+            &[&fingerprint],
+        )
+        .await?
+        .map(|row| row.get::<_, String>(0));
+    match status.as_deref() {
+        Some("cleared") => return Ok(true),
+        Some("confirmed") => return Ok(false),
+        _ => {}
+    }
+    let recent: i64 = client
+        .query_one(
+            "SELECT COUNT(*) FROM users
+             WHERE signup_fingerprint = $1 AND trial_status = 'granted'
+               AND trial_decided_at > NOW() - make_interval(mins => $2)",
+            &[&fingerprint, &TRIAL_WINDOW_MINUTES],
+        )
+        .await?
+        .get(0);
+    Ok(recent < i64::from(per_hour))
+}
+
+/// decides the user's pending trial; None if it was already decided
+pub(crate) async fn claim(
+    client: &impl GenericClient,
+    telegram_user_id: i64,
+    per_hour: i32,
+) -> Result<Option<TrialOutcome>, tokio_postgres::Error> {
+    let Some(row) = client
+        .query_opt(
+            "SELECT id, signup_fingerprint FROM users
+             WHERE telegram_user_id = $1 AND trial_status = 'pending'
+             FOR UPDATE",
+            &[&telegram_user_id],
+        )
+        .await?
+    else {
+        return Ok(None);
+    };
+    let user_id: i32 = row.get(0);
+    let fingerprint: Option<String> = row.get(1);
+
+    // signups without a referrer have no fingerprint and aren't throttled
+    if let Some(fingerprint) = &fingerprint {
+        if !trial_allowed(client, fingerprint, per_hour).await? {
+            withhold(client, user_id, fingerprint).await?;
+            warn!(
+                "Withheld the free trial of user {} ({})",
+                telegram_user_id, fingerprint
+            );
+            return Ok(Some(TrialOutcome::Withheld));
+        }
+    }
+
+    let credits = grant(client, user_id).await?;
+    info!("Granted the free trial of user {}", telegram_user_id);
+    Ok(Some(TrialOutcome::Granted { credits }))
+}
+
+/// marks the trial withheld and counts it in the fingerprint's cluster
+async fn withhold(
+    client: &impl GenericClient,
+    user_id: i32,
+    fingerprint: &str,
+) -> Result<(), tokio_postgres::Error> {
+    client
+        .execute(
+            "UPDATE users SET trial_status = 'withheld', trial_decided_at = NOW(), updated_at = NOW()
+             WHERE id = $1",
+            &[&user_id],
+        )
+        .await?;
+    client
+        .execute(
+            "INSERT INTO trial_clusters (fingerprint, withheld_count) VALUES ($1, 1)
+             ON CONFLICT (fingerprint) DO UPDATE
+             SET withheld_count = trial_clusters.withheld_count + 1, last_seen_at = NOW()",
+            &[&fingerprint],
+        )
+        .await?;
+    Ok(())
+}
+
+/// adds the trial credits to the balance and the ledger, returning the new balance
+async fn grant(client: &impl GenericClient, user_id: i32) -> Result<i32, tokio_postgres::Error> {
+    let credits: i32 = client
+        .query_one(
+            "UPDATE users
+             SET analysis_credits = analysis_credits + $2, trial_status = 'granted',
+                 trial_decided_at = NOW(), updated_at = NOW()
+             WHERE id = $1
+             RETURNING analysis_credits",
+            &[&user_id, &FREE_TRIAL_CREDITS],
+        )
+        .await?
+        .get(0);
+    UserManager::record_credit_transaction(
+        client,
+        user_id,
+        FREE_TRIAL_CREDITS,
+        credits,
+        CreditTransactionKind::SignupBonus,
+        None,
+    )
+    .await?;
+    Ok(credits)
+}
+
+/// look-alike signups stored in trial_clusters, reviewed with /trialclusters
+pub struct FreeTrialManager {
+    pool: Arc<Pool>,
+}
+
+impl FreeTrialManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// whether the user still has to press the "I'm human" button; false if that can't
+    /// be checked, so they see the usual no-credits message
+    pub async fn is_pending(&self, user_id: i32) -> bool {
+        let result = async {
+            let client = self.pool.get().await?;
+            let row = client
+                .query_opt(
+                    "SELECT 1 FROM users WHERE id = $1 AND trial_status = 'pending'",
+                    &[&user_id],
+                )
+                .await?;
+            Ok::<_, Box<dyn Error + Send + Sync>>(row.is_some())
+        }
+        .await;
+        result.unwrap_or_else(|e| {
+            warn!("Failed to check the free trial of user {}: {}", user_id, e);
+            false
+        })
+    }
+
+    /// clusters waiting for review, most withheld trials first
+    pub async fn pending(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<TrialCluster>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT id, fingerprint, withheld_count,
+                        TO_CHAR(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI'),
+                        TO_CHAR(last_seen_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM trial_clusters
+                 WHERE status = 'pending'
+                 ORDER BY withheld_count DESC, id
+                 LIMIT $1",
+                &[&limit],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| TrialCluster {
+                id: row.get(0),
+                fingerprint: row.get(1),
+                withheld_count: row.get(2),
+                created_at: row.get(3),
+                last_seen_at: row.get(4),
+            })
+            .collect())
+    }
+
+    /// records an admin's decision on a pending cluster, granting the withheld trials of
+    /// a cleared one; None if there's no such pending cluster, else the trials granted
+    pub async fn review(
+        &self,
+        cluster_id: i32,
+        status: ClusterStatus,
+        reviewer_telegram_id: i64,
+    ) -> Result<Option<usize>, Box<dyn Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        let Some(row) = transaction
+            .query_opt(
+                "UPDATE trial_clusters
+                 SET status = $2, reviewed_by = $3, reviewed_at = NOW()
+                 WHERE id = $1 AND status = 'pending'
+                 RETURNING fingerprint",
+                &[&cluster_id, &status.as_str(), &reviewer_telegram_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let fingerprint: String = row.get(0);
+        let mut granted = 0;
+        if status == ClusterStatus::Cleared {
+            let withheld = transaction
+                .query(
+                    "SELECT id FROM users
+                     WHERE signup_fingerprint = $1 AND trial_status = 'withheld'
+                       AND deleted_at IS NULL
+                     FOR UPDATE",
+                    &[&fingerprint],
+                )
+                .await?;
+            for row in &withheld {
+                grant(&transaction, row.get(0)).await?;
+            }
+            granted = withheld.len();
+        }
+        transaction.commit().await?;
+        info!(
+            "Trial cluster {} ({}) marked {} by admin {}, {} trials granted",
+            cluster_id,
+            fingerprint,
+            status.as_str(),
+            reviewer_telegram_id,
+            granted
+        );
+        Ok(Some(granted))
+    }
+}
@@ -0,0 +1,358 @@
+use crate::analysis::AnalysisDepth;
+use crate::llm::ModelTier;
+use crate::localization::Lang;
+use crate::prompts::analysis::OutputLanguage;
+
+/// telegram rejects inline buttons whose callback data exceeds 64 bytes
+pub const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+pub(crate) const ANALYSIS_TYPES: [&str; 4] = ["professional", "personal", "roast", "trends"];
+
+// forwards carry no history worth a timeline, so self-analyses skip trends
+pub(crate) const SELF_ANALYSIS_TYPES: [&str; 3] = ["professional", "personal", "roast"];
+
+/// typed inline keyboard payloads; channel names may contain underscores,
+/// so they are always encoded as the last segment and never split
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallbackData {
+    // buys a package, by package id
+    BuyPackage(i32),
+    Analysis {
+        analysis_type: String,
+        depth: AnalysisDepth,
+        channel_name: String,
+    },
+    Focus {
+        channel_name: String,
+    },
+    Depth {
+        depth: AnalysisDepth,
+        channel_name: String,
+    },
+    ModelTier(ModelTier),
+    // zero-based /balance history page
+    BalancePage(u32),
+    OutputLanguage(OutputLanguage),
+    // bot language picked with /language, None follows the Telegram app's language
+    InterfaceLanguage(Option<Lang>),
+    // new /whatsnew announcement preference
+    Announcements(bool),
+    // new /settings preference for publishing long results as a telegra.ph page
+    WebPages(bool),
+    RevokeApiKeys,
+    // free regeneration of a partial analysis, by analysis id
+    Regenerate(i32),
+    // another delivery of an analysis' stored result, by analysis id
+    Resend(i32),
+    // voice summary of an analysis, by analysis id
+    Listen(i32),
+    // second opinion on an analysis from another model, by analysis id
+    SecondOpinion(i32),
+    // rerun of an analysis stopped for low text coverage, by analysis id
+    LowTextConfirm(i32),
+    // analysis type for the channels of the user's pending batch
+    Batch(String),
+    // whole group (None) or one forum topic of the group the keyboard was sent in; the
+    // analysis type when the command preselected one
+    GroupScope {
+        analysis_type: Option<String>,
+        thread_id: Option<i32>,
+    },
+    // the user finished forwarding messages for a self-analysis
+    SelfDone,
+    // the user agreed to join the private channel of their invite link
+    InviteConsent,
+    // analysis type for the user's forwarded messages
+    SelfAnalysis(String),
+    // the user agreed to have their group messages kept for a cross-group profile
+    CrossGroupConsent,
+    // the user withdrew that consent
+    CrossGroupRevoke,
+    // analysis type for the user's kept group messages
+    CrossGroupAnalysis(String),
+    // consent to post an analysis to the showcase channel, with or without the channel name
+    Showcase {
+        analysis_id: i32,
+        anonymous: bool,
+    },
+    // thumbs up or down on a delivered analysis
+    Feedback {
+        analysis_id: i32,
+        positive: bool,
+    },
+    // the user confirmed /delete_account
+    DeleteAccount,
+    // a new user claiming their free trial with the "I'm human" button
+    VerifyHuman,
+    // checks the proof of a /claim, by claim id
+    ClaimVerify(i32),
+    // the verified owner excluding their channel from analysis, or allowing it again
+    ClaimOptOut {
+        claim_id: i32,
+        opted_out: bool,
+    },
+}
+
+impl CallbackData {
+    pub fn encode(&self) -> String {
+        let encoded = match self {
+            CallbackData::BuyPackage(package_id) => format!("buy_{}", package_id),
+            CallbackData::RevokeApiKeys => "revoke_apikeys".to_string(),
+            CallbackData::SelfDone => "self_done".to_string(),
+            CallbackData::InviteConsent => "invite_consent".to_string(),
+            CallbackData::CrossGroupConsent => "xgroup_consent".to_string(),
+            CallbackData::CrossGroupRevoke => "xgroup_revoke".to_string(),
+            CallbackData::DeleteAccount => "delete_account".to_string(),
+            CallbackData::VerifyHuman => "verify_human".to_string(),
+            CallbackData::Analysis {
+                analysis_type,
+                depth,
+                channel_name,
+            } => format!(
+                "analysis_{}_{}_{}",
+                analysis_type,
+                depth.as_str(),
+                channel_name
+            ),
+            CallbackData::Focus { channel_name } => format!("focus_{}", channel_name),
+            CallbackData::Depth {
+                depth,
+                channel_name,
+            } => format!("depth_{}_{}", depth.as_str(), channel_name),
+            CallbackData::ModelTier(tier) => format!("tier_{}", tier.as_str()),
+            CallbackData::BalancePage(page) => format!("balance_{}", page),
+            CallbackData::Regenerate(analysis_id) => format!("regen_{}", analysis_id),
+            CallbackData::Resend(analysis_id) => format!("resend_{}", analysis_id),
+            CallbackData::Listen(analysis_id) => format!("listen_{}", analysis_id),
+            CallbackData::SecondOpinion(analysis_id) => format!("second_{}", analysis_id),
+            CallbackData::LowTextConfirm(analysis_id) => format!("lowtext_{}", analysis_id),
+            CallbackData::Batch(analysis_type) => format!("batch_{}", analysis_type),
+            CallbackData::SelfAnalysis(analysis_type) => format!("self_{}", analysis_type),
+            CallbackData::CrossGroupAnalysis(analysis_type) => {
+                format!("xgroup_{}", analysis_type)
+            }
+            CallbackData::Showcase {
+                analysis_id,
+                anonymous,
+            } => format!(
+                "showcase_{}_{}",
+                if *anonymous { "anon" } else { "named" },
+                analysis_id
+            ),
+            CallbackData::Feedback {
+                analysis_id,
+                positive,
+            } => format!(
+                "fb_{}_{}",
+                if *positive { "up" } else { "down" },
+                analysis_id
+            ),
+            CallbackData::ClaimVerify(claim_id) => format!("claimv_{}", claim_id),
+            CallbackData::ClaimOptOut {
+                claim_id,
+                opted_out,
+            } => format!(
+                "optout_{}_{}",
+                if *opted_out { "on" } else { "off" },
+                claim_id
+            ),
+            CallbackData::GroupScope {
+                analysis_type,
+                thread_id,
+            } => format!(
+                "gscope_{}_{}",
+                analysis_type.as_deref().unwrap_or("pick"),
+                thread_id.map_or("all".to_string(), |id| id.to_string())
+            ),
+            CallbackData::OutputLanguage(language) => format!("outlang_{}", language.as_str()),
+            CallbackData::InterfaceLanguage(language) => {
+                format!("uilang_{}", language.map_or("auto", |lang| lang.code()))
+            }
+            CallbackData::Announcements(enabled) => {
+                format!("announce_{}", if *enabled { "on" } else { "off" })
+            }
+            CallbackData::WebPages(enabled) => {
+                format!("webpage_{}", if *enabled { "on" } else { "off" })
+            }
+        };
+        // channel names are capped at 32 chars, which keeps every payload within the limit
+        debug_assert!(encoded.len() <= MAX_CALLBACK_DATA_LEN);
+        encoded
+    }
+
+    pub fn parse(data: &str) -> Option<Self> {
+        match data {
+            "revoke_apikeys" => return Some(CallbackData::RevokeApiKeys),
+            "self_done" => return Some(CallbackData::SelfDone),
+            "invite_consent" => return Some(CallbackData::InviteConsent),
+            "xgroup_consent" => return Some(CallbackData::CrossGroupConsent),
+            "xgroup_revoke" => return Some(CallbackData::CrossGroupRevoke),
+            "delete_account" => return Some(CallbackData::DeleteAccount),
+            "verify_human" => return Some(CallbackData::VerifyHuman),
+            _ => {}
+        }
+
+        let (prefix, rest) = data.split_once('_')?;
+        match prefix {
+            "analysis" => {
+                let (analysis_type, rest) = rest.split_once('_')?;
+                if !ANALYSIS_TYPES.contains(&analysis_type) {
+                    return None;
+                }
+                let (depth, channel_name) = Self::parse_depth_and_channel(rest)?;
+                Some(CallbackData::Analysis {
+                    analysis_type: analysis_type.to_string(),
+                    depth,
+                    channel_name,
+                })
+            }
+            "focus" => Some(CallbackData::Focus {
+                channel_name: Self::parse_channel(rest)?,
+            }),
+            "depth" => {
+                let (depth, channel_name) = Self::parse_depth_and_channel(rest)?;
+                Some(CallbackData::Depth {
+                    depth,
+                    channel_name,
+                })
+            }
+            "tier" => ModelTier::ALL
+                .into_iter()
+                .find(|tier| tier.as_str() == rest)
+                .map(CallbackData::ModelTier),
+            "announce" => match rest {
+                "on" => Some(CallbackData::Announcements(true)),
+                "off" => Some(CallbackData::Announcements(false)),
+                _ => None,
+            },
+            "webpage" => match rest {
+                "on" => Some(CallbackData::WebPages(true)),
+                "off" => Some(CallbackData::WebPages(false)),
+                _ => None,
+            },
+            "outlang" => OutputLanguage::ALL
+                .into_iter()
+                .find(|language| language.as_str() == rest)
+                .map(CallbackData::OutputLanguage),
+            "uilang" if rest == "auto" => Some(CallbackData::InterfaceLanguage(None)),
+            "uilang" => Lang::ALL
+                .into_iter()
+                .find(|lang| lang.code() == rest)
+                .map(|lang| CallbackData::InterfaceLanguage(Some(lang))),
+            // u32 parsing accepts a leading '+', which encode never produces
+            "balance" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::BalancePage)
+            }
+            "regen" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::Regenerate)
+            }
+            "resend" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::Resend)
+            }
+            "listen" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::Listen)
+            }
+            "second" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::SecondOpinion)
+            }
+            "lowtext" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::LowTextConfirm)
+            }
+            "batch" if ANALYSIS_TYPES.contains(&rest) => {
+                Some(CallbackData::Batch(rest.to_string()))
+            }
+            "self" if SELF_ANALYSIS_TYPES.contains(&rest) => {
+                Some(CallbackData::SelfAnalysis(rest.to_string()))
+            }
+            "xgroup" if SELF_ANALYSIS_TYPES.contains(&rest) => {
+                Some(CallbackData::CrossGroupAnalysis(rest.to_string()))
+            }
+            "showcase" => {
+                let (mode, analysis_id) = rest.split_once('_')?;
+                let anonymous = match mode {
+                    "anon" => true,
+                    "named" => false,
+                    _ => return None,
+                };
+                if !analysis_id.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                Some(CallbackData::Showcase {
+                    analysis_id: analysis_id.parse().ok()?,
+                    anonymous,
+                })
+            }
+            "fb" => {
+                let (vote, analysis_id) = rest.split_once('_')?;
+                let positive = match vote {
+                    "up" => true,
+                    "down" => false,
+                    _ => return None,
+                };
+                if !analysis_id.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                Some(CallbackData::Feedback {
+                    analysis_id: analysis_id.parse().ok()?,
+                    positive,
+                })
+            }
+            "buy" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::BuyPackage)
+            }
+            "claimv" if rest.bytes().all(|b| b.is_ascii_digit()) => {
+                rest.parse().ok().map(CallbackData::ClaimVerify)
+            }
+            "optout" => {
+                let (state, claim_id) = rest.split_once('_')?;
+                let opted_out = match state {
+                    "on" => true,
+                    "off" => false,
+                    _ => return None,
+                };
+                if !claim_id.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                Some(CallbackData::ClaimOptOut {
+                    claim_id: claim_id.parse().ok()?,
+                    opted_out,
+                })
+            }
+            "gscope" => {
+                let (analysis_type, scope) = rest.split_once('_')?;
+                let analysis_type = match analysis_type {
+                    "pick" => None,
+                    code if ANALYSIS_TYPES.contains(&code) => Some(code.to_string()),
+                    _ => return None,
+                };
+                let thread_id = match scope {
+                    "all" => None,
+                    id if id.bytes().all(|b| b.is_ascii_digit()) => Some(id.parse().ok()?),
+                    _ => return None,
+                };
+                Some(CallbackData::GroupScope {
+                    analysis_type,
+                    thread_id,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// splits "<depth>_<channel>"; keyboards sent before depth existed carry only
+    /// the channel, which always starts with '@' and so never parses as a depth
+    fn parse_depth_and_channel(data: &str) -> Option<(AnalysisDepth, String)> {
+        let (depth, channel) = data
+            .split_once('_')
+            .and_then(|(code, channel)| AnalysisDepth::from_code(code).map(|d| (d, channel)))
+            .unwrap_or((AnalysisDepth::default(), data));
+        Some((depth, Self::parse_channel(channel)?))
+    }
+
+    // public channels are @name, private ones +invitehash
+    fn parse_channel(data: &str) -> Option<String> {
+        (data.starts_with('@') || data.starts_with('+'))
+            .then(|| data.to_string())
+            .filter(|channel| channel.len() > 1)
+    }
+}
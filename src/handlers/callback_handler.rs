@@ -1,16 +1,33 @@
-use log::{error, info};
+use log::{error, info, warn};
+use std::time::Instant;
 use teloxide::prelude::*;
 use teloxide::types::{
-    CallbackQuery, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage,
+    CallbackQuery, ChatAction, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile,
+    MaybeInaccessibleMessage, ParseMode,
 };
 
-use crate::bot::BotContext;
-use crate::handlers::payment_handler::{
-    PaymentHandler, BULK_PACKAGE_AMOUNT, BULK_PACKAGE_PRICE, SINGLE_PACKAGE_AMOUNT,
-    SINGLE_PACKAGE_PRICE,
+use crate::analysis::{
+    cross_group_corpus_name, invite_hash, self_corpus_name, AnalysisDepth, CorpusKind, ForumTopic,
 };
+use crate::bot::{BotContext, TelegramBot};
+use crate::channel_claims::{self, ChannelClaim};
+use crate::cross_group::{self, CROSS_GROUP_DEPTH, MIN_CROSS_GROUP_MESSAGES};
+use crate::error::AppError;
+use crate::feedback::Vote;
+use crate::free_trials::TrialOutcome;
+use crate::handlers::payment_handler::PaymentHandler;
+use crate::handlers::CallbackData;
+use crate::limits::Limits;
+use crate::llm::ModelTier;
 use crate::localization::Lang;
-use crate::user_manager::UserManagerError;
+use crate::packages::Package;
+use crate::prompts::analysis::{OutputLanguage, MAX_FOCUS_LENGTH};
+use crate::rate_limiters::user::Throttle;
+use crate::self_analysis::MIN_SELF_MESSAGES;
+use crate::user_manager::{AnalysisSource, User, UserManagerError};
+use crate::user_sessions::UserSession;
+use crate::utils::{MessageFormatter, ResultPresenter};
+use crate::voice::voice_script;
 
 pub struct CallbackHandler;
 
@@ -22,37 +39,382 @@ impl CallbackHandler {
         }
     }
 
-    pub fn create_payment_keyboard(lang: Lang) -> InlineKeyboardMarkup {
-        let single_button = InlineKeyboardButton::callback(
-            lang.btn_buy_single(SINGLE_PACKAGE_AMOUNT, SINGLE_PACKAGE_PRICE),
-            "buy_single",
-        );
-        let bulk_button = InlineKeyboardButton::callback(
-            lang.btn_buy_bulk(BULK_PACKAGE_AMOUNT, BULK_PACKAGE_PRICE),
-            "buy_bulk",
-        );
+    /// a button per package on sale, as PackageManager::active lists them
+    pub fn create_payment_keyboard(packages: &[Package], lang: Lang) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(
+            packages
+                .iter()
+                .map(|package| {
+                    vec![InlineKeyboardButton::callback(
+                        lang.btn_buy_package(package.credits, package.stars),
+                        CallbackData::BuyPackage(package.id).encode(),
+                    )]
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
 
-        InlineKeyboardMarkup::new(vec![vec![single_button], vec![bulk_button]])
+    pub fn create_model_tier_keyboard(
+        current: ModelTier,
+        web_pages: bool,
+        lang: Lang,
+    ) -> InlineKeyboardMarkup {
+        let mut rows = ModelTier::ALL
+            .iter()
+            .map(|tier| {
+                let label = if *tier == current {
+                    format!("✅ {}", lang.model_tier_name(*tier))
+                } else {
+                    lang.model_tier_name(*tier).to_string()
+                };
+                vec![InlineKeyboardButton::callback(
+                    label,
+                    CallbackData::ModelTier(*tier).encode(),
+                )]
+            })
+            .collect::<Vec<_>>();
+        rows.push(vec![InlineKeyboardButton::callback(
+            lang.btn_web_pages(web_pages),
+            CallbackData::WebPages(!web_pages).encode(),
+        )]);
+
+        InlineKeyboardMarkup::new(rows)
     }
 
-    pub fn create_analysis_selection_keyboard(channel_name: &str, lang: Lang) -> InlineKeyboardMarkup {
-        let professional_button = InlineKeyboardButton::callback(
-            lang.btn_professional_analysis(),
-            format!("analysis_professional_{}", channel_name),
-        );
-        let personal_button = InlineKeyboardButton::callback(
-            lang.btn_personal_analysis(),
-            format!("analysis_personal_{}", channel_name),
+    pub fn create_output_language_keyboard(
+        current: OutputLanguage,
+        lang: Lang,
+    ) -> InlineKeyboardMarkup {
+        let rows = OutputLanguage::ALL
+            .iter()
+            .map(|language| {
+                let label = if *language == current {
+                    format!("✅ {}", lang.output_language_name(*language))
+                } else {
+                    lang.output_language_name(*language).to_string()
+                };
+                vec![InlineKeyboardButton::callback(
+                    label,
+                    CallbackData::OutputLanguage(*language).encode(),
+                )]
+            })
+            .collect::<Vec<_>>();
+
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_interface_language_keyboard(
+        current: Option<Lang>,
+        lang: Lang,
+    ) -> InlineKeyboardMarkup {
+        let choices = std::iter::once(None).chain(Lang::ALL.into_iter().map(Some));
+        let rows = choices
+            .map(|choice| {
+                let name =
+                    choice.map_or(lang.ui_language_auto_name(), |choice| choice.native_name());
+                let label = if choice == current {
+                    format!("✅ {}", name)
+                } else {
+                    name.to_string()
+                };
+                vec![InlineKeyboardButton::callback(
+                    label,
+                    CallbackData::InterfaceLanguage(choice).encode(),
+                )]
+            })
+            .collect::<Vec<_>>();
+
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    fn create_balance_keyboard(page: u32, total_pages: u32, lang: Lang) -> InlineKeyboardMarkup {
+        let mut buttons = Vec::new();
+        if page > 0 {
+            buttons.push(InlineKeyboardButton::callback(
+                lang.btn_balance_newer(),
+                CallbackData::BalancePage(page - 1).encode(),
+            ));
+        }
+        if page + 1 < total_pages {
+            buttons.push(InlineKeyboardButton::callback(
+                lang.btn_balance_older(),
+                CallbackData::BalancePage(page + 1).encode(),
+            ));
+        }
+
+        let rows = if buttons.is_empty() {
+            vec![]
+        } else {
+            vec![buttons]
+        };
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    /// renders one page of the user's credit ledger with navigation buttons
+    pub async fn build_balance_page(
+        ctx: &BotContext,
+        user: &User,
+        page: u32,
+        lang: Lang,
+    ) -> Result<(String, InlineKeyboardMarkup), UserManagerError> {
+        let total = ctx.user_manager.count_credit_transactions(user.id).await?;
+        let page_size = ctx.limits().balance_page_size;
+        let total_pages = (total as u32).div_ceil(page_size as u32).max(1);
+        // the ledger may have shrunk below an old page's offset; show the last page instead
+        let page = page.min(total_pages - 1);
+        let transactions = ctx
+            .user_manager
+            .get_credit_transactions(user.id, page_size, page as i64 * page_size)
+            .await?;
+
+        Ok((
+            lang.balance_overview(user.analysis_credits, &transactions, page, total_pages),
+            Self::create_balance_keyboard(page, total_pages, lang),
+        ))
+    }
+
+    /// renders the latest changelog entries with the announcement toggle
+    pub async fn build_whats_new(
+        ctx: &BotContext,
+        user_id: i32,
+        lang: Lang,
+    ) -> Result<(String, InlineKeyboardMarkup), AppError> {
+        let enabled = ctx.user_manager.get_announcements_enabled(user_id).await?;
+        let entries = ctx
+            .changelog
+            .recent_entries(ctx.limits().whats_new_entries)
+            .await?
+            .iter()
+            .map(|entry| entry.render(lang))
+            .collect::<Vec<_>>();
+
+        let toggle = InlineKeyboardButton::callback(
+            lang.btn_announcements(enabled),
+            CallbackData::Announcements(!enabled).encode(),
         );
-        let roast_button = InlineKeyboardButton::callback(
-            lang.btn_roast_analysis(),
-            format!("analysis_roast_{}", channel_name),
+        Ok((
+            lang.whats_new(&entries, enabled),
+            InlineKeyboardMarkup::new(vec![vec![toggle]]),
+        ))
+    }
+
+    pub fn create_api_key_keyboard(lang: Lang) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            lang.btn_revoke_api_key(),
+            CallbackData::RevokeApiKeys.encode(),
+        )]])
+    }
+
+    pub fn create_analysis_selection_keyboard(
+        channel_name: &str,
+        depth: AnalysisDepth,
+        limits: &Limits,
+        lang: Lang,
+    ) -> InlineKeyboardMarkup {
+        let analysis_button = |label: &str, analysis_type: &str| {
+            InlineKeyboardButton::callback(
+                label,
+                CallbackData::Analysis {
+                    analysis_type: analysis_type.to_string(),
+                    depth,
+                    channel_name: channel_name.to_string(),
+                }
+                .encode(),
+            )
+        };
+        let professional_button = analysis_button(lang.btn_professional_analysis(), "professional");
+        let personal_button = analysis_button(lang.btn_personal_analysis(), "personal");
+        let roast_button = analysis_button(lang.btn_roast_analysis(), "roast");
+        let trends_button = analysis_button(lang.btn_trends_analysis(), "trends");
+        let depth_buttons = AnalysisDepth::ALL
+            .iter()
+            .map(|option| {
+                InlineKeyboardButton::callback(
+                    lang.btn_depth(*option, limits.depth_credits(*option), *option == depth),
+                    CallbackData::Depth {
+                        depth: *option,
+                        channel_name: channel_name.to_string(),
+                    }
+                    .encode(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let focus_button = InlineKeyboardButton::callback(
+            lang.btn_add_focus(),
+            CallbackData::Focus {
+                channel_name: channel_name.to_string(),
+            }
+            .encode(),
         );
 
         InlineKeyboardMarkup::new(vec![
+            depth_buttons,
             vec![professional_button],
             vec![personal_button],
             vec![roast_button],
+            vec![trends_button],
+            vec![focus_button],
+        ])
+    }
+
+    pub fn create_batch_keyboard(lang: Lang) -> InlineKeyboardMarkup {
+        let batch_button = |label: &str, analysis_type: &str| {
+            vec![InlineKeyboardButton::callback(
+                label,
+                CallbackData::Batch(analysis_type.to_string()).encode(),
+            )]
+        };
+        InlineKeyboardMarkup::new(vec![
+            batch_button(lang.btn_professional_analysis(), "professional"),
+            batch_button(lang.btn_personal_analysis(), "personal"),
+            batch_button(lang.btn_roast_analysis(), "roast"),
+            batch_button(lang.btn_trends_analysis(), "trends"),
+        ])
+    }
+
+    pub fn create_self_done_keyboard(lang: Lang) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            lang.btn_self_done(),
+            CallbackData::SelfDone.encode(),
+        )]])
+    }
+
+    pub fn create_showcase_keyboard(analysis_id: i32, lang: Lang) -> InlineKeyboardMarkup {
+        let showcase_button = |label: &str, anonymous: bool| {
+            vec![InlineKeyboardButton::callback(
+                label,
+                CallbackData::Showcase {
+                    analysis_id,
+                    anonymous,
+                }
+                .encode(),
+            )]
+        };
+        InlineKeyboardMarkup::new(vec![
+            showcase_button(lang.btn_showcase_named(), false),
+            showcase_button(lang.btn_showcase_anonymous(), true),
+        ])
+    }
+
+    /// rating buttons under a delivered analysis; votes can be changed, so they stay
+    pub fn create_feedback_keyboard(analysis_id: i32) -> InlineKeyboardMarkup {
+        let feedback_button = |label: &str, positive: bool| {
+            InlineKeyboardButton::callback(
+                label,
+                CallbackData::Feedback {
+                    analysis_id,
+                    positive,
+                }
+                .encode(),
+            )
+        };
+        InlineKeyboardMarkup::new(vec![vec![
+            feedback_button("👍", true),
+            feedback_button("👎", false),
+        ]])
+    }
+
+    pub fn create_self_analysis_keyboard(lang: Lang) -> InlineKeyboardMarkup {
+        let self_button = |label: &str, analysis_type: &str| {
+            vec![InlineKeyboardButton::callback(
+                label,
+                CallbackData::SelfAnalysis(analysis_type.to_string()).encode(),
+            )]
+        };
+        InlineKeyboardMarkup::new(vec![
+            self_button(lang.btn_professional_analysis(), "professional"),
+            self_button(lang.btn_personal_analysis(), "personal"),
+            self_button(lang.btn_roast_analysis(), "roast"),
+        ])
+    }
+
+    pub fn create_cross_group_consent_keyboard(lang: Lang) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            lang.btn_cross_group_consent(),
+            CallbackData::CrossGroupConsent.encode(),
+        )]])
+    }
+
+    /// the analysis types once enough group messages were kept, and the way out
+    pub fn create_cross_group_keyboard(ready: bool, lang: Lang) -> InlineKeyboardMarkup {
+        let cross_group_button = |label: &str, analysis_type: &str| {
+            vec![InlineKeyboardButton::callback(
+                label,
+                CallbackData::CrossGroupAnalysis(analysis_type.to_string()).encode(),
+            )]
+        };
+        let mut rows = Vec::new();
+        if ready {
+            rows.push(cross_group_button(
+                lang.btn_professional_analysis(),
+                "professional",
+            ));
+            rows.push(cross_group_button(lang.btn_personal_analysis(), "personal"));
+            rows.push(cross_group_button(lang.btn_roast_analysis(), "roast"));
+        }
+        rows.push(vec![InlineKeyboardButton::callback(
+            lang.btn_cross_group_revoke(),
+            CallbackData::CrossGroupRevoke.encode(),
+        )]);
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_claim_verify_keyboard(claim_id: i32, lang: Lang) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            lang.btn_claim_verify(),
+            CallbackData::ClaimVerify(claim_id).encode(),
+        )]])
+    }
+
+    pub fn create_delete_account_keyboard(lang: Lang) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            lang.btn_delete_account(),
+            CallbackData::DeleteAccount.encode(),
+        )]])
+    }
+
+    pub fn create_verify_human_keyboard(lang: Lang) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            lang.btn_verify_human(),
+            CallbackData::VerifyHuman.encode(),
+        )]])
+    }
+
+    /// `opted_out` is the state the button switches the verified owner's channel to
+    pub fn create_claim_owner_keyboard(
+        claim_id: i32,
+        opted_out: bool,
+        lang: Lang,
+    ) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            lang.btn_claim_opt_out(opted_out),
+            CallbackData::ClaimOptOut {
+                claim_id,
+                opted_out,
+            }
+            .encode(),
+        )]])
+    }
+
+    /// whole group or only the forum topic a group analysis command was sent in
+    pub fn create_group_scope_keyboard(
+        topic: &ForumTopic,
+        analysis_type: Option<&str>,
+        lang: Lang,
+    ) -> InlineKeyboardMarkup {
+        let scope_button = |label: String, thread_id: Option<i32>| {
+            vec![InlineKeyboardButton::callback(
+                label,
+                CallbackData::GroupScope {
+                    analysis_type: analysis_type.map(str::to_string),
+                    thread_id,
+                }
+                .encode(),
+            )]
+        };
+        InlineKeyboardMarkup::new(vec![
+            scope_button(lang.btn_whole_group().to_string(), None),
+            scope_button(lang.btn_topic_only(&topic.name), Some(topic.thread_id)),
         ])
     }
 
@@ -60,22 +422,215 @@ impl CallbackHandler {
         ctx: BotContext,
         query: CallbackQuery,
     ) -> ResponseResult<()> {
-        let lang = Lang::from_code(query.from.language_code.as_deref());
+        let lang = TelegramBot::user_lang(&ctx, Some(&query.from)).await;
+
+        if let Throttle::Limited { retry_after, .. } = ctx
+            .user_rate_limiter
+            .check(query.from.id.0 as i64, Instant::now())
+        {
+            info!("Throttled callback from user {}", query.from.id);
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.slow_down(retry_after.as_secs().max(1)))
+                .await?;
+            return Ok(());
+        }
 
         if let Some(data) = &query.data {
             if let Some(message) = &query.message {
-                match data.as_str() {
-                    "buy_single" => {
-                        Self::handle_buy_single_callback(ctx, message, &query, lang).await?;
+                match CallbackData::parse(data) {
+                    Some(CallbackData::BuyPackage(package_id)) => {
+                        Self::handle_buy_package_callback(ctx, message, &query, package_id, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::RevokeApiKeys) => {
+                        Self::handle_revoke_api_keys_callback(ctx, message, &query, lang).await?;
+                    }
+                    Some(CallbackData::Analysis {
+                        analysis_type,
+                        depth,
+                        channel_name,
+                    }) => {
+                        Self::handle_analysis_callback(
+                            ctx,
+                            message,
+                            &query,
+                            &analysis_type,
+                            depth,
+                            &channel_name,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::Focus { channel_name }) => {
+                        Self::handle_focus_callback(ctx, message, &query, &channel_name, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::Depth {
+                        depth,
+                        channel_name,
+                    }) => {
+                        Self::handle_depth_callback(
+                            ctx,
+                            message,
+                            &query,
+                            depth,
+                            &channel_name,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::ModelTier(tier)) => {
+                        Self::handle_model_tier_callback(ctx, message, &query, tier, lang).await?;
+                    }
+                    Some(CallbackData::OutputLanguage(language)) => {
+                        Self::handle_output_language_callback(ctx, message, &query, language, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::InterfaceLanguage(choice)) => {
+                        Self::handle_interface_language_callback(
+                            ctx, message, &query, choice, lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::Announcements(enabled)) => {
+                        Self::handle_announcements_callback(ctx, message, &query, enabled, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::WebPages(enabled)) => {
+                        Self::handle_web_pages_callback(ctx, message, &query, enabled, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::BalancePage(page)) => {
+                        Self::handle_balance_page_callback(ctx, message, &query, page, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::Regenerate(analysis_id)) => {
+                        Self::handle_regenerate_callback(ctx, message, &query, analysis_id, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::Resend(analysis_id)) => {
+                        Self::handle_resend_callback(ctx, message, &query, analysis_id, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::Listen(analysis_id)) => {
+                        Self::handle_listen_callback(ctx, message, &query, analysis_id, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::SecondOpinion(analysis_id)) => {
+                        Self::handle_second_opinion_callback(
+                            ctx,
+                            message,
+                            &query,
+                            analysis_id,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::LowTextConfirm(analysis_id)) => {
+                        Self::handle_low_text_confirm_callback(
+                            ctx,
+                            message,
+                            &query,
+                            analysis_id,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::Batch(analysis_type)) => {
+                        Self::handle_batch_callback(ctx, message, &query, analysis_type, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::SelfDone) => {
+                        Self::handle_self_done_callback(ctx, message, &query, lang).await?;
+                    }
+                    Some(CallbackData::InviteConsent) => {
+                        Self::handle_invite_consent_callback(ctx, message, &query, lang).await?;
+                    }
+                    Some(CallbackData::SelfAnalysis(analysis_type)) => {
+                        Self::handle_self_analysis_callback(
+                            ctx,
+                            message,
+                            &query,
+                            &analysis_type,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::CrossGroupConsent) => {
+                        Self::handle_cross_group_consent_callback(ctx, message, &query, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::CrossGroupRevoke) => {
+                        Self::handle_cross_group_revoke_callback(ctx, message, &query, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::CrossGroupAnalysis(analysis_type)) => {
+                        Self::handle_cross_group_analysis_callback(
+                            ctx,
+                            message,
+                            &query,
+                            &analysis_type,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::Showcase {
+                        analysis_id,
+                        anonymous,
+                    }) => {
+                        Self::handle_showcase_callback(
+                            ctx,
+                            message,
+                            &query,
+                            analysis_id,
+                            anonymous,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    Some(CallbackData::Feedback {
+                        analysis_id,
+                        positive,
+                    }) => {
+                        Self::handle_feedback_callback(ctx, &query, analysis_id, positive, lang)
+                            .await?;
+                    }
+                    Some(CallbackData::GroupScope {
+                        analysis_type,
+                        thread_id,
+                    }) => {
+                        Self::handle_group_scope_callback(
+                            ctx,
+                            message,
+                            &query,
+                            analysis_type,
+                            thread_id,
+                            lang,
+                        )
+                        .await?;
                     }
-                    "buy_bulk" => {
-                        Self::handle_buy_bulk_callback(ctx, message, &query, lang).await?;
+                    Some(CallbackData::DeleteAccount) => {
+                        Self::handle_delete_account_callback(ctx, message, &query, lang).await?;
                     }
-                    callback_data if callback_data.starts_with("analysis_") => {
-                        Self::handle_analysis_callback(ctx, message, &query, callback_data, lang)
+                    Some(CallbackData::VerifyHuman) => {
+                        Self::handle_verify_human_callback(ctx, message, &query, lang).await?;
+                    }
+                    Some(CallbackData::ClaimVerify(claim_id)) => {
+                        Self::handle_claim_verify_callback(ctx, message, &query, claim_id, lang)
                             .await?;
                     }
-                    _ => {
+                    Some(CallbackData::ClaimOptOut {
+                        claim_id,
+                        opted_out,
+                    }) => {
+                        Self::handle_claim_opt_out_callback(
+                            ctx, message, &query, claim_id, opted_out, lang,
+                        )
+                        .await?;
+                    }
+                    None => {
+                        warn!("Unknown callback data: {}", data);
                         ctx.bot.answer_callback_query(&query.id).await?;
                     }
                 }
@@ -84,19 +639,39 @@ impl CallbackHandler {
         Ok(())
     }
 
-    async fn handle_buy_single_callback(
+    /// sends the invoice of a package; the button may outlive the package
+    async fn handle_buy_package_callback(
         ctx: BotContext,
         message: &MaybeInaccessibleMessage,
         query: &CallbackQuery,
+        package_id: i32,
         lang: Lang,
     ) -> ResponseResult<()> {
-        PaymentHandler::send_payment_invoice(
+        let package = match ctx.packages.get_active(package_id).await {
+            Ok(Some(package)) => package,
+            Ok(None) => {
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.package_unavailable())
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to load package {}: {}", package_id, e);
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.error_processing_request())
+                    .await?;
+                return Ok(());
+            }
+        };
+        let packages = ctx.packages.active().await;
+        PaymentHandler::send_package_invoice(
             ctx.bot.clone(),
             Self::get_chat_id(message),
-            SINGLE_PACKAGE_AMOUNT,
-            SINGLE_PACKAGE_PRICE,
-            lang.invoice_single_title(),
-            lang.invoice_single_description(),
+            &packages,
+            &package,
+            lang,
         )
         .await?;
 
@@ -104,192 +679,2071 @@ impl CallbackHandler {
         Ok(())
     }
 
-    async fn handle_buy_bulk_callback(
+    async fn handle_focus_callback(
         ctx: BotContext,
         message: &MaybeInaccessibleMessage,
         query: &CallbackQuery,
+        channel_name: &str,
         lang: Lang,
     ) -> ResponseResult<()> {
-        let discount = (SINGLE_PACKAGE_PRICE * BULK_PACKAGE_AMOUNT as u32) - BULK_PACKAGE_PRICE;
-        PaymentHandler::send_payment_invoice(
-            ctx.bot.clone(),
-            Self::get_chat_id(message),
-            BULK_PACKAGE_AMOUNT,
-            BULK_PACKAGE_PRICE,
-            lang.invoice_bulk_title(),
-            &lang.invoice_bulk_description(discount),
-        )
-        .await?;
+        ctx.user_sessions
+            .update(query.from.id.0 as i64, |session| {
+                session.request_focus(channel_name)
+            })
+            .await;
+
+        ctx.bot
+            .send_message(
+                Self::get_chat_id(message),
+                lang.focus_request(MAX_FOCUS_LENGTH),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
 
         ctx.bot.answer_callback_query(&query.id).await?;
         Ok(())
     }
 
-    async fn handle_analysis_callback(
+    async fn handle_depth_callback(
         ctx: BotContext,
         message: &MaybeInaccessibleMessage,
         query: &CallbackQuery,
-        callback_data: &str,
-        lang: Lang,
-    ) -> ResponseResult<()> {
-        // parse analysis type and channel from callback data
-        let parts: Vec<&str> = callback_data.splitn(3, '_').collect();
-        if parts.len() >= 3 {
-            let analysis_type = parts[1]; // professional, personal, or roast
-            let channel_name = parts[2];
-
-            let telegram_user_id = query.from.id.0 as i64;
-
-            // check if user has credits before starting analysis
-            let user = match ctx
-                .user_manager
-                .get_or_create_user(
-                    telegram_user_id,
-                    query.from.username.as_deref(),
-                    Some(query.from.first_name.as_str()),
-                    query.from.last_name.as_deref(),
-                    None, // no referral in callback queries
-                    query.from.language_code.as_deref(),
-                )
-                .await
-            {
-                Ok((user, _)) => user,
-                Err(e) => {
-                    error!("Failed to get user: {}", e);
-                    ctx.bot
-                        .send_message(Self::get_chat_id(message), lang.error_check_credits())
-                        .await?;
-                    return Ok(());
-                }
-            };
+        depth: AnalysisDepth,
+        channel_name: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        ctx.user_sessions
+            .update(query.from.id.0 as i64, |session| {
+                session.choose_depth(channel_name, depth)
+            })
+            .await;
 
-            if user.analysis_credits <= 0 {
-                // no credits available, send payment options
+        // re-render the keyboard so the type buttons carry the selected depth
+        ctx.bot
+            .edit_message_reply_markup(Self::get_chat_id(message), message.id())
+            .reply_markup(Self::create_analysis_selection_keyboard(
+                channel_name,
+                depth,
+                &ctx.limits(),
+                lang,
+            ))
+            .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_model_tier_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        tier: ModelTier,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
                 ctx.bot
-                    .send_message(Self::get_chat_id(message), lang.no_credits_short())
-                    .reply_markup(Self::create_payment_keyboard(lang))
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
                     .await?;
-
                 ctx.bot.answer_callback_query(&query.id).await?;
                 return Ok(());
             }
+        };
 
-            // create pending analysis record first
-            let analysis_id = match ctx
-                .user_manager
-                .create_pending_analysis(
-                    user.id,
-                    channel_name,
-                    analysis_type,
-                    query.from.language_code.as_deref(),
-                )
-                .await
-            {
-                Ok(id) => id,
-                Err(e) => {
-                    let error_msg = match e {
-                        UserManagerError::UserNotFound(_) => lang.error_user_not_found(),
-                        _ => lang.error_start_analysis(),
-                    };
-                    let _ = ctx
-                        .bot
-                        .send_message(Self::get_chat_id(message), error_msg)
-                        .await;
-                    ctx.bot.answer_callback_query(&query.id).await?;
-                    return Ok(());
-                }
-            };
+        let web_pages = match ctx.user_manager.set_model_tier(user.id, tier).await {
+            Ok(()) => ctx.user_manager.get_web_pages_enabled(user.id).await,
+            Err(e) => Err(e),
+        };
+        let web_pages = match web_pages {
+            Ok(web_pages) => web_pages,
+            Err(e) => {
+                error!("Failed to set model tier for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
 
-            // start analysis in background
-            Self::start_analysis_in_background(
-                ctx.clone(),
+        // refresh the settings message in place so the checkmark follows the selection
+        ctx.bot
+            .edit_message_text(
                 Self::get_chat_id(message),
-                channel_name.to_string(),
-                analysis_type.to_string(),
-                user,
-                analysis_id,
-                lang,
+                message.id(),
+                lang.settings_model_tier(tier, web_pages),
             )
-            .await;
+            .parse_mode(ParseMode::Html)
+            .reply_markup(Self::create_model_tier_keyboard(tier, web_pages, lang))
+            .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_web_pages_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        enabled: bool,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        let tier = match ctx
+            .user_manager
+            .set_web_pages_enabled(user.id, enabled)
+            .await
+        {
+            Ok(()) => ctx.user_manager.get_model_tier(user.id).await,
+            Err(e) => Err(e),
+        };
+        match tier {
+            Ok(tier) => {
+                ctx.bot
+                    .edit_message_text(
+                        Self::get_chat_id(message),
+                        message.id(),
+                        lang.settings_model_tier(tier, enabled),
+                    )
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(Self::create_model_tier_keyboard(tier, enabled, lang))
+                    .await?;
+            }
+            Err(e) => {
+                error!("Failed to update web pages for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+            }
         }
 
         ctx.bot.answer_callback_query(&query.id).await?;
         Ok(())
     }
 
-    async fn start_analysis_in_background(
+    async fn handle_output_language_callback(
         ctx: BotContext,
-        user_chat_id: ChatId,
-        channel_name: String,
-        analysis_type: String,
-        user: crate::user_manager::User,
-        analysis_id: i32,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        language: OutputLanguage,
         lang: Lang,
-    ) {
-        use crate::bot::TelegramBot;
-
-        let bot_clone = ctx.bot.clone();
-        let analysis_engine_clone = ctx.analysis_engine.clone();
-        let user_manager_clone = ctx.user_manager.clone();
-        let user_manager_error_clone = ctx.user_manager.clone();
-        let channel_locks_clone = ctx.channel_locks.clone();
-
-        tokio::spawn(async move {
-            if let Err(e) = TelegramBot::perform_single_analysis(
-                bot_clone.clone(),
-                user_chat_id,
-                channel_name.clone(),
-                analysis_type.clone(),
-                analysis_engine_clone,
-                user_manager_clone,
-                user.id,
-                analysis_id,
-                channel_locks_clone,
-                lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
             )
             .await
-            {
-                // mark analysis as failed
-                if let Err(mark_err) = user_manager_error_clone
-                    .mark_analysis_failed(analysis_id)
-                    .await
-                {
-                    error!(
-                        "Failed to mark analysis {} as failed: {}",
-                        analysis_id, mark_err
-                    );
-                }
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
 
-                if let Some(user_error) = e.downcast_ref::<crate::user_manager::UserManagerError>()
-                {
-                    match user_error {
-                        crate::user_manager::UserManagerError::InsufficientCredits(user_id) => {
-                            info!("Analysis failed: User {} has insufficient credits", user_id);
-                            let _ = bot_clone
-                                .send_message(user_chat_id, lang.error_insufficient_credits())
-                                .await;
-                        }
-                        _ => {
-                            error!(
-                                "Analysis failed for channel {} (type: {}): {}",
-                                channel_name, analysis_type, e
-                            );
-                            error!("User manager error during analysis: {}", user_error);
-                            let _ = bot_clone
-                                .send_message(user_chat_id, lang.error_system())
-                                .await;
-                        }
-                    }
-                } else {
-                    // log the full error details
-                    error!(
-                        "Analysis failed for channel {} (type: {}): {}",
-                        channel_name, analysis_type, e
-                    );
-                    error!("Non-user error during analysis: {}", e);
-                    // don't send generic error - it's already handled in perform_single_analysis
-                }
+        if let Err(e) = ctx
+            .user_manager
+            .set_output_language(user.id, language)
+            .await
+        {
+            error!("Failed to set output language for user {}: {}", user.id, e);
+            ctx.bot
+                .send_message(Self::get_chat_id(message), lang.error_account_access())
+                .await?;
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+
+        ctx.bot
+            .edit_message_text(
+                Self::get_chat_id(message),
+                message.id(),
+                lang.settings_output_language(language),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(Self::create_output_language_keyboard(language, lang))
+            .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_interface_language_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        choice: Option<Lang>,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
             }
+        };
+
+        if let Err(e) = ctx
+            .language_overrides
+            .set(
+                user.id,
+                user.telegram_user_id,
+                choice.map(|choice| choice.code()),
+            )
+            .await
+        {
+            error!(
+                "Failed to set language override for user {}: {}",
+                user.id, e
+            );
+            ctx.bot
+                .send_message(Self::get_chat_id(message), lang.error_account_access())
+                .await?;
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+
+        // the confirmation is already in the newly picked language
+        let lang = Lang::resolve(
+            choice.map(|choice| choice.code()),
+            query.from.language_code.as_deref(),
+        );
+        ctx.bot
+            .edit_message_text(
+                Self::get_chat_id(message),
+                message.id(),
+                lang.settings_ui_language(choice),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(Self::create_interface_language_keyboard(choice, lang))
+            .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_announcements_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        enabled: bool,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        let page = match ctx
+            .user_manager
+            .set_announcements_enabled(user.id, enabled)
+            .await
+        {
+            Ok(()) => Self::build_whats_new(&ctx, user.id, lang).await,
+            Err(e) => Err(e.into()),
+        };
+
+        match page {
+            Ok((text, keyboard)) => {
+                ctx.bot
+                    .edit_message_text(Self::get_chat_id(message), message.id(), text)
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(keyboard)
+                    .await?;
+            }
+            Err(e) => {
+                error!("Failed to update announcements for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+            }
+        }
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_revoke_api_keys_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        match ctx.user_manager.revoke_api_keys(user.id).await {
+            // replacing the message also removes the key from the chat
+            Ok(_) => {
+                ctx.bot
+                    .edit_message_text(
+                        Self::get_chat_id(message),
+                        message.id(),
+                        lang.api_keys_revoked(),
+                    )
+                    .await?;
+            }
+            Err(e) => {
+                error!("Failed to revoke API keys of user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+            }
+        }
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_balance_page_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        page: u32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let page = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => Self::build_balance_page(&ctx, &user, page, lang)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match page {
+            Ok((text, keyboard)) => {
+                ctx.bot
+                    .edit_message_text(Self::get_chat_id(message), message.id(), text)
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(keyboard)
+                    .await?;
+            }
+            Err(e) => {
+                error!("Failed to load balance history: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+            }
+        }
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_analysis_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_type: &str, // professional, personal, roast or trends
+        depth: AnalysisDepth,
+        channel_name: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let telegram_user_id = query.from.id.0 as i64;
+
+        // check if user has credits before starting analysis
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                telegram_user_id,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None, // no referral in callback queries
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_check_credits())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        Self::start_analysis(
+            ctx.clone(),
+            Self::get_chat_id(message),
+            user,
+            channel_name,
+            analysis_type,
+            depth,
+            lang,
+        )
+        .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// checks the user's credits, records the pending analysis and queues it;
+    /// shared by the type keyboard and commands that pick the type up front
+    pub(crate) async fn start_analysis(
+        ctx: BotContext,
+        chat_id: ChatId,
+        user: User,
+        channel_name: &str,
+        analysis_type: &str, // professional, personal, roast or trends
+        depth: AnalysisDepth,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let credits_required = ctx.limits().depth_credits(depth);
+        // verified owners analyze their own channel for free a few times a month
+        let free =
+            channel_claims::is_free_owner_analysis(&ctx, user.telegram_user_id, channel_name).await;
+
+        if !free && user.analysis_credits <= 0 {
+            // no credits available, send payment options
+            ctx.bot
+                .send_message(chat_id, lang.no_credits_short())
+                .reply_markup(Self::create_payment_keyboard(
+                    &ctx.packages.active().await,
+                    lang,
+                ))
+                .await?;
+            return Ok(());
+        }
+
+        if !free && user.analysis_credits < credits_required {
+            // deeper analyses cost more than the user has left
+            ctx.bot
+                .send_message(
+                    chat_id,
+                    lang.not_enough_credits_for_depth(credits_required, user.analysis_credits),
+                )
+                .reply_markup(Self::create_payment_keyboard(
+                    &ctx.packages.active().await,
+                    lang,
+                ))
+                .await?;
+            return Ok(());
+        }
+
+        // pick up the focus instruction and topic if the user set them for this channel
+        let session = ctx
+            .user_sessions
+            .remove(user.telegram_user_id)
+            .await
+            .filter(|session| session.channel_name.as_deref() == Some(channel_name))
+            .unwrap_or_default();
+        let (focus, topic) = (session.focus, session.topic);
+
+        // create pending analysis record first
+        let analysis_id = match ctx
+            .user_manager
+            .create_pending_analysis(
+                user.id,
+                channel_name,
+                analysis_type,
+                depth.as_str(),
+                Some(lang.code()),
+                focus.as_deref(),
+                AnalysisSource::Bot,
+                Some(ctx.limits().max_running_analyses),
+            )
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                let error_msg = match e {
+                    UserManagerError::UserNotFound(_) => lang.error_user_not_found(),
+                    UserManagerError::TooManyRunning(_) => lang.analysis_already_running(),
+                    _ => lang.error_start_analysis(),
+                };
+                let _ = ctx.bot.send_message(chat_id, error_msg).await;
+                return Ok(());
+            }
+        };
+        Self::store_topic(&ctx, analysis_id, topic.as_ref()).await;
+
+        // start analysis in background
+        Self::queue_analysis(&ctx, chat_id, analysis_type, analysis_id, false, lang).await;
+        Ok(())
+    }
+
+    /// starts the user's pending batch once the whole of it can be paid for
+    async fn handle_batch_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_type: String,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        let channels = ctx
+            .user_sessions
+            .get(telegram_user_id)
+            .await
+            .map(|session| session.batch)
+            .unwrap_or_default();
+        if channels.is_empty() {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.batch_expired())
+                .await?;
+            return Ok(());
+        }
+
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                telegram_user_id,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        // the batch stays in the session, so it can be started after a top up
+        let credits_required =
+            ctx.limits().depth_credits(AnalysisDepth::default()) * channels.len() as i32;
+        if user.analysis_credits < credits_required {
+            ctx.bot
+                .send_message(
+                    chat_id,
+                    lang.not_enough_credits_for_batch(credits_required, user.analysis_credits),
+                )
+                .reply_markup(Self::create_payment_keyboard(
+                    &ctx.packages.active().await,
+                    lang,
+                ))
+                .await?;
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+
+        ctx.user_sessions.remove(telegram_user_id).await;
+        // the batch can only be started once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+
+        info!(
+            "Starting {} batch analysis of {} channels for user {}",
+            analysis_type,
+            channels.len(),
+            user.id
+        );
+        tokio::spawn(crate::batch::run_batch(
+            ctx.clone(),
+            chat_id,
+            user,
+            channels,
+            analysis_type,
+            lang,
+        ));
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// stores the forwards the user collected as their corpus and asks for the analysis type
+    async fn handle_invite_consent_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        // the invite was remembered when the consent was asked; a newer request replaces it
+        let channel_name = ctx
+            .user_sessions
+            .get(telegram_user_id)
+            .await
+            .and_then(|session| session.channel_name)
+            .filter(|name| invite_hash(name).is_some());
+        let Some(channel_name) = channel_name else {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.invite_expired())
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        };
+
+        // the consent can only be given once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+
+        TelegramBot::offer_analysis(
+            ctx.clone(),
+            chat_id,
+            Some(&query.from),
+            channel_name,
+            None,
+            None,
+            lang,
+        )
+        .await?;
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_self_done_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        let collection = ctx
+            .user_sessions
+            .get(telegram_user_id)
+            .await
+            .and_then(|session| session.self_collection);
+        let Some(collection) = collection else {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.self_analysis_expired())
+                .await?;
+            return Ok(());
+        };
+        // too few to analyze yet, the user can keep forwarding
+        if collection.messages.len() < MIN_SELF_MESSAGES {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.self_analysis_too_few(collection.messages.len(), MIN_SELF_MESSAGES))
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        }
+
+        let corpus_name = self_corpus_name(telegram_user_id);
+        let saved = ctx
+            .analysis_workers
+            .cache
+            .save_channel_messages(
+                &corpus_name,
+                &collection.messages,
+                None,
+                0,
+                CorpusKind::Channel,
+            )
+            .await;
+        if let Err(e) = saved {
+            error!(
+                "Failed to store forwarded messages of user {}: {}",
+                telegram_user_id, e
+            );
+            ctx.bot
+                .send_message(chat_id, lang.error_processing_request())
+                .await?;
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+        ctx.user_sessions.remove(telegram_user_id).await;
+
+        info!(
+            "User {} collected {} messages for a self-analysis ({} skipped)",
+            telegram_user_id,
+            collection.messages.len(),
+            collection.skipped
+        );
+        ctx.bot
+            .send_message(
+                chat_id,
+                lang.self_analysis_select_type(
+                    collection.messages.len(),
+                    collection.skipped,
+                    ctx.limits().depth_credits(AnalysisDepth::Small),
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(Self::create_self_analysis_keyboard(lang))
+            .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// analyzes the forwards the user stored last; the corpus follows from who pressed
+    /// the button, so nobody can analyze someone else's messages
+    async fn handle_self_analysis_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_type: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let telegram_user_id = query.from.id.0 as i64;
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                telegram_user_id,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_check_credits())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        // forwards are stored once, so they are always analyzed at the quick depth
+        Self::start_analysis(
+            ctx.clone(),
+            Self::get_chat_id(message),
+            user,
+            &self_corpus_name(telegram_user_id),
+            analysis_type,
+            AnalysisDepth::Small,
+            lang,
+        )
+        .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// starts keeping the user's group messages; from then on /groupprofile shows them
+    async fn handle_cross_group_consent_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        if let Err(e) = ctx.cross_group.consent(telegram_user_id).await {
+            error!(
+                "Failed to store cross-group consent of user {}: {}",
+                telegram_user_id, e
+            );
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.error_processing_request())
+                .await?;
+            return Ok(());
+        }
+
+        // the consent can only be given once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+        ctx.bot
+            .send_message(chat_id, lang.cross_group_consented(MIN_CROSS_GROUP_MESSAGES))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// withdraws the consent and deletes the kept group messages
+    async fn handle_cross_group_revoke_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        let deleted = match ctx.cross_group.revoke(telegram_user_id).await {
+            Ok(deleted) => deleted,
+            Err(e) => {
+                error!(
+                    "Failed to withdraw cross-group consent of user {}: {}",
+                    telegram_user_id, e
+                );
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.error_processing_request())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+        ctx.bot
+            .send_message(chat_id, lang.cross_group_revoked(deleted))
+            .await?;
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// deletes the account of the user who confirmed /delete_account
+    async fn handle_delete_account_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        if let Err(e) = ctx.privacy.delete_account(telegram_user_id).await {
+            error!(
+                "Failed to delete the account of user {}: {}",
+                telegram_user_id, e
+            );
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.error_processing_request())
+                .await?;
+            return Ok(());
+        }
+        ctx.user_sessions.remove(telegram_user_id).await;
+        ctx.language_overrides.forget(telegram_user_id);
+
+        // the confirmation can only be used once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+        ctx.bot
+            .send_message(chat_id, lang.delete_account_done())
+            .await?;
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// grants the free trial of the user who pressed "I'm human", unless look-alike signups
+    /// used up this hour's trials
+    async fn handle_verify_human_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        let outcome = match ctx
+            .user_manager
+            .claim_free_trial(telegram_user_id, ctx.limits().free_trials_per_hour)
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                error!(
+                    "Failed to claim the free trial of user {}: {}",
+                    telegram_user_id, e
+                );
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.error_processing_request())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        // the trial can only be claimed once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+        match outcome {
+            Some(TrialOutcome::Granted { credits }) => {
+                ctx.bot
+                    .send_message(chat_id, lang.trial_granted(credits))
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+            }
+            Some(TrialOutcome::Withheld) => {
+                ctx.bot
+                    .send_message(chat_id, lang.trial_withheld())
+                    .reply_markup(Self::create_payment_keyboard(
+                        &ctx.packages.active().await,
+                        lang,
+                    ))
+                    .await?;
+            }
+            None => {}
+        }
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// looks for the proof of the user's claim and makes them the channel's owner if it's there
+    async fn handle_claim_verify_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        claim_id: i32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        let mut claim = match ctx
+            .channel_claims
+            .get_claim(claim_id, telegram_user_id)
+            .await
+        {
+            Ok(Some(claim)) => claim,
+            result => {
+                if let Err(e) = result {
+                    error!("Failed to load channel claim {}: {}", claim_id, e);
+                }
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.error_processing_request())
+                    .await?;
+                return Ok(());
+            }
+        };
+        let channel = MessageFormatter::escape_html(&claim.channel_name);
+
+        if !claim.verified {
+            let Some(method) = channel_claims::find_proof(&ctx, &claim).await else {
+                ctx.bot
+                    .send_message(chat_id, lang.claim_not_verified(&channel, &claim.code))
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            };
+            match ctx.channel_claims.mark_verified(claim.id, method).await {
+                Ok(true) => claim.verified = true,
+                Ok(false) => {
+                    ctx.bot
+                        .send_message(chat_id, lang.claim_taken(&channel))
+                        .parse_mode(ParseMode::Html)
+                        .await?;
+                    ctx.bot.answer_callback_query(&query.id).await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!("Failed to verify channel claim {}: {}", claim.id, e);
+                    ctx.bot
+                        .answer_callback_query(&query.id)
+                        .text(lang.error_processing_request())
+                        .await?;
+                    return Ok(());
+                }
+            }
+        }
+
+        // the claim can only be verified once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+        channel_claims::send_claim_status(&ctx, chat_id, &claim, lang).await?;
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// switches the opt-out of the verified owner's channel and shows the updated choice
+    async fn handle_claim_opt_out_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        claim_id: i32,
+        opted_out: bool,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        let claim = match ctx
+            .channel_claims
+            .get_claim(claim_id, telegram_user_id)
+            .await
+        {
+            Ok(Some(claim)) if claim.verified => claim,
+            result => {
+                if let Err(e) = result {
+                    error!("Failed to load channel claim {}: {}", claim_id, e);
+                }
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.error_processing_request())
+                    .await?;
+                return Ok(());
+            }
+        };
+        if let Err(e) = channel_claims::set_opted_out(&ctx, &claim, opted_out).await {
+            error!(
+                "Failed to update the opt-out of {} by user {}: {}",
+                claim.channel_name, telegram_user_id, e
+            );
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.error_processing_request())
+                .await?;
+            return Ok(());
+        }
+
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+        let claim = ChannelClaim { opted_out, ..claim };
+        channel_claims::send_claim_status(&ctx, chat_id, &claim, lang).await?;
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// analyzes the group messages kept for the user who pressed the button, so nobody can
+    /// analyze someone else's; they are stored as a corpus right before
+    async fn handle_cross_group_analysis_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_type: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let telegram_user_id = query.from.id.0 as i64;
+        let messages = match ctx.cross_group.messages(telegram_user_id).await {
+            Ok(messages) => messages,
+            Err(e) => {
+                error!(
+                    "Failed to load group messages of user {}: {}",
+                    telegram_user_id, e
+                );
+                ctx.bot
+                    .send_message(chat_id, lang.error_processing_request())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+        // the consent may have been withdrawn since the keyboard was sent
+        if messages.len() < MIN_CROSS_GROUP_MESSAGES {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.cross_group_too_few(messages.len(), MIN_CROSS_GROUP_MESSAGES))
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        }
+
+        let corpus_name = cross_group_corpus_name(telegram_user_id);
+        let saved = ctx
+            .analysis_workers
+            .cache
+            .save_channel_messages(
+                &corpus_name,
+                &cross_group::corpus_messages(&messages),
+                None,
+                0,
+                CorpusKind::CrossGroup,
+            )
+            .await;
+        if let Err(e) = saved {
+            error!(
+                "Failed to store the cross-group corpus of user {}: {}",
+                telegram_user_id, e
+            );
+            ctx.bot
+                .send_message(chat_id, lang.error_processing_request())
+                .await?;
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                telegram_user_id,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_check_credits())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        info!(
+            "User {} requested a cross-group {} analysis of {} messages",
+            telegram_user_id,
+            analysis_type,
+            messages.len()
+        );
+        Self::start_analysis(
+            ctx.clone(),
+            chat_id,
+            user,
+            &corpus_name,
+            analysis_type,
+            CROSS_GROUP_DEPTH,
+            lang,
+        )
+        .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// records the user's consent to post their analysis to the showcase channel
+    async fn handle_showcase_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_id: i32,
+        anonymous: bool,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(showcase) = &ctx.showcase else {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.showcase_unavailable())
+                .await?;
+            return Ok(());
+        };
+        let telegram_user_id = query.from.id.0 as i64;
+        let text = match showcase
+            .consent(analysis_id, telegram_user_id, anonymous)
+            .await
+        {
+            Ok(true) => {
+                info!(
+                    "User {} agreed to showcase analysis {} (anonymous: {})",
+                    telegram_user_id, analysis_id, anonymous
+                );
+                // the choice is final
+                let _ = ctx
+                    .bot
+                    .edit_message_reply_markup(Self::get_chat_id(message), message.id())
+                    .await;
+                lang.showcase_queued()
+            }
+            Ok(false) => lang.showcase_unavailable(),
+            Err(e) => {
+                error!(
+                    "Failed to record showcase consent for analysis {}: {}",
+                    analysis_id, e
+                );
+                lang.error_processing_request()
+            }
+        };
+
+        ctx.bot.answer_callback_query(&query.id).text(text).await?;
+        Ok(())
+    }
+
+    async fn handle_feedback_callback(
+        ctx: BotContext,
+        query: &CallbackQuery,
+        analysis_id: i32,
+        positive: bool,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let telegram_user_id = query.from.id.0 as i64;
+        let vote = if positive { Vote::Up } else { Vote::Down };
+        let text = match ctx
+            .feedback
+            .record(analysis_id, telegram_user_id, vote)
+            .await
+        {
+            Ok(true) => {
+                info!(
+                    "User {} rated analysis {}: {:?}",
+                    telegram_user_id, analysis_id, vote
+                );
+                lang.feedback_thanks()
+            }
+            Ok(false) => lang.feedback_unavailable(),
+            Err(e) => {
+                error!(
+                    "Failed to record feedback on analysis {}: {}",
+                    analysis_id, e
+                );
+                lang.error_processing_request()
+            }
+        };
+
+        ctx.bot.answer_callback_query(&query.id).text(text).await?;
+        Ok(())
+    }
+
+    /// continues a group analysis command sent in a forum topic once a group admin picked
+    /// the whole group or the topic
+    async fn handle_group_scope_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_type: Option<String>,
+        thread_id: Option<i32>,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let Some(username) = message.chat().username() else {
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        };
+        let channel_name = format!("@{}", username);
+
+        // the keyboard is visible to the whole group, only its admins may pick
+        let member = ctx.bot.get_chat_member(chat_id, query.from.id).await?;
+        if !member.is_privileged() {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.analyze_group_admins_only())
+                .await?;
+            return Ok(());
+        }
+
+        // the name was remembered when the keyboard was sent; after a restart only the id is left
+        let telegram_user_id = query.from.id.0 as i64;
+        let remembered = ctx
+            .user_sessions
+            .get(telegram_user_id)
+            .await
+            .and_then(|session| session.topic);
+        let topic = thread_id.map(|thread_id| ForumTopic {
+            thread_id,
+            name: remembered
+                .filter(|topic| topic.thread_id == thread_id)
+                .map_or_else(|| format!("#{}", thread_id), |topic| topic.name),
         });
+
+        // the scope can only be picked once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(chat_id, message.id())
+            .await;
+
+        let Some(analysis_type) = analysis_type else {
+            TelegramBot::offer_analysis(
+                ctx.clone(),
+                chat_id,
+                Some(&query.from),
+                channel_name,
+                topic,
+                None,
+                lang,
+            )
+            .await?;
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        };
+
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                telegram_user_id,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_processing_request())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        // start_analysis picks the topic up from the session
+        ctx.user_sessions
+            .insert(
+                telegram_user_id,
+                UserSession {
+                    channel_name: Some(channel_name.clone()),
+                    topic,
+                    ..Default::default()
+                },
+            )
+            .await;
+        Self::start_analysis(
+            ctx.clone(),
+            chat_id,
+            user,
+            &channel_name,
+            &analysis_type,
+            AnalysisDepth::default(),
+            lang,
+        )
+        .await?;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// refunds a partial analysis and runs it again, so the regeneration is free
+    async fn handle_resend_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_id: i32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        // only the requester's own analyses are found
+        let messages = match ctx
+            .user_manager
+            .get_rendered_result(user.id, Some(analysis_id))
+            .await
+        {
+            Ok(Some((_, messages))) => messages,
+            Ok(None) => {
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.resend_unavailable())
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!(
+                    "Failed to get rendered result of analysis {}: {}",
+                    analysis_id, e
+                );
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        info!("Resending analysis {} to user {}", analysis_id, user.id);
+        TelegramBot::send_rendered_result(
+            &ctx.bot,
+            Self::get_chat_id(message),
+            &messages,
+            analysis_id,
+        )
+        .await
+    }
+
+    /// reads the opening of an analysis out as a voice message; each result is recorded once
+    /// and sent again by its telegram file id afterwards
+    async fn handle_listen_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_id: i32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let Some(voice) = ctx.voice.clone() else {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.voice_unavailable())
+                .await?;
+            return Ok(());
+        };
+
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        // only the requester's own completed analyses are found
+        let analysis = match ctx.user_manager.get_analysis(analysis_id, user.id).await {
+            Ok(analysis) => analysis.filter(|analysis| analysis.status == "completed"),
+            Err(e) => {
+                error!("Failed to get analysis {}: {}", analysis_id, e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+        let Some((cache_key, analysis_type, channel_name)) = analysis.and_then(|analysis| {
+            Some((
+                analysis.cache_key?,
+                analysis.analysis_type?,
+                analysis.channel_name,
+            ))
+        }) else {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.voice_unavailable())
+                .await?;
+            return Ok(());
+        };
+        let caption = format!(
+            "{} {}",
+            lang.analysis_type_header(&analysis_type).trim_end(),
+            ResultPresenter::target_label(&channel_name, lang)
+        );
+
+        match voice.cached_file_id(&cache_key, &analysis_type).await {
+            Ok(Some(file_id)) => {
+                ctx.bot.answer_callback_query(&query.id).await?;
+                ctx.bot
+                    .send_voice(chat_id, InputFile::file_id(file_id))
+                    .caption(caption)
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+                return Ok(());
+            }
+            Ok(None) => {}
+            // recorded again below
+            Err(e) => error!(
+                "Failed to read the voice summary of analysis {}: {}",
+                analysis_id, e
+            ),
+        }
+
+        let script = ctx
+            .analysis_workers
+            .cache
+            .load_llm_result(&cache_key)
+            .await
+            .and_then(|result| {
+                ResultPresenter::content_for(&result, &analysis_type).map(voice_script)
+            });
+        let Some(script) = script else {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.voice_unavailable())
+                .await?;
+            return Ok(());
+        };
+        if !ctx.llm_budget.allows_llm_call().await {
+            ctx.bot.answer_callback_query(&query.id).await?;
+            ctx.bot
+                .send_message(chat_id, lang.error_llm_budget_exceeded())
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        }
+
+        ctx.bot
+            .answer_callback_query(&query.id)
+            .text(lang.voice_recording())
+            .await?;
+        let _ = ctx
+            .bot
+            .send_chat_action(chat_id, ChatAction::RecordVoice)
+            .await;
+        let audio = match voice.record(&script).await {
+            Ok(audio) => audio,
+            Err(e) => {
+                error!(
+                    "Failed to record a voice summary of analysis {}: {}",
+                    analysis_id, e
+                );
+                ctx.bot.send_message(chat_id, lang.voice_failed()).await?;
+                return Ok(());
+            }
+        };
+
+        info!("Sending a voice summary of analysis {}", analysis_id);
+        let sent = ctx
+            .bot
+            .send_voice(chat_id, InputFile::memory(audio).file_name("summary.ogg"))
+            .caption(caption)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        if let Some(file) = sent.voice().map(|voice| &voice.file) {
+            if let Err(e) = voice
+                .store_file_id(&cache_key, &analysis_type, &file.id)
+                .await
+            {
+                error!(
+                    "Failed to store the voice summary of analysis {}: {}",
+                    analysis_id, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// queues the same analysis again for a model of the other tier, at a reduced price;
+    /// each analysis gets one second opinion
+    async fn handle_second_opinion_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_id: i32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let chat_id = Self::get_chat_id(message);
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        // only the requester's own completed analyses are found
+        let analysis = match ctx.user_manager.get_analysis(analysis_id, user.id).await {
+            Ok(analysis) => analysis.filter(|analysis| analysis.status == "completed"),
+            Err(e) => {
+                error!("Failed to get analysis {}: {}", analysis_id, e);
+                ctx.bot
+                    .send_message(chat_id, lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+        let Some((analysis_type, depth)) = analysis.and_then(|analysis| {
+            Some((
+                analysis.analysis_type?,
+                AnalysisDepth::from_code(&analysis.depth).unwrap_or_default(),
+            ))
+        }) else {
+            ctx.bot
+                .answer_callback_query(&query.id)
+                .text(lang.second_opinion_unavailable())
+                .await?;
+            return Ok(());
+        };
+
+        let credits_required = ctx.limits().second_opinion_credits(depth);
+        if user.analysis_credits < credits_required {
+            ctx.bot.answer_callback_query(&query.id).await?;
+            ctx.bot
+                .send_message(chat_id, lang.no_credits_short())
+                .reply_markup(Self::create_payment_keyboard(
+                    &ctx.packages.active().await,
+                    lang,
+                ))
+                .await?;
+            return Ok(());
+        }
+
+        let second_opinion_id = match ctx
+            .user_manager
+            .create_second_opinion(
+                analysis_id,
+                user.id,
+                query.from.language_code.as_deref(),
+                ctx.limits().max_running_analyses,
+            )
+            .await
+        {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.second_opinion_unavailable())
+                    .await?;
+                return Ok(());
+            }
+            Err(UserManagerError::TooManyRunning(_)) => {
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.analysis_already_running())
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!(
+                    "Failed to start a second opinion on analysis {}: {}",
+                    analysis_id, e
+                );
+                ctx.bot
+                    .send_message(chat_id, lang.error_start_analysis())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        Self::queue_analysis(
+            &ctx,
+            chat_id,
+            &analysis_type,
+            second_opinion_id,
+            // the first analysis was already confirmed or had enough text
+            true,
+            lang,
+        )
+        .await;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    async fn handle_regenerate_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_id: i32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        let regeneration = match ctx
+            .user_manager
+            .claim_partial_regeneration(analysis_id, user.id)
+            .await
+        {
+            Ok(Some(regeneration)) => regeneration,
+            Ok(None) => {
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.regeneration_unavailable())
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!(
+                    "Failed to claim regeneration of analysis {}: {}",
+                    analysis_id, e
+                );
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_start_analysis())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        info!(
+            "User {} regenerates partial analysis {} ({} credits refunded, balance {})",
+            user.id, analysis_id, regeneration.credits_refunded, regeneration.balance
+        );
+
+        // the offer can only be used once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(Self::get_chat_id(message), message.id())
+            .await;
+
+        let depth = AnalysisDepth::from_code(&regeneration.depth).unwrap_or_default();
+        let new_analysis_id = match ctx
+            .user_manager
+            .create_pending_analysis(
+                user.id,
+                &regeneration.channel_name,
+                &regeneration.analysis_type,
+                depth.as_str(),
+                query.from.language_code.as_deref(),
+                regeneration.focus.as_deref(),
+                AnalysisSource::Bot,
+                Some(ctx.limits().max_running_analyses),
+            )
+            .await
+        {
+            Ok(id) => id,
+            Err(UserManagerError::TooManyRunning(_)) => {
+                // the refunded credits stay with the user, who can start the analysis again
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.analysis_already_running())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                // the refunded credits stay with the user, who can start the analysis again
+                error!(
+                    "Failed to start regeneration of analysis {}: {}",
+                    analysis_id, e
+                );
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_start_analysis())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+        Self::store_topic(&ctx, new_analysis_id, regeneration.topic.as_ref()).await;
+
+        Self::queue_analysis(
+            &ctx,
+            Self::get_chat_id(message),
+            &regeneration.analysis_type,
+            new_analysis_id,
+            // the first run was already confirmed or had enough text
+            true,
+            lang,
+        )
+        .await;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// runs an analysis stopped for low text coverage again, now that the user confirmed it
+    async fn handle_low_text_confirm_callback(
+        ctx: BotContext,
+        message: &MaybeInaccessibleMessage,
+        query: &CallbackQuery,
+        analysis_id: i32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                query.from.id.0 as i64,
+                query.from.username.as_deref(),
+                Some(query.from.first_name.as_str()),
+                query.from.last_name.as_deref(),
+                None,
+                query.from.language_code.as_deref(),
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user: {}", e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_account_access())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        let pending = match ctx
+            .user_manager
+            .reopen_failed_analysis(analysis_id, user.id, ctx.limits().max_running_analyses)
+            .await
+        {
+            Ok(Some(pending)) => pending,
+            Ok(None) => {
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+            Err(UserManagerError::TooManyRunning(_)) => {
+                ctx.bot
+                    .answer_callback_query(&query.id)
+                    .text(lang.analysis_already_running())
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Failed to reopen analysis {}: {}", analysis_id, e);
+                ctx.bot
+                    .send_message(Self::get_chat_id(message), lang.error_start_analysis())
+                    .await?;
+                ctx.bot.answer_callback_query(&query.id).await?;
+                return Ok(());
+            }
+        };
+
+        // charged on completion, so the balance is checked up front as for a new analysis;
+        // the button stays for after a top-up
+        let depth = AnalysisDepth::from_code(&pending.depth).unwrap_or_default();
+        let credits_required = ctx.limits().depth_credits(depth);
+        if user.analysis_credits < credits_required {
+            if let Err(e) = ctx.user_manager.mark_analysis_failed(analysis_id).await {
+                error!("Failed to mark analysis {} as failed: {}", analysis_id, e);
+            }
+            ctx.bot
+                .send_message(
+                    Self::get_chat_id(message),
+                    lang.not_enough_credits_for_depth(credits_required, user.analysis_credits),
+                )
+                .reply_markup(Self::create_payment_keyboard(
+                    &ctx.packages.active().await,
+                    lang,
+                ))
+                .await?;
+            ctx.bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+
+        // the confirmation can only be used once
+        let _ = ctx
+            .bot
+            .edit_message_reply_markup(Self::get_chat_id(message), message.id())
+            .await;
+
+        info!(
+            "User {} confirmed analysis {} of low text channel {}",
+            user.id, analysis_id, pending.channel_name
+        );
+        Self::queue_analysis(
+            &ctx,
+            Self::get_chat_id(message),
+            &pending.analysis_type,
+            analysis_id,
+            true,
+            lang,
+        )
+        .await;
+
+        ctx.bot.answer_callback_query(&query.id).await?;
+        Ok(())
+    }
+
+    /// records the topic a group analysis is limited to, so recovery and regeneration keep it
+    async fn store_topic(ctx: &BotContext, analysis_id: i32, topic: Option<&ForumTopic>) {
+        let Some(topic) = topic else {
+            return;
+        };
+        if let Err(e) = ctx
+            .user_manager
+            .set_analysis_topic(analysis_id, topic)
+            .await
+        {
+            warn!(
+                "Failed to store topic {} of analysis {}: {}",
+                topic.thread_id, analysis_id, e
+            );
+        }
+    }
+
+    /// queues the analysis for the bot's job runner and tells the user it started;
+    /// the result goes to `chat_id` in `lang` even if the bot restarts meanwhile
+    async fn queue_analysis(
+        ctx: &BotContext,
+        chat_id: ChatId,
+        analysis_type: &str,
+        analysis_id: i32,
+        allow_low_text: bool,
+        lang: Lang,
+    ) {
+        if let Err(e) = ctx
+            .job_queue
+            .enqueue(
+                analysis_id,
+                Some(chat_id.0),
+                Some(lang.code()),
+                allow_low_text,
+            )
+            .await
+        {
+            error!("Failed to queue analysis {}: {}", analysis_id, e);
+            if let Err(mark_err) = ctx.user_manager.mark_analysis_failed(analysis_id).await {
+                error!(
+                    "Failed to mark analysis {} as failed: {}",
+                    analysis_id, mark_err
+                );
+            }
+            let _ = ctx
+                .bot
+                .send_message(chat_id, lang.error_start_analysis())
+                .await;
+            return;
+        }
+
+        if let Err(e) = ctx
+            .bot
+            .send_message(chat_id, lang.analysis_in_progress(analysis_type))
+            .await
+        {
+            warn!(
+                "Failed to announce analysis {} to {}: {}",
+                analysis_id, chat_id, e
+            );
+        }
     }
 }
@@ -1,15 +1,34 @@
-use log::{error, info};
+use log::{error, info, warn};
+use std::time::Duration;
 use teloxide::prelude::*;
-use teloxide::types::{ChatId, ParseMode};
-
-use crate::bot::{BotContext, Command};
-use crate::handlers::{
-    payment_handler::{
-        BULK_PACKAGE_AMOUNT, BULK_PACKAGE_PRICE, SINGLE_PACKAGE_AMOUNT, SINGLE_PACKAGE_PRICE,
-    },
-    CallbackHandler, PaymentHandler,
-};
+use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ParseMode};
+use url::Url;
+
+use crate::admin::{AdminAction, AdminError, AdminRole, AuditOutcome};
+use crate::analysis::{AnalysisDepth, ForumTopic};
+use crate::backend_config::BackendPolicy;
+use crate::blocklist::BlockTarget;
+use crate::bot::{BotContext, Command, TelegramBot};
+use crate::channel_claims;
+use crate::cross_group;
+use crate::data_export;
+use crate::feedback::{Satisfaction, DEFAULT_REPORT_DAYS};
+use crate::flagged_outputs::FLAGGED_LIST_LIMIT;
+use crate::free_trials::{ClusterStatus, CLUSTER_LIST_LIMIT};
+use crate::handlers::{callback_data::ANALYSIS_TYPES, CallbackHandler, PaymentHandler};
+use crate::llm_budget::DEFAULT_COST_REPORT_DAYS;
 use crate::localization::Lang;
+use crate::packages::PackagesCommand;
+use crate::privacy;
+use crate::receipts::{self, RevenuePeriod};
+use crate::referral_flags::{FlagStatus, ReviewedFlag, FLAG_LIST_LIMIT};
+use crate::referrals::{self, ReferralRules, LEADERBOARD_SIZE, REFERRAL_RULE_NAMES};
+use crate::roast_battle;
+use crate::self_analysis;
+use crate::subscriptions::{self, SubscriptionStatus};
+use crate::user_sessions::UserSession;
+use crate::utils::{MessageFormatter, StatsCard};
+use crate::warmup::{WarmupConfig, DEFAULT_TOP_CHANNELS};
 
 #[derive(Debug)]
 struct UserInfo<'a> {
@@ -20,95 +39,1967 @@ struct UserInfo<'a> {
     language_code: Option<&'a str>,
 }
 
-pub struct CommandHandler;
+pub struct CommandHandler;
+
+impl CommandHandler {
+    pub async fn handle_command(ctx: BotContext, msg: Message, cmd: Command) -> ResponseResult<()> {
+        let lang = TelegramBot::user_lang(&ctx, msg.from.as_ref()).await;
+
+        if !TelegramBot::admit_message(&ctx, &msg, lang).await? {
+            return Ok(());
+        }
+
+        match cmd {
+            Command::Start => {
+                Self::handle_start_command(ctx, msg, lang).await?;
+            }
+            Command::Buy => {
+                Self::handle_buy_command(ctx, msg, None, lang).await?;
+            }
+            // the shortcuts of the original packages, kept working for old links
+            Command::Buy1 => {
+                Self::handle_buy_command(ctx, msg, Some(1), lang).await?;
+            }
+            Command::Buy10 => {
+                Self::handle_buy_command(ctx, msg, Some(10), lang).await?;
+            }
+            Command::Subscribe => {
+                Self::handle_subscribe_command(ctx, msg, lang).await?;
+            }
+            Command::Settings => {
+                Self::handle_settings_command(ctx, msg, lang).await?;
+            }
+            Command::Balance => {
+                Self::handle_balance_command(ctx, msg, lang).await?;
+            }
+            Command::Language => {
+                Self::handle_language_command(ctx, msg, lang).await?;
+            }
+            Command::WhatsNew => {
+                Self::handle_whats_new_command(ctx, msg, lang).await?;
+            }
+            Command::ApiKey => {
+                Self::handle_api_key_command(ctx, msg, lang).await?;
+            }
+            Command::Resend => {
+                Self::handle_resend_command(ctx, msg, lang).await?;
+            }
+            Command::Top => {
+                Self::handle_top_command(ctx, msg, lang).await?;
+            }
+            Command::Referrals => {
+                Self::handle_referrals_command(ctx, msg, lang).await?;
+            }
+            Command::AnalyzeGroup => {
+                Self::handle_analyze_group_command(ctx, msg, lang).await?;
+            }
+            Command::Analyze(args) => {
+                Self::handle_analyze_command(ctx, msg, &args, lang).await?;
+            }
+            Command::AnalyzeMe => {
+                self_analysis::handle_self_command(ctx, &msg, lang).await?;
+            }
+            Command::GroupProfile => {
+                cross_group::handle_group_profile_command(ctx, &msg, lang).await?;
+            }
+            Command::RoastBattle(args) => {
+                roast_battle::handle_roast_battle_command(ctx, &msg, &args, lang).await?;
+            }
+            Command::Claim(args) => {
+                channel_claims::handle_claim_command(ctx, &msg, &args, lang).await?;
+            }
+            Command::ExportMyData => {
+                data_export::handle_export_command(ctx, &msg, lang).await?;
+            }
+            Command::DeleteAccount => {
+                privacy::handle_delete_account_command(ctx, &msg, lang).await?;
+            }
+            Command::Receipts => {
+                receipts::handle_receipts_command(ctx, &msg, lang).await?;
+            }
+            Command::Refund(args) => {
+                Self::handle_refund_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Role(args) => {
+                Self::handle_role_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Audit(args) => {
+                Self::handle_audit_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Announce(args) => {
+                Self::handle_announce_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Backend(args) => {
+                Self::handle_backend_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Reload => {
+                Self::handle_reload_command(ctx, msg, lang).await?;
+            }
+            Command::Requeue(args) => {
+                Self::handle_requeue_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Feedback(args) => {
+                Self::handle_feedback_command(ctx, msg, &args, lang).await?;
+            }
+            Command::LlmCosts(args) => {
+                Self::handle_llm_costs_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Revenue(args) => {
+                Self::handle_revenue_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Packages(args) => {
+                Self::handle_packages_command(ctx, msg, &args, lang).await?;
+            }
+            Command::ReferralConfig(args) => {
+                Self::handle_referral_config_command(ctx, msg, &args, lang).await?;
+            }
+            Command::ReferralFlags(args) => {
+                Self::handle_referral_flags_command(ctx, msg, &args, lang).await?;
+            }
+            Command::TrialClusters(args) => {
+                Self::handle_trial_clusters_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Warmup(args) => {
+                Self::handle_warmup_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Flagged(args) => {
+                Self::handle_flagged_command(ctx, msg, &args, lang).await?;
+            }
+            Command::Blocklist(args) => {
+                Self::handle_blocklist_command(ctx, msg, &args, lang).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_start_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        // parse referral code from message text
+        let referrer_user_id = Self::parse_referral_code(&ctx, &msg).await;
+
+        // get user info from telegram message
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        // get or create user to check credit balance
+        let (user, maybe_reward_info) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                referrer_user_id,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok((user, reward_info)) => (user, reward_info),
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        // send referral milestone notification if applicable
+        Self::send_referral_notifications(&ctx, maybe_reward_info, lang).await;
+
+        // send appropriate welcome message based on user's credit balance
+        if user.analysis_credits <= 0 && ctx.free_trials.is_pending(user.id).await {
+            ctx.bot
+                .send_message(msg.chat.id, lang.welcome_verify_human())
+                .parse_mode(ParseMode::Html)
+                .reply_markup(CallbackHandler::create_verify_human_keyboard(lang))
+                .await?;
+        } else if user.analysis_credits <= 0 {
+            Self::send_no_credits_welcome(&ctx, &msg, &user, lang).await?;
+        } else {
+            Self::send_credits_available_welcome(&ctx, &msg, &user, lang).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_settings_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        let (user, _) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let settings = match ctx.user_manager.get_model_tier(user.id).await {
+            Ok(tier) => ctx
+                .user_manager
+                .get_web_pages_enabled(user.id)
+                .await
+                .map(|web_pages| (tier, web_pages)),
+            Err(e) => Err(e),
+        };
+        let (tier, web_pages) = match settings {
+            Ok(settings) => settings,
+            Err(e) => {
+                error!("Failed to get settings for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        ctx.bot
+            .send_message(msg.chat.id, lang.settings_model_tier(tier, web_pages))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_model_tier_keyboard(
+                tier, web_pages, lang,
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn handle_language_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        let (user, _) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let language = match ctx.user_manager.get_output_language(user.id).await {
+            Ok(language) => language,
+            Err(e) => {
+                error!("Failed to get output language for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let language_override = match ctx
+            .user_manager
+            .get_language_override(user_info.telegram_user_id)
+            .await
+        {
+            Ok(language_override) => language_override,
+            Err(e) => {
+                error!(
+                    "Failed to get language override for user {}: {}",
+                    user.id, e
+                );
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+        let interface_language = language_override.map(|code| Lang::from_code(Some(&code)));
+
+        ctx.bot
+            .send_message(msg.chat.id, lang.settings_ui_language(interface_language))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_interface_language_keyboard(
+                interface_language,
+                lang,
+            ))
+            .await?;
+
+        ctx.bot
+            .send_message(msg.chat.id, lang.settings_output_language(language))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_output_language_keyboard(
+                language, lang,
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn handle_whats_new_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        let (user, _) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let (text, keyboard) = match CallbackHandler::build_whats_new(&ctx, user.id, lang).await {
+            Ok(page) => page,
+            Err(e) => {
+                error!("Failed to load changelog for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        ctx.bot
+            .send_message(msg.chat.id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn handle_top_command(ctx: BotContext, msg: Message, lang: Lang) -> ResponseResult<()> {
+        let reply = match ctx
+            .channel_stats
+            .top_this_week(ctx.limits().top_channels)
+            .await
+        {
+            Ok(channels) => {
+                let lines = channels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, channel)| {
+                        lang.top_channel_entry(
+                            i + 1,
+                            &MessageFormatter::escape_html(&channel.channel_name),
+                            channel.analyses,
+                            channel.total_analyses,
+                            channel.average_score,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                lang.top_channels(&lines)
+            }
+            Err(e) => {
+                error!("Failed to load channel leaderboard: {}", e);
+                lang.error_system().to_string()
+            }
+        };
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_referrals_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        let (user, _) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let (stats, leaderboard) = match tokio::try_join!(
+            ctx.referrals.stats(user.id),
+            ctx.referrals.weekly_leaderboard(LEADERBOARD_SIZE)
+        ) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                error!("Failed to load referral stats for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_system())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let lines = leaderboard
+            .iter()
+            .map(|entry| {
+                lang.referrals_leaderboard_entry(
+                    entry.rank,
+                    &MessageFormatter::escape_html(&entry.name),
+                    entry.referrals,
+                    entry.user_id == user.id,
+                )
+            })
+            .collect::<Vec<_>>();
+        let link = referrals::referral_link(user.id);
+        let reply = lang.referrals_stats(
+            stats.referrals,
+            stats.paid_referrals,
+            stats.this_week,
+            stats.rank,
+            &link,
+            &lang.referrals_leaderboard(&lines),
+        );
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+
+        let card = StatsCard {
+            referrals: stats.referrals,
+            paid_referrals: stats.paid_referrals,
+            this_week: stats.this_week,
+            rank: stats.rank,
+            link,
+        };
+        match card.render_png() {
+            Ok(png) => {
+                ctx.bot
+                    .send_photo(
+                        msg.chat.id,
+                        InputFile::memory(png).file_name("referrals.png"),
+                    )
+                    .caption(lang.referrals_card_caption())
+                    .await?;
+            }
+            Err(e) => error!("Failed to render referral card for user {}: {}", user.id, e),
+        }
+        Ok(())
+    }
+
+    async fn handle_resend_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        let (user, _) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match ctx.user_manager.get_rendered_result(user.id, None).await {
+            Ok(Some((analysis_id, messages))) => {
+                info!("Resending analysis {} to user {}", analysis_id, user.id);
+                TelegramBot::send_rendered_result(&ctx.bot, msg.chat.id, &messages, analysis_id)
+                    .await?;
+            }
+            Ok(None) => {
+                ctx.bot
+                    .send_message(msg.chat.id, lang.resend_unavailable())
+                    .await?;
+            }
+            Err(e) => {
+                error!(
+                    "Failed to get last rendered result of user {}: {}",
+                    user.id, e
+                );
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_api_key_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        // a key posted to a group could be used by anyone in it
+        if !msg.chat.is_private() {
+            ctx.bot
+                .send_message(msg.chat.id, lang.api_key_private_only())
+                .await?;
+            return Ok(());
+        }
+
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        let (user, _) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        // the previous key keeps working a while so the user's clients can switch over
+        let overlap_hours = ctx.limits().api_key_overlap_hours;
+        let overlap = Duration::from_secs(u64::from(overlap_hours.unsigned_abs()) * 60 * 60);
+        let (key, previous) = match ctx.user_manager.create_api_key(user.id, overlap).await {
+            Ok(issued) => issued,
+            Err(e) => {
+                error!("Failed to create API key for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        ctx.bot
+            .send_message(
+                msg.chat.id,
+                lang.api_key_created(&key, (previous > 0).then_some(overlap_hours)),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_api_key_keyboard(lang))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn handle_balance_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let user_info = Self::extract_user_info_from_message(&msg);
+
+        let (user, _) = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get/create user: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let (text, keyboard) = match CallbackHandler::build_balance_page(&ctx, &user, 0, lang).await
+        {
+            Ok(page) => page,
+            Err(e) => {
+                error!("Failed to load balance history for user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_account_access())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        ctx.bot
+            .send_message(msg.chat.id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard)
+            .await?;
+
+        Ok(())
+    }
+
+    /// returns the sender's telegram id if their admin role allows the action;
+    /// non-admins are ignored so the admin commands stay hidden
+    async fn authorize_admin(
+        ctx: &BotContext,
+        msg: &Message,
+        action: AdminAction,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<Option<i64>> {
+        let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+        match ctx.admin.authorize(telegram_user_id, action, args).await {
+            Ok(_) => Ok(Some(telegram_user_id)),
+            Err(AdminError::NotAdmin(_)) => {
+                warn!(
+                    "Ignoring {} from non-admin user {}",
+                    action.as_str(),
+                    telegram_user_id
+                );
+                Ok(None)
+            }
+            Err(AdminError::Forbidden { role, .. }) => {
+                ctx.bot
+                    .send_message(msg.chat.id, lang.admin_forbidden(role.as_str()))
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+                Ok(None)
+            }
+            Err(e) => {
+                error!(
+                    "Failed to check admin role of user {}: {}",
+                    telegram_user_id, e
+                );
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_system())
+                    .await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// audit logging must not break the command it records
+    async fn audit(
+        ctx: &BotContext,
+        telegram_user_id: i64,
+        action: AdminAction,
+        args: &str,
+        outcome: AuditOutcome,
+    ) {
+        if let Err(e) = ctx
+            .admin
+            .record(telegram_user_id, action, args.trim(), outcome)
+            .await
+        {
+            error!(
+                "Failed to audit {} by admin {}: {}",
+                action.as_str(),
+                telegram_user_id,
+                e
+            );
+        }
+    }
+
+    async fn handle_refund_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(telegram_user_id) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::Refund, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        // expected format: <charge_id> [reason]
+        let (charge_id, reason) = match args.trim().split_once(char::is_whitespace) {
+            Some((charge_id, reason)) => (charge_id, Some(reason.trim())),
+            None => (args.trim(), None),
+        };
+        if charge_id.is_empty() {
+            ctx.bot
+                .send_message(msg.chat.id, lang.refund_usage())
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        }
+
+        let (reply, outcome) = match ctx
+            .payment_handler
+            .refund_payment(ctx.bot.clone(), charge_id, reason, Some(telegram_user_id))
+            .await
+        {
+            Ok((payment, new_balance)) => (
+                lang.refund_issued(
+                    &MessageFormatter::escape_html(charge_id),
+                    payment.stars,
+                    payment.user_id,
+                    new_balance,
+                ),
+                AuditOutcome::Succeeded,
+            ),
+            Err(e) => {
+                error!("Failed to refund payment {}: {}", charge_id, e);
+                (
+                    lang.refund_failed(&MessageFormatter::escape_html(&e.to_string())),
+                    AuditOutcome::Failed,
+                )
+            }
+        };
+        Self::audit(&ctx, telegram_user_id, AdminAction::Refund, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn handle_role_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ManageRoles, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        // expected format: <telegram_user_id> <role|none>
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let parsed = match parts.as_slice() {
+            [target, "none"] => target.parse::<i64>().ok().map(|target| (target, None)),
+            [target, code] => target
+                .parse::<i64>()
+                .ok()
+                .zip(AdminRole::from_code(code).map(Some)),
+            _ => None,
+        };
+        let Some((target, role)) = parsed else {
+            ctx.bot
+                .send_message(msg.chat.id, lang.role_usage())
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        };
+
+        let (reply, outcome) = match ctx.admin.set_role(actor, target, role).await {
+            Ok(()) => (
+                lang.role_updated(target, role.map(|role| role.as_str())),
+                AuditOutcome::Succeeded,
+            ),
+            Err(e) => {
+                error!("Failed to set role of {}: {}", target, e);
+                (
+                    lang.role_update_failed(&MessageFormatter::escape_html(&e.to_string())),
+                    AuditOutcome::Failed,
+                )
+            }
+        };
+        Self::audit(&ctx, actor, AdminAction::ManageRoles, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_audit_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ViewAuditLog, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let limit = args
+            .trim()
+            .parse::<i64>()
+            .unwrap_or(ctx.limits().audit_log_entries)
+            .clamp(1, ctx.limits().max_audit_log_entries);
+        let (reply, outcome) = match ctx.admin.recent_actions(limit).await {
+            Ok(entries) => {
+                let lines = entries
+                    .iter()
+                    .map(|entry| {
+                        lang.audit_entry(
+                            &entry.created_at,
+                            entry.actor_telegram_user_id,
+                            &entry.action,
+                            &MessageFormatter::escape_html(&entry.arguments),
+                            &entry.outcome,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (lang.audit_log(&lines), AuditOutcome::Succeeded)
+            }
+            Err(e) => {
+                error!("Failed to load audit log: {}", e);
+                (lang.error_system().to_string(), AuditOutcome::Failed)
+            }
+        };
+        Self::audit(&ctx, actor, AdminAction::ViewAuditLog, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_announce_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::Announce, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let Ok(entry_id) = args.trim().parse::<i32>() else {
+            ctx.bot
+                .send_message(msg.chat.id, lang.announce_usage())
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        };
+
+        let (reply, outcome) = match ctx.changelog.announce(entry_id).await {
+            Ok(queued) => (
+                lang.announce_queued(entry_id, queued),
+                AuditOutcome::Succeeded,
+            ),
+            Err(e) => {
+                error!("Failed to announce changelog entry {}: {}", entry_id, e);
+                (
+                    lang.announce_failed(&MessageFormatter::escape_html(&e.to_string())),
+                    AuditOutcome::Failed,
+                )
+            }
+        };
+        Self::audit(&ctx, actor, AdminAction::Announce, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// shows the backend policy, or switches it until the next restart
+    async fn handle_backend_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::SetBackend, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let code = args.trim();
+        if code.is_empty() {
+            let current = ctx.analysis_workers.backend_policy();
+            ctx.bot
+                .send_message(msg.chat.id, lang.backend_status(current.as_str()))
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        }
+        let Some(policy) = BackendPolicy::from_code(code) else {
+            ctx.bot
+                .send_message(msg.chat.id, lang.backend_usage())
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        };
+
+        ctx.analysis_workers.set_backend_policy(policy);
+        Self::audit(
+            &ctx,
+            actor,
+            AdminAction::SetBackend,
+            args,
+            AuditOutcome::Succeeded,
+        )
+        .await;
+
+        ctx.bot
+            .send_message(msg.chat.id, lang.backend_updated(policy.as_str()))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// reloads limits, model routing and gemini quotas, as SIGHUP does
+    async fn handle_reload_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ReloadConfig, "", lang).await?
+        else {
+            return Ok(());
+        };
+
+        let (reply, outcome) = match ctx.reload_config().await {
+            Ok(changes) if changes.is_empty() => {
+                (lang.config_unchanged().to_string(), AuditOutcome::Succeeded)
+            }
+            Ok(changes) => (lang.config_reloaded(&changes), AuditOutcome::Succeeded),
+            Err(e) => {
+                error!("Failed to reload the config: {}", e);
+                (
+                    lang.config_reload_failed(&MessageFormatter::escape_html(&e.to_string())),
+                    AuditOutcome::Failed,
+                )
+            }
+        };
+        Self::audit(&ctx, actor, AdminAction::ReloadConfig, "", outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// shows how many messages are dead-lettered, or requeues one of them or all
+    async fn handle_requeue_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::Requeue, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let target = match args.trim() {
+            "" => {
+                let reply = match ctx.message_queue.dead_letter_count().await {
+                    Ok(count) => lang.requeue_status(count),
+                    Err(e) => {
+                        error!("Failed to count dead-lettered messages: {}", e);
+                        lang.requeue_failed(&MessageFormatter::escape_html(&e.to_string()))
+                    }
+                };
+                ctx.bot
+                    .send_message(msg.chat.id, reply)
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+                return Ok(());
+            }
+            "all" => None,
+            id => match id.parse::<i32>() {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    ctx.bot
+                        .send_message(msg.chat.id, lang.requeue_usage())
+                        .parse_mode(ParseMode::Html)
+                        .await?;
+                    return Ok(());
+                }
+            },
+        };
+
+        let (reply, outcome) = match ctx.message_queue.requeue_dead(target).await {
+            Ok(requeued) => (lang.requeue_done(requeued), AuditOutcome::Succeeded),
+            Err(e) => {
+                error!("Failed to requeue dead-lettered messages: {}", e);
+                (
+                    lang.requeue_failed(&MessageFormatter::escape_html(&e.to_string())),
+                    AuditOutcome::Failed,
+                )
+            }
+        };
+        Self::audit(&ctx, actor, AdminAction::Requeue, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// satisfaction rates per analysis type and per model, over the last days given
+    async fn handle_feedback_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ViewFeedback, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let days = args
+            .trim()
+            .parse::<i32>()
+            .unwrap_or(DEFAULT_REPORT_DAYS)
+            .clamp(1, 365);
+        let (reply, outcome) = match ctx.feedback.report(days).await {
+            Ok(report) => {
+                let lines = |rows: &[Satisfaction]| {
+                    rows.iter()
+                        .map(|row| {
+                            lang.feedback_line(
+                                &MessageFormatter::escape_html(&row.key),
+                                row.positive,
+                                row.negative,
+                                row.rate(),
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                };
+                (
+                    lang.feedback_report(
+                        days,
+                        &lines(&report.by_type),
+                        &lines(&report.by_model),
+                        &lines(&report.by_prompt_version),
+                    ),
+                    AuditOutcome::Succeeded,
+                )
+            }
+            Err(e) => {
+                error!("Failed to load feedback report: {}", e);
+                (lang.error_system().to_string(), AuditOutcome::Failed)
+            }
+        };
+        Self::audit(&ctx, actor, AdminAction::ViewFeedback, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// estimated llm spend per day over the last days given, against the daily budget
+    async fn handle_llm_costs_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ViewLlmCosts, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let days = args
+            .trim()
+            .parse::<i32>()
+            .unwrap_or(DEFAULT_COST_REPORT_DAYS)
+            .clamp(1, 365);
+        let (reply, outcome) = match ctx.llm_budget.report(days).await {
+            Ok(report) => {
+                let lines = report
+                    .iter()
+                    .map(|spend| lang.llm_costs_line(spend))
+                    .collect::<Vec<_>>();
+                (
+                    lang.llm_costs_report(days, ctx.llm_budget.daily_budget_usd(), &lines),
+                    AuditOutcome::Succeeded,
+                )
+            }
+            Err(e) => {
+                error!("Failed to load LLM cost report: {}", e);
+                (lang.error_system().to_string(), AuditOutcome::Failed)
+            }
+        };
+        Self::audit(&ctx, actor, AdminAction::ViewLlmCosts, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// stars paid per day or week over the last periods given, net of refunds
+    async fn handle_revenue_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ViewRevenue, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let (reply, outcome) = match RevenuePeriod::parse_args(args) {
+            None => (lang.revenue_usage().to_string(), AuditOutcome::Failed),
+            Some((period, count)) => match ctx.receipts.revenue(period, count).await {
+                Ok(report) => {
+                    let lines = report
+                        .iter()
+                        .map(|line| lang.revenue_line(line))
+                        .collect::<Vec<_>>();
+                    (
+                        lang.revenue_report(period, count, &lines),
+                        AuditOutcome::Succeeded,
+                    )
+                }
+                Err(e) => {
+                    error!("Failed to load revenue report: {}", e);
+                    (lang.error_system().to_string(), AuditOutcome::Failed)
+                }
+            },
+        };
+        Self::audit(&ctx, actor, AdminAction::ViewRevenue, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// lists the star packages, adds one or changes one; changes show on the next keyboard
+    async fn handle_packages_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ManagePackages, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let result = match PackagesCommand::parse(args) {
+            None => Ok((lang.packages_usage().to_string(), AuditOutcome::Failed)),
+            Some(PackagesCommand::List) => ctx.packages.all().await.map(|packages| {
+                let lines = packages
+                    .iter()
+                    .map(|package| lang.package_line(package))
+                    .collect::<Vec<_>>();
+                (lang.packages_list(&lines), AuditOutcome::Succeeded)
+            }),
+            Some(PackagesCommand::Add { credits, stars }) => {
+                ctx.packages.add(credits, stars).await.map(|added| {
+                    if let Some(package) = added {
+                        (lang.package_added(&package), AuditOutcome::Succeeded)
+                    } else {
+                        (lang.package_exists(credits), AuditOutcome::Failed)
+                    }
+                })
+            }
+            Some(PackagesCommand::Change { id, change }) => {
+                ctx.packages.update(id, &change).await.map(|updated| {
+                    if updated {
+                        (lang.package_updated(id), AuditOutcome::Succeeded)
+                    } else {
+                        (lang.package_not_found(id), AuditOutcome::Failed)
+                    }
+                })
+            }
+        };
+        let (reply, outcome) = result.unwrap_or_else(|e| {
+            error!("Failed to manage packages: {}", e);
+            (lang.error_system().to_string(), AuditOutcome::Failed)
+        });
+        Self::audit(&ctx, actor, AdminAction::ManagePackages, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// shows the referral reward rules, or changes or resets one of them
+    async fn handle_referral_config_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ConfigureReferrals, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let usage = || {
+            (
+                lang.referral_config_usage().to_string(),
+                AuditOutcome::Failed,
+            )
+        };
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let (reply, outcome) = match parts.as_slice() {
+            [] => match ctx.referrals.rules().await {
+                Ok(rules) => {
+                    let lines = rules
+                        .values()
+                        .into_iter()
+                        .map(|(name, value)| format!("<code>{name}</code> = {value}"))
+                        .collect::<Vec<_>>();
+                    (lang.referral_config_status(&lines), AuditOutcome::Succeeded)
+                }
+                Err(e) => {
+                    error!("Failed to load referral rules: {}", e);
+                    (lang.error_system().to_string(), AuditOutcome::Failed)
+                }
+            },
+            [name, "reset"] if REFERRAL_RULE_NAMES.contains(name) => {
+                match ctx.referrals.reset_rule(name).await {
+                    Ok(_) => (lang.referral_config_reset(name), AuditOutcome::Succeeded),
+                    Err(e) => {
+                        error!("Failed to reset referral rule {}: {}", name, e);
+                        (lang.error_system().to_string(), AuditOutcome::Failed)
+                    }
+                }
+            }
+            [name, value] if REFERRAL_RULE_NAMES.contains(name) => {
+                let valid = value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|value| ReferralRules::default().set(name, *value).is_ok());
+                match valid {
+                    Some(value) => match ctx.referrals.set_rule(name, value).await {
+                        Ok(()) => (
+                            lang.referral_config_updated(name, value),
+                            AuditOutcome::Succeeded,
+                        ),
+                        Err(e) => {
+                            error!("Failed to save referral rule {}: {}", name, e);
+                            (lang.error_system().to_string(), AuditOutcome::Failed)
+                        }
+                    },
+                    None => usage(),
+                }
+            }
+            _ => usage(),
+        };
+        Self::audit(&ctx, actor, AdminAction::ConfigureReferrals, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// lists the referrers flagged as suspicious, or clears or confirms one of the flags;
+    /// clearing the last flag pays out the milestone rewards held meanwhile
+    async fn handle_referral_flags_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ReviewReferrals, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let usage = || {
+            (
+                lang.referral_flags_usage().to_string(),
+                AuditOutcome::Failed,
+            )
+        };
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let (reply, outcome) = match parts.as_slice() {
+            [] => match ctx.referral_flags.pending(FLAG_LIST_LIMIT).await {
+                Ok(flags) => {
+                    let entries = flags
+                        .iter()
+                        .map(|flag| {
+                            lang.referral_flag_entry(
+                                flag.id,
+                                &flag.created_at,
+                                flag.referrer_telegram_id,
+                                &flag.reason,
+                                &MessageFormatter::escape_html(&flag.details),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (
+                        lang.referral_flags_status(&entries),
+                        AuditOutcome::Succeeded,
+                    )
+                }
+                Err(e) => {
+                    error!("Failed to list referral flags: {}", e);
+                    (lang.error_system().to_string(), AuditOutcome::Failed)
+                }
+            },
+            [action @ ("clear" | "confirm"), id] => match id.parse::<i32>() {
+                Ok(id) => {
+                    let status = if *action == "clear" {
+                        FlagStatus::Cleared
+                    } else {
+                        FlagStatus::Confirmed
+                    };
+                    match ctx.referral_flags.review(id, status, actor).await {
+                        Ok(None) => (lang.referral_flag_not_found(id), AuditOutcome::Failed),
+                        Ok(Some(_)) if status == FlagStatus::Confirmed => {
+                            (lang.referral_flag_confirmed(id), AuditOutcome::Succeeded)
+                        }
+                        Ok(Some(flag)) if flag.still_held => (
+                            lang.referral_flag_cleared(id, None),
+                            AuditOutcome::Succeeded,
+                        ),
+                        Ok(Some(flag)) => {
+                            let released = Self::release_held_referral_rewards(&ctx, &flag).await;
+                            (
+                                lang.referral_flag_cleared(id, Some(released)),
+                                AuditOutcome::Succeeded,
+                            )
+                        }
+                        Err(e) => {
+                            error!("Failed to review referral flag {}: {}", id, e);
+                            (lang.error_system().to_string(), AuditOutcome::Failed)
+                        }
+                    }
+                }
+                Err(_) => usage(),
+            },
+            _ => usage(),
+        };
+        Self::audit(&ctx, actor, AdminAction::ReviewReferrals, args, outcome).await;
 
-impl CommandHandler {
-    pub async fn handle_command(ctx: BotContext, msg: Message, cmd: Command) -> ResponseResult<()> {
-        let lang = Lang::from_code(
-            msg.from
-                .as_ref()
-                .and_then(|user| user.language_code.as_deref()),
-        );
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
 
-        match cmd {
-            Command::Start => {
-                Self::handle_start_command(ctx, msg, lang).await?;
-            }
-            Command::Buy1 => {
-                Self::handle_buy_command(
-                    ctx,
-                    msg,
-                    SINGLE_PACKAGE_AMOUNT,
-                    SINGLE_PACKAGE_PRICE,
-                    lang.invoice_single_title(),
-                    lang.invoice_single_description(),
-                )
-                .await?;
+    /// pays the milestone rewards held while the referrer was flagged and tells them;
+    /// returns the credits paid
+    async fn release_held_referral_rewards(ctx: &BotContext, flag: &ReviewedFlag) -> i32 {
+        let reward_info = match ctx
+            .user_manager
+            .check_and_award_referral_rewards(flag.referrer_user_id)
+            .await
+        {
+            Ok(reward_info) => reward_info,
+            Err(e) => {
+                error!(
+                    "Failed to release held referral rewards of user {}: {}",
+                    flag.referrer_user_id, e
+                );
+                return 0;
             }
-            Command::Buy10 => {
-                let discount =
-                    (SINGLE_PACKAGE_PRICE * BULK_PACKAGE_AMOUNT as u32) - BULK_PACKAGE_PRICE;
-                Self::handle_buy_command(
-                    ctx,
-                    msg,
-                    BULK_PACKAGE_AMOUNT,
-                    BULK_PACKAGE_PRICE,
-                    lang.invoice_bulk_title(),
-                    &lang.invoice_bulk_description(discount),
-                )
-                .await?;
+        };
+        if reward_info.milestone_rewards > 0 {
+            let message = flag.referrer_lang.referral_milestone_only(
+                reward_info.milestone_rewards,
+                reward_info.referral_count,
+                flag.referrer_user_id,
+            );
+            if let Err(e) = ctx
+                .bot
+                .send_message(ChatId(flag.referrer_telegram_id), message)
+                .parse_mode(ParseMode::Html)
+                .await
+            {
+                error!(
+                    "Failed to tell user {} about released referral rewards: {}",
+                    flag.referrer_telegram_id, e
+                );
             }
         }
+        reward_info.total_credits_awarded
+    }
+
+    /// lists the look-alike signups whose free trials were withheld, or clears or confirms
+    /// a cluster; clearing grants the trials withheld from it
+    async fn handle_trial_clusters_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ReviewTrials, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let usage = || {
+            (
+                lang.trial_clusters_usage().to_string(),
+                AuditOutcome::Failed,
+            )
+        };
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let (reply, outcome) = match parts.as_slice() {
+            [] => match ctx.free_trials.pending(CLUSTER_LIST_LIMIT).await {
+                Ok(clusters) => {
+                    let entries = clusters
+                        .iter()
+                        .map(|cluster| lang.trial_cluster_entry(cluster))
+                        .collect::<Vec<_>>();
+                    (
+                        lang.trial_clusters_status(&entries),
+                        AuditOutcome::Succeeded,
+                    )
+                }
+                Err(e) => {
+                    error!("Failed to list trial clusters: {}", e);
+                    (lang.error_system().to_string(), AuditOutcome::Failed)
+                }
+            },
+            [action @ ("clear" | "confirm"), id] => match id.parse::<i32>() {
+                Ok(id) => {
+                    let status = if *action == "clear" {
+                        ClusterStatus::Cleared
+                    } else {
+                        ClusterStatus::Confirmed
+                    };
+                    match ctx.free_trials.review(id, status, actor).await {
+                        Ok(None) => (lang.trial_cluster_not_found(id), AuditOutcome::Failed),
+                        Ok(Some(_)) if status == ClusterStatus::Confirmed => {
+                            (lang.trial_cluster_confirmed(id), AuditOutcome::Succeeded)
+                        }
+                        Ok(Some(granted)) => (
+                            lang.trial_cluster_cleared(id, granted),
+                            AuditOutcome::Succeeded,
+                        ),
+                        Err(e) => {
+                            error!("Failed to review trial cluster {}: {}", id, e);
+                            (lang.error_system().to_string(), AuditOutcome::Failed)
+                        }
+                    }
+                }
+                Err(_) => usage(),
+            },
+            _ => usage(),
+        };
+        Self::audit(&ctx, actor, AdminAction::ReviewTrials, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
         Ok(())
     }
 
-    async fn handle_start_command(
+    /// lists the answer passages the safety review flagged, or marks one as reviewed
+    async fn handle_flagged_command(
         ctx: BotContext,
         msg: Message,
+        args: &str,
         lang: Lang,
     ) -> ResponseResult<()> {
-        // parse referral code from message text
-        let referrer_user_id = Self::parse_referral_code(&ctx, &msg).await;
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ReviewFlaggedContent, args, lang)
+                .await?
+        else {
+            return Ok(());
+        };
 
-        // get user info from telegram message
-        let user_info = Self::extract_user_info_from_message(&msg);
+        let usage = || (lang.flagged_usage().to_string(), AuditOutcome::Failed);
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let (reply, outcome) = match parts.as_slice() {
+            [] => match ctx.flagged_outputs.unreviewed(FLAGGED_LIST_LIMIT).await {
+                Ok(flagged) => {
+                    let entries = flagged
+                        .iter()
+                        .map(|output| {
+                            lang.flagged_entry(
+                                output.id,
+                                &output.created_at,
+                                &MessageFormatter::escape_html(&output.channel_name),
+                                &output.analysis_type,
+                                output.model.as_deref(),
+                                &output.section,
+                                &output.category,
+                                &output.action,
+                                &MessageFormatter::escape_html(&output.excerpt),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (lang.flagged_status(&entries), AuditOutcome::Succeeded)
+                }
+                Err(e) => {
+                    error!("Failed to list flagged outputs: {}", e);
+                    (lang.error_system().to_string(), AuditOutcome::Failed)
+                }
+            },
+            ["done", id] => match id.parse::<i32>() {
+                Ok(id) => match ctx.flagged_outputs.mark_reviewed(id, actor).await {
+                    Ok(true) => (lang.flagged_reviewed(id), AuditOutcome::Succeeded),
+                    Ok(false) => (lang.flagged_not_found(id), AuditOutcome::Failed),
+                    Err(e) => {
+                        error!("Failed to review flagged output {}: {}", id, e);
+                        (lang.error_system().to_string(), AuditOutcome::Failed)
+                    }
+                },
+                Err(_) => usage(),
+            },
+            _ => usage(),
+        };
+        Self::audit(
+            &ctx,
+            actor,
+            AdminAction::ReviewFlaggedContent,
+            args,
+            outcome,
+        )
+        .await;
 
-        // get or create user to check credit balance
-        let (user, maybe_reward_info) = match ctx
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// lists the channels excluded from analysis, or adds or removes one
+    async fn handle_blocklist_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ManageBlocklist, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let usage = || (lang.blocklist_usage().to_string(), AuditOutcome::Failed);
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let (reply, outcome) = match parts.as_slice() {
+            [] => match ctx.blocklist.list().await {
+                Ok(channels) => {
+                    let entries = channels
+                        .iter()
+                        .map(|channel| {
+                            lang.blocklist_entry(
+                                &channel.target,
+                                &channel.created_at,
+                                channel.blocked_by,
+                                channel
+                                    .reason
+                                    .as_deref()
+                                    .map(MessageFormatter::escape_html)
+                                    .as_deref(),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (lang.blocklist_status(&entries), AuditOutcome::Succeeded)
+                }
+                Err(e) => {
+                    error!("Failed to list blocked channels: {}", e);
+                    (lang.error_system().to_string(), AuditOutcome::Failed)
+                }
+            },
+            ["add", target, reason @ ..] => match BlockTarget::parse(target) {
+                Some(target) => {
+                    let reason = Some(reason.join(" ")).filter(|reason| !reason.is_empty());
+                    match ctx.blocklist.block(&target, reason.as_deref(), actor).await {
+                        Ok(()) => (lang.blocklist_added(&target.key()), AuditOutcome::Succeeded),
+                        Err(e) => {
+                            error!("Failed to block {}: {}", target, e);
+                            (lang.error_system().to_string(), AuditOutcome::Failed)
+                        }
+                    }
+                }
+                None => usage(),
+            },
+            ["remove", target] => match BlockTarget::parse(target) {
+                Some(target) => match ctx.blocklist.unblock(&target).await {
+                    Ok(found) => (
+                        lang.blocklist_removed(&target.key(), found),
+                        AuditOutcome::Succeeded,
+                    ),
+                    Err(e) => {
+                        error!("Failed to unblock {}: {}", target, e);
+                        (lang.error_system().to_string(), AuditOutcome::Failed)
+                    }
+                },
+                None => usage(),
+            },
+            _ => usage(),
+        };
+        Self::audit(&ctx, actor, AdminAction::ManageBlocklist, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    /// shows tonight's cache warm-up list, or pins a channel to it, excludes one from it or
+    /// drops either
+    async fn handle_warmup_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(actor) =
+            Self::authorize_admin(&ctx, &msg, AdminAction::ManageWarmup, args, lang).await?
+        else {
+            return Ok(());
+        };
+
+        let usage = || (lang.warmup_usage().to_string(), AuditOutcome::Failed);
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let (reply, outcome) = match parts.as_slice() {
+            [] => {
+                let config = WarmupConfig::from_env();
+                let top_channels = config
+                    .as_ref()
+                    .map_or(DEFAULT_TOP_CHANNELS, |config| config.top_channels);
+                let status = async {
+                    let (pinned, excluded) = ctx.warmup.overrides().await?;
+                    let channels = ctx
+                        .warmup
+                        .warm_list(&ctx.channel_stats, top_channels)
+                        .await?;
+                    Ok::<_, Box<dyn std::error::Error + Send + Sync>>((channels, pinned, excluded))
+                };
+                match status.await {
+                    Ok((channels, pinned, excluded)) => (
+                        lang.warmup_status(
+                            config.map(|config| config.window),
+                            &channels,
+                            &pinned,
+                            &excluded,
+                        ),
+                        AuditOutcome::Succeeded,
+                    ),
+                    Err(e) => {
+                        error!("Failed to load the cache warm-up list: {}", e);
+                        (lang.error_system().to_string(), AuditOutcome::Failed)
+                    }
+                }
+            }
+            [action @ ("add" | "exclude" | "reset"), channel] => {
+                match TelegramBot::validate_and_normalize_channel(channel) {
+                    Some(channel) => {
+                        let updated = match *action {
+                            "add" => ctx
+                                .warmup
+                                .pin(&channel, actor)
+                                .await
+                                .map(|_| lang.warmup_pinned(&channel)),
+                            "exclude" => ctx
+                                .warmup
+                                .exclude(&channel, actor)
+                                .await
+                                .map(|_| lang.warmup_excluded(&channel)),
+                            _ => ctx
+                                .warmup
+                                .reset(&channel)
+                                .await
+                                .map(|found| lang.warmup_reset(&channel, found)),
+                        };
+                        match updated {
+                            Ok(reply) => (reply, AuditOutcome::Succeeded),
+                            Err(e) => {
+                                error!("Failed to update the warm list for {}: {}", channel, e);
+                                (lang.error_system().to_string(), AuditOutcome::Failed)
+                            }
+                        }
+                    }
+                    None => usage(),
+                }
+            }
+            _ => usage(),
+        };
+        Self::audit(&ctx, actor, AdminAction::ManageWarmup, args, outcome).await;
+
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_analyze_group_command(
+        ctx: BotContext,
+        msg: Message,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let Some(channel_name) = Self::group_analysis_target(&ctx, &msg, lang).await? else {
+            return Ok(());
+        };
+        if let Some(topic) = Self::message_topic(&msg) {
+            return Self::offer_group_scope(ctx, &msg, channel_name, topic, None, lang).await;
+        }
+        TelegramBot::offer_channel_analysis(ctx, &msg, channel_name, lang).await
+    }
+
+    /// /analyze in groups; a type argument skips the type choice and starts that analysis
+    async fn handle_analyze_command(
+        ctx: BotContext,
+        msg: Message,
+        args: &str,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let analysis_type = args.trim().to_lowercase();
+        if !analysis_type.is_empty() && !ANALYSIS_TYPES.contains(&analysis_type.as_str()) {
+            ctx.bot
+                .send_message(msg.chat.id, lang.analyze_usage())
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        }
+
+        let Some(channel_name) = Self::group_analysis_target(&ctx, &msg, lang).await? else {
+            return Ok(());
+        };
+        if let Some(topic) = Self::message_topic(&msg) {
+            let analysis_type = (!analysis_type.is_empty()).then_some(analysis_type.as_str());
+            return Self::offer_group_scope(ctx, &msg, channel_name, topic, analysis_type, lang)
+                .await;
+        }
+        if analysis_type.is_empty() {
+            return TelegramBot::offer_channel_analysis(ctx, &msg, channel_name, lang).await;
+        }
+
+        let user_info = Self::extract_user_info_from_message(&msg);
+        let user = match ctx
             .user_manager
             .get_or_create_user(
                 user_info.telegram_user_id,
                 user_info.username,
                 user_info.first_name,
                 user_info.last_name,
-                referrer_user_id,
+                None,
                 user_info.language_code,
             )
             .await
         {
-            Ok((user, reward_info)) => (user, reward_info),
+            Ok((user, _)) => user,
             Err(e) => {
                 error!("Failed to get/create user: {}", e);
                 ctx.bot
-                    .send_message(msg.chat.id, lang.error_account_access())
+                    .send_message(msg.chat.id, lang.error_processing_request())
                     .await?;
                 return Ok(());
             }
         };
 
-        // send referral milestone notification if applicable
-        Self::send_referral_notifications(&ctx, maybe_reward_info, lang).await;
+        info!(
+            "Group admin {} requested {} analysis of {}",
+            user_info.telegram_user_id, analysis_type, channel_name
+        );
+        ctx.bot
+            .send_message(
+                msg.chat.id,
+                lang.group_analysis_preselected(
+                    &analysis_type,
+                    &MessageFormatter::escape_html(&channel_name),
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
 
-        // send appropriate welcome message based on user's credit balance
-        if user.analysis_credits <= 0 {
-            Self::send_no_credits_welcome(&ctx, &msg, &user, lang).await?;
-        } else {
-            Self::send_credits_available_welcome(&ctx, &msg, &user, lang).await?;
+        CallbackHandler::start_analysis(
+            ctx,
+            msg.chat.id,
+            user,
+            &channel_name,
+            &analysis_type,
+            AnalysisDepth::default(),
+            lang,
+        )
+        .await
+    }
+
+    /// the forum topic a message was posted in; the name is only known when the message
+    /// replies to the topic itself rather than to another message in it
+    fn message_topic(msg: &Message) -> Option<ForumTopic> {
+        if !msg.is_topic_message {
+            return None;
         }
+        let thread_id = msg.thread_id?.0 .0;
+        let name = msg
+            .reply_to_message()
+            .and_then(|reply| reply.forum_topic_created())
+            .map_or_else(|| format!("#{}", thread_id), |created| created.name.clone());
+        Some(ForumTopic { thread_id, name })
+    }
+
+    /// asks a group admin whether to analyze the whole group or only the topic they wrote in
+    async fn offer_group_scope(
+        ctx: BotContext,
+        msg: &Message,
+        channel_name: String,
+        topic: ForumTopic,
+        analysis_type: Option<&str>,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+        let keyboard = CallbackHandler::create_group_scope_keyboard(&topic, analysis_type, lang);
+        let text = lang.group_scope_select(&MessageFormatter::escape_html(&topic.name));
+        // remember the name, the keyboard only carries the topic's id
+        ctx.user_sessions
+            .insert(
+                telegram_user_id,
+                UserSession {
+                    channel_name: Some(channel_name),
+                    topic: Some(topic),
+                    ..Default::default()
+                },
+            )
+            .await;
 
+        let mut request = ctx
+            .bot
+            .send_message(msg.chat.id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(keyboard);
+        if let Some(thread_id) = msg.thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        request.await?;
         Ok(())
     }
 
+    /// the group a group analysis command was sent in, as a channel name; None after
+    /// telling the sender why the group can't be analyzed by them
+    async fn group_analysis_target(
+        ctx: &BotContext,
+        msg: &Message,
+        lang: Lang,
+    ) -> ResponseResult<Option<String>> {
+        if !(msg.chat.is_group() || msg.chat.is_supergroup()) {
+            ctx.bot
+                .send_message(msg.chat.id, lang.analyze_group_only_in_groups())
+                .await?;
+            return Ok(None);
+        }
+
+        // anonymous admins post on behalf of the group, so there is no one to bill
+        let Some(user) = msg.from.as_ref().filter(|_| msg.sender_chat.is_none()) else {
+            ctx.bot
+                .send_message(msg.chat.id, lang.analyze_group_anonymous_admin())
+                .await?;
+            return Ok(None);
+        };
+
+        let member = ctx.bot.get_chat_member(msg.chat.id, user.id).await?;
+        if !member.is_privileged() {
+            info!(
+                "Ignoring group analysis command from non-admin user {} in chat {}",
+                user.id, msg.chat.id
+            );
+            ctx.bot
+                .send_message(msg.chat.id, lang.analyze_group_admins_only())
+                .await?;
+            return Ok(None);
+        }
+
+        // the analysis pipeline reads chats by their public username
+        let Some(username) = msg.chat.username() else {
+            ctx.bot
+                .send_message(msg.chat.id, lang.analyze_group_requires_username())
+                .await?;
+            return Ok(None);
+        };
+
+        let channel_name = format!("@{}", username);
+        info!(
+            "Group admin {} requested analysis of {}",
+            user.id, channel_name
+        );
+        Ok(Some(channel_name))
+    }
+
     async fn parse_referral_code(ctx: &BotContext, msg: &Message) -> Option<i32> {
         if let Some(text) = msg.text() {
             info!("Processing /start command with text: {}", text);
@@ -145,7 +2036,7 @@ impl CommandHandler {
         }
     }
 
-    fn extract_user_info_from_message(msg: &Message) -> UserInfo {
+    fn extract_user_info_from_message(msg: &Message) -> UserInfo<'_> {
         UserInfo {
             telegram_user_id: msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0),
             username: msg.from.as_ref().and_then(|user| user.username.as_deref()),
@@ -240,21 +2131,14 @@ impl CommandHandler {
             lang.referral_info_no_referrals().to_string()
         };
 
-        let bulk_discount =
-            (SINGLE_PACKAGE_PRICE * BULK_PACKAGE_AMOUNT as u32) - BULK_PACKAGE_PRICE;
-
-        let intro_text = lang.welcome_no_credits(
-            user.id,
-            SINGLE_PACKAGE_PRICE,
-            BULK_PACKAGE_PRICE,
-            bulk_discount,
-            &referral_info,
-        );
+        let packages = ctx.packages.active().await;
+        let intro_text =
+            lang.welcome_no_credits(user.id, &lang.package_prices(&packages), &referral_info);
 
         ctx.bot
             .send_message(msg.chat.id, intro_text)
             .parse_mode(ParseMode::Html)
-            .reply_markup(CallbackHandler::create_payment_keyboard(lang))
+            .reply_markup(CallbackHandler::create_payment_keyboard(&packages, lang))
             .await?;
 
         Ok(())
@@ -307,23 +2191,133 @@ impl CommandHandler {
         }
     }
 
-    async fn handle_buy_command(
+    /// shows the user's subscription, or a link to start one
+    async fn handle_subscribe_command(
         ctx: BotContext,
         msg: Message,
-        credits: i32,
-        stars: u32,
-        title: &str,
-        description: &str,
+        lang: Lang,
     ) -> ResponseResult<()> {
-        PaymentHandler::send_payment_invoice(
-            ctx.bot,
-            msg.chat.id,
+        let user_info = Self::extract_user_info_from_message(&msg);
+        let user = match ctx
+            .user_manager
+            .get_or_create_user(
+                user_info.telegram_user_id,
+                user_info.username,
+                user_info.first_name,
+                user_info.last_name,
+                None,
+                user_info.language_code,
+            )
+            .await
+        {
+            Ok((user, _)) => user,
+            Err(e) => {
+                error!("Failed to get user for /subscribe: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_system())
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match ctx.subscriptions.get(user.id).await {
+            Ok(Some(subscription)) if subscription.status != SubscriptionStatus::Expired => {
+                ctx.bot
+                    .send_message(
+                        msg.chat.id,
+                        lang.subscription_status(
+                            subscription.credits_per_period,
+                            &subscription.paid_until,
+                            subscription.status == SubscriptionStatus::Grace,
+                        ),
+                    )
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Failed to load subscription of user {}: {}", user.id, e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_system())
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        let (credits, price) = (
+            ctx.limits().subscription_credits,
+            ctx.limits().subscription_price,
+        );
+        let link = match subscriptions::create_subscription_link(
+            &ctx.bot,
+            lang.invoice_subscription_title(),
+            &lang.invoice_subscription_description(credits),
+            &Lang::En.credits_label(credits),
             credits,
-            stars,
-            title,
-            description,
+            price,
         )
-        .await?;
+        .await
+        {
+            Ok(link) => link,
+            Err(e) => {
+                error!("Failed to create subscription invoice: {}", e);
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_system())
+                    .await?;
+                return Ok(());
+            }
+        };
+        let Ok(link) = Url::parse(&link) else {
+            error!("Telegram returned an invalid invoice link: {}", link);
+            ctx.bot
+                .send_message(msg.chat.id, lang.error_system())
+                .await?;
+            return Ok(());
+        };
+
+        ctx.bot
+            .send_message(msg.chat.id, lang.subscription_offer(credits, price))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::url(lang.btn_subscribe(credits, price), link),
+            ]]))
+            .await?;
+        Ok(())
+    }
+
+    /// the invoice of the package with that many credits, or the list of packages on
+    /// sale to pick from
+    async fn handle_buy_command(
+        ctx: BotContext,
+        msg: Message,
+        credits: Option<i32>,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let packages = ctx.packages.active().await;
+        let requested = credits.and_then(|credits| packages.iter().find(|p| p.credits == credits));
+        if let Some(package) = requested {
+            return PaymentHandler::send_package_invoice(
+                ctx.bot.clone(),
+                msg.chat.id,
+                &packages,
+                package,
+                lang,
+            )
+            .await;
+        }
+        if packages.is_empty() {
+            ctx.bot
+                .send_message(msg.chat.id, lang.no_packages_on_sale())
+                .await?;
+            return Ok(());
+        }
+        let prices = lang.package_prices(&packages);
+        ctx.bot
+            .send_message(msg.chat.id, lang.buy_packages(&prices))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_payment_keyboard(&packages, lang))
+            .await?;
         Ok(())
     }
 }
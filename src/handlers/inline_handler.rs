@@ -0,0 +1,115 @@
+use log::{error, info};
+use teloxide::prelude::*;
+use teloxide::types::{
+    InlineQuery, InlineQueryResult, InlineQueryResultArticle, InlineQueryResultsButton,
+    InlineQueryResultsButtonKind, InputMessageContent, InputMessageContentText, ParseMode,
+};
+
+use crate::bot::{BotContext, TelegramBot};
+use crate::utils::{MessageFormatter, ResultPresenter, SummaryGenerator};
+
+// keep cards short enough to read at a glance in any chat
+const SUMMARY_MAX_CHARS: usize = 400;
+
+pub struct InlineHandler;
+
+impl InlineHandler {
+    /// answers `@bot <channel>` with share cards of the user's completed analyses
+    pub async fn handle_inline_query(ctx: BotContext, query: InlineQuery) -> ResponseResult<()> {
+        let lang = TelegramBot::user_lang(&ctx, Some(&query.from)).await;
+        let telegram_user_id = query.from.id.0 as i64;
+
+        // an empty query lists recent analyses, anything else must be a channel
+        let text = query.query.trim();
+        let channel_filter = if text.is_empty() {
+            None
+        } else {
+            match TelegramBot::validate_and_normalize_channel(text) {
+                Some(channel_name) => Some(channel_name),
+                None => {
+                    ctx.bot
+                        .answer_inline_query(&query.id, Vec::<InlineQueryResult>::new())
+                        .is_personal(true)
+                        .await?;
+                    return Ok(());
+                }
+            }
+        };
+
+        let analyses = match ctx
+            .user_manager
+            .get_shareable_analyses(
+                telegram_user_id,
+                channel_filter.as_deref(),
+                ctx.limits().inline_results,
+            )
+            .await
+        {
+            Ok(analyses) => analyses,
+            Err(e) => {
+                error!(
+                    "Failed to load shareable analyses for user {}: {}",
+                    telegram_user_id, e
+                );
+                Vec::new()
+            }
+        };
+
+        let mut results = Vec::new();
+        for analysis in analyses {
+            let Some(result) = ctx
+                .analysis_workers
+                .cache
+                .load_llm_result(&analysis.cache_key)
+                .await
+            else {
+                continue;
+            };
+            let Some(content) = ResultPresenter::content_for(&result, &analysis.analysis_type)
+            else {
+                continue;
+            };
+
+            let excerpt = SummaryGenerator::excerpt(content, SUMMARY_MAX_CHARS);
+            let channel_name = ResultPresenter::target_label(&analysis.channel_name, lang);
+            let card = lang.share_card(
+                &channel_name,
+                &analysis.analysis_type,
+                &MessageFormatter::escape_html(&excerpt),
+                analysis.user_id,
+            );
+
+            let article = InlineQueryResultArticle::new(
+                format!("{}:{}", analysis.channel_name, analysis.analysis_type),
+                lang.share_card_title(&analysis.channel_name, &analysis.analysis_type),
+                InputMessageContent::Text(
+                    InputMessageContentText::new(card).parse_mode(ParseMode::Html),
+                ),
+            )
+            .description(excerpt);
+            results.push(InlineQueryResult::Article(article));
+        }
+
+        info!(
+            "Answering inline query from user {} with {} share cards",
+            telegram_user_id,
+            results.len()
+        );
+
+        let mut answer = ctx
+            .bot
+            .answer_inline_query(&query.id, results.clone())
+            .is_personal(true)
+            .cache_time(30);
+        if results.is_empty() {
+            // point users without shareable results to the bot itself
+            answer = answer.button(InlineQueryResultsButton {
+                text: lang.inline_no_results().to_string(),
+                kind: InlineQueryResultsButtonKind::StartParameter("inline".to_string()),
+            });
+        }
+        answer.await?;
+
+        Ok(())
+    }
+}
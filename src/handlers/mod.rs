@@ -1,7 +1,11 @@
+pub mod callback_data;
 pub mod callback_handler;
 pub mod command_handler;
+pub mod inline_handler;
 pub mod payment_handler;
 
+pub use callback_data::CallbackData;
 pub use callback_handler::CallbackHandler;
 pub use command_handler::CommandHandler;
+pub use inline_handler::InlineHandler;
 pub use payment_handler::PaymentHandler;
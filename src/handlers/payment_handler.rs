@@ -1,25 +1,62 @@
-use log::{error, info};
+use log::{error, info, warn};
+use std::error::Error;
+use std::fmt;
 use std::sync::Arc;
 use teloxide::prelude::*;
 use teloxide::types::{ChatId, LabeledPrice, ParseMode, PreCheckoutQuery, SuccessfulPayment};
+use teloxide::RequestError;
 
+use crate::error::AppError;
+use crate::invoice_payload::InvoicePayload;
 use crate::localization::Lang;
-use crate::user_manager::UserManager;
+use crate::observability::{self, ErrorContext, ErrorKind};
+use crate::packages::{discount, Package};
+use crate::subscriptions::SubscriptionManager;
+use crate::user_manager::{Payment, UserManager, UserManagerError};
 
-// payment configuration constants
-pub const SINGLE_PACKAGE_PRICE: u32 = 100;
-pub const BULK_PACKAGE_PRICE: u32 = 500;
-pub const SINGLE_PACKAGE_AMOUNT: i32 = 1;
-pub const BULK_PACKAGE_AMOUNT: i32 = 10;
+#[derive(Debug)]
+pub enum RefundError {
+    PaymentNotFound(String), // telegram_payment_charge_id
+    AlreadyRefunded(String), // telegram_payment_charge_id
+    Telegram(RequestError),
+    Database(UserManagerError),
+}
+
+impl fmt::Display for RefundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefundError::PaymentNotFound(charge_id) => {
+                write!(f, "Payment {} not found", charge_id)
+            }
+            RefundError::AlreadyRefunded(charge_id) => {
+                write!(f, "Payment {} was already refunded", charge_id)
+            }
+            RefundError::Telegram(e) => write!(f, "Telegram refused the refund: {}", e),
+            RefundError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for RefundError {}
+
+impl From<UserManagerError> for RefundError {
+    fn from(err: UserManagerError) -> Self {
+        RefundError::Database(err)
+    }
+}
 
 #[derive(Clone)]
 pub struct PaymentHandler {
     user_manager: Arc<UserManager>,
+    subscriptions: Arc<SubscriptionManager>,
 }
 
 impl PaymentHandler {
-    pub fn new(user_manager: Arc<UserManager>) -> Self {
-        Self { user_manager }
+    pub fn new(user_manager: Arc<UserManager>, subscriptions: Arc<SubscriptionManager>) -> Self {
+        Self {
+            user_manager,
+            subscriptions,
+        }
     }
 
     pub async fn send_payment_invoice(
@@ -41,7 +78,7 @@ impl PaymentHandler {
             chat_id,
             title,
             description,
-            format!("credits_{}", credits),
+            InvoicePayload::Package(credits).to_string(),
             "XTR",
             prices,
         )
@@ -51,6 +88,32 @@ impl PaymentHandler {
         Ok(())
     }
 
+    /// the invoice of a package, with its own title and description if it has them
+    pub async fn send_package_invoice(
+        bot: Arc<Bot>,
+        chat_id: ChatId,
+        packages: &[Package],
+        package: &Package,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let title = package
+            .title
+            .clone()
+            .unwrap_or_else(|| lang.invoice_package_title(package.credits));
+        let description = package.description.clone().unwrap_or_else(|| {
+            lang.invoice_package_description(package.credits, discount(packages, package))
+        });
+        Self::send_payment_invoice(
+            bot,
+            chat_id,
+            package.credits,
+            package.stars,
+            &title,
+            &description,
+        )
+        .await
+    }
+
     pub async fn handle_pre_checkout_query(
         bot: Arc<Bot>,
         query: PreCheckoutQuery,
@@ -72,7 +135,18 @@ impl PaymentHandler {
     ) -> ResponseResult<()> {
         let telegram_user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
         let language_code = msg.from.as_ref().and_then(|u| u.language_code.as_deref());
-        let lang = Lang::from_code(language_code);
+        let language_override = self
+            .user_manager
+            .get_language_override(telegram_user_id)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to get language override for user {}: {}",
+                    telegram_user_id, e
+                );
+                None
+            });
+        let lang = Lang::resolve(language_override.as_deref(), language_code);
 
         // get user info for referral link
         let (user, _) = match self
@@ -83,25 +157,62 @@ impl PaymentHandler {
             Ok(result) => result,
             Err(e) => {
                 error!("Failed to get user info during payment: {}", e);
+                observability::report(
+                    ErrorKind::Payment,
+                    e.as_ref(),
+                    &ErrorContext {
+                        telegram_user_id: Some(telegram_user_id),
+                        charge_id: Some(&payment.telegram_payment_charge_id),
+                        ..Default::default()
+                    },
+                );
                 bot.send_message(msg.chat.id, lang.error_payment_processing())
                     .await?;
                 return Ok(());
             }
         };
 
-        // parse credits from payload
-        let credits = if payment.invoice_payload == "credits_1" {
-            1
-        } else if payment.invoice_payload == "credits_10" {
-            10
-        } else {
-            error!("Unknown payment payload: {}", payment.invoice_payload);
-            return Ok(());
+        // the credits are in the payload, so a package changed or retired after its
+        // invoice was sent still gets what was paid for
+        let payment_context = ErrorContext {
+            user_id: Some(user.id),
+            telegram_user_id: Some(telegram_user_id),
+            charge_id: Some(&payment.telegram_payment_charge_id),
+            ..Default::default()
+        };
+        let credits = match InvoicePayload::parse(&payment.invoice_payload) {
+            Some(InvoicePayload::Package(credits)) => credits,
+            // first payments and renewals of a subscription share its payload
+            Some(InvoicePayload::Subscription(credits)) => {
+                return self
+                    .handle_subscription_payment(bot, &msg, user.id, &payment, credits, lang)
+                    .await;
+            }
+            None => {
+                error!("Unknown payment payload: {}", payment.invoice_payload);
+                observability::report_message(
+                    ErrorKind::Payment,
+                    &format!("Unknown payment payload: {}", payment.invoice_payload),
+                    &payment_context,
+                );
+                return Ok(());
+            }
         };
 
-        // add credits to user account
-        match self.user_manager.add_credits(user.id, credits).await {
-            Ok(new_balance) => {
+        // the payment is recorded with its credits, so the charge id is kept around for a
+        // refund and a redelivered update is recognized
+        match self
+            .user_manager
+            .record_package_payment(
+                user.id,
+                &payment.telegram_payment_charge_id,
+                &payment.invoice_payload,
+                payment.total_amount as i32,
+                credits,
+            )
+            .await
+        {
+            Ok(Some(new_balance)) => {
                 let success_msg = lang.payment_success(user.id, credits, new_balance);
 
                 bot.send_message(msg.chat.id, success_msg)
@@ -121,25 +232,202 @@ impl PaymentHandler {
                     );
                 }
             }
+            Ok(None) => {
+                warn!(
+                    "Payment {} was already recorded",
+                    payment.telegram_payment_charge_id
+                );
+            }
             Err(e) => {
                 error!(
                     "Failed to add credits after payment for user {}: {}",
                     telegram_user_id, e
                 );
+                observability::report(ErrorKind::Payment, &e, &payment_context);
+                bot.send_message(msg.chat.id, lang.error_payment_credits())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// extends the user's subscription and adds the credits of the paid period; credits
+    /// that fail to be added here are added by the subscription scheduler
+    async fn handle_subscription_payment(
+        &self,
+        bot: Arc<Bot>,
+        msg: &Message,
+        user_id: i32,
+        payment: &SuccessfulPayment,
+        credits: i32,
+        lang: Lang,
+    ) -> ResponseResult<()> {
+        let charge_id = &payment.telegram_payment_charge_id;
+        let payment_context = ErrorContext {
+            user_id: Some(user_id),
+            charge_id: Some(charge_id),
+            ..Default::default()
+        };
+        let subscription = match self
+            .subscriptions
+            .record_payment(user_id, charge_id, payment.total_amount as i32, credits)
+            .await
+        {
+            Ok(Some(subscription)) => subscription,
+            Ok(None) => {
+                warn!("Subscription payment {} was already recorded", charge_id);
+                return Ok(());
+            }
+            Err(e) => {
+                error!(
+                    "Failed to record subscription payment {} for user {}: {}",
+                    charge_id, user_id, e
+                );
+                observability::report(ErrorKind::Payment, e.as_ref(), &payment_context);
                 bot.send_message(msg.chat.id, lang.error_payment_credits())
                     .await?;
+                return Ok(());
+            }
+        };
+
+        // keep the charge id around so the payment can be refunded
+        if let Err(e) = self
+            .user_manager
+            .record_payment(
+                user_id,
+                charge_id,
+                &payment.invoice_payload,
+                payment.total_amount as i32,
+                credits,
+            )
+            .await
+        {
+            error!(
+                "Failed to record payment {} for user {}: {}",
+                charge_id, user_id, e
+            );
+            observability::report(ErrorKind::Payment, &e, &payment_context);
+        }
+
+        match self.subscriptions.top_up(user_id).await {
+            Ok(Some((added, new_balance))) => {
+                bot.send_message(
+                    msg.chat.id,
+                    lang.subscription_paid(
+                        added,
+                        new_balance,
+                        &subscription.paid_until,
+                        subscription.periods_paid > 1,
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+                info!(
+                    "Processed subscription payment {}: {} credits for user {}",
+                    charge_id, added, user_id
+                );
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!(
+                    "Failed to top up subscription credits for user {}: {}",
+                    user_id, e
+                );
+                observability::report(ErrorKind::Payment, e.as_ref(), &payment_context);
+                bot.send_message(msg.chat.id, lang.subscription_credits_pending())
+                    .await?;
             }
         }
 
+        if let Err(e) = self.process_referral_rewards(bot, user_id, lang).await {
+            error!(
+                "Failed to process referral rewards for user {}: {}",
+                user_id, e
+            );
+        }
         Ok(())
     }
 
+    /// refunds a star payment through telegram, records it and notifies the payer
+    pub async fn refund_payment(
+        &self,
+        bot: Arc<Bot>,
+        telegram_payment_charge_id: &str,
+        reason: Option<&str>,
+        refunded_by: Option<i64>,
+    ) -> Result<(Payment, i32), RefundError> {
+        let payment = self
+            .user_manager
+            .get_payment(telegram_payment_charge_id)
+            .await?
+            .ok_or_else(|| RefundError::PaymentNotFound(telegram_payment_charge_id.to_string()))?;
+
+        if payment.refunded {
+            return Err(RefundError::AlreadyRefunded(
+                telegram_payment_charge_id.to_string(),
+            ));
+        }
+
+        bot.refund_star_payment(
+            UserId(payment.telegram_user_id as u64),
+            telegram_payment_charge_id,
+        )
+        .await
+        .map_err(RefundError::Telegram)?;
+
+        info!(
+            "Refunded {} stars for payment {} (reason: {:?})",
+            payment.stars, telegram_payment_charge_id, reason
+        );
+
+        // the stars are already back with the user at this point, so a failure here
+        // only affects bookkeeping and must be fixed by hand
+        let new_balance = self
+            .user_manager
+            .record_refund(&payment, reason, refunded_by)
+            .await
+            .inspect_err(|e| {
+                error!(
+                    "Refund for payment {} was issued but not recorded: {}",
+                    telegram_payment_charge_id, e
+                );
+                observability::report(
+                    ErrorKind::Payment,
+                    e,
+                    &ErrorContext {
+                        user_id: Some(payment.user_id),
+                        telegram_user_id: Some(payment.telegram_user_id),
+                        charge_id: Some(telegram_payment_charge_id),
+                        ..Default::default()
+                    },
+                );
+            })?;
+
+        let lang = Lang::from_code(payment.language.as_deref());
+        if let Err(e) = bot
+            .send_message(
+                ChatId(payment.telegram_user_id),
+                lang.refund_notice(payment.stars, new_balance),
+            )
+            .parse_mode(ParseMode::Html)
+            .await
+        {
+            warn!(
+                "Failed to notify user {} about refund: {}",
+                payment.telegram_user_id, e
+            );
+        }
+
+        Ok((payment, new_balance))
+    }
+
     async fn process_referral_rewards(
         &self,
         bot: Arc<Bot>,
         user_id: i32,
         lang: Lang,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), AppError> {
         match self.user_manager.record_paid_referral(user_id).await {
             Ok(Some(reward_info)) => {
                 if let Some(referrer_telegram_id) = reward_info.referrer_telegram_id {
@@ -0,0 +1,164 @@
+//! liveness and readiness probes for container orchestrators: /healthz answers as long as
+//! the process runs, /readyz only once the database, a telegram session and the bot token
+//! all work
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use serde::Serialize;
+use std::env;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::prelude::*;
+
+use crate::backend_config::{BackendPolicy, BackendType};
+use crate::mock;
+use crate::workers::AnalysisWorkers;
+
+// a probe that hangs is as bad as a failed one, orchestrators time out after a few seconds
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct HealthConfig {
+    pub bind_addr: String,
+}
+
+impl HealthConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            bind_addr: env::var("HEALTH_BIND_ADDR").ok()?,
+        })
+    }
+}
+
+/// what /readyz checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Readiness {
+    pub database: bool,
+    // true when no telegram session is needed, as in web-only or mock mode
+    pub telegram_sessions: bool,
+    pub bot_token: bool,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.database && self.telegram_sessions && self.bot_token
+    }
+}
+
+/// whether fetching under `policy` may need a telegram session
+pub fn sessions_required(policy: BackendPolicy) -> bool {
+    policy.backends().contains(&BackendType::Api)
+}
+
+/// whether a connection can be taken from the pool and answers a query in time
+pub async fn check_database(pool: &Pool) -> bool {
+    let ping = async {
+        let client = pool.get().await?;
+        client.query_one("SELECT 1", &[]).await?;
+        Ok::<_, Box<dyn Error + Send + Sync>>(())
+    };
+    match tokio::time::timeout(PROBE_TIMEOUT, ping).await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            warn!("Readiness probe could not reach the database: {}", e);
+            false
+        }
+        Err(_) => {
+            warn!("Readiness probe timed out reaching the database");
+            false
+        }
+    }
+}
+
+pub struct HealthState {
+    pool: Arc<Pool>,
+    analysis_workers: Arc<AnalysisWorkers>,
+    bot: Bot,
+    // a token telegram accepted once stays valid, so it is only asked until it answers
+    bot_verified: AtomicBool,
+}
+
+impl HealthState {
+    pub fn new(pool: Arc<Pool>, analysis_workers: Arc<AnalysisWorkers>, bot_token: &str) -> Self {
+        Self {
+            pool,
+            analysis_workers,
+            bot: Bot::new(bot_token),
+            bot_verified: AtomicBool::new(false),
+        }
+    }
+
+    async fn check_bot_token(&self) -> bool {
+        if self.bot_verified.load(Ordering::Relaxed) {
+            return true;
+        }
+        match tokio::time::timeout(PROBE_TIMEOUT, self.bot.get_me()).await {
+            Ok(Ok(me)) => {
+                info!("Bot token verified for @{}", me.username());
+                self.bot_verified.store(true, Ordering::Relaxed);
+                true
+            }
+            Ok(Err(e)) => {
+                warn!("Readiness probe could not verify the bot token: {}", e);
+                false
+            }
+            Err(_) => {
+                warn!("Readiness probe timed out verifying the bot token");
+                false
+            }
+        }
+    }
+
+    fn check_sessions(&self) -> bool {
+        mock::enabled()
+            || !sessions_required(self.analysis_workers.backend_policy())
+            || self.analysis_workers.healthy_sessions() > 0
+    }
+
+    pub async fn readiness(&self) -> Readiness {
+        let (database, bot_token) =
+            tokio::join!(check_database(&self.pool), self.check_bot_token());
+        Readiness {
+            database,
+            telegram_sessions: self.check_sessions(),
+            bot_token,
+        }
+    }
+}
+
+pub fn router(state: Arc<HealthState>) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(state)
+}
+
+/// serves GET /healthz and GET /readyz until the process exits
+pub async fn serve(
+    config: HealthConfig,
+    state: Arc<HealthState>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let listener = tokio::net::TcpListener::bind(&config.bind_addr).await?;
+    info!("Health checks listening on {}", config.bind_addr);
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+async fn readyz(State(state): State<Arc<HealthState>>) -> impl IntoResponse {
+    let readiness = state.readiness().await;
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
@@ -0,0 +1,47 @@
+//! invoice payloads of star payments: which package or subscription a payment is for, and
+//! the credits it grants, kept with the payment as its package
+use std::fmt;
+
+const PACKAGE_PREFIX: &str = "credits_";
+// renewals carry the payload of the first invoice, so a subscriber keeps the credits they
+// signed up for
+const SUBSCRIPTION_PREFIX: &str = "subscription_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvoicePayload {
+    /// a one-time package of this many credits
+    Package(i32),
+    /// a subscription of this many credits per period
+    Subscription(i32),
+}
+
+impl InvoicePayload {
+    /// None for payloads of neither kind and for credits that aren't positive
+    pub fn parse(payload: &str) -> Option<Self> {
+        let (kind, credits): (fn(i32) -> Self, _) =
+            if let Some(credits) = payload.strip_prefix(PACKAGE_PREFIX) {
+                (Self::Package, credits)
+            } else if let Some(credits) = payload.strip_prefix(SUBSCRIPTION_PREFIX) {
+                (Self::Subscription, credits)
+            } else {
+                return None;
+            };
+        if !credits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        credits
+            .parse()
+            .ok()
+            .filter(|credits| *credits > 0)
+            .map(kind)
+    }
+}
+
+impl fmt::Display for InvoicePayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Package(credits) => write!(f, "{}{}", PACKAGE_PREFIX, credits),
+            Self::Subscription(credits) => write!(f, "{}{}", SUBSCRIPTION_PREFIX, credits),
+        }
+    }
+}
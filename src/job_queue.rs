@@ -0,0 +1,562 @@
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use rand::Rng;
+use std::env;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::error::AppError;
+use crate::user_manager::{stored_topic, AnalysisSource, PendingAnalysis};
+
+// users who ever paid or subscribe are leased before everyone else
+pub const PAID_PRIORITY: i32 = 1;
+pub const FREE_PRIORITY: i32 = 0;
+
+// a job whose runner stops renewing its lease is taken over once it expires
+pub const JOB_LEASE: Duration = Duration::from_secs(5 * 60);
+// renewals per lease, so a few slow ones in a row don't lose it
+const HEARTBEATS_PER_LEASE: u32 = 5;
+// leases, not failures: a job that keeps losing its runner fails after this many
+pub const MAX_JOB_ATTEMPTS: i32 = 3;
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub const ALL: [JobStatus; 5] = [
+        JobStatus::Queued,
+        JobStatus::Running,
+        JobStatus::Completed,
+        JobStatus::Failed,
+        JobStatus::Cancelled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == code)
+    }
+
+    /// whether the job will not run anymore
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+/// a job a runner holds the lease of, with the analysis it runs
+#[derive(Debug, Clone)]
+pub struct LeasedJob {
+    pub id: i32,
+    // including this one
+    pub attempts: i32,
+    pub allow_low_text: bool,
+    // where the result goes, the user's private chat when unset
+    pub chat_id: Option<i64>,
+    // the ui language of the request, the analysis language when unset
+    pub language: Option<String>,
+    pub analysis: PendingAnalysis,
+}
+
+/// analyses waiting to run, in postgres so they survive restarts; runners lease jobs by
+/// priority and keep renewing the lease while they work
+pub struct JobQueue {
+    pool: Arc<Pool>,
+}
+
+impl JobQueue {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// queues a pending analysis; queuing it again puts it back in line, keeping the lease
+    /// attempts of an unfinished job so a job that crashes its runner can't loop forever,
+    /// and restarts the analysis watchdog's clock
+    pub async fn enqueue(
+        &self,
+        analysis_id: i32,
+        chat_id: Option<i64>,
+        language: Option<&str>,
+        allow_low_text: bool,
+    ) -> Result<i32, AppError> {
+        self.queue_job(analysis_id, chat_id, language, allow_low_text, true)
+            .await?
+            .ok_or_else(|| AppError::Validation(format!("No analysis with id {}", analysis_id)))
+    }
+
+    /// queues an analysis interrupted by a restart as `enqueue` does, unless a live runner,
+    /// e.g. of another replica, holds the lease of its job; None then, the job is left to
+    /// that runner and taken over only once the lease expires
+    pub async fn resume(
+        &self,
+        analysis_id: i32,
+        allow_low_text: bool,
+    ) -> Result<Option<i32>, AppError> {
+        self.queue_job(analysis_id, None, None, allow_low_text, false)
+            .await
+    }
+
+    /// fails a pending analysis interrupted by a restart unless a live runner holds the lease
+    /// of its job; false if it is running elsewhere or isn't pending anymore. Its job, if
+    /// any, is cancelled by the next `sweep`
+    pub async fn abandon(&self, analysis_id: i32) -> Result<bool, AppError> {
+        let client = self.pool.get().await?;
+        let failed = client
+            .execute(
+                "UPDATE user_analyses ua SET status = 'failed'
+                 WHERE ua.id = $1 AND ua.status = 'pending'
+                 AND NOT EXISTS (
+                     SELECT 1 FROM analysis_jobs j
+                     WHERE j.analysis_id = ua.id AND j.status = 'running' AND j.lease_expires_at > NOW()
+                 )",
+                &[&analysis_id],
+            )
+            .await?;
+        Ok(failed > 0)
+    }
+
+    // None when `take_over_lease` is unset and another runner's lease hasn't expired, or
+    // when there is no such analysis
+    async fn queue_job(
+        &self,
+        analysis_id: i32,
+        chat_id: Option<i64>,
+        language: Option<&str>,
+        allow_low_text: bool,
+        take_over_lease: bool,
+    ) -> Result<Option<i32>, AppError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "WITH live AS (
+                     SELECT 1 FROM analysis_jobs
+                     WHERE analysis_id = $1 AND status = 'running' AND lease_expires_at > NOW()
+                 ), restarted AS (
+                     UPDATE user_analyses SET pending_since = NOW()
+                     WHERE id = $1 AND status = 'pending' AND ($7 OR NOT EXISTS (SELECT 1 FROM live))
+                 )
+                 INSERT INTO analysis_jobs (analysis_id, priority, chat_id, language, allow_low_text)
+                 SELECT ua.id,
+                        CASE WHEN EXISTS (SELECT 1 FROM payments p WHERE p.user_id = ua.user_id)
+                               OR EXISTS (SELECT 1 FROM subscriptions s
+                                          WHERE s.user_id = ua.user_id AND s.status IN ('active', 'grace'))
+                             THEN $5::integer ELSE $6::integer END,
+                        $2::bigint, $3::varchar, $4::boolean
+                 FROM user_analyses ua WHERE ua.id = $1
+                 ON CONFLICT (analysis_id) DO UPDATE SET
+                     status = 'queued',
+                     priority = EXCLUDED.priority,
+                     chat_id = COALESCE(EXCLUDED.chat_id, analysis_jobs.chat_id),
+                     language = COALESCE(EXCLUDED.language, analysis_jobs.language),
+                     allow_low_text = analysis_jobs.allow_low_text OR EXCLUDED.allow_low_text,
+                     attempts = CASE WHEN analysis_jobs.status IN ('queued', 'running')
+                                     THEN analysis_jobs.attempts ELSE 0 END,
+                     leased_by = NULL,
+                     lease_expires_at = NULL,
+                     last_error = NULL,
+                     finished_at = NULL
+                 WHERE $7 OR NOT (analysis_jobs.status = 'running' AND analysis_jobs.lease_expires_at > NOW())
+                 RETURNING id, priority",
+                &[
+                    &analysis_id,
+                    &chat_id,
+                    &language,
+                    &allow_low_text,
+                    &PAID_PRIORITY,
+                    &FREE_PRIORITY,
+                    &take_over_lease,
+                ],
+            )
+            .await?;
+
+        Ok(row.map(|row| {
+            let (job_id, priority): (i32, i32) = (row.get(0), row.get(1));
+            info!(
+                "Queued analysis {} as job {} with priority {}",
+                analysis_id, job_id, priority
+            );
+            job_id
+        }))
+    }
+
+    /// leases the most urgent job of `source` that is queued or whose lease expired
+    pub async fn lease(
+        &self,
+        source: AnalysisSource,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<Option<LeasedJob>, AppError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "WITH next AS (
+                     SELECT j.id FROM analysis_jobs j
+                     JOIN user_analyses ua ON ua.id = j.analysis_id
+                     WHERE ua.source = $1 AND ua.status = 'pending' AND ua.analysis_type IS NOT NULL
+                       AND j.attempts < $4
+                       AND (j.status = 'queued' OR (j.status = 'running' AND j.lease_expires_at < NOW()))
+                     ORDER BY j.priority DESC, j.id
+                     LIMIT 1
+                     FOR UPDATE OF j SKIP LOCKED
+                 )
+                 UPDATE analysis_jobs j
+                 SET status = 'running', leased_by = $2, attempts = j.attempts + 1, started_at = NOW(),
+                     lease_expires_at = NOW() + make_interval(secs => $3)
+                 FROM next, user_analyses ua, users u
+                 WHERE j.id = next.id AND ua.id = j.analysis_id AND u.id = ua.user_id
+                 RETURNING j.id, j.attempts, j.allow_low_text, j.chat_id, j.language,
+                           ua.id, ua.user_id, u.telegram_user_id, ua.channel_name, ua.analysis_type,
+                           ua.language, ua.focus, ua.depth,
+                           EXTRACT(EPOCH FROM NOW() - ua.analysis_timestamp)::float8,
+                           ua.thread_id, ua.topic_name, ua.second_opinion_of",
+                &[
+                    &source.as_str(),
+                    &worker_id,
+                    &lease.as_secs_f64(),
+                    &MAX_JOB_ATTEMPTS,
+                ],
+            )
+            .await?;
+
+        Ok(row.map(|row| LeasedJob {
+            id: row.get(0),
+            attempts: row.get(1),
+            allow_low_text: row.get(2),
+            chat_id: row.get(3),
+            language: row.get(4),
+            analysis: PendingAnalysis {
+                id: row.get(5),
+                user_id: row.get(6),
+                telegram_user_id: row.get(7),
+                channel_name: row.get(8),
+                analysis_type: row.get(9),
+                language: row.get(10),
+                focus: row.get(11),
+                depth: row.get(12),
+                age: Duration::from_secs_f64(row.get::<_, f64>(13).max(0.0)),
+                topic: stored_topic(row.get(14), row.get(15)),
+                second_opinion_of: row.get(16),
+            },
+        }))
+    }
+
+    /// renews the lease; false if the job was taken over or finished meanwhile
+    pub async fn extend_lease(
+        &self,
+        job_id: i32,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, AppError> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE analysis_jobs SET lease_expires_at = NOW() + make_interval(secs => $3)
+                 WHERE id = $1 AND leased_by = $2 AND status = 'running'",
+                &[&job_id, &worker_id, &lease.as_secs_f64()],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    pub async fn complete(&self, job_id: i32) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE analysis_jobs
+                 SET status = 'completed', finished_at = NOW(), leased_by = NULL, lease_expires_at = NULL
+                 WHERE id = $1",
+                &[&job_id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// fails the job and its analysis if that is still pending; failed jobs aren't retried
+    pub async fn fail(&self, job_id: i32, error: &str) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "WITH failed AS (
+                     UPDATE analysis_jobs
+                     SET status = 'failed', last_error = $2, finished_at = NOW(),
+                         leased_by = NULL, lease_expires_at = NULL
+                     WHERE id = $1
+                     RETURNING analysis_id
+                 )
+                 UPDATE user_analyses SET status = 'failed'
+                 WHERE id IN (SELECT analysis_id FROM failed) AND status = 'pending'",
+                &[&job_id, &error],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// cancels the jobs of these analyses that haven't started and fails their analyses;
+    /// returns the analyses that were cancelled
+    pub async fn cancel(&self, analysis_ids: &[i32]) -> Result<Vec<i32>, AppError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "WITH cancelled AS (
+                     UPDATE analysis_jobs
+                     SET status = 'cancelled', finished_at = NOW()
+                     WHERE analysis_id = ANY($1) AND status = 'queued'
+                     RETURNING analysis_id
+                 ), failed AS (
+                     UPDATE user_analyses SET status = 'failed'
+                     WHERE id IN (SELECT analysis_id FROM cancelled) AND status = 'pending'
+                 )
+                 SELECT analysis_id FROM cancelled",
+                &[&analysis_ids],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    /// the status of each of these analyses' jobs
+    pub async fn statuses(&self, analysis_ids: &[i32]) -> Result<Vec<(i32, JobStatus)>, AppError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT analysis_id, status FROM analysis_jobs WHERE analysis_id = ANY($1)",
+                &[&analysis_ids],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let status = JobStatus::from_code(row.get(1))?;
+                Some((row.get(0), status))
+            })
+            .collect())
+    }
+
+    /// number of jobs in each status, for monitoring
+    pub async fn counts(&self) -> Result<Vec<(JobStatus, i64)>, AppError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT status, COUNT(*) FROM analysis_jobs GROUP BY status",
+                &[],
+            )
+            .await?;
+        Ok(JobStatus::ALL
+            .into_iter()
+            .map(|status| {
+                let count = rows
+                    .iter()
+                    .find(|row| row.get::<_, &str>(0) == status.as_str())
+                    .map(|row| row.get(1))
+                    .unwrap_or(0);
+                (status, count)
+            })
+            .collect())
+    }
+
+    /// fails the jobs that ran out of lease attempts and cancels the ones whose analysis
+    /// was settled elsewhere, e.g. abandoned by recovery; returns how many it closed
+    pub async fn sweep(&self) -> Result<u64, AppError> {
+        let client = self.pool.get().await?;
+        let exhausted = client
+            .query(
+                "WITH failed AS (
+                     UPDATE analysis_jobs
+                     SET status = 'failed', finished_at = NOW(), leased_by = NULL, lease_expires_at = NULL,
+                         last_error = COALESCE(last_error, 'lease expired too often')
+                     WHERE attempts >= $1
+                       AND (status = 'queued' OR (status = 'running' AND lease_expires_at < NOW()))
+                     RETURNING analysis_id
+                 ), marked AS (
+                     UPDATE user_analyses SET status = 'failed'
+                     WHERE id IN (SELECT analysis_id FROM failed) AND status = 'pending'
+                 )
+                 SELECT analysis_id FROM failed",
+                &[&MAX_JOB_ATTEMPTS],
+            )
+            .await?;
+        for row in &exhausted {
+            warn!(
+                "Analysis {} failed after {} lease attempts",
+                row.get::<_, i32>(0),
+                MAX_JOB_ATTEMPTS
+            );
+        }
+
+        let orphaned = client
+            .execute(
+                "UPDATE analysis_jobs j
+                 SET status = 'cancelled', finished_at = NOW(), leased_by = NULL, lease_expires_at = NULL
+                 FROM user_analyses ua
+                 WHERE ua.id = j.analysis_id AND ua.status <> 'pending'
+                   AND (j.status = 'queued' OR (j.status = 'running' AND j.lease_expires_at < NOW()))",
+                &[],
+            )
+            .await?;
+        Ok(exhausted.len() as u64 + orphaned)
+    }
+}
+
+/// the lease owner of a runner: unique per process, so a restarted process doesn't renew its
+/// predecessor's leases, and per host with a random part, as replicas in containers usually
+/// all run as pid 1
+pub fn worker_id(source: AnalysisSource) -> String {
+    let host = env::var("HOSTNAME").unwrap_or_else(|_| "local".to_string());
+    let pid = std::process::id();
+    let nonce = hex::encode(rand::thread_rng().gen::<[u8; 4]>());
+    format!("{}-{}-{}-{}", source.as_str(), host, pid, nonce)
+}
+
+/// leases jobs of one front end and runs up to `concurrency` of them at a time
+pub struct JobRunner {
+    queue: Arc<JobQueue>,
+    source: AnalysisSource,
+    worker_id: String,
+    concurrency: usize,
+    lease: Duration,
+}
+
+impl JobRunner {
+    /// `lease` is JOB_LEASE outside of tests, which shorten it to lose one quickly
+    pub fn new(
+        queue: Arc<JobQueue>,
+        source: AnalysisSource,
+        concurrency: usize,
+        lease: Duration,
+    ) -> Self {
+        Self {
+            queue,
+            source,
+            worker_id: worker_id(source),
+            concurrency: concurrency.max(1),
+            lease,
+        }
+    }
+
+    /// runs `handler` for every leased job until the process exits; a job is completed
+    /// when the handler succeeds and failed when it errors or panics, and its handler is
+    /// stopped when the lease can't be renewed because another runner has the job
+    pub async fn run<F, Fut, E>(self, handler: F)
+    where
+        F: Fn(LeasedJob) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: fmt::Display + Send + 'static,
+    {
+        info!(
+            "Running {} analysis jobs as {} ({} at a time)",
+            self.source.as_str(),
+            self.worker_id,
+            self.concurrency
+        );
+        let handler = Arc::new(handler);
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        loop {
+            let permit = permits
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("the semaphore is never closed");
+            match self
+                .queue
+                .lease(self.source, &self.worker_id, self.lease)
+                .await
+            {
+                Ok(Some(job)) => {
+                    tokio::spawn(Self::execute(
+                        self.queue.clone(),
+                        self.worker_id.clone(),
+                        self.lease,
+                        job,
+                        handler.clone(),
+                        permit,
+                    ));
+                }
+                Ok(None) => {
+                    drop(permit);
+                    if let Err(e) = self.queue.sweep().await {
+                        warn!("Failed to sweep analysis jobs: {}", e);
+                    }
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                Err(e) => {
+                    drop(permit);
+                    error!("Failed to lease an analysis job: {}", e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    async fn execute<F, Fut, E>(
+        queue: Arc<JobQueue>,
+        worker_id: String,
+        lease: Duration,
+        job: LeasedJob,
+        handler: Arc<F>,
+        _permit: OwnedSemaphorePermit,
+    ) where
+        F: Fn(LeasedJob) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: fmt::Display + Send + 'static,
+    {
+        let (job_id, analysis_id) = (job.id, job.analysis.id);
+        info!(
+            "Running job {} of analysis {} (attempt {})",
+            job_id, analysis_id, job.attempts
+        );
+
+        // spawned so a panic fails the job instead of leaving it to expire
+        let mut task = tokio::spawn(handler(job));
+        let mut heartbeat = tokio::time::interval(lease / HEARTBEATS_PER_LEASE);
+        heartbeat.tick().await;
+        let result = loop {
+            tokio::select! {
+                result = &mut task => break result,
+                _ = heartbeat.tick() => {
+                    match queue.extend_lease(job_id, &worker_id, lease).await {
+                        Ok(true) => {}
+                        // another runner may have taken the job over, running it on here would
+                        // deliver it twice; its outcome is the new runner's to record
+                        Ok(false) => {
+                            warn!(
+                                "Lost the lease of job {}, stopping analysis {}",
+                                job_id, analysis_id
+                            );
+                            task.abort();
+                            return;
+                        }
+                        Err(e) => warn!("Failed to renew the lease of job {}: {}", job_id, e),
+                    }
+                }
+            }
+        };
+
+        let finished = match result {
+            Ok(Ok(())) => queue.complete(job_id).await,
+            Ok(Err(e)) => queue.fail(job_id, &e.to_string()).await,
+            Err(e) => {
+                error!("Job {} of analysis {} panicked: {}", job_id, analysis_id, e);
+                queue.fail(job_id, "the runner panicked").await
+            }
+        };
+        if let Err(e) = finished {
+            error!("Failed to record the outcome of job {}: {}", job_id, e);
+        }
+    }
+}
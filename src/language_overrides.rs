@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::user_manager::{UserManager, UserManagerError};
+
+// overrides kept before the cache starts over; a miss only costs the query it saves
+const CACHE_LIMIT: usize = 10_000;
+
+/// the bot language each user picked with /language, cached by telegram user id so
+/// resolving the language of an incoming message doesn't query the database; the choice
+/// only changes through `set` and account deletion, which `forget`s it
+pub struct LanguageOverrides {
+    user_manager: Arc<UserManager>,
+    cached: Mutex<HashMap<i64, Option<String>>>,
+}
+
+impl LanguageOverrides {
+    pub fn new(user_manager: Arc<UserManager>) -> Self {
+        Self {
+            user_manager,
+            cached: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// the user's /language choice, None while they keep telegram's language_code
+    pub async fn get(&self, telegram_user_id: i64) -> Result<Option<String>, UserManagerError> {
+        if let Some(language) = self.cached().get(&telegram_user_id) {
+            return Ok(language.clone());
+        }
+        let language = self
+            .user_manager
+            .get_language_override(telegram_user_id)
+            .await?;
+        let mut cached = self.cached();
+        if cached.len() >= CACHE_LIMIT {
+            cached.clear();
+        }
+        // a choice `set` while the query ran is newer than what it read
+        Ok(cached.entry(telegram_user_id).or_insert(language).clone())
+    }
+
+    /// stores the user's choice, None goes back to telegram's language_code
+    pub async fn set(
+        &self,
+        user_id: i32,
+        telegram_user_id: i64,
+        language: Option<&str>,
+    ) -> Result<(), UserManagerError> {
+        self.user_manager
+            .set_language_override(user_id, language)
+            .await?;
+        self.cached()
+            .insert(telegram_user_id, language.map(str::to_string));
+        Ok(())
+    }
+
+    /// drops the cached choice of a user whose account is gone
+    pub fn forget(&self, telegram_user_id: i64) {
+        self.cached().remove(&telegram_user_id);
+    }
+
+    fn cached(&self) -> MutexGuard<'_, HashMap<i64, Option<String>>> {
+        self.cached.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -1,14 +1,53 @@
-pub mod analysis;
-pub mod backend_config;
+// the analysis pipeline lives in tg-analyzer-core; re-exported so bot code keeps its paths
+pub use tg_analyzer_core::{
+    analysis, backend_config, blocklist, cache, circuit_breaker, error, facts, in_flight, llm,
+    mock, prompts, report, retry_budget, session_manager, session_pool, stats, tokens, web_scraper,
+    workers,
+};
+
+pub mod admin;
+pub mod analysis_runner;
+pub mod api;
+pub mod backup;
+pub mod batch;
 pub mod bot;
-pub mod cache;
+pub mod changelog;
+pub mod channel_claims;
+pub mod channel_stats;
+pub mod config;
+pub mod cross_group;
+pub mod data_export;
+pub mod db_health;
+pub mod feedback;
+pub mod flagged_outputs;
+pub mod free_trials;
 pub mod handlers;
-pub mod llm;
+pub mod health;
+pub mod invoice_payload;
+pub mod job_queue;
+pub mod language_overrides;
+pub mod limits;
+pub mod llm_budget;
 pub mod localization;
+pub mod message_queue;
+pub mod metrics;
 pub mod migrations;
-pub mod prompts;
+pub mod observability;
+pub mod packages;
+pub mod privacy;
 pub mod rate_limiters;
-pub mod session_manager;
+pub mod receipts;
+pub mod recovery;
+pub mod referral_flags;
+pub mod referrals;
+pub mod roast_battle;
+pub mod self_analysis;
+pub mod showcase;
+pub mod subscriptions;
+pub mod telegraph;
 pub mod user_manager;
+pub mod user_sessions;
 pub mod utils;
-pub mod web_scraper;
+pub mod voice;
+pub mod warmup;
+pub mod watchdog;
@@ -0,0 +1,275 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::error::Error;
+use std::fmt;
+
+use crate::analysis::AnalysisDepth;
+use crate::config::Settings;
+
+/// names of the tunable limits, as used by limit_overrides rows and LIMIT_* env vars
+pub const LIMIT_NAMES: [&str; 20] = [
+    "small_depth_credits",
+    "medium_depth_credits",
+    "deep_depth_credits",
+    "top_channels",
+    "balance_page_size",
+    "whats_new_entries",
+    "inline_results",
+    "audit_log_entries",
+    "max_audit_log_entries",
+    "max_batch_channels",
+    "subscription_price",
+    "subscription_credits",
+    "user_burst_requests",
+    "user_requests_per_minute",
+    "daily_llm_budget_cents",
+    "owner_free_analyses",
+    "free_trials_per_hour",
+    "max_running_analyses",
+    "analysis_timeout_minutes",
+    "api_key_overlap_hours",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitsError {
+    UnknownLimit(String),
+    InvalidValue(String, i64), // limit name, rejected value
+}
+
+impl fmt::Display for LimitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitsError::UnknownLimit(name) => write!(f, "Unknown limit {}", name),
+            LimitsError::InvalidValue(name, value) => {
+                write!(f, "Invalid value {} for limit {}", value, name)
+            }
+        }
+    }
+}
+
+impl Error for LimitsError {}
+
+/// credit costs, list sizes and other tunables of the bot and the api; the defaults can be
+/// overridden by LIMIT_<NAME> settings and then by rows of the limit_overrides table,
+/// read at startup and again on every config reload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Limits {
+    // credits charged per analysis, by message fetch depth
+    pub small_depth_credits: i32,
+    pub medium_depth_credits: i32,
+    pub deep_depth_credits: i32,
+    // list sizes
+    pub top_channels: i64,
+    pub balance_page_size: i64,
+    pub whats_new_entries: i64,
+    pub inline_results: i64,
+    pub audit_log_entries: i64,
+    pub max_audit_log_entries: i64,
+    // channels one message may ask to analyze
+    pub max_batch_channels: usize,
+    // monthly star subscription
+    pub subscription_price: u32,
+    pub subscription_credits: i32,
+    // requests a user may send at once, and how many more every minute
+    pub user_burst_requests: u32,
+    pub user_requests_per_minute: u32,
+    // estimated llm spend per utc day, in us cents, after which new llm calls are refused
+    pub daily_llm_budget_cents: i64,
+    // analyses of their own channel a verified owner gets for free each month
+    pub owner_free_analyses: i32,
+    // free trials granted per hour to signups that look alike, the rest wait for review
+    pub free_trials_per_hour: i32,
+    // analyses a user may have running at once, further starts are refused until one ends
+    pub max_running_analyses: i32,
+    // analyses pending longer than this are failed by the watchdog, uncharged
+    pub analysis_timeout_minutes: i32,
+    // hours an api key keeps working after /apikey replaced it
+    pub api_key_overlap_hours: i32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            small_depth_credits: 1,
+            medium_depth_credits: 2,
+            deep_depth_credits: 3,
+            top_channels: 10,
+            balance_page_size: 10,
+            whats_new_entries: 5,
+            inline_results: 10,
+            audit_log_entries: 20,
+            max_audit_log_entries: 50,
+            max_batch_channels: 5,
+            subscription_price: 1000,
+            subscription_credits: 30,
+            user_burst_requests: 10,
+            user_requests_per_minute: 20,
+            daily_llm_budget_cents: 5000,
+            owner_free_analyses: 3,
+            free_trials_per_hour: 10,
+            max_running_analyses: 1,
+            analysis_timeout_minutes: 60,
+            api_key_overlap_hours: 24,
+        }
+    }
+}
+
+impl Limits {
+    /// defaults, then CONFIG_FILE and env overrides, then database overrides
+    pub async fn load(pool: &Pool) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::load_with(pool, &Settings::read()?).await
+    }
+
+    pub async fn load_with(
+        pool: &Pool,
+        settings: &Settings,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut limits = Self::default();
+        limits.apply_settings(settings);
+        for (name, value) in Self::load_overrides(pool).await? {
+            limits.apply_override(&name, value, "limit_overrides");
+        }
+        Ok(limits)
+    }
+
+    /// applies every LIMIT_<NAME> setting that is set
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        for name in LIMIT_NAMES {
+            let var = format!("LIMIT_{}", name.to_uppercase());
+            let Some(raw) = settings.get(&var) else {
+                continue;
+            };
+            match raw.trim().parse::<i64>() {
+                Ok(value) => self.apply_override(name, value, &var),
+                Err(_) => warn!("Ignoring {}={}: not a number", var, raw),
+            }
+        }
+    }
+
+    /// a bad override is logged and skipped so it can't keep the bot from starting
+    fn apply_override(&mut self, name: &str, value: i64, source: &str) {
+        match self.set(name, value) {
+            Ok(()) => info!("Limit {} set to {} by {}", name, value, source),
+            Err(e) => warn!("Ignoring override from {}: {}", source, e),
+        }
+    }
+
+    /// sets a limit by name; every limit has to be positive
+    pub fn set(&mut self, name: &str, value: i64) -> Result<(), LimitsError> {
+        let invalid = || LimitsError::InvalidValue(name.to_string(), value);
+        if value <= 0 {
+            return Err(invalid());
+        }
+        let as_u32 = || u32::try_from(value).map_err(|_| invalid());
+        let as_i32 = || i32::try_from(value).map_err(|_| invalid());
+        match name {
+            "small_depth_credits" => self.small_depth_credits = as_i32()?,
+            "medium_depth_credits" => self.medium_depth_credits = as_i32()?,
+            "deep_depth_credits" => self.deep_depth_credits = as_i32()?,
+            "top_channels" => self.top_channels = value,
+            "balance_page_size" => self.balance_page_size = value,
+            "whats_new_entries" => self.whats_new_entries = value,
+            "inline_results" => self.inline_results = value,
+            "audit_log_entries" => self.audit_log_entries = value,
+            "max_audit_log_entries" => self.max_audit_log_entries = value,
+            "max_batch_channels" => {
+                self.max_batch_channels = usize::try_from(value).map_err(|_| invalid())?
+            }
+            "subscription_price" => self.subscription_price = as_u32()?,
+            "subscription_credits" => self.subscription_credits = as_i32()?,
+            "user_burst_requests" => self.user_burst_requests = as_u32()?,
+            "user_requests_per_minute" => self.user_requests_per_minute = as_u32()?,
+            "daily_llm_budget_cents" => self.daily_llm_budget_cents = value,
+            "owner_free_analyses" => self.owner_free_analyses = as_i32()?,
+            "free_trials_per_hour" => self.free_trials_per_hour = as_i32()?,
+            "max_running_analyses" => self.max_running_analyses = as_i32()?,
+            "analysis_timeout_minutes" => self.analysis_timeout_minutes = as_i32()?,
+            "api_key_overlap_hours" => self.api_key_overlap_hours = as_i32()?,
+            _ => return Err(LimitsError::UnknownLimit(name.to_string())),
+        }
+        Ok(())
+    }
+
+    /// every limit by name, in LIMIT_NAMES order
+    pub fn values(&self) -> Vec<(&'static str, i64)> {
+        let values = [
+            i64::from(self.small_depth_credits),
+            i64::from(self.medium_depth_credits),
+            i64::from(self.deep_depth_credits),
+            self.top_channels,
+            self.balance_page_size,
+            self.whats_new_entries,
+            self.inline_results,
+            self.audit_log_entries,
+            self.max_audit_log_entries,
+            self.max_batch_channels as i64,
+            i64::from(self.subscription_price),
+            i64::from(self.subscription_credits),
+            i64::from(self.user_burst_requests),
+            i64::from(self.user_requests_per_minute),
+            self.daily_llm_budget_cents,
+            i64::from(self.owner_free_analyses),
+            i64::from(self.free_trials_per_hour),
+            i64::from(self.max_running_analyses),
+            i64::from(self.analysis_timeout_minutes),
+            i64::from(self.api_key_overlap_hours),
+        ];
+        LIMIT_NAMES.into_iter().zip(values).collect()
+    }
+
+    pub fn depth_credits(&self, depth: AnalysisDepth) -> i32 {
+        match depth {
+            AnalysisDepth::Small => self.small_depth_credits,
+            AnalysisDepth::Medium => self.medium_depth_credits,
+            AnalysisDepth::Deep => self.deep_depth_credits,
+        }
+    }
+
+    /// a second opinion on an analysis costs half of it, rounded up
+    pub fn second_opinion_credits(&self, depth: AnalysisDepth) -> i32 {
+        (self.depth_credits(depth) + 1) / 2
+    }
+
+    /// overrides stored in the database, by limit name
+    pub async fn load_overrides(
+        pool: &Pool,
+    ) -> Result<Vec<(String, i64)>, Box<dyn Error + Send + Sync>> {
+        let client = pool.get().await?;
+        let rows = client
+            .query("SELECT name, value FROM limit_overrides ORDER BY name", &[])
+            .await?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    /// stores an override, taking effect on the next start or config reload
+    pub async fn save_override(
+        pool: &Pool,
+        name: &str,
+        value: i64,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // reject what load() would skip
+        Self::default().set(name, value)?;
+        let client = pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO limit_overrides (name, value) VALUES ($1, $2)
+                 ON CONFLICT (name) DO UPDATE SET value = $2, updated_at = NOW()",
+                &[&name, &value],
+            )
+            .await?;
+        info!("Saved override of limit {}: {}", name, value);
+        Ok(())
+    }
+
+    /// drops an override; false if there was none
+    pub async fn remove_override(
+        pool: &Pool,
+        name: &str,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = pool.get().await?;
+        let removed = client
+            .execute("DELETE FROM limit_overrides WHERE name = $1", &[&name])
+            .await?;
+        Ok(removed > 0)
+    }
+}
@@ -0,0 +1,116 @@
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use crate::admin::AdminManager;
+use crate::cache::read_pool;
+use crate::error::AppError;
+use crate::llm::usage::{daily_spend, today_spend_usd, DailySpend};
+use crate::localization::Lang;
+
+// days shown by /llmcosts without an argument
+pub const DEFAULT_COST_REPORT_DAYS: i32 = 7;
+
+/// guards the daily llm spend: once the estimated cost of today's calls reaches the budget,
+/// analyses that need the llm are refused until midnight utc and the owners are told once
+pub struct LlmBudget {
+    pool: Arc<Pool>,
+    admin: Arc<AdminManager>,
+    daily_budget_cents: AtomicI64,
+}
+
+impl LlmBudget {
+    pub fn new(pool: Arc<Pool>, admin: Arc<AdminManager>, daily_budget_cents: i64) -> Self {
+        Self {
+            pool,
+            admin,
+            daily_budget_cents: AtomicI64::new(daily_budget_cents),
+        }
+    }
+
+    pub fn daily_budget_usd(&self) -> f64 {
+        self.daily_budget_cents.load(Ordering::Relaxed) as f64 / 100.0
+    }
+
+    /// applies to the next llm call on
+    pub fn set_daily_budget_cents(&self, daily_budget_cents: i64) {
+        self.daily_budget_cents
+            .store(daily_budget_cents, Ordering::Relaxed);
+    }
+
+    /// whether a new llm call fits today's budget; a spend that can't be read doesn't
+    /// stop analyses
+    pub async fn allows_llm_call(&self) -> bool {
+        let spend = match today_spend_usd(&self.pool).await {
+            Ok(spend) => spend,
+            Err(e) => {
+                warn!(
+                    "Failed to read today's LLM spend, not enforcing the budget: {}",
+                    e
+                );
+                return true;
+            }
+        };
+        if spend < self.daily_budget_usd() {
+            return true;
+        }
+
+        warn!(
+            "Daily LLM budget of ${:.2} exhausted (${:.2} spent)",
+            self.daily_budget_usd(),
+            spend
+        );
+        if let Err(e) = self.alert_owners(spend).await {
+            error!("Failed to alert owners about the LLM budget: {}", e);
+        }
+        false
+    }
+
+    /// usage of the last `days` days
+    pub async fn report(&self, days: i32) -> Result<Vec<DailySpend>, AppError> {
+        daily_spend(read_pool(&self.pool), days).await
+    }
+
+    /// queues the alert for every owner, once per utc day even across restarts
+    async fn alert_owners(&self, spend: f64) -> Result<(), AppError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        let claimed = transaction
+            .execute(
+                "INSERT INTO llm_budget_alerts (day, spend_usd)
+                 VALUES ((NOW() AT TIME ZONE 'UTC')::date, $1)
+                 ON CONFLICT (day) DO NOTHING",
+                &[&spend],
+            )
+            .await?;
+        if claimed == 0 {
+            return Ok(());
+        }
+
+        let owners = self.admin.owner_ids().await?;
+        let rows = transaction
+            .query(
+                "SELECT owner.id, COALESCE(users.language_override, users.language)
+                 FROM unnest($1::bigint[]) AS owner(id)
+                 LEFT JOIN users ON users.telegram_user_id = owner.id",
+                &[&owners],
+            )
+            .await?;
+        for row in &rows {
+            let telegram_user_id: i64 = row.get(0);
+            let lang = Lang::from_code(row.get::<_, Option<&str>>(1));
+            let message = lang.llm_budget_alert(spend, self.daily_budget_usd());
+            transaction
+                .execute(
+                    "INSERT INTO message_queue (telegram_user_id, message, parse_mode) VALUES ($1, $2, $3)",
+                    &[&telegram_user_id, &message, &"HTML"],
+                )
+                .await?;
+        }
+        transaction.commit().await?;
+
+        info!("Queued the LLM budget alert for {} owners", rows.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,733 @@
+//! admin commands: llm costs, revenue, packages, referral rules and flags, trial clusters,
+//! warm-up, flagged outputs and the blocklist
+use super::Lang;
+use crate::free_trials::TrialCluster;
+use crate::llm::usage::DailySpend;
+use crate::packages::Package;
+use crate::receipts::{RevenueLine, RevenuePeriod};
+use crate::utils::MessageFormatter;
+use crate::warmup::OffPeakWindow;
+
+impl Lang {
+    pub fn llm_costs_report(&self, days: i32, budget_usd: f64, lines: &[String]) -> String {
+        if lines.is_empty() {
+            return match self {
+                Lang::En => format!("💸 No LLM calls in the last {days} days. Daily budget: ${budget_usd:.2}"),
+                Lang::Ru => format!("💸 За последние {days} дн. обращений к LLM не было. Дневной бюджет: ${budget_usd:.2}"),
+                Lang::Uk => format!("💸 За останні {days} дн. звернень до LLM не було. Денний бюджет: ${budget_usd:.2}"),
+                Lang::Es => format!("💸 Sin llamadas al LLM en los últimos {days} días. Presupuesto diario: ${budget_usd:.2}"),
+                Lang::De => format!("💸 Keine LLM-Aufrufe in den letzten {days} Tagen. Tagesbudget: ${budget_usd:.2}"),
+            };
+        }
+        let lines = lines.join("\n");
+        match self {
+            Lang::En => format!(
+                "💸 <b>LLM costs for the last {days} days</b>\nDaily budget: ${budget_usd:.2}\n\n{lines}"
+            ),
+            Lang::Ru => format!(
+                "💸 <b>Расходы на LLM за последние {days} дн.</b>\nДневной бюджет: ${budget_usd:.2}\n\n{lines}"
+            ),
+            Lang::Uk => format!(
+                "💸 <b>Витрати на LLM за останні {days} дн.</b>\nДенний бюджет: ${budget_usd:.2}\n\n{lines}"
+            ),
+            Lang::Es => format!(
+                "💸 <b>Costes del LLM de los últimos {days} días</b>\nPresupuesto diario: ${budget_usd:.2}\n\n{lines}"
+            ),
+            Lang::De => format!(
+                "💸 <b>LLM-Kosten der letzten {days} Tage</b>\nTagesbudget: ${budget_usd:.2}\n\n{lines}"
+            ),
+        }
+    }
+
+    pub fn llm_costs_line(&self, spend: &DailySpend) -> String {
+        let tokens = spend.prompt_tokens + spend.output_tokens;
+        match self {
+            Lang::En => format!(
+                "<code>{}</code>: ${:.2} · {} calls · {} tokens",
+                spend.day, spend.cost_usd, spend.calls, tokens
+            ),
+            Lang::Ru => format!(
+                "<code>{}</code>: ${:.2} · {} вызовов · {} токенов",
+                spend.day, spend.cost_usd, spend.calls, tokens
+            ),
+            Lang::Uk => format!(
+                "<code>{}</code>: ${:.2} · {} викликів · {} токенів",
+                spend.day, spend.cost_usd, spend.calls, tokens
+            ),
+            Lang::Es => format!(
+                "<code>{}</code>: ${:.2} · {} llamadas · {} tokens",
+                spend.day, spend.cost_usd, spend.calls, tokens
+            ),
+            Lang::De => format!(
+                "<code>{}</code>: ${:.2} · {} Aufrufe · {} Tokens",
+                spend.day, spend.cost_usd, spend.calls, tokens
+            ),
+        }
+    }
+
+    pub fn revenue_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/revenue [day|week] [count]</code>",
+            Lang::Ru => "Использование: <code>/revenue [day|week] [количество]</code>",
+            Lang::Uk => "Використання: <code>/revenue [day|week] [кількість]</code>",
+            Lang::Es => "Uso: <code>/revenue [day|week] [cantidad]</code>",
+            Lang::De => "Verwendung: <code>/revenue [day|week] [Anzahl]</code>",
+        }
+    }
+
+    pub fn revenue_report(&self, period: RevenuePeriod, count: i32, lines: &[String]) -> String {
+        let span = match (period, self) {
+            (RevenuePeriod::Day, Lang::En) => format!("the last {count} days"),
+            (RevenuePeriod::Day, Lang::Ru) => format!("последние {count} дн."),
+            (RevenuePeriod::Day, Lang::Uk) => format!("останні {count} дн."),
+            (RevenuePeriod::Day, Lang::Es) => format!("los últimos {count} días"),
+            (RevenuePeriod::Day, Lang::De) => format!("die letzten {count} Tage"),
+            (RevenuePeriod::Week, Lang::En) => format!("the last {count} weeks"),
+            (RevenuePeriod::Week, Lang::Ru) => format!("последние {count} нед."),
+            (RevenuePeriod::Week, Lang::Uk) => format!("останні {count} тиж."),
+            (RevenuePeriod::Week, Lang::Es) => format!("las últimas {count} semanas"),
+            (RevenuePeriod::Week, Lang::De) => format!("die letzten {count} Wochen"),
+        };
+        if lines.is_empty() {
+            return match self {
+                Lang::En => format!("💰 No payments in {span}."),
+                Lang::Ru => format!("💰 За {span} платежей не было."),
+                Lang::Uk => format!("💰 За {span} платежів не було."),
+                Lang::Es => format!("💰 Sin pagos en {span}."),
+                Lang::De => format!("💰 Keine Zahlungen in {span}."),
+            };
+        }
+        let lines = lines.join("\n");
+        match self {
+            Lang::En => format!("💰 <b>Revenue for {span}</b> (UTC, net of refunds)\n\n{lines}"),
+            Lang::Ru => {
+                format!("💰 <b>Выручка за {span}</b> (UTC, за вычетом возвратов)\n\n{lines}")
+            }
+            Lang::Uk => {
+                format!("💰 <b>Виручка за {span}</b> (UTC, за вирахуванням повернень)\n\n{lines}")
+            }
+            Lang::Es => format!("💰 <b>Ingresos de {span}</b> (UTC, sin reembolsos)\n\n{lines}"),
+            Lang::De => {
+                format!("💰 <b>Umsatz für {span}</b> (UTC, abzüglich Erstattungen)\n\n{lines}")
+            }
+        }
+    }
+
+    pub fn revenue_line(&self, line: &RevenueLine) -> String {
+        let (start, net, stars, refunded, payments) = (
+            &line.period_start,
+            line.net_stars,
+            line.stars,
+            line.refunded_stars,
+            line.payments,
+        );
+        match self {
+            Lang::En => format!("<code>{start}</code>: ⭐ {net} (⭐ {stars} − ↩️ {refunded}) · {payments} payments"),
+            Lang::Ru => format!("<code>{start}</code>: ⭐ {net} (⭐ {stars} − ↩️ {refunded}) · {payments} платежей"),
+            Lang::Uk => format!("<code>{start}</code>: ⭐ {net} (⭐ {stars} − ↩️ {refunded}) · {payments} платежів"),
+            Lang::Es => format!("<code>{start}</code>: ⭐ {net} (⭐ {stars} − ↩️ {refunded}) · {payments} pagos"),
+            Lang::De => format!("<code>{start}</code>: ⭐ {net} (⭐ {stars} − ↩️ {refunded}) · {payments} Zahlungen"),
+        }
+    }
+
+    pub fn packages_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/packages [add &lt;credits&gt; &lt;stars&gt;|price &lt;id&gt; &lt;stars&gt;|title &lt;id&gt; [text]|description &lt;id&gt; [text]|enable &lt;id&gt;|disable &lt;id&gt;]</code>\nAn empty text goes back to the translated one.",
+            Lang::Ru => "Использование: <code>/packages [add &lt;кредиты&gt; &lt;звёзды&gt;|price &lt;id&gt; &lt;звёзды&gt;|title &lt;id&gt; [текст]|description &lt;id&gt; [текст]|enable &lt;id&gt;|disable &lt;id&gt;]</code>\nБез текста возвращается переведённый.",
+            Lang::Uk => "Використання: <code>/packages [add &lt;кредити&gt; &lt;зірки&gt;|price &lt;id&gt; &lt;зірки&gt;|title &lt;id&gt; [текст]|description &lt;id&gt; [текст]|enable &lt;id&gt;|disable &lt;id&gt;]</code>\nБез тексту повертається перекладений.",
+            Lang::Es => "Uso: <code>/packages [add &lt;créditos&gt; &lt;estrellas&gt;|price &lt;id&gt; &lt;estrellas&gt;|title &lt;id&gt; [texto]|description &lt;id&gt; [texto]|enable &lt;id&gt;|disable &lt;id&gt;]</code>\nSin texto se vuelve al traducido.",
+            Lang::De => "Verwendung: <code>/packages [add &lt;Credits&gt; &lt;Sterne&gt;|price &lt;id&gt; &lt;Sterne&gt;|title &lt;id&gt; [Text]|description &lt;id&gt; [Text]|enable &lt;id&gt;|disable &lt;id&gt;]</code>\nOhne Text gilt wieder der übersetzte.",
+        }
+    }
+
+    /// `lines` are the packages, formatted by `package_line`
+    pub fn packages_list(&self, lines: &[String]) -> String {
+        let header = match (self, lines.is_empty()) {
+            (Lang::En, true) => "💎 No packages yet.",
+            (Lang::Ru, true) => "💎 Пакетов пока нет.",
+            (Lang::Uk, true) => "💎 Пакетів поки немає.",
+            (Lang::Es, true) => "💎 Todavía no hay paquetes.",
+            (Lang::De, true) => "💎 Noch keine Pakete.",
+            (Lang::En, false) => "💎 <b>Credit packages</b>",
+            (Lang::Ru, false) => "💎 <b>Пакеты кредитов</b>",
+            (Lang::Uk, false) => "💎 <b>Пакети кредитів</b>",
+            (Lang::Es, false) => "💎 <b>Paquetes de créditos</b>",
+            (Lang::De, false) => "💎 <b>Credit-Pakete</b>",
+        };
+        let lines = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        format!("{header}\n\n{lines}{}", self.packages_usage())
+    }
+
+    pub fn package_line(&self, package: &Package) -> String {
+        let status = match (package.active, self) {
+            (true, Lang::En) => "on sale",
+            (true, Lang::Ru) => "в продаже",
+            (true, Lang::Uk) => "у продажу",
+            (true, Lang::Es) => "a la venta",
+            (true, Lang::De) => "im Angebot",
+            (false, Lang::En) => "retired",
+            (false, Lang::Ru) => "снят с продажи",
+            (false, Lang::Uk) => "знято з продажу",
+            (false, Lang::Es) => "retirado",
+            (false, Lang::De) => "eingestellt",
+        };
+        let title = match &package.title {
+            Some(title) => format!(" · «{}»", MessageFormatter::escape_html(title)),
+            None => String::new(),
+        };
+        let (id, credits, stars) = (package.id, package.credits, package.stars);
+        match self {
+            Lang::En => {
+                format!("<code>{id}</code>: {credits} credits · ⭐ {stars} · {status}{title}")
+            }
+            Lang::Ru => {
+                format!("<code>{id}</code>: {credits} кредитов · ⭐ {stars} · {status}{title}")
+            }
+            Lang::Uk => {
+                format!("<code>{id}</code>: {credits} кредитів · ⭐ {stars} · {status}{title}")
+            }
+            Lang::Es => {
+                format!("<code>{id}</code>: {credits} créditos · ⭐ {stars} · {status}{title}")
+            }
+            Lang::De => {
+                format!("<code>{id}</code>: {credits} Credits · ⭐ {stars} · {status}{title}")
+            }
+        }
+    }
+
+    pub fn package_added(&self, package: &Package) -> String {
+        let (id, credits, stars) = (package.id, package.credits, package.stars);
+        match self {
+            Lang::En => format!("💎 Package <code>{id}</code> is on sale: {credits} credits for ⭐ {stars}."),
+            Lang::Ru => format!("💎 Пакет <code>{id}</code> в продаже: {credits} кредитов за ⭐ {stars}."),
+            Lang::Uk => format!("💎 Пакет <code>{id}</code> у продажу: {credits} кредитів за ⭐ {stars}."),
+            Lang::Es => format!("💎 El paquete <code>{id}</code> está a la venta: {credits} créditos por ⭐ {stars}."),
+            Lang::De => format!("💎 Paket <code>{id}</code> ist im Angebot: {credits} Credits für ⭐ {stars}."),
+        }
+    }
+
+    pub fn package_exists(&self, credits: i32) -> String {
+        match self {
+            Lang::En => {
+                format!("💎 There's already a package of {credits} credits; change it instead.")
+            }
+            Lang::Ru => format!("💎 Пакет на {credits} кредитов уже есть, измените его."),
+            Lang::Uk => format!("💎 Пакет на {credits} кредитів уже є, змініть його."),
+            Lang::Es => {
+                format!("💎 Ya hay un paquete de {credits} créditos; modifícalo en su lugar.")
+            }
+            Lang::De => format!(
+                "💎 Es gibt bereits ein Paket mit {credits} Credits; ändere stattdessen dieses."
+            ),
+        }
+    }
+
+    pub fn package_updated(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("💎 Package <code>{id}</code> updated."),
+            Lang::Ru => format!("💎 Пакет <code>{id}</code> обновлён."),
+            Lang::Uk => format!("💎 Пакет <code>{id}</code> оновлено."),
+            Lang::Es => format!("💎 Paquete <code>{id}</code> actualizado."),
+            Lang::De => format!("💎 Paket <code>{id}</code> aktualisiert."),
+        }
+    }
+
+    pub fn package_not_found(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("💎 There's no package <code>{id}</code>."),
+            Lang::Ru => format!("💎 Пакета <code>{id}</code> нет."),
+            Lang::Uk => format!("💎 Пакета <code>{id}</code> немає."),
+            Lang::Es => format!("💎 No existe el paquete <code>{id}</code>."),
+            Lang::De => format!("💎 Es gibt kein Paket <code>{id}</code>."),
+        }
+    }
+
+    pub fn referral_config_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/referralconfig [rule value|rule reset]</code>",
+            Lang::Ru => "Использование: <code>/referralconfig [rule value|rule reset]</code>",
+            Lang::Uk => "Використання: <code>/referralconfig [rule value|rule reset]</code>",
+            Lang::Es => "Uso: <code>/referralconfig [rule value|rule reset]</code>",
+            Lang::De => "Verwendung: <code>/referralconfig [rule value|rule reset]</code>",
+        }
+    }
+
+    /// `lines` are the rules with their current values
+    pub fn referral_config_status(&self, lines: &[String]) -> String {
+        let lines = lines.join("\n");
+        match self {
+            Lang::En => format!(
+                "🎁 <b>Referral rewards</b>\n\n{lines}\n\n{}",
+                self.referral_config_usage()
+            ),
+            Lang::Ru => format!(
+                "🎁 <b>Награды за рефералов</b>\n\n{lines}\n\n{}",
+                self.referral_config_usage()
+            ),
+            Lang::Uk => format!(
+                "🎁 <b>Нагороди за рефералів</b>\n\n{lines}\n\n{}",
+                self.referral_config_usage()
+            ),
+            Lang::Es => format!(
+                "🎁 <b>Recompensas por referidos</b>\n\n{lines}\n\n{}",
+                self.referral_config_usage()
+            ),
+            Lang::De => format!(
+                "🎁 <b>Empfehlungsprämien</b>\n\n{lines}\n\n{}",
+                self.referral_config_usage()
+            ),
+        }
+    }
+
+    pub fn referral_config_updated(&self, rule: &str, value: i64) -> String {
+        match self {
+            Lang::En => format!(
+                "🎁 <code>{rule}</code> set to <b>{value}</b>. It applies from the next referral."
+            ),
+            Lang::Ru => format!(
+                "🎁 <code>{rule}</code> теперь <b>{value}</b>. Действует со следующего реферала."
+            ),
+            Lang::Uk => format!(
+                "🎁 <code>{rule}</code> тепер <b>{value}</b>. Діє з наступного реферала."
+            ),
+            Lang::Es => format!(
+                "🎁 <code>{rule}</code> cambiado a <b>{value}</b>. Se aplica desde el próximo referido."
+            ),
+            Lang::De => format!(
+                "🎁 <code>{rule}</code> auf <b>{value}</b> gesetzt. Gilt ab der nächsten Empfehlung."
+            ),
+        }
+    }
+
+    pub fn referral_config_reset(&self, rule: &str) -> String {
+        match self {
+            Lang::En => format!("🎁 <code>{rule}</code> is back to its default."),
+            Lang::Ru => format!("🎁 <code>{rule}</code> возвращено к значению по умолчанию."),
+            Lang::Uk => format!("🎁 <code>{rule}</code> повернуто до значення за замовчуванням."),
+            Lang::Es => format!("🎁 <code>{rule}</code> vuelve a su valor predeterminado."),
+            Lang::De => format!("🎁 <code>{rule}</code> ist wieder auf dem Standardwert."),
+        }
+    }
+
+    pub fn referral_flags_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/referralflags [clear|confirm &lt;flag id&gt;]</code>",
+            Lang::Ru => {
+                "Использование: <code>/referralflags [clear|confirm &lt;id флага&gt;]</code>"
+            }
+            Lang::Uk => {
+                "Використання: <code>/referralflags [clear|confirm &lt;id прапорця&gt;]</code>"
+            }
+            Lang::Es => "Uso: <code>/referralflags [clear|confirm &lt;id de la marca&gt;]</code>",
+            Lang::De => {
+                "Verwendung: <code>/referralflags [clear|confirm &lt;Markierungs-ID&gt;]</code>"
+            }
+        }
+    }
+
+    /// `flags` are the pending flags, formatted by `referral_flag_entry`
+    pub fn referral_flags_status(&self, flags: &[String]) -> String {
+        let header = match (self, flags.is_empty()) {
+            (Lang::En, true) => "🚩 No referrers are waiting for review.",
+            (Lang::Ru, true) => "🚩 Нет рефереров, ожидающих проверки.",
+            (Lang::Uk, true) => "🚩 Немає реферерів, які очікують перевірки.",
+            (Lang::Es, true) => "🚩 No hay referidores pendientes de revisión.",
+            (Lang::De, true) => "🚩 Keine Werber warten auf eine Prüfung.",
+            (Lang::En, false) => "🚩 <b>Flagged referrers</b>, their milestone rewards are on hold",
+            (Lang::Ru, false) => {
+                "🚩 <b>Подозрительные рефереры</b>, их награды за рубежи приостановлены"
+            }
+            (Lang::Uk, false) => {
+                "🚩 <b>Підозрілі реферери</b>, їхні нагороди за рубежі призупинено"
+            }
+            (Lang::Es, false) => {
+                "🚩 <b>Referidores marcados</b>, sus recompensas por hitos están retenidas"
+            }
+            (Lang::De, false) => {
+                "🚩 <b>Markierte Werber</b>, ihre Meilenstein-Prämien werden zurückgehalten"
+            }
+        };
+        let flags = flags
+            .iter()
+            .map(|flag| format!("{flag}\n"))
+            .collect::<String>();
+        format!("{header}\n\n{flags}{}", self.referral_flags_usage())
+    }
+
+    pub fn referral_flag_entry(
+        &self,
+        id: i32,
+        created_at: &str,
+        referrer_telegram_id: i64,
+        reason: &str,
+        details: &str,
+    ) -> String {
+        // reason and details are recorded in english for every language
+        format!("#{id} · {created_at} · {referrer_telegram_id} · <code>{reason}</code> {details}")
+    }
+
+    pub fn referral_flag_not_found(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("❌ There's no pending flag #{id}."),
+            Lang::Ru => format!("❌ Нет ожидающего проверки флага #{id}."),
+            Lang::Uk => format!("❌ Немає прапорця #{id}, що очікує перевірки."),
+            Lang::Es => format!("❌ No hay ninguna marca pendiente #{id}."),
+            Lang::De => format!("❌ Es gibt keine offene Markierung #{id}."),
+        }
+    }
+
+    pub fn referral_flag_confirmed(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("🚩 Flag #{id} confirmed, the referrer's milestone rewards stay on hold."),
+            Lang::Ru => format!("🚩 Флаг #{id} подтверждён, награды реферера за рубежи остаются приостановленными."),
+            Lang::Uk => format!("🚩 Прапорець #{id} підтверджено, нагороди реферера за рубежі залишаються призупиненими."),
+            Lang::Es => format!("🚩 Marca #{id} confirmada, las recompensas por hitos del referidor siguen retenidas."),
+            Lang::De => format!("🚩 Markierung #{id} bestätigt, die Meilenstein-Prämien des Werbers bleiben zurückgehalten."),
+        }
+    }
+
+    /// `released` are the held credits paid out, None while other flags still hold them
+    pub fn referral_flag_cleared(&self, id: i32, released: Option<i32>) -> String {
+        match (self, released) {
+            (Lang::En, Some(credits)) => format!("✅ Flag #{id} cleared, <b>{credits}</b> held credits paid out."),
+            (Lang::Ru, Some(credits)) => format!("✅ Флаг #{id} снят, начислено приостановленных кредитов: <b>{credits}</b>."),
+            (Lang::Uk, Some(credits)) => format!("✅ Прапорець #{id} знято, нараховано призупинених кредитів: <b>{credits}</b>."),
+            (Lang::Es, Some(credits)) => format!("✅ Marca #{id} retirada, se pagaron <b>{credits}</b> créditos retenidos."),
+            (Lang::De, Some(credits)) => format!("✅ Markierung #{id} aufgehoben, <b>{credits}</b> zurückgehaltene Credits ausgezahlt."),
+            (Lang::En, None) => format!("✅ Flag #{id} cleared, other flags still hold the referrer's rewards."),
+            (Lang::Ru, None) => format!("✅ Флаг #{id} снят, но награды реферера держат другие флаги."),
+            (Lang::Uk, None) => format!("✅ Прапорець #{id} знято, але нагороди реферера тримають інші прапорці."),
+            (Lang::Es, None) => format!("✅ Marca #{id} retirada, otras marcas aún retienen las recompensas del referidor."),
+            (Lang::De, None) => format!("✅ Markierung #{id} aufgehoben, andere Markierungen halten die Prämien des Werbers noch zurück."),
+        }
+    }
+
+    pub fn trial_clusters_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/trialclusters [clear|confirm &lt;cluster id&gt;]</code>",
+            Lang::Ru => {
+                "Использование: <code>/trialclusters [clear|confirm &lt;id кластера&gt;]</code>"
+            }
+            Lang::Uk => {
+                "Використання: <code>/trialclusters [clear|confirm &lt;id кластера&gt;]</code>"
+            }
+            Lang::Es => "Uso: <code>/trialclusters [clear|confirm &lt;id del grupo&gt;]</code>",
+            Lang::De => {
+                "Verwendung: <code>/trialclusters [clear|confirm &lt;Cluster-ID&gt;]</code>"
+            }
+        }
+    }
+
+    /// `clusters` are the pending clusters, formatted by `trial_cluster_entry`
+    pub fn trial_clusters_status(&self, clusters: &[String]) -> String {
+        let header = match (self, clusters.is_empty()) {
+            (Lang::En, true) => "🧪 No signup clusters are waiting for review.",
+            (Lang::Ru, true) => "🧪 Нет кластеров регистраций, ожидающих проверки.",
+            (Lang::Uk, true) => "🧪 Немає кластерів реєстрацій, які очікують перевірки.",
+            (Lang::Es, true) => "🧪 No hay grupos de registros pendientes de revisión.",
+            (Lang::De, true) => "🧪 Keine Anmelde-Cluster warten auf eine Prüfung.",
+            (Lang::En, false) => "🧪 <b>Look-alike signups</b>, their free trials are withheld",
+            (Lang::Ru, false) => "🧪 <b>Похожие регистрации</b>, их бесплатные кредиты задержаны",
+            (Lang::Uk, false) => "🧪 <b>Схожі реєстрації</b>, їхні безкоштовні кредити затримано",
+            (Lang::Es, false) => {
+                "🧪 <b>Registros parecidos</b>, sus pruebas gratuitas están retenidas"
+            }
+            (Lang::De, false) => {
+                "🧪 <b>Ähnliche Anmeldungen</b>, ihre Gratis-Analysen werden zurückgehalten"
+            }
+        };
+        let clusters = clusters
+            .iter()
+            .map(|cluster| format!("{cluster}\n"))
+            .collect::<String>();
+        format!("{header}\n\n{clusters}{}", self.trial_clusters_usage())
+    }
+
+    pub fn trial_cluster_entry(&self, cluster: &TrialCluster) -> String {
+        let (id, withheld, created_at, last_seen_at) = (
+            cluster.id,
+            cluster.withheld_count,
+            &cluster.created_at,
+            &cluster.last_seen_at,
+        );
+        let fingerprint = MessageFormatter::escape_html(&cluster.fingerprint);
+        match self {
+            Lang::En => format!("#{id} · {created_at}–{last_seen_at} · {withheld} withheld · <code>{fingerprint}</code>"),
+            Lang::Ru => format!("#{id} · {created_at}–{last_seen_at} · задержано: {withheld} · <code>{fingerprint}</code>"),
+            Lang::Uk => format!("#{id} · {created_at}–{last_seen_at} · затримано: {withheld} · <code>{fingerprint}</code>"),
+            Lang::Es => format!("#{id} · {created_at}–{last_seen_at} · {withheld} retenidas · <code>{fingerprint}</code>"),
+            Lang::De => format!("#{id} · {created_at}–{last_seen_at} · {withheld} zurückgehalten · <code>{fingerprint}</code>"),
+        }
+    }
+
+    pub fn trial_cluster_not_found(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("❌ There's no pending cluster #{id}."),
+            Lang::Ru => format!("❌ Нет ожидающего проверки кластера #{id}."),
+            Lang::Uk => format!("❌ Немає кластера #{id}, що очікує перевірки."),
+            Lang::Es => format!("❌ No hay ningún grupo pendiente #{id}."),
+            Lang::De => format!("❌ Es gibt keinen offenen Cluster #{id}."),
+        }
+    }
+
+    pub fn trial_cluster_confirmed(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("🧪 Cluster #{id} confirmed, its signups get no free trial from now on."),
+            Lang::Ru => format!("🧪 Кластер #{id} подтверждён, его регистрации больше не получают бесплатный кредит."),
+            Lang::Uk => format!("🧪 Кластер #{id} підтверджено, його реєстрації більше не отримують безкоштовний кредит."),
+            Lang::Es => format!("🧪 Grupo #{id} confirmado, sus registros ya no reciben prueba gratuita."),
+            Lang::De => format!("🧪 Cluster #{id} bestätigt, seine Anmeldungen bekommen keine Gratis-Analyse mehr."),
+        }
+    }
+
+    /// `granted` are the withheld trials given out
+    pub fn trial_cluster_cleared(&self, id: i32, granted: usize) -> String {
+        match self {
+            Lang::En => format!("✅ Cluster #{id} cleared, <b>{granted}</b> withheld trials granted."),
+            Lang::Ru => format!("✅ Кластер #{id} снят, начислено задержанных кредитов: <b>{granted}</b>."),
+            Lang::Uk => format!("✅ Кластер #{id} знято, нараховано затриманих кредитів: <b>{granted}</b>."),
+            Lang::Es => format!("✅ Grupo #{id} aprobado, se concedieron <b>{granted}</b> pruebas retenidas."),
+            Lang::De => format!("✅ Cluster #{id} freigegeben, <b>{granted}</b> zurückgehaltene Gratis-Analysen vergeben."),
+        }
+    }
+
+    pub fn warmup_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/warmup [add|exclude|reset @channel]</code>",
+            Lang::Ru => "Использование: <code>/warmup [add|exclude|reset @channel]</code>",
+            Lang::Uk => "Використання: <code>/warmup [add|exclude|reset @channel]</code>",
+            Lang::Es => "Uso: <code>/warmup [add|exclude|reset @channel]</code>",
+            Lang::De => "Verwendung: <code>/warmup [add|exclude|reset @channel]</code>",
+        }
+    }
+
+    /// `window` is None while the warm-up is disabled; `channels` is tonight's warm list
+    pub fn warmup_status(
+        &self,
+        window: Option<OffPeakWindow>,
+        channels: &[String],
+        pinned: &[String],
+        excluded: &[String],
+    ) -> String {
+        let list = |channels: &[String]| {
+            if channels.is_empty() {
+                "—".to_string()
+            } else {
+                channels.join(", ")
+            }
+        };
+        let (channels, pinned, excluded) = (list(channels), list(pinned), list(excluded));
+        let window = window
+            .map(|window| format!("{:02}:00–{:02}:00 UTC", window.start_hour, window.end_hour));
+        let usage = self.warmup_usage();
+        match (self, window) {
+            (Lang::En, Some(window)) => format!("🔥 <b>Cache warm-up</b> every night {window}\n\nTonight: {channels}\nPinned: {pinned}\nExcluded: {excluded}\n\n{usage}"),
+            (Lang::Ru, Some(window)) => format!("🔥 <b>Прогрев кэша</b> каждую ночь {window}\n\nСегодня: {channels}\nЗакреплены: {pinned}\nИсключены: {excluded}\n\n{usage}"),
+            (Lang::Uk, Some(window)) => format!("🔥 <b>Прогрів кешу</b> щоночі {window}\n\nСьогодні: {channels}\nЗакріплені: {pinned}\nВиключені: {excluded}\n\n{usage}"),
+            (Lang::Es, Some(window)) => format!("🔥 <b>Precarga de caché</b> cada noche {window}\n\nEsta noche: {channels}\nFijados: {pinned}\nExcluidos: {excluded}\n\n{usage}"),
+            (Lang::De, Some(window)) => format!("🔥 <b>Cache-Vorwärmung</b> jede Nacht {window}\n\nHeute Nacht: {channels}\nAngeheftet: {pinned}\nAusgeschlossen: {excluded}\n\n{usage}"),
+            (Lang::En, None) => format!("🔥 <b>Cache warm-up</b> is off, set <code>WARMUP_HOURS</code> to turn it on.\n\nWould warm: {channels}\nPinned: {pinned}\nExcluded: {excluded}\n\n{usage}"),
+            (Lang::Ru, None) => format!("🔥 <b>Прогрев кэша</b> выключен, задайте <code>WARMUP_HOURS</code>, чтобы включить.\n\nБыли бы прогреты: {channels}\nЗакреплены: {pinned}\nИсключены: {excluded}\n\n{usage}"),
+            (Lang::Uk, None) => format!("🔥 <b>Прогрів кешу</b> вимкнено, задайте <code>WARMUP_HOURS</code>, щоб увімкнути.\n\nБули б прогріті: {channels}\nЗакріплені: {pinned}\nВиключені: {excluded}\n\n{usage}"),
+            (Lang::Es, None) => format!("🔥 La <b>precarga de caché</b> está desactivada, define <code>WARMUP_HOURS</code> para activarla.\n\nSe precargarían: {channels}\nFijados: {pinned}\nExcluidos: {excluded}\n\n{usage}"),
+            (Lang::De, None) => format!("🔥 Die <b>Cache-Vorwärmung</b> ist aus, setze <code>WARMUP_HOURS</code>, um sie einzuschalten.\n\nWürde vorwärmen: {channels}\nAngeheftet: {pinned}\nAusgeschlossen: {excluded}\n\n{usage}"),
+        }
+    }
+
+    pub fn warmup_pinned(&self, channel: &str) -> String {
+        match self {
+            Lang::En => format!("🔥 {channel} is warmed every night now."),
+            Lang::Ru => format!("🔥 {channel} теперь прогревается каждую ночь."),
+            Lang::Uk => format!("🔥 {channel} тепер прогрівається щоночі."),
+            Lang::Es => format!("🔥 {channel} se precarga ahora cada noche."),
+            Lang::De => format!("🔥 {channel} wird jetzt jede Nacht vorgewärmt."),
+        }
+    }
+
+    pub fn warmup_excluded(&self, channel: &str) -> String {
+        match self {
+            Lang::En => format!("🔥 {channel} is left out of the warm-up, even while trending."),
+            Lang::Ru => format!("🔥 {channel} исключён из прогрева, даже если он в топе."),
+            Lang::Uk => format!("🔥 {channel} виключено з прогріву, навіть якщо він у топі."),
+            Lang::Es => format!("🔥 {channel} queda fuera de la precarga, aunque sea tendencia."),
+            Lang::De => format!("🔥 {channel} wird nicht vorgewärmt, auch wenn er gefragt ist."),
+        }
+    }
+
+    /// `found` is false when the channel was neither pinned nor excluded
+    pub fn warmup_reset(&self, channel: &str, found: bool) -> String {
+        match (self, found) {
+            (Lang::En, true) => format!("🔥 {channel} is warmed only while trending again."),
+            (Lang::Ru, true) => format!("🔥 {channel} снова прогревается, только когда он в топе."),
+            (Lang::Uk, true) => format!("🔥 {channel} знову прогрівається, лише коли він у топі."),
+            (Lang::Es, true) => {
+                format!("🔥 {channel} vuelve a precargarse solo cuando es tendencia.")
+            }
+            (Lang::De, true) => {
+                format!("🔥 {channel} wird wieder nur vorgewärmt, wenn er gefragt ist.")
+            }
+            (Lang::En, false) => format!("🔥 {channel} was neither pinned nor excluded."),
+            (Lang::Ru, false) => format!("🔥 {channel} не был ни закреплён, ни исключён."),
+            (Lang::Uk, false) => format!("🔥 {channel} не був ні закріплений, ні виключений."),
+            (Lang::Es, false) => format!("🔥 {channel} no estaba ni fijado ni excluido."),
+            (Lang::De, false) => format!("🔥 {channel} war weder angeheftet noch ausgeschlossen."),
+        }
+    }
+
+    pub fn flagged_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/flagged [done &lt;id&gt;]</code>",
+            Lang::Ru => "Использование: <code>/flagged [done &lt;id&gt;]</code>",
+            Lang::Uk => "Використання: <code>/flagged [done &lt;id&gt;]</code>",
+            Lang::Es => "Uso: <code>/flagged [done &lt;id&gt;]</code>",
+            Lang::De => "Verwendung: <code>/flagged [done &lt;ID&gt;]</code>",
+        }
+    }
+
+    /// entries are pre-formatted `flagged_entry`s
+    pub fn flagged_status(&self, entries: &[String]) -> String {
+        let header = match (self, entries.is_empty()) {
+            (Lang::En, true) => "🛡 No flagged analysis passages are waiting for review.",
+            (Lang::Ru, true) => "🛡 Нет помеченных фрагментов анализов, ожидающих проверки.",
+            (Lang::Uk, true) => "🛡 Немає позначених фрагментів аналізів, що очікують перевірки.",
+            (Lang::Es, true) => "🛡 No hay fragmentos de análisis marcados pendientes de revisión.",
+            (Lang::De, true) => "🛡 Keine markierten Analyse-Passagen warten auf eine Prüfung.",
+            (Lang::En, false) => {
+                "🛡 <b>Flagged analysis passages</b>, regenerated or removed before delivery"
+            }
+            (Lang::Ru, false) => {
+                "🛡 <b>Помеченные фрагменты анализов</b>, перегенерированы или удалены до отправки"
+            }
+            (Lang::Uk, false) => {
+                "🛡 <b>Позначені фрагменти аналізів</b>, перегенеровані або видалені до надсилання"
+            }
+            (Lang::Es, false) => {
+                "🛡 <b>Fragmentos de análisis marcados</b>, regenerados o eliminados antes del envío"
+            }
+            (Lang::De, false) => {
+                "🛡 <b>Markierte Analyse-Passagen</b>, vor dem Versand neu erzeugt oder entfernt"
+            }
+        };
+        let entries = entries
+            .iter()
+            .map(|entry| format!("{entry}\n\n"))
+            .collect::<String>();
+        format!("{header}\n\n{entries}{}", self.flagged_usage())
+    }
+
+    /// `channel` and `excerpt` are escaped
+    #[allow(clippy::too_many_arguments)]
+    pub fn flagged_entry(
+        &self,
+        id: i32,
+        created_at: &str,
+        channel: &str,
+        analysis_type: &str,
+        model: Option<&str>,
+        section: &str,
+        category: &str,
+        action: &str,
+        excerpt: &str,
+    ) -> String {
+        // type, section, category and action are stable codes, shared by all languages
+        let model = model.unwrap_or("—");
+        format!(
+            "#{id} · {created_at} · {channel} · {analysis_type} · {model}\n<code>{category}</code> in {section}, {action}: <i>{excerpt}</i>"
+        )
+    }
+
+    pub fn flagged_reviewed(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("✅ Flagged passage #{id} marked as reviewed."),
+            Lang::Ru => format!("✅ Помеченный фрагмент #{id} отмечен как проверенный."),
+            Lang::Uk => format!("✅ Позначений фрагмент #{id} відмічено як перевірений."),
+            Lang::Es => format!("✅ Fragmento marcado #{id} revisado."),
+            Lang::De => format!("✅ Markierte Passage #{id} als geprüft vermerkt."),
+        }
+    }
+
+    pub fn flagged_not_found(&self, id: i32) -> String {
+        match self {
+            Lang::En => format!("❌ There's no unreviewed flagged passage #{id}."),
+            Lang::Ru => format!("❌ Нет непроверенного помеченного фрагмента #{id}."),
+            Lang::Uk => format!("❌ Немає неперевіреного позначеного фрагмента #{id}."),
+            Lang::Es => format!("❌ No hay ningún fragmento marcado sin revisar #{id}."),
+            Lang::De => format!("❌ Es gibt keine ungeprüfte markierte Passage #{id}."),
+        }
+    }
+
+    pub fn blocklist_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/blocklist [add @channel|id [reason]|remove @channel|id]</code>",
+            Lang::Ru => "Использование: <code>/blocklist [add @channel|id [причина]|remove @channel|id]</code>",
+            Lang::Uk => "Використання: <code>/blocklist [add @channel|id [причина]|remove @channel|id]</code>",
+            Lang::Es => "Uso: <code>/blocklist [add @channel|id [motivo]|remove @channel|id]</code>",
+            Lang::De => "Verwendung: <code>/blocklist [add @channel|id [Grund]|remove @channel|id]</code>",
+        }
+    }
+
+    /// entries are pre-formatted `blocklist_entry`s
+    pub fn blocklist_status(&self, entries: &[String]) -> String {
+        let header = match (self, entries.is_empty()) {
+            (Lang::En, true) => "⛔ No channels are excluded from analysis.",
+            (Lang::Ru, true) => "⛔ Ни один канал не исключён из анализа.",
+            (Lang::Uk, true) => "⛔ Жоден канал не виключено з аналізу.",
+            (Lang::Es, true) => "⛔ Ningún canal está excluido del análisis.",
+            (Lang::De, true) => "⛔ Kein Kanal ist von der Analyse ausgeschlossen.",
+            (Lang::En, false) => "⛔ <b>Channels excluded from analysis</b>",
+            (Lang::Ru, false) => "⛔ <b>Каналы, исключённые из анализа</b>",
+            (Lang::Uk, false) => "⛔ <b>Канали, виключені з аналізу</b>",
+            (Lang::Es, false) => "⛔ <b>Canales excluidos del análisis</b>",
+            (Lang::De, false) => "⛔ <b>Von der Analyse ausgeschlossene Kanäle</b>",
+        };
+        let entries = entries
+            .iter()
+            .map(|entry| format!("{entry}\n"))
+            .collect::<String>();
+        format!("{header}\n\n{entries}\n{}", self.blocklist_usage())
+    }
+
+    /// `reason` is escaped
+    pub fn blocklist_entry(
+        &self,
+        target: &str,
+        created_at: &str,
+        blocked_by: i64,
+        reason: Option<&str>,
+    ) -> String {
+        // the reason is kept as the admin wrote it
+        match reason {
+            Some(reason) => format!("{target} · {created_at} · {blocked_by} · {reason}"),
+            None => format!("{target} · {created_at} · {blocked_by}"),
+        }
+    }
+
+    pub fn blocklist_added(&self, target: &str) -> String {
+        match self {
+            Lang::En => format!("⛔ {target} can't be analyzed anymore."),
+            Lang::Ru => format!("⛔ {target} больше нельзя проанализировать."),
+            Lang::Uk => format!("⛔ {target} більше не можна проаналізувати."),
+            Lang::Es => format!("⛔ {target} ya no se puede analizar."),
+            Lang::De => format!("⛔ {target} kann nicht mehr analysiert werden."),
+        }
+    }
+
+    /// `found` is false when the channel wasn't on the blocklist
+    pub fn blocklist_removed(&self, target: &str, found: bool) -> String {
+        match (self, found) {
+            (Lang::En, true) => format!("✅ {target} can be analyzed again."),
+            (Lang::Ru, true) => format!("✅ {target} снова можно анализировать."),
+            (Lang::Uk, true) => format!("✅ {target} знову можна аналізувати."),
+            (Lang::Es, true) => format!("✅ {target} se puede analizar de nuevo."),
+            (Lang::De, true) => format!("✅ {target} kann wieder analysiert werden."),
+            (Lang::En, false) => format!("❌ {target} wasn't excluded from analysis."),
+            (Lang::Ru, false) => format!("❌ {target} не был исключён из анализа."),
+            (Lang::Uk, false) => format!("❌ {target} не було виключено з аналізу."),
+            (Lang::Es, false) => format!("❌ {target} no estaba excluido del análisis."),
+            (Lang::De, false) => format!("❌ {target} war nicht von der Analyse ausgeschlossen."),
+        }
+    }
+}
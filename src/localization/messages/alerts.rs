@@ -0,0 +1,88 @@
+//! alerts sent to the owners
+
+use super::Lang;
+
+impl Lang {
+    pub fn llm_budget_alert(&self, spend_usd: f64, budget_usd: f64) -> String {
+        match self {
+            Lang::En => format!(
+                "🚨 <b>LLM budget exhausted</b>\n\nToday's estimated spend is ${spend_usd:.2} of the ${budget_usd:.2} daily budget. New analyses that need the LLM are refused until midnight UTC, cached results are still served.\n\nTo resume earlier, raise the <code>daily_llm_budget_cents</code> limit and restart the bot."
+            ),
+            Lang::Ru => format!(
+                "🚨 <b>Бюджет на LLM исчерпан</b>\n\nОценка расходов за сегодня: ${spend_usd:.2} из дневного бюджета ${budget_usd:.2}. Новые анализы, которым нужна LLM, отклоняются до полуночи UTC, результаты из кэша по-прежнему выдаются.\n\nЧтобы возобновить раньше, увеличьте лимит <code>daily_llm_budget_cents</code> и перезапустите бота."
+            ),
+            Lang::Uk => format!(
+                "🚨 <b>Бюджет на LLM вичерпано</b>\n\nОцінка витрат за сьогодні: ${spend_usd:.2} із денного бюджету ${budget_usd:.2}. Нові аналізи, яким потрібна LLM, відхиляються до опівночі UTC, результати з кешу й надалі видаються.\n\nЩоб відновити раніше, збільште ліміт <code>daily_llm_budget_cents</code> і перезапустіть бота."
+            ),
+            Lang::Es => format!(
+                "🚨 <b>Presupuesto del LLM agotado</b>\n\nEl gasto estimado de hoy es ${spend_usd:.2} de los ${budget_usd:.2} del presupuesto diario. Los análisis nuevos que necesitan el LLM se rechazan hasta la medianoche UTC; los resultados en caché se siguen sirviendo.\n\nPara reanudar antes, sube el límite <code>daily_llm_budget_cents</code> y reinicia el bot."
+            ),
+            Lang::De => format!(
+                "🚨 <b>LLM-Budget aufgebraucht</b>\n\nDie geschätzten Ausgaben heute liegen bei ${spend_usd:.2} von ${budget_usd:.2} Tagesbudget. Neue Analysen, die das LLM brauchen, werden bis Mitternacht UTC abgelehnt, Ergebnisse aus dem Cache werden weiter ausgeliefert.\n\nUm früher fortzufahren, erhöhe das Limit <code>daily_llm_budget_cents</code> und starte den Bot neu."
+            ),
+        }
+    }
+
+    /// sent to owners when database connections keep failing; `error` comes pre-escaped
+    pub fn db_unreachable_alert(&self, checks: u32, error: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🚨 <b>Database unreachable</b>\n\nThe last {checks} health checks couldn't get a working connection. Idle connections were dropped so the pool reconnects.\n\nLast error: <code>{error}</code>"
+            ),
+            Lang::Ru => format!(
+                "🚨 <b>База данных недоступна</b>\n\nПоследние {checks} проверки не смогли получить рабочее соединение. Простаивающие соединения сброшены, чтобы пул переподключился.\n\nПоследняя ошибка: <code>{error}</code>"
+            ),
+            Lang::Uk => format!(
+                "🚨 <b>База даних недоступна</b>\n\nОстанні {checks} перевірки не змогли отримати робоче з'єднання. Неактивні з'єднання скинуто, щоб пул перепідключився.\n\nОстання помилка: <code>{error}</code>"
+            ),
+            Lang::Es => format!(
+                "🚨 <b>Base de datos inaccesible</b>\n\nLas últimas {checks} comprobaciones no consiguieron una conexión válida. Se descartaron las conexiones inactivas para que el pool se reconecte.\n\nÚltimo error: <code>{error}</code>"
+            ),
+            Lang::De => format!(
+                "🚨 <b>Datenbank nicht erreichbar</b>\n\nDie letzten {checks} Prüfungen haben keine funktionierende Verbindung bekommen. Ungenutzte Verbindungen wurden verworfen, damit der Pool neu verbindet.\n\nLetzter Fehler: <code>{error}</code>"
+            ),
+        }
+    }
+
+    /// sent to owners when getting a database connection keeps being slow
+    pub fn db_slow_alert(&self, checks: u32, seconds: f64) -> String {
+        match self {
+            Lang::En => format!(
+                "⚠️ <b>Database connections are slow</b>\n\nThe last {checks} health checks took up to {seconds:.1}s to get a connection. The pool may be exhausted or the database overloaded."
+            ),
+            Lang::Ru => format!(
+                "⚠️ <b>Соединения с базой данных медленные</b>\n\nПоследним {checks} проверкам требовалось до {seconds:.1} с, чтобы получить соединение. Пул может быть исчерпан или база данных перегружена."
+            ),
+            Lang::Uk => format!(
+                "⚠️ <b>З'єднання з базою даних повільні</b>\n\nОстаннім {checks} перевіркам потрібно було до {seconds:.1} с, щоб отримати з'єднання. Пул може бути вичерпаний або база даних перевантажена."
+            ),
+            Lang::Es => format!(
+                "⚠️ <b>Las conexiones a la base de datos son lentas</b>\n\nLas últimas {checks} comprobaciones tardaron hasta {seconds:.1} s en obtener una conexión. El pool puede estar agotado o la base de datos sobrecargada."
+            ),
+            Lang::De => format!(
+                "⚠️ <b>Datenbankverbindungen sind langsam</b>\n\nDie letzten {checks} Prüfungen brauchten bis zu {seconds:.1} s für eine Verbindung. Der Pool ist womöglich ausgeschöpft oder die Datenbank überlastet."
+            ),
+        }
+    }
+
+    /// sent to owners once the database is healthy again after an alert-worthy problem
+    pub fn db_recovered_alert(&self, checks: u32) -> String {
+        match self {
+            Lang::En => format!(
+                "✅ <b>Database healthy again</b>\n\nConnections work normally after {checks} failed or slow health checks."
+            ),
+            Lang::Ru => format!(
+                "✅ <b>База данных снова в порядке</b>\n\nСоединения работают нормально после {checks} неудачных или медленных проверок."
+            ),
+            Lang::Uk => format!(
+                "✅ <b>База даних знову в порядку</b>\n\nЗ'єднання працюють нормально після {checks} невдалих або повільних перевірок."
+            ),
+            Lang::Es => format!(
+                "✅ <b>La base de datos vuelve a estar bien</b>\n\nLas conexiones funcionan con normalidad tras {checks} comprobaciones fallidas o lentas."
+            ),
+            Lang::De => format!(
+                "✅ <b>Datenbank wieder in Ordnung</b>\n\nVerbindungen funktionieren wieder normal nach {checks} fehlgeschlagenen oder langsamen Prüfungen."
+            ),
+        }
+    }
+}
@@ -0,0 +1,555 @@
+//! the analysis flow: channel input, focus, type choice, private channels and progress
+use super::Lang;
+use crate::analysis::MIN_TEXT_COVERAGE;
+use crate::llm::ModelTier;
+use crate::stats::{format_count, ChannelHealth};
+use crate::web_scraper::ChannelPreview;
+
+impl Lang {
+    pub fn analysis_starting(&self, credits_after: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "🔍 Starting analysis...\n\n\
+                💳 Credits remaining after analysis: <code>{credits_after}</code>"
+            ),
+            Lang::Ru => format!(
+                "🔍 Начинаю анализ...\n\n\
+                💳 Останется кредитов после анализа: <code>{credits_after}</code>"
+            ),
+            Lang::Uk => format!(
+                "🔍 Починаю аналіз...\n\n\
+                💳 Залишиться кредитів після аналізу: <code>{credits_after}</code>"
+            ),
+            Lang::Es => format!(
+                "🔍 Iniciando el análisis...\n\n\
+                💳 Créditos restantes tras el análisis: <code>{credits_after}</code>"
+            ),
+            Lang::De => format!(
+                "🔍 Analyse startet...\n\n\
+                💳 Verbleibende Credits nach der Analyse: <code>{credits_after}</code>"
+            ),
+        }
+    }
+
+    pub fn focus_request(&self, max: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "🎯 <b>Analysis Focus</b>\n\n\
+                Send a short instruction describing what the analysis should pay attention to \
+                (up to {max} characters), e.g. <i>focus on their leadership style</i>."
+            ),
+            Lang::Ru => format!(
+                "🎯 <b>Фокус анализа</b>\n\n\
+                Отправьте короткую инструкцию о том, на что обратить внимание в анализе \
+                (до {max} символов), например: <i>сфокусируйся на стиле руководства</i>."
+            ),
+            Lang::Uk => format!(
+                "🎯 <b>Фокус аналізу</b>\n\n\
+                Надішліть коротку інструкцію про те, на що звернути увагу в аналізі \
+                (до {max} символів), наприклад: <i>зосередься на стилі керівництва</i>."
+            ),
+            Lang::Es => format!(
+                "🎯 <b>Enfoque del análisis</b>\n\n\
+                Envía una instrucción breve sobre en qué debe fijarse el análisis \
+                (hasta {max} caracteres), por ejemplo: <i>céntrate en su estilo de liderazgo</i>."
+            ),
+            Lang::De => format!(
+                "🎯 <b>Analysefokus</b>\n\n\
+                Sende eine kurze Anweisung, worauf die Analyse achten soll \
+                (bis zu {max} Zeichen), z. B. <i>konzentriere dich auf den Führungsstil</i>."
+            ),
+        }
+    }
+
+    pub fn focus_saved(&self, channel_name: &str, focus: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "✅ <b>Focus saved for</b> <code>{channel_name}</code>\n\n\
+                <i>{focus}</i>\n\n\
+                Now choose the type of analysis:"
+            ),
+            Lang::Ru => format!(
+                "✅ <b>Фокус сохранён для</b> <code>{channel_name}</code>\n\n\
+                <i>{focus}</i>\n\n\
+                Теперь выберите тип анализа:"
+            ),
+            Lang::Uk => format!(
+                "✅ <b>Фокус збережено для</b> <code>{channel_name}</code>\n\n\
+                <i>{focus}</i>\n\n\
+                Тепер виберіть тип аналізу:"
+            ),
+            Lang::Es => format!(
+                "✅ <b>Enfoque guardado para</b> <code>{channel_name}</code>\n\n\
+                <i>{focus}</i>\n\n\
+                Ahora elige el tipo de análisis:"
+            ),
+            Lang::De => format!(
+                "✅ <b>Fokus gespeichert für</b> <code>{channel_name}</code>\n\n\
+                <i>{focus}</i>\n\n\
+                Wähle jetzt die Art der Analyse:"
+            ),
+        }
+    }
+
+    pub fn session_expired(&self) -> &'static str {
+        match self {
+            Lang::En => {
+                "⌛ <b>Your session expired</b>\n\n\
+                There was no reply for an hour, so the request was dropped. \
+                Send /start to begin again."
+            }
+            Lang::Ru => {
+                "⌛ <b>Сессия истекла</b>\n\n\
+                Ответа не было час, поэтому запрос сброшен. \
+                Отправьте /start, чтобы начать заново."
+            }
+            Lang::Uk => {
+                "⌛ <b>Сесія завершилася</b>\n\n\
+                Відповіді не було годину, тому запит скасовано. \
+                Надішліть /start, щоб почати знову."
+            }
+            Lang::Es => {
+                "⌛ <b>Tu sesión ha caducado</b>\n\n\
+                No hubo respuesta durante una hora, así que la solicitud se descartó. \
+                Envía /start para empezar de nuevo."
+            }
+            Lang::De => {
+                "⌛ <b>Deine Sitzung ist abgelaufen</b>\n\n\
+                Eine Stunde lang kam keine Antwort, daher wurde die Anfrage verworfen. \
+                Sende /start, um neu zu beginnen."
+            }
+        }
+    }
+
+    pub fn analysis_select_type(&self, channel_name: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🎯 <b>Channel:</b> <code>{channel_name}</code>\n\n\
+                Please choose the type of analysis you'd like to perform:\n\n\
+                ⚠️ <b>Note:</b> Only text content is analyzed. Channels consisting mostly of images or videos may not yield accurate results."
+            ),
+            Lang::Ru => format!(
+                "🎯 <b>Канал:</b> <code>{channel_name}</code>\n\n\
+                Выберите тип анализа:\n\n\
+                ⚠️ <b>Важно:</b> Анализируется только текст. Каналы с фото/видео могут не дать точных результатов."
+            ),
+            Lang::Uk => format!(
+                "🎯 <b>Канал:</b> <code>{channel_name}</code>\n\n\
+                Виберіть тип аналізу:\n\n\
+                ⚠️ <b>Важливо:</b> Аналізується лише текст. Канали з фото/відео можуть не дати точних результатів."
+            ),
+            Lang::Es => format!(
+                "🎯 <b>Canal:</b> <code>{channel_name}</code>\n\n\
+                Elige el tipo de análisis que quieres hacer:\n\n\
+                ⚠️ <b>Nota:</b> Solo se analiza el texto. Los canales formados sobre todo por imágenes o vídeos pueden no dar resultados precisos."
+            ),
+            Lang::De => format!(
+                "🎯 <b>Kanal:</b> <code>{channel_name}</code>\n\n\
+                Wähle die Art der Analyse:\n\n\
+                ⚠️ <b>Hinweis:</b> Es wird nur Text analysiert. Kanäle, die überwiegend aus Bildern oder Videos bestehen, liefern eventuell keine genauen Ergebnisse."
+            ),
+        }
+    }
+
+    /// what the channel's web page shows, above the analysis type selection; `title` is escaped
+    pub fn channel_preview(&self, title: &str, preview: &ChannelPreview) -> String {
+        let (subscribers, last_post, text_posts, unknown) = match self {
+            Lang::En => ("👥 Subscribers", "🗓 Last post", "📝 Text posts", "unknown"),
+            Lang::Ru => (
+                "👥 Подписчиков",
+                "🗓 Последний пост",
+                "📝 Текстовых постов",
+                "неизвестно",
+            ),
+            Lang::Uk => (
+                "👥 Підписників",
+                "🗓 Останній допис",
+                "📝 Текстових дописів",
+                "невідомо",
+            ),
+            Lang::Es => (
+                "👥 Suscriptores",
+                "🗓 Última publicación",
+                "📝 Publicaciones de texto",
+                "desconocido",
+            ),
+            Lang::De => (
+                "👥 Abonnenten",
+                "🗓 Letzter Beitrag",
+                "📝 Textbeiträge",
+                "unbekannt",
+            ),
+        };
+        let mut lines = vec![
+            format!("📋 <b>{title}</b>"),
+            format!(
+                "{subscribers}: {}",
+                preview
+                    .subscribers
+                    .map_or_else(|| unknown.to_string(), |count| count.to_string())
+            ),
+            format!(
+                "{last_post}: {}",
+                preview.last_post_date.as_deref().unwrap_or(unknown)
+            ),
+        ];
+        if let Some(coverage) = preview.text_coverage() {
+            lines.push(match self {
+                Lang::En => format!(
+                    "{text_posts}: ~{coverage}% of the latest {}",
+                    preview.posts_sampled
+                ),
+                Lang::Ru => format!(
+                    "{text_posts}: ~{coverage}% из последних {}",
+                    preview.posts_sampled
+                ),
+                Lang::Uk => format!(
+                    "{text_posts}: ~{coverage}% з останніх {}",
+                    preview.posts_sampled
+                ),
+                Lang::Es => format!(
+                    "{text_posts}: ~{coverage}% de las últimas {}",
+                    preview.posts_sampled
+                ),
+                Lang::De => format!(
+                    "{text_posts}: ~{coverage}% der letzten {}",
+                    preview.posts_sampled
+                ),
+            });
+        }
+        // mostly images or videos leave the analysis little to work with
+        if preview
+            .text_coverage()
+            .is_none_or(|coverage| coverage < MIN_TEXT_COVERAGE)
+        {
+            lines.push(match self {
+                Lang::En => {
+                    "⚠️ This channel has little text, the analysis may not be accurate.".to_string()
+                }
+                Lang::Ru => "⚠️ В канале мало текста, анализ может быть неточным.".to_string(),
+                Lang::Uk => "⚠️ У каналі мало тексту, аналіз може бути неточним.".to_string(),
+                Lang::Es => {
+                    "⚠️ Este canal tiene poco texto, el análisis puede no ser preciso.".to_string()
+                }
+                Lang::De => {
+                    "⚠️ Dieser Kanal hat wenig Text, die Analyse ist eventuell ungenau.".to_string()
+                }
+            });
+        }
+        lines.join("\n")
+    }
+
+    /// the channel health section that follows a channel analysis
+    pub fn channel_health(&self, health: &ChannelHealth) -> String {
+        let posts = health.posts;
+        let mut lines = vec![match self {
+            Lang::En => format!("📈 <b>Channel health</b> (latest {posts} posts)"),
+            Lang::Ru => format!("📈 <b>Здоровье канала</b> (последние {posts} постов)"),
+            Lang::Uk => format!("📈 <b>Стан каналу</b> (останні {posts} дописів)"),
+            Lang::Es => format!("📈 <b>Salud del canal</b> (últimas {posts} publicaciones)"),
+            Lang::De => format!("📈 <b>Kanalzustand</b> (letzte {posts} Beiträge)"),
+        }];
+        if let Some(per_week) = health.posts_per_week {
+            let per_week = format!("{:.1}", per_week);
+            lines.push(match self {
+                Lang::En => format!("🗓 Posts per week: {per_week}"),
+                Lang::Ru => format!("🗓 Постов в неделю: {per_week}"),
+                Lang::Uk => format!("🗓 Дописів на тиждень: {per_week}"),
+                Lang::Es => format!("🗓 Publicaciones por semana: {per_week}"),
+                Lang::De => format!("🗓 Beiträge pro Woche: {per_week}"),
+            });
+        }
+        if let Some(days) = health.days_since_last_post {
+            lines.push(match (self, days) {
+                (Lang::En, 0) => "⏱ Last post: today".to_string(),
+                (Lang::Ru, 0) => "⏱ Последний пост: сегодня".to_string(),
+                (Lang::Uk, 0) => "⏱ Останній допис: сьогодні".to_string(),
+                (Lang::Es, 0) => "⏱ Última publicación: hoy".to_string(),
+                (Lang::De, 0) => "⏱ Letzter Beitrag: heute".to_string(),
+                (Lang::En, _) => format!("⏱ Last post: {days} d ago"),
+                (Lang::Ru, _) => format!("⏱ Последний пост: {days} дн. назад"),
+                (Lang::Uk, _) => format!("⏱ Останній допис: {days} дн. тому"),
+                (Lang::Es, _) => format!("⏱ Última publicación: hace {days} d"),
+                (Lang::De, _) => format!("⏱ Letzter Beitrag: vor {days} T."),
+            });
+        }
+        if let (Some(average), Some(median)) = (health.average_views, health.median_views) {
+            let (average, median) = (format_count(average), format_count(median));
+            lines.push(match self {
+                Lang::En => format!("👁 Views: {average} average, {median} median"),
+                Lang::Ru => format!("👁 Просмотры: {average} в среднем, медиана {median}"),
+                Lang::Uk => format!("👁 Перегляди: {average} в середньому, медіана {median}"),
+                Lang::Es => format!("👁 Vistas: {average} de media, mediana {median}"),
+                Lang::De => format!("👁 Aufrufe: {average} im Schnitt, Median {median}"),
+            });
+        }
+        if let Some(reach) = health.reach_percent {
+            let reach = format!("{:.0}", reach);
+            lines.push(match self {
+                Lang::En => format!("📣 Reach: {reach}% of subscribers see a post"),
+                Lang::Ru => format!("📣 Охват: пост видят {reach}% подписчиков"),
+                Lang::Uk => format!("📣 Охоплення: допис бачать {reach}% підписників"),
+                Lang::Es => format!("📣 Alcance: {reach}% de los suscriptores ven una publicación"),
+                Lang::De => format!("📣 Reichweite: {reach}% der Abonnenten sehen einen Beitrag"),
+            });
+        }
+        if let Some(reactions) = health.reactions_per_thousand_views {
+            let reactions = format!("{:.1}", reactions);
+            lines.push(match self {
+                Lang::En => format!("❤️ Reactions: {reactions} per 1000 views"),
+                Lang::Ru => format!("❤️ Реакции: {reactions} на 1000 просмотров"),
+                Lang::Uk => format!("❤️ Реакції: {reactions} на 1000 переглядів"),
+                Lang::Es => format!("❤️ Reacciones: {reactions} por cada 1000 vistas"),
+                Lang::De => format!("❤️ Reaktionen: {reactions} pro 1000 Aufrufe"),
+            });
+        }
+        lines.join("\n")
+    }
+
+    pub fn private_channel_target(&self) -> &'static str {
+        match self {
+            Lang::En => "private channel",
+            Lang::Ru => "приватный канал",
+            Lang::Uk => "приватний канал",
+            Lang::Es => "canal privado",
+            Lang::De => "privater Kanal",
+        }
+    }
+
+    pub fn invite_expired(&self) -> &'static str {
+        match self {
+            Lang::En => "⌛ This invite link is no longer pending. Please send it again.",
+            Lang::Ru => "⌛ Эта ссылка-приглашение больше не ожидает ответа. Отправьте её ещё раз.",
+            Lang::Uk => {
+                "⌛ Це посилання-запрошення більше не очікує відповіді. Надішліть його ще раз."
+            }
+            Lang::Es => "⌛ Este enlace de invitación ya no está pendiente. Envíalo de nuevo.",
+            Lang::De => {
+                "⌛ Dieser Einladungslink wartet nicht mehr auf Antwort. Bitte sende ihn erneut."
+            }
+        }
+    }
+
+    pub fn invite_consent(&self) -> &'static str {
+        match self {
+            Lang::En => {
+                "🔒 <b>Private channel</b>\n\n\
+                To analyze it, our Telegram account will join the channel with this invite link, \
+                read its recent posts and leave right after the analysis.\n\n\
+                The channel admins will see the account join and leave. If the channel approves \
+                members manually, a join request will be sent instead.\n\n\
+                Only continue if you are allowed to share this channel."
+            }
+            Lang::Ru => {
+                "🔒 <b>Приватный канал</b>\n\n\
+                Для анализа наш Telegram-аккаунт вступит в канал по этой ссылке, \
+                прочитает последние посты и выйдет сразу после анализа.\n\n\
+                Администраторы канала увидят вступление и выход аккаунта. Если в канал \
+                принимают вручную, вместо этого будет отправлена заявка.\n\n\
+                Продолжайте, только если вам разрешено делиться этим каналом."
+            }
+            Lang::Uk => {
+                "🔒 <b>Приватний канал</b>\n\n\
+                Для аналізу наш Telegram-акаунт приєднається до каналу за цим посиланням, \
+                прочитає останні дописи й вийде одразу після аналізу.\n\n\
+                Адміністратори каналу побачать, як акаунт приєднався й вийшов. Якщо до каналу \
+                приймають вручну, натомість буде надіслано заявку.\n\n\
+                Продовжуйте, лише якщо вам дозволено ділитися цим каналом."
+            }
+            Lang::Es => {
+                "🔒 <b>Canal privado</b>\n\n\
+                Para analizarlo, nuestra cuenta de Telegram se unirá al canal con este enlace, \
+                leerá sus publicaciones recientes y saldrá justo después del análisis.\n\n\
+                Los administradores del canal verán que la cuenta entra y sale. Si el canal aprueba \
+                a los miembros manualmente, se enviará una solicitud para unirse.\n\n\
+                Continúa solo si tienes permiso para compartir este canal."
+            }
+            Lang::De => {
+                "🔒 <b>Privater Kanal</b>\n\n\
+                Für die Analyse tritt unser Telegram-Konto dem Kanal über diesen Einladungslink bei, \
+                liest die letzten Beiträge und verlässt ihn direkt nach der Analyse.\n\n\
+                Die Admins des Kanals sehen den Beitritt und das Verlassen. Wenn der Kanal \
+                Mitglieder manuell freigibt, wird stattdessen eine Beitrittsanfrage gesendet.\n\n\
+                Fahre nur fort, wenn du diesen Kanal teilen darfst."
+            }
+        }
+    }
+
+    pub fn analysis_in_progress(&self, analysis_type: &str) -> String {
+        let emoji = self.analysis_emoji(analysis_type);
+        match self {
+            Lang::En => format!(
+                "Starting {} {} analysis... This may take a few minutes.",
+                emoji, analysis_type
+            ),
+            Lang::Ru => format!(
+                "Начинаю {} {} анализ... Это может занять несколько минут.",
+                emoji,
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::Uk => format!(
+                "Починаю {} {} аналіз... Це може зайняти кілька хвилин.",
+                emoji,
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::Es => format!(
+                "Iniciando {} {}... Puede tardar unos minutos.",
+                emoji,
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::De => format!(
+                "Starte {} {}... Das kann einige Minuten dauern.",
+                emoji,
+                self.analysis_type_name(analysis_type)
+            ),
+        }
+    }
+
+    /// progress of a channel too large for one prompt, read in batches before the analysis
+    pub fn analysis_summarizing(&self, done: usize, total: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "📚 This channel is large, reading it in parts first: {done} of {total} done..."
+            ),
+            Lang::Ru => format!(
+                "📚 Канал большой, сначала читаю его по частям: готово {done} из {total}..."
+            ),
+            Lang::Uk => format!(
+                "📚 Канал великий, спершу читаю його частинами: готово {done} з {total}..."
+            ),
+            Lang::Es => format!(
+                "📚 Este canal es grande, primero lo leo por partes: {done} de {total} listas..."
+            ),
+            Lang::De => format!(
+                "📚 Der Kanal ist groß, ich lese ihn zuerst in Teilen: {done} von {total} fertig..."
+            ),
+        }
+    }
+
+    pub fn analysis_complete(
+        &self,
+        analysis_type: &str,
+        user_id: i32,
+        remaining_credits: i32,
+    ) -> String {
+        let type_capitalized = self.analysis_type_capitalized(analysis_type);
+        match self {
+            Lang::En => format!(
+                "✅ <b>{type_capitalized} Analysis Complete!</b> by <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                📊 Your results are ready.\n\
+                💳 Credits remaining: <code>{remaining_credits}</code>"
+            ),
+            Lang::Ru => format!(
+                "✅ <b>{type_capitalized} анализ завершён!</b> от <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                📊 Результаты готовы.\n\
+                💳 Осталось кредитов: <code>{remaining_credits}</code>"
+            ),
+            Lang::Uk => format!(
+                "✅ <b>{type_capitalized} аналіз завершено!</b> від <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                📊 Результати готові.\n\
+                💳 Залишилося кредитів: <code>{remaining_credits}</code>"
+            ),
+            Lang::Es => format!(
+                "✅ <b>¡{type_capitalized} completado!</b> por <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                📊 Tus resultados están listos.\n\
+                💳 Créditos restantes: <code>{remaining_credits}</code>"
+            ),
+            Lang::De => format!(
+                "✅ <b>{type_capitalized} abgeschlossen!</b> von <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                📊 Deine Ergebnisse sind fertig.\n\
+                💳 Verbleibende Credits: <code>{remaining_credits}</code>"
+            ),
+        }
+    }
+
+    pub fn analysis_model_used(&self, tier: ModelTier) -> String {
+        let tier_name = self.model_tier_name(tier);
+        match self {
+            Lang::En => format!("\n🤖 Model tier: {tier_name}"),
+            Lang::Ru => format!("\n🤖 Модель: {tier_name}"),
+            Lang::Uk => format!("\n🤖 Модель: {tier_name}"),
+            Lang::Es => format!("\n🤖 Nivel de modelo: {tier_name}"),
+            Lang::De => format!("\n🤖 Modellstufe: {tier_name}"),
+        }
+    }
+
+    pub fn analysis_removed_posts(&self, count: usize) -> String {
+        match self {
+            Lang::En => format!("\n🗑 Left out {count} posts the author has deleted"),
+            Lang::Ru => format!("\n🗑 Не учтено удалённых автором постов: {count}"),
+            Lang::Uk => format!("\n🗑 Не враховано видалених автором дописів: {count}"),
+            Lang::Es => format!("\n🗑 Se omitieron {count} publicaciones borradas por el autor"),
+            Lang::De => format!("\n🗑 {count} vom Autor gelöschte Beiträge wurden ausgelassen"),
+        }
+    }
+
+    pub fn analysis_profile_note(&self) -> &'static str {
+        match self {
+            Lang::En => "\n👤 This is a personal account, so it got a shorter analysis of its public profile: name, bio and the posts of the channel shown on it",
+            Lang::Ru => "\n👤 Это личный аккаунт, поэтому проведён сокращённый анализ его публичного профиля: имени, описания и постов канала, указанного в профиле",
+            Lang::Uk => "\n👤 Це особистий акаунт, тому проведено скорочений аналіз його публічного профілю: імені, опису й дописів каналу, вказаного в профілі",
+            Lang::Es => "\n👤 Es una cuenta personal, así que recibió un análisis más breve de su perfil público: nombre, biografía y las publicaciones del canal que muestra",
+            Lang::De => "\n👤 Das ist ein persönliches Konto, daher gab es eine kürzere Analyse des öffentlichen Profils: Name, Bio und die Beiträge des dort angezeigten Kanals",
+        }
+    }
+
+    pub fn analysis_resumed(&self, channel_name: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🔄 The bot restarted while analyzing {channel_name}. Picking your analysis back up now, no need to request it again."
+            ),
+            Lang::Ru => format!(
+                "🔄 Бот перезапустился во время анализа {channel_name}. Продолжаю анализ, запрашивать его заново не нужно."
+            ),
+            Lang::Uk => format!(
+                "🔄 Бот перезапустився під час аналізу {channel_name}. Продовжую аналіз, запитувати його знову не потрібно."
+            ),
+            Lang::Es => format!(
+                "🔄 El bot se reinició mientras analizaba {channel_name}. Retomo tu análisis ahora, no hace falta pedirlo de nuevo."
+            ),
+            Lang::De => format!(
+                "🔄 Der Bot wurde während der Analyse von {channel_name} neu gestartet. Deine Analyse wird jetzt fortgesetzt, du musst sie nicht erneut anfordern."
+            ),
+        }
+    }
+
+    pub fn analysis_not_resumed(&self, channel_name: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "😔 Sorry, your analysis of {channel_name} was interrupted by a bot restart and couldn't be resumed. No credits were charged, please request it again."
+            ),
+            Lang::Ru => format!(
+                "😔 Извините, анализ {channel_name} прервался из-за перезапуска бота, и продолжить его не получилось. Кредиты не списаны, запросите анализ ещё раз."
+            ),
+            Lang::Uk => format!(
+                "😔 Вибачте, аналіз {channel_name} перервався через перезапуск бота, і продовжити його не вдалося. Кредити не списано, запитайте аналіз ще раз."
+            ),
+            Lang::Es => format!(
+                "😔 Lo sentimos, tu análisis de {channel_name} se interrumpió por un reinicio del bot y no se pudo retomar. No se cobraron créditos, pídelo de nuevo."
+            ),
+            Lang::De => format!(
+                "😔 Leider wurde deine Analyse von {channel_name} durch einen Neustart des Bots unterbrochen und konnte nicht fortgesetzt werden. Es wurden keine Credits berechnet, bitte fordere sie erneut an."
+            ),
+        }
+    }
+
+    pub fn analysis_timed_out(&self, channel_name: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "⌛ Sorry, your analysis of {channel_name} took too long and was stopped. No credits were charged, please request it again."
+            ),
+            Lang::Ru => format!(
+                "⌛ Извините, анализ {channel_name} занял слишком много времени и был остановлен. Кредиты не списаны, запросите анализ ещё раз."
+            ),
+            Lang::Uk => format!(
+                "⌛ Вибачте, аналіз {channel_name} тривав занадто довго й був зупинений. Кредити не списано, запитайте аналіз ще раз."
+            ),
+            Lang::Es => format!(
+                "⌛ Lo sentimos, tu análisis de {channel_name} tardó demasiado y se detuvo. No se cobraron créditos, pídelo de nuevo."
+            ),
+            Lang::De => format!(
+                "⌛ Leider hat deine Analyse von {channel_name} zu lange gedauert und wurde abgebrochen. Es wurden keine Credits berechnet, bitte fordere sie erneut an."
+            ),
+        }
+    }
+}
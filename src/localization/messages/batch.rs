@@ -0,0 +1,199 @@
+//! batch requests of several channels
+
+use super::Lang;
+
+impl Lang {
+    pub fn batch_select_type(
+        &self,
+        channels: &[String],
+        credits_each: i32,
+        balance: i32,
+    ) -> String {
+        let count = channels.len();
+        let total = credits_each * count as i32;
+        let list = channels
+            .iter()
+            .map(|channel| format!("• <code>{channel}</code>"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        match self {
+            Lang::En => format!(
+                "📚 <b>Batch of {count} channels</b>\n\n{list}\n\n\
+                💳 Cost: {credits_each} credits each, {total} in total (you have {balance}). \
+                Only completed analyses are charged.\n\n\
+                The channels are analyzed one after another. Choose the type of analysis:"
+            ),
+            Lang::Ru => format!(
+                "📚 <b>Пакет из {count} каналов</b>\n\n{list}\n\n\
+                💳 Стоимость: {credits_each} кредитов за канал, всего {total} (у вас {balance}). \
+                Списываются кредиты только за завершённые анализы.\n\n\
+                Каналы анализируются по очереди. Выберите тип анализа:"
+            ),
+            Lang::Uk => format!(
+                "📚 <b>Пакет із {count} каналів</b>\n\n{list}\n\n\
+                💳 Вартість: {credits_each} кредитів за канал, усього {total} (у вас {balance}). \
+                Кредити списуються лише за завершені аналізи.\n\n\
+                Канали аналізуються по черзі. Виберіть тип аналізу:"
+            ),
+            Lang::Es => format!(
+                "📚 <b>Lote de {count} canales</b>\n\n{list}\n\n\
+                💳 Coste: {credits_each} créditos por canal, {total} en total (tienes {balance}). \
+                Solo se cobran los análisis completados.\n\n\
+                Los canales se analizan uno tras otro. Elige el tipo de análisis:"
+            ),
+            Lang::De => format!(
+                "📚 <b>Paket mit {count} Kanälen</b>\n\n{list}\n\n\
+                💳 Kosten: {credits_each} Credits pro Kanal, insgesamt {total} (du hast {balance}). \
+                Berechnet werden nur abgeschlossene Analysen.\n\n\
+                Die Kanäle werden nacheinander analysiert. Wähle die Art der Analyse:"
+            ),
+        }
+    }
+
+    pub fn batch_too_many(&self, max_channels: usize) -> String {
+        match self {
+            Lang::En => format!("❓ You can analyze up to {max_channels} channels at once. Please send fewer channels."),
+            Lang::Ru => format!("❓ За один раз можно проанализировать до {max_channels} каналов. Отправьте меньше каналов."),
+            Lang::Uk => format!("❓ За один раз можна проаналізувати до {max_channels} каналів. Надішліть менше каналів."),
+            Lang::Es => format!("❓ Puedes analizar hasta {max_channels} canales a la vez. Envía menos canales."),
+            Lang::De => format!("❓ Du kannst bis zu {max_channels} Kanäle auf einmal analysieren. Bitte sende weniger Kanäle."),
+        }
+    }
+
+    pub fn batch_invalid_channels(&self, invalid: &[String]) -> String {
+        let invalid = invalid.join(", ");
+        match self {
+            Lang::En => format!("❓ These don't look like channel usernames: {invalid}\n\nSend channels as @channelname or t.me/channelname, separated by spaces, commas or new lines."),
+            Lang::Ru => format!("❓ Это не похоже на имена каналов: {invalid}\n\nОтправьте каналы в виде @channelname или t.me/channelname через пробел, запятую или с новой строки."),
+            Lang::Uk => format!("❓ Це не схоже на імена каналів: {invalid}\n\nНадішліть канали у вигляді @channelname або t.me/channelname через пробіл, кому або з нового рядка."),
+            Lang::Es => format!("❓ Estos no parecen nombres de canal: {invalid}\n\nEnvía los canales como @channelname o t.me/channelname, separados por espacios, comas o saltos de línea."),
+            Lang::De => format!("❓ Das sehen nicht nach Kanalnamen aus: {invalid}\n\nSende Kanäle als @channelname oder t.me/channelname, getrennt durch Leerzeichen, Kommas oder Zeilenumbrüche."),
+        }
+    }
+
+    pub fn batch_expired(&self) -> &'static str {
+        match self {
+            Lang::En => "⌛ This batch is no longer available. Please send the channels again.",
+            Lang::Ru => "⌛ Этот пакет больше недоступен. Отправьте каналы ещё раз.",
+            Lang::Uk => "⌛ Цей пакет більше недоступний. Надішліть канали ще раз.",
+            Lang::Es => "⌛ Este lote ya no está disponible. Envía los canales de nuevo.",
+            Lang::De => "⌛ Dieses Paket ist nicht mehr verfügbar. Bitte sende die Kanäle erneut.",
+        }
+    }
+
+    pub fn not_enough_credits_for_batch(&self, required: i32, available: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "❌ This batch costs {required} credits, but you have {available}.\n\n\
+                Send fewer channels or top up below:"
+            ),
+            Lang::Ru => format!(
+                "❌ Этот пакет стоит {required} кредитов, а у вас {available}.\n\n\
+                Отправьте меньше каналов или пополните баланс:"
+            ),
+            Lang::Uk => format!(
+                "❌ Цей пакет коштує {required} кредитів, а у вас {available}.\n\n\
+                Надішліть менше каналів або поповніть баланс:"
+            ),
+            Lang::Es => format!(
+                "❌ Este lote cuesta {required} créditos, pero tienes {available}.\n\n\
+                Envía menos canales o recarga abajo:"
+            ),
+            Lang::De => format!(
+                "❌ Dieses Paket kostet {required} Credits, du hast aber {available}.\n\n\
+                Sende weniger Kanäle oder lade unten auf:"
+            ),
+        }
+    }
+
+    pub fn batch_progress(
+        &self,
+        analysis_type: &str,
+        done: usize,
+        total: usize,
+        channel_name: &str,
+    ) -> String {
+        let emoji = self.analysis_emoji(analysis_type);
+        match self {
+            Lang::En => format!(
+                "{emoji} <b>Batch analysis:</b> {done}/{total} done\n\
+                ⏳ Now analyzing <code>{channel_name}</code>... This may take a few minutes per channel."
+            ),
+            Lang::Ru => format!(
+                "{emoji} <b>Пакетный анализ:</b> готово {done}/{total}\n\
+                ⏳ Анализирую <code>{channel_name}</code>... Это может занять несколько минут на канал."
+            ),
+            Lang::Uk => format!(
+                "{emoji} <b>Пакетний аналіз:</b> готово {done}/{total}\n\
+                ⏳ Аналізую <code>{channel_name}</code>... Це може зайняти кілька хвилин на канал."
+            ),
+            Lang::Es => format!(
+                "{emoji} <b>Análisis por lotes:</b> {done}/{total} listos\n\
+                ⏳ Analizando <code>{channel_name}</code>... Puede tardar unos minutos por canal."
+            ),
+            Lang::De => format!(
+                "{emoji} <b>Paketanalyse:</b> {done}/{total} fertig\n\
+                ⏳ Analysiere <code>{channel_name}</code>... Das kann pro Kanal einige Minuten dauern."
+            ),
+        }
+    }
+
+    /// `failed` and `skipped` are channel names, already escaped
+    pub fn batch_finished(
+        &self,
+        completed: usize,
+        total: usize,
+        failed: &[String],
+        skipped: &[String],
+    ) -> String {
+        let mut text = match self {
+            Lang::En => format!("📚 <b>Batch analysis finished:</b> {completed}/{total} completed"),
+            Lang::Ru => {
+                format!("📚 <b>Пакетный анализ завершён:</b> выполнено {completed}/{total}")
+            }
+            Lang::Uk => {
+                format!("📚 <b>Пакетний аналіз завершено:</b> виконано {completed}/{total}")
+            }
+            Lang::Es => {
+                format!("📚 <b>Análisis por lotes terminado:</b> {completed}/{total} completados")
+            }
+            Lang::De => {
+                format!("📚 <b>Paketanalyse beendet:</b> {completed}/{total} abgeschlossen")
+            }
+        };
+        if !failed.is_empty() {
+            text.push_str(&match self {
+                Lang::En => format!("\n❌ Failed: {}", failed.join(", ")),
+                Lang::Ru => format!("\n❌ Не удалось: {}", failed.join(", ")),
+                Lang::Uk => format!("\n❌ Не вдалося: {}", failed.join(", ")),
+                Lang::Es => format!("\n❌ Fallidos: {}", failed.join(", ")),
+                Lang::De => format!("\n❌ Fehlgeschlagen: {}", failed.join(", ")),
+            });
+        }
+        if !skipped.is_empty() {
+            text.push_str(&match self {
+                Lang::En => format!("\n⏭ Skipped, out of credits: {}", skipped.join(", ")),
+                Lang::Ru => format!("\n⏭ Пропущено, не хватило кредитов: {}", skipped.join(", ")),
+                Lang::Uk => format!(
+                    "\n⏭ Пропущено, не вистачило кредитів: {}",
+                    skipped.join(", ")
+                ),
+                Lang::Es => format!("\n⏭ Omitidos, sin créditos: {}", skipped.join(", ")),
+                Lang::De => format!(
+                    "\n⏭ Übersprungen, keine Credits mehr: {}",
+                    skipped.join(", ")
+                ),
+            });
+        }
+        if !failed.is_empty() || !skipped.is_empty() {
+            text.push_str(match self {
+                Lang::En => "\n\nYou were only charged for completed analyses.",
+                Lang::Ru => "\n\nКредиты списаны только за завершённые анализы.",
+                Lang::Uk => "\n\nКредити списано лише за завершені аналізи.",
+                Lang::Es => "\n\nSolo se te cobraron los análisis completados.",
+                Lang::De => "\n\nBerechnet wurden nur abgeschlossene Analysen.",
+            });
+        }
+        text
+    }
+}
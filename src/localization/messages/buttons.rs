@@ -0,0 +1,244 @@
+//! inline keyboard buttons shared by several flows
+use super::Lang;
+use crate::analysis::AnalysisDepth;
+use crate::llm::ModelTier;
+use crate::prompts::analysis::OutputLanguage;
+
+impl Lang {
+    pub fn btn_buy_package(&self, credits: i32, price: u32) -> String {
+        match (self, credits == 1) {
+            (Lang::En, true) => format!("💎 Buy {credits} Credit ({price} ⭐)"),
+            (Lang::En, false) => format!("💎 Buy {credits} Credits ({price} ⭐)"),
+            (Lang::Ru, true) => format!("💎 Купить {credits} кредит ({price} ⭐)"),
+            (Lang::Ru, false) => format!("💎 Купить {credits} кредитов ({price} ⭐)"),
+            (Lang::Uk, true) => format!("💎 Купити {credits} кредит ({price} ⭐)"),
+            (Lang::Uk, false) => format!("💎 Купити {credits} кредитів ({price} ⭐)"),
+            (Lang::Es, true) => format!("💎 Comprar {credits} crédito ({price} ⭐)"),
+            (Lang::Es, false) => format!("💎 Comprar {credits} créditos ({price} ⭐)"),
+            (Lang::De, true) => format!("💎 {credits} Credit kaufen ({price} ⭐)"),
+            (Lang::De, false) => format!("💎 {credits} Credits kaufen ({price} ⭐)"),
+        }
+    }
+
+    pub fn btn_verify_human(&self) -> &'static str {
+        match self {
+            Lang::En => "✅ I'm human",
+            Lang::Ru => "✅ Я человек",
+            Lang::Uk => "✅ Я людина",
+            Lang::Es => "✅ Soy humano",
+            Lang::De => "✅ Ich bin ein Mensch",
+        }
+    }
+
+    pub fn btn_subscribe(&self, credits: i32, price: u32) -> String {
+        match self {
+            Lang::En => format!("🔁 Subscribe: {credits} credits a month ({price} ⭐)"),
+            Lang::Ru => format!("🔁 Подписка: {credits} кредитов в месяц ({price} ⭐)"),
+            Lang::Uk => format!("🔁 Підписка: {credits} кредитів на місяць ({price} ⭐)"),
+            Lang::Es => format!("🔁 Suscribirse: {credits} créditos al mes ({price} ⭐)"),
+            Lang::De => format!("🔁 Abonnieren: {credits} Credits im Monat ({price} ⭐)"),
+        }
+    }
+
+    pub fn btn_balance_newer(&self) -> &'static str {
+        match self {
+            Lang::En => "◀️ Newer",
+            Lang::Ru => "◀️ Новее",
+            Lang::Uk => "◀️ Новіші",
+            Lang::Es => "◀️ Más recientes",
+            Lang::De => "◀️ Neuer",
+        }
+    }
+
+    pub fn btn_balance_older(&self) -> &'static str {
+        match self {
+            Lang::En => "Older ▶️",
+            Lang::Ru => "Старее ▶️",
+            Lang::Uk => "Старіші ▶️",
+            Lang::Es => "Más antiguos ▶️",
+            Lang::De => "Älter ▶️",
+        }
+    }
+
+    pub fn btn_announcements(&self, enabled: bool) -> &'static str {
+        match (self, enabled) {
+            (Lang::En, true) => "🔕 Turn off announcements",
+            (Lang::En, false) => "🔔 Turn on announcements",
+            (Lang::Ru, true) => "🔕 Отключить уведомления",
+            (Lang::Ru, false) => "🔔 Включить уведомления",
+            (Lang::Uk, true) => "🔕 Вимкнути сповіщення",
+            (Lang::Uk, false) => "🔔 Увімкнути сповіщення",
+            (Lang::Es, true) => "🔕 Desactivar anuncios",
+            (Lang::Es, false) => "🔔 Activar anuncios",
+            (Lang::De, true) => "🔕 Ankündigungen ausschalten",
+            (Lang::De, false) => "🔔 Ankündigungen einschalten",
+        }
+    }
+
+    pub fn btn_revoke_api_key(&self) -> &'static str {
+        match self {
+            Lang::En => "🗑 Revoke API key",
+            Lang::Ru => "🗑 Отозвать API-ключ",
+            Lang::Uk => "🗑 Відкликати API-ключ",
+            Lang::Es => "🗑 Revocar clave de API",
+            Lang::De => "🗑 API-Schlüssel widerrufen",
+        }
+    }
+
+    pub fn btn_professional_analysis(&self) -> &'static str {
+        match self {
+            Lang::En => "💼 Professional Analysis",
+            Lang::Ru => "💼 Профессиональный анализ",
+            Lang::Uk => "💼 Професійний аналіз",
+            Lang::Es => "💼 Análisis profesional",
+            Lang::De => "💼 Berufsanalyse",
+        }
+    }
+
+    pub fn btn_personal_analysis(&self) -> &'static str {
+        match self {
+            Lang::En => "🧠 Personal Analysis",
+            Lang::Ru => "🧠 Личностный анализ",
+            Lang::Uk => "🧠 Особистісний аналіз",
+            Lang::Es => "🧠 Análisis de personalidad",
+            Lang::De => "🧠 Persönlichkeitsanalyse",
+        }
+    }
+
+    pub fn btn_roast_analysis(&self) -> &'static str {
+        match self {
+            Lang::En => "🔥 Roast Analysis",
+            Lang::Ru => "🔥 Роаст-анализ",
+            Lang::Uk => "🔥 Роаст-аналіз",
+            Lang::Es => "🔥 Roast",
+            Lang::De => "🔥 Roast-Analyse",
+        }
+    }
+
+    pub fn btn_trends_analysis(&self) -> &'static str {
+        match self {
+            Lang::En => "📈 Trends Analysis",
+            Lang::Ru => "📈 Анализ трендов",
+            Lang::Uk => "📈 Аналіз трендів",
+            Lang::Es => "📈 Análisis de tendencias",
+            Lang::De => "📈 Trendanalyse",
+        }
+    }
+
+    pub fn btn_analyze_anyway(&self) -> &'static str {
+        match self {
+            Lang::En => "▶️ Analyze anyway",
+            Lang::Ru => "▶️ Всё равно анализировать",
+            Lang::Uk => "▶️ Все одно аналізувати",
+            Lang::Es => "▶️ Analizar de todos modos",
+            Lang::De => "▶️ Trotzdem analysieren",
+        }
+    }
+
+    pub fn btn_invite_consent(&self) -> &'static str {
+        match self {
+            Lang::En => "✅ Join and analyze",
+            Lang::Ru => "✅ Вступить и проанализировать",
+            Lang::Uk => "✅ Приєднатися й проаналізувати",
+            Lang::Es => "✅ Unirse y analizar",
+            Lang::De => "✅ Beitreten und analysieren",
+        }
+    }
+
+    pub fn btn_regenerate_free(&self) -> &'static str {
+        match self {
+            Lang::En => "🔁 Regenerate for free",
+            Lang::Ru => "🔁 Пересоздать бесплатно",
+            Lang::Uk => "🔁 Перегенерувати безкоштовно",
+            Lang::Es => "🔁 Regenerar gratis",
+            Lang::De => "🔁 Kostenlos neu erstellen",
+        }
+    }
+
+    pub fn btn_add_focus(&self) -> &'static str {
+        match self {
+            Lang::En => "🎯 Add Focus",
+            Lang::Ru => "🎯 Задать фокус",
+            Lang::Uk => "🎯 Задати фокус",
+            Lang::Es => "🎯 Añadir enfoque",
+            Lang::De => "🎯 Fokus setzen",
+        }
+    }
+
+    pub fn btn_depth(&self, depth: AnalysisDepth, credits: i32, selected: bool) -> String {
+        let name = self.depth_name(depth);
+        let marker = if selected { "✅ " } else { "" };
+        format!("{marker}{name} ({credits}💳)")
+    }
+
+    pub fn depth_name(&self, depth: AnalysisDepth) -> &'static str {
+        match (self, depth) {
+            (Lang::En, AnalysisDepth::Small) => "Quick",
+            (Lang::En, AnalysisDepth::Medium) => "Medium",
+            (Lang::En, AnalysisDepth::Deep) => "Deep",
+            (Lang::Ru, AnalysisDepth::Small) => "Быстрый",
+            (Lang::Ru, AnalysisDepth::Medium) => "Средний",
+            (Lang::Ru, AnalysisDepth::Deep) => "Глубокий",
+            (Lang::Uk, AnalysisDepth::Small) => "Швидкий",
+            (Lang::Uk, AnalysisDepth::Medium) => "Середній",
+            (Lang::Uk, AnalysisDepth::Deep) => "Глибокий",
+            (Lang::Es, AnalysisDepth::Small) => "Rápido",
+            (Lang::Es, AnalysisDepth::Medium) => "Medio",
+            (Lang::Es, AnalysisDepth::Deep) => "Profundo",
+            (Lang::De, AnalysisDepth::Small) => "Schnell",
+            (Lang::De, AnalysisDepth::Medium) => "Mittel",
+            (Lang::De, AnalysisDepth::Deep) => "Tief",
+        }
+    }
+
+    pub fn model_tier_name(&self, tier: ModelTier) -> &'static str {
+        match (self, tier) {
+            (Lang::En, ModelTier::Auto) => "⚖️ Auto",
+            (Lang::En, ModelTier::Fast) => "⚡ Fast",
+            (Lang::En, ModelTier::Quality) => "💎 Quality",
+            (Lang::Ru, ModelTier::Auto) => "⚖️ Авто",
+            (Lang::Ru, ModelTier::Fast) => "⚡ Быстро",
+            (Lang::Ru, ModelTier::Quality) => "💎 Качество",
+            (Lang::Uk, ModelTier::Auto) => "⚖️ Авто",
+            (Lang::Uk, ModelTier::Fast) => "⚡ Швидко",
+            (Lang::Uk, ModelTier::Quality) => "💎 Якість",
+            (Lang::Es, ModelTier::Auto) => "⚖️ Automático",
+            (Lang::Es, ModelTier::Fast) => "⚡ Rápido",
+            (Lang::Es, ModelTier::Quality) => "💎 Calidad",
+            (Lang::De, ModelTier::Auto) => "⚖️ Automatisch",
+            (Lang::De, ModelTier::Fast) => "⚡ Schnell",
+            (Lang::De, ModelTier::Quality) => "💎 Qualität",
+        }
+    }
+
+    pub fn output_language_name(&self, language: OutputLanguage) -> &'static str {
+        match (self, language) {
+            (Lang::En, OutputLanguage::Channel) => "📢 Channel's language",
+            (Lang::En, OutputLanguage::English) => "🇬🇧 English",
+            (Lang::En, OutputLanguage::Russian) => "🇷🇺 Russian",
+            (Lang::Ru, OutputLanguage::Channel) => "📢 Язык канала",
+            (Lang::Ru, OutputLanguage::English) => "🇬🇧 Английский",
+            (Lang::Ru, OutputLanguage::Russian) => "🇷🇺 Русский",
+            (Lang::Uk, OutputLanguage::Channel) => "📢 Мова каналу",
+            (Lang::Uk, OutputLanguage::English) => "🇬🇧 Англійська",
+            (Lang::Uk, OutputLanguage::Russian) => "🇷🇺 Російська",
+            (Lang::Es, OutputLanguage::Channel) => "📢 Idioma del canal",
+            (Lang::Es, OutputLanguage::English) => "🇬🇧 Inglés",
+            (Lang::Es, OutputLanguage::Russian) => "🇷🇺 Ruso",
+            (Lang::De, OutputLanguage::Channel) => "📢 Sprache des Kanals",
+            (Lang::De, OutputLanguage::English) => "🇬🇧 Englisch",
+            (Lang::De, OutputLanguage::Russian) => "🇷🇺 Russisch",
+        }
+    }
+
+    /// the interface language choice that follows the Telegram app's language
+    pub fn ui_language_auto_name(&self) -> &'static str {
+        match self {
+            Lang::En => "📱 Telegram's language",
+            Lang::Ru => "📱 Язык Telegram",
+            Lang::Uk => "📱 Мова Telegram",
+            Lang::Es => "📱 Idioma de Telegram",
+            Lang::De => "📱 Telegram-Sprache",
+        }
+    }
+}
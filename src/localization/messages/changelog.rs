@@ -0,0 +1,129 @@
+//! /whatsnew and release announcements
+
+use super::Lang;
+
+impl Lang {
+    pub fn changelog_entry(&self, date: &str, title: &str, body: &str) -> String {
+        format!("🆕 <b>{title}</b> <i>({date})</i>\n{body}")
+    }
+
+    /// shown once; the key is only stored hashed. `overlap_hours` is set when a previous
+    /// key keeps working that long
+    pub fn api_key_created(&self, key: &str, overlap_hours: Option<i32>) -> String {
+        let previous = match (self, overlap_hours) {
+            (_, None) => String::new(),
+            (Lang::En, Some(hours)) => format!(
+                " Your previous key keeps working for {hours} more hours, switch your clients to the new one before then."
+            ),
+            (Lang::Ru, Some(hours)) => format!(
+                " Предыдущий ключ будет работать ещё {hours} ч., переключите на новый свои клиенты до этого."
+            ),
+            (Lang::Uk, Some(hours)) => format!(
+                " Попередній ключ працюватиме ще {hours} год., переведіть свої клієнти на новий до того часу."
+            ),
+            (Lang::Es, Some(hours)) => format!(
+                " Tu clave anterior seguirá funcionando {hours} horas más, cambia tus clientes a la nueva antes de que caduque."
+            ),
+            (Lang::De, Some(hours)) => format!(
+                " Dein bisheriger Schlüssel funktioniert noch {hours} Stunden, stelle deine Clients bis dahin auf den neuen um."
+            ),
+        };
+        match self {
+            Lang::En => format!(
+                "🔑 <b>Your API key</b>\n\n<code>{key}</code>\n\nSend it as <code>Authorization: Bearer &lt;key&gt;</code> to <code>POST /analyses</code> and <code>GET /analyses/&lt;id&gt;</code>. API analyses are paid from your credit balance.\n\n⚠️ The key is shown only once.{previous}"
+            ),
+            Lang::Ru => format!(
+                "🔑 <b>Ваш API-ключ</b>\n\n<code>{key}</code>\n\nПередавайте его как <code>Authorization: Bearer &lt;ключ&gt;</code> в <code>POST /analyses</code> и <code>GET /analyses/&lt;id&gt;</code>. Анализы через API оплачиваются кредитами с вашего баланса.\n\n⚠️ Ключ показывается только один раз.{previous}"
+            ),
+            Lang::Uk => format!(
+                "🔑 <b>Ваш API-ключ</b>\n\n<code>{key}</code>\n\nПередавайте його як <code>Authorization: Bearer &lt;ключ&gt;</code> у <code>POST /analyses</code> і <code>GET /analyses/&lt;id&gt;</code>. Аналізи через API оплачуються кредитами з вашого балансу.\n\n⚠️ Ключ показується лише один раз.{previous}"
+            ),
+            Lang::Es => format!(
+                "🔑 <b>Tu clave de API</b>\n\n<code>{key}</code>\n\nEnvíala como <code>Authorization: Bearer &lt;clave&gt;</code> a <code>POST /analyses</code> y <code>GET /analyses/&lt;id&gt;</code>. Los análisis por API se pagan con tu saldo de créditos.\n\n⚠️ La clave se muestra solo una vez.{previous}"
+            ),
+            Lang::De => format!(
+                "🔑 <b>Dein API-Schlüssel</b>\n\n<code>{key}</code>\n\nSende ihn als <code>Authorization: Bearer &lt;Schlüssel&gt;</code> an <code>POST /analyses</code> und <code>GET /analyses/&lt;id&gt;</code>. API-Analysen werden von deinem Credit-Guthaben bezahlt.\n\n⚠️ Der Schlüssel wird nur einmal angezeigt.{previous}"
+            ),
+        }
+    }
+
+    pub fn api_keys_revoked(&self) -> &'static str {
+        match self {
+            Lang::En => "🗑 Your API key has been revoked. Use /apikey to issue a new one.",
+            Lang::Ru => "🗑 Ваш API-ключ отозван. Используйте /apikey, чтобы получить новый.",
+            Lang::Uk => "🗑 Ваш API-ключ відкликано. Використайте /apikey, щоб отримати новий.",
+            Lang::Es => "🗑 Tu clave de API ha sido revocada. Usa /apikey para obtener una nueva.",
+            Lang::De => {
+                "🗑 Dein API-Schlüssel wurde widerrufen. Mit /apikey erhältst du einen neuen."
+            }
+        }
+    }
+
+    pub fn api_key_private_only(&self) -> &'static str {
+        match self {
+            Lang::En => "🔒 API keys are only issued in a private chat with the bot.",
+            Lang::Ru => "🔒 API-ключи выдаются только в личном чате с ботом.",
+            Lang::Uk => "🔒 API-ключі видаються лише в особистому чаті з ботом.",
+            Lang::Es => "🔒 Las claves de API solo se emiten en un chat privado con el bot.",
+            Lang::De => "🔒 API-Schlüssel werden nur im privaten Chat mit dem Bot ausgestellt.",
+        }
+    }
+
+    pub fn whats_new(&self, entries: &[String], announcements_enabled: bool) -> String {
+        let footer = match (self, announcements_enabled) {
+            (Lang::En, true) => "🔔 You'll get a message when a major feature ships.",
+            (Lang::En, false) => "🔕 Announcements of major features are turned off.",
+            (Lang::Ru, true) => "🔔 Мы напишем вам, когда выйдет крупное обновление.",
+            (Lang::Ru, false) => "🔕 Уведомления о крупных обновлениях отключены.",
+            (Lang::Uk, true) => "🔔 Ми напишемо вам, коли вийде велике оновлення.",
+            (Lang::Uk, false) => "🔕 Сповіщення про великі оновлення вимкнено.",
+            (Lang::Es, true) => "🔔 Te escribiremos cuando salga una función importante.",
+            (Lang::Es, false) => "🔕 Los anuncios de funciones importantes están desactivados.",
+            (Lang::De, true) => "🔔 Du bekommst eine Nachricht, wenn ein großes Feature erscheint.",
+            (Lang::De, false) => "🔕 Ankündigungen großer Features sind ausgeschaltet.",
+        };
+        if entries.is_empty() {
+            return match self {
+                Lang::En => format!("📰 <b>What's New</b>\n\nNothing here yet.\n\n{footer}"),
+                Lang::Ru => format!("📰 <b>Что нового</b>\n\nПока здесь пусто.\n\n{footer}"),
+                Lang::Uk => format!("📰 <b>Що нового</b>\n\nПоки що тут порожньо.\n\n{footer}"),
+                Lang::Es => format!("📰 <b>Novedades</b>\n\nTodavía no hay nada aquí.\n\n{footer}"),
+                Lang::De => format!("📰 <b>Was ist neu</b>\n\nHier ist noch nichts.\n\n{footer}"),
+            };
+        }
+
+        let entries = entries.join("\n\n");
+        match self {
+            Lang::En => format!("📰 <b>What's New</b>\n\n{entries}\n\n{footer}"),
+            Lang::Ru => format!("📰 <b>Что нового</b>\n\n{entries}\n\n{footer}"),
+            Lang::Uk => format!("📰 <b>Що нового</b>\n\n{entries}\n\n{footer}"),
+            Lang::Es => format!("📰 <b>Novedades</b>\n\n{entries}\n\n{footer}"),
+            Lang::De => format!("📰 <b>Was ist neu</b>\n\n{entries}\n\n{footer}"),
+        }
+    }
+
+    pub fn changelog_announcement(&self, entry: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "📣 <b>New in @ScratchAuthorEgoBot</b>\n\n{entry}\n\n\
+                <i>Turn these messages off with /whatsnew.</i>"
+            ),
+            Lang::Ru => format!(
+                "📣 <b>Новое в @ScratchAuthorEgoBot</b>\n\n{entry}\n\n\
+                <i>Отключить такие сообщения можно в /whatsnew.</i>"
+            ),
+            Lang::Uk => format!(
+                "📣 <b>Нове в @ScratchAuthorEgoBot</b>\n\n{entry}\n\n\
+                <i>Вимкнути такі повідомлення можна в /whatsnew.</i>"
+            ),
+            Lang::Es => format!(
+                "📣 <b>Novedades en @ScratchAuthorEgoBot</b>\n\n{entry}\n\n\
+                <i>Desactiva estos mensajes con /whatsnew.</i>"
+            ),
+            Lang::De => format!(
+                "📣 <b>Neu in @ScratchAuthorEgoBot</b>\n\n{entry}\n\n\
+                <i>Schalte diese Nachrichten mit /whatsnew aus.</i>"
+            ),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! channel ownership claims
+
+use super::Lang;
+
+impl Lang {
+    pub fn claim_private_only(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /claim works in a private chat with the bot.",
+            Lang::Ru => "ℹ️ /claim работает в личном чате с ботом.",
+            Lang::Uk => "ℹ️ /claim працює в особистому чаті з ботом.",
+            Lang::Es => "ℹ️ /claim funciona en un chat privado con el bot.",
+            Lang::De => "ℹ️ /claim funktioniert im privaten Chat mit dem Bot.",
+        }
+    }
+
+    pub fn claim_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/claim @channel</code>\n\nProve that you run a public channel to exclude it from analysis or to analyze it for free.",
+            Lang::Ru => "Использование: <code>/claim @channel</code>\n\nПодтвердите, что ведёте публичный канал, чтобы исключить его из анализа или анализировать его бесплатно.",
+            Lang::Uk => "Використання: <code>/claim @channel</code>\n\nПідтвердьте, що ведете публічний канал, щоб виключити його з аналізу або аналізувати його безкоштовно.",
+            Lang::Es => "Uso: <code>/claim @channel</code>\n\nDemuestra que llevas un canal público para excluirlo del análisis o analizarlo gratis.",
+            Lang::De => "Verwendung: <code>/claim @channel</code>\n\nBeweise, dass du einen öffentlichen Kanal betreibst, um ihn von der Analyse auszuschließen oder kostenlos zu analysieren.",
+        }
+    }
+
+    pub fn claim_taken(&self, channel: &str) -> String {
+        match self {
+            Lang::En => format!("🔒 {channel} has already been claimed by its owner."),
+            Lang::Ru => format!("🔒 Владелец {channel} уже подтвердил права на канал."),
+            Lang::Uk => format!("🔒 Власник {channel} уже підтвердив права на канал."),
+            Lang::Es => format!("🔒 El propietario de {channel} ya lo ha reclamado."),
+            Lang::De => format!("🔒 {channel} wurde bereits von seinem Inhaber beansprucht."),
+        }
+    }
+
+    pub fn claim_instructions(&self, channel: &str, code: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🔑 <b>Claim {channel}</b>\n\nProve that you run the channel in one of two ways:\n\n1. Publish a post containing <code>{code}</code> in {channel}.\n2. Or add the bot to the channel's admins for a moment.\n\nThen press the button below. The post or the admin rights can be removed once the channel is verified."
+            ),
+            Lang::Ru => format!(
+                "🔑 <b>Подтверждение {channel}</b>\n\nПодтвердите, что ведёте канал, одним из двух способов:\n\n1. Опубликуйте в {channel} пост с кодом <code>{code}</code>.\n2. Или ненадолго добавьте бота в администраторы канала.\n\nЗатем нажмите кнопку ниже. После подтверждения пост или права администратора можно удалить."
+            ),
+            Lang::Uk => format!(
+                "🔑 <b>Підтвердження {channel}</b>\n\nПідтвердьте, що ведете канал, одним із двох способів:\n\n1. Опублікуйте в {channel} пост із кодом <code>{code}</code>.\n2. Або ненадовго додайте бота до адміністраторів каналу.\n\nПотім натисніть кнопку нижче. Після підтвердження пост або права адміністратора можна видалити."
+            ),
+            Lang::Es => format!(
+                "🔑 <b>Reclamar {channel}</b>\n\nDemuestra que llevas el canal de una de estas dos formas:\n\n1. Publica en {channel} una publicación con <code>{code}</code>.\n2. O añade el bot a los administradores del canal por un momento.\n\nLuego pulsa el botón de abajo. Una vez verificado el canal, puedes borrar la publicación o quitar los permisos de administrador."
+            ),
+            Lang::De => format!(
+                "🔑 <b>{channel} beanspruchen</b>\n\nBeweise auf eine von zwei Arten, dass du den Kanal betreibst:\n\n1. Veröffentliche in {channel} einen Beitrag mit <code>{code}</code>.\n2. Oder füge den Bot kurz zu den Administratoren des Kanals hinzu.\n\nDrücke dann den Button unten. Sobald der Kanal bestätigt ist, kannst du den Beitrag oder die Adminrechte wieder entfernen."
+            ),
+        }
+    }
+
+    pub fn claim_not_verified(&self, channel: &str, code: &str) -> String {
+        match self {
+            Lang::En => format!("❌ Couldn't verify {channel}: <code>{code}</code> isn't among its latest posts and you aren't among the admins the bot can see. Publish the code or add the bot as an admin, then try again."),
+            Lang::Ru => format!("❌ Не удалось подтвердить {channel}: кода <code>{code}</code> нет в последних постах, а среди администраторов, которых видит бот, вас нет. Опубликуйте код или добавьте бота в администраторы и попробуйте снова."),
+            Lang::Uk => format!("❌ Не вдалося підтвердити {channel}: коду <code>{code}</code> немає в останніх постах, а серед адміністраторів, яких бачить бот, вас немає. Опублікуйте код або додайте бота до адміністраторів і спробуйте ще раз."),
+            Lang::Es => format!("❌ No se pudo verificar {channel}: <code>{code}</code> no está en sus últimas publicaciones y no figuras entre los administradores que ve el bot. Publica el código o añade el bot como administrador e inténtalo de nuevo."),
+            Lang::De => format!("❌ {channel} konnte nicht bestätigt werden: <code>{code}</code> ist nicht unter den neuesten Beiträgen und du bist nicht unter den Administratoren, die der Bot sieht. Veröffentliche den Code oder füge den Bot als Administrator hinzu und versuche es erneut."),
+        }
+    }
+
+    /// the verified owner's view of their channel
+    pub fn claim_owner_status(&self, channel: &str, opted_out: bool, free_analyses: i32) -> String {
+        match (self, opted_out) {
+            (Lang::En, false) => format!("✅ <b>You own {channel}</b>\n\nThe channel can be analyzed by anyone. Your own analyses of it are free, {free_analyses} a month."),
+            (Lang::Ru, false) => format!("✅ <b>Вы владелец {channel}</b>\n\nКанал может проанализировать кто угодно. Ваши собственные анализы канала бесплатны, {free_analyses} в месяц."),
+            (Lang::Uk, false) => format!("✅ <b>Ви власник {channel}</b>\n\nКанал може проаналізувати будь-хто. Ваші власні аналізи каналу безкоштовні, {free_analyses} на місяць."),
+            (Lang::Es, false) => format!("✅ <b>Eres el propietario de {channel}</b>\n\nCualquiera puede analizar el canal. Tus propios análisis del canal son gratis, {free_analyses} al mes."),
+            (Lang::De, false) => format!("✅ <b>Du bist Inhaber von {channel}</b>\n\nJeder kann den Kanal analysieren. Deine eigenen Analysen des Kanals sind kostenlos, {free_analyses} pro Monat."),
+            (Lang::En, true) => format!("✅ <b>You own {channel}</b>\n\nThe channel is excluded from analysis, for everyone including you."),
+            (Lang::Ru, true) => format!("✅ <b>Вы владелец {channel}</b>\n\nКанал исключён из анализа для всех, включая вас."),
+            (Lang::Uk, true) => format!("✅ <b>Ви власник {channel}</b>\n\nКанал виключено з аналізу для всіх, зокрема для вас."),
+            (Lang::Es, true) => format!("✅ <b>Eres el propietario de {channel}</b>\n\nEl canal está excluido del análisis para todos, incluido tú."),
+            (Lang::De, true) => format!("✅ <b>Du bist Inhaber von {channel}</b>\n\nDer Kanal ist für alle von der Analyse ausgeschlossen, auch für dich."),
+        }
+    }
+
+    pub fn btn_claim_verify(&self) -> &'static str {
+        match self {
+            Lang::En => "🔍 Verify",
+            Lang::Ru => "🔍 Проверить",
+            Lang::Uk => "🔍 Перевірити",
+            Lang::Es => "🔍 Verificar",
+            Lang::De => "🔍 Prüfen",
+        }
+    }
+
+    /// `opted_out` is the state the button switches to
+    pub fn btn_claim_opt_out(&self, opted_out: bool) -> &'static str {
+        match (self, opted_out) {
+            (Lang::En, true) => "⛔ Exclude from analysis",
+            (Lang::Ru, true) => "⛔ Исключить из анализа",
+            (Lang::Uk, true) => "⛔ Виключити з аналізу",
+            (Lang::Es, true) => "⛔ Excluir del análisis",
+            (Lang::De, true) => "⛔ Von der Analyse ausschließen",
+            (Lang::En, false) => "✅ Allow analysis again",
+            (Lang::Ru, false) => "✅ Снова разрешить анализ",
+            (Lang::Uk, false) => "✅ Знову дозволити аналіз",
+            (Lang::Es, false) => "✅ Permitir el análisis de nuevo",
+            (Lang::De, false) => "✅ Analyse wieder erlauben",
+        }
+    }
+}
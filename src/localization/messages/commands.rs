@@ -0,0 +1,32 @@
+//! the command menu telegram shows
+
+use super::Lang;
+
+impl Lang {
+    /// menu description of a command the bot registers itself, None keeps the english
+    /// one from `Command`
+    pub fn command_description(&self, command: &str) -> Option<&'static str> {
+        match (self, command) {
+            (Lang::En, _) => None,
+            (Lang::Ru, "analyze_group") => Some("проанализировать эту группу (только для админов)"),
+            (Lang::Ru, "analyze") => Some(
+                "проанализировать группу выбранным типом, например /analyze roast (только для админов)",
+            ),
+            (Lang::Uk, "analyze_group") => Some("проаналізувати цю групу (лише для адмінів)"),
+            (Lang::Uk, "analyze") => Some(
+                "проаналізувати групу обраним типом, наприклад /analyze roast (лише для адмінів)",
+            ),
+            (Lang::Es, "analyze_group") => {
+                Some("analizar este grupo (solo administradores del grupo)")
+            }
+            (Lang::Es, "analyze") => Some(
+                "analizar el grupo con un tipo elegido, p. ej. /analyze roast (solo administradores)",
+            ),
+            (Lang::De, "analyze_group") => Some("diese Gruppe analysieren (nur Gruppen-Admins)"),
+            (Lang::De, "analyze") => Some(
+                "Gruppe mit gewählter Art analysieren, z. B. /analyze roast (nur Gruppen-Admins)",
+            ),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! /groupprofile consent and the profile made of group messages
+
+use super::Lang;
+
+impl Lang {
+    pub fn cross_group_private_only(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /groupprofile works in a private chat with the bot, so your messages stay between us.",
+            Lang::Ru => "ℹ️ /groupprofile работает в личном чате с ботом, чтобы ваши сообщения остались между нами.",
+            Lang::Uk => "ℹ️ /groupprofile працює в особистому чаті з ботом, щоб ваші повідомлення залишилися між нами.",
+            Lang::Es => "ℹ️ /groupprofile funciona en un chat privado con el bot, para que tus mensajes queden entre nosotros.",
+            Lang::De => "ℹ️ /groupprofile funktioniert im privaten Chat mit dem Bot, damit deine Nachrichten unter uns bleiben.",
+        }
+    }
+
+    pub fn cross_group_consent(&self) -> &'static str {
+        match self {
+            Lang::En => {
+                "🧩 <b>Your profile across groups</b>\n\n\
+                With your permission, the bot keeps the text of the messages you write in the groups \
+                it is a member of, and builds one profile from all of them together.\n\n\
+                Only your own messages are kept, only from now on, and nobody in the groups is told. \
+                Members of a group you wrote in can also start a /roastbattle with you there. You can withdraw the permission under /groupprofile at any time, which deletes \
+                everything kept."
+            }
+            Lang::Ru => {
+                "🧩 <b>Ваш профиль по всем группам</b>\n\n\
+                С вашего разрешения бот сохраняет текст ваших сообщений в группах, где он состоит, \
+                и составляет по ним всем один профиль.\n\n\
+                Сохраняются только ваши сообщения, только начиная с этого момента, и в группах об этом \
+                никто не узнает. Участники группы, где вы пишете, смогут вызвать вас там на /roastbattle. \
+                Разрешение можно отозвать в /groupprofile в любой момент, \
+                и всё сохранённое будет удалено."
+            }
+            Lang::Uk => {
+                "🧩 <b>Ваш профіль за всіма групами</b>\n\n\
+                З вашого дозволу бот зберігає текст ваших повідомлень у групах, де він є учасником, \
+                і складає за ними всіма один профіль.\n\n\
+                Зберігаються лише ваші повідомлення, лише від цього моменту, і в групах про це \
+                ніхто не дізнається. Учасники групи, де ви пишете, зможуть викликати вас там на /roastbattle. \
+                Дозвіл можна відкликати в /groupprofile будь-коли, \
+                і все збережене буде видалено."
+            }
+            Lang::Es => {
+                "🧩 <b>Tu perfil a través de los grupos</b>\n\n\
+                Con tu permiso, el bot guarda el texto de los mensajes que escribes en los grupos \
+                de los que es miembro y crea un solo perfil con todos ellos.\n\n\
+                Solo se guardan tus propios mensajes, solo a partir de ahora, y nadie en los grupos \
+                se entera. Los miembros de un grupo donde escribes también pueden iniciar allí una /roastbattle contigo. \
+                Puedes retirar el permiso en /groupprofile cuando quieras, lo que borra \
+                todo lo guardado."
+            }
+            Lang::De => {
+                "🧩 <b>Dein Profil über alle Gruppen</b>\n\n\
+                Mit deiner Erlaubnis speichert der Bot den Text deiner Nachrichten in den Gruppen, \
+                in denen er Mitglied ist, und erstellt aus allen zusammen ein Profil.\n\n\
+                Gespeichert werden nur deine eigenen Nachrichten, erst ab jetzt, und in den Gruppen \
+                erfährt niemand davon. Mitglieder einer Gruppe, in der du schreibst, können dort auch eine /roastbattle mit dir starten. \
+                Du kannst die Erlaubnis unter /groupprofile jederzeit \
+                widerrufen, dabei wird alles Gespeicherte gelöscht."
+            }
+        }
+    }
+
+    pub fn btn_cross_group_consent(&self) -> &'static str {
+        match self {
+            Lang::En => "✅ I agree",
+            Lang::Ru => "✅ Я согласен",
+            Lang::Uk => "✅ Я згоден",
+            Lang::Es => "✅ Acepto",
+            Lang::De => "✅ Ich stimme zu",
+        }
+    }
+
+    pub fn cross_group_consented(&self, min: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "✅ Done. From now on the bot keeps your messages in the groups you share with it. \
+                Send /groupprofile once you've written at least {min} of them."
+            ),
+            Lang::Ru => format!(
+                "✅ Готово. Теперь бот сохраняет ваши сообщения в общих с ним группах. \
+                Отправьте /groupprofile, когда их наберётся хотя бы {min}."
+            ),
+            Lang::Uk => format!(
+                "✅ Готово. Тепер бот зберігає ваші повідомлення в спільних із ним групах. \
+                Надішліть /groupprofile, коли їх набереться хоча б {min}."
+            ),
+            Lang::Es => format!(
+                "✅ Listo. A partir de ahora el bot guarda tus mensajes en los grupos que compartes con él. \
+                Envía /groupprofile cuando hayas escrito al menos {min}."
+            ),
+            Lang::De => format!(
+                "✅ Erledigt. Ab jetzt speichert der Bot deine Nachrichten in den Gruppen, die du mit ihm teilst. \
+                Sende /groupprofile, sobald du mindestens {min} geschrieben hast."
+            ),
+        }
+    }
+
+    /// `groups` are the kept messages per group, one line each
+    pub fn cross_group_status(
+        &self,
+        groups: &[String],
+        total: usize,
+        min: usize,
+        credits: i32,
+    ) -> String {
+        let mut text = match (self, groups.is_empty()) {
+            (Lang::En, true) => "🧩 <b>Your profile across groups</b>\n\nNo messages kept yet.".to_string(),
+            (Lang::Ru, true) => "🧩 <b>Ваш профиль по всем группам</b>\n\nСохранённых сообщений пока нет.".to_string(),
+            (Lang::Uk, true) => "🧩 <b>Ваш профіль за всіма групами</b>\n\nЗбережених повідомлень поки немає.".to_string(),
+            (Lang::Es, true) => "🧩 <b>Tu perfil a través de los grupos</b>\n\nTodavía no hay mensajes guardados.".to_string(),
+            (Lang::De, true) => "🧩 <b>Dein Profil über alle Gruppen</b>\n\nNoch keine Nachrichten gespeichert.".to_string(),
+            (Lang::En, false) => format!("🧩 <b>Your profile across groups</b>\n\n{total} of your messages kept:"),
+            (Lang::Ru, false) => format!("🧩 <b>Ваш профиль по всем группам</b>\n\nСохранено ваших сообщений: {total}"),
+            (Lang::Uk, false) => format!("🧩 <b>Ваш профіль за всіма групами</b>\n\nЗбережено ваших повідомлень: {total}"),
+            (Lang::Es, false) => format!("🧩 <b>Tu perfil a través de los grupos</b>\n\n{total} de tus mensajes guardados:"),
+            (Lang::De, false) => format!("🧩 <b>Dein Profil über alle Gruppen</b>\n\n{total} deiner Nachrichten gespeichert:"),
+        };
+        for group in groups {
+            text.push('\n');
+            text.push_str(group);
+        }
+        text.push_str(&if total >= min {
+            match self {
+                Lang::En => format!("\n\n💳 Cost: {credits} credits. Choose the type of analysis:"),
+                Lang::Ru => format!("\n\n💳 Стоимость: {credits} кредитов. Выберите тип анализа:"),
+                Lang::Uk => format!("\n\n💳 Вартість: {credits} кредитів. Виберіть тип аналізу:"),
+                Lang::Es => format!("\n\n💳 Coste: {credits} créditos. Elige el tipo de análisis:"),
+                Lang::De => format!("\n\n💳 Kosten: {credits} Credits. Wähle die Art der Analyse:"),
+            }
+        } else {
+            match self {
+                Lang::En => format!(
+                    "\n\n✍️ The profile needs at least {min} messages. The bot only sees group messages \
+                    where it is an admin or its privacy mode is off."
+                ),
+                Lang::Ru => format!(
+                    "\n\n✍️ Для профиля нужно хотя бы {min} сообщений. Бот видит сообщения только в группах, \
+                    где он администратор или где у него выключен режим приватности."
+                ),
+                Lang::Uk => format!(
+                    "\n\n✍️ Для профілю потрібно щонайменше {min} повідомлень. Бот бачить повідомлення лише в групах, \
+                    де він адміністратор або де в нього вимкнено режим приватності."
+                ),
+                Lang::Es => format!(
+                    "\n\n✍️ El perfil necesita al menos {min} mensajes. El bot solo ve los mensajes de los grupos \
+                    donde es administrador o tiene el modo de privacidad desactivado."
+                ),
+                Lang::De => format!(
+                    "\n\n✍️ Das Profil braucht mindestens {min} Nachrichten. Der Bot sieht Gruppennachrichten nur, \
+                    wo er Admin ist oder sein Privatsphäre-Modus ausgeschaltet ist."
+                ),
+            }
+        });
+        text
+    }
+
+    pub fn cross_group_too_few(&self, count: usize, min: usize) -> String {
+        match self {
+            Lang::En => format!("✋ {count} messages are not enough yet, the profile needs at least {min}."),
+            Lang::Ru => format!("✋ {count} сообщений пока недостаточно, для профиля нужно хотя бы {min}."),
+            Lang::Uk => format!("✋ {count} повідомлень поки недостатньо, для профілю потрібно щонайменше {min}."),
+            Lang::Es => format!("✋ {count} mensajes aún no son suficientes, el perfil necesita al menos {min}."),
+            Lang::De => format!("✋ {count} Nachrichten reichen noch nicht, das Profil braucht mindestens {min}."),
+        }
+    }
+
+    pub fn btn_cross_group_revoke(&self) -> &'static str {
+        match self {
+            Lang::En => "🗑 Withdraw permission",
+            Lang::Ru => "🗑 Отозвать разрешение",
+            Lang::Uk => "🗑 Відкликати дозвіл",
+            Lang::Es => "🗑 Retirar el permiso",
+            Lang::De => "🗑 Erlaubnis widerrufen",
+        }
+    }
+
+    pub fn cross_group_revoked(&self, deleted: u64) -> String {
+        match self {
+            Lang::En => format!("🗑 Permission withdrawn, {deleted} kept messages deleted. The bot no longer keeps your group messages."),
+            Lang::Ru => format!("🗑 Разрешение отозвано, удалено сохранённых сообщений: {deleted}. Бот больше не сохраняет ваши сообщения в группах."),
+            Lang::Uk => format!("🗑 Дозвіл відкликано, видалено збережених повідомлень: {deleted}. Бот більше не зберігає ваші повідомлення в групах."),
+            Lang::Es => format!("🗑 Permiso retirado, se borraron {deleted} mensajes guardados. El bot ya no guarda tus mensajes de los grupos."),
+            Lang::De => format!("🗑 Erlaubnis widerrufen, {deleted} gespeicherte Nachrichten gelöscht. Der Bot speichert deine Gruppennachrichten nicht mehr."),
+        }
+    }
+
+    /// shown in place of a channel name for a profile of the user's group messages
+    pub fn cross_group_target(&self) -> &'static str {
+        match self {
+            Lang::En => "your messages in groups",
+            Lang::Ru => "ваши сообщения в группах",
+            Lang::Uk => "ваші повідомлення в групах",
+            Lang::Es => "tus mensajes en grupos",
+            Lang::De => "deine Nachrichten in Gruppen",
+        }
+    }
+}
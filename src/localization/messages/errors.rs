@@ -0,0 +1,897 @@
+//! error messages, and the advice that goes with them
+use super::Lang;
+use crate::analysis::{AnalysisDepth, AnalysisError};
+
+impl Lang {
+    pub fn error_account_access(&self) -> &'static str {
+        match self {
+            Lang::En => {
+                "❌ Sorry, there was an error accessing your account. Please try again later."
+            }
+            Lang::Ru => {
+                "❌ Извините, произошла ошибка при доступе к вашему аккаунту. Попробуйте позже."
+            }
+            Lang::Uk => {
+                "❌ Вибачте, сталася помилка під час доступу до вашого акаунта. Спробуйте пізніше."
+            }
+            Lang::Es => {
+                "❌ Lo sentimos, hubo un error al acceder a tu cuenta. Inténtalo de nuevo más tarde."
+            }
+            Lang::De => {
+                "❌ Beim Zugriff auf dein Konto ist leider ein Fehler aufgetreten. Bitte versuche es später erneut."
+            }
+        }
+    }
+
+    pub fn error_processing_request(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Error processing user request. Please try again later.",
+            Lang::Ru => "❌ Ошибка обработки запроса. Попробуйте позже.",
+            Lang::Uk => "❌ Помилка обробки запиту. Спробуйте пізніше.",
+            Lang::Es => "❌ Error al procesar la solicitud. Inténtalo de nuevo más tarde.",
+            Lang::De => {
+                "❌ Fehler bei der Verarbeitung der Anfrage. Bitte versuche es später erneut."
+            }
+        }
+    }
+
+    pub fn error_check_credits(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Failed to check credits. Please try again.",
+            Lang::Ru => "❌ Не удалось проверить кредиты. Попробуйте снова.",
+            Lang::Uk => "❌ Не вдалося перевірити кредити. Спробуйте ще раз.",
+            Lang::Es => "❌ No se pudieron comprobar los créditos. Inténtalo de nuevo.",
+            Lang::De => "❌ Guthaben konnte nicht geprüft werden. Bitte versuche es erneut.",
+        }
+    }
+
+    pub fn error_start_analysis(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Failed to start analysis. Please try again.",
+            Lang::Ru => "❌ Не удалось начать анализ. Попробуйте снова.",
+            Lang::Uk => "❌ Не вдалося почати аналіз. Спробуйте ще раз.",
+            Lang::Es => "❌ No se pudo iniciar el análisis. Inténtalo de nuevo.",
+            Lang::De => "❌ Die Analyse konnte nicht gestartet werden. Bitte versuche es erneut.",
+        }
+    }
+
+    pub fn analysis_already_running(&self) -> &'static str {
+        match self {
+            Lang::En => "⏳ Your analysis is still running. Please wait for it to finish before starting another one.",
+            Lang::Ru => "⏳ Ваш анализ ещё выполняется. Дождитесь его завершения, прежде чем начинать новый.",
+            Lang::Uk => "⏳ Ваш аналіз ще виконується. Дочекайтеся його завершення, перш ніж починати новий.",
+            Lang::Es => "⏳ Tu análisis sigue en curso. Espera a que termine antes de iniciar otro.",
+            Lang::De => "⏳ Deine Analyse läuft noch. Bitte warte, bis sie fertig ist, bevor du eine neue startest.",
+        }
+    }
+
+    pub fn error_user_not_found(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ User not found. Please try again.",
+            Lang::Ru => "❌ Пользователь не найден. Попробуйте снова.",
+            Lang::Uk => "❌ Користувача не знайдено. Спробуйте ще раз.",
+            Lang::Es => "❌ Usuario no encontrado. Inténtalo de nuevo.",
+            Lang::De => "❌ Nutzer nicht gefunden. Bitte versuche es erneut.",
+        }
+    }
+
+    pub fn error_insufficient_credits(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Insufficient credits. Please purchase more credits to continue.",
+            Lang::Ru => "❌ Недостаточно кредитов. Пожалуйста, купите кредиты для продолжения.",
+            Lang::Uk => "❌ Недостатньо кредитів. Будь ласка, придбайте кредити, щоб продовжити.",
+            Lang::Es => "❌ Créditos insuficientes. Compra más créditos para continuar.",
+            Lang::De => "❌ Nicht genug Guthaben. Bitte kaufe weitere Credits, um fortzufahren.",
+        }
+    }
+
+    pub fn error_system(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Analysis failed due to a system error. Please try again later.",
+            Lang::Ru => "❌ Анализ не удался из-за системной ошибки. Попробуйте позже.",
+            Lang::Uk => "❌ Аналіз не вдався через системну помилку. Спробуйте пізніше.",
+            Lang::Es => "❌ El análisis falló por un error del sistema. Inténtalo de nuevo más tarde.",
+            Lang::De => "❌ Die Analyse ist wegen eines Systemfehlers fehlgeschlagen. Bitte versuche es später erneut.",
+        }
+    }
+
+    /// reply to a user over their request rate limit
+    pub fn slow_down(&self, retry_after_secs: u64) -> String {
+        match self {
+            Lang::En => format!(
+                "⏳ You're sending requests too fast. Please wait {} s and try again.",
+                retry_after_secs
+            ),
+            Lang::Ru => format!(
+                "⏳ Слишком много запросов. Подождите {} с и попробуйте снова.",
+                retry_after_secs
+            ),
+            Lang::Uk => format!(
+                "⏳ Забагато запитів. Зачекайте {} с і спробуйте ще раз.",
+                retry_after_secs
+            ),
+            Lang::Es => format!(
+                "⏳ Estás enviando solicitudes demasiado rápido. Espera {} s e inténtalo de nuevo.",
+                retry_after_secs
+            ),
+            Lang::De => format!(
+                "⏳ Du sendest Anfragen zu schnell. Bitte warte {} s und versuche es erneut.",
+                retry_after_secs
+            ),
+        }
+    }
+
+    pub fn error_payment_processing(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Error processing payment. Please contact support.",
+            Lang::Ru => "❌ Ошибка обработки платежа. Свяжитесь с поддержкой.",
+            Lang::Uk => "❌ Помилка обробки платежу. Зверніться до підтримки.",
+            Lang::Es => "❌ Error al procesar el pago. Contacta con soporte.",
+            Lang::De => "❌ Fehler bei der Zahlungsverarbeitung. Bitte wende dich an den Support.",
+        }
+    }
+
+    pub fn error_payment_credits(&self) -> &'static str {
+        match self {
+            Lang::En => "⚠️ Payment received but failed to add credits. Please contact support with your payment ID.",
+            Lang::Ru => "⚠️ Платёж получен, но не удалось добавить кредиты. Свяжитесь с поддержкой, указав ID платежа.",
+            Lang::Uk => "⚠️ Платіж отримано, але не вдалося нарахувати кредити. Зверніться до підтримки, вказавши ID платежу.",
+            Lang::Es => "⚠️ Pago recibido, pero no se pudieron añadir los créditos. Contacta con soporte indicando el ID del pago.",
+            Lang::De => "⚠️ Zahlung erhalten, aber die Credits konnten nicht gutgeschrieben werden. Bitte wende dich mit deiner Zahlungs-ID an den Support.",
+        }
+    }
+
+    pub fn error_invalid_channel(&self) -> &'static str {
+        match self {
+            Lang::En => "❓ Please send a valid channel username starting with '@' (e.g., @channelname)\n\nUse /start to see the full instructions.",
+            Lang::Ru => "❓ Отправьте корректное имя канала, начинающееся с '@' (например, @channelname)\n\nИспользуйте /start для просмотра инструкций.",
+            Lang::Uk => "❓ Надішліть коректне ім'я каналу, що починається з '@' (наприклад, @channelname)\n\nВикористайте /start, щоб переглянути інструкції.",
+            Lang::Es => "❓ Envía un nombre de canal válido que empiece por '@' (p. ej., @channelname)\n\nUsa /start para ver las instrucciones completas.",
+            Lang::De => "❓ Bitte sende einen gültigen Kanalnamen, der mit '@' beginnt (z. B. @channelname)\n\nMit /start siehst du die vollständige Anleitung.",
+        }
+    }
+
+    pub fn analyze_group_only_in_groups(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /analyze_group and /analyze work in groups. Add the bot to a group and run them there, or send me a channel username here.",
+            Lang::Ru => "ℹ️ /analyze_group и /analyze работают в группах. Добавьте бота в группу и запустите команду там или отправьте мне имя канала здесь.",
+            Lang::Uk => "ℹ️ /analyze_group і /analyze працюють у групах. Додайте бота до групи й запустіть команду там або надішліть мені ім'я каналу тут.",
+            Lang::Es => "ℹ️ /analyze_group y /analyze funcionan en grupos. Añade el bot a un grupo y ejecútalos allí, o envíame aquí el nombre de un canal.",
+            Lang::De => "ℹ️ /analyze_group und /analyze funktionieren in Gruppen. Füge den Bot einer Gruppe hinzu und starte sie dort, oder sende mir hier einen Kanalnamen.",
+        }
+    }
+
+    pub fn analyze_group_admins_only(&self) -> &'static str {
+        match self {
+            Lang::En => "🔒 Only group admins can start a group analysis.",
+            Lang::Ru => "🔒 Запустить анализ группы могут только её администраторы.",
+            Lang::Uk => "🔒 Запустити аналіз групи можуть лише її адміністратори.",
+            Lang::Es => {
+                "🔒 Solo los administradores del grupo pueden iniciar un análisis del grupo."
+            }
+            Lang::De => "🔒 Nur Gruppenadmins können eine Gruppenanalyse starten.",
+        }
+    }
+
+    pub fn analyze_group_anonymous_admin(&self) -> &'static str {
+        match self {
+            Lang::En => "🔒 Group analysis is billed to the admin who starts it. Please turn off \"Remain anonymous\" and try again.",
+            Lang::Ru => "🔒 Анализ группы оплачивается запустившим его администратором. Отключите «Анонимность» и попробуйте снова.",
+            Lang::Uk => "🔒 Аналіз групи оплачує адміністратор, який його запускає. Вимкніть «Анонімність» і спробуйте ще раз.",
+            Lang::Es => "🔒 El análisis del grupo se cobra al administrador que lo inicia. Desactiva «Permanecer anónimo» e inténtalo de nuevo.",
+            Lang::De => "🔒 Die Gruppenanalyse wird dem Admin berechnet, der sie startet. Bitte deaktiviere „Anonym bleiben“ und versuche es erneut.",
+        }
+    }
+
+    pub fn analyze_group_requires_username(&self) -> &'static str {
+        match self {
+            Lang::En => "❓ Only public groups with a @username can be analyzed.",
+            Lang::Ru => "❓ Анализировать можно только публичные группы с @username.",
+            Lang::Uk => "❓ Аналізувати можна лише публічні групи з @username.",
+            Lang::Es => "❓ Solo se pueden analizar grupos públicos con @username.",
+            Lang::De => "❓ Nur öffentliche Gruppen mit @username können analysiert werden.",
+        }
+    }
+
+    pub fn analyze_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/analyze professional</code>, <code>/analyze personal</code>, <code>/analyze roast</code> or <code>/analyze trends</code> in a group. Without a type you choose it from the buttons.",
+            Lang::Ru => "Использование: <code>/analyze professional</code>, <code>/analyze personal</code>, <code>/analyze roast</code> или <code>/analyze trends</code> в группе. Без типа его можно выбрать кнопками.",
+            Lang::Uk => "Використання: <code>/analyze professional</code>, <code>/analyze personal</code>, <code>/analyze roast</code> або <code>/analyze trends</code> у групі. Без типу його можна вибрати кнопками.",
+            Lang::Es => "Uso: <code>/analyze professional</code>, <code>/analyze personal</code>, <code>/analyze roast</code> o <code>/analyze trends</code> en un grupo. Sin tipo, lo eliges con los botones.",
+            Lang::De => "Verwendung: <code>/analyze professional</code>, <code>/analyze personal</code>, <code>/analyze roast</code> oder <code>/analyze trends</code> in einer Gruppe. Ohne Typ wählst du ihn über die Schaltflächen.",
+        }
+    }
+
+    pub fn group_scope_select(&self, topic: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🧵 This was sent in the topic <b>{topic}</b>. Analyze the whole group or only this topic?"
+            ),
+            Lang::Ru => format!(
+                "🧵 Команда отправлена в теме <b>{topic}</b>. Анализировать всю группу или только эту тему?"
+            ),
+            Lang::Uk => format!(
+                "🧵 Команду надіслано в темі <b>{topic}</b>. Аналізувати всю групу чи лише цю тему?"
+            ),
+            Lang::Es => format!(
+                "🧵 Esto se envió en el tema <b>{topic}</b>. ¿Analizar todo el grupo o solo este tema?"
+            ),
+            Lang::De => format!(
+                "🧵 Das wurde im Thema <b>{topic}</b> gesendet. Die ganze Gruppe analysieren oder nur dieses Thema?"
+            ),
+        }
+    }
+
+    pub fn btn_whole_group(&self) -> &'static str {
+        match self {
+            Lang::En => "🏘 Whole group",
+            Lang::Ru => "🏘 Вся группа",
+            Lang::Uk => "🏘 Уся група",
+            Lang::Es => "🏘 Todo el grupo",
+            Lang::De => "🏘 Ganze Gruppe",
+        }
+    }
+
+    pub fn btn_topic_only(&self, topic: &str) -> String {
+        match self {
+            Lang::En => format!("🧵 Only «{topic}»"),
+            Lang::Ru => format!("🧵 Только «{topic}»"),
+            Lang::Uk => format!("🧵 Лише «{topic}»"),
+            Lang::Es => format!("🧵 Solo «{topic}»"),
+            Lang::De => format!("🧵 Nur „{topic}“"),
+        }
+    }
+
+    pub fn group_analysis_preselected(&self, analysis_type: &str, channel_name: &str) -> String {
+        let emoji = self.analysis_emoji(analysis_type);
+        match self {
+            Lang::En => format!(
+                "{emoji} A {} analysis of this group ({channel_name}) was requested with /analyze {analysis_type}.",
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::Ru => format!(
+                "{emoji} Запрошен {} анализ этой группы ({channel_name}) командой /analyze {analysis_type}.",
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::Uk => format!(
+                "{emoji} Запитано {} аналіз цієї групи ({channel_name}) командою /analyze {analysis_type}.",
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::Es => format!(
+                "{emoji} Se pidió un {} de este grupo ({channel_name}) con /analyze {analysis_type}.",
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::De => format!(
+                "{emoji} Mit /analyze {analysis_type} wurde eine {} dieser Gruppe ({channel_name}) angefordert.",
+                self.analysis_type_name(analysis_type)
+            ),
+        }
+    }
+
+    pub fn error_analysis_failed(&self, failure: &AnalysisError, channel_name: &str) -> String {
+        let explanation = match (self, failure) {
+            (Lang::En, AnalysisError::ChannelPrivate) => format!(
+                "Channel {channel_name} is private or restricted, so its posts can't be read.\n\n\
+                Only public channels with a @username can be analyzed."
+            ),
+            (Lang::Ru, AnalysisError::ChannelPrivate) => format!(
+                "Канал {channel_name} приватный или ограниченный, поэтому его посты недоступны.\n\n\
+                Анализировать можно только публичные каналы с @username."
+            ),
+            (Lang::Uk, AnalysisError::ChannelPrivate) => format!(
+                "Канал {channel_name} приватний або обмежений, тому його дописи недоступні.\n\n\
+                Аналізувати можна лише публічні канали з @username."
+            ),
+            (Lang::Es, AnalysisError::ChannelPrivate) => format!(
+                "El canal {channel_name} es privado o restringido, así que no se pueden leer sus publicaciones.\n\n\
+                Solo se pueden analizar canales públicos con @username."
+            ),
+            (Lang::De, AnalysisError::ChannelPrivate) => format!(
+                "Der Kanal {channel_name} ist privat oder eingeschränkt, seine Beiträge können nicht gelesen werden.\n\n\
+                Nur öffentliche Kanäle mit @username können analysiert werden."
+            ),
+            (Lang::En, AnalysisError::ChannelNotFound) => format!(
+                "Channel {channel_name} doesn't exist or has been deleted.\n\n\
+                Double-check the username and send it again."
+            ),
+            (Lang::Ru, AnalysisError::ChannelNotFound) => format!(
+                "Канал {channel_name} не существует или был удалён.\n\n\
+                Проверьте имя канала и отправьте его снова."
+            ),
+            (Lang::Uk, AnalysisError::ChannelNotFound) => format!(
+                "Канал {channel_name} не існує або його видалено.\n\n\
+                Перевірте ім'я каналу й надішліть його ще раз."
+            ),
+            (Lang::Es, AnalysisError::ChannelNotFound) => format!(
+                "El canal {channel_name} no existe o ha sido eliminado.\n\n\
+                Revisa el nombre de usuario y envíalo de nuevo."
+            ),
+            (Lang::De, AnalysisError::ChannelNotFound) => format!(
+                "Der Kanal {channel_name} existiert nicht oder wurde gelöscht.\n\n\
+                Prüfe den Nutzernamen und sende ihn erneut."
+            ),
+            (Lang::En, AnalysisError::FloodWait(seconds)) => format!(
+                "Telegram is temporarily limiting our requests.\n\n\
+                Please try again in {}.",
+                self.wait_duration(*seconds)
+            ),
+            (Lang::Ru, AnalysisError::FloodWait(seconds)) => format!(
+                "Telegram временно ограничил наши запросы.\n\n\
+                Попробуйте снова через {}.",
+                self.wait_duration(*seconds)
+            ),
+            (Lang::Uk, AnalysisError::FloodWait(seconds)) => format!(
+                "Telegram тимчасово обмежив наші запити.\n\n\
+                Спробуйте ще раз через {}.",
+                self.wait_duration(*seconds)
+            ),
+            (Lang::Es, AnalysisError::FloodWait(seconds)) => format!(
+                "Telegram está limitando temporalmente nuestras solicitudes.\n\n\
+                Inténtalo de nuevo dentro de {}.",
+                self.wait_duration(*seconds)
+            ),
+            (Lang::De, AnalysisError::FloodWait(seconds)) => format!(
+                "Telegram begrenzt unsere Anfragen vorübergehend.\n\n\
+                Bitte versuche es in {} erneut.",
+                self.wait_duration(*seconds)
+            ),
+            (
+                Lang::En,
+                AnalysisError::CorpusTooLarge {
+                    depth,
+                    chars,
+                    limit,
+                },
+            ) => format!(
+                "Channel {channel_name} is too large for the {} depth: its posts add up to \
+                {chars} characters, over the {limit} character limit.\n\n{}",
+                self.depth_name(*depth),
+                self.corpus_limit_advice(*depth)
+            ),
+            (
+                Lang::Ru,
+                AnalysisError::CorpusTooLarge {
+                    depth,
+                    chars,
+                    limit,
+                },
+            ) => format!(
+                "Канал {channel_name} слишком большой для глубины «{}»: его посты занимают \
+                {chars} символов при лимите {limit}.\n\n{}",
+                self.depth_name(*depth),
+                self.corpus_limit_advice(*depth)
+            ),
+            (
+                Lang::Uk,
+                AnalysisError::CorpusTooLarge {
+                    depth,
+                    chars,
+                    limit,
+                },
+            ) => format!(
+                "Канал {channel_name} завеликий для глибини «{}»: його дописи займають \
+                {chars} символів за ліміту {limit}.\n\n{}",
+                self.depth_name(*depth),
+                self.corpus_limit_advice(*depth)
+            ),
+            (
+                Lang::Es,
+                AnalysisError::CorpusTooLarge {
+                    depth,
+                    chars,
+                    limit,
+                },
+            ) => format!(
+                "El canal {channel_name} es demasiado grande para la profundidad «{}»: sus publicaciones \
+                suman {chars} caracteres, más del límite de {limit}.\n\n{}",
+                self.depth_name(*depth),
+                self.corpus_limit_advice(*depth)
+            ),
+            (
+                Lang::De,
+                AnalysisError::CorpusTooLarge {
+                    depth,
+                    chars,
+                    limit,
+                },
+            ) => format!(
+                "Der Kanal {channel_name} ist für die Tiefe „{}“ zu groß: Seine Beiträge umfassen \
+                {chars} Zeichen, mehr als das Limit von {limit}.\n\n{}",
+                self.depth_name(*depth),
+                self.corpus_limit_advice(*depth)
+            ),
+            (Lang::En, AnalysisError::AiRefusal) => {
+                "The AI declined to analyze this channel's content. \
+                This sometimes happens with sensitive topics.\n\n\
+                Try another channel or retry later."
+                    .to_string()
+            }
+            (Lang::Ru, AnalysisError::AiRefusal) => {
+                "AI отказался анализировать содержимое этого канала. \
+                Такое иногда случается с чувствительными темами.\n\n\
+                Попробуйте другой канал или повторите позже."
+                    .to_string()
+            }
+            (Lang::Uk, AnalysisError::AiRefusal) => {
+                "AI відмовився аналізувати вміст цього каналу. \
+                Таке іноді трапляється з чутливими темами.\n\n\
+                Спробуйте інший канал або повторіть пізніше."
+                    .to_string()
+            }
+            (Lang::Es, AnalysisError::AiRefusal) => {
+                "La IA se negó a analizar el contenido de este canal. \
+                A veces ocurre con temas sensibles.\n\n\
+                Prueba con otro canal o vuelve a intentarlo más tarde."
+                    .to_string()
+            }
+            (Lang::De, AnalysisError::AiRefusal) => {
+                "Die KI hat die Analyse der Inhalte dieses Kanals abgelehnt. \
+                Das passiert manchmal bei sensiblen Themen.\n\n\
+                Probiere einen anderen Kanal oder versuche es später erneut."
+                    .to_string()
+            }
+            (Lang::En, AnalysisError::AiUnavailable) => {
+                "The AI service is currently unavailable.\n\n\
+                Please try again in a few minutes."
+                    .to_string()
+            }
+            (Lang::Ru, AnalysisError::AiUnavailable) => "AI-сервис сейчас недоступен.\n\n\
+                Попробуйте снова через несколько минут."
+                .to_string(),
+            (Lang::Uk, AnalysisError::AiUnavailable) => "AI-сервіс зараз недоступний.\n\n\
+                Спробуйте ще раз за кілька хвилин."
+                .to_string(),
+            (Lang::Es, AnalysisError::AiUnavailable) => {
+                "El servicio de IA no está disponible en este momento.\n\n\
+                Inténtalo de nuevo en unos minutos."
+                    .to_string()
+            }
+            (Lang::De, AnalysisError::AiUnavailable) => {
+                "Der KI-Dienst ist derzeit nicht erreichbar.\n\n\
+                Bitte versuche es in ein paar Minuten erneut."
+                    .to_string()
+            }
+            (Lang::En, AnalysisError::BudgetExhausted) => {
+                "The analysis took too long: Telegram or the AI service kept failing.\n\n\
+                Please try again in a few minutes."
+                    .to_string()
+            }
+            (Lang::Ru, AnalysisError::BudgetExhausted) => {
+                "Анализ занял слишком много времени: Telegram или AI-сервис не отвечали.\n\n\
+                Попробуйте снова через несколько минут."
+                    .to_string()
+            }
+            (Lang::Uk, AnalysisError::BudgetExhausted) => {
+                "Аналіз тривав занадто довго: Telegram або AI-сервіс не відповідали.\n\n\
+                Спробуйте ще раз за кілька хвилин."
+                    .to_string()
+            }
+            (Lang::Es, AnalysisError::BudgetExhausted) => {
+                "El análisis tardó demasiado: Telegram o el servicio de IA fallaban una y otra vez.\n\n\
+                Inténtalo de nuevo en unos minutos."
+                    .to_string()
+            }
+            (Lang::De, AnalysisError::BudgetExhausted) => {
+                "Die Analyse hat zu lange gedauert: Telegram oder der KI-Dienst sind wiederholt ausgefallen.\n\n\
+                Bitte versuche es in ein paar Minuten erneut."
+                    .to_string()
+            }
+            (Lang::En, AnalysisError::ShortHistory) => format!(
+                "The posts of {channel_name} cover too short a period to show how it changed.\n\n\
+                Try a deeper analysis to read further back, or another analysis type."
+            ),
+            (Lang::Ru, AnalysisError::ShortHistory) => format!(
+                "Посты {channel_name} охватывают слишком короткий период, чтобы показать изменения.\n\n\
+                Попробуйте большую глубину анализа или другой тип анализа."
+            ),
+            (Lang::Uk, AnalysisError::ShortHistory) => format!(
+                "Дописи {channel_name} охоплюють надто короткий період, щоб показати зміни.\n\n\
+                Спробуйте більшу глибину аналізу або інший тип аналізу."
+            ),
+            (Lang::Es, AnalysisError::ShortHistory) => format!(
+                "Las publicaciones de {channel_name} abarcan un periodo demasiado corto para mostrar cómo ha cambiado.\n\n\
+                Prueba un análisis más profundo para leer más atrás, u otro tipo de análisis."
+            ),
+            (Lang::De, AnalysisError::ShortHistory) => format!(
+                "Die Beiträge von {channel_name} decken einen zu kurzen Zeitraum ab, um Veränderungen zu zeigen.\n\n\
+                Probiere eine tiefere Analyse, die weiter zurückreicht, oder einen anderen Analysetyp."
+            ),
+            (Lang::En, AnalysisError::LowTextCoverage(coverage)) => format!(
+                "Only {coverage}% of the posts of {channel_name} have enough text to analyze, \
+                the rest are images, videos, forwards or short captions. An analysis of so \
+                little text is likely to be inaccurate.\n\n\
+                Press «Analyze anyway» to run it regardless."
+            ),
+            (Lang::Ru, AnalysisError::LowTextCoverage(coverage)) => format!(
+                "Только в {coverage}% постов {channel_name} достаточно текста для анализа, \
+                остальное — фото, видео, репосты или короткие подписи. Анализ такого \
+                небольшого объёма текста, скорее всего, будет неточным.\n\n\
+                Нажмите «Всё равно анализировать», чтобы запустить его."
+            ),
+            (Lang::Uk, AnalysisError::LowTextCoverage(coverage)) => format!(
+                "Лише в {coverage}% дописів {channel_name} достатньо тексту для аналізу, \
+                решта — фото, відео, репости або короткі підписи. Аналіз такого \
+                невеликого обсягу тексту, найімовірніше, буде неточним.\n\n\
+                Натисніть «Все одно аналізувати», щоб запустити його."
+            ),
+            (Lang::Es, AnalysisError::LowTextCoverage(coverage)) => format!(
+                "Solo el {coverage}% de las publicaciones de {channel_name} tiene texto suficiente para analizar; \
+                el resto son imágenes, vídeos, reenvíos o pies de foto cortos. Un análisis con tan \
+                poco texto probablemente será impreciso.\n\n\
+                Pulsa «Analizar de todos modos» para ejecutarlo igualmente."
+            ),
+            (Lang::De, AnalysisError::LowTextCoverage(coverage)) => format!(
+                "Nur {coverage}% der Beiträge von {channel_name} enthalten genug Text für eine Analyse, \
+                der Rest sind Bilder, Videos, Weiterleitungen oder kurze Bildunterschriften. Eine Analyse \
+                von so wenig Text ist wahrscheinlich ungenau.\n\n\
+                Tippe auf „Trotzdem analysieren“, um sie dennoch zu starten."
+            ),
+            (Lang::En, AnalysisError::InviteInvalid) => {
+                "This invite link is invalid or has expired.\n\n\
+                Ask the channel admins for a new link and send it again."
+                    .to_string()
+            }
+            (Lang::Ru, AnalysisError::InviteInvalid) => {
+                "Эта ссылка-приглашение недействительна или устарела.\n\n\
+                Попросите у администраторов канала новую ссылку и отправьте её снова."
+                    .to_string()
+            }
+            (Lang::Uk, AnalysisError::InviteInvalid) => {
+                "Це посилання-запрошення недійсне або застаріле.\n\n\
+                Попросіть в адміністраторів каналу нове посилання й надішліть його ще раз."
+                    .to_string()
+            }
+            (Lang::Es, AnalysisError::InviteInvalid) => {
+                "Este enlace de invitación no es válido o ha caducado.\n\n\
+                Pide un enlace nuevo a los administradores del canal y envíalo de nuevo."
+                    .to_string()
+            }
+            (Lang::De, AnalysisError::InviteInvalid) => {
+                "Dieser Einladungslink ist ungültig oder abgelaufen.\n\n\
+                Bitte die Kanal-Admins um einen neuen Link und sende ihn erneut."
+                    .to_string()
+            }
+            (Lang::En, AnalysisError::JoinRequestSent) => {
+                "This channel approves new members manually, so a join request was sent.\n\n\
+                Try again once an admin approves it."
+                    .to_string()
+            }
+            (Lang::Ru, AnalysisError::JoinRequestSent) => {
+                "В этот канал участников принимают вручную, поэтому была отправлена заявка.\n\n\
+                Попробуйте снова, когда администратор её одобрит."
+                    .to_string()
+            }
+            (Lang::Uk, AnalysisError::JoinRequestSent) => {
+                "У цей канал учасників приймають вручну, тому було надіслано заявку.\n\n\
+                Спробуйте ще раз, коли адміністратор її схвалить."
+                    .to_string()
+            }
+            (Lang::Es, AnalysisError::JoinRequestSent) => {
+                "Este canal aprueba a los nuevos miembros manualmente, así que se envió una solicitud para unirse.\n\n\
+                Inténtalo de nuevo cuando un administrador la apruebe."
+                    .to_string()
+            }
+            (Lang::De, AnalysisError::JoinRequestSent) => {
+                "Dieser Kanal nimmt neue Mitglieder manuell auf, daher wurde eine Beitrittsanfrage gesendet.\n\n\
+                Versuche es erneut, sobald ein Admin sie genehmigt hat."
+                    .to_string()
+            }
+            (Lang::En, AnalysisError::EmptyProfile) => format!(
+                "{channel_name} is a personal account with no bio and no channel on its \
+                profile, so there is nothing public to analyze.\n\n\
+                Check the username, or send a channel instead."
+            ),
+            (Lang::Ru, AnalysisError::EmptyProfile) => format!(
+                "{channel_name} — личный аккаунт без описания и без канала в профиле, \
+                поэтому анализировать нечего.\n\n\
+                Проверьте имя пользователя или отправьте канал."
+            ),
+            (Lang::Uk, AnalysisError::EmptyProfile) => format!(
+                "{channel_name} — особистий акаунт без опису й без каналу в профілі, \
+                тому аналізувати нічого.\n\n\
+                Перевірте ім'я користувача або надішліть канал."
+            ),
+            (Lang::Es, AnalysisError::EmptyProfile) => format!(
+                "{channel_name} es una cuenta personal sin biografía ni canal en su \
+                perfil, así que no hay nada público que analizar.\n\n\
+                Revisa el nombre de usuario o envía un canal."
+            ),
+            (Lang::De, AnalysisError::EmptyProfile) => format!(
+                "{channel_name} ist ein persönliches Konto ohne Bio und ohne Kanal im \
+                Profil, es gibt also nichts Öffentliches zu analysieren.\n\n\
+                Prüfe den Nutzernamen oder sende stattdessen einen Kanal."
+            ),
+            (Lang::En, AnalysisError::ServiceDegraded) => {
+                "The service is degraded: Telegram or the AI service kept failing, so new \
+                analyses are paused for a moment.\n\n\
+                Please try again in a few minutes."
+                    .to_string()
+            }
+            (Lang::Ru, AnalysisError::ServiceDegraded) => {
+                "Сервис работает с перебоями: Telegram или AI-сервис раз за разом не отвечали, \
+                поэтому новые анализы ненадолго приостановлены.\n\n\
+                Попробуйте снова через несколько минут."
+                    .to_string()
+            }
+            (Lang::Uk, AnalysisError::ServiceDegraded) => {
+                "Сервіс працює з перебоями: Telegram або AI-сервіс раз у раз не відповідали, \
+                тому нові аналізи ненадовго призупинено.\n\n\
+                Спробуйте ще раз за кілька хвилин."
+                    .to_string()
+            }
+            (Lang::Es, AnalysisError::ServiceDegraded) => {
+                "El servicio funciona con fallos: Telegram o el servicio de IA fallaron varias \
+                veces seguidas, así que los nuevos análisis están en pausa un momento.\n\n\
+                Inténtalo de nuevo en unos minutos."
+                    .to_string()
+            }
+            (Lang::De, AnalysisError::ServiceDegraded) => {
+                "Der Dienst ist gestört: Telegram oder der KI-Dienst sind wiederholt \
+                ausgefallen, daher sind neue Analysen kurz pausiert.\n\n\
+                Bitte versuche es in ein paar Minuten erneut."
+                    .to_string()
+            }
+            (Lang::En, AnalysisError::ChannelBlocked) => format!(
+                "{channel_name} can't be analyzed: it is excluded from analysis, for example \
+                at its owner's request."
+            ),
+            (Lang::Ru, AnalysisError::ChannelBlocked) => format!(
+                "{channel_name} нельзя проанализировать: он исключён из анализа, например \
+                по просьбе владельца."
+            ),
+            (Lang::Uk, AnalysisError::ChannelBlocked) => format!(
+                "{channel_name} не можна проаналізувати: його виключено з аналізу, наприклад \
+                на прохання власника."
+            ),
+            (Lang::Es, AnalysisError::ChannelBlocked) => format!(
+                "{channel_name} no se puede analizar: está excluido del análisis, por ejemplo \
+                a petición de su propietario."
+            ),
+            (Lang::De, AnalysisError::ChannelBlocked) => format!(
+                "{channel_name} kann nicht analysiert werden: er ist von der Analyse \
+                ausgeschlossen, zum Beispiel auf Wunsch des Inhabers."
+            ),
+            (Lang::En, AnalysisError::Internal(_)) => "Something went wrong on our side.\n\n\
+                Please try again later. If it keeps happening, contact support."
+                .to_string(),
+            (Lang::Ru, AnalysisError::Internal(_)) => "Что-то пошло не так на нашей стороне.\n\n\
+                Попробуйте позже. Если ошибка повторяется, свяжитесь с поддержкой."
+                .to_string(),
+            (Lang::Uk, AnalysisError::Internal(_)) => "Щось пішло не так на нашому боці.\n\n\
+                Спробуйте пізніше. Якщо помилка повторюється, зверніться до підтримки."
+                .to_string(),
+            (Lang::Es, AnalysisError::Internal(_)) => "Algo salió mal por nuestra parte.\n\n\
+                Inténtalo de nuevo más tarde. Si sigue ocurriendo, contacta con soporte."
+                .to_string(),
+            (Lang::De, AnalysisError::Internal(_)) => "Bei uns ist etwas schiefgelaufen.\n\n\
+                Bitte versuche es später erneut. Wenn es weiter passiert, wende dich an den Support."
+                .to_string(),
+        };
+
+        match self {
+            Lang::En => format!(
+                "❌ <b>Analysis Error</b>\n\n{explanation}\n\nNo credits were consumed for this request."
+            ),
+            Lang::Ru => format!(
+                "❌ <b>Ошибка анализа</b>\n\n{explanation}\n\nКредиты не были списаны."
+            ),
+            Lang::Uk => format!(
+                "❌ <b>Помилка аналізу</b>\n\n{explanation}\n\nКредити не було списано."
+            ),
+            Lang::Es => format!(
+                "❌ <b>Error de análisis</b>\n\n{explanation}\n\nNo se consumieron créditos por esta solicitud."
+            ),
+            Lang::De => format!(
+                "❌ <b>Analysefehler</b>\n\n{explanation}\n\nFür diese Anfrage wurden keine Credits verbraucht."
+            ),
+        }
+    }
+
+    // deeper tiers allow larger corpora, shallower ones only read the most recent posts
+    fn corpus_limit_advice(&self, depth: AnalysisDepth) -> &'static str {
+        match (self, depth) {
+            (Lang::En, AnalysisDepth::Small) => {
+                "Pick the Deep depth below, it allows much larger channels."
+            }
+            (Lang::En, AnalysisDepth::Medium) => {
+                "Pick the Deep depth below for a larger limit, \
+                or the Quick one to cover only the most recent posts."
+            }
+            (Lang::En, AnalysisDepth::Deep) => {
+                "Pick a shallower depth below to cover only the most recent posts."
+            }
+            (Lang::Ru, AnalysisDepth::Small) => {
+                "Выберите ниже глубокий анализ, он допускает гораздо более крупные каналы."
+            }
+            (Lang::Ru, AnalysisDepth::Medium) => {
+                "Выберите ниже глубокий анализ с большим лимитом \
+                или быстрый, чтобы охватить только последние посты."
+            }
+            (Lang::Ru, AnalysisDepth::Deep) => {
+                "Выберите ниже меньшую глубину, чтобы охватить только последние посты."
+            }
+            (Lang::Uk, AnalysisDepth::Small) => {
+                "Виберіть нижче глибокий аналіз, він допускає значно більші канали."
+            }
+            (Lang::Uk, AnalysisDepth::Medium) => {
+                "Виберіть нижче глибокий аналіз із більшим лімітом \
+                або швидкий, щоб охопити лише останні дописи."
+            }
+            (Lang::Uk, AnalysisDepth::Deep) => {
+                "Виберіть нижче меншу глибину, щоб охопити лише останні дописи."
+            }
+            (Lang::Es, AnalysisDepth::Small) => {
+                "Elige abajo la profundidad Profundo, admite canales mucho más grandes."
+            }
+            (Lang::Es, AnalysisDepth::Medium) => {
+                "Elige abajo la profundidad Profundo para un límite mayor, \
+                o la Rápido para cubrir solo las publicaciones más recientes."
+            }
+            (Lang::Es, AnalysisDepth::Deep) => {
+                "Elige abajo una profundidad menor para cubrir solo las publicaciones más recientes."
+            }
+            (Lang::De, AnalysisDepth::Small) => {
+                "Wähle unten die Tiefe „Tief“, sie erlaubt deutlich größere Kanäle."
+            }
+            (Lang::De, AnalysisDepth::Medium) => {
+                "Wähle unten die Tiefe „Tief“ für ein höheres Limit \
+                oder „Schnell“, um nur die neuesten Beiträge abzudecken."
+            }
+            (Lang::De, AnalysisDepth::Deep) => {
+                "Wähle unten eine geringere Tiefe, um nur die neuesten Beiträge abzudecken."
+            }
+        }
+    }
+
+    fn wait_duration(&self, seconds: u32) -> String {
+        let minutes = seconds.div_ceil(60);
+        match self {
+            Lang::En if minutes <= 1 => "about a minute".to_string(),
+            Lang::En if minutes < 60 => format!("about {} minutes", minutes),
+            Lang::En => format!("about {} hours", minutes.div_ceil(60)),
+            Lang::Ru if minutes <= 1 => "минуту".to_string(),
+            Lang::Ru if minutes < 60 => format!("{} мин.", minutes),
+            Lang::Ru => format!("{} ч.", minutes.div_ceil(60)),
+            Lang::Uk if minutes <= 1 => "хвилину".to_string(),
+            Lang::Uk if minutes < 60 => format!("{} хв", minutes),
+            Lang::Uk => format!("{} год", minutes.div_ceil(60)),
+            Lang::Es if minutes <= 1 => "un minuto aproximadamente".to_string(),
+            Lang::Es if minutes < 60 => format!("unos {} minutos", minutes),
+            Lang::Es => format!("unas {} horas", minutes.div_ceil(60)),
+            Lang::De if minutes <= 1 => "etwa einer Minute".to_string(),
+            Lang::De if minutes < 60 => format!("etwa {} Minuten", minutes),
+            Lang::De => format!("etwa {} Stunden", minutes.div_ceil(60)),
+        }
+    }
+
+    pub fn error_no_messages(&self) -> &'static str {
+        match self {
+            Lang::En => {
+                "❌ <b>Analysis Error</b>\n\n\
+                No messages found in the channel. This could happen if:\n\
+                • The channel is private/restricted\n\
+                • The channel has no recent messages\n\
+                • There are network connectivity issues\n\n\
+                No credits were consumed for this request."
+            }
+            Lang::Ru => {
+                "❌ <b>Ошибка анализа</b>\n\n\
+                В канале не найдено сообщений. Возможные причины:\n\
+                • Канал приватный/ограниченный\n\
+                • В канале нет недавних сообщений\n\
+                • Проблемы с сетью\n\n\
+                Кредиты не были списаны."
+            }
+            Lang::Uk => {
+                "❌ <b>Помилка аналізу</b>\n\n\
+                У каналі не знайдено повідомлень. Можливі причини:\n\
+                • Канал приватний/обмежений\n\
+                • У каналі немає нещодавніх повідомлень\n\
+                • Проблеми з мережею\n\n\
+                Кредити не було списано."
+            }
+            Lang::Es => {
+                "❌ <b>Error de análisis</b>\n\n\
+                No se encontraron mensajes en el canal. Puede ocurrir si:\n\
+                • El canal es privado/restringido\n\
+                • El canal no tiene mensajes recientes\n\
+                • Hay problemas de conexión\n\n\
+                No se consumieron créditos por esta solicitud."
+            }
+            Lang::De => {
+                "❌ <b>Analysefehler</b>\n\n\
+                Im Kanal wurden keine Nachrichten gefunden. Mögliche Gründe:\n\
+                • Der Kanal ist privat/eingeschränkt\n\
+                • Der Kanal hat keine aktuellen Nachrichten\n\
+                • Es gibt Netzwerkprobleme\n\n\
+                Für diese Anfrage wurden keine Credits verbraucht."
+            }
+        }
+    }
+
+    pub fn error_llm_budget_exceeded(&self) -> &'static str {
+        match self {
+            Lang::En => "⏳ <b>Analysis Unavailable</b>\n\nThe bot reached its daily AI usage limit. Please try again tomorrow. No credits were consumed for this request.",
+            Lang::Ru => "⏳ <b>Анализ недоступен</b>\n\nБот исчерпал дневной лимит использования ИИ. Попробуйте завтра. Кредиты не были списаны.",
+            Lang::Uk => "⏳ <b>Аналіз недоступний</b>\n\nБот вичерпав денний ліміт використання ШІ. Спробуйте завтра. Кредити не було списано.",
+            Lang::Es => "⏳ <b>Análisis no disponible</b>\n\nEl bot alcanzó su límite diario de uso de IA. Inténtalo de nuevo mañana. No se consumieron créditos por esta solicitud.",
+            Lang::De => "⏳ <b>Analyse nicht verfügbar</b>\n\nDer Bot hat sein tägliches KI-Limit erreicht. Bitte versuche es morgen erneut. Für diese Anfrage wurden keine Credits verbraucht.",
+        }
+    }
+
+    pub fn error_prompt_generation(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ <b>Analysis Error</b>\n\nFailed to generate analysis prompt. No credits were consumed.",
+            Lang::Ru => "❌ <b>Ошибка анализа</b>\n\nНе удалось сгенерировать промпт. Кредиты не были списаны.",
+            Lang::Uk => "❌ <b>Помилка аналізу</b>\n\nНе вдалося згенерувати промпт. Кредити не було списано.",
+            Lang::Es => "❌ <b>Error de análisis</b>\n\nNo se pudo generar el prompt del análisis. No se consumieron créditos.",
+            Lang::De => "❌ <b>Analysefehler</b>\n\nDer Analyse-Prompt konnte nicht erstellt werden. Es wurden keine Credits verbraucht.",
+        }
+    }
+
+    pub fn error_focus_too_long(&self, max: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "❌ The focus instruction is too long. Please keep it under {max} characters."
+            ),
+            Lang::Ru => {
+                format!("❌ Слишком длинная инструкция. Пожалуйста, уложитесь в {max} символов.")
+            }
+            Lang::Uk => {
+                format!("❌ Задовга інструкція. Будь ласка, вкладіться в {max} символів.")
+            }
+            Lang::Es => format!(
+                "❌ La instrucción de enfoque es demasiado larga. Mantenla por debajo de {max} caracteres."
+            ),
+            Lang::De => format!(
+                "❌ Die Fokus-Anweisung ist zu lang. Bitte bleib unter {max} Zeichen."
+            ),
+        }
+    }
+
+    pub fn error_no_analysis_content(&self, analysis_type: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "❌ No {} analysis content was generated. Please try again.",
+                analysis_type
+            ),
+            Lang::Ru => format!(
+                "❌ Не удалось сгенерировать {} анализ. Попробуйте снова.",
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::Uk => format!(
+                "❌ Не вдалося згенерувати {} аналіз. Спробуйте ще раз.",
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::Es => format!(
+                "❌ No se generó ningún contenido para el {}. Inténtalo de nuevo.",
+                self.analysis_type_name(analysis_type)
+            ),
+            Lang::De => format!(
+                "❌ Für die {} wurde kein Inhalt erzeugt. Bitte versuche es erneut.",
+                self.analysis_type_name(analysis_type)
+            ),
+        }
+    }
+
+    pub fn analysis_credits_returned(&self, credits: i32, balance: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "↩️ The {credits} credits for it have been returned. Balance: {balance} credits."
+            ),
+            Lang::Ru => {
+                format!("↩️ Кредиты за него ({credits}) возвращены. Баланс: {balance} кредитов.")
+            }
+            Lang::Uk => {
+                format!("↩️ Кредити за нього ({credits}) повернуто. Баланс: {balance} кредитів.")
+            }
+            Lang::Es => {
+                format!("↩️ Se te han devuelto los {credits} créditos. Saldo: {balance} créditos.")
+            }
+            Lang::De => format!(
+                "↩️ Die {credits} Credits dafür wurden erstattet. Guthaben: {balance} Credits."
+            ),
+        }
+    }
+}
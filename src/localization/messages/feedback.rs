@@ -0,0 +1,71 @@
+//! feedback on results and its admin report
+
+use super::Lang;
+
+impl Lang {
+    pub fn feedback_thanks(&self) -> &'static str {
+        match self {
+            Lang::En => "Thanks for the feedback!",
+            Lang::Ru => "Спасибо за отзыв!",
+            Lang::Uk => "Дякуємо за відгук!",
+            Lang::Es => "¡Gracias por tu opinión!",
+            Lang::De => "Danke für dein Feedback!",
+        }
+    }
+
+    pub fn feedback_unavailable(&self) -> &'static str {
+        match self {
+            Lang::En => "Only the requester of an analysis can rate it.",
+            Lang::Ru => "Оценить анализ может только тот, кто его заказал.",
+            Lang::Uk => "Оцінити аналіз може лише той, хто його замовив.",
+            Lang::Es => "Solo quien pidió el análisis puede valorarlo.",
+            Lang::De => "Nur wer die Analyse angefordert hat, kann sie bewerten.",
+        }
+    }
+
+    /// /feedback report; sections hold pre-formatted `feedback_line`s
+    pub fn feedback_report(
+        &self,
+        days: i32,
+        by_type: &[String],
+        by_model: &[String],
+        by_prompt_version: &[String],
+    ) -> String {
+        if by_type.is_empty() {
+            return match self {
+                Lang::En => format!("📊 No feedback in the last {days} days."),
+                Lang::Ru => format!("📊 За последние {days} дн. отзывов нет."),
+                Lang::Uk => format!("📊 За останні {days} дн. відгуків немає."),
+                Lang::Es => format!("📊 Sin opiniones en los últimos {days} días."),
+                Lang::De => format!("📊 Kein Feedback in den letzten {days} Tagen."),
+            };
+        }
+        let (by_type, by_model, by_prompt_version) = (
+            by_type.join("\n"),
+            by_model.join("\n"),
+            by_prompt_version.join("\n"),
+        );
+        match self {
+            Lang::En => format!(
+                "📊 <b>Feedback for the last {days} days</b>\n\n<b>By analysis type</b>\n{by_type}\n\n<b>By model</b>\n{by_model}\n\n<b>By prompt version</b>\n{by_prompt_version}"
+            ),
+            Lang::Ru => format!(
+                "📊 <b>Отзывы за последние {days} дн.</b>\n\n<b>По типу анализа</b>\n{by_type}\n\n<b>По модели</b>\n{by_model}\n\n<b>По версии промпта</b>\n{by_prompt_version}"
+            ),
+            Lang::Uk => format!(
+                "📊 <b>Відгуки за останні {days} дн.</b>\n\n<b>За типом аналізу</b>\n{by_type}\n\n<b>За моделлю</b>\n{by_model}\n\n<b>За версією промпту</b>\n{by_prompt_version}"
+            ),
+            Lang::Es => format!(
+                "📊 <b>Opiniones de los últimos {days} días</b>\n\n<b>Por tipo de análisis</b>\n{by_type}\n\n<b>Por modelo</b>\n{by_model}\n\n<b>Por versión del prompt</b>\n{by_prompt_version}"
+            ),
+            Lang::De => format!(
+                "📊 <b>Feedback der letzten {days} Tage</b>\n\n<b>Nach Analyseart</b>\n{by_type}\n\n<b>Nach Modell</b>\n{by_model}\n\n<b>Nach Prompt-Version</b>\n{by_prompt_version}"
+            ),
+        }
+    }
+
+    pub fn feedback_line(&self, key: &str, positive: i64, negative: i64, rate: f64) -> String {
+        // keys are analysis types, model names and prompt versions, shared by all languages
+        format!("<code>{key}</code>: {rate:.0}% · 👍 {positive} · 👎 {negative}")
+    }
+}
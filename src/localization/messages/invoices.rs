@@ -0,0 +1,119 @@
+//! titles and descriptions of star invoices
+use super::Lang;
+use crate::packages::{discount, Package};
+
+impl Lang {
+    pub fn invoice_package_title(&self, credits: i32) -> String {
+        match (self, credits == 1) {
+            (Lang::En, true) => format!("{credits} Channel Analysis"),
+            (Lang::En, false) => format!("{credits} Channel Analyses"),
+            (Lang::Ru, true) => format!("{credits} анализ канала"),
+            (Lang::Ru, false) => format!("{credits} анализов каналов"),
+            (Lang::Uk, true) => format!("{credits} аналіз каналу"),
+            (Lang::Uk, false) => format!("{credits} аналізів каналів"),
+            (Lang::Es, true) => format!("{credits} análisis de canal"),
+            (Lang::Es, false) => format!("{credits} análisis de canales"),
+            (Lang::De, true) => format!("{credits} Kanalanalyse"),
+            (Lang::De, false) => format!("{credits} Kanalanalysen"),
+        }
+    }
+
+    pub fn invoice_package_description(&self, credits: i32, discount: u32) -> String {
+        let description = match (self, credits == 1) {
+            (Lang::En, true) => {
+                format!("Get {credits} analysis credit to analyze any Telegram channel")
+            }
+            (Lang::En, false) => {
+                format!("Get {credits} analysis credits to analyze any Telegram channels")
+            }
+            (Lang::Ru, true) => format!("Получите {credits} кредит для анализа любого Telegram-канала"),
+            (Lang::Ru, false) => format!("Получите {credits} кредитов для анализа Telegram-каналов"),
+            (Lang::Uk, true) => {
+                format!("Отримайте {credits} кредит для аналізу будь-якого Telegram-каналу")
+            }
+            (Lang::Uk, false) => format!("Отримайте {credits} кредитів для аналізу Telegram-каналів"),
+            (Lang::Es, true) => {
+                format!("Consigue {credits} crédito para analizar cualquier canal de Telegram")
+            }
+            (Lang::Es, false) => {
+                format!("Consigue {credits} créditos para analizar cualquier canal de Telegram")
+            }
+            (Lang::De, true) => format!(
+                "Erhalte {credits} Analyse-Credit, um einen beliebigen Telegram-Kanal zu analysieren"
+            ),
+            (Lang::De, false) => {
+                format!("Erhalte {credits} Analyse-Credits für beliebige Telegram-Kanäle")
+            }
+        };
+        if discount == 0 {
+            return description;
+        }
+        match self {
+            Lang::En => format!("{description} ({discount} stars discount!)"),
+            Lang::Ru => format!("{description} (скидка {discount} звёзд!)"),
+            Lang::Uk => format!("{description} (знижка {discount} зірок!)"),
+            Lang::Es => format!("{description} (¡{discount} estrellas de descuento!)"),
+            Lang::De => format!("{description} ({discount} Sterne Rabatt!)"),
+        }
+    }
+
+    /// the price list of the welcome and out-of-credits messages, one line per package
+    pub fn package_prices(&self, packages: &[Package]) -> String {
+        packages
+            .iter()
+            .map(|package| {
+                let (credits, stars) = (package.credits, package.stars);
+                let line = match (self, credits == 1) {
+                    (Lang::En, true) => format!("• {credits} analysis: {stars} ⭐ stars"),
+                    (Lang::En, false) => format!("• {credits} analyses: {stars} ⭐ stars"),
+                    (Lang::Ru, true) => format!("• {credits} анализ: {stars} ⭐ звёзд"),
+                    (Lang::Ru, false) => format!("• {credits} анализов: {stars} ⭐ звёзд"),
+                    (Lang::Uk, true) => format!("• {credits} аналіз: {stars} ⭐ зірок"),
+                    (Lang::Uk, false) => format!("• {credits} аналізів: {stars} ⭐ зірок"),
+                    (Lang::Es, _) => format!("• {credits} análisis: {stars} ⭐ estrellas"),
+                    (Lang::De, true) => format!("• {credits} Analyse: {stars} ⭐ Sterne"),
+                    (Lang::De, false) => format!("• {credits} Analysen: {stars} ⭐ Sterne"),
+                };
+                match (discount(packages, package), self) {
+                    (0, _) => line,
+                    (saved, Lang::En) => format!("{line} (save {saved} stars!)"),
+                    (saved, Lang::Ru) => format!("{line} (экономия {saved} звёзд!)"),
+                    (saved, Lang::Uk) => format!("{line} (економія {saved} зірок!)"),
+                    (saved, Lang::Es) => format!("{line} (¡ahorras {saved} estrellas!)"),
+                    (saved, Lang::De) => format!("{line} (spare {saved} Sterne!)"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn invoice_subscription_title(&self) -> &'static str {
+        match self {
+            Lang::En => "Monthly Subscription",
+            Lang::Ru => "Ежемесячная подписка",
+            Lang::Uk => "Щомісячна підписка",
+            Lang::Es => "Suscripción mensual",
+            Lang::De => "Monatsabo",
+        }
+    }
+
+    pub fn invoice_subscription_description(&self, credits: i32) -> String {
+        match self {
+            Lang::En => {
+                format!("Get {credits} analysis credits every month, renewed automatically")
+            }
+            Lang::Ru => {
+                format!("Получайте {credits} кредитов для анализа каждый месяц с автопродлением")
+            }
+            Lang::Uk => {
+                format!("Отримуйте {credits} кредитів для аналізу щомісяця з автопродовженням")
+            }
+            Lang::Es => {
+                format!("Recibe {credits} créditos de análisis cada mes, con renovación automática")
+            }
+            Lang::De => {
+                format!("Erhalte jeden Monat {credits} Analyse-Credits, automatisch verlängert")
+            }
+        }
+    }
+}
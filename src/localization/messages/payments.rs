@@ -0,0 +1,1048 @@
+//! credits, purchases, subscriptions and the /balance ledger
+use super::Lang;
+use crate::user_manager::{CreditTransaction, CreditTransactionKind};
+
+impl Lang {
+    pub fn no_credits_available(&self, prices: &str, credits: i32, total_analyses: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "❌ <b>No Analysis Credits Available</b>\n\n\
+                You have used all your free analysis credits.\n\n\
+                💰 <b>Purchase More Credits:</b>\n\
+                {prices}\n\n\
+                📊 <b>Your Stats:</b>\n\
+                • Credits remaining: <code>{credits}</code>\n\
+                • Total analyses performed: <code>{total_analyses}</code>\n\n\
+                Choose a package below to continue analyzing channels!"
+            ),
+            Lang::Ru => format!(
+                "❌ <b>Нет кредитов для анализа</b>\n\n\
+                Вы использовали все бесплатные кредиты.\n\n\
+                💰 <b>Купить кредиты:</b>\n\
+                {prices}\n\n\
+                📊 <b>Ваша статистика:</b>\n\
+                • Осталось кредитов: <code>{credits}</code>\n\
+                • Всего анализов: <code>{total_analyses}</code>\n\n\
+                Выберите пакет ниже!"
+            ),
+            Lang::Uk => format!(
+                "❌ <b>Немає кредитів для аналізу</b>\n\n\
+                Ви використали всі безкоштовні кредити.\n\n\
+                💰 <b>Придбати кредити:</b>\n\
+                {prices}\n\n\
+                📊 <b>Ваша статистика:</b>\n\
+                • Залишилося кредитів: <code>{credits}</code>\n\
+                • Усього аналізів: <code>{total_analyses}</code>\n\n\
+                Виберіть пакет нижче!"
+            ),
+            Lang::Es => format!(
+                "❌ <b>No te quedan créditos de análisis</b>\n\n\
+                Has usado todos tus créditos de análisis gratuitos.\n\n\
+                💰 <b>Compra más créditos:</b>\n\
+                {prices}\n\n\
+                📊 <b>Tus estadísticas:</b>\n\
+                • Créditos restantes: <code>{credits}</code>\n\
+                • Análisis realizados: <code>{total_analyses}</code>\n\n\
+                ¡Elige un paquete abajo para seguir analizando canales!"
+            ),
+            Lang::De => format!(
+                "❌ <b>Keine Analyse-Credits verfügbar</b>\n\n\
+                Du hast alle kostenlosen Analyse-Credits verbraucht.\n\n\
+                💰 <b>Weitere Credits kaufen:</b>\n\
+                {prices}\n\n\
+                📊 <b>Deine Statistik:</b>\n\
+                • Verbleibende Credits: <code>{credits}</code>\n\
+                • Durchgeführte Analysen: <code>{total_analyses}</code>\n\n\
+                Wähle unten ein Paket, um weiter Kanäle zu analysieren!"
+            ),
+        }
+    }
+
+    pub fn no_credits_short(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ No analysis credits available.\n\nYou need credits to analyze channels. Choose a package below:",
+            Lang::Ru => "❌ Нет кредитов для анализа.\n\nДля анализа каналов нужны кредиты. Выберите пакет ниже:",
+            Lang::Uk => "❌ Немає кредитів для аналізу.\n\nДля аналізу каналів потрібні кредити. Виберіть пакет нижче:",
+            Lang::Es => "❌ No te quedan créditos de análisis.\n\nNecesitas créditos para analizar canales. Elige un paquete abajo:",
+            Lang::De => "❌ Keine Analyse-Credits verfügbar.\n\nZum Analysieren von Kanälen brauchst du Credits. Wähle unten ein Paket:",
+        }
+    }
+
+    /// the reply to /buy, above the package keyboard
+    pub fn buy_packages(&self, prices: &str) -> String {
+        match self {
+            Lang::En => {
+                format!("💎 <b>Analysis credits</b>\n\n{prices}\n\nChoose a package below:")
+            }
+            Lang::Ru => {
+                format!("💎 <b>Кредиты для анализа</b>\n\n{prices}\n\nВыберите пакет ниже:")
+            }
+            Lang::Uk => {
+                format!("💎 <b>Кредити для аналізу</b>\n\n{prices}\n\nВиберіть пакет нижче:")
+            }
+            Lang::Es => {
+                format!("💎 <b>Créditos de análisis</b>\n\n{prices}\n\nElige un paquete abajo:")
+            }
+            Lang::De => format!("💎 <b>Analyse-Credits</b>\n\n{prices}\n\nWähle unten ein Paket:"),
+        }
+    }
+
+    pub fn no_packages_on_sale(&self) -> &'static str {
+        match self {
+            Lang::En => "💎 No credit packages are on sale right now. Please try again later.",
+            Lang::Ru => "💎 Сейчас пакеты кредитов не продаются. Попробуйте позже.",
+            Lang::Uk => "💎 Зараз пакети кредитів не продаються. Спробуйте пізніше.",
+            Lang::Es => {
+                "💎 Ahora mismo no hay paquetes de créditos a la venta. Inténtalo más tarde."
+            }
+            Lang::De => "💎 Derzeit sind keine Credit-Pakete im Angebot. Bitte versuche es später.",
+        }
+    }
+
+    /// callback answer for a package button retired after its keyboard was sent
+    pub fn package_unavailable(&self) -> &'static str {
+        match self {
+            Lang::En => "This package is no longer on sale. Use /buy to see the current ones.",
+            Lang::Ru => "Этот пакет больше не продаётся. Текущие пакеты — в /buy.",
+            Lang::Uk => "Цей пакет більше не продається. Поточні пакети — у /buy.",
+            Lang::Es => "Este paquete ya no está a la venta. Usa /buy para ver los actuales.",
+            Lang::De => {
+                "Dieses Paket ist nicht mehr im Angebot. Die aktuellen findest du unter /buy."
+            }
+        }
+    }
+
+    pub fn not_enough_credits_for_depth(&self, required: i32, available: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "❌ This analysis depth costs {required} credits, but you have {available}.\n\n\
+                Pick a smaller depth or top up below:"
+            ),
+            Lang::Ru => format!(
+                "❌ Анализ такой глубины стоит {required} кредитов, а у вас {available}.\n\n\
+                Выберите меньшую глубину или пополните баланс:"
+            ),
+            Lang::Uk => format!(
+                "❌ Аналіз такої глибини коштує {required} кредитів, а у вас {available}.\n\n\
+                Виберіть меншу глибину або поповніть баланс:"
+            ),
+            Lang::Es => format!(
+                "❌ Esta profundidad de análisis cuesta {required} créditos, pero tienes {available}.\n\n\
+                Elige una profundidad menor o recarga abajo:"
+            ),
+            Lang::De => format!(
+                "❌ Diese Analysetiefe kostet {required} Credits, du hast aber {available}.\n\n\
+                Wähle eine geringere Tiefe oder lade unten auf:"
+            ),
+        }
+    }
+
+    pub fn payment_success(&self, user_id: i32, credits: i32, new_balance: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "🎉 <b>Payment Successful!</b> - <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                ✅ Added {credits} credits to your account\n\
+                💳 New balance: {new_balance} credits\n\n\
+                You can now analyze channels by sending me a channel username like <code>@channelname</code>"
+            ),
+            Lang::Ru => format!(
+                "🎉 <b>Платёж успешен!</b> - <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                ✅ Добавлено {credits} кредитов на ваш счёт\n\
+                💳 Новый баланс: {new_balance} кредитов\n\n\
+                Теперь вы можете анализировать каналы, отправив имя канала, например <code>@channelname</code>"
+            ),
+            Lang::Uk => format!(
+                "🎉 <b>Платіж успішний!</b> - <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                ✅ На ваш рахунок додано {credits} кредитів\n\
+                💳 Новий баланс: {new_balance} кредитів\n\n\
+                Тепер ви можете аналізувати канали, надіславши ім'я каналу, наприклад <code>@channelname</code>"
+            ),
+            Lang::Es => format!(
+                "🎉 <b>¡Pago completado!</b> - <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                ✅ Se añadieron {credits} créditos a tu cuenta\n\
+                💳 Nuevo saldo: {new_balance} créditos\n\n\
+                Ya puedes analizar canales enviándome un nombre como <code>@channelname</code>"
+            ),
+            Lang::De => format!(
+                "🎉 <b>Zahlung erfolgreich!</b> - <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                ✅ {credits} Credits wurden deinem Konto gutgeschrieben\n\
+                💳 Neues Guthaben: {new_balance} Credits\n\n\
+                Du kannst jetzt Kanäle analysieren, indem du mir einen Kanalnamen wie <code>@channelname</code> sendest"
+            ),
+        }
+    }
+
+    pub fn subscription_offer(&self, credits: i32, price: u32) -> String {
+        match self {
+            Lang::En => format!(
+                "🔁 <b>Monthly subscription</b>\n\n\
+                Get <b>{credits}</b> credits every month for {price} ⭐. Telegram renews it automatically, \
+                and you can cancel it any time in your Telegram settings."
+            ),
+            Lang::Ru => format!(
+                "🔁 <b>Ежемесячная подписка</b>\n\n\
+                Получайте <b>{credits}</b> кредитов каждый месяц за {price} ⭐. Telegram продлевает её автоматически, \
+                отменить можно в любой момент в настройках Telegram."
+            ),
+            Lang::Uk => format!(
+                "🔁 <b>Щомісячна підписка</b>\n\n\
+                Отримуйте <b>{credits}</b> кредитів щомісяця за {price} ⭐. Telegram продовжує її автоматично, \
+                скасувати можна будь-коли в налаштуваннях Telegram."
+            ),
+            Lang::Es => format!(
+                "🔁 <b>Suscripción mensual</b>\n\n\
+                Recibe <b>{credits}</b> créditos cada mes por {price} ⭐. Telegram la renueva automáticamente \
+                y puedes cancelarla cuando quieras en los ajustes de Telegram."
+            ),
+            Lang::De => format!(
+                "🔁 <b>Monatsabo</b>\n\n\
+                Erhalte jeden Monat <b>{credits}</b> Credits für {price} ⭐. Telegram verlängert es automatisch, \
+                und du kannst es jederzeit in deinen Telegram-Einstellungen kündigen."
+            ),
+        }
+    }
+
+    /// /subscribe of a current subscriber; `paid_until` is UTC
+    pub fn subscription_status(&self, credits: i32, paid_until: &str, grace: bool) -> String {
+        match (self, grace) {
+            (Lang::En, false) => format!(
+                "🔁 Your subscription is active: <b>{credits}</b> credits a month, paid until {paid_until} UTC."
+            ),
+            (Lang::Ru, false) => format!(
+                "🔁 Ваша подписка активна: <b>{credits}</b> кредитов в месяц, оплачена до {paid_until} UTC."
+            ),
+            (Lang::Uk, false) => format!(
+                "🔁 Ваша підписка активна: <b>{credits}</b> кредитів на місяць, оплачена до {paid_until} UTC."
+            ),
+            (Lang::Es, false) => format!(
+                "🔁 Tu suscripción está activa: <b>{credits}</b> créditos al mes, pagada hasta el {paid_until} UTC."
+            ),
+            (Lang::De, false) => format!(
+                "🔁 Dein Abo ist aktiv: <b>{credits}</b> Credits im Monat, bezahlt bis {paid_until} UTC."
+            ),
+            (Lang::En, true) => format!(
+                "⏳ Your subscription ran out on {paid_until} UTC. Top up your stars so Telegram can renew it."
+            ),
+            (Lang::Ru, true) => format!(
+                "⏳ Подписка закончилась {paid_until} UTC. Пополните звёзды, чтобы Telegram смог её продлить."
+            ),
+            (Lang::Uk, true) => format!(
+                "⏳ Підписка закінчилася {paid_until} UTC. Поповніть зірки, щоб Telegram зміг її продовжити."
+            ),
+            (Lang::Es, true) => format!(
+                "⏳ Tu suscripción terminó el {paid_until} UTC. Recarga estrellas para que Telegram pueda renovarla."
+            ),
+            (Lang::De, true) => format!(
+                "⏳ Dein Abo ist am {paid_until} UTC abgelaufen. Lade Sterne auf, damit Telegram es verlängern kann."
+            ),
+        }
+    }
+
+    /// a subscription payment whose credits were just added; `paid_until` is UTC
+    pub fn subscription_paid(
+        &self,
+        credits: i32,
+        new_balance: i32,
+        paid_until: &str,
+        renewal: bool,
+    ) -> String {
+        match (self, renewal) {
+            (Lang::En, false) => format!(
+                "🎉 <b>Subscription started!</b>\n\n✅ Added {credits} credits\n💳 New balance: {new_balance} credits\n🔁 Next renewal: {paid_until} UTC"
+            ),
+            (Lang::Ru, false) => format!(
+                "🎉 <b>Подписка оформлена!</b>\n\n✅ Добавлено {credits} кредитов\n💳 Новый баланс: {new_balance} кредитов\n🔁 Следующее продление: {paid_until} UTC"
+            ),
+            (Lang::Uk, false) => format!(
+                "🎉 <b>Підписку оформлено!</b>\n\n✅ Додано {credits} кредитів\n💳 Новий баланс: {new_balance} кредитів\n🔁 Наступне продовження: {paid_until} UTC"
+            ),
+            (Lang::Es, false) => format!(
+                "🎉 <b>¡Suscripción iniciada!</b>\n\n✅ Se añadieron {credits} créditos\n💳 Nuevo saldo: {new_balance} créditos\n🔁 Próxima renovación: {paid_until} UTC"
+            ),
+            (Lang::De, false) => format!(
+                "🎉 <b>Abo gestartet!</b>\n\n✅ {credits} Credits gutgeschrieben\n💳 Neues Guthaben: {new_balance} Credits\n🔁 Nächste Verlängerung: {paid_until} UTC"
+            ),
+            (Lang::En, true) => format!(
+                "🔁 <b>Subscription renewed</b>\n\n✅ Added {credits} credits\n💳 New balance: {new_balance} credits\n🔁 Next renewal: {paid_until} UTC"
+            ),
+            (Lang::Ru, true) => format!(
+                "🔁 <b>Подписка продлена</b>\n\n✅ Добавлено {credits} кредитов\n💳 Новый баланс: {new_balance} кредитов\n🔁 Следующее продление: {paid_until} UTC"
+            ),
+            (Lang::Uk, true) => format!(
+                "🔁 <b>Підписку продовжено</b>\n\n✅ Додано {credits} кредитів\n💳 Новий баланс: {new_balance} кредитів\n🔁 Наступне продовження: {paid_until} UTC"
+            ),
+            (Lang::Es, true) => format!(
+                "🔁 <b>Suscripción renovada</b>\n\n✅ Se añadieron {credits} créditos\n💳 Nuevo saldo: {new_balance} créditos\n🔁 Próxima renovación: {paid_until} UTC"
+            ),
+            (Lang::De, true) => format!(
+                "🔁 <b>Abo verlängert</b>\n\n✅ {credits} Credits gutgeschrieben\n💳 Neues Guthaben: {new_balance} Credits\n🔁 Nächste Verlängerung: {paid_until} UTC"
+            ),
+        }
+    }
+
+    pub fn subscription_credits_pending(&self) -> &'static str {
+        match self {
+            Lang::En => {
+                "✅ Subscription payment received. Your credits will be added within a few minutes."
+            }
+            Lang::Ru => {
+                "✅ Оплата подписки получена. Кредиты будут добавлены в течение нескольких минут."
+            }
+            Lang::Uk => "✅ Оплату підписки отримано. Кредити буде додано протягом кількох хвилин.",
+            Lang::Es => {
+                "✅ Pago de la suscripción recibido. Tus créditos se añadirán en unos minutos."
+            }
+            Lang::De => {
+                "✅ Abo-Zahlung erhalten. Deine Credits werden in wenigen Minuten gutgeschrieben."
+            }
+        }
+    }
+
+    pub fn subscription_grace(&self, days: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "⏳ Your subscription couldn't be renewed. Top up your stars within {days} days to keep it."
+            ),
+            Lang::Ru => format!(
+                "⏳ Не удалось продлить подписку. Пополните звёзды в течение {days} дн., чтобы сохранить её."
+            ),
+            Lang::Uk => format!(
+                "⏳ Не вдалося продовжити підписку. Поповніть зірки протягом {days} дн., щоб зберегти її."
+            ),
+            Lang::Es => format!(
+                "⏳ No se pudo renovar tu suscripción. Recarga estrellas en los próximos {days} días para conservarla."
+            ),
+            Lang::De => format!(
+                "⏳ Dein Abo konnte nicht verlängert werden. Lade innerhalb von {days} Tagen Sterne auf, um es zu behalten."
+            ),
+        }
+    }
+
+    pub fn subscription_expired(&self) -> &'static str {
+        match self {
+            Lang::En => "🔁 Your subscription has ended. Send /subscribe to start a new one.",
+            Lang::Ru => "🔁 Подписка закончилась. Отправьте /subscribe, чтобы оформить новую.",
+            Lang::Uk => "🔁 Підписка закінчилася. Надішліть /subscribe, щоб оформити нову.",
+            Lang::Es => "🔁 Tu suscripción ha terminado. Envía /subscribe para empezar una nueva.",
+            Lang::De => "🔁 Dein Abo ist beendet. Sende /subscribe, um ein neues abzuschließen.",
+        }
+    }
+
+    pub fn credits_label(&self, credits: i32) -> String {
+        match self {
+            Lang::En => format!("{} credits", credits),
+            Lang::Ru => format!("{} кредитов", credits),
+            Lang::Uk => format!("{} кредитів", credits),
+            Lang::Es => format!("{} créditos", credits),
+            Lang::De => format!("{} Credits", credits),
+        }
+    }
+
+    pub fn refund_notice(&self, stars: i32, new_balance: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "↩️ <b>Payment Refunded</b>\n\n\
+                ⭐ {stars} stars have been returned to your account.\n\
+                💳 Credit balance: <code>{new_balance}</code>\n\n\
+                Sorry for the trouble with your analysis."
+            ),
+            Lang::Ru => format!(
+                "↩️ <b>Платёж возвращён</b>\n\n\
+                ⭐ {stars} звёзд возвращено на ваш счёт.\n\
+                💳 Баланс кредитов: <code>{new_balance}</code>\n\n\
+                Приносим извинения за проблемы с анализом."
+            ),
+            Lang::Uk => format!(
+                "↩️ <b>Платіж повернено</b>\n\n\
+                ⭐ {stars} зірок повернено на ваш рахунок.\n\
+                💳 Баланс кредитів: <code>{new_balance}</code>\n\n\
+                Перепрошуємо за проблеми з аналізом."
+            ),
+            Lang::Es => format!(
+                "↩️ <b>Pago reembolsado</b>\n\n\
+                ⭐ Se devolvieron {stars} estrellas a tu cuenta.\n\
+                💳 Saldo de créditos: <code>{new_balance}</code>\n\n\
+                Sentimos los problemas con tu análisis."
+            ),
+            Lang::De => format!(
+                "↩️ <b>Zahlung erstattet</b>\n\n\
+                ⭐ {stars} Sterne wurden deinem Konto zurückerstattet.\n\
+                💳 Credit-Guthaben: <code>{new_balance}</code>\n\n\
+                Entschuldige die Probleme mit deiner Analyse."
+            ),
+        }
+    }
+
+    pub fn refund_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/refund &lt;telegram_payment_charge_id&gt; [reason]</code>",
+            Lang::Ru => {
+                "Использование: <code>/refund &lt;telegram_payment_charge_id&gt; [причина]</code>"
+            }
+            Lang::Uk => {
+                "Використання: <code>/refund &lt;telegram_payment_charge_id&gt; [причина]</code>"
+            }
+            Lang::Es => "Uso: <code>/refund &lt;telegram_payment_charge_id&gt; [motivo]</code>",
+            Lang::De => {
+                "Verwendung: <code>/refund &lt;telegram_payment_charge_id&gt; [Grund]</code>"
+            }
+        }
+    }
+
+    pub fn refund_issued(
+        &self,
+        charge_id: &str,
+        stars: i32,
+        user_id: i32,
+        new_balance: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "✅ Refunded {stars} stars for payment <code>{charge_id}</code> (user {user_id}). \
+                New credit balance: {new_balance}"
+            ),
+            Lang::Ru => format!(
+                "✅ Возвращено {stars} звёзд за платёж <code>{charge_id}</code> (пользователь {user_id}). \
+                Новый баланс кредитов: {new_balance}"
+            ),
+            Lang::Uk => format!(
+                "✅ Повернено {stars} зірок за платіж <code>{charge_id}</code> (користувач {user_id}). \
+                Новий баланс кредитів: {new_balance}"
+            ),
+            Lang::Es => format!(
+                "✅ Reembolsadas {stars} estrellas del pago <code>{charge_id}</code> (usuario {user_id}). \
+                Nuevo saldo de créditos: {new_balance}"
+            ),
+            Lang::De => format!(
+                "✅ {stars} Sterne für die Zahlung <code>{charge_id}</code> erstattet (Nutzer {user_id}). \
+                Neues Credit-Guthaben: {new_balance}"
+            ),
+        }
+    }
+
+    pub fn refund_failed(&self, error: &str) -> String {
+        match self {
+            Lang::En => format!("❌ Refund failed: {error}"),
+            Lang::Ru => format!("❌ Не удалось вернуть платёж: {error}"),
+            Lang::Uk => format!("❌ Не вдалося повернути платіж: {error}"),
+            Lang::Es => format!("❌ No se pudo reembolsar: {error}"),
+            Lang::De => format!("❌ Erstattung fehlgeschlagen: {error}"),
+        }
+    }
+
+    pub fn admin_forbidden(&self, role: &str) -> String {
+        match self {
+            Lang::En => format!("⛔ Your admin role (<b>{role}</b>) doesn't allow this command."),
+            Lang::Ru => {
+                format!("⛔ Ваша роль администратора (<b>{role}</b>) не позволяет эту команду.")
+            }
+            Lang::Uk => {
+                format!("⛔ Ваша роль адміністратора (<b>{role}</b>) не дозволяє цю команду.")
+            }
+            Lang::Es => {
+                format!("⛔ Tu rol de administrador (<b>{role}</b>) no permite este comando.")
+            }
+            Lang::De => {
+                format!("⛔ Deine Admin-Rolle (<b>{role}</b>) erlaubt diesen Befehl nicht.")
+            }
+        }
+    }
+
+    pub fn role_usage(&self) -> &'static str {
+        match self {
+            Lang::En => {
+                "Usage: <code>/role &lt;telegram_user_id&gt; &lt;owner|support|marketing|none&gt;</code>"
+            }
+            Lang::Ru => {
+                "Использование: <code>/role &lt;telegram_user_id&gt; &lt;owner|support|marketing|none&gt;</code>"
+            }
+            Lang::Uk => {
+                "Використання: <code>/role &lt;telegram_user_id&gt; &lt;owner|support|marketing|none&gt;</code>"
+            }
+            Lang::Es => {
+                "Uso: <code>/role &lt;telegram_user_id&gt; &lt;owner|support|marketing|none&gt;</code>"
+            }
+            Lang::De => {
+                "Verwendung: <code>/role &lt;telegram_user_id&gt; &lt;owner|support|marketing|none&gt;</code>"
+            }
+        }
+    }
+
+    /// `role` is None when the role was removed
+    pub fn role_updated(&self, telegram_user_id: i64, role: Option<&str>) -> String {
+        match (self, role) {
+            (Lang::En, Some(role)) => {
+                format!("✅ User {telegram_user_id} is now <b>{role}</b>.")
+            }
+            (Lang::En, None) => format!("✅ User {telegram_user_id} no longer has an admin role."),
+            (Lang::Ru, Some(role)) => {
+                format!("✅ Пользователь {telegram_user_id} теперь <b>{role}</b>.")
+            }
+            (Lang::Ru, None) => {
+                format!("✅ У пользователя {telegram_user_id} больше нет роли администратора.")
+            }
+            (Lang::Uk, Some(role)) => {
+                format!("✅ Користувач {telegram_user_id} тепер <b>{role}</b>.")
+            }
+            (Lang::Uk, None) => {
+                format!("✅ У користувача {telegram_user_id} більше немає ролі адміністратора.")
+            }
+            (Lang::Es, Some(role)) => {
+                format!("✅ El usuario {telegram_user_id} ahora es <b>{role}</b>.")
+            }
+            (Lang::Es, None) => {
+                format!("✅ El usuario {telegram_user_id} ya no tiene rol de administrador.")
+            }
+            (Lang::De, Some(role)) => {
+                format!("✅ Nutzer {telegram_user_id} ist jetzt <b>{role}</b>.")
+            }
+            (Lang::De, None) => {
+                format!("✅ Nutzer {telegram_user_id} hat keine Admin-Rolle mehr.")
+            }
+        }
+    }
+
+    pub fn role_update_failed(&self, error: &str) -> String {
+        match self {
+            Lang::En => format!("❌ Failed to update the role: {error}"),
+            Lang::Ru => format!("❌ Не удалось изменить роль: {error}"),
+            Lang::Uk => format!("❌ Не вдалося змінити роль: {error}"),
+            Lang::Es => format!("❌ No se pudo actualizar el rol: {error}"),
+            Lang::De => format!("❌ Die Rolle konnte nicht geändert werden: {error}"),
+        }
+    }
+
+    /// `entries` are lines built with audit_entry, newest first
+    pub fn audit_log(&self, entries: &[String]) -> String {
+        match (self, entries.is_empty()) {
+            (Lang::En, true) => "📋 The audit log is empty.".to_string(),
+            (Lang::Ru, true) => "📋 Журнал действий пуст.".to_string(),
+            (Lang::Uk, true) => "📋 Журнал дій порожній.".to_string(),
+            (Lang::Es, true) => "📋 El registro de auditoría está vacío.".to_string(),
+            (Lang::De, true) => "📋 Das Audit-Log ist leer.".to_string(),
+            (Lang::En, false) => {
+                format!("📋 <b>Recent admin actions</b>\n\n{}", entries.join("\n"))
+            }
+            (Lang::Ru, false) => format!(
+                "📋 <b>Последние действия администраторов</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::Uk, false) => format!(
+                "📋 <b>Останні дії адміністраторів</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::Es, false) => format!(
+                "📋 <b>Acciones recientes de administradores</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::De, false) => {
+                format!("📋 <b>Letzte Admin-Aktionen</b>\n\n{}", entries.join("\n"))
+            }
+        }
+    }
+
+    pub fn audit_entry(
+        &self,
+        created_at: &str,
+        actor: i64,
+        action: &str,
+        arguments: &str,
+        outcome: &str,
+    ) -> String {
+        // action and outcome are stable codes, shared by all languages
+        format!("{created_at} · {actor} · <code>{action}</code> {arguments} · {outcome}")
+    }
+
+    pub fn top_channels(&self, entries: &[String]) -> String {
+        match (self, entries.is_empty()) {
+            (Lang::En, true) => "🏆 No channels have been analyzed this week yet.".to_string(),
+            (Lang::Ru, true) => {
+                "🏆 На этой неделе ещё не проанализировано ни одного канала.".to_string()
+            }
+            (Lang::Uk, true) => "🏆 Цього тижня ще не проаналізовано жодного каналу.".to_string(),
+            (Lang::Es, true) => {
+                "🏆 Todavía no se ha analizado ningún canal esta semana.".to_string()
+            }
+            (Lang::De, true) => "🏆 Diese Woche wurde noch kein Kanal analysiert.".to_string(),
+            (Lang::En, false) => format!(
+                "🏆 <b>Most analyzed channels this week</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::Ru, false) => format!(
+                "🏆 <b>Самые анализируемые каналы недели</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::Uk, false) => format!(
+                "🏆 <b>Найчастіше аналізовані канали тижня</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::Es, false) => format!(
+                "🏆 <b>Canales más analizados esta semana</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::De, false) => format!(
+                "🏆 <b>Meistanalysierte Kanäle dieser Woche</b>\n\n{}",
+                entries.join("\n")
+            ),
+        }
+    }
+
+    pub fn top_channel_entry(
+        &self,
+        rank: usize,
+        channel_name: &str,
+        analyses: i32,
+        total_analyses: i64,
+        average_score: Option<f64>,
+    ) -> String {
+        let score = average_score.map(|score| format!(" · ⭐ {score:.1}"));
+        let score = score.as_deref().unwrap_or("");
+        match self {
+            Lang::En => format!(
+                "{rank}. {channel_name} · {analyses} this week ({total_analyses} total){score}"
+            ),
+            Lang::Ru => format!(
+                "{rank}. {channel_name} · {analyses} за неделю (всего {total_analyses}){score}"
+            ),
+            Lang::Uk => format!(
+                "{rank}. {channel_name} · {analyses} за тиждень (усього {total_analyses}){score}"
+            ),
+            Lang::Es => format!(
+                "{rank}. {channel_name} · {analyses} esta semana ({total_analyses} en total){score}"
+            ),
+            Lang::De => format!(
+                "{rank}. {channel_name} · {analyses} diese Woche ({total_analyses} insgesamt){score}"
+            ),
+        }
+    }
+
+    /// /referrals: the user's own numbers and link; `leaderboard` comes from `referrals_leaderboard`
+    pub fn referrals_stats(
+        &self,
+        referrals: i32,
+        paid_referrals: i32,
+        this_week: i64,
+        rank: Option<i64>,
+        link: &str,
+        leaderboard: &str,
+    ) -> String {
+        let rank = match (self, rank) {
+            (_, None) => String::new(),
+            (Lang::En, Some(rank)) => format!(" · #{rank} this week"),
+            (Lang::Ru, Some(rank)) => format!(" · #{rank} на этой неделе"),
+            (Lang::Uk, Some(rank)) => format!(" · #{rank} цього тижня"),
+            (Lang::Es, Some(rank)) => format!(" · #{rank} esta semana"),
+            (Lang::De, Some(rank)) => format!(" · #{rank} diese Woche"),
+        };
+        match self {
+            Lang::En => format!(
+                "🎁 <b>Your referrals</b>\n\n\
+                👥 Invited: {referrals}\n\
+                💰 Paid: {paid_referrals}\n\
+                📅 This week: {this_week}{rank}\n\n\
+                Your link: <code>{link}</code>\n\n{leaderboard}"
+            ),
+            Lang::Ru => format!(
+                "🎁 <b>Ваши рефералы</b>\n\n\
+                👥 Приглашено: {referrals}\n\
+                💰 Оплатили: {paid_referrals}\n\
+                📅 На этой неделе: {this_week}{rank}\n\n\
+                Ваша ссылка: <code>{link}</code>\n\n{leaderboard}"
+            ),
+            Lang::Uk => format!(
+                "🎁 <b>Ваші реферали</b>\n\n\
+                👥 Запрошено: {referrals}\n\
+                💰 Оплатили: {paid_referrals}\n\
+                📅 Цього тижня: {this_week}{rank}\n\n\
+                Ваше посилання: <code>{link}</code>\n\n{leaderboard}"
+            ),
+            Lang::Es => format!(
+                "🎁 <b>Tus referidos</b>\n\n\
+                👥 Invitados: {referrals}\n\
+                💰 De pago: {paid_referrals}\n\
+                📅 Esta semana: {this_week}{rank}\n\n\
+                Tu enlace: <code>{link}</code>\n\n{leaderboard}"
+            ),
+            Lang::De => format!(
+                "🎁 <b>Deine Empfehlungen</b>\n\n\
+                👥 Eingeladen: {referrals}\n\
+                💰 Bezahlt: {paid_referrals}\n\
+                📅 Diese Woche: {this_week}{rank}\n\n\
+                Dein Link: <code>{link}</code>\n\n{leaderboard}"
+            ),
+        }
+    }
+
+    pub fn referrals_leaderboard(&self, entries: &[String]) -> String {
+        match (self, entries.is_empty()) {
+            (Lang::En, true) => "🏆 Nobody has invited anyone this week yet.".to_string(),
+            (Lang::Ru, true) => "🏆 На этой неделе ещё никто никого не пригласил.".to_string(),
+            (Lang::Uk, true) => "🏆 Цього тижня ще ніхто нікого не запросив.".to_string(),
+            (Lang::Es, true) => "🏆 Nadie ha invitado a nadie esta semana todavía.".to_string(),
+            (Lang::De, true) => "🏆 Diese Woche hat noch niemand jemanden eingeladen.".to_string(),
+            (Lang::En, false) => {
+                format!(
+                    "🏆 <b>Top referrers this week</b>\n\n{}",
+                    entries.join("\n")
+                )
+            }
+            (Lang::Ru, false) => {
+                format!("🏆 <b>Лучшие рефереры недели</b>\n\n{}", entries.join("\n"))
+            }
+            (Lang::Uk, false) => {
+                format!(
+                    "🏆 <b>Найкращі реферери тижня</b>\n\n{}",
+                    entries.join("\n")
+                )
+            }
+            (Lang::Es, false) => format!(
+                "🏆 <b>Mejores referidores de la semana</b>\n\n{}",
+                entries.join("\n")
+            ),
+            (Lang::De, false) => format!(
+                "🏆 <b>Top-Empfehler der Woche</b>\n\n{}",
+                entries.join("\n")
+            ),
+        }
+    }
+
+    /// a leaderboard line; `name` comes anonymized and pre-escaped
+    pub fn referrals_leaderboard_entry(
+        &self,
+        rank: i64,
+        name: &str,
+        referrals: i64,
+        is_you: bool,
+    ) -> String {
+        let you = match (self, is_you) {
+            (_, false) => "",
+            (Lang::En, true) => " (you)",
+            (Lang::Ru, true) => " (вы)",
+            (Lang::Uk, true) => " (ви)",
+            (Lang::Es, true) => " (tú)",
+            (Lang::De, true) => " (du)",
+        };
+        format!("{rank}. {name}{you} · {referrals}")
+    }
+
+    pub fn referrals_card_caption(&self) -> &'static str {
+        match self {
+            Lang::En => "📸 Share this card to invite friends with your link!",
+            Lang::Ru => "📸 Поделитесь этой карточкой, чтобы пригласить друзей по вашей ссылке!",
+            Lang::Uk => "📸 Поділіться цією карткою, щоб запросити друзів за вашим посиланням!",
+            Lang::Es => "📸 ¡Comparte esta tarjeta para invitar a tus amigos con tu enlace!",
+            Lang::De => "📸 Teile diese Karte, um Freunde über deinen Link einzuladen!",
+        }
+    }
+
+    pub fn announce_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/announce &lt;changelog_entry_id&gt;</code>",
+            Lang::Ru => "Использование: <code>/announce &lt;changelog_entry_id&gt;</code>",
+            Lang::Uk => "Використання: <code>/announce &lt;changelog_entry_id&gt;</code>",
+            Lang::Es => "Uso: <code>/announce &lt;changelog_entry_id&gt;</code>",
+            Lang::De => "Verwendung: <code>/announce &lt;changelog_entry_id&gt;</code>",
+        }
+    }
+
+    pub fn announce_queued(&self, entry_id: i32, recipients: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "📣 Changelog entry {entry_id} queued for announcement to {recipients} users."
+            ),
+            Lang::Ru => format!(
+                "📣 Запись {entry_id} поставлена в очередь на рассылку {recipients} пользователям."
+            ),
+            Lang::Uk => format!(
+                "📣 Запис {entry_id} поставлено в чергу на розсилку {recipients} користувачам."
+            ),
+            Lang::Es => format!(
+                "📣 La entrada {entry_id} del changelog se puso en cola para anunciarse a {recipients} usuarios."
+            ),
+            Lang::De => format!(
+                "📣 Changelog-Eintrag {entry_id} wurde für die Ankündigung an {recipients} Nutzer eingereiht."
+            ),
+        }
+    }
+
+    pub fn announce_failed(&self, error: &str) -> String {
+        match self {
+            Lang::En => format!("❌ Failed to queue the announcement: {error}"),
+            Lang::Ru => format!("❌ Не удалось запланировать рассылку: {error}"),
+            Lang::Uk => format!("❌ Не вдалося запланувати розсилку: {error}"),
+            Lang::Es => format!("❌ No se pudo poner en cola el anuncio: {error}"),
+            Lang::De => format!("❌ Die Ankündigung konnte nicht eingereiht werden: {error}"),
+        }
+    }
+
+    pub fn backend_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/backend web-only|api-only|prefer-web|prefer-api</code>",
+            Lang::Ru => {
+                "Использование: <code>/backend web-only|api-only|prefer-web|prefer-api</code>"
+            }
+            Lang::Uk => {
+                "Використання: <code>/backend web-only|api-only|prefer-web|prefer-api</code>"
+            }
+            Lang::Es => "Uso: <code>/backend web-only|api-only|prefer-web|prefer-api</code>",
+            Lang::De => "Verwendung: <code>/backend web-only|api-only|prefer-web|prefer-api</code>",
+        }
+    }
+
+    pub fn backend_status(&self, policy: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🛰 Messages are fetched with the <b>{policy}</b> backend policy.\n\n{}",
+                self.backend_usage()
+            ),
+            Lang::Ru => format!(
+                "🛰 Сообщения загружаются по политике бэкендов <b>{policy}</b>.\n\n{}",
+                self.backend_usage()
+            ),
+            Lang::Uk => format!(
+                "🛰 Повідомлення завантажуються за політикою бекендів <b>{policy}</b>.\n\n{}",
+                self.backend_usage()
+            ),
+            Lang::Es => format!(
+                "🛰 Los mensajes se obtienen con la política de backend <b>{policy}</b>.\n\n{}",
+                self.backend_usage()
+            ),
+            Lang::De => format!(
+                "🛰 Nachrichten werden mit der Backend-Richtlinie <b>{policy}</b> abgerufen.\n\n{}",
+                self.backend_usage()
+            ),
+        }
+    }
+
+    pub fn backend_updated(&self, policy: &str) -> String {
+        match self {
+            Lang::En => format!("🛰 Backend policy set to <b>{policy}</b> until the next restart."),
+            Lang::Ru => format!(
+                "🛰 Политика бэкендов изменена на <b>{policy}</b> до следующего перезапуска."
+            ),
+            Lang::Uk => {
+                format!("🛰 Політику бекендів змінено на <b>{policy}</b> до наступного перезапуску.")
+            }
+            Lang::Es => format!(
+                "🛰 Política de backend cambiada a <b>{policy}</b> hasta el próximo reinicio."
+            ),
+            Lang::De => format!(
+                "🛰 Backend-Richtlinie bis zum nächsten Neustart auf <b>{policy}</b> gesetzt."
+            ),
+        }
+    }
+
+    pub fn config_reloaded(&self, changes: &[String]) -> String {
+        let changes = changes
+            .iter()
+            .map(|change| format!("• <code>{change}</code>"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        match self {
+            Lang::En => format!("🔄 Config reloaded:\n{changes}"),
+            Lang::Ru => format!("🔄 Конфигурация перезагружена:\n{changes}"),
+            Lang::Uk => format!("🔄 Конфігурацію перезавантажено:\n{changes}"),
+            Lang::Es => format!("🔄 Configuración recargada:\n{changes}"),
+            Lang::De => format!("🔄 Konfiguration neu geladen:\n{changes}"),
+        }
+    }
+
+    pub fn config_unchanged(&self) -> &'static str {
+        match self {
+            Lang::En => "🔄 Config reloaded, nothing changed.",
+            Lang::Ru => "🔄 Конфигурация перезагружена, изменений нет.",
+            Lang::Uk => "🔄 Конфігурацію перезавантажено, змін немає.",
+            Lang::Es => "🔄 Configuración recargada, sin cambios.",
+            Lang::De => "🔄 Konfiguration neu geladen, nichts geändert.",
+        }
+    }
+
+    pub fn config_reload_failed(&self, error: &str) -> String {
+        match self {
+            Lang::En => format!("❌ Config reload failed, the current one stays: {error}"),
+            Lang::Ru => {
+                format!("❌ Не удалось перезагрузить конфигурацию, действует текущая: {error}")
+            }
+            Lang::Uk => {
+                format!("❌ Не вдалося перезавантажити конфігурацію, діє поточна: {error}")
+            }
+            Lang::Es => {
+                format!("❌ No se pudo recargar la configuración, sigue la actual: {error}")
+            }
+            Lang::De => format!(
+                "❌ Neuladen der Konfiguration fehlgeschlagen, die aktuelle bleibt: {error}"
+            ),
+        }
+    }
+
+    pub fn requeue_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/requeue all</code> or <code>/requeue &lt;message id&gt;</code>",
+            Lang::Ru => {
+                "Использование: <code>/requeue all</code> или <code>/requeue &lt;id сообщения&gt;</code>"
+            }
+            Lang::Uk => {
+                "Використання: <code>/requeue all</code> або <code>/requeue &lt;id повідомлення&gt;</code>"
+            }
+            Lang::Es => {
+                "Uso: <code>/requeue all</code> o <code>/requeue &lt;id del mensaje&gt;</code>"
+            }
+            Lang::De => {
+                "Verwendung: <code>/requeue all</code> oder <code>/requeue &lt;Nachrichten-ID&gt;</code>"
+            }
+        }
+    }
+
+    pub fn requeue_status(&self, dead: i64) -> String {
+        match self {
+            Lang::En => format!(
+                "📭 <b>{dead}</b> queued messages ran out of send attempts.\n\n{}",
+                self.requeue_usage()
+            ),
+            Lang::Ru => format!(
+                "📭 У <b>{dead}</b> сообщений в очереди закончились попытки отправки.\n\n{}",
+                self.requeue_usage()
+            ),
+            Lang::Uk => format!(
+                "📭 У <b>{dead}</b> повідомлень у черзі закінчилися спроби надсилання.\n\n{}",
+                self.requeue_usage()
+            ),
+            Lang::Es => format!(
+                "📭 <b>{dead}</b> mensajes en cola agotaron sus intentos de envío.\n\n{}",
+                self.requeue_usage()
+            ),
+            Lang::De => format!(
+                "📭 <b>{dead}</b> Nachrichten in der Warteschlange haben keine Sendeversuche mehr.\n\n{}",
+                self.requeue_usage()
+            ),
+        }
+    }
+
+    pub fn requeue_done(&self, requeued: u64) -> String {
+        match self {
+            Lang::En => format!("📬 Requeued <b>{requeued}</b> messages."),
+            Lang::Ru => format!("📬 Возвращено в очередь сообщений: <b>{requeued}</b>."),
+            Lang::Uk => format!("📬 Повернено в чергу повідомлень: <b>{requeued}</b>."),
+            Lang::Es => format!("📬 Se devolvieron <b>{requeued}</b> mensajes a la cola."),
+            Lang::De => format!("📬 <b>{requeued}</b> Nachrichten erneut eingereiht."),
+        }
+    }
+
+    pub fn requeue_failed(&self, error: &str) -> String {
+        match self {
+            Lang::En => format!("❌ Failed to requeue messages: {error}"),
+            Lang::Ru => format!("❌ Не удалось вернуть сообщения в очередь: {error}"),
+            Lang::Uk => format!("❌ Не вдалося повернути повідомлення в чергу: {error}"),
+            Lang::Es => format!("❌ No se pudieron devolver los mensajes a la cola: {error}"),
+            Lang::De => format!("❌ Nachrichten konnten nicht erneut eingereiht werden: {error}"),
+        }
+    }
+
+    /// /balance page; `page` is zero-based
+    pub fn balance_overview(
+        &self,
+        balance: i32,
+        transactions: &[CreditTransaction],
+        page: u32,
+        total_pages: u32,
+    ) -> String {
+        if transactions.is_empty() {
+            return match self {
+                Lang::En => format!(
+                    "💳 <b>Balance:</b> <code>{balance}</code> credits\n\n\
+                    No credit changes yet."
+                ),
+                Lang::Ru => format!(
+                    "💳 <b>Баланс:</b> <code>{balance}</code> кредитов\n\n\
+                    Изменений баланса пока нет."
+                ),
+                Lang::Uk => format!(
+                    "💳 <b>Баланс:</b> <code>{balance}</code> кредитів\n\n\
+                    Змін балансу поки немає."
+                ),
+                Lang::Es => format!(
+                    "💳 <b>Saldo:</b> <code>{balance}</code> créditos\n\n\
+                    Todavía no hay movimientos de créditos."
+                ),
+                Lang::De => format!(
+                    "💳 <b>Guthaben:</b> <code>{balance}</code> Credits\n\n\
+                    Noch keine Guthabenänderungen."
+                ),
+            };
+        }
+
+        let history = transactions
+            .iter()
+            .map(|transaction| self.credit_transaction_line(transaction))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let page = page + 1;
+        match self {
+            Lang::En => format!(
+                "💳 <b>Balance:</b> <code>{balance}</code> credits\n\n\
+                📜 <b>History</b> (page {page}/{total_pages}, UTC):\n{history}"
+            ),
+            Lang::Ru => format!(
+                "💳 <b>Баланс:</b> <code>{balance}</code> кредитов\n\n\
+                📜 <b>История</b> (страница {page}/{total_pages}, UTC):\n{history}"
+            ),
+            Lang::Uk => format!(
+                "💳 <b>Баланс:</b> <code>{balance}</code> кредитів\n\n\
+                📜 <b>Історія</b> (сторінка {page}/{total_pages}, UTC):\n{history}"
+            ),
+            Lang::Es => format!(
+                "💳 <b>Saldo:</b> <code>{balance}</code> créditos\n\n\
+                📜 <b>Historial</b> (página {page}/{total_pages}, UTC):\n{history}"
+            ),
+            Lang::De => format!(
+                "💳 <b>Guthaben:</b> <code>{balance}</code> Credits\n\n\
+                📜 <b>Verlauf</b> (Seite {page}/{total_pages}, UTC):\n{history}"
+            ),
+        }
+    }
+
+    fn credit_transaction_line(&self, transaction: &CreditTransaction) -> String {
+        format!(
+            "<code>{}</code> <b>{:+}</b> {} → {}",
+            transaction.created_at,
+            transaction.amount,
+            self.credit_transaction_kind_name(transaction.kind),
+            transaction.balance_after
+        )
+    }
+
+    pub fn credit_transaction_kind_name(&self, kind: CreditTransactionKind) -> &'static str {
+        match (self, kind) {
+            (Lang::En, CreditTransactionKind::SignupBonus) => "🎁 Welcome bonus",
+            (Lang::En, CreditTransactionKind::Purchase) => "💎 Purchase",
+            (Lang::En, CreditTransactionKind::Analysis) => "🔍 Analysis",
+            (Lang::En, CreditTransactionKind::ReferralReward) => "🤝 Referral reward",
+            (Lang::En, CreditTransactionKind::Refund) => "↩️ Refund",
+            (Lang::En, CreditTransactionKind::Subscription) => "🔁 Subscription",
+            (Lang::Ru, CreditTransactionKind::SignupBonus) => "🎁 Приветственный бонус",
+            (Lang::Ru, CreditTransactionKind::Purchase) => "💎 Покупка",
+            (Lang::Ru, CreditTransactionKind::Analysis) => "🔍 Анализ",
+            (Lang::Ru, CreditTransactionKind::ReferralReward) => "🤝 Реферальная награда",
+            (Lang::Ru, CreditTransactionKind::Refund) => "↩️ Возврат",
+            (Lang::Ru, CreditTransactionKind::Subscription) => "🔁 Подписка",
+            (Lang::Uk, CreditTransactionKind::SignupBonus) => "🎁 Вітальний бонус",
+            (Lang::Uk, CreditTransactionKind::Purchase) => "💎 Покупка",
+            (Lang::Uk, CreditTransactionKind::Analysis) => "🔍 Аналіз",
+            (Lang::Uk, CreditTransactionKind::ReferralReward) => "🤝 Реферальна нагорода",
+            (Lang::Uk, CreditTransactionKind::Refund) => "↩️ Повернення",
+            (Lang::Uk, CreditTransactionKind::Subscription) => "🔁 Підписка",
+            (Lang::Es, CreditTransactionKind::SignupBonus) => "🎁 Bono de bienvenida",
+            (Lang::Es, CreditTransactionKind::Purchase) => "💎 Compra",
+            (Lang::Es, CreditTransactionKind::Analysis) => "🔍 Análisis",
+            (Lang::Es, CreditTransactionKind::ReferralReward) => "🤝 Recompensa por referido",
+            (Lang::Es, CreditTransactionKind::Refund) => "↩️ Reembolso",
+            (Lang::Es, CreditTransactionKind::Subscription) => "🔁 Suscripción",
+            (Lang::De, CreditTransactionKind::SignupBonus) => "🎁 Willkommensbonus",
+            (Lang::De, CreditTransactionKind::Purchase) => "💎 Kauf",
+            (Lang::De, CreditTransactionKind::Analysis) => "🔍 Analyse",
+            (Lang::De, CreditTransactionKind::ReferralReward) => "🤝 Empfehlungsbelohnung",
+            (Lang::De, CreditTransactionKind::Refund) => "↩️ Erstattung",
+            (Lang::De, CreditTransactionKind::Subscription) => "🔁 Abo",
+        }
+    }
+}
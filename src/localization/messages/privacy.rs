@@ -0,0 +1,85 @@
+//! /export_my_data and /delete_account
+
+use super::Lang;
+
+impl Lang {
+    pub fn data_export_private_only(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /export_my_data works in a private chat with the bot, so your data stays between us.",
+            Lang::Ru => "ℹ️ /export_my_data работает в личном чате с ботом, чтобы ваши данные остались между нами.",
+            Lang::Uk => "ℹ️ /export_my_data працює в особистому чаті з ботом, щоб ваші дані залишилися між нами.",
+            Lang::Es => "ℹ️ /export_my_data funciona en un chat privado con el bot, para que tus datos queden entre nosotros.",
+            Lang::De => "ℹ️ /export_my_data funktioniert im privaten Chat mit dem Bot, damit deine Daten unter uns bleiben.",
+        }
+    }
+
+    pub fn data_export_started(&self) -> &'static str {
+        match self {
+            Lang::En => "📦 Gathering your data, the file will arrive here in a moment.",
+            Lang::Ru => "📦 Собираем ваши данные, файл придёт сюда через минуту.",
+            Lang::Uk => "📦 Збираємо ваші дані, файл надійде сюди за хвилину.",
+            Lang::Es => "📦 Reuniendo tus datos, el archivo llegará aquí en un momento.",
+            Lang::De => "📦 Deine Daten werden gesammelt, die Datei kommt gleich hier an.",
+        }
+    }
+
+    pub fn data_export_caption(&self) -> &'static str {
+        match self {
+            Lang::En => "📦 Everything the bot stores about you: your account, analyses, payments, referral rewards and the group messages kept for /groupprofile.",
+            Lang::Ru => "📦 Всё, что бот хранит о вас: аккаунт, анализы, платежи, реферальные награды и сообщения из групп, сохранённые для /groupprofile.",
+            Lang::Uk => "📦 Усе, що бот зберігає про вас: акаунт, аналізи, платежі, реферальні винагороди та повідомлення з груп, збережені для /groupprofile.",
+            Lang::Es => "📦 Todo lo que el bot guarda sobre ti: tu cuenta, análisis, pagos, recompensas por referidos y los mensajes de grupos guardados para /groupprofile.",
+            Lang::De => "📦 Alles, was der Bot über dich speichert: dein Konto, Analysen, Zahlungen, Empfehlungsprämien und die für /groupprofile gespeicherten Gruppennachrichten.",
+        }
+    }
+
+    pub fn data_export_empty(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ The bot doesn't store any data about you.",
+            Lang::Ru => "ℹ️ Бот не хранит о вас никаких данных.",
+            Lang::Uk => "ℹ️ Бот не зберігає про вас жодних даних.",
+            Lang::Es => "ℹ️ El bot no guarda ningún dato sobre ti.",
+            Lang::De => "ℹ️ Der Bot speichert keine Daten über dich.",
+        }
+    }
+
+    pub fn delete_account_private_only(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /delete_account works in a private chat with the bot.",
+            Lang::Ru => "ℹ️ /delete_account работает в личном чате с ботом.",
+            Lang::Uk => "ℹ️ /delete_account працює в особистому чаті з ботом.",
+            Lang::Es => "ℹ️ /delete_account funciona en un chat privado con el bot.",
+            Lang::De => "ℹ️ /delete_account funktioniert im privaten Chat mit dem Bot.",
+        }
+    }
+
+    pub fn delete_account_confirm(&self) -> &'static str {
+        match self {
+            Lang::En => "⚠️ <b>Delete your account?</b>\n\nYour name, your saved analysis texts, the messages you forwarded, the group messages kept for /groupprofile, your API keys and channel claims will be deleted. Your remaining credits are lost and queued analyses are cancelled.\n\nPayments and completed analyses stay in anonymized form. If you have a Stars subscription, cancel it in Telegram's settings as well.\n\nThis can't be undone. Want a copy first? Use /export_my_data.",
+            Lang::Ru => "⚠️ <b>Удалить аккаунт?</b>\n\nВаше имя, сохранённые тексты анализов, пересланные сообщения, сообщения из групп для /groupprofile, API-ключи и заявки на каналы будут удалены. Оставшиеся кредиты сгорят, анализы в очереди будут отменены.\n\nПлатежи и выполненные анализы останутся в обезличенном виде. Если у вас есть подписка за Stars, отмените её также в настройках Telegram.\n\nЭто нельзя отменить. Нужна копия? Используйте /export_my_data.",
+            Lang::Uk => "⚠️ <b>Видалити акаунт?</b>\n\nВаше ім'я, збережені тексти аналізів, переслані повідомлення, повідомлення з груп для /groupprofile, API-ключі та заявки на канали буде видалено. Залишок кредитів згорить, аналізи в черзі буде скасовано.\n\nПлатежі та виконані аналізи залишаться в знеособленому вигляді. Якщо у вас є підписка за Stars, скасуйте її також у налаштуваннях Telegram.\n\nЦе не можна скасувати. Потрібна копія? Скористайтеся /export_my_data.",
+            Lang::Es => "⚠️ <b>¿Eliminar tu cuenta?</b>\n\nSe eliminarán tu nombre, los textos de tus análisis guardados, los mensajes que reenviaste, los mensajes de grupo guardados para /groupprofile, tus claves de API y tus reclamaciones de canales. Perderás los créditos restantes y se cancelarán los análisis en cola.\n\nLos pagos y los análisis completados se conservan de forma anónima. Si tienes una suscripción con Stars, cancélala también en los ajustes de Telegram.\n\nNo se puede deshacer. ¿Quieres una copia antes? Usa /export_my_data.",
+            Lang::De => "⚠️ <b>Konto löschen?</b>\n\nDein Name, deine gespeicherten Analysetexte, die weitergeleiteten Nachrichten, die für /groupprofile gespeicherten Gruppennachrichten, deine API-Schlüssel und Kanalansprüche werden gelöscht. Verbleibende Credits verfallen, Analysen in der Warteschlange werden abgebrochen.\n\nZahlungen und abgeschlossene Analysen bleiben anonymisiert erhalten. Falls du ein Stars-Abo hast, kündige es auch in den Telegram-Einstellungen.\n\nDas kann nicht rückgängig gemacht werden. Vorher eine Kopie? Nutze /export_my_data.",
+        }
+    }
+
+    pub fn delete_account_done(&self) -> &'static str {
+        match self {
+            Lang::En => "✅ Your account is deleted. If you write to the bot again, it starts from scratch.",
+            Lang::Ru => "✅ Ваш аккаунт удалён. Если вы снова напишете боту, всё начнётся с нуля.",
+            Lang::Uk => "✅ Ваш акаунт видалено. Якщо ви знову напишете боту, все почнеться з нуля.",
+            Lang::Es => "✅ Tu cuenta se ha eliminado. Si vuelves a escribir al bot, empezará desde cero.",
+            Lang::De => "✅ Dein Konto ist gelöscht. Wenn du dem Bot wieder schreibst, beginnt alles von vorn.",
+        }
+    }
+
+    pub fn btn_delete_account(&self) -> &'static str {
+        match self {
+            Lang::En => "🗑 Delete my account",
+            Lang::Ru => "🗑 Удалить мой аккаунт",
+            Lang::Uk => "🗑 Видалити мій акаунт",
+            Lang::Es => "🗑 Eliminar mi cuenta",
+            Lang::De => "🗑 Mein Konto löschen",
+        }
+    }
+}
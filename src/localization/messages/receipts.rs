@@ -0,0 +1,87 @@
+//! /receipts
+use super::Lang;
+use crate::invoice_payload::InvoicePayload;
+use crate::receipts::Receipt;
+use crate::utils::MessageFormatter;
+
+impl Lang {
+    pub fn receipts_private_only(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /receipts works in a private chat with the bot.",
+            Lang::Ru => "ℹ️ /receipts работает в личном чате с ботом.",
+            Lang::Uk => "ℹ️ /receipts працює в особистому чаті з ботом.",
+            Lang::Es => "ℹ️ /receipts funciona en un chat privado con el bot.",
+            Lang::De => "ℹ️ /receipts funktioniert im privaten Chat mit dem Bot.",
+        }
+    }
+
+    pub fn receipts_list(&self, lines: &[String]) -> String {
+        if lines.is_empty() {
+            return match self {
+                Lang::En => "🧾 No purchases yet. Use /buy to get credits.".to_string(),
+                Lang::Ru => {
+                    "🧾 Покупок пока нет. Используйте /buy, чтобы получить кредиты.".to_string()
+                }
+                Lang::Uk => {
+                    "🧾 Покупок поки немає. Скористайтеся /buy, щоб отримати кредити.".to_string()
+                }
+                Lang::Es => {
+                    "🧾 Todavía no hay compras. Usa /buy para conseguir créditos.".to_string()
+                }
+                Lang::De => "🧾 Noch keine Käufe. Nutze /buy, um Credits zu bekommen.".to_string(),
+            };
+        }
+        let lines = lines.join("\n\n");
+        match self {
+            Lang::En => format!("🧾 <b>Your purchases</b> (UTC)\n\n{lines}"),
+            Lang::Ru => format!("🧾 <b>Ваши покупки</b> (UTC)\n\n{lines}"),
+            Lang::Uk => format!("🧾 <b>Ваші покупки</b> (UTC)\n\n{lines}"),
+            Lang::Es => format!("🧾 <b>Tus compras</b> (UTC)\n\n{lines}"),
+            Lang::De => format!("🧾 <b>Deine Käufe</b> (UTC)\n\n{lines}"),
+        }
+    }
+
+    pub fn receipt_line(&self, receipt: &Receipt) -> String {
+        let package = self.receipt_package_name(&receipt.package);
+        let refunded = match (receipt.refunded, self) {
+            (false, _) => "",
+            (true, Lang::En) => " · ↩️ refunded",
+            (true, Lang::Ru) => " · ↩️ возвращено",
+            (true, Lang::Uk) => " · ↩️ повернено",
+            (true, Lang::Es) => " · ↩️ reembolsado",
+            (true, Lang::De) => " · ↩️ erstattet",
+        };
+        let (date, stars, credits, charge_id) = (
+            &receipt.created_at,
+            receipt.stars,
+            receipt.credits,
+            &receipt.telegram_payment_charge_id,
+        );
+        match self {
+            Lang::En => format!(
+                "<code>{date}</code> {package}: ⭐ {stars} · {credits} credits{refunded}\n<code>{charge_id}</code>"
+            ),
+            Lang::Ru => format!(
+                "<code>{date}</code> {package}: ⭐ {stars} · {credits} кредитов{refunded}\n<code>{charge_id}</code>"
+            ),
+            Lang::Uk => format!(
+                "<code>{date}</code> {package}: ⭐ {stars} · {credits} кредитів{refunded}\n<code>{charge_id}</code>"
+            ),
+            Lang::Es => format!(
+                "<code>{date}</code> {package}: ⭐ {stars} · {credits} créditos{refunded}\n<code>{charge_id}</code>"
+            ),
+            Lang::De => format!(
+                "<code>{date}</code> {package}: ⭐ {stars} · {credits} Credits{refunded}\n<code>{charge_id}</code>"
+            ),
+        }
+    }
+
+    // the title of the invoice the package was bought with
+    fn receipt_package_name(&self, package: &str) -> String {
+        match InvoicePayload::parse(package) {
+            Some(InvoicePayload::Package(credits)) => self.invoice_package_title(credits),
+            Some(InvoicePayload::Subscription(_)) => self.invoice_subscription_title().to_string(),
+            None => MessageFormatter::escape_html(package),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! referral links, rewards and the notifications about them
+
+use super::Lang;
+
+impl Lang {
+    pub fn referral_milestone_with_credits(
+        &self,
+        referral_count: i32,
+        credits_awarded: i32,
+        referrer_user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "🎉 <b>Referral Milestone!</b>\n\n\
+                Congratulations! You've reached <b>{referral_count}</b> referrals and earned <b>{credits_awarded}</b> credit(s)!\n\n\
+                Keep sharing: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">your referral link</a>"
+            ),
+            Lang::Ru => format!(
+                "🎉 <b>Реферальный рубеж!</b>\n\n\
+                Поздравляем! Вы достигли <b>{referral_count}</b> рефералов и получили <b>{credits_awarded}</b> кредит(ов)!\n\n\
+                Продолжайте делиться: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашей реферальной ссылкой</a>"
+            ),
+            Lang::Uk => format!(
+                "🎉 <b>Реферальний рубіж!</b>\n\n\
+                Вітаємо! Ви досягли <b>{referral_count}</b> рефералів і отримали <b>{credits_awarded}</b> кредит(ів)!\n\n\
+                Діліться далі: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашим реферальним посиланням</a>"
+            ),
+            Lang::Es => format!(
+                "🎉 <b>¡Hito de referidos!</b>\n\n\
+                ¡Enhorabuena! Has llegado a <b>{referral_count}</b> referidos y ganado <b>{credits_awarded}</b> crédito(s).\n\n\
+                Sigue compartiendo: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">tu enlace de referido</a>"
+            ),
+            Lang::De => format!(
+                "🎉 <b>Empfehlungs-Meilenstein!</b>\n\n\
+                Glückwunsch! Du hast <b>{referral_count}</b> Empfehlungen erreicht und <b>{credits_awarded}</b> Credit(s) verdient!\n\n\
+                Teile weiter: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">dein Empfehlungslink</a>"
+            ),
+        }
+    }
+
+    pub fn referral_milestone_no_credits(
+        &self,
+        referral_count: i32,
+        referrer_user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "🎊 <b>Referral Milestone!</b>\n\n\
+                Congratulations! You've reached <b>{referral_count}</b> referrals!\n\n\
+                Keep sharing: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">your referral link</a>"
+            ),
+            Lang::Ru => format!(
+                "🎊 <b>Реферальный рубеж!</b>\n\n\
+                Поздравляем! Вы достигли <b>{referral_count}</b> рефералов!\n\n\
+                Продолжайте делиться: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашей реферальной ссылкой</a>"
+            ),
+            Lang::Uk => format!(
+                "🎊 <b>Реферальний рубіж!</b>\n\n\
+                Вітаємо! Ви досягли <b>{referral_count}</b> рефералів!\n\n\
+                Діліться далі: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашим реферальним посиланням</a>"
+            ),
+            Lang::Es => format!(
+                "🎊 <b>¡Hito de referidos!</b>\n\n\
+                ¡Enhorabuena! ¡Has llegado a <b>{referral_count}</b> referidos!\n\n\
+                Sigue compartiendo: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">tu enlace de referido</a>"
+            ),
+            Lang::De => format!(
+                "🎊 <b>Empfehlungs-Meilenstein!</b>\n\n\
+                Glückwunsch! Du hast <b>{referral_count}</b> Empfehlungen erreicht!\n\n\
+                Teile weiter: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">dein Empfehlungslink</a>"
+            ),
+        }
+    }
+
+    pub fn referral_reward(
+        &self,
+        credits_awarded: i32,
+        referral_count: i32,
+        referrer_user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "🎉 <b>Referral Reward!</b>\n\n\
+                You've earned <b>{credits_awarded}</b> credit(s) for reaching <b>{referral_count}</b> referrals!\n\n\
+                Keep sharing: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">your referral link</a>"
+            ),
+            Lang::Ru => format!(
+                "🎉 <b>Реферальная награда!</b>\n\n\
+                Вы получили <b>{credits_awarded}</b> кредит(ов) за <b>{referral_count}</b> рефералов!\n\n\
+                Продолжайте делиться: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашей реферальной ссылкой</a>"
+            ),
+            Lang::Uk => format!(
+                "🎉 <b>Реферальна нагорода!</b>\n\n\
+                Ви отримали <b>{credits_awarded}</b> кредит(ів) за <b>{referral_count}</b> рефералів!\n\n\
+                Діліться далі: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашим реферальним посиланням</a>"
+            ),
+            Lang::Es => format!(
+                "🎉 <b>¡Recompensa por referidos!</b>\n\n\
+                ¡Has ganado <b>{credits_awarded}</b> crédito(s) por llegar a <b>{referral_count}</b> referidos!\n\n\
+                Sigue compartiendo: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">tu enlace de referido</a>"
+            ),
+            Lang::De => format!(
+                "🎉 <b>Empfehlungsbelohnung!</b>\n\n\
+                Du hast <b>{credits_awarded}</b> Credit(s) für <b>{referral_count}</b> Empfehlungen verdient!\n\n\
+                Teile weiter: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">dein Empfehlungslink</a>"
+            ),
+        }
+    }
+
+    pub fn referral_paid_and_milestone(
+        &self,
+        total_credits: i32,
+        referral_count: i32,
+        paid_rewards: i32,
+        milestone_rewards: i32,
+        referrer_user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "🎉 <b>Referral Rewards!</b>\n\n\
+                You've earned <b>{total_credits}</b> credits (Total referrals: <b>{referral_count}</b>):\n\
+                • {paid_rewards} credit(s) for paid referral\n\
+                • {milestone_rewards} credit(s) for milestone bonus\n\n\
+                Keep sharing: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">your referral link</a>"
+            ),
+            Lang::Ru => format!(
+                "🎉 <b>Реферальные награды!</b>\n\n\
+                Вы получили <b>{total_credits}</b> кредитов (Всего рефералов: <b>{referral_count}</b>):\n\
+                • {paid_rewards} кредит(ов) за оплатившего реферала\n\
+                • {milestone_rewards} кредит(ов) за рубеж\n\n\
+                Продолжайте делиться: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашей реферальной ссылкой</a>"
+            ),
+            Lang::Uk => format!(
+                "🎉 <b>Реферальні нагороди!</b>\n\n\
+                Ви отримали <b>{total_credits}</b> кредитів (Усього рефералів: <b>{referral_count}</b>):\n\
+                • {paid_rewards} кредит(ів) за реферала, який оплатив\n\
+                • {milestone_rewards} кредит(ів) за рубіж\n\n\
+                Діліться далі: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашим реферальним посиланням</a>"
+            ),
+            Lang::Es => format!(
+                "🎉 <b>¡Recompensas por referidos!</b>\n\n\
+                Has ganado <b>{total_credits}</b> créditos (Referidos en total: <b>{referral_count}</b>):\n\
+                • {paid_rewards} crédito(s) por un referido de pago\n\
+                • {milestone_rewards} crédito(s) de bonificación por hito\n\n\
+                Sigue compartiendo: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">tu enlace de referido</a>"
+            ),
+            Lang::De => format!(
+                "🎉 <b>Empfehlungsbelohnungen!</b>\n\n\
+                Du hast <b>{total_credits}</b> Credits verdient (Empfehlungen insgesamt: <b>{referral_count}</b>):\n\
+                • {paid_rewards} Credit(s) für eine zahlende Empfehlung\n\
+                • {milestone_rewards} Credit(s) als Meilenstein-Bonus\n\n\
+                Teile weiter: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">dein Empfehlungslink</a>"
+            ),
+        }
+    }
+
+    pub fn referral_paid_only(
+        &self,
+        paid_rewards: i32,
+        referral_count: i32,
+        referrer_user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "🎉 <b>Referral Reward!</b>\n\n\
+                You've earned <b>{paid_rewards}</b> credit(s) for a paid referral! (Total referrals: <b>{referral_count}</b>)\n\n\
+                Keep sharing: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">your referral link</a>"
+            ),
+            Lang::Ru => format!(
+                "🎉 <b>Реферальная награда!</b>\n\n\
+                Вы получили <b>{paid_rewards}</b> кредит(ов) за оплатившего реферала! (Всего рефералов: <b>{referral_count}</b>)\n\n\
+                Продолжайте делиться: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашей реферальной ссылкой</a>"
+            ),
+            Lang::Uk => format!(
+                "🎉 <b>Реферальна нагорода!</b>\n\n\
+                Ви отримали <b>{paid_rewards}</b> кредит(ів) за реферала, який оплатив! (Усього рефералів: <b>{referral_count}</b>)\n\n\
+                Діліться далі: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашим реферальним посиланням</a>"
+            ),
+            Lang::Es => format!(
+                "🎉 <b>¡Recompensa por referidos!</b>\n\n\
+                ¡Has ganado <b>{paid_rewards}</b> crédito(s) por un referido de pago! (Referidos en total: <b>{referral_count}</b>)\n\n\
+                Sigue compartiendo: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">tu enlace de referido</a>"
+            ),
+            Lang::De => format!(
+                "🎉 <b>Empfehlungsbelohnung!</b>\n\n\
+                Du hast <b>{paid_rewards}</b> Credit(s) für eine zahlende Empfehlung verdient! (Empfehlungen insgesamt: <b>{referral_count}</b>)\n\n\
+                Teile weiter: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">dein Empfehlungslink</a>"
+            ),
+        }
+    }
+
+    pub fn referral_milestone_only(
+        &self,
+        milestone_rewards: i32,
+        referral_count: i32,
+        referrer_user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "🎉 <b>Milestone Reward!</b>\n\n\
+                You've earned <b>{milestone_rewards}</b> credit(s) for reaching <b>{referral_count}</b> referrals!\n\n\
+                Keep sharing: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">your referral link</a>"
+            ),
+            Lang::Ru => format!(
+                "🎉 <b>Награда за рубеж!</b>\n\n\
+                Вы получили <b>{milestone_rewards}</b> кредит(ов) за <b>{referral_count}</b> рефералов!\n\n\
+                Продолжайте делиться: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашей реферальной ссылкой</a>"
+            ),
+            Lang::Uk => format!(
+                "🎉 <b>Нагорода за рубіж!</b>\n\n\
+                Ви отримали <b>{milestone_rewards}</b> кредит(ів) за <b>{referral_count}</b> рефералів!\n\n\
+                Діліться далі: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">вашим реферальним посиланням</a>"
+            ),
+            Lang::Es => format!(
+                "🎉 <b>¡Recompensa por hito!</b>\n\n\
+                ¡Has ganado <b>{milestone_rewards}</b> crédito(s) por llegar a <b>{referral_count}</b> referidos!\n\n\
+                Sigue compartiendo: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">tu enlace de referido</a>"
+            ),
+            Lang::De => format!(
+                "🎉 <b>Meilenstein-Belohnung!</b>\n\n\
+                Du hast <b>{milestone_rewards}</b> Credit(s) für <b>{referral_count}</b> Empfehlungen verdient!\n\n\
+                Teile weiter: <a href=\"https://t.me/ScratchAuthorEgoBot?start={referrer_user_id}\">dein Empfehlungslink</a>"
+            ),
+        }
+    }
+}
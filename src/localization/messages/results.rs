@@ -0,0 +1,314 @@
+//! analysis results: headers, quick facts, partial answers, voice, second opinions and /resend
+use chrono::Weekday;
+
+use super::Lang;
+
+impl Lang {
+    pub fn analysis_result_header(&self, channel_name: &str, user_id: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "📊 <b>Channel Analysis Results</b> by <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🎯 <b>Channel:</b> <code>{channel_name}</code>\n\n"
+            ),
+            Lang::Ru => format!(
+                "📊 <b>Результаты анализа канала</b> от <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🎯 <b>Канал:</b> <code>{channel_name}</code>\n\n"
+            ),
+            Lang::Uk => format!(
+                "📊 <b>Результати аналізу каналу</b> від <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🎯 <b>Канал:</b> <code>{channel_name}</code>\n\n"
+            ),
+            Lang::Es => format!(
+                "📊 <b>Resultados del análisis del canal</b> por <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🎯 <b>Canal:</b> <code>{channel_name}</code>\n\n"
+            ),
+            Lang::De => format!(
+                "📊 <b>Ergebnisse der Kanalanalyse</b> von <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🎯 <b>Kanal:</b> <code>{channel_name}</code>\n\n"
+            ),
+        }
+    }
+
+    /// the quick facts line under a result header, counted without the llm; keywords and
+    /// hashtags come escaped and comma-separated, empty when there are none
+    pub fn quick_facts(
+        &self,
+        keywords: &str,
+        hashtags: &str,
+        busiest_day: Option<Weekday>,
+    ) -> String {
+        let (label, words, tags) = match self {
+            Lang::En => ("💡 <b>Quick facts:</b>", "words", "tags"),
+            Lang::Ru => ("💡 <b>Коротко:</b>", "слова", "теги"),
+            Lang::Uk => ("💡 <b>Коротко:</b>", "слова", "теги"),
+            Lang::Es => ("💡 <b>Datos rápidos:</b>", "palabras", "etiquetas"),
+            Lang::De => ("💡 <b>Kurz notiert:</b>", "Wörter", "Tags"),
+        };
+        let mut facts = Vec::new();
+        if !keywords.is_empty() {
+            facts.push(format!("{words}: {keywords}"));
+        }
+        if !hashtags.is_empty() {
+            facts.push(format!("{tags}: {hashtags}"));
+        }
+        if let Some(day) = busiest_day {
+            facts.push(self.busiest_weekday(day));
+        }
+        if facts.is_empty() {
+            return String::new();
+        }
+        format!("{label} {}\n\n", facts.join(" · "))
+    }
+
+    fn busiest_weekday(&self, day: Weekday) -> String {
+        let index = day.num_days_from_monday() as usize;
+        match self {
+            Lang::En => {
+                let days = [
+                    "Mondays",
+                    "Tuesdays",
+                    "Wednesdays",
+                    "Thursdays",
+                    "Fridays",
+                    "Saturdays",
+                    "Sundays",
+                ];
+                format!("most posts on {}", days[index])
+            }
+            Lang::Ru => {
+                let days = [
+                    "понедельникам",
+                    "вторникам",
+                    "средам",
+                    "четвергам",
+                    "пятницам",
+                    "субботам",
+                    "воскресеньям",
+                ];
+                format!("чаще всего пишет по {}", days[index])
+            }
+            Lang::Uk => {
+                let days = [
+                    "понеділках",
+                    "вівторках",
+                    "середах",
+                    "четвергах",
+                    "п'ятницях",
+                    "суботах",
+                    "неділях",
+                ];
+                format!("найчастіше пише по {}", days[index])
+            }
+            Lang::Es => {
+                let days = [
+                    "los lunes",
+                    "los martes",
+                    "los miércoles",
+                    "los jueves",
+                    "los viernes",
+                    "los sábados",
+                    "los domingos",
+                ];
+                format!("más publicaciones {}", days[index])
+            }
+            Lang::De => {
+                let days = [
+                    "montags",
+                    "dienstags",
+                    "mittwochs",
+                    "donnerstags",
+                    "freitags",
+                    "samstags",
+                    "sonntags",
+                ];
+                format!("die meisten Beiträge {}", days[index])
+            }
+        }
+    }
+
+    pub fn analysis_type_header(&self, analysis_type: &str) -> String {
+        let emoji = self.analysis_emoji(analysis_type);
+        let type_capitalized = self.analysis_type_capitalized(analysis_type);
+        match self {
+            Lang::En => format!("{} <b>{} Analysis:</b>\n\n", emoji, type_capitalized),
+            Lang::Ru => format!("{} <b>{} анализ:</b>\n\n", emoji, type_capitalized),
+            Lang::Uk => format!("{} <b>{} аналіз:</b>\n\n", emoji, type_capitalized),
+            // the spanish and german type names already name the analysis
+            Lang::Es | Lang::De => format!("{} <b>{}:</b>\n\n", emoji, type_capitalized),
+        }
+    }
+
+    pub fn analysis_part_indicator(&self, part: usize, total: usize) -> String {
+        match self {
+            Lang::En => format!("\n\n<i>📄 Part {} of {}</i>", part, total),
+            Lang::Ru => format!("\n\n<i>📄 Часть {} из {}</i>", part, total),
+            Lang::Uk => format!("\n\n<i>📄 Частина {} з {}</i>", part, total),
+            Lang::Es => format!("\n\n<i>📄 Parte {} de {}</i>", part, total),
+            Lang::De => format!("\n\n<i>📄 Teil {} von {}</i>", part, total),
+        }
+    }
+
+    /// the summary sent in place of a result published as a page
+    pub fn analysis_web_page(&self, excerpt: &str, url: &str) -> String {
+        match self {
+            Lang::En => format!("{excerpt}\n\n📄 <a href=\"{url}\">Read the full analysis</a>"),
+            Lang::Ru => format!("{excerpt}\n\n📄 <a href=\"{url}\">Читать анализ полностью</a>"),
+            Lang::Uk => format!("{excerpt}\n\n📄 <a href=\"{url}\">Читати аналіз повністю</a>"),
+            Lang::Es => format!("{excerpt}\n\n📄 <a href=\"{url}\">Leer el análisis completo</a>"),
+            Lang::De => format!("{excerpt}\n\n📄 <a href=\"{url}\">Die ganze Analyse lesen</a>"),
+        }
+    }
+
+    pub fn analysis_partial_label(&self) -> &'static str {
+        match self {
+            Lang::En => "<i>⚠️ Partial report: the AI's answer was cut off, so this section may stop early.</i>\n\n",
+            Lang::Ru => "<i>⚠️ Неполный отчёт: ответ ИИ оборвался, поэтому раздел может заканчиваться на полуслове.</i>\n\n",
+            Lang::Uk => "<i>⚠️ Неповний звіт: відповідь ШІ обірвалася, тому розділ може закінчуватися на півслові.</i>\n\n",
+            Lang::Es => "<i>⚠️ Informe parcial: la respuesta de la IA se cortó, así que esta sección puede terminar antes de tiempo.</i>\n\n",
+            Lang::De => "<i>⚠️ Unvollständiger Bericht: Die Antwort der KI brach ab, daher kann dieser Abschnitt vorzeitig enden.</i>\n\n",
+        }
+    }
+
+    pub fn analysis_partial_offer(&self) -> &'static str {
+        match self {
+            Lang::En => "⚠️ The AI's answer was cut off and this report is incomplete. You can regenerate it free of charge: the credits for it are returned and a new analysis starts.",
+            Lang::Ru => "⚠️ Ответ ИИ оборвался, и отчёт получился неполным. Его можно пересоздать бесплатно: кредиты за него вернутся, и начнётся новый анализ.",
+            Lang::Uk => "⚠️ Відповідь ШІ обірвалася, і звіт вийшов неповним. Його можна перегенерувати безкоштовно: кредити за нього повернуться, і почнеться новий аналіз.",
+            Lang::Es => "⚠️ La respuesta de la IA se cortó y este informe está incompleto. Puedes regenerarlo gratis: se te devuelven los créditos y empieza un nuevo análisis.",
+            Lang::De => "⚠️ Die Antwort der KI brach ab und dieser Bericht ist unvollständig. Du kannst ihn kostenlos neu erstellen: Die Credits dafür werden erstattet und eine neue Analyse startet.",
+        }
+    }
+
+    pub fn regeneration_unavailable(&self) -> &'static str {
+        match self {
+            Lang::En => "This report was already regenerated.",
+            Lang::Ru => "Этот отчёт уже пересоздан.",
+            Lang::Uk => "Цей звіт уже перегенеровано.",
+            Lang::Es => "Este informe ya se regeneró.",
+            Lang::De => "Dieser Bericht wurde bereits neu erstellt.",
+        }
+    }
+
+    pub fn btn_resend_result(&self) -> &'static str {
+        match self {
+            Lang::En => "📨 Send the result again",
+            Lang::Ru => "📨 Прислать результат ещё раз",
+            Lang::Uk => "📨 Надіслати результат ще раз",
+            Lang::Es => "📨 Enviar el resultado de nuevo",
+            Lang::De => "📨 Ergebnis erneut senden",
+        }
+    }
+
+    pub fn btn_listen(&self) -> &'static str {
+        match self {
+            Lang::En => "🔊 Listen",
+            Lang::Ru => "🔊 Послушать",
+            Lang::Uk => "🔊 Прослухати",
+            Lang::Es => "🔊 Escuchar",
+            Lang::De => "🔊 Anhören",
+        }
+    }
+
+    pub fn voice_recording(&self) -> &'static str {
+        match self {
+            Lang::En => "🎙 Recording a voice summary, this takes up to a minute…",
+            Lang::Ru => "🎙 Записываю голосовое резюме, это займёт до минуты…",
+            Lang::Uk => "🎙 Записую голосове резюме, це займе до хвилини…",
+            Lang::Es => "🎙 Grabando un resumen de voz, tarda hasta un minuto…",
+            Lang::De => "🎙 Nehme eine Sprachzusammenfassung auf, das dauert bis zu einer Minute…",
+        }
+    }
+
+    pub fn voice_unavailable(&self) -> &'static str {
+        match self {
+            Lang::En => "🔇 This analysis has no result to read out.",
+            Lang::Ru => "🔇 У этого анализа нет результата, который можно озвучить.",
+            Lang::Uk => "🔇 У цього аналізу немає результату, який можна озвучити.",
+            Lang::Es => "🔇 Este análisis no tiene un resultado que leer en voz alta.",
+            Lang::De => "🔇 Diese Analyse hat kein Ergebnis zum Vorlesen.",
+        }
+    }
+
+    pub fn voice_failed(&self) -> &'static str {
+        match self {
+            Lang::En => "❌ Couldn't record the voice summary. Please try again later.",
+            Lang::Ru => "❌ Не удалось записать голосовое резюме. Попробуйте позже.",
+            Lang::Uk => "❌ Не вдалося записати голосове резюме. Спробуйте пізніше.",
+            Lang::Es => "❌ No se pudo grabar el resumen de voz. Inténtalo más tarde.",
+            Lang::De => "❌ Die Sprachzusammenfassung konnte nicht aufgenommen werden. Bitte versuche es später erneut.",
+        }
+    }
+
+    pub fn btn_second_opinion(&self, credits: i32) -> String {
+        match self {
+            Lang::En => format!("⚖️ Second opinion from another model ({credits} cr.)"),
+            Lang::Ru => format!("⚖️ Мнение другой модели ({credits} кр.)"),
+            Lang::Uk => format!("⚖️ Думка іншої моделі ({credits} кр.)"),
+            Lang::Es => format!("⚖️ Segunda opinión de otro modelo ({credits} cr.)"),
+            Lang::De => format!("⚖️ Zweitmeinung eines anderen Modells ({credits} Cr.)"),
+        }
+    }
+
+    pub fn second_opinion_unavailable(&self) -> &'static str {
+        match self {
+            Lang::En => "⚖️ This analysis already has a second opinion or can't get one.",
+            Lang::Ru => "⚖️ У этого анализа уже есть второе мнение, или его нельзя получить.",
+            Lang::Uk => "⚖️ Цей аналіз уже має другу думку, або її не можна отримати.",
+            Lang::Es => "⚖️ Este análisis ya tiene una segunda opinión o no puede tenerla.",
+            Lang::De => "⚖️ Diese Analyse hat bereits eine Zweitmeinung oder kann keine bekommen.",
+        }
+    }
+
+    /// the two models side by side, with the average score each gave when it gave one
+    pub fn second_opinion_comparison(
+        &self,
+        first_model: &str,
+        first_score: Option<f64>,
+        second_model: &str,
+        second_score: Option<f64>,
+    ) -> String {
+        let score = |score: Option<f64>| {
+            score
+                .map(|score| format!(" · {score:.1}/10"))
+                .unwrap_or_default()
+        };
+        let (first_score, second_score) = (score(first_score), score(second_score));
+        match self {
+            Lang::En => format!(
+                "\n\n⚖️ <b>Second opinion</b>\n\
+                1️⃣ First analysis: <code>{first_model}</code>{first_score}\n\
+                2️⃣ This one: <code>{second_model}</code>{second_score}"
+            ),
+            Lang::Ru => format!(
+                "\n\n⚖️ <b>Второе мнение</b>\n\
+                1️⃣ Первый анализ: <code>{first_model}</code>{first_score}\n\
+                2️⃣ Этот: <code>{second_model}</code>{second_score}"
+            ),
+            Lang::Uk => format!(
+                "\n\n⚖️ <b>Друга думка</b>\n\
+                1️⃣ Перший аналіз: <code>{first_model}</code>{first_score}\n\
+                2️⃣ Цей: <code>{second_model}</code>{second_score}"
+            ),
+            Lang::Es => format!(
+                "\n\n⚖️ <b>Segunda opinión</b>\n\
+                1️⃣ Primer análisis: <code>{first_model}</code>{first_score}\n\
+                2️⃣ Este: <code>{second_model}</code>{second_score}"
+            ),
+            Lang::De => format!(
+                "\n\n⚖️ <b>Zweitmeinung</b>\n\
+                1️⃣ Erste Analyse: <code>{first_model}</code>{first_score}\n\
+                2️⃣ Diese: <code>{second_model}</code>{second_score}"
+            ),
+        }
+    }
+
+    pub fn resend_unavailable(&self) -> &'static str {
+        match self {
+            Lang::En => "📭 There is no analysis result to send again yet.",
+            Lang::Ru => "📭 Пока нет результата анализа, который можно прислать ещё раз.",
+            Lang::Uk => "📭 Поки що немає результату аналізу, який можна надіслати ще раз.",
+            Lang::Es => "📭 Aún no hay ningún resultado de análisis que reenviar.",
+            Lang::De => "📭 Es gibt noch kein Analyseergebnis zum erneuten Senden.",
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! roast battles of group members
+
+use super::Lang;
+
+impl Lang {
+    pub fn roast_battle_groups_only(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /roastbattle works in groups: add the bot to a group and run it there.",
+            Lang::Ru => "ℹ️ /roastbattle работает в группах: добавьте бота в группу и запустите команду там.",
+            Lang::Uk => "ℹ️ /roastbattle працює в групах: додайте бота до групи й запустіть команду там.",
+            Lang::Es => "ℹ️ /roastbattle funciona en grupos: añade el bot a un grupo y ejecútalo allí.",
+            Lang::De => "ℹ️ /roastbattle funktioniert in Gruppen: Füge den Bot einer Gruppe hinzu und starte es dort.",
+        }
+    }
+
+    pub fn roast_battle_anonymous(&self) -> &'static str {
+        match self {
+            Lang::En => "🔒 A roast battle is billed to whoever starts it. Please turn off \"Remain anonymous\" and try again.",
+            Lang::Ru => "🔒 Баттл оплачивает тот, кто его запускает. Отключите «Анонимность» и попробуйте снова.",
+            Lang::Uk => "🔒 Батл оплачує той, хто його запускає. Вимкніть «Анонімність» і спробуйте ще раз.",
+            Lang::Es => "🔒 La batalla se cobra a quien la inicia. Desactiva «Permanecer anónimo» e inténtalo de nuevo.",
+            Lang::De => "🔒 Eine Roast-Battle wird dem berechnet, der sie startet. Bitte deaktiviere „Anonym bleiben“ und versuche es erneut.",
+        }
+    }
+
+    pub fn roast_battle_usage(&self) -> &'static str {
+        match self {
+            Lang::En => "Usage: <code>/roastbattle @first @second</code> with the usernames of two different group members.",
+            Lang::Ru => "Использование: <code>/roastbattle @first @second</code> с именами пользователей двух разных участников группы.",
+            Lang::Uk => "Використання: <code>/roastbattle @first @second</code> з іменами користувачів двох різних учасників групи.",
+            Lang::Es => "Uso: <code>/roastbattle @first @second</code> con los nombres de usuario de dos miembros distintos del grupo.",
+            Lang::De => "Verwendung: <code>/roastbattle @first @second</code> mit den Benutzernamen zweier verschiedener Gruppenmitglieder.",
+        }
+    }
+
+    /// `username` is escaped, without the @
+    pub fn roast_battle_not_opted_in(&self, username: &str) -> String {
+        match self {
+            Lang::En => format!("🙅 @{username} hasn't allowed the bot to keep their group messages. They can send /groupprofile to the bot in a private chat and agree there."),
+            Lang::Ru => format!("🙅 @{username} не разрешал боту сохранять свои сообщения в группах. Для этого нужно отправить боту /groupprofile в личном чате и согласиться."),
+            Lang::Uk => format!("🙅 @{username} не дозволяв боту зберігати свої повідомлення в групах. Для цього треба надіслати боту /groupprofile в особистому чаті й погодитися."),
+            Lang::Es => format!("🙅 @{username} no ha permitido que el bot guarde sus mensajes de grupo. Puede enviar /groupprofile al bot en un chat privado y aceptar allí."),
+            Lang::De => format!("🙅 @{username} hat dem Bot nicht erlaubt, die eigenen Gruppennachrichten zu speichern. Dazu /groupprofile im privaten Chat an den Bot senden und zustimmen."),
+        }
+    }
+
+    /// `username` is escaped, without the @
+    pub fn roast_battle_too_few(&self, username: &str, count: usize, min: usize) -> String {
+        match self {
+            Lang::En => format!("✋ The bot has kept only {count} messages of @{username} in this group, a battle needs at least {min}."),
+            Lang::Ru => format!("✋ Бот сохранил в этой группе только {count} сообщений @{username}, для баттла нужно хотя бы {min}."),
+            Lang::Uk => format!("✋ Бот зберіг у цій групі лише {count} повідомлень @{username}, для батлу потрібно щонайменше {min}."),
+            Lang::Es => format!("✋ El bot solo ha guardado {count} mensajes de @{username} en este grupo, una batalla necesita al menos {min}."),
+            Lang::De => format!("✋ Der Bot hat in dieser Gruppe nur {count} Nachrichten von @{username} gespeichert, eine Battle braucht mindestens {min}."),
+        }
+    }
+
+    /// shown in place of a channel name for a roast battle; the usernames are escaped
+    pub fn roast_battle_target(&self, first: &str, second: &str) -> String {
+        match self {
+            Lang::En => format!("🥊 roast battle @{first} vs @{second}"),
+            Lang::Ru => format!("🥊 баттл @{first} против @{second}"),
+            Lang::Uk => format!("🥊 батл @{first} проти @{second}"),
+            Lang::Es => format!("🥊 batalla @{first} contra @{second}"),
+            Lang::De => format!("🥊 Roast-Battle @{first} gegen @{second}"),
+        }
+    }
+
+    /// the header of a roast battle result; the usernames are escaped
+    pub fn roast_battle_result_header(&self, first: &str, second: &str, user_id: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "🥊 <b>Roast Battle</b> by <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🔴 @{first}  vs  🔵 @{second}\n\n"
+            ),
+            Lang::Ru => format!(
+                "🥊 <b>Роаст-баттл</b> от <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🔴 @{first}  против  🔵 @{second}\n\n"
+            ),
+            Lang::Uk => format!(
+                "🥊 <b>Роаст-батл</b> від <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🔴 @{first}  проти  🔵 @{second}\n\n"
+            ),
+            Lang::Es => format!(
+                "🥊 <b>Batalla de roast</b> por <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🔴 @{first}  contra  🔵 @{second}\n\n"
+            ),
+            Lang::De => format!(
+                "🥊 <b>Roast-Battle</b> von <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>\n\n\
+                🔴 @{first}  gegen  🔵 @{second}\n\n"
+            ),
+        }
+    }
+}
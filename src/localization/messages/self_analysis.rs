@@ -0,0 +1,172 @@
+//! self-analysis of forwarded messages
+
+use super::Lang;
+
+impl Lang {
+    pub fn self_analysis_private_only(&self) -> &'static str {
+        match self {
+            Lang::En => "ℹ️ /analyze_me works in a private chat with the bot, so your messages stay between us.",
+            Lang::Ru => "ℹ️ /analyze_me работает в личном чате с ботом, чтобы ваши сообщения остались между нами.",
+            Lang::Uk => "ℹ️ /analyze_me працює в особистому чаті з ботом, щоб ваші повідомлення залишилися між нами.",
+            Lang::Es => "ℹ️ /analyze_me funciona en un chat privado con el bot, para que tus mensajes queden entre nosotros.",
+            Lang::De => "ℹ️ /analyze_me funktioniert im privaten Chat mit dem Bot, damit deine Nachrichten unter uns bleiben.",
+        }
+    }
+
+    pub fn self_analysis_start(&self, min: usize, max: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "🪞 <b>Analyze yourself</b>\n\n\
+                Forward me your own messages from any chats, at least {min} and up to {max}. \
+                Messages written by others are skipped.\n\n\
+                Press the button when you are done:"
+            ),
+            Lang::Ru => format!(
+                "🪞 <b>Анализ себя</b>\n\n\
+                Перешлите мне свои сообщения из любых чатов: не меньше {min} и не больше {max}. \
+                Сообщения других людей пропускаются.\n\n\
+                Когда закончите, нажмите кнопку:"
+            ),
+            Lang::Uk => format!(
+                "🪞 <b>Аналіз себе</b>\n\n\
+                Перешліть мені свої повідомлення з будь-яких чатів: не менше {min} і не більше {max}. \
+                Повідомлення інших людей пропускаються.\n\n\
+                Коли закінчите, натисніть кнопку:"
+            ),
+            Lang::Es => format!(
+                "🪞 <b>Analízate a ti mismo</b>\n\n\
+                Reenvíame tus propios mensajes de cualquier chat, al menos {min} y hasta {max}. \
+                Los mensajes escritos por otros se omiten.\n\n\
+                Pulsa el botón cuando termines:"
+            ),
+            Lang::De => format!(
+                "🪞 <b>Analysiere dich selbst</b>\n\n\
+                Leite mir deine eigenen Nachrichten aus beliebigen Chats weiter, mindestens {min} und höchstens {max}. \
+                Nachrichten anderer werden übersprungen.\n\n\
+                Drücke den Button, wenn du fertig bist:"
+            ),
+        }
+    }
+
+    pub fn btn_self_done(&self) -> &'static str {
+        match self {
+            Lang::En => "✅ Done, analyze",
+            Lang::Ru => "✅ Готово, анализировать",
+            Lang::Uk => "✅ Готово, аналізувати",
+            Lang::Es => "✅ Listo, analizar",
+            Lang::De => "✅ Fertig, analysieren",
+        }
+    }
+
+    pub fn self_analysis_collecting(&self, count: usize, min: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "📥 Collected {count} of at least {min} messages. Keep forwarding, then press the button."
+            ),
+            Lang::Ru => format!(
+                "📥 Собрано {count} из минимум {min} сообщений. Пересылайте дальше, а затем нажмите кнопку."
+            ),
+            Lang::Uk => format!(
+                "📥 Зібрано {count} з мінімум {min} повідомлень. Пересилайте далі, а потім натисніть кнопку."
+            ),
+            Lang::Es => format!(
+                "📥 Recopilados {count} de al menos {min} mensajes. Sigue reenviando y luego pulsa el botón."
+            ),
+            Lang::De => format!(
+                "📥 {count} von mindestens {min} Nachrichten gesammelt. Leite weiter und drücke dann den Button."
+            ),
+        }
+    }
+
+    pub fn self_analysis_full(&self, max: usize) -> String {
+        match self {
+            Lang::En => format!(
+                "📦 That's {max} messages, the most one analysis reads. Further forwards are ignored."
+            ),
+            Lang::Ru => format!(
+                "📦 Это {max} сообщений — максимум для одного анализа. Остальные пересылки не учитываются."
+            ),
+            Lang::Uk => format!(
+                "📦 Це {max} повідомлень — максимум для одного аналізу. Решта пересилань не враховується."
+            ),
+            Lang::Es => format!(
+                "📦 Son {max} mensajes, lo máximo que lee un análisis. Los siguientes reenvíos se ignoran."
+            ),
+            Lang::De => format!(
+                "📦 Das sind {max} Nachrichten, mehr liest eine Analyse nicht. Weitere Weiterleitungen werden ignoriert."
+            ),
+        }
+    }
+
+    pub fn self_analysis_expired(&self) -> &'static str {
+        match self {
+            Lang::En => "⌛ Nothing is being collected. Send /analyze_me to start again.",
+            Lang::Ru => "⌛ Сбор сообщений не идёт. Отправьте /analyze_me, чтобы начать заново.",
+            Lang::Uk => "⌛ Збір повідомлень не триває. Надішліть /analyze_me, щоб почати знову.",
+            Lang::Es => "⌛ No se está recopilando nada. Envía /analyze_me para empezar de nuevo.",
+            Lang::De => {
+                "⌛ Es wird gerade nichts gesammelt. Sende /analyze_me, um neu zu beginnen."
+            }
+        }
+    }
+
+    pub fn self_analysis_too_few(&self, count: usize, min: usize) -> String {
+        match self {
+            Lang::En => {
+                format!("✋ {count} messages are not enough, please forward at least {min}.")
+            }
+            Lang::Ru => {
+                format!("✋ {count} сообщений недостаточно, перешлите хотя бы {min}.")
+            }
+            Lang::Uk => {
+                format!("✋ {count} повідомлень недостатньо, перешліть хоча б {min}.")
+            }
+            Lang::Es => {
+                format!("✋ {count} mensajes no son suficientes, reenvía al menos {min}.")
+            }
+            Lang::De => {
+                format!(
+                    "✋ {count} Nachrichten reichen nicht, bitte leite mindestens {min} weiter."
+                )
+            }
+        }
+    }
+
+    pub fn self_analysis_select_type(&self, count: usize, skipped: usize, credits: i32) -> String {
+        let mut text = match self {
+            Lang::En => format!("🪞 <b>{count} of your messages collected.</b>"),
+            Lang::Ru => format!("🪞 <b>Собрано ваших сообщений: {count}.</b>"),
+            Lang::Uk => format!("🪞 <b>Зібрано ваших повідомлень: {count}.</b>"),
+            Lang::Es => format!("🪞 <b>Se recopilaron {count} de tus mensajes.</b>"),
+            Lang::De => format!("🪞 <b>{count} deiner Nachrichten gesammelt.</b>"),
+        };
+        if skipped > 0 {
+            text.push_str(&match self {
+                Lang::En => format!(" {skipped} written by others were skipped."),
+                Lang::Ru => format!(" Пропущено чужих сообщений: {skipped}."),
+                Lang::Uk => format!(" Пропущено чужих повідомлень: {skipped}."),
+                Lang::Es => format!(" Se omitieron {skipped} escritos por otros."),
+                Lang::De => format!(" {skipped} von anderen wurden übersprungen."),
+            });
+        }
+        text.push_str(&match self {
+            Lang::En => format!("\n\n💳 Cost: {credits} credits. Choose the type of analysis:"),
+            Lang::Ru => format!("\n\n💳 Стоимость: {credits} кредитов. Выберите тип анализа:"),
+            Lang::Uk => format!("\n\n💳 Вартість: {credits} кредитів. Виберіть тип аналізу:"),
+            Lang::Es => format!("\n\n💳 Coste: {credits} créditos. Elige el tipo de análisis:"),
+            Lang::De => format!("\n\n💳 Kosten: {credits} Credits. Wähle die Art der Analyse:"),
+        });
+        text
+    }
+
+    /// shown in place of a channel name for an analysis of the user's own messages
+    pub fn self_analysis_target(&self) -> &'static str {
+        match self {
+            Lang::En => "your forwarded messages",
+            Lang::Ru => "ваши пересланные сообщения",
+            Lang::Uk => "ваші переслані повідомлення",
+            Lang::Es => "tus mensajes reenviados",
+            Lang::De => "deine weitergeleiteten Nachrichten",
+        }
+    }
+}
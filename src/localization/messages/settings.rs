@@ -0,0 +1,142 @@
+//! /settings and /language
+use super::Lang;
+use crate::llm::ModelTier;
+use crate::prompts::analysis::OutputLanguage;
+
+impl Lang {
+    pub fn settings_model_tier(&self, current: ModelTier, web_pages: bool) -> String {
+        let current_name = self.model_tier_name(current);
+        let mut text = match self {
+            Lang::En => format!(
+                "⚙️ <b>Settings</b>\n\n\
+                <b>Preferred model:</b> {current_name}\n\n\
+                • <b>Auto</b> — balanced speed and quality\n\
+                • <b>Fast</b> — quickest results\n\
+                • <b>Quality</b> — the most capable model, analyses take longer"
+            ),
+            Lang::Ru => format!(
+                "⚙️ <b>Настройки</b>\n\n\
+                <b>Предпочтительная модель:</b> {current_name}\n\n\
+                • <b>Авто</b> — баланс скорости и качества\n\
+                • <b>Быстро</b> — самые быстрые результаты\n\
+                • <b>Качество</b> — самая мощная модель, анализ занимает больше времени"
+            ),
+            Lang::Uk => format!(
+                "⚙️ <b>Налаштування</b>\n\n\
+                <b>Бажана модель:</b> {current_name}\n\n\
+                • <b>Авто</b> — баланс швидкості та якості\n\
+                • <b>Швидко</b> — найшвидші результати\n\
+                • <b>Якість</b> — найпотужніша модель, аналіз триває довше"
+            ),
+            Lang::Es => format!(
+                "⚙️ <b>Ajustes</b>\n\n\
+                <b>Modelo preferido:</b> {current_name}\n\n\
+                • <b>Automático</b> — equilibrio entre velocidad y calidad\n\
+                • <b>Rápido</b> — los resultados más rápidos\n\
+                • <b>Calidad</b> — el modelo más capaz, los análisis tardan más"
+            ),
+            Lang::De => format!(
+                "⚙️ <b>Einstellungen</b>\n\n\
+                <b>Bevorzugtes Modell:</b> {current_name}\n\n\
+                • <b>Automatisch</b> — ausgewogen zwischen Tempo und Qualität\n\
+                • <b>Schnell</b> — die schnellsten Ergebnisse\n\
+                • <b>Qualität</b> — das leistungsstärkste Modell, Analysen dauern länger"
+            ),
+        };
+        text.push_str(self.settings_web_pages(web_pages));
+        text
+    }
+
+    fn settings_web_pages(&self, enabled: bool) -> &'static str {
+        match (self, enabled) {
+            (Lang::En, true) => "\n\n<b>Long results:</b> a telegra.ph page with a short summary here. Anyone with the link can open the page",
+            (Lang::En, false) => "\n\n<b>Long results:</b> several messages in this chat",
+            (Lang::Ru, true) => "\n\n<b>Длинные результаты:</b> страница на telegra.ph и краткое содержание здесь. Страницу может открыть любой, у кого есть ссылка",
+            (Lang::Ru, false) => "\n\n<b>Длинные результаты:</b> несколько сообщений в этом чате",
+            (Lang::Uk, true) => "\n\n<b>Довгі результати:</b> сторінка на telegra.ph і короткий зміст тут. Сторінку може відкрити будь-хто, у кого є посилання",
+            (Lang::Uk, false) => "\n\n<b>Довгі результати:</b> кілька повідомлень у цьому чаті",
+            (Lang::Es, true) => "\n\n<b>Resultados largos:</b> una página de telegra.ph con un breve resumen aquí. Cualquiera con el enlace puede abrir la página",
+            (Lang::Es, false) => "\n\n<b>Resultados largos:</b> varios mensajes en este chat",
+            (Lang::De, true) => "\n\n<b>Lange Ergebnisse:</b> eine telegra.ph-Seite mit einer kurzen Zusammenfassung hier. Jeder mit dem Link kann die Seite öffnen",
+            (Lang::De, false) => "\n\n<b>Lange Ergebnisse:</b> mehrere Nachrichten in diesem Chat",
+        }
+    }
+
+    pub fn btn_web_pages(&self, enabled: bool) -> &'static str {
+        match (self, enabled) {
+            (Lang::En, true) => "💬 Send long results as messages",
+            (Lang::En, false) => "📄 Publish long results as a page",
+            (Lang::Ru, true) => "💬 Присылать длинные результаты сообщениями",
+            (Lang::Ru, false) => "📄 Публиковать длинные результаты страницей",
+            (Lang::Uk, true) => "💬 Надсилати довгі результати повідомленнями",
+            (Lang::Uk, false) => "📄 Публікувати довгі результати сторінкою",
+            (Lang::Es, true) => "💬 Enviar resultados largos como mensajes",
+            (Lang::Es, false) => "📄 Publicar resultados largos como página",
+            (Lang::De, true) => "💬 Lange Ergebnisse als Nachrichten senden",
+            (Lang::De, false) => "📄 Lange Ergebnisse als Seite veröffentlichen",
+        }
+    }
+
+    pub fn settings_output_language(&self, current: OutputLanguage) -> String {
+        let current_name = self.output_language_name(current);
+        match self {
+            Lang::En => format!(
+                "🌐 <b>Analysis language:</b> {current_name}\n\n\
+                Choose the language your analyses are written in. \
+                <b>Channel's language</b> follows the language most of the posts are written in."
+            ),
+            Lang::Ru => format!(
+                "🌐 <b>Язык анализа:</b> {current_name}\n\n\
+                Выберите язык, на котором будут написаны анализы. \
+                <b>Язык канала</b> — язык, на котором написано большинство постов."
+            ),
+            Lang::Uk => format!(
+                "🌐 <b>Мова аналізу:</b> {current_name}\n\n\
+                Виберіть мову, якою будуть написані аналізи. \
+                <b>Мова каналу</b> — мова, якою написано більшість дописів."
+            ),
+            Lang::Es => format!(
+                "🌐 <b>Idioma del análisis:</b> {current_name}\n\n\
+                Elige el idioma en el que se escriben tus análisis. \
+                <b>Idioma del canal</b> sigue el idioma de la mayoría de las publicaciones."
+            ),
+            Lang::De => format!(
+                "🌐 <b>Analysesprache:</b> {current_name}\n\n\
+                Wähle die Sprache, in der deine Analysen geschrieben werden. \
+                <b>Sprache des Kanals</b> folgt der Sprache, in der die meisten Beiträge verfasst sind."
+            ),
+        }
+    }
+
+    /// `current` is None while the interface follows the Telegram app's language
+    pub fn settings_ui_language(&self, current: Option<Lang>) -> String {
+        let current_name = current.map_or(self.ui_language_auto_name(), |lang| lang.native_name());
+        match self {
+            Lang::En => format!(
+                "🗣 <b>Bot language:</b> {current_name}\n\n\
+                Choose the language of the bot's messages and buttons. \
+                <b>Telegram's language</b> follows the language of your Telegram app."
+            ),
+            Lang::Ru => format!(
+                "🗣 <b>Язык бота:</b> {current_name}\n\n\
+                Выберите язык сообщений и кнопок бота. \
+                <b>Язык Telegram</b> — язык вашего приложения Telegram."
+            ),
+            Lang::Uk => format!(
+                "🗣 <b>Мова бота:</b> {current_name}\n\n\
+                Виберіть мову повідомлень і кнопок бота. \
+                <b>Мова Telegram</b> — мова вашого застосунку Telegram."
+            ),
+            Lang::Es => format!(
+                "🗣 <b>Idioma del bot:</b> {current_name}\n\n\
+                Elige el idioma de los mensajes y botones del bot. \
+                <b>Idioma de Telegram</b> sigue el idioma de tu aplicación de Telegram."
+            ),
+            Lang::De => format!(
+                "🗣 <b>Sprache des Bots:</b> {current_name}\n\n\
+                Wähle die Sprache der Nachrichten und Schaltflächen des Bots. \
+                <b>Telegram-Sprache</b> folgt der Sprache deiner Telegram-App."
+            ),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! report highlights, share cards and inline results
+use super::Lang;
+use crate::report::{ReportScores, MAX_SCORE};
+
+impl Lang {
+    /// structured highlights appended to a professional analysis; lists come pre-escaped
+    pub fn report_professional_highlights(
+        &self,
+        strengths: &str,
+        weaknesses: &str,
+        scores: &ReportScores,
+    ) -> String {
+        let score = |value: u8| format!("{}/{}", value, MAX_SCORE);
+        match self {
+            Lang::En => format!(
+                "\n\n<b>💪 Strengths:</b> {}\n<b>⚠️ Weaknesses:</b> {}\n<b>📊 Scores:</b> expertise {} · communication {} · consistency {} · humor {}",
+                strengths,
+                weaknesses,
+                score(scores.expertise),
+                score(scores.communication),
+                score(scores.consistency),
+                score(scores.humor)
+            ),
+            Lang::Ru => format!(
+                "\n\n<b>💪 Сильные стороны:</b> {}\n<b>⚠️ Слабые стороны:</b> {}\n<b>📊 Оценки:</b> экспертиза {} · коммуникация {} · последовательность {} · юмор {}",
+                strengths,
+                weaknesses,
+                score(scores.expertise),
+                score(scores.communication),
+                score(scores.consistency),
+                score(scores.humor)
+            ),
+            Lang::Uk => format!(
+                "\n\n<b>💪 Сильні сторони:</b> {}\n<b>⚠️ Слабкі сторони:</b> {}\n<b>📊 Оцінки:</b> експертиза {} · комунікація {} · послідовність {} · гумор {}",
+                strengths,
+                weaknesses,
+                score(scores.expertise),
+                score(scores.communication),
+                score(scores.consistency),
+                score(scores.humor)
+            ),
+            Lang::Es => format!(
+                "\n\n<b>💪 Fortalezas:</b> {}\n<b>⚠️ Debilidades:</b> {}\n<b>📊 Puntuaciones:</b> experiencia {} · comunicación {} · constancia {} · humor {}",
+                strengths,
+                weaknesses,
+                score(scores.expertise),
+                score(scores.communication),
+                score(scores.consistency),
+                score(scores.humor)
+            ),
+            Lang::De => format!(
+                "\n\n<b>💪 Stärken:</b> {}\n<b>⚠️ Schwächen:</b> {}\n<b>📊 Bewertungen:</b> Expertise {} · Kommunikation {} · Beständigkeit {} · Humor {}",
+                strengths,
+                weaknesses,
+                score(scores.expertise),
+                score(scores.communication),
+                score(scores.consistency),
+                score(scores.humor)
+            ),
+        }
+    }
+
+    /// structured highlights appended to a personal analysis; texts come pre-escaped
+    pub fn report_personal_highlights(&self, tone: &str, topics: &str) -> String {
+        match self {
+            Lang::En => format!("\n\n<b>🎭 Tone:</b> {}\n<b>🏷 Topics:</b> {}", tone, topics),
+            Lang::Ru => format!("\n\n<b>🎭 Тон:</b> {}\n<b>🏷 Темы:</b> {}", tone, topics),
+            Lang::Uk => format!("\n\n<b>🎭 Тон:</b> {}\n<b>🏷 Теми:</b> {}", tone, topics),
+            Lang::Es => format!("\n\n<b>🎭 Tono:</b> {}\n<b>🏷 Temas:</b> {}", tone, topics),
+            Lang::De => format!("\n\n<b>🎭 Ton:</b> {}\n<b>🏷 Themen:</b> {}", tone, topics),
+        }
+    }
+
+    pub fn share_card_title(&self, channel_name: &str, analysis_type: &str) -> String {
+        let emoji = self.analysis_emoji(analysis_type);
+        let type_capitalized = self.analysis_type_capitalized(analysis_type);
+        match self {
+            Lang::En => format!("{emoji} {type_capitalized} analysis of {channel_name}"),
+            Lang::Ru => format!("{emoji} {type_capitalized} анализ {channel_name}"),
+            Lang::Uk => format!("{emoji} {type_capitalized} аналіз {channel_name}"),
+            Lang::Es => format!("{emoji} {type_capitalized} de {channel_name}"),
+            Lang::De => format!("{emoji} {type_capitalized} von {channel_name}"),
+        }
+    }
+
+    pub fn share_card(
+        &self,
+        channel_name: &str,
+        analysis_type: &str,
+        excerpt: &str,
+        user_id: i32,
+    ) -> String {
+        let emoji = self.analysis_emoji(analysis_type);
+        let type_capitalized = self.analysis_type_capitalized(analysis_type);
+        match self {
+            Lang::En => format!(
+                "{emoji} <b>{type_capitalized} analysis of</b> <code>{channel_name}</code>\n\n\
+                {excerpt}\n\n\
+                Get yours from <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>"
+            ),
+            Lang::Ru => format!(
+                "{emoji} <b>{type_capitalized} анализ</b> <code>{channel_name}</code>\n\n\
+                {excerpt}\n\n\
+                Получите свой в <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>"
+            ),
+            Lang::Uk => format!(
+                "{emoji} <b>{type_capitalized} аналіз</b> <code>{channel_name}</code>\n\n\
+                {excerpt}\n\n\
+                Отримайте свій у <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>"
+            ),
+            Lang::Es => format!(
+                "{emoji} <b>{type_capitalized} de</b> <code>{channel_name}</code>\n\n\
+                {excerpt}\n\n\
+                Consigue el tuyo en <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>"
+            ),
+            Lang::De => format!(
+                "{emoji} <b>{type_capitalized} von</b> <code>{channel_name}</code>\n\n\
+                {excerpt}\n\n\
+                Hol dir deine bei <a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a>"
+            ),
+        }
+    }
+
+    pub fn inline_no_results(&self) -> &'static str {
+        match self {
+            Lang::En => "No completed analyses to share yet — analyze a channel",
+            Lang::Ru => "Пока нечем поделиться — проанализируйте канал",
+            Lang::Uk => "Поки що нічим поділитися — проаналізуйте канал",
+            Lang::Es => "Aún no hay análisis para compartir: analiza un canal",
+            Lang::De => "Noch keine Analysen zum Teilen — analysiere einen Kanal",
+        }
+    }
+}
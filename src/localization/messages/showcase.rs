@@ -0,0 +1,91 @@
+//! the showcase channel offer and posts
+
+use super::Lang;
+
+impl Lang {
+    pub fn showcase_offer(&self) -> &'static str {
+        match self {
+            Lang::En => "📣 Like this analysis? With your consent it can be posted to our showcase channel, with the channel name or anonymously.",
+            Lang::Ru => "📣 Понравился анализ? С вашего согласия его можно опубликовать в нашем канале-витрине — с именем канала или анонимно.",
+            Lang::Uk => "📣 Сподобався аналіз? З вашої згоди його можна опублікувати в нашому каналі-вітрині — з назвою каналу або анонімно.",
+            Lang::Es => "📣 ¿Te gusta este análisis? Con tu consentimiento puede publicarse en nuestro canal escaparate, con el nombre del canal o de forma anónima.",
+            Lang::De => "📣 Gefällt dir diese Analyse? Mit deiner Zustimmung kann sie in unserem Schaufenster-Kanal veröffentlicht werden, mit dem Kanalnamen oder anonym.",
+        }
+    }
+
+    pub fn btn_showcase_named(&self) -> &'static str {
+        match self {
+            Lang::En => "📣 Publish with the channel name",
+            Lang::Ru => "📣 Опубликовать с именем канала",
+            Lang::Uk => "📣 Опублікувати з назвою каналу",
+            Lang::Es => "📣 Publicar con el nombre del canal",
+            Lang::De => "📣 Mit Kanalnamen veröffentlichen",
+        }
+    }
+
+    pub fn btn_showcase_anonymous(&self) -> &'static str {
+        match self {
+            Lang::En => "🕶 Publish anonymously",
+            Lang::Ru => "🕶 Опубликовать анонимно",
+            Lang::Uk => "🕶 Опублікувати анонімно",
+            Lang::Es => "🕶 Publicar de forma anónima",
+            Lang::De => "🕶 Anonym veröffentlichen",
+        }
+    }
+
+    pub fn showcase_queued(&self) -> &'static str {
+        match self {
+            Lang::En => "Thanks! The analysis will appear in the showcase channel soon.",
+            Lang::Ru => "Спасибо! Анализ скоро появится в канале-витрине.",
+            Lang::Uk => "Дякуємо! Аналіз незабаром з'явиться в каналі-вітрині.",
+            Lang::Es => "¡Gracias! El análisis aparecerá pronto en el canal escaparate.",
+            Lang::De => "Danke! Die Analyse erscheint bald im Schaufenster-Kanal.",
+        }
+    }
+
+    pub fn showcase_unavailable(&self) -> &'static str {
+        match self {
+            Lang::En => "This analysis can't be published.",
+            Lang::Ru => "Этот анализ нельзя опубликовать.",
+            Lang::Uk => "Цей аналіз не можна опублікувати.",
+            Lang::Es => "Este análisis no se puede publicar.",
+            Lang::De => "Diese Analyse kann nicht veröffentlicht werden.",
+        }
+    }
+
+    /// header of a showcase post; `channel` is escaped, None keeps the channel anonymous
+    pub fn showcase_post_header(&self, channel: Option<&str>) -> String {
+        match (self, channel) {
+            (Lang::En, Some(channel)) => {
+                format!("📣 <b>Analysis of <code>{channel}</code></b> by @ScratchAuthorEgoBot\n\n")
+            }
+            (Lang::En, None) => {
+                "📣 <b>Analysis of an anonymous channel</b> by @ScratchAuthorEgoBot\n\n".to_string()
+            }
+            (Lang::Ru, Some(channel)) => format!(
+                "📣 <b>Анализ канала <code>{channel}</code></b> от @ScratchAuthorEgoBot\n\n"
+            ),
+            (Lang::Ru, None) => {
+                "📣 <b>Анализ анонимного канала</b> от @ScratchAuthorEgoBot\n\n".to_string()
+            }
+            (Lang::Uk, Some(channel)) => format!(
+                "📣 <b>Аналіз каналу <code>{channel}</code></b> від @ScratchAuthorEgoBot\n\n"
+            ),
+            (Lang::Uk, None) => {
+                "📣 <b>Аналіз анонімного каналу</b> від @ScratchAuthorEgoBot\n\n".to_string()
+            }
+            (Lang::Es, Some(channel)) => {
+                format!("📣 <b>Análisis de <code>{channel}</code></b> por @ScratchAuthorEgoBot\n\n")
+            }
+            (Lang::Es, None) => {
+                "📣 <b>Análisis de un canal anónimo</b> por @ScratchAuthorEgoBot\n\n".to_string()
+            }
+            (Lang::De, Some(channel)) => {
+                format!("📣 <b>Analyse von <code>{channel}</code></b> von @ScratchAuthorEgoBot\n\n")
+            }
+            (Lang::De, None) => {
+                "📣 <b>Analyse eines anonymen Kanals</b> von @ScratchAuthorEgoBot\n\n".to_string()
+            }
+        }
+    }
+}
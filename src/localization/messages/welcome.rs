@@ -0,0 +1,404 @@
+//! /start and /help
+
+use super::Lang;
+
+impl Lang {
+    pub fn welcome_no_credits(&self, user_id: i32, prices: &str, referral_info: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Channel Analyzer</b>\n\n\
+                Welcome! I can analyze Telegram channels and provide insights.\n\n\
+                📋 <b>How to use:</b>\n\
+                • Send me a channel username (e.g., <code>@channelname</code>)\n\
+                • I'll validate the channel and show analysis options\n\
+                • Choose your preferred analysis type\n\
+                • Get detailed results in seconds!\n\n\
+                ⚠️ <b>Note:</b> Only text content is analyzed. Channels with mostly images or videos may not work well.\n\n\
+                ⚡ <b>Analysis Types:</b>\n\
+                • 💼 Professional: Expert assessment for hiring\n\
+                • 🧠 Personal: Psychological profile insights\n\
+                • 🔥 Roast: Fun, brutally honest critique\n\n\
+                💰 <b>Pricing:</b>\n\
+                {prices}\n\n\
+                🎁 <b>Referral Program:</b> {referral_info}\n\
+                Share your link: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Get credits at milestones: 1, 5, 10, 20, 30...\n\
+                • Get 1 credit for each paid referral\n\n\
+                Choose a package below or just send me a channel name to get started!"
+            ),
+            Lang::Ru => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Анализатор каналов</b>\n\n\
+                Добро пожаловать! Я анализирую Telegram-каналы и предоставляю инсайты.\n\n\
+                📋 <b>Как использовать:</b>\n\
+                • Отправьте имя канала (например, <code>@channelname</code>)\n\
+                • Я проверю канал и покажу варианты анализа\n\
+                • Выберите тип анализа\n\
+                • Получите результаты за секунды!\n\n\
+                ⚠️ <b>Важно:</b> Анализируется только текст. Каналы с фото/видео могут не подойти.\n\n\
+                ⚡ <b>Типы анализа:</b>\n\
+                • 💼 Профессиональный: оценка для найма\n\
+                • 🧠 Личностный: психологический профиль\n\
+                • 🔥 Роаст: весёлая, честная критика\n\n\
+                💰 <b>Цены:</b>\n\
+                {prices}\n\n\
+                🎁 <b>Реферальная программа:</b> {referral_info}\n\
+                Ваша ссылка: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Кредиты на этапах: 1, 5, 10, 20, 30...\n\
+                • 1 кредит за каждого оплатившего реферала\n\n\
+                Выберите пакет ниже или отправьте имя канала!"
+            ),
+            Lang::Uk => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Аналізатор каналів</b>\n\n\
+                Ласкаво просимо! Я аналізую Telegram-канали та ділюся інсайтами.\n\n\
+                📋 <b>Як користуватися:</b>\n\
+                • Надішліть ім'я каналу (наприклад, <code>@channelname</code>)\n\
+                • Я перевірю канал і покажу варіанти аналізу\n\
+                • Виберіть тип аналізу\n\
+                • Отримайте результати за секунди!\n\n\
+                ⚠️ <b>Важливо:</b> Аналізується лише текст. Канали з фото/відео можуть не підійти.\n\n\
+                ⚡ <b>Типи аналізу:</b>\n\
+                • 💼 Професійний: оцінка для найму\n\
+                • 🧠 Особистісний: психологічний профіль\n\
+                • 🔥 Роаст: весела, чесна критика\n\n\
+                💰 <b>Ціни:</b>\n\
+                {prices}\n\n\
+                🎁 <b>Реферальна програма:</b> {referral_info}\n\
+                Ваше посилання: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Кредити на етапах: 1, 5, 10, 20, 30...\n\
+                • 1 кредит за кожного реферала, який оплатив\n\n\
+                Виберіть пакет нижче або надішліть ім'я каналу!"
+            ),
+            Lang::Es => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Analizador de canales</b>\n\n\
+                ¡Bienvenido! Analizo canales de Telegram y te doy ideas sobre ellos.\n\n\
+                📋 <b>Cómo usarlo:</b>\n\
+                • Envíame el nombre de un canal (p. ej., <code>@channelname</code>)\n\
+                • Validaré el canal y te mostraré las opciones de análisis\n\
+                • Elige el tipo de análisis que prefieras\n\
+                • ¡Recibe resultados detallados en segundos!\n\n\
+                ⚠️ <b>Nota:</b> Solo se analiza el texto. Los canales con sobre todo imágenes o vídeos pueden no funcionar bien.\n\n\
+                ⚡ <b>Tipos de análisis:</b>\n\
+                • 💼 Profesional: evaluación experta para contratar\n\
+                • 🧠 Personal: perfil psicológico\n\
+                • 🔥 Roast: crítica divertida y brutalmente honesta\n\n\
+                💰 <b>Precios:</b>\n\
+                {prices}\n\n\
+                🎁 <b>Programa de referidos:</b> {referral_info}\n\
+                Comparte tu enlace: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Consigue créditos en los hitos: 1, 5, 10, 20, 30...\n\
+                • Consigue 1 crédito por cada referido que pague\n\n\
+                ¡Elige un paquete abajo o envíame el nombre de un canal para empezar!"
+            ),
+            Lang::De => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Kanal-Analyzer</b>\n\n\
+                Willkommen! Ich analysiere Telegram-Kanäle und liefere dir Einblicke.\n\n\
+                📋 <b>So funktioniert's:</b>\n\
+                • Sende mir einen Kanalnamen (z. B. <code>@channelname</code>)\n\
+                • Ich prüfe den Kanal und zeige dir die Analyseoptionen\n\
+                • Wähle die gewünschte Analyseart\n\
+                • Erhalte ausführliche Ergebnisse in Sekunden!\n\n\
+                ⚠️ <b>Hinweis:</b> Analysiert wird nur Text. Kanäle mit überwiegend Bildern oder Videos funktionieren eventuell nicht gut.\n\n\
+                ⚡ <b>Analysearten:</b>\n\
+                • 💼 Beruflich: Experteneinschätzung für Recruiting\n\
+                • 🧠 Persönlich: psychologisches Profil\n\
+                • 🔥 Roast: lustige, schonungslos ehrliche Kritik\n\n\
+                💰 <b>Preise:</b>\n\
+                {prices}\n\n\
+                🎁 <b>Empfehlungsprogramm:</b> {referral_info}\n\
+                Teile deinen Link: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Erhalte Credits bei Meilensteinen: 1, 5, 10, 20, 30...\n\
+                • Erhalte 1 Credit für jede zahlende Empfehlung\n\n\
+                Wähle unten ein Paket oder sende mir einfach einen Kanalnamen, um loszulegen!"
+            ),
+        }
+    }
+
+    pub fn welcome_with_credits(&self, user_id: i32, referral_section: &str) -> String {
+        match self {
+            Lang::En => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Channel Analyzer</b>\n\n\
+                Welcome back! I can analyze Telegram channels and provide insights.\n\n\
+                📋 <b>How to use:</b>\n\
+                • Send me a channel username (e.g., <code>@channelname</code>)\n\
+                • I'll validate the channel and show analysis options\n\
+                • Choose your preferred analysis type\n\
+                • Get detailed results in seconds!\n\n\
+                ⚠️ <b>Note:</b> Only text content is analyzed. Channels with mostly images or videos may not work well.\n\n\
+                ⚡ <b>Analysis Types:</b>\n\
+                • 💼 Professional: Expert assessment for hiring\n\
+                • 🧠 Personal: Psychological profile insights\n\
+                • 🔥 Roast: Fun, brutally honest critique\n\n\
+                {referral_section}\n\n\
+                Just send me a channel name to get started!"
+            ),
+            Lang::Ru => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Анализатор каналов</b>\n\n\
+                С возвращением! Я анализирую Telegram-каналы и предоставляю инсайты.\n\n\
+                📋 <b>Как использовать:</b>\n\
+                • Отправьте имя канала (например, <code>@channelname</code>)\n\
+                • Я проверю канал и покажу варианты анализа\n\
+                • Выберите тип анализа\n\
+                • Получите результаты за секунды!\n\n\
+                ⚠️ <b>Важно:</b> Анализируется только текст. Каналы с фото/видео могут не подойти.\n\n\
+                ⚡ <b>Типы анализа:</b>\n\
+                • 💼 Профессиональный: оценка для найма\n\
+                • 🧠 Личностный: психологический профиль\n\
+                • 🔥 Роаст: весёлая, честная критика\n\n\
+                {referral_section}\n\n\
+                Отправьте имя канала, чтобы начать!"
+            ),
+            Lang::Uk => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Аналізатор каналів</b>\n\n\
+                З поверненням! Я аналізую Telegram-канали та ділюся інсайтами.\n\n\
+                📋 <b>Як користуватися:</b>\n\
+                • Надішліть ім'я каналу (наприклад, <code>@channelname</code>)\n\
+                • Я перевірю канал і покажу варіанти аналізу\n\
+                • Виберіть тип аналізу\n\
+                • Отримайте результати за секунди!\n\n\
+                ⚠️ <b>Важливо:</b> Аналізується лише текст. Канали з фото/відео можуть не підійти.\n\n\
+                ⚡ <b>Типи аналізу:</b>\n\
+                • 💼 Професійний: оцінка для найму\n\
+                • 🧠 Особистісний: психологічний профіль\n\
+                • 🔥 Роаст: весела, чесна критика\n\n\
+                {referral_section}\n\n\
+                Надішліть ім'я каналу, щоб почати!"
+            ),
+            Lang::Es => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Analizador de canales</b>\n\n\
+                ¡Hola de nuevo! Analizo canales de Telegram y te doy ideas sobre ellos.\n\n\
+                📋 <b>Cómo usarlo:</b>\n\
+                • Envíame el nombre de un canal (p. ej., <code>@channelname</code>)\n\
+                • Validaré el canal y te mostraré las opciones de análisis\n\
+                • Elige el tipo de análisis que prefieras\n\
+                • ¡Recibe resultados detallados en segundos!\n\n\
+                ⚠️ <b>Nota:</b> Solo se analiza el texto. Los canales con sobre todo imágenes o vídeos pueden no funcionar bien.\n\n\
+                ⚡ <b>Tipos de análisis:</b>\n\
+                • 💼 Profesional: evaluación experta para contratar\n\
+                • 🧠 Personal: perfil psicológico\n\
+                • 🔥 Roast: crítica divertida y brutalmente honesta\n\n\
+                {referral_section}\n\n\
+                ¡Envíame el nombre de un canal para empezar!"
+            ),
+            Lang::De => format!(
+                "🤖 <b><a href=\"https://t.me/ScratchAuthorEgoBot?start={user_id}\">@ScratchAuthorEgoBot</a> - Kanal-Analyzer</b>\n\n\
+                Willkommen zurück! Ich analysiere Telegram-Kanäle und liefere dir Einblicke.\n\n\
+                📋 <b>So funktioniert's:</b>\n\
+                • Sende mir einen Kanalnamen (z. B. <code>@channelname</code>)\n\
+                • Ich prüfe den Kanal und zeige dir die Analyseoptionen\n\
+                • Wähle die gewünschte Analyseart\n\
+                • Erhalte ausführliche Ergebnisse in Sekunden!\n\n\
+                ⚠️ <b>Hinweis:</b> Analysiert wird nur Text. Kanäle mit überwiegend Bildern oder Videos funktionieren eventuell nicht gut.\n\n\
+                ⚡ <b>Analysearten:</b>\n\
+                • 💼 Beruflich: Experteneinschätzung für Recruiting\n\
+                • 🧠 Persönlich: psychologisches Profil\n\
+                • 🔥 Roast: lustige, schonungslos ehrliche Kritik\n\n\
+                {referral_section}\n\n\
+                Sende mir einfach einen Kanalnamen, um loszulegen!"
+            ),
+        }
+    }
+
+    /// the first welcome, before the free trial is claimed with the "I'm human" button
+    pub fn welcome_verify_human(&self) -> &'static str {
+        match self {
+            Lang::En => "🤖 <b>Welcome to the Channel Analyzer!</b>\n\n\
+                I analyze Telegram channels and give you insights about their authors.\n\n\
+                🎁 Tap the button below to confirm you're human and get your free analysis credit.",
+            Lang::Ru => "🤖 <b>Добро пожаловать в Анализатор каналов!</b>\n\n\
+                Я анализирую Telegram-каналы и рассказываю об их авторах.\n\n\
+                🎁 Нажмите кнопку ниже, чтобы подтвердить, что вы человек, и получить бесплатный кредит.",
+            Lang::Uk => "🤖 <b>Ласкаво просимо до Аналізатора каналів!</b>\n\n\
+                Я аналізую Telegram-канали й розповідаю про їхніх авторів.\n\n\
+                🎁 Натисніть кнопку нижче, щоб підтвердити, що ви людина, і отримати безкоштовний кредит.",
+            Lang::Es => "🤖 <b>¡Bienvenido al Analizador de canales!</b>\n\n\
+                Analizo canales de Telegram y te cuento cosas sobre sus autores.\n\n\
+                🎁 Pulsa el botón de abajo para confirmar que eres humano y recibir tu crédito gratuito.",
+            Lang::De => "🤖 <b>Willkommen beim Kanal-Analyzer!</b>\n\n\
+                Ich analysiere Telegram-Kanäle und verrate dir etwas über ihre Autoren.\n\n\
+                🎁 Tippe unten, um zu bestätigen, dass du ein Mensch bist, und hol dir deinen kostenlosen Credit.",
+        }
+    }
+
+    pub fn trial_granted(&self, credits: i32) -> String {
+        match self {
+            Lang::En => format!(
+                "🎁 Thanks! Your free analysis credit is in, you have <b>{credits}</b>.\n\n\
+                Send me a channel name (e.g., <code>@channelname</code>) to get started!"
+            ),
+            Lang::Ru => format!(
+                "🎁 Спасибо! Бесплатный кредит начислен, у вас <b>{credits}</b>.\n\n\
+                Отправьте имя канала (например, <code>@channelname</code>), чтобы начать!"
+            ),
+            Lang::Uk => format!(
+                "🎁 Дякуємо! Безкоштовний кредит нараховано, у вас <b>{credits}</b>.\n\n\
+                Надішліть ім'я каналу (наприклад, <code>@channelname</code>), щоб почати!"
+            ),
+            Lang::Es => format!(
+                "🎁 ¡Gracias! Ya tienes tu crédito gratuito, te quedan <b>{credits}</b>.\n\n\
+                ¡Envíame el nombre de un canal (p. ej., <code>@channelname</code>) para empezar!"
+            ),
+            Lang::De => format!(
+                "🎁 Danke! Dein kostenloser Credit ist da, du hast <b>{credits}</b>.\n\n\
+                Schick mir einen Kanalnamen (z. B. <code>@channelname</code>), um loszulegen!"
+            ),
+        }
+    }
+
+    pub fn trial_withheld(&self) -> &'static str {
+        match self {
+            Lang::En => "⏳ Many accounts like yours got a free trial in the last hour, so yours waits for a review. You'll find the credit in /balance once it's approved, or you can buy credits below.",
+            Lang::Ru => "⏳ За последний час бесплатный кредит получили многие похожие аккаунты, поэтому ваш ждёт проверки. После одобрения он появится в /balance, а пока можно купить кредиты ниже.",
+            Lang::Uk => "⏳ За останню годину безкоштовний кредит отримали багато схожих акаунтів, тому ваш чекає на перевірку. Після схвалення він з'явиться в /balance, а поки можна купити кредити нижче.",
+            Lang::Es => "⏳ Muchas cuentas como la tuya recibieron una prueba gratuita en la última hora, así que la tuya espera una revisión. Verás el crédito en /balance cuando se apruebe, o puedes comprar créditos abajo.",
+            Lang::De => "⏳ In der letzten Stunde haben viele ähnliche Konten eine Gratis-Analyse bekommen, deshalb wartet deine auf eine Prüfung. Nach der Freigabe findest du den Credit unter /balance, oder du kaufst unten Credits.",
+        }
+    }
+
+    pub fn referral_info_has_referrals(&self, count: i32) -> String {
+        match self {
+            Lang::En => format!("You have {} referrals! 🎉", count),
+            Lang::Ru => format!("У вас {} рефералов! 🎉", count),
+            Lang::Uk => format!("У вас {} рефералів! 🎉", count),
+            Lang::Es => format!("¡Tienes {} referidos! 🎉", count),
+            Lang::De => format!("Du hast {} Empfehlungen! 🎉", count),
+        }
+    }
+
+    pub fn referral_info_no_referrals(&self) -> &'static str {
+        match self {
+            Lang::En => "Start earning free credits by referring friends!",
+            Lang::Ru => "Приглашайте друзей и получайте бесплатные кредиты!",
+            Lang::Uk => "Запрошуйте друзів і отримуйте безкоштовні кредити!",
+            Lang::Es => "¡Invita a tus amigos y gana créditos gratis!",
+            Lang::De => "Lade Freunde ein und sammle kostenlose Credits!",
+        }
+    }
+
+    pub fn referral_section_with_referrals(
+        &self,
+        credits: i32,
+        total_analyses: i32,
+        referrals: i32,
+        paid_referrals: i32,
+        referrals_to_next: i32,
+        user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "💳 <b>Your Status:</b>\n\
+                • Credits remaining: <b>{credits}</b>\n\
+                • Total analyses performed: <b>{total_analyses}</b>\n\
+                • Referrals: <b>{referrals}</b> (Paid: <b>{paid_referrals}</b>)\n\
+                • Next milestone reward in <b>{referrals_to_next}</b> referrals\n\n\
+                🎁 <b>Referral Program:</b>\n\
+                Share your link: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Get credits at milestones: 1, 5, 10, 20, 30...\n\
+                • Get 1 credit for each paid referral\n\n\
+                Great job on your {referrals} referrals! 🎉"
+            ),
+            Lang::Ru => format!(
+                "💳 <b>Ваш статус:</b>\n\
+                • Осталось кредитов: <b>{credits}</b>\n\
+                • Всего анализов: <b>{total_analyses}</b>\n\
+                • Рефералов: <b>{referrals}</b> (Оплативших: <b>{paid_referrals}</b>)\n\
+                • До следующей награды: <b>{referrals_to_next}</b> рефералов\n\n\
+                🎁 <b>Реферальная программа:</b>\n\
+                Ваша ссылка: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Кредиты на этапах: 1, 5, 10, 20, 30...\n\
+                • 1 кредит за каждого оплатившего реферала\n\n\
+                Отлично, у вас уже {referrals} рефералов! 🎉"
+            ),
+            Lang::Uk => format!(
+                "💳 <b>Ваш статус:</b>\n\
+                • Залишилося кредитів: <b>{credits}</b>\n\
+                • Усього аналізів: <b>{total_analyses}</b>\n\
+                • Рефералів: <b>{referrals}</b> (Оплатили: <b>{paid_referrals}</b>)\n\
+                • До наступної нагороди: <b>{referrals_to_next}</b> рефералів\n\n\
+                🎁 <b>Реферальна програма:</b>\n\
+                Ваше посилання: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Кредити на етапах: 1, 5, 10, 20, 30...\n\
+                • 1 кредит за кожного реферала, який оплатив\n\n\
+                Чудово, у вас уже {referrals} рефералів! 🎉"
+            ),
+            Lang::Es => format!(
+                "💳 <b>Tu estado:</b>\n\
+                • Créditos restantes: <b>{credits}</b>\n\
+                • Análisis realizados: <b>{total_analyses}</b>\n\
+                • Referidos: <b>{referrals}</b> (De pago: <b>{paid_referrals}</b>)\n\
+                • Próxima recompensa en <b>{referrals_to_next}</b> referidos\n\n\
+                🎁 <b>Programa de referidos:</b>\n\
+                Comparte tu enlace: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Consigue créditos en los hitos: 1, 5, 10, 20, 30...\n\
+                • Consigue 1 crédito por cada referido que pague\n\n\
+                ¡Buen trabajo con tus {referrals} referidos! 🎉"
+            ),
+            Lang::De => format!(
+                "💳 <b>Dein Status:</b>\n\
+                • Verbleibende Credits: <b>{credits}</b>\n\
+                • Durchgeführte Analysen: <b>{total_analyses}</b>\n\
+                • Empfehlungen: <b>{referrals}</b> (Zahlend: <b>{paid_referrals}</b>)\n\
+                • Nächste Meilenstein-Belohnung in <b>{referrals_to_next}</b> Empfehlungen\n\n\
+                🎁 <b>Empfehlungsprogramm:</b>\n\
+                Teile deinen Link: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Erhalte Credits bei Meilensteinen: 1, 5, 10, 20, 30...\n\
+                • Erhalte 1 Credit für jede zahlende Empfehlung\n\n\
+                Super, schon {referrals} Empfehlungen! 🎉"
+            ),
+        }
+    }
+
+    pub fn referral_section_no_referrals(
+        &self,
+        credits: i32,
+        total_analyses: i32,
+        user_id: i32,
+    ) -> String {
+        match self {
+            Lang::En => format!(
+                "💳 <b>Your Status:</b>\n\
+                • Credits remaining: <b>{credits}</b>\n\
+                • Total analyses performed: <b>{total_analyses}</b>\n\n\
+                🎁 <b>Referral Program:</b>\n\
+                Share your link: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Get credits at milestones: 1, 5, 10, 20, 30...\n\
+                • Get 1 credit for each paid referral"
+            ),
+            Lang::Ru => format!(
+                "💳 <b>Ваш статус:</b>\n\
+                • Осталось кредитов: <b>{credits}</b>\n\
+                • Всего анализов: <b>{total_analyses}</b>\n\n\
+                🎁 <b>Реферальная программа:</b>\n\
+                Ваша ссылка: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Кредиты на этапах: 1, 5, 10, 20, 30...\n\
+                • 1 кредит за каждого оплатившего реферала"
+            ),
+            Lang::Uk => format!(
+                "💳 <b>Ваш статус:</b>\n\
+                • Залишилося кредитів: <b>{credits}</b>\n\
+                • Усього аналізів: <b>{total_analyses}</b>\n\n\
+                🎁 <b>Реферальна програма:</b>\n\
+                Ваше посилання: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Кредити на етапах: 1, 5, 10, 20, 30...\n\
+                • 1 кредит за кожного реферала, який оплатив"
+            ),
+            Lang::Es => format!(
+                "💳 <b>Tu estado:</b>\n\
+                • Créditos restantes: <b>{credits}</b>\n\
+                • Análisis realizados: <b>{total_analyses}</b>\n\n\
+                🎁 <b>Programa de referidos:</b>\n\
+                Comparte tu enlace: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Consigue créditos en los hitos: 1, 5, 10, 20, 30...\n\
+                • Consigue 1 crédito por cada referido que pague"
+            ),
+            Lang::De => format!(
+                "💳 <b>Dein Status:</b>\n\
+                • Verbleibende Credits: <b>{credits}</b>\n\
+                • Durchgeführte Analysen: <b>{total_analyses}</b>\n\n\
+                🎁 <b>Empfehlungsprogramm:</b>\n\
+                Teile deinen Link: <code>https://t.me/ScratchAuthorEgoBot?start={user_id}</code>\n\
+                • Erhalte Credits bei Meilensteinen: 1, 5, 10, 20, 30...\n\
+                • Erhalte 1 Credit für jede zahlende Empfehlung"
+            ),
+        }
+    }
+}
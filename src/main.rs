@@ -1,36 +1,188 @@
-mod analysis;
-mod backend_config;
+mod admin;
+mod analysis_runner;
+mod api;
+mod backup;
+mod batch;
 mod bot;
-mod cache;
+mod changelog;
+mod channel_claims;
+mod channel_stats;
+mod config;
+mod cross_group;
+mod data_export;
+mod db_health;
+mod feedback;
+mod flagged_outputs;
+mod free_trials;
 mod handlers;
-mod llm;
+mod health;
+mod invoice_payload;
+mod job_queue;
+mod language_overrides;
+mod limits;
+mod llm_budget;
 mod localization;
+mod message_queue;
+mod metrics;
 mod migrations;
-mod prompts;
+mod observability;
+mod packages;
+mod privacy;
 mod rate_limiters;
-mod session_manager;
+mod receipts;
+mod recovery;
+mod referral_flags;
+mod referrals;
+mod roast_battle;
+mod self_analysis;
+mod showcase;
+mod subscriptions;
+mod telegraph;
 mod user_manager;
+mod user_sessions;
 mod utils;
-mod web_scraper;
+mod voice;
+mod warmup;
+mod watchdog;
 
-use analysis::AnalysisEngine;
-use bot::{ChannelLocks, TelegramBot};
+use tg_analyzer_core::{
+    analysis, backend_config, blocklist, cache, circuit_breaker, error, facts, in_flight, llm,
+    mock, prompts, report, retry_budget, session_manager, stats, web_scraper, workers,
+};
+
+use admin::AdminManager;
+use analysis::AnalysisDepth;
+use analysis_runner::{run_unrecorded_analysis, AnalysisTarget};
+use api::{ApiConfig, ApiState};
+use backup::{BackupConfig, BackupLocation, BackupManager};
+use bot::TelegramBot;
 use cache::CacheManager;
-use clap::Parser;
-use localization::Lang;
-use log::{error, info};
+use changelog::ChangelogManager;
+use clap::{Parser, Subcommand};
+use config::LiveConfig;
+use deadpool_postgres::Pool;
+use handlers::callback_data::ANALYSIS_TYPES;
+use health::{HealthConfig, HealthState};
+use limits::Limits;
+use llm_budget::LlmBudget;
+use log::{error, info, warn};
+use metrics::MetricsConfig;
 use migrations::MigrationManager;
-use session_manager::SessionManager;
+use observability::ObservabilityConfig;
+use prompts::analysis::MAX_FOCUS_LENGTH;
+use session_manager::{SessionManager, ValidationResult};
 use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use user_manager::UserManager;
+use utils::ResultPresenter;
+use workers::AnalysisWorkers;
 
 #[derive(Parser)]
 #[command(name = "tg-analyzer")]
 #[command(about = "A Telegram bot that analyzes channels")]
-struct Args {}
+struct Args {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Dump the database to a local path or s3:// URI (directories and prefixes get a timestamped file)
+    Backup {
+        #[arg(long)]
+        out: String,
+    },
+    /// Restore the database from a dump created by `backup`
+    Restore {
+        #[arg(long)]
+        from: String,
+    },
+    /// Manage the changelog shown by /whatsnew
+    Changelog {
+        #[command(subcommand)]
+        command: ChangelogCommand,
+    },
+    /// Manage the database overrides of prices and limits, applied on the next start or reload
+    Limits {
+        #[command(subcommand)]
+        command: LimitsCommand,
+    },
+    /// Analyze a channel without Telegram, e.g. `analyze @channel --type roast --out report.md`
+    ///
+    /// Nothing is charged or recorded; cached messages and llm answers are shared with the bot.
+    Analyze {
+        /// @channel or t.me link
+        channel: String,
+        #[arg(long = "type", default_value = "professional", value_parser = ANALYSIS_TYPES)]
+        analysis_type: String,
+        #[arg(long, default_value = "small", value_parser = ["small", "medium", "deep"])]
+        depth: String,
+        /// what the analysis should pay special attention to
+        #[arg(long)]
+        focus: Option<String>,
+        /// markdown file to write the report to, stdout by default
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Check that the Telegram sessions in sessions/ connect and are authorized
+    ValidateSessions,
+    /// Manage the cache of fetched channel messages
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChangelogCommand {
+    /// Add an entry; texts are plain text, russian ones fall back to english
+    Add {
+        #[arg(long)]
+        title: String,
+        #[arg(long)]
+        body: String,
+        #[arg(long)]
+        title_ru: Option<String>,
+        #[arg(long)]
+        body_ru: Option<String>,
+        /// major entries can be announced to active users
+        #[arg(long)]
+        major: bool,
+    },
+    /// List the latest entries
+    List {
+        #[arg(long, default_value_t = 10)]
+        limit: i64,
+    },
+    /// Queue a one-time announcement of a major entry to active users
+    Announce { id: i32 },
+}
+
+#[derive(Subcommand)]
+enum LimitsCommand {
+    /// Show the limits the bot would start with
+    List,
+    /// Override a limit, e.g. `limits set subscription_price 900`
+    Set { name: String, value: i64 },
+    /// Drop an override, going back to the env var or the default
+    Reset { name: String },
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Drop cached messages past their ttl, one channel's or all of them
+    ///
+    /// LLM answers are kept since shared and showcased analyses read them.
+    Purge {
+        #[arg(long)]
+        channel: Option<String>,
+        #[arg(long, conflicts_with = "channel")]
+        all: bool,
+    },
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -53,27 +205,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Info)
         .init();
+    // kept until main returns so queued error reports are sent before the process exits
+    let _error_reporting = start_error_reporting();
 
-    let _args = Args::parse();
+    let args = Args::parse();
+    if let Some(command) = args.command {
+        return run_cli_command(command).await;
+    }
 
     let bot_token =
         env::var("BOT_TOKEN").map_err(|_| "BOT_TOKEN environment variable is required")?;
 
     info!("Starting bot...");
 
-    // validate sessions before initialization
-    info!("Validating Telegram sessions...");
-    let validation_result = SessionManager::validate_sessions().await?;
+    // validate sessions before initialization; mock mode reads fixtures instead
+    if !mock::enabled() {
+        info!("Validating Telegram sessions...");
+        let validation_result = SessionManager::validate_sessions().await?;
 
-    if !validation_result.is_success() {
-        if let Some(error_msg) = validation_result.error_message() {
-            error!("Session validation failed:\n{}", error_msg);
-            return Err("Session validation failed - see above for details".into());
+        if !validation_result.is_success() {
+            if let Some(error_msg) = validation_result.error_message() {
+                error!("Session validation failed:\n{}", error_msg);
+                return Err("Session validation failed - see above for details".into());
+            }
         }
-    }
 
-    if let Some(success_msg) = validation_result.success_message() {
-        info!("{}", success_msg);
+        if let Some(success_msg) = validation_result.success_message() {
+            info!("{}", success_msg);
+        }
     }
 
     // initialize database pool and run migrations
@@ -84,88 +243,338 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // wrap pool in Arc for sharing
     let pool = Arc::new(pool);
 
+    // every llm call from here on is priced into llm_calls for the daily budget
+    llm::usage::enable_usage_recording(pool.clone());
+    // and every answer the safety review flags is kept for admins in flagged_outputs
+    llm::safety::enable_flag_recording(pool.clone());
+
+    // history, stats and cached messages are read from the replica if there is one
+    if let Some(read_pool) = CacheManager::create_read_pool().await? {
+        info!("DATABASE_READ_URL is set: read-heavy queries go to the read replica");
+        cache::enable_read_replica(Arc::new(read_pool));
+    }
+
+    // limits, model routing and gemini quotas, reloaded on SIGHUP or /reload
+    let config = Arc::new(LiveConfig::load(pool.clone()).await?);
+    // one budget for the bot and the api, so a reload updates both
+    let llm_budget = Arc::new(LlmBudget::new(
+        pool.clone(),
+        Arc::new(AdminManager::from_env(pool.clone())),
+        config.limits().daily_llm_budget_cents,
+    ));
+
+    start_scheduled_backups(pool.clone())?;
+    // one worker per telegram session fetches, shared by the bot and the api
+    let analysis_workers = Arc::new(AnalysisWorkers::start(pool.clone())?);
+
+    start_api(
+        pool.clone(),
+        config.clone(),
+        analysis_workers.clone(),
+        llm_budget.clone(),
+    );
+    start_metrics(pool.clone());
+    start_health(pool.clone(), analysis_workers.clone(), &bot_token);
+    start_db_health_checker(pool.clone());
+    // open circuit breakers close on their own once their backend answers again
+    tokio::spawn(circuit_breaker::run_recovery_probes());
+
     // initialize user manager with shared pool
     let user_manager = Arc::new(UserManager::new(pool.clone()));
 
-    // recover pending analyses from previous session
-    info!("Recovering pending analyses...");
-    recover_pending_analyses(user_manager.clone(), &bot_token).await?;
-
-    let bot = TelegramBot::new(&bot_token, user_manager, pool).await?;
+    let bot = TelegramBot::new(
+        &bot_token,
+        user_manager,
+        pool,
+        config,
+        analysis_workers,
+        llm_budget,
+    )
+    .await?;
     bot.run().await;
 
     Ok(())
 }
 
-/// recovers and resumes pending analyses from previous session
-async fn recover_pending_analyses(
-    user_manager: Arc<UserManager>,
-    bot_token: &str,
+/// runs a one-off maintenance command instead of the bot
+async fn run_cli_command(
+    command: CliCommand,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let pending_analyses = user_manager.get_pending_analyses().await?;
-
-    if pending_analyses.is_empty() {
-        info!("No pending analyses to recover");
-        return Ok(());
+    // the only command without a database
+    if let CliCommand::ValidateSessions = command {
+        return validate_sessions_command().await;
     }
 
-    info!(
-        "Found {} pending analyses to recover",
-        pending_analyses.len()
-    );
-
-    // create analysis engine for recovery
+    let database_url =
+        env::var("DATABASE_URL").map_err(|_| "DATABASE_URL environment variable not set")?;
     let pool = CacheManager::create_pool().await?;
+    MigrationManager::run_migrations(&pool).await?;
     let pool = Arc::new(pool);
-    let analysis_engine = Arc::new(Mutex::new(AnalysisEngine::new(pool)?));
-
-    // create bot instance for recovery
-    let bot = Arc::new(teloxide::Bot::new(bot_token));
-
-    // create channel locks for recovery
-    let channel_locks: ChannelLocks = Arc::new(Mutex::new(HashMap::new()));
-
-    for analysis in pending_analyses {
-        let bot_clone = bot.clone();
-        let analysis_engine_clone = analysis_engine.clone();
-        let user_manager_clone = user_manager.clone();
-        let channel_locks_clone = channel_locks.clone();
-
-        info!(
-            "Resuming analysis {} for user {} (channel: {}, type: {})",
-            analysis.id, analysis.telegram_user_id, analysis.channel_name, analysis.analysis_type
-        );
-
-        tokio::spawn(async move {
-            // use stored language from pending analysis, fallback to English
-            let lang = Lang::from_code(analysis.language.as_deref());
-            
-            if let Err(e) = TelegramBot::perform_single_analysis(
-                bot_clone,
-                teloxide::types::ChatId(analysis.telegram_user_id),
-                analysis.channel_name.clone(),
-                analysis.analysis_type.clone(),
-                analysis_engine_clone,
-                user_manager_clone.clone(),
-                analysis.user_id,
-                analysis.id,
-                channel_locks_clone,
-                lang,
-            )
-            .await
-            {
-                error!("Failed to recover analysis {}: {}", analysis.id, e);
-                // mark as failed if recovery failed
-                if let Err(mark_err) = user_manager_clone.mark_analysis_failed(analysis.id).await {
-                    error!(
-                        "Failed to mark recovered analysis {} as failed: {}",
-                        analysis.id, mark_err
-                    );
-                }
+    let backup_manager = BackupManager::new(pool.clone(), database_url);
+    let changelog = ChangelogManager::new(pool.clone());
+
+    match command {
+        CliCommand::Backup { out } => {
+            let location = backup_manager.backup(&BackupLocation::parse(&out)).await?;
+            println!("Backup written to {}", location);
+        }
+        CliCommand::Restore { from } => {
+            backup_manager
+                .restore(&BackupLocation::parse(&from))
+                .await?;
+            println!("Database restored from {}", from);
+        }
+        CliCommand::Changelog {
+            command:
+                ChangelogCommand::Add {
+                    title,
+                    body,
+                    title_ru,
+                    body_ru,
+                    major,
+                },
+        } => {
+            let id = changelog
+                .add_entry(
+                    &title,
+                    title_ru.as_deref(),
+                    &body,
+                    body_ru.as_deref(),
+                    major,
+                )
+                .await?;
+            println!("Added changelog entry {}", id);
+        }
+        CliCommand::Changelog {
+            command: ChangelogCommand::List { limit },
+        } => {
+            for entry in changelog.recent_entries(limit).await? {
+                let status = match (entry.major, entry.announced) {
+                    (true, true) => "major, announced",
+                    (true, false) => "major",
+                    (false, _) => "minor",
+                };
+                println!(
+                    "{:>4}  {}  {} ({})",
+                    entry.id, entry.published_at, entry.title_en, status
+                );
+            }
+        }
+        CliCommand::Changelog {
+            command: ChangelogCommand::Announce { id },
+        } => {
+            let queued = changelog.announce(id).await?;
+            println!("Queued announcement of entry {} for {} users", id, queued);
+        }
+        CliCommand::Limits {
+            command: LimitsCommand::List,
+        } => {
+            for (name, value) in Limits::load(&pool).await?.values() {
+                println!("{:<24} {}", name, value);
             }
-        });
+        }
+        CliCommand::Limits {
+            command: LimitsCommand::Set { name, value },
+        } => {
+            Limits::save_override(&pool, &name, value).await?;
+            println!(
+                "Limit {} overridden to {}, send /reload or restart the bot to apply it",
+                name, value
+            );
+        }
+        CliCommand::Limits {
+            command: LimitsCommand::Reset { name },
+        } => {
+            if Limits::remove_override(&pool, &name).await? {
+                println!(
+                    "Override of {} removed, send /reload or restart the bot to apply it",
+                    name
+                );
+            } else {
+                println!("{} has no override", name);
+            }
+        }
+        CliCommand::Analyze {
+            channel,
+            analysis_type,
+            depth,
+            focus,
+            out,
+        } => {
+            analyze_command(pool, &channel, analysis_type, &depth, focus, out).await?;
+        }
+        CliCommand::ValidateSessions => unreachable!("handled before connecting"),
+        CliCommand::Cache {
+            command: CacheCommand::Purge { channel, all },
+        } => {
+            let cache = CacheManager::new(pool);
+            let purged = match (channel, all) {
+                (Some(channel), _) => {
+                    let channel = TelegramBot::validate_and_normalize_channel(&channel)
+                        .ok_or_else(|| format!("{} is not a channel username", channel))?;
+                    cache.purge_channel_messages(&channel).await?
+                }
+                (None, true) => cache.purge_all_channel_messages().await?,
+                (None, false) => cache.purge_stale_channel_messages().await?,
+            };
+            println!("Purged {} cached corpora", purged);
+        }
     }
+    Ok(())
+}
 
-    info!("Started recovery for all pending analyses");
+/// runs one analysis and writes its markdown report to `out` or stdout
+async fn analyze_command(
+    pool: Arc<Pool>,
+    channel: &str,
+    analysis_type: String,
+    depth: &str,
+    focus: Option<String>,
+    out: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let channel_name = TelegramBot::validate_and_normalize_channel(channel)
+        .ok_or_else(|| format!("{} is not a channel username or t.me link", channel))?;
+    if focus
+        .as_ref()
+        .is_some_and(|focus| focus.chars().count() > MAX_FOCUS_LENGTH)
+    {
+        return Err(format!("Focus is longer than {} characters", MAX_FOCUS_LENGTH).into());
+    }
+
+    let limits = Limits::load(&pool).await?;
+    let admin = Arc::new(AdminManager::from_env(pool.clone()));
+    let llm_budget = LlmBudget::new(pool.clone(), admin, limits.daily_llm_budget_cents);
+    llm::usage::enable_usage_recording(pool.clone());
+    llm::safety::enable_flag_recording(pool.clone());
+    let analysis_workers = AnalysisWorkers::start(pool)?;
+    let target = AnalysisTarget {
+        channel_name,
+        analysis_type,
+        depth: AnalysisDepth::from_code(depth).unwrap_or_default(),
+        focus,
+        topic: None,
+        allow_low_text: true,
+    };
+
+    let result = run_unrecorded_analysis(
+        &analysis_workers,
+        &Arc::new(Mutex::new(HashMap::new())),
+        &llm_budget,
+        &target,
+    )
+    .await?;
+    let report = ResultPresenter::markdown(&result, &target.analysis_type, &target.channel_name)
+        .ok_or("The model returned no content for this analysis type")?;
+    match out {
+        Some(path) => {
+            std::fs::write(&path, report)?;
+            println!("Report written to {}", path.display());
+        }
+        None => print!("{}", report),
+    }
     Ok(())
 }
+
+/// reports which telegram sessions work, failing when none does
+async fn validate_sessions_command() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let validation_result = SessionManager::validate_sessions().await?;
+    if let Some(error_msg) = validation_result.error_message() {
+        return Err(error_msg.into());
+    }
+    if let Some(success_msg) = validation_result.success_message() {
+        println!("{}", success_msg);
+    }
+    if let ValidationResult::Success {
+        invalid_sessions, ..
+    } = &validation_result
+    {
+        for session in invalid_sessions {
+            println!("  invalid: {}", session);
+        }
+    }
+    Ok(())
+}
+
+/// warns about stale backups and starts the nightly backup task if configured
+fn start_scheduled_backups(
+    pool: Arc<Pool>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(config) = BackupConfig::from_env() else {
+        warn!("BACKUP_DESTINATION is not set, scheduled database backups are disabled");
+        return Ok(());
+    };
+    let database_url =
+        env::var("DATABASE_URL").map_err(|_| "DATABASE_URL environment variable not set")?;
+    let backup_manager = Arc::new(BackupManager::new(pool, database_url));
+
+    info!(
+        "Scheduling nightly database backups to {} (retention {} days)",
+        config.destination, config.retention_days
+    );
+    tokio::spawn(async move {
+        backup_manager.warn_if_stale(config.max_age_hours).await;
+        backup_manager.run_nightly(config).await;
+    });
+    Ok(())
+}
+
+/// starts the rest api next to the bot if configured
+fn start_api(
+    pool: Arc<Pool>,
+    live_config: Arc<LiveConfig>,
+    analysis_workers: Arc<AnalysisWorkers>,
+    llm_budget: Arc<LlmBudget>,
+) {
+    let Some(config) = ApiConfig::from_env() else {
+        info!("API_BIND_ADDR is not set, REST API is disabled");
+        return;
+    };
+    let state = ApiState::new(pool, live_config, analysis_workers, llm_budget);
+    tokio::spawn(async move {
+        if let Err(e) = api::serve(config, state).await {
+            error!("REST API stopped: {}", e);
+        }
+    });
+}
+
+/// pings the pool in the background and alerts the owners when the database misbehaves
+fn start_db_health_checker(pool: Arc<Pool>) {
+    let admin = Arc::new(AdminManager::from_env(pool.clone()));
+    tokio::spawn(db_health::run_db_health_checker(pool, admin));
+}
+
+/// reports panics and failures to sentry if configured
+fn start_error_reporting() -> Option<sentry::ClientInitGuard> {
+    let Some(config) = ObservabilityConfig::from_env() else {
+        info!("SENTRY_DSN is not set, error reporting is disabled");
+        return None;
+    };
+    observability::init(config)
+}
+
+/// starts the liveness and readiness probes if configured
+fn start_health(pool: Arc<Pool>, analysis_workers: Arc<AnalysisWorkers>, bot_token: &str) {
+    let Some(config) = HealthConfig::from_env() else {
+        info!("HEALTH_BIND_ADDR is not set, health checks are disabled");
+        return;
+    };
+    let state = Arc::new(HealthState::new(pool, analysis_workers, bot_token));
+    tokio::spawn(async move {
+        if let Err(e) = health::serve(config, state).await {
+            error!("Health checks stopped: {}", e);
+        }
+    });
+}
+
+/// starts the prometheus metrics endpoint if configured
+fn start_metrics(pool: Arc<Pool>) {
+    let Some(config) = MetricsConfig::from_env() else {
+        info!("METRICS_BIND_ADDR is not set, metrics endpoint is disabled");
+        return;
+    };
+    tokio::spawn(async move {
+        if let Err(e) = metrics::serve(config, pool).await {
+            error!("Metrics endpoint stopped: {}", e);
+        }
+    });
+}
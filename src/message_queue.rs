@@ -0,0 +1,212 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::error::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// sends tried before a message is dead-lettered
+pub const MAX_SEND_ATTEMPTS: i32 = 5;
+
+// wait after the first failed send, doubled after each further one
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+// telegram's bot-wide limit on sent messages
+pub const TELEGRAM_MESSAGES_PER_SECOND: u32 = 30;
+
+// messages sent in parallel per tick of the processor
+pub const SEND_BATCH_SIZE: i64 = 30;
+
+/// a message waiting in message_queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedMessage {
+    pub id: i32,
+    pub telegram_user_id: i64,
+    pub message: String,
+    pub parse_mode: String,
+    // failed sends so far
+    pub retry_count: i32,
+}
+
+/// what happens to a message after a failed send
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOutcome {
+    Retry(Duration),
+    DeadLettered,
+}
+
+/// wait before the next send after `failures` failed ones
+pub fn retry_delay(failures: i32) -> Duration {
+    let doublings = failures.clamp(1, MAX_SEND_ATTEMPTS) - 1;
+    BASE_RETRY_DELAY * 2u32.pow(doublings as u32)
+}
+
+/// retries with backoff until the message is out of attempts
+pub fn failure_outcome(failures: i32) -> FailureOutcome {
+    if failures >= MAX_SEND_ATTEMPTS {
+        FailureOutcome::DeadLettered
+    } else {
+        FailureOutcome::Retry(retry_delay(failures))
+    }
+}
+
+/// token bucket spreading sends over time; tokens go negative so that concurrent
+/// senders queue up behind each other instead of all waking at once
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// starts full, allowing a burst of `capacity`
+    pub fn new(capacity: u32, per_second: u32, now: Instant) -> Self {
+        Self {
+            capacity: f64::from(capacity),
+            per_second: f64::from(per_second),
+            tokens: f64::from(capacity),
+            updated: now,
+        }
+    }
+
+    /// takes a token, returning how long to wait before using it
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_second)
+        }
+    }
+}
+
+/// outgoing messages queued by the changelog, the notifier and bulk messaging
+pub struct MessageQueue {
+    pool: Arc<Pool>,
+}
+
+impl MessageQueue {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// up to `limit` messages to send now, at most one per recipient: a recipient's
+    /// oldest pending message, if it is due. later messages wait behind it, so they
+    /// arrive in order even when it is backing off
+    pub async fn due_batch(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<QueuedMessage>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT id, telegram_user_id, message, parse_mode, retry_count
+                 FROM (
+                     SELECT DISTINCT ON (telegram_user_id) *
+                     FROM message_queue
+                     WHERE status = 'pending'
+                     ORDER BY telegram_user_id, created_at, id
+                 ) heads
+                 WHERE next_attempt_at <= NOW()
+                 ORDER BY next_attempt_at, created_at, id
+                 LIMIT $1",
+                &[&limit],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| QueuedMessage {
+                id: row.get(0),
+                telegram_user_id: row.get(1),
+                message: row.get(2),
+                parse_mode: row.get::<_, Option<String>>(3).unwrap_or_default(),
+                retry_count: row.get(4),
+            })
+            .collect())
+    }
+
+    pub async fn mark_sent(&self, id: i32) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE message_queue SET status = 'sent', sent_at = NOW() WHERE id = $1",
+                &[&id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// counts a failed send, scheduling a retry or dead-lettering the message
+    pub async fn record_failure(
+        &self,
+        message: &QueuedMessage,
+        error: &str,
+    ) -> Result<FailureOutcome, Box<dyn Error + Send + Sync>> {
+        let failures = message.retry_count + 1;
+        let outcome = failure_outcome(failures);
+        let (status, delay) = match outcome {
+            FailureOutcome::Retry(delay) => ("pending", delay),
+            FailureOutcome::DeadLettered => ("dead", Duration::ZERO),
+        };
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE message_queue
+                 SET status = $2, retry_count = $3, error_message = $4,
+                     next_attempt_at = NOW() + $5 * INTERVAL '1 second'
+                 WHERE id = $1",
+                &[
+                    &message.id,
+                    &status,
+                    &failures,
+                    &error,
+                    &delay.as_secs_f64(),
+                ],
+            )
+            .await?;
+        match outcome {
+            FailureOutcome::Retry(delay) => info!(
+                "Message {} failed to send ({}/{}), retrying in {}s",
+                message.id,
+                failures,
+                MAX_SEND_ATTEMPTS,
+                delay.as_secs()
+            ),
+            FailureOutcome::DeadLettered => warn!(
+                "Message {} dead-lettered after {} failed sends: {}",
+                message.id, failures, error
+            ),
+        }
+        Ok(outcome)
+    }
+
+    pub async fn dead_letter_count(&self) -> Result<i64, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "SELECT COUNT(*) FROM message_queue WHERE status = 'dead'",
+                &[],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    /// puts one dead-lettered message, or all of them, back in the queue with fresh attempts
+    pub async fn requeue_dead(&self, id: Option<i32>) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let requeued = client
+            .execute(
+                "UPDATE message_queue
+                 SET status = 'pending', retry_count = 0, next_attempt_at = NOW()
+                 WHERE status = 'dead' AND ($1::INTEGER IS NULL OR id = $1)",
+                &[&id],
+            )
+            .await?;
+        info!("Requeued {} dead-lettered messages", requeued);
+        Ok(requeued)
+    }
+}
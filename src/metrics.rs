@@ -0,0 +1,307 @@
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use deadpool_postgres::{Pool, Status};
+use log::{error, info};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::env;
+use std::error::Error;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use crate::cache::read_pool;
+use crate::job_queue::JobQueue;
+
+// llm calls take seconds to minutes, telegram fetches of deep analyses even longer
+const LLM_LATENCY_BUCKETS: [f64; 10] = [1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 240.0, 480.0];
+const FETCH_DURATION_BUCKETS: [f64; 10] =
+    [0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+// a healthy connection is ready within milliseconds
+const DB_ACQUIRE_BUCKETS: [f64; 9] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0, 2.5, 10.0];
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// process-wide metrics shared by the bot and the rest api
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// which cache a lookup went to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    Messages,
+    Llm,
+}
+
+impl CacheKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheKind::Messages => "messages",
+            CacheKind::Llm => "llm",
+        }
+    }
+}
+
+/// prometheus collectors behind the /metrics endpoint
+pub struct Metrics {
+    registry: Registry,
+    analyses_started: IntCounter,
+    analyses_completed: IntCounter,
+    analyses_failed: IntCounterVec,
+    llm_latency: Histogram,
+    telegram_fetch_duration: Histogram,
+    cache_lookups: IntCounterVec,
+    message_queue_depth: IntGauge,
+    analysis_jobs: IntGaugeVec,
+    db_pool_connections: IntGaugeVec,
+    db_acquire_duration: Histogram,
+    db_health_check_failures: IntCounter,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("tg_analyzer".to_string()), None)
+            .expect("metrics prefix is valid");
+
+        let analyses_started =
+            IntCounter::new("analyses_started_total", "Analyses started").unwrap();
+        let analyses_completed =
+            IntCounter::new("analyses_completed_total", "Analyses completed and charged").unwrap();
+        let analyses_failed = IntCounterVec::new(
+            Opts::new("analyses_failed_total", "Analyses failed, by stage"),
+            &["stage"],
+        )
+        .unwrap();
+        let llm_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "llm_request_duration_seconds",
+                "Time to get a parsed analysis from the LLM, including retries",
+            )
+            .buckets(LLM_LATENCY_BUCKETS.to_vec()),
+        )
+        .unwrap();
+        let telegram_fetch_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "telegram_fetch_duration_seconds",
+                "Time to fetch a channel's messages from Telegram",
+            )
+            .buckets(FETCH_DURATION_BUCKETS.to_vec()),
+        )
+        .unwrap();
+        let cache_lookups = IntCounterVec::new(
+            Opts::new("cache_lookups_total", "Cache lookups, by cache and result"),
+            &["cache", "result"],
+        )
+        .unwrap();
+        let message_queue_depth =
+            IntGauge::new("message_queue_depth", "Pending messages in message_queue").unwrap();
+        let analysis_jobs = IntGaugeVec::new(
+            Opts::new("analysis_jobs", "Analysis jobs, by status"),
+            &["status"],
+        )
+        .unwrap();
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Database pool connections (size, available, max) and tasks waiting for one",
+            ),
+            &["state"],
+        )
+        .unwrap();
+        let db_acquire_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "db_acquire_duration_seconds",
+                "Time to get a pooled database connection and ping it, by the health check",
+            )
+            .buckets(DB_ACQUIRE_BUCKETS.to_vec()),
+        )
+        .unwrap();
+        let db_health_check_failures = IntCounter::new(
+            "db_health_check_failures_total",
+            "Health checks that couldn't get or ping a database connection",
+        )
+        .unwrap();
+
+        for collector in [
+            Box::new(analyses_started.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(analyses_completed.clone()),
+            Box::new(analyses_failed.clone()),
+            Box::new(llm_latency.clone()),
+            Box::new(telegram_fetch_duration.clone()),
+            Box::new(cache_lookups.clone()),
+            Box::new(message_queue_depth.clone()),
+            Box::new(analysis_jobs.clone()),
+            Box::new(db_pool_connections.clone()),
+            Box::new(db_acquire_duration.clone()),
+            Box::new(db_health_check_failures.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+
+        Self {
+            registry,
+            analyses_started,
+            analyses_completed,
+            analyses_failed,
+            llm_latency,
+            telegram_fetch_duration,
+            cache_lookups,
+            message_queue_depth,
+            analysis_jobs,
+            db_pool_connections,
+            db_acquire_duration,
+            db_health_check_failures,
+        }
+    }
+
+    pub fn analysis_started(&self) {
+        self.analyses_started.inc();
+    }
+
+    pub fn analysis_completed(&self) {
+        self.analyses_completed.inc();
+    }
+
+    /// `stage` is where the analysis stopped, e.g. "llm"
+    pub fn analysis_failed(&self, stage: &str) {
+        self.analyses_failed.with_label_values(&[stage]).inc();
+    }
+
+    pub fn observe_llm_latency(&self, duration: Duration) {
+        self.llm_latency.observe(duration.as_secs_f64());
+    }
+
+    pub fn observe_telegram_fetch(&self, duration: Duration) {
+        self.telegram_fetch_duration.observe(duration.as_secs_f64());
+    }
+
+    /// the hit ratio is left to the dashboard, e.g. rate(hits) / rate(all lookups)
+    pub fn cache_lookup(&self, cache: CacheKind, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+        self.cache_lookups
+            .with_label_values(&[cache.as_str(), result])
+            .inc();
+    }
+
+    pub fn set_db_pool_status(&self, status: &Status) {
+        for (state, value) in [
+            ("size", status.size),
+            ("available", status.available),
+            ("max", status.max_size),
+            ("waiting", status.waiting),
+        ] {
+            self.db_pool_connections
+                .with_label_values(&[state])
+                .set(value as i64);
+        }
+    }
+
+    pub fn observe_db_acquire(&self, duration: Duration) {
+        self.db_acquire_duration.observe(duration.as_secs_f64());
+    }
+
+    pub fn db_health_check_failed(&self) {
+        self.db_health_check_failures.inc();
+    }
+
+    /// the queue lives in postgres, so its depth is read on every scrape
+    pub async fn refresh_message_queue_depth(
+        &self,
+        pool: &Pool,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = pool.get().await?;
+        let row = client
+            .query_one(
+                "SELECT COUNT(*) FROM message_queue WHERE status = 'pending'",
+                &[],
+            )
+            .await?;
+        self.message_queue_depth.set(row.get(0));
+        Ok(())
+    }
+
+    /// the job queue lives in postgres too, so it is counted on every scrape as well
+    pub async fn refresh_analysis_jobs(
+        &self,
+        pool: &Arc<Pool>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for (status, count) in JobQueue::new(pool.clone()).counts().await? {
+            self.analysis_jobs
+                .with_label_values(&[status.as_str()])
+                .set(count);
+        }
+        Ok(())
+    }
+
+    /// all metrics in the prometheus text format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding doesn't fail");
+        String::from_utf8(buffer).expect("text encoding is utf-8")
+    }
+}
+
+/// metrics endpoint settings; the endpoint is disabled unless METRICS_BIND_ADDR is set
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    pub bind_addr: String,
+}
+
+impl MetricsConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            bind_addr: env::var("METRICS_BIND_ADDR").ok()?,
+        })
+    }
+}
+
+pub fn router(pool: Arc<Pool>) -> Router {
+    Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state(pool)
+}
+
+/// serves GET /metrics until the process exits
+pub async fn serve(
+    config: MetricsConfig,
+    pool: Arc<Pool>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let listener = tokio::net::TcpListener::bind(&config.bind_addr).await?;
+    info!("Metrics listening on {}", config.bind_addr);
+    axum::serve(listener, router(pool)).await?;
+    Ok(())
+}
+
+async fn render_metrics(State(pool): State<Arc<Pool>>) -> impl IntoResponse {
+    let pool = read_pool(&pool);
+    // a stale queue depth is better than no metrics at all
+    if let Err(e) = metrics().refresh_message_queue_depth(pool).await {
+        error!("Failed to refresh message queue depth: {}", e);
+    }
+    if let Err(e) = metrics().refresh_analysis_jobs(pool).await {
+        error!("Failed to refresh analysis job counts: {}", e);
+    }
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            TextEncoder::new().format_type().to_string(),
+        )],
+        metrics().render(),
+    )
+}
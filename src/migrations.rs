@@ -29,7 +29,7 @@ impl MigrationManager {
         }
 
         // check if we need to run any new migrations (always check, even after initial setup)
-        let current_version = Self::get_current_version(&mut client).await?;
+        let current_version = Self::get_current_version(&client).await?;
         if current_version < Self::latest_version() {
             let transaction = client.transaction().await?;
             Self::run_pending_migrations(&transaction, current_version).await?;
@@ -119,7 +119,7 @@ impl MigrationManager {
     }
 
     fn latest_version() -> i32 {
-        5 // increment this when adding new migrations
+        54 // increment this when adding new migrations
     }
 
     async fn run_pending_migrations(
@@ -206,6 +206,699 @@ impl MigrationManager {
                     "#;
                     transaction.batch_execute(migration_sql).await?;
                 }
+                6 => {
+                    // add optional user-provided focus instruction to analyses
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN focus TEXT;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                7 => {
+                    // add per-user preferred model tier (auto/fast/quality)
+                    let migration_sql = r#"
+                        ALTER TABLE users ADD COLUMN model_tier TEXT NOT NULL DEFAULT 'auto';
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                8 => {
+                    // record star payments so they can be refunded, and the refunds issued
+                    let migration_sql = r#"
+                        CREATE TABLE payments (
+                            id SERIAL PRIMARY KEY,
+                            user_id INTEGER NOT NULL REFERENCES users(id),
+                            telegram_payment_charge_id VARCHAR(255) NOT NULL UNIQUE,
+                            stars INTEGER NOT NULL,
+                            credits INTEGER NOT NULL,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_payments_user_id ON payments(user_id);
+
+                        CREATE TABLE refunds (
+                            id SERIAL PRIMARY KEY,
+                            payment_id INTEGER NOT NULL UNIQUE REFERENCES payments(id),
+                            user_id INTEGER NOT NULL REFERENCES users(id),
+                            stars INTEGER NOT NULL,
+                            credits_removed INTEGER NOT NULL,
+                            reason TEXT,
+                            refunded_by BIGINT,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_refunds_user_id ON refunds(user_id);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                9 => {
+                    // add message fetch depth to analyses so recovery re-fetches at the paid depth
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses
+                        ADD COLUMN depth VARCHAR(10) NOT NULL DEFAULT 'small' CHECK (depth IN ('small', 'medium', 'deep'));
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                10 => {
+                    // link analyses to their cached llm result so completed ones can be shared
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN cache_key VARCHAR(64);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                11 => {
+                    // successful database backups, used for retention and staleness warnings
+                    let migration_sql = r#"
+                        CREATE TABLE database_backups (
+                            id SERIAL PRIMARY KEY,
+                            location TEXT NOT NULL,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            deleted_at TIMESTAMP WITH TIME ZONE
+                        );
+
+                        CREATE INDEX idx_database_backups_created_at ON database_backups(created_at);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                12 => {
+                    // ledger of every credit change, shown to users by /balance
+                    let migration_sql = r#"
+                        CREATE TABLE credit_transactions (
+                            id SERIAL PRIMARY KEY,
+                            user_id INTEGER NOT NULL REFERENCES users(id),
+                            amount INTEGER NOT NULL,
+                            balance_after INTEGER NOT NULL,
+                            kind VARCHAR(20) NOT NULL CHECK (kind IN ('signup_bonus', 'purchase', 'analysis', 'referral_reward', 'refund')),
+                            reference TEXT,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_credit_transactions_user_id ON credit_transactions(user_id, id DESC);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                13 => {
+                    // add per-user analysis output language (channel/en/ru)
+                    let migration_sql = r#"
+                        ALTER TABLE users ADD COLUMN output_language TEXT NOT NULL DEFAULT 'channel';
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                14 => {
+                    // changelog shown by /whatsnew, plus an opt-out for release announcements
+                    let migration_sql = r#"
+                        CREATE TABLE changelog_entries (
+                            id SERIAL PRIMARY KEY,
+                            title_en TEXT NOT NULL,
+                            title_ru TEXT,
+                            body_en TEXT NOT NULL,
+                            body_ru TEXT,
+                            major BOOLEAN NOT NULL DEFAULT FALSE,
+                            published_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            announced_at TIMESTAMP WITH TIME ZONE
+                        );
+
+                        CREATE INDEX idx_changelog_entries_published_at ON changelog_entries(published_at);
+
+                        ALTER TABLE users ADD COLUMN announcements_enabled BOOLEAN NOT NULL DEFAULT TRUE;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                15 => {
+                    // api keys for the rest api, and which front end an analysis came from
+                    // so bot recovery doesn't deliver api analyses through telegram
+                    let migration_sql = r#"
+                        CREATE TABLE api_keys (
+                            id SERIAL PRIMARY KEY,
+                            user_id INTEGER NOT NULL REFERENCES users(id),
+                            key_hash VARCHAR(64) NOT NULL UNIQUE,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            last_used_at TIMESTAMP WITH TIME ZONE,
+                            revoked_at TIMESTAMP WITH TIME ZONE
+                        );
+
+                        CREATE INDEX idx_api_keys_user_id ON api_keys(user_id);
+
+                        ALTER TABLE user_analyses
+                        ADD COLUMN source VARCHAR(10) NOT NULL DEFAULT 'bot' CHECK (source IN ('bot', 'api'));
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                16 => {
+                    // admin roles on top of the ADMIN_USER_IDS owners, and the audit log of privileged actions
+                    let migration_sql = r#"
+                        CREATE TABLE admin_roles (
+                            telegram_user_id BIGINT PRIMARY KEY,
+                            role VARCHAR(20) NOT NULL CHECK (role IN ('owner', 'support', 'marketing')),
+                            granted_by BIGINT,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE TABLE admin_audit_log (
+                            id SERIAL PRIMARY KEY,
+                            actor_telegram_user_id BIGINT NOT NULL,
+                            action VARCHAR(50) NOT NULL,
+                            arguments TEXT NOT NULL DEFAULT '',
+                            outcome VARCHAR(20) NOT NULL CHECK (outcome IN ('succeeded', 'failed', 'denied')),
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_admin_audit_log_actor ON admin_audit_log(actor_telegram_user_id);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                17 => {
+                    // analyses delivered with a cut off llm answer; the flag is cleared once
+                    // the user claims the free regeneration
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN partial BOOLEAN NOT NULL DEFAULT FALSE;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                18 => {
+                    // weekly per-channel analysis counts for /top; the score columns only
+                    // count analyses with a structured report
+                    let migration_sql = r#"
+                        CREATE TABLE channel_stats (
+                            channel_name VARCHAR(255) NOT NULL,
+                            week_start DATE NOT NULL,
+                            analyses INTEGER NOT NULL DEFAULT 0,
+                            scored_analyses INTEGER NOT NULL DEFAULT 0,
+                            score_total DOUBLE PRECISION NOT NULL DEFAULT 0,
+                            PRIMARY KEY (channel_name, week_start)
+                        );
+
+                        CREATE INDEX idx_channel_stats_week ON channel_stats(week_start, analyses DESC);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                19 => {
+                    // ops overrides of prices and limits, read at startup
+                    let migration_sql = r#"
+                        CREATE TABLE limit_overrides (
+                            name VARCHAR(64) PRIMARY KEY,
+                            value BIGINT NOT NULL,
+                            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                20 => {
+                    // trends analysis type
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses DROP CONSTRAINT user_analyses_analysis_type_check;
+                        ALTER TABLE user_analyses ADD CONSTRAINT user_analyses_analysis_type_check
+                            CHECK (analysis_type IN ('professional', 'personal', 'roast', 'trends'));
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                21 => {
+                    // backend that fetched a cached corpus, and the one behind each analysis
+                    let migration_sql = r#"
+                        ALTER TABLE channel_messages ADD COLUMN backend VARCHAR(16);
+                        ALTER TABLE user_analyses ADD COLUMN backend VARCHAR(16);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                22 => {
+                    // send retries with backoff; messages out of attempts are dead-lettered
+                    // until an admin requeues them
+                    let migration_sql = r#"
+                        ALTER TABLE message_queue ADD COLUMN retry_count INTEGER NOT NULL DEFAULT 0;
+                        ALTER TABLE message_queue ADD COLUMN next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW();
+                        ALTER TABLE message_queue DROP CONSTRAINT message_queue_status_check;
+                        UPDATE message_queue SET status = 'dead' WHERE status = 'failed';
+                        ALTER TABLE message_queue ADD CONSTRAINT message_queue_status_check
+                            CHECK (status IN ('pending', 'sent', 'dead'));
+
+                        DROP INDEX IF EXISTS idx_message_queue_status;
+                        CREATE INDEX idx_message_queue_due ON message_queue(status, next_attempt_at);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                23 => {
+                    // the processor picks the oldest pending message of every recipient
+                    let migration_sql = r#"
+                        CREATE INDEX idx_message_queue_recipient_pending
+                            ON message_queue(telegram_user_id, created_at, id)
+                            WHERE status = 'pending';
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                24 => {
+                    // group analyses scoped to one forum topic
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN thread_id INTEGER;
+                        ALTER TABLE user_analyses ADD COLUMN topic_name VARCHAR(128);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                25 => {
+                    // completed analyses their requesters agreed to show in the showcase channel
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN showcase_consent_at TIMESTAMP WITH TIME ZONE;
+                        ALTER TABLE user_analyses ADD COLUMN showcase_anonymous BOOLEAN NOT NULL DEFAULT FALSE;
+                        ALTER TABLE user_analyses ADD COLUMN showcase_posted_at TIMESTAMP WITH TIME ZONE;
+
+                        CREATE INDEX idx_user_analyses_showcase_unposted
+                            ON user_analyses(showcase_consent_at)
+                            WHERE showcase_consent_at IS NOT NULL AND showcase_posted_at IS NULL;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                26 => {
+                    // thumbs up/down votes on delivered analyses, one per analysis; the type,
+                    // model and prompt version are copied so reports survive cache cleanups
+                    let migration_sql = r#"
+                        CREATE TABLE feedback (
+                            id SERIAL PRIMARY KEY,
+                            analysis_id INTEGER NOT NULL UNIQUE REFERENCES user_analyses(id) ON DELETE CASCADE,
+                            user_id INTEGER NOT NULL REFERENCES users(id),
+                            vote SMALLINT NOT NULL CHECK (vote IN (-1, 1)),
+                            analysis_type VARCHAR(50) NOT NULL,
+                            model VARCHAR(64),
+                            prompt_version INTEGER NOT NULL,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_feedback_created ON feedback(created_at);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                27 => {
+                    // prompt version an analysis ran with, NULL for analyses before versions
+                    // were recorded, which all ran the base prompt
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN prompt_version INTEGER;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                28 => {
+                    // monthly star subscriptions, one per user, and the credits they top up
+                    let migration_sql = r#"
+                        CREATE TABLE subscriptions (
+                            id SERIAL PRIMARY KEY,
+                            user_id INTEGER NOT NULL UNIQUE REFERENCES users(id),
+                            status VARCHAR(20) NOT NULL CHECK (status IN ('active', 'grace', 'expired')),
+                            credits_per_period INTEGER NOT NULL,
+                            stars INTEGER NOT NULL,
+                            last_charge_id VARCHAR(255),
+                            paid_until TIMESTAMP WITH TIME ZONE NOT NULL,
+                            periods_paid INTEGER NOT NULL DEFAULT 0,
+                            periods_credited INTEGER NOT NULL DEFAULT 0,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_subscriptions_status_paid_until ON subscriptions(status, paid_until);
+
+                        ALTER TABLE credit_transactions DROP CONSTRAINT credit_transactions_kind_check;
+                        ALTER TABLE credit_transactions ADD CONSTRAINT credit_transactions_kind_check
+                            CHECK (kind IN ('signup_bonus', 'purchase', 'analysis', 'referral_reward', 'refund', 'subscription'));
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                29 => {
+                    // posts a fetch skipped as forwarded, too short or media only, for text coverage
+                    let migration_sql = r#"
+                        ALTER TABLE channel_messages ADD COLUMN skipped_messages INTEGER NOT NULL DEFAULT 0;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                30 => {
+                    // whether a corpus is a channel's posts or a user's public profile
+                    let migration_sql = r#"
+                        ALTER TABLE channel_messages ADD COLUMN peer_kind TEXT NOT NULL DEFAULT 'channel';
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                31 => {
+                    // token usage and estimated cost of every llm call, and the days the
+                    // owners were told the daily llm budget ran out
+                    let migration_sql = r#"
+                        CREATE TABLE llm_calls (
+                            id SERIAL PRIMARY KEY,
+                            model TEXT NOT NULL,
+                            prompt_tokens BIGINT NOT NULL,
+                            output_tokens BIGINT NOT NULL,
+                            cost_usd DOUBLE PRECISION NOT NULL,
+                            created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_llm_calls_created_at ON llm_calls(created_at);
+
+                        CREATE TABLE llm_budget_alerts (
+                            day DATE PRIMARY KEY,
+                            spend_usd DOUBLE PRECISION NOT NULL,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                32 => {
+                    // analyses wait here until a runner leases them, so they survive restarts
+                    let migration_sql = r#"
+                        CREATE TABLE analysis_jobs (
+                            id SERIAL PRIMARY KEY,
+                            analysis_id INTEGER NOT NULL UNIQUE REFERENCES user_analyses(id) ON DELETE CASCADE,
+                            priority INTEGER NOT NULL DEFAULT 0,
+                            status VARCHAR(20) NOT NULL DEFAULT 'queued'
+                                CHECK (status IN ('queued', 'running', 'completed', 'failed', 'cancelled')),
+                            -- where and in which language the bot delivers the result
+                            chat_id BIGINT,
+                            language VARCHAR(10),
+                            allow_low_text BOOLEAN NOT NULL DEFAULT FALSE,
+                            attempts INTEGER NOT NULL DEFAULT 0,
+                            leased_by TEXT,
+                            lease_expires_at TIMESTAMP WITH TIME ZONE,
+                            last_error TEXT,
+                            created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+                            started_at TIMESTAMP WITH TIME ZONE,
+                            finished_at TIMESTAMP WITH TIME ZONE
+                        );
+
+                        CREATE INDEX idx_analysis_jobs_queued ON analysis_jobs(priority DESC, id)
+                            WHERE status IN ('queued', 'running');
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                33 => {
+                    // conversation state of the bot's users, restored after a restart
+                    let migration_sql = r#"
+                        CREATE TABLE user_sessions (
+                            telegram_user_id BIGINT PRIMARY KEY,
+                            state JSONB NOT NULL,
+                            updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+                            expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+                        );
+
+                        CREATE INDEX idx_user_sessions_expires_at ON user_sessions(expires_at);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                34 => {
+                    // bot language picked with /language, NULL follows telegram's language_code
+                    let migration_sql = r#"
+                        ALTER TABLE users ADD COLUMN language_override VARCHAR(10);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                35 => {
+                    // the messages a result was delivered as, resent by /resend without the llm
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN rendered_result JSONB;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                36 => {
+                    // referral reward rules set by /referralconfig, read on every referral
+                    let migration_sql = r#"
+                        CREATE TABLE referral_config (
+                            name VARCHAR(64) PRIMARY KEY,
+                            value BIGINT NOT NULL,
+                            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                37 => {
+                    // suspicious referrers, whose milestone rewards wait for /referralflags
+                    let migration_sql = r#"
+                        CREATE TABLE referral_flags (
+                            id SERIAL PRIMARY KEY,
+                            referrer_user_id INTEGER NOT NULL REFERENCES users(id),
+                            reason VARCHAR(32) NOT NULL,
+                            details TEXT NOT NULL,
+                            status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'cleared', 'confirmed')),
+                            reviewed_by BIGINT,
+                            reviewed_at TIMESTAMP WITH TIME ZONE,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            UNIQUE (referrer_user_id, reason)
+                        );
+
+                        CREATE INDEX idx_referral_flags_status ON referral_flags(status);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                38 => {
+                    // users who let the bot keep their group messages for /groupprofile, and
+                    // those messages; nothing is stored for anyone else
+                    let migration_sql = r#"
+                        CREATE TABLE cross_group_consents (
+                            telegram_user_id BIGINT PRIMARY KEY,
+                            consented_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE TABLE group_messages (
+                            id BIGSERIAL PRIMARY KEY,
+                            telegram_user_id BIGINT NOT NULL REFERENCES cross_group_consents(telegram_user_id) ON DELETE CASCADE,
+                            chat_id BIGINT NOT NULL,
+                            chat_title TEXT NOT NULL,
+                            message_id INTEGER NOT NULL,
+                            text TEXT NOT NULL,
+                            sent_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                            UNIQUE (chat_id, message_id)
+                        );
+
+                        CREATE INDEX idx_group_messages_user ON group_messages(telegram_user_id, chat_id, sent_at DESC);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                39 => {
+                    // long results go out as a telegra.ph page for users who turn it on
+                    let migration_sql = r#"
+                        ALTER TABLE users ADD COLUMN web_pages_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                40 => {
+                    // telegram file ids of voice summaries, one per analysis result and type
+                    let migration_sql = r#"
+                        CREATE TABLE voice_summaries (
+                            cache_key TEXT NOT NULL,
+                            analysis_type VARCHAR(20) NOT NULL,
+                            file_id TEXT NOT NULL,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            PRIMARY KEY (cache_key, analysis_type)
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                41 => {
+                    // channels /warmup pinned to or excluded from the nightly cache warm-up
+                    let migration_sql = r#"
+                        CREATE TABLE warm_channels (
+                            channel_name TEXT PRIMARY KEY,
+                            pinned BOOLEAN NOT NULL,
+                            updated_by BIGINT NOT NULL,
+                            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                42 => {
+                    // newest cached message id, so a refresh fetches only the posts after it,
+                    // and when the corpus was last fetched in full
+                    let migration_sql = r#"
+                        ALTER TABLE channel_messages
+                            ADD COLUMN last_message_id BIGINT,
+                            ADD COLUMN full_fetch_at TIMESTAMP WITH TIME ZONE;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                43 => {
+                    // the model that answered each analysis, and the analysis a second
+                    // opinion was asked for; one live second opinion per analysis
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses
+                            ADD COLUMN model TEXT,
+                            ADD COLUMN second_opinion_of INTEGER REFERENCES user_analyses(id) ON DELETE SET NULL;
+
+                        CREATE UNIQUE INDEX idx_user_analyses_second_opinion_of
+                            ON user_analyses(second_opinion_of) WHERE status <> 'failed';
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                44 => {
+                    // passages of llm answers the safety review flagged, for admins to review
+                    let migration_sql = r#"
+                        CREATE TABLE flagged_outputs (
+                            id SERIAL PRIMARY KEY,
+                            channel_name TEXT NOT NULL,
+                            analysis_type VARCHAR(20) NOT NULL,
+                            cache_key TEXT NOT NULL,
+                            model TEXT,
+                            section VARCHAR(20) NOT NULL,
+                            category VARCHAR(20) NOT NULL,
+                            excerpt TEXT NOT NULL,
+                            action VARCHAR(20) NOT NULL,
+                            reviewed_by BIGINT,
+                            reviewed_at TIMESTAMP WITH TIME ZONE,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        CREATE INDEX idx_flagged_outputs_unreviewed ON flagged_outputs(created_at DESC) WHERE reviewed_at IS NULL;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                45 => {
+                    // channels that must not be analyzed, by `@username` or bare channel id
+                    let migration_sql = r#"
+                        CREATE TABLE blocked_channels (
+                            target TEXT PRIMARY KEY,
+                            reason TEXT,
+                            blocked_by BIGINT NOT NULL,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                46 => {
+                    // channel ownership claims from /claim; a channel has at most one verified owner
+                    let migration_sql = r#"
+                        CREATE TABLE channel_claims (
+                            id SERIAL PRIMARY KEY,
+                            channel_name TEXT NOT NULL,
+                            telegram_user_id BIGINT NOT NULL,
+                            code VARCHAR(16) NOT NULL,
+                            verified_at TIMESTAMP WITH TIME ZONE,
+                            method VARCHAR(20) CHECK (method IN ('post', 'admin')),
+                            opted_out BOOLEAN NOT NULL DEFAULT FALSE,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            UNIQUE (channel_name, telegram_user_id)
+                        );
+
+                        CREATE UNIQUE INDEX idx_channel_claims_owner ON channel_claims(channel_name) WHERE verified_at IS NOT NULL;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                47 => {
+                    // accounts deleted with /delete_account, kept anonymized for the totals
+                    let migration_sql = r#"
+                        ALTER TABLE users ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                48 => {
+                    // the invoice payload of a payment, for /receipts; earlier payments are
+                    // packages if the ledger has their purchase, subscriptions otherwise
+                    let migration_sql = r#"
+                        ALTER TABLE payments ADD COLUMN package VARCHAR(64);
+
+                        UPDATE payments p SET package = CASE
+                            WHEN EXISTS (
+                                SELECT 1 FROM credit_transactions ct
+                                WHERE ct.kind = 'purchase' AND ct.reference = p.telegram_payment_charge_id
+                            ) THEN 'credits_' || p.credits
+                            ELSE 'subscription_' || p.credits
+                        END;
+
+                        ALTER TABLE payments ALTER COLUMN package SET NOT NULL;
+                        CREATE INDEX idx_payments_created_at ON payments(created_at);
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                49 => {
+                    // the star packages, edited with /packages; the first two take over the
+                    // single and bulk package limits, overrides included
+                    let migration_sql = r#"
+                        CREATE TABLE packages (
+                            id SERIAL PRIMARY KEY,
+                            credits INTEGER NOT NULL UNIQUE CHECK (credits > 0),
+                            stars INTEGER NOT NULL CHECK (stars > 0),
+                            title TEXT,
+                            description TEXT,
+                            active BOOLEAN NOT NULL DEFAULT TRUE,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+
+                        INSERT INTO packages (credits, stars)
+                        SELECT
+                            COALESCE((SELECT value FROM limit_overrides WHERE name = 'single_package_amount'), 1),
+                            COALESCE((SELECT value FROM limit_overrides WHERE name = 'single_package_price'), 100)
+                        ON CONFLICT (credits) DO NOTHING;
+
+                        INSERT INTO packages (credits, stars)
+                        SELECT
+                            COALESCE((SELECT value FROM limit_overrides WHERE name = 'bulk_package_amount'), 10),
+                            COALESCE((SELECT value FROM limit_overrides WHERE name = 'bulk_package_price'), 500)
+                        ON CONFLICT (credits) DO NOTHING;
+
+                        DELETE FROM limit_overrides WHERE name IN (
+                            'single_package_price', 'bulk_package_price',
+                            'single_package_amount', 'bulk_package_amount'
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                50 => {
+                    // free trials wait for the "I'm human" button and are throttled per
+                    // signup fingerprint; existing users already had theirs
+                    let migration_sql = r#"
+                        ALTER TABLE users
+                            ADD COLUMN trial_status VARCHAR(10) NOT NULL DEFAULT 'granted'
+                                CHECK (trial_status IN ('pending', 'granted', 'withheld')),
+                            ADD COLUMN signup_fingerprint TEXT,
+                            ADD COLUMN trial_decided_at TIMESTAMP WITH TIME ZONE;
+
+                        CREATE INDEX idx_users_signup_fingerprint
+                            ON users(signup_fingerprint, trial_decided_at)
+                            WHERE trial_status = 'granted';
+
+                        CREATE TABLE trial_clusters (
+                            id SERIAL PRIMARY KEY,
+                            fingerprint TEXT NOT NULL UNIQUE,
+                            withheld_count INTEGER NOT NULL DEFAULT 0,
+                            status VARCHAR(10) NOT NULL DEFAULT 'pending'
+                                CHECK (status IN ('pending', 'cleared', 'confirmed')),
+                            reviewed_by BIGINT,
+                            reviewed_at TIMESTAMP WITH TIME ZONE,
+                            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                            last_seen_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                        );
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                51 => {
+                    // the analysis watchdog times pending analyses from when they were last
+                    // queued, which is not when they were requested once they are reopened
+                    let migration_sql = r#"
+                        ALTER TABLE user_analyses ADD COLUMN pending_since TIMESTAMP WITH TIME ZONE;
+                        UPDATE user_analyses SET pending_since = analysis_timestamp
+                            WHERE status = 'pending';
+                        ALTER TABLE user_analyses ALTER COLUMN pending_since SET DEFAULT NOW();
+
+                        CREATE INDEX idx_user_analyses_pending_since ON user_analyses(pending_since)
+                            WHERE status = 'pending';
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                52 => {
+                    // only signups with a referrer are throttled; pending ones without keep
+                    // no fingerprint, withheld ones stay in their cluster for review
+                    let migration_sql = r#"
+                        UPDATE users SET signup_fingerprint = NULL
+                            WHERE trial_status = 'pending' AND signup_fingerprint LIKE '% referrer=-';
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                53 => {
+                    // a replaced api key keeps working until it expires, so clients can
+                    // switch to the new one without downtime
+                    let migration_sql = r#"
+                        ALTER TABLE api_keys ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
+                54 => {
+                    // cache keys carry the trends, prompt version and model suffixes, routed
+                    // model names alone can be longer than the old limit
+                    let migration_sql = r#"
+                        ALTER TABLE llm_results ALTER COLUMN cache_key TYPE TEXT;
+                        ALTER TABLE user_analyses ALTER COLUMN cache_key TYPE TEXT;
+                    "#;
+                    transaction.batch_execute(migration_sql).await?;
+                }
                 _ => {}
             }
             transaction
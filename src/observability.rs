@@ -0,0 +1,162 @@
+//! optional error reporting to sentry: panics, failed analyses, llm errors and payment
+//! failures are sent with the user, channel and analysis type they concern; every report
+//! is a no-op unless SENTRY_DSN is set
+use log::{info, warn};
+use std::env;
+use std::error::Error;
+
+use crate::analysis::AnalysisError;
+use crate::analysis_runner::{AnalysisJob, AnalysisRunError};
+use crate::user_manager::UserManagerError;
+
+/// error reporting settings; reporting is disabled unless SENTRY_DSN is set
+#[derive(Debug, Clone)]
+pub struct ObservabilityConfig {
+    pub dsn: String,
+    // e.g. production or staging, sentry's default when unset
+    pub environment: Option<String>,
+}
+
+impl ObservabilityConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            dsn: env::var("SENTRY_DSN").ok().filter(|dsn| !dsn.is_empty())?,
+            environment: env::var("SENTRY_ENVIRONMENT").ok(),
+        })
+    }
+}
+
+/// starts reporting, panics included; events are flushed when the guard is dropped, so
+/// it has to live as long as the process
+pub fn init(config: ObservabilityConfig) -> Option<sentry::ClientInitGuard> {
+    let dsn = match config.dsn.parse::<sentry::types::Dsn>() {
+        Ok(dsn) => dsn,
+        Err(e) => {
+            warn!(
+                "Ignoring malformed SENTRY_DSN, error reporting is disabled: {}",
+                e
+            );
+            return None;
+        }
+    };
+    let guard = sentry::init(sentry::ClientOptions {
+        dsn: Some(dsn),
+        release: sentry::release_name!(),
+        environment: config.environment.map(Into::into),
+        ..Default::default()
+    });
+    info!("Reporting errors to Sentry");
+    Some(guard)
+}
+
+/// where a reported error comes from, sent as the `kind` tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Analysis,
+    Llm,
+    Payment,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Analysis => "analysis",
+            ErrorKind::Llm => "llm",
+            ErrorKind::Payment => "payment",
+        }
+    }
+}
+
+/// what a reported error is about, as far as the caller knows
+#[derive(Debug, Clone, Default)]
+pub struct ErrorContext<'a> {
+    pub user_id: Option<i32>,
+    pub telegram_user_id: Option<i64>,
+    pub channel: Option<&'a str>,
+    pub analysis_type: Option<&'a str>,
+    pub analysis_id: Option<i32>,
+    // telegram_payment_charge_id of the payment
+    pub charge_id: Option<&'a str>,
+}
+
+impl ErrorContext<'_> {
+    fn apply(&self, kind: ErrorKind, scope: &mut sentry::Scope) {
+        scope.set_tag("kind", kind.as_str());
+        if let Some(user_id) = self.user_id {
+            scope.set_user(Some(sentry::User {
+                id: Some(user_id.to_string()),
+                ..Default::default()
+            }));
+        }
+        if let Some(telegram_user_id) = self.telegram_user_id {
+            scope.set_extra("telegram_user_id", telegram_user_id.into());
+        }
+        if let Some(channel) = self.channel {
+            scope.set_tag("channel", channel);
+        }
+        if let Some(analysis_type) = self.analysis_type {
+            scope.set_tag("analysis_type", analysis_type);
+        }
+        if let Some(analysis_id) = self.analysis_id {
+            scope.set_extra("analysis_id", analysis_id.into());
+        }
+        if let Some(charge_id) = self.charge_id {
+            scope.set_extra("charge_id", charge_id.into());
+        }
+    }
+}
+
+/// sends an error with its context
+pub fn report(kind: ErrorKind, error: &(dyn Error + 'static), context: &ErrorContext) {
+    sentry::with_scope(
+        |scope| context.apply(kind, scope),
+        || sentry::capture_error(error),
+    );
+}
+
+/// sends a failure that has no error value, e.g. a payment for an unknown package
+pub fn report_message(kind: ErrorKind, message: &str, context: &ErrorContext) {
+    sentry::with_scope(
+        |scope| context.apply(kind, scope),
+        || sentry::capture_message(message, sentry::Level::Error),
+    );
+}
+
+/// whether a failed analysis is worth a report: ours to fix, not the channel's or the user's
+pub fn is_reportable(error: &AnalysisRunError) -> bool {
+    let failure = match error {
+        AnalysisRunError::Prepare(e) | AnalysisRunError::Llm(e) => e.failure(),
+        AnalysisRunError::Prompt(_) => return true,
+        AnalysisRunError::NoMessages | AnalysisRunError::BudgetExceeded => return false,
+        AnalysisRunError::Complete(e) => return matches!(e, UserManagerError::DatabaseError(_)),
+    };
+    matches!(
+        failure,
+        AnalysisError::AiUnavailable
+            | AnalysisError::BudgetExhausted
+            | AnalysisError::ServiceDegraded
+            | AnalysisError::Internal(_)
+    )
+}
+
+/// reports a failed bot or api analysis unless the failure is an expected one
+pub fn report_analysis_failure(job: &AnalysisJob, error: &AnalysisRunError) {
+    if !is_reportable(error) {
+        return;
+    }
+    let kind = match error {
+        AnalysisRunError::Llm(_) => ErrorKind::Llm,
+        _ => ErrorKind::Analysis,
+    };
+    report(
+        kind,
+        error,
+        &ErrorContext {
+            user_id: Some(job.user_id),
+            channel: Some(&job.channel_name),
+            analysis_type: Some(&job.analysis_type),
+            analysis_id: Some(job.analysis_id),
+            ..Default::default()
+        },
+    );
+}
@@ -0,0 +1,226 @@
+//! the star packages of credits users can buy, kept in the packages table and edited by
+//! owners with /packages; read on every use so a change shows on the next keyboard
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub id: i32,
+    pub credits: i32,
+    pub stars: u32,
+    // shown on the invoice in place of the localized ones
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub active: bool,
+}
+
+/// what the packages were before they moved to the database, offered if it can't be read
+pub fn default_packages() -> Vec<Package> {
+    [(1, 1, 100), (2, 10, 500)]
+        .into_iter()
+        .map(|(id, credits, stars)| Package {
+            id,
+            credits,
+            stars,
+            title: None,
+            description: None,
+            active: true,
+        })
+        .collect()
+}
+
+/// stars saved by buying the package instead of its credits at the price per credit of
+/// the smallest package
+pub fn discount(packages: &[Package], package: &Package) -> u32 {
+    let Some(base) = packages.iter().min_by_key(|p| (p.credits, p.id)) else {
+        return 0;
+    };
+    let full_price = u64::from(base.stars) * package.credits as u64 / base.credits as u64;
+    u32::try_from(full_price.saturating_sub(u64::from(package.stars))).unwrap_or(u32::MAX)
+}
+
+/// what /packages can change about a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageChange {
+    Stars(u32),
+    // None goes back to the localized text
+    Title(Option<String>),
+    Description(Option<String>),
+    Active(bool),
+}
+
+pub struct PackageManager {
+    pool: Arc<Pool>,
+}
+
+impl PackageManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// the packages on sale, fewest credits first; the defaults if the table can't be read
+    /// so users can still buy
+    pub async fn active(&self) -> Vec<Package> {
+        match self.load(true).await {
+            Ok(packages) => packages,
+            Err(e) => {
+                warn!("Failed to load packages, offering the defaults: {}", e);
+                default_packages()
+            }
+        }
+    }
+
+    /// every package, the retired ones included
+    pub async fn all(&self) -> Result<Vec<Package>, Box<dyn Error + Send + Sync>> {
+        self.load(false).await
+    }
+
+    async fn load(&self, active_only: bool) -> Result<Vec<Package>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT id, credits, stars, title, description, active
+                 FROM packages
+                 WHERE active OR NOT $1
+                 ORDER BY credits, id",
+                &[&active_only],
+            )
+            .await?;
+        Ok(rows.iter().map(Self::package_from_row).collect())
+    }
+
+    /// a package on sale by id; None once it's retired
+    pub async fn get_active(
+        &self,
+        id: i32,
+    ) -> Result<Option<Package>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT id, credits, stars, title, description, active
+                 FROM packages WHERE id = $1 AND active",
+                &[&id],
+            )
+            .await?;
+        Ok(row.as_ref().map(Self::package_from_row))
+    }
+
+    /// puts a new package on sale; None if one with as many credits exists, since the
+    /// credits name the package in payments
+    pub async fn add(
+        &self,
+        credits: i32,
+        stars: u32,
+    ) -> Result<Option<Package>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "INSERT INTO packages (credits, stars) VALUES ($1, $2)
+                 ON CONFLICT (credits) DO NOTHING
+                 RETURNING id, credits, stars, title, description, active",
+                &[&credits, &(stars as i32)],
+            )
+            .await?;
+        let package = row.as_ref().map(Self::package_from_row);
+        if let Some(package) = &package {
+            info!(
+                "Added package {}: {} credits for {} stars",
+                package.id, credits, stars
+            );
+        }
+        Ok(package)
+    }
+
+    /// false if there's no such package
+    pub async fn update(
+        &self,
+        id: i32,
+        change: &PackageChange,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let column = match change {
+            PackageChange::Stars(_) => "stars",
+            PackageChange::Title(_) => "title",
+            PackageChange::Description(_) => "description",
+            PackageChange::Active(_) => "active",
+        };
+        let query = format!(
+            "UPDATE packages SET {} = $2, updated_at = NOW() WHERE id = $1",
+            column
+        );
+        let client = self.pool.get().await?;
+        let updated = match change {
+            PackageChange::Stars(stars) => client.execute(&query, &[&id, &(*stars as i32)]).await?,
+            PackageChange::Title(text) | PackageChange::Description(text) => {
+                client.execute(&query, &[&id, text]).await?
+            }
+            PackageChange::Active(active) => client.execute(&query, &[&id, active]).await?,
+        };
+        if updated > 0 {
+            info!("Package {} changed: {:?}", id, change);
+        }
+        Ok(updated > 0)
+    }
+
+    fn package_from_row(row: &tokio_postgres::Row) -> Package {
+        Package {
+            id: row.get(0),
+            credits: row.get(1),
+            stars: row.get::<_, i32>(2) as u32,
+            title: row.get(3),
+            description: row.get(4),
+            active: row.get(5),
+        }
+    }
+}
+
+/// what /packages was asked to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackagesCommand {
+    List,
+    Add { credits: i32, stars: u32 },
+    Change { id: i32, change: PackageChange },
+}
+
+impl PackagesCommand {
+    /// reads `/packages [add <credits> <stars>|price <id> <stars>|title <id> [text]|
+    /// description <id> [text]|enable <id>|disable <id>]`; an empty text clears it
+    pub fn parse(args: &str) -> Option<Self> {
+        let args = args.trim();
+        if args.is_empty() {
+            return Some(PackagesCommand::List);
+        }
+        let (action, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let (id, text) = rest
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((rest.trim(), ""));
+        let positive = |value: &str| value.parse::<i32>().ok().filter(|value| *value > 0);
+        let text = || {
+            Some(text.trim())
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        };
+        let change = match action.to_lowercase().as_str() {
+            "add" => {
+                return Some(PackagesCommand::Add {
+                    credits: positive(id)?,
+                    stars: positive(text()?.as_str())? as u32,
+                })
+            }
+            "price" => PackageChange::Stars(positive(text()?.as_str())? as u32),
+            "title" => PackageChange::Title(text()),
+            "description" => PackageChange::Description(text()),
+            "enable" if text().is_none() => PackageChange::Active(true),
+            "disable" if text().is_none() => PackageChange::Active(false),
+            _ => return None,
+        };
+        Some(PackagesCommand::Change {
+            id: positive(id)?,
+            change,
+        })
+    }
+}
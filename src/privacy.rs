@@ -0,0 +1,235 @@
+//! /delete_account: removes what identifies a user and keeps their anonymized analyses,
+//! payments and rewards, so channel, payment and referral totals don't change
+use deadpool_postgres::{Pool, Transaction};
+use log::info;
+use std::error::Error;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+use crate::analysis::{cross_group_corpus_name, roast_battle_members, self_corpus_name};
+use crate::bot::BotContext;
+use crate::handlers::CallbackHandler;
+use crate::localization::Lang;
+
+// stands in for the name of self, cross-group and battle corpora, which name their users
+const DELETED_CORPUS_NAME: &str = "self:deleted";
+
+/// deletes the stored messages of the `corpora` and what was made of them: the cached llm
+/// answers, their voice summaries and flagged passages, and the rendered results of the
+/// analyses that read them. the analyses themselves stay; returns the messages deleted
+pub async fn purge_corpora(
+    transaction: &Transaction<'_>,
+    corpora: &[String],
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    // an answer is cached under the hash of the messages it was made from, followed by what
+    // it is for, and the analyses that read it record the key
+    let hashes: Vec<String> = transaction
+        .query(
+            "SELECT DISTINCT split_part(cache_key, ':', 1) FROM user_analyses
+             WHERE channel_name = ANY($1) AND cache_key IS NOT NULL",
+            &[&corpora],
+        )
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    for table in ["llm_results", "voice_summaries"] {
+        transaction
+            .execute(
+                &format!(
+                    "DELETE FROM {} WHERE split_part(cache_key, ':', 1) = ANY($1)",
+                    table
+                ),
+                &[&hashes],
+            )
+            .await?;
+    }
+    transaction
+        .execute(
+            "DELETE FROM flagged_outputs
+             WHERE channel_name = ANY($1) OR split_part(cache_key, ':', 1) = ANY($2)",
+            &[&corpora, &hashes],
+        )
+        .await?;
+    transaction
+        .execute(
+            "UPDATE user_analyses SET rendered_result = NULL WHERE channel_name = ANY($1)",
+            &[&corpora],
+        )
+        .await?;
+    let deleted = transaction
+        .execute(
+            "DELETE FROM channel_messages WHERE channel_name = ANY($1)",
+            &[&corpora],
+        )
+        .await?;
+    Ok(deleted)
+}
+
+pub struct PrivacyManager {
+    pool: Arc<Pool>,
+}
+
+impl PrivacyManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// anonymizes the user's row and analyses, and deletes their kept and forwarded
+    /// messages, the self, cross-group and roast battle corpora made of them with their
+    /// cached answers, voice summaries and flagged passages, and their queued messages,
+    /// session, api keys and channel claims; queued analyses
+    /// are cancelled and a subscription stops topping up. referral rewards, payments and
+    /// the credit ledger only point at the anonymized row and stay. false if the bot
+    /// doesn't know the user
+    pub async fn delete_account(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        let Some(row) = transaction
+            .query_opt(
+                "SELECT id, username FROM users WHERE telegram_user_id = $1 FOR UPDATE",
+                &[&telegram_user_id],
+            )
+            .await?
+        else {
+            return Ok(false);
+        };
+        let user_id: i32 = row.get(0);
+        let username: Option<String> = row.get(1);
+
+        // the user's own corpora, and the roast battles they were in, which may have been
+        // started by someone else and are named by the members' usernames
+        let mut corpora = vec![
+            self_corpus_name(telegram_user_id),
+            cross_group_corpus_name(telegram_user_id),
+        ];
+        let named = transaction
+            .query(
+                "SELECT channel_name FROM user_analyses
+                 WHERE user_id = $1 AND channel_name LIKE 'self:%' AND channel_name <> $2
+                 UNION
+                 SELECT channel_name FROM user_analyses WHERE channel_name LIKE 'self:battle:%'
+                 UNION
+                 SELECT channel_name FROM channel_messages WHERE channel_name LIKE 'self:battle:%'",
+                &[&user_id, &DELETED_CORPUS_NAME],
+            )
+            .await?;
+        for row in named {
+            let name: String = row.get(0);
+            let battled = match (roast_battle_members(&name), &username) {
+                (Some((first, second)), Some(username)) => {
+                    first.eq_ignore_ascii_case(username) || second.eq_ignore_ascii_case(username)
+                }
+                _ => false,
+            };
+            let own = !name.starts_with("self:battle:");
+            if (own || battled) && !corpora.contains(&name) {
+                corpora.push(name);
+            }
+        }
+
+        transaction
+            .execute(
+                "UPDATE analysis_jobs SET status = 'cancelled', finished_at = NOW()
+                 WHERE status = 'queued'
+                   AND analysis_id IN (SELECT id FROM user_analyses WHERE user_id = $1)",
+                &[&user_id],
+            )
+            .await?;
+        // before the analyses are renamed, as their corpora are found by name
+        purge_corpora(&transaction, &corpora).await?;
+        // other members' battles with the user are named after them too
+        transaction
+            .execute(
+                "UPDATE user_analyses SET channel_name = $2 WHERE channel_name = ANY($1)",
+                &[&corpora, &DELETED_CORPUS_NAME],
+            )
+            .await?;
+        transaction
+            .execute(
+                "UPDATE user_analyses
+                 SET focus = NULL, topic_name = NULL, rendered_result = NULL,
+                     channel_name = CASE WHEN channel_name LIKE 'self:%' THEN $2
+                                         ELSE channel_name END
+                 WHERE user_id = $1",
+                &[&user_id, &DELETED_CORPUS_NAME],
+            )
+            .await?;
+        transaction
+            .execute(
+                "UPDATE subscriptions SET status = 'expired', updated_at = NOW()
+                 WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await?;
+        transaction
+            .execute(
+                "DELETE FROM user_analysis_choices WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await?;
+        transaction
+            .execute("DELETE FROM api_keys WHERE user_id = $1", &[&user_id])
+            .await?;
+        transaction
+            .execute(
+                "DELETE FROM referral_flags WHERE referrer_user_id = $1",
+                &[&user_id],
+            )
+            .await?;
+        // group_messages go with the consent
+        for table in [
+            "cross_group_consents",
+            "message_queue",
+            "user_sessions",
+            "channel_claims",
+        ] {
+            transaction
+                .execute(
+                    &format!("DELETE FROM {} WHERE telegram_user_id = $1", table),
+                    &[&telegram_user_id],
+                )
+                .await?;
+        }
+        // telegram user ids are positive, so the row can't be found or reused by anyone
+        transaction
+            .execute(
+                "UPDATE users
+                 SET telegram_user_id = -id, username = NULL, first_name = NULL,
+                     last_name = NULL, language = NULL, language_override = NULL,
+                     referred_by_user_id = NULL, announcements_enabled = FALSE,
+                     deleted_at = NOW(), updated_at = NOW()
+                 WHERE id = $1",
+                &[&user_id],
+            )
+            .await?;
+        transaction.commit().await?;
+
+        info!("Deleted the account of user {}", user_id);
+        Ok(true)
+    }
+}
+
+/// /delete_account asks for a confirmation first, in private chats only
+pub async fn handle_delete_account_command(
+    ctx: BotContext,
+    msg: &Message,
+    lang: Lang,
+) -> ResponseResult<()> {
+    if !msg.chat.is_private() {
+        ctx.bot
+            .send_message(msg.chat.id, lang.delete_account_private_only())
+            .await?;
+        return Ok(());
+    }
+    ctx.bot
+        .send_message(msg.chat.id, lang.delete_account_confirm())
+        .parse_mode(ParseMode::Html)
+        .reply_markup(CallbackHandler::create_delete_account_keyboard(lang))
+        .await?;
+    Ok(())
+}
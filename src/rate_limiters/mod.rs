@@ -1 +1,4 @@
-pub mod telegram;
+// the gemini and telegram limiters are the analysis pipeline's, the per-user one is the bot's
+pub use tg_analyzer_core::rate_limiters::*;
+
+pub mod user;
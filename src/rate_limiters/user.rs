@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+// buckets kept before idle ones are dropped; a full bucket behaves like a missing one
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// outcome of a user's request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Throttle {
+    Allowed,
+    // the request is dropped; `notify` is set for the first one of a throttled streak,
+    // so a flood gets one reply instead of one per message
+    Limited { retry_after: Duration, notify: bool },
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    notified: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rates {
+    capacity: f64,
+    per_second: f64,
+}
+
+impl Rates {
+    fn new(burst: u32, per_minute: u32) -> Self {
+        Self {
+            capacity: f64::from(burst.max(1)),
+            per_second: f64::from(per_minute.max(1)) / 60.0,
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.per_second).min(self.capacity)
+    }
+}
+
+/// per-user token buckets against request floods: a user may send `burst` requests at
+/// once and `per_minute` more every minute
+pub struct UserRateLimiter {
+    rates: RwLock<Rates>,
+    buckets: Mutex<HashMap<i64, Bucket>>,
+}
+
+impl UserRateLimiter {
+    pub fn new(burst: u32, per_minute: u32) -> Self {
+        Self {
+            rates: RwLock::new(Rates::new(burst, per_minute)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// changes the rates from the next request on; tokens already in a bucket are kept,
+    /// up to the new burst
+    pub fn set_rates(&self, burst: u32, per_minute: u32) {
+        *self.rates.write().unwrap_or_else(|e| e.into_inner()) = Rates::new(burst, per_minute);
+    }
+
+    /// takes a token of the user's bucket if one is left
+    pub fn check(&self, telegram_user_id: i64, now: Instant) -> Throttle {
+        let rates = *self.rates.read().unwrap_or_else(|e| e.into_inner());
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| rates.refilled(bucket, now) < rates.capacity);
+        }
+
+        let bucket = buckets.entry(telegram_user_id).or_insert(Bucket {
+            tokens: rates.capacity,
+            updated: now,
+            notified: false,
+        });
+        bucket.tokens = rates.refilled(bucket, now);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.notified = false;
+            return Throttle::Allowed;
+        }
+
+        let notify = !bucket.notified;
+        bucket.notified = true;
+        Throttle::Limited {
+            retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / rates.per_second),
+            notify,
+        }
+    }
+}
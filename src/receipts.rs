@@ -0,0 +1,206 @@
+//! /receipts lists a user's purchases; /revenue sums the payments by day or week for admins
+use deadpool_postgres::Pool;
+use log::error;
+use std::error::Error;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+use crate::bot::BotContext;
+use crate::cache::read_pool;
+use crate::localization::Lang;
+
+// /receipts shows at most this many purchases, newest first
+pub const RECEIPTS_LIMIT: i64 = 20;
+
+// /revenue without a count covers this many days or weeks
+pub const DEFAULT_REVENUE_DAYS: i32 = 14;
+pub const DEFAULT_REVENUE_WEEKS: i32 = 8;
+
+/// a recorded star payment, as the user sees it
+#[derive(Debug, Clone)]
+pub struct Receipt {
+    pub telegram_payment_charge_id: String,
+    // the invoice payload: credits_<credits> or subscription_<credits>
+    pub package: String,
+    pub stars: i32,
+    pub credits: i32,
+    pub refunded: bool,
+    pub created_at: String, // formatted by postgres as YYYY-MM-DD HH24:MI (UTC)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevenuePeriod {
+    Day,
+    Week,
+}
+
+impl RevenuePeriod {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "day" | "days" | "daily" => Some(RevenuePeriod::Day),
+            "week" | "weeks" | "weekly" => Some(RevenuePeriod::Week),
+            _ => None,
+        }
+    }
+
+    /// the date_trunc field; weeks start on monday
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevenuePeriod::Day => "day",
+            RevenuePeriod::Week => "week",
+        }
+    }
+
+    pub fn default_count(&self) -> i32 {
+        match self {
+            RevenuePeriod::Day => DEFAULT_REVENUE_DAYS,
+            RevenuePeriod::Week => DEFAULT_REVENUE_WEEKS,
+        }
+    }
+
+    /// reads `/revenue [day|week] [count]`; None if the arguments are neither
+    pub fn parse_args(args: &str) -> Option<(Self, i32)> {
+        let parts = args.split_whitespace().collect::<Vec<_>>();
+        let (period, count) = match parts.as_slice() {
+            [] => (RevenuePeriod::Day, None),
+            [count] if count.parse::<i32>().is_ok() => (RevenuePeriod::Day, Some(*count)),
+            [period] => (Self::parse(period)?, None),
+            [period, count] => (Self::parse(period)?, Some(*count)),
+            _ => return None,
+        };
+        let count = match count {
+            Some(count) => count.parse::<i32>().ok()?,
+            None => period.default_count(),
+        };
+        Some((period, count.clamp(1, 365)))
+    }
+}
+
+/// payments of one day or week, refunded ones included in `stars` and subtracted in
+/// `net_stars`
+#[derive(Debug, Clone)]
+pub struct RevenueLine {
+    // the first day of the period, YYYY-MM-DD (UTC)
+    pub period_start: String,
+    pub payments: i64,
+    pub stars: i64,
+    pub refunded_stars: i64,
+    pub net_stars: i64,
+}
+
+pub struct ReceiptsManager {
+    pool: Arc<Pool>,
+}
+
+impl ReceiptsManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// the user's latest payments, newest first
+    pub async fn history(
+        &self,
+        telegram_user_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Receipt>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT p.telegram_payment_charge_id, p.package, p.stars, p.credits, r.id IS NOT NULL,
+                        TO_CHAR(p.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM payments p
+                 JOIN users u ON p.user_id = u.id
+                 LEFT JOIN refunds r ON r.payment_id = p.id
+                 WHERE u.telegram_user_id = $1
+                 ORDER BY p.created_at DESC, p.id DESC
+                 LIMIT $2",
+                &[&telegram_user_id, &limit],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Receipt {
+                telegram_payment_charge_id: row.get(0),
+                package: row.get(1),
+                stars: row.get(2),
+                credits: row.get(3),
+                refunded: row.get(4),
+                created_at: row.get(5),
+            })
+            .collect())
+    }
+
+    /// revenue of the last `count` days or weeks including the current one, newest
+    /// first; periods without payments are left out
+    pub async fn revenue(
+        &self,
+        period: RevenuePeriod,
+        count: i32,
+    ) -> Result<Vec<RevenueLine>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT TO_CHAR(date_trunc($1, p.created_at AT TIME ZONE 'UTC'), 'YYYY-MM-DD') AS period_start,
+                        COUNT(*), SUM(p.stars)::bigint, COALESCE(SUM(r.stars), 0)::bigint
+                 FROM payments p
+                 LEFT JOIN refunds r ON r.payment_id = p.id
+                 WHERE p.created_at >= date_trunc($1, NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+                                       - ($2 - 1) * ('1 ' || $1)::interval
+                 GROUP BY period_start
+                 ORDER BY period_start DESC",
+                &[&period.as_str(), &count],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let stars: i64 = row.get(2);
+                let refunded_stars: i64 = row.get(3);
+                RevenueLine {
+                    period_start: row.get(0),
+                    payments: row.get(1),
+                    stars,
+                    refunded_stars,
+                    net_stars: stars - refunded_stars,
+                }
+            })
+            .collect())
+    }
+}
+
+/// /receipts, in private chats only like /export_my_data
+pub async fn handle_receipts_command(
+    ctx: BotContext,
+    msg: &Message,
+    lang: Lang,
+) -> ResponseResult<()> {
+    if !msg.chat.is_private() {
+        ctx.bot
+            .send_message(msg.chat.id, lang.receipts_private_only())
+            .await?;
+        return Ok(());
+    }
+    let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+    let reply = match ctx.receipts.history(telegram_user_id, RECEIPTS_LIMIT).await {
+        Ok(receipts) => {
+            let lines = receipts
+                .iter()
+                .map(|receipt| lang.receipt_line(receipt))
+                .collect::<Vec<_>>();
+            lang.receipts_list(&lines)
+        }
+        Err(e) => {
+            error!(
+                "Failed to load the receipts of user {}: {}",
+                telegram_user_id, e
+            );
+            lang.error_processing_request().to_string()
+        }
+    };
+    ctx.bot
+        .send_message(msg.chat.id, reply)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use log::{error, info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::ChatId;
+
+use crate::analysis::{invite_hash, AnalysisDepth};
+use crate::bot::{BotContext, TelegramBot};
+use crate::handlers::callback_data::ANALYSIS_TYPES;
+use crate::localization::Lang;
+use crate::user_manager::{AnalysisSource, PendingAnalysis, UserManager};
+
+/// analyses requested longer ago than this are not resumed, the user has moved on by then
+pub const MAX_RECOVERY_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// what to do with an analysis left pending by a restart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryPlan {
+    Resume(AnalysisDepth),
+    Abandon,
+}
+
+impl RecoveryPlan {
+    /// resumes analyses that are recent and still describe a valid request
+    pub fn for_pending(pending: &PendingAnalysis) -> Self {
+        let Some(depth) = AnalysisDepth::from_code(&pending.depth) else {
+            return RecoveryPlan::Abandon;
+        };
+        let valid = ANALYSIS_TYPES.contains(&pending.analysis_type.as_str())
+            && (TelegramBot::validate_and_normalize_channel(&pending.channel_name).is_some()
+                || invite_hash(&pending.channel_name).is_some())
+            && pending.age <= MAX_RECOVERY_AGE;
+        if valid {
+            RecoveryPlan::Resume(depth)
+        } else {
+            RecoveryPlan::Abandon
+        }
+    }
+}
+
+/// re-queues the bot analyses interrupted by a restart and tells their users;
+/// the ones that can't be resumed are marked failed with an apology. Analyses whose job
+/// another replica's runner holds a live lease on are left to it
+pub async fn recover_pending_analyses(ctx: BotContext) {
+    let pending_analyses = match ctx
+        .user_manager
+        .get_pending_analyses(AnalysisSource::Bot)
+        .await
+    {
+        Ok(pending) => pending,
+        Err(e) => {
+            error!("Failed to load pending analyses for recovery: {}", e);
+            return;
+        }
+    };
+
+    if pending_analyses.is_empty() {
+        info!("No pending analyses to recover");
+        return;
+    }
+
+    info!(
+        "Found {} pending analyses to recover",
+        pending_analyses.len()
+    );
+
+    for analysis in pending_analyses {
+        // use stored language from pending analysis, fallback to English
+        let lang = Lang::from_code(analysis.language.as_deref());
+        let chat_id = ChatId(analysis.telegram_user_id);
+
+        match RecoveryPlan::for_pending(&analysis) {
+            RecoveryPlan::Resume(_) => {
+                // the job may already be queued, queuing it again releases the expired lease
+                // of the previous process; one another replica is running is left alone
+                match ctx.job_queue.resume(analysis.id, false).await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        info!(
+                            "Analysis {} is running elsewhere, leaving it be",
+                            analysis.id
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("Failed to re-queue analysis {}: {}", analysis.id, e);
+                        mark_failed(&ctx.user_manager, analysis.id).await;
+                        continue;
+                    }
+                }
+                info!(
+                    "Resumed analysis {} for user {} (channel: {}, type: {})",
+                    analysis.id,
+                    analysis.telegram_user_id,
+                    analysis.channel_name,
+                    analysis.analysis_type
+                );
+                if let Err(e) = ctx
+                    .bot
+                    .send_message(chat_id, lang.analysis_resumed(&analysis.channel_name))
+                    .await
+                {
+                    warn!(
+                        "Failed to notify user {} about resumed analysis {}: {}",
+                        analysis.telegram_user_id, analysis.id, e
+                    );
+                }
+            }
+            RecoveryPlan::Abandon => {
+                match ctx.job_queue.abandon(analysis.id).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        info!(
+                            "Analysis {} is running elsewhere or settled, leaving it be",
+                            analysis.id
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("Failed to abandon analysis {}: {}", analysis.id, e);
+                        continue;
+                    }
+                }
+                warn!(
+                    "Abandoned analysis {} for user {} (channel: {}, type: {}, depth: {}, age: {}s)",
+                    analysis.id,
+                    analysis.telegram_user_id,
+                    analysis.channel_name,
+                    analysis.analysis_type,
+                    analysis.depth,
+                    analysis.age.as_secs()
+                );
+                if let Err(e) = ctx
+                    .bot
+                    .send_message(chat_id, lang.analysis_not_resumed(&analysis.channel_name))
+                    .await
+                {
+                    warn!(
+                        "Failed to apologize to user {} for analysis {}: {}",
+                        analysis.telegram_user_id, analysis.id, e
+                    );
+                }
+            }
+        }
+    }
+
+    info!("Finished recovering pending analyses");
+}
+
+async fn mark_failed(user_manager: &Arc<UserManager>, analysis_id: i32) {
+    if let Err(e) = user_manager.mark_analysis_failed(analysis_id).await {
+        error!(
+            "Failed to mark recovered analysis {} as failed: {}",
+            analysis_id, e
+        );
+    }
+}
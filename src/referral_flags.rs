@@ -0,0 +1,272 @@
+use deadpool_postgres::{GenericClient, Pool};
+use log::{info, warn};
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+use crate::localization::Lang;
+
+// referees who neither analyzed nor paid this many hours after joining count as idle
+pub const IDLE_AFTER_HOURS: i32 = 24;
+// signups through one link within this many minutes count as rapid
+pub const RAPID_SIGNUP_MINUTES: i32 = 10;
+// /referralflags lists at most this many pending flags
+pub const FLAG_LIST_LIMIT: i64 = 20;
+
+/// a heuristic a referrer tripped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagReason {
+    // many idle referees sharing a language, the closest we get to "same device"
+    IdleReferees,
+    RapidSignups,
+}
+
+impl FlagReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagReason::IdleReferees => "idle_referees",
+            FlagReason::RapidSignups => "rapid_signups",
+        }
+    }
+}
+
+/// an admin's decision on a pending flag; milestone rewards stay held unless every flag is cleared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagStatus {
+    Cleared,
+    Confirmed,
+}
+
+impl FlagStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagStatus::Cleared => "cleared",
+            FlagStatus::Confirmed => "confirmed",
+        }
+    }
+}
+
+/// what the heuristics look at in a referrer's referees
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefereeActivity {
+    // idle referees of the most common language among them
+    pub idle_same_language: i64,
+    pub idle_language: Option<String>,
+    // referees who joined within the last RAPID_SIGNUP_MINUTES
+    pub recent_signups: i64,
+}
+
+impl RefereeActivity {
+    /// the heuristics tripped by `threshold` look-alike referees, with a note for the
+    /// admin reviewing them; a threshold of 0 trips none
+    pub fn suspicious(&self, threshold: i32) -> Vec<(FlagReason, String)> {
+        let mut reasons = Vec::new();
+        if threshold == 0 {
+            return reasons;
+        }
+        let threshold = i64::from(threshold);
+        if self.idle_same_language >= threshold {
+            reasons.push((
+                FlagReason::IdleReferees,
+                format!(
+                    "{} idle referees with language {}",
+                    self.idle_same_language,
+                    self.idle_language.as_deref().unwrap_or("unknown")
+                ),
+            ));
+        }
+        if self.recent_signups >= threshold {
+            reasons.push((
+                FlagReason::RapidSignups,
+                format!(
+                    "{} signups within {} minutes",
+                    self.recent_signups, RAPID_SIGNUP_MINUTES
+                ),
+            ));
+        }
+        reasons
+    }
+}
+
+/// a flag as listed by /referralflags
+#[derive(Debug, Clone)]
+pub struct ReferralFlag {
+    pub id: i32,
+    pub referrer_telegram_id: i64,
+    pub reason: String,
+    pub details: String,
+    pub created_at: String, // formatted by postgres as YYYY-MM-DD HH24:MI (UTC)
+}
+
+/// the outcome of reviewing a flag
+#[derive(Debug, Clone)]
+pub struct ReviewedFlag {
+    pub referrer_user_id: i32,
+    pub referrer_telegram_id: i64,
+    pub referrer_lang: Lang,
+    // other flags still hold the referrer's milestone rewards
+    pub still_held: bool,
+}
+
+async fn referee_activity(
+    client: &impl GenericClient,
+    referrer_user_id: i32,
+) -> Result<RefereeActivity, tokio_postgres::Error> {
+    let recent_signups: i64 = client
+        .query_one(
+            "SELECT COUNT(*) FROM users
+             WHERE referred_by_user_id = $1 AND created_at > NOW() - make_interval(mins => $2)",
+            &[&referrer_user_id, &RAPID_SIGNUP_MINUTES],
+        )
+        .await?
+        .get(0);
+    let idle = client
+        .query_opt(
+            "SELECT u.language, COUNT(*) FROM users u
+             WHERE u.referred_by_user_id = $1
+               AND u.total_analyses_performed = 0
+               AND u.created_at < NOW() - make_interval(hours => $2)
+               AND NOT EXISTS (SELECT 1 FROM payments p WHERE p.user_id = u.id)
+             GROUP BY u.language
+             ORDER BY COUNT(*) DESC
+             LIMIT 1",
+            &[&referrer_user_id, &IDLE_AFTER_HOURS],
+        )
+        .await?;
+    let (idle_language, idle_same_language) = match idle {
+        Some(row) => (row.get(0), row.get(1)),
+        None => (None, 0),
+    };
+    Ok(RefereeActivity {
+        idle_same_language,
+        idle_language,
+        recent_signups,
+    })
+}
+
+/// runs the heuristics on a referrer and records new flags; each reason is flagged once
+/// per referrer, so a cleared flag isn't raised again
+pub async fn flag_suspicious(
+    client: &impl GenericClient,
+    referrer_user_id: i32,
+    threshold: i32,
+) -> Result<Vec<FlagReason>, tokio_postgres::Error> {
+    if threshold == 0 {
+        return Ok(Vec::new());
+    }
+    let activity = referee_activity(client, referrer_user_id).await?;
+    let mut flagged = Vec::new();
+    for (reason, details) in activity.suspicious(threshold) {
+        let inserted = client
+            .execute(
+                "INSERT INTO referral_flags (referrer_user_id, reason, details) VALUES ($1, $2, $3)
+                 ON CONFLICT (referrer_user_id, reason) DO NOTHING",
+                &[&referrer_user_id, &reason.as_str(), &details],
+            )
+            .await?;
+        if inserted > 0 {
+            warn!(
+                "Flagged referrer {} for {}: {}",
+                referrer_user_id,
+                reason.as_str(),
+                details
+            );
+            flagged.push(reason);
+        }
+    }
+    Ok(flagged)
+}
+
+/// whether the referrer's milestone rewards are held by a pending or confirmed flag
+pub async fn rewards_held(
+    client: &impl GenericClient,
+    referrer_user_id: i32,
+) -> Result<bool, tokio_postgres::Error> {
+    let row = client
+        .query_opt(
+            "SELECT 1 FROM referral_flags WHERE referrer_user_id = $1 AND status <> 'cleared' LIMIT 1",
+            &[&referrer_user_id],
+        )
+        .await?;
+    Ok(row.is_some())
+}
+
+/// suspicious referral patterns stored in referral_flags, reviewed with /referralflags
+pub struct ReferralFlagManager {
+    pool: Arc<Pool>,
+}
+
+impl ReferralFlagManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// flags waiting for review, oldest first
+    pub async fn pending(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<ReferralFlag>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT f.id, u.telegram_user_id, f.reason, f.details,
+                        TO_CHAR(f.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM referral_flags f
+                 JOIN users u ON u.id = f.referrer_user_id
+                 WHERE f.status = 'pending'
+                 ORDER BY f.created_at, f.id
+                 LIMIT $1",
+                &[&limit],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| ReferralFlag {
+                id: row.get(0),
+                referrer_telegram_id: row.get(1),
+                reason: row.get(2),
+                details: row.get(3),
+                created_at: row.get(4),
+            })
+            .collect())
+    }
+
+    /// records an admin's decision on a pending flag; None if there's no such pending flag
+    pub async fn review(
+        &self,
+        flag_id: i32,
+        status: FlagStatus,
+        reviewer_telegram_id: i64,
+    ) -> Result<Option<ReviewedFlag>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let Some(row) = client
+            .query_opt(
+                "UPDATE referral_flags f
+                 SET status = $2, reviewed_by = $3, reviewed_at = NOW()
+                 FROM users u
+                 WHERE f.id = $1 AND f.status = 'pending' AND u.id = f.referrer_user_id
+                 RETURNING f.referrer_user_id, u.telegram_user_id,
+                           COALESCE(u.language_override, u.language)",
+                &[&flag_id, &status.as_str(), &reviewer_telegram_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let referrer_user_id: i32 = row.get(0);
+        let still_held = rewards_held(&client, referrer_user_id).await?;
+        info!(
+            "Referral flag {} of referrer {} marked {} by admin {}",
+            flag_id,
+            referrer_user_id,
+            status.as_str(),
+            reviewer_telegram_id
+        );
+        Ok(Some(ReviewedFlag {
+            referrer_user_id,
+            referrer_telegram_id: row.get(1),
+            referrer_lang: Lang::from_code(row.get::<_, Option<&str>>(2)),
+            still_held,
+        }))
+    }
+}
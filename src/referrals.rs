@@ -0,0 +1,308 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::cache::read_pool;
+
+// the bot's public link, which /start payloads are appended to
+pub const BOT_LINK: &str = "https://t.me/ScratchAuthorEgoBot";
+// referrers shown on the /referrals leaderboard
+pub const LEADERBOARD_SIZE: i64 = 10;
+
+// referrals per referrer among users who joined this week, weeks start on monday like /top
+const WEEKLY_RANKING: &str = "
+    WITH weekly AS (
+        SELECT referred_by_user_id AS referrer_id, COUNT(*) AS referrals
+        FROM users
+        WHERE referred_by_user_id IS NOT NULL
+          AND created_at >= date_trunc('week', NOW())
+        GROUP BY referred_by_user_id
+    )
+    SELECT referrer_id, referrals, RANK() OVER (ORDER BY referrals DESC) AS rank
+    FROM weekly";
+
+/// names of the reward rules, as used by referral_config rows and /referralconfig
+pub const REFERRAL_RULE_NAMES: [&str; 6] = [
+    "milestone_every",
+    "milestone_credits",
+    "paid_referral_credits",
+    "celebrate_early",
+    "celebrate_every",
+    "suspicious_referees",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferralConfigError {
+    UnknownRule(String),
+    InvalidValue(String, i64), // rule name, rejected value
+}
+
+impl fmt::Display for ReferralConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferralConfigError::UnknownRule(name) => write!(f, "Unknown referral rule {}", name),
+            ReferralConfigError::InvalidValue(name, value) => {
+                write!(f, "Invalid value {} for referral rule {}", value, name)
+            }
+        }
+    }
+}
+
+impl Error for ReferralConfigError {}
+
+/// how referrers are rewarded; the defaults can be overridden by rows of the
+/// referral_config table, read on every referral so changes apply right away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReferralRules {
+    // a milestone credit every this many referrals, 0 turns them off
+    pub milestone_every: i32,
+    pub milestone_credits: i32,
+    // credits for each referral who pays, 0 turns them off
+    pub paid_referral_credits: i32,
+    // celebrate the first referral and the first milestone
+    pub celebrate_early: bool,
+    // celebrate every this many referrals, 0 turns it off
+    pub celebrate_every: i32,
+    // look-alike referees that flag a referrer in referral_flags, 0 turns flagging off
+    pub suspicious_referees: i32,
+}
+
+impl Default for ReferralRules {
+    fn default() -> Self {
+        Self {
+            milestone_every: 5,
+            milestone_credits: 1,
+            paid_referral_credits: 1,
+            celebrate_early: true,
+            celebrate_every: 10,
+            suspicious_referees: 5,
+        }
+    }
+}
+
+impl ReferralRules {
+    /// defaults, then database overrides
+    pub async fn load(pool: &Pool) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let client = pool.get().await?;
+        let rows = client
+            .query("SELECT name, value FROM referral_config ORDER BY name", &[])
+            .await?;
+        let mut rules = Self::default();
+        for row in &rows {
+            let (name, value): (&str, i64) = (row.get(0), row.get(1));
+            // a bad row is skipped so it can't block referrals
+            if let Err(e) = rules.set(name, value) {
+                warn!("Ignoring referral_config row: {}", e);
+            }
+        }
+        Ok(rules)
+    }
+
+    /// sets a rule by name; counts can't be negative, celebrate_early is 0 or 1
+    pub fn set(&mut self, name: &str, value: i64) -> Result<(), ReferralConfigError> {
+        let invalid = || ReferralConfigError::InvalidValue(name.to_string(), value);
+        let as_count = || {
+            i32::try_from(value)
+                .ok()
+                .filter(|value| *value >= 0)
+                .ok_or_else(invalid)
+        };
+        match name {
+            "milestone_every" => self.milestone_every = as_count()?,
+            "milestone_credits" => self.milestone_credits = as_count()?,
+            "paid_referral_credits" => self.paid_referral_credits = as_count()?,
+            "celebrate_early" => {
+                self.celebrate_early = match value {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(invalid()),
+                }
+            }
+            "celebrate_every" => self.celebrate_every = as_count()?,
+            "suspicious_referees" => self.suspicious_referees = as_count()?,
+            _ => return Err(ReferralConfigError::UnknownRule(name.to_string())),
+        }
+        Ok(())
+    }
+
+    /// every rule by name, in REFERRAL_RULE_NAMES order
+    pub fn values(&self) -> Vec<(&'static str, i64)> {
+        let values = [
+            i64::from(self.milestone_every),
+            i64::from(self.milestone_credits),
+            i64::from(self.paid_referral_credits),
+            i64::from(self.celebrate_early),
+            i64::from(self.celebrate_every),
+            i64::from(self.suspicious_referees),
+        ];
+        REFERRAL_RULE_NAMES.into_iter().zip(values).collect()
+    }
+
+    /// milestones reached with `referrals` referrals
+    pub fn milestones(&self, referrals: i32) -> i32 {
+        if self.milestone_every > 0 {
+            referrals / self.milestone_every
+        } else {
+            0
+        }
+    }
+
+    /// whether reaching `referrals` referrals is celebrated, by default at 1, 5, 10, 20, 30...
+    pub fn is_celebration(&self, referrals: i32) -> bool {
+        let early = self.celebrate_early && (referrals == 1 || referrals == self.milestone_every);
+        let periodic =
+            self.celebrate_every > 0 && referrals > 0 && referrals % self.celebrate_every == 0;
+        early || periodic
+    }
+
+    /// stores a rule, taking effect on the next referral
+    pub async fn save(
+        pool: &Pool,
+        name: &str,
+        value: i64,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // reject what load() would skip
+        Self::default().set(name, value)?;
+        let client = pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO referral_config (name, value) VALUES ($1, $2)
+                 ON CONFLICT (name) DO UPDATE SET value = $2, updated_at = NOW()",
+                &[&name, &value],
+            )
+            .await?;
+        info!("Saved referral rule {}: {}", name, value);
+        Ok(())
+    }
+
+    /// puts a rule back to its default; false if it wasn't overridden
+    pub async fn reset(pool: &Pool, name: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = pool.get().await?;
+        let removed = client
+            .execute("DELETE FROM referral_config WHERE name = $1", &[&name])
+            .await?;
+        Ok(removed > 0)
+    }
+}
+
+/// the link that credits `user_id` for everyone who starts the bot through it
+pub fn referral_link(user_id: i32) -> String {
+    format!("{BOT_LINK}?start={user_id}")
+}
+
+/// the first letter of a referrer's name, so the leaderboard doesn't expose anyone
+pub fn anonymize(first_name: Option<&str>, username: Option<&str>) -> String {
+    let initial = [first_name, username]
+        .into_iter()
+        .flatten()
+        .find_map(|name| name.trim().chars().next());
+    match initial {
+        Some(initial) => format!("{}***", initial.to_uppercase()),
+        None => "***".to_string(),
+    }
+}
+
+/// what /referrals shows a user about their own referrals
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferralStats {
+    pub referrals: i32,
+    pub paid_referrals: i32,
+    // users who joined through the link this week
+    pub this_week: i64,
+    // place on this week's leaderboard, None without referrals this week
+    pub rank: Option<i64>,
+}
+
+/// a referrer's place on this week's leaderboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub user_id: i32,
+    // anonymized name, see `anonymize`
+    pub name: String,
+    pub referrals: i64,
+}
+
+/// referral counts per user and the weekly leaderboard behind /referrals
+pub struct ReferralManager {
+    pool: Arc<Pool>,
+}
+
+impl ReferralManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// the reward rules currently in effect
+    pub async fn rules(&self) -> Result<ReferralRules, Box<dyn Error + Send + Sync>> {
+        ReferralRules::load(&self.pool).await
+    }
+
+    pub async fn set_rule(
+        &self,
+        name: &str,
+        value: i64,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        ReferralRules::save(&self.pool, name, value).await
+    }
+
+    pub async fn reset_rule(&self, name: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        ReferralRules::reset(&self.pool, name).await
+    }
+
+    pub async fn stats(&self, user_id: i32) -> Result<ReferralStats, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let row = client
+            .query_one(
+                &format!(
+                    "WITH ranking AS ({WEEKLY_RANKING})
+                     SELECT users.referrals_count, users.paid_referrals_count,
+                            COALESCE(ranking.referrals, 0), ranking.rank
+                     FROM users
+                     LEFT JOIN ranking ON ranking.referrer_id = users.id
+                     WHERE users.id = $1"
+                ),
+                &[&user_id],
+            )
+            .await?;
+        Ok(ReferralStats {
+            referrals: row.get(0),
+            paid_referrals: row.get(1),
+            this_week: row.get(2),
+            rank: row.get(3),
+        })
+    }
+
+    /// top referrers of this week, ties share a rank
+    pub async fn weekly_leaderboard(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardEntry>, Box<dyn Error + Send + Sync>> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "WITH ranking AS ({WEEKLY_RANKING})
+                     SELECT ranking.rank, users.id, users.first_name, users.username,
+                            ranking.referrals
+                     FROM ranking
+                     JOIN users ON users.id = ranking.referrer_id
+                     ORDER BY ranking.rank, users.id
+                     LIMIT $1"
+                ),
+                &[&limit],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| LeaderboardEntry {
+                rank: row.get(0),
+                user_id: row.get(1),
+                name: anonymize(row.get(2), row.get(3)),
+                referrals: row.get(4),
+            })
+            .collect())
+    }
+}
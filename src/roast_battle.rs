@@ -0,0 +1,202 @@
+use log::{error, info};
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+use crate::analysis::{roast_battle_corpus_name, AnalysisDepth, CorpusKind, MessageDict};
+use crate::bot::BotContext;
+use crate::cross_group::StoredGroupMessage;
+use crate::handlers::CallbackHandler;
+use crate::localization::Lang;
+use crate::utils::MessageFormatter;
+
+// each member needs this many kept messages in the group to give the roast some material
+pub const MIN_BATTLE_MESSAGES: usize = 15;
+
+// the latest messages of each member that go into the battle
+pub const MAX_BATTLE_MESSAGES_PER_MEMBER: i64 = 150;
+
+/// the two usernames of `/roastbattle @first @second`, without the @; None unless there are
+/// exactly two different valid usernames
+pub fn parse_battle_members(args: &str) -> Option<(String, String)> {
+    let mut names = args.split_whitespace().map(|name| {
+        let name = name.strip_prefix('@')?;
+        let valid = (5..=32).contains(&name.len())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        valid.then(|| name.to_string())
+    });
+    let (first, second) = (names.next()??, names.next()??);
+    if names.next().is_some() || first.eq_ignore_ascii_case(&second) {
+        return None;
+    }
+    Some((first, second))
+}
+
+/// the corpus of a roast battle: each member's messages in the order they were written,
+/// prefixed with their username in brackets, the first member's before the second's
+pub fn battle_messages(
+    first: &str,
+    first_messages: &[StoredGroupMessage],
+    second: &str,
+    second_messages: &[StoredGroupMessage],
+) -> Vec<MessageDict> {
+    [(first, first_messages), (second, second_messages)]
+        .into_iter()
+        .flat_map(|(name, messages)| {
+            messages.iter().map(move |msg| MessageDict {
+                id: None,
+                date: Some(msg.date.clone()),
+                message: Some(format!("[@{}] {}", name, msg.text)),
+                images: None,
+                thread_id: None,
+            })
+        })
+        .collect()
+}
+
+/// /roastbattle in groups: a comparative roast of two members who agreed to keep their
+/// group messages, billed to whoever asked for it
+pub async fn handle_roast_battle_command(
+    ctx: BotContext,
+    msg: &Message,
+    args: &str,
+    lang: Lang,
+) -> ResponseResult<()> {
+    if !(msg.chat.is_group() || msg.chat.is_supergroup()) {
+        ctx.bot
+            .send_message(msg.chat.id, lang.roast_battle_groups_only())
+            .await?;
+        return Ok(());
+    }
+    // anonymous admins post on behalf of the group, so there is no one to bill
+    let Some(requester) = msg.from.as_ref().filter(|_| msg.sender_chat.is_none()) else {
+        ctx.bot
+            .send_message(msg.chat.id, lang.roast_battle_anonymous())
+            .await?;
+        return Ok(());
+    };
+    let Some((first, second)) = parse_battle_members(args) else {
+        ctx.bot
+            .send_message(msg.chat.id, lang.roast_battle_usage())
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    };
+
+    let mut corpus = Vec::with_capacity(2);
+    for name in [&first, &second] {
+        let messages = match member_messages(&ctx, msg.chat.id, name).await {
+            Ok(Some(messages)) => messages,
+            Ok(None) => {
+                ctx.bot
+                    .send_message(
+                        msg.chat.id,
+                        lang.roast_battle_not_opted_in(&MessageFormatter::escape_html(name)),
+                    )
+                    .parse_mode(ParseMode::Html)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                error!(
+                    "Failed to load group messages of @{} in chat {}: {}",
+                    name, msg.chat.id, e
+                );
+                ctx.bot
+                    .send_message(msg.chat.id, lang.error_processing_request())
+                    .await?;
+                return Ok(());
+            }
+        };
+        if messages.len() < MIN_BATTLE_MESSAGES {
+            ctx.bot
+                .send_message(
+                    msg.chat.id,
+                    lang.roast_battle_too_few(
+                        &MessageFormatter::escape_html(name),
+                        messages.len(),
+                        MIN_BATTLE_MESSAGES,
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        }
+        corpus.push(messages);
+    }
+
+    let corpus_name = roast_battle_corpus_name(msg.chat.id.0, &first, &second);
+    let saved = ctx
+        .analysis_workers
+        .cache
+        .save_channel_messages(
+            &corpus_name,
+            &battle_messages(&first, &corpus[0], &second, &corpus[1]),
+            None,
+            0,
+            CorpusKind::RoastBattle,
+        )
+        .await;
+    if let Err(e) = saved {
+        error!(
+            "Failed to store the roast battle corpus {}: {}",
+            corpus_name, e
+        );
+        ctx.bot
+            .send_message(msg.chat.id, lang.error_processing_request())
+            .await?;
+        return Ok(());
+    }
+
+    let user = match ctx
+        .user_manager
+        .get_or_create_user(
+            requester.id.0 as i64,
+            requester.username.as_deref(),
+            Some(requester.first_name.as_str()),
+            requester.last_name.as_deref(),
+            None,
+            requester.language_code.as_deref(),
+        )
+        .await
+    {
+        Ok((user, _)) => user,
+        Err(e) => {
+            error!("Failed to get/create user: {}", e);
+            ctx.bot
+                .send_message(msg.chat.id, lang.error_processing_request())
+                .await?;
+            return Ok(());
+        }
+    };
+
+    info!(
+        "User {} started a roast battle of @{} and @{} in chat {}",
+        requester.id, first, second, msg.chat.id
+    );
+    CallbackHandler::start_analysis(
+        ctx,
+        msg.chat.id,
+        user,
+        &corpus_name,
+        "roast",
+        AnalysisDepth::default(),
+        lang,
+    )
+    .await
+}
+
+// the member's kept messages in the chat; None when they never agreed to keep them
+async fn member_messages(
+    ctx: &BotContext,
+    chat_id: ChatId,
+    username: &str,
+) -> Result<Option<Vec<StoredGroupMessage>>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(telegram_user_id) = ctx.cross_group.consenting_user(username).await? else {
+        return Ok(None);
+    };
+    let messages = ctx
+        .cross_group
+        .chat_messages(telegram_user_id, chat_id.0, MAX_BATTLE_MESSAGES_PER_MEMBER)
+        .await?;
+    Ok(Some(messages))
+}
@@ -0,0 +1,145 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use teloxide::prelude::*;
+use teloxide::types::{MessageOrigin, ParseMode, UserId};
+
+use crate::analysis::MessageDict;
+use crate::bot::BotContext;
+use crate::handlers::CallbackHandler;
+use crate::localization::Lang;
+use crate::user_sessions::UserSession;
+
+// fewer messages say too little about their author
+pub const MIN_SELF_MESSAGES: usize = 10;
+
+// forwards beyond this are ignored, the analysis reads the first ones
+pub const MAX_SELF_MESSAGES: usize = 500;
+
+/// what became of a forwarded message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collected {
+    Added,
+    // written by someone else
+    NotOwn,
+    // no text or caption to analyze
+    NoText,
+    Full,
+}
+
+/// messages a user forwards to the bot for an analysis of themselves
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SelfCollection {
+    pub messages: Vec<MessageDict>,
+    // forwards left out because someone else wrote them
+    pub skipped: usize,
+    // forwards left out because the collection was full
+    pub dropped: usize,
+}
+
+impl SelfCollection {
+    /// keeps the text of a forward written by `sender`; senders hiding their account
+    /// behind a name can't be told apart, so their forwards are trusted
+    pub fn add(&mut self, origin: &MessageOrigin, sender: UserId, text: Option<&str>) -> Collected {
+        let own = match origin {
+            MessageOrigin::User { sender_user, .. } => sender_user.id == sender,
+            MessageOrigin::HiddenUser { .. } => true,
+            MessageOrigin::Chat { .. } | MessageOrigin::Channel { .. } => false,
+        };
+        if !own {
+            self.skipped += 1;
+            return Collected::NotOwn;
+        }
+        let Some(text) = text.map(str::trim).filter(|text| !text.is_empty()) else {
+            return Collected::NoText;
+        };
+        if self.messages.len() >= MAX_SELF_MESSAGES {
+            self.dropped += 1;
+            return Collected::Full;
+        }
+        self.messages.push(MessageDict {
+            id: None,
+            date: Some(origin.date().format("%Y-%m-%d").to_string()),
+            message: Some(text.to_string()),
+            images: None,
+            thread_id: None,
+        });
+        Collected::Added
+    }
+}
+
+/// starts collecting the forwards of the user; anything collected before is dropped
+pub async fn handle_self_command(ctx: BotContext, msg: &Message, lang: Lang) -> ResponseResult<()> {
+    if !msg.chat.is_private() {
+        ctx.bot
+            .send_message(msg.chat.id, lang.self_analysis_private_only())
+            .await?;
+        return Ok(());
+    }
+    let telegram_user_id = msg.from.as_ref().map(|user| user.id.0 as i64).unwrap_or(0);
+    ctx.user_sessions
+        .insert(
+            telegram_user_id,
+            UserSession {
+                self_collection: Some(SelfCollection::default()),
+                ..Default::default()
+            },
+        )
+        .await;
+
+    info!(
+        "User {} started collecting a self-analysis",
+        telegram_user_id
+    );
+    ctx.bot
+        .send_message(
+            msg.chat.id,
+            lang.self_analysis_start(MIN_SELF_MESSAGES, MAX_SELF_MESSAGES),
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(CallbackHandler::create_self_done_keyboard(lang))
+        .await?;
+    Ok(())
+}
+
+/// adds a forwarded message to the sender's collection; false when they aren't collecting,
+/// so the message gets its usual handling
+pub async fn collect_forward(
+    ctx: &BotContext,
+    msg: &Message,
+    origin: &MessageOrigin,
+    lang: Lang,
+) -> ResponseResult<bool> {
+    let Some(sender) = msg.from.as_ref().map(|user| user.id) else {
+        return Ok(false);
+    };
+    let text = msg.text().or(msg.caption());
+    let Some((collected, count, dropped)) = ctx
+        .user_sessions
+        .update_existing(sender.0 as i64, |session| {
+            let collection = session.self_collection.as_mut()?;
+            let collected = collection.add(origin, sender, text);
+            Some((collected, collection.messages.len(), collection.dropped))
+        })
+        .await
+        .flatten()
+    else {
+        return Ok(false);
+    };
+
+    // a batch of forwards arrives as many messages, only milestones are acknowledged
+    let reply = match collected {
+        Collected::Added if count == 1 || count == MIN_SELF_MESSAGES => {
+            Some(lang.self_analysis_collecting(count, MIN_SELF_MESSAGES))
+        }
+        Collected::Full if dropped == 1 => Some(lang.self_analysis_full(MAX_SELF_MESSAGES)),
+        _ => None,
+    };
+    if let Some(reply) = reply {
+        ctx.bot
+            .send_message(msg.chat.id, reply)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(CallbackHandler::create_self_done_keyboard(lang))
+            .await?;
+    }
+    Ok(true)
+}
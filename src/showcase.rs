@@ -0,0 +1,241 @@
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use regex::Regex;
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode};
+
+use crate::cache::{AnalysisResult, CacheManager};
+use crate::localization::Lang;
+use crate::utils::{MessageFormatter, ResultPresenter};
+
+// one showcase post per hour unless SHOWCASE_POST_INTERVAL_MINUTES says otherwise
+const DEFAULT_POST_INTERVAL_MINUTES: u64 = 60;
+
+// stands in for the channel's name in anonymous posts
+const ANONYMOUS_MENTION: &str = "@•••";
+
+/// showcase channel settings; publishing is disabled unless SHOWCASE_CHANNEL_ID is set
+#[derive(Debug, Clone)]
+pub struct ShowcaseConfig {
+    pub channel_id: ChatId,
+    pub post_interval: Duration,
+}
+
+impl ShowcaseConfig {
+    pub fn from_env() -> Option<Self> {
+        let channel_id = env::var("SHOWCASE_CHANNEL_ID").ok()?.parse().ok()?;
+        let minutes = env::var("SHOWCASE_POST_INTERVAL_MINUTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_POST_INTERVAL_MINUTES);
+        Some(Self {
+            channel_id: ChatId(channel_id),
+            post_interval: Duration::from_secs(minutes * 60),
+        })
+    }
+}
+
+/// a completed analysis its requester agreed to publish
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShowcaseEntry {
+    pub analysis_id: i32,
+    pub channel_name: String,
+    pub analysis_type: String,
+    pub cache_key: String,
+    // the channel name is left out of the post
+    pub anonymous: bool,
+    pub language_code: Option<String>,
+}
+
+impl ShowcaseEntry {
+    /// the post's messages in the analysis' language, None when the result lacks the
+    /// content of its type
+    pub fn render(&self, result: &AnalysisResult) -> Option<Vec<String>> {
+        let lang = Lang::from_code(self.language_code.as_deref());
+        if !self.anonymous {
+            let header =
+                lang.showcase_post_header(Some(&MessageFormatter::escape_html(&self.channel_name)));
+            return ResultPresenter::render_with_header(result, &self.analysis_type, header, lang);
+        }
+
+        // the analysis itself may name the channel too
+        let mut result = result.clone();
+        for content in [
+            &mut result.professional,
+            &mut result.personal,
+            &mut result.roast,
+            &mut result.trends,
+        ] {
+            if let Some(text) = content.as_mut() {
+                *text = anonymize(text, &self.channel_name);
+            }
+        }
+        let header = lang.showcase_post_header(None);
+        ResultPresenter::render_with_header(&result, &self.analysis_type, header, lang)
+    }
+}
+
+/// masks every mention of the channel's username in `text`, with or without the '@'
+pub fn anonymize(text: &str, channel_name: &str) -> String {
+    let username = channel_name.trim_start_matches('@');
+    if username.is_empty() {
+        return text.to_string();
+    }
+    let mention = Regex::new(&format!(r"(?i)@?\b{}\b", regex::escape(username)))
+        .expect("an escaped username is a valid pattern");
+    mention.replace_all(text, ANONYMOUS_MENTION).into_owned()
+}
+
+/// consent and posting state of showcase analyses, kept in user_analyses
+pub struct ShowcaseManager {
+    pool: Arc<Pool>,
+}
+
+impl ShowcaseManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    /// records that the user agreed to publish their analysis; false unless it is theirs,
+    /// completed in full and not consented to before
+    pub async fn consent(
+        &self,
+        analysis_id: i32,
+        telegram_user_id: i64,
+        anonymous: bool,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE user_analyses ua
+                 SET showcase_consent_at = NOW(), showcase_anonymous = $3
+                 FROM users u
+                 WHERE ua.id = $1 AND ua.user_id = u.id AND u.telegram_user_id = $2
+                   AND ua.status = 'completed' AND NOT ua.partial AND ua.cache_key IS NOT NULL
+                   AND ua.showcase_consent_at IS NULL",
+                &[&analysis_id, &telegram_user_id, &anonymous],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    /// the analysis consented to first among the ones not posted yet
+    pub async fn next_unposted(
+        &self,
+    ) -> Result<Option<ShowcaseEntry>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT id, channel_name, analysis_type, cache_key, showcase_anonymous, language
+                 FROM user_analyses
+                 WHERE showcase_consent_at IS NOT NULL AND showcase_posted_at IS NULL
+                 ORDER BY showcase_consent_at, id
+                 LIMIT 1",
+                &[],
+            )
+            .await?;
+        Ok(row.map(|row| ShowcaseEntry {
+            analysis_id: row.get(0),
+            channel_name: row.get(1),
+            analysis_type: row.get(2),
+            cache_key: row.get(3),
+            anonymous: row.get(4),
+            language_code: row.get(5),
+        }))
+    }
+
+    /// takes the analysis off the queue, whether it was posted or can't ever be
+    pub async fn mark_posted(&self, analysis_id: i32) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET showcase_posted_at = NOW() WHERE id = $1",
+                &[&analysis_id],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// posts one consented analysis per interval to the showcase channel, oldest consent first
+pub async fn run_showcase_publisher(
+    bot: Arc<Bot>,
+    showcase: Arc<ShowcaseManager>,
+    cache: CacheManager,
+    config: ShowcaseConfig,
+) {
+    info!(
+        "Starting showcase publisher for channel {}, one post every {} minutes",
+        config.channel_id,
+        config.post_interval.as_secs() / 60
+    );
+    let mut interval = tokio::time::interval(config.post_interval);
+
+    loop {
+        interval.tick().await;
+
+        let entry = match showcase.next_unposted().await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("Failed to query showcase queue: {}", e);
+                continue;
+            }
+        };
+
+        // a result that is gone or empty can never be posted, so it leaves the queue
+        let Some(parts) = cache
+            .load_llm_result(&entry.cache_key)
+            .await
+            .and_then(|result| entry.render(&result))
+        else {
+            warn!(
+                "No result to showcase for analysis {}, skipping it",
+                entry.analysis_id
+            );
+            if let Err(e) = showcase.mark_posted(entry.analysis_id).await {
+                error!(
+                    "Failed to skip showcase analysis {}: {}",
+                    entry.analysis_id, e
+                );
+            }
+            continue;
+        };
+
+        // a failed send is retried on the next tick
+        let mut sent = true;
+        for part in parts {
+            if let Err(e) = bot
+                .send_message(config.channel_id, part)
+                .parse_mode(ParseMode::Html)
+                .await
+            {
+                error!(
+                    "Failed to post analysis {} to the showcase channel: {}",
+                    entry.analysis_id, e
+                );
+                sent = false;
+                break;
+            }
+        }
+        if !sent {
+            continue;
+        }
+
+        info!(
+            "Posted analysis {} to the showcase channel",
+            entry.analysis_id
+        );
+        if let Err(e) = showcase.mark_posted(entry.analysis_id).await {
+            error!(
+                "Failed to mark showcase analysis {} as posted: {}",
+                entry.analysis_id, e
+            );
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use serde_json::json;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode};
+
+use crate::error::AppError;
+use crate::invoice_payload::InvoicePayload;
+use crate::localization::Lang;
+use crate::user_manager::{CreditTransactionKind, UserManager};
+
+/// telegram renews star subscriptions every 30 days, the only period it supports
+pub const SUBSCRIPTION_PERIOD_DAYS: i32 = 30;
+
+// days a lapsed subscription waits for a late renewal before it expires
+pub const GRACE_PERIOD_DAYS: i32 = 3;
+
+// how often the scheduler tops up renewals and lapses unpaid subscriptions
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionStatus {
+    Active,
+    // the period ended without a renewal, which may still come in
+    Grace,
+    Expired,
+}
+
+impl SubscriptionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionStatus::Active => "active",
+            SubscriptionStatus::Grace => "grace",
+            SubscriptionStatus::Expired => "expired",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "active" => Some(SubscriptionStatus::Active),
+            "grace" => Some(SubscriptionStatus::Grace),
+            "expired" => Some(SubscriptionStatus::Expired),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscription {
+    pub user_id: i32,
+    pub status: SubscriptionStatus,
+    pub credits_per_period: i32,
+    pub stars: i32,
+    // UTC, as YYYY-MM-DD HH24:MI
+    pub paid_until: String,
+    // renewals paid and renewals whose credits were added; the scheduler catches up
+    // on any difference
+    pub periods_paid: i32,
+    pub periods_credited: i32,
+}
+
+/// a subscriber whose subscription just moved to another status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lapsed {
+    pub telegram_user_id: i64,
+    pub language: Option<String>,
+    pub status: SubscriptionStatus,
+}
+
+/// monthly star subscriptions, one per user, kept in the subscriptions table
+pub struct SubscriptionManager {
+    pool: Arc<Pool>,
+}
+
+impl SubscriptionManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    fn subscription_from_row(row: &tokio_postgres::Row) -> Subscription {
+        let status: String = row.get(1);
+        Subscription {
+            user_id: row.get(0),
+            status: SubscriptionStatus::from_code(&status).unwrap_or(SubscriptionStatus::Expired),
+            credits_per_period: row.get(2),
+            stars: row.get(3),
+            paid_until: row.get(4),
+            periods_paid: row.get(5),
+            periods_credited: row.get(6),
+        }
+    }
+
+    pub async fn get(
+        &self,
+        user_id: i32,
+    ) -> Result<Option<Subscription>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT user_id, status, credits_per_period, stars,
+                        TO_CHAR(paid_until AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI'),
+                        periods_paid, periods_credited
+                 FROM subscriptions WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await?;
+        Ok(row.as_ref().map(Self::subscription_from_row))
+    }
+
+    /// records a first payment or a renewal and extends the paid period; a renewal during
+    /// the grace period continues from the old end, one after expiry starts over. None when
+    /// the charge was already recorded
+    pub async fn record_payment(
+        &self,
+        user_id: i32,
+        telegram_payment_charge_id: &str,
+        stars: i32,
+        credits_per_period: i32,
+    ) -> Result<Option<Subscription>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "INSERT INTO subscriptions
+                     (user_id, status, credits_per_period, stars, last_charge_id, paid_until, periods_paid)
+                 VALUES ($1, 'active', $2, $3, $4, NOW() + make_interval(days => $5), 1)
+                 ON CONFLICT (user_id) DO UPDATE SET
+                     status = 'active',
+                     credits_per_period = EXCLUDED.credits_per_period,
+                     stars = EXCLUDED.stars,
+                     last_charge_id = EXCLUDED.last_charge_id,
+                     paid_until = CASE WHEN subscriptions.status = 'expired' THEN NOW()
+                                       ELSE subscriptions.paid_until END
+                                  + make_interval(days => $5),
+                     periods_paid = subscriptions.periods_paid + 1,
+                     updated_at = NOW()
+                 WHERE subscriptions.last_charge_id IS DISTINCT FROM EXCLUDED.last_charge_id
+                 RETURNING user_id, status, credits_per_period, stars,
+                           TO_CHAR(paid_until AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI'),
+                           periods_paid, periods_credited",
+                &[
+                    &user_id,
+                    &credits_per_period,
+                    &stars,
+                    &telegram_payment_charge_id,
+                    &SUBSCRIPTION_PERIOD_DAYS,
+                ],
+            )
+            .await?;
+        Ok(row.as_ref().map(Self::subscription_from_row))
+    }
+
+    /// adds the credits of every paid period not credited yet; returns the credits added
+    /// and the new balance, None when nothing was due
+    pub async fn top_up(
+        &self,
+        user_id: i32,
+    ) -> Result<Option<(i32, i32)>, Box<dyn Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        let Some(row) = transaction
+            .query_opt(
+                "SELECT periods_paid - periods_credited, credits_per_period, last_charge_id
+                 FROM subscriptions WHERE user_id = $1
+                 FOR UPDATE",
+                &[&user_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let due: i32 = row.get(0);
+        if due <= 0 {
+            return Ok(None);
+        }
+        let credits = due * row.get::<_, i32>(1);
+        let charge_id: Option<String> = row.get(2);
+
+        let new_balance: i32 = transaction
+            .query_one(
+                "UPDATE users SET analysis_credits = analysis_credits + $2, updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING analysis_credits",
+                &[&user_id, &credits],
+            )
+            .await?
+            .get(0);
+        UserManager::record_credit_transaction(
+            &transaction,
+            user_id,
+            credits,
+            new_balance,
+            CreditTransactionKind::Subscription,
+            charge_id.as_deref(),
+        )
+        .await?;
+        transaction
+            .execute(
+                "UPDATE subscriptions SET periods_credited = periods_paid, updated_at = NOW()
+                 WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await?;
+        transaction.commit().await?;
+
+        info!(
+            "Topped up {} subscription credits for user {}, new balance: {}",
+            credits, user_id, new_balance
+        );
+        Ok(Some((credits, new_balance)))
+    }
+
+    /// users with paid periods whose credits weren't added yet
+    pub async fn due_top_ups(&self) -> Result<Vec<i32>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT user_id FROM subscriptions WHERE periods_credited < periods_paid",
+                &[],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    /// moves subscriptions whose period ended into the grace period, and ones whose grace
+    /// period ended to expired; returns their subscribers to notify
+    pub async fn lapse(&self) -> Result<Vec<Lapsed>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "UPDATE subscriptions s
+                 SET status = CASE WHEN s.status = 'active' THEN 'grace' ELSE 'expired' END,
+                     updated_at = NOW()
+                 FROM users u
+                 WHERE u.id = s.user_id
+                   AND ((s.status = 'active' AND s.paid_until < NOW())
+                     OR (s.status = 'grace'
+                         AND s.paid_until + make_interval(days => $1) < NOW()))
+                 RETURNING u.telegram_user_id, COALESCE(u.language_override, u.language), s.status",
+                &[&GRACE_PERIOD_DAYS],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let status: String = row.get(2);
+                Lapsed {
+                    telegram_user_id: row.get(0),
+                    language: row.get(1),
+                    status: SubscriptionStatus::from_code(&status)
+                        .unwrap_or(SubscriptionStatus::Expired),
+                }
+            })
+            .collect())
+    }
+}
+
+/// link to a star invoice that renews every SUBSCRIPTION_PERIOD_DAYS; teloxide has no
+/// subscription_period yet, so createInvoiceLink is called directly
+pub async fn create_subscription_link(
+    bot: &Bot,
+    title: &str,
+    description: &str,
+    label: &str,
+    credits: i32,
+    stars: u32,
+) -> Result<String, AppError> {
+    let url = format!(
+        "{}bot{}/createInvoiceLink",
+        bot.api_url().as_str(),
+        bot.token()
+    );
+    let response: serde_json::Value = reqwest::Client::new()
+        .post(url)
+        .json(&json!({
+            "title": title,
+            "description": description,
+            "payload": InvoicePayload::Subscription(credits).to_string(),
+            "currency": "XTR",
+            "prices": [{"label": label, "amount": stars}],
+            "subscription_period": SUBSCRIPTION_PERIOD_DAYS * 24 * 60 * 60,
+        }))
+        .send()
+        .await
+        .map_err(AppError::telegram)?
+        .json()
+        .await
+        .map_err(AppError::telegram)?;
+    match response["result"].as_str() {
+        Some(link) => Ok(link.to_string()),
+        None => Err(AppError::Payment(format!(
+            "Telegram refused the subscription invoice: {}",
+            response["description"].as_str().unwrap_or("no description")
+        ))),
+    }
+}
+
+/// credits paid renewals the payment handler couldn't, and moves unpaid subscriptions
+/// through the grace period to expiry, telling their subscribers
+pub async fn run_subscription_scheduler(bot: Arc<Bot>, subscriptions: Arc<SubscriptionManager>) {
+    info!("Starting subscription scheduler");
+    let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match subscriptions.due_top_ups().await {
+            Ok(user_ids) => {
+                for user_id in user_ids {
+                    if let Err(e) = subscriptions.top_up(user_id).await {
+                        error!(
+                            "Failed to top up subscription credits for user {}: {}",
+                            user_id, e
+                        );
+                    }
+                }
+            }
+            Err(e) => error!("Failed to query due subscription top-ups: {}", e),
+        }
+
+        let lapsed = match subscriptions.lapse().await {
+            Ok(lapsed) => lapsed,
+            Err(e) => {
+                error!("Failed to lapse unpaid subscriptions: {}", e);
+                continue;
+            }
+        };
+        for subscriber in lapsed {
+            let lang = Lang::from_code(subscriber.language.as_deref());
+            let text = match subscriber.status {
+                SubscriptionStatus::Grace => lang.subscription_grace(GRACE_PERIOD_DAYS),
+                _ => lang.subscription_expired().to_string(),
+            };
+            info!(
+                "Subscription of user {} is now {}",
+                subscriber.telegram_user_id,
+                subscriber.status.as_str()
+            );
+            if let Err(e) = bot
+                .send_message(ChatId(subscriber.telegram_user_id), text)
+                .parse_mode(ParseMode::Html)
+                .await
+            {
+                warn!(
+                    "Failed to notify user {} about their subscription: {}",
+                    subscriber.telegram_user_id, e
+                );
+            }
+        }
+    }
+}
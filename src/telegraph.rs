@@ -0,0 +1,191 @@
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{parse_document, Arena};
+use log::info;
+use serde_json::{json, Value};
+use std::env;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+use crate::error::AppError;
+use crate::utils::MessageFormatter;
+
+const DEFAULT_API_URL: &str = "https://api.telegra.ph";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+// telegra.ph cuts longer titles
+const MAX_TITLE_CHARS: usize = 256;
+
+const AUTHOR_NAME: &str = "@ScratchAuthorEgoBot";
+const AUTHOR_URL: &str = "https://t.me/ScratchAuthorEgoBot";
+
+/// results that would take at least this many messages go out as a page for users who
+/// turned pages on
+pub const WEB_PAGE_MIN_PARTS: usize = 3;
+
+/// publishes long analyses as telegra.ph pages; TELEGRAPH_API_URL points it at a
+/// self-hosted instance and TELEGRAPH_ACCESS_TOKEN at an existing account, otherwise an
+/// account is created on the first publish
+pub struct TelegraphClient {
+    http: reqwest::Client,
+    api_url: String,
+    access_token: OnceCell<String>,
+}
+
+impl TelegraphClient {
+    pub fn new(api_url: &str, access_token: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            access_token: access_token.map_or_else(OnceCell::new, OnceCell::from),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let setting = |name| {
+            env::var(name)
+                .ok()
+                .filter(|value: &String| !value.is_empty())
+        };
+        Self::new(
+            &setting("TELEGRAPH_API_URL").unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+            setting("TELEGRAPH_ACCESS_TOKEN"),
+        )
+    }
+
+    /// creates a page and returns its url
+    pub async fn publish(&self, title: &str, content: &[Value]) -> Result<String, AppError> {
+        let access_token = self
+            .access_token
+            .get_or_try_init(|| self.create_account())
+            .await?;
+        let page = self
+            .call(
+                "createPage",
+                json!({
+                    "access_token": access_token,
+                    "title": title.chars().take(MAX_TITLE_CHARS).collect::<String>(),
+                    "author_name": AUTHOR_NAME,
+                    "author_url": AUTHOR_URL,
+                    "content": content,
+                }),
+            )
+            .await?;
+        match page["url"].as_str() {
+            Some(url) => Ok(url.to_string()),
+            None => Err(AppError::telegram(
+                "telegra.ph returned a page without a url",
+            )),
+        }
+    }
+
+    async fn create_account(&self) -> Result<String, AppError> {
+        let account = self
+            .call(
+                "createAccount",
+                json!({
+                    "short_name": "ChannelAnalyzer",
+                    "author_name": AUTHOR_NAME,
+                    "author_url": AUTHOR_URL,
+                }),
+            )
+            .await?;
+        info!("Created a telegra.ph account for publishing results");
+        match account["access_token"].as_str() {
+            Some(access_token) => Ok(access_token.to_string()),
+            None => Err(AppError::telegram(
+                "telegra.ph returned an account without an access token",
+            )),
+        }
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, AppError> {
+        let response: Value = self
+            .http
+            .post(format!("{}/{}", self.api_url, method))
+            .timeout(REQUEST_TIMEOUT)
+            .json(&params)
+            .send()
+            .await
+            .map_err(AppError::telegram)?
+            .json()
+            .await
+            .map_err(AppError::telegram)?;
+        if response["ok"].as_bool() == Some(true) {
+            Ok(response["result"].clone())
+        } else {
+            Err(AppError::telegram(format!(
+                "telegra.ph refused {}: {}",
+                method,
+                response["error"].as_str().unwrap_or("no error given")
+            )))
+        }
+    }
+}
+
+/// converts the markdown LLMs write into telegra.ph content nodes
+pub fn markdown_to_nodes(markdown: &str) -> Vec<Value> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &MessageFormatter::markdown_options());
+    render_nodes(root)
+}
+
+/// a paragraph of plain text for each line of an html message
+pub fn html_to_paragraphs(html: &str) -> Vec<Value> {
+    MessageFormatter::strip_html(html)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| element("p", vec![Value::String(line.to_string())]))
+        .collect()
+}
+
+fn element(tag: &str, children: Vec<Value>) -> Value {
+    json!({"tag": tag, "children": children})
+}
+
+// telegra.ph pages know only a few tags: headings become h3 and h4, raw html is dropped
+fn render_nodes<'a>(node: &'a AstNode<'a>) -> Vec<Value> {
+    let children = || node.children().flat_map(render_nodes).collect::<Vec<_>>();
+
+    let value = node.data.borrow().value.clone();
+    match value {
+        NodeValue::Text(text) => vec![Value::String(text)],
+        NodeValue::SoftBreak | NodeValue::LineBreak => vec![json!({"tag": "br"})],
+        NodeValue::Paragraph => {
+            // items of tight lists hold their text directly, as in html
+            let in_tight_list = node.parent().and_then(|item| item.parent()).is_some_and(
+                |list| matches!(&list.data.borrow().value, NodeValue::List(list) if list.tight),
+            );
+            if in_tight_list {
+                children()
+            } else {
+                vec![element("p", children())]
+            }
+        }
+        NodeValue::Heading(heading) => {
+            let tag = if heading.level <= 2 { "h3" } else { "h4" };
+            vec![element(tag, children())]
+        }
+        NodeValue::Strong => vec![element("strong", children())],
+        NodeValue::Emph => vec![element("em", children())],
+        NodeValue::Strikethrough => vec![element("s", children())],
+        NodeValue::Code(code) => vec![element("code", vec![Value::String(code.literal)])],
+        NodeValue::CodeBlock(block) => vec![element("pre", vec![Value::String(block.literal)])],
+        NodeValue::Link(link) | NodeValue::Image(link) => {
+            vec![json!({"tag": "a", "attrs": {"href": link.url}, "children": children()})]
+        }
+        NodeValue::List(list) => {
+            let tag = match list.list_type {
+                ListType::Bullet => "ul",
+                ListType::Ordered => "ol",
+            };
+            vec![element(tag, children())]
+        }
+        NodeValue::Item(_) => vec![element("li", children())],
+        NodeValue::BlockQuote => vec![element("blockquote", children())],
+        NodeValue::ThematicBreak => vec![json!({"tag": "hr"})],
+        NodeValue::HtmlBlock(_) | NodeValue::HtmlInline(_) => vec![],
+        _ => children(),
+    }
+}
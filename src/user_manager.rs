@@ -1,14 +1,29 @@
-use deadpool_postgres::Pool;
+use deadpool_postgres::{GenericClient, Pool};
 use log::{error, info};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
+
+use crate::analysis::ForumTopic;
+use crate::backend_config::BackendType;
+use crate::cache::read_pool;
+use crate::error::AppError;
+use crate::free_trials::{self, TrialOutcome};
+use crate::llm::ModelTier;
+use crate::prompts::analysis::OutputLanguage;
+use crate::referral_flags;
+use crate::referrals::ReferralRules;
 
 #[derive(Debug)]
 pub enum UserManagerError {
     UserNotFound(i32),        // user_id
     InsufficientCredits(i32), // user_id
+    TooManyRunning(i32),      // user_id
+    NotPending(i32),          // analysis_id
     DatabaseError(Box<dyn Error + Send + Sync>),
 }
 
@@ -21,6 +36,12 @@ impl fmt::Display for UserManagerError {
             UserManagerError::InsufficientCredits(user_id) => {
                 write!(f, "User with id {} has insufficient credits", user_id)
             }
+            UserManagerError::TooManyRunning(user_id) => {
+                write!(f, "User with id {} has too many running analyses", user_id)
+            }
+            UserManagerError::NotPending(analysis_id) => {
+                write!(f, "Analysis {} is no longer pending", analysis_id)
+            }
             UserManagerError::DatabaseError(e) => write!(f, "Database error: {}", e),
         }
     }
@@ -40,6 +61,20 @@ impl From<deadpool_postgres::PoolError> for UserManagerError {
     }
 }
 
+impl From<UserManagerError> for AppError {
+    fn from(err: UserManagerError) -> Self {
+        match err {
+            UserManagerError::UserNotFound(_)
+            | UserManagerError::TooManyRunning(_)
+            | UserManagerError::NotPending(_) => AppError::Validation(err.to_string()),
+            UserManagerError::InsufficientCredits(user_id) => {
+                AppError::InsufficientCredits(user_id)
+            }
+            UserManagerError::DatabaseError(e) => AppError::Db(e),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
     pub id: i32,
@@ -55,6 +90,114 @@ pub struct User {
     pub language: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct Payment {
+    pub id: i32,
+    pub user_id: i32,
+    pub telegram_user_id: i64,
+    pub telegram_payment_charge_id: String,
+    pub stars: i32,
+    pub credits: i32,
+    pub refunded: bool,
+    pub language: Option<String>,
+}
+
+/// reason of a credit balance change, stored in the credit_transactions ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreditTransactionKind {
+    SignupBonus,
+    Purchase,
+    Analysis,
+    ReferralReward,
+    Refund,
+    // monthly top-up of a star subscription
+    Subscription,
+}
+
+impl CreditTransactionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreditTransactionKind::SignupBonus => "signup_bonus",
+            CreditTransactionKind::Purchase => "purchase",
+            CreditTransactionKind::Analysis => "analysis",
+            CreditTransactionKind::ReferralReward => "referral_reward",
+            CreditTransactionKind::Refund => "refund",
+            CreditTransactionKind::Subscription => "subscription",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "signup_bonus" => Some(CreditTransactionKind::SignupBonus),
+            "purchase" => Some(CreditTransactionKind::Purchase),
+            "analysis" => Some(CreditTransactionKind::Analysis),
+            "referral_reward" => Some(CreditTransactionKind::ReferralReward),
+            "refund" => Some(CreditTransactionKind::Refund),
+            "subscription" => Some(CreditTransactionKind::Subscription),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreditTransaction {
+    pub amount: i32,
+    pub balance_after: i32,
+    pub kind: CreditTransactionKind,
+    pub created_at: String, // formatted by postgres as YYYY-MM-DD HH24:MI (UTC)
+}
+
+/// front end an analysis was requested through; each one recovers its own pending analyses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisSource {
+    Bot,
+    Api,
+}
+
+impl AnalysisSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalysisSource::Bot => "bot",
+            AnalysisSource::Api => "api",
+        }
+    }
+}
+
+/// an analysis as reported by the rest api
+#[derive(Debug, Clone)]
+pub struct AnalysisRecord {
+    pub id: i32,
+    pub channel_name: String,
+    pub analysis_type: Option<String>, // missing on analyses from before types existed
+    pub depth: String,
+    pub status: String,
+    pub credits_used: i32,
+    pub cache_key: Option<String>,
+    pub created_at: String, // formatted by postgres as RFC 3339 (UTC)
+    // the model that answered, missing until it completes
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShareableAnalysis {
+    pub user_id: i32,
+    pub channel_name: String,
+    pub analysis_type: String,
+    pub cache_key: String,
+}
+
+/// a partial analysis whose credits were given back so it can be regenerated for free
+#[derive(Debug, Clone)]
+pub struct PartialRegeneration {
+    pub channel_name: String,
+    pub analysis_type: String,
+    pub depth: String,
+    pub focus: Option<String>,
+    pub topic: Option<ForumTopic>,
+    pub credits_refunded: i32,
+    pub balance: i32,
+}
+
 #[derive(Debug, Clone)]
 pub struct PendingAnalysis {
     pub id: i32,
@@ -62,7 +205,25 @@ pub struct PendingAnalysis {
     pub telegram_user_id: i64, // kept for bot notification purposes
     pub channel_name: String,
     pub analysis_type: String,
+    pub depth: String,
     pub language: Option<String>,
+    pub focus: Option<String>,
+    pub topic: Option<ForumTopic>,
+    // time since the analysis was requested
+    pub age: Duration,
+    // the analysis this one is a second opinion on
+    pub second_opinion_of: Option<i32>,
+}
+
+/// the topic stored with an analysis, if it was scoped to one
+pub(crate) fn stored_topic(
+    thread_id: Option<i32>,
+    topic_name: Option<String>,
+) -> Option<ForumTopic> {
+    thread_id.map(|thread_id| ForumTopic {
+        thread_id,
+        name: topic_name.unwrap_or_default(),
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -85,22 +246,73 @@ impl UserManager {
         Self { pool }
     }
 
-    /// calculates how many milestone rewards should be earned for given referral count
-    /// rewards are given every 5 referrals: 5, 10, 15, 20, 25, etc.
-    fn calculate_milestone_rewards(referral_count: i32) -> i32 {
-        referral_count / 5
+    /// credits a referrer for one reward and records it in referral_rewards; a reward worth
+    /// no credits under the current rules is recorded too, so it isn't paid out later. The
+    /// balance, the ledger and the reward change together
+    async fn award_referral_reward(
+        client: &mut impl GenericClient,
+        user_id: i32,
+        reward_type: &str,
+        credits: i32,
+    ) -> Result<(), tokio_postgres::Error> {
+        let transaction = client.transaction().await?;
+        if credits > 0 {
+            let balance: i32 = transaction
+                .query_one(
+                    "UPDATE users SET analysis_credits = analysis_credits + $2 WHERE id = $1 RETURNING analysis_credits",
+                    &[&user_id, &credits],
+                )
+                .await?
+                .get(0);
+            let reference = match reward_type {
+                "unpaid_milestone" => "milestone",
+                _ => "paid_user",
+            };
+            Self::record_credit_transaction(
+                &transaction,
+                user_id,
+                credits,
+                balance,
+                CreditTransactionKind::ReferralReward,
+                Some(reference),
+            )
+            .await?;
+        }
+        transaction
+            .execute(
+                "INSERT INTO referral_rewards (referrer_user_id, referee_user_id, reward_type, credits_awarded) VALUES ($1, $1, $2, $3)",
+                &[&user_id, &reward_type, &credits],
+            )
+            .await?;
+        transaction.commit().await
+    }
+
+    /// appends a credit change to the ledger; runs on the caller's transaction so the entry
+    /// lands together with the balance update, or not at all
+    pub(crate) async fn record_credit_transaction(
+        client: &impl GenericClient,
+        user_id: i32,
+        amount: i32,
+        balance_after: i32,
+        kind: CreditTransactionKind,
+        reference: Option<&str>,
+    ) -> Result<(), tokio_postgres::Error> {
+        client
+            .execute(
+                "INSERT INTO credit_transactions (user_id, amount, balance_after, kind, reference) VALUES ($1, $2, $3, $4, $5)",
+                &[&user_id, &amount, &balance_after, &kind.as_str(), &reference],
+            )
+            .await?;
+        Ok(())
     }
 
-    /// checks if referral count hits a celebration milestone: 1, 5, 10, 20, 30, 40, 50, etc.
-    fn is_celebration_milestone(referral_count: i32) -> bool {
-        match referral_count {
-            1 | 5 => true,
-            n if n >= 10 && n % 10 == 0 => true,
-            _ => false,
-        }
+    /// api keys are stored as sha-256 hashes only
+    fn hash_api_key(key: &str) -> String {
+        hex::encode(Sha256::digest(key.as_bytes()))
     }
 
-    /// gets existing user or creates new user with default credits
+    /// gets existing user or creates a new one whose free trial waits for
+    /// `claim_free_trial`
     pub async fn get_or_create_user(
         &self,
         telegram_user_id: i64,
@@ -157,13 +369,19 @@ impl UserManager {
             return Ok((user, None));
         }
 
-        // create new user with default credits
+        // create new user without credits until they claim the free trial
+        let fingerprint = free_trials::signup_fingerprint(
+            language_code,
+            username.is_some(),
+            last_name.is_some(),
+            referrer_user_id,
+        );
         let row = client
             .query_one(
-                "INSERT INTO users (telegram_user_id, username, first_name, last_name, analysis_credits, total_analyses_performed, referred_by_user_id, referrals_count, paid_referrals_count, language) 
-                 VALUES ($1, $2, $3, $4, 1, 0, $5, 0, 0, $6) 
+                "INSERT INTO users (telegram_user_id, username, first_name, last_name, analysis_credits, total_analyses_performed, referred_by_user_id, referrals_count, paid_referrals_count, language, trial_status, signup_fingerprint) 
+                 VALUES ($1, $2, $3, $4, 0, 0, $5, 0, 0, $6, 'pending', $7) 
                  RETURNING id, telegram_user_id, username, first_name, last_name, analysis_credits, total_analyses_performed, referred_by_user_id, referrals_count, paid_referrals_count, language",
-                &[&telegram_user_id, &username, &first_name, &last_name, &referrer_user_id, &language_code],
+                &[&telegram_user_id, &username, &first_name, &last_name, &referrer_user_id, &language_code, &fingerprint],
             )
             .await?;
 
@@ -182,8 +400,9 @@ impl UserManager {
         };
 
         info!(
-            "Created new user: {} with {} credits",
-            telegram_user_id, user.analysis_credits
+            "Created new user: {} ({}), free trial pending",
+            telegram_user_id,
+            fingerprint.as_deref().unwrap_or("no referrer")
         );
 
         // if user was referred, increment referrer's count and check for rewards
@@ -215,12 +434,28 @@ impl UserManager {
         Ok((user, None))
     }
 
+    /// the "I'm human" button: grants the pending free trial unless `per_hour` look-alike
+    /// signups already got theirs; None if the trial was already decided
+    pub async fn claim_free_trial(
+        &self,
+        telegram_user_id: i64,
+        per_hour: i32,
+    ) -> Result<Option<TrialOutcome>, Box<dyn Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        let outcome = free_trials::claim(&transaction, telegram_user_id, per_hour).await?;
+        transaction.commit().await?;
+        Ok(outcome)
+    }
+
     /// processes a new referral: increments count and checks for rewards/milestones
     async fn process_new_referral(
         &self,
         referrer_user_id: i32,
     ) -> Result<Option<ReferralRewardInfo>, Box<dyn Error + Send + Sync>> {
-        let client = self.pool.get().await?;
+        // loaded before taking a connection, the caller already holds one
+        let rules = ReferralRules::load(&self.pool).await?;
+        let mut client = self.pool.get().await?;
 
         // increment referrals count and get new count
         info!(
@@ -243,14 +478,14 @@ impl UserManager {
         );
 
         // check if this is a celebration milestone
-        let is_celebration = Self::is_celebration_milestone(new_referral_count);
+        let is_celebration = rules.is_celebration(new_referral_count);
         info!(
             "Referral milestone check for user {}: count={}, is_celebration={}",
             referrer_user_id, new_referral_count, is_celebration
         );
 
-        // check for credit rewards (every 5 referrals)
-        let expected_milestone_rewards = Self::calculate_milestone_rewards(new_referral_count);
+        // check for credit rewards (every milestone_every referrals)
+        let expected_milestone_rewards = rules.milestones(new_referral_count);
         info!(
             "Expected milestone rewards for {} referrals: {}",
             new_referral_count, expected_milestone_rewards
@@ -263,10 +498,20 @@ impl UserManager {
             .await?
             .get::<_, i64>(0) as i32;
 
+        // suspicious referrers get their milestone rewards once an admin clears them
+        referral_flags::flag_suspicious(&client, referrer_user_id, rules.suspicious_referees)
+            .await?;
+        let held = referral_flags::rewards_held(&client, referrer_user_id).await?;
+
         let mut milestone_rewards = 0;
-        if expected_milestone_rewards > existing_unpaid_rewards {
+        if held {
+            info!(
+                "Holding milestone rewards of flagged user {} (expected: {}, existing: {})",
+                referrer_user_id, expected_milestone_rewards, existing_unpaid_rewards
+            );
+        } else if expected_milestone_rewards > existing_unpaid_rewards {
             let new_rewards = expected_milestone_rewards - existing_unpaid_rewards;
-            milestone_rewards = new_rewards;
+            milestone_rewards = new_rewards * rules.milestone_credits;
             info!(
                 "Awarding {} new milestone rewards to user {} (expected: {}, existing: {})",
                 new_rewards, referrer_user_id, expected_milestone_rewards, existing_unpaid_rewards
@@ -278,21 +523,13 @@ impl UserManager {
                     new_rewards,
                     referrer_user_id
                 );
-                // award 1 credit for milestone
-                client
-                    .execute(
-                        "UPDATE users SET analysis_credits = analysis_credits + 1 WHERE id = $1",
-                        &[&referrer_user_id],
-                    )
-                    .await?;
-
-                // record the reward
-                client
-                    .execute(
-                        "INSERT INTO referral_rewards (referrer_user_id, referee_user_id, reward_type, credits_awarded) VALUES ($1, $1, 'unpaid_milestone', 1)",
-                        &[&referrer_user_id],
-                    )
-                    .await?;
+                Self::award_referral_reward(
+                    &mut client,
+                    referrer_user_id,
+                    "unpaid_milestone",
+                    rules.milestone_credits,
+                )
+                .await?;
                 info!(
                     "Successfully awarded milestone reward {} to user {}",
                     i + 1,
@@ -348,48 +585,224 @@ impl UserManager {
         Ok(())
     }
 
-    /// creates a pending analysis record without consuming credit
+    /// refuses to start another analysis once the user has max_running pending ones; locks
+    /// the user until the caller's transaction ends so concurrent starts are counted one by one
+    async fn check_running_analyses(
+        client: &impl GenericClient,
+        user_id: i32,
+        max_running: i32,
+    ) -> Result<(), UserManagerError> {
+        client
+            .execute("SELECT 1 FROM users WHERE id = $1 FOR UPDATE", &[&user_id])
+            .await?;
+        let running: i64 = client
+            .query_one(
+                "SELECT COUNT(*) FROM user_analyses WHERE user_id = $1 AND status = 'pending'",
+                &[&user_id],
+            )
+            .await?
+            .get(0);
+        if running >= i64::from(max_running) {
+            info!(
+                "Refused a new analysis for user {}: {} already running",
+                user_id, running
+            );
+            return Err(UserManagerError::TooManyRunning(user_id));
+        }
+        Ok(())
+    }
+
+    /// creates a pending analysis record without consuming credit; with max_running set,
+    /// fails with TooManyRunning if the user already has that many pending analyses
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_pending_analysis(
         &self,
         user_id: i32,
         channel_name: &str,
         analysis_type: &str,
+        depth: &str,
         language: Option<&str>,
+        focus: Option<&str>,
+        source: AnalysisSource,
+        max_running: Option<i32>,
     ) -> Result<i32, UserManagerError> {
-        let client = self.pool.get().await?;
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        if let Some(max_running) = max_running {
+            Self::check_running_analyses(&transaction, user_id, max_running).await?;
+        }
 
         // create pending analysis record
-        let analysis_id = client
+        let analysis_id = transaction
             .query_one(
-                "INSERT INTO user_analyses (user_id, channel_name, credits_used, analysis_type, status, language) VALUES ($1, $2, 0, $3, 'pending', $4) RETURNING id",
-                &[&user_id, &channel_name, &analysis_type, &language],
+                "INSERT INTO user_analyses (user_id, channel_name, credits_used, analysis_type, depth, status, language, focus, source) VALUES ($1, $2, 0, $3, $4, 'pending', $5, $6, $7) RETURNING id",
+                &[&user_id, &channel_name, &analysis_type, &depth, &language, &focus, &source.as_str()],
             )
             .await?
             .get::<_, i32>(0);
+        transaction.commit().await?;
 
         info!(
-            "Created pending analysis {} for user {} (channel: {}, lang: {:?})",
-            analysis_id, user_id, channel_name, language
+            "Created pending {} analysis {} for user {} (channel: {}, lang: {:?})",
+            source.as_str(),
+            analysis_id,
+            user_id,
+            channel_name,
+            language
         );
         Ok(analysis_id)
     }
 
-    /// atomically consumes credit, marks analysis completed, and returns remaining credits
+    /// stores the llm cache key an analysis reads its result from
+    pub async fn set_analysis_cache_key(
+        &self,
+        analysis_id: i32,
+        cache_key: &str,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET cache_key = $2 WHERE id = $1",
+                &[&analysis_id, &cache_key],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// stores the model that answered an analysis
+    pub async fn set_analysis_model(
+        &self,
+        analysis_id: i32,
+        model: &str,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET model = $2 WHERE id = $1",
+                &[&analysis_id, &model],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// stores the prompt version an analysis ran with, for comparing the feedback on versions
+    pub async fn set_analysis_prompt_version(
+        &self,
+        analysis_id: i32,
+        prompt_version: i32,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET prompt_version = $2 WHERE id = $1",
+                &[&analysis_id, &prompt_version],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// stores the backend that fetched the messages an analysis read, for comparing
+    /// the data quality of the backends
+    pub async fn set_analysis_backend(
+        &self,
+        analysis_id: i32,
+        backend: BackendType,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET backend = $2 WHERE id = $1",
+                &[&analysis_id, &backend.as_str()],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// scopes an analysis of a forum group to one of its topics
+    pub async fn set_analysis_topic(
+        &self,
+        analysis_id: i32,
+        topic: &ForumTopic,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET thread_id = $2, topic_name = $3 WHERE id = $1",
+                &[&analysis_id, &topic.thread_id, &topic.name],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// returns the user's most recent completed analyses, newest first, optionally for one channel
+    pub async fn get_shareable_analyses(
+        &self,
+        telegram_user_id: i64,
+        channel_name: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ShareableAnalysis>, UserManagerError> {
+        let client = self.pool.get().await?;
+        // latest completed analysis per channel and type, then newest first overall
+        let rows = client
+            .query(
+                "SELECT user_id, channel_name, analysis_type, cache_key FROM (
+                     SELECT DISTINCT ON (ua.channel_name, ua.analysis_type)
+                            ua.user_id, ua.channel_name, ua.analysis_type, ua.cache_key, ua.analysis_timestamp
+                     FROM user_analyses ua
+                     JOIN users u ON ua.user_id = u.id
+                     WHERE u.telegram_user_id = $1
+                       AND ua.status = 'completed'
+                       AND ua.cache_key IS NOT NULL
+                       AND ($2::TEXT IS NULL OR LOWER(ua.channel_name) = LOWER($2))
+                     ORDER BY ua.channel_name, ua.analysis_type, ua.analysis_timestamp DESC
+                 ) latest
+                 ORDER BY analysis_timestamp DESC
+                 LIMIT $3",
+                &[&telegram_user_id, &channel_name, &limit],
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ShareableAnalysis {
+                user_id: row.get(0),
+                channel_name: row.get(1),
+                analysis_type: row.get(2),
+                cache_key: row.get(3),
+            })
+            .collect())
+    }
+
+    /// atomically consumes the analysis price in credits, marks analysis completed, and returns remaining credits
     pub async fn atomic_complete_analysis(
         &self,
         analysis_id: i32,
         user_id: i32,
+        credits: i32,
     ) -> Result<i32, UserManagerError> {
         let mut client = self.pool.get().await?;
         let transaction = client.transaction().await?;
 
+        // an analysis the watchdog timed out stays failed and uncharged
+        let marked = transaction
+            .execute(
+                "UPDATE user_analyses SET status = 'completed', credits_used = $2
+                 WHERE id = $1 AND status = 'pending'",
+                &[&analysis_id, &credits],
+            )
+            .await?;
+        if marked == 0 {
+            transaction.rollback().await?;
+            return Err(UserManagerError::NotPending(analysis_id));
+        }
+
         // consume credit only if user has sufficient credits
         let row = transaction
             .query_opt(
-                "UPDATE users SET analysis_credits = analysis_credits - 1, total_analyses_performed = total_analyses_performed + 1, updated_at = NOW() 
-                 WHERE id = $1 AND analysis_credits > 0 
+                "UPDATE users SET analysis_credits = analysis_credits - $2, total_analyses_performed = total_analyses_performed + 1, updated_at = NOW() 
+                 WHERE id = $1 AND analysis_credits >= $2 
                  RETURNING analysis_credits",
-                &[&user_id],
+                &[&user_id, &credits],
             )
             .await?;
 
@@ -412,13 +825,15 @@ impl UserManager {
             }
         };
 
-        // mark analysis as completed
-        transaction
-            .execute(
-                "UPDATE user_analyses SET status = 'completed', credits_used = 1 WHERE id = $1",
-                &[&analysis_id],
-            )
-            .await?;
+        Self::record_credit_transaction(
+            &transaction,
+            user_id,
+            -credits,
+            remaining_credits,
+            CreditTransactionKind::Analysis,
+            Some(&analysis_id.to_string()),
+        )
+        .await?;
 
         transaction.commit().await?;
 
@@ -429,61 +844,304 @@ impl UserManager {
         Ok(remaining_credits)
     }
 
-    /// gets all pending analyses for recovery
-    pub async fn get_pending_analyses(
+    /// gives back the credits of a charged analysis that had nothing to deliver and marks it
+    /// failed; None when it was never charged or its credits were already given back, for a
+    /// free regeneration too, so the credits of one analysis come back once; returns the
+    /// credits given back and the new balance
+    pub async fn refund_analysis_credits(
         &self,
-    ) -> Result<Vec<PendingAnalysis>, Box<dyn std::error::Error + Send + Sync>> {
-        let client = self.pool.get().await?;
-        let rows = client
-            .query(
-                "SELECT ua.id, ua.user_id, u.telegram_user_id, ua.channel_name, ua.analysis_type, ua.language 
-                 FROM user_analyses ua 
-                 JOIN users u ON ua.user_id = u.id 
-                 WHERE ua.status = 'pending' 
-                 ORDER BY ua.analysis_timestamp ASC",
-                &[],
+        analysis_id: i32,
+    ) -> Result<Option<(i32, i32)>, UserManagerError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        let Some(row) = transaction
+            .query_opt(
+                "UPDATE user_analyses ua SET status = 'failed'
+                 WHERE ua.id = $1 AND ua.status = 'completed' AND ua.credits_used > 0
+                 AND NOT EXISTS (
+                     SELECT 1 FROM credit_transactions ct
+                     WHERE ct.user_id = ua.user_id AND ct.kind = 'refund' AND ct.reference = ua.id::text
+                 )
+                 RETURNING ua.user_id, ua.credits_used",
+                &[&analysis_id],
             )
-            .await?;
+            .await?
+        else {
+            transaction.rollback().await?;
+            return Ok(None);
+        };
+        let user_id: i32 = row.get(0);
+        let credits: i32 = row.get(1);
 
-        let pending_analyses: Vec<PendingAnalysis> = rows
-            .into_iter()
-            .map(|row| PendingAnalysis {
-                id: row.get(0),
-                user_id: row.get(1),
-                telegram_user_id: row.get(2),
-                channel_name: row.get(3),
-                analysis_type: row.get(4),
-                language: row.get(5),
-            })
-            .collect();
+        let new_balance: i32 = transaction
+            .query_one(
+                "UPDATE users SET analysis_credits = analysis_credits + $2, total_analyses_performed = GREATEST(total_analyses_performed - 1, 0), updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING analysis_credits",
+                &[&user_id, &credits],
+            )
+            .await?
+            .get(0);
+        Self::record_credit_transaction(
+            &transaction,
+            user_id,
+            credits,
+            new_balance,
+            CreditTransactionKind::Refund,
+            Some(&analysis_id.to_string()),
+        )
+        .await?;
+
+        transaction.commit().await?;
 
         info!(
-            "Found {} pending analyses for recovery",
-            pending_analyses.len()
+            "Gave back {} credits of analysis {} to user {} (new balance: {})",
+            credits, analysis_id, user_id, new_balance
         );
-        Ok(pending_analyses)
+        Ok(Some((credits, new_balance)))
     }
 
-    /// adds credits to user (for future payment integration)
-    pub async fn add_credits(
+    /// flags a completed analysis whose llm answer was cut off
+    pub async fn mark_analysis_partial(&self, analysis_id: i32) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET partial = TRUE WHERE id = $1",
+                &[&analysis_id],
+            )
+            .await?;
+        info!("Marked analysis {} as partial", analysis_id);
+        Ok(())
+    }
+
+    /// gives back the credits of a partial analysis so it can be regenerated free of charge;
+    /// each partial analysis can be claimed once, None when it isn't the user's or was claimed
+    pub async fn claim_partial_regeneration(
         &self,
+        analysis_id: i32,
         user_id: i32,
-        credits_to_add: i32,
-    ) -> Result<i32, Box<dyn Error + Send + Sync>> {
-        let client = self.pool.get().await?;
+    ) -> Result<Option<PartialRegeneration>, UserManagerError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
 
-        let row = client
+        let Some(row) = transaction
             .query_opt(
-                "UPDATE users SET analysis_credits = analysis_credits + $2, updated_at = NOW() 
-                 WHERE id = $1 
+                "UPDATE user_analyses SET partial = FALSE
+                 WHERE id = $1 AND user_id = $2 AND partial AND status = 'completed'
+                 AND analysis_type IS NOT NULL
+                 RETURNING channel_name, analysis_type, depth, focus, credits_used, thread_id, topic_name",
+                &[&analysis_id, &user_id],
+            )
+            .await?
+        else {
+            transaction.rollback().await?;
+            return Ok(None);
+        };
+        let credits_refunded: i32 = row.get(4);
+
+        let balance: i32 = transaction
+            .query_one(
+                "UPDATE users SET analysis_credits = analysis_credits + $2, updated_at = NOW()
+                 WHERE id = $1
                  RETURNING analysis_credits",
-                &[&user_id, &credits_to_add],
+                &[&user_id, &credits_refunded],
             )
-            .await?;
+            .await?
+            .get(0);
+        Self::record_credit_transaction(
+            &transaction,
+            user_id,
+            credits_refunded,
+            balance,
+            CreditTransactionKind::Refund,
+            Some(&analysis_id.to_string()),
+        )
+        .await?;
 
-        match row {
-            Some(row) => {
-                let new_balance: i32 = row.get(0);
+        transaction.commit().await?;
+
+        info!(
+            "Refunded {} credits of partial analysis {} to user {} for regeneration",
+            credits_refunded, analysis_id, user_id
+        );
+        Ok(Some(PartialRegeneration {
+            channel_name: row.get(0),
+            analysis_type: row.get(1),
+            depth: row.get(2),
+            focus: row.get(3),
+            topic: stored_topic(row.get(5), row.get(6)),
+            credits_refunded,
+            balance,
+        }))
+    }
+
+    /// creates a pending second opinion on one of the user's completed bot analyses, for the
+    /// same channel, type, depth, focus and topic; None if the analysis isn't theirs, has no
+    /// model recorded, is a second opinion itself or already has one that didn't fail;
+    /// TooManyRunning as for create_pending_analysis
+    pub async fn create_second_opinion(
+        &self,
+        analysis_id: i32,
+        user_id: i32,
+        language: Option<&str>,
+        max_running: i32,
+    ) -> Result<Option<i32>, UserManagerError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        Self::check_running_analyses(&transaction, user_id, max_running).await?;
+        let row = transaction
+            .query_opt(
+                "INSERT INTO user_analyses (user_id, channel_name, credits_used, analysis_type, depth, status, language, focus, source, thread_id, topic_name, second_opinion_of)
+                 SELECT user_id, channel_name, 0, analysis_type, depth, 'pending', $3, focus, source, thread_id, topic_name, id
+                 FROM user_analyses
+                 WHERE id = $1 AND user_id = $2 AND status = 'completed' AND source = 'bot'
+                 AND analysis_type IS NOT NULL AND model IS NOT NULL AND second_opinion_of IS NULL
+                 AND NOT EXISTS (
+                     SELECT 1 FROM user_analyses WHERE second_opinion_of = $1 AND status <> 'failed'
+                 )
+                 RETURNING id",
+                &[&analysis_id, &user_id, &language],
+            )
+            .await?;
+        transaction.commit().await?;
+        let second_opinion_id = row.map(|row| row.get::<_, i32>(0));
+        if let Some(second_opinion_id) = second_opinion_id {
+            info!(
+                "Created second opinion {} on analysis {} for user {}",
+                second_opinion_id, analysis_id, user_id
+            );
+        }
+        Ok(second_opinion_id)
+    }
+
+    /// puts one of the user's failed bot analyses back to pending so it can run again;
+    /// None if it isn't failed anymore, e.g. because it was already reopened, or if it was
+    /// charged: a charged analysis only fails when its credits were given back, and running
+    /// it again would charge it a second time;
+    /// TooManyRunning as for create_pending_analysis
+    pub async fn reopen_failed_analysis(
+        &self,
+        analysis_id: i32,
+        user_id: i32,
+        max_running: i32,
+    ) -> Result<Option<PendingAnalysis>, UserManagerError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        Self::check_running_analyses(&transaction, user_id, max_running).await?;
+        let row = transaction
+            .query_opt(
+                "UPDATE user_analyses ua SET status = 'pending', pending_since = NOW()
+                 FROM users u
+                 WHERE ua.id = $1 AND ua.user_id = $2 AND u.id = ua.user_id
+                 AND ua.status = 'failed' AND ua.source = 'bot' AND ua.analysis_type IS NOT NULL
+                 AND ua.credits_used = 0
+                 AND NOT EXISTS (
+                     SELECT 1 FROM credit_transactions ct
+                     WHERE ct.user_id = ua.user_id AND ct.kind = 'refund' AND ct.reference = ua.id::text
+                 )
+                 RETURNING ua.id, ua.user_id, u.telegram_user_id, ua.channel_name, ua.analysis_type, ua.language, ua.focus, ua.depth,
+                           EXTRACT(EPOCH FROM NOW() - ua.analysis_timestamp)::float8, ua.thread_id, ua.topic_name,
+                           ua.second_opinion_of",
+                &[&analysis_id, &user_id],
+            )
+            .await?;
+        transaction.commit().await?;
+
+        Ok(row.map(|row| PendingAnalysis {
+            id: row.get(0),
+            user_id: row.get(1),
+            telegram_user_id: row.get(2),
+            channel_name: row.get(3),
+            analysis_type: row.get(4),
+            language: row.get(5),
+            focus: row.get(6),
+            depth: row.get(7),
+            age: Duration::from_secs_f64(row.get::<_, f64>(8).max(0.0)),
+            topic: stored_topic(row.get(9), row.get(10)),
+            second_opinion_of: row.get(11),
+        }))
+    }
+
+    /// gets the pending analyses of one front end for recovery
+    pub async fn get_pending_analyses(
+        &self,
+        source: AnalysisSource,
+    ) -> Result<Vec<PendingAnalysis>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT ua.id, ua.user_id, u.telegram_user_id, ua.channel_name, ua.analysis_type, ua.language, ua.focus, ua.depth,
+                        EXTRACT(EPOCH FROM NOW() - ua.analysis_timestamp)::float8, ua.thread_id, ua.topic_name,
+                        ua.second_opinion_of
+                 FROM user_analyses ua 
+                 JOIN users u ON ua.user_id = u.id 
+                 WHERE ua.status = 'pending' AND ua.source = $1
+                 ORDER BY ua.analysis_timestamp ASC",
+                &[&source.as_str()],
+            )
+            .await?;
+
+        let pending_analyses: Vec<PendingAnalysis> = rows
+            .into_iter()
+            .map(|row| PendingAnalysis {
+                id: row.get(0),
+                user_id: row.get(1),
+                telegram_user_id: row.get(2),
+                channel_name: row.get(3),
+                analysis_type: row.get(4),
+                language: row.get(5),
+                focus: row.get(6),
+                depth: row.get(7),
+                age: Duration::from_secs_f64(row.get::<_, f64>(8).max(0.0)),
+                topic: stored_topic(row.get(9), row.get(10)),
+                second_opinion_of: row.get(11),
+            })
+            .collect();
+
+        info!(
+            "Found {} pending {} analyses for recovery",
+            pending_analyses.len(),
+            source.as_str()
+        );
+        Ok(pending_analyses)
+    }
+
+    /// adds credits to user and records why in the ledger; purchases go through
+    /// record_package_payment instead, which keeps the payment with its credits
+    #[allow(dead_code)]
+    pub async fn add_credits(
+        &self,
+        user_id: i32,
+        credits_to_add: i32,
+        kind: CreditTransactionKind,
+        reference: Option<&str>,
+    ) -> Result<i32, Box<dyn Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        let row = transaction
+            .query_opt(
+                "UPDATE users SET analysis_credits = analysis_credits + $2, updated_at = NOW() 
+                 WHERE id = $1 
+                 RETURNING analysis_credits",
+                &[&user_id, &credits_to_add],
+            )
+            .await?;
+
+        match row {
+            Some(row) => {
+                let new_balance: i32 = row.get(0);
+                Self::record_credit_transaction(
+                    &transaction,
+                    user_id,
+                    credits_to_add,
+                    new_balance,
+                    kind,
+                    reference,
+                )
+                .await?;
+                transaction.commit().await?;
                 info!(
                     "Added {} credits to user {}, new balance: {}",
                     credits_to_add, user_id, new_balance
@@ -497,6 +1155,571 @@ impl UserManager {
         }
     }
 
+    /// returns a page of the user's credit ledger, newest first
+    pub async fn get_credit_transactions(
+        &self,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CreditTransaction>, UserManagerError> {
+        let client = read_pool(&self.pool).get().await?;
+        let rows = client
+            .query(
+                "SELECT amount, balance_after, kind,
+                        TO_CHAR(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM credit_transactions
+                 WHERE user_id = $1
+                 ORDER BY id DESC
+                 LIMIT $2 OFFSET $3",
+                &[&user_id, &limit, &offset],
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind = CreditTransactionKind::from_code(row.get(2))?;
+                Some(CreditTransaction {
+                    amount: row.get(0),
+                    balance_after: row.get(1),
+                    kind,
+                    created_at: row.get(3),
+                })
+            })
+            .collect())
+    }
+
+    /// number of ledger entries for the user, used to paginate /balance
+    pub async fn count_credit_transactions(&self, user_id: i32) -> Result<i64, UserManagerError> {
+        let client = read_pool(&self.pool).get().await?;
+        let row = client
+            .query_one(
+                "SELECT COUNT(*) FROM credit_transactions WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    /// the user's current credit balance
+    pub async fn get_credits(&self, user_id: i32) -> Result<i32, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT analysis_credits FROM users WHERE id = $1",
+                &[&user_id],
+            )
+            .await?
+            .ok_or(UserManagerError::UserNotFound(user_id))?;
+        Ok(row.get(0))
+    }
+
+    /// returns the user's preferred model tier
+    pub async fn get_model_tier(&self, user_id: i32) -> Result<ModelTier, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt("SELECT model_tier FROM users WHERE id = $1", &[&user_id])
+            .await?
+            .ok_or(UserManagerError::UserNotFound(user_id))?;
+        Ok(ModelTier::from_code(Some(row.get::<_, &str>(0))))
+    }
+
+    /// stores the user's preferred model tier
+    pub async fn set_model_tier(
+        &self,
+        user_id: i32,
+        tier: ModelTier,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE users SET model_tier = $2, updated_at = NOW() WHERE id = $1",
+                &[&user_id, &tier.as_str()],
+            )
+            .await?;
+        if updated == 0 {
+            return Err(UserManagerError::UserNotFound(user_id));
+        }
+        info!("Set model tier for user {} to {}", user_id, tier.as_str());
+        Ok(())
+    }
+
+    /// returns the language the user wants analyses written in
+    pub async fn get_output_language(
+        &self,
+        user_id: i32,
+    ) -> Result<OutputLanguage, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT output_language FROM users WHERE id = $1",
+                &[&user_id],
+            )
+            .await?
+            .ok_or(UserManagerError::UserNotFound(user_id))?;
+        Ok(OutputLanguage::from_code(Some(row.get::<_, &str>(0))))
+    }
+
+    /// stores the language the user wants analyses written in
+    pub async fn set_output_language(
+        &self,
+        user_id: i32,
+        language: OutputLanguage,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE users SET output_language = $2, updated_at = NOW() WHERE id = $1",
+                &[&user_id, &language.as_str()],
+            )
+            .await?;
+        if updated == 0 {
+            return Err(UserManagerError::UserNotFound(user_id));
+        }
+        info!(
+            "Set output language for user {} to {}",
+            user_id,
+            language.as_str()
+        );
+        Ok(())
+    }
+
+    /// returns the bot language the user picked, None while it follows telegram's language_code
+    pub async fn get_language_override(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<Option<String>, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT language_override FROM users WHERE telegram_user_id = $1",
+                &[&telegram_user_id],
+            )
+            .await?;
+        Ok(row.and_then(|row| row.get(0)))
+    }
+
+    /// stores the bot language the user picked, None goes back to telegram's language_code
+    pub async fn set_language_override(
+        &self,
+        user_id: i32,
+        language: Option<&str>,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE users SET language_override = $2, updated_at = NOW() WHERE id = $1",
+                &[&user_id, &language],
+            )
+            .await?;
+        if updated == 0 {
+            return Err(UserManagerError::UserNotFound(user_id));
+        }
+        info!(
+            "Set language override for user {} to {:?}",
+            user_id, language
+        );
+        Ok(())
+    }
+
+    /// whether the user receives release announcements
+    pub async fn get_announcements_enabled(&self, user_id: i32) -> Result<bool, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT announcements_enabled FROM users WHERE id = $1",
+                &[&user_id],
+            )
+            .await?
+            .ok_or(UserManagerError::UserNotFound(user_id))?;
+        Ok(row.get(0))
+    }
+
+    /// turns release announcements on or off for the user
+    pub async fn set_announcements_enabled(
+        &self,
+        user_id: i32,
+        enabled: bool,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE users SET announcements_enabled = $2, updated_at = NOW() WHERE id = $1",
+                &[&user_id, &enabled],
+            )
+            .await?;
+        if updated == 0 {
+            return Err(UserManagerError::UserNotFound(user_id));
+        }
+        info!(
+            "Turned announcements {} for user {}",
+            if enabled { "on" } else { "off" },
+            user_id
+        );
+        Ok(())
+    }
+
+    /// whether the user's long results are published as a telegra.ph page
+    pub async fn get_web_pages_enabled(&self, user_id: i32) -> Result<bool, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT web_pages_enabled FROM users WHERE id = $1",
+                &[&user_id],
+            )
+            .await?
+            .ok_or(UserManagerError::UserNotFound(user_id))?;
+        Ok(row.get(0))
+    }
+
+    /// turns telegra.ph pages for long results on or off for the user
+    pub async fn set_web_pages_enabled(
+        &self,
+        user_id: i32,
+        enabled: bool,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE users SET web_pages_enabled = $2, updated_at = NOW() WHERE id = $1",
+                &[&user_id, &enabled],
+            )
+            .await?;
+        if updated == 0 {
+            return Err(UserManagerError::UserNotFound(user_id));
+        }
+        info!(
+            "Turned web pages {} for user {}",
+            if enabled { "on" } else { "off" },
+            user_id
+        );
+        Ok(())
+    }
+
+    /// returns one of the user's analyses, None if it doesn't exist or belongs to someone else
+    pub async fn get_analysis(
+        &self,
+        analysis_id: i32,
+        user_id: i32,
+    ) -> Result<Option<AnalysisRecord>, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT id, channel_name, analysis_type, depth, status, credits_used, cache_key,
+                        TO_CHAR(analysis_timestamp AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"'), model
+                 FROM user_analyses
+                 WHERE id = $1 AND user_id = $2",
+                &[&analysis_id, &user_id],
+            )
+            .await?;
+        Ok(row.map(|row| AnalysisRecord {
+            id: row.get(0),
+            channel_name: row.get(1),
+            analysis_type: row.get(2),
+            depth: row.get(3),
+            status: row.get(4),
+            credits_used: row.get(5),
+            cache_key: row.get(6),
+            created_at: row.get(7),
+            model: row.get(8),
+        }))
+    }
+
+    /// stores the messages an analysis result was delivered as
+    pub async fn store_rendered_result(
+        &self,
+        analysis_id: i32,
+        messages: &[String],
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE user_analyses SET rendered_result = $2 WHERE id = $1",
+                &[&analysis_id, &serde_json::json!(messages)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// the stored messages of one of the user's analyses, or of their latest one when
+    /// `analysis_id` is None; returned with the analysis id
+    pub async fn get_rendered_result(
+        &self,
+        user_id: i32,
+        analysis_id: Option<i32>,
+    ) -> Result<Option<(i32, Vec<String>)>, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT id, rendered_result FROM user_analyses
+                 WHERE user_id = $1 AND rendered_result IS NOT NULL AND ($2::INT IS NULL OR id = $2)
+                 ORDER BY id DESC
+                 LIMIT 1",
+                &[&user_id, &analysis_id],
+            )
+            .await?;
+        Ok(row.and_then(|row| {
+            serde_json::from_value(row.get(1))
+                .ok()
+                .map(|messages| (row.get(0), messages))
+        }))
+    }
+
+    /// issues a new rest api key for the user; the previous ones keep working for `overlap`,
+    /// so clients can switch to the new key without downtime, and expire then. The key is
+    /// returned once, with how many previous keys still work, and only its hash is stored
+    pub async fn create_api_key(
+        &self,
+        user_id: i32,
+        overlap: Duration,
+    ) -> Result<(String, u64), UserManagerError> {
+        let key = format!("tga_{}", hex::encode(rand::thread_rng().gen::<[u8; 24]>()));
+
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        let expiring = transaction
+            .execute(
+                "UPDATE api_keys
+                 SET expires_at = LEAST(COALESCE(expires_at, 'infinity'), NOW() + make_interval(secs => $2))
+                 WHERE user_id = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())",
+                &[&user_id, &overlap.as_secs_f64()],
+            )
+            .await?;
+        transaction
+            .execute(
+                "INSERT INTO api_keys (user_id, key_hash) VALUES ($1, $2)",
+                &[&user_id, &Self::hash_api_key(&key)],
+            )
+            .await?;
+        transaction.commit().await?;
+
+        info!(
+            "Issued API key for user {} ({} previous expire within {}s)",
+            user_id,
+            expiring,
+            overlap.as_secs()
+        );
+        Ok((key, expiring))
+    }
+
+    /// revokes all of the user's api keys, including those still in their overlap, returns
+    /// how many were active
+    pub async fn revoke_api_keys(&self, user_id: i32) -> Result<u64, UserManagerError> {
+        let client = self.pool.get().await?;
+        let revoked = client
+            .execute(
+                "UPDATE api_keys SET revoked_at = NOW()
+                 WHERE user_id = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())",
+                &[&user_id],
+            )
+            .await?;
+        info!("Revoked {} API keys of user {}", revoked, user_id);
+        Ok(revoked)
+    }
+
+    /// resolves an active api key to its owner and records its use
+    pub async fn get_user_by_api_key(&self, key: &str) -> Result<Option<User>, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "UPDATE api_keys k SET last_used_at = NOW()
+                 FROM users u
+                 WHERE k.user_id = u.id AND k.key_hash = $1 AND k.revoked_at IS NULL
+                   AND (k.expires_at IS NULL OR k.expires_at > NOW())
+                 RETURNING u.id, u.telegram_user_id, u.username, u.first_name, u.last_name, u.analysis_credits, u.total_analyses_performed, u.referred_by_user_id, u.referrals_count, u.paid_referrals_count, COALESCE(u.language_override, u.language)",
+                &[&Self::hash_api_key(key)],
+            )
+            .await?;
+        Ok(row.map(|row| User {
+            id: row.get(0),
+            telegram_user_id: row.get(1),
+            username: row.get(2),
+            first_name: row.get(3),
+            last_name: row.get(4),
+            analysis_credits: row.get(5),
+            total_analyses_performed: row.get(6),
+            referred_by_user_id: row.get(7),
+            referrals_count: row.get(8),
+            paid_referrals_count: row.get(9),
+            language: row.get(10),
+        }))
+    }
+
+    /// records a successful star payment so it can be refunded later
+    pub async fn record_payment(
+        &self,
+        user_id: i32,
+        telegram_payment_charge_id: &str,
+        package: &str,
+        stars: i32,
+        credits: i32,
+    ) -> Result<(), UserManagerError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO payments (user_id, telegram_payment_charge_id, package, stars, credits) VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (telegram_payment_charge_id) DO NOTHING",
+                &[&user_id, &telegram_payment_charge_id, &package, &stars, &credits],
+            )
+            .await?;
+        info!(
+            "Recorded payment {} for user {}: {} ({} stars, {} credits)",
+            telegram_payment_charge_id, user_id, package, stars, credits
+        );
+        Ok(())
+    }
+
+    /// records a package payment and adds its credits in one transaction, so the charge
+    /// can always be refunded and a redelivered update doesn't credit it twice; returns the
+    /// new balance, None when the charge was already recorded
+    pub async fn record_package_payment(
+        &self,
+        user_id: i32,
+        telegram_payment_charge_id: &str,
+        package: &str,
+        stars: i32,
+        credits: i32,
+    ) -> Result<Option<i32>, UserManagerError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        let recorded = transaction
+            .execute(
+                "INSERT INTO payments (user_id, telegram_payment_charge_id, package, stars, credits) VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (telegram_payment_charge_id) DO NOTHING",
+                &[&user_id, &telegram_payment_charge_id, &package, &stars, &credits],
+            )
+            .await?;
+        if recorded == 0 {
+            transaction.rollback().await?;
+            return Ok(None);
+        }
+
+        let new_balance: i32 = transaction
+            .query_opt(
+                "UPDATE users SET analysis_credits = analysis_credits + $2, updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING analysis_credits",
+                &[&user_id, &credits],
+            )
+            .await?
+            .ok_or(UserManagerError::UserNotFound(user_id))?
+            .get(0);
+        Self::record_credit_transaction(
+            &transaction,
+            user_id,
+            credits,
+            new_balance,
+            CreditTransactionKind::Purchase,
+            Some(telegram_payment_charge_id),
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        info!(
+            "Recorded payment {} for user {}: {} ({} stars, {} credits), new balance: {}",
+            telegram_payment_charge_id, user_id, package, stars, credits, new_balance
+        );
+        Ok(Some(new_balance))
+    }
+
+    /// looks up a recorded payment by its telegram charge id
+    pub async fn get_payment(
+        &self,
+        telegram_payment_charge_id: &str,
+    ) -> Result<Option<Payment>, UserManagerError> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT p.id, p.user_id, u.telegram_user_id, p.telegram_payment_charge_id, p.stars, p.credits, r.id IS NOT NULL, COALESCE(u.language_override, u.language)
+                 FROM payments p
+                 JOIN users u ON p.user_id = u.id
+                 LEFT JOIN refunds r ON r.payment_id = p.id
+                 WHERE p.telegram_payment_charge_id = $1",
+                &[&telegram_payment_charge_id],
+            )
+            .await?;
+
+        Ok(row.map(|row| Payment {
+            id: row.get(0),
+            user_id: row.get(1),
+            telegram_user_id: row.get(2),
+            telegram_payment_charge_id: row.get(3),
+            stars: row.get(4),
+            credits: row.get(5),
+            refunded: row.get(6),
+            language: row.get(7),
+        }))
+    }
+
+    /// records an issued refund and removes the refunded credits that are still unspent,
+    /// returns the user's new balance
+    pub async fn record_refund(
+        &self,
+        payment: &Payment,
+        reason: Option<&str>,
+        refunded_by: Option<i64>,
+    ) -> Result<i32, UserManagerError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        let current_credits: i32 = transaction
+            .query_opt(
+                "SELECT analysis_credits FROM users WHERE id = $1 FOR UPDATE",
+                &[&payment.user_id],
+            )
+            .await?
+            .ok_or(UserManagerError::UserNotFound(payment.user_id))?
+            .get(0);
+
+        // credits spent on delivered analyses are not clawed back; those given back for an
+        // analysis that failed after the charge are in the balance again and are, so a
+        // payment isn't refunded in stars on top of the credits
+        let credits_removed = payment.credits.min(current_credits.max(0));
+
+        transaction
+            .execute(
+                "INSERT INTO refunds (payment_id, user_id, stars, credits_removed, reason, refunded_by) VALUES ($1, $2, $3, $4, $5, $6)",
+                &[
+                    &payment.id,
+                    &payment.user_id,
+                    &payment.stars,
+                    &credits_removed,
+                    &reason,
+                    &refunded_by,
+                ],
+            )
+            .await?;
+
+        let new_balance: i32 = transaction
+            .query_one(
+                "UPDATE users SET analysis_credits = analysis_credits - $2, updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING analysis_credits",
+                &[&payment.user_id, &credits_removed],
+            )
+            .await?
+            .get(0);
+        Self::record_credit_transaction(
+            &transaction,
+            payment.user_id,
+            -credits_removed,
+            new_balance,
+            CreditTransactionKind::Refund,
+            Some(&payment.telegram_payment_charge_id),
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        info!(
+            "Recorded refund of payment {} for user {}: {} stars, {} credits removed",
+            payment.telegram_payment_charge_id, payment.user_id, payment.stars, credits_removed
+        );
+        Ok(new_balance)
+    }
+
     /// validates that a user ID exists and can be used as a referrer
     pub async fn validate_referrer(
         &self,
@@ -514,7 +1737,8 @@ impl UserManager {
         &self,
         user_id: i32,
     ) -> Result<ReferralRewardInfo, Box<dyn Error + Send + Sync>> {
-        let client = self.pool.get().await?;
+        let rules = ReferralRules::load(&self.pool).await?;
+        let mut client = self.pool.get().await?;
 
         // get current referral counts and telegram_user_id
         let row = client
@@ -532,8 +1756,8 @@ impl UserManager {
             let mut milestone_rewards = 0;
             let mut paid_rewards = 0;
 
-            // check for milestone rewards using new pattern (1, 5, 10, 20, 30, etc.)
-            let expected_milestone_rewards = Self::calculate_milestone_rewards(referrals_count);
+            // check for milestone rewards (every milestone_every referrals)
+            let expected_milestone_rewards = rules.milestones(referrals_count);
             let existing_unpaid_rewards = client
                 .query_one(
                     "SELECT COUNT(*) FROM referral_rewards WHERE referrer_user_id = $1 AND reward_type = 'unpaid_milestone'",
@@ -542,25 +1766,19 @@ impl UserManager {
                 .await?
                 .get::<_, i64>(0) as i32;
 
-            if expected_milestone_rewards > existing_unpaid_rewards {
+            // held milestone rewards are paid here once the last flag is cleared
+            let held = referral_flags::rewards_held(&client, user_id).await?;
+            if !held && expected_milestone_rewards > existing_unpaid_rewards {
                 let new_rewards = expected_milestone_rewards - existing_unpaid_rewards;
-                milestone_rewards = new_rewards;
+                milestone_rewards = new_rewards * rules.milestone_credits;
                 for _ in 0..new_rewards {
-                    // award 1 credit for milestone
-                    client
-                        .execute(
-                            "UPDATE users SET analysis_credits = analysis_credits + 1 WHERE id = $1",
-                            &[&user_id],
-                        )
-                        .await?;
-
-                    // record the reward
-                    client
-                        .execute(
-                            "INSERT INTO referral_rewards (referrer_user_id, referee_user_id, reward_type, credits_awarded) VALUES ($1, $1, 'unpaid_milestone', 1)",
-                            &[&user_id],
-                        )
-                        .await?;
+                    Self::award_referral_reward(
+                        &mut client,
+                        user_id,
+                        "unpaid_milestone",
+                        rules.milestone_credits,
+                    )
+                    .await?;
                 }
                 info!(
                     "Awarded {} milestone rewards to user {}",
@@ -579,23 +1797,15 @@ impl UserManager {
 
             if paid_referrals_count > existing_paid_rewards {
                 let new_paid_rewards = paid_referrals_count - existing_paid_rewards;
-                paid_rewards = new_paid_rewards;
+                paid_rewards = new_paid_rewards * rules.paid_referral_credits;
                 for _ in 0..new_paid_rewards {
-                    // award 1 credit for paid referral
-                    client
-                        .execute(
-                            "UPDATE users SET analysis_credits = analysis_credits + 1 WHERE id = $1",
-                            &[&user_id],
-                        )
-                        .await?;
-
-                    // record the reward
-                    client
-                        .execute(
-                            "INSERT INTO referral_rewards (referrer_user_id, referee_user_id, reward_type, credits_awarded) VALUES ($1, $1, 'paid_user', 1)",
-                            &[&user_id],
-                        )
-                        .await?;
+                    Self::award_referral_reward(
+                        &mut client,
+                        user_id,
+                        "paid_user",
+                        rules.paid_referral_credits,
+                    )
+                    .await?;
                 }
                 info!(
                     "Awarded {} paid referral rewards to user {}",
@@ -617,7 +1827,7 @@ impl UserManager {
                 } else {
                     None
                 },
-                is_celebration_milestone: Self::is_celebration_milestone(referrals_count),
+                is_celebration_milestone: rules.is_celebration(referrals_count),
                 referral_count: referrals_count,
             })
         } else {
@@ -0,0 +1,409 @@
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::analysis::{AnalysisDepth, ForumTopic};
+use crate::cross_group::CrossGroupManager;
+use crate::error::AppError;
+use crate::localization::Lang;
+use crate::self_analysis::SelfCollection;
+
+/// a session not changed for this long is gone, in memory and in the database
+pub const SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// a session waiting this long for the user's reply is dropped and the user told so
+pub const INPUT_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// per-user conversation state kept between the channel input and the analysis type choice
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct UserSession {
+    pub channel_name: Option<String>,
+    pub focus: Option<String>,
+    pub awaiting_focus: bool,
+    pub depth: AnalysisDepth,
+    // channels of a batch request waiting for its analysis type
+    pub batch: Vec<String>,
+    // forum topic a group analysis of channel_name is limited to
+    pub topic: Option<ForumTopic>,
+    // forwards collected for a self-analysis, until the user is done forwarding
+    pub self_collection: Option<SelfCollection>,
+}
+
+impl UserSession {
+    /// whether the bot is waiting on the user to go on with the flow
+    pub fn awaiting_input(&self) -> bool {
+        self.channel_name.is_some()
+            || self.awaiting_focus
+            || !self.batch.is_empty()
+            || self.self_collection.is_some()
+    }
+
+    /// starts the session over for another channel; the same channel keeps its choices
+    fn switch_channel(&mut self, channel_name: &str) {
+        if self.channel_name.as_deref() != Some(channel_name) {
+            *self = Default::default();
+            self.channel_name = Some(channel_name.to_string());
+        }
+    }
+
+    /// the user picked an analysis depth on the channel's keyboard
+    pub fn choose_depth(&mut self, channel_name: &str, depth: AnalysisDepth) {
+        self.switch_channel(channel_name);
+        self.depth = depth;
+    }
+
+    /// the user asked to focus the channel's analysis; their next text is the instruction
+    pub fn request_focus(&mut self, channel_name: &str) {
+        self.switch_channel(channel_name);
+        self.focus = None;
+        self.awaiting_focus = true;
+    }
+
+    /// stores the focus instruction the user sent; returns the depth to offer the types with
+    pub fn set_focus(&mut self, focus: &str) -> AnalysisDepth {
+        self.focus = Some(focus.to_string());
+        self.awaiting_focus = false;
+        self.depth
+    }
+}
+
+/// sessions keyed by telegram user id, kept in memory and written through to
+/// `user_sessions`, so a restart doesn't drop users mid-flow; a session missing from
+/// memory is loaded on the user's next message, and users who had none stored cost no
+/// lookup. failed writes are logged and the in-memory session still serves until the
+/// restart
+pub struct UserSessions {
+    pool: Arc<Pool>,
+    sessions: Mutex<Sessions>,
+}
+
+#[derive(Default)]
+struct Sessions {
+    // with the time each was last changed
+    live: HashMap<i64, (UserSession, Instant)>,
+    // users whose stored session was already loaded; every later change goes through
+    // memory, so they aren't looked up again
+    loaded: HashSet<i64>,
+    // users who had a stored session when the store was first used; nobody else has one
+    // that isn't in memory. None until read
+    stored: Option<HashSet<i64>>,
+}
+
+impl UserSessions {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self {
+            pool,
+            sessions: Mutex::new(Sessions::default()),
+        }
+    }
+
+    pub async fn get(&self, telegram_user_id: i64) -> Option<UserSession> {
+        self.load(telegram_user_id).await;
+        self.sessions
+            .lock()
+            .await
+            .live
+            .get(&telegram_user_id)
+            .map(|(session, _)| session.clone())
+    }
+
+    /// replaces the user's session
+    pub async fn insert(&self, telegram_user_id: i64, session: UserSession) {
+        let mut sessions = self.sessions.lock().await;
+        sessions
+            .live
+            .insert(telegram_user_id, (session.clone(), Instant::now()));
+        sessions.loaded.insert(telegram_user_id);
+        drop(sessions);
+        self.persist(telegram_user_id, &session).await;
+    }
+
+    pub async fn remove(&self, telegram_user_id: i64) -> Option<UserSession> {
+        self.load(telegram_user_id).await;
+        let removed = self.sessions.lock().await.live.remove(&telegram_user_id);
+        if let Err(e) = self.delete(telegram_user_id).await {
+            warn!(
+                "Failed to delete the session of user {}: {}",
+                telegram_user_id, e
+            );
+        }
+        removed.map(|(session, _)| session)
+    }
+
+    /// changes the user's session, starting from an empty one if there is none
+    pub async fn update<R>(
+        &self,
+        telegram_user_id: i64,
+        change: impl FnOnce(&mut UserSession) -> R,
+    ) -> R {
+        self.load(telegram_user_id).await;
+        let (result, session) = {
+            let mut sessions = self.sessions.lock().await;
+            let (session, touched) = sessions
+                .live
+                .entry(telegram_user_id)
+                .or_insert_with(|| (UserSession::default(), Instant::now()));
+            *touched = Instant::now();
+            (change(session), session.clone())
+        };
+        self.persist(telegram_user_id, &session).await;
+        result
+    }
+
+    /// changes the user's session only if they have one
+    pub async fn update_existing<R>(
+        &self,
+        telegram_user_id: i64,
+        change: impl FnOnce(&mut UserSession) -> R,
+    ) -> Option<R> {
+        self.load(telegram_user_id).await;
+        let (result, session) = {
+            let mut sessions = self.sessions.lock().await;
+            let (session, touched) = sessions.live.get_mut(&telegram_user_id)?;
+            *touched = Instant::now();
+            (change(session), session.clone())
+        };
+        self.persist(telegram_user_id, &session).await;
+        Some(result)
+    }
+
+    /// drops expired sessions from memory and the database; returns how many rows went
+    pub async fn purge_expired(&self) -> Result<u64, AppError> {
+        self.sessions
+            .lock()
+            .await
+            .live
+            .retain(|_, (_, touched)| touched.elapsed() < SESSION_TTL);
+        let client = self.pool.get().await?;
+        Ok(client
+            .execute("DELETE FROM user_sessions WHERE expires_at <= NOW()", &[])
+            .await?)
+    }
+
+    /// drops sessions left waiting for input longer than `timeout` and queues a
+    /// "session expired" message for each of their users; returns the users
+    pub async fn expire_idle(&self, timeout: Duration) -> Result<Vec<i64>, AppError> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+        // locked so a reply arriving meanwhile waits and then starts a fresh session
+        let rows = transaction
+            .query(
+                "SELECT user_sessions.telegram_user_id, user_sessions.state, COALESCE(users.language_override, users.language)
+                 FROM user_sessions
+                 LEFT JOIN users ON users.telegram_user_id = user_sessions.telegram_user_id
+                 WHERE user_sessions.expires_at > NOW()
+                   AND user_sessions.updated_at < NOW() - make_interval(secs => $1)
+                 FOR UPDATE OF user_sessions SKIP LOCKED",
+                &[&timeout.as_secs_f64()],
+            )
+            .await?;
+
+        let mut expired = Vec::new();
+        for row in &rows {
+            let telegram_user_id: i64 = row.get(0);
+            // unreadable states are left to the purge
+            let Ok(session) = serde_json::from_value::<UserSession>(row.get(1)) else {
+                continue;
+            };
+            if !session.awaiting_input() {
+                continue;
+            }
+            let lang = Lang::from_code(row.get::<_, Option<&str>>(2));
+            transaction
+                .execute(
+                    "DELETE FROM user_sessions WHERE telegram_user_id = $1",
+                    &[&telegram_user_id],
+                )
+                .await?;
+            transaction
+                .execute(
+                    "INSERT INTO message_queue (telegram_user_id, message, parse_mode) VALUES ($1, $2, $3)",
+                    &[&telegram_user_id, &lang.session_expired(), &"HTML"],
+                )
+                .await?;
+            expired.push(telegram_user_id);
+        }
+        transaction.commit().await?;
+
+        let mut sessions = self.sessions.lock().await;
+        for telegram_user_id in &expired {
+            if sessions
+                .live
+                .get(telegram_user_id)
+                .is_some_and(|(_, touched)| touched.elapsed() >= timeout)
+            {
+                sessions.live.remove(telegram_user_id);
+            }
+        }
+        Ok(expired)
+    }
+
+    /// brings the stored session into memory the first time the user shows up after a
+    /// restart, and forgets the one in memory once it expired
+    async fn load(&self, telegram_user_id: i64) {
+        {
+            let mut sessions = self.sessions.lock().await;
+            if sessions
+                .live
+                .get(&telegram_user_id)
+                .is_some_and(|(_, touched)| touched.elapsed() >= SESSION_TTL)
+            {
+                sessions.live.remove(&telegram_user_id);
+            }
+            if sessions.loaded.contains(&telegram_user_id)
+                || sessions
+                    .stored
+                    .as_ref()
+                    .is_some_and(|stored| !stored.contains(&telegram_user_id))
+            {
+                return;
+            }
+        }
+
+        if self.sessions.lock().await.stored.is_none() {
+            match self.fetch_stored_users().await {
+                Ok(users) => {
+                    let mut sessions = self.sessions.lock().await;
+                    if !sessions
+                        .stored
+                        .get_or_insert(users)
+                        .contains(&telegram_user_id)
+                    {
+                        return;
+                    }
+                }
+                // the user's own session is looked up instead
+                Err(e) => warn!("Failed to list stored sessions: {}", e),
+            }
+        }
+
+        let stored = match self.fetch(telegram_user_id).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                // tried again on the next message
+                warn!(
+                    "Failed to load the session of user {}: {}",
+                    telegram_user_id, e
+                );
+                return;
+            }
+        };
+        let mut sessions = self.sessions.lock().await;
+        sessions.loaded.insert(telegram_user_id);
+        if let Some((session, age)) = stored {
+            // a session set meanwhile is newer than the stored one
+            let touched = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+            sessions
+                .live
+                .entry(telegram_user_id)
+                .or_insert((session, touched));
+        }
+    }
+
+    async fn fetch_stored_users(&self) -> Result<HashSet<i64>, AppError> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT telegram_user_id FROM user_sessions WHERE expires_at > NOW()",
+                &[],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    // the stored session and how long ago it was last written
+    async fn fetch(
+        &self,
+        telegram_user_id: i64,
+    ) -> Result<Option<(UserSession, Duration)>, AppError> {
+        let client = self.pool.get().await?;
+        let Some(row) = client
+            .query_opt(
+                "SELECT state, EXTRACT(EPOCH FROM NOW() - updated_at)::float8
+                 FROM user_sessions WHERE telegram_user_id = $1 AND expires_at > NOW()",
+                &[&telegram_user_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        // a state the current code can't read is dropped like an expired one
+        let session = match serde_json::from_value(row.get(0)) {
+            Ok(session) => session,
+            Err(e) => {
+                warn!(
+                    "Dropping the unreadable session of user {}: {}",
+                    telegram_user_id, e
+                );
+                return Ok(None);
+            }
+        };
+        let age = Duration::from_secs_f64(row.get::<_, f64>(1).max(0.0));
+        Ok(Some((session, age)))
+    }
+
+    async fn persist(&self, telegram_user_id: i64, session: &UserSession) {
+        if let Err(e) = self.store(telegram_user_id, session).await {
+            warn!(
+                "Failed to store the session of user {}: {}",
+                telegram_user_id, e
+            );
+        }
+    }
+
+    async fn store(&self, telegram_user_id: i64, session: &UserSession) -> Result<(), AppError> {
+        let state = serde_json::to_value(session)
+            .map_err(|e| AppError::Validation(format!("Unserializable session: {}", e)))?;
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO user_sessions (telegram_user_id, state, updated_at, expires_at)
+                 VALUES ($1, $2, NOW(), NOW() + make_interval(secs => $3))
+                 ON CONFLICT (telegram_user_id) DO UPDATE
+                 SET state = EXCLUDED.state, updated_at = NOW(), expires_at = EXCLUDED.expires_at",
+                &[&telegram_user_id, &state, &SESSION_TTL.as_secs_f64()],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, telegram_user_id: i64) -> Result<(), AppError> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "DELETE FROM user_sessions WHERE telegram_user_id = $1",
+                &[&telegram_user_id],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// every few minutes, expires sessions idle past the input timeout, purges expired ones and
+/// prunes group messages no profile reads anymore, until the process exits
+pub async fn run_session_purger(sessions: Arc<UserSessions>, cross_group: Arc<CrossGroupManager>) {
+    let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        match sessions.expire_idle(INPUT_TIMEOUT).await {
+            Ok(expired) if expired.is_empty() => {}
+            Ok(expired) => info!("Expired {} sessions waiting for input", expired.len()),
+            Err(e) => error!("Failed to expire idle user sessions: {}", e),
+        }
+        match sessions.purge_expired().await {
+            Ok(0) => {}
+            Ok(purged) => info!("Purged {} expired user sessions", purged),
+            Err(e) => error!("Failed to purge expired user sessions: {}", e),
+        }
+        match cross_group.prune().await {
+            Ok(0) => {}
+            Ok(pruned) => info!("Pruned {} old group messages", pruned),
+            Err(e) => error!("Failed to prune old group messages: {}", e),
+        }
+    }
+}
@@ -1,6 +1,17 @@
-use comrak::{markdown_to_html, ComrakOptions};
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{markdown_to_html, parse_document, Arena, ComrakOptions};
 use html_escape;
 
+// characters MarkdownV2 reserves outside of entities, all of them must be escaped
+const MARKDOWN_V2_RESERVED: &str = "_*[]()~`>#+-=|{}.!\\";
+
+/// the markup a message is written in, so splitting can keep its entities whole
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Markup {
+    Html,
+    MarkdownV2,
+}
+
 pub struct MessageFormatter;
 
 impl MessageFormatter {
@@ -9,15 +20,50 @@ impl MessageFormatter {
         html_escape::encode_text(text).to_string()
     }
 
-    pub fn markdown_to_html_safe(text: &str) -> String {
-        // convert markdown to HTML with Telegram-compatible options
+    /// the text of an HTML message without its tags, character entities decoded
+    pub fn strip_html(html: &str) -> String {
+        let mut text = String::with_capacity(html.len());
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            text.push_str(&rest[..start]);
+            rest = rest[start..]
+                .find('>')
+                .map_or("", |end| &rest[start + end + 1..]);
+        }
+        text.push_str(rest);
+        html_escape::decode_html_entities(&text).to_string()
+    }
+
+    /// escapes text for Telegram's MarkdownV2 outside of entities
+    pub fn escape_markdown_v2(text: &str) -> String {
+        Self::escape_with(text, MARKDOWN_V2_RESERVED)
+    }
+
+    // inside code and pre entities only ` and \ are reserved, inside link urls ) and \
+    fn escape_with(text: &str, reserved: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if reserved.contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    // the markdown dialect LLMs write, with Telegram-compatible options
+    pub(crate) fn markdown_options() -> ComrakOptions<'static> {
         let mut options = ComrakOptions::default();
         options.extension.strikethrough = true;
         options.extension.autolink = true;
         options.render.hardbreaks = true;
         options.render.unsafe_ = false;
+        options
+    }
 
-        let html = markdown_to_html(text, &options);
+    pub fn markdown_to_html_safe(text: &str) -> String {
+        // convert markdown to HTML with Telegram-compatible options
+        let html = markdown_to_html(text, &Self::markdown_options());
 
         // telegram HTML mode only supports: b, i, u, s, code, pre, a
         // replace unsupported tags with supported ones or remove them
@@ -61,8 +107,118 @@ impl MessageFormatter {
             .replace("<hr/>", "\n───────────\n")
             .replace("<hr />", "\n───────────\n");
 
-        // clean up excessive whitespace
-        let lines: Vec<&str> = html.lines().collect();
+        Self::collapse_blank_lines(&html)
+    }
+
+    /// converts markdown to Telegram MarkdownV2, with the same mapping of unsupported
+    /// elements as markdown_to_html_safe and every reserved character escaped
+    pub fn markdown_to_markdown_v2(text: &str) -> String {
+        let arena = Arena::new();
+        let root = parse_document(&arena, text, &Self::markdown_options());
+        let mut rendered = String::new();
+        Self::render_markdown_v2(root, &mut rendered, false);
+        Self::collapse_blank_lines(&rendered)
+    }
+
+    // `tight` is set inside list items, whose paragraphs aren't separated by blank lines
+    fn render_markdown_v2<'a>(node: &'a AstNode<'a>, out: &mut String, tight: bool) {
+        let children = |out: &mut String, tight: bool| {
+            for child in node.children() {
+                Self::render_markdown_v2(child, out, tight);
+            }
+        };
+        // entities can't nest in themselves, so an inner marker of the same kind is dropped
+        let wrap = |out: &mut String, marker: &str| {
+            let nested = node.ancestors().skip(1).any(|ancestor| {
+                Self::markdown_v2_marker(&ancestor.data.borrow().value) == Some(marker)
+            });
+            if !nested {
+                out.push_str(marker);
+            }
+            for child in node.children() {
+                Self::render_markdown_v2(child, out, tight);
+            }
+            if !nested {
+                out.push_str(marker);
+            }
+        };
+
+        let value = node.data.borrow().value.clone();
+        match value {
+            NodeValue::Text(text) => out.push_str(&Self::escape_markdown_v2(&text)),
+            NodeValue::SoftBreak | NodeValue::LineBreak => out.push('\n'),
+            NodeValue::Paragraph => {
+                children(out, tight);
+                out.push_str(if tight { "\n" } else { "\n\n" });
+            }
+            NodeValue::Heading(heading) => {
+                wrap(out, "*");
+                out.push_str(if heading.level <= 2 { "\n\n" } else { "\n" });
+            }
+            NodeValue::Strong => wrap(out, "*"),
+            NodeValue::Emph => wrap(out, "_"),
+            NodeValue::Strikethrough => wrap(out, "~"),
+            NodeValue::Code(code) => {
+                out.push('`');
+                out.push_str(&Self::escape_with(&code.literal, "`\\"));
+                out.push('`');
+            }
+            NodeValue::CodeBlock(block) => {
+                out.push_str("```\n");
+                out.push_str(&Self::escape_with(&block.literal, "`\\"));
+                out.push_str("```\n\n");
+            }
+            NodeValue::Link(link) | NodeValue::Image(link) => {
+                out.push('[');
+                children(out, tight);
+                out.push_str("](");
+                out.push_str(&Self::escape_with(&link.url, ")\\"));
+                out.push(')');
+            }
+            NodeValue::List(list) => {
+                for (number, item) in (list.start..).zip(node.children()) {
+                    match list.list_type {
+                        ListType::Bullet => out.push_str("• "),
+                        ListType::Ordered => out.push_str(&format!("{}\\. ", number)),
+                    }
+                    let mut rendered = String::new();
+                    for child in item.children() {
+                        Self::render_markdown_v2(child, &mut rendered, true);
+                    }
+                    out.push_str(rendered.trim_end());
+                    out.push('\n');
+                }
+                out.push('\n');
+            }
+            NodeValue::BlockQuote => {
+                let mut quoted = String::new();
+                children(&mut quoted, tight);
+                for line in quoted.trim_end().lines() {
+                    out.push('>');
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                out.push('\n');
+            }
+            NodeValue::ThematicBreak => out.push_str("\n───────────\n"),
+            // raw html is dropped, as in the html path
+            NodeValue::HtmlBlock(_) | NodeValue::HtmlInline(_) => {}
+            _ => children(out, tight),
+        }
+    }
+
+    fn markdown_v2_marker(value: &NodeValue) -> Option<&'static str> {
+        match value {
+            NodeValue::Strong | NodeValue::Heading(_) => Some("*"),
+            NodeValue::Emph => Some("_"),
+            NodeValue::Strikethrough => Some("~"),
+            _ => None,
+        }
+    }
+
+    // trims every line and allows at most one blank line in a row
+    fn collapse_blank_lines(text: &str) -> String {
+        let lines: Vec<&str> = text.lines().collect();
         let mut result = Vec::new();
         let mut empty_line_count = 0;
 
@@ -88,63 +244,282 @@ impl MessageFormatter {
         text.encode_utf16().count()
     }
 
-    /// splits a message into chunks that fit within Telegram's 4096 UTF-16 code unit limit
+    /// splits an HTML message into chunks that fit within Telegram's 4096 UTF-16 code unit
+    /// limit; tags open at a boundary are closed at the end of the chunk and reopened at the
+    /// start of the next one, and no tag or character entity is cut in half
     pub fn split_message_into_chunks(text: &str, max_length: usize) -> Vec<String> {
+        Self::split_into_chunks(text, max_length, Markup::Html)
+    }
+
+    /// like split_message_into_chunks, for MarkdownV2 entities and escapes
+    pub fn split_markdown_v2_into_chunks(text: &str, max_length: usize) -> Vec<String> {
+        Self::split_into_chunks(text, max_length, Markup::MarkdownV2)
+    }
+
+    fn split_into_chunks(text: &str, max_length: usize, markup: Markup) -> Vec<String> {
         if Self::count_utf16_code_units(text) <= max_length {
             return vec![text.to_string()];
         }
 
-        let mut chunks = Vec::new();
-        let mut current_chunk = String::new();
-
+        let mut chunks = Chunks::new(max_length, markup);
         // split by lines to avoid breaking in the middle of formatting
         for line in text.lines() {
             let line_with_newline = format!("{}\n", line);
+            if !chunks.fits(&line_with_newline) {
+                chunks.finish_chunk();
+            }
+            if chunks.fits(&line_with_newline) {
+                chunks.push(&line_with_newline);
+                continue;
+            }
 
-            // if adding this line would exceed the limit, finalize current chunk
-            if Self::count_utf16_code_units(&current_chunk)
-                + Self::count_utf16_code_units(&line_with_newline)
-                > max_length
-            {
-                if !current_chunk.is_empty() {
-                    chunks.push(current_chunk.trim_end().to_string());
-                    current_chunk.clear();
+            // if single line is too long, split it at word boundaries
+            for unit in markup_units(line, markup) {
+                let blank = unit.trim().is_empty();
+                if blank && chunks.is_empty() {
+                    continue;
                 }
-
-                // if single line is too long, split it at word boundaries
-                if Self::count_utf16_code_units(&line_with_newline) > max_length {
-                    let words: Vec<&str> = line.split_whitespace().collect();
-                    let mut word_chunk = String::new();
-
-                    for word in words {
-                        let word_with_space = format!("{} ", word);
-                        if Self::count_utf16_code_units(&word_chunk)
-                            + Self::count_utf16_code_units(&word_with_space)
-                            > max_length
-                        {
-                            if !word_chunk.is_empty() {
-                                chunks.push(word_chunk.trim_end().to_string());
-                                word_chunk.clear();
-                            }
-                        }
-                        word_chunk.push_str(&word_with_space);
+                if !chunks.fits(unit) {
+                    chunks.finish_chunk();
+                    if blank {
+                        continue;
                     }
-
-                    if !word_chunk.is_empty() {
-                        current_chunk = word_chunk.trim_end().to_string();
+                }
+                let whole = unit.len() > 1
+                    && match markup {
+                        Markup::Html => unit.starts_with('<'),
+                        Markup::MarkdownV2 => unit.starts_with('['),
+                    };
+                if chunks.fits(unit) || whole {
+                    // tags and links longer than a chunk can't be split at all
+                    chunks.push(unit);
+                    continue;
+                }
+                // a word longer than a whole chunk is split between its characters
+                for atom in markup_atoms(unit, markup) {
+                    if !chunks.fits(atom) {
+                        chunks.finish_chunk();
                     }
-                } else {
-                    current_chunk.push_str(&line_with_newline);
+                    chunks.push(atom);
                 }
-            } else {
-                current_chunk.push_str(&line_with_newline);
             }
+            chunks.push("\n");
+        }
+        chunks.finish()
+    }
+}
+
+/// chunks of a message being split, with the entities open at the end of the current one
+struct Chunks {
+    max_length: usize,
+    markup: Markup,
+    chunks: Vec<String>,
+    current: String,
+    current_length: usize,
+    has_content: bool,
+    // the text opening each entity and the text closing it, outermost first
+    open: Vec<(String, String)>,
+}
+
+impl Chunks {
+    fn new(max_length: usize, markup: Markup) -> Self {
+        Self {
+            max_length,
+            markup,
+            chunks: Vec::new(),
+            current: String::new(),
+            current_length: 0,
+            has_content: false,
+            open: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.has_content
+    }
+
+    /// whether the piece fits into the current chunk along with the closing of every entity
+    /// still open after it
+    fn fits(&self, piece: &str) -> bool {
+        let mut open = self.open.clone();
+        track_entities(piece, self.markup, &mut open);
+        let closing = open
+            .iter()
+            .map(|(_, close)| MessageFormatter::count_utf16_code_units(close))
+            .sum::<usize>();
+        self.current_length + MessageFormatter::count_utf16_code_units(piece) + closing
+            <= self.max_length
+    }
+
+    fn push(&mut self, piece: &str) {
+        self.current.push_str(piece);
+        self.current_length += MessageFormatter::count_utf16_code_units(piece);
+        self.has_content |= !piece.trim().is_empty();
+        track_entities(piece, self.markup, &mut self.open);
+    }
+
+    /// closes the open entities of the current chunk and reopens them in the next one
+    fn finish_chunk(&mut self) {
+        if !self.has_content {
+            return;
+        }
+        let mut chunk = self.current.trim_end().to_string();
+        for (_, close) in self.open.iter().rev() {
+            chunk.push_str(close);
+        }
+        self.chunks.push(chunk);
+
+        self.current = self.open.iter().map(|(open, _)| open.as_str()).collect();
+        self.current_length = MessageFormatter::count_utf16_code_units(&self.current);
+        self.has_content = false;
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        self.finish_chunk();
+        self.chunks
+    }
+}
+
+// the pieces a line may be split between: whitespace, words, and tags or links, which are
+// never split
+fn markup_units(line: &str, markup: Markup) -> Vec<&str> {
+    let mut units = Vec::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_whitespace() {
+            c.len_utf8()
+        } else if markup == Markup::Html && c == '<' {
+            rest.find('>').map_or(rest.len(), |end| end + 1)
+        } else if markup == Markup::MarkdownV2 && c == '[' {
+            markdown_v2_link_length(rest).unwrap_or(1)
+        } else {
+            word_length(rest, markup)
+        };
+        units.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    units
+}
+
+// a word runs until whitespace or a tag; a MarkdownV2 escape always keeps its character
+fn word_length(text: &str, markup: Markup) -> usize {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if i > 0 && (c.is_whitespace() || (markup == Markup::Html && c == '<')) {
+            return i;
+        }
+        if markup == Markup::MarkdownV2 && c == '\\' {
+            chars.next();
+        }
+    }
+    text.len()
+}
+
+// the length of the `[text](url)` link the text starts with
+fn markdown_v2_link_length(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices();
+    let mut in_url = false;
+    let mut previous = None;
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '(' if previous == Some(']') => in_url = true,
+            ')' if in_url => return Some(i + 1),
+            '\n' => return None,
+            _ => {}
         }
+        previous = Some(c);
+    }
+    None
+}
+
+// the characters of an overlong word, keeping character entities and escapes whole
+fn markup_atoms(word: &str, markup: Markup) -> Vec<&str> {
+    let mut atoms = Vec::new();
+    let mut rest = word;
+    while let Some(c) = rest.chars().next() {
+        let len = match (markup, c) {
+            (Markup::Html, '&') => rest
+                .find(';')
+                .filter(|end| *end <= 10)
+                .map_or(1, |end| end + 1),
+            (Markup::MarkdownV2, '\\') => 1 + rest[1..].chars().next().map_or(0, char::len_utf8),
+            _ => c.len_utf8(),
+        };
+        atoms.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    atoms
+}
+
+// updates the entities open after `piece` was appended
+fn track_entities(piece: &str, markup: Markup, open: &mut Vec<(String, String)>) {
+    match markup {
+        Markup::Html => track_html_tags(piece, open),
+        Markup::MarkdownV2 => track_markdown_v2_entities(piece, open),
+    }
+}
 
-        if !current_chunk.is_empty() {
-            chunks.push(current_chunk.trim_end().to_string());
+fn track_html_tags(piece: &str, open: &mut Vec<(String, String)>) {
+    let mut rest = piece;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            return;
+        };
+        let tag = &rest[start..start + end + 1];
+        rest = &rest[start + end + 1..];
+        if let Some(name) = tag.strip_prefix("</") {
+            let close = format!("</{}", name);
+            if let Some(index) = open.iter().rposition(|(_, c)| *c == close) {
+                open.remove(index);
+            }
+        } else {
+            let name = tag[1..tag.len() - 1]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+            open.push((tag.to_string(), format!("</{}>", name)));
         }
+    }
+}
 
-        chunks
+fn track_markdown_v2_entities(piece: &str, open: &mut Vec<(String, String)>) {
+    let toggle = |open: &mut Vec<(String, String)>, reopen: &str, close: &str| match open
+        .iter()
+        .rposition(|(_, c)| c == close)
+    {
+        Some(index) => {
+            open.remove(index);
+        }
+        None => open.push((reopen.to_string(), close.to_string())),
+    };
+    let mut rest = piece;
+    while let Some(c) = rest.chars().next() {
+        let in_code = open.last().is_some_and(|(_, close)| close.ends_with('`'));
+        let mut len = c.len_utf8();
+        if c == '\\' {
+            len += rest[1..].chars().next().map_or(0, char::len_utf8);
+        } else if rest.starts_with("```") {
+            toggle(open, "```\n", "\n```");
+            len = 3;
+        } else if c == '`' {
+            toggle(open, "`", "`");
+        } else if in_code {
+            // only backticks end code, every other marker is literal there
+        } else if rest.starts_with("](") {
+            // a link's url is never formatted
+            len = markdown_v2_link_length(&format!("[{}", rest)).map_or(2, |link| link - 1);
+        } else if rest.starts_with("||") {
+            toggle(open, "||", "||");
+            len = 2;
+        } else if rest.starts_with("__") {
+            toggle(open, "__", "__");
+            len = 2;
+        } else if let Some(marker) = ["*", "_", "~"].into_iter().find(|m| rest.starts_with(m)) {
+            toggle(open, marker, marker);
+        }
+        rest = &rest[len.min(rest.len())..];
     }
 }
@@ -1,3 +1,9 @@
 pub mod message_formatter;
+pub mod result_presenter;
+pub mod stats_card;
+pub mod summary;
 
 pub use message_formatter::MessageFormatter;
+pub use result_presenter::ResultPresenter;
+pub use stats_card::StatsCard;
+pub use summary::SummaryGenerator;
@@ -0,0 +1,295 @@
+use crate::analysis::{invite_hash, is_cross_group_corpus, is_self_corpus, roast_battle_members};
+use crate::cache::AnalysisResult;
+use crate::facts::QuickFacts;
+use crate::localization::Lang;
+use crate::telegraph::{html_to_paragraphs, markdown_to_nodes};
+use crate::utils::{MessageFormatter, SummaryGenerator};
+use serde_json::Value;
+
+// stay well below Telegram's 4096 limit, leaving room for part indicators
+const MAX_MESSAGE_LENGTH: usize = 3584;
+const PART_INDICATOR_BUFFER: usize = 100;
+
+// opening of a result published as a page, sent along with the link
+const WEB_PAGE_EXCERPT_CHARS: usize = 500;
+
+// the quick facts under the header keep to what fits on a line or two
+const HEADER_KEYWORDS: usize = 5;
+const HEADER_HASHTAGS: usize = 3;
+
+/// renders analysis results into Telegram-sized HTML messages for any chat target
+pub struct ResultPresenter;
+
+impl ResultPresenter {
+    /// returns the analysis content for the given type, if it was generated
+    pub fn content_for<'a>(result: &'a AnalysisResult, analysis_type: &str) -> Option<&'a str> {
+        let content = match analysis_type {
+            "professional" => &result.professional,
+            "personal" => &result.personal,
+            "roast" => &result.roast,
+            "trends" => &result.trends,
+            _ => &None,
+        };
+        content.as_deref().filter(|c| !c.is_empty())
+    }
+
+    /// escaped name of what was analyzed, as shown to users
+    pub fn target_label(channel_name: &str, lang: Lang) -> String {
+        if let Some((first, second)) = roast_battle_members(channel_name) {
+            lang.roast_battle_target(
+                &MessageFormatter::escape_html(first),
+                &MessageFormatter::escape_html(second),
+            )
+        } else if is_cross_group_corpus(channel_name) {
+            lang.cross_group_target().to_string()
+        } else if is_self_corpus(channel_name) {
+            lang.self_analysis_target().to_string()
+        } else if invite_hash(channel_name).is_some() {
+            lang.private_channel_target().to_string()
+        } else {
+            MessageFormatter::escape_html(channel_name)
+        }
+    }
+
+    /// renders one analysis type into ready-to-send messages, each carrying the full header;
+    /// returns None when the result has no content for that type
+    pub fn render(
+        result: &AnalysisResult,
+        analysis_type: &str,
+        channel_name: &str,
+        user_id: i32,
+        lang: Lang,
+    ) -> Option<Vec<String>> {
+        let header = Self::header(result, channel_name, user_id, lang);
+        Self::render_with_header(result, analysis_type, header, lang)
+    }
+
+    /// the same messages as render in MarkdownV2, under a header without formatting; sent
+    /// when Telegram refuses to parse the HTML ones
+    pub fn render_markdown_v2(
+        result: &AnalysisResult,
+        analysis_type: &str,
+        channel_name: &str,
+        user_id: i32,
+        lang: Lang,
+    ) -> Option<Vec<String>> {
+        let content = Self::content_for(result, analysis_type)?;
+        let plain =
+            |html: &str| MessageFormatter::escape_markdown_v2(&MessageFormatter::strip_html(html));
+
+        let mut markdown_content = MessageFormatter::markdown_to_markdown_v2(content);
+        if result.partial {
+            markdown_content.insert_str(0, &plain(lang.analysis_partial_label()));
+        }
+        if let Some(highlights) = Self::report_highlights(result, analysis_type, lang) {
+            markdown_content.push_str(&plain(&highlights));
+        }
+
+        Some(Self::assemble(
+            &plain(&Self::header(result, channel_name, user_id, lang)),
+            &plain(&lang.analysis_type_header(analysis_type)),
+            &markdown_content,
+            |part, total| plain(&lang.analysis_part_indicator(part, total)),
+            MessageFormatter::split_markdown_v2_into_chunks,
+        ))
+    }
+
+    /// title and telegra.ph content of a result published as a page; returns None when the
+    /// result has no content for that type
+    pub fn render_web_page(
+        result: &AnalysisResult,
+        analysis_type: &str,
+        channel_name: &str,
+        lang: Lang,
+    ) -> Option<(String, Vec<Value>)> {
+        let content = Self::content_for(result, analysis_type)?;
+        let title = format!(
+            "{} — {}",
+            MessageFormatter::strip_html(&lang.analysis_type_header(analysis_type))
+                .trim()
+                .trim_end_matches(':'),
+            MessageFormatter::strip_html(&Self::target_label(channel_name, lang))
+        );
+
+        let mut nodes = Vec::new();
+        if let Some(facts) = &result.facts {
+            nodes.extend(html_to_paragraphs(&Self::quick_facts(facts, lang)));
+        }
+        if result.partial {
+            nodes.extend(html_to_paragraphs(lang.analysis_partial_label()));
+        }
+        nodes.extend(markdown_to_nodes(content));
+        if let Some(highlights) = Self::report_highlights(result, analysis_type, lang) {
+            nodes.extend(html_to_paragraphs(&highlights));
+        }
+        Some((title, nodes))
+    }
+
+    /// the message sent in place of a result published at `url`: the usual headers over the
+    /// opening of the analysis and the link
+    pub fn render_web_page_summary(
+        result: &AnalysisResult,
+        analysis_type: &str,
+        channel_name: &str,
+        user_id: i32,
+        url: &str,
+        lang: Lang,
+    ) -> Option<String> {
+        let content = Self::content_for(result, analysis_type)?;
+        let excerpt = SummaryGenerator::excerpt(content, WEB_PAGE_EXCERPT_CHARS);
+        Some(format!(
+            "{}{}{}",
+            Self::header(result, channel_name, user_id, lang),
+            lang.analysis_type_header(analysis_type),
+            lang.analysis_web_page(&MessageFormatter::escape_html(&excerpt), url)
+        ))
+    }
+
+    fn header(result: &AnalysisResult, channel_name: &str, user_id: i32, lang: Lang) -> String {
+        // a roast battle is about two people, not a channel
+        let mut header = match roast_battle_members(channel_name) {
+            Some((first, second)) => lang.roast_battle_result_header(
+                &MessageFormatter::escape_html(first),
+                &MessageFormatter::escape_html(second),
+                user_id,
+            ),
+            None => {
+                lang.analysis_result_header(&Self::target_label(channel_name, lang), user_id)
+            }
+        };
+        if let Some(facts) = &result.facts {
+            header.push_str(&Self::quick_facts(facts, lang));
+        }
+        header
+    }
+
+    // the quick facts line of a result, empty when nothing was counted
+    fn quick_facts(facts: &QuickFacts, lang: Lang) -> String {
+        let list = |items: &[(String, usize)], limit: usize| {
+            let names: Vec<&str> = items
+                .iter()
+                .take(limit)
+                .map(|(name, _)| name.as_str())
+                .collect();
+            MessageFormatter::escape_html(&names.join(", "))
+        };
+        lang.quick_facts(
+            &list(&facts.keywords, HEADER_KEYWORDS),
+            &list(&facts.hashtags, HEADER_HASHTAGS),
+            facts.busiest_weekday(),
+        )
+    }
+
+    /// like render, under a header of the caller's choosing
+    pub fn render_with_header(
+        result: &AnalysisResult,
+        analysis_type: &str,
+        header: String,
+        lang: Lang,
+    ) -> Option<Vec<String>> {
+        let content = Self::content_for(result, analysis_type)?;
+
+        // convert LLM markdown content to HTML first
+        let mut html_content = MessageFormatter::markdown_to_html_safe(content);
+        if result.partial {
+            html_content.insert_str(0, lang.analysis_partial_label());
+        }
+        if let Some(highlights) = Self::report_highlights(result, analysis_type, lang) {
+            html_content.push_str(&highlights);
+        }
+
+        Some(Self::assemble(
+            &header,
+            &lang.analysis_type_header(analysis_type),
+            &html_content,
+            |part, total| lang.analysis_part_indicator(part, total),
+            MessageFormatter::split_message_into_chunks,
+        ))
+    }
+
+    // splits the content into messages that each carry both headers
+    fn assemble(
+        header: &str,
+        analysis_header: &str,
+        content: &str,
+        part_indicator: impl Fn(usize, usize) -> String,
+        split: fn(&str, usize) -> Vec<String>,
+    ) -> Vec<String> {
+        // calculate available space for content after headers (using UTF-16 code units as Telegram does)
+        let headers_length = MessageFormatter::count_utf16_code_units(header)
+            + MessageFormatter::count_utf16_code_units(analysis_header);
+        let available_content_length =
+            MAX_MESSAGE_LENGTH.saturating_sub(headers_length + PART_INDICATOR_BUFFER);
+
+        let content_chunks = split(content, available_content_length);
+        let total = content_chunks.len();
+
+        content_chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                if total > 1 {
+                    format!(
+                        "{}{}{}{}",
+                        header,
+                        analysis_header,
+                        chunk,
+                        part_indicator(i + 1, total)
+                    )
+                } else {
+                    format!("{}{}{}", header, analysis_header, chunk)
+                }
+            })
+            .collect()
+    }
+
+    /// renders one analysis type as a standalone markdown document, for reports saved to
+    /// files; returns None when the result has no content for that type
+    pub fn markdown(
+        result: &AnalysisResult,
+        analysis_type: &str,
+        channel_name: &str,
+    ) -> Option<String> {
+        let content = Self::content_for(result, analysis_type)?;
+        let mut type_name = analysis_type.to_string();
+        if let Some(first) = type_name.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+
+        let mut report = format!("# {} analysis of {}\n\n", type_name, channel_name);
+        if result.partial {
+            report
+                .push_str("> The answer was cut short, parts of the analysis may be missing.\n\n");
+        }
+        report.push_str(content.trim());
+        report.push_str(&format!(
+            "\n\n---\n\nBased on {} messages.\n",
+            result.messages_count
+        ));
+        Some(report)
+    }
+
+    /// structured highlights for json mode results, None for tagged ones and the roast
+    fn report_highlights(
+        result: &AnalysisResult,
+        analysis_type: &str,
+        lang: Lang,
+    ) -> Option<String> {
+        let report = result.report.as_ref()?;
+        let list = |items: &[String]| MessageFormatter::escape_html(&items.join(", "));
+        match analysis_type {
+            "professional" if !report.strengths.is_empty() || !report.weaknesses.is_empty() => {
+                Some(lang.report_professional_highlights(
+                    &list(&report.strengths),
+                    &list(&report.weaknesses),
+                    &report.scores,
+                ))
+            }
+            "personal" if !report.topics.is_empty() => Some(lang.report_personal_highlights(
+                &MessageFormatter::escape_html(&report.tone),
+                &list(&report.topics),
+            )),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use image::{ImageFormat, Rgb, RgbImage};
+use std::io::Cursor;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 420;
+const MARGIN: u32 = 40;
+const BACKGROUND: Rgb<u8> = Rgb([24, 28, 48]);
+const FOREGROUND: Rgb<u8> = Rgb([240, 240, 245]);
+const MUTED: Rgb<u8> = Rgb([150, 156, 180]);
+const ACCENT: Rgb<u8> = Rgb([255, 196, 64]);
+
+// glyphs are 5x7 pixels plus one pixel of spacing, scaled up when drawn
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+/// a user's referral numbers drawn as a png they can share to promote their link
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsCard {
+    pub referrals: i32,
+    pub paid_referrals: i32,
+    pub this_week: i64,
+    // place on this week's leaderboard, None without referrals this week
+    pub rank: Option<i64>,
+    pub link: String,
+}
+
+impl StatsCard {
+    /// the card as png bytes; the bitmap font only has latin capitals, digits and url
+    /// punctuation, so the card is in english whatever the user's language
+    pub fn render_png(&self) -> Result<Vec<u8>, image::ImageError> {
+        let mut image = RgbImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+        fill_rect(&mut image, 0, 0, WIDTH, 8, ACCENT);
+
+        draw_text(&mut image, "REFERRAL STATS", MARGIN, MARGIN, 5, FOREGROUND);
+
+        let columns = [
+            (self.referrals.to_string(), "INVITED"),
+            (self.paid_referrals.to_string(), "PAID"),
+            (self.this_week.to_string(), "THIS WEEK"),
+        ];
+        let column_width = (WIDTH - 2 * MARGIN) / columns.len() as u32;
+        for (i, (value, label)) in columns.iter().enumerate() {
+            let x = MARGIN + i as u32 * column_width;
+            draw_text(&mut image, value, x, 120, 10, ACCENT);
+            draw_text(&mut image, label, x, 210, 3, MUTED);
+        }
+
+        if let Some(rank) = self.rank {
+            let rank = format!("RANK #{rank} THIS WEEK");
+            draw_text(&mut image, &rank, MARGIN, 270, 4, FOREGROUND);
+        }
+
+        // drop the scheme, it only costs width
+        let link = self
+            .link
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        let link_y = HEIGHT - MARGIN - GLYPH_HEIGHT * 3;
+        draw_text(&mut image, link, MARGIN, link_y, 3, ACCENT);
+
+        let mut png = Vec::new();
+        image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+        Ok(png)
+    }
+}
+
+fn fill_rect(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// draws `text` with its top left corner at `x`, `y`; whatever runs past the edge is cut
+fn draw_text(image: &mut RgbImage, text: &str, x: u32, y: u32, scale: u32, color: Rgb<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i as u32 * GLYPH_ADVANCE * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    let top = y + row as u32 * scale;
+                    fill_rect(image, left + column * scale, top, scale, scale, color);
+                }
+            }
+        }
+    }
+}
+
+/// rows of a 5x7 glyph, most significant bit on the left; lowercase is drawn as
+/// uppercase and characters without a glyph as a question mark
+#[rustfmt::skip]
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11110, 0b00001, 0b00001, 0b01110, 0b00001, 0b00001, 0b11110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        ' ' => [0; 7],
+        '.' => [0, 0, 0, 0, 0, 0b01100, 0b01100],
+        '/' => [0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000],
+        ':' => [0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0],
+        '=' => [0, 0, 0b11111, 0, 0b11111, 0, 0],
+        '#' => [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010],
+        '@' => [0b01110, 0b10001, 0b10111, 0b10101, 0b10111, 0b10000, 0b01110],
+        '-' => [0, 0, 0, 0b11111, 0, 0, 0],
+        '_' => [0, 0, 0, 0, 0, 0, 0b11111],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0, 0b00100],
+    }
+}
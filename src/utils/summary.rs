@@ -0,0 +1,49 @@
+/// builds short plain-text excerpts of analysis content for share cards
+pub struct SummaryGenerator;
+
+impl SummaryGenerator {
+    /// returns the opening of the analysis as plain text, cut at a sentence
+    /// boundary when possible and at a word boundary otherwise
+    pub fn excerpt(content: &str, max_chars: usize) -> String {
+        let text = content
+            .lines()
+            .map(Self::strip_markdown)
+            .filter(|line| !line.is_empty())
+            // section headings make poor summaries
+            .filter(|line| !line.ends_with(':'))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if text.chars().count() <= max_chars {
+            return text;
+        }
+
+        let truncated: String = text.chars().take(max_chars).collect();
+
+        // prefer ending on a full sentence if one fits in the back half of the budget
+        if let Some(end) = truncated.rfind(['.', '!', '?']) {
+            if end >= truncated.len() / 2 {
+                return truncated[..=end].to_string();
+            }
+        }
+
+        let cut = truncated.rfind(' ').unwrap_or(truncated.len());
+        format!(
+            "{}…",
+            truncated[..cut].trim_end_matches([',', ';', ':', ' '])
+        )
+    }
+
+    fn strip_markdown(line: &str) -> String {
+        let line = line
+            .trim()
+            .trim_start_matches('#')
+            .trim_start_matches('>')
+            .trim_start_matches("- ")
+            .trim_start_matches("* ")
+            .trim();
+        line.replace("**", "")
+            .replace("__", "")
+            .replace(['`', '*'], "")
+    }
+}
@@ -0,0 +1,194 @@
+use base64::Engine;
+use deadpool_postgres::Pool;
+use log::info;
+use serde_json::{json, Value};
+use std::env;
+use std::error::Error;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::llm::gemini_api_key;
+use crate::utils::SummaryGenerator;
+
+// about a minute of speech
+pub const VOICE_SCRIPT_CHARS: usize = 900;
+
+const DEFAULT_VOICE: &str = "Kore";
+
+// gemini speech is 16-bit mono pcm, at this rate unless the mime type says otherwise
+const DEFAULT_SAMPLE_RATE: u32 = 24000;
+
+const TTS_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// text-to-speech settings; voice summaries are disabled unless TTS_MODEL is set
+#[derive(Debug, Clone)]
+pub struct VoiceConfig {
+    pub model: String,
+    pub voice: String,
+}
+
+impl VoiceConfig {
+    pub fn from_env() -> Option<Self> {
+        let model = env::var("TTS_MODEL")
+            .ok()
+            .filter(|model| !model.is_empty())?;
+        let voice = env::var("TTS_VOICE")
+            .ok()
+            .filter(|voice| !voice.is_empty())
+            .unwrap_or_else(|| DEFAULT_VOICE.to_string());
+        Some(Self { model, voice })
+    }
+}
+
+/// what the voice summary of an analysis says: its opening, as plain text
+pub fn voice_script(content: &str) -> String {
+    SummaryGenerator::excerpt(content, VOICE_SCRIPT_CHARS)
+}
+
+/// the sample rate of an `audio/L16;codec=pcm;rate=24000` mime type
+pub fn pcm_sample_rate(mime_type: &str) -> u32 {
+    mime_type
+        .split(';')
+        .find_map(|param| param.trim().strip_prefix("rate="))
+        .and_then(|rate| rate.parse().ok())
+        .unwrap_or(DEFAULT_SAMPLE_RATE)
+}
+
+/// voice summaries of analyses: speech from Gemini, encoded to OGG/Opus with ffmpeg, and the
+/// Telegram file ids of sent summaries in voice_summaries so each is generated once
+pub struct VoiceSummaries {
+    pool: Arc<Pool>,
+    http: reqwest::Client,
+    config: VoiceConfig,
+}
+
+impl VoiceSummaries {
+    pub fn new(pool: Arc<Pool>, config: VoiceConfig) -> Self {
+        Self {
+            pool,
+            http: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    /// the file id of the summary already sent for an analysis result
+    pub async fn cached_file_id(
+        &self,
+        cache_key: &str,
+        analysis_type: &str,
+    ) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT file_id FROM voice_summaries WHERE cache_key = $1 AND analysis_type = $2",
+                &[&cache_key, &analysis_type],
+            )
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    pub async fn store_file_id(
+        &self,
+        cache_key: &str,
+        analysis_type: &str,
+        file_id: &str,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO voice_summaries (cache_key, analysis_type, file_id) VALUES ($1, $2, $3)
+                 ON CONFLICT (cache_key, analysis_type) DO UPDATE SET file_id = EXCLUDED.file_id",
+                &[&cache_key, &analysis_type, &file_id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// speaks the text and returns it as an OGG/Opus voice message
+    pub async fn record(&self, text: &str) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let (pcm, sample_rate) = self.synthesize(text).await?;
+        let voice = encode_ogg_opus(&pcm, sample_rate).await?;
+        info!(
+            "Recorded a voice summary of {} characters ({} bytes)",
+            text.chars().count(),
+            voice.len()
+        );
+        Ok(voice)
+    }
+
+    // raw pcm and its sample rate
+    async fn synthesize(&self, text: &str) -> Result<(Vec<u8>, u32), Box<dyn Error + Send + Sync>> {
+        let api_key = gemini_api_key().ok_or("GEMINI_API_KEY not set")?;
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+            self.config.model, api_key
+        );
+        let response = self
+            .http
+            .post(&url)
+            .timeout(TTS_TIMEOUT)
+            .json(&json!({
+                "contents": [{"parts": [{"text": text}]}],
+                "generationConfig": {
+                    "responseModalities": ["AUDIO"],
+                    "speechConfig": {
+                        "voiceConfig": {"prebuiltVoiceConfig": {"voiceName": self.config.voice}}
+                    }
+                }
+            }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("TTS API error {}: {}", status, error_text).into());
+        }
+
+        let body: Value = response.json().await?;
+        let audio = &body["candidates"][0]["content"]["parts"][0]["inlineData"];
+        let data = audio["data"]
+            .as_str()
+            .ok_or("TTS answer carries no audio")?;
+        let pcm = base64::engine::general_purpose::STANDARD.decode(data)?;
+        let sample_rate = pcm_sample_rate(audio["mimeType"].as_str().unwrap_or_default());
+        Ok((pcm, sample_rate))
+    }
+}
+
+/// encodes 16-bit mono pcm as OGG/Opus, the format Telegram plays as a voice message
+pub async fn encode_ogg_opus(
+    pcm: &[u8],
+    sample_rate: u32,
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let mut ffmpeg = Command::new("ffmpeg")
+        .args(["-loglevel", "error", "-f", "s16le", "-ac", "1", "-ar"])
+        .arg(sample_rate.to_string())
+        .args(["-i", "pipe:0", "-c:a", "libopus", "-b:a", "32k"])
+        .args(["-application", "voip", "-f", "ogg", "pipe:1"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to start ffmpeg: {}", e))?;
+
+    // fed from another task, ffmpeg would stall writing to a full stdout otherwise
+    let mut stdin = ffmpeg.stdin.take().expect("ffmpeg stdin is piped");
+    let pcm = pcm.to_vec();
+    let feed = tokio::spawn(async move { stdin.write_all(&pcm).await });
+
+    let output = ffmpeg.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    feed.await??;
+    Ok(output.stdout)
+}
@@ -0,0 +1,287 @@
+use chrono::{DateTime, Duration as ChronoDuration, Timelike, Utc};
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::analysis::AnalysisDepth;
+use crate::analysis_runner::{run_unrecorded_analysis, AnalysisRunError, AnalysisTarget};
+use crate::bot::ChannelLocks;
+use crate::channel_stats::ChannelStatsManager;
+use crate::llm_budget::LlmBudget;
+use crate::workers::AnalysisWorkers;
+
+/// this week's most analyzed channels warmed unless WARMUP_TOP_CHANNELS says otherwise
+pub const DEFAULT_TOP_CHANNELS: usize = 10;
+
+// the professional, personal and roast analyses share one llm answer, so warming one type
+// serves all three
+const WARMUP_ANALYSIS_TYPE: &str = "professional";
+
+/// hours of the day (UTC) the warm-up may run in, `end_hour` excluded; wraps past midnight
+/// when it ends before it starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffPeakWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl OffPeakWindow {
+    /// parses "<start>-<end>" such as "2-6" or "23-5"
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (start, end) = spec.trim().split_once('-')?;
+        let start_hour = start.trim().parse().ok().filter(|hour| *hour < 24)?;
+        let end_hour = end.trim().parse().ok().filter(|hour| *hour < 24)?;
+        (start_hour != end_hour).then_some(Self {
+            start_hour,
+            end_hour,
+        })
+    }
+
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start_hour < self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+
+    /// how long from `now` until the window next opens, not counting a window already open
+    pub fn until_next_start(&self, now: DateTime<Utc>) -> Duration {
+        let today = now
+            .date_naive()
+            .and_hms_opt(self.start_hour, 0, 0)
+            .expect("the start hour is below 24")
+            .and_utc();
+        let start = if today > now {
+            today
+        } else {
+            today + ChronoDuration::days(1)
+        };
+        (start - now).to_std().unwrap_or_default()
+    }
+
+    /// how long from `now` until the warm-up may run, zero while the window is open
+    pub fn until_open(&self, now: DateTime<Utc>) -> Duration {
+        if self.contains(now.hour()) {
+            Duration::ZERO
+        } else {
+            self.until_next_start(now)
+        }
+    }
+}
+
+/// cache warm-up settings; disabled unless WARMUP_HOURS sets an off-peak window
+#[derive(Debug, Clone)]
+pub struct WarmupConfig {
+    pub window: OffPeakWindow,
+    pub top_channels: usize,
+}
+
+impl WarmupConfig {
+    pub fn from_env() -> Option<Self> {
+        let spec = env::var("WARMUP_HOURS").ok()?;
+        let Some(window) = OffPeakWindow::parse(&spec) else {
+            warn!(
+                "Ignoring malformed WARMUP_HOURS {:?}, expected e.g. 2-6",
+                spec
+            );
+            return None;
+        };
+        let top_channels = env::var("WARMUP_TOP_CHANNELS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_TOP_CHANNELS);
+        Some(Self {
+            window,
+            top_channels,
+        })
+    }
+}
+
+/// the channels to warm: the pinned ones, then up to `top_channels` of the trending ones
+/// that aren't excluded, each once
+pub fn warm_list(
+    pinned: &[String],
+    excluded: &[String],
+    trending: &[String],
+    top_channels: usize,
+) -> Vec<String> {
+    let mut channels: Vec<String> = Vec::new();
+    for channel in pinned {
+        if !channels.contains(channel) {
+            channels.push(channel.clone());
+        }
+    }
+    let trending = trending
+        .iter()
+        .filter(|channel| !excluded.contains(channel))
+        .take(top_channels);
+    for channel in trending {
+        if !channels.contains(channel) {
+            channels.push(channel.clone());
+        }
+    }
+    channels
+}
+
+/// channels admins pinned to or excluded from the warm list, kept in warm_channels
+pub struct WarmupManager {
+    pool: Arc<Pool>,
+}
+
+impl WarmupManager {
+    pub fn new(pool: Arc<Pool>) -> Self {
+        Self { pool }
+    }
+
+    async fn set(
+        &self,
+        channel_name: &str,
+        pinned: bool,
+        telegram_user_id: i64,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        // usernames are case-insensitive, like in channel_stats
+        let channel_name = channel_name.to_lowercase();
+        client
+            .execute(
+                "INSERT INTO warm_channels (channel_name, pinned, updated_by)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (channel_name) DO UPDATE SET
+                     pinned = EXCLUDED.pinned,
+                     updated_by = EXCLUDED.updated_by,
+                     updated_at = NOW()",
+                &[&channel_name, &pinned, &telegram_user_id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// warms the channel every night whether it's trending or not
+    pub async fn pin(
+        &self,
+        channel_name: &str,
+        telegram_user_id: i64,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.set(channel_name, true, telegram_user_id).await
+    }
+
+    /// keeps the channel out of the warm-up even while it's trending
+    pub async fn exclude(
+        &self,
+        channel_name: &str,
+        telegram_user_id: i64,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.set(channel_name, false, telegram_user_id).await
+    }
+
+    /// drops a pin or an exclusion; false when the channel had neither
+    pub async fn reset(&self, channel_name: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let removed = client
+            .execute(
+                "DELETE FROM warm_channels WHERE channel_name = $1",
+                &[&channel_name.to_lowercase()],
+            )
+            .await?;
+        Ok(removed > 0)
+    }
+
+    /// the pinned and the excluded channels, alphabetically
+    pub async fn overrides(
+        &self,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT channel_name, pinned FROM warm_channels ORDER BY channel_name",
+                &[],
+            )
+            .await?;
+        let (pinned, excluded): (Vec<_>, Vec<_>) =
+            rows.iter().partition(|row| row.get::<_, bool>(1));
+        let names = |rows: Vec<&tokio_postgres::Row>| {
+            rows.into_iter()
+                .map(|row| row.get::<_, String>(0))
+                .collect::<Vec<_>>()
+        };
+        Ok((names(pinned), names(excluded)))
+    }
+
+    /// tonight's warm list: the pins and this week's most analyzed channels
+    pub async fn warm_list(
+        &self,
+        channel_stats: &ChannelStatsManager,
+        top_channels: usize,
+    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let (pinned, excluded) = self.overrides().await?;
+        // excluded channels may take places in the top, ask for enough to fill it anyway
+        let limit = (top_channels + excluded.len()) as i64;
+        let trending = channel_stats
+            .top_this_week(limit)
+            .await?
+            .into_iter()
+            .map(|popularity| popularity.channel_name)
+            .collect::<Vec<_>>();
+        Ok(warm_list(&pinned, &excluded, &trending, top_channels))
+    }
+}
+
+/// every night in the off-peak window, fetches the messages of the warm list and asks the
+/// llm for their analyses, so daytime requests for popular channels are served from the
+/// caches; nothing is recorded or charged and the daily llm budget still applies
+pub async fn run_cache_warmup(
+    analysis_workers: Arc<AnalysisWorkers>,
+    channel_stats: Arc<ChannelStatsManager>,
+    warmup: Arc<WarmupManager>,
+    channel_locks: ChannelLocks,
+    llm_budget: Arc<LlmBudget>,
+    config: WarmupConfig,
+) {
+    loop {
+        sleep(config.window.until_open(Utc::now())).await;
+
+        let channels = match warmup.warm_list(&channel_stats, config.top_channels).await {
+            Ok(channels) => channels,
+            Err(e) => {
+                error!("Failed to load the cache warm-up list: {}", e);
+                Vec::new()
+            }
+        };
+        info!("Warming the caches of {} channels", channels.len());
+
+        let mut warmed = 0;
+        for channel_name in channels {
+            // whatever is left waits for the next night rather than running into the day
+            if !config.window.contains(Utc::now().hour()) {
+                info!("Off-peak window closed, the rest of the warm-up waits for tomorrow");
+                break;
+            }
+            let target = AnalysisTarget {
+                channel_name,
+                analysis_type: WARMUP_ANALYSIS_TYPE.to_string(),
+                depth: AnalysisDepth::default(),
+                focus: None,
+                topic: None,
+                allow_low_text: true,
+            };
+            match run_unrecorded_analysis(&analysis_workers, &channel_locks, &llm_budget, &target)
+                .await
+            {
+                Ok(_) => warmed += 1,
+                Err(AnalysisRunError::BudgetExceeded) => {
+                    warn!("Daily LLM budget is spent, stopping the cache warm-up");
+                    break;
+                }
+                Err(e) => warn!("Failed to warm the cache of {}: {}", target.channel_name, e),
+            }
+        }
+        info!("Cache warm-up done, {} channels warmed", warmed);
+
+        sleep(config.window.until_next_start(Utc::now())).await;
+    }
+}
@@ -0,0 +1,91 @@
+//! the analysis watchdog: fails analyses left pending longer than analysis_timeout_minutes,
+//! e.g. orphaned by a crash or hung on a fetch, so they don't hold the user's running slot
+//! forever; they were never charged, and bot users are told through the message queue
+use deadpool_postgres::Pool;
+use log::{error, info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::LiveConfig;
+use crate::error::AppError;
+use crate::localization::Lang;
+use crate::metrics::metrics;
+
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// fails the analyses pending for longer than `timeout_minutes` together with their
+/// unfinished jobs and queues a notice to the users of the bot ones; returns their ids
+pub async fn fail_stuck_analyses(pool: &Pool, timeout_minutes: i32) -> Result<Vec<i32>, AppError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let rows = transaction
+        .query(
+            "WITH stuck AS (
+                 UPDATE user_analyses ua SET status = 'failed'
+                 FROM users u
+                 WHERE u.id = ua.user_id AND ua.status = 'pending'
+                   AND ua.pending_since < NOW() - make_interval(mins => $1)
+                 RETURNING ua.id, ua.source, ua.channel_name, u.telegram_user_id,
+                           COALESCE(ua.language, u.language_override, u.language) AS language
+             ), jobs AS (
+                 UPDATE analysis_jobs
+                 SET status = 'failed', finished_at = NOW(), leased_by = NULL, lease_expires_at = NULL,
+                     last_error = 'timed out by the watchdog'
+                 WHERE analysis_id IN (SELECT id FROM stuck) AND status IN ('queued', 'running')
+             )
+             SELECT id, source, channel_name, telegram_user_id, language FROM stuck",
+            &[&timeout_minutes],
+        )
+        .await?;
+
+    let mut analysis_ids = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let analysis_id: i32 = row.get(0);
+        let channel_name: &str = row.get(2);
+        warn!(
+            "Analysis {} of {} was pending for over {} minutes, failing it",
+            analysis_id, channel_name, timeout_minutes
+        );
+        // api clients see the failed status when they poll
+        if row.get::<_, &str>(1) == "bot" {
+            let lang = Lang::from_code(row.get::<_, Option<&str>>(4));
+            transaction
+                .execute(
+                    "INSERT INTO message_queue (telegram_user_id, message) VALUES ($1, $2)",
+                    &[
+                        &row.get::<_, i64>(3),
+                        &lang.analysis_timed_out(channel_name),
+                    ],
+                )
+                .await?;
+        }
+        analysis_ids.push(analysis_id);
+    }
+    transaction.commit().await?;
+
+    for _ in &analysis_ids {
+        metrics().analysis_failed("timeout");
+    }
+    Ok(analysis_ids)
+}
+
+/// checks for stuck analyses every few minutes until the process exits, with the timeout
+/// in effect at each check
+pub async fn run_analysis_watchdog(pool: Arc<Pool>, config: Arc<LiveConfig>) {
+    info!(
+        "Starting analysis watchdog, pending analyses time out after {} minutes",
+        config.limits().analysis_timeout_minutes
+    );
+    let mut interval = tokio::time::interval(WATCHDOG_INTERVAL);
+    loop {
+        interval.tick().await;
+        let timeout_minutes = config.limits().analysis_timeout_minutes;
+        match fail_stuck_analyses(&pool, timeout_minutes).await {
+            Ok(analysis_ids) if !analysis_ids.is_empty() => {
+                info!("Timed out {} stuck analyses", analysis_ids.len())
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to check for stuck analyses: {}", e),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+// Tests for splitting sessions between analysis workers
+use tg_main::workers::assign_sessions;
+
+fn sessions(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| format!("sessions/{}.session", i))
+        .collect()
+}
+
+#[test]
+fn test_every_session_goes_to_exactly_one_worker() {
+    let assigned = assign_sessions(sessions(5), 2);
+    assert_eq!(assigned.len(), 2);
+    assert_eq!(
+        assigned[0],
+        vec![
+            "sessions/0.session",
+            "sessions/2.session",
+            "sessions/4.session"
+        ]
+    );
+    assert_eq!(
+        assigned[1],
+        vec!["sessions/1.session", "sessions/3.session"]
+    );
+}
+
+#[test]
+fn test_workers_never_outnumber_sessions() {
+    let assigned = assign_sessions(sessions(2), 8);
+    assert_eq!(assigned.len(), 2);
+    assert!(assigned.iter().all(|worker| worker.len() == 1));
+}
+
+#[test]
+fn test_no_sessions_means_no_workers() {
+    assert!(assign_sessions(Vec::new(), 3).is_empty());
+    assert_eq!(assign_sessions(sessions(3), 0).len(), 1);
+}
@@ -0,0 +1,60 @@
+// Tests for the crate-wide error type and how its variants map to analysis failures
+use std::error::Error;
+use tg_main::analysis::AnalysisError;
+use tg_main::changelog::ChangelogError;
+use tg_main::error::AppError;
+use tg_main::user_manager::UserManagerError;
+use tg_main::web_scraper::WebScrapingError;
+
+#[test]
+fn test_analysis_failures_pass_through() {
+    let err = AppError::from(AnalysisError::FloodWait(30));
+    assert_eq!(err.failure(), AnalysisError::FloodWait(30));
+    assert_eq!(err.to_string(), AnalysisError::FloodWait(30).to_string());
+
+    // boxed the way callers that still return Box<dyn Error> see it
+    let boxed: Box<dyn Error + Send + Sync> = AppError::from(AnalysisError::ChannelPrivate).into();
+    assert_eq!(
+        AnalysisError::classify(boxed.as_ref()),
+        AnalysisError::ChannelPrivate
+    );
+}
+
+#[test]
+fn test_wrapped_errors_are_classified_through_their_source() {
+    let err = AppError::from(WebScrapingError::StatusCodeError(404));
+    assert!(matches!(err, AppError::Telegram(_)));
+    assert_eq!(err.failure(), AnalysisError::ChannelNotFound);
+    assert!(err.source().is_some());
+
+    let err = AppError::llm(AnalysisError::AiRefusal);
+    assert_eq!(err.failure(), AnalysisError::AiRefusal);
+
+    let err = AppError::Payment("invoice refused".to_string());
+    assert!(matches!(err.failure(), AnalysisError::Internal(_)));
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn test_user_manager_errors_keep_their_kind() {
+    assert!(matches!(
+        AppError::from(UserManagerError::InsufficientCredits(7)),
+        AppError::InsufficientCredits(7)
+    ));
+    assert!(matches!(
+        AppError::from(UserManagerError::UserNotFound(7)),
+        AppError::Validation(_)
+    ));
+    assert!(matches!(
+        AppError::from(UserManagerError::TooManyRunning(7)),
+        AppError::Validation(_)
+    ));
+    assert!(matches!(
+        AppError::from(UserManagerError::DatabaseError("connection reset".into())),
+        AppError::Db(_)
+    ));
+    assert!(matches!(
+        AppError::from(ChangelogError::NotFound(3)),
+        AppError::Validation(_)
+    ));
+}
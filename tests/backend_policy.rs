@@ -0,0 +1,44 @@
+// Tests for the backend policy that picks how channel messages are fetched
+use tg_main::backend_config::{BackendConfig, BackendPolicy, BackendType};
+
+#[test]
+fn test_policy_codes_roundtrip() {
+    for policy in BackendPolicy::ALL {
+        assert_eq!(BackendPolicy::from_code(policy.as_str()), Some(policy));
+    }
+    // env values may use underscores or capitals
+    assert_eq!(
+        BackendPolicy::from_code(" API_ONLY "),
+        Some(BackendPolicy::ApiOnly)
+    );
+    assert_eq!(BackendPolicy::from_code("api"), None);
+}
+
+#[test]
+fn test_policy_backends_in_order_of_preference() {
+    assert_eq!(
+        BackendPolicy::WebOnly.backends(),
+        vec![BackendType::WebScraping]
+    );
+    assert_eq!(BackendPolicy::ApiOnly.backends(), vec![BackendType::Api]);
+    assert_eq!(
+        BackendPolicy::PreferApi.backends(),
+        vec![BackendType::Api, BackendType::WebScraping]
+    );
+
+    // web scraping first stays the default
+    let config = BackendConfig::default();
+    assert_eq!(config.policy, BackendPolicy::PreferWeb);
+    assert_eq!(
+        config.enabled_backends,
+        vec![BackendType::WebScraping, BackendType::Api]
+    );
+}
+
+#[test]
+fn test_backend_codes_roundtrip() {
+    for backend in [BackendType::Api, BackendType::WebScraping] {
+        assert_eq!(BackendType::from_code(backend.as_str()), Some(backend));
+    }
+    assert_eq!(BackendType::from_code("WebScraping"), None);
+}
@@ -0,0 +1,41 @@
+// Tests for resolving backup destinations
+use std::path::PathBuf;
+use tg_main::backup::BackupLocation;
+
+#[test]
+fn test_parse_distinguishes_s3_and_local() {
+    assert_eq!(
+        BackupLocation::parse("s3://bucket/backups"),
+        BackupLocation::S3("s3://bucket/backups".to_string())
+    );
+    assert_eq!(
+        BackupLocation::parse("/var/backups"),
+        BackupLocation::Local(PathBuf::from("/var/backups"))
+    );
+}
+
+#[test]
+fn test_directories_and_prefixes_get_timestamped_file() {
+    let local = BackupLocation::parse("/var/backups").resolve_file("20240101T000000Z");
+    assert_eq!(
+        local,
+        BackupLocation::Local(PathBuf::from(
+            "/var/backups/tg-analyzer-20240101T000000Z.dump"
+        ))
+    );
+
+    let s3 = BackupLocation::parse("s3://bucket/backups/").resolve_file("20240101T000000Z");
+    assert_eq!(
+        s3,
+        BackupLocation::S3("s3://bucket/backups/tg-analyzer-20240101T000000Z.dump".to_string())
+    );
+}
+
+#[test]
+fn test_explicit_dump_files_are_kept() {
+    let local = BackupLocation::parse("/tmp/manual.dump");
+    assert_eq!(local.resolve_file("20240101T000000Z"), local);
+
+    let s3 = BackupLocation::parse("s3://bucket/manual.dump");
+    assert_eq!(s3.resolve_file("20240101T000000Z"), s3);
+}
@@ -0,0 +1,68 @@
+// Tests for parsing and reporting batch analysis requests
+use tg_main::batch::BatchRequest;
+use tg_main::localization::Lang;
+
+#[test]
+fn test_several_channels_form_a_batch() {
+    assert_eq!(
+        BatchRequest::parse("@rustlang, t.me/golang\nhttps://t.me/python_news", 5),
+        Some(BatchRequest::Channels(vec![
+            "@rustlang".to_string(),
+            "@golang".to_string(),
+            "@python_news".to_string(),
+        ]))
+    );
+}
+
+#[test]
+fn test_duplicates_are_dropped_case_insensitively() {
+    assert_eq!(
+        BatchRequest::parse("@rustlang @RustLang @golang", 5),
+        Some(BatchRequest::Channels(vec![
+            "@rustlang".to_string(),
+            "@golang".to_string(),
+        ]))
+    );
+    // a lone channel in disguise is still reported, the bot offers it as a single analysis
+    assert_eq!(
+        BatchRequest::parse("@rustlang @RUSTLANG", 5),
+        Some(BatchRequest::Channels(vec!["@rustlang".to_string()]))
+    );
+}
+
+#[test]
+fn test_single_channels_and_chatter_are_not_batches() {
+    assert_eq!(BatchRequest::parse("@rustlang", 5), None);
+    assert_eq!(BatchRequest::parse("  @rustlang  ", 5), None);
+    assert_eq!(BatchRequest::parse("hello there", 5), None);
+}
+
+#[test]
+fn test_invalid_tokens_and_oversized_batches_are_rejected() {
+    assert_eq!(
+        BatchRequest::parse("@rustlang and @golang", 5),
+        Some(BatchRequest::Invalid(vec!["and".to_string()]))
+    );
+    assert_eq!(
+        BatchRequest::parse("@channel1 @channel2 @channel3", 2),
+        Some(BatchRequest::TooMany(3))
+    );
+}
+
+#[test]
+fn test_batch_summary_mentions_uncharged_failures() {
+    let all_done = Lang::En.batch_finished(3, 3, &[], &[]);
+    assert!(all_done.contains("3/3"));
+    assert!(!all_done.contains("charged"));
+
+    let summary = Lang::En.batch_finished(
+        1,
+        3,
+        &["@golang".to_string()],
+        &["@python_news".to_string()],
+    );
+    assert!(summary.contains("1/3"));
+    assert!(summary.contains("@golang"));
+    assert!(summary.contains("@python_news"));
+    assert!(summary.contains("only charged for completed analyses"));
+}
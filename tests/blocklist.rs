@@ -0,0 +1,56 @@
+// Tests for the channel blocklist
+use tg_main::analysis::AnalysisError;
+use tg_main::blocklist::BlockTarget;
+use tg_main::localization::Lang;
+
+#[test]
+fn test_targets_are_read_from_names_links_and_ids() {
+    let rustlang = Some(BlockTarget::Username("rustlang".to_string()));
+    assert_eq!(BlockTarget::parse("@RustLang"), rustlang);
+    assert_eq!(BlockTarget::parse("rustlang"), rustlang);
+    assert_eq!(BlockTarget::parse("https://t.me/rustlang/"), rustlang);
+    assert_eq!(
+        BlockTarget::parse("-1001234567890"),
+        Some(BlockTarget::ChannelId(1234567890))
+    );
+    assert_eq!(
+        BlockTarget::parse("1234567890"),
+        Some(BlockTarget::ChannelId(1234567890))
+    );
+    assert_eq!(BlockTarget::parse("0"), None);
+    assert_eq!(BlockTarget::parse("@"), None);
+    assert_eq!(BlockTarget::parse("not a channel"), None);
+}
+
+#[test]
+fn test_targets_are_stored_by_name_or_bare_id() {
+    assert_eq!(BlockTarget::parse("@RustLang").unwrap().key(), "@rustlang");
+    assert_eq!(BlockTarget::parse("-1001234").unwrap().key(), "1234");
+}
+
+#[test]
+fn test_only_named_chats_are_checked_by_name() {
+    assert_eq!(
+        BlockTarget::of_channel("@RustLang"),
+        Some(BlockTarget::Username("rustlang".to_string()))
+    );
+    // forwarded messages, group profiles and private invite links have no name to block
+    assert_eq!(BlockTarget::of_channel("self:42"), None);
+    assert_eq!(BlockTarget::of_channel("self:groups:42"), None);
+    assert_eq!(BlockTarget::of_channel("+AbCdEf"), None);
+}
+
+#[test]
+fn test_blocked_channel_message_promises_no_charge() {
+    for lang in [Lang::En, Lang::Ru, Lang::Uk, Lang::Es, Lang::De] {
+        let message = lang.error_analysis_failed(&AnalysisError::ChannelBlocked, "@rustlang");
+        assert!(message.contains("@rustlang"));
+        assert_ne!(
+            message,
+            lang.error_analysis_failed(&AnalysisError::ChannelNotFound, "@rustlang")
+        );
+    }
+    assert!(Lang::En
+        .error_analysis_failed(&AnalysisError::ChannelBlocked, "@rustlang")
+        .contains("No credits were consumed"));
+}
@@ -0,0 +1,238 @@
+// Tests for inline keyboard callback data encoding and parsing
+use tg_main::analysis::AnalysisDepth;
+use tg_main::handlers::callback_data::{CallbackData, MAX_CALLBACK_DATA_LEN};
+use tg_main::llm::ModelTier;
+use tg_main::localization::Lang;
+use tg_main::prompts::analysis::OutputLanguage;
+
+// longest username telegram allows, full of underscores
+const LONG_CHANNEL: &str = "@a_b_c_d_e_f_g_h_i_j_k_l_m_n_o_p_";
+
+fn roundtrip(data: CallbackData) {
+    let encoded = data.encode();
+    assert!(
+        encoded.len() <= MAX_CALLBACK_DATA_LEN,
+        "{} is {} bytes",
+        encoded,
+        encoded.len()
+    );
+    assert_eq!(CallbackData::parse(&encoded), Some(data));
+}
+
+#[test]
+fn test_all_variants_roundtrip() {
+    roundtrip(CallbackData::BuyPackage(1));
+    roundtrip(CallbackData::BuyPackage(42));
+    for tier in ModelTier::ALL {
+        roundtrip(CallbackData::ModelTier(tier));
+    }
+    for language in OutputLanguage::ALL {
+        roundtrip(CallbackData::OutputLanguage(language));
+    }
+    roundtrip(CallbackData::InterfaceLanguage(None));
+    for lang in Lang::ALL {
+        roundtrip(CallbackData::InterfaceLanguage(Some(lang)));
+    }
+    roundtrip(CallbackData::Announcements(true));
+    roundtrip(CallbackData::Announcements(false));
+    roundtrip(CallbackData::WebPages(true));
+    roundtrip(CallbackData::WebPages(false));
+    roundtrip(CallbackData::RevokeApiKeys);
+    for page in [0, 1, 42, u32::MAX] {
+        roundtrip(CallbackData::BalancePage(page));
+    }
+    for analysis_id in [1, 42, i32::MAX] {
+        roundtrip(CallbackData::Regenerate(analysis_id));
+        roundtrip(CallbackData::Resend(analysis_id));
+        roundtrip(CallbackData::Listen(analysis_id));
+        roundtrip(CallbackData::SecondOpinion(analysis_id));
+        roundtrip(CallbackData::LowTextConfirm(analysis_id));
+    }
+    for analysis_type in ["professional", "personal", "roast", "trends"] {
+        roundtrip(CallbackData::Batch(analysis_type.to_string()));
+    }
+    roundtrip(CallbackData::SelfDone);
+    roundtrip(CallbackData::InviteConsent);
+    for anonymous in [false, true] {
+        for analysis_id in [1, i32::MAX] {
+            roundtrip(CallbackData::Showcase {
+                analysis_id,
+                anonymous,
+            });
+        }
+    }
+    for positive in [false, true] {
+        for analysis_id in [1, i32::MAX] {
+            roundtrip(CallbackData::Feedback {
+                analysis_id,
+                positive,
+            });
+        }
+    }
+    for claim_id in [1, i32::MAX] {
+        roundtrip(CallbackData::ClaimVerify(claim_id));
+        for opted_out in [false, true] {
+            roundtrip(CallbackData::ClaimOptOut {
+                claim_id,
+                opted_out,
+            });
+        }
+    }
+    for analysis_type in ["professional", "personal", "roast"] {
+        roundtrip(CallbackData::SelfAnalysis(analysis_type.to_string()));
+        roundtrip(CallbackData::CrossGroupAnalysis(analysis_type.to_string()));
+    }
+    roundtrip(CallbackData::CrossGroupConsent);
+    roundtrip(CallbackData::CrossGroupRevoke);
+    roundtrip(CallbackData::DeleteAccount);
+    roundtrip(CallbackData::VerifyHuman);
+    for depth in AnalysisDepth::ALL {
+        for analysis_type in ["professional", "personal", "roast", "trends"] {
+            roundtrip(CallbackData::Analysis {
+                analysis_type: analysis_type.to_string(),
+                depth,
+                channel_name: "@rust_lang".to_string(),
+            });
+        }
+        roundtrip(CallbackData::Depth {
+            depth,
+            channel_name: "@rust_lang".to_string(),
+        });
+    }
+    roundtrip(CallbackData::Focus {
+        channel_name: "@rust_lang".to_string(),
+    });
+    for analysis_type in [None, Some("roast".to_string())] {
+        for thread_id in [None, Some(1), Some(i32::MAX)] {
+            roundtrip(CallbackData::GroupScope {
+                analysis_type: analysis_type.clone(),
+                thread_id,
+            });
+        }
+    }
+}
+
+#[test]
+fn test_private_channels_roundtrip() {
+    // longest invite hash the bot accepts, with the characters telegram uses
+    let channel_name = "+Ab_c-D_e-F_g-H_i-J_k-L_m-N_o-P_q";
+    assert_eq!(channel_name.len(), 33);
+
+    for depth in AnalysisDepth::ALL {
+        roundtrip(CallbackData::Analysis {
+            analysis_type: "professional".to_string(),
+            depth,
+            channel_name: channel_name.to_string(),
+        });
+        roundtrip(CallbackData::Depth {
+            depth,
+            channel_name: channel_name.to_string(),
+        });
+    }
+    roundtrip(CallbackData::Focus {
+        channel_name: channel_name.to_string(),
+    });
+}
+
+#[test]
+fn test_underscore_heavy_channels_near_size_limit() {
+    assert_eq!(LONG_CHANNEL.len(), 33);
+
+    for depth in AnalysisDepth::ALL {
+        roundtrip(CallbackData::Analysis {
+            analysis_type: "professional".to_string(),
+            depth,
+            channel_name: LONG_CHANNEL.to_string(),
+        });
+        roundtrip(CallbackData::Depth {
+            depth,
+            channel_name: LONG_CHANNEL.to_string(),
+        });
+    }
+    roundtrip(CallbackData::Focus {
+        channel_name: LONG_CHANNEL.to_string(),
+    });
+
+    // channel names that look like other segments must not be split
+    roundtrip(CallbackData::Analysis {
+        analysis_type: "roast".to_string(),
+        depth: AnalysisDepth::Deep,
+        channel_name: "@deep_medium_small".to_string(),
+    });
+}
+
+#[test]
+fn test_legacy_analysis_data_without_depth() {
+    assert_eq!(
+        CallbackData::parse("analysis_personal_@my_channel_name"),
+        Some(CallbackData::Analysis {
+            analysis_type: "personal".to_string(),
+            depth: AnalysisDepth::Small,
+            channel_name: "@my_channel_name".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_malformed_data_is_rejected() {
+    for data in [
+        "",
+        "analysis",
+        "analysis_professional",
+        "analysis_unknown_small_@channel",
+        "analysis_professional_small_channel",
+        "analysis_professional_small_@",
+        "depth_huge_@channel",
+        "focus_",
+        "tier_ultra",
+        "outlang_de",
+        "announce_maybe",
+        "webpage_",
+        "listen_",
+        "listen_+1",
+        "second_",
+        "second_-1",
+        "balance_",
+        "balance_-1",
+        "balance_+1",
+        "balance_next",
+        "buy_triple",
+        "buy_",
+        "buy_-1",
+        "buy_99999999999",
+        "batch_",
+        "batch_unknown",
+        "gscope_",
+        "gscope_pick",
+        "gscope_pick_",
+        "gscope_unknown_all",
+        "gscope_pick_+1",
+        "gscope_pick_99999999999",
+        "self_",
+        "self_trends",
+        "xgroup_",
+        "xgroup_trends",
+        "showcase_named",
+        "showcase_named_",
+        "showcase_public_1",
+        "showcase_anon_+1",
+        "showcase_anon_99999999999",
+        "fb_up",
+        "fb_up_",
+        "fb_meh_1",
+        "fb_down_-1",
+        "claimv_",
+        "claimv_+1",
+        "optout_on",
+        "optout_maybe_1",
+        "optout_off_-1",
+        "analysis_roast_small_self:42",
+    ] {
+        assert_eq!(
+            CallbackData::parse(data),
+            None,
+            "{:?} should not parse",
+            data
+        );
+    }
+}
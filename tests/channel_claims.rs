@@ -0,0 +1,41 @@
+// Tests for proving channel ownership with /claim
+use tg_main::channel_claims::{new_claim_code, posts_contain_code};
+use tg_main::web_scraper::recent_post_texts;
+
+#[test]
+fn test_codes_are_prefixed_and_unambiguous() {
+    let code = new_claim_code();
+    let suffix = code
+        .strip_prefix("claim-")
+        .expect("Code should be prefixed");
+    assert_eq!(suffix.len(), 8);
+    assert!(suffix
+        .chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+    assert!(!suffix.contains(['0', 'O', '1', 'I']));
+    assert_ne!(new_claim_code(), new_claim_code());
+}
+
+#[test]
+fn test_code_is_found_anywhere_in_a_post() {
+    let posts = vec![
+        "Weekly digest".to_string(),
+        "Verifying my channel: CLAIM-7KQ2MX9P, please ignore".to_string(),
+    ];
+    assert!(posts_contain_code(&posts, "claim-7KQ2MX9P"));
+    assert!(!posts_contain_code(&posts, "claim-7KQ2MX9Q"));
+    assert!(!posts_contain_code(&[], "claim-7KQ2MX9P"));
+}
+
+#[test]
+fn test_post_texts_are_read_from_the_web_preview() {
+    let html = r#"<html><body>
+        <div class="tgme_widget_message"><div class="tgme_widget_message_text">First <b>post</b></div></div>
+        <div class="tgme_widget_message"><div class="tgme_widget_message_photo"></div></div>
+        <div class="tgme_widget_message"><div class="tgme_widget_message_text">  claim-7KQ2MX9P  </div></div>
+        </body></html>"#;
+    assert_eq!(
+        recent_post_texts(html),
+        vec!["First post".to_string(), "claim-7KQ2MX9P".to_string()]
+    );
+}
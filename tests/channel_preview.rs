@@ -0,0 +1,96 @@
+// Tests for parsing the channel preview shown before the analysis types
+use tg_main::web_scraper::ChannelPreview;
+
+const LONG_TEXT: &str = "A post with enough text to count towards the analysis";
+
+fn post(id: u32, date: &str, body: &str) -> String {
+    format!(
+        r#"<div class="tgme_widget_message_wrap"><div class="tgme_widget_message" data-post="channel/{id}">{body}<a class="tgme_widget_message_date"><time datetime="{date}T10:00:00+00:00"></time></a></div></div>"#
+    )
+}
+
+fn page(posts: &[String]) -> String {
+    format!(
+        r#"<html><body>
+        <div class="tgme_channel_info_header_title"><span dir="auto">Test &amp; Channel</span></div>
+        <div class="tgme_channel_info_counters">
+            <div class="tgme_channel_info_counter"><span class="counter_value">12.3K</span> <span class="counter_type">subscribers</span></div>
+            <div class="tgme_channel_info_counter"><span class="counter_value">85</span> <span class="counter_type">photos</span></div>
+        </div>
+        {}
+        </body></html>"#,
+        posts.join("\n")
+    )
+}
+
+#[test]
+fn test_preview_of_text_channel() {
+    let text = format!(r#"<div class="tgme_widget_message_text">{LONG_TEXT}</div>"#);
+    let html = page(&[
+        post(1, "2026-09-30", &text),
+        post(
+            2,
+            "2026-10-01",
+            r#"<div class="tgme_widget_message_text">ok</div>"#,
+        ),
+        post(3, "2026-10-02", &text),
+        post(
+            4,
+            "2026-10-03",
+            r#"<div class="tgme_widget_message_forwarded_from">other</div><div class="tgme_widget_message_text">forwarded, not counted at all</div>"#,
+        ),
+    ]);
+
+    let preview = ChannelPreview::parse(&html).expect("Channel page should parse");
+    assert_eq!(preview.title, "Test & Channel");
+    assert_eq!(preview.subscribers, Some(12_300));
+    // forwarded posts still tell when the channel was last active
+    assert_eq!(preview.last_post_date.as_deref(), Some("2026-10-03"));
+    assert_eq!(preview.posts_sampled, 3);
+    assert_eq!(preview.text_posts, 2);
+    assert_eq!(preview.text_coverage(), Some(66));
+}
+
+#[test]
+fn test_preview_of_image_only_channel() {
+    let photo = r#"<a class="tgme_widget_message_photo_wrap" style="background-image:url('https://cdn/1.jpg')"></a>"#;
+    let html = page(&[post(1, "2026-10-01", photo), post(2, "2026-10-02", photo)]);
+
+    let preview = ChannelPreview::parse(&html).expect("Channel page should parse");
+    assert_eq!(preview.posts_sampled, 2);
+    assert_eq!(preview.text_coverage(), Some(0));
+
+    let empty = ChannelPreview::parse(&page(&[])).expect("Channel page should parse");
+    assert_eq!(empty.last_post_date, None);
+    assert_eq!(empty.text_coverage(), None);
+}
+
+#[test]
+fn test_pages_other_than_channels_have_no_preview() {
+    // telegram redirects users and bots to a page without channel info
+    let html =
+        r#"<html><body><div class="tgme_page_title"><span>Some User</span></div></body></html>"#;
+    assert_eq!(ChannelPreview::parse(html), None);
+}
+
+#[test]
+fn test_preview_keeps_views_and_reactions_of_every_post() {
+    let counters = r#"<div class="tgme_widget_message_reactions"><span class="tgme_reaction"><i class="emoji">👍</i>1.2K</span><span class="tgme_reaction"><i class="emoji">🔥</i>34</span></div><span class="tgme_widget_message_views">15.4K</span>"#;
+    let html = page(&[
+        post(1, "2026-10-01", counters),
+        post(
+            2,
+            "2026-10-02",
+            r#"<div class="tgme_widget_message_forwarded_from">other</div><span class="tgme_widget_message_views">900</span>"#,
+        ),
+        post(3, "2026-10-03", "<div>views hidden</div>"),
+    ]);
+
+    let preview = ChannelPreview::parse(&html).expect("Channel page should parse");
+    let views: Vec<_> = preview.posts.iter().map(|post| post.views).collect();
+    let reactions: Vec<_> = preview.posts.iter().map(|post| post.reactions).collect();
+    // forwarded posts reach the channel's audience too
+    assert_eq!(views, vec![Some(15_400), Some(900), None]);
+    assert_eq!(reactions, vec![1_234, 0, 0]);
+    assert_eq!(preview.posts[0].published_at, Some(1_790_848_800));
+}
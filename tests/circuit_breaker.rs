@@ -0,0 +1,84 @@
+// Tests for the circuit breakers of the LLM and Telegram backends
+use std::time::{Duration, Instant};
+use tg_main::analysis::AnalysisError;
+use tg_main::circuit_breaker::{BreakerState, CircuitBreaker};
+use tg_main::localization::Lang;
+
+const COOLDOWN: Duration = Duration::from_secs(60);
+
+#[test]
+fn test_breaker_opens_after_consecutive_failures() {
+    let breaker = CircuitBreaker::new("test", 3, COOLDOWN);
+    let now = Instant::now();
+
+    breaker.record_failure(now);
+    breaker.record_failure(now);
+    // a success in between resets the count
+    breaker.record_success();
+    breaker.record_failure(now);
+    breaker.record_failure(now);
+    assert_eq!(breaker.state(), BreakerState::Closed);
+    assert!(breaker.try_acquire(now).is_ok());
+
+    breaker.record_failure(now);
+    assert_eq!(breaker.state(), BreakerState::Open);
+    assert_eq!(
+        breaker.try_acquire(now + Duration::from_secs(59)),
+        Err(AnalysisError::ServiceDegraded)
+    );
+}
+
+#[test]
+fn test_one_probe_goes_through_after_the_cooldown() {
+    let breaker = CircuitBreaker::new("test", 1, COOLDOWN);
+    let opened = Instant::now();
+    breaker.record_failure(opened);
+
+    let later = opened + COOLDOWN;
+    assert!(breaker.try_acquire(later).is_ok());
+    assert_eq!(breaker.state(), BreakerState::HalfOpen);
+    // everyone else still fails fast while the probe runs
+    assert!(breaker.try_acquire(later).is_err());
+
+    breaker.record_success();
+    assert_eq!(breaker.state(), BreakerState::Closed);
+    assert!(breaker.try_acquire(later).is_ok());
+}
+
+#[test]
+fn test_failed_probe_opens_the_breaker_for_another_cooldown() {
+    let breaker = CircuitBreaker::new("test", 1, COOLDOWN);
+    let opened = Instant::now();
+    breaker.record_failure(opened);
+
+    let probed = opened + COOLDOWN;
+    assert!(breaker.try_acquire(probed).is_ok());
+    breaker.record_failure(probed);
+    assert_eq!(breaker.state(), BreakerState::Open);
+    assert!(breaker
+        .try_acquire(probed + Duration::from_secs(30))
+        .is_err());
+    assert!(breaker.try_acquire(probed + COOLDOWN).is_ok());
+}
+
+#[test]
+fn test_probe_that_never_reports_is_replaced() {
+    let breaker = CircuitBreaker::new("test", 1, COOLDOWN);
+    let opened = Instant::now();
+    breaker.record_failure(opened);
+
+    assert!(breaker.try_acquire(opened + COOLDOWN).is_ok());
+    assert!(breaker.try_acquire(opened + COOLDOWN * 2).is_ok());
+    assert_eq!(breaker.state(), BreakerState::HalfOpen);
+}
+
+#[test]
+fn test_degraded_service_message_says_no_credits_were_spent() {
+    for lang in Lang::ALL {
+        let message = lang.error_analysis_failed(&AnalysisError::ServiceDegraded, "@channel");
+        assert!(!message.is_empty());
+    }
+    let message = Lang::En.error_analysis_failed(&AnalysisError::ServiceDegraded, "@channel");
+    assert!(message.contains("try again in a few minutes"));
+    assert!(message.contains("No credits were consumed"));
+}
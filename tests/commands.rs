@@ -0,0 +1,45 @@
+// Tests for bot command parsing
+use teloxide::utils::command::BotCommands;
+use tg_main::bot::Command;
+
+#[test]
+fn test_analyze_group_command_parses_with_bot_mention() {
+    let cmd = Command::parse("/analyze_group@ScratchAuthorEgoBot", "ScratchAuthorEgoBot")
+        .expect("Failed to parse command");
+    assert!(matches!(cmd, Command::AnalyzeGroup));
+}
+
+#[test]
+fn test_analyze_group_command_is_listed() {
+    assert!(Command::bot_commands()
+        .iter()
+        .any(|command| command.command == "/analyze_group"));
+}
+
+#[test]
+fn test_top_command_is_listed() {
+    let cmd = Command::parse("/top", "ScratchAuthorEgoBot").expect("Failed to parse command");
+    assert!(matches!(cmd, Command::Top));
+    assert!(Command::bot_commands()
+        .iter()
+        .any(|command| command.command == "/top"));
+}
+
+#[test]
+fn test_analyze_command_parses_type_argument() {
+    let cmd = Command::parse("/analyze@ScratchAuthorEgoBot roast", "ScratchAuthorEgoBot")
+        .expect("Failed to parse command");
+    assert!(matches!(cmd, Command::Analyze(ref args) if args == "roast"));
+
+    let cmd = Command::parse("/analyze", "ScratchAuthorEgoBot").expect("Failed to parse command");
+    assert!(matches!(cmd, Command::Analyze(ref args) if args.is_empty()));
+}
+
+#[test]
+fn test_resend_command_is_listed() {
+    let cmd = Command::parse("/resend", "ScratchAuthorEgoBot").expect("Failed to parse command");
+    assert!(matches!(cmd, Command::Resend));
+    assert!(Command::bot_commands()
+        .iter()
+        .any(|command| command.command == "/resend"));
+}
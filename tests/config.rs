@@ -0,0 +1,86 @@
+// Tests for the reloadable config
+use tg_main::config::{is_reloadable, Config, Settings};
+use tg_main::limits::Limits;
+use tg_main::llm::routing::ModelRouting;
+use tg_main::rate_limiters::gemini::GeminiQuotas;
+
+#[test]
+fn test_config_file_wins_over_the_environment() {
+    std::env::set_var("LIMIT_WHATS_NEW_ENTRIES", "7");
+    std::env::set_var("LIMIT_AUDIT_LOG_ENTRIES", "30");
+    let settings = Settings::parse(
+        "# tuned without a redeploy\nLIMIT_WHATS_NEW_ENTRIES=9\nMODEL_ROUTING=\"roast=gemini-2.5-flash-lite\"\n",
+    )
+    .expect("Failed to parse settings");
+
+    let mut limits = Limits::default();
+    limits.apply_settings(&settings);
+    assert_eq!(limits.whats_new_entries, 9);
+    assert_eq!(limits.audit_log_entries, 30);
+    assert_eq!(
+        settings.get("MODEL_ROUTING").as_deref(),
+        Some("roast=gemini-2.5-flash-lite")
+    );
+}
+
+#[test]
+fn test_malformed_config_file_is_rejected() {
+    assert!(Settings::parse("LIMIT_TOP_CHANNELS='25\n").is_err());
+}
+
+#[test]
+fn test_config_reads_model_routing_and_gemini_quotas() {
+    let settings = Settings::parse(
+        "MODEL_ROUTING=roast=gemini-2.5-flash-lite:60:1\nGEMINI_QUOTAS=*=30:500000:2\n",
+    )
+    .expect("Failed to parse settings");
+
+    let config = Config::from_settings(&settings, Limits::default());
+    assert_eq!(
+        config.model_routing,
+        ModelRouting::parse("roast=gemini-2.5-flash-lite:60:1")
+    );
+    assert_eq!(config.gemini_quotas, GeminiQuotas::parse("*=30:500000:2"));
+}
+
+#[test]
+fn test_changes_list_limits_then_specs() {
+    let current = Config::from_settings(&Settings::default(), Limits::default());
+    assert!(current.changes(&current).is_empty());
+
+    let mut limits = Limits::default();
+    limits.set("top_channels", 20).expect("Failed to set limit");
+    limits
+        .set("max_running_analyses", 2)
+        .expect("Failed to set limit");
+    let mut next = Config::from_settings(&Settings::default(), limits);
+    next.model_routing = ModelRouting::parse("roast=gemini-2.5-pro");
+
+    assert_eq!(
+        current.changes(&next),
+        vec![
+            "top_channels: 10 → 20".to_string(),
+            "max_running_analyses: 1 → 2".to_string(),
+            "MODEL_ROUTING".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_config_file_only_carries_reloadable_settings() {
+    assert!(is_reloadable("LIMIT_TOP_CHANNELS"));
+    assert!(is_reloadable("MODEL_ROUTING"));
+    assert!(is_reloadable("GEMINI_QUOTAS"));
+    assert!(!is_reloadable("LIMIT_NOT_A_LIMIT"));
+    assert!(!is_reloadable("BACKUP_DESTINATION"));
+    assert!(!is_reloadable("GEMINI_API_KEY"));
+
+    // startup-only settings are read from the environment, the file's would go unused
+    let settings = Settings::parse(
+        "LIMIT_TOP_CHANNELS=25\nBACKUP_DESTINATION=/from/config/file\nLIMIT_NOT_A_LIMIT=3\n",
+    )
+    .expect("Failed to parse settings");
+    assert_eq!(settings.get("LIMIT_TOP_CHANNELS").as_deref(), Some("25"));
+    assert_eq!(settings.get("BACKUP_DESTINATION"), None);
+    assert_eq!(settings.get("LIMIT_NOT_A_LIMIT"), None);
+}
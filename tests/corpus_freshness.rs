@@ -0,0 +1,102 @@
+// Tests for when cached channel messages are served, refreshed behind the analysis or
+// refreshed before it, and how a refresh of only the newest posts is merged into them
+use std::time::Duration;
+use tg_main::analysis::{CorpusKind, MessageDict};
+use tg_main::cache::{
+    merge_fetched_messages, CachedCorpus, CorpusFreshness, CORPUS_FRESH_AGE, CORPUS_FULL_FETCH_AGE,
+    CORPUS_REVALIDATE_AGE,
+};
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn post(id: i64) -> MessageDict {
+    MessageDict {
+        id: Some(id),
+        date: Some("2025-11-30".to_string()),
+        message: Some(format!("post number {}", id)),
+        images: None,
+        thread_id: None,
+    }
+}
+
+fn ids(messages: &[MessageDict]) -> Vec<Option<i64>> {
+    messages.iter().map(|msg| msg.id).collect()
+}
+
+fn corpus(kind: CorpusKind, full_fetch_age: Option<Duration>) -> CachedCorpus {
+    CachedCorpus {
+        messages: vec![post(12), post(11)],
+        age: 30 * HOUR,
+        backend: None,
+        skipped: 0,
+        kind,
+        last_message_id: Some(12),
+        full_fetch_age,
+    }
+}
+
+#[test]
+fn test_corpus_freshness_by_age() {
+    assert_eq!(CorpusFreshness::of(Duration::ZERO), CorpusFreshness::Fresh);
+    assert_eq!(CorpusFreshness::of(23 * HOUR), CorpusFreshness::Fresh);
+    assert_eq!(
+        CorpusFreshness::of(CORPUS_FRESH_AGE),
+        CorpusFreshness::Stale
+    );
+    assert_eq!(CorpusFreshness::of(60 * HOUR), CorpusFreshness::Stale);
+    assert_eq!(
+        CorpusFreshness::of(CORPUS_REVALIDATE_AGE),
+        CorpusFreshness::Outdated
+    );
+    // stale corpora are served well before the cache expires
+    assert!(CORPUS_REVALIDATE_AGE < 7 * 24 * HOUR);
+}
+
+#[test]
+fn test_refresh_fetches_from_the_newest_cached_post_after_a_recent_full_fetch() {
+    let recent = corpus(CorpusKind::Channel, Some(30 * HOUR));
+    assert_eq!(recent.fetch_since(), Some(12));
+
+    // posts deleted since are only seen by a full fetch, which is due every few days
+    let due = corpus(CorpusKind::Channel, Some(CORPUS_FULL_FETCH_AGE));
+    assert_eq!(due.fetch_since(), None);
+    let never = corpus(CorpusKind::Channel, None);
+    assert_eq!(never.fetch_since(), None);
+    // a profile's name and bio have no ids to fetch from
+    let profile = corpus(CorpusKind::Profile, Some(HOUR));
+    assert_eq!(profile.fetch_since(), None);
+}
+
+#[test]
+fn test_merge_puts_new_posts_before_the_cached_ones() {
+    let cached = vec![post(12), post(11), post(10)];
+    // the fetch overlaps the cache at 12, which is kept once
+    let fetched = vec![post(14), post(13), post(12)];
+
+    let (messages, skipped) = merge_fetched_messages(fetched, 1, &cached, 2, 12, 10);
+    assert_eq!(
+        ids(&messages),
+        vec![Some(14), Some(13), Some(12), Some(11), Some(10)]
+    );
+    assert_eq!(skipped, 3);
+
+    // nothing new leaves the corpus as it was
+    let (messages, skipped) = merge_fetched_messages(Vec::new(), 0, &cached, 2, 12, 10);
+    assert_eq!(ids(&messages), ids(&cached));
+    assert_eq!(skipped, 2);
+}
+
+#[test]
+fn test_merge_drops_the_oldest_posts_past_the_limit() {
+    // web preview pages list their posts oldest first
+    let cached = vec![post(9), post(10), post(11), post(7), post(8)];
+    let fetched = vec![post(12), post(13)];
+
+    let (messages, skipped) = merge_fetched_messages(fetched, 0, &cached, 7, 11, 5);
+    assert_eq!(
+        ids(&messages),
+        vec![Some(12), Some(13), Some(9), Some(10), Some(11)]
+    );
+    // the skipped posts shrink with the two messages dropped
+    assert_eq!(skipped, 5);
+}
@@ -0,0 +1,55 @@
+// Tests for refusing oversized channels before the llm is queried
+use tg_main::analysis::{corpus_chars, AnalysisDepth, AnalysisError, MessageDict};
+use tg_main::localization::Lang;
+
+fn message(text: Option<&str>, images: Option<Vec<String>>) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: Some("2024-01-01T00:00:00Z".to_string()),
+        message: text.map(str::to_string),
+        images,
+        thread_id: None,
+    }
+}
+
+#[test]
+fn test_corpus_chars_counts_only_message_text() {
+    let messages = vec![
+        message(Some("привет"), None),
+        message(
+            None,
+            Some(vec!["https://example.com/image.jpg".to_string()]),
+        ),
+        message(
+            Some("hello"),
+            Some(vec!["https://example.com/other.jpg".to_string()]),
+        ),
+    ];
+
+    assert_eq!(corpus_chars(&messages), 11);
+}
+
+#[test]
+fn test_deeper_tiers_get_larger_corpus_budget() {
+    let small = AnalysisDepth::Small.corpus_char_limit(1000);
+    let medium = AnalysisDepth::Medium.corpus_char_limit(1000);
+    let deep = AnalysisDepth::Deep.corpus_char_limit(1000);
+
+    assert_eq!(small, 1000);
+    assert!(small < medium && medium < deep);
+}
+
+#[test]
+fn test_oversized_channel_error_explains_limit_and_alternatives() {
+    let failure = AnalysisError::CorpusTooLarge {
+        depth: AnalysisDepth::Small,
+        chars: 512_000,
+        limit: 400_000,
+    };
+
+    let text = Lang::En.error_analysis_failed(&failure, "@huge");
+    assert!(text.contains("@huge"));
+    assert!(text.contains("400000"));
+    assert!(text.contains("Deep"));
+    assert!(text.contains("No credits were consumed"));
+}
@@ -0,0 +1,66 @@
+// Tests for the profile built from a user's messages across the groups they share with the bot
+use tg_main::analysis::{cross_group_corpus_name, is_cross_group_corpus, is_self_corpus};
+use tg_main::cross_group::{corpus_messages, StoredGroupMessage};
+use tg_main::localization::Lang;
+use tg_main::prompts::analysis::{generate_cross_group_prompt, OutputLanguage};
+use tg_main::utils::ResultPresenter;
+
+fn stored(chat_title: &str, text: &str) -> StoredGroupMessage {
+    StoredGroupMessage {
+        chat_title: chat_title.to_string(),
+        text: text.to_string(),
+        date: "2024-03-04".to_string(),
+    }
+}
+
+#[test]
+fn test_corpus_messages_name_their_group() {
+    let messages = corpus_messages(&[
+        stored("Rust Moscow", "borrow checker again"),
+        stored("Climbing", "who's in on saturday?"),
+    ]);
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].message.as_deref(),
+        Some("[Rust Moscow] borrow checker again")
+    );
+    assert_eq!(
+        messages[1].message.as_deref(),
+        Some("[Climbing] who's in on saturday?")
+    );
+    assert_eq!(messages[0].date.as_deref(), Some("2024-03-04"));
+    assert_eq!(messages[0].id, None);
+}
+
+#[test]
+fn test_cross_group_corpus_is_never_fetched() {
+    let name = cross_group_corpus_name(42);
+    assert!(is_cross_group_corpus(&name));
+    // treated like a self-analysis everywhere channels are fetched or ranked
+    assert!(is_self_corpus(&name));
+    assert!(!name.starts_with('@'));
+    assert!(!is_cross_group_corpus("@groups"));
+
+    assert_eq!(
+        ResultPresenter::target_label(&name, Lang::En),
+        "your messages in groups"
+    );
+}
+
+#[test]
+fn test_cross_group_prompt_explains_the_group_prefix() {
+    let prompt = generate_cross_group_prompt(
+        &corpus_messages(&[stored("Rust Moscow", "borrow checker again")]),
+        Some("career"),
+        OutputLanguage::English,
+    )
+    .unwrap();
+    for text in [&prompt.json, &prompt.tagged] {
+        assert!(text.contains("several group chats"));
+        assert!(text.contains("[Rust Moscow] borrow checker again"));
+        assert!(text.contains("REQUESTER FOCUS"));
+        assert!(text.contains("Write in English"));
+    }
+    assert!(prompt.tagged.contains("<roast>"));
+}
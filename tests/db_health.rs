@@ -0,0 +1,124 @@
+// Tests for deciding when database health checks alert the owners
+use std::time::Duration;
+use tg_main::db_health::{
+    CheckOutcome, HealthAlert, HealthTracker, ALERT_AFTER_CHECKS, SLOW_ACQUIRE,
+};
+use tg_main::localization::Lang;
+
+fn failed() -> CheckOutcome {
+    CheckOutcome::Failed("connection refused".to_string())
+}
+
+#[test]
+fn test_outcome_is_slow_past_the_threshold() {
+    assert_eq!(
+        CheckOutcome::from_elapsed(Duration::from_millis(20)),
+        CheckOutcome::Healthy(Duration::from_millis(20))
+    );
+    assert_eq!(
+        CheckOutcome::from_elapsed(SLOW_ACQUIRE),
+        CheckOutcome::Slow(SLOW_ACQUIRE)
+    );
+}
+
+#[test]
+fn test_blips_dont_alert() {
+    let mut tracker = HealthTracker::new();
+    for _ in 1..ALERT_AFTER_CHECKS {
+        assert!(tracker.record(&failed()).is_none());
+    }
+    // a healthy check ends the streak without a recovery message
+    assert!(tracker
+        .record(&CheckOutcome::Healthy(Duration::from_millis(5)))
+        .is_none());
+    for _ in 1..ALERT_AFTER_CHECKS {
+        assert!(tracker.record(&failed()).is_none());
+    }
+}
+
+#[test]
+fn test_outage_alerts_once_and_then_recovers() {
+    let mut tracker = HealthTracker::new();
+    for _ in 1..ALERT_AFTER_CHECKS {
+        assert!(tracker.record(&failed()).is_none());
+    }
+    let alert = tracker.record(&failed()).expect("Outage should alert");
+    assert_eq!(
+        alert,
+        HealthAlert::Unreachable {
+            checks: ALERT_AFTER_CHECKS,
+            error: "connection refused".to_string(),
+        }
+    );
+    tracker.alert_sent(&alert);
+    assert!(tracker.record(&failed()).is_none());
+
+    let recovered = tracker.record(&CheckOutcome::Healthy(Duration::from_millis(5)));
+    assert_eq!(
+        recovered,
+        Some(HealthAlert::Recovered {
+            checks: ALERT_AFTER_CHECKS + 1
+        })
+    );
+    tracker.alert_sent(&recovered.unwrap());
+    // a new outage is reported again
+    for _ in 1..ALERT_AFTER_CHECKS {
+        assert!(tracker.record(&failed()).is_none());
+    }
+    assert!(tracker.record(&failed()).is_some());
+}
+
+#[test]
+fn test_undelivered_alert_is_retried() {
+    let mut tracker = HealthTracker::new();
+    for _ in 1..ALERT_AFTER_CHECKS {
+        tracker.record(&failed());
+    }
+    assert!(tracker.record(&failed()).is_some());
+    assert!(tracker.record(&failed()).is_some());
+}
+
+#[test]
+fn test_slow_streak_reports_the_slowest_check() {
+    let mut tracker = HealthTracker::new();
+    tracker.record(&CheckOutcome::Slow(Duration::from_secs(3)));
+    tracker.record(&CheckOutcome::Slow(Duration::from_secs(7)));
+    let alert = tracker.record(&CheckOutcome::Slow(Duration::from_secs(4)));
+    assert_eq!(
+        alert,
+        Some(HealthAlert::Slow {
+            checks: 3,
+            slowest: Duration::from_secs(7),
+        })
+    );
+}
+
+#[test]
+fn test_a_failure_among_slow_checks_reports_unreachable() {
+    let mut tracker = HealthTracker::new();
+    tracker.record(&CheckOutcome::Slow(Duration::from_secs(3)));
+    tracker.record(&failed());
+    let alert = tracker.record(&CheckOutcome::Slow(Duration::from_secs(3)));
+    assert!(matches!(
+        alert,
+        Some(HealthAlert::Unreachable { checks: 3, .. })
+    ));
+}
+
+#[test]
+fn test_alert_messages_escape_the_error() {
+    let alert = HealthAlert::Unreachable {
+        checks: 3,
+        error: "error <db>".to_string(),
+    };
+    for lang in [Lang::En, Lang::Ru, Lang::Uk, Lang::Es, Lang::De] {
+        let message = alert.message(lang);
+        assert!(message.contains("error &lt;db&gt;"));
+        assert!(!message.contains("<db>"));
+    }
+    let slow = HealthAlert::Slow {
+        checks: 3,
+        slowest: Duration::from_millis(2500),
+    };
+    assert!(slow.message(Lang::En).contains("2.5s"));
+}
@@ -0,0 +1,65 @@
+// Tests for reconciling cached corpora with the posts still visible in a channel
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use tg_main::analysis::{count_deleted_messages, MessageDict};
+
+fn message(id: Option<i64>, text: &str) -> MessageDict {
+    MessageDict {
+        id,
+        date: Some("2024-01-01".to_string()),
+        message: Some(text.to_string()),
+        images: None,
+        thread_id: None,
+    }
+}
+
+fn hash(messages: &[MessageDict]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    messages.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_deleted_posts_within_fetch_window_are_counted() {
+    let cached = vec![
+        message(Some(10), "old post out of the window"),
+        message(Some(20), "kept"),
+        message(Some(21), "deleted"),
+        message(Some(23), "also deleted"),
+    ];
+    let fresh = vec![
+        message(Some(20), "kept"),
+        message(Some(22), "new since the cache"),
+        message(Some(24), "newer"),
+    ];
+
+    assert_eq!(count_deleted_messages(&cached, &fresh), 2);
+}
+
+#[test]
+fn test_corpora_without_ids_are_not_reconciled() {
+    let cached = vec![message(None, "cached before ids"), message(None, "another")];
+    let fresh = vec![message(Some(5), "fresh")];
+    assert_eq!(count_deleted_messages(&cached, &fresh), 0);
+
+    // nothing visible means nothing to compare against
+    let cached = vec![message(Some(5), "post")];
+    assert_eq!(count_deleted_messages(&cached, &[]), 0);
+}
+
+#[test]
+fn test_ids_keep_llm_cache_keys_stable() {
+    let with_ids = vec![message(Some(1), "same"), message(Some(2), "text")];
+    let without_ids = vec![message(None, "same"), message(None, "text")];
+    assert_eq!(hash(&with_ids), hash(&without_ids));
+}
+
+#[test]
+fn test_corpora_cached_without_ids_still_parse() {
+    let cached = r#"[{"date": "2024-01-01", "message": "hello"}]"#;
+    let messages: Vec<MessageDict> = serde_json::from_str(cached).expect("should parse");
+    assert_eq!(messages[0].id, None);
+
+    let json = serde_json::to_string(&[message(Some(7), "hello")]).expect("should serialize");
+    assert!(json.contains("\"id\":7"));
+}
@@ -0,0 +1,98 @@
+// Tests for the quick facts counted from a corpus before the llm call
+use chrono::Weekday;
+use tg_main::analysis::MessageDict;
+use tg_main::facts::{QuickFacts, TOP_KEYWORDS};
+
+fn message(date: &str, text: &str) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: Some(date.to_string()),
+        message: Some(text.to_string()),
+        images: None,
+        thread_id: None,
+    }
+}
+
+#[test]
+fn test_keywords_and_hashtags_are_counted() {
+    let messages = vec![
+        message(
+            "2026-10-13",
+            "Rust async runtimes: tokio and smol. #Rust #release",
+        ),
+        message(
+            "2026-10-14",
+            "Another tokio release, with async traits. #rust",
+        ),
+        message(
+            "2026-10-15",
+            "Read https://tokio.rs/blog and ask @tokio_rs about Tokio 2026",
+        ),
+    ];
+    let facts = QuickFacts::from_messages(&messages);
+
+    // links, mentions, numbers and words used once aren't counted
+    assert_eq!(
+        facts.keywords,
+        vec![("tokio".to_string(), 3), ("async".to_string(), 2)]
+    );
+    // hashtags are counted apart from the words, case and trailing punctuation aside
+    assert_eq!(
+        facts.hashtags,
+        vec![("#rust".to_string(), 2), ("#release".to_string(), 1)]
+    );
+}
+
+#[test]
+fn test_stop_words_and_short_words_are_skipped() {
+    let text = "this is what they said about that, and this is what it was about";
+    let facts =
+        QuickFacts::from_messages(&[message("2026-10-13", text), message("2026-10-13", text)]);
+    assert!(facts.keywords.iter().all(|(word, _)| word == "said"));
+
+    let russian = "Это очень просто, когда есть Rust. Это очень просто";
+    let facts = QuickFacts::from_messages(&[message("2026-10-13", russian)]);
+    assert!(facts.keywords.is_empty());
+}
+
+#[test]
+fn test_keywords_are_limited_and_ordered() {
+    let text = (0..TOP_KEYWORDS + 5)
+        .map(|i| format!("word{:02} ", i).repeat(i + 2))
+        .collect::<String>();
+    let facts = QuickFacts::from_messages(&[message("2026-10-13", &text)]);
+    assert_eq!(facts.keywords.len(), TOP_KEYWORDS);
+    assert_eq!(facts.keywords[0], ("word14".to_string(), 16));
+    assert!(facts.keywords.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+}
+
+#[test]
+fn test_weekday_histogram() {
+    let messages = vec![
+        // monday, tuesday twice and sunday
+        message("2026-10-12", "a"),
+        message("2026-10-13", "b"),
+        message("2026-10-13T09:30:00Z", "c"),
+        message("2026-10-18", "d"),
+        MessageDict {
+            date: None,
+            ..message("", "undated")
+        },
+    ];
+    let facts = QuickFacts::from_messages(&messages);
+    assert_eq!(facts.weekdays, [1, 2, 0, 0, 0, 0, 1]);
+    assert_eq!(facts.busiest_weekday(), Some(Weekday::Tue));
+
+    let section = facts.prompt_section();
+    assert!(section.contains("QUICK FACTS"));
+    assert!(section.contains("Mon 1, Tue 2, Wed 0, Thu 0, Fri 0, Sat 0, Sun 1"));
+    assert!(!section.contains("Hashtags"));
+}
+
+#[test]
+fn test_nothing_counted_gives_no_prompt_section() {
+    let facts = QuickFacts::from_messages(&[]);
+    assert!(facts.is_empty());
+    assert_eq!(facts.busiest_weekday(), None);
+    assert_eq!(facts.prompt_section(), "");
+}
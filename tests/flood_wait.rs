@@ -0,0 +1,52 @@
+// Tests for FLOOD_WAIT handling in the rate limiters
+use std::time::Duration;
+use tg_main::backend_config::{BackendRateLimiter, BackendType};
+use tg_main::rate_limiters::telegram::{TelegramRateLimiter, FLOOD_WAIT_THRESHOLD};
+
+#[tokio::test]
+async fn test_short_flood_wait_is_recorded_precisely() {
+    let limiter = TelegramRateLimiter::new();
+    assert!(limiter.flood_wait_remaining().await.is_none());
+
+    assert!(limiter.record_flood_wait(5).await);
+    let remaining = limiter
+        .flood_wait_remaining()
+        .await
+        .expect("Flood wait should be in effect");
+    assert!(remaining <= Duration::from_secs(5));
+    assert!(remaining > Duration::from_secs(4));
+
+    // a shorter wait doesn't cut the current one short
+    assert!(limiter.record_flood_wait(1).await);
+    assert!(limiter.flood_wait_remaining().await.unwrap() > Duration::from_secs(4));
+}
+
+#[tokio::test]
+async fn test_long_flood_wait_is_not_waited_out() {
+    let limiter = TelegramRateLimiter::new();
+    let too_long = FLOOD_WAIT_THRESHOLD.as_secs() as u32 + 1;
+
+    assert!(!limiter.record_flood_wait(too_long).await);
+    assert!(limiter.flood_wait_remaining().await.is_none());
+}
+
+#[test]
+fn test_flood_waited_api_backend_is_skipped() {
+    let mut limiter = BackendRateLimiter::new();
+    let backends = [BackendType::Api, BackendType::WebScraping];
+    assert_eq!(
+        limiter.select_available_backend(&backends),
+        Some(BackendType::Api)
+    );
+
+    limiter.record_flood_wait(3600);
+    let wait = limiter
+        .time_until_available(BackendType::Api)
+        .expect("API should be flood-waited");
+    assert!(wait > Duration::from_secs(3500));
+    assert!(limiter.is_available(BackendType::WebScraping));
+    assert_eq!(
+        limiter.select_available_backend(&backends),
+        Some(BackendType::WebScraping)
+    );
+}
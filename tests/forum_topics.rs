@@ -0,0 +1,51 @@
+// Tests for limiting group analyses to one forum topic
+use tg_main::analysis::{topic_messages, MessageDict};
+
+fn message(thread_id: Option<i32>, text: &str) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: Some("2024-01-01".to_string()),
+        message: Some(text.to_string()),
+        images: None,
+        thread_id,
+    }
+}
+
+fn texts(messages: &[MessageDict]) -> Vec<&str> {
+    messages
+        .iter()
+        .filter_map(|msg| msg.message.as_deref())
+        .collect()
+}
+
+#[test]
+fn test_topic_messages_keep_only_the_topic() {
+    let messages = vec![
+        message(Some(7), "hiring post"),
+        message(None, "general chatter"),
+        message(Some(9), "memes"),
+        message(Some(7), "another hiring post"),
+    ];
+
+    assert_eq!(
+        texts(&topic_messages(messages, 7)),
+        vec!["hiring post", "another hiring post"]
+    );
+}
+
+#[test]
+fn test_corpora_cached_without_topics_have_no_topic_messages() {
+    // thread ids were added later, older cached corpora lack them
+    let cached: Vec<MessageDict> =
+        serde_json::from_str(r#"[{"date": "2024-01-01", "message": "old"}]"#).unwrap();
+    assert_eq!(cached[0].thread_id, None);
+    assert!(topic_messages(cached, 7).is_empty());
+}
+
+#[test]
+fn test_thread_ids_are_only_serialized_when_set() {
+    let json = serde_json::to_string(&message(None, "general")).unwrap();
+    assert!(!json.contains("thread_id"));
+    let json = serde_json::to_string(&message(Some(7), "topic")).unwrap();
+    assert!(json.contains("\"thread_id\":7"));
+}
@@ -0,0 +1,56 @@
+// Tests for the free trial fingerprints and the /trialclusters command
+use teloxide::utils::command::BotCommands;
+use tg_main::bot::Command;
+use tg_main::free_trials::signup_fingerprint;
+use tg_main::limits::Limits;
+
+#[test]
+fn test_fingerprint_groups_look_alike_signups() {
+    assert_eq!(
+        signup_fingerprint(Some("ru"), false, false, Some(42)).as_deref(),
+        Some("lang=ru username=no last_name=no referrer=42")
+    );
+    assert_eq!(
+        signup_fingerprint(None, true, true, Some(42)).as_deref(),
+        Some("lang=- username=yes last_name=yes referrer=42")
+    );
+    // the client's language casing and blank codes don't split a cluster
+    assert_eq!(
+        signup_fingerprint(Some(" RU "), false, false, Some(42)),
+        signup_fingerprint(Some("ru"), false, false, Some(42))
+    );
+    assert_eq!(
+        signup_fingerprint(Some(""), false, false, Some(42)),
+        signup_fingerprint(None, false, false, Some(42))
+    );
+    assert_ne!(
+        signup_fingerprint(Some("ru"), false, false, Some(42)),
+        signup_fingerprint(Some("ru"), true, false, Some(42))
+    );
+    assert_ne!(
+        signup_fingerprint(Some("ru"), false, false, Some(42)),
+        signup_fingerprint(Some("ru"), false, false, Some(43))
+    );
+}
+
+#[test]
+fn test_signups_without_a_referrer_have_no_fingerprint() {
+    assert_eq!(signup_fingerprint(Some("en"), true, false, None), None);
+    assert_eq!(signup_fingerprint(None, false, false, None), None);
+}
+
+#[test]
+fn test_free_trials_per_hour_default() {
+    assert_eq!(Limits::default().free_trials_per_hour, 10);
+}
+
+#[test]
+fn test_trial_clusters_command_is_hidden() {
+    let commands = Command::bot_commands();
+    assert!(!commands
+        .iter()
+        .any(|command| command.command == "/trialclusters"));
+    let cmd = Command::parse("/trialclusters clear 3", "ScratchAuthorEgoBot")
+        .expect("Failed to parse command");
+    assert!(matches!(cmd, Command::TrialClusters(ref args) if args == "clear 3"));
+}
@@ -0,0 +1,148 @@
+// Tests for the per model Gemini quotas
+use std::time::{Duration, Instant};
+use tg_main::rate_limiters::gemini::{
+    GeminiQuota, GeminiQuotas, GeminiRateLimiter, QuotaWindow, TOKENS_PER_SLOT,
+};
+use tokio::time::timeout;
+
+fn quota(requests_per_minute: u32, tokens_per_minute: u64, max_concurrent: u32) -> GeminiQuota {
+    GeminiQuota {
+        requests_per_minute,
+        tokens_per_minute,
+        max_concurrent,
+    }
+}
+
+#[test]
+fn test_quotas_parse_per_model_with_longest_prefix() {
+    let quotas = GeminiQuotas::parse(
+        "gemini-2.5-flash=1000:1000000, gemini-2.5-flash-lite=4000:4000000:16,*=30:500000:2",
+    );
+
+    assert_eq!(
+        quotas.for_model("gemini-2.5-flash"),
+        quota(1000, 1_000_000, GeminiQuota::DEFAULT.max_concurrent)
+    );
+    assert_eq!(
+        quotas.for_model("gemini-2.5-flash-lite-preview-06-17"),
+        quota(4000, 4_000_000, 16)
+    );
+    assert_eq!(quotas.for_model("gemini-2.5-pro"), quota(30, 500_000, 2));
+}
+
+#[test]
+fn test_malformed_quota_entries_are_ignored() {
+    let quotas = GeminiQuotas::parse("gemini-2.5-pro=0:100,=5:5,gemini-3=10:x,gemini-2=1:2:3:4");
+
+    assert_eq!(quotas, GeminiQuotas::default());
+    assert_eq!(quotas.for_model("gemini-2.5-pro"), GeminiQuota::DEFAULT);
+}
+
+#[test]
+fn test_large_prompts_take_more_slots() {
+    let quota = quota(60, 1_000_000, 4);
+
+    assert_eq!(quota.slots(0), 1);
+    assert_eq!(quota.slots(TOKENS_PER_SLOT - 1), 1);
+    assert_eq!(quota.slots(TOKENS_PER_SLOT), 2);
+    assert_eq!(quota.slots(100 * TOKENS_PER_SLOT), 4);
+}
+
+#[test]
+fn test_requests_per_minute_wait_for_the_oldest_call() {
+    let mut window = QuotaWindow::new(quota(2, 1_000_000, 8));
+    let start = Instant::now();
+
+    assert_eq!(window.try_reserve(10, start), Ok(()));
+    assert_eq!(
+        window.try_reserve(10, start + Duration::from_secs(20)),
+        Ok(())
+    );
+    assert_eq!(
+        window.try_reserve(10, start + Duration::from_secs(30)),
+        Err(Duration::from_secs(30))
+    );
+    // a minute after the first call there is room again
+    assert_eq!(
+        window.try_reserve(10, start + Duration::from_secs(60)),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_tokens_per_minute_wait_until_enough_tokens_expire() {
+    let mut window = QuotaWindow::new(quota(100, 1000, 8));
+    let start = Instant::now();
+
+    assert_eq!(window.try_reserve(400, start), Ok(()));
+    assert_eq!(
+        window.try_reserve(400, start + Duration::from_secs(10)),
+        Ok(())
+    );
+    // 900 more tokens need both earlier calls gone
+    assert_eq!(
+        window.try_reserve(900, start + Duration::from_secs(15)),
+        Err(Duration::from_secs(55))
+    );
+    // 300 fit once the first call is gone
+    assert_eq!(
+        window.try_reserve(300, start + Duration::from_secs(15)),
+        Err(Duration::from_secs(45))
+    );
+    assert_eq!(
+        window.try_reserve(200, start + Duration::from_secs(15)),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_prompt_over_the_whole_token_quota_goes_through_alone() {
+    let mut window = QuotaWindow::new(quota(100, 1000, 8));
+    let start = Instant::now();
+
+    assert_eq!(window.try_reserve(5000, start), Ok(()));
+    assert_eq!(
+        window.try_reserve(5000, start + Duration::from_secs(1)),
+        Err(Duration::from_secs(59))
+    );
+}
+
+#[tokio::test]
+async fn test_concurrent_calls_wait_for_slots() {
+    let limiter = GeminiRateLimiter::new(GeminiQuotas::parse("gemini-test=100:10000000:2"));
+
+    let large = limiter.acquire("gemini-test", TOKENS_PER_SLOT).await;
+    assert!(
+        timeout(
+            Duration::from_millis(50),
+            limiter.acquire("gemini-test", 10)
+        )
+        .await
+        .is_err(),
+        "a prompt taking both slots should hold back other calls"
+    );
+    // other models have slots of their own
+    let _other = limiter.acquire("gemini-other", 10).await;
+
+    drop(large);
+    let small = timeout(Duration::from_secs(1), limiter.acquire("gemini-test", 10)).await;
+    assert!(small.is_ok());
+}
+
+#[tokio::test]
+async fn test_new_quotas_apply_to_the_next_calls() {
+    let limiter = GeminiRateLimiter::new(GeminiQuotas::parse("gemini-test=100:10000000:1"));
+    let held = limiter.acquire("gemini-test", 10).await;
+    assert!(timeout(
+        Duration::from_millis(50),
+        limiter.acquire("gemini-test", 10)
+    )
+    .await
+    .is_err());
+
+    // the held permit keeps the old slot, the next call gets the new quota's slots
+    limiter.set_quotas(GeminiQuotas::parse("gemini-test=100:10000000:2"));
+    let next = timeout(Duration::from_secs(1), limiter.acquire("gemini-test", 10)).await;
+    assert!(next.is_ok());
+    drop(held);
+}
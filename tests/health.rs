@@ -0,0 +1,49 @@
+// Tests for the readiness probe
+use tg_main::backend_config::BackendPolicy;
+use tg_main::health::{sessions_required, Readiness};
+
+#[test]
+fn test_ready_only_when_every_check_passes() {
+    let ready = Readiness {
+        database: true,
+        telegram_sessions: true,
+        bot_token: true,
+    };
+    assert!(ready.is_ready());
+    assert!(!Readiness {
+        database: false,
+        ..ready
+    }
+    .is_ready());
+    assert!(!Readiness {
+        telegram_sessions: false,
+        ..ready
+    }
+    .is_ready());
+    assert!(!Readiness {
+        bot_token: false,
+        ..ready
+    }
+    .is_ready());
+}
+
+#[test]
+fn test_web_only_needs_no_sessions() {
+    assert!(!sessions_required(BackendPolicy::WebOnly));
+    assert!(sessions_required(BackendPolicy::ApiOnly));
+    assert!(sessions_required(BackendPolicy::PreferWeb));
+    assert!(sessions_required(BackendPolicy::PreferApi));
+}
+
+#[test]
+fn test_readiness_lists_each_check() {
+    let readiness = Readiness {
+        database: true,
+        telegram_sessions: false,
+        bot_token: true,
+    };
+    assert_eq!(
+        serde_json::to_value(readiness).unwrap(),
+        serde_json::json!({ "database": true, "telegram_sessions": false, "bot_token": true })
+    );
+}
@@ -0,0 +1,68 @@
+// Tests for the registry of analyses in progress
+use std::time::Duration;
+use tg_main::in_flight::{Flight, InFlight};
+use tokio::time::timeout;
+
+#[tokio::test]
+async fn test_second_request_waits_for_the_first_result() {
+    let registry = InFlight::<String>::new();
+
+    let Flight::Lead(lead) = registry.join("@channel|roast") else {
+        panic!("the first request should lead");
+    };
+    let Flight::Follow(follower) = registry.join("@channel|roast") else {
+        panic!("a request for the same key should follow");
+    };
+    // other keys aren't held back
+    assert!(matches!(
+        registry.join("@channel|professional"),
+        Flight::Lead(_)
+    ));
+
+    let waiting = tokio::spawn(follower.wait());
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!waiting.is_finished());
+
+    lead.complete("result".to_string());
+    let result = timeout(Duration::from_secs(1), waiting)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.as_deref(), Some("result"));
+    assert!(registry.is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_leader_lets_followers_go_on_their_own() {
+    let registry = InFlight::<u32>::new();
+
+    let Flight::Lead(lead) = registry.join("@channel|roast") else {
+        panic!("the first request should lead");
+    };
+    let Flight::Follow(follower) = registry.join("@channel|roast") else {
+        panic!("a request for the same key should follow");
+    };
+    drop(lead);
+
+    let result = timeout(Duration::from_secs(1), follower.wait())
+        .await
+        .unwrap();
+    assert_eq!(result, None);
+    // the next request leads again
+    assert!(matches!(registry.join("@channel|roast"), Flight::Lead(_)));
+}
+
+#[tokio::test]
+async fn test_completed_key_is_led_again() {
+    let registry = InFlight::<u32>::new();
+
+    let Flight::Lead(lead) = registry.join("@channel|roast") else {
+        panic!("the first request should lead");
+    };
+    assert_eq!(registry.len(), 1);
+    lead.complete(1);
+    assert!(registry.is_empty());
+
+    // a finished analysis is served by the result cache, not the registry
+    assert!(matches!(registry.join("@channel|roast"), Flight::Lead(_)));
+}
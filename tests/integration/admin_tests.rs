@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use tg_main::admin::{AdminAction, AdminError, AdminManager, AdminRole, AuditOutcome};
+
+use super::TestDatabase;
+
+const OWNER_ID: i64 = 1000;
+const SUPPORT_ID: i64 = 2000;
+
+#[test]
+fn test_role_permissions() {
+    for action in [
+        AdminAction::Refund,
+        AdminAction::ManageRoles,
+        AdminAction::ViewAuditLog,
+        AdminAction::Announce,
+        AdminAction::SetBackend,
+        AdminAction::Requeue,
+        AdminAction::ViewFeedback,
+        AdminAction::ViewLlmCosts,
+    ] {
+        assert!(AdminRole::Owner.allows(action));
+    }
+    assert!(AdminRole::Support.allows(AdminAction::Refund));
+    assert!(!AdminRole::Support.allows(AdminAction::ManageRoles));
+    assert!(!AdminRole::Support.allows(AdminAction::Announce));
+    assert!(AdminRole::Marketing.allows(AdminAction::Announce));
+    assert!(!AdminRole::Marketing.allows(AdminAction::Refund));
+    assert!(!AdminRole::Support.allows(AdminAction::SetBackend));
+    assert!(!AdminRole::Marketing.allows(AdminAction::SetBackend));
+    assert!(!AdminRole::Support.allows(AdminAction::Requeue));
+    assert!(!AdminRole::Marketing.allows(AdminAction::Requeue));
+    assert!(!AdminRole::Support.allows(AdminAction::ViewFeedback));
+    assert!(!AdminRole::Marketing.allows(AdminAction::ViewFeedback));
+    assert!(!AdminRole::Support.allows(AdminAction::ViewLlmCosts));
+    assert!(!AdminRole::Marketing.allows(AdminAction::ViewLlmCosts));
+    assert!(AdminRole::Owner.allows(AdminAction::ViewRevenue));
+    assert!(!AdminRole::Support.allows(AdminAction::ViewRevenue));
+    assert!(!AdminRole::Marketing.allows(AdminAction::ViewRevenue));
+    assert!(AdminRole::Owner.allows(AdminAction::ManagePackages));
+    assert!(!AdminRole::Support.allows(AdminAction::ManagePackages));
+    assert!(!AdminRole::Marketing.allows(AdminAction::ManagePackages));
+    assert!(AdminRole::Owner.allows(AdminAction::ReviewTrials));
+    assert!(!AdminRole::Support.allows(AdminAction::ReviewTrials));
+    assert!(!AdminRole::Marketing.allows(AdminAction::ReviewTrials));
+    assert!(AdminRole::Owner.allows(AdminAction::ReloadConfig));
+    assert!(!AdminRole::Support.allows(AdminAction::ReloadConfig));
+    assert!(!AdminRole::Marketing.allows(AdminAction::ReloadConfig));
+
+    for role in AdminRole::ALL {
+        assert_eq!(AdminRole::from_code(role.as_str()), Some(role));
+    }
+    assert_eq!(AdminRole::from_code("none"), None);
+}
+
+#[tokio::test]
+async fn test_roles_are_granted_changed_and_removed() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let admin = AdminManager::new(Arc::new(db.pool.clone()), vec![OWNER_ID]);
+
+    assert_eq!(
+        admin.role_of(OWNER_ID).await.expect("Failed to load role"),
+        Some(AdminRole::Owner)
+    );
+    assert_eq!(
+        admin
+            .role_of(SUPPORT_ID)
+            .await
+            .expect("Failed to load role"),
+        None
+    );
+
+    admin
+        .set_role(OWNER_ID, SUPPORT_ID, Some(AdminRole::Support))
+        .await
+        .expect("Failed to grant role");
+    assert_eq!(
+        admin
+            .role_of(SUPPORT_ID)
+            .await
+            .expect("Failed to load role"),
+        Some(AdminRole::Support)
+    );
+
+    admin
+        .set_role(OWNER_ID, SUPPORT_ID, Some(AdminRole::Marketing))
+        .await
+        .expect("Failed to change role");
+    assert_eq!(
+        admin
+            .role_of(SUPPORT_ID)
+            .await
+            .expect("Failed to load role"),
+        Some(AdminRole::Marketing)
+    );
+
+    admin
+        .set_role(OWNER_ID, SUPPORT_ID, None)
+        .await
+        .expect("Failed to remove role");
+    assert_eq!(
+        admin
+            .role_of(SUPPORT_ID)
+            .await
+            .expect("Failed to load role"),
+        None
+    );
+
+    // bootstrap owners are managed through ADMIN_USER_IDS only
+    assert!(matches!(
+        admin.set_role(OWNER_ID, OWNER_ID, None).await,
+        Err(AdminError::BootstrapOwner(OWNER_ID))
+    ));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_authorize_audits_denials() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let admin = AdminManager::new(Arc::new(db.pool.clone()), vec![OWNER_ID]);
+    admin
+        .set_role(OWNER_ID, SUPPORT_ID, Some(AdminRole::Support))
+        .await
+        .expect("Failed to grant role");
+
+    assert_eq!(
+        admin
+            .authorize(SUPPORT_ID, AdminAction::Refund, "charge_1")
+            .await
+            .expect("Support should be allowed to refund"),
+        AdminRole::Support
+    );
+    assert!(matches!(
+        admin
+            .authorize(SUPPORT_ID, AdminAction::ManageRoles, "3000 owner")
+            .await,
+        Err(AdminError::Forbidden {
+            role: AdminRole::Support,
+            action: AdminAction::ManageRoles,
+        })
+    ));
+    // non-admins leave no trace
+    assert!(matches!(
+        admin.authorize(3000, AdminAction::Refund, "charge_2").await,
+        Err(AdminError::NotAdmin(3000))
+    ));
+
+    admin
+        .record(
+            OWNER_ID,
+            AdminAction::Announce,
+            "7",
+            AuditOutcome::Succeeded,
+        )
+        .await
+        .expect("Failed to record action");
+
+    let entries = admin
+        .recent_actions(10)
+        .await
+        .expect("Failed to load audit log");
+    assert_eq!(entries.len(), 2);
+    // newest first
+    assert_eq!(entries[0].actor_telegram_user_id, OWNER_ID);
+    assert_eq!(entries[0].action, "announce");
+    assert_eq!(entries[0].outcome, "succeeded");
+    assert_eq!(entries[1].actor_telegram_user_id, SUPPORT_ID);
+    assert_eq!(entries[1].action, "manage_roles");
+    assert_eq!(entries[1].arguments, "3000 owner");
+    assert_eq!(entries[1].outcome, "denied");
+
+    assert_eq!(
+        admin
+            .recent_actions(1)
+            .await
+            .expect("Failed to load audit log")
+            .len(),
+        1
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
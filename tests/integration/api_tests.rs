@@ -0,0 +1,217 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tg_main::user_manager::{AnalysisSource, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_api_keys_authenticate_their_owner_until_rotated_out() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 900, Some("api"), Some("Api"), None, None)
+        .await
+        .expect("Failed to create user");
+
+    let (key, previous) = user_manager
+        .create_api_key(user.id, Duration::from_secs(60 * 60))
+        .await
+        .expect("Failed to create API key");
+    assert!(key.starts_with("tga_"));
+    assert_eq!(previous, 0);
+
+    let owner = user_manager
+        .get_user_by_api_key(&key)
+        .await
+        .expect("Failed to look up API key")
+        .expect("Key should authenticate");
+    assert_eq!(owner.id, user.id);
+    assert!(user_manager
+        .get_user_by_api_key("tga_unknown")
+        .await
+        .expect("Failed to look up API key")
+        .is_none());
+
+    // a rotated key keeps working through the overlap, next to the new one
+    let (new_key, previous) = user_manager
+        .create_api_key(user.id, Duration::from_secs(60 * 60))
+        .await
+        .expect("Failed to create API key");
+    assert_ne!(key, new_key);
+    assert_eq!(previous, 1);
+    for key in [&key, &new_key] {
+        assert!(user_manager
+            .get_user_by_api_key(key)
+            .await
+            .expect("Failed to look up API key")
+            .is_some());
+    }
+
+    // and expires after it; rotating again doesn't extend an expiring key
+    let (newest_key, previous) = user_manager
+        .create_api_key(user.id, Duration::from_secs(24 * 60 * 60))
+        .await
+        .expect("Failed to create API key");
+    assert_eq!(previous, 2);
+    let client = db.pool.get().await.unwrap();
+    let extended: i64 = client
+        .query_one(
+            "SELECT COUNT(*) FROM api_keys WHERE user_id = $1 AND expires_at > NOW() + INTERVAL '2 hours'",
+            &[&user.id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(extended, 1);
+    client
+        .execute(
+            "UPDATE api_keys SET expires_at = NOW() - INTERVAL '1 second'
+             WHERE user_id = $1 AND expires_at < NOW() + INTERVAL '2 hours'",
+            &[&user.id],
+        )
+        .await
+        .unwrap();
+    drop(client);
+    assert!(user_manager
+        .get_user_by_api_key(&key)
+        .await
+        .expect("Failed to look up API key")
+        .is_none());
+    assert!(user_manager
+        .get_user_by_api_key(&new_key)
+        .await
+        .expect("Failed to look up API key")
+        .is_some());
+
+    // revoking drops the keys still in their overlap too
+    let revoked = user_manager
+        .revoke_api_keys(user.id)
+        .await
+        .expect("Failed to revoke API keys");
+    assert_eq!(revoked, 2);
+    for key in [&new_key, &newest_key] {
+        assert!(user_manager
+            .get_user_by_api_key(key)
+            .await
+            .expect("Failed to look up API key")
+            .is_none());
+    }
+
+    // without an overlap the previous key retires right away
+    let (key, _) = user_manager
+        .create_api_key(user.id, Duration::ZERO)
+        .await
+        .expect("Failed to create API key");
+    user_manager
+        .create_api_key(user.id, Duration::ZERO)
+        .await
+        .expect("Failed to create API key");
+    assert!(user_manager
+        .get_user_by_api_key(&key)
+        .await
+        .expect("Failed to look up API key")
+        .is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_api_analyses_are_scoped_to_owner_and_source() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (owner, _) = bot
+        .simulate_user_start(&user_manager, 901, Some("owner"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let (other, _) = bot
+        .simulate_user_start(&user_manager, 902, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    let api_analysis = user_manager
+        .create_pending_analysis(
+            owner.id,
+            "@rustlang",
+            "professional",
+            "medium",
+            None,
+            Some("hiring"),
+            AnalysisSource::Api,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    let bot_analysis = user_manager
+        .create_pending_analysis(
+            owner.id,
+            "@golang",
+            "roast",
+            "small",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+
+    let record = user_manager
+        .get_analysis(api_analysis, owner.id)
+        .await
+        .expect("Failed to get analysis")
+        .expect("Owner should see the analysis");
+    assert_eq!(record.channel_name, "@rustlang");
+    assert_eq!(record.analysis_type.as_deref(), Some("professional"));
+    assert_eq!(record.depth, "medium");
+    assert_eq!(record.status, "pending");
+    assert!(record.created_at.ends_with('Z'));
+
+    // other users can't see it
+    assert!(user_manager
+        .get_analysis(api_analysis, other.id)
+        .await
+        .expect("Failed to get analysis")
+        .is_none());
+
+    // each front end only recovers its own analyses
+    let pending_api = user_manager
+        .get_pending_analyses(AnalysisSource::Api)
+        .await
+        .expect("Failed to get pending analyses");
+    assert_eq!(
+        pending_api.iter().map(|a| a.id).collect::<Vec<_>>(),
+        vec![api_analysis]
+    );
+    assert_eq!(pending_api[0].focus.as_deref(), Some("hiring"));
+    assert!(pending_api[0].age < std::time::Duration::from_secs(60));
+    let pending_bot = user_manager
+        .get_pending_analyses(AnalysisSource::Bot)
+        .await
+        .expect("Failed to get pending analyses");
+    assert_eq!(
+        pending_bot.iter().map(|a| a.id).collect::<Vec<_>>(),
+        vec![bot_analysis]
+    );
+
+    user_manager
+        .atomic_complete_analysis(api_analysis, owner.id, 1)
+        .await
+        .expect("Failed to complete analysis");
+    let record = user_manager
+        .get_analysis(api_analysis, owner.id)
+        .await
+        .expect("Failed to get analysis")
+        .expect("Owner should see the analysis");
+    assert_eq!(record.status, "completed");
+    assert_eq!(record.credits_used, 1);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use tg_main::backup::BackupManager;
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_prune_removes_only_expired_backups() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let backup_manager = BackupManager::new(Arc::new(db.pool.clone()), String::new());
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+    // no backups yet
+    let age = backup_manager
+        .hours_since_last_backup()
+        .await
+        .expect("Failed to check last backup");
+    assert!(age.is_none());
+
+    let expired = dir.path().join("expired.dump");
+    let recent = dir.path().join("recent.dump");
+    std::fs::write(&expired, b"old").expect("Failed to write backup");
+    std::fs::write(&recent, b"new").expect("Failed to write backup");
+
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "INSERT INTO database_backups (location, created_at)
+             VALUES ($1, NOW() - INTERVAL '30 days'), ($2, NOW() - INTERVAL '2 hours')",
+            &[
+                &expired.display().to_string(),
+                &recent.display().to_string(),
+            ],
+        )
+        .await
+        .expect("Failed to record backups");
+
+    let age = backup_manager
+        .hours_since_last_backup()
+        .await
+        .expect("Failed to check last backup")
+        .expect("Expected a recorded backup");
+    assert!((1.9..2.1).contains(&age));
+
+    let removed = backup_manager.prune(14).await.expect("Failed to prune");
+    assert_eq!(removed, 1);
+    assert!(!expired.exists());
+    assert!(recent.exists());
+
+    // already pruned backups are not processed again
+    let removed = backup_manager.prune(14).await.expect("Failed to prune");
+    assert_eq!(removed, 0);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+use tg_main::user_manager::{AnalysisSource, CreditTransactionKind, UserManager};
+
+use super::{
+    mock_bot::MockTelegramBot,
+    test_utils::{TestAssertions, TestScenario},
+    TestDatabase,
+};
+
+#[tokio::test]
+async fn test_credit_ledger_records_every_balance_change() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    // new users start with a free credit
+    let (user, _) = bot
+        .simulate_user_start(
+            &user_manager,
+            700,
+            Some("ledger"),
+            Some("Ledger"),
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create user");
+
+    user_manager
+        .add_credits(
+            user.id,
+            10,
+            CreditTransactionKind::Purchase,
+            Some("charge_ledger"),
+        )
+        .await
+        .expect("Failed to add credits");
+    user_manager
+        .record_payment(user.id, "charge_ledger", "credits_10", 500, 10)
+        .await
+        .expect("Failed to record payment");
+
+    let analysis_id = user_manager
+        .create_pending_analysis(
+            user.id,
+            "@rustlang",
+            "roast",
+            "small",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .atomic_complete_analysis(analysis_id, user.id, 1)
+        .await
+        .expect("Failed to complete analysis");
+
+    let payment = user_manager
+        .get_payment("charge_ledger")
+        .await
+        .expect("Failed to get payment")
+        .expect("Payment should exist");
+    let final_balance = user_manager
+        .record_refund(&payment, None, None)
+        .await
+        .expect("Failed to record refund");
+
+    // newest first, and each entry carries the balance it left behind
+    let transactions = user_manager
+        .get_credit_transactions(user.id, 10, 0)
+        .await
+        .expect("Failed to load ledger");
+    let summary: Vec<_> = transactions
+        .iter()
+        .map(|t| (t.kind, t.amount, t.balance_after))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (CreditTransactionKind::Refund, -10, 0),
+            (CreditTransactionKind::Analysis, -1, 10),
+            (CreditTransactionKind::Purchase, 10, 11),
+            (CreditTransactionKind::SignupBonus, 1, 1),
+        ]
+    );
+    assert_eq!(final_balance, 0);
+    assert_eq!(transactions.iter().map(|t| t.amount).sum::<i32>(), 0);
+
+    // pagination
+    assert_eq!(
+        user_manager
+            .count_credit_transactions(user.id)
+            .await
+            .expect("Failed to count ledger"),
+        4
+    );
+    let second_page = user_manager
+        .get_credit_transactions(user.id, 3, 3)
+        .await
+        .expect("Failed to load ledger");
+    assert_eq!(second_page.len(), 1);
+    assert_eq!(second_page[0].kind, CreditTransactionKind::SignupBonus);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_balance_is_left_alone_when_its_ledger_row_fails() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 750, Some("atomic"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    // a ledger entry postgres refuses takes the balance update down with it
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "ALTER TABLE credit_transactions ADD CONSTRAINT no_poison CHECK (reference IS DISTINCT FROM 'poison')",
+            &[],
+        )
+        .await
+        .unwrap();
+    drop(client);
+    assert!(user_manager
+        .add_credits(user.id, 10, CreditTransactionKind::Purchase, Some("poison"))
+        .await
+        .is_err());
+    TestAssertions::assert_user_credit_count(&db, user.id, 1)
+        .await
+        .expect("Credit count assertion failed");
+
+    let new_balance = user_manager
+        .add_credits(user.id, 10, CreditTransactionKind::Purchase, Some("charge"))
+        .await
+        .expect("Failed to add credits");
+    assert_eq!(new_balance, 11);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_referral_rewards_are_recorded_in_ledger() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (referrer, _) = bot
+        .simulate_user_start(
+            &user_manager,
+            800,
+            Some("referrer"),
+            Some("Referrer"),
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create referrer");
+
+    // every fifth referral earns the referrer a credit
+    for i in 0..5 {
+        bot.simulate_user_start(
+            &user_manager,
+            801 + i,
+            Some(&format!("referred_{}", i)),
+            Some("Referred"),
+            None,
+            Some(referrer.id),
+        )
+        .await
+        .expect("Failed to create referred user");
+    }
+
+    let transactions = user_manager
+        .get_credit_transactions(referrer.id, 10, 0)
+        .await
+        .expect("Failed to load ledger");
+    let summary: Vec<_> = transactions
+        .iter()
+        .map(|t| (t.kind, t.amount, t.balance_after))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (CreditTransactionKind::ReferralReward, 1, 2),
+            (CreditTransactionKind::SignupBonus, 1, 1),
+        ]
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use tg_main::analysis::{AnalysisDepth, AnalysisError};
+use tg_main::analysis_runner::AnalysisRunError;
+use tg_main::blocklist::{BlockTarget, ChannelBlocklist};
+
+use super::{mock_analysis::MockAnalysisFlow, TestDatabase};
+
+// a channel with a fixture in fixtures/
+const CHANNEL: &str = "@example_channel";
+const OWNER_ID: i64 = 1000;
+
+#[tokio::test]
+async fn test_blocklist_is_listed_and_updated() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let blocklist = ChannelBlocklist::new(Arc::new(db.pool.clone()));
+
+    let rustlang = BlockTarget::parse("@RustLang").unwrap();
+    let by_id = BlockTarget::parse("-1001234567890").unwrap();
+    blocklist
+        .block(&rustlang, Some("owner asked"), OWNER_ID)
+        .await
+        .expect("Failed to block channel");
+    blocklist
+        .block(&by_id, None, OWNER_ID)
+        .await
+        .expect("Failed to block channel");
+    // blocking again only updates the reason
+    blocklist
+        .block(&rustlang, Some("owner asked twice"), OWNER_ID)
+        .await
+        .expect("Failed to block channel");
+
+    let channels = blocklist.list().await.expect("Failed to list blocklist");
+    assert_eq!(channels.len(), 2);
+    let entry = channels
+        .iter()
+        .find(|channel| channel.target == "@rustlang")
+        .expect("Channel should be listed");
+    assert_eq!(entry.reason.as_deref(), Some("owner asked twice"));
+    assert_eq!(entry.blocked_by, OWNER_ID);
+
+    assert!(blocklist
+        .is_blocked(std::slice::from_ref(&rustlang))
+        .await
+        .unwrap());
+    assert!(blocklist
+        .is_blocked(&[
+            BlockTarget::parse("@golang").unwrap(),
+            BlockTarget::ChannelId(1234567890)
+        ])
+        .await
+        .unwrap());
+    assert!(!blocklist.is_blocked(&[]).await.unwrap());
+
+    assert!(blocklist.unblock(&rustlang).await.unwrap());
+    assert!(!blocklist.unblock(&rustlang).await.unwrap());
+    assert!(!blocklist.is_blocked(&[rustlang]).await.unwrap());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_blocked_channel_is_refused_without_charging() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let blocklist = ChannelBlocklist::new(Arc::new(db.pool.clone()));
+    blocklist
+        .block(
+            &BlockTarget::parse(CHANNEL).unwrap(),
+            Some("owner asked"),
+            OWNER_ID,
+        )
+        .await
+        .expect("Failed to block channel");
+
+    let user = flow.start(2200, "reader").await;
+    let credits = flow.user_manager.get_credits(user.id).await.unwrap();
+    flow.send_channel(2200, CHANNEL).await;
+    flow.select_type(2200, "roast", AnalysisDepth::Small)
+        .await
+        .expect("Analysis should be queued");
+
+    let run = flow.run_next_job().await.expect("A job should be queued");
+    match run.outcome {
+        Err(AnalysisRunError::Prepare(e)) => {
+            assert_eq!(e.failure(), AnalysisError::ChannelBlocked)
+        }
+        _ => panic!("A blocked channel should be refused"),
+    }
+    assert_eq!(
+        flow.user_manager.get_credits(user.id).await.unwrap(),
+        credits
+    );
+    assert!(flow
+        .bot
+        .chat_received_message_containing(2200, "excluded from analysis"));
+
+    // once unblocked it is analyzed as usual
+    blocklist
+        .unblock(&BlockTarget::parse(CHANNEL).unwrap())
+        .await
+        .expect("Failed to unblock channel");
+    flow.send_channel(2200, CHANNEL).await;
+    flow.select_type(2200, "roast", AnalysisDepth::Small)
+        .await
+        .expect("Analysis should be queued");
+    let run = flow.run_next_job().await.expect("A job should be queued");
+    assert!(run.outcome.is_ok());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
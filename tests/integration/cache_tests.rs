@@ -0,0 +1,378 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tg_main::analysis::{CorpusKind, MessageDict};
+use tg_main::backend_config::BackendType;
+use tg_main::cache::{AnalysisResult, CacheManager, CorpusFreshness};
+use tg_main::user_manager::{AnalysisSource, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_cached_messages_report_their_age() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let cache = CacheManager::new(Arc::new(db.pool.clone()));
+
+    let messages = vec![MessageDict {
+        id: Some(42),
+        date: Some("2024-01-01".to_string()),
+        message: Some("a post long enough to be analyzed".to_string()),
+        images: None,
+        thread_id: None,
+    }];
+    cache
+        .save_channel_messages(
+            "@channel",
+            &messages,
+            Some(BackendType::WebScraping),
+            3,
+            CorpusKind::Channel,
+        )
+        .await
+        .expect("Failed to cache messages");
+
+    let corpus = cache
+        .load_channel_messages_with_age("@channel")
+        .await
+        .expect("Messages should be cached");
+    assert_eq!(corpus.messages[0].id, Some(42));
+    assert!(corpus.age < Duration::from_secs(60));
+    assert_eq!(corpus.backend, Some(BackendType::WebScraping));
+    assert_eq!(corpus.skipped, 3);
+    assert_eq!(corpus.kind, CorpusKind::Channel);
+    assert_eq!(corpus.freshness(), CorpusFreshness::Fresh);
+    assert_eq!(corpus.last_message_id, Some(42));
+    assert_eq!(corpus.fetch_since(), Some(42));
+
+    // a corpus fetched two days ago is still served, with its age
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE channel_messages SET updated_at = NOW() - INTERVAL '2 days' WHERE channel_name = '@channel'",
+            &[],
+        )
+        .await
+        .expect("Failed to backdate cache entry");
+    let corpus = cache
+        .load_channel_messages_with_age("@channel")
+        .await
+        .expect("Messages should still be cached");
+    assert!(corpus.age > Duration::from_secs(47 * 3600));
+    assert_eq!(corpus.freshness(), CorpusFreshness::Stale);
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_merged_corpus_keeps_the_time_of_its_last_full_fetch() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let cache = CacheManager::new(Arc::new(db.pool.clone()));
+    let post = |id: i64| MessageDict {
+        id: Some(id),
+        date: Some("2024-01-01".to_string()),
+        message: Some("a post long enough to be analyzed".to_string()),
+        images: None,
+        thread_id: None,
+    };
+
+    cache
+        .save_channel_messages(
+            "@channel",
+            &[post(7), post(6)],
+            Some(BackendType::Api),
+            0,
+            CorpusKind::Channel,
+        )
+        .await
+        .expect("Failed to cache messages");
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE channel_messages
+             SET updated_at = NOW() - INTERVAL '2 days', full_fetch_at = NOW() - INTERVAL '2 days'
+             WHERE channel_name = '@channel'",
+            &[],
+        )
+        .await
+        .expect("Failed to backdate cache entry");
+
+    // a refresh of the newest posts moves the high-water mark, not the full fetch
+    cache
+        .save_merged_channel_messages(
+            "@channel",
+            &[post(9), post(8), post(7), post(6)],
+            Some(BackendType::Api),
+            0,
+            CorpusKind::Channel,
+        )
+        .await
+        .expect("Failed to cache merged messages");
+    let corpus = cache
+        .load_channel_messages_with_age("@channel")
+        .await
+        .expect("Messages should be cached");
+    assert_eq!(corpus.freshness(), CorpusFreshness::Fresh);
+    assert_eq!(corpus.last_message_id, Some(9));
+    assert!(corpus.full_fetch_age.unwrap() > Duration::from_secs(47 * 3600));
+    assert_eq!(corpus.fetch_since(), Some(9));
+
+    // once the last full fetch is old enough, the next refresh fetches everything again
+    client
+        .execute(
+            "UPDATE channel_messages SET full_fetch_at = NOW() - INTERVAL '4 days'
+             WHERE channel_name = '@channel'",
+            &[],
+        )
+        .await
+        .expect("Failed to backdate full fetch");
+    let corpus = cache
+        .load_channel_messages_with_age("@channel")
+        .await
+        .expect("Messages should be cached");
+    assert_eq!(corpus.fetch_since(), None);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_partial_llm_results_are_replaced_by_complete_ones() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let cache = CacheManager::new(Arc::new(db.pool.clone()));
+
+    let result = |roast: &str, partial: bool| AnalysisResult {
+        professional: Some("professional".to_string()),
+        personal: Some("personal".to_string()),
+        roast: Some(roast.to_string()),
+        trends: None,
+        messages_count: 10,
+        model: Some("gemini-2.5-flash".to_string()),
+        report: None,
+        removed_messages: 0,
+        partial,
+        prompt_version: None,
+        facts: None,
+    };
+
+    cache
+        .save_llm_result("key", &result("cut", true))
+        .await
+        .expect("Failed to cache result");
+    cache
+        .save_llm_result("key", &result("complete", false))
+        .await
+        .expect("Failed to cache result");
+    let cached = cache
+        .load_llm_result("key")
+        .await
+        .expect("Result should be cached");
+    assert!(!cached.partial);
+    assert_eq!(cached.roast.as_deref(), Some("complete"));
+
+    // complete results stay put
+    cache
+        .save_llm_result("key", &result("other", false))
+        .await
+        .expect("Failed to cache result");
+    let cached = cache
+        .load_llm_result("key")
+        .await
+        .expect("Result should be cached");
+    assert_eq!(cached.roast.as_deref(), Some("complete"));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_results_of_long_routed_model_names_are_cached() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let cache = CacheManager::new(pool.clone());
+    let user_manager = UserManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    // a second opinion from a routed model on a newer prompt version, as the runner keys it
+    let model = "gemini-2.5-flash-preview-05-20";
+    let cache_key = format!("1f2e3d4c5b6a7980:v2:m{}:m{}", model, model);
+    assert!(cache_key.len() > 64);
+
+    let result = AnalysisResult {
+        professional: Some("professional".to_string()),
+        personal: Some("personal".to_string()),
+        roast: Some("roast".to_string()),
+        trends: None,
+        messages_count: 10,
+        model: Some(model.to_string()),
+        report: None,
+        removed_messages: 0,
+        partial: false,
+        prompt_version: Some(2),
+        facts: None,
+    };
+    cache
+        .save_llm_result(&cache_key, &result)
+        .await
+        .expect("Failed to cache result");
+    let cached = cache
+        .load_llm_result(&cache_key)
+        .await
+        .expect("Result should be cached");
+    assert_eq!(cached.model.as_deref(), Some(model));
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1900, Some("routed"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let analysis_id = user_manager
+        .create_pending_analysis(
+            user.id,
+            "@routed",
+            "roast",
+            "medium",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .set_analysis_cache_key(analysis_id, &cache_key)
+        .await
+        .expect("Failed to store the cache key");
+    let record = user_manager
+        .get_analysis(analysis_id, user.id)
+        .await
+        .expect("Failed to get analysis")
+        .expect("Analysis should exist");
+    assert_eq!(record.cache_key.as_deref(), Some(cache_key.as_str()));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_forwarded_messages_are_cached_without_a_backend() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let cache = CacheManager::new(Arc::new(db.pool.clone()));
+
+    let messages = vec![MessageDict {
+        id: None,
+        date: Some("2024-01-01".to_string()),
+        message: Some("something i wrote".to_string()),
+        images: None,
+        thread_id: None,
+    }];
+    cache
+        .save_channel_messages("self:42", &messages, None, 0, CorpusKind::Channel)
+        .await
+        .expect("Failed to cache messages");
+
+    let corpus = cache
+        .load_channel_messages_with_age("self:42")
+        .await
+        .expect("Messages should be cached");
+    assert_eq!(
+        corpus.messages[0].message.as_deref(),
+        Some("something i wrote")
+    );
+    assert_eq!(corpus.backend, None);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_profiles_are_cached_as_profiles() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let cache = CacheManager::new(Arc::new(db.pool.clone()));
+
+    let messages = vec![MessageDict {
+        id: None,
+        date: None,
+        message: Some("Name: Jane Doe\nBio: rust and databases".to_string()),
+        images: None,
+        thread_id: None,
+    }];
+    cache
+        .save_channel_messages(
+            "@jane",
+            &messages,
+            Some(BackendType::Api),
+            0,
+            CorpusKind::Profile,
+        )
+        .await
+        .expect("Failed to cache messages");
+
+    let corpus = cache
+        .load_channel_messages_with_age("@jane")
+        .await
+        .expect("Messages should be cached");
+    assert_eq!(corpus.kind, CorpusKind::Profile);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_purging_cached_messages() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let cache = CacheManager::new(Arc::new(db.pool.clone()));
+
+    let messages = vec![MessageDict {
+        id: Some(1),
+        date: Some("2024-01-01".to_string()),
+        message: Some("a post long enough to be analyzed".to_string()),
+        images: None,
+        thread_id: None,
+    }];
+    // one corpus per depth; underscores must not match other channels
+    for name in [
+        "@some_channel",
+        "@some_channel#deep",
+        "@someXchannel",
+        "@other_channel",
+    ] {
+        cache
+            .save_channel_messages(name, &messages, None, 0, CorpusKind::Channel)
+            .await
+            .expect("Failed to cache messages");
+    }
+
+    let purged = cache
+        .purge_channel_messages("@some_channel")
+        .await
+        .expect("Failed to purge channel");
+    assert_eq!(purged, 2);
+    assert!(cache.load_channel_messages("@some_channel").await.is_none());
+    assert!(cache.load_channel_messages("@someXchannel").await.is_some());
+
+    // only corpora past the ttl are stale
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE channel_messages SET updated_at = NOW() - INTERVAL '8 days' WHERE channel_name = '@other_channel'",
+            &[],
+        )
+        .await
+        .expect("Failed to backdate cache entry");
+    assert_eq!(cache.purge_stale_channel_messages().await.unwrap(), 1);
+    assert!(cache.load_channel_messages("@someXchannel").await.is_some());
+
+    assert_eq!(cache.purge_all_channel_messages().await.unwrap(), 1);
+    assert!(cache.load_channel_messages("@someXchannel").await.is_none());
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,209 @@
+use std::sync::Arc;
+use tg_main::analysis::AnalysisDepth;
+use tg_main::localization::Lang;
+use tg_main::user_manager::CreditTransactionKind;
+
+use super::{mock_analysis::MockAnalysisFlow, TestDatabase};
+
+const CHANNEL: &str = "@example_channel";
+
+fn depth_button(flow: &MockAnalysisFlow, depth: AnalysisDepth, selected: bool) -> String {
+    Lang::En.btn_depth(depth, flow.limits.depth_credits(depth), selected)
+}
+
+async fn analysis_row(db: &TestDatabase, analysis_id: i32) -> (String, String, Option<String>) {
+    let client = db.pool.get().await.unwrap();
+    let row = client
+        .query_one(
+            "SELECT channel_name, depth, focus FROM user_analyses WHERE id = $1",
+            &[&analysis_id],
+        )
+        .await
+        .unwrap();
+    (row.get(0), row.get(1), row.get(2))
+}
+
+#[tokio::test]
+async fn test_depth_button_edits_the_keyboard_and_sets_the_depth() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let user = flow.start(3000, "reader").await;
+    flow.user_manager
+        .add_credits(user.id, 10, CreditTransactionKind::Purchase, None)
+        .await
+        .expect("Failed to add credits");
+
+    let message_id = flow.send_channel(3000, CHANNEL).await;
+    let keyboard = flow
+        .bot
+        .get_message(3000, message_id)
+        .and_then(|message| message.keyboard)
+        .expect("Type selection should have a keyboard");
+    assert_eq!(keyboard[0].len(), AnalysisDepth::ALL.len());
+    assert_eq!(
+        keyboard[0][0].text,
+        depth_button(&flow, AnalysisDepth::default(), true)
+    );
+
+    flow.press(
+        3000,
+        message_id,
+        &depth_button(&flow, AnalysisDepth::Deep, false),
+    )
+    .await
+    .expect("Depth button should be handled");
+
+    // the keyboard is re-rendered in place with the new depth selected
+    let edits = flow.bot.get_edits(3000, message_id);
+    assert_eq!(edits.len(), 1);
+    assert!(edits[0].text.is_none());
+    let keyboard = flow
+        .bot
+        .get_message(3000, message_id)
+        .and_then(|message| message.keyboard)
+        .unwrap();
+    assert!(keyboard[0]
+        .iter()
+        .any(|button| button.text == depth_button(&flow, AnalysisDepth::Deep, true)));
+    assert_eq!(
+        flow.user_sessions.get(3000).await.unwrap().depth,
+        AnalysisDepth::Deep
+    );
+    assert!(flow.bot.unanswered_callback_queries().is_empty());
+
+    // type buttons of the edited keyboard carry the selected depth
+    let analysis_id = flow
+        .press(3000, message_id, Lang::En.btn_professional_analysis())
+        .await
+        .expect("Type button should be handled")
+        .expect("Analysis should be queued");
+    let (channel_name, depth, focus) = analysis_row(&db, analysis_id).await;
+    assert_eq!(channel_name, CHANNEL);
+    assert_eq!(depth, "deep");
+    assert!(focus.is_none());
+    assert!(flow.user_sessions.get(3000).await.is_none());
+    assert!(flow.bot.unanswered_callback_queries().is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_focus_request_and_input_are_applied_to_the_analysis() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    flow.start(3010, "reader").await;
+
+    let message_id = flow.send_channel(3010, CHANNEL).await;
+    flow.press(3010, message_id, Lang::En.btn_add_focus())
+        .await
+        .expect("Focus button should be handled");
+    let session = flow.user_sessions.get(3010).await.unwrap();
+    assert!(session.awaiting_focus);
+    assert!(session.focus.is_none());
+    assert!(flow
+        .bot
+        .last_message_for_chat(3010)
+        .is_some_and(|message| message.keyboard.is_none()));
+
+    // too long instructions are rejected and the bot keeps waiting
+    let too_long = "x".repeat(tg_main::prompts::analysis::MAX_FOCUS_LENGTH + 1);
+    assert!(flow.send_focus(3010, &too_long).await.is_none());
+    assert!(flow.user_sessions.get(3010).await.unwrap().awaiting_focus);
+
+    let focus_message_id = flow
+        .send_focus(3010, "hiring <signals>")
+        .await
+        .expect("Focus should be saved");
+    let focus_message = flow.bot.get_message(3010, focus_message_id).unwrap();
+    assert!(focus_message.text.contains("hiring &lt;signals&gt;"));
+    let session = flow.user_sessions.get(3010).await.unwrap();
+    assert!(!session.awaiting_focus);
+    assert_eq!(session.focus.as_deref(), Some("hiring <signals>"));
+
+    let analysis_id = flow
+        .press(3010, focus_message_id, Lang::En.btn_roast_analysis())
+        .await
+        .expect("Type button should be handled")
+        .expect("Analysis should be queued");
+    let (_, depth, focus) = analysis_row(&db, analysis_id).await;
+    assert_eq!(depth, AnalysisDepth::default().as_str());
+    assert_eq!(focus.as_deref(), Some("hiring <signals>"));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_buttons_of_another_channel_reset_the_session() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let user = flow.start(3020, "reader").await;
+    flow.user_manager
+        .add_credits(user.id, 10, CreditTransactionKind::Purchase, None)
+        .await
+        .expect("Failed to add credits");
+
+    let first = flow.send_channel(3020, "@first_channel").await;
+    flow.press(3020, first, Lang::En.btn_add_focus())
+        .await
+        .expect("Focus button should be handled");
+    flow.send_focus(3020, "only for the first channel")
+        .await
+        .expect("Focus should be saved");
+    let second = flow.send_channel(3020, CHANNEL).await;
+
+    // an old keyboard still works and switches the session back to its channel
+    flow.press(
+        3020,
+        first,
+        &depth_button(&flow, AnalysisDepth::Deep, false),
+    )
+    .await
+    .expect("Depth button should be handled");
+    let session = flow.user_sessions.get(3020).await.unwrap();
+    assert_eq!(session.channel_name.as_deref(), Some("@first_channel"));
+    assert_eq!(session.depth, AnalysisDepth::Deep);
+    assert!(session.focus.is_none());
+
+    // a type button of the other channel doesn't pick up this session's choices
+    let analysis_id = flow
+        .press(3020, second, Lang::En.btn_professional_analysis())
+        .await
+        .expect("Type button should be handled")
+        .expect("Analysis should be queued");
+    let (channel_name, depth, focus) = analysis_row(&db, analysis_id).await;
+    assert_eq!(channel_name, CHANNEL);
+    assert_eq!(depth, AnalysisDepth::default().as_str());
+    assert!(focus.is_none());
+    assert!(flow.user_sessions.get(3020).await.is_none());
+    assert!(flow.bot.get_edits(3020, second).is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_pressing_a_missing_button_fails() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    flow.start(3030, "reader").await;
+
+    let message_id = flow.send_channel(3030, CHANNEL).await;
+    assert!(flow
+        .press(3030, message_id, "No such button")
+        .await
+        .is_err());
+    assert!(flow
+        .press(3030, message_id + 100, Lang::En.btn_add_focus())
+        .await
+        .is_err());
+    assert!(flow.bot.callback_queries.lock().unwrap().is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
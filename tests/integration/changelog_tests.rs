@@ -0,0 +1,125 @@
+use std::sync::Arc;
+use tg_main::changelog::{ChangelogError, ChangelogManager};
+use tg_main::localization::Lang;
+use tg_main::user_manager::UserManager;
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+async fn queued_messages(db: &TestDatabase) -> Vec<(i64, String)> {
+    let client = db.pool.get().await.expect("Failed to get client");
+    client
+        .query(
+            "SELECT telegram_user_id, message FROM message_queue ORDER BY telegram_user_id",
+            &[],
+        )
+        .await
+        .expect("Failed to query message queue")
+        .into_iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_changelog_entries_are_localized_and_ordered() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let changelog = ChangelogManager::new(Arc::new(db.pool.clone()));
+
+    changelog
+        .add_entry("Balance", None, "See your credit history", None, false)
+        .await
+        .expect("Failed to add entry");
+    changelog
+        .add_entry(
+            "Languages",
+            Some("Языки"),
+            "Pick the <analysis> language",
+            Some("Выберите язык анализа"),
+            true,
+        )
+        .await
+        .expect("Failed to add entry");
+
+    let entries = changelog
+        .recent_entries(5)
+        .await
+        .expect("Failed to load entries");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].title_en, "Languages");
+    assert!(entries[0].major);
+    assert!(!entries[0].announced);
+
+    assert_eq!(entries[0].title(Lang::Ru), "Языки");
+    // missing translations fall back to english
+    assert_eq!(entries[1].title(Lang::Ru), "Balance");
+    // entry texts are escaped for html messages
+    assert!(entries[0].render(Lang::En).contains("&lt;analysis&gt;"));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_major_entries_are_announced_once_to_opted_in_users() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let changelog = ChangelogManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (_subscriber, _) = bot
+        .simulate_user_start(&user_manager, 900, Some("sub"), Some("Sub"), None, None)
+        .await
+        .expect("Failed to create user");
+    let (opted_out, _) = bot
+        .simulate_user_start(&user_manager, 901, Some("quiet"), Some("Quiet"), None, None)
+        .await
+        .expect("Failed to create user");
+    assert!(user_manager
+        .get_announcements_enabled(opted_out.id)
+        .await
+        .expect("Failed to get preference"));
+    user_manager
+        .set_announcements_enabled(opted_out.id, false)
+        .await
+        .expect("Failed to set preference");
+
+    let minor = changelog
+        .add_entry("Fixes", None, "Small fixes", None, false)
+        .await
+        .expect("Failed to add entry");
+    let major = changelog
+        .add_entry("Big feature", None, "Something big", None, true)
+        .await
+        .expect("Failed to add entry");
+
+    assert!(matches!(
+        changelog.announce(minor).await,
+        Err(ChangelogError::NotMajor(_))
+    ));
+
+    let queued = changelog.announce(major).await.expect("Failed to announce");
+    assert_eq!(queued, 1);
+    let messages = queued_messages(&db).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].0, 900);
+    assert!(messages[0].1.contains("Big feature"));
+
+    // a second announcement is refused and queues nothing
+    assert!(matches!(
+        changelog.announce(major).await,
+        Err(ChangelogError::AlreadyAnnounced(_))
+    ));
+    assert_eq!(queued_messages(&db).await.len(), 1);
+    assert!(
+        changelog
+            .get_entry(major)
+            .await
+            .expect("Failed to get entry")
+            .announced
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use tg_main::analysis::AnalysisDepth;
+use tg_main::channel_claims::{ChannelClaimManager, ClaimMethod};
+
+use super::{mock_analysis::MockAnalysisFlow, TestDatabase};
+
+// a channel with a fixture in fixtures/
+const CHANNEL: &str = "@example_channel";
+
+#[tokio::test]
+async fn test_channel_has_one_verified_owner() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let claims = ChannelClaimManager::new(Arc::new(db.pool.clone()));
+
+    let owner = claims
+        .start_claim("@Example_Channel", 2300)
+        .await
+        .expect("Failed to start claim")
+        .expect("Channel should be claimable");
+    assert_eq!(owner.channel_name, CHANNEL);
+    assert!(!owner.verified);
+    // claiming again keeps the code the user may have posted already
+    let again = claims
+        .start_claim(CHANNEL, 2300)
+        .await
+        .unwrap()
+        .expect("Channel should be claimable");
+    assert_eq!((again.id, &again.code), (owner.id, &owner.code));
+
+    let rival = claims
+        .start_claim(CHANNEL, 2301)
+        .await
+        .unwrap()
+        .expect("Unverified channels can be claimed by anyone");
+    assert_ne!(rival.code, owner.code);
+
+    assert!(claims
+        .mark_verified(owner.id, ClaimMethod::Post)
+        .await
+        .unwrap());
+    assert!(!claims
+        .mark_verified(owner.id, ClaimMethod::Admin)
+        .await
+        .unwrap());
+    assert!(!claims
+        .mark_verified(rival.id, ClaimMethod::Admin)
+        .await
+        .unwrap());
+    assert!(claims.start_claim(CHANNEL, 2301).await.unwrap().is_none());
+
+    // claims are only shown to whoever started them
+    assert!(claims.get_claim(owner.id, 2301).await.unwrap().is_none());
+    claims.set_opted_out(owner.id, true).await.unwrap();
+    let owner = claims
+        .get_claim(owner.id, 2300)
+        .await
+        .unwrap()
+        .expect("Claim should exist");
+    assert!(owner.verified && owner.opted_out);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_owner_analyses_are_free_up_to_the_monthly_limit() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+
+    let user = flow.start(2302, "owner").await;
+    let claim = flow
+        .channel_claims
+        .start_claim(CHANNEL, 2302)
+        .await
+        .unwrap()
+        .expect("Channel should be claimable");
+    flow.channel_claims
+        .mark_verified(claim.id, ClaimMethod::Admin)
+        .await
+        .unwrap();
+    let credits = flow.user_manager.get_credits(user.id).await.unwrap();
+
+    for _ in 0..flow.limits.owner_free_analyses {
+        flow.send_channel(2302, CHANNEL).await;
+        flow.select_type(2302, "roast", AnalysisDepth::Small)
+            .await
+            .expect("Analysis should be queued");
+        let run = flow.run_next_job().await.expect("A job should be queued");
+        assert!(run.outcome.is_ok());
+    }
+    assert_eq!(
+        flow.user_manager.get_credits(user.id).await.unwrap(),
+        credits
+    );
+
+    // past the limit the owner pays like anyone else
+    flow.send_channel(2302, CHANNEL).await;
+    flow.select_type(2302, "roast", AnalysisDepth::Small)
+        .await
+        .expect("Analysis should be queued");
+    let run = flow.run_next_job().await.expect("A job should be queued");
+    assert!(run.outcome.is_ok());
+    assert_eq!(
+        flow.user_manager.get_credits(user.id).await.unwrap(),
+        credits - flow.limits.depth_credits(AnalysisDepth::Small)
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use tg_main::channel_stats::{ChannelPopularity, ChannelStatsManager};
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_leaderboard_ranks_this_weeks_most_analyzed_channels() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let stats = ChannelStatsManager::new(Arc::new(db.pool.clone()));
+
+    for (channel, score) in [
+        ("@rustlang", Some(8.0)),
+        ("@RustLang", Some(6.5)),
+        ("@rustlang", None),
+        ("@golang", None),
+        ("@python", Some(7.0)),
+        ("@python", Some(9.0)),
+    ] {
+        stats
+            .record_analysis(channel, score)
+            .await
+            .expect("Failed to record analysis");
+    }
+
+    // analyses from earlier weeks count towards the total only
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "INSERT INTO channel_stats (channel_name, week_start, analyses, scored_analyses, score_total)
+             VALUES ('@golang', date_trunc('week', NOW())::date - 7, 5, 0, 0)",
+            &[],
+        )
+        .await
+        .expect("Failed to insert last week's stats");
+
+    let top = stats.top_this_week(10).await.expect("Failed to load top");
+    assert_eq!(
+        top,
+        vec![
+            ChannelPopularity {
+                channel_name: "@rustlang".to_string(),
+                analyses: 3,
+                total_analyses: 3,
+                average_score: Some(7.25),
+            },
+            ChannelPopularity {
+                channel_name: "@python".to_string(),
+                analyses: 2,
+                total_analyses: 2,
+                average_score: Some(8.0),
+            },
+            ChannelPopularity {
+                channel_name: "@golang".to_string(),
+                analyses: 1,
+                total_analyses: 6,
+                average_score: None,
+            },
+        ]
+    );
+
+    let top = stats.top_this_week(1).await.expect("Failed to load top");
+    assert_eq!(top.len(), 1);
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
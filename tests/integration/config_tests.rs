@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use tg_main::config::LiveConfig;
+use tg_main::limits::Limits;
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_reload_picks_up_database_overrides() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+
+    let config = LiveConfig::load(pool.clone())
+        .await
+        .expect("Failed to load config");
+    let before = config.limits();
+    assert!(config
+        .reload()
+        .await
+        .expect("Failed to reload config")
+        .is_empty());
+
+    Limits::save_override(
+        &pool,
+        "max_batch_channels",
+        before.max_batch_channels as i64 + 1,
+    )
+    .await
+    .expect("Failed to save override");
+    let changes = config.reload().await.expect("Failed to reload config");
+    assert_eq!(
+        changes,
+        vec![format!(
+            "max_batch_channels: {} → {}",
+            before.max_batch_channels,
+            before.max_batch_channels + 1
+        )]
+    );
+    assert_eq!(
+        config.limits().max_batch_channels,
+        before.max_batch_channels + 1
+    );
+    // a snapshot taken before the reload keeps its values
+    assert_eq!(
+        before.max_batch_channels,
+        Limits::default().max_batch_channels
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
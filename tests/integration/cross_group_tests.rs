@@ -0,0 +1,315 @@
+use std::sync::Arc;
+use tg_main::analysis::cross_group_corpus_name;
+use tg_main::cross_group::{CrossGroupManager, MAX_MESSAGES_PER_GROUP};
+use tg_main::user_manager::UserManager;
+
+use super::{
+    mock_bot::MockTelegramBot,
+    test_utils::{TestAssertions, TestScenario},
+    TestDatabase,
+};
+
+const USER: i64 = 4200;
+const START: f64 = 1_700_000_000.0;
+
+#[tokio::test]
+async fn test_only_consenting_users_messages_are_kept() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let manager = CrossGroupManager::new(Arc::new(db.pool.clone()));
+
+    assert!(!manager
+        .record(USER, -100, "Rust Moscow", 1, "hello", START)
+        .await
+        .unwrap());
+    assert!(manager.groups(USER).await.unwrap().is_empty());
+
+    assert!(manager.consent(USER).await.unwrap());
+    assert!(!manager.consent(USER).await.unwrap());
+    assert!(manager.has_consent(USER).await.unwrap());
+
+    for (chat_id, title, message_id, text) in [
+        (-100, "Rust Moscow", 2, "borrow checker again"),
+        (-100, "Rust Moscow", 3, "async traits landed"),
+        (-200, "Climbing", 1, "who's in on saturday?"),
+    ] {
+        assert!(manager
+            .record(
+                USER,
+                chat_id,
+                title,
+                message_id,
+                text,
+                START + message_id as f64
+            )
+            .await
+            .unwrap());
+    }
+    // an edited or redelivered message is kept once
+    assert!(!manager
+        .record(USER, -100, "Rust Moscow", 2, "borrow checker again", START)
+        .await
+        .unwrap());
+    // other people in the same groups aren't kept
+    assert!(!manager
+        .record(USER + 1, -100, "Rust Moscow", 4, "me too", START)
+        .await
+        .unwrap());
+
+    let groups = manager.groups(USER).await.unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].chat_title, "Rust Moscow");
+    assert_eq!(groups[0].messages, 2);
+    assert_eq!(groups[1].messages, 1);
+
+    // oldest first, whichever group they come from
+    let messages = manager.messages(USER).await.unwrap();
+    let texts = messages
+        .iter()
+        .map(|msg| msg.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        [
+            "who's in on saturday?",
+            "borrow checker again",
+            "async traits landed"
+        ]
+    );
+
+    assert_eq!(manager.revoke(USER).await.unwrap(), 3);
+    assert!(!manager.has_consent(USER).await.unwrap());
+    assert!(manager.groups(USER).await.unwrap().is_empty());
+    assert!(!manager
+        .record(USER, -100, "Rust Moscow", 5, "still here", START)
+        .await
+        .unwrap());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_busy_group_does_not_drown_out_the_others() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let manager = CrossGroupManager::new(Arc::new(db.pool.clone()));
+    manager.consent(USER).await.unwrap();
+
+    let busy = MAX_MESSAGES_PER_GROUP as i32 + 20;
+    for message_id in 1..=busy {
+        manager
+            .record(
+                USER,
+                -100,
+                "Flood",
+                message_id,
+                &format!("message {}", message_id),
+                START + message_id as f64,
+            )
+            .await
+            .unwrap();
+    }
+    manager
+        .record(USER, -200, "Quiet", 1, "the only one", START)
+        .await
+        .unwrap();
+
+    let messages = manager.messages(USER).await.unwrap();
+    assert_eq!(messages.len(), MAX_MESSAGES_PER_GROUP as usize + 1);
+    // the quiet group's older message survives, the busy group keeps its latest ones
+    assert_eq!(messages[0].text, "the only one");
+    assert_eq!(messages[1].text, "message 21");
+    assert_eq!(messages.last().unwrap().text, format!("message {}", busy));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_messages_past_what_the_profile_reads_are_pruned() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let manager = CrossGroupManager::new(Arc::new(db.pool.clone()));
+    manager.consent(USER).await.unwrap();
+
+    let busy = MAX_MESSAGES_PER_GROUP as i32 + 20;
+    for message_id in 1..=busy {
+        manager
+            .record(
+                USER,
+                -100,
+                "Flood",
+                message_id,
+                &format!("message {}", message_id),
+                START + message_id as f64,
+            )
+            .await
+            .unwrap();
+    }
+    manager
+        .record(USER, -200, "Quiet", 1, "the only one", START)
+        .await
+        .unwrap();
+    let before = manager.messages(USER).await.unwrap();
+
+    assert_eq!(manager.prune().await.unwrap(), 20);
+    assert_eq!(manager.prune().await.unwrap(), 0);
+
+    let groups = manager.groups(USER).await.unwrap();
+    assert_eq!(groups[0].messages, MAX_MESSAGES_PER_GROUP);
+    assert_eq!(groups[1].messages, 1);
+    assert_eq!(manager.messages(USER).await.unwrap(), before);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_consents_are_followed_without_reading_them_per_message() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let manager = CrossGroupManager::new(pool.clone());
+    // another replica of the bot
+    let replica = CrossGroupManager::new(pool);
+
+    assert!(!manager
+        .record(USER, -100, "Rust Moscow", 1, "before agreeing", START)
+        .await
+        .unwrap());
+    // a consent given on another replica shows up at the next read of the consents
+    assert!(replica.consent(USER).await.unwrap());
+    assert!(!manager
+        .record(
+            USER,
+            -100,
+            "Rust Moscow",
+            2,
+            "agreed elsewhere",
+            START + 1.0
+        )
+        .await
+        .unwrap());
+    // one given here counts right away
+    assert!(!manager.consent(USER).await.unwrap());
+    assert!(manager
+        .record(USER, -100, "Rust Moscow", 3, "agreed here", START + 2.0)
+        .await
+        .unwrap());
+    // a withdrawn one keeps nothing even before the next read
+    replica.revoke(USER).await.unwrap();
+    assert!(!manager
+        .record(
+            USER,
+            -100,
+            "Rust Moscow",
+            4,
+            "withdrew elsewhere",
+            START + 3.0
+        )
+        .await
+        .unwrap());
+    assert!(manager.groups(USER).await.unwrap().is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_roast_battle_reads_one_group_of_a_consenting_member() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let manager = CrossGroupManager::new(pool.clone());
+    let user_manager = UserManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    bot.simulate_user_start(&user_manager, USER, Some("Alice_1"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    bot.simulate_user_start(&user_manager, USER + 1, Some("bob_2"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    // only members who agreed to keep their messages can be battled
+    assert_eq!(manager.consenting_user("alice_1").await.unwrap(), None);
+    manager.consent(USER).await.unwrap();
+    assert_eq!(
+        manager.consenting_user("alice_1").await.unwrap(),
+        Some(USER)
+    );
+    assert_eq!(manager.consenting_user("bob_2").await.unwrap(), None);
+
+    for message_id in 1..=5 {
+        manager
+            .record(
+                USER,
+                -100,
+                "Rust Moscow",
+                message_id,
+                &format!("message {}", message_id),
+                START + message_id as f64,
+            )
+            .await
+            .unwrap();
+    }
+    manager
+        .record(USER, -200, "Climbing", 1, "elsewhere", START)
+        .await
+        .unwrap();
+
+    let messages = manager.chat_messages(USER, -100, 3).await.unwrap();
+    let texts = messages
+        .iter()
+        .map(|msg| msg.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, ["message 3", "message 4", "message 5"]);
+    assert!(manager
+        .chat_messages(USER, -300, 3)
+        .await
+        .unwrap()
+        .is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_revoking_consent_purges_the_profile_made_of_the_messages() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let manager = CrossGroupManager::new(pool.clone());
+    let user_manager = UserManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, USER, Some("alice"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    manager.consent(USER).await.unwrap();
+    manager
+        .record(USER, -100, "Rust Moscow", 1, "my own words", START)
+        .await
+        .unwrap();
+    let corpus = cross_group_corpus_name(USER);
+    TestScenario::create_analyzed_corpus(&db, user.id, &corpus, "a1b2c3")
+        .await
+        .expect("Failed to store the profile");
+    // a channel the user analyzed has nothing to do with the consent
+    TestScenario::create_analyzed_corpus(&db, user.id, "@rustlang", "d4e5f6")
+        .await
+        .expect("Failed to store the channel analysis");
+
+    assert_eq!(manager.revoke(USER).await.unwrap(), 1);
+
+    TestAssertions::assert_corpus_left(&db, &corpus, "a1b2c3", false)
+        .await
+        .unwrap();
+    TestAssertions::assert_corpus_left(&db, "@rustlang", "d4e5f6", true)
+        .await
+        .unwrap();
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use tg_main::analysis::AnalysisDepth;
+use tg_main::cross_group::CrossGroupManager;
+use tg_main::data_export::DataExportManager;
+
+use super::{mock_analysis::MockAnalysisFlow, TestDatabase};
+
+// a channel with a fixture in fixtures/
+const CHANNEL: &str = "@example_channel";
+
+#[tokio::test]
+async fn test_export_holds_only_the_users_own_data() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let flow = MockAnalysisFlow::new(pool.clone()).await;
+    let cross_group = CrossGroupManager::new(pool.clone());
+    let exports = DataExportManager::new(pool);
+
+    let user = flow.start(2400, "exporter").await;
+    let other = flow.start(2401, "someone_else").await;
+    flow.user_manager
+        .record_payment(user.id, "charge-2400", "credits_10", 500, 10)
+        .await
+        .expect("Failed to record payment");
+    flow.user_manager
+        .record_payment(other.id, "charge-2401", "credits_1", 100, 1)
+        .await
+        .expect("Failed to record payment");
+    for (telegram_user_id, message_id, text) in [(2400, 1, "my message"), (2401, 2, "not mine")] {
+        cross_group.consent(telegram_user_id).await.unwrap();
+        cross_group
+            .record(
+                telegram_user_id,
+                -100,
+                "Chat",
+                message_id,
+                text,
+                1_700_000_000.0,
+            )
+            .await
+            .unwrap();
+    }
+    flow.send_channel(2400, CHANNEL).await;
+    flow.select_type(2400, "roast", AnalysisDepth::Small)
+        .await
+        .expect("Analysis should be queued");
+    assert!(flow.run_next_job().await.unwrap().outcome.is_ok());
+
+    let export = exports
+        .export(2400)
+        .await
+        .expect("Failed to export")
+        .expect("The user should be known");
+    assert_eq!(export["user"]["telegram_user_id"], 2400);
+    assert_eq!(export["user"]["username"], "exporter");
+    let analyses = export["analyses"].as_array().unwrap();
+    assert_eq!(analyses.len(), 1);
+    assert_eq!(analyses[0]["channel_name"], CHANNEL);
+    assert_eq!(analyses[0]["status"], "completed");
+    let payments = export["payments"].as_array().unwrap();
+    assert_eq!(payments.len(), 1);
+    assert_eq!(payments[0]["telegram_payment_charge_id"], "charge-2400");
+    let messages = export["group_messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["text"], "my message");
+    assert_eq!(export["refunds"].as_array().unwrap().len(), 0);
+    assert_eq!(export["referral_rewards"].as_array().unwrap().len(), 0);
+
+    assert!(exports.export(2402).await.unwrap().is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
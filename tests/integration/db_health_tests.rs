@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use tg_main::admin::AdminManager;
+use tg_main::db_health::{alert_owners, check_pool, CheckOutcome, HealthAlert};
+
+use super::TestDatabase;
+
+const OWNER_ID: i64 = 1000;
+
+#[tokio::test]
+async fn test_check_pool_pings_a_healthy_database() {
+    let db = TestDatabase::create_fresh().await.unwrap();
+
+    let outcome = check_pool(&db.pool).await;
+    assert!(matches!(outcome, CheckOutcome::Healthy(_)));
+    assert!(db.pool.status().size >= 1);
+
+    db.cleanup().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_check_pool_fails_on_a_closed_pool() {
+    let db = TestDatabase::create_fresh().await.unwrap();
+    let pool = db.pool.clone();
+    pool.close();
+
+    assert!(matches!(check_pool(&pool).await, CheckOutcome::Failed(_)));
+
+    db.cleanup().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_alerts_are_queued_for_owners_in_their_language() {
+    let db = TestDatabase::create_fresh().await.unwrap();
+    let pool = Arc::new(db.pool.clone());
+    let admin = AdminManager::new(pool.clone(), vec![OWNER_ID]);
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (telegram_user_id, language) VALUES ($1, 'de')",
+            &[&OWNER_ID],
+        )
+        .await
+        .unwrap();
+
+    alert_owners(&db.pool, &admin, &HealthAlert::Recovered { checks: 4 })
+        .await
+        .unwrap();
+
+    let rows = client
+        .query(
+            "SELECT telegram_user_id, message, parse_mode FROM message_queue",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, i64>(0), OWNER_ID);
+    let message: String = rows[0].get(1);
+    assert!(message.contains("Datenbank wieder in Ordnung"));
+    assert_eq!(rows[0].get::<_, String>(2), "HTML");
+
+    db.cleanup().await.unwrap();
+}
@@ -0,0 +1,209 @@
+use std::sync::Arc;
+use tg_main::cache::{AnalysisResult, CacheManager};
+use tg_main::feedback::{FeedbackManager, Satisfaction, Vote};
+use tg_main::user_manager::{AnalysisSource, CreditTransactionKind, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+fn result(model: Option<&str>) -> AnalysisResult {
+    AnalysisResult {
+        professional: Some("professional".to_string()),
+        personal: Some("personal".to_string()),
+        roast: Some("roast".to_string()),
+        trends: None,
+        messages_count: 10,
+        model: model.map(str::to_string),
+        report: None,
+        removed_messages: 0,
+        partial: false,
+        prompt_version: None,
+        facts: None,
+    }
+}
+
+/// a completed analysis of `user_id` whose result was produced by `model`
+async fn completed_analysis(
+    user_manager: &UserManager,
+    cache: &CacheManager,
+    user_id: i32,
+    analysis_type: &str,
+    model: Option<&str>,
+) -> i32 {
+    let analysis_id = user_manager
+        .create_pending_analysis(
+            user_id,
+            "@rustlang",
+            analysis_type,
+            "small",
+            None,
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    let cache_key = format!("key-{}", analysis_id);
+    cache
+        .save_llm_result(&cache_key, &result(model))
+        .await
+        .expect("Failed to save result");
+    user_manager
+        .set_analysis_cache_key(analysis_id, &cache_key)
+        .await
+        .expect("Failed to store cache key");
+    user_manager
+        .atomic_complete_analysis(analysis_id, user_id, 1)
+        .await
+        .expect("Failed to complete analysis");
+    analysis_id
+}
+
+#[tokio::test]
+async fn test_only_the_requester_rates_and_can_change_the_vote() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let cache = CacheManager::new(pool.clone());
+    let feedback = FeedbackManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (owner, _) = bot
+        .simulate_user_start(&user_manager, 1200, Some("owner"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    bot.simulate_user_start(&user_manager, 1201, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(owner.id, 3, CreditTransactionKind::Purchase, Some("charge"))
+        .await
+        .expect("Failed to add credits");
+
+    let pending_id = user_manager
+        .create_pending_analysis(
+            owner.id,
+            "@rustlang",
+            "roast",
+            "small",
+            None,
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    // not delivered yet
+    assert!(!feedback
+        .record(pending_id, 1200, Vote::Up)
+        .await
+        .expect("Failed to record vote"));
+
+    let analysis_id = completed_analysis(
+        &user_manager,
+        &cache,
+        owner.id,
+        "roast",
+        Some("gemini-2.5-flash"),
+    )
+    .await;
+    // not theirs
+    assert!(!feedback
+        .record(analysis_id, 1201, Vote::Up)
+        .await
+        .expect("Failed to record vote"));
+
+    assert!(feedback
+        .record(analysis_id, 1200, Vote::Up)
+        .await
+        .expect("Failed to record vote"));
+    assert!(feedback
+        .record(analysis_id, 1200, Vote::Down)
+        .await
+        .expect("Failed to record vote"));
+
+    // the second vote replaced the first
+    let report = feedback.report(30).await.expect("Failed to load report");
+    assert_eq!(
+        report.by_type,
+        vec![Satisfaction {
+            key: "roast".to_string(),
+            positive: 0,
+            negative: 1,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_report_groups_votes_by_type_and_model() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let cache = CacheManager::new(pool.clone());
+    let feedback = FeedbackManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1300, Some("rater"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(user.id, 10, CreditTransactionKind::Purchase, Some("charge"))
+        .await
+        .expect("Failed to add credits");
+
+    for (analysis_type, model, prompt_version, vote) in [
+        ("roast", Some("gemini-2.5-pro"), Some(2), Vote::Up),
+        ("roast", Some("gemini-2.5-flash"), Some(1), Vote::Up),
+        ("roast", Some("gemini-2.5-flash"), Some(2), Vote::Down),
+        ("professional", None, None, Vote::Up),
+    ] {
+        let analysis_id =
+            completed_analysis(&user_manager, &cache, user.id, analysis_type, model).await;
+        if let Some(prompt_version) = prompt_version {
+            user_manager
+                .set_analysis_prompt_version(analysis_id, prompt_version)
+                .await
+                .expect("Failed to store prompt version");
+        }
+        assert!(feedback
+            .record(analysis_id, 1300, vote)
+            .await
+            .expect("Failed to record vote"));
+    }
+
+    let report = feedback.report(30).await.expect("Failed to load report");
+    let satisfaction = |key: &str, positive, negative| Satisfaction {
+        key: key.to_string(),
+        positive,
+        negative,
+    };
+    assert_eq!(
+        report.by_type,
+        vec![
+            satisfaction("roast", 2, 1),
+            satisfaction("professional", 1, 0)
+        ]
+    );
+    // results cached before the model was recorded have none
+    assert_eq!(
+        report.by_model,
+        vec![
+            satisfaction("gemini-2.5-flash", 1, 1),
+            satisfaction("gemini-2.5-pro", 1, 0),
+            satisfaction("unknown", 1, 0),
+        ]
+    );
+    assert_eq!(report.by_model[0].rate(), 50.0);
+    // analyses from before versions were recorded ran the base prompt
+    assert_eq!(
+        report.by_prompt_version,
+        vec![
+            satisfaction("1 (base)", 2, 0),
+            satisfaction("2 (evidence)", 1, 1)
+        ]
+    );
+}
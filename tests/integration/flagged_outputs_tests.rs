@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use tg_main::flagged_outputs::FlaggedOutputsManager;
+use tg_main::llm::safety::{insert_flags, FlagContext, SafetyAction, SafetyCategory, SafetyFlag};
+
+use super::TestDatabase;
+
+const OWNER_ID: i64 = 1000;
+
+fn context(channel_name: &str) -> FlagContext {
+    FlagContext {
+        channel_name: channel_name.to_string(),
+        analysis_type: "roast".to_string(),
+        cache_key: format!("{channel_name}:roast"),
+        model: Some("gemini-2.5-pro".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_flagged_outputs_are_listed_until_reviewed() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flagged = FlaggedOutputsManager::new(Arc::new(db.pool.clone()));
+
+    insert_flags(
+        &db.pool,
+        &context("@rustlang"),
+        &[
+            SafetyFlag {
+                section: "roast".to_string(),
+                category: SafetyCategory::Harassment,
+                excerpt: "someone should visit him".to_string(),
+            },
+            SafetyFlag {
+                section: "personal".to_string(),
+                category: SafetyCategory::Doxxing,
+                excerpt: "+1 555 123 4567".to_string(),
+            },
+        ],
+        SafetyAction::Regenerated,
+    )
+    .await
+    .expect("Failed to record flags");
+    insert_flags(
+        &db.pool,
+        &context("@golang"),
+        &[SafetyFlag {
+            section: "roast".to_string(),
+            category: SafetyCategory::Slur,
+            excerpt: "a slur".to_string(),
+        }],
+        SafetyAction::Redacted,
+    )
+    .await
+    .expect("Failed to record flags");
+
+    let entries = flagged.unreviewed(10).await.expect("Failed to list flags");
+    assert_eq!(entries.len(), 3);
+    // newest first
+    assert_eq!(entries[0].channel_name, "@golang");
+    assert_eq!(entries[0].category, "slur");
+    assert_eq!(entries[0].action, "redacted");
+    assert_eq!(entries[0].model.as_deref(), Some("gemini-2.5-pro"));
+    assert_eq!(entries[1].action, "regenerated");
+    assert_eq!(flagged.unreviewed(1).await.unwrap().len(), 1);
+
+    let id = entries[0].id;
+    assert!(flagged
+        .mark_reviewed(id, OWNER_ID)
+        .await
+        .expect("Failed to mark reviewed"));
+    // already reviewed, and a flag that doesn't exist
+    assert!(!flagged.mark_reviewed(id, OWNER_ID).await.unwrap());
+    assert!(!flagged.mark_reviewed(id + 100, OWNER_ID).await.unwrap());
+
+    let entries = flagged.unreviewed(10).await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.channel_name == "@rustlang"));
+
+    let client = db.pool.get().await.unwrap();
+    let reviewed_by: Option<i64> = client
+        .query_one(
+            "SELECT reviewed_by FROM flagged_outputs WHERE id = $1",
+            &[&id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(reviewed_by, Some(OWNER_ID));
+    drop(client);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
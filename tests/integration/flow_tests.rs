@@ -0,0 +1,463 @@
+use std::sync::Arc;
+use tg_main::analysis::AnalysisDepth;
+use tg_main::analysis_runner::{AnalysisRunError, AnalysisTarget};
+use tg_main::user_manager::{CreditTransactionKind, UserManager};
+
+use super::{mock_analysis::MockAnalysisFlow, TestDatabase};
+
+// a channel with a fixture in fixtures/
+const CHANNEL: &str = "@example_channel";
+
+async fn balance(user_manager: &UserManager, telegram_user_id: i64) -> i32 {
+    let (user, _) = user_manager
+        .get_or_create_user(telegram_user_id, None, None, None, None, None)
+        .await
+        .expect("Failed to load user");
+    user.analysis_credits
+}
+
+async fn count(db: &TestDatabase, query: &str, id: i32) -> i64 {
+    let client = db.pool.get().await.unwrap();
+    client.query_one(query, &[&id]).await.unwrap().get(0)
+}
+
+async fn analysis_status(db: &TestDatabase, analysis_id: i32) -> String {
+    let client = db.pool.get().await.unwrap();
+    client
+        .query_one(
+            "SELECT status FROM user_analyses WHERE id = $1",
+            &[&analysis_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+async fn job_status(db: &TestDatabase, analysis_id: i32) -> String {
+    let client = db.pool.get().await.unwrap();
+    client
+        .query_one(
+            "SELECT status FROM analysis_jobs WHERE analysis_id = $1",
+            &[&analysis_id],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[tokio::test]
+async fn test_analysis_flow_from_start_to_delivery() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let cost = flow.limits.depth_credits(AnalysisDepth::Medium);
+
+    // start
+    let user = flow.start(2000, "reader").await;
+    flow.user_manager
+        .add_credits(user.id, cost, CreditTransactionKind::Purchase, None)
+        .await
+        .expect("Failed to add credits");
+    let credits = balance(&flow.user_manager, 2000).await;
+
+    // channel input is kept as the user's session
+    flow.send_channel(2000, CHANNEL).await;
+    assert_eq!(
+        count(
+            &db,
+            "SELECT COUNT(*) FROM user_sessions WHERE telegram_user_id = $1::INT",
+            2000
+        )
+        .await,
+        1
+    );
+
+    // type selection records a pending analysis and queues it, nothing is charged yet
+    let analysis_id = flow
+        .select_type(2000, "professional", AnalysisDepth::Medium)
+        .await
+        .expect("Analysis should be queued");
+    assert_eq!(analysis_status(&db, analysis_id).await, "pending");
+    assert_eq!(job_status(&db, analysis_id).await, "queued");
+    assert_eq!(balance(&flow.user_manager, 2000).await, credits);
+    assert!(flow.user_sessions.get(2000).await.is_none());
+
+    // the queued job runs, completes atomically and is delivered
+    let run = flow.run_next_job().await.expect("A job should be queued");
+    assert_eq!(run.analysis_id, analysis_id);
+    let outcome = run.outcome.expect("Analysis should succeed");
+    assert_eq!(outcome.remaining_credits, credits - cost);
+    assert_eq!(outcome.result.messages_count, 12);
+    assert_eq!(analysis_status(&db, analysis_id).await, "completed");
+    assert_eq!(job_status(&db, analysis_id).await, "completed");
+    assert_eq!(balance(&flow.user_manager, 2000).await, credits - cost);
+
+    let transactions = flow
+        .user_manager
+        .get_credit_transactions(user.id, 1, 0)
+        .await
+        .expect("Failed to get transactions");
+    assert_eq!(transactions[0].amount, -cost);
+    assert_eq!(transactions[0].kind, CreditTransactionKind::Analysis);
+
+    let client = db.pool.get().await.unwrap();
+    let cached: i64 = client
+        .query_one("SELECT COUNT(*) FROM channel_messages", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(cached, 1);
+    let stats: i32 = client
+        .query_one(
+            "SELECT analyses FROM channel_stats WHERE channel_name = $1",
+            &[&CHANNEL],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(stats, 1);
+
+    assert!(flow
+        .bot
+        .chat_received_message_containing(2000, "Mock professional analysis"));
+    let (_, rendered) = flow
+        .user_manager
+        .get_rendered_result(user.id, Some(analysis_id))
+        .await
+        .expect("Failed to get rendered result")
+        .expect("Result should be stored for /resend");
+    assert!(rendered.concat().contains("Mock professional analysis"));
+
+    assert!(flow.run_next_job().await.is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_second_analysis_of_a_channel_reuses_the_llm_result() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let cost = flow.limits.depth_credits(AnalysisDepth::Small);
+
+    for (telegram_user_id, analysis_type) in [(2010, "professional"), (2011, "roast")] {
+        let user = flow.start(telegram_user_id, "reader").await;
+        let credits = balance(&flow.user_manager, telegram_user_id).await;
+        assert!(credits >= cost, "new users can afford a quick analysis");
+
+        flow.send_channel(telegram_user_id, CHANNEL).await;
+        flow.select_type(telegram_user_id, analysis_type, AnalysisDepth::Small)
+            .await
+            .expect("Analysis should be queued");
+        let run = flow.run_next_job().await.expect("A job should be queued");
+        run.outcome.expect("Analysis should succeed");
+
+        // the cached result is charged like a fresh one
+        assert_eq!(
+            balance(&flow.user_manager, telegram_user_id).await,
+            credits - cost
+        );
+        assert_eq!(
+            count(
+                &db,
+                "SELECT COUNT(*) FROM user_analyses WHERE user_id = $1 AND status = 'completed'",
+                user.id
+            )
+            .await,
+            1
+        );
+    }
+
+    // both types come out of one llm answer
+    let client = db.pool.get().await.unwrap();
+    let llm_results: i64 = client
+        .query_one("SELECT COUNT(*) FROM llm_results", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(llm_results, 1);
+    assert!(flow
+        .bot
+        .chat_received_message_containing(2011, "Mock roast"));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_unknown_channel_fails_without_charging() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+
+    flow.start(2020, "reader").await;
+    let credits = balance(&flow.user_manager, 2020).await;
+    flow.send_channel(2020, "@no_such_fixture").await;
+    let analysis_id = flow
+        .select_type(2020, "personal", AnalysisDepth::Small)
+        .await
+        .expect("Analysis should be queued");
+
+    let run = flow.run_next_job().await.expect("A job should be queued");
+    assert!(matches!(run.outcome, Err(AnalysisRunError::Prepare(_))));
+    assert_eq!(analysis_status(&db, analysis_id).await, "failed");
+    assert_eq!(job_status(&db, analysis_id).await, "failed");
+    assert_eq!(balance(&flow.user_manager, 2020).await, credits);
+    assert_eq!(flow.bot.message_count_for_chat(2020), 4);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_type_selection_without_enough_credits_queues_nothing() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+
+    let user = flow.start(2030, "reader").await;
+    let credits = balance(&flow.user_manager, 2030).await;
+    assert!(credits < flow.limits.depth_credits(AnalysisDepth::Deep));
+
+    flow.send_channel(2030, CHANNEL).await;
+    assert!(flow
+        .select_type(2030, "professional", AnalysisDepth::Deep)
+        .await
+        .is_none());
+    assert_eq!(
+        count(
+            &db,
+            "SELECT COUNT(*) FROM user_analyses WHERE user_id = $1",
+            user.id
+        )
+        .await,
+        0
+    );
+    // the channel stays picked, so the user can choose a cheaper depth
+    assert!(flow.user_sessions.get(2030).await.is_some());
+    assert!(flow.run_next_job().await.is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_unrecorded_analysis_shares_the_llm_cache_without_records() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let target = AnalysisTarget {
+        channel_name: CHANNEL.to_string(),
+        analysis_type: "roast".to_string(),
+        depth: AnalysisDepth::Small,
+        focus: None,
+        topic: None,
+        allow_low_text: true,
+    };
+
+    let result = flow
+        .analyze_unrecorded(&target)
+        .await
+        .expect("Analysis should succeed");
+    assert!(result.roast.unwrap().contains("Mock roast"));
+    assert_eq!(result.messages_count, 12);
+
+    let client = db.pool.get().await.unwrap();
+    let analyses: i64 = client
+        .query_one("SELECT COUNT(*) FROM user_analyses", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(analyses, 0);
+
+    // a user's analysis of the channel reuses the operator's llm answer
+    flow.start(2040, "reader").await;
+    flow.send_channel(2040, CHANNEL).await;
+    flow.select_type(2040, "professional", AnalysisDepth::Small)
+        .await
+        .expect("Analysis should be queued");
+    flow.run_next_job()
+        .await
+        .expect("A job should be queued")
+        .outcome
+        .expect("Analysis should succeed");
+    let llm_results: i64 = client
+        .query_one("SELECT COUNT(*) FROM llm_results", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(llm_results, 1);
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_unrecorded_analysis_of_an_unknown_channel_fails() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let target = AnalysisTarget {
+        channel_name: "@no_such_fixture".to_string(),
+        analysis_type: "professional".to_string(),
+        depth: AnalysisDepth::Small,
+        focus: None,
+        topic: None,
+        allow_low_text: true,
+    };
+
+    assert!(matches!(
+        flow.analyze_unrecorded(&target).await,
+        Err(AnalysisRunError::Prepare(_))
+    ));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_stale_messages_are_served_while_a_background_refresh_replaces_them() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let target = AnalysisTarget {
+        channel_name: CHANNEL.to_string(),
+        analysis_type: "roast".to_string(),
+        depth: AnalysisDepth::Small,
+        focus: None,
+        topic: None,
+        allow_low_text: true,
+    };
+    let pool = &db.pool;
+    let cached_messages = || async {
+        let client = pool.get().await.unwrap();
+        client
+            .query_one(
+                "SELECT jsonb_array_length(messages_data) FROM channel_messages
+                 WHERE channel_name = $1",
+                &[&CHANNEL],
+            )
+            .await
+            .unwrap()
+            .get::<_, i32>(0)
+    };
+    // the channel's cache keeps one of its posts, fetched `age` ago and never in full since
+    // that was recorded, so a refresh fetches it in full
+    let backdate = |age: &'static str| async move {
+        let client = pool.get().await.unwrap();
+        client
+            .execute(
+                &format!(
+                    "UPDATE channel_messages
+                     SET messages_data = jsonb_path_query_array(messages_data, '$[0]'),
+                         updated_at = NOW() - INTERVAL '{age}',
+                         full_fetch_at = NULL
+                     WHERE channel_name = $1"
+                ),
+                &[&CHANNEL],
+            )
+            .await
+            .unwrap();
+    };
+
+    let result = flow.analyze_unrecorded(&target).await.unwrap();
+    assert_eq!(result.messages_count, 12);
+
+    // a day and a half old: the analysis reads the cached post right away
+    backdate("36 hours").await;
+    let result = flow.analyze_unrecorded(&target).await.unwrap();
+    assert_eq!(result.messages_count, 1);
+    // and the channel is fetched again behind it
+    let mut refreshed = false;
+    for _ in 0..50 {
+        if cached_messages().await == 12 {
+            refreshed = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert!(
+        refreshed,
+        "the stale corpus should be refreshed in the background"
+    );
+
+    // four days old: fetched again before the analysis
+    backdate("4 days").await;
+    let result = flow.analyze_unrecorded(&target).await.unwrap();
+    assert_eq!(result.messages_count, 12);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_refresh_fetches_only_the_posts_newer_than_the_cached_ones() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let target = AnalysisTarget {
+        channel_name: CHANNEL.to_string(),
+        analysis_type: "roast".to_string(),
+        depth: AnalysisDepth::Small,
+        focus: None,
+        topic: None,
+        allow_low_text: true,
+    };
+    let pool = &db.pool;
+    let cached_ids = || async {
+        let client = pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "SELECT jsonb_path_query_array(messages_data, '$[*].id'), last_message_id
+                 FROM channel_messages WHERE channel_name = $1",
+                &[&CHANNEL],
+            )
+            .await
+            .unwrap();
+        let ids: serde_json::Value = row.get(0);
+        (ids, row.get::<_, Option<i64>>(1))
+    };
+
+    let result = flow.analyze_unrecorded(&target).await.unwrap();
+    assert_eq!(result.messages_count, 12);
+
+    // fetched in full a day and a half ago, with the three newest posts yet to come and
+    // some of the older ones out of the fetch window
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE channel_messages
+             SET messages_data = jsonb_path_query_array(messages_data, '$[3 to 5]'),
+                 last_message_id = 108,
+                 updated_at = NOW() - INTERVAL '36 hours',
+                 full_fetch_at = NOW() - INTERVAL '36 hours'
+             WHERE channel_name = $1",
+            &[&CHANNEL],
+        )
+        .await
+        .unwrap();
+    drop(client);
+
+    let result = flow.analyze_unrecorded(&target).await.unwrap();
+    assert_eq!(result.messages_count, 3);
+    // the background refresh adds the new posts in front of the cached ones, leaving the
+    // older posts a full fetch would bring back out
+    let mut merged = None;
+    for _ in 0..50 {
+        let (ids, last_message_id) = cached_ids().await;
+        if last_message_id == Some(111) {
+            merged = Some(ids);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(
+        merged.expect("the stale corpus should be refreshed in the background"),
+        serde_json::json!([111, 110, 109, 108, 107, 106])
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+use tg_main::free_trials::{ClusterStatus, FreeTrialManager, TrialOutcome, CLUSTER_LIST_LIMIT};
+use tg_main::user_manager::{CreditTransactionKind, UserManager};
+
+use super::TestDatabase;
+
+async fn join(
+    user_manager: &UserManager,
+    telegram_user_id: i64,
+    language: &str,
+    referrer_user_id: Option<i32>,
+) -> i32 {
+    user_manager
+        .get_or_create_user(
+            telegram_user_id,
+            None,
+            Some("Bot"),
+            None,
+            referrer_user_id,
+            Some(language),
+        )
+        .await
+        .expect("Failed to create user")
+        .0
+        .id
+}
+
+#[tokio::test]
+async fn test_free_trial_waits_for_the_human_check() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let free_trials = FreeTrialManager::new(pool);
+
+    let (user, _) = user_manager
+        .get_or_create_user(5000, Some("human"), Some("Human"), None, None, Some("en"))
+        .await
+        .expect("Failed to create user");
+    assert_eq!(user.analysis_credits, 0);
+    assert!(free_trials.is_pending(user.id).await);
+    assert!(user_manager
+        .get_credit_transactions(user.id, 10, 0)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let outcome = user_manager.claim_free_trial(5000, 10).await.unwrap();
+    assert_eq!(outcome, Some(TrialOutcome::Granted { credits: 1 }));
+    assert!(!free_trials.is_pending(user.id).await);
+    let history = user_manager
+        .get_credit_transactions(user.id, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, CreditTransactionKind::SignupBonus);
+
+    // a second press doesn't grant another trial
+    assert_eq!(user_manager.claim_free_trial(5000, 10).await.unwrap(), None);
+    let (user, _) = user_manager
+        .get_or_create_user(5000, Some("human"), Some("Human"), None, None, Some("en"))
+        .await
+        .unwrap();
+    assert_eq!(user.analysis_credits, 1);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_look_alike_signups_are_throttled_and_reviewed() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let free_trials = FreeTrialManager::new(pool);
+    let referrer = Some(join(&user_manager, 5099, "en", None).await);
+
+    for telegram_user_id in 5100..5104 {
+        join(&user_manager, telegram_user_id, "ru", referrer).await;
+    }
+    let withheld = join(&user_manager, 5110, "ru", referrer).await;
+    let other = join(&user_manager, 5200, "de", referrer).await;
+
+    for telegram_user_id in 5100..5102 {
+        assert!(matches!(
+            user_manager
+                .claim_free_trial(telegram_user_id, 2)
+                .await
+                .unwrap(),
+            Some(TrialOutcome::Granted { .. })
+        ));
+    }
+    for telegram_user_id in [5102, 5103, 5110] {
+        assert_eq!(
+            user_manager
+                .claim_free_trial(telegram_user_id, 2)
+                .await
+                .unwrap(),
+            Some(TrialOutcome::Withheld)
+        );
+    }
+    // another language is another pattern
+    assert_eq!(
+        user_manager.claim_free_trial(5200, 2).await.unwrap(),
+        Some(TrialOutcome::Granted { credits: 1 })
+    );
+    assert!(!free_trials.is_pending(other).await);
+
+    let clusters = free_trials.pending(CLUSTER_LIST_LIMIT).await.unwrap();
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].withheld_count, 3);
+    assert!(clusters[0].fingerprint.starts_with("lang=ru "));
+
+    // clearing grants the withheld trials and exempts the pattern from the limit
+    let granted = free_trials
+        .review(clusters[0].id, ClusterStatus::Cleared, 1)
+        .await
+        .unwrap();
+    assert_eq!(granted, Some(3));
+    let history = user_manager
+        .get_credit_transactions(withheld, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].balance_after, 1);
+    assert_eq!(
+        free_trials
+            .review(clusters[0].id, ClusterStatus::Confirmed, 1)
+            .await
+            .unwrap(),
+        None
+    );
+    join(&user_manager, 5120, "ru", referrer).await;
+    assert!(matches!(
+        user_manager.claim_free_trial(5120, 2).await.unwrap(),
+        Some(TrialOutcome::Granted { .. })
+    ));
+    assert!(free_trials
+        .pending(CLUSTER_LIST_LIMIT)
+        .await
+        .unwrap()
+        .is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_confirmed_clusters_get_no_more_trials() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let free_trials = FreeTrialManager::new(pool);
+    let referrer = Some(join(&user_manager, 5299, "en", None).await);
+
+    for telegram_user_id in 5300..5302 {
+        join(&user_manager, telegram_user_id, "es", referrer).await;
+        user_manager
+            .claim_free_trial(telegram_user_id, 1)
+            .await
+            .unwrap();
+    }
+    let cluster = free_trials.pending(CLUSTER_LIST_LIMIT).await.unwrap()[0].clone();
+    assert_eq!(
+        free_trials
+            .review(cluster.id, ClusterStatus::Confirmed, 1)
+            .await
+            .unwrap(),
+        Some(0)
+    );
+
+    // withheld even with trials to spare
+    join(&user_manager, 5310, "es", referrer).await;
+    assert_eq!(
+        user_manager.claim_free_trial(5310, 100).await.unwrap(),
+        Some(TrialOutcome::Withheld)
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_unrelated_organic_signups_are_not_throttled_together() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let free_trials = FreeTrialManager::new(pool);
+
+    // many more signups in one language within the hour than the limit
+    for telegram_user_id in 5400..5410 {
+        join(&user_manager, telegram_user_id, "en", None).await;
+        assert!(matches!(
+            user_manager
+                .claim_free_trial(telegram_user_id, 2)
+                .await
+                .unwrap(),
+            Some(TrialOutcome::Granted { .. })
+        ));
+    }
+    // and the referrals of different referrers are counted apart
+    for (referrer, first_referral) in [(5400, 5500), (5401, 5510), (5402, 5520)] {
+        let referrer = Some(join(&user_manager, referrer, "en", None).await);
+        for telegram_user_id in first_referral..first_referral + 2 {
+            join(&user_manager, telegram_user_id, "en", referrer).await;
+            assert!(matches!(
+                user_manager
+                    .claim_free_trial(telegram_user_id, 2)
+                    .await
+                    .unwrap(),
+                Some(TrialOutcome::Granted { .. })
+            ));
+        }
+    }
+    assert!(free_trials
+        .pending(CLUSTER_LIST_LIMIT)
+        .await
+        .unwrap()
+        .is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,14 @@
+use tg_main::health::check_database;
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_database_check_passes_on_a_live_pool() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+
+    assert!(check_database(&db.pool).await);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,396 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tg_main::job_queue::{worker_id, JobQueue, JobRunner, JobStatus, JOB_LEASE, MAX_JOB_ATTEMPTS};
+use tg_main::user_manager::{AnalysisSource, UserManager};
+use tokio::sync::oneshot;
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+async fn pending_analysis(
+    user_manager: &UserManager,
+    user_id: i32,
+    channel: &str,
+    source: AnalysisSource,
+) -> i32 {
+    user_manager
+        .create_pending_analysis(
+            user_id,
+            channel,
+            "roast",
+            "medium",
+            Some("en"),
+            None,
+            source,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis")
+}
+
+async fn expire_leases(db: &TestDatabase) {
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE analysis_jobs SET lease_expires_at = NOW() - INTERVAL '1 second'
+             WHERE status = 'running'",
+            &[],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_paid_users_are_leased_first() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let queue = JobQueue::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (free, _) = bot
+        .simulate_user_start(&user_manager, 1700, Some("free"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let (paid, _) = bot
+        .simulate_user_start(&user_manager, 1701, Some("paid"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .record_payment(paid.id, "charge_1", "credits_10", 100, 10)
+        .await
+        .expect("Failed to record payment");
+
+    let free_first = pending_analysis(&user_manager, free.id, "@first", AnalysisSource::Bot).await;
+    let free_second =
+        pending_analysis(&user_manager, free.id, "@second", AnalysisSource::Bot).await;
+    let paid_last = pending_analysis(&user_manager, paid.id, "@paid", AnalysisSource::Bot).await;
+    let api = pending_analysis(&user_manager, paid.id, "@api", AnalysisSource::Api).await;
+    for analysis_id in [free_first, free_second, paid_last, api] {
+        queue
+            .enqueue(analysis_id, Some(-100), Some("ru"), false)
+            .await
+            .expect("Failed to queue analysis");
+    }
+
+    let leased = queue
+        .lease(AnalysisSource::Bot, "bot-1", JOB_LEASE)
+        .await
+        .expect("Failed to lease")
+        .expect("A job should be queued");
+    assert_eq!(leased.analysis.id, paid_last);
+    assert_eq!(leased.attempts, 1);
+    assert_eq!(leased.chat_id, Some(-100));
+    assert_eq!(leased.language.as_deref(), Some("ru"));
+
+    // then free users in the order they asked, and never another front end's jobs
+    let order = [
+        queue
+            .lease(AnalysisSource::Bot, "bot-1", JOB_LEASE)
+            .await
+            .unwrap()
+            .unwrap()
+            .analysis
+            .id,
+        queue
+            .lease(AnalysisSource::Bot, "bot-1", JOB_LEASE)
+            .await
+            .unwrap()
+            .unwrap()
+            .analysis
+            .id,
+    ];
+    assert_eq!(order, [free_first, free_second]);
+    assert!(queue
+        .lease(AnalysisSource::Bot, "bot-1", JOB_LEASE)
+        .await
+        .unwrap()
+        .is_none());
+
+    let statuses = queue.statuses(&[paid_last, api]).await.unwrap();
+    assert!(statuses.contains(&(paid_last, JobStatus::Running)));
+    assert!(statuses.contains(&(api, JobStatus::Queued)));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_expired_lease_is_taken_over_until_attempts_run_out() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let queue = JobQueue::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1710, Some("crashy"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let analysis_id =
+        pending_analysis(&user_manager, user.id, "@crashy", AnalysisSource::Bot).await;
+    queue.enqueue(analysis_id, None, None, false).await.unwrap();
+
+    let job = queue
+        .lease(AnalysisSource::Bot, "bot-1", JOB_LEASE)
+        .await
+        .unwrap()
+        .unwrap();
+    // a live lease is neither taken over nor lost
+    assert!(queue
+        .lease(AnalysisSource::Bot, "bot-2", JOB_LEASE)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(queue
+        .extend_lease(job.id, "bot-1", JOB_LEASE)
+        .await
+        .unwrap());
+
+    for attempt in 2..=MAX_JOB_ATTEMPTS {
+        expire_leases(&db).await;
+        let job = queue
+            .lease(AnalysisSource::Bot, "bot-2", Duration::from_secs(60))
+            .await
+            .unwrap()
+            .expect("An expired lease should be taken over");
+        assert_eq!(job.attempts, attempt);
+    }
+    assert!(!queue
+        .extend_lease(job.id, "bot-1", JOB_LEASE)
+        .await
+        .unwrap());
+
+    expire_leases(&db).await;
+    assert!(queue
+        .lease(AnalysisSource::Bot, "bot-2", JOB_LEASE)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(queue.sweep().await.unwrap(), 1);
+    assert_eq!(
+        queue.statuses(&[analysis_id]).await.unwrap(),
+        vec![(analysis_id, JobStatus::Failed)]
+    );
+    let record = user_manager
+        .get_analysis(analysis_id, user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.status, "failed");
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_requeue_keeps_confirmation_and_cancel_skips_queued_jobs() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let queue = JobQueue::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1720, Some("batcher"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let running = pending_analysis(&user_manager, user.id, "@running", AnalysisSource::Bot).await;
+    let waiting = pending_analysis(&user_manager, user.id, "@waiting", AnalysisSource::Bot).await;
+    queue
+        .enqueue(running, Some(-200), Some("ru"), true)
+        .await
+        .unwrap();
+    queue.enqueue(waiting, None, None, false).await.unwrap();
+    let job = queue
+        .lease(AnalysisSource::Bot, "bot-old", JOB_LEASE)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.analysis.id, running);
+
+    // recovery queues it again without the chat or the confirmation, both are kept
+    queue.enqueue(running, None, None, false).await.unwrap();
+    let cancelled = queue.cancel(&[running, waiting]).await.unwrap();
+    assert_eq!(cancelled.len(), 2);
+    let counts = queue.counts().await.unwrap();
+    assert!(counts.contains(&(JobStatus::Cancelled, 2)));
+    assert!(counts.contains(&(JobStatus::Running, 0)));
+
+    // a cancelled job starts over when the analysis is queued again
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE user_analyses SET status = 'pending' WHERE id = $1",
+            &[&running],
+        )
+        .await
+        .unwrap();
+    drop(client);
+    queue.enqueue(running, None, None, false).await.unwrap();
+    let job = queue
+        .lease(AnalysisSource::Bot, "bot-new", JOB_LEASE)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.analysis.id, running);
+    assert_eq!(job.attempts, 1);
+    assert!(job.allow_low_text);
+    assert_eq!(job.chat_id, Some(-200));
+    assert_eq!(job.language.as_deref(), Some("ru"));
+
+    queue.complete(job.id).await.unwrap();
+    assert_eq!(
+        queue.statuses(&[running]).await.unwrap(),
+        vec![(running, JobStatus::Completed)]
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_recovery_leaves_jobs_running_on_another_replica_alone() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let queue = JobQueue::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1730, Some("replicas"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let running = pending_analysis(&user_manager, user.id, "@running", AnalysisSource::Bot).await;
+    let stale = pending_analysis(&user_manager, user.id, "@stale", AnalysisSource::Bot).await;
+    let unqueued = pending_analysis(&user_manager, user.id, "@unqueued", AnalysisSource::Bot).await;
+    queue.enqueue(running, None, None, false).await.unwrap();
+    let job = queue
+        .lease(AnalysisSource::Bot, "bot-replica", JOB_LEASE)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.analysis.id, running);
+
+    // a starting replica neither takes the job over nor fails its analysis
+    assert!(queue.resume(running, false).await.unwrap().is_none());
+    assert!(!queue.abandon(running).await.unwrap());
+    assert!(queue
+        .extend_lease(job.id, "bot-replica", JOB_LEASE)
+        .await
+        .unwrap());
+    let record = user_manager
+        .get_analysis(running, user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.status, "pending");
+
+    // an analysis without a job is queued, one without a live runner can be abandoned
+    assert!(queue.resume(unqueued, false).await.unwrap().is_some());
+    assert!(queue.abandon(stale).await.unwrap());
+    let record = user_manager
+        .get_analysis(stale, user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.status, "failed");
+
+    // once the replica's lease expires its job is queued again
+    expire_leases(&db).await;
+    assert_eq!(queue.resume(running, false).await.unwrap(), Some(job.id));
+    assert!(!queue
+        .extend_lease(job.id, "bot-replica", JOB_LEASE)
+        .await
+        .unwrap());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_runner_stops_a_job_whose_lease_was_taken_over() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let queue = Arc::new(JobQueue::new(pool));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1740, Some("slow"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let analysis_id = pending_analysis(&user_manager, user.id, "@slow", AnalysisSource::Bot).await;
+    queue.enqueue(analysis_id, None, None, false).await.unwrap();
+
+    // the handler reports when it finishes; dropping the sender means it was stopped
+    let (started_tx, started_rx) = oneshot::channel();
+    let (finished_tx, finished_rx) = oneshot::channel::<()>();
+    let channels = Arc::new(Mutex::new(Some((started_tx, finished_tx))));
+    let runner = JobRunner::new(
+        queue.clone(),
+        AnalysisSource::Bot,
+        1,
+        Duration::from_secs(1),
+    );
+    let running = tokio::spawn(runner.run(move |job| {
+        let channels = channels.lock().unwrap().take();
+        async move {
+            if let Some((started, finished)) = channels {
+                started.send(job.id).unwrap();
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                let _ = finished.send(());
+            }
+            Ok::<(), String>(())
+        }
+    }));
+    let job_id = tokio::time::timeout(Duration::from_secs(10), started_rx)
+        .await
+        .expect("The job should be leased")
+        .unwrap();
+
+    // another runner takes the job over
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE analysis_jobs SET leased_by = 'bot-other', lease_expires_at = NOW() + INTERVAL '1 hour'
+             WHERE id = $1",
+            &[&job_id],
+        )
+        .await
+        .unwrap();
+    drop(client);
+
+    let finished = tokio::time::timeout(Duration::from_secs(5), finished_rx)
+        .await
+        .expect("The handler should be stopped well before it finishes");
+    assert!(finished.is_err());
+    running.abort();
+
+    // and the job is left to the runner that has it
+    assert_eq!(
+        queue.statuses(&[analysis_id]).await.unwrap(),
+        vec![(analysis_id, JobStatus::Running)]
+    );
+    assert!(queue
+        .extend_lease(job_id, "bot-other", JOB_LEASE)
+        .await
+        .unwrap());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[test]
+fn test_worker_ids_differ_between_runners_of_one_pid() {
+    // replicas in containers usually all run as pid 1
+    let first = worker_id(AnalysisSource::Bot);
+    let second = worker_id(AnalysisSource::Bot);
+    assert!(first.starts_with("bot-"));
+    assert!(first.contains(&format!("-{}-", std::process::id())));
+    assert_ne!(first, second);
+}
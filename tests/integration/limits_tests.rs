@@ -0,0 +1,38 @@
+use tg_main::limits::Limits;
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_database_overrides_apply_on_load() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+
+    Limits::save_override(&db.pool, "subscription_price", 450)
+        .await
+        .expect("Failed to save override");
+    Limits::save_override(&db.pool, "subscription_price", 400)
+        .await
+        .expect("Failed to update override");
+    assert!(Limits::save_override(&db.pool, "subscription_price", 0)
+        .await
+        .is_err());
+    assert!(Limits::save_override(&db.pool, "free_lunches", 1)
+        .await
+        .is_err());
+
+    let limits = Limits::load(&db.pool).await.expect("Failed to load limits");
+    assert_eq!(limits.subscription_price, 400);
+
+    assert!(Limits::remove_override(&db.pool, "subscription_price")
+        .await
+        .expect("Failed to remove override"));
+    assert!(!Limits::remove_override(&db.pool, "subscription_price")
+        .await
+        .expect("Failed to remove override"));
+    let limits = Limits::load(&db.pool).await.expect("Failed to load limits");
+    assert_eq!(
+        limits.subscription_price,
+        Limits::default().subscription_price
+    );
+}
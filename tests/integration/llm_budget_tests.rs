@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use tg_main::admin::AdminManager;
+use tg_main::llm::usage::{daily_spend, today_spend_usd};
+use tg_main::llm_budget::LlmBudget;
+
+use super::TestDatabase;
+
+const OWNER_ID: i64 = 1000;
+
+async fn record(db: &TestDatabase, cost_usd: f64, days_ago: i32) {
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO llm_calls (model, prompt_tokens, output_tokens, cost_usd, created_at)
+             VALUES ('gemini-2.5-flash', 1000, 200, $1, NOW() - make_interval(days => $2))",
+            &[&cost_usd, &days_ago],
+        )
+        .await
+        .unwrap();
+}
+
+async fn queued_alerts(db: &TestDatabase) -> i64 {
+    let client = db.pool.get().await.unwrap();
+    client
+        .query_one(
+            "SELECT COUNT(*) FROM message_queue WHERE telegram_user_id = $1",
+            &[&OWNER_ID],
+        )
+        .await
+        .unwrap()
+        .get(0)
+}
+
+#[tokio::test]
+async fn test_spend_is_aggregated_per_day() {
+    let db = TestDatabase::create_fresh().await.unwrap();
+    record(&db, 0.25, 0).await;
+    record(&db, 0.5, 0).await;
+    record(&db, 2.0, 3).await;
+    record(&db, 9.0, 30).await;
+
+    let today = today_spend_usd(&db.pool).await.unwrap();
+    assert!((today - 0.75).abs() < 1e-9);
+
+    let report = daily_spend(&db.pool, 7).await.unwrap();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].calls, 2);
+    assert_eq!(report[0].prompt_tokens, 2000);
+    assert!((report[1].cost_usd - 2.0).abs() < 1e-9);
+
+    db.cleanup().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_exhausted_budget_refuses_and_alerts_owners_once() {
+    let db = TestDatabase::create_fresh().await.unwrap();
+    let pool = Arc::new(db.pool.clone());
+    let admin = Arc::new(AdminManager::new(pool.clone(), vec![OWNER_ID]));
+    let budget = LlmBudget::new(pool, admin, 100);
+
+    record(&db, 0.6, 0).await;
+    // yesterday's spend doesn't count against today's budget
+    record(&db, 5.0, 1).await;
+    assert!(budget.allows_llm_call().await);
+    assert_eq!(queued_alerts(&db).await, 0);
+
+    record(&db, 0.6, 0).await;
+    assert!(!budget.allows_llm_call().await);
+    assert!(!budget.allows_llm_call().await);
+    assert_eq!(queued_alerts(&db).await, 1);
+
+    db.cleanup().await.unwrap();
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use tg_main::user_manager::{AnalysisSource, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_failed_analysis_is_reopened_once_by_its_owner() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (owner, _) = bot
+        .simulate_user_start(&user_manager, 1600, Some("owner"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let (other, _) = bot
+        .simulate_user_start(&user_manager, 1601, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    let analysis_id = user_manager
+        .create_pending_analysis(
+            owner.id,
+            "@memes_only",
+            "roast",
+            "medium",
+            Some("ru"),
+            Some("captions"),
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+
+    // still pending, nothing to reopen
+    assert!(user_manager
+        .reopen_failed_analysis(analysis_id, owner.id, i32::MAX)
+        .await
+        .expect("Failed to reopen analysis")
+        .is_none());
+
+    user_manager
+        .mark_analysis_failed(analysis_id)
+        .await
+        .expect("Failed to mark analysis failed");
+    assert!(user_manager
+        .reopen_failed_analysis(analysis_id, other.id, i32::MAX)
+        .await
+        .expect("Failed to reopen analysis")
+        .is_none());
+
+    let reopened = user_manager
+        .reopen_failed_analysis(analysis_id, owner.id, i32::MAX)
+        .await
+        .expect("Failed to reopen analysis")
+        .expect("The owner should reopen a failed analysis");
+    assert_eq!(reopened.id, analysis_id);
+    assert_eq!(reopened.telegram_user_id, 1600);
+    assert_eq!(reopened.channel_name, "@memes_only");
+    assert_eq!(reopened.analysis_type, "roast");
+    assert_eq!(reopened.depth, "medium");
+    assert_eq!(reopened.language.as_deref(), Some("ru"));
+    assert_eq!(reopened.focus.as_deref(), Some("captions"));
+
+    // a second press of the button doesn't start it twice
+    assert!(user_manager
+        .reopen_failed_analysis(analysis_id, owner.id, i32::MAX)
+        .await
+        .expect("Failed to reopen analysis")
+        .is_none());
+    let record = user_manager
+        .get_analysis(analysis_id, owner.id)
+        .await
+        .expect("Failed to load analysis")
+        .expect("Analysis should exist");
+    assert_eq!(record.status, "pending");
+}
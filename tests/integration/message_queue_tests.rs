@@ -0,0 +1,145 @@
+use std::sync::Arc;
+use tg_main::message_queue::{FailureOutcome, MessageQueue, QueuedMessage, MAX_SEND_ATTEMPTS};
+
+use super::TestDatabase;
+
+async fn queue_message(db: &TestDatabase, telegram_user_id: i64, text: &str) -> i32 {
+    let client = db.pool.get().await.expect("Failed to get database client");
+    let row = client
+        .query_one(
+            "INSERT INTO message_queue (telegram_user_id, message) VALUES ($1, $2) RETURNING id",
+            &[&telegram_user_id, &text],
+        )
+        .await
+        .expect("Failed to queue message");
+    row.get(0)
+}
+
+async fn next_due(queue: &MessageQueue) -> Option<QueuedMessage> {
+    queue.due_batch(10).await.unwrap().into_iter().next()
+}
+
+#[tokio::test]
+async fn test_failed_send_is_retried_later() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let queue = MessageQueue::new(Arc::new(db.pool.clone()));
+    let id = queue_message(&db, 1, "hi").await;
+
+    let queued = next_due(&queue).await.expect("message is due");
+    assert_eq!(queued.id, id);
+    assert_eq!(queued.retry_count, 0);
+
+    let outcome = queue
+        .record_failure(&queued, "chat not found")
+        .await
+        .unwrap();
+    assert!(matches!(outcome, FailureOutcome::Retry(_)));
+    // backing off, so not due right away
+    assert!(next_due(&queue).await.is_none());
+
+    let client = db.pool.get().await.expect("Failed to get database client");
+    let row = client
+        .query_one(
+            "SELECT status, retry_count, error_message FROM message_queue WHERE id = $1",
+            &[&id],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, String>(0), "pending");
+    assert_eq!(row.get::<_, i32>(1), 1);
+    assert_eq!(row.get::<_, String>(2), "chat not found");
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_dead_lettered_messages_are_requeued() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let queue = MessageQueue::new(Arc::new(db.pool.clone()));
+    let first = queue_message(&db, 1, "first").await;
+    let second = queue_message(&db, 1, "second").await;
+
+    // both on their last attempt
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE message_queue SET retry_count = $1",
+            &[&(MAX_SEND_ATTEMPTS - 1)],
+        )
+        .await
+        .unwrap();
+    for _ in 0..2 {
+        let queued = next_due(&queue).await.expect("message is due");
+        let outcome = queue.record_failure(&queued, "blocked").await.unwrap();
+        assert_eq!(outcome, FailureOutcome::DeadLettered);
+    }
+    assert!(next_due(&queue).await.is_none());
+    assert_eq!(queue.dead_letter_count().await.unwrap(), 2);
+
+    assert_eq!(queue.requeue_dead(Some(second)).await.unwrap(), 1);
+    let queued = next_due(&queue).await.expect("requeued message");
+    assert_eq!(queued.id, second);
+    assert_eq!(queued.retry_count, 0);
+    // only dead-lettered messages are requeued
+    assert_eq!(queue.requeue_dead(Some(second)).await.unwrap(), 0);
+
+    assert_eq!(queue.requeue_dead(None).await.unwrap(), 1);
+    assert_eq!(queue.dead_letter_count().await.unwrap(), 0);
+    let ids = client
+        .query(
+            "SELECT id FROM message_queue WHERE status = 'pending' ORDER BY id",
+            &[],
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.get::<_, i32>(0))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![first, second]);
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_batches_keep_per_recipient_order() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let queue = MessageQueue::new(Arc::new(db.pool.clone()));
+    let first = queue_message(&db, 1, "first").await;
+    let second = queue_message(&db, 1, "second").await;
+    let other = queue_message(&db, 2, "other chat").await;
+
+    // one message per recipient, the oldest of each
+    let batch = queue.due_batch(10).await.unwrap();
+    let ids = batch.iter().map(|queued| queued.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![first, other]);
+    assert_eq!(queue.due_batch(1).await.unwrap().len(), 1);
+
+    // a backing off message holds back the later ones of its chat only
+    queue.record_failure(&batch[0], "timeout").await.unwrap();
+    queue.mark_sent(other).await.unwrap();
+    assert!(queue.due_batch(10).await.unwrap().is_empty());
+
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE message_queue SET next_attempt_at = NOW() WHERE id = $1",
+            &[&first],
+        )
+        .await
+        .unwrap();
+    queue.mark_sent(first).await.unwrap();
+    let batch = queue.due_batch(10).await.unwrap();
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].id, second);
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
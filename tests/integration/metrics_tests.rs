@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use tg_main::metrics::Metrics;
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_message_queue_depth_counts_pending_messages() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let client = db.pool.get().await.expect("Failed to get database client");
+    for status in ["pending", "pending", "sent", "dead"] {
+        client
+            .execute(
+                "INSERT INTO message_queue (telegram_user_id, message, status) VALUES (1, 'hi', $1)",
+                &[&status],
+            )
+            .await
+            .expect("Failed to queue message");
+    }
+
+    let metrics = Metrics::new();
+    metrics
+        .refresh_message_queue_depth(&db.pool)
+        .await
+        .expect("Failed to refresh queue depth");
+    assert!(metrics
+        .render()
+        .contains("tg_analyzer_message_queue_depth 2"));
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_analysis_jobs_are_counted_by_status() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .batch_execute(
+            "INSERT INTO users (telegram_user_id) VALUES (1);
+             INSERT INTO user_analyses (user_id, channel_name, status) VALUES (1, '@a', 'pending'), (1, '@b', 'pending');
+             INSERT INTO analysis_jobs (analysis_id, status) VALUES (1, 'queued'), (2, 'running');",
+        )
+        .await
+        .expect("Failed to queue jobs");
+
+    let metrics = Metrics::new();
+    metrics
+        .refresh_analysis_jobs(&pool)
+        .await
+        .expect("Failed to refresh job counts");
+    let rendered = metrics.render();
+    assert!(rendered.contains("tg_analyzer_analysis_jobs{status=\"queued\"} 1"));
+    assert!(rendered.contains("tg_analyzer_analysis_jobs{status=\"running\"} 1"));
+    assert!(rendered.contains("tg_analyzer_analysis_jobs{status=\"failed\"} 0"));
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
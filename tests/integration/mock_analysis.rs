@@ -0,0 +1,448 @@
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tg_main::admin::AdminManager;
+use tg_main::analysis::AnalysisDepth;
+use tg_main::analysis_runner::{
+    run_analysis, run_unrecorded_analysis, AnalysisJob, AnalysisOutcome, AnalysisRunError,
+    AnalysisTarget,
+};
+use tg_main::bot::ChannelLocks;
+use tg_main::cache::AnalysisResult;
+use tg_main::channel_claims::ChannelClaimManager;
+use tg_main::channel_stats::ChannelStatsManager;
+use tg_main::handlers::{CallbackData, CallbackHandler};
+use tg_main::job_queue::{JobQueue, JOB_LEASE};
+use tg_main::limits::Limits;
+use tg_main::llm_budget::LlmBudget;
+use tg_main::localization::Lang;
+use tg_main::prompts::analysis::MAX_FOCUS_LENGTH;
+use tg_main::user_manager::{AnalysisSource, User, UserManager};
+use tg_main::user_sessions::{UserSession, UserSessions};
+use tg_main::utils::{MessageFormatter, ResultPresenter};
+use tg_main::workers::AnalysisWorkers;
+use tokio::sync::Mutex;
+
+use super::mock_bot::MockTelegramBot;
+
+/// a finished run of a queued analysis
+pub struct FlowRun {
+    pub analysis_id: i32,
+    pub outcome: Result<AnalysisOutcome, AnalysisRunError>,
+}
+
+/// the bot's analysis flow on top of LLM_MOCK mode: channels are read from fixtures/
+/// and the llm answers with canned responses, so an analysis runs end to end without
+/// telegram sessions, api keys or spend; replies go to a MockTelegramBot
+pub struct MockAnalysisFlow {
+    pub bot: MockTelegramBot,
+    pub user_manager: UserManager,
+    pub user_sessions: UserSessions,
+    pub job_queue: JobQueue,
+    pub limits: Limits,
+    pub channel_claims: ChannelClaimManager,
+    workers: AnalysisWorkers,
+    channel_stats: ChannelStatsManager,
+    channel_locks: ChannelLocks,
+    llm_budget: LlmBudget,
+}
+
+impl MockAnalysisFlow {
+    pub async fn new(pool: Arc<Pool>) -> Self {
+        // read once per process; no other test talks to telegram or the llm
+        std::env::set_var("LLM_MOCK", "1");
+        let limits = Limits::load(&pool).await.expect("Failed to load limits");
+        let admin = Arc::new(AdminManager::new(pool.clone(), Vec::new()));
+        Self {
+            bot: MockTelegramBot::new(),
+            user_manager: UserManager::new(pool.clone()),
+            user_sessions: UserSessions::new(pool.clone()),
+            job_queue: JobQueue::new(pool.clone()),
+            channel_claims: ChannelClaimManager::new(pool.clone()),
+            workers: AnalysisWorkers::start(pool.clone()).expect("Failed to start workers"),
+            channel_stats: ChannelStatsManager::new(pool.clone()),
+            channel_locks: Arc::new(Mutex::new(HashMap::new())),
+            llm_budget: LlmBudget::new(pool, admin, limits.daily_llm_budget_cents),
+            limits,
+        }
+    }
+
+    /// the user sends /start
+    pub async fn start(&self, telegram_user_id: i64, username: &str) -> User {
+        let (user, _) = self
+            .bot
+            .simulate_user_start(
+                &self.user_manager,
+                telegram_user_id,
+                Some(username),
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("Failed to start");
+        user
+    }
+
+    /// the user sends a channel name; it's remembered until the type is picked. returns
+    /// the id of the type selection message
+    pub async fn send_channel(&self, telegram_user_id: i64, channel_name: &str) -> i32 {
+        self.user_sessions
+            .insert(
+                telegram_user_id,
+                UserSession {
+                    channel_name: Some(channel_name.to_string()),
+                    ..Default::default()
+                },
+            )
+            .await;
+        let lang = Lang::En;
+        self.bot.send_message_with_keyboard(
+            telegram_user_id,
+            lang.analysis_select_type(&ResultPresenter::target_label(channel_name, lang)),
+            Some("Html".to_string()),
+            Some(&CallbackHandler::create_analysis_selection_keyboard(
+                channel_name,
+                AnalysisDepth::default(),
+                &self.limits,
+                lang,
+            )),
+        )
+    }
+
+    /// the user presses a button of a message in their chat and the bot handles the
+    /// query like CallbackHandler: depth and focus buttons update the session, type
+    /// buttons start the analysis. returns the queued analysis, if any
+    pub async fn press(
+        &self,
+        telegram_user_id: i64,
+        message_id: i32,
+        button_text: &str,
+    ) -> Result<Option<i32>, String> {
+        let lang = Lang::En;
+        let chat_id = telegram_user_id;
+        let (query_id, data) =
+            self.bot
+                .press_button(telegram_user_id, chat_id, message_id, button_text)?;
+        let analysis_id = match data {
+            CallbackData::Depth {
+                depth,
+                channel_name,
+            } => {
+                self.user_sessions
+                    .update(telegram_user_id, |session| {
+                        session.choose_depth(&channel_name, depth)
+                    })
+                    .await;
+                self.bot.edit_message_reply_markup(
+                    chat_id,
+                    message_id,
+                    &CallbackHandler::create_analysis_selection_keyboard(
+                        &channel_name,
+                        depth,
+                        &self.limits,
+                        lang,
+                    ),
+                );
+                None
+            }
+            CallbackData::Focus { channel_name } => {
+                self.user_sessions
+                    .update(telegram_user_id, |session| {
+                        session.request_focus(&channel_name)
+                    })
+                    .await;
+                self.bot.send_message(
+                    chat_id,
+                    lang.focus_request(MAX_FOCUS_LENGTH),
+                    Some("Html".to_string()),
+                );
+                None
+            }
+            CallbackData::Analysis {
+                analysis_type,
+                depth,
+                channel_name,
+            } => {
+                self.start_analysis(telegram_user_id, &channel_name, &analysis_type, depth)
+                    .await
+            }
+            other => return Err(format!("unsupported callback {:?}", other)),
+        };
+        self.bot.answer_callback_query(&query_id, None);
+        Ok(analysis_id)
+    }
+
+    /// the user answers a focus request, like TelegramBot::handle_focus_input; returns
+    /// the id of the message offering the types again, None if the focus was rejected
+    pub async fn send_focus(&self, telegram_user_id: i64, text: &str) -> Option<i32> {
+        let lang = Lang::En;
+        let session = self.user_sessions.get(telegram_user_id).await?;
+        let channel_name = session.channel_name.filter(|_| session.awaiting_focus)?;
+        if text.chars().count() > MAX_FOCUS_LENGTH {
+            self.bot.send_message(
+                telegram_user_id,
+                lang.error_focus_too_long(MAX_FOCUS_LENGTH),
+                None,
+            );
+            return None;
+        }
+
+        let depth = self
+            .user_sessions
+            .update_existing(telegram_user_id, |session| session.set_focus(text))
+            .await
+            .unwrap_or_default();
+        Some(self.bot.send_message_with_keyboard(
+            telegram_user_id,
+            lang.focus_saved(
+                &ResultPresenter::target_label(&channel_name, lang),
+                &MessageFormatter::escape_html(text),
+            ),
+            Some("Html".to_string()),
+            Some(&CallbackHandler::create_analysis_selection_keyboard(
+                &channel_name,
+                depth,
+                &self.limits,
+                lang,
+            )),
+        ))
+    }
+
+    /// the user picks the analysis type of the channel they sent; the queued analysis,
+    /// None without enough credits
+    pub async fn select_type(
+        &self,
+        telegram_user_id: i64,
+        analysis_type: &str,
+        depth: AnalysisDepth,
+    ) -> Option<i32> {
+        let session = self.user_sessions.get(telegram_user_id).await?;
+        let channel_name = session.channel_name?;
+        self.start_analysis(telegram_user_id, &channel_name, analysis_type, depth)
+            .await
+    }
+
+    /// like CallbackHandler::start_analysis
+    async fn start_analysis(
+        &self,
+        telegram_user_id: i64,
+        channel_name: &str,
+        analysis_type: &str,
+        depth: AnalysisDepth,
+    ) -> Option<i32> {
+        let lang = Lang::En;
+        let (user, _) = self
+            .user_manager
+            .get_or_create_user(telegram_user_id, None, None, None, None, None)
+            .await
+            .expect("Failed to load user");
+        let credits_required = self.limits.depth_credits(depth);
+        let free = self
+            .owner_analysis_is_free(telegram_user_id, channel_name)
+            .await;
+        if !free && user.analysis_credits < credits_required {
+            self.bot
+                .send_message(telegram_user_id, lang.no_credits_short().to_string(), None);
+            return None;
+        }
+
+        // the focus only applies to the channel it was set for
+        let session = self
+            .user_sessions
+            .remove(telegram_user_id)
+            .await
+            .filter(|session| session.channel_name.as_deref() == Some(channel_name))
+            .unwrap_or_default();
+        let analysis_id = self
+            .user_manager
+            .create_pending_analysis(
+                user.id,
+                channel_name,
+                analysis_type,
+                depth.as_str(),
+                Some(lang.code()),
+                session.focus.as_deref(),
+                AnalysisSource::Bot,
+                None,
+            )
+            .await
+            .expect("Failed to create analysis");
+        self.job_queue
+            .enqueue(
+                analysis_id,
+                Some(telegram_user_id),
+                Some(lang.code()),
+                false,
+            )
+            .await
+            .expect("Failed to queue analysis");
+        self.bot.send_message(
+            telegram_user_id,
+            lang.analysis_in_progress(analysis_type),
+            None,
+        );
+        Some(analysis_id)
+    }
+
+    /// the user asks for a second opinion on one of their analyses, like
+    /// CallbackHandler::handle_second_opinion_callback; the queued second opinion, None
+    /// when it isn't available or the user can't afford it
+    pub async fn request_second_opinion(
+        &self,
+        telegram_user_id: i64,
+        analysis_id: i32,
+    ) -> Option<i32> {
+        let lang = Lang::En;
+        let (user, _) = self
+            .user_manager
+            .get_or_create_user(telegram_user_id, None, None, None, None, None)
+            .await
+            .expect("Failed to load user");
+        let analysis = self
+            .user_manager
+            .get_analysis(analysis_id, user.id)
+            .await
+            .expect("Failed to load analysis")?;
+        let depth = AnalysisDepth::from_code(&analysis.depth).unwrap_or_default();
+        if user.analysis_credits < self.limits.second_opinion_credits(depth) {
+            self.bot
+                .send_message(telegram_user_id, lang.no_credits_short().to_string(), None);
+            return None;
+        }
+        let second_opinion_id = self
+            .user_manager
+            .create_second_opinion(analysis_id, user.id, Some(lang.code()), i32::MAX)
+            .await
+            .expect("Failed to create second opinion")?;
+        self.job_queue
+            .enqueue(
+                second_opinion_id,
+                Some(telegram_user_id),
+                Some(lang.code()),
+                true,
+            )
+            .await
+            .expect("Failed to queue second opinion");
+        Some(second_opinion_id)
+    }
+
+    /// like channel_claims::is_free_owner_analysis
+    async fn owner_analysis_is_free(&self, telegram_user_id: i64, channel_name: &str) -> bool {
+        self.channel_claims
+            .owner_analysis_is_free(
+                telegram_user_id,
+                channel_name,
+                self.limits.owner_free_analyses,
+            )
+            .await
+            .expect("Failed to check channel claims")
+    }
+
+    /// an operator's analysis from the command line, sharing the bot's caches
+    pub async fn analyze_unrecorded(
+        &self,
+        target: &AnalysisTarget,
+    ) -> Result<AnalysisResult, AnalysisRunError> {
+        run_unrecorded_analysis(&self.workers, &self.channel_locks, &self.llm_budget, target).await
+    }
+
+    /// leases the next bot job and runs it like TelegramBot::run_queued_analysis, sending
+    /// the rendered result or the failure to the mock bot; None when nothing is queued
+    pub async fn run_next_job(&self) -> Option<FlowRun> {
+        let leased = self
+            .job_queue
+            .lease(AnalysisSource::Bot, "test-runner", JOB_LEASE)
+            .await
+            .expect("Failed to lease a job")?;
+        let analysis = leased.analysis;
+        let chat_id = leased.chat_id.unwrap_or(analysis.telegram_user_id);
+        let lang = Lang::from_code(leased.language.as_deref());
+        let depth = AnalysisDepth::from_code(&analysis.depth).unwrap_or_default();
+        let second_opinion_on = match analysis.second_opinion_of {
+            Some(original_id) => self
+                .user_manager
+                .get_analysis(original_id, analysis.user_id)
+                .await
+                .expect("Failed to load the analysis of the second opinion")
+                .and_then(|original| original.model),
+            None => None,
+        };
+        let job = AnalysisJob {
+            analysis_id: analysis.id,
+            user_id: analysis.user_id,
+            channel_name: analysis.channel_name.clone(),
+            analysis_type: analysis.analysis_type.clone(),
+            depth,
+            credits: match analysis.second_opinion_of {
+                Some(_) => self.limits.second_opinion_credits(depth),
+                None if self
+                    .owner_analysis_is_free(analysis.telegram_user_id, &analysis.channel_name)
+                    .await =>
+                {
+                    0
+                }
+                None => self.limits.depth_credits(depth),
+            },
+            focus: analysis.focus,
+            topic: analysis.topic,
+            allow_low_text: leased.allow_low_text,
+            second_opinion_on,
+        };
+
+        let outcome = run_analysis(
+            &self.workers,
+            &self.user_manager,
+            &self.channel_stats,
+            &self.channel_locks,
+            &self.llm_budget,
+            &job,
+            None,
+        )
+        .await;
+        match &outcome {
+            Ok(outcome) => {
+                let messages = ResultPresenter::render(
+                    &outcome.result,
+                    &job.analysis_type,
+                    &job.channel_name,
+                    job.user_id,
+                    lang,
+                )
+                .expect("Failed to render the result");
+                self.user_manager
+                    .store_rendered_result(job.analysis_id, &messages)
+                    .await
+                    .expect("Failed to store the rendered result");
+                for message in messages {
+                    self.bot
+                        .send_message(chat_id, message, Some("Html".to_string()));
+                }
+                self.job_queue
+                    .complete(leased.id)
+                    .await
+                    .expect("Failed to complete the job");
+            }
+            Err(e) => {
+                let text = match e {
+                    AnalysisRunError::Prepare(e) => lang.error_analysis_failed(
+                        &e.failure(),
+                        &ResultPresenter::target_label(&job.channel_name, lang),
+                    ),
+                    _ => lang.error_system().to_string(),
+                };
+                self.bot
+                    .send_message(chat_id, text, Some("Html".to_string()));
+                self.job_queue
+                    .fail(leased.id, &e.to_string())
+                    .await
+                    .expect("Failed to fail the job");
+            }
+        }
+
+        Some(FlowRun {
+            analysis_id: job.analysis_id,
+            outcome,
+        })
+    }
+}
@@ -1,13 +1,73 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tg_main::user_manager::{ReferralRewardInfo, UserManager};
+use teloxide::types::{InlineKeyboardButtonKind, InlineKeyboardMarkup};
+use tg_main::free_trials::TrialOutcome;
+use tg_main::handlers::CallbackData;
+use tg_main::user_manager::{CreditTransactionKind, ReferralRewardInfo, UserManager};
+
+/// a button of an inline keyboard, captured for inspection in tests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockButton {
+    pub text: String,
+    // None for url, payment and other non-callback buttons
+    pub callback_data: Option<String>,
+}
+
+/// rows of buttons of an inline keyboard
+pub type MockKeyboard = Vec<Vec<MockButton>>;
+
+impl MockButton {
+    /// captures the rows of a keyboard built by the bot
+    pub fn keyboard(markup: &InlineKeyboardMarkup) -> MockKeyboard {
+        markup
+            .inline_keyboard
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|button| MockButton {
+                        text: button.text.clone(),
+                        callback_data: match &button.kind {
+                            InlineKeyboardButtonKind::CallbackData(data) => Some(data.clone()),
+                            _ => None,
+                        },
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
 
 /// represents a sent message for verification in tests
 #[derive(Debug, Clone)]
 pub struct SentMessage {
     pub chat_id: i64,
+    // unique per bot, like telegram's per chat ids
+    pub message_id: i32,
     pub text: String,
     pub parse_mode: Option<String>,
+    // the current keyboard, edits included
+    pub keyboard: Option<MockKeyboard>,
+}
+
+/// an edit of a sent message's text or keyboard
+#[derive(Debug, Clone)]
+pub struct EditedMessage {
+    pub chat_id: i64,
+    pub message_id: i32,
+    pub text: Option<String>,
+    pub keyboard: Option<MockKeyboard>,
+}
+
+/// a callback query sent by pressing an inline button
+#[derive(Debug, Clone)]
+pub struct MockCallbackQuery {
+    pub id: String,
+    pub telegram_user_id: i64,
+    pub chat_id: i64,
+    pub message_id: i32,
+    pub data: String,
+    // set once the bot answered the query, with the notification text if any
+    pub answered: Option<Option<String>>,
 }
 
 /// mock telegram bot that simulates bot behavior without real API calls
@@ -17,6 +77,16 @@ pub struct MockTelegramBot {
     pub sent_messages: Arc<Mutex<Vec<SentMessage>>>,
     /// tracks user interactions
     pub user_interactions: Arc<Mutex<HashMap<i64, Vec<String>>>>,
+    /// edits of sent messages, in order
+    pub edited_messages: Arc<Mutex<Vec<EditedMessage>>>,
+    /// pressed inline buttons, in order
+    pub callback_queries: Arc<Mutex<Vec<MockCallbackQuery>>>,
+}
+
+impl Default for MockTelegramBot {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MockTelegramBot {
@@ -24,26 +94,173 @@ impl MockTelegramBot {
         Self {
             sent_messages: Arc::new(Mutex::new(Vec::new())),
             user_interactions: Arc::new(Mutex::new(HashMap::new())),
+            edited_messages: Arc::new(Mutex::new(Vec::new())),
+            callback_queries: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
     /// simulates sending a message (stores it for verification)
     pub fn send_message(&self, chat_id: i64, text: String, parse_mode: Option<String>) {
-        let message = SentMessage {
+        self.send_message_with_keyboard(chat_id, text, parse_mode, None);
+    }
+
+    /// simulates sending a message with an optional inline keyboard; returns its id
+    pub fn send_message_with_keyboard(
+        &self,
+        chat_id: i64,
+        text: String,
+        parse_mode: Option<String>,
+        keyboard: Option<&InlineKeyboardMarkup>,
+    ) -> i32 {
+        let mut sent_messages = self.sent_messages.lock().unwrap();
+        let message_id = sent_messages.len() as i32 + 1;
+        sent_messages.push(SentMessage {
             chat_id,
+            message_id,
             text: text.clone(),
             parse_mode,
-        };
-
-        self.sent_messages.lock().unwrap().push(message);
+            keyboard: keyboard.map(MockButton::keyboard),
+        });
+        drop(sent_messages);
 
         // track user interaction
         self.user_interactions
             .lock()
             .unwrap()
             .entry(chat_id)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(text);
+        message_id
+    }
+
+    /// simulates editing the text of a sent message, keeping its keyboard
+    pub fn edit_message_text(&self, chat_id: i64, message_id: i32, text: String) {
+        self.edit_message(chat_id, message_id, Some(text), None);
+    }
+
+    /// simulates replacing the keyboard of a sent message
+    pub fn edit_message_reply_markup(
+        &self,
+        chat_id: i64,
+        message_id: i32,
+        keyboard: &InlineKeyboardMarkup,
+    ) {
+        self.edit_message(
+            chat_id,
+            message_id,
+            None,
+            Some(MockButton::keyboard(keyboard)),
+        );
+    }
+
+    fn edit_message(
+        &self,
+        chat_id: i64,
+        message_id: i32,
+        text: Option<String>,
+        keyboard: Option<MockKeyboard>,
+    ) {
+        let mut sent_messages = self.sent_messages.lock().unwrap();
+        let message = sent_messages
+            .iter_mut()
+            .find(|msg| msg.chat_id == chat_id && msg.message_id == message_id)
+            .expect("only sent messages can be edited");
+        if let Some(text) = &text {
+            message.text = text.clone();
+        }
+        if let Some(keyboard) = &keyboard {
+            message.keyboard = Some(keyboard.clone());
+        }
+        drop(sent_messages);
+
+        self.edited_messages.lock().unwrap().push(EditedMessage {
+            chat_id,
+            message_id,
+            text,
+            keyboard,
+        });
+    }
+
+    /// gets a sent message by id, with its current text and keyboard
+    pub fn get_message(&self, chat_id: i64, message_id: i32) -> Option<SentMessage> {
+        self.get_messages_for_chat(chat_id)
+            .into_iter()
+            .find(|msg| msg.message_id == message_id)
+    }
+
+    /// gets the last message sent to a chat
+    pub fn last_message_for_chat(&self, chat_id: i64) -> Option<SentMessage> {
+        self.get_messages_for_chat(chat_id).pop()
+    }
+
+    /// gets the edits of a sent message, oldest first
+    pub fn get_edits(&self, chat_id: i64, message_id: i32) -> Vec<EditedMessage> {
+        self.edited_messages
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|edit| edit.chat_id == chat_id && edit.message_id == message_id)
+            .cloned()
+            .collect()
+    }
+
+    /// simulates the user pressing the button labeled `button_text` on a sent message;
+    /// fails like telegram would if the message has no such callback button. returns the
+    /// query id and the parsed payload
+    pub fn press_button(
+        &self,
+        telegram_user_id: i64,
+        chat_id: i64,
+        message_id: i32,
+        button_text: &str,
+    ) -> Result<(String, CallbackData), String> {
+        let message = self
+            .get_message(chat_id, message_id)
+            .ok_or_else(|| format!("no message {} in chat {}", message_id, chat_id))?;
+        let data = message
+            .keyboard
+            .iter()
+            .flatten()
+            .flatten()
+            .find(|button| button.text == button_text)
+            .and_then(|button| button.callback_data.clone())
+            .ok_or_else(|| format!("no callback button \"{}\" on the message", button_text))?;
+        let callback_data =
+            CallbackData::parse(&data).ok_or_else(|| format!("unknown callback data {}", data))?;
+
+        let mut queries = self.callback_queries.lock().unwrap();
+        let id = format!("query-{}", queries.len() + 1);
+        queries.push(MockCallbackQuery {
+            id: id.clone(),
+            telegram_user_id,
+            chat_id,
+            message_id,
+            data,
+            answered: None,
+        });
+        Ok((id, callback_data))
+    }
+
+    /// simulates answering a callback query, optionally with a notification
+    pub fn answer_callback_query(&self, query_id: &str, text: Option<&str>) {
+        let mut queries = self.callback_queries.lock().unwrap();
+        let query = queries
+            .iter_mut()
+            .find(|query| query.id == query_id)
+            .expect("only pressed buttons can be answered");
+        assert!(query.answered.is_none(), "callback query answered twice");
+        query.answered = Some(text.map(str::to_string));
+    }
+
+    /// gets the callback queries the bot hasn't answered, which leave a spinner on the button
+    pub fn unanswered_callback_queries(&self) -> Vec<MockCallbackQuery> {
+        self.callback_queries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|query| query.answered.is_none())
+            .cloned()
+            .collect()
     }
 
     /// gets all sent messages for verification
@@ -66,6 +283,8 @@ impl MockTelegramBot {
     pub fn clear_messages(&self) {
         self.sent_messages.lock().unwrap().clear();
         self.user_interactions.lock().unwrap().clear();
+        self.edited_messages.lock().unwrap().clear();
+        self.callback_queries.lock().unwrap().clear();
     }
 
     /// gets count of messages sent to a specific chat
@@ -103,16 +322,26 @@ impl MockTelegramBot {
             None
         };
 
-        let (user, reward_info) = user_manager
+        let (mut user, reward_info) = user_manager
             .get_or_create_user(
                 telegram_user_id,
                 username,
                 first_name,
                 last_name,
                 validated_referrer,
+                None,
             )
             .await?;
 
+        // simulate pressing "I'm human"; every simulated user is a different person, so
+        // the look-alike limit doesn't apply here
+        if let Some(TrialOutcome::Granted { credits }) = user_manager
+            .claim_free_trial(telegram_user_id, i32::MAX)
+            .await?
+        {
+            user.analysis_credits = credits;
+        }
+
         // simulate sending welcome message
         let welcome_msg = if user.analysis_credits > 0 {
             format!("Welcome! You have {} credits", user.analysis_credits)
@@ -162,8 +391,15 @@ impl MockTelegramBot {
         telegram_user_id: i64,
         credits: i32,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // resolve internal user id (like the real payment handler does)
+        let (user, _) = user_manager
+            .get_or_create_user(telegram_user_id, None, None, None, None, None)
+            .await?;
+
         // add credits to user
-        let new_balance = user_manager.add_credits(telegram_user_id, credits).await?;
+        let new_balance = user_manager
+            .add_credits(user.id, credits, CreditTransactionKind::Purchase, None)
+            .await?;
 
         // simulate payment success message
         let success_msg = format!(
@@ -173,7 +409,7 @@ impl MockTelegramBot {
         self.send_message(telegram_user_id, success_msg, Some("Html".to_string()));
 
         // process referral rewards for paid user
-        if let Some(reward_info) = user_manager.record_paid_referral(telegram_user_id).await? {
+        if let Some(reward_info) = user_manager.record_paid_referral(user.id).await? {
             if let Some(referrer_telegram_id) = reward_info.referrer_telegram_id {
                 let reward_msg = if reward_info.paid_rewards > 0
                     && reward_info.milestone_rewards > 0
@@ -209,6 +445,7 @@ impl MockTelegramBot {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use teloxide::types::InlineKeyboardButton;
 
     #[tokio::test]
     async fn test_mock_bot_basic_functionality() {
@@ -240,4 +477,44 @@ mod tests {
         bot.clear_messages();
         assert_eq!(bot.get_sent_messages().len(), 0);
     }
+
+    #[test]
+    fn test_mock_bot_keyboards_edits_and_callbacks() {
+        let bot = MockTelegramBot::new();
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback("Buy", CallbackData::BuyPackage(1).encode()),
+            InlineKeyboardButton::url("Docs", "https://example.com".parse().unwrap()),
+        ]]);
+
+        let message_id =
+            bot.send_message_with_keyboard(123, "Pick".to_string(), None, Some(&keyboard));
+        let captured = bot.get_message(123, message_id).unwrap().keyboard.unwrap();
+        assert_eq!(captured[0][0].callback_data, Some("buy_1".to_string()));
+        assert_eq!(captured[0][1].callback_data, None);
+
+        // only callback buttons can be pressed
+        assert!(bot.press_button(123, 123, message_id, "Docs").is_err());
+        let (query_id, data) = bot.press_button(123, 123, message_id, "Buy").unwrap();
+        assert_eq!(data, CallbackData::BuyPackage(1));
+        let query = bot.unanswered_callback_queries().pop().unwrap();
+        assert_eq!(
+            (query.telegram_user_id, query.chat_id, query.message_id),
+            (123, 123, message_id)
+        );
+        assert_eq!(query.data, "buy_1");
+        bot.answer_callback_query(&query_id, Some("Done"));
+        assert!(bot.unanswered_callback_queries().is_empty());
+
+        // edits change the stored message and are recorded separately
+        bot.edit_message_text(123, message_id, "Paid".to_string());
+        bot.edit_message_reply_markup(123, message_id, &InlineKeyboardMarkup::default());
+        let message = bot.last_message_for_chat(123).unwrap();
+        assert_eq!(message.text, "Paid");
+        assert_eq!(message.keyboard, Some(Vec::new()));
+        let edits = bot.get_edits(123, message_id);
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].text.as_deref(), Some("Paid"));
+        assert!(edits[0].keyboard.is_none());
+        assert_eq!(edits[1].keyboard, Some(Vec::new()));
+    }
 }
@@ -2,9 +2,55 @@ use deadpool_postgres::{Config, Pool, Runtime};
 use std::env;
 use tokio_postgres_rustls::MakeRustlsConnect;
 
+pub mod admin_tests;
+pub mod api_tests;
+pub mod backup_tests;
+pub mod balance_tests;
+pub mod blocklist_tests;
+pub mod cache_tests;
+pub mod callback_tests;
+pub mod changelog_tests;
+pub mod channel_claims_tests;
+pub mod config_tests;
+pub mod cross_group_tests;
+pub mod data_export_tests;
+pub mod channel_stats_tests;
+pub mod db_health_tests;
+pub mod feedback_tests;
+pub mod flagged_outputs_tests;
+pub mod flow_tests;
+pub mod free_trial_tests;
+pub mod health_tests;
+pub mod job_queue_tests;
+pub mod limits_tests;
+pub mod llm_budget_tests;
+pub mod low_text_tests;
+pub mod message_queue_tests;
+pub mod metrics_tests;
+pub mod mock_analysis;
 pub mod mock_bot;
+pub mod packages_tests;
+pub mod partial_tests;
+pub mod payment_tests;
+pub mod privacy_tests;
+pub mod receipts_tests;
+pub mod referral_config_tests;
+pub mod referral_flag_tests;
+pub mod referral_leaderboard_tests;
 pub mod referral_tests;
+pub mod resend_tests;
+pub mod running_limit_tests;
+pub mod second_opinion_tests;
+pub mod settings_tests;
+pub mod share_tests;
+pub mod showcase_tests;
+pub mod subscription_tests;
 pub mod test_utils;
+pub mod topic_tests;
+pub mod user_sessions_tests;
+pub mod voice_tests;
+pub mod warmup_tests;
+pub mod watchdog_tests;
 
 /// test database configuration and setup
 pub struct TestDatabase {
@@ -21,7 +67,7 @@ impl TestDatabase {
         let tls = MakeRustlsConnect::new(
             rustls::ClientConfig::builder()
                 .with_root_certificates(rustls::RootCertStore {
-                    roots: webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect(),
+                    roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
                 })
                 .with_no_client_auth(),
         );
@@ -96,7 +142,7 @@ impl TestDatabase {
         let tls = MakeRustlsConnect::new(
             rustls::ClientConfig::builder()
                 .with_root_certificates(rustls::RootCertStore {
-                    roots: webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect(),
+                    roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
                 })
                 .with_no_client_auth(),
         );
@@ -172,7 +218,7 @@ mod tests {
             .await
             .expect("Failed to check schema");
 
-        assert!(tables.len() > 0, "No tables found in test database");
+        assert!(!tables.is_empty(), "No tables found in test database");
 
         // check for specific tables we need
         let table_names: Vec<String> = tables.iter().map(|row| row.get(0)).collect();
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use tg_main::packages::{PackageChange, PackageManager};
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_migration_seeds_the_old_packages() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let packages = PackageManager::new(Arc::new(db.pool.clone()));
+
+    let active = packages.active().await;
+    let offered = active
+        .iter()
+        .map(|package| (package.credits, package.stars))
+        .collect::<Vec<_>>();
+    assert_eq!(offered, vec![(1, 100), (10, 500)]);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_packages_can_be_added_repriced_and_retired() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let packages = PackageManager::new(Arc::new(db.pool.clone()));
+
+    let added = packages
+        .add(5, 300)
+        .await
+        .unwrap()
+        .expect("Package should be added");
+    assert_eq!((added.credits, added.stars, added.active), (5, 300, true));
+    // credits name the package in payments, so they can't repeat
+    assert!(packages.add(5, 250).await.unwrap().is_none());
+
+    let credits = packages
+        .active()
+        .await
+        .iter()
+        .map(|package| package.credits)
+        .collect::<Vec<_>>();
+    assert_eq!(credits, vec![1, 5, 10]);
+
+    assert!(packages
+        .update(added.id, &PackageChange::Stars(280))
+        .await
+        .unwrap());
+    assert!(packages
+        .update(
+            added.id,
+            &PackageChange::Title(Some("Five pack".to_string()))
+        )
+        .await
+        .unwrap());
+    let updated = packages
+        .get_active(added.id)
+        .await
+        .unwrap()
+        .expect("Package should be on sale");
+    assert_eq!(updated.stars, 280);
+    assert_eq!(updated.title.as_deref(), Some("Five pack"));
+
+    assert!(packages
+        .update(added.id, &PackageChange::Active(false))
+        .await
+        .unwrap());
+    assert!(packages.get_active(added.id).await.unwrap().is_none());
+    assert_eq!(packages.active().await.len(), 2);
+    assert_eq!(packages.all().await.unwrap().len(), 3);
+
+    assert!(!packages
+        .update(9999, &PackageChange::Stars(100))
+        .await
+        .unwrap());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use tg_main::user_manager::{AnalysisSource, CreditTransactionKind, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_partial_analysis_can_be_regenerated_once_for_free() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (owner, _) = bot
+        .simulate_user_start(&user_manager, 800, Some("owner"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let (other, _) = bot
+        .simulate_user_start(&user_manager, 801, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(owner.id, 4, CreditTransactionKind::Purchase, Some("charge"))
+        .await
+        .expect("Failed to add credits");
+
+    let analysis_id = user_manager
+        .create_pending_analysis(
+            owner.id,
+            "@rustlang",
+            "personal",
+            "deep",
+            Some("en"),
+            Some("hiring"),
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    let balance = user_manager
+        .atomic_complete_analysis(analysis_id, owner.id, 3)
+        .await
+        .expect("Failed to complete analysis");
+    assert_eq!(balance, 2);
+
+    // complete analyses can't be claimed
+    assert!(user_manager
+        .claim_partial_regeneration(analysis_id, owner.id)
+        .await
+        .expect("Failed to claim regeneration")
+        .is_none());
+
+    user_manager
+        .mark_analysis_partial(analysis_id)
+        .await
+        .expect("Failed to mark analysis partial");
+
+    // only by the owner
+    assert!(user_manager
+        .claim_partial_regeneration(analysis_id, other.id)
+        .await
+        .expect("Failed to claim regeneration")
+        .is_none());
+
+    let regeneration = user_manager
+        .claim_partial_regeneration(analysis_id, owner.id)
+        .await
+        .expect("Failed to claim regeneration")
+        .expect("Partial analysis should be claimable");
+    assert_eq!(regeneration.channel_name, "@rustlang");
+    assert_eq!(regeneration.analysis_type, "personal");
+    assert_eq!(regeneration.depth, "deep");
+    assert_eq!(regeneration.focus.as_deref(), Some("hiring"));
+    assert_eq!(regeneration.credits_refunded, 3);
+    assert_eq!(regeneration.balance, 5);
+
+    let transactions = user_manager
+        .get_credit_transactions(owner.id, 1, 0)
+        .await
+        .expect("Failed to get transactions");
+    assert_eq!(transactions[0].amount, 3);
+    assert_eq!(transactions[0].kind, CreditTransactionKind::Refund);
+
+    // and only once
+    assert!(user_manager
+        .claim_partial_regeneration(analysis_id, owner.id)
+        .await
+        .expect("Failed to claim regeneration")
+        .is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
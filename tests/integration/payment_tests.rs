@@ -0,0 +1,275 @@
+use std::sync::Arc;
+use tg_main::user_manager::{AnalysisSource, CreditTransactionKind, UserManager, UserManagerError};
+
+use super::{mock_bot::MockTelegramBot, test_utils::TestAssertions, TestDatabase};
+
+#[tokio::test]
+async fn test_refund_removes_only_unspent_credits() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 600, Some("payer"), Some("Payer"), None, None)
+        .await
+        .expect("Failed to create user");
+
+    // buy 10 credits on top of the initial free one
+    user_manager
+        .add_credits(
+            user.id,
+            10,
+            CreditTransactionKind::Purchase,
+            Some("charge_1"),
+        )
+        .await
+        .expect("Failed to add credits");
+    user_manager
+        .record_payment(user.id, "charge_1", "credits_10", 500, 10)
+        .await
+        .expect("Failed to record payment");
+
+    let payment = user_manager
+        .get_payment("charge_1")
+        .await
+        .expect("Failed to get payment")
+        .expect("Payment should exist");
+    assert_eq!(payment.stars, 500);
+    assert_eq!(payment.credits, 10);
+    assert!(!payment.refunded);
+
+    // refunding takes back the purchased credits but keeps the free one
+    let new_balance = user_manager
+        .record_refund(&payment, Some("analysis failed"), Some(1))
+        .await
+        .expect("Failed to record refund");
+    assert_eq!(new_balance, 1);
+    TestAssertions::assert_user_credit_count(&db, user.id, 1)
+        .await
+        .expect("Credit count assertion failed");
+
+    let payment = user_manager
+        .get_payment("charge_1")
+        .await
+        .expect("Failed to get payment")
+        .expect("Payment should exist");
+    assert!(payment.refunded);
+
+    // a payment can only be refunded once
+    assert!(user_manager
+        .record_refund(&payment, None, None)
+        .await
+        .is_err());
+
+    assert!(user_manager
+        .get_payment("unknown_charge")
+        .await
+        .expect("Failed to get payment")
+        .is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_package_payment_is_credited_once_with_its_receipt() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 605, Some("payer"), Some("Payer"), None, None)
+        .await
+        .expect("Failed to create user");
+
+    let new_balance = user_manager
+        .record_package_payment(user.id, "charge_3", "credits_10", 500, 10)
+        .await
+        .expect("Failed to record payment");
+    assert_eq!(new_balance, Some(11));
+
+    // telegram redelivers the update, the payment isn't credited again
+    assert!(user_manager
+        .record_package_payment(user.id, "charge_3", "credits_10", 500, 10)
+        .await
+        .expect("Failed to record payment")
+        .is_none());
+    TestAssertions::assert_user_credit_count(&db, user.id, 11)
+        .await
+        .expect("Credit count assertion failed");
+    let transactions = user_manager
+        .get_credit_transactions(user.id, 10, 0)
+        .await
+        .expect("Failed to get transactions");
+    let purchases: Vec<_> = transactions
+        .iter()
+        .filter(|transaction| transaction.kind == CreditTransactionKind::Purchase)
+        .collect();
+    assert_eq!(purchases.len(), 1);
+    assert_eq!(purchases[0].amount, 10);
+    assert_eq!(purchases[0].balance_after, 11);
+
+    // and its charge can be refunded
+    let payment = user_manager
+        .get_payment("charge_3")
+        .await
+        .expect("Failed to get payment")
+        .expect("Payment should exist");
+    assert_eq!(payment.stars, 500);
+    assert_eq!(payment.credits, 10);
+
+    // nothing is kept of a payment whose credits couldn't be added
+    assert!(user_manager
+        .record_package_payment(-1, "charge_4", "credits_10", 500, 10)
+        .await
+        .is_err());
+    assert!(user_manager
+        .get_payment("charge_4")
+        .await
+        .expect("Failed to get payment")
+        .is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_empty_analysis_gets_its_credits_back_once_and_not_in_stars_too() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 610, Some("payer"), Some("Payer"), None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(
+            user.id,
+            10,
+            CreditTransactionKind::Purchase,
+            Some("charge_2"),
+        )
+        .await
+        .expect("Failed to add credits");
+    user_manager
+        .record_payment(user.id, "charge_2", "credits_10", 500, 10)
+        .await
+        .expect("Failed to record payment");
+
+    let create = || {
+        user_manager.create_pending_analysis(
+            user.id,
+            "@rustlang",
+            "professional",
+            "deep",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+    };
+
+    // a pending analysis hasn't been charged, there is nothing to give back
+    let analysis_id = create().await.expect("Failed to create analysis");
+    assert!(user_manager
+        .refund_analysis_credits(analysis_id)
+        .await
+        .expect("Failed to refund analysis credits")
+        .is_none());
+
+    let balance = user_manager
+        .atomic_complete_analysis(analysis_id, user.id, 4)
+        .await
+        .expect("Failed to complete analysis");
+    assert_eq!(balance, 7);
+
+    let refunded = user_manager
+        .refund_analysis_credits(analysis_id)
+        .await
+        .expect("Failed to refund analysis credits");
+    assert_eq!(refunded, Some((4, 11)));
+    let record = user_manager
+        .get_analysis(analysis_id, user.id)
+        .await
+        .expect("Failed to get analysis")
+        .expect("Analysis should exist");
+    assert_eq!(record.status, "failed");
+    let transactions = user_manager
+        .get_credit_transactions(user.id, 1, 0)
+        .await
+        .expect("Failed to get transactions");
+    assert_eq!(transactions[0].amount, 4);
+    assert_eq!(transactions[0].kind, CreditTransactionKind::Refund);
+
+    // only once
+    assert!(user_manager
+        .refund_analysis_credits(analysis_id)
+        .await
+        .expect("Failed to refund analysis credits")
+        .is_none());
+
+    // nor charged again by reopening it, which would leave a second empty result unrefunded
+    assert!(user_manager
+        .reopen_failed_analysis(analysis_id, user.id, i32::MAX)
+        .await
+        .expect("Failed to reopen analysis")
+        .is_none());
+    assert!(matches!(
+        user_manager
+            .atomic_complete_analysis(analysis_id, user.id, 4)
+            .await,
+        Err(UserManagerError::NotPending(id)) if id == analysis_id
+    ));
+    assert!(user_manager
+        .refund_analysis_credits(analysis_id)
+        .await
+        .expect("Failed to refund analysis credits")
+        .is_none());
+    TestAssertions::assert_user_credit_count(&db, user.id, 11)
+        .await
+        .expect("Credit count assertion failed");
+
+    // nor on top of a free regeneration of a partial analysis
+    let partial_id = create().await.expect("Failed to create analysis");
+    user_manager
+        .atomic_complete_analysis(partial_id, user.id, 4)
+        .await
+        .expect("Failed to complete analysis");
+    user_manager
+        .mark_analysis_partial(partial_id)
+        .await
+        .expect("Failed to mark analysis partial");
+    user_manager
+        .claim_partial_regeneration(partial_id, user.id)
+        .await
+        .expect("Failed to claim regeneration")
+        .expect("Partial analysis should be claimable");
+    assert!(user_manager
+        .refund_analysis_credits(partial_id)
+        .await
+        .expect("Failed to refund analysis credits")
+        .is_none());
+    TestAssertions::assert_user_credit_count(&db, user.id, 11)
+        .await
+        .expect("Credit count assertion failed");
+
+    // the credits given back are in the balance again, refunding the payment in stars takes
+    // them too
+    let payment = user_manager
+        .get_payment("charge_2")
+        .await
+        .expect("Failed to get payment")
+        .expect("Payment should exist");
+    let new_balance = user_manager
+        .record_refund(&payment, None, None)
+        .await
+        .expect("Failed to record refund");
+    assert_eq!(new_balance, 1);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
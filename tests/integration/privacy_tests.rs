@@ -0,0 +1,196 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tg_main::analysis::{
+    cross_group_corpus_name, roast_battle_corpus_name, self_corpus_name, AnalysisDepth,
+};
+use tg_main::cross_group::CrossGroupManager;
+use tg_main::privacy::PrivacyManager;
+
+use super::{
+    mock_analysis::MockAnalysisFlow,
+    test_utils::{TestAssertions, TestScenario},
+    TestDatabase,
+};
+
+// a channel with a fixture in fixtures/
+const CHANNEL: &str = "@example_channel";
+
+async fn count(db: &TestDatabase, query: &str, telegram_user_id: i64) -> i64 {
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .query_one(query, &[&telegram_user_id])
+        .await
+        .expect("Failed to count rows")
+        .get(0)
+}
+
+#[tokio::test]
+async fn test_deleted_account_is_anonymized_and_keeps_analyses() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let flow = MockAnalysisFlow::new(pool.clone()).await;
+    let cross_group = CrossGroupManager::new(pool.clone());
+    let privacy = PrivacyManager::new(pool);
+
+    let user = flow.start(2500, "leaving").await;
+    let other = flow.start(2501, "staying").await;
+    flow.user_manager
+        .record_payment(user.id, "charge-2500", "credits_10", 500, 10)
+        .await
+        .expect("Failed to record payment");
+    flow.user_manager
+        .create_api_key(user.id, Duration::ZERO)
+        .await
+        .expect("Failed to create api key");
+    for (telegram_user_id, message_id) in [(2500, 1), (2501, 2)] {
+        cross_group.consent(telegram_user_id).await.unwrap();
+        cross_group
+            .record(
+                telegram_user_id,
+                -100,
+                "Chat",
+                message_id,
+                "hello",
+                1_700_000_000.0,
+            )
+            .await
+            .unwrap();
+    }
+    {
+        let client = db.pool.get().await.expect("Failed to get database client");
+        client
+            .execute(
+                "INSERT INTO message_queue (telegram_user_id, message) VALUES ($1, 'hi')",
+                &[&2500_i64],
+            )
+            .await
+            .expect("Failed to queue message");
+    }
+    flow.send_channel(2500, CHANNEL).await;
+    flow.select_type(2500, "roast", AnalysisDepth::Small)
+        .await
+        .expect("Analysis should be queued");
+    assert!(flow.run_next_job().await.unwrap().outcome.is_ok());
+
+    assert!(privacy
+        .delete_account(2500)
+        .await
+        .expect("Failed to delete"));
+
+    {
+        let client = db.pool.get().await.expect("Failed to get database client");
+        let row = client
+            .query_one(
+                "SELECT telegram_user_id, username, first_name, deleted_at IS NOT NULL
+                 FROM users WHERE id = $1",
+                &[&user.id],
+            )
+            .await
+            .expect("The user row should be kept");
+        assert!(row.get::<_, i64>(0) < 0);
+        assert_eq!(row.get::<_, Option<String>>(1), None);
+        assert_eq!(row.get::<_, Option<String>>(2), None);
+        assert!(row.get::<_, bool>(3));
+        let analyses: i64 = client
+            .query_one(
+                "SELECT COUNT(*) FROM user_analyses WHERE user_id = $1 AND channel_name = $2",
+                &[&user.id, &CHANNEL],
+            )
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(analyses, 1);
+        let payments: i64 = client
+            .query_one(
+                "SELECT COUNT(*) FROM payments WHERE user_id = $1",
+                &[&user.id],
+            )
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(payments, 1);
+        let keys: i64 = client
+            .query_one(
+                "SELECT COUNT(*) FROM api_keys WHERE user_id = $1",
+                &[&user.id],
+            )
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(keys, 0);
+    }
+    let messages = "SELECT COUNT(*) FROM group_messages WHERE telegram_user_id = $1";
+    assert_eq!(count(&db, messages, 2500).await, 0);
+    assert_eq!(count(&db, messages, 2501).await, 1);
+    let queued = "SELECT COUNT(*) FROM message_queue WHERE telegram_user_id = $1";
+    assert_eq!(count(&db, queued, 2500).await, 0);
+    assert_eq!(
+        flow.user_manager
+            .get_credits(other.id)
+            .await
+            .expect("The other user should be untouched"),
+        other.analysis_credits
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_unknown_user_has_nothing_to_delete() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let privacy = PrivacyManager::new(Arc::new(db.pool.clone()));
+
+    assert!(!privacy.delete_account(2502).await.unwrap());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_deleted_account_leaves_nothing_made_of_the_users_messages() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let flow = MockAnalysisFlow::new(pool.clone()).await;
+    let privacy = PrivacyManager::new(pool);
+
+    let user = flow.start(2500, "leaving").await;
+    let other = flow.start(2501, "staying").await;
+    let started_by_other = roast_battle_corpus_name(-100, "Leaving", "staying");
+    let without_the_user = roast_battle_corpus_name(-100, "staying", "someone");
+    let corpora = [
+        (user.id, self_corpus_name(2500), "a1", false),
+        (user.id, cross_group_corpus_name(2500), "b2", false),
+        (other.id, started_by_other.clone(), "c3", false),
+        (other.id, without_the_user.clone(), "d4", true),
+        (other.id, self_corpus_name(2501), "e5", true),
+        (other.id, CHANNEL.to_string(), "f6", true),
+    ];
+    for (user_id, corpus, hash, _) in &corpora {
+        TestScenario::create_analyzed_corpus(&db, *user_id, corpus, hash)
+            .await
+            .expect("Failed to store a corpus");
+    }
+
+    assert!(privacy
+        .delete_account(2500)
+        .await
+        .expect("Failed to delete"));
+
+    for (_, corpus, hash, kept) in &corpora {
+        TestAssertions::assert_corpus_left(&db, corpus, hash, *kept)
+            .await
+            .unwrap();
+    }
+    // the other member's battle analysis no longer names the user
+    let named = "SELECT COUNT(*) FROM user_analyses
+                 WHERE user_id = (SELECT id FROM users WHERE telegram_user_id = $1)
+                   AND channel_name LIKE '%eaving%'";
+    assert_eq!(count(&db, named, 2501).await, 0);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use tg_main::receipts::{ReceiptsManager, RevenuePeriod, RECEIPTS_LIMIT};
+use tg_main::user_manager::UserManager;
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_receipts_list_only_the_users_payments_newest_first() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let receipts = ReceiptsManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 2600, Some("buyer"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let (other, _) = bot
+        .simulate_user_start(&user_manager, 2601, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    for (user_id, charge_id, package, stars, credits) in [
+        (user.id, "charge-2600-1", "credits_1", 50, 1),
+        (user.id, "charge-2600-2", "subscription_30", 1000, 30),
+        (other.id, "charge-2601", "credits_10", 500, 10),
+    ] {
+        user_manager
+            .record_payment(user_id, charge_id, package, stars, credits)
+            .await
+            .expect("Failed to record payment");
+    }
+    let payment = user_manager
+        .get_payment("charge-2600-1")
+        .await
+        .unwrap()
+        .expect("Payment should exist");
+    user_manager
+        .record_refund(&payment, None, None)
+        .await
+        .expect("Failed to record refund");
+
+    let history = receipts
+        .history(2600, RECEIPTS_LIMIT)
+        .await
+        .expect("Failed to load receipts");
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].telegram_payment_charge_id, "charge-2600-2");
+    assert_eq!(history[0].package, "subscription_30");
+    assert_eq!(history[0].stars, 1000);
+    assert!(!history[0].refunded);
+    assert_eq!(history[1].package, "credits_1");
+    assert!(history[1].refunded);
+
+    assert!(receipts
+        .history(2602, RECEIPTS_LIMIT)
+        .await
+        .unwrap()
+        .is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_revenue_is_grouped_by_period_net_of_refunds() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let receipts = ReceiptsManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 2610, Some("buyer"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    for (charge_id, stars) in [
+        ("charge-today", 500),
+        ("charge-refunded", 50),
+        ("charge-old", 100),
+    ] {
+        user_manager
+            .record_payment(user.id, charge_id, "credits_10", stars, 10)
+            .await
+            .expect("Failed to record payment");
+    }
+    let payment = user_manager
+        .get_payment("charge-refunded")
+        .await
+        .unwrap()
+        .expect("Payment should exist");
+    user_manager
+        .record_refund(&payment, None, None)
+        .await
+        .expect("Failed to record refund");
+    {
+        let client = db.pool.get().await.expect("Failed to get database client");
+        client
+            .execute(
+                "UPDATE payments SET created_at = NOW() - INTERVAL '3 days'
+                 WHERE telegram_payment_charge_id = 'charge-old'",
+                &[],
+            )
+            .await
+            .expect("Failed to backdate payment");
+    }
+
+    let days = receipts
+        .revenue(RevenuePeriod::Day, 7)
+        .await
+        .expect("Failed to load revenue");
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[0].payments, 2);
+    assert_eq!(days[0].stars, 550);
+    assert_eq!(days[0].refunded_stars, 50);
+    assert_eq!(days[0].net_stars, 500);
+    assert_eq!(days[1].net_stars, 100);
+
+    // the backdated payment is outside today alone
+    let today = receipts.revenue(RevenuePeriod::Day, 1).await.unwrap();
+    assert_eq!(today.len(), 1);
+    assert_eq!(today[0].net_stars, 500);
+
+    let weeks = receipts.revenue(RevenuePeriod::Week, 2).await.unwrap();
+    assert_eq!(weeks.iter().map(|week| week.net_stars).sum::<i64>(), 600);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use tg_main::referrals::{ReferralManager, ReferralRules};
+use tg_main::user_manager::UserManager;
+
+use super::{mock_bot::MockTelegramBot, test_utils::TestAssertions, TestDatabase};
+
+#[tokio::test]
+async fn test_changed_rules_apply_to_the_next_referral() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let referrals = ReferralManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    referrals.set_rule("milestone_every", 2).await.unwrap();
+    referrals.set_rule("milestone_credits", 3).await.unwrap();
+    referrals.set_rule("celebrate_early", 0).await.unwrap();
+    let rules = referrals.rules().await.unwrap();
+    assert_eq!(rules.milestone_every, 2);
+    assert_eq!(rules.milestone_credits, 3);
+    assert!(!rules.celebrate_early);
+
+    let (referrer, _) = bot
+        .simulate_user_start(&user_manager, 500, Some("referrer"), None, None, None)
+        .await
+        .expect("Failed to create referrer");
+    let (_, first) = bot
+        .simulate_user_start(&user_manager, 501, None, None, None, Some(referrer.id))
+        .await
+        .expect("Failed to create referee");
+    // the first referral is no longer celebrated and isn't a milestone yet
+    assert!(first.is_none());
+
+    let (_, second) = bot
+        .simulate_user_start(&user_manager, 502, None, None, None, Some(referrer.id))
+        .await
+        .expect("Failed to create referee");
+    let reward = second.expect("Second referral should be a milestone");
+    assert_eq!(reward.milestone_rewards, 3);
+    assert_eq!(reward.total_credits_awarded, 3);
+    assert!(!reward.is_celebration_milestone);
+    TestAssertions::assert_user_credit_count(&db, referrer.id, 4)
+        .await
+        .expect("Milestone credits assertion failed");
+
+    // back to the default of a milestone every 5 referrals
+    assert!(referrals.reset_rule("milestone_every").await.unwrap());
+    assert!(!referrals.reset_rule("milestone_every").await.unwrap());
+    let (_, third) = bot
+        .simulate_user_start(&user_manager, 503, None, None, None, Some(referrer.id))
+        .await
+        .expect("Failed to create referee");
+    assert!(third.is_none());
+    TestAssertions::assert_referral_reward_count(&db, referrer.id, "unpaid_milestone", 1)
+        .await
+        .expect("Milestone reward count assertion failed");
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_turned_off_paid_rewards_are_not_paid_later() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let referrals = ReferralManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    referrals
+        .set_rule("paid_referral_credits", 0)
+        .await
+        .unwrap();
+    let (referrer, _) = bot
+        .simulate_user_start(&user_manager, 600, Some("referrer"), None, None, None)
+        .await
+        .expect("Failed to create referrer");
+    bot.simulate_user_start(&user_manager, 601, None, None, None, Some(referrer.id))
+        .await
+        .expect("Failed to create referee");
+    bot.simulate_user_payment(&user_manager, 601, 10)
+        .await
+        .expect("Failed to simulate payment");
+
+    TestAssertions::assert_paid_referral_count(&db, referrer.id, 1)
+        .await
+        .expect("Paid referral count assertion failed");
+    TestAssertions::assert_user_credit_count(&db, referrer.id, 1)
+        .await
+        .expect("No credits should be awarded");
+    TestAssertions::assert_referral_reward_count(&db, referrer.id, "paid_user", 1)
+        .await
+        .expect("The paid referral should still be recorded");
+
+    // turning the reward back on doesn't pay for referrals made while it was off
+    referrals
+        .set_rule("paid_referral_credits", 2)
+        .await
+        .unwrap();
+    user_manager
+        .check_and_award_referral_rewards(referrer.id)
+        .await
+        .unwrap();
+    TestAssertions::assert_user_credit_count(&db, referrer.id, 1)
+        .await
+        .expect("No credits should be awarded retroactively");
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_invalid_rules_are_rejected_and_skipped() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let referrals = ReferralManager::new(Arc::new(db.pool.clone()));
+
+    assert!(referrals.set_rule("milestone_every", -1).await.is_err());
+    assert!(referrals.set_rule("no_such_rule", 1).await.is_err());
+
+    // a row written around the validation doesn't break loading
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO referral_config (name, value) VALUES ('celebrate_early', 7)",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(referrals.rules().await.unwrap(), ReferralRules::default());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use tg_main::referral_flags::{FlagStatus, ReferralFlagManager};
+use tg_main::user_manager::UserManager;
+
+use super::{mock_bot::MockTelegramBot, test_utils::TestAssertions, TestDatabase};
+
+#[tokio::test]
+async fn test_rapid_signups_hold_milestone_rewards_until_cleared() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let flags = ReferralFlagManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (referrer, _) = bot
+        .simulate_user_start(&user_manager, 700, Some("referrer"), None, None, None)
+        .await
+        .expect("Failed to create referrer");
+    for i in 0..5 {
+        bot.simulate_user_start(&user_manager, 701 + i, None, None, None, Some(referrer.id))
+            .await
+            .expect("Failed to create referee");
+    }
+
+    // the fifth signup within minutes flags the referrer before the milestone is paid
+    TestAssertions::assert_referral_reward_count(&db, referrer.id, "unpaid_milestone", 0)
+        .await
+        .expect("The milestone should be held");
+    TestAssertions::assert_user_credit_count(&db, referrer.id, 1)
+        .await
+        .expect("Only the signup bonus should be credited");
+    let pending = flags.pending(10).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].referrer_telegram_id, 700);
+    assert_eq!(pending[0].reason, "rapid_signups");
+
+    let reviewed = flags
+        .review(pending[0].id, FlagStatus::Cleared, 1)
+        .await
+        .unwrap()
+        .expect("The flag should be pending");
+    assert!(!reviewed.still_held);
+    assert!(flags.pending(10).await.unwrap().is_empty());
+    // a flag is reviewed once
+    assert!(flags
+        .review(pending[0].id, FlagStatus::Confirmed, 1)
+        .await
+        .unwrap()
+        .is_none());
+
+    let reward = user_manager
+        .check_and_award_referral_rewards(referrer.id)
+        .await
+        .unwrap();
+    assert_eq!(reward.milestone_rewards, 1);
+    TestAssertions::assert_user_credit_count(&db, referrer.id, 2)
+        .await
+        .expect("The held milestone should be paid");
+
+    // a cleared reason isn't raised again by later signups
+    bot.simulate_user_start(&user_manager, 706, None, None, None, Some(referrer.id))
+        .await
+        .expect("Failed to create referee");
+    assert!(flags.pending(10).await.unwrap().is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+/// makes every referee of the referrer a russian speaker who joined three days ago
+async fn age_referees(db: &TestDatabase, referrer_user_id: i32) {
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE users SET language = 'ru', created_at = NOW() - INTERVAL '3 days'
+             WHERE referred_by_user_id = $1",
+            &[&referrer_user_id],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_idle_referees_of_one_language_flag_their_referrer() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let flags = ReferralFlagManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (referrer, _) = bot
+        .simulate_user_start(&user_manager, 800, Some("referrer"), None, None, None)
+        .await
+        .expect("Failed to create referrer");
+    for i in 0..4 {
+        bot.simulate_user_start(&user_manager, 801 + i, None, None, None, Some(referrer.id))
+            .await
+            .expect("Failed to create referee");
+    }
+    // four referees who joined days ago and never analyzed or paid
+    age_referees(&db, referrer.id).await;
+    assert!(flags.pending(10).await.unwrap().is_empty());
+
+    bot.simulate_user_start(&user_manager, 805, None, None, None, Some(referrer.id))
+        .await
+        .expect("Failed to create referee");
+    assert!(flags.pending(10).await.unwrap().is_empty());
+    age_referees(&db, referrer.id).await;
+
+    bot.simulate_user_start(&user_manager, 806, None, None, None, Some(referrer.id))
+        .await
+        .expect("Failed to create referee");
+    let pending = flags.pending(10).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].reason, "idle_referees");
+    assert!(pending[0].details.contains("language ru"));
+
+    // a confirmed flag keeps holding the rewards
+    let reviewed = flags
+        .review(pending[0].id, FlagStatus::Confirmed, 1)
+        .await
+        .unwrap()
+        .expect("The flag should be pending");
+    assert!(reviewed.still_held);
+    let reward = user_manager
+        .check_and_award_referral_rewards(referrer.id)
+        .await
+        .unwrap();
+    assert_eq!(reward.milestone_rewards, 0);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
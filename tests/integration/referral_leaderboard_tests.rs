@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use tg_main::referrals::ReferralManager;
+use tg_main::user_manager::{User, UserManager};
+
+use super::TestDatabase;
+
+async fn join(
+    user_manager: &UserManager,
+    telegram_user_id: i64,
+    first_name: &str,
+    referrer: Option<&User>,
+) -> User {
+    user_manager
+        .get_or_create_user(
+            telegram_user_id,
+            None,
+            Some(first_name),
+            None,
+            referrer.map(|referrer| referrer.id),
+            None,
+        )
+        .await
+        .expect("Failed to create user")
+        .0
+}
+
+#[tokio::test]
+async fn test_weekly_leaderboard_ranks_anonymized_referrers() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let referrals = ReferralManager::new(pool);
+
+    let alice = join(&user_manager, 4000, "alice", None).await;
+    let bob = join(&user_manager, 4001, "Bob", None).await;
+    let carol = join(&user_manager, 4002, "Carol", None).await;
+    for i in 0..3 {
+        join(&user_manager, 4100 + i, "Friend", Some(&alice)).await;
+    }
+    join(&user_manager, 4200, "Friend", Some(&bob)).await;
+    let old = join(&user_manager, 4201, "Friend", Some(&bob)).await;
+    join(&user_manager, 4300, "Friend", Some(&carol)).await;
+
+    // bob's second referral joined before this week
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE users SET created_at = NOW() - INTERVAL '8 days' WHERE id = $1",
+            &[&old.id],
+        )
+        .await
+        .unwrap();
+
+    let leaderboard = referrals.weekly_leaderboard(10).await.unwrap();
+    let board = leaderboard
+        .iter()
+        .map(|entry| (entry.rank, entry.name.as_str(), entry.referrals))
+        .collect::<Vec<_>>();
+    assert_eq!(board, vec![(1, "A***", 3), (2, "B***", 1), (2, "C***", 1)]);
+    assert_eq!(leaderboard[0].user_id, alice.id);
+    assert_eq!(referrals.weekly_leaderboard(1).await.unwrap().len(), 1);
+
+    let stats = referrals.stats(bob.id).await.unwrap();
+    assert_eq!(stats.referrals, 2);
+    assert_eq!(stats.paid_referrals, 0);
+    assert_eq!(stats.this_week, 1);
+    assert_eq!(stats.rank, Some(2));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_stats_without_referrals_have_no_rank() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let referrals = ReferralManager::new(pool);
+
+    let loner = join(&user_manager, 4400, "Loner", None).await;
+    let stats = referrals.stats(loner.id).await.unwrap();
+    assert_eq!(stats.referrals, 0);
+    assert_eq!(stats.this_week, 0);
+    assert!(stats.rank.is_none());
+    assert!(referrals.weekly_leaderboard(10).await.unwrap().is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use tg_main::user_manager::UserManager;
 
 use super::{
@@ -11,7 +12,10 @@ async fn test_basic_referral_chain() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // create referrer user
@@ -93,7 +97,10 @@ async fn test_milestone_celebrations() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // create referrer
@@ -214,7 +221,7 @@ async fn test_paid_referral_rewards() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // create referrer
@@ -287,7 +294,10 @@ async fn test_mixed_paid_and_unpaid_referrals() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let _bot = MockTelegramBot::new();
 
     // create referrer with 4 unpaid referrals and 1 paid referral
@@ -345,7 +355,10 @@ async fn test_progressive_milestone_rewards() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // create referrer with exactly 25 referrals to test progression
@@ -415,7 +428,7 @@ async fn test_edge_cases() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // test invalid referrer ID
@@ -481,7 +494,10 @@ async fn test_database_consistency() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
 
     // create a comprehensive scenario
     let (referrer, _, _) = TestScenario::create_referrer_with_mixed_referrals(
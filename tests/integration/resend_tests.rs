@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use tg_main::user_manager::{AnalysisSource, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+async fn create_analysis(user_manager: &UserManager, user_id: i32, channel: &str) -> i32 {
+    user_manager
+        .create_pending_analysis(
+            user_id,
+            channel,
+            "professional",
+            "medium",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis")
+}
+
+#[tokio::test]
+async fn test_rendered_result_is_resent_only_to_its_owner() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (owner, _) = bot
+        .simulate_user_start(&user_manager, 900, Some("owner"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let (other, _) = bot
+        .simulate_user_start(&user_manager, 901, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    // nothing rendered yet
+    assert!(user_manager
+        .get_rendered_result(owner.id, None)
+        .await
+        .expect("Failed to get rendered result")
+        .is_none());
+
+    let first = create_analysis(&user_manager, owner.id, "@first").await;
+    let second = create_analysis(&user_manager, owner.id, "@second").await;
+    // an analysis without a stored result is skipped
+    create_analysis(&user_manager, owner.id, "@third").await;
+
+    user_manager
+        .store_rendered_result(first, &["first part".to_string()])
+        .await
+        .expect("Failed to store rendered result");
+    user_manager
+        .store_rendered_result(second, &["part one".to_string(), "part two".to_string()])
+        .await
+        .expect("Failed to store rendered result");
+
+    let (id, messages) = user_manager
+        .get_rendered_result(owner.id, None)
+        .await
+        .expect("Failed to get rendered result")
+        .expect("Latest rendered result should exist");
+    assert_eq!(id, second);
+    assert_eq!(messages, vec!["part one", "part two"]);
+
+    let (id, messages) = user_manager
+        .get_rendered_result(owner.id, Some(first))
+        .await
+        .expect("Failed to get rendered result")
+        .expect("Rendered result should exist");
+    assert_eq!(id, first);
+    assert_eq!(messages, vec!["first part"]);
+
+    // other users can't fetch it
+    assert!(user_manager
+        .get_rendered_result(other.id, Some(first))
+        .await
+        .expect("Failed to get rendered result")
+        .is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
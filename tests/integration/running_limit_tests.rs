@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use tg_main::user_manager::{AnalysisSource, UserManager, UserManagerError};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+async fn start(
+    user_manager: &UserManager,
+    user_id: i32,
+    channel: &str,
+    max_running: Option<i32>,
+) -> Result<i32, UserManagerError> {
+    user_manager
+        .create_pending_analysis(
+            user_id,
+            channel,
+            "roast",
+            "medium",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            max_running,
+        )
+        .await
+}
+
+#[tokio::test]
+async fn test_second_analysis_waits_for_the_running_one() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1700, Some("busy"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let (other, _) = bot
+        .simulate_user_start(&user_manager, 1701, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    let running = start(&user_manager, user.id, "@first", Some(1))
+        .await
+        .expect("The first analysis should start");
+    assert!(matches!(
+        start(&user_manager, user.id, "@second", Some(1)).await,
+        Err(UserManagerError::TooManyRunning(id)) if id == user.id
+    ));
+    // the limit is per user, and a higher one lets more run
+    start(&user_manager, other.id, "@first", Some(1))
+        .await
+        .expect("Another user's analysis should start");
+    start(&user_manager, user.id, "@second", Some(2))
+        .await
+        .expect("A second analysis should start under a limit of two");
+    // unchecked, as for the rest of a batch
+    start(&user_manager, user.id, "@third", None)
+        .await
+        .expect("An unchecked analysis should start");
+
+    // a finished analysis frees its slot
+    let client = db.pool.get().await.expect("Failed to get client");
+    client
+        .execute(
+            "UPDATE user_analyses SET status = 'failed' WHERE user_id = $1 AND id <> $2",
+            &[&user.id, &running],
+        )
+        .await
+        .expect("Failed to fail analyses");
+    drop(client);
+
+    // reopening a failed analysis counts as a start too
+    let failed = start(&user_manager, other.id, "@retry", None)
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .mark_analysis_failed(failed)
+        .await
+        .expect("Failed to fail analysis");
+    assert!(matches!(
+        user_manager
+            .reopen_failed_analysis(failed, other.id, 1)
+            .await,
+        Err(UserManagerError::TooManyRunning(_))
+    ));
+
+    user_manager
+        .mark_analysis_failed(running)
+        .await
+        .expect("Failed to fail analysis");
+    start(&user_manager, user.id, "@second", Some(1))
+        .await
+        .expect("An analysis should start once the running one is done");
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_concurrent_starts_let_only_one_through() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = Arc::new(UserManager::new(Arc::new(db.pool.clone())));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1702, Some("clicker"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    let user_id = user.id;
+    let starts = (0..5).map(|i| {
+        let user_manager = user_manager.clone();
+        tokio::spawn(async move {
+            start(&user_manager, user_id, &format!("@channel{}", i), Some(1)).await
+        })
+    });
+    let mut started = 0;
+    for handle in starts.collect::<Vec<_>>() {
+        match handle.await.expect("Start task panicked") {
+            Ok(_) => started += 1,
+            Err(UserManagerError::TooManyRunning(_)) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+    assert_eq!(started, 1);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
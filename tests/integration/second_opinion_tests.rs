@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use tg_main::analysis::AnalysisDepth;
+use tg_main::user_manager::CreditTransactionKind;
+
+use super::{mock_analysis::MockAnalysisFlow, TestDatabase};
+
+// a channel with a fixture in fixtures/
+const CHANNEL: &str = "@example_channel";
+
+#[tokio::test]
+async fn test_second_opinion_asks_another_model_at_a_reduced_price() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let flow = MockAnalysisFlow::new(Arc::new(db.pool.clone())).await;
+    let cost = flow.limits.depth_credits(AnalysisDepth::Deep);
+    let second_opinion_cost = flow.limits.second_opinion_credits(AnalysisDepth::Deep);
+    assert!(second_opinion_cost < cost);
+
+    let user = flow.start(2100, "reader").await;
+    let other = flow.start(2101, "other").await;
+    for user_id in [user.id, other.id] {
+        flow.user_manager
+            .add_credits(
+                user_id,
+                cost + second_opinion_cost,
+                CreditTransactionKind::Purchase,
+                None,
+            )
+            .await
+            .expect("Failed to add credits");
+    }
+
+    flow.send_channel(2100, CHANNEL).await;
+    let analysis_id = flow
+        .select_type(2100, "roast", AnalysisDepth::Deep)
+        .await
+        .expect("Analysis should be queued");
+    let first = flow
+        .run_next_job()
+        .await
+        .expect("A job should be queued")
+        .outcome
+        .expect("Analysis should succeed");
+    let first_model = first
+        .result
+        .model
+        .clone()
+        .expect("Result should name its model");
+    let original = flow
+        .user_manager
+        .get_analysis(analysis_id, user.id)
+        .await
+        .expect("Failed to load analysis")
+        .expect("Analysis should exist");
+    assert_eq!(original.model.as_deref(), Some(first_model.as_str()));
+
+    // only the owner can ask
+    assert!(flow
+        .request_second_opinion(2101, analysis_id)
+        .await
+        .is_none());
+
+    let second_opinion_id = flow
+        .request_second_opinion(2100, analysis_id)
+        .await
+        .expect("Second opinion should be queued");
+    // one per analysis
+    assert!(flow
+        .request_second_opinion(2100, analysis_id)
+        .await
+        .is_none());
+
+    let run = flow.run_next_job().await.expect("A job should be queued");
+    assert_eq!(run.analysis_id, second_opinion_id);
+    let second = run.outcome.expect("Second opinion should succeed");
+    let second_model = second
+        .result
+        .model
+        .clone()
+        .expect("Result should name its model");
+    assert_ne!(second_model, first_model);
+    assert_eq!(
+        second.remaining_credits,
+        first.remaining_credits - second_opinion_cost
+    );
+
+    let record = flow
+        .user_manager
+        .get_analysis(second_opinion_id, user.id)
+        .await
+        .expect("Failed to load second opinion")
+        .expect("Second opinion should exist");
+    assert_eq!(record.status, "completed");
+    assert_eq!(record.credits_used, second_opinion_cost);
+    assert_eq!(record.model.as_deref(), Some(second_model.as_str()));
+    assert_eq!(record.channel_name, CHANNEL);
+    assert_eq!(record.analysis_type.as_deref(), Some("roast"));
+    // each answer is cached apart
+    assert_ne!(record.cache_key, original.cache_key);
+
+    let client = db.pool.get().await.unwrap();
+    let second_opinion_of: Option<i32> = client
+        .query_one(
+            "SELECT second_opinion_of FROM user_analyses WHERE id = $1",
+            &[&second_opinion_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(second_opinion_of, Some(analysis_id));
+    // the channel was analyzed once as far as the leaderboard goes
+    let stats: i32 = client
+        .query_one(
+            "SELECT analyses FROM channel_stats WHERE channel_name = $1",
+            &[&CHANNEL],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(stats, 1);
+    drop(client);
+
+    // a second opinion doesn't get one of its own
+    assert!(flow
+        .request_second_opinion(2100, second_opinion_id)
+        .await
+        .is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
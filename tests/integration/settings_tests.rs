@@ -0,0 +1,263 @@
+use std::sync::Arc;
+use tg_main::language_overrides::LanguageOverrides;
+use tg_main::llm::ModelTier;
+use tg_main::localization::Lang;
+use tg_main::prompts::analysis::OutputLanguage;
+use tg_main::user_manager::UserManager;
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_model_tier_preference_persists() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(
+            &user_manager,
+            500,
+            Some("settings"),
+            Some("Settings"),
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create user");
+
+    // new users start on auto
+    let tier = user_manager
+        .get_model_tier(user.id)
+        .await
+        .expect("Failed to get model tier");
+    assert_eq!(tier, ModelTier::Auto);
+
+    user_manager
+        .set_model_tier(user.id, ModelTier::Quality)
+        .await
+        .expect("Failed to set model tier");
+    let tier = user_manager
+        .get_model_tier(user.id)
+        .await
+        .expect("Failed to get model tier");
+    assert_eq!(tier, ModelTier::Quality);
+
+    // unknown users are reported rather than silently ignored
+    assert!(user_manager
+        .set_model_tier(user.id + 1000, ModelTier::Fast)
+        .await
+        .is_err());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_web_pages_preference_persists() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 503, Some("pages"), Some("Pages"), None, None)
+        .await
+        .expect("Failed to create user");
+
+    // results are published only for users who turned pages on
+    assert!(!user_manager
+        .get_web_pages_enabled(user.id)
+        .await
+        .expect("Failed to get web pages setting"));
+
+    user_manager
+        .set_web_pages_enabled(user.id, true)
+        .await
+        .expect("Failed to turn web pages on");
+    assert!(user_manager
+        .get_web_pages_enabled(user.id)
+        .await
+        .expect("Failed to get web pages setting"));
+
+    assert!(user_manager
+        .set_web_pages_enabled(user.id + 1000, true)
+        .await
+        .is_err());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_output_language_preference_persists() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(
+            &user_manager,
+            501,
+            Some("language"),
+            Some("Language"),
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create user");
+
+    // new users get analyses in the channel's language
+    let language = user_manager
+        .get_output_language(user.id)
+        .await
+        .expect("Failed to get output language");
+    assert_eq!(language, OutputLanguage::Channel);
+
+    user_manager
+        .set_output_language(user.id, OutputLanguage::Russian)
+        .await
+        .expect("Failed to set output language");
+    let language = user_manager
+        .get_output_language(user.id)
+        .await
+        .expect("Failed to get output language");
+    assert_eq!(language, OutputLanguage::Russian);
+
+    assert!(user_manager
+        .set_output_language(user.id + 1000, OutputLanguage::English)
+        .await
+        .is_err());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_language_override_persists_and_resets() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(
+            &user_manager,
+            502,
+            Some("override"),
+            Some("Override"),
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create user");
+
+    // new users follow telegram's language
+    let language_override = user_manager
+        .get_language_override(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language_override, None);
+
+    user_manager
+        .set_language_override(user.id, Some(Lang::Uk.code()))
+        .await
+        .expect("Failed to set language override");
+    let language_override = user_manager
+        .get_language_override(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language_override.as_deref(), Some("uk"));
+    assert_eq!(
+        Lang::resolve(language_override.as_deref(), Some("en")),
+        Lang::Uk
+    );
+
+    user_manager
+        .set_language_override(user.id, None)
+        .await
+        .expect("Failed to reset language override");
+    let language_override = user_manager
+        .get_language_override(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language_override, None);
+
+    // unknown telegram users have no override rather than an error
+    let language_override = user_manager
+        .get_language_override(user.telegram_user_id + 1000)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language_override, None);
+
+    assert!(user_manager
+        .set_language_override(user.id + 1000, Some(Lang::De.code()))
+        .await
+        .is_err());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_language_overrides_are_cached_until_changed() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = Arc::new(UserManager::new(Arc::new(db.pool.clone())));
+    let overrides = LanguageOverrides::new(user_manager.clone());
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 503, Some("cached"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    let language = overrides
+        .get(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language, None);
+
+    // a change behind the cache's back isn't seen, the first lookup is kept
+    user_manager
+        .set_language_override(user.id, Some(Lang::De.code()))
+        .await
+        .expect("Failed to set language override");
+    let language = overrides
+        .get(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language, None);
+
+    // /language goes through the cache, which is stored and served at once
+    overrides
+        .set(user.id, user.telegram_user_id, Some(Lang::Uk.code()))
+        .await
+        .expect("Failed to set language override");
+    let language = overrides
+        .get(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language.as_deref(), Some("uk"));
+    let stored = user_manager
+        .get_language_override(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(stored.as_deref(), Some("uk"));
+
+    // a forgotten user is looked up again
+    user_manager
+        .set_language_override(user.id, None)
+        .await
+        .expect("Failed to reset language override");
+    overrides.forget(user.telegram_user_id);
+    let language = overrides
+        .get(user.telegram_user_id)
+        .await
+        .expect("Failed to get language override");
+    assert_eq!(language, None);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
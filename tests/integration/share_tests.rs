@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use tg_main::user_manager::{AnalysisSource, CreditTransactionKind, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_shareable_analyses_only_include_completed_cached_results() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(
+            &user_manager,
+            600,
+            Some("sharer"),
+            Some("Sharer"),
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(user.id, 5, CreditTransactionKind::Purchase, None)
+        .await
+        .expect("Failed to add credits");
+
+    // completed with a cached result: shareable
+    let completed = user_manager
+        .create_pending_analysis(
+            user.id,
+            "@rustlang",
+            "roast",
+            "small",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .set_analysis_cache_key(completed, "abc123")
+        .await
+        .expect("Failed to set cache key");
+    user_manager
+        .atomic_complete_analysis(completed, user.id, 1)
+        .await
+        .expect("Failed to complete analysis");
+
+    // still pending: not shareable
+    let pending = user_manager
+        .create_pending_analysis(
+            user.id,
+            "@golang",
+            "roast",
+            "small",
+            Some("en"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .set_analysis_cache_key(pending, "def456")
+        .await
+        .expect("Failed to set cache key");
+
+    let shareable = user_manager
+        .get_shareable_analyses(600, None, 10)
+        .await
+        .expect("Failed to get shareable analyses");
+    assert_eq!(shareable.len(), 1);
+    assert_eq!(shareable[0].channel_name, "@rustlang");
+    assert_eq!(shareable[0].cache_key, "abc123");
+
+    // channel filter is case-insensitive
+    let filtered = user_manager
+        .get_shareable_analyses(600, Some("@RustLang"), 10)
+        .await
+        .expect("Failed to get shareable analyses");
+    assert_eq!(filtered.len(), 1);
+    let other = user_manager
+        .get_shareable_analyses(600, Some("@golang"), 10)
+        .await
+        .expect("Failed to get shareable analyses");
+    assert!(other.is_empty());
+
+    // other users cannot see them
+    let foreign = user_manager
+        .get_shareable_analyses(601, None, 10)
+        .await
+        .expect("Failed to get shareable analyses");
+    assert!(foreign.is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
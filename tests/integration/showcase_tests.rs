@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use tg_main::showcase::ShowcaseManager;
+use tg_main::user_manager::{AnalysisSource, CreditTransactionKind, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_only_the_owner_can_publish_a_complete_analysis() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let showcase = ShowcaseManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (owner, _) = bot
+        .simulate_user_start(&user_manager, 1100, Some("owner"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    bot.simulate_user_start(&user_manager, 1101, Some("other"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(owner.id, 3, CreditTransactionKind::Purchase, Some("charge"))
+        .await
+        .expect("Failed to add credits");
+
+    let analysis_id = user_manager
+        .create_pending_analysis(
+            owner.id,
+            "@rustlang",
+            "roast",
+            "small",
+            Some("ru"),
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .set_analysis_cache_key(analysis_id, "key")
+        .await
+        .expect("Failed to store cache key");
+
+    // not completed yet
+    assert!(!showcase
+        .consent(analysis_id, 1100, false)
+        .await
+        .expect("Failed to consent"));
+
+    user_manager
+        .atomic_complete_analysis(analysis_id, owner.id, 1)
+        .await
+        .expect("Failed to complete analysis");
+
+    // not theirs
+    assert!(!showcase
+        .consent(analysis_id, 1101, false)
+        .await
+        .expect("Failed to consent"));
+    assert!(showcase
+        .next_unposted()
+        .await
+        .expect("Failed to query showcase")
+        .is_none());
+
+    assert!(showcase
+        .consent(analysis_id, 1100, true)
+        .await
+        .expect("Failed to consent"));
+    // the first choice sticks
+    assert!(!showcase
+        .consent(analysis_id, 1100, false)
+        .await
+        .expect("Failed to consent"));
+
+    let entry = showcase
+        .next_unposted()
+        .await
+        .expect("Failed to query showcase")
+        .expect("Consented analysis should be queued");
+    assert_eq!(entry.analysis_id, analysis_id);
+    assert_eq!(entry.channel_name, "@rustlang");
+    assert_eq!(entry.analysis_type, "roast");
+    assert_eq!(entry.cache_key, "key");
+    assert!(entry.anonymous);
+    assert_eq!(entry.language_code.as_deref(), Some("ru"));
+
+    showcase
+        .mark_posted(analysis_id)
+        .await
+        .expect("Failed to mark posted");
+    assert!(showcase
+        .next_unposted()
+        .await
+        .expect("Failed to query showcase")
+        .is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_partial_analyses_are_not_published() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let showcase = ShowcaseManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (owner, _) = bot
+        .simulate_user_start(&user_manager, 1102, Some("owner"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(owner.id, 1, CreditTransactionKind::Purchase, Some("charge"))
+        .await
+        .expect("Failed to add credits");
+
+    let analysis_id = user_manager
+        .create_pending_analysis(
+            owner.id,
+            "@rustlang",
+            "professional",
+            "small",
+            None,
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .set_analysis_cache_key(analysis_id, "key")
+        .await
+        .expect("Failed to store cache key");
+    user_manager
+        .atomic_complete_analysis(analysis_id, owner.id, 1)
+        .await
+        .expect("Failed to complete analysis");
+    user_manager
+        .mark_analysis_partial(analysis_id)
+        .await
+        .expect("Failed to mark analysis partial");
+
+    assert!(!showcase
+        .consent(analysis_id, 1102, false)
+        .await
+        .expect("Failed to consent"));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
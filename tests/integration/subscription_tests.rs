@@ -0,0 +1,149 @@
+use std::sync::Arc;
+use tg_main::subscriptions::{Lapsed, SubscriptionManager, SubscriptionStatus};
+use tg_main::user_manager::{CreditTransactionKind, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_each_paid_period_is_credited_once() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let subscriptions = SubscriptionManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1400, Some("subscriber"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    let starting_balance = user.analysis_credits;
+
+    let subscription = subscriptions
+        .record_payment(user.id, "charge-1", 1000, 30)
+        .await
+        .expect("Failed to record payment")
+        .expect("First payment should be recorded");
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(subscription.periods_paid, 1);
+    assert_eq!(subscription.periods_credited, 0);
+    assert_eq!(subscriptions.due_top_ups().await.unwrap(), vec![user.id]);
+
+    // telegram delivering the same charge twice doesn't extend the subscription
+    assert!(subscriptions
+        .record_payment(user.id, "charge-1", 1000, 30)
+        .await
+        .expect("Failed to record payment")
+        .is_none());
+
+    assert_eq!(
+        subscriptions
+            .top_up(user.id)
+            .await
+            .expect("Failed to top up"),
+        Some((30, starting_balance + 30))
+    );
+    assert_eq!(
+        subscriptions
+            .top_up(user.id)
+            .await
+            .expect("Failed to top up"),
+        None
+    );
+    assert!(subscriptions.due_top_ups().await.unwrap().is_empty());
+
+    // a renewal continues from the end of the paid period
+    let renewed = subscriptions
+        .record_payment(user.id, "charge-2", 1000, 30)
+        .await
+        .expect("Failed to record payment")
+        .expect("Renewal should be recorded");
+    assert_eq!(renewed.periods_paid, 2);
+    assert!(renewed.paid_until > subscription.paid_until);
+    assert_eq!(
+        subscriptions
+            .top_up(user.id)
+            .await
+            .expect("Failed to top up"),
+        Some((30, starting_balance + 60))
+    );
+
+    let transactions = user_manager
+        .get_credit_transactions(user.id, 10, 0)
+        .await
+        .expect("Failed to load transactions");
+    assert_eq!(transactions[0].kind, CreditTransactionKind::Subscription);
+    assert_eq!(transactions[0].amount, 30);
+}
+
+#[tokio::test]
+async fn test_unpaid_subscriptions_lapse_through_grace_to_expiry() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let subscriptions = SubscriptionManager::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1500, Some("lapsing"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    subscriptions
+        .record_payment(user.id, "charge-1", 1000, 30)
+        .await
+        .expect("Failed to record payment");
+
+    // still paid for
+    assert!(subscriptions.lapse().await.unwrap().is_empty());
+
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE subscriptions SET paid_until = NOW() - INTERVAL '1 day' WHERE user_id = $1",
+            &[&user.id],
+        )
+        .await
+        .expect("Failed to move paid period");
+    let lapsed = subscriptions.lapse().await.expect("Failed to lapse");
+    assert_eq!(
+        lapsed,
+        vec![Lapsed {
+            telegram_user_id: 1500,
+            language: None,
+            status: SubscriptionStatus::Grace,
+        }]
+    );
+    // subscribers are told once
+    assert!(subscriptions.lapse().await.unwrap().is_empty());
+
+    client
+        .execute(
+            "UPDATE subscriptions SET paid_until = NOW() - INTERVAL '4 days' WHERE user_id = $1",
+            &[&user.id],
+        )
+        .await
+        .expect("Failed to move paid period");
+    let lapsed = subscriptions.lapse().await.expect("Failed to lapse");
+    assert_eq!(lapsed.len(), 1);
+    assert_eq!(lapsed[0].status, SubscriptionStatus::Expired);
+
+    // subscribing again starts a new period from now
+    let restarted = subscriptions
+        .record_payment(user.id, "charge-2", 1000, 30)
+        .await
+        .expect("Failed to record payment")
+        .expect("New payment should be recorded");
+    assert_eq!(restarted.status, SubscriptionStatus::Active);
+    let fresh: bool = client
+        .query_one(
+            "SELECT paid_until > NOW() + INTERVAL '29 days' FROM subscriptions WHERE user_id = $1",
+            &[&user.id],
+        )
+        .await
+        .expect("Failed to read subscription")
+        .get(0);
+    assert!(fresh);
+}
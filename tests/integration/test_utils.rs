@@ -1,5 +1,8 @@
 use super::TestDatabase;
-use tg_main::user_manager::{User, UserManager};
+use tg_main::free_trials::TrialOutcome;
+use tg_main::referrals::ReferralRules;
+use tg_main::user_manager::{CreditTransactionKind, User, UserManager};
+use std::sync::Arc;
 
 /// helper struct for creating test users with predictable IDs
 pub struct TestUserBuilder {
@@ -39,15 +42,23 @@ impl TestUserBuilder {
         user_manager: &UserManager,
         referrer_user_id: Option<i32>,
     ) -> Result<User, Box<dyn std::error::Error + Send + Sync>> {
-        let (user, _) = user_manager
+        let (mut user, _) = user_manager
             .get_or_create_user(
                 self.telegram_user_id,
                 self.username.as_deref(),
                 self.first_name.as_deref(),
                 self.last_name.as_deref(),
                 referrer_user_id,
+                None,
             )
             .await?;
+        // the user presses "I'm human" like a real one would
+        if let Some(TrialOutcome::Granted { credits }) = user_manager
+            .claim_free_trial(self.telegram_user_id, i32::MAX)
+            .await?
+        {
+            user.analysis_credits = credits;
+        }
         Ok(user)
     }
 }
@@ -79,6 +90,56 @@ impl TestAssertions {
         Ok(())
     }
 
+    /// verifies whether the rows of a corpus stored by `create_analyzed_corpus` are left
+    pub async fn assert_corpus_left(
+        db: &TestDatabase,
+        corpus: &str,
+        hash: &str,
+        expected_left: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = db.pool.get().await?;
+        let pattern = format!("{}%", hash);
+        for (what, query, param) in [
+            (
+                "messages",
+                "SELECT COUNT(*) FROM channel_messages WHERE channel_name = $1",
+                corpus,
+            ),
+            (
+                "cached answers",
+                "SELECT COUNT(*) FROM llm_results WHERE cache_key LIKE $1",
+                &pattern,
+            ),
+            (
+                "voice summaries",
+                "SELECT COUNT(*) FROM voice_summaries WHERE cache_key LIKE $1",
+                &pattern,
+            ),
+            (
+                "flagged passages",
+                "SELECT COUNT(*) FROM flagged_outputs WHERE cache_key LIKE $1",
+                &pattern,
+            ),
+            (
+                "rendered results",
+                "SELECT COUNT(*) FROM user_analyses
+                 WHERE cache_key LIKE $1 AND rendered_result IS NOT NULL",
+                &pattern,
+            ),
+        ] {
+            let count: i64 = client.query_one(query, &[&param]).await?.get(0);
+            assert_eq!(
+                count > 0,
+                expected_left,
+                "Expected the {} of {} to be {}",
+                what,
+                corpus,
+                if expected_left { "left" } else { "deleted" }
+            );
+        }
+        Ok(())
+    }
+
     /// verifies that a user has the expected number of analysis credits
     pub async fn assert_user_credit_count(
         db: &TestDatabase,
@@ -177,6 +238,66 @@ impl TestAssertions {
 pub struct TestScenario;
 
 impl TestScenario {
+    /// turns off referral fraud flags, which would hold the milestone rewards of
+    /// referrals created within milliseconds of each other
+    pub async fn allow_rapid_referrals(
+        db: &TestDatabase,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        ReferralRules::save(&db.pool, "suspicious_referees", 0).await
+    }
+
+    /// stores a corpus, a completed analysis of it by the user and what was made of its
+    /// answer: the answer cached under `hash` and under a routed model's key, a voice
+    /// summary, a flagged passage and the rendered result
+    pub async fn create_analyzed_corpus(
+        db: &TestDatabase,
+        user_id: i32,
+        corpus: &str,
+        hash: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = db.pool.get().await?;
+        let routed_key = format!("{}:mgemini-2.5-pro", hash);
+        client
+            .execute(
+                "INSERT INTO channel_messages (channel_name, messages_data)
+                 VALUES ($1, '[{\"message\": \"my own words\"}]')",
+                &[&corpus],
+            )
+            .await?;
+        client
+            .execute(
+                "INSERT INTO user_analyses
+                     (user_id, channel_name, status, cache_key, rendered_result)
+                 VALUES ($1, $2, 'completed', $3, '{\"text\": \"a roast of my words\"}')",
+                &[&user_id, &corpus, &routed_key],
+            )
+            .await?;
+        for key in [hash, routed_key.as_str()] {
+            client
+                .execute(
+                    "INSERT INTO llm_results (cache_key, analysis_result) VALUES ($1, '{}')",
+                    &[&key],
+                )
+                .await?;
+        }
+        client
+            .execute(
+                "INSERT INTO voice_summaries (cache_key, analysis_type, file_id)
+                 VALUES ($1, 'roast', 'voice-file')",
+                &[&routed_key],
+            )
+            .await?;
+        client
+            .execute(
+                "INSERT INTO flagged_outputs
+                     (channel_name, analysis_type, cache_key, section, category, excerpt, action)
+                 VALUES ($1, 'roast', $2, 'roast', 'doxxing', 'my own words', 'redacted')",
+                &[&corpus, &routed_key],
+            )
+            .await?;
+        Ok(())
+    }
+
     /// creates a referrer and a specified number of unpaid referrals
     pub async fn create_referrer_with_unpaid_referrals(
         user_manager: &UserManager,
@@ -247,10 +368,10 @@ impl TestScenario {
 
             // simulate payment by this referral
             user_manager
-                .add_credits(referral.telegram_user_id, 1)
+                .add_credits(referral.id, 1, CreditTransactionKind::Purchase, None)
                 .await?;
             user_manager
-                .record_paid_referral(referral.telegram_user_id)
+                .record_paid_referral(referral.id)
                 .await?;
 
             paid_referrals.push(referral);
@@ -263,14 +384,13 @@ impl TestScenario {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::integration::TestDatabase;
 
     #[tokio::test]
     async fn test_user_builder() {
         let db = TestDatabase::create_fresh()
             .await
             .expect("Failed to create test database");
-        let user_manager = UserManager::new(db.pool.clone());
+        let user_manager = UserManager::new(Arc::new(db.pool.clone()));
 
         let user = TestUserBuilder::new(12345)
             .username("testuser")
@@ -294,7 +414,7 @@ mod tests {
         let db = TestDatabase::create_fresh()
             .await
             .expect("Failed to create test database");
-        let user_manager = UserManager::new(db.pool.clone());
+        let user_manager = UserManager::new(Arc::new(db.pool.clone()));
 
         let user = TestUserBuilder::new(12345)
             .create(&user_manager, None)
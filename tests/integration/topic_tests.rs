@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use tg_main::analysis::ForumTopic;
+use tg_main::user_manager::{AnalysisSource, CreditTransactionKind, UserManager};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_topic_is_kept_for_recovery_and_regeneration() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
+    let bot = MockTelegramBot::new();
+
+    let (admin, _) = bot
+        .simulate_user_start(&user_manager, 900, Some("admin"), None, None, None)
+        .await
+        .expect("Failed to create user");
+    user_manager
+        .add_credits(admin.id, 2, CreditTransactionKind::Purchase, Some("charge"))
+        .await
+        .expect("Failed to add credits");
+
+    let topic = ForumTopic {
+        thread_id: 42,
+        name: "Hiring".to_string(),
+    };
+    let scoped = user_manager
+        .create_pending_analysis(
+            admin.id,
+            "@rust_chat",
+            "professional",
+            "small",
+            None,
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+    user_manager
+        .set_analysis_topic(scoped, &topic)
+        .await
+        .expect("Failed to store topic");
+    let whole_group = user_manager
+        .create_pending_analysis(
+            admin.id,
+            "@rust_chat",
+            "roast",
+            "small",
+            None,
+            None,
+            AnalysisSource::Bot,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis");
+
+    let pending = user_manager
+        .get_pending_analyses(AnalysisSource::Bot)
+        .await
+        .expect("Failed to get pending analyses");
+    let topic_of = |id: i32| {
+        pending
+            .iter()
+            .find(|analysis| analysis.id == id)
+            .and_then(|analysis| analysis.topic.clone())
+    };
+    assert_eq!(topic_of(scoped), Some(topic.clone()));
+    assert_eq!(topic_of(whole_group), None);
+
+    user_manager
+        .atomic_complete_analysis(scoped, admin.id, 1)
+        .await
+        .expect("Failed to complete analysis");
+    user_manager
+        .mark_analysis_partial(scoped)
+        .await
+        .expect("Failed to mark analysis partial");
+    let regeneration = user_manager
+        .claim_partial_regeneration(scoped, admin.id)
+        .await
+        .expect("Failed to claim regeneration")
+        .expect("Partial analysis should be claimable");
+    assert_eq!(regeneration.topic, Some(topic));
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tg_main::analysis::{AnalysisDepth, ForumTopic};
+use tg_main::user_manager::UserManager;
+use tg_main::user_sessions::{UserSession, UserSessions, INPUT_TIMEOUT};
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+#[tokio::test]
+async fn test_session_survives_a_restart() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+
+    let sessions = UserSessions::new(pool.clone());
+    sessions
+        .insert(
+            1800,
+            UserSession {
+                channel_name: Some("@forum_group".to_string()),
+                topic: Some(ForumTopic {
+                    thread_id: 42,
+                    name: "Offtopic".to_string(),
+                }),
+                ..Default::default()
+            },
+        )
+        .await;
+    sessions
+        .update(1800, |session| {
+            session.depth = AnalysisDepth::Deep;
+            session.awaiting_focus = true;
+        })
+        .await;
+
+    // a new store is what the bot has after a restart
+    let restarted = UserSessions::new(pool.clone());
+    let session = restarted
+        .get(1800)
+        .await
+        .expect("The session should be restored");
+    assert_eq!(session.channel_name.as_deref(), Some("@forum_group"));
+    assert_eq!(session.depth, AnalysisDepth::Deep);
+    assert!(session.awaiting_focus);
+    assert_eq!(session.topic.map(|topic| topic.thread_id), Some(42));
+
+    let focus = restarted
+        .update_existing(1800, |session| {
+            session.focus = Some("hiring posts".to_string());
+            session.depth
+        })
+        .await;
+    assert_eq!(focus, Some(AnalysisDepth::Deep));
+    assert!(restarted.update_existing(1801, |_| ()).await.is_none());
+    assert!(restarted.get(1801).await.is_none());
+
+    assert!(restarted.remove(1800).await.is_some());
+    assert!(UserSessions::new(pool).get(1800).await.is_none());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_expired_sessions_are_neither_restored_nor_kept() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+
+    let sessions = UserSessions::new(pool.clone());
+    for telegram_user_id in [1810, 1811] {
+        sessions
+            .insert(
+                telegram_user_id,
+                UserSession {
+                    batch: vec!["@first_channel".to_string(), "@second_channel".to_string()],
+                    ..Default::default()
+                },
+            )
+            .await;
+    }
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE user_sessions SET expires_at = NOW() - INTERVAL '1 minute'
+             WHERE telegram_user_id = 1810",
+            &[],
+        )
+        .await
+        .expect("Failed to expire session");
+
+    let restarted = UserSessions::new(pool);
+    assert!(restarted.get(1810).await.is_none());
+    assert_eq!(
+        restarted.get(1811).await.map(|session| session.batch.len()),
+        Some(2)
+    );
+    assert_eq!(restarted.purge_expired().await.unwrap(), 1);
+    let remaining: i64 = client
+        .query_one("SELECT COUNT(*) FROM user_sessions", &[])
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(remaining, 1);
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_sessions_idle_waiting_for_input_expire_with_a_notice() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    MockTelegramBot::new()
+        .simulate_user_start(&user_manager, 1820, Some("stuck"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    let sessions = UserSessions::new(pool);
+    sessions
+        .insert(
+            1820,
+            UserSession {
+                channel_name: Some("@stuck_channel".to_string()),
+                awaiting_focus: true,
+                ..Default::default()
+            },
+        )
+        .await;
+    sessions
+        .insert(
+            1821,
+            UserSession {
+                batch: vec!["@fresh_channel".to_string()],
+                ..Default::default()
+            },
+        )
+        .await;
+    // nothing the bot waits on, so nothing to expire
+    sessions.insert(1822, UserSession::default()).await;
+
+    let client = db.pool.get().await.expect("Failed to get database client");
+    client
+        .execute(
+            "UPDATE user_sessions SET updated_at = NOW() - INTERVAL '2 hours'
+             WHERE telegram_user_id IN (1820, 1822)",
+            &[],
+        )
+        .await
+        .expect("Failed to backdate sessions");
+    client
+        .execute(
+            "UPDATE users SET language = 'ru' WHERE telegram_user_id = 1820",
+            &[],
+        )
+        .await
+        .expect("Failed to set language");
+
+    let expired = sessions
+        .expire_idle(Duration::from_secs(60))
+        .await
+        .expect("Failed to expire sessions");
+    assert_eq!(expired, vec![1820]);
+    // the one in memory was only just touched, so it stays until a restart
+    assert!(UserSessions::new(Arc::new(db.pool.clone()))
+        .get(1820)
+        .await
+        .is_none());
+    assert!(sessions.get(1821).await.is_some());
+
+    let queued = client
+        .query(
+            "SELECT telegram_user_id, message FROM message_queue ORDER BY id",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].get::<_, i64>(0), 1820);
+    assert_eq!(
+        queued[0].get::<_, String>(1),
+        tg_main::localization::Lang::Ru.session_expired()
+    );
+
+    // a second sweep doesn't notify again
+    assert!(sessions
+        .expire_idle(INPUT_TIMEOUT)
+        .await
+        .unwrap()
+        .is_empty());
+
+    drop(client);
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
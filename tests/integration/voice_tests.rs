@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use tg_main::voice::{VoiceConfig, VoiceSummaries};
+
+use super::TestDatabase;
+
+#[tokio::test]
+async fn test_voice_summary_file_ids_are_cached_per_result_and_type() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let voice = VoiceSummaries::new(
+        Arc::new(db.pool.clone()),
+        VoiceConfig {
+            model: "tts-model".to_string(),
+            voice: "Kore".to_string(),
+        },
+    );
+
+    assert_eq!(
+        voice
+            .cached_file_id("key", "roast")
+            .await
+            .expect("Failed to read voice summary"),
+        None
+    );
+
+    voice
+        .store_file_id("key", "roast", "first-file")
+        .await
+        .expect("Failed to store voice summary");
+    // a summary recorded twice keeps the latest file
+    voice
+        .store_file_id("key", "roast", "second-file")
+        .await
+        .expect("Failed to store voice summary");
+
+    assert_eq!(
+        voice
+            .cached_file_id("key", "roast")
+            .await
+            .expect("Failed to read voice summary")
+            .as_deref(),
+        Some("second-file")
+    );
+    assert_eq!(
+        voice
+            .cached_file_id("key", "personal")
+            .await
+            .expect("Failed to read voice summary"),
+        None
+    );
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use tg_main::channel_stats::ChannelStatsManager;
+use tg_main::warmup::WarmupManager;
+
+use super::TestDatabase;
+
+const OWNER_ID: i64 = 1000;
+
+#[tokio::test]
+async fn test_warm_list_combines_pins_exclusions_and_trending_channels() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let stats = ChannelStatsManager::new(pool.clone());
+    let warmup = WarmupManager::new(pool);
+
+    for channel in [
+        "@rustlang",
+        "@rustlang",
+        "@golang",
+        "@python",
+        "@python",
+        "@python",
+    ] {
+        stats
+            .record_analysis(channel, None)
+            .await
+            .expect("Failed to record analysis");
+    }
+    warmup
+        .pin("@MyChannel", OWNER_ID)
+        .await
+        .expect("Failed to pin channel");
+    warmup
+        .exclude("@python", OWNER_ID)
+        .await
+        .expect("Failed to exclude channel");
+
+    let (pinned, excluded) = warmup.overrides().await.expect("Failed to load overrides");
+    assert_eq!(pinned, vec!["@mychannel".to_string()]);
+    assert_eq!(excluded, vec!["@python".to_string()]);
+    // the excluded channel doesn't take one of the two trending places
+    assert_eq!(
+        warmup
+            .warm_list(&stats, 2)
+            .await
+            .expect("Failed to load warm list"),
+        vec!["@mychannel", "@rustlang", "@golang"]
+    );
+
+    // an exclusion replaces the pin of the same channel, and a reset drops either
+    warmup
+        .exclude("@mychannel", OWNER_ID)
+        .await
+        .expect("Failed to exclude channel");
+    assert!(warmup.reset("@Python").await.expect("Failed to reset"));
+    assert!(!warmup.reset("@python").await.expect("Failed to reset"));
+    assert_eq!(
+        warmup
+            .warm_list(&stats, 2)
+            .await
+            .expect("Failed to load warm list"),
+        vec!["@python", "@rustlang"]
+    );
+}
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+use tg_main::job_queue::{JobQueue, JobStatus};
+use tg_main::localization::Lang;
+use tg_main::user_manager::{AnalysisSource, UserManager, UserManagerError};
+use tg_main::watchdog::fail_stuck_analyses;
+
+use super::{mock_bot::MockTelegramBot, TestDatabase};
+
+async fn pending_analysis(
+    user_manager: &UserManager,
+    user_id: i32,
+    channel: &str,
+    source: AnalysisSource,
+) -> i32 {
+    user_manager
+        .create_pending_analysis(
+            user_id,
+            channel,
+            "roast",
+            "medium",
+            Some("de"),
+            None,
+            source,
+            None,
+        )
+        .await
+        .expect("Failed to create analysis")
+}
+
+async fn backdate(db: &TestDatabase, analysis_id: i32, minutes: i32) {
+    let client = db.pool.get().await.unwrap();
+    client
+        .execute(
+            "UPDATE user_analyses SET pending_since = NOW() - make_interval(mins => $2)
+             WHERE id = $1",
+            &[&analysis_id, &minutes],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_stuck_analyses_fail_uncharged_and_notify() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let queue = JobQueue::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1800, Some("stuck"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    let stuck = pending_analysis(&user_manager, user.id, "@stuck", AnalysisSource::Bot).await;
+    let api = pending_analysis(&user_manager, user.id, "@api", AnalysisSource::Api).await;
+    let fresh = pending_analysis(&user_manager, user.id, "@fresh", AnalysisSource::Bot).await;
+    queue
+        .enqueue(stuck, None, None, false)
+        .await
+        .expect("Failed to queue analysis");
+    backdate(&db, stuck, 90).await;
+    backdate(&db, api, 90).await;
+    backdate(&db, fresh, 30).await;
+
+    let mut timed_out = fail_stuck_analyses(&db.pool, 60)
+        .await
+        .expect("Failed to check for stuck analyses");
+    timed_out.sort();
+    assert_eq!(timed_out, vec![stuck, api]);
+    assert!(fail_stuck_analyses(&db.pool, 60)
+        .await
+        .expect("Failed to check for stuck analyses")
+        .is_empty());
+
+    let client = db.pool.get().await.unwrap();
+    let statuses = client
+        .query(
+            "SELECT id, status FROM user_analyses WHERE id = ANY($1) ORDER BY id",
+            &[&vec![stuck, api, fresh]],
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| (row.get::<_, i32>(0), row.get::<_, String>(1)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        vec![
+            (stuck, "failed".to_string()),
+            (api, "failed".to_string()),
+            (fresh, "pending".to_string())
+        ]
+    );
+    assert_eq!(
+        queue
+            .statuses(&[stuck])
+            .await
+            .expect("Failed to get statuses"),
+        vec![(stuck, JobStatus::Failed)]
+    );
+
+    // only the bot user is told, in the language of the analysis
+    let notices = client
+        .query(
+            "SELECT telegram_user_id, message FROM message_queue ORDER BY id",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(notices.len(), 1);
+    assert_eq!(notices[0].get::<_, i64>(0), 1800);
+    assert_eq!(
+        notices[0].get::<_, String>(1),
+        Lang::De.analysis_timed_out("@stuck")
+    );
+    drop(client);
+
+    // a runner that finishes after the timeout can't charge for it
+    assert!(matches!(
+        user_manager.atomic_complete_analysis(stuck, user.id, 1).await,
+        Err(UserManagerError::NotPending(id)) if id == stuck
+    ));
+    let (after, _) = bot
+        .simulate_user_start(&user_manager, 1800, Some("stuck"), None, None, None)
+        .await
+        .expect("Failed to get user");
+    assert_eq!(after.analysis_credits, user.analysis_credits);
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
+
+#[tokio::test]
+async fn test_requeued_analyses_restart_the_clock() {
+    let db = TestDatabase::create_fresh()
+        .await
+        .expect("Failed to create test database");
+    let pool = Arc::new(db.pool.clone());
+    let user_manager = UserManager::new(pool.clone());
+    let queue = JobQueue::new(pool);
+    let bot = MockTelegramBot::new();
+
+    let (user, _) = bot
+        .simulate_user_start(&user_manager, 1801, Some("resumed"), None, None, None)
+        .await
+        .expect("Failed to create user");
+
+    // resumed by recovery after a long restart
+    let resumed = pending_analysis(&user_manager, user.id, "@resumed", AnalysisSource::Bot).await;
+    backdate(&db, resumed, 90).await;
+    queue
+        .enqueue(resumed, None, None, false)
+        .await
+        .expect("Failed to queue analysis");
+
+    // reopened long after it was first requested
+    let reopened = pending_analysis(&user_manager, user.id, "@reopened", AnalysisSource::Bot).await;
+    user_manager
+        .mark_analysis_failed(reopened)
+        .await
+        .expect("Failed to fail analysis");
+    backdate(&db, reopened, 90).await;
+    user_manager
+        .reopen_failed_analysis(reopened, user.id, i32::MAX)
+        .await
+        .expect("Failed to reopen analysis")
+        .expect("The analysis should reopen");
+
+    assert!(fail_stuck_analyses(&db.pool, 60)
+        .await
+        .expect("Failed to check for stuck analyses")
+        .is_empty());
+
+    db.cleanup().await.expect("Failed to cleanup test database");
+}
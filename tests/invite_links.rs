@@ -0,0 +1,108 @@
+// Tests for recognizing private channel invite links and naming their channels
+use std::time::Duration;
+use tg_main::analysis::{invite_channel_name, invite_hash, AnalysisError};
+use tg_main::bot::TelegramBot;
+use tg_main::localization::Lang;
+use tg_main::recovery::RecoveryPlan;
+use tg_main::user_manager::PendingAnalysis;
+use tg_main::utils::ResultPresenter;
+
+#[test]
+fn test_invite_links_are_parsed() {
+    for link in [
+        "https://t.me/+AbCdEf_12-3xyz",
+        "http://t.me/+AbCdEf_12-3xyz",
+        "t.me/+AbCdEf_12-3xyz",
+        "https://t.me/joinchat/AbCdEf_12-3xyz",
+        "https://t.me/+AbCdEf_12-3xyz/",
+    ] {
+        assert_eq!(
+            TelegramBot::parse_invite_link(link).as_deref(),
+            Some("+AbCdEf_12-3xyz"),
+            "{:?} should parse",
+            link
+        );
+    }
+}
+
+#[test]
+fn test_public_links_and_malformed_invites_are_rejected() {
+    for text in [
+        "@rustlang",
+        "https://t.me/rustlang",
+        "https://t.me/+short",
+        "https://t.me/+",
+        "https://t.me/+AbCdEf 12",
+        "https://t.me/+AbCdEf_12-3xyz/15",
+        "https://example.com/+AbCdEf_12-3xyz",
+        "see https://t.me/+AbCdEf_12-3xyz",
+    ] {
+        assert_eq!(
+            TelegramBot::parse_invite_link(text),
+            None,
+            "{:?} should not parse",
+            text
+        );
+    }
+}
+
+#[test]
+fn test_invite_hash_roundtrip() {
+    let name = invite_channel_name("AbCdEf_12-3xyz");
+    assert_eq!(invite_hash(&name), Some("AbCdEf_12-3xyz"));
+    assert_eq!(invite_hash("@rustlang"), None);
+    assert_eq!(invite_hash("+"), None);
+}
+
+#[test]
+fn test_private_channels_are_not_named_in_results() {
+    assert_eq!(
+        ResultPresenter::target_label("+AbCdEf_12-3xyz", Lang::En),
+        "private channel"
+    );
+    assert_eq!(
+        ResultPresenter::target_label("+AbCdEf_12-3xyz", Lang::Ru),
+        "приватный канал"
+    );
+    assert_eq!(
+        ResultPresenter::target_label("@rustlang", Lang::En),
+        "@rustlang"
+    );
+}
+
+#[test]
+fn test_interrupted_private_analysis_is_resumed() {
+    let pending = PendingAnalysis {
+        id: 1,
+        user_id: 1,
+        telegram_user_id: 42,
+        channel_name: "+AbCdEf_12-3xyz".to_string(),
+        analysis_type: "professional".to_string(),
+        depth: "small".to_string(),
+        language: Some("en".to_string()),
+        focus: None,
+        topic: None,
+        age: Duration::from_secs(60),
+        second_opinion_of: None,
+    };
+    assert!(matches!(
+        RecoveryPlan::for_pending(&pending),
+        RecoveryPlan::Resume(_)
+    ));
+}
+
+#[test]
+fn test_invite_errors_explain_next_step() {
+    for lang in [Lang::En, Lang::Ru] {
+        let invalid = lang.error_analysis_failed(&AnalysisError::InviteInvalid, "private channel");
+        let requested =
+            lang.error_analysis_failed(&AnalysisError::JoinRequestSent, "private channel");
+        assert_ne!(invalid, requested);
+    }
+    assert!(Lang::En
+        .error_analysis_failed(&AnalysisError::InviteInvalid, "private channel")
+        .contains("new link"));
+    assert!(Lang::En
+        .error_analysis_failed(&AnalysisError::JoinRequestSent, "private channel")
+        .contains("approves"));
+}
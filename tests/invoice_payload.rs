@@ -0,0 +1,38 @@
+// Tests for the invoice payloads of packages and subscriptions
+use tg_main::invoice_payload::InvoicePayload;
+
+#[test]
+fn test_payloads_roundtrip() {
+    assert_eq!(InvoicePayload::Package(10).to_string(), "credits_10");
+    assert_eq!(
+        InvoicePayload::Subscription(30).to_string(),
+        "subscription_30"
+    );
+    for credits in [1, 30, i32::MAX] {
+        for payload in [
+            InvoicePayload::Package(credits),
+            InvoicePayload::Subscription(credits),
+        ] {
+            assert_eq!(InvoicePayload::parse(&payload.to_string()), Some(payload));
+        }
+    }
+}
+
+#[test]
+fn test_malformed_payloads_are_rejected() {
+    for payload in [
+        "credits_",
+        "credits_0",
+        "credits_-5",
+        "subscription_",
+        "subscription_0",
+        "subscription_+5",
+        "subscription_-5",
+        "subscription_99999999999",
+        "subscription_monthly",
+        "bulk_10",
+        "",
+    ] {
+        assert_eq!(InvoicePayload::parse(payload), None, "{}", payload);
+    }
+}
@@ -0,0 +1,69 @@
+// Tests for the post-generation output language check
+use tg_main::analysis::MessageDict;
+use tg_main::cache::AnalysisResult;
+use tg_main::llm::language_check::{dominant_script, result_script, LanguageTarget, Script};
+use tg_main::prompts::analysis::OutputLanguage;
+
+const ENGLISH: &str = "The author writes about distributed systems and shares practical advice on running databases in production.";
+const RUSSIAN: &str = "Автор пишет о распределённых системах и делится практическими советами по эксплуатации баз данных.";
+
+fn message(text: &str) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: None,
+        message: Some(text.to_string()),
+        images: None,
+        thread_id: None,
+    }
+}
+
+fn result(text: &str) -> AnalysisResult {
+    AnalysisResult {
+        professional: Some(text.to_string()),
+        personal: Some(text.to_string()),
+        roast: Some(text.to_string()),
+        trends: None,
+        messages_count: 1,
+        model: Some("gemini-2.5-flash".to_string()),
+        report: None,
+        removed_messages: 0,
+        partial: false,
+        prompt_version: None,
+        facts: None,
+    }
+}
+
+#[test]
+fn test_dominant_script() {
+    assert_eq!(dominant_script(ENGLISH), Some(Script::Latin));
+    assert_eq!(dominant_script(RUSSIAN), Some(Script::Cyrillic));
+    // a few english terms don't change the script of a russian text
+    assert_eq!(
+        dominant_script(&format!("{} Rust, Kubernetes, PostgreSQL.", RUSSIAN)),
+        Some(Script::Cyrillic)
+    );
+    // too short or evenly mixed texts are undecided
+    assert_eq!(dominant_script("ok, спасибо"), None);
+    assert_eq!(dominant_script(&format!("{}\n{}", ENGLISH, RUSSIAN)), None);
+}
+
+#[test]
+fn test_language_target_resolution() {
+    let russian_channel = vec![message(RUSSIAN), message(RUSSIAN)];
+
+    // explicit preferences win over the channel's language
+    let target = LanguageTarget::resolve(OutputLanguage::English, &russian_channel).unwrap();
+    assert_eq!(target.script, Script::Latin);
+
+    let target = LanguageTarget::resolve(OutputLanguage::Channel, &russian_channel).unwrap();
+    assert_eq!(target.script, Script::Cyrillic);
+
+    // nothing to check against when the channel has no dominant script
+    assert!(LanguageTarget::resolve(OutputLanguage::Channel, &[message("👍")]).is_none());
+}
+
+#[test]
+fn test_result_script() {
+    assert_eq!(result_script(&result(ENGLISH)), Some(Script::Latin));
+    assert_eq!(result_script(&result(RUSSIAN)), Some(Script::Cyrillic));
+}
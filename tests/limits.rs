@@ -0,0 +1,88 @@
+// Tests for the tunable prices and limits
+use tg_main::analysis::AnalysisDepth;
+use tg_main::config::Settings;
+use tg_main::limits::{Limits, LimitsError, LIMIT_NAMES};
+
+#[test]
+fn test_defaults_match_the_published_prices() {
+    let limits = Limits::default();
+    assert_eq!(limits.subscription_price, 1000);
+    assert_eq!(limits.subscription_credits, 30);
+    assert_eq!(limits.depth_credits(AnalysisDepth::Small), 1);
+    assert_eq!(limits.depth_credits(AnalysisDepth::Medium), 2);
+    assert_eq!(limits.depth_credits(AnalysisDepth::Deep), 3);
+}
+
+#[test]
+fn test_second_opinions_cost_half_rounded_up() {
+    let mut limits = Limits::default();
+    assert_eq!(limits.second_opinion_credits(AnalysisDepth::Small), 1);
+    assert_eq!(limits.second_opinion_credits(AnalysisDepth::Medium), 1);
+    assert_eq!(limits.second_opinion_credits(AnalysisDepth::Deep), 2);
+    limits
+        .set("deep_depth_credits", 10)
+        .expect("Failed to set deep credits");
+    assert_eq!(limits.second_opinion_credits(AnalysisDepth::Deep), 5);
+}
+
+#[test]
+fn test_one_analysis_runs_at_a_time_by_default() {
+    assert_eq!(Limits::default().max_running_analyses, 1);
+}
+
+#[test]
+fn test_stuck_analyses_time_out_after_an_hour_by_default() {
+    assert_eq!(Limits::default().analysis_timeout_minutes, 60);
+}
+
+#[test]
+fn test_every_limit_can_be_set_by_name() {
+    let mut limits = Limits::default();
+    for (i, name) in LIMIT_NAMES.iter().enumerate() {
+        limits
+            .set(name, 1000 + i as i64)
+            .unwrap_or_else(|e| panic!("Failed to set {}: {}", name, e));
+    }
+    let values = limits.values();
+    assert_eq!(values.len(), LIMIT_NAMES.len());
+    for (i, (name, value)) in values.into_iter().enumerate() {
+        assert_eq!(name, LIMIT_NAMES[i]);
+        assert_eq!(value, 1000 + i as i64);
+    }
+}
+
+#[test]
+fn test_invalid_values_are_rejected() {
+    let mut limits = Limits::default();
+    assert_eq!(
+        limits.set("top_channels", 0),
+        Err(LimitsError::InvalidValue("top_channels".to_string(), 0))
+    );
+    assert_eq!(
+        limits.set("deep_depth_credits", i64::from(i32::MAX) + 1),
+        Err(LimitsError::InvalidValue(
+            "deep_depth_credits".to_string(),
+            i64::from(i32::MAX) + 1
+        ))
+    );
+    assert_eq!(
+        limits.set("free_lunches", 1),
+        Err(LimitsError::UnknownLimit("free_lunches".to_string()))
+    );
+    assert_eq!(limits, Limits::default());
+}
+
+#[test]
+fn test_env_overrides_skip_bad_values() {
+    std::env::set_var("LIMIT_TOP_CHANNELS", "25");
+    std::env::set_var("LIMIT_BALANCE_PAGE_SIZE", "-3");
+    std::env::set_var("LIMIT_INLINE_RESULTS", "many");
+    let mut limits = Limits::default();
+    limits.apply_settings(&Settings::default());
+    assert_eq!(limits.top_channels, 25);
+    assert_eq!(
+        limits.balance_page_size,
+        Limits::default().balance_page_size
+    );
+    assert_eq!(limits.inline_results, Limits::default().inline_results);
+}
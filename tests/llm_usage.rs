@@ -0,0 +1,32 @@
+// Tests for pricing LLM calls
+use tg_main::llm::usage::{estimate_cost_usd, model_price};
+
+#[test]
+fn test_models_are_priced_by_family() {
+    assert_eq!(model_price("gemini-2.5-flash-lite").input_per_million, 0.10);
+    assert_eq!(model_price("gemini-2.5-flash").output_per_million, 2.50);
+    assert_eq!(
+        model_price("gemini-3-flash-preview").input_per_million,
+        0.50
+    );
+}
+
+#[test]
+fn test_unknown_models_get_the_highest_price() {
+    let fallback = model_price("some-future-model");
+    for model in [
+        "gemini-2.5-flash-lite",
+        "gemini-2.5-flash",
+        "gemini-3-flash-preview",
+    ] {
+        assert!(fallback.input_per_million >= model_price(model).input_per_million);
+        assert!(fallback.output_per_million >= model_price(model).output_per_million);
+    }
+}
+
+#[test]
+fn test_cost_counts_input_and_output_tokens() {
+    let cost = estimate_cost_usd("gemini-2.5-flash", 1_000_000, 100_000);
+    assert!((cost - 0.55).abs() < 1e-9);
+    assert_eq!(estimate_cost_usd("gemini-2.5-flash", 0, 0), 0.0);
+}
@@ -0,0 +1,125 @@
+// Tests for picking the bot's UI language and for the coverage of its translations
+use teloxide::types::InlineKeyboardMarkup;
+use teloxide::utils::command::BotCommands;
+use tg_main::analysis::{AnalysisDepth, ForumTopic};
+use tg_main::bot::Command;
+use tg_main::handlers::CallbackHandler;
+use tg_main::limits::Limits;
+use tg_main::llm::ModelTier;
+use tg_main::localization::Lang;
+use tg_main::packages::default_packages;
+use tg_main::prompts::analysis::OutputLanguage;
+
+/// labels of every keyboard whose buttons are all translated
+fn keyboard_labels(lang: Lang) -> Vec<String> {
+    let limits = Limits::default();
+    let topic = ForumTopic {
+        thread_id: 7,
+        name: "news".to_string(),
+    };
+    let keyboards: Vec<InlineKeyboardMarkup> = vec![
+        CallbackHandler::create_payment_keyboard(&default_packages(), lang),
+        CallbackHandler::create_model_tier_keyboard(ModelTier::Auto, false, lang),
+        CallbackHandler::create_output_language_keyboard(OutputLanguage::Channel, lang),
+        CallbackHandler::create_api_key_keyboard(lang),
+        CallbackHandler::create_analysis_selection_keyboard(
+            "@rust_lang",
+            AnalysisDepth::default(),
+            &limits,
+            lang,
+        ),
+        CallbackHandler::create_batch_keyboard(lang),
+        CallbackHandler::create_self_done_keyboard(lang),
+        CallbackHandler::create_showcase_keyboard(1, lang),
+        CallbackHandler::create_self_analysis_keyboard(lang),
+        CallbackHandler::create_cross_group_consent_keyboard(lang),
+        CallbackHandler::create_cross_group_keyboard(true, lang),
+        CallbackHandler::create_group_scope_keyboard(&topic, None, lang),
+    ];
+    keyboards
+        .into_iter()
+        .flat_map(|keyboard| keyboard.inline_keyboard)
+        .flatten()
+        .map(|button| button.text)
+        .collect()
+}
+
+#[test]
+fn test_from_code_matches_the_primary_subtag() {
+    assert_eq!(Lang::from_code(Some("ru")), Lang::Ru);
+    assert_eq!(Lang::from_code(Some("uk")), Lang::Uk);
+    assert_eq!(Lang::from_code(Some("es-419")), Lang::Es);
+    assert_eq!(Lang::from_code(Some("de_AT")), Lang::De);
+    assert_eq!(Lang::from_code(Some("DE")), Lang::De);
+}
+
+#[test]
+fn test_unknown_or_missing_codes_fall_back_to_english() {
+    assert_eq!(Lang::from_code(Some("fr")), Lang::En);
+    assert_eq!(Lang::from_code(Some("")), Lang::En);
+    assert_eq!(Lang::from_code(None), Lang::En);
+}
+
+#[test]
+fn test_codes_roundtrip() {
+    for lang in Lang::ALL {
+        assert_eq!(Lang::from_code(Some(lang.code())), lang);
+    }
+}
+
+#[test]
+fn test_override_wins_over_telegram_language() {
+    assert_eq!(Lang::resolve(Some("de"), Some("ru")), Lang::De);
+    assert_eq!(Lang::resolve(None, Some("ru")), Lang::Ru);
+    assert_eq!(Lang::resolve(None, None), Lang::En);
+}
+
+#[test]
+fn test_analysis_type_names_are_translated() {
+    // spanish and german names already say "analysis", so the header doesn't repeat it
+    assert_eq!(
+        Lang::Es.analysis_type_header("professional"),
+        "💼 <b>Análisis profesional:</b>\n\n"
+    );
+    assert_eq!(
+        Lang::De.analysis_type_header("trends"),
+        "📈 <b>Trendanalyse:</b>\n\n"
+    );
+    assert_eq!(
+        Lang::Uk.analysis_type_header("roast"),
+        "🔥 <b>Роаст аналіз:</b>\n\n"
+    );
+}
+
+#[test]
+fn test_keyboards_have_no_english_labels_left() {
+    let english = keyboard_labels(Lang::En);
+    for lang in Lang::ALL.into_iter().filter(|lang| *lang != Lang::En) {
+        let labels = keyboard_labels(lang);
+        assert_eq!(labels.len(), english.len());
+        for (label, english) in labels.iter().zip(&english) {
+            assert_ne!(label, english, "{:?} keyboard label is untranslated", lang);
+        }
+    }
+}
+
+#[test]
+fn test_group_admin_commands_are_described_in_every_language() {
+    let commands = Command::bot_commands()
+        .into_iter()
+        .map(|command| command.command.trim_start_matches('/').to_string())
+        .filter(|command| matches!(command.as_str(), "analyze_group" | "analyze"))
+        .collect::<Vec<_>>();
+    assert_eq!(commands.len(), 2);
+    for command in commands.iter().map(String::as_str) {
+        assert_eq!(Lang::En.command_description(command), None);
+        for lang in Lang::ALL.into_iter().filter(|lang| *lang != Lang::En) {
+            assert!(
+                lang.command_description(command).is_some(),
+                "/{} has no {:?} description",
+                command,
+                lang
+            );
+        }
+    }
+}
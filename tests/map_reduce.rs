@@ -0,0 +1,141 @@
+// Tests for the map-reduce analysis of channels too large for one prompt
+use tg_main::analysis::MessageDict;
+use tg_main::facts::QuickFacts;
+use tg_main::llm::analysis_query::{
+    batch_messages, needs_map_reduce, query_and_parse_large_analysis, summarize_batches,
+    BATCH_CHARS, MAP_REDUCE_CHARS,
+};
+use tg_main::llm::routing::ModelRoute;
+use tg_main::llm::ModelTier;
+use tg_main::prompts::analysis::{
+    generate_batch_summary_prompt, generate_summaries_analysis_prompt, BatchSummary, OutputLanguage,
+};
+use tg_main::prompts::versions::PromptVersion;
+use tg_main::retry_budget::RetryBudget;
+use tokio::sync::watch;
+
+fn post(day: u32, chars: usize) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: Some(format!("2026-09-{:02}", day)),
+        message: Some("x".repeat(chars)),
+        images: None,
+        thread_id: None,
+    }
+}
+
+#[test]
+fn test_batches_keep_order_and_size() {
+    let messages = vec![
+        post(1, 40),
+        post(2, 40),
+        post(3, 40),
+        post(4, 150),
+        post(5, 10),
+    ];
+    let batches = batch_messages(&messages, 100);
+
+    let sizes: Vec<_> = batches.iter().map(|batch| batch.len()).collect();
+    // a post longer than a batch gets one of its own instead of being cut
+    assert_eq!(sizes, vec![2, 1, 1, 1]);
+    assert_eq!(batches[0][0].date, messages[0].date);
+    assert_eq!(batches[3][0].date, messages[4].date);
+
+    assert!(batch_messages(&[], 100).is_empty());
+    assert_eq!(batch_messages(&messages, 1_000).len(), 1);
+}
+
+#[test]
+fn test_only_large_corpora_take_the_map_reduce_path() {
+    let posts = |count| {
+        (0..count)
+            .map(|i| post(1 + i % 28, 1_500))
+            .collect::<Vec<_>>()
+    };
+    // 200 posts of 1500 characters are right at the limit
+    assert_eq!(200 * 1_500, MAP_REDUCE_CHARS);
+    assert!(!needs_map_reduce(&posts(200)));
+    assert!(needs_map_reduce(&posts(201)));
+    // and then span several batches
+    assert_eq!(batch_messages(&posts(201), BATCH_CHARS).len(), 6);
+}
+
+#[test]
+fn test_batch_summary_prompt() {
+    let prompt = generate_batch_summary_prompt(&[post(3, 50)], 2, 5, Some("news")).unwrap();
+    assert!(prompt.contains("5 consecutive batches and this is batch 2"));
+    assert!(prompt.contains("verbatim quotes"));
+    assert!(prompt.contains("TOPIC SCOPE"));
+    assert!(prompt.contains("2026-09-03"));
+}
+
+#[test]
+fn test_summaries_prompt_reads_summaries_and_whole_corpus_facts() {
+    let summaries = vec![
+        BatchSummary {
+            batch: 1,
+            period: Some("2026-09-01 – 2026-09-09".to_string()),
+            summary: "Writes about compilers.".to_string(),
+        },
+        BatchSummary {
+            batch: 2,
+            period: None,
+            summary: "Argues about borrow checking.".to_string(),
+        },
+    ];
+    let facts = QuickFacts {
+        keywords: vec![("compilers".to_string(), 40)],
+        hashtags: vec![],
+        weekdays: [3, 0, 0, 0, 0, 0, 0],
+    };
+    let prompt = generate_summaries_analysis_prompt(
+        &summaries,
+        &facts,
+        Some("career"),
+        None,
+        OutputLanguage::English,
+        PromptVersion::base(),
+    )
+    .unwrap();
+    for text in [&prompt.json, &prompt.tagged] {
+        assert!(text.contains("summarized in 2 consecutive batches"));
+        assert!(text.contains("Argues about borrow checking."));
+        assert!(text.contains("2026-09-01 – 2026-09-09"));
+        assert!(text.contains("compilers (40)"));
+        assert!(text.contains("REQUESTER FOCUS"));
+        assert!(!text.contains("Messages to analyze"));
+    }
+}
+
+#[tokio::test]
+async fn test_large_corpus_is_summarized_then_analyzed() {
+    std::env::set_var("LLM_MOCK", "1");
+    let messages: Vec<_> = (0..30).map(|i| post(1 + i % 28, 10_000)).collect();
+    let route = ModelRoute::for_tier(ModelTier::Auto);
+    let budget = RetryBudget::unbounded();
+    let (progress, updates) = watch::channel((0, 0));
+
+    let summaries = summarize_batches(&messages, None, &route, &budget, Some(&progress))
+        .await
+        .expect("Mock batches should summarize");
+    let batches = batch_messages(&messages, BATCH_CHARS).len();
+    assert_eq!(summaries.len(), batches);
+    let order: Vec<_> = summaries.iter().map(|summary| summary.batch).collect();
+    assert_eq!(order, (1..=batches).collect::<Vec<_>>());
+    assert_eq!(*updates.borrow(), (batches, batches));
+
+    let result = query_and_parse_large_analysis(
+        &messages,
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        &route,
+        &budget,
+        None,
+    )
+    .await
+    .expect("Mock analysis should complete");
+    assert!(!result.partial);
+    assert!(result.professional.is_some());
+}
@@ -0,0 +1,134 @@
+// Tests for the MarkdownV2 rendering and entity-safe splitting of messages
+use tg_main::utils::MessageFormatter;
+
+// every chunk closes what it opens, in order
+fn assert_balanced_html(chunk: &str) {
+    let mut open = Vec::new();
+    let mut rest = chunk;
+    while let Some(start) = rest.find('<') {
+        let end = rest[start..].find('>').expect("a tag was cut in half") + start;
+        let tag = &rest[start + 1..end];
+        match tag.strip_prefix('/') {
+            Some(name) => assert_eq!(open.pop(), Some(name.to_string()), "{}", chunk),
+            None => open.push(tag.split_whitespace().next().unwrap().to_string()),
+        }
+        rest = &rest[end + 1..];
+    }
+    assert!(open.is_empty(), "unclosed tags in {}", chunk);
+}
+
+#[test]
+fn test_markdown_v2_escapes_reserved_characters() {
+    assert_eq!(
+        MessageFormatter::escape_markdown_v2("1+1=2. (really!) #no_way"),
+        "1\\+1\\=2\\. \\(really\\!\\) \\#no\\_way"
+    );
+}
+
+#[test]
+fn test_markdown_v2_keeps_formatting() {
+    let markdown = "## Verdict\n\nA **bold** claim, an *aside* and ~~a retraction~~.\n\n\
+                    - first point\n- second `co_de`\n\nSee [the docs](https://example.com/a_(b)).";
+    assert_eq!(
+        MessageFormatter::markdown_to_markdown_v2(markdown),
+        "*Verdict*\n\nA *bold* claim, an _aside_ and ~a retraction~\\.\n\n\
+         • first point\n• second `co_de`\n\n\
+         See [the docs](https://example.com/a_(b\\))\\."
+    );
+}
+
+#[test]
+fn test_markdown_v2_does_not_nest_an_entity_in_itself() {
+    assert_eq!(
+        MessageFormatter::markdown_to_markdown_v2("# A **loud** title"),
+        "*A loud title*"
+    );
+    assert_eq!(
+        MessageFormatter::markdown_to_markdown_v2("1. one\n2. two"),
+        "1\\. one\n2\\. two"
+    );
+}
+
+#[test]
+fn test_strip_html_decodes_entities() {
+    assert_eq!(
+        MessageFormatter::strip_html("<b>Channel:</b> <code>a &lt;b&gt; &amp; c</code>"),
+        "Channel: a <b> & c"
+    );
+}
+
+#[test]
+fn test_long_bold_section_is_closed_and_reopened() {
+    let words = ["word"; 200].join(" ");
+    let text = format!("intro\n<b>{}</b>\noutro", words);
+
+    let chunks = MessageFormatter::split_message_into_chunks(&text, 300);
+    assert!(chunks.len() > 2);
+    for chunk in &chunks {
+        assert!(MessageFormatter::count_utf16_code_units(chunk) <= 300);
+        assert_balanced_html(chunk);
+    }
+    assert!(chunks[1].starts_with("<b>word"));
+    assert!(chunks.last().unwrap().ends_with("outro"));
+    // nothing is lost apart from the tags added at the boundaries
+    let joined = chunks.join(" ").replace("</b> <b>", " ");
+    assert_eq!(joined.matches("word").count(), 200);
+}
+
+#[test]
+fn test_links_and_entities_are_not_cut() {
+    let link = "<a href=\"https://example.com/some path\">a link</a>";
+    let text = format!("{} {}", ["a&amp;b"; 60].join(" "), [link; 5].join(" "));
+
+    for chunk in MessageFormatter::split_message_into_chunks(&text, 120) {
+        assert_balanced_html(&chunk);
+        // every entity is whole
+        for (i, _) in chunk.match_indices('&') {
+            assert!(chunk[i..].starts_with("&amp;"), "{}", chunk);
+        }
+    }
+}
+
+#[test]
+fn test_overlong_word_keeps_its_entities() {
+    let text = format!("<i>{}</i>", "&lt;".repeat(100));
+    let chunks = MessageFormatter::split_message_into_chunks(&text, 50);
+    assert!(chunks.len() > 1);
+    for chunk in &chunks {
+        assert!(MessageFormatter::count_utf16_code_units(chunk) <= 50);
+        assert_balanced_html(chunk);
+        assert_eq!(chunk.matches('&').count(), chunk.matches("&lt;").count());
+    }
+}
+
+#[test]
+fn test_markdown_v2_chunks_reopen_their_entities() {
+    let words = ["word\\."; 100].join(" ");
+    let text = format!("*{}*\n```\n{}\n```", words, ["let x_1 = 1;"; 40].join("\n"));
+
+    let chunks = MessageFormatter::split_markdown_v2_into_chunks(&text, 200);
+    assert!(chunks.len() > 3);
+    for chunk in &chunks {
+        assert!(MessageFormatter::count_utf16_code_units(chunk) <= 200);
+        // escapes are never split from their character
+        assert!(!chunk.ends_with('\\'), "{}", chunk);
+        let code = chunk.matches("```").count();
+        assert_eq!(code % 2, 0, "{}", chunk);
+        if code == 0 {
+            assert_eq!(chunk.matches('*').count() % 2, 0, "{}", chunk);
+        }
+    }
+    assert!(chunks[1].starts_with("*word"));
+    // underscores in code are literal and don't open an italic entity
+    assert!(chunks.last().unwrap().starts_with("```\n"));
+    assert!(chunks.last().unwrap().ends_with("\n```"));
+}
+
+#[test]
+fn test_short_messages_are_not_split() {
+    let text = "<b>short</b>";
+    assert_eq!(
+        MessageFormatter::split_message_into_chunks(text, 100),
+        vec![text.to_string()]
+    );
+}
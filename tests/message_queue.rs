@@ -0,0 +1,61 @@
+// Tests for the send retry backoff and rate limiting of the message queue
+use std::time::{Duration, Instant};
+use tg_main::message_queue::{
+    failure_outcome, retry_delay, FailureOutcome, TokenBucket, MAX_SEND_ATTEMPTS,
+};
+
+#[test]
+fn test_retry_delay_doubles_with_each_failure() {
+    assert_eq!(retry_delay(1), Duration::from_secs(30));
+    assert_eq!(retry_delay(2), Duration::from_secs(60));
+    assert_eq!(retry_delay(3), Duration::from_secs(120));
+    assert_eq!(retry_delay(4), Duration::from_secs(240));
+    // out of range counts stay within the backoff
+    assert_eq!(retry_delay(0), retry_delay(1));
+    assert_eq!(retry_delay(100), retry_delay(MAX_SEND_ATTEMPTS));
+}
+
+#[test]
+fn test_messages_are_dead_lettered_after_max_attempts() {
+    for failures in 1..MAX_SEND_ATTEMPTS {
+        assert_eq!(
+            failure_outcome(failures),
+            FailureOutcome::Retry(retry_delay(failures))
+        );
+    }
+    assert_eq!(
+        failure_outcome(MAX_SEND_ATTEMPTS),
+        FailureOutcome::DeadLettered
+    );
+}
+
+#[test]
+fn test_token_bucket_allows_a_burst_then_spaces_sends() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(3, 2, start);
+    for _ in 0..3 {
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+    }
+    // each further send waits for its own token
+    assert_eq!(bucket.reserve(start), Duration::from_millis(500));
+    assert_eq!(bucket.reserve(start), Duration::from_secs(1));
+}
+
+#[test]
+fn test_token_bucket_refills_up_to_capacity() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(2, 2, start);
+    bucket.reserve(start);
+    bucket.reserve(start);
+
+    // half a second buys one token back
+    let later = start + Duration::from_millis(500);
+    assert_eq!(bucket.reserve(later), Duration::ZERO);
+    assert_eq!(bucket.reserve(later), Duration::from_millis(500));
+
+    // an idle bucket holds no more than its capacity
+    let idle = later + Duration::from_secs(60);
+    assert_eq!(bucket.reserve(idle), Duration::ZERO);
+    assert_eq!(bucket.reserve(idle), Duration::ZERO);
+    assert_eq!(bucket.reserve(idle), Duration::from_millis(500));
+}
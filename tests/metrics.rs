@@ -0,0 +1,38 @@
+// Tests for the prometheus metrics
+use std::time::Duration;
+use tg_main::metrics::{CacheKind, Metrics};
+
+#[test]
+fn test_render_counts_analyses_and_cache_lookups() {
+    let metrics = Metrics::new();
+    metrics.analysis_started();
+    metrics.analysis_started();
+    metrics.analysis_completed();
+    metrics.analysis_failed("llm");
+    metrics.cache_lookup(CacheKind::Messages, true);
+    metrics.cache_lookup(CacheKind::Llm, false);
+    metrics.cache_lookup(CacheKind::Llm, false);
+
+    let rendered = metrics.render();
+    assert!(rendered.contains("tg_analyzer_analyses_started_total 2"));
+    assert!(rendered.contains("tg_analyzer_analyses_completed_total 1"));
+    assert!(rendered.contains("tg_analyzer_analyses_failed_total{stage=\"llm\"} 1"));
+    assert!(
+        rendered.contains("tg_analyzer_cache_lookups_total{cache=\"messages\",result=\"hit\"} 1")
+    );
+    assert!(rendered.contains("tg_analyzer_cache_lookups_total{cache=\"llm\",result=\"miss\"} 2"));
+}
+
+#[test]
+fn test_render_histograms() {
+    let metrics = Metrics::new();
+    metrics.observe_llm_latency(Duration::from_secs(3));
+    metrics.observe_telegram_fetch(Duration::from_millis(700));
+
+    let rendered = metrics.render();
+    assert!(rendered.contains("tg_analyzer_llm_request_duration_seconds_bucket{le=\"2.5\"} 0"));
+    assert!(rendered.contains("tg_analyzer_llm_request_duration_seconds_bucket{le=\"5\"} 1"));
+    assert!(rendered.contains("tg_analyzer_llm_request_duration_seconds_count 1"));
+    assert!(rendered.contains("tg_analyzer_telegram_fetch_duration_seconds_bucket{le=\"1\"} 1"));
+    assert!(rendered.contains("tg_analyzer_message_queue_depth 0"));
+}
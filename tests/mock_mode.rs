@@ -0,0 +1,71 @@
+// Tests for the LLM_MOCK development mode: canned llm answers and channel fixtures
+use std::path::Path;
+use tg_main::analysis::AnalysisError;
+use tg_main::llm::analysis_query::parse_partial_analysis;
+use tg_main::llm::extract_tag;
+use tg_main::llm::trends_query::parse_trends;
+use tg_main::mock;
+use tg_main::prompts::trends::bucket_messages;
+use tg_main::report::AnalysisReport;
+
+#[test]
+fn test_json_mode_answer_is_a_valid_report() {
+    let response = mock::llm_response("any prompt", Some(&AnalysisReport::schema()));
+    assert!(!response.truncated);
+    let report = AnalysisReport::parse(&response.content).expect("Mock report should parse");
+    assert!(report.professional.starts_with("Mock"));
+}
+
+#[test]
+fn test_tagged_answer_has_all_sections() {
+    let prompt = "OUTPUT FORMAT (use these exact tags):\n<professional>\n</professional>";
+    let response = mock::llm_response(prompt, None);
+    let result = parse_partial_analysis(&response.content, "mock").expect("Sections expected");
+    assert!(result.professional.is_some());
+    assert!(result.personal.is_some());
+    assert!(result.roast.is_some());
+    assert!(extract_tag(&response.content, "trends").is_none());
+
+    // canned answers are deterministic
+    assert_eq!(mock::llm_response(prompt, None).content, response.content);
+}
+
+#[test]
+fn test_trends_prompt_gets_a_timeline() {
+    let response = mock::llm_response("Answer in this format:\n<trends>\n...\n</trends>", None);
+    let result = parse_trends(&response.content, "mock").expect("Timeline expected");
+    assert!(!result.partial);
+}
+
+#[test]
+fn test_fixture_path_ignores_at_and_case() {
+    let dir = Path::new("fixtures");
+    assert_eq!(
+        mock::fixture_path(dir, "@Example_Channel"),
+        dir.join("example_channel.json")
+    );
+}
+
+#[test]
+fn test_example_fixture_loads_and_spans_several_months() {
+    let messages = mock::load_fixture_from(Path::new("fixtures"), "@example_channel")
+        .expect("Failed to load fixture");
+    assert_eq!(messages.len(), 12);
+    // enough history for a trends analysis
+    assert!(bucket_messages(&messages).len() >= 2);
+}
+
+#[test]
+fn test_missing_fixture_is_a_missing_channel() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    assert!(matches!(
+        mock::load_fixture_from(dir.path(), "@nobody"),
+        Err(AnalysisError::ChannelNotFound)
+    ));
+
+    std::fs::write(dir.path().join("broken.json"), "not json").expect("Failed to write");
+    assert!(matches!(
+        mock::load_fixture_from(dir.path(), "broken"),
+        Err(AnalysisError::Internal(_))
+    ));
+}
@@ -0,0 +1,73 @@
+// Tests for routing analysis types to their own models
+use std::time::Duration;
+use tg_main::llm::routing::{ModelRoute, ModelRouting, DEFAULT_API_ATTEMPTS};
+use tg_main::llm::{ModelTier, GEMINI_TIMEOUT_SECS};
+
+#[test]
+fn test_unrouted_types_follow_the_tier() {
+    let routing = ModelRouting::parse("roast=gemini-2.5-flash-lite");
+    let route = routing.route("professional", ModelTier::Quality);
+    assert_eq!(route, ModelRoute::for_tier(ModelTier::Quality));
+    assert_eq!(route.timeout, Duration::from_secs(GEMINI_TIMEOUT_SECS));
+    assert_eq!(route.api_attempts, DEFAULT_API_ATTEMPTS);
+    assert_eq!(routing.model_for("professional"), None);
+    assert_eq!(ModelRouting::from_env(), ModelRouting::default());
+}
+
+#[test]
+fn test_routed_model_comes_first_with_the_tier_as_fallback() {
+    let routing =
+        ModelRouting::parse("roast=gemini-2.5-flash-lite:60:1, professional=gemini-2.5-flash");
+    let roast = routing.route("roast", ModelTier::Auto);
+    assert_eq!(
+        roast.models,
+        vec![
+            "gemini-2.5-flash-lite",
+            "gemini-3-flash-preview",
+            "gemini-2.5-flash"
+        ]
+    );
+    assert_eq!(roast.timeout, Duration::from_secs(60));
+    assert_eq!(roast.api_attempts, 1);
+
+    // a routed model the tier already has isn't tried twice
+    let professional = routing.route("professional", ModelTier::Auto);
+    assert_eq!(
+        professional.models,
+        vec!["gemini-2.5-flash", "gemini-3-flash-preview"]
+    );
+    assert_eq!(routing.model_for("professional"), Some("gemini-2.5-flash"));
+}
+
+#[test]
+fn test_empty_model_only_overrides_call_settings() {
+    let routing = ModelRouting::parse("trends=:600");
+    let trends = routing.route("trends", ModelTier::Fast);
+    assert_eq!(trends.models, ModelRoute::for_tier(ModelTier::Fast).models);
+    assert_eq!(trends.timeout, Duration::from_secs(600));
+    assert_eq!(trends.api_attempts, DEFAULT_API_ATTEMPTS);
+    assert_eq!(routing.model_for("trends"), None);
+}
+
+#[test]
+fn test_malformed_entries_are_ignored() {
+    let routing =
+        ModelRouting::parse("roast=model:0,personal=model:ten,=model,trends,professional=a:1:2:3");
+    assert_eq!(routing, ModelRouting::default());
+}
+
+#[test]
+fn test_second_opinion_asks_a_model_of_the_other_tier() {
+    assert_eq!(
+        ModelRoute::second_opinion("gemini-3-flash-preview").models,
+        vec!["gemini-2.5-pro"]
+    );
+    assert_eq!(
+        ModelRoute::second_opinion("gemini-2.5-flash").models,
+        vec!["gemini-2.5-pro", "gemini-3-flash-preview"]
+    );
+    assert_eq!(
+        ModelRoute::second_opinion("gemini-2.5-pro").models,
+        vec!["gemini-2.5-flash", "gemini-3-flash-preview"]
+    );
+}
@@ -0,0 +1,54 @@
+// Tests for which failed analyses are reported to error tracking
+use tg_main::analysis::AnalysisError;
+use tg_main::analysis_runner::AnalysisRunError;
+use tg_main::error::AppError;
+use tg_main::observability::{is_reportable, ErrorKind};
+use tg_main::user_manager::UserManagerError;
+
+#[test]
+fn test_our_failures_are_reported() {
+    assert!(is_reportable(&AnalysisRunError::Llm(AppError::llm(
+        AnalysisError::AiUnavailable
+    ))));
+    assert!(is_reportable(&AnalysisRunError::Prepare(AppError::from(
+        AnalysisError::Internal("session expired".to_string())
+    ))));
+    assert!(is_reportable(&AnalysisRunError::Prompt(
+        "template missing".into()
+    )));
+    assert!(is_reportable(&AnalysisRunError::Complete(
+        UserManagerError::DatabaseError("connection reset".into())
+    )));
+}
+
+#[test]
+fn test_channel_and_user_failures_are_not_reported() {
+    for failure in [
+        AnalysisError::ChannelPrivate,
+        AnalysisError::ChannelNotFound,
+        AnalysisError::FloodWait(30),
+        AnalysisError::LowTextCoverage(10),
+        AnalysisError::ChannelBlocked,
+    ] {
+        assert!(
+            !is_reportable(&AnalysisRunError::Prepare(AppError::from(failure.clone()))),
+            "{:?} should not be reported",
+            failure
+        );
+    }
+    assert!(!is_reportable(&AnalysisRunError::NoMessages));
+    assert!(!is_reportable(&AnalysisRunError::BudgetExceeded));
+    assert!(!is_reportable(&AnalysisRunError::Complete(
+        UserManagerError::InsufficientCredits(7)
+    )));
+    assert!(!is_reportable(&AnalysisRunError::Complete(
+        UserManagerError::NotPending(7)
+    )));
+}
+
+#[test]
+fn test_error_kinds_are_tagged() {
+    assert_eq!(ErrorKind::Analysis.as_str(), "analysis");
+    assert_eq!(ErrorKind::Llm.as_str(), "llm");
+    assert_eq!(ErrorKind::Payment.as_str(), "payment");
+}
@@ -0,0 +1,97 @@
+// Tests for the star packages: discounts, the price list and /packages arguments
+use teloxide::utils::command::BotCommands;
+use tg_main::bot::Command;
+use tg_main::localization::Lang;
+use tg_main::packages::{default_packages, discount, Package, PackageChange, PackagesCommand};
+
+fn package(id: i32, credits: i32, stars: u32) -> Package {
+    Package {
+        id,
+        credits,
+        stars,
+        title: None,
+        description: None,
+        active: true,
+    }
+}
+
+#[test]
+fn test_discount_compares_with_the_smallest_package() {
+    let packages = default_packages();
+    assert_eq!(discount(&packages, &packages[0]), 0);
+    assert_eq!(discount(&packages, &packages[1]), 500);
+
+    // dearer per credit than the smallest package is no discount
+    let packages = vec![package(1, 5, 100), package(2, 10, 300)];
+    assert_eq!(discount(&packages, &packages[1]), 0);
+    assert_eq!(discount(&[], &packages[0]), 0);
+}
+
+#[test]
+fn test_package_prices_list_every_package() {
+    let prices = Lang::En.package_prices(&default_packages());
+    assert_eq!(
+        prices,
+        "• 1 analysis: 100 ⭐ stars\n• 10 analyses: 500 ⭐ stars (save 500 stars!)"
+    );
+    let prices = Lang::En.package_prices(&[package(3, 25, 2000)]);
+    assert_eq!(prices, "• 25 analyses: 2000 ⭐ stars");
+}
+
+#[test]
+fn test_packages_command_arguments() {
+    assert_eq!(PackagesCommand::parse(""), Some(PackagesCommand::List));
+    assert_eq!(
+        PackagesCommand::parse("add 25 1000"),
+        Some(PackagesCommand::Add {
+            credits: 25,
+            stars: 1000
+        })
+    );
+    assert_eq!(
+        PackagesCommand::parse("PRICE 2 450"),
+        Some(PackagesCommand::Change {
+            id: 2,
+            change: PackageChange::Stars(450)
+        })
+    );
+    assert_eq!(
+        PackagesCommand::parse("title 2 Starter pack"),
+        Some(PackagesCommand::Change {
+            id: 2,
+            change: PackageChange::Title(Some("Starter pack".to_string()))
+        })
+    );
+    assert_eq!(
+        PackagesCommand::parse("description 2"),
+        Some(PackagesCommand::Change {
+            id: 2,
+            change: PackageChange::Description(None)
+        })
+    );
+    assert_eq!(
+        PackagesCommand::parse("disable 1"),
+        Some(PackagesCommand::Change {
+            id: 1,
+            change: PackageChange::Active(false)
+        })
+    );
+    assert_eq!(PackagesCommand::parse("add 0 100"), None);
+    assert_eq!(PackagesCommand::parse("add 10"), None);
+    assert_eq!(PackagesCommand::parse("price 2 -5"), None);
+    assert_eq!(PackagesCommand::parse("enable 1 now"), None);
+    assert_eq!(PackagesCommand::parse("remove 1"), None);
+}
+
+#[test]
+fn test_buy_is_listed_and_packages_is_hidden() {
+    let commands = Command::bot_commands();
+    assert!(commands.iter().any(|command| command.command == "/buy"));
+    assert!(!commands.iter().any(|command| command.command == "/buy10"));
+    assert!(!commands
+        .iter()
+        .any(|command| command.command == "/packages"));
+    let cmd = Command::parse("/packages price 2 450", "ScratchAuthorEgoBot")
+        .expect("Failed to parse command");
+    assert!(matches!(cmd, Command::Packages(ref args) if args == "price 2 450"));
+}
@@ -0,0 +1,68 @@
+// Tests for building the corpus of a user's public profile
+use tg_main::analysis::{profile_messages, AnalysisError, CorpusKind, MessageDict};
+use tg_main::localization::Lang;
+
+fn post(text: &str) -> MessageDict {
+    MessageDict {
+        id: Some(1),
+        date: Some("2024-01-01".to_string()),
+        message: Some(text.to_string()),
+        images: None,
+        thread_id: None,
+    }
+}
+
+#[test]
+fn test_profile_card_comes_first() {
+    let messages = profile_messages(
+        "Jane Doe",
+        Some("  rust and databases  "),
+        vec![post("a post of the personal channel")],
+    );
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].message.as_deref(),
+        Some("Name: Jane Doe\nBio: rust and databases")
+    );
+    assert_eq!(messages[0].date, None);
+    assert_eq!(
+        messages[1].message.as_deref(),
+        Some("a post of the personal channel")
+    );
+}
+
+#[test]
+fn test_profile_without_bio_keeps_its_posts() {
+    let messages = profile_messages("Jane Doe", Some("   "), vec![post("a post")]);
+    assert_eq!(messages[0].message.as_deref(), Some("Name: Jane Doe"));
+    assert_eq!(messages.len(), 2);
+}
+
+#[test]
+fn test_profile_without_bio_or_posts_is_empty() {
+    assert!(profile_messages("Jane Doe", None, Vec::new()).is_empty());
+    assert!(profile_messages("Jane Doe", Some(""), Vec::new()).is_empty());
+}
+
+#[test]
+fn test_corpus_kind_codes_roundtrip() {
+    for kind in [
+        CorpusKind::Channel,
+        CorpusKind::Profile,
+        CorpusKind::CrossGroup,
+        CorpusKind::RoastBattle,
+    ] {
+        assert_eq!(CorpusKind::from_code(kind.as_str()), kind);
+    }
+    // corpora cached before profiles were recognized
+    assert_eq!(CorpusKind::from_code("unknown"), CorpusKind::Channel);
+}
+
+#[test]
+fn test_empty_profile_error_names_the_account() {
+    for lang in [Lang::En, Lang::Ru] {
+        let message = lang.error_analysis_failed(&AnalysisError::EmptyProfile, "@jane");
+        assert!(message.contains("@jane"));
+    }
+}
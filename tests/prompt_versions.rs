@@ -0,0 +1,49 @@
+// Tests for the prompt version registry and the A/B split of users between versions
+use std::collections::HashSet;
+use tg_main::prompts::versions::{
+    PromptExperiment, PromptVersion, BASE_PROMPT_VERSION, PROMPT_VERSIONS,
+};
+
+#[test]
+fn test_registry_ids_are_unique_and_start_with_the_base() {
+    assert_eq!(PromptVersion::base().id, BASE_PROMPT_VERSION);
+    let ids: HashSet<i32> = PROMPT_VERSIONS.iter().map(|version| version.id).collect();
+    assert_eq!(ids.len(), PROMPT_VERSIONS.len());
+    assert_eq!(
+        PromptVersion::find(2).map(|version| version.name),
+        Some("evidence")
+    );
+    assert!(PromptVersion::find(0).is_none());
+}
+
+#[test]
+fn test_without_an_experiment_everyone_gets_the_base() {
+    for experiment in [
+        PromptExperiment::default(),
+        PromptExperiment::parse(""),
+        // unknown versions and zero weights are left out
+        PromptExperiment::parse("99:50,2:0,garbage"),
+    ] {
+        for user_id in 0..100 {
+            assert_eq!(experiment.version_for(user_id).id, BASE_PROMPT_VERSION);
+        }
+    }
+}
+
+#[test]
+fn test_users_are_split_by_weight_and_stay_in_their_bucket() {
+    let experiment = PromptExperiment::parse("1:1, 2:3");
+
+    let variant_users = (1..=1000)
+        .filter(|user_id| experiment.version_for(*user_id).id == 2)
+        .count();
+    // roughly three quarters of the users get the variant
+    assert!((650..=850).contains(&variant_users), "{}", variant_users);
+
+    for user_id in 1..=100 {
+        assert_eq!(
+            experiment.version_for(user_id),
+            experiment.version_for(user_id)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+// Tests for analysis prompt generation
+use tg_main::analysis::MessageDict;
+use tg_main::prompts::analysis::{
+    generate_analysis_prompt, generate_profile_prompt, OutputLanguage,
+};
+use tg_main::prompts::versions::PromptVersion;
+
+const TOKEN_LIMIT: usize = 100_000;
+
+fn messages() -> Vec<MessageDict> {
+    vec![MessageDict {
+        id: None,
+        date: Some("2024-01-01T00:00:00Z".to_string()),
+        message: Some("Привет, мир".to_string()),
+        images: None,
+        thread_id: None,
+    }]
+}
+
+#[test]
+fn test_output_language_is_requested_in_prompt() {
+    let prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    assert!(prompt.tagged.contains("same language as the messages"));
+
+    let prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::English,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    assert!(prompt.json.contains("Write in English"));
+    assert!(!prompt.json.contains("same language as the messages"));
+
+    let prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Russian,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    assert!(prompt.tagged.contains("Write in Russian"));
+}
+
+#[test]
+fn test_output_language_codes_roundtrip() {
+    for language in OutputLanguage::ALL {
+        assert_eq!(OutputLanguage::from_code(Some(language.as_str())), language);
+    }
+    // unknown or missing values fall back to the channel's language
+    assert_eq!(
+        OutputLanguage::from_code(Some("de")),
+        OutputLanguage::Channel
+    );
+    assert_eq!(OutputLanguage::from_code(None), OutputLanguage::Channel);
+}
+
+#[test]
+fn test_prompt_formats() {
+    let prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    // the fallback prompt asks for tags, the json prompt for the report fields
+    assert!(prompt.tagged.contains("<professional>"));
+    assert!(!prompt.json.contains("<professional>"));
+    for field in [
+        "\"strengths\"",
+        "\"weaknesses\"",
+        "\"topics\"",
+        "\"tone\"",
+        "\"scores\"",
+    ] {
+        assert!(prompt.json.contains(field), "missing {}", field);
+    }
+    // both carry the same messages
+    assert!(prompt.json.contains("Привет, мир"));
+    assert!(prompt.tagged.contains("Привет, мир"));
+}
+
+#[test]
+fn test_topic_scopes_the_prompt() {
+    let prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        Some("Hiring"),
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    for text in [&prompt.json, &prompt.tagged] {
+        assert!(text.contains("TOPIC SCOPE"));
+        assert!(text.contains("\"Hiring\""));
+    }
+
+    let prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    assert!(!prompt.tagged.contains("TOPIC SCOPE"));
+}
+
+#[test]
+fn test_prompt_versions_differ_only_in_guidelines() {
+    let base = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    let evidence = PromptVersion::find(2).expect("the evidence variant is registered");
+    let variant = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Channel,
+        evidence,
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+
+    assert!(!base.tagged.contains("paraphrase"));
+    assert!(variant.tagged.contains("paraphrase"));
+    assert!(variant.json.contains("paraphrase"));
+    assert!(variant.tagged.contains("<professional>"));
+}
+
+#[test]
+fn test_profile_prompt_is_reduced() {
+    let prompt =
+        generate_profile_prompt(&messages(), Some("career"), OutputLanguage::English).unwrap();
+    for text in [&prompt.json, &prompt.tagged] {
+        assert!(text.contains("profile card"));
+        assert!(text.contains("approximately 1024 characters"));
+        assert!(!text.contains("~2048 characters"));
+        assert!(text.contains("REQUESTER FOCUS"));
+        assert!(text.contains("Write in English"));
+        assert!(text.contains("Привет, мир"));
+    }
+    // the same output formats, so profile answers parse like channel ones
+    assert!(prompt.tagged.contains("<roast>"));
+    assert!(prompt.json.contains("\"scores\""));
+
+    let channel_prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    assert!(channel_prompt.tagged.contains("~2048 characters"));
+    assert!(!channel_prompt.tagged.contains("profile card"));
+}
+
+#[test]
+fn test_channel_prompt_carries_quick_facts() {
+    let prompt = generate_analysis_prompt(
+        &messages(),
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        TOKEN_LIMIT,
+    )
+    .unwrap();
+    for text in [&prompt.json, &prompt.tagged] {
+        assert!(text.contains("QUICK FACTS"));
+        // 2024-01-01 was a monday
+        assert!(text.contains("Mon 1, Tue 0"));
+    }
+
+    let profile_prompt =
+        generate_profile_prompt(&messages(), None, OutputLanguage::Channel).unwrap();
+    assert!(!profile_prompt.tagged.contains("QUICK FACTS"));
+}
@@ -0,0 +1,69 @@
+// Tests for /receipts and the /revenue report arguments
+use teloxide::utils::command::BotCommands;
+use tg_main::bot::Command;
+use tg_main::localization::Lang;
+use tg_main::receipts::{Receipt, RevenuePeriod, DEFAULT_REVENUE_DAYS, DEFAULT_REVENUE_WEEKS};
+
+#[test]
+fn test_revenue_arguments_pick_the_period_and_count() {
+    assert_eq!(
+        RevenuePeriod::parse_args(""),
+        Some((RevenuePeriod::Day, DEFAULT_REVENUE_DAYS))
+    );
+    assert_eq!(
+        RevenuePeriod::parse_args("week"),
+        Some((RevenuePeriod::Week, DEFAULT_REVENUE_WEEKS))
+    );
+    assert_eq!(
+        RevenuePeriod::parse_args("30"),
+        Some((RevenuePeriod::Day, 30))
+    );
+    assert_eq!(
+        RevenuePeriod::parse_args("Weekly 12"),
+        Some((RevenuePeriod::Week, 12))
+    );
+    assert_eq!(
+        RevenuePeriod::parse_args("day 10000"),
+        Some((RevenuePeriod::Day, 365))
+    );
+    assert_eq!(RevenuePeriod::parse_args("month"), None);
+    assert_eq!(RevenuePeriod::parse_args("day many"), None);
+    assert_eq!(RevenuePeriod::parse_args("day 1 2"), None);
+}
+
+#[test]
+fn test_receipts_name_the_package_bought() {
+    let receipt = |package: &str| Receipt {
+        telegram_payment_charge_id: "charge-1".to_string(),
+        package: package.to_string(),
+        stars: 500,
+        credits: 10,
+        refunded: false,
+        created_at: "2026-01-02 03:04".to_string(),
+    };
+    let line = Lang::En.receipt_line(&receipt("credits_10"));
+    assert!(line.contains(&Lang::En.invoice_package_title(10)));
+    assert!(line.contains("⭐ 500"));
+    assert!(line.contains("charge-1"));
+    assert!(!line.contains("refunded"));
+    let line = Lang::En.receipt_line(&receipt("subscription_30"));
+    assert!(line.contains(Lang::En.invoice_subscription_title()));
+    let line = Lang::En.receipt_line(&Receipt {
+        refunded: true,
+        ..receipt("credits_1")
+    });
+    assert!(line.contains(&Lang::En.invoice_package_title(1)));
+    assert!(line.contains("refunded"));
+}
+
+#[test]
+fn test_receipts_command_is_listed_and_revenue_is_hidden() {
+    let commands = Command::bot_commands();
+    assert!(commands
+        .iter()
+        .any(|command| command.command == "/receipts"));
+    assert!(!commands.iter().any(|command| command.command == "/revenue"));
+    let cmd =
+        Command::parse("/revenue week 4", "ScratchAuthorEgoBot").expect("Failed to parse command");
+    assert!(matches!(cmd, Command::Revenue(ref args) if args == "week 4"));
+}
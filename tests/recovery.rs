@@ -0,0 +1,66 @@
+// Tests for deciding which interrupted analyses are resumed after a restart
+use std::time::Duration;
+use tg_main::analysis::AnalysisDepth;
+use tg_main::recovery::{RecoveryPlan, MAX_RECOVERY_AGE};
+use tg_main::user_manager::PendingAnalysis;
+
+fn pending() -> PendingAnalysis {
+    PendingAnalysis {
+        id: 1,
+        user_id: 1,
+        telegram_user_id: 42,
+        channel_name: "@rustlang".to_string(),
+        analysis_type: "professional".to_string(),
+        depth: "deep".to_string(),
+        language: Some("en".to_string()),
+        focus: None,
+        topic: None,
+        age: Duration::from_secs(120),
+        second_opinion_of: None,
+    }
+}
+
+#[test]
+fn test_recent_valid_analysis_is_resumed() {
+    assert_eq!(
+        RecoveryPlan::for_pending(&pending()),
+        RecoveryPlan::Resume(AnalysisDepth::Deep)
+    );
+
+    let at_limit = PendingAnalysis {
+        age: MAX_RECOVERY_AGE,
+        ..pending()
+    };
+    assert_eq!(
+        RecoveryPlan::for_pending(&at_limit),
+        RecoveryPlan::Resume(AnalysisDepth::Deep)
+    );
+}
+
+#[test]
+fn test_stale_analysis_is_abandoned() {
+    let stale = PendingAnalysis {
+        age: MAX_RECOVERY_AGE + Duration::from_secs(1),
+        ..pending()
+    };
+    assert_eq!(RecoveryPlan::for_pending(&stale), RecoveryPlan::Abandon);
+}
+
+#[test]
+fn test_invalid_requests_are_abandoned() {
+    let unknown_depth = PendingAnalysis {
+        depth: "bottomless".to_string(),
+        ..pending()
+    };
+    let unknown_type = PendingAnalysis {
+        analysis_type: "horoscope".to_string(),
+        ..pending()
+    };
+    let bad_channel = PendingAnalysis {
+        channel_name: "not a channel".to_string(),
+        ..pending()
+    };
+    for analysis in [unknown_depth, unknown_type, bad_channel] {
+        assert_eq!(RecoveryPlan::for_pending(&analysis), RecoveryPlan::Abandon);
+    }
+}
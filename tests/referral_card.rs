@@ -0,0 +1,56 @@
+// Tests for the /referrals leaderboard names and the shareable stats card
+use tg_main::referrals::{anonymize, referral_link};
+use tg_main::utils::StatsCard;
+
+#[test]
+fn test_anonymize_keeps_only_an_initial() {
+    assert_eq!(anonymize(Some("alice"), Some("alice_99")), "A***");
+    assert_eq!(anonymize(Some("  "), Some("bob")), "B***");
+    assert_eq!(anonymize(None, Some("борис")), "Б***");
+    assert_eq!(anonymize(None, None), "***");
+}
+
+#[test]
+fn test_referral_link_uses_the_user_id() {
+    assert_eq!(
+        referral_link(42),
+        "https://t.me/ScratchAuthorEgoBot?start=42"
+    );
+}
+
+#[test]
+fn test_stats_card_renders_a_png() {
+    let card = StatsCard {
+        referrals: 12,
+        paid_referrals: 3,
+        this_week: 4,
+        rank: Some(2),
+        link: referral_link(1234567),
+    };
+    let png = card.render_png().expect("Card should render");
+    let image = image::load_from_memory(&png)
+        .expect("Card should be a valid image")
+        .to_rgb8();
+    assert_eq!(image.dimensions(), (800, 420));
+
+    // the text is drawn in more than the background colour
+    let background = *image.get_pixel(image.width() - 1, image.height() - 1);
+    let drawn = image.pixels().filter(|pixel| **pixel != background).count();
+    assert!(drawn > 1000);
+}
+
+#[test]
+fn test_stats_card_without_rank_differs() {
+    let ranked = StatsCard {
+        referrals: 1,
+        paid_referrals: 0,
+        this_week: 1,
+        rank: Some(1),
+        link: referral_link(1),
+    };
+    let unranked = StatsCard {
+        rank: None,
+        ..ranked.clone()
+    };
+    assert_ne!(ranked.render_png().unwrap(), unranked.render_png().unwrap());
+}
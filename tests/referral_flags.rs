@@ -0,0 +1,38 @@
+// Tests for the referral fraud heuristics
+use tg_main::referral_flags::{FlagReason, RefereeActivity};
+
+#[test]
+fn test_heuristics_trip_at_the_threshold() {
+    let activity = RefereeActivity {
+        idle_same_language: 5,
+        idle_language: Some("ru".to_string()),
+        recent_signups: 4,
+    };
+    let reasons = activity.suspicious(5);
+    assert_eq!(reasons.len(), 1);
+    assert_eq!(reasons[0].0, FlagReason::IdleReferees);
+    assert_eq!(reasons[0].1, "5 idle referees with language ru");
+
+    let reasons = activity.suspicious(4);
+    assert_eq!(
+        reasons
+            .iter()
+            .map(|(reason, _)| *reason)
+            .collect::<Vec<_>>(),
+        vec![FlagReason::IdleReferees, FlagReason::RapidSignups]
+    );
+    assert!(activity.suspicious(6).is_empty());
+}
+
+#[test]
+fn test_zero_threshold_trips_nothing() {
+    let activity = RefereeActivity {
+        recent_signups: 100,
+        ..Default::default()
+    };
+    assert!(activity.suspicious(0).is_empty());
+    assert_eq!(
+        RefereeActivity::default().suspicious(1),
+        Vec::<(FlagReason, String)>::new()
+    );
+}
@@ -1,6 +1,8 @@
 // Integration tests for referral system
 mod integration;
 
+use std::sync::Arc;
+
 use integration::{
     mock_bot::MockTelegramBot,
     test_utils::{TestAssertions, TestScenario},
@@ -13,7 +15,10 @@ async fn test_basic_referral_chain() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // create referrer user
@@ -95,7 +100,10 @@ async fn test_milestone_celebrations() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // create referrer
@@ -216,7 +224,7 @@ async fn test_paid_referral_rewards() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let bot = MockTelegramBot::new();
 
     // create referrer
@@ -289,7 +297,10 @@ async fn test_mixed_paid_and_unpaid_referrals() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
     let _bot = MockTelegramBot::new();
 
     // create referrer with 4 unpaid referrals and 1 paid referral
@@ -347,7 +358,10 @@ async fn test_database_consistency() {
     let db = TestDatabase::create_fresh()
         .await
         .expect("Failed to create test database");
-    let user_manager = UserManager::new(db.pool.clone());
+    TestScenario::allow_rapid_referrals(&db)
+        .await
+        .expect("Failed to turn off referral flags");
+    let user_manager = UserManager::new(Arc::new(db.pool.clone()));
 
     // create a comprehensive scenario
     let (referrer, _, _) = TestScenario::create_referrer_with_mixed_referrals(
@@ -0,0 +1,76 @@
+// Tests for the configurable referral reward rules
+use tg_main::referrals::{ReferralConfigError, ReferralRules, REFERRAL_RULE_NAMES};
+
+#[test]
+fn test_default_rules_keep_the_original_rewards() {
+    let rules = ReferralRules::default();
+    let milestones = [1, 4, 5, 9, 10, 14, 15, 25].map(|count| rules.milestones(count));
+    assert_eq!(milestones, [0, 0, 1, 1, 2, 2, 3, 5]);
+
+    let celebrations = (1..=50)
+        .filter(|count| rules.is_celebration(*count))
+        .collect::<Vec<_>>();
+    assert_eq!(celebrations, vec![1, 5, 10, 20, 30, 40, 50]);
+}
+
+#[test]
+fn test_zero_turns_rewards_and_celebrations_off() {
+    let mut rules = ReferralRules::default();
+    rules.set("milestone_every", 0).unwrap();
+    rules.set("celebrate_every", 0).unwrap();
+    rules.set("celebrate_early", 0).unwrap();
+    assert_eq!(rules.milestones(100), 0);
+    assert!((0..=100).all(|count| !rules.is_celebration(count)));
+}
+
+#[test]
+fn test_early_celebration_follows_the_milestone_interval() {
+    let mut rules = ReferralRules::default();
+    rules.set("milestone_every", 3).unwrap();
+    rules.set("celebrate_every", 25).unwrap();
+    let celebrations = (1..=50)
+        .filter(|count| rules.is_celebration(*count))
+        .collect::<Vec<_>>();
+    assert_eq!(celebrations, vec![1, 3, 25, 50]);
+}
+
+#[test]
+fn test_set_validates_names_and_values() {
+    let mut rules = ReferralRules::default();
+    assert_eq!(
+        rules.set("milestone_credits", -1),
+        Err(ReferralConfigError::InvalidValue(
+            "milestone_credits".to_string(),
+            -1
+        ))
+    );
+    assert!(rules.set("celebrate_early", 2).is_err());
+    assert!(rules.set("milestone_every", i64::MAX).is_err());
+    assert_eq!(
+        rules.set("bonus", 1),
+        Err(ReferralConfigError::UnknownRule("bonus".to_string()))
+    );
+    assert_eq!(rules, ReferralRules::default());
+}
+
+#[test]
+fn test_values_cover_every_rule() {
+    let rules = ReferralRules::default();
+    let values = rules.values();
+    assert_eq!(
+        values.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+        REFERRAL_RULE_NAMES
+    );
+    let mut copy = ReferralRules {
+        milestone_every: 1,
+        milestone_credits: 1,
+        paid_referral_credits: 1,
+        celebrate_early: false,
+        celebrate_every: 1,
+        suspicious_referees: 1,
+    };
+    for (name, value) in values {
+        copy.set(name, value).unwrap();
+    }
+    assert_eq!(copy, rules);
+}
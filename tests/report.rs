@@ -0,0 +1,80 @@
+// Tests for structured analysis reports
+use tg_main::cache::AnalysisResult;
+use tg_main::localization::Lang;
+use tg_main::report::AnalysisReport;
+use tg_main::utils::ResultPresenter;
+
+const REPORT: &str = r#"{
+    "professional": "Seasoned backend engineer.",
+    "personal": "Curious and patient.",
+    "roast": "Has opinions about semicolons.",
+    "strengths": ["Rust", "databases"],
+    "weaknesses": ["overengineering"],
+    "topics": ["rust", "postgres", "hiring"],
+    "tone": "dry and technical",
+    "scores": {"expertise": 9, "communication": 7, "consistency": 8, "humor": 4}
+}"#;
+
+#[test]
+fn test_parse_valid_report() {
+    let report = AnalysisReport::parse(REPORT).expect("report should parse");
+    assert_eq!(report.professional, "Seasoned backend engineer.");
+    assert_eq!(report.strengths, vec!["Rust", "databases"]);
+    assert_eq!(report.scores.expertise, 9);
+}
+
+#[test]
+fn test_parse_rejects_incomplete_reports() {
+    assert!(AnalysisReport::parse("not json").is_none());
+    assert!(AnalysisReport::parse(&REPORT.replace("Curious and patient.", " ")).is_none());
+    assert!(
+        AnalysisReport::parse(&REPORT.replace("\"tone\": \"dry and technical\",", "")).is_none()
+    );
+}
+
+#[test]
+fn test_parse_normalizes_lists_and_scores() {
+    let json = REPORT
+        .replace(
+            r#"["rust", "postgres", "hiring"]"#,
+            r#"["a", "b", "", "c", "d", "e", "f"]"#,
+        )
+        .replace(r#""expertise": 9"#, r#""expertise": 0"#)
+        .replace(r#""humor": 4"#, r#""humor": 42"#);
+    let report = AnalysisReport::parse(&json).expect("report should parse");
+
+    assert_eq!(report.topics, vec!["a", "b", "c", "d", "e"]);
+    assert_eq!(report.scores.expertise, 1);
+    assert_eq!(report.scores.humor, 10);
+}
+
+#[test]
+fn test_overall_score_is_the_mean_of_all_scores() {
+    let report = AnalysisReport::parse(REPORT).expect("report should parse");
+    assert_eq!(report.scores.average(), 7.0);
+}
+
+#[test]
+fn test_results_cached_before_reports_still_load() {
+    let json = r#"{"professional": "p", "personal": "q", "roast": "r", "messages_count": 5}"#;
+    let result: AnalysisResult = serde_json::from_str(json).expect("old result should load");
+    assert!(result.report.is_none());
+}
+
+#[test]
+fn test_report_highlights_are_rendered() {
+    let report = AnalysisReport::parse(REPORT).unwrap();
+    let result = AnalysisResult::from_report(report, "gemini-2.5-flash");
+    assert_eq!(result.personal.as_deref(), Some("Curious and patient."));
+
+    let professional =
+        ResultPresenter::render(&result, "professional", "channel", 1, Lang::En).unwrap();
+    assert!(professional[0].contains("Rust, databases"));
+    assert!(professional[0].contains("expertise 9/10"));
+
+    let personal = ResultPresenter::render(&result, "personal", "channel", 1, Lang::En).unwrap();
+    assert!(personal[0].contains("rust, postgres, hiring"));
+
+    let roast = ResultPresenter::render(&result, "roast", "channel", 1, Lang::En).unwrap();
+    assert!(!roast[0].contains("Scores"));
+}
@@ -0,0 +1,181 @@
+// Tests for rendering and chunking analysis results
+use tg_main::cache::AnalysisResult;
+use tg_main::facts::QuickFacts;
+use tg_main::localization::Lang;
+use tg_main::utils::{MessageFormatter, ResultPresenter};
+
+fn result_with_professional(content: &str) -> AnalysisResult {
+    AnalysisResult {
+        professional: Some(content.to_string()),
+        personal: None,
+        roast: Some(String::new()),
+        trends: None,
+        messages_count: 10,
+        model: None,
+        report: None,
+        removed_messages: 0,
+        partial: false,
+        prompt_version: None,
+        facts: None,
+    }
+}
+
+#[test]
+fn test_short_content_renders_single_message() {
+    let result = result_with_professional("Writes about **Rust** and databases.");
+
+    let messages = ResultPresenter::render(&result, "professional", "rustacean", 1, Lang::En)
+        .expect("professional content should render");
+
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("<code>rustacean</code>"));
+    assert!(messages[0].contains("<b>Rust</b>"));
+}
+
+#[test]
+fn test_long_content_is_chunked_with_headers_on_every_part() {
+    let paragraph = "This author keeps shipping side projects and writing about them. ".repeat(20);
+    let content = vec![paragraph; 10].join("\n\n");
+    let result = result_with_professional(&content);
+
+    let messages = ResultPresenter::render(&result, "professional", "prolific", 42, Lang::En)
+        .expect("professional content should render");
+
+    assert!(messages.len() > 1);
+    for message in &messages {
+        assert!(MessageFormatter::count_utf16_code_units(message) <= 4096);
+        assert!(message.contains("<code>prolific</code>"));
+    }
+}
+
+#[test]
+fn test_missing_or_empty_analysis_types_render_nothing() {
+    let result = result_with_professional("Some content");
+
+    assert!(ResultPresenter::render(&result, "personal", "channel", 1, Lang::En).is_none());
+    assert!(ResultPresenter::render(&result, "roast", "channel", 1, Lang::En).is_none());
+    assert!(ResultPresenter::render(&result, "trends", "channel", 1, Lang::En).is_none());
+    assert!(ResultPresenter::render(&result, "unknown", "channel", 1, Lang::En).is_none());
+}
+
+#[test]
+fn test_channel_name_is_escaped_in_header() {
+    let result = result_with_professional("Content");
+
+    let messages = ResultPresenter::render(&result, "professional", "a<b>", 1, Lang::En)
+        .expect("professional content should render");
+
+    assert!(messages[0].contains("a&lt;b&gt;"));
+}
+
+#[test]
+fn test_partial_results_are_labeled() {
+    let mut result = result_with_professional("Writes about Rust and");
+    let messages = ResultPresenter::render(&result, "professional", "channel", 1, Lang::En)
+        .expect("professional content should render");
+    assert!(!messages[0].contains(Lang::En.analysis_partial_label()));
+
+    result.partial = true;
+    let messages = ResultPresenter::render(&result, "professional", "channel", 1, Lang::En)
+        .expect("partial content should still render");
+    assert!(messages[0].contains(Lang::En.analysis_partial_label()));
+}
+
+#[test]
+fn test_markdown_v2_fallback_has_no_html() {
+    let result = result_with_professional(&format!("**Rust** fan. {}", "word ".repeat(1500)));
+
+    let messages =
+        ResultPresenter::render_markdown_v2(&result, "professional", "rust_lang", 1, Lang::En)
+            .expect("professional content should render");
+
+    assert!(messages.len() > 1);
+    for message in &messages {
+        assert!(!message.contains("<b>"));
+        assert!(!message.contains("&lt;"));
+        // the header is plain text, escaped
+        assert!(message.contains("rust\\_lang"));
+    }
+    assert!(messages[0].contains("*Rust* fan\\."));
+}
+
+#[test]
+fn test_markdown_report_keeps_the_llm_markdown() {
+    let mut result = result_with_professional("  Writes about **Rust** and databases.\n");
+
+    let report = ResultPresenter::markdown(&result, "professional", "@rustacean")
+        .expect("professional content should render");
+    assert!(report.starts_with("# Professional analysis of @rustacean\n\n"));
+    assert!(report.contains("Writes about **Rust** and databases.\n\n---"));
+    assert!(report.ends_with("Based on 10 messages.\n"));
+    assert!(ResultPresenter::markdown(&result, "roast", "@rustacean").is_none());
+
+    result.partial = true;
+    let report = ResultPresenter::markdown(&result, "professional", "@rustacean").unwrap();
+    assert!(report.contains("cut short"));
+}
+
+#[test]
+fn test_web_page_carries_the_whole_result_under_a_plain_title() {
+    let mut result = result_with_professional("Ships **Rust** crates.");
+    result.partial = true;
+
+    let (title, content) =
+        ResultPresenter::render_web_page(&result, "professional", "a&b_channel", Lang::En)
+            .expect("professional content should render");
+
+    assert!(title.ends_with("Professional Analysis — a&b_channel"));
+    assert!(!title.contains('<'));
+    assert_eq!(content.len(), 2);
+    assert!(content[0].to_string().contains("Partial report"));
+    assert!(content[1].to_string().contains("Rust"));
+    assert!(
+        ResultPresenter::render_web_page(&result, "personal", "a&b_channel", Lang::En).is_none()
+    );
+}
+
+#[test]
+fn test_web_page_summary_links_to_the_page() {
+    let paragraph = "This author keeps shipping side projects and writing about them. ".repeat(20);
+    let result = result_with_professional(&paragraph);
+
+    let summary = ResultPresenter::render_web_page_summary(
+        &result,
+        "professional",
+        "prolific",
+        42,
+        "https://telegra.ph/Analysis-10-17",
+        Lang::En,
+    )
+    .expect("professional content should render");
+
+    assert!(summary.contains("<code>prolific</code>"));
+    assert!(summary.contains("<a href=\"https://telegra.ph/Analysis-10-17\">"));
+    assert!(MessageFormatter::count_utf16_code_units(&summary) < 1500);
+}
+
+#[test]
+fn test_quick_facts_follow_the_header() {
+    let mut result = result_with_professional("Writes about **Rust** and databases.");
+    result.facts = Some(QuickFacts {
+        keywords: ["rust", "tokio", "async", "traits", "crates", "cargo"]
+            .iter()
+            .map(|word| (word.to_string(), 3))
+            .collect(),
+        hashtags: vec![("#release".to_string(), 2)],
+        weekdays: [0, 4, 1, 0, 0, 0, 0],
+    });
+
+    let messages = ResultPresenter::render(&result, "professional", "rustacean", 1, Lang::En)
+        .expect("professional content should render");
+    assert!(messages[0].contains(
+        "💡 <b>Quick facts:</b> words: rust, tokio, async, traits, crates · tags: #release · most posts on Tuesdays"
+    ));
+    assert!(!messages[0].contains("cargo"));
+
+    // results without facts keep the plain header
+    let plain = result_with_professional("Writes about **Rust** and databases.");
+    let messages = ResultPresenter::render(&plain, "professional", "rustacean", 1, Lang::En)
+        .expect("professional content should render");
+    assert!(!messages[0].contains("Quick facts"));
+}
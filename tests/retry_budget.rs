@@ -0,0 +1,78 @@
+// Tests for the per-analysis retry budget
+use std::time::Duration;
+use tg_main::analysis::AnalysisError;
+use tg_main::llm::analysis_query::query_and_parse_analysis;
+use tg_main::llm::routing::ModelRoute;
+use tg_main::llm::{ModelTier, MAX_RETRIES};
+use tg_main::prompts::analysis::AnalysisPrompt;
+use tg_main::retry_budget::RetryBudget;
+
+#[test]
+fn test_retries_are_shared_across_stages() {
+    let budget = RetryBudget::new(Duration::from_secs(600), 3);
+
+    // two stages retrying once each, then a third one draws the last retry
+    assert!(budget.retry_delay(0).is_some());
+    assert!(budget.retry_delay(0).is_some());
+    assert!(budget.retry_delay(1).is_some());
+    assert_eq!(budget.retries_left(), 0);
+    assert!(budget.retry_delay(0).is_none());
+    assert!(!budget.take_retry(Duration::ZERO));
+}
+
+#[test]
+fn test_stage_limit_still_applies() {
+    let budget = RetryBudget::new(Duration::from_secs(600), 100);
+    assert!(budget.retry_delay(MAX_RETRIES).is_none());
+    assert_eq!(budget.retries_left(), 100);
+}
+
+#[test]
+fn test_retries_must_fit_before_the_deadline() {
+    // the first backoff is at least a second
+    let budget = RetryBudget::new(Duration::from_millis(500), 10);
+    assert!(budget.retry_delay(0).is_none());
+    assert_eq!(budget.retries_left(), 10);
+    assert!(budget.take_retry(Duration::ZERO));
+    assert!(budget.cap_timeout(Duration::from_secs(300)) <= Duration::from_millis(500));
+
+    let exhausted = RetryBudget::new(Duration::ZERO, 10);
+    assert!(exhausted.is_exhausted());
+    assert!(!exhausted.take_retry(Duration::ZERO));
+    assert_eq!(
+        exhausted.cap_timeout(Duration::from_secs(300)),
+        Duration::ZERO
+    );
+}
+
+#[test]
+fn test_unbounded_budget() {
+    let budget = RetryBudget::unbounded();
+    assert!(!budget.is_exhausted());
+    assert_eq!(
+        budget.cap_timeout(Duration::from_secs(300)),
+        Duration::from_secs(300)
+    );
+    for attempt in 0..MAX_RETRIES {
+        assert!(budget.retry_delay(attempt).is_some());
+    }
+}
+
+#[tokio::test]
+async fn test_llm_stage_stops_on_exhausted_budget() {
+    let prompt = AnalysisPrompt {
+        json: "unused".to_string(),
+        tagged: "unused".to_string(),
+    };
+    let err = query_and_parse_analysis(
+        &prompt,
+        &ModelRoute::for_tier(ModelTier::Auto),
+        &RetryBudget::new(Duration::ZERO, 10),
+    )
+    .await
+    .expect_err("An exhausted budget should not query any model");
+    assert_eq!(
+        AnalysisError::classify(err.as_ref()),
+        AnalysisError::BudgetExhausted
+    );
+}
@@ -0,0 +1,123 @@
+// Tests for roast battles between two group members
+use tg_main::analysis::{is_self_corpus, roast_battle_corpus_name, roast_battle_members};
+use tg_main::cache::AnalysisResult;
+use tg_main::cross_group::StoredGroupMessage;
+use tg_main::localization::Lang;
+use tg_main::prompts::analysis::{generate_roast_battle_prompt, OutputLanguage};
+use tg_main::roast_battle::{battle_messages, parse_battle_members};
+use tg_main::utils::ResultPresenter;
+
+fn stored(text: &str) -> StoredGroupMessage {
+    StoredGroupMessage {
+        chat_title: "Rust Moscow".to_string(),
+        text: text.to_string(),
+        date: "2024-03-04".to_string(),
+    }
+}
+
+#[test]
+fn test_battle_needs_two_different_usernames() {
+    assert_eq!(
+        parse_battle_members("@alice_1 @bob_the_builder"),
+        Some(("alice_1".to_string(), "bob_the_builder".to_string()))
+    );
+    assert_eq!(
+        parse_battle_members("  @alice_1\t@Bob_2  "),
+        Some(("alice_1".to_string(), "Bob_2".to_string()))
+    );
+    for args in [
+        "",
+        "@alice_1",
+        "@alice_1 @ALICE_1",
+        "@alice_1 bob_the_builder",
+        "@alice_1 @bob",
+        "@alice_1 @bob-builder",
+        "@alice_1 @bob_the_builder @carol_1",
+    ] {
+        assert_eq!(parse_battle_members(args), None, "{}", args);
+    }
+}
+
+#[test]
+fn test_battle_messages_name_their_author() {
+    let messages = battle_messages(
+        "alice_1",
+        &[stored("tabs"), stored("always tabs")],
+        "bob_2",
+        &[stored("spaces")],
+    );
+    let texts = messages
+        .iter()
+        .map(|msg| msg.message.as_deref().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        [
+            "[@alice_1] tabs",
+            "[@alice_1] always tabs",
+            "[@bob_2] spaces"
+        ]
+    );
+    assert_eq!(messages[0].date.as_deref(), Some("2024-03-04"));
+}
+
+#[test]
+fn test_battle_corpus_names_keep_both_members() {
+    let name = roast_battle_corpus_name(-1001234, "alice_1", "bob_2");
+    assert_eq!(roast_battle_members(&name), Some(("alice_1", "bob_2")));
+    // never fetched or ranked like a channel
+    assert!(is_self_corpus(&name));
+    assert_eq!(roast_battle_members("@alice_1"), None);
+    assert_eq!(roast_battle_members("self:42"), None);
+
+    assert_eq!(
+        ResultPresenter::target_label(&name, Lang::En),
+        "🥊 roast battle @alice_1 vs @bob_2"
+    );
+}
+
+#[test]
+fn test_battle_result_has_its_own_header() {
+    let result = AnalysisResult {
+        professional: None,
+        personal: None,
+        roast: Some("round one".to_string()),
+        trends: None,
+        messages_count: 30,
+        model: None,
+        report: None,
+        removed_messages: 0,
+        partial: false,
+        prompt_version: None,
+        facts: None,
+    };
+    let name = roast_battle_corpus_name(-1001234, "alice_1", "bob_2");
+    let messages = ResultPresenter::render(&result, "roast", &name, 7, Lang::En).unwrap();
+    assert!(messages[0].contains("Roast Battle"));
+    assert!(messages[0].contains("@alice_1  vs  🔵 @bob_2"));
+    assert!(!messages[0].contains("Channel Analysis Results"));
+    assert!(messages[0].contains("round one"));
+}
+
+#[test]
+fn test_battle_prompt_names_both_members() {
+    let prompt = generate_roast_battle_prompt(
+        &battle_messages("alice_1", &[stored("tabs")], "bob_2", &[stored("spaces")]),
+        "alice_1",
+        "bob_2",
+        None,
+        OutputLanguage::English,
+    )
+    .unwrap();
+    for text in [&prompt.json, &prompt.tagged] {
+        assert!(text.contains("roast battle between @alice_1 and @bob_2"));
+        assert!(text.contains("[@bob_2] spaces"));
+        assert!(text.contains("verdict naming the winner"));
+        assert!(text.contains("Write in English"));
+    }
+    // the same sections, so battle answers parse like any analysis
+    for tag in ["<professional>", "<personal>", "<roast>"] {
+        assert!(prompt.tagged.contains(tag));
+    }
+    assert!(prompt.json.contains("\"scores\""));
+}
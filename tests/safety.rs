@@ -0,0 +1,142 @@
+// Tests for the safety pass over analysis output
+use std::time::Duration;
+use tg_main::cache::AnalysisResult;
+use tg_main::llm::routing::ModelRouting;
+use tg_main::llm::safety::{
+    parse_review, personal_data, personal_data_flags, redact, review_prompt, review_route,
+    SafetyAction, SafetyCategory, SafetyFlag, REDACTED,
+};
+use tg_main::report::{AnalysisReport, ReportScores};
+
+fn result(roast: &str) -> AnalysisResult {
+    AnalysisResult {
+        professional: Some("A careful writer about databases.".to_string()),
+        personal: Some("Patient and curious.".to_string()),
+        roast: Some(roast.to_string()),
+        trends: None,
+        messages_count: 10,
+        model: Some("gemini-2.5-pro".to_string()),
+        report: None,
+        removed_messages: 0,
+        partial: false,
+        prompt_version: None,
+        facts: None,
+    }
+}
+
+fn flag(section: &str, category: SafetyCategory, excerpt: &str) -> SafetyFlag {
+    SafetyFlag {
+        section: section.to_string(),
+        category,
+        excerpt: excerpt.to_string(),
+    }
+}
+
+#[test]
+fn test_personal_data_finds_emails_and_phone_numbers() {
+    let found = personal_data(
+        "Write to john.doe@example.com or call +1 (555) 123-4567, posting since 2019 with 1500 subscribers",
+    );
+    assert_eq!(found, vec!["john.doe@example.com", "+1 (555) 123-4567"]);
+    assert!(personal_data("12 posts in 2023, 350 views on average").is_empty());
+}
+
+#[test]
+fn test_personal_data_is_flagged_as_doxxing_in_its_section() {
+    let flags = personal_data_flags(&result("Reach the author at +7 912 345 67 89 anytime."));
+    assert_eq!(
+        flags,
+        vec![flag("roast", SafetyCategory::Doxxing, "+7 912 345 67 89")]
+    );
+    assert!(personal_data_flags(&result("Posts too long to finish.")).is_empty());
+}
+
+#[test]
+fn test_review_prompt_lists_every_section_and_the_verdict() {
+    let prompt = review_prompt(&result("Posts too long to finish."));
+    assert!(prompt.contains("<verdict>safe</verdict>"));
+    assert!(prompt.contains("<section name=\"professional\">"));
+    assert!(prompt.contains("<section name=\"roast\">\nPosts too long to finish.\n</section>"));
+    for category in SafetyCategory::ALL {
+        assert!(prompt.contains(category.as_str()));
+    }
+}
+
+#[test]
+fn test_parse_review_reads_verdicts_and_flags() {
+    assert_eq!(parse_review("<verdict>safe</verdict>"), Some(Vec::new()));
+    assert_eq!(
+        parse_review(
+            "<flag section=\"roast\" category=\"harassment\"> someone should visit him </flag>\n\
+             <flag section=\"personal\" category=\"unknown\">ignored</flag>\n\
+             <flag section=\"personal\" category=\"slur\"></flag>"
+        ),
+        Some(vec![flag(
+            "roast",
+            SafetyCategory::Harassment,
+            "someone should visit him"
+        )])
+    );
+    // an answer the review can't be read from lets the analysis through
+    assert_eq!(parse_review("I can't help with that."), None);
+}
+
+#[test]
+fn test_category_and_action_codes() {
+    for category in SafetyCategory::ALL {
+        assert_eq!(SafetyCategory::from_code(category.as_str()), Some(category));
+    }
+    assert_eq!(
+        SafetyCategory::from_code(" slur "),
+        Some(SafetyCategory::Slur)
+    );
+    assert_eq!(SafetyCategory::from_code("spam"), None);
+    assert_eq!(SafetyAction::Regenerated.as_str(), "regenerated");
+    assert_eq!(SafetyAction::Redacted.as_str(), "redacted");
+}
+
+#[test]
+fn test_redact_removes_flagged_passages_everywhere() {
+    let mut analysis = result("Call him at +1 555 123 4567. Posts too long to finish.");
+    analysis.report = Some(AnalysisReport {
+        professional: "A careful writer about databases.".to_string(),
+        personal: "Patient and curious.".to_string(),
+        roast: "Call him at +1 555 123 4567. Posts too long to finish.".to_string(),
+        strengths: vec!["clear writing".to_string()],
+        weaknesses: vec!["shares +1 555 123 4567".to_string()],
+        topics: vec!["databases".to_string()],
+        tone: "friendly".to_string(),
+        scores: ReportScores {
+            expertise: 7,
+            communication: 8,
+            consistency: 6,
+            humor: 5,
+        },
+    });
+    let flags = personal_data_flags(&analysis);
+    assert_eq!(flags.len(), 2);
+
+    let redacted = redact(analysis, &flags);
+    let expected = format!("Call him at {REDACTED}. Posts too long to finish.");
+    assert_eq!(redacted.roast.as_deref(), Some(expected.as_str()));
+    assert_eq!(
+        redacted.professional.as_deref(),
+        Some("A careful writer about databases.")
+    );
+    let report = redacted.report.expect("Report should be kept");
+    assert_eq!(report.roast, expected);
+    assert_eq!(report.weaknesses, vec![format!("shares {REDACTED}")]);
+    assert!(personal_data_flags(&result(&report.roast)).is_empty());
+}
+
+#[test]
+fn test_review_model_follows_model_routing() {
+    let route = review_route(&ModelRouting::default());
+    assert_eq!(route.models[0], "gemini-2.5-flash");
+
+    let route = review_route(&ModelRouting::parse(
+        "roast=gemini-2.5-pro,safety_review=gemini-2.5-flash-lite:30",
+    ));
+    assert_eq!(route.models[0], "gemini-2.5-flash-lite");
+    assert_eq!(route.timeout, Duration::from_secs(30));
+}
@@ -0,0 +1,111 @@
+// Tests for collecting forwarded messages for a self-analysis
+use chrono::{TimeZone, Utc};
+use teloxide::types::{MessageOrigin, User, UserId};
+use tg_main::analysis::{is_self_corpus, self_corpus_name};
+use tg_main::localization::Lang;
+use tg_main::self_analysis::{Collected, SelfCollection, MAX_SELF_MESSAGES};
+use tg_main::utils::ResultPresenter;
+
+const SENDER: UserId = UserId(42);
+
+fn user_origin(id: UserId) -> MessageOrigin {
+    MessageOrigin::User {
+        date: Utc.with_ymd_and_hms(2024, 3, 4, 12, 0, 0).unwrap(),
+        sender_user: User {
+            id,
+            is_bot: false,
+            first_name: "Ann".to_string(),
+            last_name: None,
+            username: None,
+            language_code: None,
+            is_premium: false,
+            added_to_attachment_menu: false,
+        },
+    }
+}
+
+#[test]
+fn test_own_forwards_are_collected_with_their_date() {
+    let mut collection = SelfCollection::default();
+
+    assert_eq!(
+        collection.add(&user_origin(SENDER), SENDER, Some("  my thoughts  ")),
+        Collected::Added
+    );
+    assert_eq!(collection.messages.len(), 1);
+    assert_eq!(
+        collection.messages[0].message.as_deref(),
+        Some("my thoughts")
+    );
+    assert_eq!(collection.messages[0].date.as_deref(), Some("2024-03-04"));
+}
+
+#[test]
+fn test_forwards_of_others_are_skipped() {
+    let mut collection = SelfCollection::default();
+
+    assert_eq!(
+        collection.add(&user_origin(UserId(7)), SENDER, Some("not mine")),
+        Collected::NotOwn
+    );
+    // hidden senders can't be told apart from the user
+    let hidden = MessageOrigin::HiddenUser {
+        date: Utc.with_ymd_and_hms(2024, 3, 4, 12, 0, 0).unwrap(),
+        sender_user_name: "Ann".to_string(),
+    };
+    assert_eq!(
+        collection.add(&hidden, SENDER, Some("probably mine")),
+        Collected::Added
+    );
+    assert_eq!(collection.messages.len(), 1);
+    assert_eq!(collection.skipped, 1);
+}
+
+#[test]
+fn test_forwards_without_text_are_ignored() {
+    let mut collection = SelfCollection::default();
+
+    assert_eq!(
+        collection.add(&user_origin(SENDER), SENDER, None),
+        Collected::NoText
+    );
+    assert_eq!(
+        collection.add(&user_origin(SENDER), SENDER, Some("   ")),
+        Collected::NoText
+    );
+    assert!(collection.messages.is_empty());
+    assert_eq!(collection.skipped, 0);
+}
+
+#[test]
+fn test_collection_stops_when_full() {
+    let mut collection = SelfCollection::default();
+    for i in 0..MAX_SELF_MESSAGES {
+        let text = format!("message {}", i);
+        assert_eq!(
+            collection.add(&user_origin(SENDER), SENDER, Some(&text)),
+            Collected::Added
+        );
+    }
+
+    assert_eq!(
+        collection.add(&user_origin(SENDER), SENDER, Some("one too many")),
+        Collected::Full
+    );
+    assert_eq!(collection.messages.len(), MAX_SELF_MESSAGES);
+    assert_eq!(collection.dropped, 1);
+}
+
+#[test]
+fn test_self_corpus_names_never_look_like_channels() {
+    let name = self_corpus_name(42);
+    assert!(is_self_corpus(&name));
+    assert!(!name.starts_with('@'));
+    assert!(!is_self_corpus("@selfhelp"));
+
+    assert_eq!(
+        ResultPresenter::target_label(&name, Lang::En),
+        "your forwarded messages"
+    );
+    assert_eq!(ResultPresenter::target_label("@a<b", Lang::En), "@a&lt;b");
+}
@@ -0,0 +1,72 @@
+// Tests for rendering showcase channel posts
+use tg_main::cache::AnalysisResult;
+use tg_main::showcase::{anonymize, ShowcaseEntry};
+
+fn entry(anonymous: bool) -> ShowcaseEntry {
+    ShowcaseEntry {
+        analysis_id: 1,
+        channel_name: "@rust_lang".to_string(),
+        analysis_type: "roast".to_string(),
+        cache_key: "key".to_string(),
+        anonymous,
+        language_code: Some("en".to_string()),
+    }
+}
+
+fn roast(text: &str) -> AnalysisResult {
+    AnalysisResult {
+        professional: None,
+        personal: None,
+        roast: Some(text.to_string()),
+        trends: None,
+        messages_count: 10,
+        model: None,
+        report: None,
+        removed_messages: 0,
+        partial: false,
+        prompt_version: None,
+        facts: None,
+    }
+}
+
+#[test]
+fn test_anonymize_masks_every_mention_of_the_channel() {
+    assert_eq!(
+        anonymize(
+            "@rust_lang posts a lot; Rust_Lang never sleeps, unlike rust_language",
+            "@rust_lang"
+        ),
+        "@••• posts a lot; @••• never sleeps, unlike rust_language"
+    );
+    assert_eq!(
+        anonymize("no mentions here", "@rust_lang"),
+        "no mentions here"
+    );
+}
+
+#[test]
+fn test_named_post_shows_the_channel() {
+    let parts = entry(false)
+        .render(&roast("@rust_lang is all about crabs"))
+        .expect("Roast should render");
+    assert_eq!(parts.len(), 1);
+    assert!(parts[0].contains("<code>@rust_lang</code>"));
+    assert!(parts[0].contains("@rust_lang is all about crabs"));
+}
+
+#[test]
+fn test_anonymous_post_hides_the_channel() {
+    let parts = entry(true)
+        .render(&roast("@rust_lang is all about crabs"))
+        .expect("Roast should render");
+    assert!(!parts[0].contains("rust_lang"));
+    assert!(parts[0].contains("anonymous channel"));
+}
+
+#[test]
+fn test_post_needs_content_of_its_type() {
+    let mut result = roast("crabs");
+    result.roast = None;
+    result.professional = Some("an expert".to_string());
+    assert!(entry(false).render(&result).is_none());
+}
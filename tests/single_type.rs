@@ -0,0 +1,98 @@
+// Tests for single-type analyses, which ask the llm for the requested section only
+use tg_main::analysis::MessageDict;
+use tg_main::analysis_runner::AnalysisMode;
+use tg_main::llm::analysis_query::{query_and_parse_section, section_result};
+use tg_main::llm::routing::ModelRoute;
+use tg_main::llm::ModelTier;
+use tg_main::prompts::analysis::{generate_section_prompt, OutputLanguage};
+use tg_main::prompts::versions::PromptVersion;
+use tg_main::retry_budget::RetryBudget;
+
+fn messages() -> Vec<MessageDict> {
+    vec![MessageDict {
+        id: Some(1),
+        date: Some("2026-09-01".to_string()),
+        message: Some("Shipped the new release after a week of fighting the borrow checker".into()),
+        images: None,
+        thread_id: None,
+    }]
+}
+
+#[test]
+fn test_section_prompt_asks_for_the_requested_section_only() {
+    let prompt = generate_section_prompt(
+        &messages(),
+        "roast",
+        Some("their release habits"),
+        None,
+        OutputLanguage::English,
+        PromptVersion::base(),
+        100_000,
+    )
+    .expect("Failed to build section prompt");
+
+    assert!(prompt.contains("<roast>") && prompt.contains("</roast>"));
+    assert!(!prompt.contains("<professional>") && !prompt.contains("<personal>"));
+    assert!(prompt.contains("Brutally honest"));
+    assert!(prompt.contains("Write in English"));
+    assert!(prompt.contains("their release habits"));
+    assert!(prompt.contains("fighting the borrow checker"));
+
+    // trends and unknown types have no section of their own
+    for section in ["trends", "everything"] {
+        assert!(generate_section_prompt(
+            &messages(),
+            section,
+            None,
+            None,
+            OutputLanguage::Channel,
+            PromptVersion::base(),
+            100_000,
+        )
+        .is_err());
+    }
+}
+
+#[test]
+fn test_section_result_holds_one_section() {
+    let result = section_result("personal", "curious".to_string(), "model", false);
+    assert_eq!(result.personal.as_deref(), Some("curious"));
+    assert!(result.professional.is_none() && result.roast.is_none());
+    assert_eq!(result.model.as_deref(), Some("model"));
+    assert!(!result.partial);
+}
+
+#[tokio::test]
+async fn test_single_type_answer_keeps_the_requested_section() {
+    std::env::set_var("LLM_MOCK", "1");
+    let route = ModelRoute::for_tier(ModelTier::Auto);
+    let prompt = generate_section_prompt(
+        &messages(),
+        "professional",
+        None,
+        None,
+        OutputLanguage::Channel,
+        PromptVersion::base(),
+        route.prompt_token_limit(),
+    )
+    .expect("Failed to build section prompt");
+
+    let result =
+        query_and_parse_section(&prompt, "professional", &route, &RetryBudget::unbounded())
+            .await
+            .expect("Mock analysis should complete");
+    assert!(!result.partial);
+    assert!(result.professional.is_some());
+    assert!(result.personal.is_none() && result.roast.is_none());
+}
+
+#[test]
+fn test_analysis_mode_from_env() {
+    std::env::remove_var("ANALYSIS_MODE");
+    assert_eq!(AnalysisMode::from_env(), AnalysisMode::Combined);
+    std::env::set_var("ANALYSIS_MODE", " Single ");
+    assert_eq!(AnalysisMode::from_env(), AnalysisMode::Single);
+    std::env::set_var("ANALYSIS_MODE", "per-type");
+    assert_eq!(AnalysisMode::from_env(), AnalysisMode::Combined);
+    std::env::remove_var("ANALYSIS_MODE");
+}
@@ -0,0 +1,106 @@
+// Tests for the channel health statistics computed from the web preview
+use tg_main::stats::{format_count, ChannelHealth};
+use tg_main::web_scraper::{ChannelPreview, PostMetrics};
+
+const DAY: i64 = 24 * 60 * 60;
+const NOW: i64 = 1_790_848_800;
+
+fn preview(subscribers: Option<u64>, posts: Vec<PostMetrics>) -> ChannelPreview {
+    ChannelPreview {
+        title: "Test Channel".to_string(),
+        subscribers,
+        last_post_date: None,
+        posts_sampled: posts.len(),
+        text_posts: posts.len(),
+        posts,
+    }
+}
+
+fn post(days_ago: i64, views: Option<u64>, reactions: u64) -> PostMetrics {
+    PostMetrics {
+        published_at: Some(NOW - days_ago * DAY),
+        views,
+        reactions,
+    }
+}
+
+#[test]
+fn test_health_of_active_channel() {
+    let posts = vec![
+        post(16, Some(1_000), 10),
+        post(9, Some(3_000), 20),
+        post(2, Some(2_000), 0),
+    ];
+    let health = ChannelHealth::from_preview(&preview(Some(10_000), posts), NOW)
+        .expect("Posts should give a health");
+
+    assert_eq!(health.posts, 3);
+    // two intervals over two weeks
+    assert_eq!(health.posts_per_week, Some(1.0));
+    assert_eq!(health.days_since_last_post, Some(2));
+    assert_eq!(health.average_views, Some(2_000));
+    assert_eq!(health.median_views, Some(2_000));
+    assert_eq!(health.reach_percent, Some(20.0));
+    assert_eq!(health.reactions_per_thousand_views, Some(5.0));
+}
+
+#[test]
+fn test_health_without_view_counters() {
+    let posts = vec![post(3, None, 5), post(1, Some(400), 2), post(0, None, 9)];
+    let health = ChannelHealth::from_preview(&preview(None, posts), NOW)
+        .expect("Posts should give a health");
+
+    assert_eq!(health.average_views, Some(400));
+    // reactions on posts without views would inflate the ratio
+    assert_eq!(health.reactions_per_thousand_views, Some(5.0));
+    // reach needs the subscriber count
+    assert_eq!(health.reach_percent, None);
+
+    let hidden = vec![post(1, None, 3), post(0, None, 1)];
+    let health = ChannelHealth::from_preview(&preview(Some(500), hidden), NOW)
+        .expect("Posts should give a health");
+    assert_eq!(health.average_views, None);
+    assert_eq!(health.median_views, None);
+    assert_eq!(health.reach_percent, None);
+    assert_eq!(health.reactions_per_thousand_views, None);
+}
+
+#[test]
+fn test_health_needs_posts_and_two_dates_for_frequency() {
+    assert_eq!(
+        ChannelHealth::from_preview(&preview(Some(100), vec![]), NOW),
+        None
+    );
+
+    let single = ChannelHealth::from_preview(&preview(Some(100), vec![post(0, Some(50), 1)]), NOW)
+        .expect("A post should give a health");
+    assert_eq!(single.posts_per_week, None);
+    assert_eq!(single.days_since_last_post, Some(0));
+    assert_eq!(single.median_views, Some(50));
+}
+
+#[test]
+fn test_median_of_even_number_of_posts() {
+    let posts = vec![
+        post(3, Some(100), 0),
+        post(2, Some(900), 0),
+        post(1, Some(300), 0),
+        post(0, Some(500), 0),
+    ];
+    let health = ChannelHealth::from_preview(&preview(None, posts), NOW)
+        .expect("Posts should give a health");
+    assert_eq!(health.median_views, Some(400));
+    assert_eq!(health.average_views, Some(450));
+}
+
+#[test]
+fn test_format_count() {
+    assert_eq!(format_count(0), "0");
+    assert_eq!(format_count(999), "999");
+    assert_eq!(format_count(1_000), "1K");
+    assert_eq!(format_count(12_345), "12.3K");
+    assert_eq!(format_count(1_240_000), "1.2M");
+    assert_eq!(format_count(3_000_000), "3M");
+    // rounding up to a thousand thousands reads as a million
+    assert_eq!(format_count(999_960), "1M");
+}
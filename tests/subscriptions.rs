@@ -0,0 +1,14 @@
+// Tests for subscription statuses
+use tg_main::subscriptions::SubscriptionStatus;
+
+#[test]
+fn test_status_codes_roundtrip() {
+    for status in [
+        SubscriptionStatus::Active,
+        SubscriptionStatus::Grace,
+        SubscriptionStatus::Expired,
+    ] {
+        assert_eq!(SubscriptionStatus::from_code(status.as_str()), Some(status));
+    }
+    assert_eq!(SubscriptionStatus::from_code("cancelled"), None);
+}
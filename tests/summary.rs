@@ -0,0 +1,25 @@
+// Tests for share card excerpts
+use tg_main::utils::SummaryGenerator;
+
+#[test]
+fn test_short_content_is_kept_without_markdown() {
+    let excerpt = SummaryGenerator::excerpt(
+        "## Overview:\n**Rust** developer who writes `async` code.",
+        100,
+    );
+    assert_eq!(excerpt, "Rust developer who writes async code.");
+}
+
+#[test]
+fn test_long_content_is_cut_at_sentence_boundary() {
+    let content = "First sentence is here. Second sentence is somewhat longer than the first one.";
+    let excerpt = SummaryGenerator::excerpt(content, 40);
+    assert_eq!(excerpt, "First sentence is here.");
+}
+
+#[test]
+fn test_long_content_without_sentences_is_cut_at_word_boundary() {
+    let content = "one two three four five six seven eight nine ten";
+    let excerpt = SummaryGenerator::excerpt(content, 20);
+    assert_eq!(excerpt, "one two three four…");
+}
@@ -0,0 +1,57 @@
+// Tests for turning analyses into telegra.ph page content
+use serde_json::json;
+use tg_main::telegraph::{html_to_paragraphs, markdown_to_nodes};
+
+#[test]
+fn test_markdown_becomes_page_nodes() {
+    let nodes = markdown_to_nodes("## Style\n\nWrites **bold** takes with *flair*.");
+
+    assert_eq!(
+        nodes,
+        vec![
+            json!({"tag": "h3", "children": ["Style"]}),
+            json!({"tag": "p", "children": [
+                "Writes ",
+                {"tag": "strong", "children": ["bold"]},
+                " takes with ",
+                {"tag": "em", "children": ["flair"]},
+                "."
+            ]}),
+        ]
+    );
+}
+
+#[test]
+fn test_tight_list_items_hold_their_text() {
+    let nodes = markdown_to_nodes("1. first\n2. [second](https://example.com)");
+
+    assert_eq!(
+        nodes,
+        vec![json!({"tag": "ol", "children": [
+            {"tag": "li", "children": ["first"]},
+            {"tag": "li", "children": [
+                {"tag": "a", "attrs": {"href": "https://example.com"}, "children": ["second"]}
+            ]},
+        ]})]
+    );
+}
+
+#[test]
+fn test_raw_html_is_dropped() {
+    let nodes = markdown_to_nodes("<script>alert(1)</script>\n\nplain");
+
+    assert_eq!(nodes, vec![json!({"tag": "p", "children": ["plain"]})]);
+}
+
+#[test]
+fn test_html_lines_become_plain_paragraphs() {
+    let nodes = html_to_paragraphs("<b>Strengths:</b> Rust &amp; Go\n\n<i>Tone:</i> dry\n");
+
+    assert_eq!(
+        nodes,
+        vec![
+            json!({"tag": "p", "children": ["Strengths: Rust & Go"]}),
+            json!({"tag": "p", "children": ["Tone: dry"]}),
+        ]
+    );
+}
@@ -0,0 +1,47 @@
+// Tests for detecting channels with too little text to analyze
+use tg_main::analysis::{text_coverage, AnalysisError, MessageDict, MIN_TEXT_COVERAGE};
+use tg_main::localization::Lang;
+
+fn message(text: Option<&str>, images: Option<Vec<String>>) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: Some("2024-01-01".to_string()),
+        message: text.map(str::to_string),
+        images,
+        thread_id: None,
+    }
+}
+
+#[test]
+fn test_short_and_media_posts_lower_coverage() {
+    let long = "a post with more than enough text to analyze";
+    let messages = vec![
+        message(Some(long), None),
+        message(Some(long), Some(vec!["https://cdn/1.jpg".to_string()])),
+        message(Some("nice"), Some(vec!["https://cdn/2.jpg".to_string()])),
+        message(None, Some(vec!["https://cdn/3.jpg".to_string()])),
+    ];
+
+    assert_eq!(text_coverage(&messages, 0), Some(50));
+    // posts the fetch left out count against the coverage too
+    assert_eq!(text_coverage(&messages, 4), Some(25));
+    assert!(text_coverage(&messages, 4).unwrap() < MIN_TEXT_COVERAGE);
+
+    assert_eq!(text_coverage(&messages[..2], 0), Some(100));
+    assert_eq!(text_coverage(&[], 0), None);
+    assert_eq!(text_coverage(&[], 3), Some(0));
+}
+
+#[test]
+fn test_low_coverage_error_asks_for_confirmation() {
+    let failure = AnalysisError::LowTextCoverage(12);
+
+    let text = Lang::En.error_analysis_failed(&failure, "@memes");
+    assert!(text.contains("@memes"));
+    assert!(text.contains("12%"));
+    assert!(text.contains(Lang::En.btn_analyze_anyway().trim_start_matches("▶️ ")));
+    assert!(text.contains("No credits were consumed"));
+
+    let text = Lang::Ru.error_analysis_failed(&failure, "@memes");
+    assert!(text.contains(Lang::Ru.btn_analyze_anyway().trim_start_matches("▶️ ")));
+}
@@ -0,0 +1,141 @@
+// Tests for token estimates and fitting analysis prompts into the model's context
+use tg_main::analysis::MessageDict;
+use tg_main::llm::routing::ModelRoute;
+use tg_main::llm::ModelTier;
+use tg_main::prompts::analysis::{fit_messages, generate_analysis_prompt, OutputLanguage};
+use tg_main::prompts::versions::PromptVersion;
+use tg_main::tokens::{context_tokens, estimate_tokens, prompt_token_limit, OUTPUT_RESERVE_TOKENS};
+
+fn message(date: &str, text: &str) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: Some(date.to_string()),
+        message: Some(text.to_string()),
+        images: None,
+        thread_id: None,
+    }
+}
+
+fn texts(messages: &[MessageDict]) -> Vec<&str> {
+    messages
+        .iter()
+        .filter_map(|msg| msg.message.as_deref())
+        .collect()
+}
+
+#[test]
+fn test_estimate_counts_words_not_characters() {
+    assert_eq!(estimate_tokens(""), 0);
+    let english = "The author keeps shipping side projects and writing about them. ".repeat(10);
+    let tokens = estimate_tokens(&english);
+    assert!(tokens > 100 && tokens < english.len() / 2, "{}", tokens);
+
+    // cyrillic takes more tokens per character, but still far fewer than bytes
+    let russian = "Автор продолжает выпускать свои проекты и писать о них. ".repeat(10);
+    assert!(estimate_tokens(&russian) < russian.len() / 2);
+}
+
+#[test]
+fn test_prompt_limits_leave_room_for_the_answer() {
+    assert_eq!(context_tokens("gemini-2.5-flash"), 1_048_576);
+    assert_eq!(context_tokens("gemini-3-flash-preview"), 1_048_576);
+    let unknown = context_tokens("some-local-model");
+    assert!(unknown < context_tokens("gemini-2.5-pro"));
+    assert!(prompt_token_limit("some-local-model") + OUTPUT_RESERVE_TOKENS < unknown);
+
+    // a route fits the smallest context among its models
+    let mut route = ModelRoute::for_tier(ModelTier::Auto);
+    assert_eq!(
+        route.prompt_token_limit(),
+        prompt_token_limit("gemini-2.5-flash")
+    );
+    route.models.push("some-local-model".to_string());
+    assert_eq!(
+        route.prompt_token_limit(),
+        prompt_token_limit("some-local-model")
+    );
+}
+
+#[test]
+fn test_messages_within_budget_are_kept_as_they_are() {
+    let messages = vec![
+        message("2024-01-01", "First post about Rust"),
+        message("2024-01-02", "Second post about tokio"),
+    ];
+
+    let fitted = fit_messages(&messages, 10_000).unwrap();
+    assert_eq!(texts(&fitted), texts(&messages));
+}
+
+#[test]
+fn test_trimming_prefers_recent_and_long_messages_and_keeps_their_order() {
+    let long = "A long and detailed post about the release process of the project. ".repeat(8);
+    let messages = vec![
+        message("2024-01-01", "ok"),
+        message("2024-01-02", &long),
+        message("2024-03-01", "old news"),
+        message("2024-03-02", "hi"),
+        message(
+            "2024-03-03",
+            "Latest thoughts on async traits and their ergonomics",
+        ),
+    ];
+    // a message's json in the prompt plus its separator
+    let cost = |text: &str| {
+        estimate_tokens(&serde_json::to_string_pretty(&message("2024-01-01", text)).unwrap()) + 2
+    };
+    // room for the long post and the latest one only
+    let budget = cost(&long) + cost("Latest thoughts on async traits and their ergonomics");
+
+    let fitted = fit_messages(&messages, budget).unwrap();
+    assert_eq!(
+        texts(&fitted),
+        vec![
+            long.as_str(),
+            "Latest thoughts on async traits and their ergonomics"
+        ]
+    );
+
+    // whole messages only: a budget below the smallest message keeps nothing
+    assert!(fit_messages(&messages, 3).unwrap().is_empty());
+}
+
+#[test]
+fn test_analysis_prompt_drops_the_oldest_messages_to_fit() {
+    let messages: Vec<MessageDict> = (1..=28)
+        .map(|day| {
+            message(
+                &format!("2024-02-{:02}", day),
+                &format!(
+                    "Post number {} about the project roadmap and its progress",
+                    day
+                ),
+            )
+        })
+        .collect();
+    let prompt = |token_limit| {
+        generate_analysis_prompt(
+            &messages,
+            None,
+            None,
+            OutputLanguage::Channel,
+            PromptVersion::base(),
+            token_limit,
+        )
+        .unwrap()
+    };
+
+    let full = prompt(1_000_000);
+    assert!(full.tagged.contains("Post number 1 about"));
+    assert!(full.tagged.contains("Post number 28 about"));
+
+    let limit = estimate_tokens(&full.tagged) - 150;
+    let trimmed = prompt(limit);
+    for text in [&trimmed.json, &trimmed.tagged] {
+        assert!(estimate_tokens(text) <= limit);
+        assert!(!text.contains("Post number 1 about"));
+        assert!(text.contains("Post number 28 about"));
+        // the quick facts still count every message
+        assert!(text.contains("roadmap (28)"));
+    }
+}
@@ -0,0 +1,127 @@
+// Tests for bucketing messages by time and the trends analysis
+use tg_main::analysis::{AnalysisError, MessageDict};
+use tg_main::llm::trends_query::parse_trends;
+use tg_main::prompts::analysis::OutputLanguage;
+use tg_main::prompts::trends::{bucket_messages, generate_trends_prompt, TimeBucket};
+
+fn message(date: Option<&str>, text: &str) -> MessageDict {
+    MessageDict {
+        id: None,
+        date: date.map(str::to_string),
+        message: Some(text.to_string()),
+        images: None,
+        thread_id: None,
+    }
+}
+
+fn bucket(period: &str, messages: &[&str]) -> TimeBucket {
+    TimeBucket {
+        period: period.to_string(),
+        messages: messages.iter().map(|text| text.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_messages_spanning_months_are_bucketed_by_month() {
+    // newest first, as fetched
+    let messages = vec![
+        message(Some("2024-03-02"), "march"),
+        message(Some("2024-01-31T23:59:00+00:00"), "late january"),
+        message(Some("2024-01-05"), "early january"),
+    ];
+
+    assert_eq!(
+        bucket_messages(&messages),
+        vec![
+            bucket("2024-01", &["early january", "late january"]),
+            bucket("2024-03", &["march"]),
+        ]
+    );
+}
+
+#[test]
+fn test_messages_within_a_month_are_bucketed_by_iso_week() {
+    let messages = vec![
+        message(Some("2024-03-12"), "second week"),
+        message(Some("2024-03-04"), "first week"),
+        message(Some("2024-03-05"), "first week again"),
+    ];
+
+    assert_eq!(
+        bucket_messages(&messages),
+        vec![
+            bucket("2024-W10", &["first week", "first week again"]),
+            bucket("2024-W11", &["second week"]),
+        ]
+    );
+}
+
+#[test]
+fn test_undated_and_empty_messages_are_left_out() {
+    let messages = vec![
+        message(None, "undated"),
+        message(Some("not a date"), "garbled date"),
+        message(Some("2024-03-04"), "   "),
+        MessageDict {
+            id: None,
+            date: Some("2024-03-04".to_string()),
+            message: None,
+            images: Some(vec!["https://example.com/a.jpg".to_string()]),
+            thread_id: None,
+        },
+        message(Some("2024-03-04"), "kept"),
+    ];
+
+    assert_eq!(
+        bucket_messages(&messages),
+        vec![bucket("2024-W10", &["kept"])]
+    );
+}
+
+#[test]
+fn test_trends_prompt_lists_periods_in_order() {
+    let messages = vec![
+        message(Some("2024-02-10"), "about rust"),
+        message(Some("2024-01-10"), "about go"),
+    ];
+
+    let prompt = generate_trends_prompt(&messages, Some("hiring"), None, OutputLanguage::English)
+        .expect("two periods are enough for a trends prompt");
+    assert!(prompt.contains("<trends>"));
+    assert!(prompt.contains("Write in English"));
+    assert!(prompt.contains("hiring"));
+    let january = prompt.find("2024-01").unwrap();
+    let february = prompt.find("2024-02").unwrap();
+    assert!(january < february);
+}
+
+#[test]
+fn test_trends_prompt_needs_two_periods() {
+    let messages = vec![
+        message(Some("2024-03-04"), "one"),
+        message(Some("2024-03-05"), "week"),
+        message(None, "undated"),
+    ];
+
+    let err = generate_trends_prompt(&messages, None, None, OutputLanguage::Channel).unwrap_err();
+    assert_eq!(
+        AnalysisError::classify(err.as_ref()),
+        AnalysisError::ShortHistory
+    );
+}
+
+#[test]
+fn test_parse_trends() {
+    let result = parse_trends("<trends>**2024-01** calm</trends>", "gemini-2.5-flash").unwrap();
+    assert_eq!(result.trends.as_deref(), Some("**2024-01** calm"));
+    assert!(result.professional.is_none());
+    assert!(!result.partial);
+
+    // a cut off timeline is kept, labeled partial
+    let result = parse_trends("<trends>**2024-01** calm, then", "gemini-2.5-flash").unwrap();
+    assert_eq!(result.trends.as_deref(), Some("**2024-01** calm, then"));
+    assert!(result.partial);
+
+    assert!(parse_trends("I can't help with that.", "gemini-2.5-flash").is_none());
+    assert!(parse_trends("<trends></trends>", "gemini-2.5-flash").is_none());
+}
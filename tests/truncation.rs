@@ -0,0 +1,56 @@
+// Tests for continuing and salvaging analyses the model cut off
+use tg_main::llm::analysis_query::{parse_partial_analysis, stitch_continuation, unclosed_section};
+
+#[test]
+fn test_unclosed_section_finds_where_the_answer_stopped() {
+    let complete = "<professional>a</professional><personal>b</personal><roast>c</roast>";
+    assert_eq!(unclosed_section(complete), None);
+
+    let cut_off = "<professional>a</professional><personal>b is in the mid";
+    assert_eq!(unclosed_section(cut_off), Some("personal"));
+
+    // a section that never started is missing, not cut off
+    assert_eq!(unclosed_section("<professional>a</professional>"), None);
+}
+
+#[test]
+fn test_continuation_is_appended() {
+    let partial = "<professional>a</professional><personal>b is in the mid";
+    let continuation = "dle</personal><roast>c</roast>";
+
+    let stitched = stitch_continuation(partial, continuation);
+    assert_eq!(
+        stitched,
+        "<professional>a</professional><personal>b is in the middle</personal><roast>c</roast>"
+    );
+    assert_eq!(unclosed_section(&stitched), None);
+}
+
+#[test]
+fn test_restarted_section_replaces_the_cut_off_one() {
+    let partial = "<professional>a</professional><personal>b is in the mid";
+    let continuation = "\n<personal>b is in the middle</personal><roast>c</roast>";
+
+    assert_eq!(
+        stitch_continuation(partial, continuation),
+        "<professional>a</professional><personal>b is in the middle</personal><roast>c</roast>"
+    );
+}
+
+#[test]
+fn test_partial_analysis_keeps_closed_and_cut_off_sections() {
+    let text = "<professional>Ships side projects</professional>\n<personal>Curious and";
+
+    let result = parse_partial_analysis(text, "gemini-2.5-flash").expect("should salvage");
+    assert!(result.partial);
+    assert_eq!(result.professional.as_deref(), Some("Ships side projects"));
+    assert_eq!(result.personal.as_deref(), Some("Curious and"));
+    assert_eq!(result.roast, None);
+    assert_eq!(result.model.as_deref(), Some("gemini-2.5-flash"));
+}
+
+#[test]
+fn test_answers_without_sections_salvage_nothing() {
+    assert!(parse_partial_analysis("I can't help with that.", "gemini-2.5-flash").is_none());
+    assert!(parse_partial_analysis("<roast>", "gemini-2.5-flash").is_none());
+}
@@ -0,0 +1,71 @@
+// Tests for the per-user request rate limiter
+use std::time::{Duration, Instant};
+use tg_main::rate_limiters::user::{Throttle, UserRateLimiter};
+
+#[test]
+fn test_burst_is_allowed_then_throttled_with_one_notice() {
+    let limiter = UserRateLimiter::new(3, 60);
+    let now = Instant::now();
+
+    for _ in 0..3 {
+        assert_eq!(limiter.check(1, now), Throttle::Allowed);
+    }
+    let Throttle::Limited {
+        retry_after,
+        notify,
+    } = limiter.check(1, now)
+    else {
+        panic!("A request over the burst should be throttled");
+    };
+    assert!(notify);
+    assert!(retry_after <= Duration::from_secs(1));
+    assert!(retry_after > Duration::ZERO);
+
+    // the rest of the flood is dropped silently
+    assert!(matches!(
+        limiter.check(1, now),
+        Throttle::Limited { notify: false, .. }
+    ));
+
+    // other users have their own bucket
+    assert_eq!(limiter.check(2, now), Throttle::Allowed);
+}
+
+#[test]
+fn test_tokens_refill_over_time() {
+    let limiter = UserRateLimiter::new(1, 6);
+    let start = Instant::now();
+
+    assert_eq!(limiter.check(1, start), Throttle::Allowed);
+    assert!(matches!(
+        limiter.check(1, start + Duration::from_secs(5)),
+        Throttle::Limited { notify: true, .. }
+    ));
+    // six per minute is one token every ten seconds
+    assert_eq!(
+        limiter.check(1, start + Duration::from_secs(10)),
+        Throttle::Allowed
+    );
+
+    // a new flood after an allowed request gets a notice again
+    assert!(matches!(
+        limiter.check(1, start + Duration::from_secs(10)),
+        Throttle::Limited { notify: true, .. }
+    ));
+}
+
+#[test]
+fn test_new_rates_apply_to_the_next_request() {
+    let limiter = UserRateLimiter::new(1, 6);
+    let start = Instant::now();
+
+    assert_eq!(limiter.check(1, start), Throttle::Allowed);
+    assert!(matches!(limiter.check(1, start), Throttle::Limited { .. }));
+
+    // sixty per minute is one token every second
+    limiter.set_rates(1, 60);
+    assert_eq!(
+        limiter.check(1, start + Duration::from_secs(1)),
+        Throttle::Allowed
+    );
+}
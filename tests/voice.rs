@@ -0,0 +1,25 @@
+// Tests for the text and audio format of voice summaries
+use tg_main::voice::{pcm_sample_rate, voice_script, VOICE_SCRIPT_CHARS};
+
+#[test]
+fn test_sample_rate_is_read_from_the_mime_type() {
+    assert_eq!(pcm_sample_rate("audio/L16;codec=pcm;rate=24000"), 24000);
+    assert_eq!(pcm_sample_rate("audio/L16; rate=16000"), 16000);
+    // gemini's rate when the answer doesn't say
+    assert_eq!(pcm_sample_rate("audio/L16"), 24000);
+    assert_eq!(pcm_sample_rate(""), 24000);
+}
+
+#[test]
+fn test_voice_script_is_the_plain_opening_of_the_analysis() {
+    let content = format!(
+        "## Style:\n\n**Dry humour** and `code` samples.\n\n{}",
+        "The author explains databases patiently. ".repeat(50)
+    );
+
+    let script = voice_script(&content);
+
+    assert!(script.starts_with("Dry humour and code samples."));
+    assert!(!script.contains(['*', '#', '`']));
+    assert!(script.chars().count() <= VOICE_SCRIPT_CHARS + 1);
+}
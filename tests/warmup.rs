@@ -0,0 +1,63 @@
+// Tests for the off-peak window and the warm list of the cache warm-up
+use chrono::{TimeZone, Utc};
+use std::time::Duration;
+use tg_main::admin::{AdminAction, AdminRole};
+use tg_main::warmup::{warm_list, OffPeakWindow};
+
+fn names(channels: &[&str]) -> Vec<String> {
+    channels.iter().map(|channel| channel.to_string()).collect()
+}
+
+#[test]
+fn test_window_parses_and_wraps_past_midnight() {
+    let night = OffPeakWindow::parse("2-6").unwrap();
+    assert!(night.contains(2) && night.contains(5));
+    assert!(!night.contains(6) && !night.contains(1));
+
+    let late = OffPeakWindow::parse(" 23 - 4 ").unwrap();
+    assert!(late.contains(23) && late.contains(0) && late.contains(3));
+    assert!(!late.contains(4) && !late.contains(12));
+
+    for malformed in ["", "2", "6-6", "2-24", "a-b", "2-6-8"] {
+        assert_eq!(OffPeakWindow::parse(malformed), None, "{:?}", malformed);
+    }
+}
+
+#[test]
+fn test_warm_up_waits_for_the_next_window() {
+    let window = OffPeakWindow::parse("2-6").unwrap();
+    let evening = Utc.with_ymd_and_hms(2026, 3, 10, 22, 30, 0).unwrap();
+    assert_eq!(
+        window.until_open(evening),
+        Duration::from_secs(3 * 3600 + 30 * 60)
+    );
+
+    let during = Utc.with_ymd_and_hms(2026, 3, 10, 3, 0, 0).unwrap();
+    assert_eq!(window.until_open(during), Duration::ZERO);
+    // once a night's pass is done the next one starts the following night
+    assert_eq!(
+        window.until_next_start(during),
+        Duration::from_secs(23 * 3600)
+    );
+}
+
+#[test]
+fn test_warm_list_takes_pins_then_trending_channels() {
+    let pinned = names(&["@pinned", "@rustlang"]);
+    let excluded = names(&["@spam"]);
+    let trending = names(&["@rustlang", "@spam", "@golang", "@python", "@zig"]);
+
+    assert_eq!(
+        warm_list(&pinned, &excluded, &trending, 3),
+        names(&["@pinned", "@rustlang", "@golang", "@python"])
+    );
+    // pins are warmed even without any trending places
+    assert_eq!(warm_list(&pinned, &excluded, &trending, 0), pinned);
+}
+
+#[test]
+fn test_only_owners_manage_the_warm_list() {
+    assert!(AdminRole::Owner.allows(AdminAction::ManageWarmup));
+    assert!(!AdminRole::Support.allows(AdminAction::ManageWarmup));
+    assert!(!AdminRole::Marketing.allows(AdminAction::ManageWarmup));
+}